@@ -21,7 +21,13 @@
 use clap::Parser;
 use fyrox::core::log::Log;
 use fyrox::event_loop::EventLoop;
+use fyrox::resource::texture::CompressionOptions;
+use fyroxed_base::export::{
+    headless::{export_headless, HeadlessExportOptions},
+    TargetPlatform,
+};
 use fyroxed_base::{Editor, StartupData};
+use std::{path::PathBuf, str::FromStr};
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
@@ -33,12 +39,71 @@ struct Args {
     /// List of scenes to load
     #[arg(short, long)]
     scenes: Option<Vec<String>>,
+
+    /// Export the project to the given platforms (pc, wasm, android) without opening the editor
+    #[arg(long, value_parser = TargetPlatform::from_str)]
+    export: Option<Vec<TargetPlatform>>,
+
+    /// Folder for the exported builds, every platform gets its own sub-folder in it
+    #[arg(long, default_value = "./build/")]
+    destination: PathBuf,
+
+    /// Folders with the assets of the game
+    #[arg(long, default_value = "./data/")]
+    assets: Vec<PathBuf>,
+
+    /// Build target for the platforms that support multiple targets
+    #[arg(long)]
+    build_target: Option<String>,
+
+    /// Compress every texture (NoCompression, Speed, Quality)
+    #[arg(long, default_value = "NoCompression", value_parser = CompressionOptions::from_str)]
+    texture_compression: CompressionOptions,
+
+    /// Pack the assets into a single resource pack
+    #[arg(long)]
+    pack_assets: bool,
+
+    /// Keep the files that are used only by the editor
+    #[arg(long)]
+    keep_editor_data: bool,
+
+    /// Do not import the assets before exporting
+    #[arg(long)]
+    skip_import: bool,
+
+    /// Fail the export if any asset cannot be imported
+    #[arg(long)]
+    strict_import: bool,
 }
 
 fn main() {
     Log::set_file_name("fyrox.log");
 
     let args = Args::parse();
+
+    if let Some(target_platforms) = args.export {
+        let result = export_headless(HeadlessExportOptions {
+            project_directory: args.project_directory.unwrap_or_else(|| "./".into()).into(),
+            target_platforms,
+            destination_folder: args.destination,
+            assets_folders: args.assets,
+            build_target: args.build_target,
+            texture_compression: args.texture_compression,
+            pack_assets: args.pack_assets,
+            strip_editor_only_data: !args.keep_editor_data,
+            import_assets: !args.skip_import,
+            strict_import: args.strict_import,
+        });
+
+        if let Err(err) = result {
+            Log::err(err);
+            std::process::exit(1);
+        }
+
+        return;
+    }
+
     let startup_data = if let Some(proj_dir) = args.project_directory {
         Some(StartupData {
             working_directory: proj_dir.into(),
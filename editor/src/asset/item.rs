@@ -145,6 +145,12 @@ impl AssetItem {
                     sender.send(Message::OpenMaterialEditor(material));
                 }
             }
+        } else if self
+            .path
+            .extension()
+            .is_some_and(|ext| ext == "shadergraph")
+        {
+            sender.send(Message::OpenShaderGraphEditor(self.path.clone()));
         } else if self.path.extension().is_some_and(|ext| ext == "tileset") {
             if let Ok(path) = make_relative_path(&self.path) {
                 match block_on(resource_manager.request::<TileSet>(path)) {
@@ -27,6 +27,7 @@ use crate::{
             cache::{AssetPreviewCache, IconRequest},
             AssetPreviewGeneratorsCollection,
         },
+        references::ResourceReferencesViewer,
     },
     fyrox::{
         asset::{
@@ -72,6 +73,7 @@ use crate::{
     load_image,
     message::MessageSender,
     preview::PreviewPanel,
+    scene::container::SceneContainer,
     utils::window_content,
     Message, Mode,
 };
@@ -93,6 +95,7 @@ mod dependency;
 mod inspector;
 pub mod item;
 pub mod preview;
+pub mod references;
 
 struct ContextMenu {
     menu: RcUiNodeHandle,
@@ -104,6 +107,7 @@ struct ContextMenu {
     delete: Handle<UiNode>,
     placement_target: Handle<UiNode>,
     dependencies: Handle<UiNode>,
+    find_references: Handle<UiNode>,
 }
 
 fn execute_command(command: &mut Command) {
@@ -154,6 +158,7 @@ impl ContextMenu {
         let copy_path;
         let copy_file_name;
         let dependencies;
+        let find_references;
         let menu = ContextMenuBuilder::new(
             PopupBuilder::new(WidgetBuilder::new()).with_content(
                 StackPanelBuilder::new(
@@ -199,6 +204,12 @@ impl ContextMenu {
                                 .with_content(MenuItemContent::text("Dependencies"))
                                 .build(ctx);
                             dependencies
+                        })
+                        .with_child({
+                            find_references = MenuItemBuilder::new(WidgetBuilder::new())
+                                .with_content(MenuItemContent::text("Find References"))
+                                .build(ctx);
+                            find_references
                         }),
                 )
                 .build(ctx),
@@ -217,6 +228,7 @@ impl ContextMenu {
             placement_target: Default::default(),
             copy_file_name,
             dependencies,
+            find_references,
         }
     }
 
@@ -551,6 +563,7 @@ pub struct AssetBrowser {
     selected_item_path: PathBuf,
     watcher: Option<RecommendedWatcher>,
     dependency_viewer: DependencyViewer,
+    references_viewer: ResourceReferencesViewer,
     resource_creator: Option<ResourceCreator>,
     preview_cache: AssetPreviewCache,
     preview_sender: Sender<IconRequest>,
@@ -739,6 +752,7 @@ impl AssetBrowser {
         let context_menu = ContextMenu::new(ctx);
 
         let dependency_viewer = DependencyViewer::new(ctx);
+        let references_viewer = ResourceReferencesViewer::new(ctx);
 
         let (preview_sender, preview_receiver) = mpsc::channel();
 
@@ -748,6 +762,7 @@ impl AssetBrowser {
 
         Self {
             dependency_viewer,
+            references_viewer,
             window,
             content_panel,
             folder_browser,
@@ -1013,6 +1028,7 @@ impl AssetBrowser {
         message: &UiMessage,
         engine: &mut Engine,
         sender: MessageSender,
+        scenes: &SceneContainer,
     ) {
         self.inspector.handle_ui_message(message, engine);
         self.preview.handle_message(message, engine);
@@ -1025,6 +1041,8 @@ impl AssetBrowser {
         }
         self.dependency_viewer
             .handle_ui_message(message, engine.user_interfaces.first_mut());
+        self.references_viewer
+            .handle_ui_message(message, engine, scenes);
         if let Some(resource_creator) = self.resource_creator.as_mut() {
             let asset_added = resource_creator.handle_ui_message(
                 message,
@@ -1185,6 +1203,18 @@ impl AssetBrowser {
                             .open(&resource, engine.user_interfaces.first_mut());
                     }
                 }
+            } else if message.destination() == self.context_menu.find_references {
+                if let Some(path) = engine
+                    .user_interfaces
+                    .first_mut()
+                    .try_get(self.context_menu.placement_target)
+                    .and_then(|n| n.cast::<AssetItem>())
+                    .map(|item| item.path.clone())
+                {
+                    if let Ok(resource) = block_on(engine.resource_manager.request_untyped(path)) {
+                        self.references_viewer.open(resource, engine);
+                    }
+                }
             }
         } else if let Some(WindowMessage::Close) = message.data() {
             if let Some(resource_creator) = self.resource_creator.as_ref() {
@@ -1234,6 +1264,8 @@ impl AssetBrowser {
         self.preview_cache
             .update(&mut self.preview_generators, engine);
         self.preview.update(engine);
+        self.references_viewer
+            .update(engine.user_interfaces.first());
         if self.need_refresh.load(Ordering::Relaxed) {
             self.refresh(
                 engine.user_interfaces.first_mut(),
@@ -39,7 +39,10 @@ use crate::{
             widget::WidgetBuilder, HorizontalAlignment, UserInterface, VerticalAlignment,
         },
         material::{shader::Shader, Material, MaterialResource},
-        renderer::framework::gpu_texture::{GpuTextureKind, PixelKind},
+        renderer::framework::{
+            gpu_texture::{GpuTextureKind, PixelKind},
+            pixel_conversion::PixelConversionOptions,
+        },
         resource::{
             curve::CurveResourceState,
             model::{Model, ModelResourceExtension},
@@ -349,7 +352,9 @@ fn render_scene_to_texture(
             _ => unreachable!(),
         };
 
-        let pixels = ldr_texture.read_pixels();
+        // The frame is already tone mapped and gamma corrected, so keep the values as is and
+        // convert only the layout of pixels.
+        let pixels = ldr_texture.read_pixels_converted(&PixelConversionOptions::default());
 
         // TODO: This is a hack, refactor `render_scene` method to accept render data from
         // outside, instead of messing around with these temporary handles.
@@ -364,7 +369,7 @@ fn render_scene_to_texture(
                 height: height as u32,
             },
             TexturePixelKind::RGBA8,
-            pixels,
+            pixels.ok()?,
             ResourceKind::Embedded,
         )
         .map(|texture| AssetPreviewTexture {
@@ -0,0 +1,500 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Finds all scenes and prefabs of the project that reference a resource and allows replacing
+//! the resource with another one in all of them at once.
+
+use crate::{
+    fyrox::{
+        asset::{io::FsResourceIo, manager::ResourceManager, untyped::UntypedResource},
+        core::{
+            futures::executor::block_on, log::Log, pool::Handle, reflect::prelude::*,
+            visitor::Visitor,
+        },
+        engine::{Engine, SerializationContext},
+        gui::{
+            button::{ButtonBuilder, ButtonMessage},
+            grid::{Column, GridBuilder, Row},
+            message::{MessageDirection, UiMessage},
+            scroll_viewer::ScrollViewerBuilder,
+            stack_panel::StackPanelBuilder,
+            text::{TextBuilder, TextMessage},
+            text_box::TextBoxBuilder,
+            widget::WidgetBuilder,
+            window::{WindowBuilder, WindowMessage, WindowTitle},
+            BuildContext, HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
+            VerticalAlignment,
+        },
+        scene::{Scene, SceneLoader},
+        walkdir,
+    },
+    scene::container::SceneContainer,
+};
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{self, Receiver},
+        Arc,
+    },
+};
+
+/// Returns paths of all scenes and prefabs in the given directory and its subdirectories. Hidden
+/// directories and build artifacts are skipped.
+pub fn find_scene_files(root: &Path) -> Vec<PathBuf> {
+    walkdir::WalkDir::new(root)
+        .into_iter()
+        .filter_entry(|entry| {
+            let name = entry.file_name().to_string_lossy();
+            entry.depth() == 0 || !(name.starts_with('.') || name == "target")
+        })
+        .flatten()
+        .filter(|entry| {
+            entry.file_type().is_file() && entry.path().extension().is_some_and(|ext| ext == "rgs")
+        })
+        .map(|entry| entry.into_path())
+        .collect()
+}
+
+async fn load_scene_async(
+    path: &Path,
+    serialization_context: Arc<SerializationContext>,
+    resource_manager: ResourceManager,
+) -> Result<Scene, String> {
+    let (loader, _) =
+        SceneLoader::from_file(path, &FsResourceIo, serialization_context, resource_manager)
+            .await
+            .map_err(|err| err.to_string())?;
+    Ok(loader.finish().await)
+}
+
+pub(crate) fn load_scene(path: &Path, engine: &Engine) -> Result<Scene, String> {
+    block_on(load_scene_async(
+        path,
+        engine.serialization_context.clone(),
+        engine.resource_manager.clone(),
+    ))
+}
+
+/// Returns paths of all scenes and prefabs of the project, that use the given resource.
+async fn find_references(
+    resource: &UntypedResource,
+    serialization_context: Arc<SerializationContext>,
+    resource_manager: ResourceManager,
+) -> Vec<PathBuf> {
+    let mut references = Vec::new();
+    for path in find_scene_files(Path::new(".")) {
+        match load_scene_async(
+            &path,
+            serialization_context.clone(),
+            resource_manager.clone(),
+        )
+        .await
+        {
+            Ok(scene) => {
+                if scene.collect_used_resources().contains(resource) {
+                    references.push(path);
+                }
+            }
+            Err(err) => {
+                Log::warn(format!(
+                    "Unable to check references in {}. Reason: {}",
+                    path.display(),
+                    err
+                ));
+            }
+        }
+    }
+    references
+}
+
+pub(crate) fn save_scene(path: &Path, scene: &mut Scene) -> Result<(), String> {
+    let mut visitor = Visitor::new();
+    scene
+        .save("Scene", &mut visitor)
+        .map_err(|err| err.to_string())?;
+    visitor.save_binary(path).map_err(|err| err.to_string())
+}
+
+fn contains_resource(object: &dyn Reflect, resource: &UntypedResource) -> bool {
+    let mut found = false;
+    object.apply_recursively(
+        &mut |object| {
+            object.as_any(&mut |any| {
+                if any.downcast_ref::<UntypedResource>() == Some(resource) {
+                    found = true;
+                }
+            })
+        },
+        &[],
+    );
+    found
+}
+
+/// Replaces every usage of the `old` resource in the scene with the `new` one. Returns the
+/// amount of replaced usages. Inheritable variables, that hold the resource, are marked as
+/// modified, otherwise property inheritance would revert the replacement in prefab instances.
+pub fn replace_resource(scene: &mut Scene, old: &UntypedResource, new: &UntypedResource) -> usize {
+    let mut count = 0;
+    (scene as &mut dyn Reflect).apply_recursively_mut(
+        &mut |object| {
+            object.as_inheritable_variable_mut(&mut |variable| {
+                if let Some(variable) = variable {
+                    if contains_resource(variable.inner_value_ref(), old) {
+                        variable.mark_modified();
+                    }
+                }
+            });
+
+            object.as_any_mut(&mut |any| {
+                if let Some(resource) = any.downcast_mut::<UntypedResource>() {
+                    if *resource == *old {
+                        *resource = new.clone();
+                        count += 1;
+                    }
+                }
+            })
+        },
+        &[],
+    );
+    count
+}
+
+pub(crate) fn is_same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+pub struct ResourceReferencesViewer {
+    pub window: Handle<UiNode>,
+    references_text: Handle<UiNode>,
+    replacement: Handle<UiNode>,
+    replace: Handle<UiNode>,
+    close: Handle<UiNode>,
+    resource: Option<UntypedResource>,
+    references: Vec<PathBuf>,
+    replacement_path: String,
+    search: Option<Receiver<Vec<PathBuf>>>,
+}
+
+impl ResourceReferencesViewer {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let references_text;
+        let replacement;
+        let replace;
+        let close;
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(400.0).with_height(400.0))
+            .open(false)
+            .with_title(WindowTitle::text("Resource References"))
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child(
+                            ScrollViewerBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(0)
+                                    .on_column(0)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .with_content({
+                                references_text = TextBuilder::new(WidgetBuilder::new()).build(ctx);
+                                references_text
+                            })
+                            .build(ctx),
+                        )
+                        .with_child({
+                            replacement = TextBoxBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(1)
+                                    .on_column(0)
+                                    .with_height(22.0)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx);
+                            replacement
+                        })
+                        .with_child(
+                            StackPanelBuilder::new(
+                                WidgetBuilder::new()
+                                    .with_margin(Thickness::uniform(2.0))
+                                    .with_horizontal_alignment(HorizontalAlignment::Right)
+                                    .on_row(2)
+                                    .with_child({
+                                        replace = ButtonBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_width(130.0)
+                                                .with_margin(Thickness::uniform(1.0)),
+                                        )
+                                        .with_text("Replace All")
+                                        .build(ctx);
+                                        replace
+                                    })
+                                    .with_child({
+                                        close = ButtonBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_width(130.0)
+                                                .with_margin(Thickness::uniform(1.0)),
+                                        )
+                                        .with_text("Close")
+                                        .build(ctx);
+                                        close
+                                    }),
+                            )
+                            .with_orientation(Orientation::Horizontal)
+                            .build(ctx),
+                        ),
+                )
+                .add_row(Row::stretch())
+                .add_row(Row::strict(24.0))
+                .add_row(Row::strict(24.0))
+                .add_column(Column::stretch())
+                .build(ctx),
+            )
+            .build(ctx);
+
+        Self {
+            window,
+            references_text,
+            replacement,
+            replace,
+            close,
+            resource: None,
+            references: Default::default(),
+            replacement_path: Default::default(),
+            search: None,
+        }
+    }
+
+    fn set_text(&self, text: String, ui: &UserInterface) {
+        ui.send_message(TextMessage::text(
+            self.references_text,
+            MessageDirection::ToWidget,
+            text,
+        ));
+    }
+
+    /// Opens the window and starts scanning every scene of the project for references to the
+    /// given resource. Scenes are loaded in a separate thread, the results are shown in
+    /// [`Self::update`] once the scan is finished.
+    pub fn open(&mut self, resource: UntypedResource, engine: &mut Engine) {
+        let ui = engine.user_interfaces.first_mut();
+
+        self.set_text(
+            format!("Searching for references to {}...", resource.kind()),
+            ui,
+        );
+
+        ui.send_message(WindowMessage::open(
+            self.window,
+            MessageDirection::ToWidget,
+            true,
+            true,
+        ));
+
+        let (sender, receiver) = mpsc::channel();
+        let serialization_context = engine.serialization_context.clone();
+        let resource_manager = engine.resource_manager.clone();
+        let searched = resource.clone();
+        std::thread::spawn(move || {
+            let references = block_on(find_references(
+                &searched,
+                serialization_context,
+                resource_manager,
+            ));
+            // The window could be closed before the search is finished, the results are not
+            // needed anymore in this case.
+            let _ = sender.send(references);
+        });
+
+        self.references.clear();
+        self.search = Some(receiver);
+        self.resource = Some(resource);
+    }
+
+    pub fn update(&mut self, ui: &UserInterface) {
+        let Some(references) = self
+            .search
+            .as_ref()
+            .and_then(|receiver| receiver.try_recv().ok())
+        else {
+            return;
+        };
+        self.search = None;
+        self.references = references;
+
+        let Some(resource) = self.resource.as_ref() else {
+            return;
+        };
+
+        let mut text = format!(
+            "{} is referenced by {} scene(s):\n",
+            resource.kind(),
+            self.references.len()
+        );
+        for path in self.references.iter() {
+            text += &format!("{}\n", path.display());
+        }
+        text += "\nEnter a path to another resource below to replace this one everywhere.";
+        self.set_text(text, ui);
+    }
+
+    fn replace_all(&mut self, engine: &mut Engine, scenes: &SceneContainer) -> String {
+        let Some(old) = self.resource.clone() else {
+            return String::new();
+        };
+
+        let new = match block_on(
+            engine
+                .resource_manager
+                .request_untyped(&self.replacement_path),
+        ) {
+            Ok(new) => new,
+            Err(err) => {
+                return format!(
+                    "Unable to load {}. Reason: {:?}",
+                    self.replacement_path, err
+                )
+            }
+        };
+
+        if new.type_uuid() != old.type_uuid() {
+            return format!(
+                "{} cannot replace {}, because they are resources of different types.",
+                self.replacement_path,
+                old.kind()
+            );
+        }
+
+        let mut report = String::new();
+        let mut remaining = Vec::new();
+        for path in std::mem::take(&mut self.references) {
+            let is_open = scenes
+                .iter()
+                .filter_map(|entry| entry.path.as_ref())
+                .any(|open| is_same_file(open, &path));
+            if is_open {
+                report += &format!(
+                    "{} is skipped, because it is open in the editor.\n",
+                    path.display()
+                );
+                remaining.push(path);
+                continue;
+            }
+
+            let result = load_scene(&path, engine).and_then(|mut scene| {
+                let count = replace_resource(&mut scene, &old, &new);
+                save_scene(&path, &mut scene).map(|_| count)
+            });
+
+            match result {
+                Ok(count) => {
+                    report += &format!("{}: {} usage(s) replaced.\n", path.display(), count);
+                }
+                Err(err) => {
+                    report += &format!("{} failed: {}\n", path.display(), err);
+                    remaining.push(path);
+                }
+            }
+        }
+        self.references = remaining;
+        report
+    }
+
+    pub fn handle_ui_message(
+        &mut self,
+        message: &UiMessage,
+        engine: &mut Engine,
+        scenes: &SceneContainer,
+    ) {
+        if let Some(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.close {
+                engine
+                    .user_interfaces
+                    .first_mut()
+                    .send_message(WindowMessage::close(
+                        self.window,
+                        MessageDirection::ToWidget,
+                    ));
+            } else if message.destination() == self.replace {
+                let report = self.replace_all(engine, scenes);
+                self.set_text(report, engine.user_interfaces.first());
+            }
+        } else if let Some(TextMessage::Text(text)) = message.data() {
+            if message.destination() == self.replacement
+                && message.direction() == MessageDirection::FromWidget
+            {
+                self.replacement_path.clone_from(text);
+            }
+        } else if let Some(WindowMessage::Close) = message.data() {
+            if message.destination() == self.window {
+                self.resource = None;
+                self.references.clear();
+                self.search = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::replace_resource;
+    use crate::fyrox::{
+        asset::untyped::ResourceKind,
+        core::{reflect::prelude::*, variable::mark_inheritable_properties_non_modified},
+        resource::texture::{Texture, TextureResource},
+        scene::{base::BaseBuilder, decal::DecalBuilder, Scene},
+    };
+
+    #[test]
+    fn test_replace_resource() {
+        let old = TextureResource::new_ok(ResourceKind::Embedded, Texture::default());
+        let new = TextureResource::new_ok(ResourceKind::Embedded, Texture::default());
+
+        let mut scene = Scene::new();
+        let decal = DecalBuilder::new(BaseBuilder::new())
+            .with_diffuse_texture(old.clone())
+            .with_normal_texture(old.clone())
+            .build(&mut scene.graph);
+        // Properties of prefab instances are not modified until they're changed by a user.
+        mark_inheritable_properties_non_modified(&mut scene.graph[decal], &[]);
+
+        let count = replace_resource(
+            &mut scene,
+            &old.clone().into_untyped(),
+            &new.clone().into_untyped(),
+        );
+        assert_eq!(count, 2);
+
+        let node = &scene.graph[decal];
+        for name in ["diffuse_texture", "normal_texture"] {
+            node.field(name, &mut |field| {
+                field.unwrap().as_inheritable_variable(&mut |variable| {
+                    assert!(variable.unwrap().is_modified());
+                })
+            });
+        }
+
+        let decal = scene.graph[decal].as_decal();
+        assert_eq!(decal.diffuse_texture(), Some(&new));
+        assert_eq!(decal.normal_texture(), Some(&new));
+    }
+}
@@ -808,7 +808,7 @@ fn transform_vertex(vertex: Vector3<f32>, transform: &Matrix4<f32>) -> Vector3<f
     transform.transform_point(&Point3::from(vertex)).coords
 }
 
-fn read_triangle(
+pub(crate) fn read_triangle(
     data: &SurfaceData,
     triangle: &TriangleDefinition,
     transform: &Matrix4<f32>,
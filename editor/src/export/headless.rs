@@ -0,0 +1,223 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Headless export of a project. It does not require a window or a graphics context, so it could
+//! be used on CI servers. See [`export_headless`] for more info.
+
+use crate::{
+    export::{export, ExportOptions, TargetPlatform},
+    fyrox::{
+        asset::manager::ResourceManager,
+        core::{futures::executor::block_on, log::Log, task::TaskPool},
+        engine::{Engine, EngineInitParams, SerializationContext},
+        gui::constructor::new_widget_constructor_container,
+        resource::texture::CompressionOptions,
+    },
+};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{atomic::AtomicBool, Arc},
+};
+
+/// A set of options for [`export_headless`].
+#[derive(Debug, Clone)]
+pub struct HeadlessExportOptions {
+    /// Root directory of the project (the one with the workspace manifest).
+    pub project_directory: PathBuf,
+    /// A set of platforms to export the game to. Every platform gets its own build directory
+    /// inside the destination folder.
+    pub target_platforms: Vec<TargetPlatform>,
+    /// A folder where build directories will be created. Relative to the project directory.
+    pub destination_folder: PathBuf,
+    /// A set of folders with assets. Relative to the project directory.
+    pub assets_folders: Vec<PathBuf>,
+    /// Build target for the platforms that support multiple targets (for example, Android). The
+    /// first available build target is used if not specified.
+    pub build_target: Option<String>,
+    /// Compression that will be applied to every texture.
+    pub texture_compression: CompressionOptions,
+    /// Pack all the assets into a single resource pack.
+    pub pack_assets: bool,
+    /// Removes the files that are used only by the editor.
+    pub strip_editor_only_data: bool,
+    /// Tries to import every asset before exporting to find broken assets early.
+    pub import_assets: bool,
+    /// Treat asset import errors as export errors. Keep in mind, that the game plugin is not
+    /// loaded in headless mode, which means that assets with game-specific types (for example,
+    /// scenes with scripts) cannot be imported.
+    pub strict_import: bool,
+}
+
+impl Default for HeadlessExportOptions {
+    fn default() -> Self {
+        let defaults = ExportOptions::default();
+        Self {
+            project_directory: "./".into(),
+            target_platforms: vec![TargetPlatform::PC],
+            destination_folder: defaults.destination_folder,
+            assets_folders: defaults.assets_folders,
+            build_target: None,
+            texture_compression: defaults.texture_compression,
+            pack_assets: defaults.pack_assets,
+            strip_editor_only_data: defaults.strip_editor_only_data,
+            import_assets: true,
+            strict_import: false,
+        }
+    }
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            collect_files(&entry.path(), files)?;
+        } else {
+            files.push(entry.path());
+        }
+    }
+    Ok(())
+}
+
+/// Tries to load every asset, that has a respective resource loader. Returns the amount of assets
+/// that failed to load.
+fn import_assets(assets_folders: &[PathBuf]) -> Result<usize, String> {
+    let serialization_context = Arc::new(SerializationContext::new());
+    let task_pool = Arc::new(TaskPool::new());
+    let engine = Engine::new(EngineInitParams {
+        graphics_context_params: Default::default(),
+        resource_manager: ResourceManager::new(task_pool.clone()),
+        serialization_context,
+        task_pool,
+        widget_constructors: Arc::new(new_widget_constructor_container()),
+    })
+    .map_err(|err| format!("Unable to initialize the engine. Reason: {err:?}"))?;
+
+    let mut files = Vec::new();
+    for folder in assets_folders {
+        collect_files(folder, &mut files).map_err(|err| {
+            format!(
+                "Unable to read assets folder {}. Reason: {err:?}",
+                folder.display()
+            )
+        })?;
+    }
+
+    let mut resources = Vec::new();
+    for path in files {
+        let Some(extension) = path.extension().and_then(|ext| ext.to_str()) else {
+            continue;
+        };
+
+        let is_supported = engine
+            .resource_manager
+            .state()
+            .loaders
+            .iter()
+            .any(|loader| loader.supports_extension(extension));
+        if is_supported {
+            resources.push((engine.resource_manager.request_untyped(&path), path));
+        }
+    }
+
+    Log::info(format!("Importing {} assets...", resources.len()));
+
+    let mut failed = 0;
+    for (resource, path) in resources {
+        match block_on(resource) {
+            Ok(_) => Log::info(format!("{} was imported successfully.", path.display())),
+            Err(err) => {
+                failed += 1;
+                Log::err(format!(
+                    "Unable to import {}. Reason: {err:?}",
+                    path.display()
+                ));
+            }
+        }
+    }
+
+    Ok(failed)
+}
+
+/// Exports the project for every requested platform without creating a window or a graphics
+/// context. The export consists of the following steps:
+///
+/// 1) Every asset, that has a respective resource loader, is imported to find broken assets.
+/// 2) For every target platform the game is built in release mode and the assets are copied (or
+/// packed) to a respective build directory (`<destination>/<platform>`). Textures are compressed
+/// and editor-only files are removed at this step.
+///
+/// Changes the working directory of the process to the project directory.
+pub fn export_headless(options: HeadlessExportOptions) -> Result<(), String> {
+    std::env::set_current_dir(&options.project_directory).map_err(|err| {
+        format!(
+            "Unable to use {} as the project directory. Reason: {err:?}",
+            options.project_directory.display()
+        )
+    })?;
+
+    if options.import_assets {
+        let failed = import_assets(&options.assets_folders)?;
+        if failed > 0 {
+            let message = format!("{failed} assets failed to import.");
+            if options.strict_import {
+                return Err(message);
+            }
+            Log::warn(message);
+        }
+    }
+
+    for target_platform in options.target_platforms.iter().cloned() {
+        let mut build_targets = target_platform.default_build_targets();
+        if let Some(build_target) = options.build_target.as_ref() {
+            if target_platform != TargetPlatform::PC {
+                build_targets = vec![build_target.clone()];
+            }
+        }
+
+        let destination_folder = options
+            .destination_folder
+            .join(target_platform.to_string().to_lowercase());
+
+        Log::info(format!(
+            "Exporting the game for {target_platform} to {}...",
+            destination_folder.display()
+        ));
+
+        export(
+            ExportOptions {
+                target_platform,
+                destination_folder,
+                assets_folders: options.assets_folders.clone(),
+                strip_editor_only_data: options.strip_editor_only_data,
+                texture_compression: options.texture_compression,
+                pack_assets: options.pack_assets,
+                build_targets,
+                selected_build_target: 0,
+                run_after_build: false,
+                open_destination_folder: false,
+                ..Default::default()
+            },
+            Arc::new(AtomicBool::new(false)),
+        )?;
+    }
+
+    Ok(())
+}
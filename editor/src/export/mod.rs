@@ -20,7 +20,15 @@
 
 use crate::{
     fyrox::{
+        asset::{
+            io::FsResourceIo,
+            manifest::AssetManifest,
+            options::{try_get_import_settings, OPTIONS_EXTENSION},
+            pack::{ResourcePackBuilder, DEFAULT_PACK_FILE_NAME},
+        },
         core::{
+            append_extension,
+            futures::executor::block_on,
             log::{Log, LogMessage, MessageKind},
             pool::Handle,
             reflect::prelude::*,
@@ -51,6 +59,7 @@ use crate::{
             BuildContext, HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
             VerticalAlignment,
         },
+        resource::texture::{CompressionOptions, Texture, TextureImportOptions},
     },
     message::MessageSender,
     Message,
@@ -64,6 +73,7 @@ use std::{
     io::{self, BufRead, BufReader},
     path::{Path, PathBuf},
     process::Stdio,
+    str::FromStr,
     sync::{
         atomic::{AtomicBool, Ordering},
         mpsc::{self, Receiver},
@@ -74,6 +84,11 @@ use std::{
 use strum::VariantNames;
 use strum_macros::VariantNames;
 
+pub mod headless;
+
+/// Extensions of the files that are used only by the editor and never loaded by a game.
+const EDITOR_ONLY_EXTENSIONS: &[&str] = &["shadergraph"];
+
 #[derive(Reflect, Debug, Clone)]
 struct ExportOptions {
     #[reflect(hidden)]
@@ -82,6 +97,15 @@ struct ExportOptions {
     include_used_assets: bool,
     assets_folders: Vec<PathBuf>,
     ignored_extensions: Vec<String>,
+    strip_editor_only_data: bool,
+    #[reflect(
+        description = "Compression that will be applied to every texture at export time. \
+        Compressed textures are stored in DDS format and loaded as-is. `NoCompression` keeps \
+        the textures untouched."
+    )]
+    texture_compression: CompressionOptions,
+    #[reflect(description = "Pack all the assets into a single resource pack file.")]
+    pack_assets: bool,
     #[reflect(hidden)]
     build_targets: Vec<String>,
     #[reflect(hidden)]
@@ -98,6 +122,9 @@ impl Default for ExportOptions {
             assets_folders: vec!["./data/".into()],
             include_used_assets: false,
             ignored_extensions: vec!["log".to_string()],
+            strip_editor_only_data: true,
+            texture_compression: CompressionOptions::NoCompression,
+            pack_assets: false,
             build_targets: vec!["default".to_string()],
             selected_build_target: 0,
             run_after_build: false,
@@ -107,7 +134,7 @@ impl Default for ExportOptions {
 }
 
 #[derive(Copy, Clone, VariantNames, Default, Debug, Eq, PartialEq)]
-enum TargetPlatform {
+pub enum TargetPlatform {
     #[default]
     PC,
     WebAssembly,
@@ -128,6 +155,36 @@ impl Display for TargetPlatform {
     }
 }
 
+impl FromStr for TargetPlatform {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "pc" => Ok(Self::PC),
+            "wasm" | "webassembly" => Ok(Self::WebAssembly),
+            "android" => Ok(Self::Android),
+            _ => Err(format!(
+                "Unknown target platform {s}! Supported platforms are: pc, wasm, android."
+            )),
+        }
+    }
+}
+
+impl TargetPlatform {
+    fn default_build_targets(self) -> Vec<String> {
+        match self {
+            TargetPlatform::PC => vec!["default".to_string()],
+            TargetPlatform::WebAssembly => vec!["wasm32-unknown-unknown".to_string()],
+            TargetPlatform::Android => {
+                vec![
+                    "armv7-linux-androideabi".to_string(),
+                    "aarch64-linux-android".to_string(),
+                ]
+            }
+        }
+    }
+}
+
 pub struct ExportWindow {
     pub window: Handle<UiNode>,
     log: Handle<UiNode>,
@@ -171,6 +228,145 @@ where
     Ok(())
 }
 
+fn is_asset_ignored(path: &Path, export_options: &ExportOptions) -> bool {
+    let Some(extension) = path.extension().and_then(|ext| ext.to_str()) else {
+        return false;
+    };
+
+    export_options
+        .ignored_extensions
+        .iter()
+        .any(|ignored| ignored.eq_ignore_ascii_case(extension))
+        || (export_options.strip_editor_only_data
+            && EDITOR_ONLY_EXTENSIONS
+                .iter()
+                .any(|ignored| ignored.eq_ignore_ascii_case(extension)))
+}
+
+fn compress_texture(
+    path: &Path,
+    data: &[u8],
+    compression: CompressionOptions,
+) -> Result<Vec<u8>, String> {
+    let mut import_options = if append_extension(path, OPTIONS_EXTENSION).exists() {
+        block_on(try_get_import_settings::<TextureImportOptions>(
+            path,
+            &FsResourceIo,
+        ))
+        .unwrap_or_default()
+    } else {
+        TextureImportOptions::default()
+    };
+    import_options.set_compression(compression);
+
+    Texture::load_from_memory(data, import_options)
+        .and_then(|texture| texture.encode_dds())
+        .map_err(|err| err.to_string())
+}
+
+fn prepare_asset(path: &Path, data: Vec<u8>, export_options: &ExportOptions) -> Vec<u8> {
+    let is_texture = path.extension().is_some_and(|ext| {
+        ["jpg", "jpeg", "tga", "gif", "bmp", "png", "tiff", "tif"]
+            .iter()
+            .any(|supported| ext.eq_ignore_ascii_case(supported))
+    });
+
+    if is_texture && export_options.texture_compression != CompressionOptions::NoCompression {
+        match compress_texture(path, &data, export_options.texture_compression) {
+            Ok(compressed) => {
+                Log::info(format!("{} was compressed successfully.", path.display()));
+                return compressed;
+            }
+            Err(err) => Log::warn(format!(
+                "Unable to compress {}, it will be exported as is. Reason: {err}",
+                path.display()
+            )),
+        }
+    }
+
+    data
+}
+
+fn export_assets_dir(
+    src: &Path,
+    dst: &Path,
+    export_options: &ExportOptions,
+    pack: &mut Option<ResourcePackBuilder>,
+) -> io::Result<()> {
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let path = entry.path();
+        if is_asset_ignored(&path, export_options) {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            export_assets_dir(&path, &dst.join(entry.file_name()), export_options, pack)?;
+        } else {
+            let data = prepare_asset(&path, fs::read(&path)?, export_options);
+            if let Some(pack) = pack {
+                pack.add_file(&path, data);
+            } else {
+                fs::create_dir_all(dst)?;
+                let to = dst.join(entry.file_name());
+                fs::write(&to, data)?;
+                Log::info(format!(
+                    "{} successfully exported to {}",
+                    path.display(),
+                    to.display()
+                ))
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Copies the assets to the given destination folder, preserving their relative paths. Assets
+/// could also be packed into a single resource pack, that will be written to the destination
+/// folder.
+fn export_assets(export_options: &ExportOptions, destination: &Path) -> Result<(), String> {
+    let pack_assets =
+        export_options.pack_assets && export_options.target_platform != TargetPlatform::WebAssembly;
+    if export_options.pack_assets && !pack_assets {
+        Log::warn("Resource packs are not supported on WebAssembly, the assets will be copied.");
+    }
+
+    let mut pack = pack_assets.then(ResourcePackBuilder::new);
+
+    for folder in export_options.assets_folders.iter() {
+        Log::info(format!(
+            "Trying to export assets from {} to {}...",
+            folder.display(),
+            destination.display()
+        ));
+
+        export_assets_dir(folder, &destination.join(folder), export_options, &mut pack).map_err(
+            |err| {
+                format!(
+                    "Unable to export assets from {}. Reason: {err:?}",
+                    folder.display()
+                )
+            },
+        )?;
+    }
+
+    if let Some(pack) = pack {
+        let pack_path = destination.join(DEFAULT_PACK_FILE_NAME);
+        pack.write(&pack_path).map_err(|err| {
+            format!(
+                "Unable to write resource pack {}. Reason: {err:?}",
+                pack_path.display()
+            )
+        })?;
+        Log::info(format!(
+            "{} assets were packed into {}",
+            pack.len(),
+            pack_path.display()
+        ));
+    }
+
+    Ok(())
+}
+
 fn make_command(program: &str) -> std::process::Command {
     let mut command = std::process::Command::new(program);
     // Remove the `RUSTFLAGS` environment variable, which could be added to the child process
@@ -549,19 +745,7 @@ fn export(export_options: ExportOptions, cancel_flag: Arc<AtomicBool>) -> Result
         TargetPlatform::PC | TargetPlatform::WebAssembly => {
             Log::info("Trying to copy the assets...");
 
-            for folder in export_options.assets_folders {
-                Log::info(format!(
-                    "Trying to copy assets from {} to {}...",
-                    folder.display(),
-                    export_options.destination_folder.display()
-                ));
-
-                Log::verify(copy_dir(
-                    &folder,
-                    export_options.destination_folder.join(&folder),
-                    &|_| true,
-                ));
-            }
+            export_assets(&export_options, &export_options.destination_folder)?;
         }
         TargetPlatform::Android => {
             // Asset management on Android is quite annoying, because all other target platforms
@@ -599,19 +783,13 @@ fn export(export_options: ExportOptions, cancel_flag: Arc<AtomicBool>) -> Result
 
                 temp_folders.push(temp_assets_storage.clone());
 
-                for folder in export_options.assets_folders {
-                    Log::info(format!(
-                        "Trying to copy assets from {} to {}...",
-                        folder.display(),
-                        temp_assets_storage.display()
-                    ));
+                export_assets(&export_options, &temp_assets_storage)?;
 
-                    Log::verify(copy_dir(
-                        &folder,
-                        temp_assets_storage.join(&folder),
-                        &|_| true,
-                    ));
-                }
+                // Android asset manager cannot list subdirectories, so the list of all the
+                // exported files is stored next to the assets.
+                AssetManifest::generate_and_write(&temp_assets_storage).map_err(|err| {
+                    format!("Failed to write the asset manifest. Reason: {err:?}")
+                })?;
             } else {
                 return Err("Android executor must specify assets folder in \
                     [package.metadata.android] section"
@@ -1045,18 +1223,8 @@ impl ExportWindow {
                     }
 
                     // TODO: move this to settings.
-                    let build_targets = match self.export_options.target_platform {
-                        TargetPlatform::PC => vec!["default".to_string()],
-                        TargetPlatform::WebAssembly => vec!["wasm32-unknown-unknown".to_string()],
-                        TargetPlatform::Android => {
-                            vec![
-                                "armv7-linux-androideabi".to_string(),
-                                "aarch64-linux-android".to_string(),
-                            ]
-                        }
-                    };
-
-                    self.export_options.build_targets = build_targets;
+                    self.export_options.build_targets =
+                        self.export_options.target_platform.default_build_targets();
 
                     let ui_items = self
                         .export_options
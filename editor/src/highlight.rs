@@ -31,7 +31,7 @@ use crate::{
             framework::{
                 buffer::BufferUsage,
                 error::FrameworkError,
-                framebuffer::{Attachment, AttachmentKind, GpuFrameBuffer},
+                framebuffer::{Attachment, GpuFrameBuffer},
                 geometry_buffer::GpuGeometryBuffer,
                 gpu_texture::PixelKind,
                 server::GraphicsServer,
@@ -72,14 +72,8 @@ impl HighlightRenderPass {
 
         server
             .create_frame_buffer(
-                Some(Attachment {
-                    kind: AttachmentKind::DepthStencil,
-                    texture: depth_stencil,
-                }),
-                vec![Attachment {
-                    kind: AttachmentKind::Color,
-                    texture: frame_texture,
-                }],
+                Some(Attachment::depth_stencil(depth_stencil)),
+                vec![Attachment::color(frame_texture)],
             )
             .unwrap()
     }
@@ -146,6 +140,7 @@ impl SceneRenderPass for HighlightRenderPass {
                 storage: &mut render_bundle_storage,
                 graph: &ctx.scene.graph,
                 render_pass_name: &render_pass_name,
+                lod_fade: 1.0,
             };
 
             for &root_node_handle in self.nodes_to_highlight.iter() {
@@ -172,10 +167,12 @@ impl SceneRenderPass for HighlightRenderPass {
                     render_pass_name: &render_pass_name,
                     frame_buffer: &self.framebuffer,
                     use_pom: false,
+                    use_instancing: true,
                     light_position: &Default::default(),
                     fallback_resources: ctx.fallback_resources,
                     ambient_light: Default::default(),
                     scene_depth: Some(ctx.depth_texture),
+                    motion: None,
                     viewport: ctx.viewport,
                     uniform_memory_allocator: ctx.uniform_memory_allocator,
                 },
@@ -53,6 +53,7 @@ pub mod navmesh;
 pub mod plane;
 pub mod rotate_mode;
 pub mod scale_mode;
+pub mod scatter;
 pub mod select_mode;
 pub mod terrain;
 
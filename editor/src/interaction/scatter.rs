@@ -0,0 +1,703 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Scatter brush interaction mode. It paints instances of a set of prefabs onto the surfaces
+//! (terrains and meshes) of a scene and allows to erase them back. See [`ScatterBrush`] for
+//! the list of available options.
+
+use crate::{
+    camera::read_triangle,
+    command::{Command, CommandGroup},
+    fyrox::{
+        core::{
+            algebra::{UnitQuaternion, Vector2, Vector3},
+            arrayvec::ArrayVec,
+            log::Log,
+            math::{curve::Curve, ray::Ray, vector_to_quat},
+            pool::Handle,
+            rand::{thread_rng, Rng},
+            reflect::prelude::*,
+            uuid::{uuid, Uuid},
+            TypeUuidProvider,
+        },
+        engine::Engine,
+        graph::{BaseSceneGraph, SceneGraph},
+        gui::{
+            inspector::{
+                Inspector, InspectorBuilder, InspectorContext, InspectorMessage, PropertyAction,
+            },
+            key::HotKey,
+            message::{MessageDirection, UiMessage},
+            widget::{WidgetBuilder, WidgetMessage},
+            window::{WindowBuilder, WindowMessage, WindowTitle},
+            BuildContext, HorizontalAlignment, Thickness, UiNode, UserInterface, VerticalAlignment,
+        },
+        resource::model::{ModelResource, ModelResourceExtension},
+        scene::{
+            camera::Camera,
+            graph::Graph,
+            mesh::Mesh,
+            node::Node,
+            terrain::{Terrain, TerrainRayCastResult},
+            Scene,
+        },
+    },
+    interaction::{make_interaction_mode_button, terrain::BrushGizmo, InteractionMode},
+    message::MessageSender,
+    plugins::inspector::editors::make_property_editors_container,
+    scene::{
+        commands::graph::{AddModelCommand, DeleteSubGraphCommand},
+        controller::SceneController,
+        GameScene, Selection,
+    },
+    settings::Settings,
+    MSG_SYNC_FLAG,
+};
+use std::{f32::consts::PI, ops::Range, sync::Arc};
+
+/// Options of the scatter brush.
+#[derive(Clone, Debug, Reflect)]
+pub struct ScatterBrush {
+    #[reflect(
+        description = "A set of prefabs to scatter. Every new instance uses a random prefab from this list."
+    )]
+    pub prefabs: Vec<Option<ModelResource>>,
+    #[reflect(min_value = 0.0, description = "Radius of the brush in meters.")]
+    pub radius: f32,
+    #[reflect(
+        min_value = 0.0,
+        description = "Average amount of instances per square meter placed by a single stamp of the brush."
+    )]
+    pub density: f32,
+    #[reflect(
+        description = "Probability of placing an instance depending on its normalized distance from the \
+        center (0.0) to the edge (1.0) of the brush. An empty curve means uniform distribution."
+    )]
+    pub falloff: Curve,
+    #[reflect(
+        min_value = 0.0,
+        description = "Minimal distance between the instances of the brush prefabs."
+    )]
+    pub min_spacing: f32,
+    #[reflect(description = "Range of random rotation (in degrees) around the up axis.")]
+    pub yaw_range: Range<f32>,
+    #[reflect(description = "Range of random uniform scale of the instances.")]
+    pub scale_range: Range<f32>,
+    #[reflect(
+        description = "Defines whether the instances should be aligned with the normal of the surface or not."
+    )]
+    pub align_to_normal: bool,
+    #[reflect(
+        description = "Range of slopes (in degrees) of the surface on which the instances can be placed."
+    )]
+    pub slope_range: Range<f32>,
+    #[reflect(description = "Range of world-space heights at which the instances can be placed.")]
+    pub altitude_range: Range<f32>,
+    #[reflect(
+        description = "Defines whether the brush removes the instances of its prefabs instead of placing \
+        them. Holding Shift temporarily inverts this option."
+    )]
+    pub erase: bool,
+}
+
+impl Default for ScatterBrush {
+    fn default() -> Self {
+        Self {
+            prefabs: Default::default(),
+            radius: 2.0,
+            density: 1.0,
+            falloff: Default::default(),
+            min_spacing: 0.5,
+            yaw_range: 0.0..360.0,
+            scale_range: 0.8..1.2,
+            align_to_normal: false,
+            slope_range: 0.0..45.0,
+            altitude_range: -1000.0..1000.0,
+            erase: false,
+        }
+    }
+}
+
+impl ScatterBrush {
+    fn prefabs(&self) -> Vec<ModelResource> {
+        self.prefabs.iter().flatten().cloned().collect()
+    }
+}
+
+fn sample_range(rng: &mut impl Rng, range: &Range<f32>) -> f32 {
+    if range.end > range.start {
+        rng.gen_range(range.clone())
+    } else {
+        range.start
+    }
+}
+
+fn in_range(range: &Range<f32>, value: f32) -> bool {
+    value >= range.start && value <= range.end
+}
+
+fn is_brush_instance(node: &Node, prefabs: &[ModelResource]) -> bool {
+    node.resource()
+        .is_some_and(|resource| prefabs.contains(&resource))
+}
+
+struct SurfaceHit {
+    position: Vector3<f32>,
+    normal: Vector3<f32>,
+}
+
+/// Finds the closest intersection of the ray with terrains and meshes of the sub-graph starting
+/// from the `root`. Instances of the brush prefabs are ignored, so the brush does not stack the
+/// instances on top of each other.
+fn probe_surface(
+    graph: &Graph,
+    root: Handle<Node>,
+    ray: &Ray,
+    prefabs: &[ModelResource],
+) -> Option<SurfaceHit> {
+    let mut closest_toi = f32::MAX;
+    let mut closest = None;
+
+    for (_, node) in graph.traverse_iter(root) {
+        if !node.global_visibility() || is_brush_instance(node, prefabs) {
+            continue;
+        }
+
+        if let Some(terrain) = node.cast::<Terrain>() {
+            let mut intersections = ArrayVec::<TerrainRayCastResult, 128>::new();
+            terrain.raycast(*ray, &mut intersections, true);
+            if let Some(first) = intersections.first() {
+                if first.toi < closest_toi {
+                    closest_toi = first.toi;
+                    closest = Some(SurfaceHit {
+                        position: first.position,
+                        normal: first.normal,
+                    });
+                }
+            }
+        } else if let Some(mesh) = node.cast::<Mesh>() {
+            if ray.aabb_intersection(&node.world_bounding_box()).is_none() {
+                continue;
+            }
+
+            let transform = mesh.global_transform();
+            for surface in mesh.surfaces() {
+                let data = surface.data();
+                let data = data.data_ref();
+
+                for triangle in data
+                    .geometry_buffer
+                    .iter()
+                    .filter_map(|t| read_triangle(&data, t, &transform))
+                {
+                    // Back faces cannot be painted on.
+                    let normal = (triangle[1] - triangle[0]).cross(&(triangle[2] - triangle[0]));
+                    if normal.dot(&ray.dir) >= 0.0 {
+                        continue;
+                    }
+
+                    if let Some((toi, position)) = ray.triangle_intersection(&triangle) {
+                        if toi < closest_toi {
+                            closest_toi = toi;
+                            closest = Some(SurfaceHit {
+                                position,
+                                normal: normal
+                                    .try_normalize(f32::EPSILON)
+                                    .unwrap_or_else(Vector3::y),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    closest
+}
+
+#[derive(Default)]
+struct ScatterStroke {
+    erase: bool,
+    last_stamp: Option<Vector3<f32>>,
+    /// Instances placed during the stroke. They're added to the scene directly and then moved
+    /// to a single command at the end of the stroke, so the whole stroke could be undone at once.
+    painted: Vec<(Handle<Node>, Vector3<f32>)>,
+    erased: Vec<Handle<Node>>,
+}
+
+pub struct ScatterInteractionMode {
+    message_sender: MessageSender,
+    brush: ScatterBrush,
+    brush_panel: ScatterBrushPanel,
+    brush_gizmo: BrushGizmo,
+    stroke: Option<ScatterStroke>,
+    scene_viewer_frame: Handle<UiNode>,
+}
+
+impl ScatterInteractionMode {
+    pub fn new(
+        game_scene: &GameScene,
+        engine: &mut Engine,
+        message_sender: MessageSender,
+        scene_viewer_frame: Handle<UiNode>,
+    ) -> Self {
+        let brush = ScatterBrush::default();
+        let brush_panel = ScatterBrushPanel::new(
+            &mut engine.user_interfaces.first_mut().build_ctx(),
+            &brush,
+            message_sender.clone(),
+        );
+
+        Self {
+            message_sender,
+            brush,
+            brush_panel,
+            brush_gizmo: BrushGizmo::new(game_scene, engine),
+            stroke: None,
+            scene_viewer_frame,
+        }
+    }
+
+    fn probe_cursor(
+        &self,
+        game_scene: &GameScene,
+        graph: &Graph,
+        mouse_position: Vector2<f32>,
+        frame_size: Vector2<f32>,
+    ) -> Option<SurfaceHit> {
+        let camera = graph[game_scene.camera_controller.camera].cast::<Camera>()?;
+        let ray = camera.make_ray(mouse_position, frame_size);
+        probe_surface(
+            graph,
+            game_scene.scene_content_root,
+            &ray,
+            &self.brush.prefabs(),
+        )
+    }
+
+    fn stamp(&mut self, game_scene: &GameScene, scene: &mut Scene, center: Vector3<f32>) {
+        let Some(stroke) = self.stroke.as_mut() else {
+            return;
+        };
+
+        // Do not stamp too often, otherwise the density will depend on the speed of the cursor.
+        if let Some(last_stamp) = stroke.last_stamp {
+            if last_stamp.metric_distance(&center) < self.brush.radius * 0.5 {
+                return;
+            }
+        }
+        stroke.last_stamp = Some(center);
+
+        let prefabs = self.brush.prefabs();
+        let root = game_scene.scene_content_root;
+        let radius = self.brush.radius;
+
+        if stroke.erase {
+            let mut group = Vec::new();
+            for (handle, node) in scene.graph.traverse_iter(root) {
+                if node.is_resource_instance_root()
+                    && is_brush_instance(node, &prefabs)
+                    && !stroke.erased.contains(&handle)
+                    && node.global_position().metric_distance(&center) <= radius
+                {
+                    stroke.erased.push(handle);
+                    group.push(Command::new(DeleteSubGraphCommand::new(handle)));
+                }
+            }
+            if !group.is_empty() {
+                self.message_sender
+                    .do_command(CommandGroup::from(group).with_custom_name("Scatter Erase"));
+            }
+            return;
+        }
+
+        let loaded = prefabs
+            .iter()
+            .filter(|prefab| prefab.is_ok())
+            .collect::<Vec<_>>();
+        if loaded.is_empty() {
+            return;
+        }
+
+        let mut occupied = scene
+            .graph
+            .traverse_iter(root)
+            .filter(|(_, node)| {
+                node.is_resource_instance_root() && is_brush_instance(node, &prefabs)
+            })
+            .map(|(_, node)| node.global_position())
+            .chain(stroke.painted.iter().map(|(_, position)| *position))
+            .filter(|position| position.metric_distance(&center) <= radius + self.brush.min_spacing)
+            .collect::<Vec<_>>();
+
+        let mut rng = thread_rng();
+        let expected = self.brush.density * PI * radius * radius;
+        let mut count = expected.floor() as usize;
+        if rng.gen::<f32>() < expected.fract() {
+            count += 1;
+        }
+
+        // Probe the surface vertically from above the brush, the probe must be long enough to
+        // reach surfaces that are lower or higher than the center of the brush.
+        let up = Vector3::y();
+        let probe_height = radius * 2.0 + 1.0;
+
+        for _ in 0..count {
+            let angle = rng.gen_range(0.0..2.0 * PI);
+            let distance = radius * rng.gen::<f32>().sqrt();
+
+            if !self.brush.falloff.is_empty() && radius > 0.0 {
+                let probability = self.brush.falloff.value_at(distance / radius);
+                if rng.gen::<f32>() > probability {
+                    continue;
+                }
+            }
+
+            let origin = center
+                + Vector3::new(angle.cos() * distance, 0.0, angle.sin() * distance)
+                + up.scale(probe_height);
+            let ray = Ray::new(origin, up.scale(-2.0 * probe_height));
+
+            let Some(hit) = probe_surface(&scene.graph, root, &ray, &prefabs) else {
+                continue;
+            };
+
+            if !in_range(&self.brush.slope_range, hit.normal.angle(&up).to_degrees())
+                || !in_range(&self.brush.altitude_range, hit.position.y)
+                || occupied
+                    .iter()
+                    .any(|p| p.metric_distance(&hit.position) < self.brush.min_spacing)
+            {
+                continue;
+            }
+
+            let yaw = sample_range(&mut rng, &self.brush.yaw_range).to_radians();
+            let mut rotation = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), yaw);
+            if self.brush.align_to_normal {
+                rotation = UnitQuaternion::rotation_between(&up, &hit.normal).unwrap_or_default()
+                    * rotation;
+            }
+            let scale = sample_range(&mut rng, &self.brush.scale_range);
+
+            let prefab = loaded[rng.gen_range(0..loaded.len())];
+            let instance = prefab
+                .begin_instantiation(scene)
+                .with_position(hit.position)
+                .with_rotation(rotation)
+                .with_scale(Vector3::repeat(scale))
+                .finish();
+            scene.graph.link_nodes(instance, root);
+
+            occupied.push(hit.position);
+            stroke.painted.push((instance, hit.position));
+        }
+    }
+
+    fn finish_stroke(&mut self, game_scene: &GameScene, engine: &mut Engine) {
+        let Some(stroke) = self.stroke.take() else {
+            return;
+        };
+
+        let graph = &mut engine.scenes[game_scene.scene].graph;
+
+        // Immediately extract the painted instances from the scene to sub-graphs. This is
+        // required to not violate the rule of one place of execution, only commands allowed to
+        // modify the scene.
+        let group = stroke
+            .painted
+            .into_iter()
+            .filter(|(instance, _)| graph.is_valid_handle(*instance))
+            .map(|(instance, _)| {
+                Command::new(AddModelCommand::new(graph.take_reserve_sub_graph(instance)))
+            })
+            .collect::<Vec<_>>();
+
+        if !group.is_empty() {
+            self.message_sender
+                .do_command(CommandGroup::from(group).with_custom_name("Scatter Paint"));
+        }
+    }
+
+    fn update_gizmo(&self, graph: &mut Graph, hit: Option<&SurfaceHit>) {
+        let gizmo = &mut graph[self.brush_gizmo.brush];
+        if let Some(hit) = hit {
+            let diameter = self.brush.radius * 2.0;
+            gizmo
+                .local_transform_mut()
+                .set_position(hit.position)
+                .set_scale(Vector3::new(diameter, diameter, 1.0))
+                .set_rotation(vector_to_quat(hit.normal));
+        }
+        if gizmo.visibility() != hit.is_some() {
+            gizmo.set_visibility(hit.is_some());
+        }
+    }
+}
+
+impl TypeUuidProvider for ScatterInteractionMode {
+    fn type_uuid() -> Uuid {
+        uuid!("6a0a2b7e-3c1f-4a59-9a4e-2d8f1b7c5e13")
+    }
+}
+
+impl InteractionMode for ScatterInteractionMode {
+    fn on_left_mouse_button_down(
+        &mut self,
+        _editor_selection: &Selection,
+        controller: &mut dyn SceneController,
+        engine: &mut Engine,
+        mouse_pos: Vector2<f32>,
+        frame_size: Vector2<f32>,
+        _settings: &Settings,
+    ) {
+        let Some(game_scene) = controller.downcast_mut::<GameScene>() else {
+            return;
+        };
+
+        // Shift inverts the behavior of the brush.
+        let shift = engine
+            .user_interfaces
+            .first_mut()
+            .keyboard_modifiers()
+            .shift;
+
+        self.stroke = Some(ScatterStroke {
+            erase: self.brush.erase != shift,
+            ..Default::default()
+        });
+
+        let scene = &mut engine.scenes[game_scene.scene];
+        if let Some(hit) = self.probe_cursor(game_scene, &scene.graph, mouse_pos, frame_size) {
+            self.stamp(game_scene, scene, hit.position);
+        }
+    }
+
+    fn on_left_mouse_button_up(
+        &mut self,
+        _editor_selection: &Selection,
+        controller: &mut dyn SceneController,
+        engine: &mut Engine,
+        _mouse_pos: Vector2<f32>,
+        _frame_size: Vector2<f32>,
+        _settings: &Settings,
+    ) {
+        let Some(game_scene) = controller.downcast_mut::<GameScene>() else {
+            return;
+        };
+
+        self.finish_stroke(game_scene, engine);
+    }
+
+    fn on_mouse_move(
+        &mut self,
+        _mouse_offset: Vector2<f32>,
+        mouse_position: Vector2<f32>,
+        _editor_selection: &Selection,
+        controller: &mut dyn SceneController,
+        engine: &mut Engine,
+        frame_size: Vector2<f32>,
+        _settings: &Settings,
+    ) {
+        let Some(game_scene) = controller.downcast_mut::<GameScene>() else {
+            return;
+        };
+
+        let scene = &mut engine.scenes[game_scene.scene];
+        let hit = self.probe_cursor(game_scene, &scene.graph, mouse_position, frame_size);
+
+        if let Some(hit) = hit.as_ref() {
+            self.stamp(game_scene, scene, hit.position);
+        }
+
+        self.update_gizmo(&mut scene.graph, hit.as_ref());
+    }
+
+    fn activate(&mut self, controller: &dyn SceneController, engine: &mut Engine) {
+        let Some(game_scene) = controller.downcast_ref::<GameScene>() else {
+            return;
+        };
+
+        self.brush_gizmo
+            .set_visible(&mut engine.scenes[game_scene.scene].graph, false);
+
+        self.brush_panel
+            .sync_to_model(engine.user_interfaces.first_mut(), &self.brush);
+
+        engine
+            .user_interfaces
+            .first_mut()
+            .send_message(WindowMessage::open_and_align(
+                self.brush_panel.window,
+                MessageDirection::ToWidget,
+                self.scene_viewer_frame,
+                HorizontalAlignment::Right,
+                VerticalAlignment::Top,
+                Thickness::top_right(5.0),
+                false,
+                false,
+            ));
+    }
+
+    fn deactivate(&mut self, controller: &dyn SceneController, engine: &mut Engine) {
+        let Some(game_scene) = controller.downcast_ref::<GameScene>() else {
+            return;
+        };
+
+        self.finish_stroke(game_scene, engine);
+
+        self.brush_gizmo
+            .set_visible(&mut engine.scenes[game_scene.scene].graph, false);
+
+        engine
+            .user_interfaces
+            .first_mut()
+            .send_message(WindowMessage::close(
+                self.brush_panel.window,
+                MessageDirection::ToWidget,
+            ));
+    }
+
+    fn handle_ui_message(
+        &mut self,
+        message: &UiMessage,
+        _editor_selection: &Selection,
+        _controller: &mut dyn SceneController,
+        _engine: &mut Engine,
+    ) {
+        self.brush_panel.handle_ui_message(message, &mut self.brush);
+    }
+
+    fn on_drop(&mut self, engine: &mut Engine) {
+        engine
+            .user_interfaces
+            .first_mut()
+            .send_message(WidgetMessage::remove(
+                self.brush_panel.window,
+                MessageDirection::ToWidget,
+            ));
+    }
+
+    fn on_hot_key_pressed(
+        &mut self,
+        hotkey: &HotKey,
+        _controller: &mut dyn SceneController,
+        engine: &mut Engine,
+        settings: &Settings,
+    ) -> bool {
+        // The brush shares the size key bindings with the terrain brush.
+        let key_bindings = &settings.key_bindings.terrain_key_bindings;
+        if hotkey == &key_bindings.increase_brush_size {
+            self.brush.radius += 0.1;
+        } else if hotkey == &key_bindings.decrease_brush_size {
+            self.brush.radius = (self.brush.radius - 0.1).max(0.0);
+        } else {
+            return false;
+        }
+
+        self.brush_panel
+            .sync_to_model(engine.user_interfaces.first_mut(), &self.brush);
+
+        true
+    }
+
+    fn make_button(&mut self, ctx: &mut BuildContext, selected: bool) -> Handle<UiNode> {
+        let scatter_mode_tooltip = "Scatter\n\nScatter mode allows you to paint instances of \
+        prefabs onto terrains and meshes. Hold Shift to erase the instances.";
+
+        make_interaction_mode_button(
+            ctx,
+            include_bytes!("../../resources/brush.png"),
+            scatter_mode_tooltip,
+            selected,
+        )
+    }
+
+    fn uuid(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
+
+struct ScatterBrushPanel {
+    window: Handle<UiNode>,
+    inspector: Handle<UiNode>,
+}
+
+impl ScatterBrushPanel {
+    fn new(ctx: &mut BuildContext, brush: &ScatterBrush, sender: MessageSender) -> Self {
+        let context = InspectorContext::from_object(
+            brush,
+            ctx,
+            Arc::new(make_property_editors_container(sender)),
+            None,
+            MSG_SYNC_FLAG,
+            0,
+            true,
+            Default::default(),
+            150.0,
+        );
+
+        let inspector;
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(320.0).with_height(420.0))
+            .can_minimize(false)
+            .can_maximize(false)
+            .with_content({
+                inspector = InspectorBuilder::new(WidgetBuilder::new())
+                    .with_context(context)
+                    .build(ctx);
+                inspector
+            })
+            .open(false)
+            .with_title(WindowTitle::text("Scatter Brush"))
+            .build(ctx);
+
+        Self { window, inspector }
+    }
+
+    fn sync_to_model(&self, ui: &mut UserInterface, brush: &ScatterBrush) {
+        let ctx = ui
+            .node(self.inspector)
+            .cast::<Inspector>()
+            .expect("Must be Inspector!")
+            .context()
+            .clone();
+
+        if let Err(e) = ctx.sync(brush, ui, 0, true, Default::default()) {
+            Log::err(format!(
+                "Failed to sync ScatterBrushPanel's inspector. Reason: {e:?}"
+            ))
+        }
+    }
+
+    fn handle_ui_message(&self, message: &UiMessage, brush: &mut ScatterBrush) {
+        if message.destination() == self.inspector
+            && message.direction() == MessageDirection::FromWidget
+        {
+            if let Some(InspectorMessage::PropertyChanged(msg)) = message.data::<InspectorMessage>()
+            {
+                PropertyAction::from_field_kind(&msg.value).apply(
+                    &msg.path(),
+                    brush,
+                    &mut |result| {
+                        Log::verify(result);
+                    },
+                );
+            }
+        }
+    }
+}
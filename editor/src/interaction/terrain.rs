@@ -228,7 +228,7 @@ impl TerrainInteractionMode {
 }
 
 pub struct BrushGizmo {
-    brush: Handle<Node>,
+    pub(crate) brush: Handle<Node>,
 }
 
 impl BrushGizmo {
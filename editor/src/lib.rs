@@ -44,6 +44,7 @@ pub mod mesh;
 pub mod message;
 pub mod overlay;
 pub mod particle;
+pub mod play;
 pub mod plugin;
 pub mod plugins;
 pub mod preview;
@@ -65,7 +66,12 @@ use crate::{
     configurator::Configurator,
     export::ExportWindow,
     fyrox::{
-        asset::{io::FsResourceIo, manager::ResourceManager, untyped::ResourceKind},
+        asset::{
+            io::FsResourceIo,
+            manager::ResourceManager,
+            normalize::{CaseCheck, NormalizedResourceIo},
+            untyped::ResourceKind,
+        },
         core::{
             algebra::{Matrix3, Vector2},
             color::Color,
@@ -137,12 +143,15 @@ use crate::{
     message::MessageSender,
     overlay::OverlayRenderPass,
     particle::ParticleSystemPreviewControlPanel,
+    play::PlayInEditor,
     plugin::{EditorPlugin, EditorPluginsContainer},
     plugins::{
         absm::AbsmEditor, absm::AbsmEditorPlugin, animation::AnimationEditorPlugin,
-        collider::ColliderPlugin, curve_editor::CurveEditorPlugin, material::MaterialPlugin,
-        path_fixer::PathFixerPlugin, ragdoll::RagdollPlugin, settings::SettingsPlugin,
-        stats::UiStatisticsPlugin, tilemap::TileMapEditorPlugin,
+        batch_rename::BatchRenamePlugin, collider::ColliderPlugin, curve_editor::CurveEditorPlugin,
+        duplicates::DuplicateAssetsPlugin, material::MaterialPlugin, path_fixer::PathFixerPlugin,
+        ragdoll::RagdollPlugin, script_profiler::ScriptProfilerPlugin, settings::SettingsPlugin,
+        shader_graph::ShaderGraphPlugin, stats::UiStatisticsPlugin, tilemap::TileMapEditorPlugin,
+        validation::SceneValidationPlugin,
     },
     scene::{
         commands::{
@@ -322,12 +331,23 @@ pub enum Mode {
         process: std::process::Child,
         active: Arc<AtomicBool>,
     },
+    PlayInEditor(PlayInEditor),
 }
 
 impl Mode {
     pub fn is_edit(&self) -> bool {
         matches!(self, Mode::Edit { .. })
     }
+
+    /// Returns `true` if the scene could be inspected (and edited) in the current mode. It is
+    /// possible when the editor is in the edit mode or when the play in the editor is paused.
+    pub fn is_inspectable(&self) -> bool {
+        match self {
+            Mode::Edit => true,
+            Mode::PlayInEditor(play) => play.paused,
+            _ => false,
+        }
+    }
 }
 
 pub struct GameLoopData {
@@ -700,6 +720,17 @@ impl Editor {
         })
         .unwrap();
 
+        // Paths that differ from the actual ones only by case are loaded fine on Windows, but
+        // break the game on other platforms, so the editor warns about them.
+        let resource_io = engine.resource_manager.resource_io();
+        engine
+            .resource_manager
+            .state()
+            .set_resource_io(Arc::new(NormalizedResourceIo::new(
+                resource_io,
+                CaseCheck::Warn,
+            )));
+
         let (message_sender, message_receiver) = mpsc::channel();
         let message_sender = MessageSender(message_sender);
 
@@ -967,8 +998,13 @@ impl Editor {
                 .with(AnimationEditorPlugin::default())
                 .with(AbsmEditorPlugin::default())
                 .with(UiStatisticsPlugin::default())
+                .with(ScriptProfilerPlugin::default())
                 .with(CurveEditorPlugin::default())
+                .with(ShaderGraphPlugin::default())
                 .with(PathFixerPlugin::default())
+                .with(SceneValidationPlugin::default())
+                .with(BatchRenamePlugin::default())
+                .with(DuplicateAssetsPlugin::default())
                 .with(inspector_plugin),
             // Apparently, some window managers (like Wayland), does not send `Focused` event after the window
             // was created. So we must assume that the editor is focused by default, otherwise editor's thread
@@ -1335,8 +1371,12 @@ impl Editor {
             }
         }
         self.log.handle_ui_message(message, ui);
-        self.asset_browser
-            .handle_ui_message(message, engine, self.message_sender.clone());
+        self.asset_browser.handle_ui_message(
+            message,
+            engine,
+            self.message_sender.clone(),
+            &self.scenes,
+        );
         self.command_stack_viewer.handle_ui_message(message);
         self.scene_viewer.handle_ui_message(
             message,
@@ -1565,6 +1605,10 @@ impl Editor {
             return;
         }
 
+        if matches!(self.mode, Mode::PlayInEditor(_)) {
+            self.set_editor_mode();
+        }
+
         let Some(entry) = self.scenes.current_scene_entry_ref() else {
             Log::err("Cannot enter build mode when there is no scene!");
             return;
@@ -1602,7 +1646,7 @@ impl Editor {
 
         match old_mode {
             Mode::Edit => {}
-            Mode::Build { .. } => {
+            Mode::Build { .. } | Mode::PlayInEditor(_) => {
                 unreachable!();
             }
             Mode::Play { process, active } => {
@@ -1628,10 +1672,77 @@ impl Editor {
                 }
                 self.on_mode_changed();
             }
+            Mode::PlayInEditor(play) => {
+                if let Some(entry) = self.scenes.entry_by_scene_id_mut(play.scene_id) {
+                    play.stop(entry, &mut self.engine);
+                }
+                self.on_mode_changed();
+                self.message_sender.send(Message::SelectionChanged {
+                    old_selection: Default::default(),
+                });
+                self.message_sender.send(Message::ForceSync);
+            }
             _ => {}
         }
     }
 
+    fn set_play_in_editor_mode(&mut self) {
+        if !self.mode.is_edit() {
+            Log::err("Cannot play the scene in the editor when another mode is active!");
+            return;
+        }
+
+        let Some(entry) = self.scenes.current_scene_entry_mut() else {
+            Log::err("Cannot play in the editor when there is no scene!");
+            return;
+        };
+
+        match PlayInEditor::start(entry, &mut self.engine, &self.settings) {
+            Ok(play) => {
+                self.mode = Mode::PlayInEditor(play);
+                self.on_mode_changed();
+                self.message_sender.send(Message::ForceSync);
+            }
+            Err(e) => Log::err(format!("Failed to play the scene in the editor: {e}")),
+        }
+    }
+
+    fn toggle_play_in_editor_pause(&mut self) {
+        if let Mode::PlayInEditor(ref mut play) = self.mode {
+            play.set_paused(!play.paused, &mut self.engine);
+            self.on_mode_changed();
+            // Show the live state of the scene when paused.
+            self.message_sender.send(Message::ForceSync);
+        }
+    }
+
+    fn route_event_to_play_in_editor(
+        &mut self,
+        event: &Event<()>,
+        window_target: &EventLoopWindowTarget<()>,
+    ) {
+        if !matches!(self.mode, Mode::PlayInEditor(ref play) if !play.paused)
+            || !matches!(event, Event::WindowEvent { .. } | Event::DeviceEvent { .. })
+        {
+            return;
+        }
+
+        // Only the input that happens over the scene preview belongs to the game.
+        let ui = self.engine.user_interfaces.first();
+        if self
+            .scene_viewer
+            .frame_bounds(ui)
+            .contains(ui.cursor_position())
+        {
+            self.engine.handle_os_event_in_embedded_play(
+                event,
+                FIXED_TIMESTEP,
+                window_target,
+                &mut self.game_loop_data.lag,
+            );
+        }
+    }
+
     fn on_mode_changed(&mut self) {
         for_each_plugin!(self.plugins => on_mode_changed(self));
 
@@ -1846,6 +1957,7 @@ impl Editor {
             Mode::Edit => false,
             Mode::Build { .. } => true,
             Mode::Play { .. } => false,
+            Mode::PlayInEditor(ref play) => !play.paused,
         };
 
         self.particle_system_control_panel.is_in_preview_mode()
@@ -2249,6 +2361,15 @@ impl Editor {
                     }
                 }
             }
+            Mode::PlayInEditor(ref play) => {
+                if let Some(game_scene) = self
+                    .scenes
+                    .entry_by_scene_id_mut(play.scene_id)
+                    .and_then(|e| e.controller.downcast_mut::<GameScene>())
+                {
+                    play.update(game_scene, &self.engine);
+                }
+            }
             _ => {}
         }
     }
@@ -2350,6 +2471,23 @@ impl Editor {
                 }
                 self.scene_viewer.handle_message(&message, &mut self.engine);
 
+                // The play copy of the scene must never be saved or closed, so the play is
+                // stopped first to put the original scene back.
+                if matches!(self.mode, Mode::PlayInEditor(_))
+                    && matches!(
+                        message,
+                        Message::SaveScene { .. }
+                            | Message::LoadScene(_)
+                            | Message::CloseScene(_)
+                            | Message::NewScene
+                            | Message::NewUiScene
+                            | Message::SetCurrentScene(_)
+                            | Message::Exit { .. }
+                    )
+                {
+                    self.set_editor_mode();
+                }
+
                 match message {
                     Message::DoCommand(command) => {
                         needs_sync |= self.do_current_scene_command(command);
@@ -2428,6 +2566,8 @@ impl Editor {
                         self.set_build_mode(play_after_build)
                     }
                     Message::SwitchToEditMode => self.set_editor_mode(),
+                    Message::SwitchToPlayInEditorMode => self.set_play_in_editor_mode(),
+                    Message::TogglePlayInEditorPause => self.toggle_play_in_editor_pause(),
                     Message::OpenLoadSceneDialog => {
                         self.menu
                             .open_load_file_selector(self.engine.user_interfaces.first_mut());
@@ -2674,6 +2814,13 @@ impl Editor {
             }
             Err(e) => Log::err(format!("Failed to apply graphics settings! Reason: {e:?}")),
         }
+
+        if let Err(e) = graphics_context
+            .renderer
+            .set_present_settings(self.settings.graphics.present)
+        {
+            Log::err(format!("Failed to apply present settings! Reason: {e:?}"));
+        }
     }
 
     fn on_suspended(&mut self) {
@@ -2687,145 +2834,151 @@ impl Editor {
         for_each_plugin!(self.plugins => on_start(&mut self));
 
         event_loop
-            .run(move |event, window_target| match event {
-                Event::AboutToWait => {
-                    if self.is_active() {
-                        update(&mut self, window_target);
-                    }
+            .run(move |event, window_target| {
+                self.route_event_to_play_in_editor(&event, window_target);
 
-                    if self.exit {
-                        window_target.exit();
+                match event {
+                    Event::AboutToWait => {
+                        if self.is_active() {
+                            update(&mut self, window_target);
+                        }
 
-                        // Kill any active child process on exit.
-                        match self.mode {
-                            Mode::Edit => {}
-                            Mode::Build {
-                                ref mut process, ..
-                            } => {
-                                if let Some(process) = process {
+                        if self.exit {
+                            window_target.exit();
+
+                            // Kill any active child process on exit.
+                            match self.mode {
+                                Mode::Edit | Mode::PlayInEditor(_) => {}
+                                Mode::Build {
+                                    ref mut process, ..
+                                } => {
+                                    if let Some(process) = process {
+                                        let _ = process.kill();
+                                    }
+                                }
+                                Mode::Play {
+                                    ref mut process, ..
+                                } => {
                                     let _ = process.kill();
                                 }
                             }
-                            Mode::Play {
-                                ref mut process, ..
-                            } => {
-                                let _ = process.kill();
-                            }
                         }
                     }
-                }
-                Event::Resumed => {
-                    self.on_resumed(window_target);
-                }
-                Event::Suspended => {
-                    self.on_suspended();
-                }
-                Event::WindowEvent { ref event, .. } => {
-                    match event {
-                        WindowEvent::CloseRequested => {
-                            self.message_sender.send(Message::Exit { force: false });
-                        }
-                        WindowEvent::Resized(size) => {
-                            if let Err(e) = self.engine.set_frame_size((*size).into()) {
-                                fyrox::core::log::Log::writeln(
-                                    MessageKind::Error,
-                                    format!("Failed to set renderer size! Reason: {e:?}"),
-                                );
+                    Event::Resumed => {
+                        self.on_resumed(window_target);
+                    }
+                    Event::Suspended => {
+                        self.on_suspended();
+                    }
+                    Event::WindowEvent { ref event, .. } => {
+                        match event {
+                            WindowEvent::CloseRequested => {
+                                self.message_sender.send(Message::Exit { force: false });
                             }
+                            WindowEvent::Resized(size) => {
+                                if let Err(e) = self.engine.set_frame_size((*size).into()) {
+                                    fyrox::core::log::Log::writeln(
+                                        MessageKind::Error,
+                                        format!("Failed to set renderer size! Reason: {e:?}"),
+                                    );
+                                }
 
-                            let window = &self.engine.graphics_context.as_initialized_ref().window;
-
-                            let logical_size = size.to_logical(window.scale_factor());
-                            self.engine.user_interfaces.first_mut().send_message(
-                                WidgetMessage::width(
-                                    self.root_grid,
-                                    MessageDirection::ToWidget,
-                                    logical_size.width,
-                                ),
-                            );
-                            self.engine.user_interfaces.first_mut().send_message(
-                                WidgetMessage::height(
-                                    self.root_grid,
-                                    MessageDirection::ToWidget,
-                                    logical_size.height,
-                                ),
-                            );
-
-                            if size.width > 0 && size.height > 0 {
-                                self.settings.windows.window_size.x = size.width as f32;
-                                self.settings.windows.window_size.y = size.height as f32;
-                            }
+                                let window =
+                                    &self.engine.graphics_context.as_initialized_ref().window;
 
-                            self.settings.windows.window_maximized = window.is_maximized();
-                        }
-                        WindowEvent::Focused(focused) => {
-                            self.focused = *focused;
-                        }
-                        WindowEvent::Moved(new_position) => {
-                            // Allow the window to go outside the screen bounds by a little. This
-                            // happens when the window is maximized.
-                            if new_position.x > -50 && new_position.y > -50 {
-                                self.settings.windows.window_position.x = new_position.x as f32;
-                                self.settings.windows.window_position.y = new_position.y as f32;
+                                let logical_size = size.to_logical(window.scale_factor());
+                                self.engine.user_interfaces.first_mut().send_message(
+                                    WidgetMessage::width(
+                                        self.root_grid,
+                                        MessageDirection::ToWidget,
+                                        logical_size.width,
+                                    ),
+                                );
+                                self.engine.user_interfaces.first_mut().send_message(
+                                    WidgetMessage::height(
+                                        self.root_grid,
+                                        MessageDirection::ToWidget,
+                                        logical_size.height,
+                                    ),
+                                );
+
+                                if size.width > 0 && size.height > 0 {
+                                    self.settings.windows.window_size.x = size.width as f32;
+                                    self.settings.windows.window_size.y = size.height as f32;
+                                }
+
+                                self.settings.windows.window_maximized = window.is_maximized();
                             }
-                        }
-                        WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
-                            set_ui_scaling(
-                                self.engine.user_interfaces.first(),
-                                *scale_factor as f32,
-                            );
-                        }
-                        WindowEvent::RedrawRequested => {
-                            if self.is_active() {
-                                if let Some(entry) = self.scenes.current_scene_entry_mut() {
-                                    entry
-                                        .controller
-                                        .on_before_render(&entry.selection, &mut self.engine);
+                            WindowEvent::Focused(focused) => {
+                                self.focused = *focused;
+                            }
+                            WindowEvent::Moved(new_position) => {
+                                // Allow the window to go outside the screen bounds by a little. This
+                                // happens when the window is maximized.
+                                if new_position.x > -50 && new_position.y > -50 {
+                                    self.settings.windows.window_position.x = new_position.x as f32;
+                                    self.settings.windows.window_position.y = new_position.y as f32;
                                 }
+                            }
+                            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                                set_ui_scaling(
+                                    self.engine.user_interfaces.first(),
+                                    *scale_factor as f32,
+                                );
+                            }
+                            WindowEvent::RedrawRequested => {
+                                if self.is_active() {
+                                    if let Some(entry) = self.scenes.current_scene_entry_mut() {
+                                        entry
+                                            .controller
+                                            .on_before_render(&entry.selection, &mut self.engine);
+                                    }
 
-                                self.engine.render().unwrap();
+                                    self.engine.render().unwrap();
 
-                                if let Some(scene) = self.scenes.current_scene_controller_mut() {
-                                    scene.on_after_render(&mut self.engine);
+                                    if let Some(scene) = self.scenes.current_scene_controller_mut()
+                                    {
+                                        scene.on_after_render(&mut self.engine);
+                                    }
                                 }
                             }
+                            _ => (),
                         }
-                        _ => (),
-                    }
 
-                    // Any action in the window, other than a redraw request forces the editor to
-                    // do another update pass which then pushes a redraw request to the event
-                    // queue. This check prevents infinite loop of this kind.
-                    if !matches!(event, WindowEvent::RedrawRequested) {
-                        self.update_loop_state.request_update_in_current_frame();
-                    }
+                        // Any action in the window, other than a redraw request forces the editor to
+                        // do another update pass which then pushes a redraw request to the event
+                        // queue. This check prevents infinite loop of this kind.
+                        if !matches!(event, WindowEvent::RedrawRequested) {
+                            self.update_loop_state.request_update_in_current_frame();
+                        }
 
-                    if let Some(os_event) = translate_event(event) {
-                        self.engine
-                            .user_interfaces
-                            .first_mut()
-                            .process_os_event(&os_event);
-                    }
-                }
-                Event::LoopExiting => {
-                    let ids = self.scenes.entries.iter().map(|e| e.id).collect::<Vec<_>>();
-                    for id in ids {
-                        self.close_scene(id);
+                        if let Some(os_event) = translate_event(event) {
+                            self.engine
+                                .user_interfaces
+                                .first_mut()
+                                .process_os_event(&os_event);
+                        }
                     }
+                    Event::LoopExiting => {
+                        let ids = self.scenes.entries.iter().map(|e| e.id).collect::<Vec<_>>();
+                        for id in ids {
+                            self.close_scene(id);
+                        }
 
-                    self.settings.force_save();
+                        self.settings.force_save();
 
-                    for_each_plugin!(self.plugins => on_exit(&mut self));
-                }
-                _ => {
-                    if self.is_active() {
-                        if self.is_suspended {
-                            for_each_plugin!(self.plugins => on_resumed(&mut self));
-                            self.is_suspended = false;
+                        for_each_plugin!(self.plugins => on_exit(&mut self));
+                    }
+                    _ => {
+                        if self.is_active() {
+                            if self.is_suspended {
+                                for_each_plugin!(self.plugins => on_resumed(&mut self));
+                                self.is_suspended = false;
+                            }
+                        } else if !self.is_suspended {
+                            for_each_plugin!(self.plugins => on_suspended(&mut self));
+                            self.is_suspended = true;
                         }
-                    } else if !self.is_suspended {
-                        for_each_plugin!(self.plugins => on_suspended(&mut self));
-                        self.is_suspended = true;
                     }
                 }
             })
@@ -2862,10 +3015,12 @@ fn update(editor: &mut Editor, window_target: &EventLoopWindowTarget<()>) {
                     .current_scene_controller_ref()
                     .and_then(|e| e.downcast_ref::<GameScene>())
                 {
-                    switches.insert(
-                        current_game_scene.scene,
-                        current_game_scene.graph_switches.clone(),
-                    );
+                    let current_switches = match editor.mode {
+                        // The game scene is updated as usual while it is played in the editor.
+                        Mode::PlayInEditor(ref play) => play.graph_switches(),
+                        _ => current_game_scene.graph_switches.clone(),
+                    };
+                    switches.insert(current_game_scene.scene, current_switches);
 
                     if current_game_scene.scene == other_game_scene.scene {
                         continue;
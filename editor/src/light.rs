@@ -19,6 +19,7 @@
 // SOFTWARE.
 
 use crate::fyrox::{
+    asset::manager::ResourceManager,
     core::{log::Log, pool::Handle, reflect::prelude::*},
     gui::{
         button::{ButtonBuilder, ButtonMessage},
@@ -34,8 +35,10 @@ use crate::fyrox::{
         window::{WindowBuilder, WindowMessage, WindowTitle},
         BuildContext, HorizontalAlignment, Thickness, UiNode, UserInterface, VerticalAlignment,
     },
+    renderer::lightmap_baker::GpuLightmapBaker,
     utils::lightmap::{
-        CancellationToken, Lightmap, LightmapGenerationError, LightmapInputData, ProgressIndicator,
+        CancellationToken, Lightmap, LightmapBakeSettings, LightmapBaker, LightmapDenoiseSettings,
+        LightmapGenerationError, LightmapInputData, ProgressIndicator,
     },
 };
 use crate::plugins::inspector::editors::make_property_editors_container;
@@ -72,6 +75,15 @@ struct LightmapperSettings {
     the lightmapper automatically generates names for the files."
     )]
     path: PathBuf,
+    #[reflect(
+        description = "Use GPU to calculate lighting. GPU baker is much faster than CPU one on large scenes, \
+    but the editor will be unresponsive while it works."
+    )]
+    use_gpu: bool,
+    #[reflect(
+        description = "Remove noise from the generated light maps using edge-preserving filter."
+    )]
+    denoise: bool,
 }
 
 impl Default for LightmapperSettings {
@@ -80,7 +92,70 @@ impl Default for LightmapperSettings {
             texels_per_unit: 64,
             spacing: 0.005,
             path: Default::default(),
+            use_gpu: false,
+            denoise: false,
+        }
+    }
+}
+
+impl LightmapperSettings {
+    fn bake_settings(&self) -> LightmapBakeSettings {
+        LightmapBakeSettings {
+            texels_per_unit: self.texels_per_unit,
+            uv_spacing: self.spacing,
+            denoise: self.denoise.then(LightmapDenoiseSettings::default),
+        }
+    }
+}
+
+/// Bakes a new lightmap or re-bakes changed objects of the `previous` one and saves the textures
+/// of the baked objects.
+fn bake_lightmap(
+    input_data: LightmapInputData,
+    settings: &LightmapBakeSettings,
+    baker: &mut LightmapBaker,
+    previous: Option<Lightmap>,
+    path: PathBuf,
+    resource_manager: ResourceManager,
+    cancellation_token: CancellationToken,
+    progress_indicator: ProgressIndicator,
+) -> Result<Lightmap, LightmapGenerationError> {
+    let (lightmap, baked) = match previous {
+        Some(mut lightmap) => {
+            let changed = lightmap.rebake(
+                input_data,
+                settings,
+                baker,
+                cancellation_token,
+                progress_indicator,
+            )?;
+            let baked = Lightmap {
+                map: lightmap
+                    .map
+                    .iter()
+                    .filter(|(handle, _)| changed.contains(handle))
+                    .map(|(handle, entries)| (*handle, entries.clone()))
+                    .collect(),
+                ..Default::default()
+            };
+            (lightmap, baked)
         }
+        None => {
+            let lightmap = Lightmap::bake(
+                input_data,
+                settings,
+                baker,
+                cancellation_token,
+                progress_indicator,
+            )?;
+            (lightmap.clone(), lightmap)
+        }
+    };
+
+    if baked.save_textures(path, resource_manager).is_err() {
+        Err(LightmapGenerationError::Cancelled)
+    } else {
+        Ok(lightmap)
     }
 }
 
@@ -206,6 +281,7 @@ pub struct LightPanel {
     pub window: Handle<UiNode>,
     inspector: Handle<UiNode>,
     generate: Handle<UiNode>,
+    rebake: Handle<UiNode>,
     settings: LightmapperSettings,
     progress_window: Option<ProgressWindow>,
     sender: Sender<Result<Lightmap, LightmapGenerationError>>,
@@ -218,6 +294,7 @@ impl LightPanel {
         let container = Arc::new(make_property_editors_container(sender));
 
         let generate;
+        let rebake;
         let inspector;
         let ctx = &mut engine.user_interfaces.first_mut().build_ctx();
         let window = WindowBuilder::new(
@@ -267,11 +344,23 @@ impl LightPanel {
                         .with_text("Generate Lightmap")
                         .build(ctx);
                         generate
+                    })
+                    .with_child({
+                        rebake = ButtonBuilder::new(
+                            WidgetBuilder::new()
+                                .on_row(2)
+                                .on_column(0)
+                                .with_margin(Thickness::uniform(1.0)),
+                        )
+                        .with_text("Re-bake Changed")
+                        .build(ctx);
+                        rebake
                     }),
             )
             .add_column(Column::stretch())
             .add_row(Row::stretch())
             .add_row(Row::strict(25.0))
+            .add_row(Row::strict(25.0))
             .build(ctx),
         )
         .build(ctx);
@@ -282,6 +371,7 @@ impl LightPanel {
             window,
             inspector,
             generate,
+            rebake,
             settings,
             progress_window: None,
             sender,
@@ -296,9 +386,15 @@ impl LightPanel {
         engine: &mut Engine,
     ) {
         if let Some(ButtonMessage::Click) = message.data::<ButtonMessage>() {
-            if message.destination() == self.generate {
+            if message.destination() == self.generate || message.destination() == self.rebake {
                 let scene = &mut engine.scenes[game_scene.scene];
 
+                let previous = if message.destination() == self.rebake {
+                    scene.graph.lightmap().cloned()
+                } else {
+                    None
+                };
+
                 let progress_indicator = ProgressIndicator::new();
                 let cancellation_token = CancellationToken::new();
 
@@ -317,34 +413,48 @@ impl LightPanel {
                     progress_indicator.clone(),
                 ) {
                     let sender = self.sender.clone();
-                    let texels_per_unit = self.settings.texels_per_unit;
-                    let spacing = self.settings.spacing;
+                    let settings = self.settings.bake_settings();
                     let path = self.settings.path.clone();
                     let resource_manager = engine.resource_manager.clone();
 
-                    if let Err(e) = std::thread::Builder::new()
+                    if self.settings.use_gpu {
+                        // GPU baker must run on the thread that owns the graphics context.
+                        let server = engine
+                            .graphics_context
+                            .as_initialized_ref()
+                            .renderer
+                            .server
+                            .clone();
+                        let result = GpuLightmapBaker::new(server, Default::default())
+                            .map_err(LightmapGenerationError::from)
+                            .and_then(|baker| {
+                                bake_lightmap(
+                                    input_data,
+                                    &settings,
+                                    &mut LightmapBaker::Gpu(baker),
+                                    previous,
+                                    path,
+                                    resource_manager,
+                                    cancellation_token,
+                                    progress_indicator,
+                                )
+                            });
+                        sender.send(result).unwrap();
+                    } else if let Err(e) = std::thread::Builder::new()
                         .name("LightmapGenerationThread".to_string())
                         .spawn(move || {
-                            match Lightmap::new(
-                                input_data,
-                                texels_per_unit,
-                                spacing,
-                                cancellation_token,
-                                progress_indicator,
-                            ) {
-                                Ok(lightmap) => {
-                                    if lightmap.save_textures(path, resource_manager).is_err() {
-                                        sender
-                                            .send(Err(LightmapGenerationError::Cancelled))
-                                            .unwrap();
-                                    } else {
-                                        sender.send(Ok(lightmap)).unwrap();
-                                    }
-                                }
-                                Err(err) => {
-                                    sender.send(Err(err)).unwrap();
-                                }
-                            }
+                            sender
+                                .send(bake_lightmap(
+                                    input_data,
+                                    &settings,
+                                    &mut LightmapBaker::Cpu,
+                                    previous,
+                                    path,
+                                    resource_manager,
+                                    cancellation_token,
+                                    progress_indicator,
+                                ))
+                                .unwrap();
                         })
                     {
                         Log::err(format!(
@@ -65,6 +65,7 @@ pub enum Message {
     OpenAnimationEditor,
     OpenAbsmEditor,
     OpenMaterialEditor(MaterialResource),
+    OpenShaderGraphEditor(PathBuf),
     OpenTileSetEditor(TileSetResource),
     OpenTileMapBrushEditor(TileMapBrushResource),
     OpenNodeRemovalDialog,
@@ -82,6 +83,8 @@ pub enum Message {
         play_after_build: bool,
     },
     SwitchToEditMode,
+    SwitchToPlayInEditorMode,
+    TogglePlayInEditorPause,
     SwitchMode,
     OpenLoadSceneDialog,
     OpenSaveSceneDialog {
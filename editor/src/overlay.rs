@@ -94,12 +94,13 @@ impl SceneRenderPass for OverlayRenderPass {
         let inv_view = ctx.camera.inv_view_matrix().unwrap();
         let camera_up = -inv_view.up();
         let camera_side = inv_view.side();
-        let sound_icon = ctx
-            .texture_cache
-            .get(ctx.server, &self.sound_icon)
-            .cloned()
-            .unwrap();
-        let light_icon = ctx.texture_cache.get(ctx.server, &self.light_icon).unwrap();
+        // Icons could still be in the upload queue.
+        let Some(sound_icon) = ctx.texture_cache.get(ctx.server, &self.sound_icon).cloned() else {
+            return Ok(stats);
+        };
+        let Some(light_icon) = ctx.texture_cache.get(ctx.server, &self.light_icon) else {
+            return Ok(stats);
+        };
 
         for node in ctx.scene.graph.linear_iter() {
             let icon =
@@ -0,0 +1,141 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Play-in-editor mode runs plugins and scripts of the current scene inside the editor process.
+//! The scene is replaced with its copy for the time of the play, and the original scene (along
+//! with the selection and the command stack) is put back when the play is stopped.
+
+use crate::{
+    command::CommandStack,
+    fyrox::{
+        core::{log::Log, pool::Handle, uuid::Uuid},
+        engine::Engine,
+        graph::{BaseSceneGraph, SceneGraph},
+        scene::{camera::Camera, graph::GraphUpdateSwitches, node::Node, Scene},
+    },
+    scene::{container::EditorSceneEntry, GameScene, Selection},
+    settings::Settings,
+};
+
+pub struct PlayInEditor {
+    /// Id of the editor scene entry that is being played.
+    pub scene_id: Uuid,
+    /// Paused play does not update the scene, plugins and scripts, which allows to inspect the
+    /// live state of the scene nodes.
+    pub paused: bool,
+    original_scene: Scene,
+    command_stack: CommandStack,
+    selection: Selection,
+    has_unsaved_changes: bool,
+    preview_camera: Handle<Node>,
+}
+
+fn find_play_camera(game_scene: &GameScene, scene: &Scene) -> Handle<Node> {
+    scene
+        .graph
+        .traverse_handle_iter(game_scene.scene_content_root)
+        .find(|h| {
+            scene.graph[*h]
+                .cast::<Camera>()
+                .is_some_and(|c| c.is_globally_enabled())
+        })
+        .unwrap_or_default()
+}
+
+impl PlayInEditor {
+    pub fn start(
+        entry: &mut EditorSceneEntry,
+        engine: &mut Engine,
+        settings: &Settings,
+    ) -> Result<Self, String> {
+        let Some(game_scene) = entry.controller.downcast_mut::<GameScene>() else {
+            return Err("Only game scenes can be played in the editor!".to_string());
+        };
+
+        // One-to-one copy keeps the handles of the nodes, so the selection and every other
+        // handle stored in the editor remains valid for the play copy.
+        let (mut play_scene, _) = engine.scenes[game_scene.scene].clone_one_to_one();
+        play_scene.graph.physics.integration_parameters.dt = None;
+        play_scene.graph.physics2d.integration_parameters.dt = None;
+
+        let original_scene = engine.scenes.replace(game_scene.scene, play_scene);
+
+        // The window target is not passed to the plugins, so the game won't be able to close
+        // the editor.
+        if let Err(err) = engine.start_embedded_play(game_scene.scene, entry.path.as_deref(), None)
+        {
+            engine.scenes.replace(game_scene.scene, original_scene);
+            return Err(err);
+        }
+
+        // The editor renders the scene using a single camera, the first enabled camera of the
+        // scene is used for that.
+        let preview_camera = std::mem::replace(
+            &mut game_scene.preview_camera,
+            find_play_camera(game_scene, &engine.scenes[game_scene.scene]),
+        );
+
+        Ok(Self {
+            scene_id: entry.id,
+            paused: false,
+            original_scene,
+            command_stack: std::mem::replace(
+                &mut entry.command_stack,
+                CommandStack::new(false, settings.general.max_history_entries),
+            ),
+            selection: entry.selection.clone(),
+            has_unsaved_changes: entry.has_unsaved_changes,
+            preview_camera,
+        })
+    }
+
+    pub fn stop(self, entry: &mut EditorSceneEntry, engine: &mut Engine) {
+        Log::verify(engine.stop_embedded_play(None));
+
+        if let Some(game_scene) = entry.controller.downcast_mut::<GameScene>() {
+            engine.scenes.replace(game_scene.scene, self.original_scene);
+            game_scene.preview_camera = self.preview_camera;
+        }
+
+        // Everything that was done while the play was paused is discarded as well.
+        entry.command_stack = self.command_stack;
+        entry.selection = self.selection;
+        entry.has_unsaved_changes = self.has_unsaved_changes;
+    }
+
+    pub fn set_paused(&mut self, paused: bool, engine: &mut Engine) {
+        self.paused = paused;
+        engine.set_embedded_play_paused(paused);
+    }
+
+    pub fn graph_switches(&self) -> GraphUpdateSwitches {
+        GraphUpdateSwitches {
+            paused: self.paused,
+            ..Default::default()
+        }
+    }
+
+    pub fn update(&self, game_scene: &mut GameScene, engine: &Engine) {
+        let scene = &engine.scenes[game_scene.scene];
+        // The game could destroy its camera or create a new one at any time.
+        if !scene.graph.is_valid_handle(game_scene.preview_camera) {
+            game_scene.preview_camera = find_play_camera(game_scene, scene);
+        }
+    }
+}
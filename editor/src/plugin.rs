@@ -56,7 +56,8 @@ pub trait EditorPlugin: Downcast {
     /// This method is called when the editor switches to another mode. For example, if a user clicks the "Play" button,
     /// the mode will be changed from [`crate::Mode::Edit`] to [`crate::Mode::Build`], and if the build was successful,
     /// it will then be changed to [`crate::Mode::Play`]. When the game was closed, the mode will be changed back to
-    /// [`crate::Mode::Edit`].
+    /// [`crate::Mode::Edit`]. The "Play In Editor" button switches the mode to [`crate::Mode::PlayInEditor`], the
+    /// method is also called when such play is paused or resumed.
     fn on_mode_changed(&mut self, #[allow(unused_variables)] editor: &mut Editor) {}
 
     /// This method is called when active scene was changed. It could happen if a user opens or loads
@@ -30,8 +30,11 @@ use crate::fyrox::{
         visitor::prelude::*,
     },
     generic_animation::machine::{
-        node::blendspace::BlendSpacePoint, node::PoseNode, parameter::Parameter,
-        parameter::ParameterContainer, Machine, MachineLayer,
+        node::blendspace::{BlendSpaceKind, BlendSpacePoint},
+        node::PoseNode,
+        parameter::Parameter,
+        parameter::ParameterContainer,
+        Machine, MachineLayer,
     },
     graph::{BaseSceneGraph, PrefabData, SceneGraph, SceneGraphNode},
     gui::{
@@ -74,6 +77,7 @@ pub enum BlendSpaceFieldMessage {
     MaxValues(Vector2<f32>),
     SnapStep(Vector2<f32>),
     SamplingPoint(Vector2<f32>),
+    OneDimensional(bool),
     MovePoint {
         index: usize,
         position: Vector2<f32>,
@@ -89,6 +93,7 @@ impl BlendSpaceFieldMessage {
     define_constructor!(BlendSpaceFieldMessage:MaxValues => fn max_values(Vector2<f32>), layout: false);
     define_constructor!(BlendSpaceFieldMessage:SnapStep => fn snap_step(Vector2<f32>), layout: false);
     define_constructor!(BlendSpaceFieldMessage:SamplingPoint => fn sampling_point(Vector2<f32>), layout: false);
+    define_constructor!(BlendSpaceFieldMessage:OneDimensional => fn one_dimensional(bool), layout: true);
     define_constructor!(BlendSpaceFieldMessage:MovePoint  => fn move_point(index: usize, position: Vector2<f32>), layout: false);
     define_constructor!(BlendSpaceFieldMessage:AddPoint  => fn add_point(Vector2<f32>), layout: false);
     define_constructor!(BlendSpaceFieldMessage:RemovePoint  => fn remove_point(usize), layout: false);
@@ -122,6 +127,7 @@ struct BlendSpaceField {
     triangles: Vec<TriangleDefinition>,
     grid_brush: Brush,
     sampling_point: Vector2<f32>,
+    one_dimensional: bool,
     #[visit(skip)]
     #[reflect(hidden)]
     drag_context: Option<DragContext>,
@@ -136,14 +142,20 @@ impl Debug for BlendSpaceField {
 
 define_widget_deref!(BlendSpaceField);
 
+// One dimensional blend spaces are drawn as a horizontal line in the middle of the field.
 fn blend_to_local(
     p: Vector2<f32>,
     min: Vector2<f32>,
     max: Vector2<f32>,
     bounds: Rect<f32>,
+    one_dimensional: bool,
 ) -> Vector2<f32> {
     let kx = (p.x - min.x) / (max.x - min.x);
-    let ky = (p.y - min.y) / (max.y - min.y);
+    let ky = if one_dimensional {
+        0.5
+    } else {
+        (p.y - min.y) / (max.y - min.y)
+    };
     bounds.position + Vector2::new(kx * bounds.w(), bounds.h() - ky * bounds.h())
 }
 
@@ -152,12 +164,17 @@ fn screen_to_blend(
     min: Vector2<f32>,
     max: Vector2<f32>,
     screen_bounds: Rect<f32>,
+    one_dimensional: bool,
 ) -> Vector2<f32> {
     let rel = pos - screen_bounds.position;
     let kx = (rel.x / screen_bounds.w()).clamp(0.0, 1.0);
     let ky = 1.0 - (rel.y / screen_bounds.h()).clamp(0.0, 1.0);
     let bx = min.x + kx * (max.x - min.x);
-    let by = min.y + ky * (max.y - min.y);
+    let by = if one_dimensional {
+        0.0
+    } else {
+        min.y + ky * (max.y - min.y)
+    };
     Vector2::new(bx, by)
 }
 
@@ -204,6 +221,7 @@ impl Control for BlendSpaceField {
                 self.min_values,
                 self.max_values,
                 Rect::new(0.0, 0.0, final_size.x, final_size.y),
+                self.one_dimensional,
             ) - child.desired_size().scale(0.5);
 
             ui.arrange_node(
@@ -242,9 +260,11 @@ impl Control for BlendSpaceField {
             drawing_context.push_line(Vector2::new(x, 0.0), Vector2::new(x, bounds.h()), 1.0);
         }
 
-        for ys in 0..=ny {
-            let y = (ys as f32 / ny as f32) * bounds.h();
-            drawing_context.push_line(Vector2::new(0.0, y), Vector2::new(bounds.w(), y), 1.0);
+        if !self.one_dimensional {
+            for ys in 0..=ny {
+                let y = (ys as f32 / ny as f32) * bounds.h();
+                drawing_context.push_line(Vector2::new(0.0, y), Vector2::new(bounds.w(), y), 1.0);
+            }
         }
 
         drawing_context.commit(
@@ -261,24 +281,37 @@ impl Control for BlendSpaceField {
                 self.min_values,
                 self.max_values,
                 bounds,
+                self.one_dimensional,
             );
             let b = blend_to_local(
                 self.point_positions[triangle[1] as usize],
                 self.min_values,
                 self.max_values,
                 bounds,
+                self.one_dimensional,
             );
             let c = blend_to_local(
                 self.point_positions[triangle[2] as usize],
                 self.min_values,
                 self.max_values,
                 bounds,
+                self.one_dimensional,
             );
 
             for (begin, end) in [(a, b), (b, c), (c, a)] {
                 drawing_context.push_line(begin, end, 2.0);
             }
         }
+
+        // Points of one dimensional blend spaces are blended along a single line.
+        if self.one_dimensional {
+            let y = bounds.y() + bounds.h() * 0.5;
+            drawing_context.push_line(
+                Vector2::new(bounds.x(), y),
+                Vector2::new(bounds.x() + bounds.w(), y),
+                2.0,
+            );
+        }
         drawing_context.commit(
             self.clip_bounds(),
             self.foreground(),
@@ -293,6 +326,7 @@ impl Control for BlendSpaceField {
             self.min_values,
             self.max_values,
             bounds,
+            self.one_dimensional,
         );
         drawing_context.push_line(
             Vector2::new(sampling_point.x - size * 0.5, sampling_point.y),
@@ -352,6 +386,9 @@ impl Control for BlendSpaceField {
                     BlendSpaceFieldMessage::SnapStep(snap_step) => {
                         self.snap_step = *snap_step;
                     }
+                    BlendSpaceFieldMessage::OneDimensional(one_dimensional) => {
+                        self.one_dimensional = *one_dimensional;
+                    }
                     BlendSpaceFieldMessage::SamplingPoint(sampling_point) => {
                         if message.direction == MessageDirection::ToWidget {
                             self.sampling_point = *sampling_point;
@@ -400,6 +437,7 @@ impl Control for BlendSpaceField {
                                         self.min_values,
                                         self.max_values,
                                         self.screen_bounds(),
+                                        self.one_dimensional,
                                     ),
                                 ));
                             }
@@ -415,6 +453,7 @@ impl Control for BlendSpaceField {
                             self.min_values,
                             self.max_values,
                             self.screen_bounds(),
+                            self.one_dimensional,
                         );
                         match drag_context {
                             DragContext::SamplingPoint => {
@@ -461,6 +500,7 @@ impl Control for BlendSpaceField {
                     self.min_values,
                     self.max_values,
                     self.screen_bounds(),
+                    self.one_dimensional,
                 );
                 ui.send_message(BlendSpaceFieldMessage::add_point(
                     self.handle,
@@ -538,6 +578,7 @@ impl BlendSpaceFieldBuilder {
             triangles: Default::default(),
             grid_brush: ctx.style.get_or_default(Style::BRUSH_LIGHT),
             sampling_point: Vector2::new(0.25, 0.5),
+            one_dimensional: false,
             drag_context: None,
             field_context_menu: ContextMenu {
                 menu,
@@ -840,6 +881,26 @@ impl BlendSpaceEditor {
                 sync_text(self.x_axis_name, blend_space.x_axis_name().to_string());
                 sync_text(self.y_axis_name, blend_space.y_axis_name().to_string());
 
+                let one_dimensional = blend_space.kind() == BlendSpaceKind::OneDimensional;
+                for widget in [self.min_y, self.max_y, self.y_axis_name] {
+                    send_sync_message(
+                        ui,
+                        WidgetMessage::visibility(
+                            widget,
+                            MessageDirection::ToWidget,
+                            !one_dimensional,
+                        ),
+                    );
+                }
+                send_sync_message(
+                    ui,
+                    BlendSpaceFieldMessage::one_dimensional(
+                        self.field,
+                        MessageDirection::ToWidget,
+                        one_dimensional,
+                    ),
+                );
+
                 send_sync_message(
                     ui,
                     BlendSpaceFieldMessage::min_values(
@@ -881,15 +942,13 @@ impl BlendSpaceEditor {
                     ),
                 );
 
-                if let Some(Parameter::SamplingPoint(pt)) =
-                    parameters.get(blend_space.sampling_parameter())
-                {
+                if let Some(pt) = blend_space.sampling_point(parameters) {
                     send_sync_message(
                         ui,
                         BlendSpaceFieldMessage::sampling_point(
                             self.field,
                             MessageDirection::ToWidget,
-                            *pt,
+                            pt,
                         ),
                     );
                 }
@@ -922,10 +981,16 @@ impl BlendSpaceEditor {
                                         && message.direction() == MessageDirection::FromWidget
                                     {
                                         let param = blend_space.sampling_parameter().to_string();
-                                        if let Some(Parameter::SamplingPoint(param)) =
-                                            machine.parameters_mut().get_mut(&param)
-                                        {
-                                            *param = point;
+                                        let one_dimensional =
+                                            blend_space.kind() == BlendSpaceKind::OneDimensional;
+                                        match machine.parameters_mut().get_mut(&param) {
+                                            Some(Parameter::SamplingPoint(param)) => {
+                                                *param = point;
+                                            }
+                                            Some(Parameter::Weight(param)) if one_dimensional => {
+                                                *param = point.x;
+                                            }
+                                            _ => (),
                                         }
                                     }
                                 }
@@ -161,7 +161,6 @@ impl SocketBuilder {
         self
     }
 
-    #[allow(dead_code)] // TODO
     pub fn with_editor(mut self, editor: Handle<UiNode>) -> Self {
         self.editor = editor;
         self
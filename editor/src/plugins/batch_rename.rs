@@ -0,0 +1,405 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Batch rename tool. It renames every selected scene node using a regular expression and a
+//! replacement string, which makes it possible to rename dozens of nodes in one go.
+
+use crate::{
+    command::{Command, CommandGroup},
+    fyrox::{
+        core::pool::Handle,
+        graph::BaseSceneGraph,
+        gui::{
+            button::{ButtonBuilder, ButtonMessage},
+            grid::{Column, GridBuilder, Row},
+            menu::MenuItemMessage,
+            message::{MessageDirection, UiMessage},
+            scroll_viewer::ScrollViewerBuilder,
+            stack_panel::StackPanelBuilder,
+            text::{TextBuilder, TextMessage},
+            text_box::TextBoxBuilder,
+            widget::{WidgetBuilder, WidgetMessage},
+            window::{WindowBuilder, WindowMessage, WindowTitle},
+            HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface, VerticalAlignment,
+        },
+        scene::node::Node,
+    },
+    menu::create_menu_item,
+    plugin::EditorPlugin,
+    scene::{commands::graph::SetNodeNameCommand, GameScene},
+    Editor,
+};
+use regex::Regex;
+
+/// Computes new names for the given nodes. Every match of the pattern is replaced with the
+/// replacement string, which could reference capture groups as `$1` or `$name`. `{n}` in the
+/// replacement is substituted with the 1-based index of the node in the list. Nodes, whose names
+/// are not changed, are excluded from the result.
+pub fn batch_rename(
+    nodes: &[(Handle<Node>, String)],
+    pattern: &Regex,
+    replacement: &str,
+) -> Vec<(Handle<Node>, String)> {
+    nodes
+        .iter()
+        .enumerate()
+        .filter_map(|(index, (handle, name))| {
+            let new_name = pattern
+                .replace_all(name, replacement)
+                .replace("{n}", &(index + 1).to_string());
+            (new_name != *name).then_some((*handle, new_name))
+        })
+        .collect()
+}
+
+struct BatchRenameWindow {
+    window: Handle<UiNode>,
+    pattern: Handle<UiNode>,
+    replacement: Handle<UiNode>,
+    preview: Handle<UiNode>,
+    rename: Handle<UiNode>,
+    cancel: Handle<UiNode>,
+    pattern_str: String,
+    replacement_str: String,
+    nodes: Vec<(Handle<Node>, String)>,
+}
+
+impl BatchRenameWindow {
+    fn new(nodes: Vec<(Handle<Node>, String)>, ui: &mut UserInterface) -> Self {
+        let ctx = &mut ui.build_ctx();
+
+        let pattern;
+        let replacement;
+        let preview;
+        let rename;
+        let cancel;
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(400.0).with_height(350.0))
+            .with_title(WindowTitle::text("Batch Rename"))
+            .open(false)
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_margin(Thickness::uniform(2.0))
+                        .with_child(
+                            TextBuilder::new(WidgetBuilder::new().on_row(0).on_column(0))
+                                .with_vertical_text_alignment(VerticalAlignment::Center)
+                                .with_text("Pattern")
+                                .build(ctx),
+                        )
+                        .with_child({
+                            pattern = TextBoxBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(0)
+                                    .on_column(1)
+                                    .with_tab_index(Some(0))
+                                    .with_height(22.0)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .build(ctx);
+                            pattern
+                        })
+                        .with_child(
+                            TextBuilder::new(WidgetBuilder::new().on_row(1).on_column(0))
+                                .with_vertical_text_alignment(VerticalAlignment::Center)
+                                .with_text("Replacement")
+                                .build(ctx),
+                        )
+                        .with_child({
+                            replacement = TextBoxBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(1)
+                                    .on_column(1)
+                                    .with_tab_index(Some(1))
+                                    .with_height(22.0)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .build(ctx);
+                            replacement
+                        })
+                        .with_child(
+                            ScrollViewerBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(2)
+                                    .on_column(0)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .with_content({
+                                preview = TextBuilder::new(WidgetBuilder::new()).build(ctx);
+                                preview
+                            })
+                            .build(ctx),
+                        )
+                        .with_child(
+                            StackPanelBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(3)
+                                    .on_column(1)
+                                    .with_horizontal_alignment(HorizontalAlignment::Right)
+                                    .with_child({
+                                        rename = ButtonBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_width(100.0)
+                                                .with_margin(Thickness::uniform(1.0)),
+                                        )
+                                        .with_text("Rename")
+                                        .build(ctx);
+                                        rename
+                                    })
+                                    .with_child({
+                                        cancel = ButtonBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_width(100.0)
+                                                .with_margin(Thickness::uniform(1.0)),
+                                        )
+                                        .with_text("Cancel")
+                                        .build(ctx);
+                                        cancel
+                                    }),
+                            )
+                            .with_orientation(Orientation::Horizontal)
+                            .build(ctx),
+                        ),
+                )
+                .add_row(Row::strict(24.0))
+                .add_row(Row::strict(24.0))
+                .add_row(Row::stretch())
+                .add_row(Row::strict(26.0))
+                .add_column(Column::strict(90.0))
+                .add_column(Column::stretch())
+                .build(ctx),
+            )
+            .build(ctx);
+
+        ui.send_message(WindowMessage::open_modal(
+            window,
+            MessageDirection::ToWidget,
+            true,
+            true,
+        ));
+
+        let this = Self {
+            window,
+            pattern,
+            replacement,
+            preview,
+            rename,
+            cancel,
+            pattern_str: Default::default(),
+            replacement_str: Default::default(),
+            nodes,
+        };
+        this.update_preview(ui);
+        this
+    }
+
+    fn renamed_nodes(&self) -> Result<Vec<(Handle<Node>, String)>, regex::Error> {
+        let pattern = Regex::new(&self.pattern_str)?;
+        Ok(batch_rename(&self.nodes, &pattern, &self.replacement_str))
+    }
+
+    fn update_preview(&self, ui: &UserInterface) {
+        let text = if self.pattern_str.is_empty() {
+            format!(
+                "{} node(s) selected. Use $1 to reference capture groups and {{n}} to insert \
+                the index of a node.",
+                self.nodes.len()
+            )
+        } else {
+            match self.renamed_nodes() {
+                Ok(renamed) => renamed
+                    .iter()
+                    .filter_map(|(handle, new_name)| {
+                        self.nodes
+                            .iter()
+                            .find(|(h, _)| h == handle)
+                            .map(|(_, old_name)| format!("{old_name} -> {new_name}\n"))
+                    })
+                    .collect::<String>(),
+                Err(err) => err.to_string(),
+            }
+        };
+
+        ui.send_message(TextMessage::text(
+            self.preview,
+            MessageDirection::ToWidget,
+            text,
+        ));
+    }
+
+    fn destroy(self, ui: &UserInterface) {
+        ui.send_message(WidgetMessage::remove(
+            self.window,
+            MessageDirection::ToWidget,
+        ));
+    }
+
+    fn handle_ui_message(mut self, message: &UiMessage, editor: &mut Editor) -> Option<Self> {
+        let ui = editor.engine.user_interfaces.first_mut();
+
+        if let Some(TextMessage::Text(text)) = message.data() {
+            if message.direction() == MessageDirection::FromWidget {
+                if message.destination() == self.pattern {
+                    self.pattern_str.clone_from(text);
+                    self.update_preview(ui);
+                } else if message.destination() == self.replacement {
+                    self.replacement_str.clone_from(text);
+                    self.update_preview(ui);
+                }
+            }
+        } else if let Some(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.rename {
+                if let Ok(renamed) = self.renamed_nodes() {
+                    if !renamed.is_empty() {
+                        let group = renamed
+                            .into_iter()
+                            .map(|(handle, name)| {
+                                Command::new(SetNodeNameCommand::new(handle, name))
+                            })
+                            .collect::<Vec<_>>();
+                        editor
+                            .message_sender
+                            .do_command(CommandGroup::from(group).with_custom_name("Batch Rename"));
+                    }
+                }
+                self.destroy(ui);
+                return None;
+            } else if message.destination() == self.cancel {
+                self.destroy(ui);
+                return None;
+            }
+        } else if let Some(WindowMessage::Close) = message.data() {
+            if message.destination() == self.window {
+                self.destroy(ui);
+                return None;
+            }
+        }
+
+        Some(self)
+    }
+}
+
+#[derive(Default)]
+pub struct BatchRenamePlugin {
+    open_batch_rename: Handle<UiNode>,
+    window: Option<BatchRenameWindow>,
+}
+
+impl BatchRenamePlugin {
+    fn open(&mut self, editor: &mut Editor) {
+        let Some(entry) = editor.scenes.current_scene_entry_ref() else {
+            return;
+        };
+        let Some(game_scene) = entry.controller.downcast_ref::<GameScene>() else {
+            return;
+        };
+        let Some(selection) = entry.selection.as_graph() else {
+            return;
+        };
+
+        let graph = &editor.engine.scenes[game_scene.scene].graph;
+        let nodes = selection
+            .nodes()
+            .iter()
+            .filter_map(|handle| {
+                graph
+                    .try_get(*handle)
+                    .map(|node| (*handle, node.name_owned()))
+            })
+            .collect::<Vec<_>>();
+
+        if !nodes.is_empty() {
+            self.window = Some(BatchRenameWindow::new(
+                nodes,
+                editor.engine.user_interfaces.first_mut(),
+            ));
+        }
+    }
+}
+
+impl EditorPlugin for BatchRenamePlugin {
+    fn on_start(&mut self, editor: &mut Editor) {
+        let ui = editor.engine.user_interfaces.first_mut();
+        let ctx = &mut ui.build_ctx();
+        self.open_batch_rename = create_menu_item("Batch Rename...", vec![], ctx);
+        ui.send_message(MenuItemMessage::add_item(
+            editor.menu.edit_menu.menu,
+            MessageDirection::ToWidget,
+            self.open_batch_rename,
+        ));
+    }
+
+    fn on_ui_message(&mut self, message: &mut UiMessage, editor: &mut Editor) {
+        if let Some(MenuItemMessage::Click) = message.data() {
+            if message.destination() == self.open_batch_rename && self.window.is_none() {
+                self.open(editor);
+            }
+        }
+
+        if let Some(window) = self.window.take() {
+            self.window = window.handle_ui_message(message, editor);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::batch_rename;
+    use crate::fyrox::core::pool::Handle;
+    use regex::Regex;
+
+    #[test]
+    fn test_batch_rename() {
+        let nodes = [
+            (Handle::new(1, 1), "Enemy_Old".to_string()),
+            (Handle::new(2, 1), "Crate".to_string()),
+            (Handle::new(3, 1), "Enemy_Boss".to_string()),
+        ];
+
+        // Unchanged names are excluded.
+        let renamed = batch_rename(&nodes, &Regex::new("^Enemy_(.*)$").unwrap(), "Foe_$1");
+        assert_eq!(
+            renamed,
+            [
+                (Handle::new(1, 1), "Foe_Old".to_string()),
+                (Handle::new(3, 1), "Foe_Boss".to_string()),
+            ]
+        );
+
+        // Indices are 1-based and are taken from the position of the node in the list.
+        let renamed = batch_rename(&nodes, &Regex::new("^.*$").unwrap(), "Node{n}");
+        assert_eq!(
+            renamed,
+            [
+                (Handle::new(1, 1), "Node1".to_string()),
+                (Handle::new(2, 1), "Node2".to_string()),
+                (Handle::new(3, 1), "Node3".to_string()),
+            ]
+        );
+
+        // Named groups are supported as well.
+        let renamed = batch_rename(
+            &nodes,
+            &Regex::new("(?P<kind>Enemy)_(?P<name>.*)").unwrap(),
+            "${name}_$kind",
+        );
+        assert_eq!(renamed[0].1, "Old_Enemy");
+        assert_eq!(renamed[1].1, "Boss_Enemy");
+    }
+}
@@ -0,0 +1,335 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Duplicate assets finder. It searches the project for assets with identical content and import
+//! options (see [`crate::fyrox::asset::duplicates`]) and merges the references to the duplicates
+//! in all scenes of the project, so the duplicates could be safely deleted afterwards.
+
+use crate::{
+    asset::references::{find_scene_files, is_same_file, load_scene, replace_resource, save_scene},
+    fyrox::{
+        asset::{duplicates::DuplicateReport, untyped::UntypedResource},
+        core::{futures::executor::block_on, log::Log, make_relative_path, pool::Handle},
+        gui::{
+            button::{ButtonBuilder, ButtonMessage},
+            grid::{Column, GridBuilder, Row},
+            menu::MenuItemMessage,
+            message::{MessageDirection, UiMessage},
+            scroll_viewer::ScrollViewerBuilder,
+            stack_panel::StackPanelBuilder,
+            text::{TextBuilder, TextMessage},
+            widget::{WidgetBuilder, WidgetMessage},
+            window::{WindowBuilder, WindowMessage, WindowTitle},
+            HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
+        },
+    },
+    menu::create_menu_item,
+    plugin::EditorPlugin,
+    Editor,
+};
+use std::path::{Component, Path};
+
+/// Hidden directories and build artifacts may contain copies of the assets, that are not part of
+/// the project.
+fn is_project_asset(path: &Path) -> bool {
+    !path.components().any(|component| match component {
+        Component::Normal(name) => {
+            let name = name.to_string_lossy();
+            name.starts_with('.') || name == "target"
+        }
+        _ => false,
+    })
+}
+
+fn find_duplicate_assets(editor: &Editor) -> DuplicateReport {
+    match block_on(
+        editor
+            .engine
+            .resource_manager
+            .find_duplicate_resources(".", is_project_asset),
+    ) {
+        Ok(report) => report,
+        Err(err) => {
+            Log::err(format!(
+                "Unable to search for duplicate assets. Reason: {err:?}"
+            ));
+            Default::default()
+        }
+    }
+}
+
+fn request(editor: &Editor, path: &Path) -> Option<UntypedResource> {
+    // Scenes reference the assets by paths relative to the working directory.
+    let path = make_relative_path(path).ok()?;
+    block_on(editor.engine.resource_manager.request_untyped(path)).ok()
+}
+
+fn format_report(report: &DuplicateReport) -> String {
+    if report.is_empty() {
+        return "No duplicate assets found.".to_string();
+    }
+
+    let mut text = format!(
+        "{} group(s) of duplicate assets found, {:.2} MiB wasted.\n\n",
+        report.groups.len(),
+        report.wasted_bytes() as f64 / (1024.0 * 1024.0)
+    );
+    for group in report.groups.iter() {
+        text += &format!("{} ({} bytes)\n", group.original().display(), group.size);
+        for duplicate in group.duplicates() {
+            text += &format!("    = {}\n", duplicate.display());
+        }
+    }
+    text += "\nMerge References replaces the duplicates with the first asset of each group in \
+    all the scenes of the project. Other assets (materials, etc.) are not modified.";
+    text
+}
+
+/// Replaces every duplicate with the original asset of its group in all the scenes of the project.
+/// Scenes that are open in the editor are skipped. Returns a human-readable report.
+fn merge_references(report: &DuplicateReport, editor: &Editor) -> String {
+    let replacements = report
+        .groups
+        .iter()
+        .filter_map(|group| Some((group, request(editor, group.original())?)))
+        .flat_map(|(group, original)| {
+            group
+                .duplicates()
+                .filter_map(|duplicate| Some((request(editor, duplicate)?, original.clone())))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut text = String::new();
+    for path in find_scene_files(Path::new(".")) {
+        let is_open = editor
+            .scenes
+            .iter()
+            .filter_map(|entry| entry.path.as_ref())
+            .any(|open| is_same_file(open, &path));
+        if is_open {
+            text += &format!(
+                "{} is skipped, because it is open in the editor.\n",
+                path.display()
+            );
+            continue;
+        }
+
+        let result = load_scene(&path, &editor.engine).and_then(|mut scene| {
+            let count = replacements
+                .iter()
+                .map(|(duplicate, original)| replace_resource(&mut scene, duplicate, original))
+                .sum::<usize>();
+            if count > 0 {
+                save_scene(&path, &mut scene)?;
+            }
+            Ok(count)
+        });
+
+        match result {
+            Ok(0) => (),
+            Ok(count) => text += &format!("{}: {} reference(s) merged.\n", path.display(), count),
+            Err(err) => text += &format!("{} failed: {}\n", path.display(), err),
+        }
+    }
+
+    if text.is_empty() {
+        text = "No references to the duplicates found.".to_string();
+    }
+    text
+}
+
+struct DuplicateAssetsWindow {
+    window: Handle<UiNode>,
+    text: Handle<UiNode>,
+    merge: Handle<UiNode>,
+    rescan: Handle<UiNode>,
+    close: Handle<UiNode>,
+    report: DuplicateReport,
+}
+
+impl DuplicateAssetsWindow {
+    fn new(ui: &mut UserInterface) -> Self {
+        let ctx = &mut ui.build_ctx();
+
+        let text;
+        let merge;
+        let rescan;
+        let close;
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(500.0).with_height(400.0))
+            .with_title(WindowTitle::text("Duplicate Assets"))
+            .open(false)
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_margin(Thickness::uniform(2.0))
+                        .with_child(
+                            ScrollViewerBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(0)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .with_content({
+                                text = TextBuilder::new(WidgetBuilder::new()).build(ctx);
+                                text
+                            })
+                            .build(ctx),
+                        )
+                        .with_child(
+                            StackPanelBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(1)
+                                    .with_horizontal_alignment(HorizontalAlignment::Right)
+                                    .with_child({
+                                        merge = ButtonBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_width(130.0)
+                                                .with_margin(Thickness::uniform(1.0)),
+                                        )
+                                        .with_text("Merge References")
+                                        .build(ctx);
+                                        merge
+                                    })
+                                    .with_child({
+                                        rescan = ButtonBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_width(100.0)
+                                                .with_margin(Thickness::uniform(1.0)),
+                                        )
+                                        .with_text("Rescan")
+                                        .build(ctx);
+                                        rescan
+                                    })
+                                    .with_child({
+                                        close = ButtonBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_width(100.0)
+                                                .with_margin(Thickness::uniform(1.0)),
+                                        )
+                                        .with_text("Close")
+                                        .build(ctx);
+                                        close
+                                    }),
+                            )
+                            .with_orientation(Orientation::Horizontal)
+                            .build(ctx),
+                        ),
+                )
+                .add_row(Row::stretch())
+                .add_row(Row::strict(26.0))
+                .add_column(Column::stretch())
+                .build(ctx),
+            )
+            .build(ctx);
+
+        ui.send_message(WindowMessage::open(
+            window,
+            MessageDirection::ToWidget,
+            true,
+            true,
+        ));
+
+        Self {
+            window,
+            text,
+            merge,
+            rescan,
+            close,
+            report: Default::default(),
+        }
+    }
+
+    fn set_text(&self, text: String, ui: &UserInterface) {
+        ui.send_message(TextMessage::text(
+            self.text,
+            MessageDirection::ToWidget,
+            text,
+        ));
+    }
+
+    fn scan(&mut self, editor: &Editor) {
+        self.report = find_duplicate_assets(editor);
+        self.set_text(
+            format_report(&self.report),
+            editor.engine.user_interfaces.first(),
+        );
+    }
+
+    fn destroy(self, ui: &UserInterface) {
+        ui.send_message(WidgetMessage::remove(
+            self.window,
+            MessageDirection::ToWidget,
+        ));
+    }
+
+    fn handle_ui_message(mut self, message: &UiMessage, editor: &mut Editor) -> Option<Self> {
+        if let Some(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.merge {
+                let text = merge_references(&self.report, editor);
+                self.set_text(text, editor.engine.user_interfaces.first());
+            } else if message.destination() == self.rescan {
+                self.scan(editor);
+            } else if message.destination() == self.close {
+                self.destroy(editor.engine.user_interfaces.first());
+                return None;
+            }
+        } else if let Some(WindowMessage::Close) = message.data() {
+            if message.destination() == self.window {
+                self.destroy(editor.engine.user_interfaces.first());
+                return None;
+            }
+        }
+
+        Some(self)
+    }
+}
+
+#[derive(Default)]
+pub struct DuplicateAssetsPlugin {
+    open_duplicate_assets: Handle<UiNode>,
+    window: Option<DuplicateAssetsWindow>,
+}
+
+impl EditorPlugin for DuplicateAssetsPlugin {
+    fn on_start(&mut self, editor: &mut Editor) {
+        let ui = editor.engine.user_interfaces.first_mut();
+        let ctx = &mut ui.build_ctx();
+        self.open_duplicate_assets = create_menu_item("Find Duplicate Assets", vec![], ctx);
+        ui.send_message(MenuItemMessage::add_item(
+            editor.menu.utils_menu.menu,
+            MessageDirection::ToWidget,
+            self.open_duplicate_assets,
+        ));
+    }
+
+    fn on_ui_message(&mut self, message: &mut UiMessage, editor: &mut Editor) {
+        if let Some(MenuItemMessage::Click) = message.data() {
+            if message.destination() == self.open_duplicate_assets && self.window.is_none() {
+                let mut window =
+                    DuplicateAssetsWindow::new(editor.engine.user_interfaces.first_mut());
+                window.scan(editor);
+                self.window = Some(window);
+            }
+        }
+
+        if let Some(window) = self.window.take() {
+            self.window = window.handle_ui_message(message, editor);
+        }
+    }
+}
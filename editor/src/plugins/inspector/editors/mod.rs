@@ -60,16 +60,20 @@ use crate::{
         renderer::framework::PolygonFillMode,
         resource::{
             curve::{CurveResource, CurveResourceState},
-            model::{MaterialSearchOptions, Model, ModelResource},
+            model::{
+                GeneratedLodLevel, LodGenerationOptions, MaterialSearchOptions, Model,
+                ModelResource,
+            },
             texture::{
-                CompressionOptions, MipFilter, TextureMagnificationFilter,
-                TextureMinificationFilter, TextureResource, TextureWrapMode,
+                CompressionOptions, MipFilter, MipGeneration, TextureMagnificationFilter,
+                TextureMinificationFilter, TextureResource, TextureSampler, TextureWrapMode,
             },
         },
         scene::{
             self,
             base::{
-                Base, LevelOfDetail, LodGroup, Mobility, Property, PropertyValue, ScriptRecord,
+                Base, LevelOfDetail, LodGroup, LodMetric, Mobility, Property, PropertyValue,
+                ScriptRecord,
             },
             camera::{
                 ColorGradingLut, Exposure, OrthographicProjection, PerspectiveProjection,
@@ -80,6 +84,7 @@ use crate::{
                 CuboidShape, CylinderShape, GeometrySource, HeightfieldShape, InteractionGroups,
                 SegmentShape, TriangleShape, TrimeshShape,
             },
+            destruction::DestructibleChunk,
             dim2,
             graph::physics::CoefficientCombineRule,
             joint::*,
@@ -94,14 +99,22 @@ use crate::{
             node::Node,
             particle_system::CoordinateSystem,
             particle_system::{
+                collision::ParticleCollision,
                 emitter::{
-                    base::BaseEmitter, cuboid::CuboidEmitter, cylinder::CylinderEmitter,
-                    sphere::SphereEmitter, Emitter,
+                    base::BaseEmitter,
+                    cuboid::CuboidEmitter,
+                    cylinder::CylinderEmitter,
+                    sphere::SphereEmitter,
+                    sub::{SubEmitter, SubEmitterTrigger},
+                    Emitter,
                 },
+                trail::ParticleTrail,
                 ParticleSystemRng,
             },
             ragdoll::Limb,
             rigidbody::RigidBodyType,
+            socket::Socket,
+            soft_body::Pin,
             sound::{
                 self,
                 filter::{
@@ -109,7 +122,7 @@ use crate::{
                     HighShelfFilterEffect, LowPassFilterEffect, LowShelfFilterEffect,
                 },
                 reverb::Reverb,
-                Attenuate, AudioBus, Biquad, DistanceModel, Effect, SoundBuffer,
+                Attenuate, AudioBus, Biquad, ChannelConversion, DistanceModel, Effect, SoundBuffer,
                 SoundBufferResource, Status,
             },
             terrain::{Chunk, Layer},
@@ -117,6 +130,7 @@ use crate::{
             tilemap::TileCollider,
             tilemap::{tileset::TileSet, Tile},
             transform::Transform,
+            vehicle::Wheel,
         },
     },
     message::MessageSender,
@@ -156,7 +170,7 @@ where
 {
     use crate::fyrox::generic_animation::machine::{
         node::{
-            blendspace::{BlendSpace, BlendSpacePoint},
+            blendspace::{BlendSpace, BlendSpaceKind, BlendSpacePoint},
             BasePoseNode,
         },
         state::{StateAction, StateActionWrapper},
@@ -181,6 +195,7 @@ where
     container.insert(InspectablePropertyEditorDefinition::<BlendPose<Handle<T>>>::new());
     container.insert(VecCollectionPropertyEditorDefinition::<BlendPose<Handle<T>>>::new());
     container.insert(EnumPropertyEditorDefinition::<PoseWeight>::new());
+    container.insert(EnumPropertyEditorDefinition::<BlendSpaceKind>::new());
     container.insert(EnumPropertyEditorDefinition::<StateAction<Handle<T>>>::new());
     container.insert(InspectablePropertyEditorDefinition::<
         StateActionWrapper<Handle<T>>,
@@ -254,16 +269,23 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     container.register_inheritable_inspectable::<Layer>();
 
     container.register_inheritable_vec_collection::<Emitter>();
+    container.register_inheritable_vec_collection::<SubEmitter>();
 
     container.register_inheritable_vec_collection::<LevelOfDetail>();
     container.register_inheritable_inspectable::<LevelOfDetail>();
 
+    container.register_inheritable_vec_collection::<GeneratedLodLevel>();
+    container.register_inheritable_inspectable::<GeneratedLodLevel>();
+
     container.register_inheritable_vec_collection::<ErasedHandle>();
     container.register_inheritable_inspectable::<ErasedHandle>();
 
     container.register_inheritable_vec_collection::<Property>();
     container.register_inheritable_inspectable::<Property>();
 
+    container.register_inheritable_vec_collection::<Socket>();
+    container.register_inheritable_inspectable::<Socket>();
+
     container.register_inheritable_vec_collection::<GeometrySource>();
     container.register_inheritable_inspectable::<GeometrySource>();
 
@@ -275,6 +297,9 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     container.insert(EnumPropertyEditorDefinition::<LodGroup>::new_optional());
     container.insert(InheritablePropertyEditorDefinition::<Option<LodGroup>>::new());
 
+    container.insert(EnumPropertyEditorDefinition::<LodGenerationOptions>::new_optional());
+    container.register_inheritable_inspectable::<LodGenerationOptions>();
+
     {
         use crate::fyrox::scene::animation::spritesheet::prelude::*;
         container.register_inheritable_enum::<Status, _>();
@@ -285,6 +310,13 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
         container.register_inheritable_vec_collection::<Signal>();
     }
 
+    {
+        use crate::fyrox::scene::animation::lod::{AnimationLod, AnimationLodLevel};
+        container.register_inheritable_inspectable::<AnimationLod>();
+        container.register_inheritable_inspectable::<AnimationLodLevel>();
+        container.register_inheritable_vec_collection::<AnimationLodLevel>();
+    }
+
     container.insert(ResourceFieldPropertyEditorDefinition::<Model>::new(
         sender.clone(),
     ));
@@ -372,6 +404,10 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     container.register_inheritable_inspectable::<SphereEmitter>();
     container.register_inheritable_inspectable::<CylinderEmitter>();
     container.register_inheritable_inspectable::<CuboidEmitter>();
+    container.register_inheritable_inspectable::<SubEmitter>();
+    container.register_inheritable_enum::<SubEmitterTrigger, _>();
+    container.register_inheritable_inspectable::<ParticleTrail>();
+    container.register_inheritable_inspectable::<ParticleCollision>();
     container.register_inheritable_inspectable::<PerspectiveProjection>();
     container.register_inheritable_inspectable::<OrthographicProjection>();
     container.register_inheritable_inspectable::<Transform>();
@@ -389,6 +425,9 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
 
     container.register_inheritable_inspectable::<SkyBox>();
 
+    container.register_inheritable_option::<TextureSampler>();
+    container.register_inheritable_inspectable::<TextureSampler>();
+
     container.register_inheritable_enum::<dim2::collider::ColliderShape, _>();
     container.register_inheritable_enum::<CoefficientCombineRule, _>();
     container.register_inheritable_enum::<CompressionOptions, _>();
@@ -399,6 +438,7 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     container.register_inheritable_enum::<ColliderShape, _>();
     container.register_inheritable_enum::<PropertyValue, _>();
     container.register_inheritable_enum::<Mobility, _>();
+    container.register_inheritable_enum::<LodMetric, _>();
     container.register_inheritable_enum::<RigidBodyType, _>();
     container.register_inheritable_enum::<Exposure, _>();
     container.register_inheritable_enum::<FrustumSplitOptions, _>();
@@ -475,6 +515,15 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     container.insert(EnumPropertyEditorDefinition::<PolygonFillMode>::new());
 
     container.insert(EnumPropertyEditorDefinition::<MipFilter>::new());
+    container.insert(EnumPropertyEditorDefinition::<MipGeneration>::new());
+    container.insert(EnumPropertyEditorDefinition::<ChannelConversion>::new());
+
+    container.register_inheritable_vec_collection::<Wheel>();
+    container.register_inheritable_inspectable::<Wheel>();
+    container.register_inheritable_vec_collection::<DestructibleChunk>();
+    container.register_inheritable_inspectable::<DestructibleChunk>();
+    container.register_inheritable_vec_collection::<Pin>();
+    container.register_inheritable_inspectable::<Pin>();
 
     container.register_inheritable_inspectable::<Limb>();
     container.insert(VecCollectionPropertyEditorDefinition::<Limb>::new());
@@ -376,7 +376,7 @@ impl EditorPlugin for InspectorPlugin {
         ui.send_message(WidgetMessage::enabled(
             window_content(self.window, ui),
             MessageDirection::ToWidget,
-            editor.mode.is_edit(),
+            editor.mode.is_inspectable(),
         ));
     }
 
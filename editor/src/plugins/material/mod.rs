@@ -658,7 +658,7 @@ impl MaterialEditor {
                     sender.do_command(SetMaterialBindingCommand::new(
                         material.clone(),
                         binding_name.clone(),
-                        MaterialResourceBinding::Texture(MaterialTextureBinding { value: None }),
+                        MaterialResourceBinding::Texture(MaterialTextureBinding::default()),
                     ));
                 }
             }
@@ -690,6 +690,7 @@ impl MaterialEditor {
                                     resource_view.name.clone(),
                                     MaterialResourceBinding::Texture(MaterialTextureBinding {
                                         value: texture,
+                                        sampler: None,
                                     }),
                                 ));
                             }
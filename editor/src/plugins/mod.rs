@@ -20,12 +20,17 @@
 
 pub mod absm;
 pub mod animation;
+pub mod batch_rename;
 pub mod collider;
 pub mod curve_editor;
+pub mod duplicates;
 pub mod inspector;
 pub mod material;
 pub mod path_fixer;
 pub mod ragdoll;
+pub mod script_profiler;
 pub mod settings;
+pub mod shader_graph;
 pub mod stats;
 pub mod tilemap;
+pub mod validation;
@@ -0,0 +1,162 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{
+    fyrox::{
+        core::pool::Handle,
+        gui::{
+            button::{ButtonBuilder, ButtonMessage},
+            grid::{Column, GridBuilder, Row},
+            menu::MenuItemMessage,
+            message::{MessageDirection, UiMessage},
+            scroll_viewer::ScrollViewerBuilder,
+            text::{TextBuilder, TextMessage},
+            widget::{WidgetBuilder, WidgetMessage},
+            window::{WindowBuilder, WindowMessage, WindowTitle},
+            HorizontalAlignment, Thickness, UiNode, VerticalAlignment,
+        },
+    },
+    menu::create_menu_item,
+    plugin::EditorPlugin,
+    Editor,
+};
+
+/// Shows per-type timings of scripts, useful to find which script types are the most expensive.
+/// The profiler is enabled only while the window is open.
+#[derive(Default)]
+pub struct ScriptProfilerPlugin {
+    window: Handle<UiNode>,
+    text: Handle<UiNode>,
+    reset: Handle<UiNode>,
+    open_script_profiler: Handle<UiNode>,
+}
+
+impl ScriptProfilerPlugin {
+    fn close(&mut self, editor: &mut Editor) {
+        editor.engine.script_processor.profiler.set_enabled(false);
+
+        editor
+            .engine
+            .user_interfaces
+            .first_mut()
+            .send_message(WidgetMessage::remove(
+                self.window,
+                MessageDirection::ToWidget,
+            ));
+        self.window = Handle::NONE;
+    }
+}
+
+impl EditorPlugin for ScriptProfilerPlugin {
+    fn on_start(&mut self, editor: &mut Editor) {
+        let ui = editor.engine.user_interfaces.first_mut();
+        let ctx = &mut ui.build_ctx();
+        self.open_script_profiler = create_menu_item("Script Profiler", vec![], ctx);
+        ui.send_message(MenuItemMessage::add_item(
+            editor.menu.utils_menu.menu,
+            MessageDirection::ToWidget,
+            self.open_script_profiler,
+        ));
+    }
+
+    fn on_ui_message(&mut self, message: &mut UiMessage, editor: &mut Editor) {
+        if let Some(MenuItemMessage::Click) = message.data() {
+            if message.destination() == self.open_script_profiler && self.window.is_none() {
+                let ui = editor.engine.user_interfaces.first_mut();
+                let ctx = &mut ui.build_ctx();
+                self.text =
+                    TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(1.0)))
+                        .build(ctx);
+                self.reset = ButtonBuilder::new(
+                    WidgetBuilder::new()
+                        .on_row(1)
+                        .with_width(80.0)
+                        .with_horizontal_alignment(HorizontalAlignment::Right)
+                        .with_margin(Thickness::uniform(1.0)),
+                )
+                .with_text("Reset")
+                .build(ctx);
+                self.window =
+                    WindowBuilder::new(WidgetBuilder::new().with_width(500.0).with_height(300.0))
+                        .with_title(WindowTitle::text("Script Profiler"))
+                        .with_content(
+                            GridBuilder::new(
+                                WidgetBuilder::new()
+                                    .with_child(
+                                        ScrollViewerBuilder::new(WidgetBuilder::new().on_row(0))
+                                            .with_content(self.text)
+                                            .build(ctx),
+                                    )
+                                    .with_child(self.reset),
+                            )
+                            .add_row(Row::stretch())
+                            .add_row(Row::auto())
+                            .add_column(Column::stretch())
+                            .build(ctx),
+                        )
+                        .open(false)
+                        .build(ctx);
+
+                ui.send_message(WindowMessage::open_and_align(
+                    self.window,
+                    MessageDirection::ToWidget,
+                    editor.scene_viewer.frame(),
+                    HorizontalAlignment::Right,
+                    VerticalAlignment::Bottom,
+                    Thickness::uniform(1.0),
+                    false,
+                    true,
+                ));
+
+                editor.engine.script_processor.profiler.set_enabled(true);
+            }
+        }
+
+        if let Some(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.reset {
+                editor.engine.script_processor.profiler.reset();
+            }
+        }
+
+        if let Some(WindowMessage::Close) = message.data() {
+            if message.destination() == self.window {
+                self.close(editor);
+            }
+        }
+    }
+
+    fn on_update(&mut self, editor: &mut Editor) {
+        if self.window.is_none() {
+            return;
+        }
+
+        let report = editor.engine.script_processor.profiler.report();
+
+        editor
+            .engine
+            .user_interfaces
+            .first()
+            .send_message(TextMessage::text(
+                self.text,
+                MessageDirection::ToWidget,
+                report.to_string(),
+            ));
+    }
+}
@@ -47,7 +47,11 @@ use crate::{
             window::{WindowBuilder, WindowMessage, WindowTitle},
             HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
         },
-        renderer::{CsmSettings, QualitySettings, ShadowMapPrecision},
+        renderer::{
+            framework::server::{PresentSettings, VSyncMode},
+            CsmSettings, QualitySettings, ShadowMapPrecision, SsgiQuality, SsgiSettings,
+            TaaSettings,
+        },
     },
     menu::create_menu_item,
     message::MessageSender,
@@ -55,7 +59,7 @@ use crate::{
     settings::{
         build::BuildSettings,
         camera::CameraSettings,
-        debugging::DebuggingSettings,
+        debugging::{DebugOverlayColors, DebuggingSettings},
         general::{EditorStyle, GeneralSettings, ScriptEditor},
         graphics::GraphicsSettings,
         keys::{KeyBindings, TerrainKeyBindings},
@@ -84,8 +88,14 @@ fn make_property_editors_container(
     container.insert(EnumPropertyEditorDefinition::<ScriptEditor>::new());
     container.insert(EnumPropertyEditorDefinition::<EditorStyle>::new());
     container.insert(InspectablePropertyEditorDefinition::<DebuggingSettings>::new());
+    container.insert(InspectablePropertyEditorDefinition::<DebugOverlayColors>::new());
     container.insert(InspectablePropertyEditorDefinition::<CsmSettings>::new());
+    container.insert(InspectablePropertyEditorDefinition::<SsgiSettings>::new());
+    container.insert(EnumPropertyEditorDefinition::<SsgiQuality>::new());
+    container.insert(InspectablePropertyEditorDefinition::<TaaSettings>::new());
     container.insert(InspectablePropertyEditorDefinition::<QualitySettings>::new());
+    container.insert(InspectablePropertyEditorDefinition::<PresentSettings>::new());
+    container.insert(EnumPropertyEditorDefinition::<VSyncMode>::new());
     container.insert(InspectablePropertyEditorDefinition::<CameraSettings>::new());
     container.insert(InspectablePropertyEditorDefinition::<
         MoveInteractionModeSettings,
@@ -393,6 +403,19 @@ impl SettingsWindow {
             }
         }
 
+        if settings.graphics.present != graphics_context.renderer.present_settings() {
+            if let Err(e) = graphics_context
+                .renderer
+                .set_present_settings(settings.graphics.present)
+            {
+                Log::err(format!(
+                    "An error occurred at attempt to set new present settings: {e:?}"
+                ));
+            } else {
+                Log::info("New present settings were successfully set!");
+            }
+        }
+
         Some(self)
     }
 }
@@ -0,0 +1,854 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Data model of a shader graph and a code generator that turns it into a shader definition that
+//! is fully compatible with the engine's deferred renderer. The generated shader reuses vertex
+//! processing and shadow passes of the standard shader and replaces the G-Buffer fragment shader
+//! with the code produced from the graph.
+
+use crate::fyrox::{
+    core::{
+        algebra::{Vector2, Vector4},
+        color::Color,
+        pool::{Handle, Pool},
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        uuid_provider,
+        visitor::prelude::*,
+    },
+    fxhash::{FxHashMap, FxHashSet},
+    material::shader::{
+        SamplerFallback, ShaderDefinition, ShaderResourceDefinition, ShaderResourceKind,
+        STANDARD_SHADER_SRC,
+    },
+    renderer::framework::gpu_program::{SamplerKind, ShaderProperty, ShaderPropertyKind},
+};
+use ron::ser::PrettyConfig;
+use std::{
+    fmt::{Display, Formatter, Write},
+    path::Path,
+};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Type of a value produced by a node of a shader graph.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ValueType {
+    Float,
+    Vec2,
+    Vec3,
+    Vec4,
+}
+
+impl ValueType {
+    fn from_components(count: usize) -> Self {
+        match count {
+            1 => Self::Float,
+            2 => Self::Vec2,
+            3 => Self::Vec3,
+            _ => Self::Vec4,
+        }
+    }
+
+    fn components(self) -> usize {
+        match self {
+            Self::Float => 1,
+            Self::Vec2 => 2,
+            Self::Vec3 => 3,
+            Self::Vec4 => 4,
+        }
+    }
+
+    fn glsl(self) -> &'static str {
+        match self {
+            Self::Float => "float",
+            Self::Vec2 => "vec2",
+            Self::Vec3 => "vec3",
+            Self::Vec4 => "vec4",
+        }
+    }
+
+    /// Converts an expression of this type to an expression of the given type. Scalars are
+    /// splatted, vectors are truncated or extended with zeros (and one for `w`).
+    fn convert(self, expr: &str, to: Self) -> String {
+        match (self, to) {
+            (from, to) if from == to => expr.to_string(),
+            (Self::Float, to) => format!("{}({expr})", to.glsl()),
+            (_, Self::Float) => format!("({expr}).x"),
+            (from, to) if from > to => format!("({expr}).{}", &"xyzw"[..to.components()]),
+            (Self::Vec2, Self::Vec3) => format!("vec3({expr}, 0.0)"),
+            (Self::Vec2, Self::Vec4) => format!("vec4({expr}, 0.0, 1.0)"),
+            _ => format!("vec4({expr}, 1.0)"),
+        }
+    }
+}
+
+/// Description of a single input of a node.
+pub struct InputDefinition {
+    /// A name of the input that will be shown next to its socket.
+    pub name: &'static str,
+    /// Type of the input. `None` means that the input is generic and its type is defined by the
+    /// widest type among all generic inputs of the node.
+    pub ty: Option<ValueType>,
+    /// A GLSL expression that will be used when the input is not connected.
+    pub default: &'static str,
+}
+
+const fn input(
+    name: &'static str,
+    ty: Option<ValueType>,
+    default: &'static str,
+) -> InputDefinition {
+    InputDefinition { name, ty, default }
+}
+
+const OUTPUT_INPUTS: &[InputDefinition] = &[
+    input("Albedo", Some(ValueType::Vec3), "vec3(1.0)"),
+    input("Alpha", Some(ValueType::Float), "1.0"),
+    input("Normal", Some(ValueType::Vec3), "normalize(normal)"),
+    input("Metallic", Some(ValueType::Float), "0.0"),
+    input("Roughness", Some(ValueType::Float), "1.0"),
+    input("Occlusion", Some(ValueType::Float), "1.0"),
+    input("Emission", Some(ValueType::Vec3), "vec3(0.0)"),
+];
+
+const TEXTURE_INPUTS: &[InputDefinition] = &[input("UV", Some(ValueType::Vec2), "texCoord")];
+
+const UNPACK_NORMAL_INPUTS: &[InputDefinition] = &[input(
+    "Normal",
+    Some(ValueType::Vec3),
+    "vec3(0.5, 0.5, 1.0)",
+)];
+
+const ADD_INPUTS: &[InputDefinition] = &[input("A", None, "0.0"), input("B", None, "0.0")];
+
+const MULTIPLY_INPUTS: &[InputDefinition] = &[input("A", None, "1.0"), input("B", None, "1.0")];
+
+const POWER_INPUTS: &[InputDefinition] =
+    &[input("Base", None, "1.0"), input("Exponent", None, "1.0")];
+
+const LERP_INPUTS: &[InputDefinition] = &[
+    input("A", None, "0.0"),
+    input("B", None, "1.0"),
+    input("T", Some(ValueType::Float), "0.5"),
+];
+
+const UNARY_INPUTS: &[InputDefinition] = &[input("Value", None, "0.0")];
+
+const COMBINE_INPUTS: &[InputDefinition] = &[
+    input("X", Some(ValueType::Float), "0.0"),
+    input("Y", Some(ValueType::Float), "0.0"),
+    input("Z", Some(ValueType::Float), "0.0"),
+    input("W", Some(ValueType::Float), "1.0"),
+];
+
+const FRESNEL_INPUTS: &[InputDefinition] =
+    &[input("Normal", Some(ValueType::Vec3), "normalize(normal)")];
+
+/// Kind of a shader graph node. Every node produces a single value (except the output node, which
+/// consumes the final surface parameters) computed from its inputs.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect, AsRefStr, EnumString, VariantNames)]
+pub enum ShaderGraphNodeKind {
+    /// Final surface parameters of the material. There must be exactly one output node in a graph.
+    #[default]
+    Output,
+    /// First texture coordinates of a mesh.
+    TexCoord,
+    /// World-space position of a fragment.
+    WorldPosition,
+    /// World-space normal of a fragment.
+    WorldNormal,
+    /// Normalized world-space direction from a fragment to the camera.
+    ViewDirection,
+    /// Constant scalar.
+    Float {
+        value: f32,
+    },
+    /// Constant vector.
+    Vector {
+        value: Vector4<f32>,
+    },
+    /// Scalar material property that can be changed per material.
+    FloatProperty {
+        name: String,
+        value: f32,
+    },
+    /// Color material property that can be changed per material.
+    ColorProperty {
+        name: String,
+        value: Color,
+    },
+    /// Texture material property, samples the texture at the given texture coordinates.
+    TextureProperty {
+        name: String,
+        fallback: SamplerFallback,
+    },
+    /// Transforms a normal fetched from a tangent-space normal map to world space.
+    UnpackNormal,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Power,
+    Lerp,
+    OneMinus,
+    Saturate,
+    Normalize,
+    Dot,
+    /// Selects components of a value using GLSL swizzle syntax, for example `xy` or `zyx`.
+    Swizzle {
+        mask: String,
+    },
+    /// Combines four scalars into a vector.
+    Combine,
+    /// Fresnel term, that is stronger when a surface is viewed at grazing angles.
+    Fresnel {
+        power: f32,
+    },
+}
+
+uuid_provider!(ShaderGraphNodeKind = "1ae7a13f-7723-489f-91ba-162a6e0d06f4");
+
+impl ShaderGraphNodeKind {
+    /// Returns a description of inputs of the node.
+    pub fn inputs(&self) -> &'static [InputDefinition] {
+        match self {
+            Self::Output => OUTPUT_INPUTS,
+            Self::TexCoord
+            | Self::WorldPosition
+            | Self::WorldNormal
+            | Self::ViewDirection
+            | Self::Float { .. }
+            | Self::Vector { .. }
+            | Self::FloatProperty { .. }
+            | Self::ColorProperty { .. } => &[],
+            Self::TextureProperty { .. } => TEXTURE_INPUTS,
+            Self::UnpackNormal => UNPACK_NORMAL_INPUTS,
+            Self::Add | Self::Subtract | Self::Dot => ADD_INPUTS,
+            Self::Multiply | Self::Divide => MULTIPLY_INPUTS,
+            Self::Power => POWER_INPUTS,
+            Self::Lerp => LERP_INPUTS,
+            Self::OneMinus | Self::Saturate | Self::Normalize | Self::Swizzle { .. } => {
+                UNARY_INPUTS
+            }
+            Self::Combine => COMBINE_INPUTS,
+            Self::Fresnel { .. } => FRESNEL_INPUTS,
+        }
+    }
+
+    /// Returns `true` if the node produces a value that could be connected to other nodes.
+    pub fn has_output(&self) -> bool {
+        !matches!(self, Self::Output)
+    }
+
+    /// Returns a short human-readable description of the node parameters.
+    pub fn description(&self) -> String {
+        match self {
+            Self::Float { value } => format!("{value}"),
+            Self::Vector { value } => format!("{} {} {} {}", value.x, value.y, value.z, value.w),
+            Self::FloatProperty { name, .. }
+            | Self::ColorProperty { name, .. }
+            | Self::TextureProperty { name, .. } => name.clone(),
+            Self::Swizzle { mask } => mask.clone(),
+            Self::Fresnel { power } => format!("Power {power}"),
+            _ => String::new(),
+        }
+    }
+
+    fn property_name(&self) -> Option<&str> {
+        match self {
+            Self::FloatProperty { name, .. }
+            | Self::ColorProperty { name, .. }
+            | Self::TextureProperty { name, .. } => Some(name),
+            _ => None,
+        }
+    }
+
+    /// Produces an expression and its type from the given input values.
+    fn expression(
+        &self,
+        inputs: &[(String, ValueType)],
+    ) -> Result<(String, ValueType), ShaderGraphError> {
+        let generic = inputs
+            .iter()
+            .zip(self.inputs())
+            .filter(|(_, definition)| definition.ty.is_none())
+            .map(|((_, ty), _)| *ty)
+            .max()
+            .unwrap_or(ValueType::Float);
+
+        let arg = |i: usize| {
+            let (expr, ty) = &inputs[i];
+            ty.convert(expr, self.inputs()[i].ty.unwrap_or(generic))
+        };
+
+        Ok(match self {
+            Self::Output => unreachable!("output node does not produce a value"),
+            Self::TexCoord => ("texCoord".to_string(), ValueType::Vec2),
+            Self::WorldPosition => ("position".to_string(), ValueType::Vec3),
+            Self::WorldNormal => ("normalize(normal)".to_string(), ValueType::Vec3),
+            Self::ViewDirection => ("-toFragment".to_string(), ValueType::Vec3),
+            Self::Float { value } => (float_literal(*value), ValueType::Float),
+            Self::Vector { value } => (
+                format!(
+                    "vec4({}, {}, {}, {})",
+                    float_literal(value.x),
+                    float_literal(value.y),
+                    float_literal(value.z),
+                    float_literal(value.w)
+                ),
+                ValueType::Vec4,
+            ),
+            Self::FloatProperty { name, .. } => (format!("properties.{name}"), ValueType::Float),
+            Self::ColorProperty { name, .. } => (format!("properties.{name}"), ValueType::Vec4),
+            Self::TextureProperty { name, .. } => {
+                (format!("texture({name}, {})", arg(0)), ValueType::Vec4)
+            }
+            Self::UnpackNormal => (
+                format!("normalize(tangentSpace * ({} * 2.0 - 1.0))", arg(0)),
+                ValueType::Vec3,
+            ),
+            Self::Add => (format!("{} + {}", arg(0), arg(1)), generic),
+            Self::Subtract => (format!("{} - {}", arg(0), arg(1)), generic),
+            Self::Multiply => (format!("{} * {}", arg(0), arg(1)), generic),
+            Self::Divide => (format!("{} / {}", arg(0), arg(1)), generic),
+            Self::Power => (format!("pow({}, {})", arg(0), arg(1)), generic),
+            Self::Lerp => (format!("mix({}, {}, {})", arg(0), arg(1), arg(2)), generic),
+            Self::OneMinus => (format!("1.0 - {}", arg(0)), generic),
+            Self::Saturate => (format!("clamp({}, 0.0, 1.0)", arg(0)), generic),
+            Self::Normalize => (format!("normalize({})", arg(0)), generic),
+            Self::Dot => (format!("dot({}, {})", arg(0), arg(1)), ValueType::Float),
+            Self::Swizzle { mask } => {
+                // Scalars cannot be swizzled in GLSL ES, so splat them first.
+                let source_type = generic.max(ValueType::Vec2);
+                let source = inputs[0].1.convert(&inputs[0].0, source_type);
+                if mask.is_empty()
+                    || mask.len() > 4
+                    || !mask
+                        .chars()
+                        .all(|c| "xyzw"[..source_type.components()].contains(c))
+                {
+                    return Err(ShaderGraphError::InvalidSwizzle(mask.clone()));
+                }
+                (
+                    format!("({source}).{mask}"),
+                    ValueType::from_components(mask.len()),
+                )
+            }
+            Self::Combine => (
+                format!("vec4({}, {}, {}, {})", arg(0), arg(1), arg(2), arg(3)),
+                ValueType::Vec4,
+            ),
+            Self::Fresnel { power } => (
+                format!(
+                    "pow(1.0 - clamp(dot({}, -toFragment), 0.0, 1.0), {})",
+                    arg(0),
+                    float_literal(*power)
+                ),
+                ValueType::Float,
+            ),
+        })
+    }
+}
+
+fn float_literal(value: f32) -> String {
+    // Debug formatting always emits a fractional part (or an exponent), which makes the literal a
+    // valid GLSL float.
+    format!("{value:?}")
+}
+
+fn is_valid_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !name.starts_with("fyrox_")
+        && !name.starts_with("gl_")
+}
+
+/// A node of a shader graph.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct ShaderGraphNode {
+    /// Position of the node on the canvas.
+    #[reflect(hidden)]
+    pub position: Vector2<f32>,
+    pub kind: ShaderGraphNodeKind,
+    /// Source nodes of every input. Unassigned handle means that the input is not connected and
+    /// its default value will be used.
+    #[reflect(hidden)]
+    pub inputs: Vec<Handle<ShaderGraphNode>>,
+}
+
+impl ShaderGraphNode {
+    /// Creates a new node of the given kind with all its inputs disconnected.
+    pub fn new(kind: ShaderGraphNodeKind, position: Vector2<f32>) -> Self {
+        Self {
+            position,
+            inputs: vec![Handle::NONE; kind.inputs().len()],
+            kind,
+        }
+    }
+
+    /// Ensures that the amount of inputs matches the node kind. Must be called after the kind was
+    /// changed.
+    pub fn sync_inputs(&mut self) {
+        self.inputs.resize(self.kind.inputs().len(), Handle::NONE);
+    }
+}
+
+/// A set of possible errors that could occur during shader generation.
+#[derive(Debug)]
+pub enum ShaderGraphError {
+    /// The graph does not have an output node.
+    NoOutput,
+    /// The graph has more than one output node.
+    MultipleOutputs,
+    /// The graph contains a cycle.
+    Cycle,
+    /// A property has a name that cannot be used as a GLSL identifier.
+    InvalidPropertyName(String),
+    /// Two or more properties share the same name.
+    DuplicatePropertyName(String),
+    /// A swizzle node has an invalid mask.
+    InvalidSwizzle(String),
+    /// Standard shader template could not be parsed.
+    Template(ron::error::SpannedError),
+    /// Generated shader definition could not be serialized.
+    Serialization(ron::Error),
+}
+
+impl Display for ShaderGraphError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoOutput => write!(f, "The graph does not have an output node."),
+            Self::MultipleOutputs => write!(f, "The graph must have exactly one output node."),
+            Self::Cycle => write!(f, "The graph contains a cycle."),
+            Self::InvalidPropertyName(name) => {
+                write!(f, "\"{name}\" is not a valid property name.")
+            }
+            Self::DuplicatePropertyName(name) => {
+                write!(f, "There is more than one property named \"{name}\".")
+            }
+            Self::InvalidSwizzle(mask) => write!(f, "\"{mask}\" is not a valid swizzle mask."),
+            Self::Template(err) => write!(f, "Unable to parse the shader template: {err}"),
+            Self::Serialization(err) => write!(f, "Unable to serialize the shader: {err}"),
+        }
+    }
+}
+
+/// A graph that describes surface parameters of a material.
+#[derive(Clone, Debug, Default, Visit)]
+pub struct ShaderGraph {
+    pub nodes: Pool<ShaderGraphNode>,
+}
+
+/// Names of the resources of the standard shader that are used by the generated shader as well.
+const PRESERVED_RESOURCES: &[&str] = &["blendShapesStorage"];
+
+/// A line of the shadow passes of the standard shader that samples a texture which might not exist
+/// in the generated shader.
+const SHADOW_ALPHA_TEST: &str = "if (texture(diffuseTexture, texCoord).a < 0.2) discard;";
+
+const GBUFFER_FRAGMENT_HEADER: &str = r#"
+layout(location = 0) out vec4 outColor;
+layout(location = 1) out vec4 outNormal;
+layout(location = 2) out vec4 outAmbient;
+layout(location = 3) out vec4 outMaterial;
+layout(location = 4) out uint outDecalMask;
+layout(location = 5) out vec4 outMotion;
+
+in vec3 position;
+in vec3 normal;
+in vec2 texCoord;
+in vec3 tangent;
+in vec3 binormal;
+in vec2 secondTexCoord;
+in vec4 clipPosition;
+in vec4 previousClipPosition;
+
+void main()
+{
+    if (S_LodFadeDiscard(fyrox_instanceData.lodFade, gl_FragCoord.xy)) discard;
+
+    mat3 tangentSpace = mat3(tangent, binormal, normal);
+    vec3 toFragment = normalize(position - fyrox_cameraData.position);
+"#;
+
+const GBUFFER_FRAGMENT_FOOTER: &str = r#"
+    if (alpha < 0.5) {
+        discard;
+    }
+
+    outColor = vec4(albedo, 1.0);
+    outNormal = vec4(surfaceNormal * 0.5 + 0.5, 1.0);
+    outMaterial = vec4(metallic, roughness, occlusion, 1.0);
+    outAmbient = vec4(emission, 1.0);
+    outDecalMask = 0u;
+    outMotion = vec4(S_MotionVector(clipPosition, previousClipPosition, fyrox_cameraData.jitter), 1.0, 1.0);
+}
+"#;
+
+#[derive(Default)]
+struct CodeGenerator {
+    values: FxHashMap<Handle<ShaderGraphNode>, (String, ValueType)>,
+    visiting: FxHashSet<Handle<ShaderGraphNode>>,
+    body: String,
+}
+
+impl CodeGenerator {
+    fn inputs(
+        &mut self,
+        graph: &ShaderGraph,
+        node: &ShaderGraphNode,
+    ) -> Result<Vec<(String, ValueType)>, ShaderGraphError> {
+        node.kind
+            .inputs()
+            .iter()
+            .enumerate()
+            .map(|(i, definition)| {
+                let source = node.inputs.get(i).cloned().unwrap_or_default();
+                if graph.nodes.is_valid_handle(source) && graph.nodes[source].kind.has_output() {
+                    self.value(graph, source)
+                } else {
+                    Ok((
+                        definition.default.to_string(),
+                        definition.ty.unwrap_or(ValueType::Float),
+                    ))
+                }
+            })
+            .collect()
+    }
+
+    fn value(
+        &mut self,
+        graph: &ShaderGraph,
+        handle: Handle<ShaderGraphNode>,
+    ) -> Result<(String, ValueType), ShaderGraphError> {
+        if let Some(value) = self.values.get(&handle) {
+            return Ok(value.clone());
+        }
+
+        if !self.visiting.insert(handle) {
+            return Err(ShaderGraphError::Cycle);
+        }
+
+        let node = &graph.nodes[handle];
+        let inputs = self.inputs(graph, node)?;
+        let (expr, ty) = node.kind.expression(&inputs)?;
+        let name = format!("node{}", handle.index());
+        writeln!(self.body, "    {} {name} = {expr};", ty.glsl()).unwrap();
+
+        self.visiting.remove(&handle);
+        self.values.insert(handle, (name.clone(), ty));
+
+        Ok((name, ty))
+    }
+}
+
+impl ShaderGraph {
+    /// Creates a new graph with a single output node.
+    pub fn new() -> Self {
+        let mut nodes = Pool::new();
+        nodes.spawn(ShaderGraphNode::new(
+            ShaderGraphNodeKind::Output,
+            Vector2::new(400.0, 100.0),
+        ));
+        Self { nodes }
+    }
+
+    /// Loads a graph from the given file.
+    pub async fn load(path: &Path) -> Result<Self, VisitError> {
+        let mut visitor = Visitor::load_binary(path).await?;
+        let mut graph = Self::default();
+        graph.visit("ShaderGraph", &mut visitor)?;
+        Ok(graph)
+    }
+
+    /// Saves the graph to the given file.
+    pub fn save(&mut self, path: &Path) -> VisitResult {
+        let mut visitor = Visitor::new();
+        self.visit("ShaderGraph", &mut visitor)?;
+        visitor.save_binary(path)
+    }
+
+    /// Removes a node and breaks every connection to it.
+    pub fn remove_node(&mut self, handle: Handle<ShaderGraphNode>) {
+        self.nodes.free(handle);
+        for node in self.nodes.iter_mut() {
+            for input in node.inputs.iter_mut() {
+                if *input == handle {
+                    *input = Handle::NONE;
+                }
+            }
+        }
+    }
+
+    fn output(&self) -> Result<&ShaderGraphNode, ShaderGraphError> {
+        let mut outputs = self
+            .nodes
+            .iter()
+            .filter(|node| node.kind == ShaderGraphNodeKind::Output);
+        let output = outputs.next().ok_or(ShaderGraphError::NoOutput)?;
+        if outputs.next().is_some() {
+            return Err(ShaderGraphError::MultipleOutputs);
+        }
+        Ok(output)
+    }
+
+    fn generate_resources(&self) -> Result<Vec<ShaderResourceDefinition>, ShaderGraphError> {
+        let mut names = FxHashSet::default();
+        let mut textures = Vec::new();
+        let mut properties = Vec::new();
+        for node in self.nodes.iter() {
+            let Some(name) = node.kind.property_name() else {
+                continue;
+            };
+
+            if !is_valid_identifier(name)
+                || name == "properties"
+                || PRESERVED_RESOURCES.contains(&name)
+            {
+                return Err(ShaderGraphError::InvalidPropertyName(name.to_string()));
+            }
+            if !names.insert(name) {
+                return Err(ShaderGraphError::DuplicatePropertyName(name.to_string()));
+            }
+
+            match node.kind {
+                ShaderGraphNodeKind::FloatProperty { value, .. } => properties.push(
+                    ShaderProperty::new(name, ShaderPropertyKind::Float { value }),
+                ),
+                ShaderGraphNodeKind::ColorProperty { value, .. } => {
+                    properties.push(ShaderProperty::new(
+                        name,
+                        ShaderPropertyKind::Color {
+                            r: value.r,
+                            g: value.g,
+                            b: value.b,
+                            a: value.a,
+                        },
+                    ))
+                }
+                ShaderGraphNodeKind::TextureProperty { fallback, .. } => {
+                    textures.push(ShaderResourceDefinition {
+                        name: name.into(),
+                        kind: ShaderResourceKind::Texture {
+                            kind: SamplerKind::Sampler2D,
+                            fallback,
+                        },
+                        binding: textures.len(),
+                    })
+                }
+                _ => (),
+            }
+        }
+
+        if !properties.is_empty() {
+            textures.push(ShaderResourceDefinition {
+                name: "properties".into(),
+                kind: ShaderResourceKind::PropertyGroup(properties),
+                binding: 0,
+            });
+        }
+
+        Ok(textures)
+    }
+
+    /// Generates fragment shader of the G-Buffer pass.
+    pub fn generate_fragment_shader(&self) -> Result<String, ShaderGraphError> {
+        let output = self.output()?;
+        let mut generator = CodeGenerator::default();
+        let inputs = generator.inputs(self, output)?;
+
+        let mut source = GBUFFER_FRAGMENT_HEADER.to_string();
+        source += &generator.body;
+        for ((expr, ty), (definition, name)) in inputs.iter().zip(OUTPUT_INPUTS.iter().zip([
+            "albedo",
+            "alpha",
+            "surfaceNormal",
+            "metallic",
+            "roughness",
+            "occlusion",
+            "emission",
+        ])) {
+            let target = definition.ty.unwrap();
+            writeln!(
+                source,
+                "    {} {name} = {};",
+                target.glsl(),
+                ty.convert(expr, target)
+            )
+            .unwrap();
+        }
+        source += GBUFFER_FRAGMENT_FOOTER;
+
+        Ok(source)
+    }
+
+    /// Generates a shader definition out of the graph. The definition uses the standard shader as a
+    /// template: it keeps its vertex shader and shadow passes, and replaces the fragment shader of
+    /// the G-Buffer pass with the code generated from the graph. Forward pass is disabled, because
+    /// the generated materials are opaque.
+    pub fn generate(&self, name: &str) -> Result<ShaderDefinition, ShaderGraphError> {
+        let fragment_shader = self.generate_fragment_shader()?;
+
+        let mut definition: ShaderDefinition =
+            ron::de::from_str(STANDARD_SHADER_SRC).map_err(ShaderGraphError::Template)?;
+        definition.name = name.to_string();
+
+        let mut resources = self.generate_resources()?;
+        let texture_count = resources
+            .iter()
+            .filter(|r| matches!(r.kind, ShaderResourceKind::Texture { .. }))
+            .count();
+        for mut resource in definition.resources.drain(..) {
+            if PRESERVED_RESOURCES.contains(&resource.name.as_str()) {
+                resource.binding = texture_count;
+                resources.push(resource);
+            } else if resource.is_built_in() {
+                resources.push(resource);
+            }
+        }
+        definition.resources = resources;
+
+        definition.passes.retain(|pass| pass.name != "Forward");
+        for pass in definition.passes.iter_mut() {
+            if pass.name == "GBuffer" {
+                pass.fragment_shader.clone_from(&fragment_shader);
+            } else {
+                pass.fragment_shader = pass.fragment_shader.replace(SHADOW_ALPHA_TEST, "");
+            }
+        }
+        definition.disabled_passes.push("Forward".to_string());
+
+        Ok(definition)
+    }
+
+    /// Generates a shader definition and serializes it into the format that can be loaded by the
+    /// resource manager.
+    pub fn generate_source(&self, name: &str) -> Result<String, ShaderGraphError> {
+        let definition = self.generate(name)?;
+        ron::ser::to_string_pretty(&definition, PrettyConfig::default())
+            .map_err(ShaderGraphError::Serialization)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_default_graph() {
+        let graph = ShaderGraph::new();
+        let definition = graph.generate("Test").unwrap();
+        assert!(definition.disabled_passes.contains(&"Forward".to_string()));
+        assert!(definition.passes.iter().all(|p| p.name != "Forward"));
+        assert!(definition
+            .resources
+            .iter()
+            .all(|r| r.is_built_in() || r.name.as_str() == "blendShapesStorage"));
+        assert!(definition
+            .passes
+            .iter()
+            .all(|p| !p.fragment_shader.contains("diffuseTexture")));
+    }
+
+    #[test]
+    fn test_texture_and_properties() {
+        let mut graph = ShaderGraph::new();
+        let output = graph.nodes.handle_from_index(0);
+        let texture = graph.nodes.spawn(ShaderGraphNode::new(
+            ShaderGraphNodeKind::TextureProperty {
+                name: "albedoTexture".to_string(),
+                fallback: SamplerFallback::White,
+            },
+            Default::default(),
+        ));
+        let tint = graph.nodes.spawn(ShaderGraphNode::new(
+            ShaderGraphNodeKind::ColorProperty {
+                name: "tint".to_string(),
+                value: Color::WHITE,
+            },
+            Default::default(),
+        ));
+        let multiply = graph.nodes.spawn(ShaderGraphNode::new(
+            ShaderGraphNodeKind::Multiply,
+            Default::default(),
+        ));
+        graph.nodes[multiply].inputs = vec![texture, tint];
+        graph.nodes[output].inputs[0] = multiply;
+
+        let source = graph.generate_fragment_shader().unwrap();
+        assert!(source.contains("vec4 node1 = texture(albedoTexture, texCoord);"));
+        assert!(source.contains("vec4 node3 = node1 * node2;"));
+        assert!(source.contains("vec3 albedo = (node3).xyz;"));
+
+        let definition = graph.generate("Test").unwrap();
+        let texture = definition
+            .resources
+            .iter()
+            .find(|r| r.name.as_str() == "albedoTexture")
+            .unwrap();
+        assert_eq!(texture.binding, 0);
+        let storage = definition
+            .resources
+            .iter()
+            .find(|r| r.name.as_str() == "blendShapesStorage")
+            .unwrap();
+        assert_eq!(storage.binding, 1);
+        assert!(definition
+            .resources
+            .iter()
+            .any(|r| r.name.as_str() == "properties"));
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut graph = ShaderGraph::new();
+        let output = graph.nodes.handle_from_index(0);
+        let a = graph.nodes.spawn(ShaderGraphNode::new(
+            ShaderGraphNodeKind::Add,
+            Default::default(),
+        ));
+        let b = graph.nodes.spawn(ShaderGraphNode::new(
+            ShaderGraphNodeKind::Add,
+            Default::default(),
+        ));
+        graph.nodes[a].inputs[0] = b;
+        graph.nodes[b].inputs[0] = a;
+        graph.nodes[output].inputs[1] = a;
+        assert!(matches!(
+            graph.generate_fragment_shader(),
+            Err(ShaderGraphError::Cycle)
+        ));
+
+        graph.remove_node(b);
+        graph.nodes.spawn(ShaderGraphNode::new(
+            ShaderGraphNodeKind::FloatProperty {
+                name: "1abc".to_string(),
+                value: 0.0,
+            },
+            Default::default(),
+        ));
+        assert!(matches!(
+            graph.generate("Test"),
+            Err(ShaderGraphError::InvalidPropertyName(_))
+        ));
+    }
+}
@@ -0,0 +1,961 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Shader graph editor allows to create shaders by connecting nodes on a canvas, instead of writing
+//! GLSL code by hand. See [`graph`] module docs for more info about the generated shaders.
+
+use crate::{
+    fyrox::{
+        asset::untyped::ResourceKind,
+        core::{
+            algebra::Matrix4, futures::executor::block_on, log::Log, pool::Handle, some_or_return,
+        },
+        engine::Engine,
+        graph::BaseSceneGraph,
+        gui::{
+            border::BorderBuilder,
+            dock::DockingManagerMessage,
+            file_browser::{FileBrowserMode, FileSelectorMessage},
+            grid::{Column, GridBuilder, Row},
+            inspector::{
+                editors::{
+                    enumeration::EnumPropertyEditorDefinition, PropertyEditorDefinitionContainer,
+                },
+                Inspector, InspectorBuilder, InspectorContext, InspectorMessage, PropertyAction,
+            },
+            menu::{
+                ContextMenuBuilder, MenuBuilder, MenuItemBuilder, MenuItemContent, MenuItemMessage,
+            },
+            message::{MessageDirection, UiMessage},
+            popup::{Placement, PopupBuilder, PopupMessage},
+            scroll_viewer::ScrollViewerBuilder,
+            stack_panel::StackPanelBuilder,
+            style::{resource::StyleResourceExt, Style},
+            text::{TextBuilder, TextMessage},
+            widget::{WidgetBuilder, WidgetMessage},
+            window::{WindowBuilder, WindowMessage, WindowTitle},
+            BuildContext, RcUiNodeHandle, Thickness, UiNode, UserInterface,
+        },
+        material::{
+            shader::{SamplerFallback, ShaderResource, ShaderResourceExtension},
+            Material, MaterialResource,
+        },
+        scene::{
+            base::BaseBuilder,
+            mesh::{
+                surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+                MeshBuilder,
+            },
+        },
+    },
+    menu::create_menu_item,
+    plugin::EditorPlugin,
+    plugins::{
+        absm::{
+            canvas::{AbsmCanvasBuilder, AbsmCanvasMessage},
+            connection::{Connection, ConnectionBuilder},
+            node::{AbsmNode, AbsmNodeBuilder, AbsmNodeMessage},
+            socket::{Socket, SocketBuilder, SocketDirection},
+        },
+        shader_graph::graph::{ShaderGraph, ShaderGraphNode, ShaderGraphNodeKind},
+    },
+    preview::PreviewPanel,
+    send_sync_message,
+    utils::create_file_selector,
+    Editor, Message, MSG_SYNC_FLAG,
+};
+use std::{path::PathBuf, str::FromStr, sync::Arc};
+
+pub mod graph;
+
+const NODE_CATEGORIES: &[(&str, &[&str])] = &[
+    (
+        "Input",
+        &[
+            "TexCoord",
+            "WorldPosition",
+            "WorldNormal",
+            "ViewDirection",
+            "Float",
+            "Vector",
+        ],
+    ),
+    (
+        "Property",
+        &["FloatProperty", "ColorProperty", "TextureProperty"],
+    ),
+    (
+        "Math",
+        &[
+            "Add",
+            "Subtract",
+            "Multiply",
+            "Divide",
+            "Power",
+            "Lerp",
+            "OneMinus",
+            "Saturate",
+            "Normalize",
+            "Dot",
+            "Swizzle",
+            "Combine",
+        ],
+    ),
+    ("Surface", &["UnpackNormal", "Fresnel", "Output"]),
+];
+
+struct CanvasContextMenu {
+    menu: RcUiNodeHandle,
+    items: Vec<(Handle<UiNode>, &'static str)>,
+}
+
+impl CanvasContextMenu {
+    fn new(ctx: &mut BuildContext) -> Self {
+        let mut items = Vec::new();
+        let categories = NODE_CATEGORIES
+            .iter()
+            .map(|(category, kinds)| {
+                let kind_items = kinds
+                    .iter()
+                    .map(|kind| {
+                        let item = create_menu_item(kind, vec![], ctx);
+                        items.push((item, *kind));
+                        item
+                    })
+                    .collect();
+                create_menu_item(category, kind_items, ctx)
+            })
+            .collect::<Vec<_>>();
+
+        let menu = ContextMenuBuilder::new(
+            PopupBuilder::new(WidgetBuilder::new().with_visibility(false)).with_content(
+                StackPanelBuilder::new(WidgetBuilder::new().with_children(categories)).build(ctx),
+            ),
+        )
+        .build(ctx);
+        let menu = RcUiNodeHandle::new(menu, ctx.sender());
+
+        Self { menu, items }
+    }
+}
+
+struct RemoveContextMenu {
+    menu: RcUiNodeHandle,
+    remove: Handle<UiNode>,
+    placement_target: Handle<UiNode>,
+}
+
+impl RemoveContextMenu {
+    fn new(text: &str, ctx: &mut BuildContext) -> Self {
+        let remove;
+        let menu = ContextMenuBuilder::new(
+            PopupBuilder::new(WidgetBuilder::new().with_visibility(false)).with_content(
+                StackPanelBuilder::new(WidgetBuilder::new().with_child({
+                    remove = create_menu_item(text, vec![], ctx);
+                    remove
+                }))
+                .build(ctx),
+            ),
+        )
+        .build(ctx);
+        let menu = RcUiNodeHandle::new(menu, ctx.sender());
+
+        Self {
+            menu,
+            remove,
+            placement_target: Default::default(),
+        }
+    }
+
+    fn handle_ui_message(&mut self, message: &UiMessage) {
+        if let Some(PopupMessage::Placement(Placement::Cursor(target))) = message.data() {
+            if message.destination() == self.menu.handle() {
+                self.placement_target = *target;
+            }
+        }
+    }
+}
+
+struct FileMenu {
+    new: Handle<UiNode>,
+    load: Handle<UiNode>,
+    save: Handle<UiNode>,
+}
+
+pub struct ShaderGraphEditor {
+    window: Handle<UiNode>,
+    canvas: Handle<UiNode>,
+    inspector: Handle<UiNode>,
+    error: Handle<UiNode>,
+    file_menu: FileMenu,
+    canvas_context_menu: CanvasContextMenu,
+    node_context_menu: RemoveContextMenu,
+    connection_context_menu: RemoveContextMenu,
+    load_file_selector: Handle<UiNode>,
+    save_file_selector: Handle<UiNode>,
+    property_editors: Arc<PropertyEditorDefinitionContainer>,
+    preview: PreviewPanel,
+    graph: ShaderGraph,
+    selection: Vec<Handle<ShaderGraphNode>>,
+    path: Option<PathBuf>,
+}
+
+fn create_input_socket(
+    index: usize,
+    name: &str,
+    parent_node: Handle<ShaderGraphNode>,
+    ctx: &mut BuildContext,
+) -> Handle<UiNode> {
+    let label = TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::left(2.0)))
+        .with_text(name)
+        .build(ctx);
+    SocketBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(2.0)))
+        .with_direction(SocketDirection::Input)
+        .with_parent_node(parent_node.into())
+        .with_index(index)
+        .with_show_index(false)
+        .with_editor(label)
+        .build(ctx)
+}
+
+fn fetch_socket_model(
+    socket: Handle<UiNode>,
+    ui: &UserInterface,
+) -> (Handle<ShaderGraphNode>, usize) {
+    let socket_ref = ui.node(socket).query_component::<Socket>().unwrap();
+    (socket_ref.parent_node.into(), socket_ref.index)
+}
+
+impl ShaderGraphEditor {
+    pub fn new(engine: &mut Engine) -> Self {
+        let mut preview = PreviewPanel::new(engine, 300, 300);
+
+        let graph = &mut engine.scenes[preview.scene()].graph;
+        let sphere = MeshBuilder::new(BaseBuilder::new())
+            .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+                ResourceKind::Embedded,
+                SurfaceData::make_sphere(30, 30, 1.0, &Matrix4::identity()),
+            ))
+            .build()])
+            .build(graph);
+        preview.set_model(sphere, engine);
+
+        let ctx = &mut engine.user_interfaces.first_mut().build_ctx();
+
+        let property_editors = PropertyEditorDefinitionContainer::with_default_editors();
+        property_editors.insert(EnumPropertyEditorDefinition::<ShaderGraphNodeKind>::new());
+        property_editors.insert(EnumPropertyEditorDefinition::<SamplerFallback>::new());
+
+        let load_file_selector = create_file_selector(ctx, "shadergraph", FileBrowserMode::Open);
+        let save_file_selector = create_file_selector(
+            ctx,
+            "shadergraph",
+            FileBrowserMode::Save {
+                default_file_name: PathBuf::from("unnamed.shadergraph"),
+            },
+        );
+
+        let canvas_context_menu = CanvasContextMenu::new(ctx);
+        let node_context_menu = RemoveContextMenu::new("Remove", ctx);
+        let connection_context_menu = RemoveContextMenu::new("Remove Connection", ctx);
+
+        let new;
+        let load;
+        let save;
+        let canvas;
+        let inspector;
+        let error;
+        let panel;
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(1000.0).with_height(700.0))
+            .open(false)
+            .with_title(WindowTitle::text("Shader Graph Editor"))
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child(
+                            MenuBuilder::new(WidgetBuilder::new().on_row(0))
+                                .with_items(vec![MenuItemBuilder::new(WidgetBuilder::new())
+                                    .with_content(MenuItemContent::text("File"))
+                                    .with_items(vec![
+                                        {
+                                            new = create_menu_item("New", vec![], ctx);
+                                            new
+                                        },
+                                        {
+                                            load = create_menu_item("Load...", vec![], ctx);
+                                            load
+                                        },
+                                        {
+                                            save = create_menu_item("Save", vec![], ctx);
+                                            save
+                                        },
+                                    ])
+                                    .build(ctx)])
+                                .build(ctx),
+                        )
+                        .with_child(
+                            GridBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(1)
+                                    .with_child(
+                                        BorderBuilder::new(
+                                            WidgetBuilder::new()
+                                                .on_column(0)
+                                                .with_margin(Thickness::uniform(1.0))
+                                                .with_background(
+                                                    ctx.style.property(Style::BRUSH_DARKEST),
+                                                )
+                                                .with_child({
+                                                    canvas = AbsmCanvasBuilder::new(
+                                                        WidgetBuilder::new().with_context_menu(
+                                                            canvas_context_menu.menu.clone(),
+                                                        ),
+                                                    )
+                                                    .build(ctx);
+                                                    canvas
+                                                }),
+                                        )
+                                        .build(ctx),
+                                    )
+                                    .with_child(
+                                        GridBuilder::new(
+                                            WidgetBuilder::new()
+                                                .on_column(1)
+                                                .with_child(
+                                                    ScrollViewerBuilder::new(
+                                                        WidgetBuilder::new().on_row(0),
+                                                    )
+                                                    .with_content({
+                                                        inspector = InspectorBuilder::new(
+                                                            WidgetBuilder::new(),
+                                                        )
+                                                        .build(ctx);
+                                                        inspector
+                                                    })
+                                                    .build(ctx),
+                                                )
+                                                .with_child({
+                                                    panel = BorderBuilder::new(
+                                                        WidgetBuilder::new().on_row(1),
+                                                    )
+                                                    .build(ctx);
+                                                    panel
+                                                }),
+                                        )
+                                        .add_row(Row::stretch())
+                                        .add_row(Row::strict(300.0))
+                                        .add_column(Column::stretch())
+                                        .build(ctx),
+                                    ),
+                            )
+                            .add_row(Row::stretch())
+                            .add_column(Column::stretch())
+                            .add_column(Column::strict(300.0))
+                            .build(ctx),
+                        )
+                        .with_child({
+                            error = TextBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(2)
+                                    .with_margin(Thickness::uniform(2.0))
+                                    .with_foreground(ctx.style.property(Style::BRUSH_ERROR)),
+                            )
+                            .build(ctx);
+                            error
+                        }),
+                )
+                .add_row(Row::strict(22.0))
+                .add_row(Row::stretch())
+                .add_row(Row::auto())
+                .add_column(Column::stretch())
+                .build(ctx),
+            )
+            .build(ctx);
+
+        ctx.link(preview.root, panel);
+
+        let mut editor = Self {
+            window,
+            canvas,
+            inspector,
+            error,
+            file_menu: FileMenu { new, load, save },
+            canvas_context_menu,
+            node_context_menu,
+            connection_context_menu,
+            load_file_selector,
+            save_file_selector,
+            property_editors: Arc::new(property_editors),
+            preview,
+            graph: Default::default(),
+            selection: Default::default(),
+            path: None,
+        };
+        editor.set_graph(ShaderGraph::new(), None, engine);
+        editor
+    }
+
+    pub fn destroy(self, docking_manager: Handle<UiNode>, engine: &mut Engine) {
+        self.preview.destroy(engine);
+        let ui = engine.user_interfaces.first();
+        ui.send_message(DockingManagerMessage::remove_floating_window(
+            docking_manager,
+            MessageDirection::ToWidget,
+            self.window,
+        ));
+        for widget in [
+            self.window,
+            self.load_file_selector,
+            self.save_file_selector,
+        ] {
+            ui.send_message(WidgetMessage::remove(widget, MessageDirection::ToWidget));
+        }
+    }
+
+    pub fn set_graph(&mut self, graph: ShaderGraph, path: Option<PathBuf>, engine: &mut Engine) {
+        self.graph = graph;
+        self.path = path;
+        self.selection.clear();
+
+        let title = match self.path.as_ref() {
+            Some(path) => format!("Shader Graph Editor - {}", path.display()),
+            None => "Shader Graph Editor - Unnamed Graph".to_string(),
+        };
+        let ui = engine.user_interfaces.first();
+        ui.send_message(WindowMessage::title(
+            self.window,
+            MessageDirection::ToWidget,
+            WindowTitle::text(title),
+        ));
+
+        self.sync_inspector(ui);
+        self.on_graph_changed(true, engine);
+    }
+
+    pub fn load(&mut self, path: PathBuf, engine: &mut Engine) {
+        match block_on(ShaderGraph::load(&path)) {
+            Ok(graph) => self.set_graph(graph, Some(path), engine),
+            Err(err) => Log::err(format!(
+                "Unable to load shader graph from {}. Reason: {err:?}",
+                path.display()
+            )),
+        }
+    }
+
+    fn shader_name(&self) -> String {
+        self.path
+            .as_ref()
+            .and_then(|path| path.file_stem())
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| "ShaderGraph".to_string())
+    }
+
+    /// Saves the graph and writes the generated shader next to it, so the resource manager could
+    /// load it as any other shader.
+    fn save(&mut self) {
+        let path = some_or_return!(self.path.clone());
+
+        Log::verify(self.graph.save(&path));
+
+        match self.graph.generate_source(&self.shader_name()) {
+            Ok(source) => Log::verify(std::fs::write(path.with_extension("shader"), source)),
+            Err(err) => Log::err(format!(
+                "Shader graph was saved, but the shader was not generated. Reason: {err}"
+            )),
+        }
+    }
+
+    /// Regenerates the shader for the preview and, if `rebuild` is set, recreates the views of the
+    /// graph on the canvas.
+    fn on_graph_changed(&mut self, rebuild: bool, engine: &mut Engine) {
+        let ui = engine.user_interfaces.first_mut();
+
+        if rebuild {
+            self.sync_canvas(ui);
+        } else {
+            for &view in ui.node(self.canvas).children() {
+                if let Some(view_ref) = ui.node(view).query_component::<AbsmNode<ShaderGraphNode>>()
+                {
+                    if let Some(node) = self.graph.nodes.try_borrow(view_ref.model_handle) {
+                        send_sync_message(
+                            ui,
+                            AbsmNodeMessage::name(
+                                view,
+                                MessageDirection::ToWidget,
+                                node.kind.description(),
+                            ),
+                        );
+                    }
+                }
+            }
+        }
+
+        let source = match self.graph.generate_source(&self.shader_name()) {
+            Ok(source) => source,
+            Err(err) => {
+                ui.send_message(TextMessage::text(
+                    self.error,
+                    MessageDirection::ToWidget,
+                    err.to_string(),
+                ));
+                return;
+            }
+        };
+
+        match ShaderResource::from_str(&source, ResourceKind::Embedded) {
+            Ok(shader) => {
+                let material =
+                    MaterialResource::new_ok(ResourceKind::Embedded, Material::from_shader(shader));
+
+                ui.send_message(TextMessage::text(
+                    self.error,
+                    MessageDirection::ToWidget,
+                    Default::default(),
+                ));
+
+                engine.scenes[self.preview.scene()].graph[self.preview.model()]
+                    .as_mesh_mut()
+                    .surfaces_mut()
+                    .first_mut()
+                    .unwrap()
+                    .set_material(material);
+            }
+            Err(err) => ui.send_message(TextMessage::text(
+                self.error,
+                MessageDirection::ToWidget,
+                err.to_string(),
+            )),
+        }
+    }
+
+    /// Recreates every node view and every connection on the canvas.
+    fn sync_canvas(&mut self, ui: &mut UserInterface) {
+        for &child in ui.node(self.canvas).children() {
+            send_sync_message(ui, WidgetMessage::remove(child, MessageDirection::ToWidget));
+        }
+
+        let mut views = Vec::new();
+        for (handle, node) in self.graph.nodes.pair_iter() {
+            let ctx = &mut ui.build_ctx();
+            let input_sockets = node
+                .kind
+                .inputs()
+                .iter()
+                .enumerate()
+                .map(|(i, input)| create_input_socket(i, input.name, handle, ctx))
+                .collect::<Vec<_>>();
+            let output_socket = if node.kind.has_output() {
+                SocketBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(2.0)))
+                    .with_direction(SocketDirection::Output)
+                    .with_parent_node(handle.into())
+                    .with_show_index(false)
+                    .build(ctx)
+            } else {
+                Handle::NONE
+            };
+
+            let view = AbsmNodeBuilder::new(
+                WidgetBuilder::new()
+                    .with_desired_position(node.position)
+                    .with_context_menu(self.node_context_menu.menu.clone()),
+            )
+            .with_title(node.kind.as_ref().to_owned())
+            .with_name(node.kind.description())
+            .with_input_sockets(input_sockets)
+            .with_output_socket(output_socket)
+            .with_normal_brush(ctx.style.property(Style::BRUSH_LIGHTER_PRIMARY))
+            .with_selected_brush(ctx.style.property(Style::BRUSH_LIGHTER))
+            .with_model_handle(handle)
+            .build(ctx);
+
+            send_sync_message(
+                ui,
+                WidgetMessage::link(view, MessageDirection::ToWidget, self.canvas),
+            );
+
+            views.push((handle, view, output_socket));
+        }
+
+        // Force update layout to be able to fetch positions of sockets for connections.
+        ui.update_layout(ui.screen_size());
+
+        for &(handle, dest, _) in views.iter() {
+            let input_sockets = ui
+                .node(dest)
+                .query_component::<AbsmNode<ShaderGraphNode>>()
+                .unwrap()
+                .base
+                .input_sockets
+                .clone();
+
+            for (input, dest_socket) in self.graph.nodes[handle].inputs.iter().zip(input_sockets) {
+                let Some(&(_, source, source_socket)) = views
+                    .iter()
+                    .find(|(model, _, socket)| model == input && socket.is_some())
+                else {
+                    continue;
+                };
+
+                let connection = ConnectionBuilder::new(
+                    WidgetBuilder::new()
+                        .with_context_menu(self.connection_context_menu.menu.clone()),
+                )
+                .with_source_socket(source_socket)
+                .with_source_node(source)
+                .with_dest_socket(dest_socket)
+                .with_dest_node(dest)
+                .build(self.canvas, &mut ui.build_ctx());
+
+                send_sync_message(
+                    ui,
+                    WidgetMessage::link(connection, MessageDirection::ToWidget, self.canvas),
+                );
+                send_sync_message(
+                    ui,
+                    WidgetMessage::lowermost(connection, MessageDirection::ToWidget),
+                );
+            }
+        }
+
+        self.selection
+            .retain(|handle| self.graph.nodes.is_valid_handle(*handle));
+        let selection = views
+            .iter()
+            .filter(|(model, _, _)| self.selection.contains(model))
+            .map(|(_, view, _)| *view)
+            .collect::<Vec<_>>();
+        send_sync_message(
+            ui,
+            AbsmCanvasMessage::selection_changed(
+                self.canvas,
+                MessageDirection::ToWidget,
+                selection,
+            ),
+        );
+        send_sync_message(
+            ui,
+            AbsmCanvasMessage::force_sync_dependent_objects(
+                self.canvas,
+                MessageDirection::ToWidget,
+            ),
+        );
+    }
+
+    fn sync_inspector(&self, ui: &UserInterface) {
+        let context = self
+            .selection
+            .first()
+            .and_then(|handle| self.graph.nodes.try_borrow(*handle))
+            .map(|node| {
+                InspectorContext::from_object(
+                    node,
+                    &mut ui.build_ctx(),
+                    self.property_editors.clone(),
+                    None,
+                    MSG_SYNC_FLAG,
+                    0,
+                    true,
+                    Default::default(),
+                    100.0,
+                )
+            })
+            .unwrap_or_default();
+
+        ui.send_message(InspectorMessage::context(
+            self.inspector,
+            MessageDirection::ToWidget,
+            context,
+        ));
+    }
+
+    fn unique_property_name(&self) -> String {
+        (0..)
+            .map(|i| format!("property{i}"))
+            .find(|name| {
+                self.graph.nodes.iter().all(|node| match &node.kind {
+                    ShaderGraphNodeKind::FloatProperty { name: other, .. }
+                    | ShaderGraphNodeKind::ColorProperty { name: other, .. }
+                    | ShaderGraphNodeKind::TextureProperty { name: other, .. } => other != name,
+                    _ => true,
+                })
+            })
+            .unwrap()
+    }
+
+    fn open_file_selector(&self, file_selector: Handle<UiNode>, ui: &UserInterface) {
+        ui.send_message(FileSelectorMessage::root(
+            file_selector,
+            MessageDirection::ToWidget,
+            Some(std::env::current_dir().unwrap()),
+        ));
+        ui.send_message(WindowMessage::open_modal(
+            file_selector,
+            MessageDirection::ToWidget,
+            true,
+            true,
+        ));
+    }
+
+    fn handle_canvas_message(
+        &mut self,
+        msg: &AbsmCanvasMessage,
+        ui: &UserInterface,
+    ) -> Option<bool> {
+        match msg {
+            AbsmCanvasMessage::CommitDrag { entries } => {
+                for entry in entries {
+                    let view = ui
+                        .node(entry.node)
+                        .query_component::<AbsmNode<ShaderGraphNode>>()?;
+                    self.graph.nodes[view.model_handle].position = view.actual_local_position();
+                }
+                None
+            }
+            AbsmCanvasMessage::CommitConnection {
+                source_socket,
+                dest_socket,
+            } => {
+                let (source, _) = fetch_socket_model(*source_socket, ui);
+                let (dest, index) = fetch_socket_model(*dest_socket, ui);
+                self.graph.nodes[dest].inputs[index] = source;
+                Some(true)
+            }
+            AbsmCanvasMessage::SelectionChanged(selection) => {
+                self.selection = selection
+                    .iter()
+                    .filter_map(|view| {
+                        ui.node(*view)
+                            .query_component::<AbsmNode<ShaderGraphNode>>()
+                            .map(|view| view.model_handle)
+                    })
+                    .collect();
+                self.sync_inspector(ui);
+                None
+            }
+            _ => None,
+        }
+    }
+
+    pub fn handle_ui_message(&mut self, message: &UiMessage, engine: &mut Engine) {
+        self.preview.handle_message(message, engine);
+        self.node_context_menu.handle_ui_message(message);
+        self.connection_context_menu.handle_ui_message(message);
+
+        let ui = engine.user_interfaces.first();
+
+        // `Some(rebuild)` means that the graph was changed and the views must be rebuilt if the
+        // flag is set.
+        let mut changed = None;
+
+        if let Some(msg) = message.data::<AbsmCanvasMessage>() {
+            if message.destination() == self.canvas
+                && message.direction() == MessageDirection::FromWidget
+            {
+                changed = self.handle_canvas_message(msg, ui);
+            }
+        } else if let Some(InspectorMessage::PropertyChanged(msg)) = message.data() {
+            if message.destination() == self.inspector
+                && message.direction() == MessageDirection::FromWidget
+            {
+                if let Some(node) = self
+                    .selection
+                    .first()
+                    .and_then(|handle| self.graph.nodes.try_borrow_mut(*handle))
+                {
+                    let prev_kind = std::mem::discriminant(&node.kind);
+
+                    PropertyAction::from_field_kind(&msg.value).apply(
+                        &msg.path(),
+                        node,
+                        &mut |result| {
+                            Log::verify(result);
+                        },
+                    );
+
+                    node.sync_inputs();
+
+                    // Changing the kind of a node changes its sockets, so its view must be rebuilt.
+                    changed = Some(prev_kind != std::mem::discriminant(&node.kind));
+                }
+            }
+        } else if let Some(MenuItemMessage::Click) = message.data() {
+            let destination = message.destination();
+            if destination == self.file_menu.new {
+                self.set_graph(ShaderGraph::new(), None, engine);
+                return;
+            } else if destination == self.file_menu.load {
+                self.open_file_selector(self.load_file_selector, ui);
+            } else if destination == self.file_menu.save {
+                if self.path.is_some() {
+                    self.save();
+                } else {
+                    self.open_file_selector(self.save_file_selector, ui);
+                }
+            } else if destination == self.node_context_menu.remove {
+                let mut removed = self.selection.clone();
+                if let Some(view) = ui
+                    .try_get(self.node_context_menu.placement_target)
+                    .and_then(|n| n.query_component::<AbsmNode<ShaderGraphNode>>())
+                {
+                    if !removed.contains(&view.model_handle) {
+                        removed = vec![view.model_handle];
+                    }
+                }
+                for handle in removed {
+                    if self.graph.nodes.is_valid_handle(handle) {
+                        self.graph.remove_node(handle);
+                    }
+                }
+                self.selection.clear();
+                self.sync_inspector(ui);
+                changed = Some(true);
+            } else if destination == self.connection_context_menu.remove {
+                if let Some(connection) = ui
+                    .try_get(self.connection_context_menu.placement_target)
+                    .and_then(|n| n.query_component::<Connection>())
+                {
+                    let (dest, index) = fetch_socket_model(connection.segment.dest, ui);
+                    self.graph.nodes[dest].inputs[index] = Handle::NONE;
+                    changed = Some(true);
+                }
+            } else if let Some(&(_, kind)) = self
+                .canvas_context_menu
+                .items
+                .iter()
+                .find(|(item, _)| *item == destination)
+            {
+                let position = ui.node(self.canvas).screen_to_local(
+                    ui.node(self.canvas_context_menu.menu.handle())
+                        .screen_position(),
+                );
+                let mut kind = ShaderGraphNodeKind::from_str(kind).unwrap();
+                match kind {
+                    ShaderGraphNodeKind::FloatProperty { ref mut name, .. }
+                    | ShaderGraphNodeKind::ColorProperty { ref mut name, .. }
+                    | ShaderGraphNodeKind::TextureProperty { ref mut name, .. } => {
+                        *name = self.unique_property_name()
+                    }
+                    ShaderGraphNodeKind::Swizzle { ref mut mask } => *mask = "xyz".to_string(),
+                    ShaderGraphNodeKind::Fresnel { ref mut power } => *power = 5.0,
+                    _ => (),
+                }
+                self.graph.nodes.spawn(ShaderGraphNode::new(kind, position));
+                changed = Some(true);
+            }
+        } else if let Some(FileSelectorMessage::Commit(path)) = message.data() {
+            if message.destination() == self.load_file_selector {
+                self.load(path.clone(), engine);
+                return;
+            } else if message.destination() == self.save_file_selector {
+                self.path = Some(path.clone());
+                self.save();
+                ui.send_message(WindowMessage::title(
+                    self.window,
+                    MessageDirection::ToWidget,
+                    WindowTitle::text(format!("Shader Graph Editor - {}", path.display())),
+                ));
+            }
+        }
+
+        if let Some(rebuild) = changed {
+            self.on_graph_changed(rebuild, engine);
+        }
+    }
+
+    pub fn update(&mut self, engine: &mut Engine) {
+        self.preview.update(engine)
+    }
+}
+
+#[derive(Default)]
+pub struct ShaderGraphPlugin {
+    editor: Option<ShaderGraphEditor>,
+    open_editor: Handle<UiNode>,
+}
+
+impl ShaderGraphPlugin {
+    fn open_editor(&mut self, path: Option<PathBuf>, editor: &mut Editor) {
+        let engine = &mut editor.engine;
+        let shader_graph_editor = self
+            .editor
+            .get_or_insert_with(|| ShaderGraphEditor::new(engine));
+
+        if let Some(path) = path {
+            shader_graph_editor.load(path, engine);
+        }
+
+        let ui = engine.user_interfaces.first();
+        ui.send_message(WindowMessage::open(
+            shader_graph_editor.window,
+            MessageDirection::ToWidget,
+            true,
+            true,
+        ));
+        ui.send_message(DockingManagerMessage::add_floating_window(
+            editor.docking_manager,
+            MessageDirection::ToWidget,
+            shader_graph_editor.window,
+        ));
+    }
+}
+
+impl EditorPlugin for ShaderGraphPlugin {
+    fn on_start(&mut self, editor: &mut Editor) {
+        let ui = editor.engine.user_interfaces.first_mut();
+        let ctx = &mut ui.build_ctx();
+        self.open_editor = create_menu_item("Shader Graph Editor", vec![], ctx);
+        ui.send_message(MenuItemMessage::add_item(
+            editor.menu.utils_menu.menu,
+            MessageDirection::ToWidget,
+            self.open_editor,
+        ));
+    }
+
+    fn on_ui_message(&mut self, message: &mut UiMessage, editor: &mut Editor) {
+        if let Some(MenuItemMessage::Click) = message.data() {
+            if message.destination() == self.open_editor {
+                self.open_editor(None, editor);
+            }
+        }
+
+        let mut shader_graph_editor = some_or_return!(self.editor.take());
+
+        shader_graph_editor.handle_ui_message(message, &mut editor.engine);
+
+        if let Some(WindowMessage::Close) = message.data() {
+            if message.destination() == shader_graph_editor.window {
+                shader_graph_editor.destroy(editor.docking_manager, &mut editor.engine);
+                return;
+            }
+        }
+
+        self.editor = Some(shader_graph_editor);
+    }
+
+    fn on_update(&mut self, editor: &mut Editor) {
+        let shader_graph_editor = some_or_return!(self.editor.as_mut());
+        shader_graph_editor.update(&mut editor.engine);
+    }
+
+    fn on_message(&mut self, message: &Message, editor: &mut Editor) {
+        if let Message::OpenShaderGraphEditor(path) = message {
+            self.open_editor(Some(path.clone()), editor);
+        }
+    }
+}
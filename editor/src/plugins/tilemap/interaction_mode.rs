@@ -232,6 +232,19 @@ impl TileMapInteractionMode {
     }
 }
 
+/// Replace tiles of the update that belong to auto-tile rules of the active brush, along with
+/// their neighbours, by the tiles that match their surroundings.
+fn apply_brush_auto_tiles(tile_map: &TileMap, update: &mut TilesUpdate) {
+    let (Some(brush), Some(tiles)) = (tile_map.active_brush(), tile_map.tiles()) else {
+        return;
+    };
+    let brush = brush.data_ref();
+    let tiles = tiles.data_ref();
+    if let (Some(brush), Some(tiles)) = (brush.as_loaded_ref(), tiles.as_loaded_ref()) {
+        update.apply_auto_tile_rules(tiles, &brush.auto_tile_rules);
+    }
+}
+
 fn update_select(
     tile_map: &TileMap,
     selected: &mut FxHashSet<Vector2<i32>>,
@@ -456,8 +469,9 @@ impl InteractionMode for TileMapInteractionMode {
                         .clone_from(&self.select_effect.lock().positions);
                 } else if let Some(tile_set) = state.tile_set.as_ref().or(tile_map.tile_set()) {
                     let update_source = &mut self.update_effect.lock().update;
-                    let update =
+                    let mut update =
                         update_source.build_tiles_update(&TileSetRef::new(tile_set).as_loaded());
+                    apply_brush_auto_tiles(tile_map, &mut update);
                     self.sender.do_command(SetMapTilesCommand {
                         tile_map: tile_map_handle,
                         tiles: update,
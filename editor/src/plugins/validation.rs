@@ -0,0 +1,699 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Scene validation panel. It scans the current scene using a set of validation rules and shows
+//! found problems in a list. Clicking an issue selects the problematic node, some issues could
+//! also be fixed automatically. Plugins could add their own rules using
+//! [`SceneValidationPlugin::add_rule`].
+
+use crate::{
+    command::{Command, CommandTrait},
+    fyrox::{
+        asset::{collect_used_resources, state::ResourceState, untyped::ResourceKind},
+        core::{algebra::Vector3, math::Matrix4Ext, pool::Handle, reflect::prelude::*},
+        fxhash::{FxHashMap, FxHashSet},
+        graph::{BaseSceneGraph, SceneGraph},
+        gui::{
+            border::BorderBuilder,
+            button::{ButtonBuilder, ButtonMessage},
+            decorator::DecoratorBuilder,
+            grid::{Column, GridBuilder, Row},
+            list_view::{ListViewBuilder, ListViewMessage},
+            menu::MenuItemMessage,
+            message::{MessageDirection, UiMessage},
+            scroll_viewer::ScrollViewerBuilder,
+            style::{resource::StyleResourceExt, Style},
+            text::{TextBuilder, TextMessage},
+            widget::{WidgetBuilder, WidgetMessage},
+            window::{WindowBuilder, WindowMessage, WindowTitle},
+            BuildContext, HorizontalAlignment, Thickness, UiNode, VerticalAlignment,
+        },
+        scene::{
+            collider::Collider, dim2, light::BaseLight, node::Node, rigidbody::RigidBody, Scene,
+        },
+    },
+    menu::create_menu_item,
+    plugin::EditorPlugin,
+    scene::{
+        commands::{graph::ScaleNodeCommand, ChangeSelectionCommand},
+        GameScene, Selection,
+    },
+    world::graph::selection::GraphSelection,
+    Editor, Message,
+};
+use std::fmt::{Display, Formatter};
+
+/// Severity of a validation issue.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ValidationSeverity {
+    /// The scene works, but most likely not as intended.
+    Warning,
+    /// The scene is broken and will most likely fail at runtime.
+    Error,
+}
+
+impl Display for ValidationSeverity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationSeverity::Warning => write!(f, "Warning"),
+            ValidationSeverity::Error => write!(f, "Error"),
+        }
+    }
+}
+
+/// An automatic fix of a validation issue. The fix is performed as a regular editor command, so
+/// it could be undone.
+pub struct ValidationFix {
+    /// Short description of the fix, it is used as a caption of the fix button.
+    pub description: String,
+    /// A command that fixes the issue.
+    pub command: Command,
+}
+
+/// A single problem found by a validation rule.
+pub struct ValidationIssue {
+    /// A node that has the problem. Clicking the issue in the panel selects this node.
+    pub node: Handle<Node>,
+    /// Severity of the issue.
+    pub severity: ValidationSeverity,
+    /// Human-readable description of the issue.
+    pub message: String,
+    /// Optional automatic fix of the issue.
+    pub fix: Option<ValidationFix>,
+}
+
+impl ValidationIssue {
+    /// Creates a new warning for the given node.
+    pub fn warning(node: Handle<Node>, message: impl Into<String>) -> Self {
+        Self {
+            node,
+            severity: ValidationSeverity::Warning,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    /// Creates a new error for the given node.
+    pub fn error(node: Handle<Node>, message: impl Into<String>) -> Self {
+        Self {
+            node,
+            severity: ValidationSeverity::Error,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    /// Attaches an automatic fix to the issue.
+    pub fn with_fix<C: CommandTrait>(mut self, description: impl Into<String>, command: C) -> Self {
+        self.fix = Some(ValidationFix {
+            description: description.into(),
+            command: Command::new(command),
+        });
+        self
+    }
+}
+
+/// Everything a validation rule needs to check a scene.
+pub struct ValidationContext<'a> {
+    /// The scene being validated.
+    pub scene: &'a Scene,
+    /// Editor-specific data of the scene.
+    pub game_scene: &'a GameScene,
+    editor_nodes: FxHashSet<Handle<Node>>,
+}
+
+impl<'a> ValidationContext<'a> {
+    pub fn new(scene: &'a Scene, game_scene: &'a GameScene) -> Self {
+        Self {
+            scene,
+            game_scene,
+            editor_nodes: scene
+                .graph
+                .traverse_handle_iter(game_scene.editor_objects_root)
+                .collect(),
+        }
+    }
+
+    /// Returns an iterator over the nodes of the scene, excluding the nodes created by the editor.
+    pub fn nodes(&self) -> impl Iterator<Item = (Handle<Node>, &'a Node)> + '_ {
+        self.scene
+            .graph
+            .pair_iter()
+            .filter(|(handle, _)| !self.editor_nodes.contains(handle))
+    }
+}
+
+/// A rule that checks a scene for a specific kind of problems.
+pub trait ValidationRule: 'static {
+    /// Name of the rule, it is shown next to every issue found by the rule.
+    fn name(&self) -> &str;
+
+    /// Checks the scene and adds every found problem to the given list of issues.
+    fn validate(&self, ctx: &ValidationContext, issues: &mut Vec<ValidationIssue>);
+}
+
+/// Finds nodes that use resources which failed to load, for example, because of a moved or
+/// deleted file.
+pub struct MissingResourcesRule;
+
+impl ValidationRule for MissingResourcesRule {
+    fn name(&self) -> &str {
+        "Missing Resources"
+    }
+
+    fn validate(&self, ctx: &ValidationContext, issues: &mut Vec<ValidationIssue>) {
+        for (handle, node) in ctx.nodes() {
+            let mut resources = FxHashSet::default();
+            collect_used_resources(node as &dyn Reflect, &mut resources);
+            for resource in resources {
+                if let ResourceKind::External(ref path) = resource.kind() {
+                    if matches!(resource.0.lock().state, ResourceState::LoadError { .. }) {
+                        issues.push(ValidationIssue::error(
+                            handle,
+                            format!("{} uses missing resource {}.", node.name(), path.display()),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Finds scripts with node handles that point to deleted nodes.
+pub struct DanglingScriptReferencesRule;
+
+impl ValidationRule for DanglingScriptReferencesRule {
+    fn name(&self) -> &str {
+        "Dangling Script References"
+    }
+
+    fn validate(&self, ctx: &ValidationContext, issues: &mut Vec<ValidationIssue>) {
+        let graph = &ctx.scene.graph;
+        for (handle, node) in ctx.nodes() {
+            for script in node.scripts() {
+                let mut dangling = 0;
+                (script as &dyn Reflect).apply_recursively(
+                    &mut |object| {
+                        object.as_any(&mut |any| {
+                            if let Some(reference) = any.downcast_ref::<Handle<Node>>() {
+                                if reference.is_some() && !graph.is_valid_handle(*reference) {
+                                    dangling += 1;
+                                }
+                            }
+                        })
+                    },
+                    &[],
+                );
+
+                if dangling > 0 {
+                    issues.push(ValidationIssue::error(
+                        handle,
+                        format!(
+                            "A script of {} references {} deleted node(s).",
+                            node.name(),
+                            dangling
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// Finds colliders that are not attached to a rigid body. Such colliders are not simulated.
+pub struct ColliderWithoutBodyRule;
+
+impl ValidationRule for ColliderWithoutBodyRule {
+    fn name(&self) -> &str {
+        "Colliders Without Bodies"
+    }
+
+    fn validate(&self, ctx: &ValidationContext, issues: &mut Vec<ValidationIssue>) {
+        let graph = &ctx.scene.graph;
+        for (handle, node) in ctx.nodes() {
+            let parent = graph.try_get(node.parent());
+            let has_body = if node.cast::<Collider>().is_some() {
+                parent.is_some_and(|parent| parent.cast::<RigidBody>().is_some())
+            } else if node.cast::<dim2::collider::Collider>().is_some() {
+                parent.is_some_and(|parent| parent.cast::<dim2::rigidbody::RigidBody>().is_some())
+            } else {
+                continue;
+            };
+
+            if !has_body {
+                issues.push(ValidationIssue::error(
+                    handle,
+                    format!(
+                        "Collider {} must be a direct child of a rigid body.",
+                        node.name()
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+/// Reports lights that exceed the light budget of the scene. Every light has a rendering cost,
+/// and shadow casting lights are much more expensive than the rest.
+pub struct LightBudgetRule {
+    /// Maximum number of lights in the scene.
+    pub max_lights: usize,
+    /// Maximum number of lights that cast shadows.
+    pub max_shadow_casting_lights: usize,
+}
+
+impl Default for LightBudgetRule {
+    fn default() -> Self {
+        Self {
+            max_lights: 64,
+            max_shadow_casting_lights: 8,
+        }
+    }
+}
+
+impl ValidationRule for LightBudgetRule {
+    fn name(&self) -> &str {
+        "Light Budget"
+    }
+
+    fn validate(&self, ctx: &ValidationContext, issues: &mut Vec<ValidationIssue>) {
+        let mut lights = 0;
+        let mut shadow_casting_lights = 0;
+        for (handle, node) in ctx.nodes() {
+            if node.component_ref::<BaseLight>().is_none() {
+                continue;
+            }
+
+            lights += 1;
+            if lights > self.max_lights {
+                issues.push(ValidationIssue::warning(
+                    handle,
+                    format!(
+                        "Light {} exceeds the budget of {} lights.",
+                        node.name(),
+                        self.max_lights
+                    ),
+                ));
+            }
+
+            if node.cast_shadows() {
+                shadow_casting_lights += 1;
+                if shadow_casting_lights > self.max_shadow_casting_lights {
+                    issues.push(ValidationIssue::warning(
+                        handle,
+                        format!(
+                            "Light {} exceeds the budget of {} shadow casting lights.",
+                            node.name(),
+                            self.max_shadow_casting_lights
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// Finds rigid bodies and colliders with non-uniform scale. Physics ignores the scale of nodes,
+/// so such nodes are simulated with shapes that do not match their visual representation.
+pub struct NonUniformPhysicsScaleRule;
+
+fn is_uniform(scale: Vector3<f32>) -> bool {
+    let tolerance = 1.0e-3 * scale.amax().max(1.0);
+    (scale.x - scale.y).abs() <= tolerance && (scale.x - scale.z).abs() <= tolerance
+}
+
+impl ValidationRule for NonUniformPhysicsScaleRule {
+    fn name(&self) -> &str {
+        "Non-Uniform Physics Scale"
+    }
+
+    fn validate(&self, ctx: &ValidationContext, issues: &mut Vec<ValidationIssue>) {
+        for (handle, node) in ctx.nodes() {
+            let is_physics_node = node.cast::<RigidBody>().is_some()
+                || node.cast::<Collider>().is_some()
+                || node.cast::<dim2::rigidbody::RigidBody>().is_some()
+                || node.cast::<dim2::collider::Collider>().is_some();
+            if !is_physics_node {
+                continue;
+            }
+
+            let basis = node.global_transform().basis();
+            let global_scale = Vector3::new(
+                basis.column(0).norm(),
+                basis.column(1).norm(),
+                basis.column(2).norm(),
+            );
+            if is_uniform(global_scale) {
+                continue;
+            }
+
+            let mut issue = ValidationIssue::warning(
+                handle,
+                format!("Physics node {} has non-uniform scale.", node.name()),
+            );
+
+            // Non-uniform scale could be inherited from an ancestor, it can be fixed only if it
+            // is set on the node itself.
+            let local_scale = **node.local_transform().scale();
+            if !is_uniform(local_scale) {
+                issue = issue.with_fix(
+                    "Make Uniform",
+                    ScaleNodeCommand::new(handle, local_scale, Vector3::repeat(local_scale.amax())),
+                );
+            }
+
+            issues.push(issue);
+        }
+    }
+}
+
+/// Shows problems of the current scene found by a set of validation rules.
+pub struct SceneValidationPlugin {
+    rules: Vec<Box<dyn ValidationRule>>,
+    issues: Vec<(String, ValidationIssue)>,
+    fix_buttons: FxHashMap<Handle<UiNode>, usize>,
+    need_validation: bool,
+    window: Handle<UiNode>,
+    issues_list: Handle<UiNode>,
+    summary: Handle<UiNode>,
+    refresh: Handle<UiNode>,
+    open_validation_panel: Handle<UiNode>,
+}
+
+impl Default for SceneValidationPlugin {
+    fn default() -> Self {
+        Self {
+            rules: vec![
+                Box::new(MissingResourcesRule),
+                Box::new(DanglingScriptReferencesRule),
+                Box::new(ColliderWithoutBodyRule),
+                Box::new(LightBudgetRule::default()),
+                Box::new(NonUniformPhysicsScaleRule),
+            ],
+            issues: Default::default(),
+            fix_buttons: Default::default(),
+            need_validation: false,
+            window: Default::default(),
+            issues_list: Default::default(),
+            summary: Default::default(),
+            refresh: Default::default(),
+            open_validation_panel: Default::default(),
+        }
+    }
+}
+
+impl SceneValidationPlugin {
+    /// Adds a new validation rule. The rule will be used on the next validation of a scene.
+    pub fn add_rule<R: ValidationRule>(&mut self, rule: R) {
+        self.rules.push(Box::new(rule));
+        self.need_validation = true;
+    }
+
+    /// Returns a list of all registered validation rules.
+    pub fn rules(&self) -> &[Box<dyn ValidationRule>] {
+        &self.rules
+    }
+
+    /// Returns a list of issues found during the last validation, each issue is paired with the
+    /// name of the rule that found it.
+    pub fn issues(&self) -> &[(String, ValidationIssue)] {
+        &self.issues
+    }
+
+    /// Checks the given scene using every registered rule.
+    pub fn validate(&self, ctx: &ValidationContext) -> Vec<(String, ValidationIssue)> {
+        let mut issues = Vec::new();
+        for rule in self.rules.iter() {
+            let mut rule_issues = Vec::new();
+            rule.validate(ctx, &mut rule_issues);
+            issues.extend(
+                rule_issues
+                    .into_iter()
+                    .map(|issue| (rule.name().to_string(), issue)),
+            );
+        }
+        issues
+    }
+
+    fn make_issue_item(
+        &mut self,
+        index: usize,
+        rule: &str,
+        issue: &ValidationIssue,
+        ctx: &mut BuildContext,
+    ) -> Handle<UiNode> {
+        let brush = match issue.severity {
+            ValidationSeverity::Warning => ctx.style.property(Style::BRUSH_WARNING),
+            ValidationSeverity::Error => ctx.style.property(Style::BRUSH_ERROR),
+        };
+
+        let fix = issue.fix.as_ref().map(|fix| {
+            let button = ButtonBuilder::new(
+                WidgetBuilder::new()
+                    .on_column(1)
+                    .with_width(100.0)
+                    .with_margin(Thickness::uniform(1.0)),
+            )
+            .with_text(&fix.description)
+            .build(ctx);
+            self.fix_buttons.insert(button, index);
+            button
+        });
+
+        DecoratorBuilder::new(BorderBuilder::new(
+            WidgetBuilder::new().with_child(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child(
+                            TextBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_column(0)
+                                    .with_margin(Thickness::uniform(2.0))
+                                    .with_foreground(brush),
+                            )
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .with_text(format!("[{}] {}: {}", issue.severity, rule, issue.message))
+                            .build(ctx),
+                        )
+                        .with_children(fix),
+                )
+                .add_row(Row::auto())
+                .add_column(Column::stretch())
+                .add_column(Column::auto())
+                .build(ctx),
+            ),
+        ))
+        .build(ctx)
+    }
+
+    fn run_validation(&mut self, editor: &mut Editor) {
+        self.need_validation = false;
+
+        self.issues = editor
+            .scenes
+            .current_scene_entry_ref()
+            .and_then(|entry| entry.controller.downcast_ref::<GameScene>())
+            .map(|game_scene| {
+                let scene = &editor.engine.scenes[game_scene.scene];
+                self.validate(&ValidationContext::new(scene, game_scene))
+            })
+            .unwrap_or_default();
+
+        let ui = editor.engine.user_interfaces.first_mut();
+
+        let issues = std::mem::take(&mut self.issues);
+        self.fix_buttons.clear();
+        let items = issues
+            .iter()
+            .enumerate()
+            .map(|(index, (rule, issue))| {
+                self.make_issue_item(index, rule, issue, &mut ui.build_ctx())
+            })
+            .collect::<Vec<_>>();
+        self.issues = issues;
+
+        ui.send_message(ListViewMessage::items(
+            self.issues_list,
+            MessageDirection::ToWidget,
+            items,
+        ));
+
+        let summary = if self.issues.is_empty() {
+            "No issues found.".to_string()
+        } else {
+            let errors = self
+                .issues
+                .iter()
+                .filter(|(_, issue)| issue.severity == ValidationSeverity::Error)
+                .count();
+            format!(
+                "Errors: {}, Warnings: {}",
+                errors,
+                self.issues.len() - errors
+            )
+        };
+        ui.send_message(TextMessage::text(
+            self.summary,
+            MessageDirection::ToWidget,
+            summary,
+        ));
+    }
+
+    fn open(&mut self, editor: &mut Editor) {
+        let ui = editor.engine.user_interfaces.first_mut();
+        let ctx = &mut ui.build_ctx();
+
+        self.issues_list = ListViewBuilder::new(WidgetBuilder::new()).build(ctx);
+        self.summary = TextBuilder::new(
+            WidgetBuilder::new()
+                .on_row(1)
+                .on_column(0)
+                .with_margin(Thickness::uniform(1.0)),
+        )
+        .with_vertical_text_alignment(VerticalAlignment::Center)
+        .build(ctx);
+        self.refresh = ButtonBuilder::new(
+            WidgetBuilder::new()
+                .on_row(1)
+                .on_column(1)
+                .with_width(80.0)
+                .with_margin(Thickness::uniform(1.0)),
+        )
+        .with_text("Refresh")
+        .build(ctx);
+        self.window = WindowBuilder::new(WidgetBuilder::new().with_width(600.0).with_height(300.0))
+            .with_title(WindowTitle::text("Scene Validation"))
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child(
+                            ScrollViewerBuilder::new(WidgetBuilder::new().on_row(0).on_column(0))
+                                .with_content(self.issues_list)
+                                .build(ctx),
+                        )
+                        .with_child(self.summary)
+                        .with_child(self.refresh),
+                )
+                .add_row(Row::stretch())
+                .add_row(Row::auto())
+                .add_column(Column::stretch())
+                .add_column(Column::auto())
+                .build(ctx),
+            )
+            .open(false)
+            .build(ctx);
+
+        ui.send_message(WindowMessage::open_and_align(
+            self.window,
+            MessageDirection::ToWidget,
+            editor.scene_viewer.frame(),
+            HorizontalAlignment::Right,
+            VerticalAlignment::Bottom,
+            Thickness::uniform(1.0),
+            false,
+            true,
+        ));
+
+        self.need_validation = true;
+    }
+
+    fn close(&mut self, editor: &mut Editor) {
+        editor
+            .engine
+            .user_interfaces
+            .first_mut()
+            .send_message(WidgetMessage::remove(
+                self.window,
+                MessageDirection::ToWidget,
+            ));
+        self.window = Handle::NONE;
+        self.issues.clear();
+        self.fix_buttons.clear();
+    }
+}
+
+impl EditorPlugin for SceneValidationPlugin {
+    fn on_start(&mut self, editor: &mut Editor) {
+        let ui = editor.engine.user_interfaces.first_mut();
+        let ctx = &mut ui.build_ctx();
+        self.open_validation_panel = create_menu_item("Scene Validation", vec![], ctx);
+        ui.send_message(MenuItemMessage::add_item(
+            editor.menu.utils_menu.menu,
+            MessageDirection::ToWidget,
+            self.open_validation_panel,
+        ));
+    }
+
+    fn on_sync_to_model(&mut self, _editor: &mut Editor) {
+        self.need_validation = true;
+    }
+
+    fn on_scene_changed(&mut self, _editor: &mut Editor) {
+        self.need_validation = true;
+    }
+
+    fn on_ui_message(&mut self, message: &mut UiMessage, editor: &mut Editor) {
+        if let Some(MenuItemMessage::Click) = message.data() {
+            if message.destination() == self.open_validation_panel && self.window.is_none() {
+                self.open(editor);
+            }
+        }
+
+        if self.window.is_none() {
+            return;
+        }
+
+        if let Some(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.refresh {
+                self.need_validation = true;
+            } else if let Some(index) = self.fix_buttons.remove(&message.destination()) {
+                if let Some(fix) = self
+                    .issues
+                    .get_mut(index)
+                    .and_then(|(_, issue)| issue.fix.take())
+                {
+                    editor.message_sender.send(Message::DoCommand(fix.command));
+                }
+            }
+        } else if let Some(ListViewMessage::SelectionChanged(selection)) = message.data() {
+            if message.destination() == self.issues_list
+                && message.direction() == MessageDirection::FromWidget
+            {
+                if let Some((_, issue)) = selection.first().and_then(|i| self.issues.get(*i)) {
+                    editor
+                        .message_sender
+                        .do_command(ChangeSelectionCommand::new(Selection::new(
+                            GraphSelection::single_or_empty(issue.node),
+                        )));
+                }
+            }
+        } else if let Some(WindowMessage::Close) = message.data() {
+            if message.destination() == self.window {
+                self.close(editor);
+            }
+        }
+    }
+
+    fn on_update(&mut self, editor: &mut Editor) {
+        if self.window.is_some() && self.need_validation {
+            self.run_validation(editor);
+        }
+    }
+}
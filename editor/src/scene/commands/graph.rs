@@ -175,6 +175,39 @@ impl CommandTrait for ScaleNodeCommand {
     }
 }
 
+#[derive(Debug)]
+pub struct SetNodeNameCommand {
+    node: Handle<Node>,
+    name: String,
+}
+
+impl SetNodeNameCommand {
+    pub fn new(node: Handle<Node>, name: String) -> Self {
+        Self { node, name }
+    }
+
+    fn swap(&mut self, graph: &mut Graph) {
+        let node = &mut graph[self.node];
+        let old_name = node.name_owned();
+        node.set_name(&self.name);
+        self.name = old_name;
+    }
+}
+
+impl CommandTrait for SetNodeNameCommand {
+    fn name(&mut self, _context: &dyn CommandContext) -> String {
+        "Set Node Name".to_owned()
+    }
+
+    fn execute(&mut self, context: &mut dyn CommandContext) {
+        self.swap(&mut context.get_mut::<GameSceneContext>().scene.graph);
+    }
+
+    fn revert(&mut self, context: &mut dyn CommandContext) {
+        self.swap(&mut context.get_mut::<GameSceneContext>().scene.graph);
+    }
+}
+
 #[derive(Debug)]
 pub struct RotateNodeCommand {
     node: Handle<Node>,
@@ -107,6 +107,7 @@ impl CommandTrait for SetMeshTextureCommand {
                     "diffuseTexture",
                     MaterialResourceBinding::Texture(MaterialTextureBinding {
                         value: old_texture.clone(),
+                        sampler: None,
                     }),
                 );
             }
@@ -33,8 +33,8 @@ use crate::{
     interaction::{
         move_mode::MoveInteractionMode, navmesh::EditNavmeshMode,
         rotate_mode::RotateInteractionMode, scale_mode::ScaleInteractionMode,
-        select_mode::SelectInteractionMode, terrain::TerrainInteractionMode,
-        InteractionModeContainer,
+        scatter::ScatterInteractionMode, select_mode::SelectInteractionMode,
+        terrain::TerrainInteractionMode, InteractionModeContainer,
     },
     message::MessageSender,
     scene::{controller::SceneController, GameScene, Selection},
@@ -113,6 +113,12 @@ impl EditorSceneEntry {
             message_sender.clone(),
             scene_viewer.frame(),
         ));
+        interaction_modes.add(ScatterInteractionMode::new(
+            &game_scene,
+            engine,
+            message_sender.clone(),
+            scene_viewer.frame(),
+        ));
         interaction_modes.sender = Some(message_sender.clone());
 
         let mut entry = EditorSceneEntry {
@@ -67,7 +67,6 @@ use crate::{
             camera::{Camera, Projection},
             debug::{Line, SceneDrawingContext},
             graph::{Graph, GraphUpdateSwitches},
-            light::{point::PointLight, spot::SpotLight},
             mesh::RenderPath,
             mesh::{
                 surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
@@ -116,6 +115,7 @@ use std::{
 
 pub mod clipboard;
 pub mod dialog;
+pub mod overlay;
 pub mod property;
 pub mod selector;
 pub mod settings;
@@ -337,7 +337,9 @@ impl GameScene {
                 );
             }
 
-            if node.cast::<Mesh>().is_some() {
+            if overlay::draw_debug_overlays(node, graph, ctx, &settings.debugging) {
+                // Already drawn by one of the overlays.
+            } else if node.cast::<Mesh>().is_some() {
                 if settings.debugging.show_tbn {
                     node.debug_draw(ctx);
                 }
@@ -347,19 +349,14 @@ impl GameScene {
                 {
                     node.debug_draw(ctx);
                 }
-            } else if node.component_ref::<PointLight>().is_some()
-                || node.component_ref::<SpotLight>().is_some()
-            {
-                if settings.debugging.show_light_bounds {
-                    node.debug_draw(ctx);
-                }
             } else if node.component_ref::<Terrain>().is_some() {
                 if settings.debugging.show_terrains {
                     node.debug_draw(ctx);
                 }
             } else if let Some(navmesh) = node.component_ref::<NavigationalMesh>() {
-                if settings.navmesh.draw_all {
+                if settings.navmesh.draw_all || settings.debugging.show_navmeshes {
                     let selection = editor_selection.as_navmesh();
+                    let color = settings.debugging.overlay_colors.navmeshes;
 
                     for (index, vertex) in navmesh.navmesh_ref().vertices().iter().enumerate() {
                         ctx.draw_sphere(
@@ -367,11 +364,11 @@ impl GameScene {
                             10,
                             10,
                             settings.navmesh.vertex_radius,
-                            selection.map_or(Color::GREEN, |s| {
+                            selection.map_or(color, |s| {
                                 if s.unique_vertices().contains(&index) {
                                     Color::RED
                                 } else {
-                                    Color::GREEN
+                                    color
                                 }
                             }),
                         );
@@ -382,11 +379,11 @@ impl GameScene {
                             ctx.add_line(Line {
                                 begin: navmesh.navmesh_ref().vertices()[edge.a as usize],
                                 end: navmesh.navmesh_ref().vertices()[edge.b as usize],
-                                color: selection.map_or(Color::GREEN, |s| {
+                                color: selection.map_or(color, |s| {
                                     if s.contains_edge(*edge) {
                                         Color::RED
                                     } else {
-                                        Color::GREEN
+                                        color
                                     }
                                 }),
                             });
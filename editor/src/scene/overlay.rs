@@ -0,0 +1,280 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Debug overlays of the scene viewer. Each overlay category could be toggled separately in the
+//! viewport and has its own color, see [`DebuggingSettings`] for more info.
+
+use crate::{
+    fyrox::{
+        core::{
+            algebra::{Matrix4, Point3, UnitQuaternion, Vector2, Vector3},
+            color::Color,
+            math::{aabb::AxisAlignedBoundingBox, Matrix4Ext},
+        },
+        graph::BaseSceneGraph,
+        scene::{
+            base::Base,
+            collider::{Collider, ColliderShape, GeometrySource},
+            debug::{Line, SceneDrawingContext},
+            dim2,
+            graph::Graph,
+            joint::Joint,
+            light::{point::PointLight, spot::SpotLight},
+            node::Node,
+            sound::Sound,
+        },
+    },
+    settings::debugging::DebuggingSettings,
+};
+
+/// Returns a transform of the node without scaling. Physics and sound ignore the scale of nodes,
+/// so the overlays must ignore it as well.
+fn rigid_transform(node: &Base) -> Matrix4<f32> {
+    Matrix4::new_translation(&node.global_position())
+        * UnitQuaternion::from_matrix_eps(
+            &node.global_transform().basis(),
+            f32::EPSILON,
+            16,
+            UnitQuaternion::identity(),
+        )
+        .to_homogeneous()
+}
+
+fn transform_point(transform: &Matrix4<f32>, point: Vector3<f32>) -> Vector3<f32> {
+    transform.transform_point(&Point3::from(point)).coords
+}
+
+fn transform_point_2d(transform: &Matrix4<f32>, point: Vector2<f32>) -> Vector3<f32> {
+    transform_point(transform, Vector3::new(point.x, point.y, 0.0))
+}
+
+fn draw_geometry_source_bounds(
+    source: &GeometrySource,
+    graph: &Graph,
+    ctx: &mut SceneDrawingContext,
+    color: Color,
+) {
+    if let Some(source) = graph.try_get(source.0) {
+        ctx.draw_oob(
+            &source.local_bounding_box(),
+            source.global_transform(),
+            color,
+        );
+    }
+}
+
+fn draw_collider(collider: &Collider, graph: &Graph, ctx: &mut SceneDrawingContext, color: Color) {
+    let transform = rigid_transform(collider);
+    match collider.shape() {
+        ColliderShape::Ball(ball) => {
+            ctx.draw_wire_sphere(collider.global_position(), ball.radius, 24, color)
+        }
+        ColliderShape::Cylinder(cylinder) => ctx.draw_cylinder(
+            16,
+            cylinder.radius,
+            cylinder.half_height * 2.0,
+            true,
+            transform,
+            color,
+        ),
+        ColliderShape::Cone(cone) => ctx.draw_cone(
+            16,
+            cone.radius,
+            cone.half_height * 2.0,
+            transform,
+            color,
+            true,
+        ),
+        ColliderShape::Cuboid(cuboid) => ctx.draw_oob(
+            &AxisAlignedBoundingBox::from_min_max(-cuboid.half_extents, cuboid.half_extents),
+            transform,
+            color,
+        ),
+        ColliderShape::Capsule(capsule) => ctx.draw_segment_capsule(
+            capsule.begin,
+            capsule.end,
+            capsule.radius,
+            10,
+            10,
+            transform,
+            color,
+        ),
+        ColliderShape::Segment(segment) => ctx.add_line(Line {
+            begin: transform_point(&transform, segment.begin),
+            end: transform_point(&transform, segment.end),
+            color,
+        }),
+        ColliderShape::Triangle(triangle) => ctx.draw_triangle(
+            transform_point(&transform, triangle.a),
+            transform_point(&transform, triangle.b),
+            transform_point(&transform, triangle.c),
+            color,
+        ),
+        ColliderShape::Trimesh(trimesh) => {
+            for source in trimesh.sources.iter() {
+                draw_geometry_source_bounds(source, graph, ctx, color);
+            }
+        }
+        ColliderShape::Heightfield(heightfield) => {
+            draw_geometry_source_bounds(&heightfield.geometry_source, graph, ctx, color)
+        }
+        ColliderShape::Polyhedron(polyhedron) => {
+            draw_geometry_source_bounds(&polyhedron.geometry_source, graph, ctx, color)
+        }
+    }
+}
+
+fn draw_collider_2d(
+    collider: &dim2::collider::Collider,
+    ctx: &mut SceneDrawingContext,
+    color: Color,
+) {
+    let transform = rigid_transform(collider);
+    match collider.shape() {
+        dim2::collider::ColliderShape::Ball(ball) => {
+            ctx.draw_circle(Default::default(), ball.radius, 24, transform, color)
+        }
+        dim2::collider::ColliderShape::Cuboid(cuboid) => ctx.draw_rectangle(
+            cuboid.half_extents.x,
+            cuboid.half_extents.y,
+            transform,
+            color,
+        ),
+        dim2::collider::ColliderShape::Capsule(capsule) => ctx.draw_segment_flat_capsule(
+            capsule.begin,
+            capsule.end,
+            capsule.radius,
+            10,
+            transform,
+            color,
+        ),
+        dim2::collider::ColliderShape::Segment(segment) => ctx.add_line(Line {
+            begin: transform_point_2d(&transform, segment.begin),
+            end: transform_point_2d(&transform, segment.end),
+            color,
+        }),
+        dim2::collider::ColliderShape::Triangle(triangle) => ctx.draw_triangle(
+            transform_point_2d(&transform, triangle.a),
+            transform_point_2d(&transform, triangle.b),
+            transform_point_2d(&transform, triangle.c),
+            color,
+        ),
+        // Complex shapes are built from other nodes, which are visible on their own.
+        dim2::collider::ColliderShape::Trimesh(_)
+        | dim2::collider::ColliderShape::Heightfield(_)
+        | dim2::collider::ColliderShape::TileMap(_) => ctx.draw_oob(
+            &collider.local_bounding_box(),
+            collider.global_transform(),
+            color,
+        ),
+    }
+}
+
+fn draw_joint(
+    joint: &Base,
+    bodies: impl IntoIterator<Item = Vector3<f32>>,
+    ctx: &mut SceneDrawingContext,
+    color: Color,
+) {
+    let position = joint.global_position();
+    ctx.draw_wire_sphere(position, 0.1, 12, color);
+    for body in bodies {
+        ctx.add_line(Line {
+            begin: position,
+            end: body,
+            color,
+        });
+    }
+}
+
+/// Draws the overlays enabled in the given settings for a single node. Returns `true` if the node
+/// was drawn by any of the overlays.
+pub fn draw_debug_overlays(
+    node: &Node,
+    graph: &Graph,
+    ctx: &mut SceneDrawingContext,
+    settings: &DebuggingSettings,
+) -> bool {
+    let colors = &settings.overlay_colors;
+
+    let body_position = |body| graph.try_get(body).map(|body| body.global_position());
+
+    if let Some(collider) = node.cast::<Collider>() {
+        if collider.is_sensor() {
+            if settings.show_triggers {
+                draw_collider(collider, graph, ctx, colors.triggers);
+            }
+        } else if settings.show_colliders {
+            draw_collider(collider, graph, ctx, colors.colliders);
+        }
+    } else if let Some(collider) = node.cast::<dim2::collider::Collider>() {
+        if collider.is_sensor() {
+            if settings.show_triggers {
+                draw_collider_2d(collider, ctx, colors.triggers);
+            }
+        } else if settings.show_colliders {
+            draw_collider_2d(collider, ctx, colors.colliders);
+        }
+    } else if let Some(joint) = node.cast::<Joint>() {
+        if settings.show_joints {
+            let bodies = [joint.body1(), joint.body2()].map(body_position);
+            draw_joint(joint, bodies.into_iter().flatten(), ctx, colors.joints);
+        }
+    } else if let Some(joint) = node.cast::<dim2::joint::Joint>() {
+        if settings.show_joints {
+            let bodies = [joint.body1(), joint.body2()].map(body_position);
+            draw_joint(joint, bodies.into_iter().flatten(), ctx, colors.joints);
+        }
+    } else if let Some(sound) = node.cast::<Sound>() {
+        if settings.show_sound_sources {
+            let position = sound.global_position();
+            ctx.draw_wire_sphere(position, sound.radius(), 24, colors.sound_sources);
+            // Max distance is unbounded by default, there is nothing to draw in this case.
+            if sound.max_distance() < f32::MAX {
+                ctx.draw_wire_sphere(position, sound.max_distance(), 32, colors.sound_sources);
+            }
+        }
+    } else if let Some(light) = node.cast::<PointLight>() {
+        if settings.show_light_bounds {
+            ctx.draw_wire_sphere(
+                light.global_position(),
+                light.radius(),
+                30,
+                colors.light_bounds,
+            );
+        }
+    } else if let Some(light) = node.cast::<SpotLight>() {
+        if settings.show_light_bounds {
+            ctx.draw_cone(
+                16,
+                (light.full_cone_angle() * 0.5).tan() * light.distance(),
+                light.distance(),
+                rigid_transform(light)
+                    * Matrix4::new_translation(&Vector3::new(0.0, -light.distance() * 0.5, 0.0)),
+                colors.light_bounds,
+                false,
+            );
+        }
+    } else {
+        return false;
+    }
+
+    true
+}
@@ -40,6 +40,7 @@ use crate::{
         resource::texture::TextureResource,
         scene::{
             dim2,
+            floating_origin::FloatingOrigin,
             graph::{
                 physics::{IntegrationParameters, PhysicsWorld},
                 Graph, NodePool,
@@ -90,6 +91,7 @@ impl SceneSettingsWindow {
         container.register_inheritable_inspectable::<PhysicsWorld>();
         container.register_inheritable_inspectable::<dim2::physics::PhysicsWorld>();
         container.register_inheritable_inspectable::<SceneRenderingOptions>();
+        container.register_inheritable_inspectable::<FloatingOrigin>();
         container.insert(EnumPropertyEditorDefinition::<Color>::new_optional());
 
         Self {
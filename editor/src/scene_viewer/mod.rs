@@ -62,7 +62,10 @@ use crate::{
     load_image,
     message::MessageSender,
     scene::container::EditorSceneEntry,
-    scene_viewer::gizmo::{SceneGizmo, SceneGizmoAction},
+    scene_viewer::{
+        gizmo::{SceneGizmo, SceneGizmoAction},
+        overlays::DebugOverlaysMenu,
+    },
     send_sync_message,
     settings::SettingsMessage,
     utils::enable_widget,
@@ -77,6 +80,7 @@ use strum::{IntoEnumIterator, VariantNames};
 use strum_macros::{AsRefStr, EnumIter, EnumString, VariantNames};
 
 mod gizmo;
+mod overlays;
 
 #[derive(Default, Clone, Debug, EnumIter, AsRefStr, EnumString, VariantNames)]
 pub enum GraphicsDebugSwitches {
@@ -280,6 +284,8 @@ pub struct SceneViewer {
     interaction_modes: FxHashMap<Uuid, Handle<UiNode>>,
     camera_projection: Handle<UiNode>,
     play: Handle<UiNode>,
+    play_in_editor: Handle<UiNode>,
+    pause: Handle<UiNode>,
     build: Handle<UiNode>,
     stop: Handle<UiNode>,
     build_profile: Handle<UiNode>,
@@ -293,6 +299,7 @@ pub struct SceneViewer {
     scene_gizmo_image: Handle<UiNode>,
     debug_switches: Handle<UiNode>,
     grid_snap_menu: GridSnappingMenu,
+    debug_overlays_menu: DebugOverlaysMenu,
 }
 
 impl SceneViewer {
@@ -305,6 +312,8 @@ impl SceneViewer {
         let selection_frame;
         let camera_projection;
         let play;
+        let play_in_editor;
+        let pause;
         let build;
         let stop;
         let build_profile;
@@ -319,6 +328,7 @@ impl SceneViewer {
         .build(ctx);
 
         let grid_snap_menu = GridSnappingMenu::new(ctx, settings);
+        let debug_overlays_menu = DebugOverlaysMenu::new(ctx, settings);
 
         let global_position_display;
         let debug_switches;
@@ -343,6 +353,7 @@ impl SceneViewer {
                     camera_projection
                 })
                 .with_child(grid_snap_menu.menu)
+                .with_child(debug_overlays_menu.menu)
                 .with_child({
                     global_position_display = Vec3EditorBuilder::<f32>::new(
                         WidgetBuilder::new()
@@ -435,6 +446,49 @@ impl SceneViewer {
                                 .build(ctx);
                                 play
                             })
+                            .with_child({
+                                play_in_editor = ButtonBuilder::new(
+                                    WidgetBuilder::new()
+                                        .with_margin(Thickness::uniform(1.0))
+                                        .with_width(26.0)
+                                        .with_tooltip(make_simple_tooltip(
+                                            ctx,
+                                            "Play In Editor\nRuns the current scene inside the \
+                                            editor. The scene is restored when the play is stopped.",
+                                        )),
+                                )
+                                .with_content(
+                                    ImageBuilder::new(
+                                        WidgetBuilder::new()
+                                            .with_width(16.0)
+                                            .with_height(16.0)
+                                            .with_margin(Thickness::uniform(4.0))
+                                            .with_background(
+                                                Brush::Solid(Color::opaque(0, 160, 220)).into(),
+                                            ),
+                                    )
+                                    .with_opt_texture(load_image!("../../resources/play.png"))
+                                    .build(ctx),
+                                )
+                                .build(ctx);
+                                play_in_editor
+                            })
+                            .with_child({
+                                pause = ButtonBuilder::new(
+                                    WidgetBuilder::new()
+                                        .with_enabled(false)
+                                        .with_margin(Thickness::uniform(1.0))
+                                        .with_width(26.0)
+                                        .with_tooltip(make_simple_tooltip(
+                                            ctx,
+                                            "Pause/Resume\nPauses the play in the editor, \
+                                            so the live state of the scene could be inspected.",
+                                        )),
+                                )
+                                .with_text("||")
+                                .build(ctx);
+                                pause
+                            })
                             .with_child({
                                 build = ButtonBuilder::new(
                                     WidgetBuilder::new()
@@ -589,6 +643,8 @@ impl SceneViewer {
             selection_frame,
             camera_projection,
             play,
+            play_in_editor,
+            pause,
             interaction_mode_panel,
             contextual_actions,
             global_position_display,
@@ -600,6 +656,7 @@ impl SceneViewer {
             scene_gizmo_image,
             debug_switches,
             grid_snap_menu,
+            debug_overlays_menu,
             build,
         }
     }
@@ -736,6 +793,8 @@ impl SceneViewer {
         mode: &Mode,
     ) {
         self.grid_snap_menu.handle_ui_message(message, settings);
+        self.debug_overlays_menu
+            .handle_ui_message(message, settings);
 
         let ui = engine.user_interfaces.first_mut();
 
@@ -750,6 +809,10 @@ impl SceneViewer {
                 self.sender.send(Message::SwitchToBuildMode {
                     play_after_build: true,
                 });
+            } else if message.destination() == self.play_in_editor {
+                self.sender.send(Message::SwitchToPlayInEditorMode);
+            } else if message.destination() == self.pause {
+                self.sender.send(Message::TogglePlayInEditorPause);
             } else if message.destination() == self.build {
                 self.sender.send(Message::SwitchToBuildMode {
                     play_after_build: false,
@@ -1138,10 +1201,17 @@ impl SceneViewer {
             enable_widget(widget, enabled, ui);
         }
 
+        for button in [self.play, self.play_in_editor] {
+            ui.send_message(WidgetMessage::enabled(
+                button,
+                MessageDirection::ToWidget,
+                mode.is_edit(),
+            ));
+        }
         ui.send_message(WidgetMessage::enabled(
-            self.play,
+            self.pause,
             MessageDirection::ToWidget,
-            mode.is_edit(),
+            matches!(mode, Mode::PlayInEditor(_)),
         ));
         ui.send_message(WidgetMessage::enabled(
             self.stop,
@@ -1182,6 +1252,8 @@ impl SceneViewer {
     pub fn pre_update(&self, settings: &Settings, engine: &mut Engine) {
         self.grid_snap_menu
             .update(settings, engine.user_interfaces.first());
+        self.debug_overlays_menu
+            .update(settings, engine.user_interfaces.first());
     }
 
     pub fn update(&self, game_scene: &GameScene, engine: &mut Engine) {
@@ -0,0 +1,177 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{
+    fyrox::{
+        core::pool::Handle,
+        gui::{
+            button::Button,
+            check_box::{CheckBoxBuilder, CheckBoxMessage},
+            decorator::DecoratorMessage,
+            dropdown_menu::DropdownMenuBuilder,
+            grid::{Column, GridBuilder, Row},
+            message::{MessageDirection, UiMessage},
+            style::{resource::StyleResourceExt, Style},
+            text::TextBuilder,
+            utils::make_image_button_with_tooltip,
+            widget::WidgetBuilder,
+            BuildContext, Thickness, UiNode, UserInterface,
+        },
+    },
+    load_image,
+    settings::{debugging::DebuggingSettings, SettingsMessage},
+    Settings,
+};
+use std::sync::mpsc::{self, Receiver};
+
+type OverlaySwitch = fn(&mut DebuggingSettings) -> &mut bool;
+
+const OVERLAYS: [(&str, OverlaySwitch); 7] = [
+    ("Navmeshes", |s| &mut s.show_navmeshes),
+    ("Colliders", |s| &mut s.show_colliders),
+    ("Triggers", |s| &mut s.show_triggers),
+    ("Joints", |s| &mut s.show_joints),
+    ("Light Bounds", |s| &mut s.show_light_bounds),
+    ("Sound Sources", |s| &mut s.show_sound_sources),
+    ("Physics (Raw)", |s| &mut s.show_physics),
+];
+
+/// A dropdown menu of the scene viewer with a switch for every debug overlay category.
+pub struct DebugOverlaysMenu {
+    pub menu: Handle<UiNode>,
+    button: Handle<UiNode>,
+    switches: Vec<(Handle<UiNode>, OverlaySwitch)>,
+    receiver: Receiver<SettingsMessage>,
+}
+
+impl DebugOverlaysMenu {
+    pub fn new(ctx: &mut BuildContext, settings: &mut Settings) -> Self {
+        let (sender, receiver) = mpsc::channel();
+
+        settings.subscribers.push(sender);
+
+        // Read-only copy, mutable access to the settings marks them as modified.
+        let mut debugging = settings.debugging.clone();
+
+        let mut switches = Vec::new();
+        let mut children = Vec::new();
+        for (row, (name, switch)) in OVERLAYS.iter().enumerate() {
+            children.push(
+                TextBuilder::new(WidgetBuilder::new().on_row(row).on_column(0))
+                    .with_text(*name)
+                    .build(ctx),
+            );
+            let check_box = CheckBoxBuilder::new(
+                WidgetBuilder::new()
+                    .on_row(row)
+                    .on_column(1)
+                    .with_tab_index(Some(row)),
+            )
+            .checked(Some(*switch(&mut debugging)))
+            .build(ctx);
+            children.push(check_box);
+            switches.push((check_box, *switch));
+        }
+
+        let button;
+        let menu = DropdownMenuBuilder::new(WidgetBuilder::new())
+            .with_header({
+                button = make_image_button_with_tooltip(
+                    ctx,
+                    22.0,
+                    22.0,
+                    load_image!("../../resources/visible.png"),
+                    "Debug Overlays",
+                    None,
+                );
+                button
+            })
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_margin(Thickness::uniform(2.0))
+                        .with_children(children),
+                )
+                .add_rows(vec![Row::auto(); OVERLAYS.len()])
+                .add_column(Column::stretch())
+                .add_column(Column::auto())
+                .build(ctx),
+            )
+            .build(ctx);
+
+        Self {
+            menu,
+            button,
+            switches,
+            receiver,
+        }
+    }
+
+    pub fn update(&self, settings: &Settings, ui: &UserInterface) {
+        for message in self.receiver.try_iter() {
+            match message {
+                SettingsMessage::Changed => {
+                    let mut debugging = settings.debugging.clone();
+
+                    let mut any_enabled = false;
+                    for (check_box, switch) in self.switches.iter() {
+                        let enabled = *switch(&mut debugging);
+                        any_enabled |= enabled;
+                        ui.send_message(CheckBoxMessage::checked(
+                            *check_box,
+                            MessageDirection::ToWidget,
+                            Some(enabled),
+                        ));
+                    }
+
+                    if let Some(button) = ui.try_get_of_type::<Button>(self.button) {
+                        ui.send_message(DecoratorMessage::selected_brush(
+                            *button.decorator,
+                            MessageDirection::ToWidget,
+                            ui.style.property(Style::BRUSH_BRIGHT_BLUE),
+                        ));
+
+                        ui.send_message(DecoratorMessage::select(
+                            *button.decorator,
+                            MessageDirection::ToWidget,
+                            any_enabled,
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn handle_ui_message(&self, message: &UiMessage, settings: &mut Settings) {
+        if message.direction() != MessageDirection::FromWidget {
+            return;
+        }
+
+        if let Some(CheckBoxMessage::Check(Some(value))) = message.data() {
+            if let Some((_, switch)) = self
+                .switches
+                .iter()
+                .find(|(check_box, _)| *check_box == message.destination())
+            {
+                *switch(&mut settings.debugging) = *value;
+            }
+        }
+    }
+}
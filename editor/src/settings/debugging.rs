@@ -18,8 +18,51 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use crate::fyrox::core::reflect::prelude::*;
-use serde::{Deserialize, Serialize};
+use crate::fyrox::core::{color::Color, reflect::prelude::*};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Colors of the debug overlays in the scene viewer, one per overlay category.
+#[derive(Deserialize, Serialize, PartialEq, Clone, Debug, Reflect)]
+pub struct DebugOverlayColors {
+    #[serde(with = "color_rgba")]
+    pub navmeshes: Color,
+    #[serde(with = "color_rgba")]
+    pub colliders: Color,
+    #[serde(with = "color_rgba")]
+    pub triggers: Color,
+    #[serde(with = "color_rgba")]
+    pub joints: Color,
+    #[serde(with = "color_rgba")]
+    pub light_bounds: Color,
+    #[serde(with = "color_rgba")]
+    pub sound_sources: Color,
+}
+
+impl Default for DebugOverlayColors {
+    fn default() -> Self {
+        Self {
+            navmeshes: Color::GREEN,
+            colliders: Color::opaque(0, 200, 255),
+            triggers: Color::opaque(255, 200, 0),
+            joints: Color::opaque(255, 0, 255),
+            light_bounds: Color::GREEN,
+            sound_sources: Color::opaque(120, 120, 255),
+        }
+    }
+}
+
+mod color_rgba {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(color: &Color, serializer: S) -> Result<S::Ok, S::Error> {
+        [color.r, color.g, color.b, color.a].serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        let [r, g, b, a] = <[u8; 4]>::deserialize(deserializer)?;
+        Ok(Color::from_rgba(r, g, b, a))
+    }
+}
 
 #[derive(Deserialize, Serialize, PartialEq, Clone, Debug, Reflect)]
 pub struct DebuggingSettings {
@@ -32,6 +75,18 @@ pub struct DebuggingSettings {
     pub show_light_bounds: bool,
     #[serde(default)]
     pub show_camera_bounds: bool,
+    #[serde(default)]
+    pub show_navmeshes: bool,
+    #[serde(default)]
+    pub show_colliders: bool,
+    #[serde(default)]
+    pub show_triggers: bool,
+    #[serde(default)]
+    pub show_joints: bool,
+    #[serde(default)]
+    pub show_sound_sources: bool,
+    #[serde(default)]
+    pub overlay_colors: DebugOverlayColors,
     #[reflect(description = "Size of pictograms in meters. It is used for objects like lights.")]
     #[serde(default)]
     pub pictogram_size: f32,
@@ -51,6 +106,12 @@ impl Default for DebuggingSettings {
             show_terrains: false,
             show_light_bounds: true,
             show_camera_bounds: true,
+            show_navmeshes: false,
+            show_colliders: false,
+            show_triggers: false,
+            show_joints: false,
+            show_sound_sources: false,
+            overlay_colors: Default::default(),
             pictogram_size: 0.33,
             save_scene_in_text_form: false,
         }
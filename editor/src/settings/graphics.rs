@@ -18,7 +18,10 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use crate::fyrox::{core::reflect::prelude::*, renderer::QualitySettings};
+use crate::fyrox::{
+    core::reflect::prelude::*,
+    renderer::{framework::server::PresentSettings, QualitySettings},
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize, PartialEq, Debug, Clone, Reflect)]
@@ -28,6 +31,8 @@ pub struct GraphicsSettings {
     pub z_far: f32,
     #[serde(default = "default_draw_grid")]
     pub draw_grid: bool,
+    #[serde(default)]
+    pub present: PresentSettings,
 }
 
 fn default_draw_grid() -> bool {
@@ -41,6 +46,7 @@ impl Default for GraphicsSettings {
             z_near: 0.025,
             z_far: 128.0,
             draw_grid: default_draw_grid(),
+            present: Default::default(),
         }
     }
 }
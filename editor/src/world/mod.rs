@@ -870,7 +870,7 @@ impl WorldViewer {
         ui.send_message(WidgetMessage::enabled(
             window_content(self.window, ui),
             MessageDirection::ToWidget,
-            mode.is_edit(),
+            mode.is_inspectable(),
         ));
     }
 
@@ -404,6 +404,12 @@ impl RootMotion {
         self.delta_position = self.delta_position.lerp(&other.delta_position, weight);
         self.delta_rotation = nlerp(self.delta_rotation, &other.delta_rotation, weight);
     }
+
+    /// Appends the motion of a subsequent update step to this motion.
+    pub fn accumulate(&mut self, next: &RootMotion) {
+        self.delta_position += next.delta_position;
+        self.delta_rotation = next.delta_rotation * self.delta_rotation;
+    }
 }
 
 impl<T: EntityId> NameProvider for Animation<T> {
@@ -529,6 +535,31 @@ impl<T: EntityId> Animation<T> {
         self.time_slice.end - self.time_slice.start
     }
 
+    /// Same as [`Self::tick`], but splits the time step into the given amount of equal sub-steps. It
+    /// is useful for large time steps, because it prevents the animation from skipping signals on
+    /// loop boundaries. Root motion of all the sub-steps is accumulated, so it is the same as if
+    /// the animation was updated with a single step.
+    pub fn tick_substeps(&mut self, dt: f32, substeps: u32) {
+        if substeps <= 1 {
+            self.tick(dt);
+            return;
+        }
+
+        let step = dt / substeps as f32;
+        let mut motion = RootMotion::default();
+        for _ in 0..substeps {
+            self.tick(step);
+            if let Some(root_motion) = self.root_motion.as_ref() {
+                motion.accumulate(root_motion);
+            }
+        }
+
+        if let Some(root_motion) = self.root_motion.as_mut() {
+            root_motion.delta_position = motion.delta_position;
+            root_motion.delta_rotation = motion.delta_rotation;
+        }
+    }
+
     /// Performs a single update tick and calculates an output pose. This method is low level, you should not use it
     /// in normal circumstances - the engine will call it for you.
     pub fn tick(&mut self, dt: f32) {
@@ -30,7 +30,7 @@ use crate::{
         reflect::prelude::*,
         visitor::{Visit, VisitResult, Visitor},
     },
-    Animation, AnimationContainer, AnimationPose, EntityId,
+    Animation, AnimationContainer, AnimationPose, EntityId, RootMotion,
 };
 use fxhash::FxHashSet;
 
@@ -316,6 +316,40 @@ impl<T: EntityId> Machine<T> {
         &self.final_pose
     }
 
+    /// Same as [`Self::evaluate_pose`], but splits the time step into the given amount of equal
+    /// sub-steps. It keeps transitions and blending stable with large time steps. Root motion of
+    /// all the sub-steps is accumulated in the final pose.
+    pub fn evaluate_pose_substeps(
+        &mut self,
+        animations: &mut AnimationContainer<T>,
+        dt: f32,
+        substeps: u32,
+    ) -> &AnimationPose<T> {
+        if substeps <= 1 {
+            return self.evaluate_pose(animations, dt);
+        }
+
+        let step = dt / substeps as f32;
+        let mut motion: Option<RootMotion> = None;
+        for _ in 0..substeps {
+            let pose = self.evaluate_pose(animations, step);
+            if let Some(root_motion) = pose.root_motion() {
+                motion
+                    .get_or_insert_with(Default::default)
+                    .accumulate(root_motion);
+            }
+        }
+
+        if let Some(motion) = motion {
+            let mut root_motion = self.final_pose.root_motion().cloned().unwrap_or_default();
+            root_motion.delta_position = motion.delta_position;
+            root_motion.delta_rotation = motion.delta_rotation;
+            self.final_pose.set_root_motion(Some(root_motion));
+        }
+
+        &self.final_pose
+    }
+
     /// Computes final animation pose that could be then applied to a set of entities graph. This
     /// method will update all the animations used by the machine automatically. Make sure to **not**
     /// update the animations in the container before using this method. Otherwise your animations
@@ -42,6 +42,24 @@ use std::{
     cell::{Ref, RefCell},
     ops::{Deref, DerefMut},
 };
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Defines how many parameters drive a blend space.
+#[derive(
+    Copy, Clone, Default, Debug, PartialEq, Eq, Visit, Reflect, AsRefStr, EnumString, VariantNames,
+)]
+pub enum BlendSpaceKind {
+    /// Points are placed on a plane and blended using triangulation of the points. The blend
+    /// space is sampled by a [`Parameter::SamplingPoint`] parameter (for example, direction and
+    /// speed of a character).
+    #[default]
+    TwoDimensional,
+    /// Points are placed on a line (only X coordinate of the points is used) and every two
+    /// neighbouring points are blended linearly. The blend space is sampled by either a
+    /// [`Parameter::Weight`] or X coordinate of a [`Parameter::SamplingPoint`] parameter (for
+    /// example, speed of a character).
+    OneDimensional,
+}
 
 #[derive(Debug, Visit, Clone, Reflect, PartialEq, Default)]
 pub struct BlendSpacePoint<T: EntityId> {
@@ -59,6 +77,10 @@ impl<T: EntityId> TypeUuidProvider for BlendSpacePoint<T> {
 pub struct BlendSpace<T: EntityId> {
     base: BasePoseNode<T>,
 
+    #[reflect(setter = "set_kind")]
+    #[visit(optional)]
+    kind: BlendSpaceKind,
+
     #[reflect(hidden)]
     points: Vec<BlendSpacePoint<T>>,
 
@@ -92,6 +114,7 @@ impl<T: EntityId> Default for BlendSpace<T> {
     fn default() -> Self {
         Self {
             base: Default::default(),
+            kind: Default::default(),
             points: vec![],
             triangles: Default::default(),
             x_axis_name: "X".to_string(),
@@ -131,9 +154,8 @@ impl<T: EntityId> AnimationPoseSource<T> for BlendSpace<T> {
 
         pose.reset();
 
-        if let Some(Parameter::SamplingPoint(sampling_point)) = params.get(&self.sampling_parameter)
-        {
-            if let Some(weights) = self.fetch_weights(*sampling_point) {
+        if let Some(sampling_point) = self.sampling_point(params) {
+            if let Some(weights) = self.fetch_weights(sampling_point) {
                 let (ia, wa) = weights[0];
                 let (ib, wb) = weights[1];
                 let (ic, wc) = weights[2];
@@ -166,9 +188,8 @@ impl<T: EntityId> AnimationPoseSource<T> for BlendSpace<T> {
         animations: &AnimationContainer<T>,
         strategy: AnimationEventCollectionStrategy,
     ) -> Vec<(Handle<Animation<T>>, AnimationEvent)> {
-        if let Some(Parameter::SamplingPoint(sampling_point)) = params.get(&self.sampling_parameter)
-        {
-            if let Some(weights) = self.fetch_weights(*sampling_point) {
+        if let Some(sampling_point) = self.sampling_point(params) {
+            if let Some(weights) = self.fetch_weights(sampling_point) {
                 let (ia, wa) = weights[0];
                 let (ib, wb) = weights[1];
                 let (ic, wc) = weights[2];
@@ -248,6 +269,34 @@ impl<T: EntityId> Drop for PointsMut<'_, T> {
 }
 
 impl<T: EntityId> BlendSpace<T> {
+    /// Sets new kind of the blend space. See [`BlendSpaceKind`] docs for more info.
+    pub fn set_kind(&mut self, kind: BlendSpaceKind) -> BlendSpaceKind {
+        let prev = std::mem::replace(&mut self.kind, kind);
+        self.triangulate();
+        prev
+    }
+
+    /// Returns current kind of the blend space.
+    pub fn kind(&self) -> BlendSpaceKind {
+        self.kind
+    }
+
+    /// Fetches current sampling point of the blend space from the given parameters. One
+    /// dimensional blend spaces could be sampled by weight parameters as well, the weight is used
+    /// as X coordinate in this case.
+    pub fn sampling_point(&self, params: &ParameterContainer) -> Option<Vector2<f32>> {
+        match params.get(&self.sampling_parameter)? {
+            Parameter::SamplingPoint(sampling_point) => Some(match self.kind {
+                BlendSpaceKind::TwoDimensional => *sampling_point,
+                BlendSpaceKind::OneDimensional => Vector2::new(sampling_point.x, 0.0),
+            }),
+            Parameter::Weight(weight) if self.kind == BlendSpaceKind::OneDimensional => {
+                Some(Vector2::new(*weight, 0.0))
+            }
+            _ => None,
+        }
+    }
+
     pub fn add_point(&mut self, point: BlendSpacePoint<T>) -> bool {
         self.points.push(point);
         self.triangulate()
@@ -334,12 +383,56 @@ impl<T: EntityId> BlendSpace<T> {
         for point in self.points.iter_mut() {
             let x = math::round_to_step(point.position.x, self.snap_step.x)
                 .clamp(self.min_values.x, self.max_values.x);
-            let y = math::round_to_step(point.position.y, self.snap_step.y)
-                .clamp(self.min_values.y, self.max_values.y);
+            let y = match self.kind {
+                BlendSpaceKind::TwoDimensional => {
+                    math::round_to_step(point.position.y, self.snap_step.y)
+                        .clamp(self.min_values.y, self.max_values.y)
+                }
+                BlendSpaceKind::OneDimensional => 0.0,
+            };
             point.position = Vector2::new(x, y);
         }
     }
 
+    /// Blends the points placed on a line. Every point is projected on the given axis, and the
+    /// sampling point is interpolated between two neighbouring projections. Sampling points
+    /// outside of the line are clamped to its ends.
+    fn fetch_weights_on_line(
+        &self,
+        axis: Vector2<f32>,
+        sampling_point: Vector2<f32>,
+    ) -> Option<[(usize, f32); 3]> {
+        let origin = self.points.first()?.position;
+        let project = |position: Vector2<f32>| (position - origin).dot(&axis);
+
+        let mut sorted = (0..self.points.len()).collect::<Vec<_>>();
+        sorted.sort_by(|a, b| {
+            project(self.points[*a].position)
+                .partial_cmp(&project(self.points[*b].position))
+                .unwrap_or(Ordering::Equal)
+        });
+
+        let s = project(sampling_point);
+        for pair in sorted.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            let sa = project(self.points[a].position);
+            let sb = project(self.points[b].position);
+            if sa <= s && s <= sb {
+                let t = if sb > sa { (s - sa) / (sb - sa) } else { 0.0 };
+                return Some([(a, 1.0 - t), (b, t), (a, 0.0)]);
+            }
+        }
+
+        let first = *sorted.first()?;
+        let last = *sorted.last()?;
+        let closest = if s < project(self.points[first].position) {
+            first
+        } else {
+            last
+        };
+        Some([(closest, 1.0), (closest, 0.0), (closest, 0.0)])
+    }
+
     pub fn fetch_weights(&self, sampling_point: Vector2<f32>) -> Option<[(usize, f32); 3]> {
         if self.points.is_empty() {
             return None;
@@ -350,18 +443,29 @@ impl<T: EntityId> BlendSpace<T> {
             return Some([(0, 1.0), (0, 0.0), (0, 0.0)]);
         }
 
-        // Check if there's an edge that contains a projection of the sampling point.
-        if self.points.len() == 2 {
-            let edge = self.points[1].position - self.points[0].position;
-            let to_point = sampling_point - self.points[0].position;
-            let t = to_point.dot(&edge) / edge.dot(&edge);
-            if (0.0..=1.0).contains(&t) {
-                return Some([(0, (1.0 - t)), (1, t), (0, 0.0)]);
-            }
+        if self.kind == BlendSpaceKind::OneDimensional {
+            return self.fetch_weights_on_line(Vector2::x(), sampling_point);
         }
 
         let triangles = &self.triangles;
 
+        // There are no triangles if the points lie on a single line (or there are just two of
+        // them), blend them along the line.
+        if triangles.is_empty() {
+            let origin = self.points[0].position;
+            let axis = self
+                .points
+                .iter()
+                .map(|point| point.position - origin)
+                .max_by(|a, b| {
+                    a.norm_squared()
+                        .partial_cmp(&b.norm_squared())
+                        .unwrap_or(Ordering::Equal)
+                })
+                .and_then(|direction| direction.try_normalize(f32::EPSILON))?;
+            return self.fetch_weights_on_line(axis, sampling_point);
+        }
+
         // Try to find a triangle that contains the sampling point.
         for triangle in triangles.iter() {
             let ia = triangle[0] as usize;
@@ -415,13 +519,26 @@ impl<T: EntityId> BlendSpace<T> {
             }
         }
 
-        weights
+        // The sampling point is outside of the triangulation and does not project on any of the
+        // edges (it is near a corner), use the closest point.
+        weights.or_else(|| {
+            self.points
+                .iter()
+                .enumerate()
+                .min_by(|(_, a), (_, b)| {
+                    a.position
+                        .metric_distance(&sampling_point)
+                        .partial_cmp(&b.position.metric_distance(&sampling_point))
+                        .unwrap_or(Ordering::Equal)
+                })
+                .map(|(index, _)| [(index, 1.0), (index, 0.0), (index, 0.0)])
+        })
     }
 
     fn triangulate(&mut self) -> bool {
         self.triangles.clear();
 
-        if self.points.len() < 3 {
+        if self.points.len() < 3 || self.kind == BlendSpaceKind::OneDimensional {
             return false;
         }
 
@@ -453,7 +570,7 @@ impl<T: EntityId> BlendSpace<T> {
 mod test {
     use crate::{
         core::{algebra::Vector2, math::TriangleDefinition},
-        machine::node::blendspace::{BlendSpace, BlendSpacePoint},
+        machine::node::blendspace::{BlendSpace, BlendSpaceKind, BlendSpacePoint},
     };
     use fyrox_core::pool::ErasedHandle;
 
@@ -540,4 +657,63 @@ mod test {
             Some([(0, 0.0), (1, 1.0), (0, 0.0)])
         );
     }
+
+    fn point(x: f32, y: f32) -> BlendSpacePoint<ErasedHandle> {
+        BlendSpacePoint {
+            position: Vector2::new(x, y),
+            pose_source: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_one_dimensional_blend_space_sampling() {
+        let mut blend_space = BlendSpace::<ErasedHandle>::default();
+        blend_space.set_kind(BlendSpaceKind::OneDimensional);
+        // Idle, run and walk - the order of the points does not matter.
+        blend_space.set_points(vec![point(0.0, 0.0), point(4.0, 0.5), point(1.0, 0.0)]);
+        assert!(blend_space.triangles().is_empty());
+
+        assert_eq!(
+            blend_space.fetch_weights(Vector2::new(0.5, 0.0)),
+            Some([(0, 0.5), (2, 0.5), (0, 0.0)])
+        );
+        assert_eq!(
+            blend_space.fetch_weights(Vector2::new(2.5, 0.0)),
+            Some([(2, 0.5), (1, 0.5), (2, 0.0)])
+        );
+
+        // Out of range values are clamped.
+        assert_eq!(
+            blend_space.fetch_weights(Vector2::new(-1.0, 0.0)),
+            Some([(0, 1.0), (0, 0.0), (0, 0.0)])
+        );
+        assert_eq!(
+            blend_space.fetch_weights(Vector2::new(10.0, 0.0)),
+            Some([(1, 1.0), (1, 0.0), (1, 0.0)])
+        );
+    }
+
+    #[test]
+    fn test_blend_space_sampling_outside_of_triangulation() {
+        let mut blend_space = BlendSpace::<ErasedHandle>::default();
+        blend_space.set_points(vec![point(0.0, 0.0), point(1.0, 0.0), point(0.0, 1.0)]);
+
+        // Near a corner, no edge contains the projection of the point.
+        assert_eq!(
+            blend_space.fetch_weights(Vector2::new(2.0, -1.0)),
+            Some([(1, 1.0), (1, 0.0), (1, 0.0)])
+        );
+    }
+
+    #[test]
+    fn test_collinear_blend_space_sampling() {
+        let mut blend_space = BlendSpace::<ErasedHandle>::default();
+        blend_space.set_points(vec![point(0.0, 0.0), point(2.0, 2.0), point(1.0, 1.0)]);
+
+        let weights = blend_space.fetch_weights(Vector2::new(1.5, 1.5)).unwrap();
+        assert_eq!(weights[0].0, 2);
+        assert_eq!(weights[1].0, 1);
+        assert!((weights[0].1 - 0.5).abs() < 1.0e-5);
+        assert!((weights[1].1 - 0.5).abs() < 1.0e-5);
+    }
 }
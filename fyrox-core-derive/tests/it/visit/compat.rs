@@ -20,7 +20,7 @@
 
 //! Fight the compatibility hell with attributes! .. someday :)
 
-use fyrox_core::visitor::prelude::*;
+use fyrox_core::visitor::{prelude::*, VisitorFlags};
 
 // Comment it out and make sure it panics
 // #[derive(Debug, Clone, PartialEq, Visit)]
@@ -45,3 +45,52 @@ fn rename() {
 
     assert_eq!(data, data_default);
 }
+
+#[derive(Debug, Clone, PartialEq, Visit)]
+pub struct LayoutV1 {
+    pub a: f32,
+    pub b: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Visit)]
+pub struct LayoutV2 {
+    pub a: f32,
+    pub b: String,
+    pub c: u32,
+}
+
+#[test]
+fn ignore_field_errors() {
+    let mut data = LayoutV1 { a: 100.0, b: 123 };
+
+    let mut visitor = Visitor::new();
+    data.visit("Data", &mut visitor).unwrap();
+    let blob = visitor.save_binary_to_vec().unwrap();
+
+    // Strict reading must fail, because the layout was changed.
+    let mut visitor = Visitor::load_from_memory(&blob).unwrap();
+    let mut data_v2 = LayoutV2 {
+        a: 0.0,
+        b: "default".to_string(),
+        c: 42,
+    };
+    assert!(data_v2.visit("Data", &mut visitor).is_err());
+
+    // Lenient reading must restore matching fields and keep the rest untouched.
+    let mut visitor = Visitor::load_from_memory(&blob).unwrap();
+    visitor.flags |= VisitorFlags::IGNORE_FIELD_ERRORS;
+    let mut data_v2 = LayoutV2 {
+        a: 0.0,
+        b: "default".to_string(),
+        c: 42,
+    };
+    data_v2.visit("Data", &mut visitor).unwrap();
+    assert_eq!(
+        data_v2,
+        LayoutV2 {
+            a: 100.0,
+            b: "default".to_string(),
+            c: 42,
+        }
+    );
+}
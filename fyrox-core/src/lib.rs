@@ -62,6 +62,7 @@ pub mod math;
 pub mod net;
 pub mod numeric_range;
 pub mod pool;
+pub mod profiler;
 pub mod quadtree;
 pub mod rectpack;
 pub mod reflect;
@@ -76,6 +77,9 @@ pub mod watcher;
 pub use futures;
 pub use instant;
 
+#[cfg(target_os = "android")]
+pub use android_activity;
+
 pub use notify;
 
 #[cfg(target_arch = "wasm32")]
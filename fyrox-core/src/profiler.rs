@@ -0,0 +1,601 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Frame profiler. It collects timings of the instrumented scopes (see [`scope_profile`] macro)
+//! from every thread, timings of GPU work and arbitrary counters, and groups them by frames. The
+//! collected data could be shown in an in-game overlay or exported to `chrome://tracing` (see
+//! [`save_chrome_trace`]).
+//!
+//! Scope recording is compiled only when `enable_profiler` feature is enabled, otherwise the
+//! [`scope_profile`] macro does nothing. The profiler is also disabled at runtime by default, use
+//! [`set_enabled`] to enable it. When `enable_tracy` feature is enabled, every scope, frame mark
+//! and counter is also streamed to [Tracy](https://github.com/wolfpld/tracy) profiler.
+
+use crate::{instant::Instant, parking_lot::Mutex};
+use fxhash::FxHashMap;
+#[cfg(feature = "enable_profiler")]
+use std::{cell::Cell, sync::atomic::AtomicU64};
+use std::{
+    collections::VecDeque,
+    fmt::Write as _,
+    future::Future,
+    io::{self, Write},
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        LazyLock,
+    },
+    time::Duration,
+};
+
+/// Creates a profiler scope that lasts until the end of the current block. The scope is recorded
+/// only if the profiler is enabled, see [module docs](crate::profiler) for more info.
+///
+/// ```rust
+/// use fyrox_core::scope_profile;
+///
+/// fn update_physics() {
+///     scope_profile!("Physics");
+///
+///     // Do the actual work.
+/// }
+/// ```
+#[macro_export]
+macro_rules! scope_profile {
+    ($name:expr) => {
+        let _profiler_scope = $crate::profiler::ProfilerScope::new($name, file!(), line!());
+    };
+}
+
+/// A record of a single scope on CPU.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScopeRecord {
+    /// Name of the scope.
+    pub name: &'static str,
+    /// Index of the thread on which the scope was recorded. See [`thread_name`].
+    pub thread: u64,
+    /// Nesting level of the scope. Top-level scopes have zero depth.
+    pub depth: u32,
+    /// Start of the scope relative to the moment when the profiler was initialized.
+    pub start: Duration,
+    /// Duration of the scope.
+    pub duration: Duration,
+}
+
+/// A record of GPU work. GPU timings are obtained asynchronously, so they usually belong to one of
+/// the previous frames.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GpuScopeRecord {
+    /// Name of the scope.
+    pub name: &'static str,
+    /// Time spent by GPU to execute the commands of the scope.
+    pub duration: Duration,
+}
+
+/// Everything that was recorded during a single frame.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FrameRecord {
+    /// Index of the frame.
+    pub index: u64,
+    /// Start of the frame relative to the moment when the profiler was initialized.
+    pub start: Duration,
+    /// Total duration of the frame.
+    pub duration: Duration,
+    /// CPU scopes recorded during the frame, sorted by their start time.
+    pub scopes: Vec<ScopeRecord>,
+    /// GPU timings that were received during the frame.
+    pub gpu_scopes: Vec<GpuScopeRecord>,
+    /// Values of the counters at the end of the frame.
+    pub counters: Vec<(&'static str, f64)>,
+}
+
+impl FrameRecord {
+    /// Returns total time of all top-level scopes with the given name.
+    pub fn total_time(&self, name: &str) -> Duration {
+        self.scopes
+            .iter()
+            .filter(|scope| scope.depth == 0 && scope.name == name)
+            .map(|scope| scope.duration)
+            .sum()
+    }
+
+    /// Returns a value of the counter with the given name.
+    pub fn counter(&self, name: &str) -> Option<f64> {
+        self.counters
+            .iter()
+            .find_map(|(counter, value)| (*counter == name).then_some(*value))
+    }
+}
+
+/// Default amount of frames stored in the profiler history.
+pub const DEFAULT_HISTORY_SIZE: usize = 240;
+
+struct ProfilerState {
+    frame_index: u64,
+    frame_start: Duration,
+    scopes: Vec<ScopeRecord>,
+    gpu_scopes: Vec<GpuScopeRecord>,
+    counters: FxHashMap<&'static str, f64>,
+    history: VecDeque<FrameRecord>,
+    history_size: usize,
+    thread_names: FxHashMap<u64, String>,
+    #[cfg(feature = "enable_tracy")]
+    tracy: Option<tracy::TracyState>,
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+static EPOCH: LazyLock<Instant> = LazyLock::new(Instant::now);
+
+static PROFILER: LazyLock<Mutex<ProfilerState>> = LazyLock::new(|| {
+    Mutex::new(ProfilerState {
+        frame_index: 0,
+        frame_start: Duration::default(),
+        scopes: Default::default(),
+        gpu_scopes: Default::default(),
+        counters: Default::default(),
+        history: Default::default(),
+        history_size: DEFAULT_HISTORY_SIZE,
+        thread_names: Default::default(),
+        #[cfg(feature = "enable_tracy")]
+        tracy: None,
+    })
+});
+
+#[cfg(feature = "enable_profiler")]
+static NEXT_THREAD_INDEX: AtomicU64 = AtomicU64::new(0);
+
+#[cfg(feature = "enable_profiler")]
+thread_local! {
+    static THREAD_INDEX: u64 = {
+        let index = NEXT_THREAD_INDEX.fetch_add(1, Ordering::Relaxed);
+        let name = std::thread::current()
+            .name()
+            .map(|name| name.to_string())
+            .unwrap_or_else(|| format!("Thread {index}"));
+        PROFILER.lock().thread_names.insert(index, name);
+        index
+    };
+
+    static DEPTH: Cell<u32> = const { Cell::new(0) };
+}
+
+fn now() -> Duration {
+    EPOCH.elapsed()
+}
+
+/// Enables or disables the profiler at runtime. Disabling the profiler does not clear the
+/// collected data.
+pub fn set_enabled(enabled: bool) {
+    // Initialize the time origin.
+    let _ = now();
+    ENABLED.store(enabled, Ordering::Relaxed);
+
+    #[cfg(feature = "enable_tracy")]
+    if enabled {
+        let mut state = PROFILER.lock();
+        if state.tracy.is_none() {
+            state.tracy = Some(tracy::TracyState::new());
+        }
+    }
+}
+
+/// Returns `true` if the profiler is enabled, `false` - otherwise.
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Sets the maximum amount of frames stored in the history.
+pub fn set_history_size(size: usize) {
+    let mut state = PROFILER.lock();
+    state.history_size = size.max(1);
+    while state.history.len() > state.history_size {
+        state.history.pop_front();
+    }
+}
+
+/// Ends the current frame and puts everything recorded during the frame to the history. Must be
+/// called once per frame, the engine does this automatically.
+pub fn end_frame() {
+    if !is_enabled() {
+        return;
+    }
+
+    let end = now();
+    let mut state = PROFILER.lock();
+    let state = &mut *state;
+
+    let mut scopes = std::mem::take(&mut state.scopes);
+    scopes.sort_by_key(|scope| scope.start);
+    let mut counters = state
+        .counters
+        .iter()
+        .map(|(name, value)| (*name, *value))
+        .collect::<Vec<_>>();
+    counters.sort_by_key(|(name, _)| *name);
+
+    let record = FrameRecord {
+        index: state.frame_index,
+        start: state.frame_start,
+        duration: end.saturating_sub(state.frame_start),
+        scopes,
+        gpu_scopes: std::mem::take(&mut state.gpu_scopes),
+        counters,
+    };
+
+    #[cfg(feature = "enable_tracy")]
+    if let Some(tracy) = state.tracy.as_mut() {
+        tracy.end_frame(&record);
+    }
+
+    state.history.push_back(record);
+    while state.history.len() > state.history_size {
+        state.history.pop_front();
+    }
+    state.frame_index += 1;
+    state.frame_start = end;
+}
+
+/// Records GPU timings of some work. Usually called by the renderer when the results of GPU timer
+/// queries become available.
+pub fn record_gpu_scope(name: &'static str, duration: Duration) {
+    if is_enabled() {
+        PROFILER
+            .lock()
+            .gpu_scopes
+            .push(GpuScopeRecord { name, duration });
+    }
+}
+
+/// Sets a value of a counter (for example, the amount of draw calls). Counters keep their values
+/// between frames.
+pub fn set_counter(name: &'static str, value: f64) {
+    if is_enabled() {
+        PROFILER.lock().counters.insert(name, value);
+    }
+}
+
+/// Returns a copy of the last finished frame.
+pub fn last_frame() -> Option<FrameRecord> {
+    PROFILER.lock().history.back().cloned()
+}
+
+/// Calls the given closure with the frames history. The frames are sorted from the oldest to the
+/// newest.
+pub fn with_history<R>(func: impl FnOnce(&VecDeque<FrameRecord>) -> R) -> R {
+    func(&PROFILER.lock().history)
+}
+
+/// Removes all the collected data.
+pub fn clear() {
+    let mut state = PROFILER.lock();
+    state.history.clear();
+    state.scopes.clear();
+    state.gpu_scopes.clear();
+    state.counters.clear();
+}
+
+/// Returns the name of the thread with the given index. See [`ScopeRecord::thread`].
+pub fn thread_name(thread: u64) -> Option<String> {
+    PROFILER.lock().thread_names.get(&thread).cloned()
+}
+
+/// A guard that records a scope when dropped. Use [`scope_profile`] macro to create it.
+pub struct ProfilerScope {
+    #[cfg(feature = "enable_profiler")]
+    active: Option<ActiveScope>,
+}
+
+#[cfg(feature = "enable_profiler")]
+struct ActiveScope {
+    name: &'static str,
+    start: Duration,
+    #[cfg(feature = "enable_tracy")]
+    _span: Option<tracy_client::Span>,
+}
+
+impl ProfilerScope {
+    /// Starts a new scope. `file` and `line` are used only to locate the scope in Tracy.
+    #[inline]
+    #[allow(unused_variables)]
+    pub fn new(name: &'static str, file: &'static str, line: u32) -> Self {
+        #[cfg(feature = "enable_profiler")]
+        {
+            if !is_enabled() {
+                return Self { active: None };
+            }
+
+            DEPTH.with(|depth| depth.set(depth.get() + 1));
+
+            Self {
+                active: Some(ActiveScope {
+                    name,
+                    start: now(),
+                    #[cfg(feature = "enable_tracy")]
+                    _span: tracy::span(name, file, line),
+                }),
+            }
+        }
+
+        #[cfg(not(feature = "enable_profiler"))]
+        Self {}
+    }
+}
+
+#[cfg(feature = "enable_profiler")]
+impl Drop for ProfilerScope {
+    fn drop(&mut self) {
+        if let Some(active) = self.active.take() {
+            let depth = DEPTH.with(|depth| {
+                let value = depth.get().saturating_sub(1);
+                depth.set(value);
+                value
+            });
+            let thread = THREAD_INDEX.with(|index| *index);
+            let record = ScopeRecord {
+                name: active.name,
+                thread,
+                depth,
+                start: active.start,
+                duration: now().saturating_sub(active.start),
+            };
+            PROFILER.lock().scopes.push(record);
+        }
+    }
+}
+
+/// Wraps the given future, so every poll of it is recorded as a separate scope with the given
+/// name. Scopes cannot be held across `.await` points, because the future could be resumed on a
+/// different thread, so this function must be used to profile asynchronous tasks.
+pub async fn profile_future<F: Future>(name: &'static str, future: F) -> F::Output {
+    let mut future = std::pin::pin!(future);
+    std::future::poll_fn(move |cx| {
+        let _scope = ProfilerScope::new(name, file!(), line!());
+        future.as_mut().poll(cx)
+    })
+    .await
+}
+
+fn write_json_string(out: &mut String, string: &str) {
+    out.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Converts the given frames to the [Trace Event Format](https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU)
+/// that is supported by `chrome://tracing`, [Perfetto](https://ui.perfetto.dev) and many other
+/// tools. CPU scopes are written as complete events, counters - as counter events. GPU scopes do
+/// not have exact start time, so they're laid out one after another at the start of their frame
+/// on a separate "GPU" track.
+pub fn chrome_trace<'a>(
+    frames: impl IntoIterator<Item = &'a FrameRecord>,
+    thread_names: &FxHashMap<u64, String>,
+) -> String {
+    const GPU_THREAD: u64 = u64::MAX;
+
+    let mut events = Vec::new();
+
+    for (thread, name) in thread_names
+        .iter()
+        .map(|(thread, name)| (*thread, name.as_str()))
+        .chain([(GPU_THREAD, "GPU")])
+    {
+        let mut event = String::new();
+        let _ = write!(
+            event,
+            r#"{{"name":"thread_name","ph":"M","pid":0,"tid":{thread},"args":{{"name":"#
+        );
+        write_json_string(&mut event, name);
+        event.push_str("}}");
+        events.push(event);
+    }
+
+    for frame in frames {
+        let mut event = String::new();
+        let _ = write!(
+            event,
+            r#"{{"name":"Frame {}","ph":"X","pid":0,"tid":0,"ts":{:.3},"dur":{:.3}}}"#,
+            frame.index,
+            frame.start.as_secs_f64() * 1_000_000.0,
+            frame.duration.as_secs_f64() * 1_000_000.0,
+        );
+        events.push(event);
+
+        for scope in frame.scopes.iter() {
+            let mut event = String::from(r#"{"name":"#);
+            write_json_string(&mut event, scope.name);
+            let _ = write!(
+                event,
+                r#","ph":"X","pid":0,"tid":{},"ts":{:.3},"dur":{:.3}}}"#,
+                scope.thread,
+                scope.start.as_secs_f64() * 1_000_000.0,
+                scope.duration.as_secs_f64() * 1_000_000.0,
+            );
+            events.push(event);
+        }
+
+        let mut gpu_time = frame.start;
+        for scope in frame.gpu_scopes.iter() {
+            let mut event = String::from(r#"{"name":"#);
+            write_json_string(&mut event, scope.name);
+            let _ = write!(
+                event,
+                r#","ph":"X","pid":0,"tid":{GPU_THREAD},"ts":{:.3},"dur":{:.3}}}"#,
+                gpu_time.as_secs_f64() * 1_000_000.0,
+                scope.duration.as_secs_f64() * 1_000_000.0,
+            );
+            events.push(event);
+            gpu_time += scope.duration;
+        }
+
+        for (name, value) in frame.counters.iter() {
+            let mut event = String::from(r#"{"name":"#);
+            write_json_string(&mut event, name);
+            let _ = write!(
+                event,
+                r#","ph":"C","pid":0,"ts":{:.3},"args":{{"value":{value}}}}}"#,
+                (frame.start + frame.duration).as_secs_f64() * 1_000_000.0,
+            );
+            events.push(event);
+        }
+    }
+
+    format!("[\n{}\n]\n", events.join(",\n"))
+}
+
+/// Writes the frames history in the format supported by `chrome://tracing`. See [`chrome_trace`]
+/// for more info.
+pub fn write_chrome_trace(writer: &mut dyn Write) -> io::Result<()> {
+    let trace = {
+        let state = PROFILER.lock();
+        chrome_trace(state.history.iter(), &state.thread_names)
+    };
+    writer.write_all(trace.as_bytes())
+}
+
+/// Saves the frames history to a file in the format supported by `chrome://tracing`. See
+/// [`chrome_trace`] for more info.
+pub fn save_chrome_trace(path: impl AsRef<Path>) -> io::Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    write_chrome_trace(&mut file)
+}
+
+#[cfg(feature = "enable_tracy")]
+mod tracy {
+    use super::FrameRecord;
+    use fxhash::FxHashMap;
+
+    pub struct TracyState {
+        client: tracy_client::Client,
+        plot_names: FxHashMap<String, tracy_client::PlotName>,
+    }
+
+    impl TracyState {
+        pub fn new() -> Self {
+            Self {
+                client: tracy_client::Client::start(),
+                plot_names: Default::default(),
+            }
+        }
+
+        fn plot(&mut self, name: String, value: f64) {
+            let plot_name = *self
+                .plot_names
+                .entry(name)
+                .or_insert_with_key(|name| tracy_client::PlotName::new_leak(name.clone()));
+            self.client.plot(plot_name, value);
+        }
+
+        pub fn end_frame(&mut self, record: &FrameRecord) {
+            for scope in record.gpu_scopes.iter() {
+                self.plot(
+                    format!("GPU: {} (ms)", scope.name),
+                    scope.duration.as_secs_f64() * 1000.0,
+                );
+            }
+            for (name, value) in record.counters.iter() {
+                self.plot(name.to_string(), *value);
+            }
+            self.client.frame_mark();
+        }
+    }
+
+    pub fn span(name: &str, file: &str, line: u32) -> Option<tracy_client::Span> {
+        tracy_client::Client::running()
+            .map(|client| client.span_alloc(Some(name), "", file, line, 0))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_chrome_trace() {
+        let frame = FrameRecord {
+            index: 3,
+            start: Duration::from_millis(1),
+            duration: Duration::from_millis(16),
+            scopes: vec![ScopeRecord {
+                name: "Render \"Scene\"",
+                thread: 0,
+                depth: 0,
+                start: Duration::from_millis(2),
+                duration: Duration::from_micros(1500),
+            }],
+            gpu_scopes: vec![GpuScopeRecord {
+                name: "GBuffer",
+                duration: Duration::from_millis(3),
+            }],
+            counters: vec![("DrawCalls", 42.0)],
+        };
+        let mut thread_names = FxHashMap::default();
+        thread_names.insert(0, "main".to_string());
+
+        let trace = chrome_trace([&frame], &thread_names);
+        assert!(trace.starts_with('[') && trace.trim_end().ends_with(']'));
+        assert!(trace.contains(r#""args":{"name":"main"}"#));
+        assert!(trace.contains(
+            r#"{"name":"Frame 3","ph":"X","pid":0,"tid":0,"ts":1000.000,"dur":16000.000}"#
+        ));
+        assert!(trace.contains(
+            r#"{"name":"Render \"Scene\"","ph":"X","pid":0,"tid":0,"ts":2000.000,"dur":1500.000}"#
+        ));
+        assert!(trace.contains(r#""name":"GBuffer","ph":"X","pid":0,"tid":18446744073709551615,"ts":1000.000,"dur":3000.000"#));
+        assert!(trace.contains(
+            r#"{"name":"DrawCalls","ph":"C","pid":0,"ts":17000.000,"args":{"value":42}}"#
+        ));
+    }
+
+    #[test]
+    fn test_frame_record_queries() {
+        let scope = |name, depth, duration| ScopeRecord {
+            name,
+            thread: 0,
+            depth,
+            start: Duration::default(),
+            duration: Duration::from_millis(duration),
+        };
+        let frame = FrameRecord {
+            scopes: vec![
+                scope("Update", 0, 2),
+                scope("Physics", 1, 1),
+                scope("Update", 0, 3),
+            ],
+            counters: vec![("Triangles", 100.0)],
+            ..Default::default()
+        };
+        assert_eq!(frame.total_time("Update"), Duration::from_millis(5));
+        assert_eq!(frame.total_time("Physics"), Duration::ZERO);
+        assert_eq!(frame.counter("Triangles"), Some(100.0));
+        assert_eq!(frame.counter("Foo"), None);
+    }
+}
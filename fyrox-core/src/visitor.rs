@@ -1231,6 +1231,11 @@ bitflags! {
         /// and therefore write its data. Otherwise, InheritableVariable has the special
         /// property of *not writing itself* when the `MODIFIED` flag is not set.
         const SERIALIZE_EVERYTHING = 1 << 1;
+        /// Tell derived [Visit] implementations to keep the current value of a field, if it cannot
+        /// be read (for example, when the field is missing or its type was changed), instead of
+        /// failing the whole region. This is used to migrate data between different layouts of the
+        /// same type, for example when hot-reloading scripts.
+        const IGNORE_FIELD_ERRORS = 1 << 2;
     }
 }
 
@@ -1356,6 +1361,13 @@ impl Visitor {
         self.reading
     }
 
+    /// True if [`VisitorFlags::IGNORE_FIELD_ERRORS`] flag is set and this visitor is reading. In this
+    /// case, derived [Visit] implementations will skip fields that cannot be read, leaving them
+    /// untouched.
+    pub fn ignores_field_errors(&self) -> bool {
+        self.reading && self.flags.contains(VisitorFlags::IGNORE_FIELD_ERRORS)
+    }
+
     fn current_node(&mut self) -> &mut VisitorNode {
         self.nodes.borrow_mut(self.current_node)
     }
@@ -175,6 +175,18 @@ where
         node.as_reflect_mut(&mut |node| self.remap_handles_internal(node, &name, ignored_types));
     }
 
+    /// The same as [`Self::remap_handles`], but works with an arbitrary entity (a script of a node,
+    /// for example). `owner_name` is used only in warnings.
+    #[inline]
+    pub fn remap_entity_handles(
+        &self,
+        entity: &mut dyn Reflect,
+        owner_name: &str,
+        ignored_types: &[TypeId],
+    ) {
+        self.remap_handles_internal(entity, owner_name, ignored_types)
+    }
+
     fn remap_handles_internal(
         &self,
         entity: &mut dyn Reflect,
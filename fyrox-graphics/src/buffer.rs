@@ -45,6 +45,11 @@ pub enum BufferKind {
     /// Pixel write buffer. It is a special buffer that is used to write some data to GPU
     /// asynchronously.
     PixelWrite,
+    /// Indirect draw buffer. It is used to source the parameters of draw calls directly from GPU
+    /// memory, the buffer must contain a tightly packed array of
+    /// [`crate::framebuffer::IndirectDrawCommand`]. See
+    /// [`crate::framebuffer::GpuFrameBufferTrait::draw_indirect`] for more info.
+    DrawIndirect,
 }
 
 /// A hint for video driver that allows it to optimize buffer's content for more efficient use.
@@ -21,21 +21,125 @@
 //! Contains all possible errors that may occur during rendering, initialization of
 //! renderer structures, or GAPI.
 
+use crate::gpu_texture::{GpuTextureKind, PixelKind};
 use std::{
     error::Error,
     ffi::NulError,
     fmt::{Display, Formatter},
 };
 
+/// Severity of a shader compiler message.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ShaderDiagnosticSeverity {
+    /// The shader cannot be compiled.
+    Error,
+    /// The shader is compiled, but it may not work as expected.
+    Warning,
+    /// Any other message.
+    Info,
+}
+
+impl Display for ShaderDiagnosticSeverity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Error => write!(f, "error"),
+            Self::Warning => write!(f, "warning"),
+            Self::Info => write!(f, "info"),
+        }
+    }
+}
+
+/// A single message of a shader compiler, mapped back to the source it points to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShaderDiagnostic {
+    /// Severity of the message.
+    pub severity: ShaderDiagnosticSeverity,
+    /// Name of the source the message points to. It could be the name of the shader itself, the
+    /// name of an included file (for example, `shared.glsl`) or `<resources>` for the declarations
+    /// of the shader resources generated by the engine.
+    pub source: String,
+    /// Line number (starting from 1) within the source, if it was reported by the compiler.
+    pub line: Option<usize>,
+    /// The message itself.
+    pub message: String,
+}
+
+impl Display for ShaderDiagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.source)?;
+        if let Some(line) = self.line {
+            write!(f, ":{line}")?;
+        }
+        write!(f, ": {}: {}", self.severity, self.message)
+    }
+}
+
+/// The reason why a frame buffer is incomplete.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum FrameBufferIncompleteness {
+    /// The default frame buffer does not exist.
+    Undefined,
+    /// One of the attachments is incomplete, for example, its texture has zero size or a pixel
+    /// kind that cannot be rendered to.
+    IncompleteAttachment,
+    /// The frame buffer does not have any attachments.
+    MissingAttachment,
+    /// A draw buffer points to an attachment that does not exist.
+    IncompleteDrawBuffer,
+    /// The read buffer points to an attachment that does not exist.
+    IncompleteReadBuffer,
+    /// The combination of pixel kinds of the attachments is not supported by the implementation.
+    Unsupported,
+    /// The attachments have different number of samples.
+    IncompleteMultisample,
+    /// Some attachments are layered (cube maps or volume textures) and some are not.
+    IncompleteLayerTargets,
+    /// Any other reason with the status code reported by the graphics API.
+    Unknown(u32),
+}
+
+impl Display for FrameBufferIncompleteness {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Undefined => write!(f, "the default frame buffer does not exist"),
+            Self::IncompleteAttachment => write!(
+                f,
+                "one of the attachments is incomplete (zero size or non-renderable pixel kind)"
+            ),
+            Self::MissingAttachment => write!(f, "there are no attachments"),
+            Self::IncompleteDrawBuffer => {
+                write!(f, "a draw buffer points to a missing attachment")
+            }
+            Self::IncompleteReadBuffer => {
+                write!(f, "the read buffer points to a missing attachment")
+            }
+            Self::Unsupported => write!(
+                f,
+                "the combination of pixel kinds of the attachments is not supported"
+            ),
+            Self::IncompleteMultisample => {
+                write!(f, "the attachments have different number of samples")
+            }
+            Self::IncompleteLayerTargets => {
+                write!(f, "some attachments are layered and some are not")
+            }
+            Self::Unknown(status) => write!(f, "unknown status {status:#x}"),
+        }
+    }
+}
+
 /// Set of possible renderer errors.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum FrameworkError {
     /// Compilation of a shader has failed.
     ShaderCompilationFailed {
         /// Name of shader.
         shader_name: String,
-        /// Compilation error message.
+        /// Compilation error message, exactly as it was reported by the compiler.
         error_message: String,
+        /// Messages of the compiler, mapped back to the sources they point to. Could be empty, if
+        /// the format of the compiler messages is unknown.
+        diagnostics: Vec<ShaderDiagnostic>,
     },
     /// Means that shader link stage failed, exact reason is inside `error_message`
     ShaderLinkingFailed {
@@ -52,6 +156,12 @@ pub enum FrameworkError {
     UnableToFindShaderUniformBlock(String),
     /// Texture has invalid data - insufficient size.
     InvalidTextureData {
+        /// Kind of the texture (or of the region of the texture) the data is for.
+        kind: GpuTextureKind,
+        /// Pixel kind of the texture.
+        pixel_kind: PixelKind,
+        /// Amount of mip levels in the data.
+        mip_count: usize,
         /// Expected data size in bytes.
         expected_data_size: usize,
         /// Actual data size in bytes.
@@ -78,11 +188,41 @@ pub enum FrameworkError {
     /// Framebuffer is invalid.
     InvalidFrameBuffer,
     /// OpenGL failed to construct framebuffer.
-    FailedToConstructFBO,
+    FailedToConstructFBO {
+        /// The reason why the frame buffer is incomplete.
+        reason: FrameBufferIncompleteness,
+    },
     /// Custom error. Usually used for internal errors.
     Custom(String),
     /// Graphics server disconnected.
     GraphicsServerUnavailable,
+    /// An error with a description of what was being done when it occurred (for example, which
+    /// asset was being processed). See [`FrameworkError::context`].
+    Context {
+        /// Description of the operation.
+        context: String,
+        /// The actual error.
+        error: Box<FrameworkError>,
+    },
+}
+
+impl FrameworkError {
+    /// Wraps the error with a description of the operation, that has caused the error. For example
+    /// `"Creating GPU texture for data/textures/wall.png"`.
+    pub fn context(self, context: impl Into<String>) -> Self {
+        Self::Context {
+            context: context.into(),
+            error: Box::new(self),
+        }
+    }
+
+    /// Returns the innermost error, skipping all the context wrappers.
+    pub fn root_cause(&self) -> &Self {
+        match self {
+            Self::Context { error, .. } => error.root_cause(),
+            _ => self,
+        }
+    }
 }
 
 impl Display for FrameworkError {
@@ -91,11 +231,20 @@ impl Display for FrameworkError {
             FrameworkError::ShaderCompilationFailed {
                 shader_name,
                 error_message,
+                diagnostics,
             } => {
-                write!(
-                    f,
-                    "Compilation of \"{shader_name}\" shader has failed: {error_message}",
-                )
+                if diagnostics.is_empty() {
+                    write!(
+                        f,
+                        "Compilation of \"{shader_name}\" shader has failed: {error_message}",
+                    )
+                } else {
+                    write!(f, "Compilation of \"{shader_name}\" shader has failed:")?;
+                    for diagnostic in diagnostics {
+                        write!(f, "\n{diagnostic}")?;
+                    }
+                    Ok(())
+                }
             }
             FrameworkError::ShaderLinkingFailed {
                 shader_name,
@@ -116,13 +265,17 @@ impl Display for FrameworkError {
                 write!(f, "There is no such shader uniform block: {v}")
             }
             FrameworkError::InvalidTextureData {
+                kind,
+                pixel_kind,
+                mip_count,
                 expected_data_size,
                 actual_data_size,
             } => {
                 write!(
                     f,
-                    "Texture has invalid data (insufficent size): \
-                expected {expected_data_size}, actual: {actual_data_size}",
+                    "Texture has invalid data (insufficent size) for {kind:?} texture with \
+                    {mip_count} mip(s) of {pixel_kind:?} pixels: expected {expected_data_size}, \
+                    actual: {actual_data_size}",
                 )
             }
             FrameworkError::EmptyTextureData => {
@@ -148,8 +301,8 @@ impl Display for FrameworkError {
             FrameworkError::InvalidFrameBuffer => {
                 write!(f, "Framebuffer is invalid")
             }
-            FrameworkError::FailedToConstructFBO => {
-                write!(f, "OpenGL failed to construct framebuffer.")
+            FrameworkError::FailedToConstructFBO { reason } => {
+                write!(f, "OpenGL failed to construct framebuffer: {reason}.")
             }
             FrameworkError::Custom(v) => {
                 write!(f, "Custom error: {v}")
@@ -157,6 +310,9 @@ impl Display for FrameworkError {
             FrameworkError::GraphicsServerUnavailable => {
                 write!(f, "Graphics server disconnected.")
             }
+            FrameworkError::Context { context, error } => {
+                write!(f, "{context}: {error}")
+            }
         }
     }
 }
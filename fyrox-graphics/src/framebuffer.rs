@@ -32,8 +32,12 @@ use crate::{
     geometry_buffer::GpuGeometryBuffer,
     gpu_program::GpuProgram,
     gpu_texture::{CubeMapFace, GpuTexture},
+    pipeline::GpuPipelineState,
+    sampler::GpuSampler,
     DrawParameters, ElementRange,
 };
+use bytemuck::{Pod, Zeroable};
+use std::cell::Cell;
 
 /// Frame buffer attachment kind.
 #[derive(Copy, Clone, PartialOrd, PartialEq, Hash, Debug, Eq)]
@@ -47,37 +51,88 @@ pub enum AttachmentKind {
     Depth,
 }
 
+/// Defines which layer of a texture is attached to a frame buffer.
+#[derive(Copy, Clone, PartialOrd, PartialEq, Hash, Debug, Eq, Default)]
+pub enum AttachmentLayer {
+    /// The first layer of the texture. It is the entire texture for line and rectangle textures,
+    /// the positive X face for cube maps and the first slice for volume textures.
+    #[default]
+    First,
+    /// A specific slice of a volume texture or a specific face of a cube map. Faces of cube maps
+    /// are numbered in [`CubeMapFace`] order.
+    Specific(usize),
+    /// Every layer of a cube map or a volume texture at once (layered rendering). The layer each
+    /// primitive is rendered to is selected by `gl_Layer` output of a shader, which allows to
+    /// render all faces of a cube map (for example, a light probe) in a single pass.
+    All,
+}
+
 /// Frame buffer attachment.
 pub struct Attachment {
     /// Current kind of attachment. Tells the renderer how the texture should be used.
     pub kind: AttachmentKind,
     /// A texture that is used to write the rendered image to.
     pub texture: GpuTexture,
+    // Interior mutability is needed, because frame buffers are shared and the target of an
+    // attachment could be changed via [`GpuFrameBufferTrait::set_color_attachment_target`].
+    pub(crate) level: Cell<usize>,
+    pub(crate) layer: Cell<AttachmentLayer>,
 }
 
 impl Attachment {
-    /// Creates a new [`AttachmentKind::Color`] attachment with the given texture.
-    pub fn color(texture: GpuTexture) -> Self {
+    fn new(kind: AttachmentKind, texture: GpuTexture) -> Self {
         Self {
-            kind: AttachmentKind::Color,
+            kind,
             texture,
+            level: Cell::new(0),
+            layer: Default::default(),
         }
     }
 
+    /// Returns the mip level of the texture that is used to write the rendered image to.
+    pub fn level(&self) -> usize {
+        self.level.get()
+    }
+
+    /// Returns the layer of the texture that is used to write the rendered image to. See
+    /// [`AttachmentLayer`] docs for more info.
+    pub fn layer(&self) -> AttachmentLayer {
+        self.layer.get()
+    }
+
+    /// Creates a new [`AttachmentKind::Color`] attachment with the given texture.
+    pub fn color(texture: GpuTexture) -> Self {
+        Self::new(AttachmentKind::Color, texture)
+    }
+
     /// Creates a new [`AttachmentKind::Depth`] attachment with the given texture.
     pub fn depth(texture: GpuTexture) -> Self {
-        Self {
-            kind: AttachmentKind::Depth,
-            texture,
-        }
+        Self::new(AttachmentKind::Depth, texture)
     }
 
     /// Creates a new [`AttachmentKind::DepthStencil`] attachment with the given texture.
     pub fn depth_stencil(texture: GpuTexture) -> Self {
-        Self {
-            kind: AttachmentKind::DepthStencil,
-            texture,
-        }
+        Self::new(AttachmentKind::DepthStencil, texture)
+    }
+
+    /// Sets the mip level of the texture that will be attached. Could be used to render into
+    /// specific mips of a texture (for example, to generate a blurred mip chain of a variance
+    /// shadow map).
+    pub fn with_level(self, level: usize) -> Self {
+        self.level.set(level);
+        self
+    }
+
+    /// Sets the layer of the texture that will be attached.
+    pub fn with_layer(self, layer: AttachmentLayer) -> Self {
+        self.layer.set(layer);
+        self
+    }
+
+    /// Sets the face of a cube map that will be attached. It is a shortcut for
+    /// [`Self::with_layer`] with [`AttachmentLayer::Specific`].
+    pub fn with_face(self, face: CubeMapFace) -> Self {
+        self.with_layer(AttachmentLayer::Specific(face as usize))
     }
 }
 
@@ -106,6 +161,8 @@ pub enum ResourceBinding {
     Texture {
         /// A shared reference to a texture.
         texture: GpuTexture,
+        /// An optional sampler, that overrides the sampling parameters of the texture.
+        sampler: Option<GpuSampler>,
         /// Binding mode for the texture.
         binding: usize,
     },
@@ -125,6 +182,21 @@ impl ResourceBinding {
     pub fn texture(texture: &GpuTexture, binding: usize) -> Self {
         Self::Texture {
             texture: texture.clone(),
+            sampler: None,
+            binding,
+        }
+    }
+
+    /// Creates a new explicit texture binding, that uses the given sampler instead of the
+    /// sampling parameters of the texture.
+    pub fn texture_with_sampler(
+        texture: &GpuTexture,
+        sampler: &GpuSampler,
+        binding: usize,
+    ) -> Self {
+        Self::Texture {
+            texture: texture.clone(),
+            sampler: Some(sampler.clone()),
             binding,
         }
     }
@@ -153,6 +225,27 @@ pub struct DrawCallStatistics {
     pub triangles: usize,
 }
 
+/// Parameters of a single indexed draw call, that are sourced from a GPU buffer by
+/// [`GpuFrameBufferTrait::draw_indirect`]. The layout of this structure matches the layout expected
+/// by the video driver, so an array of such commands could be uploaded to a buffer of
+/// [`crate::buffer::BufferKind::DrawIndirect`] kind as is. Usually, the commands are written by a
+/// compute shader (for example, a GPU culling pass), but they could be written from CPU side as
+/// well.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Pod, Zeroable)]
+pub struct IndirectDrawCommand {
+    /// Total number of indices to draw.
+    pub index_count: u32,
+    /// Total number of instances to draw. Zero means that the command will be skipped.
+    pub instance_count: u32,
+    /// Index of the first index in the index buffer of a geometry buffer.
+    pub first_index: u32,
+    /// A value that will be added to every index fetched from the index buffer.
+    pub base_vertex: i32,
+    /// Index of the first instance. It is used as an offset for instanced vertex attributes.
+    pub base_instance: u32,
+}
+
 /// Frame buffer is a set of images that is used as a storage for an image generated by a renderer.
 /// It consists of one or more color buffers and an optional depth/stencil buffer. Frame buffer is
 /// a high level abstraction that consolidates multiple images and supports drawing meshes to them
@@ -165,8 +258,27 @@ pub trait GpuFrameBufferTrait: Downcast {
     fn depth_attachment(&self) -> Option<&Attachment>;
 
     /// Sets an active face of a cube map (only for frame buffers that using cube maps for rendering).
+    /// Mip level of the attachment is preserved, the layer of the attachment is set to the face.
     fn set_cubemap_face(&self, attachment_index: usize, face: CubeMapFace);
 
+    /// Re-attaches the given mip level and layer of the texture of the color attachment with the
+    /// given index. It is much faster than creating a new frame buffer per each mip level or layer.
+    /// Returns an error if the level or the layer is out of bounds of the texture, the attachment
+    /// keeps its previous target in this case.
+    fn set_color_attachment_target(
+        &self,
+        attachment_index: usize,
+        level: usize,
+        layer: AttachmentLayer,
+    ) -> Result<(), FrameworkError>;
+
+    /// Returns `true` if the frame buffer does not have any color attachments and only writes to
+    /// its depth (or depth-stencil) attachment. Such frame buffers are used for depth-only passes
+    /// (shadow maps, depth pre-pass, etc.).
+    fn is_depth_only(&self) -> bool {
+        self.color_attachments().is_empty() && self.depth_attachment().is_some()
+    }
+
     /// Performs data transfer from one frame buffer to another with scaling. It copies a region
     /// defined by `src_x0`, `src_y0`, `src_x1`, `src_y1` coordinates from the frame buffer and
     /// "pastes" it to the other frame buffer into a region defined by `dst_x0`, `dst_y0`, `dst_x1`,
@@ -240,6 +352,41 @@ pub trait GpuFrameBufferTrait: Downcast {
         resources: &[ResourceBindGroup],
         element_range: ElementRange,
     ) -> Result<DrawCallStatistics, FrameworkError>;
+
+    /// Almost the same as [`Self::draw_instances`], but uses a pipeline state object instead of a
+    /// separate program and drawing parameters. Consecutive draw calls with the same pipeline state
+    /// do not change the state of the graphics pipeline at all, which makes them much cheaper on
+    /// CPU side. See [`crate::pipeline::GpuPipelineStateTrait`] docs for more info.
+    fn draw_with_pipeline_state(
+        &self,
+        instance_count: usize,
+        geometry: &GpuGeometryBuffer,
+        viewport: Rect<i32>,
+        pipeline_state: &GpuPipelineState,
+        resources: &[ResourceBindGroup],
+        element_range: ElementRange,
+    ) -> Result<DrawCallStatistics, FrameworkError>;
+
+    /// Almost the same as [`Self::draw_instances`], but the parameters of draw calls are sourced from
+    /// the given `indirect_buffer`, which must be of [`crate::buffer::BufferKind::DrawIndirect`] kind
+    /// and contain at least `draw_count` [`IndirectDrawCommand`]s starting from `offset` (in bytes,
+    /// must be a multiple of 4). This method allows the GPU to decide what and how much to draw without
+    /// any round trips to CPU, which is the base building block of GPU-driven rendering pipelines.
+    ///
+    /// The returned statistics does not contain the number of rendered triangles, because it is
+    /// unknown on CPU side. Indirect drawing requires OpenGL 4.0 or OpenGL ES 3.1, the support could be
+    /// checked by [`crate::server::ServerCapabilities::indirect_draw`].
+    fn draw_indirect(
+        &self,
+        geometry: &GpuGeometryBuffer,
+        viewport: Rect<i32>,
+        program: &GpuProgram,
+        params: &DrawParameters,
+        resources: &[ResourceBindGroup],
+        indirect_buffer: &GpuBuffer,
+        offset: usize,
+        draw_count: usize,
+    ) -> Result<DrawCallStatistics, FrameworkError>;
 }
 
 define_shared_wrapper!(GpuFrameBuffer<dyn GpuFrameBufferTrait>);
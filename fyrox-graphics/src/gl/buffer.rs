@@ -35,6 +35,7 @@ impl ToGlConstant for BufferKind {
             BufferKind::Uniform => glow::UNIFORM_BUFFER,
             BufferKind::PixelRead => glow::PIXEL_PACK_BUFFER,
             BufferKind::PixelWrite => glow::PIXEL_UNPACK_BUFFER,
+            BufferKind::DrawIndirect => glow::DRAW_INDIRECT_BUFFER,
         }
     }
 }
@@ -61,6 +62,9 @@ pub struct GlBuffer {
     pub size: Cell<usize>,
     pub kind: BufferKind,
     pub usage: BufferUsage,
+    // Storage of the buffer is managed by its owner (a ring buffer, for example) and cannot be
+    // written or reallocated directly.
+    pub is_owned_storage: bool,
 }
 
 impl GlBuffer {
@@ -87,6 +91,7 @@ impl GlBuffer {
                 size: Cell::new(size_bytes),
                 kind,
                 usage,
+                is_owned_storage: false,
             })
         }
     }
@@ -120,6 +125,13 @@ impl GpuBufferTrait for GlBuffer {
             return Ok(());
         }
 
+        if self.is_owned_storage {
+            return Err(FrameworkError::Custom(
+                "The buffer cannot be written directly, use its owner to write the data!"
+                    .to_string(),
+            ));
+        }
+
         let Some(server) = self.state.upgrade() else {
             return Err(FrameworkError::GraphicsServerUnavailable);
         };
@@ -19,20 +19,23 @@
 // SOFTWARE.
 
 use crate::{
-    buffer::GpuBufferTrait,
+    buffer::{BufferKind, GpuBuffer, GpuBufferTrait},
     core::{color::Color, math::Rect},
-    error::FrameworkError,
+    error::{FrameBufferIncompleteness, FrameworkError},
     framebuffer::{
-        Attachment, AttachmentKind, BufferDataUsage, DrawCallStatistics, GpuFrameBuffer,
-        GpuFrameBufferTrait, ResourceBindGroup, ResourceBinding,
+        Attachment, AttachmentKind, AttachmentLayer, BufferDataUsage, DrawCallStatistics,
+        GpuFrameBuffer, GpuFrameBufferTrait, IndirectDrawCommand, ResourceBindGroup,
+        ResourceBinding,
     },
     geometry_buffer::GpuGeometryBuffer,
     gl::{
-        buffer::GlBuffer, geometry_buffer::GlGeometryBuffer, program::GlProgram,
-        server::GlGraphicsServer, texture::GlTexture, ToGlConstant,
+        buffer::GlBuffer, geometry_buffer::GlGeometryBuffer, pipeline::GlPipelineState,
+        program::GlProgram, sampler::GlSampler, server::GlGraphicsServer, texture::GlTexture,
+        ToGlConstant,
     },
     gpu_program::GpuProgram,
-    gpu_texture::{CubeMapFace, GpuTextureKind, GpuTextureTrait, PixelElementKind},
+    gpu_texture::{CubeMapFace, GpuTextureKind, GpuTextureTrait, PixelElementKind, PixelKind},
+    pipeline::GpuPipelineState,
     ColorMask, DrawParameters, ElementRange,
 };
 use glow::HasContext;
@@ -43,47 +46,135 @@ pub struct GlFrameBuffer {
     fbo: Option<glow::Framebuffer>,
     depth_attachment: Option<Attachment>,
     color_attachments: Vec<Attachment>,
+    // Render buffers of multisampled frame buffers, see `GlFrameBuffer::new_multisampled`.
+    render_buffers: Vec<glow::Renderbuffer>,
 }
 
-unsafe fn set_attachment(server: &GlGraphicsServer, gl_attachment_kind: u32, texture: &GlTexture) {
-    match texture.kind() {
-        GpuTextureKind::Line { .. } => {
+fn incompleteness_from_status(status: u32) -> FrameBufferIncompleteness {
+    match status {
+        glow::FRAMEBUFFER_UNDEFINED => FrameBufferIncompleteness::Undefined,
+        glow::FRAMEBUFFER_INCOMPLETE_ATTACHMENT => FrameBufferIncompleteness::IncompleteAttachment,
+        glow::FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT => {
+            FrameBufferIncompleteness::MissingAttachment
+        }
+        glow::FRAMEBUFFER_INCOMPLETE_DRAW_BUFFER => FrameBufferIncompleteness::IncompleteDrawBuffer,
+        glow::FRAMEBUFFER_INCOMPLETE_READ_BUFFER => FrameBufferIncompleteness::IncompleteReadBuffer,
+        glow::FRAMEBUFFER_UNSUPPORTED => FrameBufferIncompleteness::Unsupported,
+        glow::FRAMEBUFFER_INCOMPLETE_MULTISAMPLE => {
+            FrameBufferIncompleteness::IncompleteMultisample
+        }
+        glow::FRAMEBUFFER_INCOMPLETE_LAYER_TARGETS => {
+            FrameBufferIncompleteness::IncompleteLayerTargets
+        }
+        _ => FrameBufferIncompleteness::Unknown(status),
+    }
+}
+
+fn validate_attachment_target(
+    texture: &GlTexture,
+    level: usize,
+    layer: AttachmentLayer,
+) -> Result<(), FrameworkError> {
+    let kind = texture.kind();
+    let (max_size, layer_count) = match kind {
+        GpuTextureKind::Line { length } => (length, 1),
+        GpuTextureKind::Rectangle { width, height } => (width.max(height), 1),
+        GpuTextureKind::Cube { width, height } => (width.max(height), 6),
+        GpuTextureKind::Volume {
+            width,
+            height,
+            depth,
+        } => (width.max(height).max(depth), depth),
+    };
+
+    let max_level = max_size.max(1).ilog2() as usize;
+    if level > max_level {
+        return Err(FrameworkError::Custom(format!(
+            "Mip level {level} is out of bounds of the texture of {kind:?} kind! The maximum \
+            mip level is {max_level}."
+        )));
+    }
+
+    match layer {
+        AttachmentLayer::First => Ok(()),
+        AttachmentLayer::Specific(layer) => {
+            if layer < layer_count {
+                Ok(())
+            } else {
+                Err(FrameworkError::Custom(format!(
+                    "Layer {layer} is out of bounds of the texture of {kind:?} kind! The texture \
+                    has {layer_count} layers."
+                )))
+            }
+        }
+        AttachmentLayer::All => match kind {
+            GpuTextureKind::Cube { .. } | GpuTextureKind::Volume { .. } => Ok(()),
+            GpuTextureKind::Line { .. } | GpuTextureKind::Rectangle { .. } => {
+                Err(FrameworkError::Custom(format!(
+                    "Layered rendering is not supported for the texture of {kind:?} kind!"
+                )))
+            }
+        },
+    }
+}
+
+unsafe fn set_attachment(
+    server: &GlGraphicsServer,
+    gl_attachment_kind: u32,
+    texture: &GlTexture,
+    level: usize,
+    layer: AttachmentLayer,
+) -> Result<(), FrameworkError> {
+    validate_attachment_target(texture, level, layer)?;
+
+    let level = level as i32;
+    match (texture.kind(), layer) {
+        (GpuTextureKind::Line { .. }, _) | (_, AttachmentLayer::All) => {
             server.gl.framebuffer_texture(
                 glow::FRAMEBUFFER,
                 gl_attachment_kind,
                 Some(texture.id()),
-                0,
+                level,
             );
         }
-        GpuTextureKind::Rectangle { .. } => {
+        (GpuTextureKind::Rectangle { .. }, _) => {
             server.gl.framebuffer_texture_2d(
                 glow::FRAMEBUFFER,
                 gl_attachment_kind,
                 glow::TEXTURE_2D,
                 Some(texture.id()),
-                0,
+                level,
             );
         }
-        GpuTextureKind::Cube { .. } => {
+        (GpuTextureKind::Cube { .. }, layer) => {
+            let face = match layer {
+                AttachmentLayer::Specific(face) => face as u32,
+                _ => 0,
+            };
             server.gl.framebuffer_texture_2d(
                 glow::FRAMEBUFFER,
                 gl_attachment_kind,
-                glow::TEXTURE_CUBE_MAP_POSITIVE_X,
+                glow::TEXTURE_CUBE_MAP_POSITIVE_X + face,
                 Some(texture.id()),
-                0,
+                level,
             );
         }
-        GpuTextureKind::Volume { .. } => {
-            server.gl.framebuffer_texture_3d(
+        (GpuTextureKind::Volume { .. }, layer) => {
+            let layer = match layer {
+                AttachmentLayer::Specific(layer) => layer as i32,
+                _ => 0,
+            };
+            server.gl.framebuffer_texture_layer(
                 glow::FRAMEBUFFER,
                 gl_attachment_kind,
-                glow::TEXTURE_3D,
                 Some(texture.id()),
-                0,
-                0,
+                level,
+                layer,
             );
         }
     }
+
+    Ok(())
 }
 
 impl GlFrameBuffer {
@@ -92,67 +183,218 @@ impl GlFrameBuffer {
         depth_attachment: Option<Attachment>,
         color_attachments: Vec<Attachment>,
     ) -> Result<Self, FrameworkError> {
+        if depth_attachment.is_none() && color_attachments.is_empty() {
+            return Err(FrameworkError::Custom(
+                "A frame buffer must have at least one attachment!".to_string(),
+            ));
+        }
+
+        let max_color_attachments = unsafe {
+            server
+                .gl
+                .get_parameter_i32(glow::MAX_COLOR_ATTACHMENTS)
+                .min(server.gl.get_parameter_i32(glow::MAX_DRAW_BUFFERS)) as usize
+        };
+        if color_attachments.len() > max_color_attachments {
+            return Err(FrameworkError::Custom(format!(
+                "The frame buffer has {} color attachments, but the maximum supported amount \
+                is {max_color_attachments}!",
+                color_attachments.len(),
+            )));
+        }
+
         unsafe {
             let fbo = server.gl.create_framebuffer()?;
 
             server.set_framebuffer(Some(fbo));
 
-            if let Some(depth_attachment) = depth_attachment.as_ref() {
-                let depth_attachment_kind = match depth_attachment.kind {
-                    AttachmentKind::Color => {
-                        panic!("Attempt to use color attachment as depth/stencil!")
-                    }
-                    AttachmentKind::DepthStencil => glow::DEPTH_STENCIL_ATTACHMENT,
-                    AttachmentKind::Depth => glow::DEPTH_ATTACHMENT,
-                };
-                let texture = depth_attachment
-                    .texture
-                    .as_any()
-                    .downcast_ref::<GlTexture>()
-                    .unwrap();
-                set_attachment(server, depth_attachment_kind, texture);
-            }
+            let result = Self::attach(server, depth_attachment.as_ref(), &color_attachments);
 
-            let mut color_buffers = Vec::new();
-            for (i, color_attachment) in color_attachments.iter().enumerate() {
-                assert_eq!(color_attachment.kind, AttachmentKind::Color);
-                let color_attachment_kind = glow::COLOR_ATTACHMENT0 + i as u32;
-                let texture = color_attachment
-                    .texture
-                    .as_any()
-                    .downcast_ref::<GlTexture>()
-                    .unwrap();
-                set_attachment(server, color_attachment_kind, texture);
-                color_buffers.push(color_attachment_kind);
-            }
+            server.set_framebuffer(None);
 
-            if color_buffers.is_empty() {
-                server.gl.draw_buffers(&[glow::NONE])
-            } else {
-                server.gl.draw_buffers(&color_buffers);
+            // Prevents leaking the frame buffer on errors.
+            if let Err(err) = result {
+                server.gl.delete_framebuffer(fbo);
+                return Err(err);
             }
 
-            if server.gl.check_framebuffer_status(glow::FRAMEBUFFER) != glow::FRAMEBUFFER_COMPLETE {
-                return Err(FrameworkError::FailedToConstructFBO);
-            }
+            Ok(Self {
+                state: server.weak(),
+                fbo: Some(fbo),
+                depth_attachment,
+                color_attachments,
+                render_buffers: Default::default(),
+            })
+        }
+    }
+
+    /// Creates a frame buffer with multisampled color and depth-stencil render buffers. Render
+    /// buffers cannot be sampled, so the frame buffer has no attachments and its content must be
+    /// resolved into another frame buffer via [`GpuFrameBufferTrait::blit_to`]. The sample count
+    /// is clamped to the maximum supported by the graphics server.
+    pub fn new_multisampled(
+        server: &GlGraphicsServer,
+        color_pixel_kind: PixelKind,
+        width: usize,
+        height: usize,
+        sample_count: usize,
+    ) -> Result<Self, FrameworkError> {
+        unsafe {
+            let max_sample_count = server.gl.get_parameter_i32(glow::MAX_SAMPLES).max(1) as usize;
+            let sample_count = sample_count.clamp(1, max_sample_count);
+
+            let fbo = server.gl.create_framebuffer()?;
+
+            server.set_framebuffer(Some(fbo));
+
+            let mut render_buffers = Vec::new();
+            let result = Self::attach_render_buffers(
+                server,
+                &mut render_buffers,
+                color_pixel_kind,
+                width,
+                height,
+                sample_count,
+            );
 
             server.set_framebuffer(None);
 
+            // Prevents leaking the frame buffer and its render buffers on errors.
+            if let Err(err) = result {
+                for render_buffer in render_buffers {
+                    server.gl.delete_renderbuffer(render_buffer);
+                }
+                server.gl.delete_framebuffer(fbo);
+                return Err(err);
+            }
+
             Ok(Self {
                 state: server.weak(),
                 fbo: Some(fbo),
-                depth_attachment,
-                color_attachments,
+                depth_attachment: None,
+                color_attachments: Default::default(),
+                render_buffers,
             })
         }
     }
 
+    unsafe fn attach_render_buffers(
+        server: &GlGraphicsServer,
+        render_buffers: &mut Vec<glow::Renderbuffer>,
+        color_pixel_kind: PixelKind,
+        width: usize,
+        height: usize,
+        sample_count: usize,
+    ) -> Result<(), FrameworkError> {
+        for (pixel_kind, gl_attachment_kind) in [
+            (color_pixel_kind, glow::COLOR_ATTACHMENT0),
+            (PixelKind::D24S8, glow::DEPTH_STENCIL_ATTACHMENT),
+        ] {
+            let render_buffer = server.gl.create_renderbuffer()?;
+            render_buffers.push(render_buffer);
+
+            server
+                .gl
+                .bind_renderbuffer(glow::RENDERBUFFER, Some(render_buffer));
+            server.gl.renderbuffer_storage_multisample(
+                glow::RENDERBUFFER,
+                sample_count as i32,
+                pixel_kind.pixel_descriptor().internal_format,
+                width as i32,
+                height as i32,
+            );
+            server.gl.framebuffer_renderbuffer(
+                glow::FRAMEBUFFER,
+                gl_attachment_kind,
+                glow::RENDERBUFFER,
+                Some(render_buffer),
+            );
+        }
+        server.gl.bind_renderbuffer(glow::RENDERBUFFER, None);
+
+        server.gl.draw_buffers(&[glow::COLOR_ATTACHMENT0]);
+
+        let status = server.gl.check_framebuffer_status(glow::FRAMEBUFFER);
+        if status != glow::FRAMEBUFFER_COMPLETE {
+            return Err(FrameworkError::FailedToConstructFBO {
+                reason: incompleteness_from_status(status),
+            });
+        }
+
+        Ok(())
+    }
+
+    unsafe fn attach(
+        server: &GlGraphicsServer,
+        depth_attachment: Option<&Attachment>,
+        color_attachments: &[Attachment],
+    ) -> Result<(), FrameworkError> {
+        if let Some(depth_attachment) = depth_attachment.as_ref() {
+            let depth_attachment_kind = match depth_attachment.kind {
+                AttachmentKind::Color => {
+                    panic!("Attempt to use color attachment as depth/stencil!")
+                }
+                AttachmentKind::DepthStencil => glow::DEPTH_STENCIL_ATTACHMENT,
+                AttachmentKind::Depth => glow::DEPTH_ATTACHMENT,
+            };
+            let texture = depth_attachment
+                .texture
+                .as_any()
+                .downcast_ref::<GlTexture>()
+                .unwrap();
+            set_attachment(
+                server,
+                depth_attachment_kind,
+                texture,
+                depth_attachment.level(),
+                depth_attachment.layer(),
+            )?;
+        }
+
+        let mut color_buffers = Vec::new();
+        for (i, color_attachment) in color_attachments.iter().enumerate() {
+            assert_eq!(color_attachment.kind, AttachmentKind::Color);
+            let color_attachment_kind = glow::COLOR_ATTACHMENT0 + i as u32;
+            let texture = color_attachment
+                .texture
+                .as_any()
+                .downcast_ref::<GlTexture>()
+                .unwrap();
+            set_attachment(
+                server,
+                color_attachment_kind,
+                texture,
+                color_attachment.level(),
+                color_attachment.layer(),
+            )?;
+            color_buffers.push(color_attachment_kind);
+        }
+
+        if color_buffers.is_empty() {
+            // Depth-only frame buffer, there's nothing to write to or read from.
+            server.gl.draw_buffers(&[glow::NONE]);
+            server.gl.read_buffer(glow::NONE);
+        } else {
+            server.gl.draw_buffers(&color_buffers);
+        }
+
+        let status = server.gl.check_framebuffer_status(glow::FRAMEBUFFER);
+        if status != glow::FRAMEBUFFER_COMPLETE {
+            return Err(FrameworkError::FailedToConstructFBO {
+                reason: incompleteness_from_status(status),
+            });
+        }
+
+        Ok(())
+    }
+
     pub fn backbuffer(server: &GlGraphicsServer) -> Self {
         Self {
             state: server.weak(),
             fbo: None,
             depth_attachment: None,
             color_attachments: Default::default(),
+            render_buffers: Default::default(),
         }
     }
 
@@ -174,10 +416,11 @@ impl GpuFrameBufferTrait for GlFrameBuffer {
     fn set_cubemap_face(&self, attachment_index: usize, face: CubeMapFace) {
         let server = self.state.upgrade().unwrap();
 
+        let attachment = self.color_attachments.get(attachment_index).unwrap();
+
         unsafe {
             server.set_framebuffer(self.fbo);
 
-            let attachment = self.color_attachments.get(attachment_index).unwrap();
             let texture = attachment
                 .texture
                 .as_any()
@@ -188,9 +431,56 @@ impl GpuFrameBufferTrait for GlFrameBuffer {
                 glow::COLOR_ATTACHMENT0 + attachment_index as u32,
                 face.into_gl(),
                 Some(texture.id()),
-                0,
+                attachment.level() as i32,
             );
         }
+
+        attachment
+            .layer
+            .set(AttachmentLayer::Specific(face as usize));
+    }
+
+    fn set_color_attachment_target(
+        &self,
+        attachment_index: usize,
+        level: usize,
+        layer: AttachmentLayer,
+    ) -> Result<(), FrameworkError> {
+        let server = self
+            .state
+            .upgrade()
+            .ok_or(FrameworkError::GraphicsServerUnavailable)?;
+
+        let attachment = self
+            .color_attachments
+            .get(attachment_index)
+            .ok_or_else(|| {
+                FrameworkError::Custom(format!(
+                    "There's no color attachment with {attachment_index} index!"
+                ))
+            })?;
+        let texture = attachment
+            .texture
+            .as_any()
+            .downcast_ref::<GlTexture>()
+            .unwrap();
+
+        server.set_framebuffer(self.fbo);
+
+        unsafe {
+            set_attachment(
+                &server,
+                glow::COLOR_ATTACHMENT0 + attachment_index as u32,
+                texture,
+                level,
+                layer,
+            )?;
+        }
+
+        attachment.level.set(level);
+        attachment.layer.set(layer);
+
+        Ok(())
     }
 
     fn blit_to(
@@ -255,13 +545,15 @@ impl GpuFrameBufferTrait for GlFrameBuffer {
     ) {
         let server = self.state.upgrade().unwrap();
 
+        server.invalidate_pipeline_state();
         server.set_scissor_test(false);
         server.set_viewport(viewport);
         server.set_framebuffer(self.id());
 
         unsafe {
-            // Special route for default buffer.
-            if self.fbo == Default::default() {
+            // Special route for default buffer and multisampled frame buffers, that have no
+            // attachments.
+            if self.fbo.is_none() || !self.render_buffers.is_empty() {
                 let mut mask = 0;
 
                 if let Some(color) = color {
@@ -371,106 +663,192 @@ impl GpuFrameBufferTrait for GlFrameBuffer {
         element_range: ElementRange,
     ) -> Result<DrawCallStatistics, FrameworkError> {
         let server = self.state.upgrade().unwrap();
-        let geometry = geometry
-            .as_any()
-            .downcast_ref::<GlGeometryBuffer>()
-            .unwrap();
+
+        if server.is_draw_discarded() {
+            return Ok(DrawCallStatistics::default());
+        }
 
         pre_draw(self.id(), &server, viewport, program, params, resources);
 
-        let (offset, element_count) = match element_range {
-            ElementRange::Full => (0, geometry.element_count.get()),
-            ElementRange::Specific { offset, count } => (offset, count),
-        };
+        draw_elements(&server, geometry, element_range, 1)
+    }
 
-        let last_element_index = offset + element_count;
+    fn draw_instances(
+        &self,
+        instance_count: usize,
+        geometry: &GpuGeometryBuffer,
+        viewport: Rect<i32>,
+        program: &GpuProgram,
+        params: &DrawParameters,
+        resources: &[ResourceBindGroup],
+        element_range: ElementRange,
+    ) -> Result<DrawCallStatistics, FrameworkError> {
+        let server = self.state.upgrade().unwrap();
 
-        if last_element_index > geometry.element_count.get() {
-            Err(FrameworkError::InvalidElementRange {
-                start: offset,
-                end: last_element_index,
-                total: geometry.element_count.get(),
-            })
-        } else {
-            let index_per_element = geometry.element_kind.index_per_element();
-            let start_index = offset * index_per_element;
-            let index_count = element_count * index_per_element;
+        if server.is_draw_discarded() {
+            return Ok(DrawCallStatistics::default());
+        }
 
-            unsafe {
-                if index_count > 0 {
-                    server.set_vertex_array_object(Some(geometry.vertex_array_object));
-
-                    let indices = (start_index * size_of::<u32>()) as i32;
-                    server.gl.draw_elements(
-                        geometry.mode(),
-                        index_count as i32,
-                        glow::UNSIGNED_INT,
-                        indices,
-                    );
-                }
-            }
+        pre_draw(self.id(), &server, viewport, program, params, resources);
 
-            Ok(DrawCallStatistics {
-                triangles: element_count,
-            })
-        }
+        draw_elements(&server, geometry, element_range, instance_count)
     }
 
-    fn draw_instances(
+    fn draw_with_pipeline_state(
         &self,
         instance_count: usize,
         geometry: &GpuGeometryBuffer,
         viewport: Rect<i32>,
+        pipeline_state: &GpuPipelineState,
+        resources: &[ResourceBindGroup],
+        element_range: ElementRange,
+    ) -> Result<DrawCallStatistics, FrameworkError> {
+        let server = self.state.upgrade().unwrap();
+
+        if server.is_draw_discarded() {
+            return Ok(DrawCallStatistics::default());
+        }
+
+        let pipeline_state = pipeline_state
+            .as_any()
+            .downcast_ref::<GlPipelineState>()
+            .expect("Must be OpenGL pipeline state");
+
+        server.set_framebuffer(self.id());
+        server.set_viewport(viewport);
+        server.apply_pipeline_state(pipeline_state);
+        bind_resources(&server, resources);
+
+        draw_elements(&server, geometry, element_range, instance_count)
+    }
+
+    fn draw_indirect(
+        &self,
+        geometry: &GpuGeometryBuffer,
+        viewport: Rect<i32>,
         program: &GpuProgram,
         params: &DrawParameters,
         resources: &[ResourceBindGroup],
-        element_range: ElementRange,
+        indirect_buffer: &GpuBuffer,
+        offset: usize,
+        draw_count: usize,
     ) -> Result<DrawCallStatistics, FrameworkError> {
         let server = self.state.upgrade().unwrap();
         let geometry = geometry
             .as_any()
             .downcast_ref::<GlGeometryBuffer>()
             .unwrap();
+        let indirect_buffer = indirect_buffer
+            .as_any()
+            .downcast_ref::<GlBuffer>()
+            .expect("Must be OpenGL buffer");
+
+        if !server.supports_indirect_draw() {
+            return Err(FrameworkError::Custom(
+                "Indirect drawing is not supported by the graphics server!".to_string(),
+            ));
+        }
+        if indirect_buffer.kind() != BufferKind::DrawIndirect {
+            return Err(FrameworkError::Custom(format!(
+                "Indirect draw buffer must be of DrawIndirect kind, got {:?}!",
+                indirect_buffer.kind()
+            )));
+        }
+        let stride = size_of::<IndirectDrawCommand>();
+        let required_size = offset + draw_count * stride;
+        if offset % 4 != 0 || required_size > indirect_buffer.size() {
+            return Err(FrameworkError::Custom(format!(
+                "Indirect draw range {offset}..{required_size} is misaligned or out of bounds \
+                of the buffer of {} bytes!",
+                indirect_buffer.size()
+            )));
+        }
+
+        if draw_count == 0 || server.is_draw_discarded() {
+            return Ok(DrawCallStatistics::default());
+        }
 
         pre_draw(self.id(), &server, viewport, program, params, resources);
 
-        let (offset, element_count) = match element_range {
-            ElementRange::Full => (0, geometry.element_count.get()),
-            ElementRange::Specific { offset, count } => (offset, count),
-        };
+        unsafe {
+            server.set_vertex_array_object(Some(geometry.vertex_array_object));
+            server
+                .gl
+                .bind_buffer(glow::DRAW_INDIRECT_BUFFER, Some(indirect_buffer.id));
+
+            for i in 0..draw_count {
+                server.gl.draw_elements_indirect_offset(
+                    geometry.mode(),
+                    glow::UNSIGNED_INT,
+                    (offset + i * stride) as i32,
+                );
+            }
 
-        let last_element_index = offset + element_count;
+            server.gl.bind_buffer(glow::DRAW_INDIRECT_BUFFER, None);
+        }
 
-        if last_element_index > geometry.element_count.get() {
-            Err(FrameworkError::InvalidElementRange {
-                start: offset,
-                end: last_element_index,
-                total: geometry.element_count.get(),
-            })
-        } else {
-            let index_per_element = geometry.element_kind.index_per_element();
-            let start_index = offset * index_per_element;
-            let index_count = geometry.element_count.get() * index_per_element;
+        // The actual amount of triangles is known only on GPU side.
+        Ok(DrawCallStatistics::default())
+    }
+}
 
-            unsafe {
-                if index_count > 0 {
-                    server.set_vertex_array_object(Some(geometry.vertex_array_object));
-                    let indices = (start_index * size_of::<u32>()) as i32;
-                    server.gl.draw_elements_instanced(
-                        geometry.mode(),
-                        index_count as i32,
-                        glow::UNSIGNED_INT,
-                        indices,
-                        instance_count as i32,
-                    )
-                }
-            }
+fn draw_elements(
+    server: &GlGraphicsServer,
+    geometry: &GpuGeometryBuffer,
+    element_range: ElementRange,
+    instance_count: usize,
+) -> Result<DrawCallStatistics, FrameworkError> {
+    let geometry = geometry
+        .as_any()
+        .downcast_ref::<GlGeometryBuffer>()
+        .unwrap();
+
+    let (offset, element_count) = match element_range {
+        ElementRange::Full => (0, geometry.element_count.get()),
+        ElementRange::Specific { offset, count } => (offset, count),
+    };
+
+    let last_element_index = offset + element_count;
+
+    if last_element_index > geometry.element_count.get() {
+        return Err(FrameworkError::InvalidElementRange {
+            start: offset,
+            end: last_element_index,
+            total: geometry.element_count.get(),
+        });
+    }
 
-            Ok(DrawCallStatistics {
-                triangles: geometry.element_count.get() * instance_count,
-            })
+    let index_per_element = geometry.element_kind.index_per_element();
+    let start_index = offset * index_per_element;
+    let index_count = element_count * index_per_element;
+
+    unsafe {
+        if index_count > 0 {
+            server.set_vertex_array_object(Some(geometry.vertex_array_object));
+
+            let indices = (start_index * size_of::<u32>()) as i32;
+            if instance_count > 1 {
+                server.gl.draw_elements_instanced(
+                    geometry.mode(),
+                    index_count as i32,
+                    glow::UNSIGNED_INT,
+                    indices,
+                    instance_count as i32,
+                );
+            } else {
+                server.gl.draw_elements(
+                    geometry.mode(),
+                    index_count as i32,
+                    glow::UNSIGNED_INT,
+                    indices,
+                );
+            }
         }
     }
+
+    Ok(DrawCallStatistics {
+        triangles: element_count * instance_count,
+    })
 }
 
 fn pre_draw(
@@ -486,16 +864,30 @@ fn pre_draw(
     server.apply_draw_parameters(params);
     let program = program.as_any().downcast_ref::<GlProgram>().unwrap();
     server.set_program(Some(program.id));
+    server.invalidate_pipeline_state();
 
+    bind_resources(server, resources);
+}
+
+fn bind_resources(server: &GlGraphicsServer, resources: &[ResourceBindGroup]) {
     for bind_group in resources {
         for binding in bind_group.bindings {
             match binding {
                 ResourceBinding::Texture {
                     texture,
+                    sampler,
                     binding: shader_location,
                 } => {
                     let texture = texture.as_any().downcast_ref::<GlTexture>().unwrap();
                     texture.bind(server, *shader_location as u32);
+                    let sampler = sampler.as_ref().map(|sampler| {
+                        sampler
+                            .as_any()
+                            .downcast_ref::<GlSampler>()
+                            .expect("Must be OpenGL sampler")
+                            .id
+                    });
+                    server.set_sampler(*shader_location as u32, sampler);
                 }
                 ResourceBinding::Buffer {
                     buffer,
@@ -543,6 +935,9 @@ impl Drop for GlFrameBuffer {
                 if let Some(id) = self.fbo {
                     state.gl.delete_framebuffer(id);
                 }
+                for render_buffer in self.render_buffers.drain(..) {
+                    state.gl.delete_renderbuffer(render_buffer);
+                }
             }
         }
     }
@@ -21,11 +21,16 @@
 pub mod buffer;
 pub mod framebuffer;
 pub mod geometry_buffer;
+pub mod pipeline;
 pub mod program;
 pub mod query;
 pub mod read_buffer;
+pub mod ring_buffer;
+pub mod sampler;
 pub mod server;
 pub mod texture;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod upload;
 
 pub trait ToGlConstant {
     fn into_gl(self) -> u32;
@@ -0,0 +1,61 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+use crate::{
+    gl::program::GlProgram, gpu_program::GpuProgram, pipeline::GpuPipelineStateTrait,
+    DrawParameters,
+};
+
+pub struct GlPipelineState {
+    pub(crate) id: u64,
+    pub(crate) gl_program: glow::Program,
+    pub(crate) draw_params: DrawParameters,
+    program: GpuProgram,
+}
+
+impl GlPipelineState {
+    pub fn new(id: u64, program: &GpuProgram, draw_params: DrawParameters) -> Self {
+        let gl_program = program
+            .as_any()
+            .downcast_ref::<GlProgram>()
+            .expect("Must be OpenGL program")
+            .id;
+
+        Self {
+            id,
+            gl_program,
+            program: program.clone(),
+            draw_params,
+        }
+    }
+}
+
+impl GpuPipelineStateTrait for GlPipelineState {
+    fn id(&self) -> u64 {
+        self.id
+    }
+
+    fn program(&self) -> &GpuProgram {
+        &self.program
+    }
+
+    fn draw_params(&self) -> &DrawParameters {
+        &self.draw_params
+    }
+}
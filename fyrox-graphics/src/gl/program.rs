@@ -20,7 +20,7 @@
 
 use crate::{
     core::log::{Log, MessageKind},
-    error::FrameworkError,
+    error::{FrameworkError, ShaderDiagnostic, ShaderDiagnosticSeverity},
     gl::server::{GlGraphicsServer, GlKind},
     gpu_program::{
         GpuProgramTrait, SamplerKind, ShaderPropertyKind, ShaderResourceDefinition,
@@ -41,35 +41,180 @@ impl SamplerKind {
             SamplerKind::USampler2D => "usampler2D",
             SamplerKind::USampler3D => "usampler3D",
             SamplerKind::USamplerCube => "usamplerCube",
+            SamplerKind::Sampler2DShadow => "sampler2DShadow",
+            SamplerKind::SamplerCubeShadow => "samplerCubeShadow",
         }
     }
 }
 
+/// Shader source assembled from multiple named chunks. It remembers where every chunk starts, so
+/// the line numbers reported by the compiler could be mapped back to the chunks.
+#[derive(Default)]
+struct AssembledSource {
+    code: String,
+    line_count: usize,
+    // Name of every chunk together with its first line (starting from 1).
+    chunks: Vec<(usize, String)>,
+}
+
+impl AssembledSource {
+    fn push(&mut self, name: &str, code: &str) {
+        self.chunks.push((self.line_count + 1, name.to_string()));
+        self.code += code;
+        self.line_count += code.matches('\n').count();
+        if !code.ends_with('\n') {
+            self.code.push('\n');
+            self.line_count += 1;
+        }
+    }
+
+    /// Returns the name of the chunk and the line within the chunk for the given line of the
+    /// assembled source.
+    fn resolve(&self, line: usize) -> Option<(&str, usize)> {
+        self.chunks
+            .iter()
+            .rev()
+            .find(|(first_line, _)| *first_line <= line)
+            .map(|(first_line, name)| (name.as_str(), line - first_line + 1))
+    }
+}
+
+fn parse_severity(word: &str) -> Option<ShaderDiagnosticSeverity> {
+    let word = word.trim().to_lowercase();
+    if word == "error" || word == "fatal error" {
+        Some(ShaderDiagnosticSeverity::Error)
+    } else if word == "warning" {
+        Some(ShaderDiagnosticSeverity::Warning)
+    } else if word == "info" || word == "note" {
+        Some(ShaderDiagnosticSeverity::Info)
+    } else {
+        None
+    }
+}
+
+/// Parses a location in one of the commonly used formats: `0:12(5):`, `0:12:` or `0(12) :`.
+/// Returns the line and the rest of the message.
+fn parse_location(text: &str) -> Option<(usize, &str)> {
+    let text = text.trim_start();
+    let source_end = text.find(|c: char| !c.is_ascii_digit())?;
+    if source_end == 0 {
+        return None;
+    }
+    let rest = &text[source_end..];
+    let (line, rest) = if let Some(rest) = rest.strip_prefix(':') {
+        let line_end = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        (&rest[..line_end], &rest[line_end..])
+    } else if let Some(rest) = rest.strip_prefix('(') {
+        let line_end = rest.find(')')?;
+        (&rest[..line_end], &rest[line_end + 1..])
+    } else {
+        return None;
+    };
+    let line = line.parse().ok()?;
+    // Skip the column, if any.
+    let rest = match rest.strip_prefix('(') {
+        Some(column) => &column[column.find(')')? + 1..],
+        None => rest,
+    };
+    let rest = rest.trim_start().strip_prefix(':')?;
+    Some((line, rest.trim()))
+}
+
+/// Converts a single line of a compiler log into a diagnostic. Compilers do not have a standard
+/// format of messages, but the majority of them use one of the following:
+///
+/// - `0:12(5): error: message` (Mesa)
+/// - `0(12) : error C1008: message` (NVIDIA)
+/// - `ERROR: 0:12: message` (AMD, ANGLE, mobile drivers)
+fn parse_log_line(
+    line: &str,
+    source: &AssembledSource,
+    shader_name: &str,
+) -> Option<ShaderDiagnostic> {
+    let line = line.trim().trim_end_matches('\0');
+    if line.is_empty() {
+        return None;
+    }
+
+    let mut severity = None;
+    let mut rest = line;
+    if let Some((prefix, tail)) = line.split_once(':') {
+        if let Some(prefix_severity) = parse_severity(prefix) {
+            severity = Some(prefix_severity);
+            rest = tail;
+        }
+    }
+
+    let Some((global_line, mut message)) = parse_location(rest) else {
+        return Some(ShaderDiagnostic {
+            severity: severity.unwrap_or(if line.to_lowercase().contains("error") {
+                ShaderDiagnosticSeverity::Error
+            } else {
+                ShaderDiagnosticSeverity::Info
+            }),
+            source: shader_name.to_string(),
+            line: None,
+            message: line.to_string(),
+        });
+    };
+
+    if severity.is_none() {
+        if let Some((head, tail)) = message.split_once(':') {
+            // The head could also contain an error code, for example `error C1008`.
+            let keyword = head.split_whitespace().next().unwrap_or_default();
+            if let Some(head_severity) = parse_severity(keyword) {
+                severity = Some(head_severity);
+                message = tail.trim();
+            }
+        }
+    }
+
+    let (source_name, line) = source
+        .resolve(global_line)
+        .unwrap_or((shader_name, global_line));
+
+    Some(ShaderDiagnostic {
+        severity: severity.unwrap_or(ShaderDiagnosticSeverity::Error),
+        source: source_name.to_string(),
+        line: Some(line),
+        message: message.to_string(),
+    })
+}
+
+fn parse_log(log: &str, source: &AssembledSource, shader_name: &str) -> Vec<ShaderDiagnostic> {
+    log.lines()
+        .filter_map(|line| parse_log_line(line, source, shader_name))
+        .collect()
+}
+
 unsafe fn create_shader(
     server: &GlGraphicsServer,
     name: String,
     actual_type: u32,
+    declarations: &str,
     source: &str,
     gl_kind: GlKind,
 ) -> Result<glow::Shader, FrameworkError> {
-    let merged_source = prepare_source_code(source, gl_kind);
+    let assembled_source = prepare_source_code(&name, declarations, source, gl_kind);
 
     let shader = server.gl.create_shader(actual_type)?;
-    server.gl.shader_source(shader, &merged_source);
+    server.gl.shader_source(shader, &assembled_source.code);
     server.gl.compile_shader(shader);
 
     let status = server.gl.get_shader_compile_status(shader);
     let compilation_message = server.gl.get_shader_info_log(shader);
 
     if !status {
-        Log::writeln(
-            MessageKind::Error,
-            format!("Failed to compile {name} shader: {compilation_message}"),
-        );
-        Err(FrameworkError::ShaderCompilationFailed {
+        let diagnostics = parse_log(&compilation_message, &assembled_source, &name);
+        let error = FrameworkError::ShaderCompilationFailed {
             shader_name: name,
             error_message: compilation_message,
-        })
+            diagnostics,
+        };
+        Log::writeln(MessageKind::Error, error.to_string());
+        Err(error)
     } else {
         let msg = if compilation_message.is_empty()
             || compilation_message.chars().all(|c| c.is_whitespace())
@@ -85,11 +230,18 @@ unsafe fn create_shader(
     }
 }
 
-fn prepare_source_code(code: &str, gl_kind: GlKind) -> String {
-    let mut full_source_code = "#version 330 core\n// include 'shared.glsl'\n".to_owned();
+fn prepare_source_code(
+    name: &str,
+    declarations: &str,
+    code: &str,
+    gl_kind: GlKind,
+) -> AssembledSource {
+    let mut source = AssembledSource::default();
 
     if gl_kind == GlKind::OpenGLES {
-        full_source_code += r#"
+        source.push(
+            "<preamble>",
+            r#"#version 300 es
             precision highp float;
             precision highp int;
             precision highp usampler2D;
@@ -98,18 +250,17 @@ fn prepare_source_code(code: &str, gl_kind: GlKind) -> String {
             precision highp sampler2D;
             precision highp sampler3D;
             precision highp samplerCube;
-        "#;
+        "#,
+        );
+    } else {
+        source.push("<preamble>", "#version 330 core\n");
     }
 
-    full_source_code += include_str!("shaders/shared.glsl");
-    full_source_code += "\n// end of include\n";
-    full_source_code += code;
+    source.push("shared.glsl", include_str!("shaders/shared.glsl"));
+    source.push("<resources>", declarations);
+    source.push(name, code);
 
-    if gl_kind == GlKind::OpenGLES {
-        full_source_code.replace("#version 330 core", "#version 300 es")
-    } else {
-        full_source_code
-    }
+    source
 }
 
 pub struct GlProgram {
@@ -127,9 +278,6 @@ impl GlProgram {
         fragment_source: &str,
         resources: &[ShaderResourceDefinition],
     ) -> Result<GlProgram, FrameworkError> {
-        let mut vertex_source = vertex_source.to_string();
-        let mut fragment_source = fragment_source.to_string();
-
         // Initial validation. The program will be validated once more by the compiler.
         for resource in resources {
             for other_resource in resources {
@@ -160,102 +308,109 @@ impl GlProgram {
         }
 
         // Generate appropriate texture binding points and uniform blocks for the specified properties.
-        for initial_source in [&mut vertex_source, &mut fragment_source] {
-            let mut texture_bindings = String::new();
-
-            for property in resources {
-                let resource_name = &property.name;
-                match property.kind {
-                    ShaderResourceKind::Texture { kind, .. } => {
-                        let glsl_name = kind.glsl_name();
-                        texture_bindings += &format!("uniform {glsl_name} {resource_name};\n");
+        // The declarations are the same for both shaders.
+        let mut declarations = String::new();
+        let mut texture_bindings = String::new();
+
+        for property in resources {
+            let resource_name = &property.name;
+            match property.kind {
+                ShaderResourceKind::Texture { kind, .. } => {
+                    let glsl_name = kind.glsl_name();
+                    texture_bindings += &format!("uniform {glsl_name} {resource_name};\n");
+                }
+                ShaderResourceKind::PropertyGroup(ref fields) => {
+                    if fields.is_empty() {
+                        Log::warn(format!(
+                            "Uniform block {resource_name} is empty and will be ignored!"
+                        ));
+                        continue;
                     }
-                    ShaderResourceKind::PropertyGroup(ref fields) => {
-                        if fields.is_empty() {
-                            Log::warn(format!(
-                                "Uniform block {resource_name} is empty and will be ignored!"
-                            ));
-                            continue;
-                        }
-                        let mut block = format!("struct T{resource_name}{{\n");
-                        for field in fields {
-                            let field_name = &field.name;
-                            match field.kind {
-                                ShaderPropertyKind::Float { .. } => {
-                                    block += &format!("\tfloat {field_name};\n");
-                                }
-                                ShaderPropertyKind::FloatArray { max_len, .. } => {
-                                    block += &format!("\tfloat {field_name}[{max_len}];\n");
-                                }
-                                ShaderPropertyKind::Int { .. } => {
-                                    block += &format!("\tint {field_name};\n");
-                                }
-                                ShaderPropertyKind::IntArray { max_len, .. } => {
-                                    block += &format!("\tint {field_name}[{max_len}];\n");
-                                }
-                                ShaderPropertyKind::UInt { .. } => {
-                                    block += &format!("\tuint {field_name};\n");
-                                }
-                                ShaderPropertyKind::UIntArray { max_len, .. } => {
-                                    block += &format!("\tuint {field_name}[{max_len}];\n");
-                                }
-                                ShaderPropertyKind::Bool { .. } => {
-                                    block += &format!("\tbool {field_name};\n");
-                                }
-                                ShaderPropertyKind::Vector2 { .. } => {
-                                    block += &format!("\tvec2 {field_name};\n");
-                                }
-                                ShaderPropertyKind::Vector2Array { max_len, .. } => {
-                                    block += &format!("\tvec2 {field_name}[{max_len}];\n");
-                                }
-                                ShaderPropertyKind::Vector3 { .. } => {
-                                    block += &format!("\tvec3 {field_name};\n");
-                                }
-                                ShaderPropertyKind::Vector3Array { max_len, .. } => {
-                                    block += &format!("\tvec3 {field_name}[{max_len}];\n");
-                                }
-                                ShaderPropertyKind::Vector4 { .. } => {
-                                    block += &format!("\tvec4 {field_name};\n");
-                                }
-                                ShaderPropertyKind::Vector4Array { max_len, .. } => {
-                                    block += &format!("\tvec4 {field_name}[{max_len}];\n");
-                                }
-                                ShaderPropertyKind::Matrix2 { .. } => {
-                                    block += &format!("\tmat2 {field_name};\n");
-                                }
-                                ShaderPropertyKind::Matrix2Array { max_len, .. } => {
-                                    block += &format!("\tmat2 {field_name}[{max_len}];\n");
-                                }
-                                ShaderPropertyKind::Matrix3 { .. } => {
-                                    block += &format!("\tmat3 {field_name};\n");
-                                }
-                                ShaderPropertyKind::Matrix3Array { max_len, .. } => {
-                                    block += &format!("\tmat3 {field_name}[{max_len}];\n");
-                                }
-                                ShaderPropertyKind::Matrix4 { .. } => {
-                                    block += &format!("\tmat4 {field_name};\n");
-                                }
-                                ShaderPropertyKind::Matrix4Array { max_len, .. } => {
-                                    block += &format!("\tmat4 {field_name}[{max_len}];\n");
-                                }
-                                ShaderPropertyKind::Color { .. } => {
-                                    block += &format!("\tvec4 {field_name};\n");
-                                }
+                    let mut block = format!("struct T{resource_name}{{\n");
+                    for field in fields {
+                        let field_name = &field.name;
+                        match field.kind {
+                            ShaderPropertyKind::Float { .. } => {
+                                block += &format!("\tfloat {field_name};\n");
+                            }
+                            ShaderPropertyKind::FloatArray { max_len, .. } => {
+                                block += &format!("\tfloat {field_name}[{max_len}];\n");
+                            }
+                            ShaderPropertyKind::Int { .. } => {
+                                block += &format!("\tint {field_name};\n");
+                            }
+                            ShaderPropertyKind::IntArray { max_len, .. } => {
+                                block += &format!("\tint {field_name}[{max_len}];\n");
+                            }
+                            ShaderPropertyKind::UInt { .. } => {
+                                block += &format!("\tuint {field_name};\n");
+                            }
+                            ShaderPropertyKind::UIntArray { max_len, .. } => {
+                                block += &format!("\tuint {field_name}[{max_len}];\n");
+                            }
+                            ShaderPropertyKind::Bool { .. } => {
+                                block += &format!("\tbool {field_name};\n");
+                            }
+                            ShaderPropertyKind::Vector2 { .. } => {
+                                block += &format!("\tvec2 {field_name};\n");
+                            }
+                            ShaderPropertyKind::Vector2Array { max_len, .. } => {
+                                block += &format!("\tvec2 {field_name}[{max_len}];\n");
+                            }
+                            ShaderPropertyKind::Vector3 { .. } => {
+                                block += &format!("\tvec3 {field_name};\n");
+                            }
+                            ShaderPropertyKind::Vector3Array { max_len, .. } => {
+                                block += &format!("\tvec3 {field_name}[{max_len}];\n");
+                            }
+                            ShaderPropertyKind::Vector4 { .. } => {
+                                block += &format!("\tvec4 {field_name};\n");
+                            }
+                            ShaderPropertyKind::Vector4Array { max_len, .. } => {
+                                block += &format!("\tvec4 {field_name}[{max_len}];\n");
+                            }
+                            ShaderPropertyKind::Matrix2 { .. } => {
+                                block += &format!("\tmat2 {field_name};\n");
+                            }
+                            ShaderPropertyKind::Matrix2Array { max_len, .. } => {
+                                block += &format!("\tmat2 {field_name}[{max_len}];\n");
+                            }
+                            ShaderPropertyKind::Matrix3 { .. } => {
+                                block += &format!("\tmat3 {field_name};\n");
+                            }
+                            ShaderPropertyKind::Matrix3Array { max_len, .. } => {
+                                block += &format!("\tmat3 {field_name}[{max_len}];\n");
+                            }
+                            ShaderPropertyKind::Matrix4 { .. } => {
+                                block += &format!("\tmat4 {field_name};\n");
+                            }
+                            ShaderPropertyKind::Matrix4Array { max_len, .. } => {
+                                block += &format!("\tmat4 {field_name}[{max_len}];\n");
+                            }
+                            ShaderPropertyKind::Color { .. } => {
+                                block += &format!("\tvec4 {field_name};\n");
                             }
                         }
-                        block += "};\n";
-                        block += &format!("layout(std140) uniform U{resource_name} {{ T{resource_name} {resource_name}; }};\n");
-                        initial_source.insert_str(0, &block);
                     }
+                    block += "};\n";
+                    block += &format!("layout(std140) uniform U{resource_name} {{ T{resource_name} {resource_name}; }};\n");
+                    declarations.insert_str(0, &block);
                 }
             }
-            initial_source.insert_str(0, &texture_bindings);
         }
+        declarations.insert_str(0, &texture_bindings);
 
-        let program = Self::from_source(server, program_name, &vertex_source, &fragment_source)?;
+        let program = Self::from_source(
+            server,
+            program_name,
+            &declarations,
+            vertex_source,
+            fragment_source,
+        )?;
 
         unsafe {
             server.set_program(Some(program.id));
+            server.invalidate_pipeline_state();
             for resource_definition in resources {
                 match resource_definition.kind {
                     ShaderResourceKind::Texture { .. } => {
@@ -295,6 +450,7 @@ impl GlProgram {
     fn from_source(
         server: &GlGraphicsServer,
         name: &str,
+        declarations: &str,
         vertex_source: &str,
         fragment_source: &str,
     ) -> Result<GlProgram, FrameworkError> {
@@ -303,6 +459,7 @@ impl GlProgram {
                 server,
                 format!("{name}_VertexShader"),
                 glow::VERTEX_SHADER,
+                declarations,
                 vertex_source,
                 server.gl_kind(),
             )?;
@@ -310,6 +467,7 @@ impl GlProgram {
                 server,
                 format!("{name}_FragmentShader"),
                 glow::FRAGMENT_SHADER,
+                declarations,
                 fragment_source,
                 server.gl_kind(),
             )?;
@@ -363,3 +521,58 @@ impl Drop for GlProgram {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{parse_log, AssembledSource};
+    use crate::error::ShaderDiagnosticSeverity;
+
+    fn source() -> AssembledSource {
+        let mut source = AssembledSource::default();
+        source.push("<preamble>", "#version 330 core\n");
+        source.push("shared.glsl", "float a;\nfloat b;\nfloat c;\n");
+        source.push("<resources>", "");
+        source.push("MyShader", "void main()\n{\n    foo();\n}");
+        source
+    }
+
+    #[test]
+    fn test_resolve_lines() {
+        let source = source();
+        assert_eq!(source.resolve(1), Some(("<preamble>", 1)));
+        assert_eq!(source.resolve(3), Some(("shared.glsl", 2)));
+        assert_eq!(source.resolve(5), Some(("<resources>", 1)));
+        assert_eq!(source.resolve(8), Some(("MyShader", 3)));
+        assert_eq!(source.resolve(0), None);
+    }
+
+    #[test]
+    fn test_parse_log_formats() {
+        let source = source();
+        let log = "0:8(5): error: `foo' undeclared\n\
+                   0(3) : warning C7050: \"b\" might be used before being initialized\n\
+                   ERROR: 0:8: 'foo' : no matching overloaded function found\n\
+                   Something went wrong";
+        let diagnostics = parse_log(log, &source, "MyShader");
+        assert_eq!(diagnostics.len(), 4);
+
+        assert_eq!(diagnostics[0].severity, ShaderDiagnosticSeverity::Error);
+        assert_eq!(diagnostics[0].source, "MyShader");
+        assert_eq!(diagnostics[0].line, Some(3));
+        assert_eq!(diagnostics[0].message, "`foo' undeclared");
+
+        assert_eq!(diagnostics[1].severity, ShaderDiagnosticSeverity::Warning);
+        assert_eq!(diagnostics[1].source, "shared.glsl");
+        assert_eq!(diagnostics[1].line, Some(2));
+
+        assert_eq!(diagnostics[2].severity, ShaderDiagnosticSeverity::Error);
+        assert_eq!(diagnostics[2].line, Some(3));
+        assert_eq!(
+            diagnostics[2].message,
+            "'foo' : no matching overloaded function found"
+        );
+
+        assert_eq!(diagnostics[3].line, None);
+        assert_eq!(diagnostics[3].message, "Something went wrong");
+    }
+}
@@ -24,7 +24,7 @@ use crate::{
     query::{GpuQueryTrait, QueryKind, QueryResult},
 };
 use glow::HasContext;
-use std::{cell::Cell, rc::Weak};
+use std::{cell::Cell, rc::Weak, time::Duration};
 
 #[derive(Debug)]
 pub struct GlQuery {
@@ -91,6 +91,9 @@ impl GpuQueryTrait for GlQuery {
                     QueryKind::AnySamplesPassed => {
                         Some(QueryResult::AnySamplesPassed(query_result > 0))
                     }
+                    QueryKind::TimeElapsed => Some(QueryResult::TimeElapsed(Duration::from_nanos(
+                        query_result as u64,
+                    ))),
                 }
             } else {
                 None
@@ -0,0 +1,300 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+use crate::{
+    buffer::{BufferKind, BufferUsage, GpuBuffer},
+    error::FrameworkError,
+    gl::{buffer::GlBuffer, server::GlGraphicsServer, ToGlConstant},
+    ring_buffer::GpuRingBufferTrait,
+};
+use glow::HasContext;
+use std::{
+    cell::{Cell, RefCell},
+    rc::{Rc, Weak},
+};
+
+/// Maximum amount of time (in nanoseconds) to wait until the GPU finishes using a segment.
+const MAX_SEGMENT_WAIT_NS: i32 = 1_000_000_000;
+
+fn align_up(value: usize, alignment: usize) -> usize {
+    value.div_ceil(alignment) * alignment
+}
+
+/// Placement of the segments of a ring buffer in its memory.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct RingLayout {
+    segment_size: usize,
+    segment_count: usize,
+    alignment: usize,
+}
+
+impl RingLayout {
+    fn new(
+        segment_size: usize,
+        segment_count: usize,
+        alignment: usize,
+    ) -> Result<Self, FrameworkError> {
+        if segment_size == 0 || segment_count == 0 {
+            return Err(FrameworkError::Custom(format!(
+                "Invalid ring buffer layout: {segment_count} segments of {segment_size} bytes!"
+            )));
+        }
+
+        let alignment = alignment.max(1);
+        Ok(Self {
+            // Every segment must start at an aligned offset.
+            segment_size: align_up(segment_size, alignment),
+            segment_count,
+            alignment,
+        })
+    }
+
+    fn total_size(&self) -> usize {
+        self.segment_size * self.segment_count
+    }
+
+    /// Places `size` bytes in the given segment after the `cursor` (local to the segment). Returns
+    /// the offset of the data from the beginning of the buffer and the new cursor, or [`None`] if
+    /// the data does not fit into the rest of the segment.
+    fn place(&self, segment: usize, cursor: usize, size: usize) -> Option<(usize, usize)> {
+        let local_offset = align_up(cursor, self.alignment);
+        let end = local_offset.checked_add(size)?;
+        if end > self.segment_size {
+            return None;
+        }
+        Some((segment * self.segment_size + local_offset, end))
+    }
+
+    fn next_segment(&self, segment: usize) -> usize {
+        (segment + 1) % self.segment_count
+    }
+}
+
+pub struct GlRingBuffer {
+    server: Weak<GlGraphicsServer>,
+    buffer: GpuBuffer,
+    id: glow::Buffer,
+    gl_kind: u32,
+    // Null if the buffer is not persistently mapped.
+    mapped: *mut u8,
+    layout: RingLayout,
+    current_segment: Cell<usize>,
+    cursor: Cell<usize>,
+    fences: RefCell<Vec<Option<glow::Fence>>>,
+}
+
+impl GlRingBuffer {
+    pub fn new(
+        server: &GlGraphicsServer,
+        kind: BufferKind,
+        segment_size: usize,
+        segment_count: usize,
+        alignment: usize,
+    ) -> Result<Self, FrameworkError> {
+        let layout = RingLayout::new(segment_size, segment_count, alignment)?;
+        let total_size = layout.total_size();
+        let gl_kind = kind.into_gl();
+
+        unsafe {
+            let id = server.gl.create_buffer()?;
+            server.gl.bind_buffer(gl_kind, Some(id));
+
+            #[allow(unused_mut)]
+            let mut mapped = std::ptr::null_mut();
+
+            #[cfg(not(target_arch = "wasm32"))]
+            if server.supports_persistent_mapping() {
+                let flags = glow::MAP_WRITE_BIT | glow::MAP_PERSISTENT_BIT | glow::MAP_COHERENT_BIT;
+                server.gl.buffer_storage(
+                    gl_kind,
+                    total_size as i32,
+                    None,
+                    flags | glow::DYNAMIC_STORAGE_BIT,
+                );
+                mapped = server
+                    .gl
+                    .map_buffer_range(gl_kind, 0, total_size as i32, flags);
+            }
+
+            if mapped.is_null() {
+                server
+                    .gl
+                    .buffer_data_size(gl_kind, total_size as i32, glow::DYNAMIC_DRAW);
+            }
+
+            server.gl.bind_buffer(gl_kind, None);
+
+            let buffer = GpuBuffer(Rc::new(GlBuffer {
+                state: server.weak(),
+                id,
+                size: Cell::new(total_size),
+                kind,
+                usage: BufferUsage::DynamicDraw,
+                is_owned_storage: true,
+            }));
+
+            Ok(Self {
+                server: server.weak(),
+                buffer,
+                id,
+                gl_kind,
+                mapped,
+                layout,
+                current_segment: Cell::new(0),
+                cursor: Cell::new(0),
+                fences: RefCell::new(vec![None; segment_count]),
+            })
+        }
+    }
+}
+
+impl GpuRingBufferTrait for GlRingBuffer {
+    fn buffer(&self) -> &GpuBuffer {
+        &self.buffer
+    }
+
+    fn segment_size(&self) -> usize {
+        self.layout.segment_size
+    }
+
+    fn segment_count(&self) -> usize {
+        self.layout.segment_count
+    }
+
+    fn is_persistently_mapped(&self) -> bool {
+        !self.mapped.is_null()
+    }
+
+    fn write(&self, data: &[u8]) -> Option<usize> {
+        let (offset, cursor) =
+            self.layout
+                .place(self.current_segment.get(), self.cursor.get(), data.len())?;
+
+        if self.mapped.is_null() {
+            let server = self.server.upgrade()?;
+            unsafe {
+                server.gl.bind_buffer(self.gl_kind, Some(self.id));
+                server
+                    .gl
+                    .buffer_sub_data_u8_slice(self.gl_kind, offset as i32, data);
+            }
+        } else {
+            // SAFETY: The range is checked above to be within the mapped memory, and the segment is
+            // guaranteed to be unused by the GPU (see `next_segment`).
+            unsafe {
+                std::ptr::copy_nonoverlapping(data.as_ptr(), self.mapped.add(offset), data.len());
+            }
+        }
+
+        self.cursor.set(cursor);
+
+        Some(offset)
+    }
+
+    fn next_segment(&self) {
+        let Some(server) = self.server.upgrade() else {
+            return;
+        };
+
+        let mut fences = self.fences.borrow_mut();
+        let current = self.current_segment.get();
+        let next = self.layout.next_segment(current);
+
+        unsafe {
+            // Coherent mapping has no implicit synchronization, so the segments must be guarded
+            // manually. Non-mapped buffers are synchronized by the driver.
+            if !self.mapped.is_null() {
+                if let Ok(fence) = server.gl.fence_sync(glow::SYNC_GPU_COMMANDS_COMPLETE, 0) {
+                    if let Some(previous) = fences[current].replace(fence) {
+                        server.gl.delete_sync(previous);
+                    }
+                }
+            }
+
+            if let Some(fence) = fences[next].take() {
+                server.gl.client_wait_sync(
+                    fence,
+                    glow::SYNC_FLUSH_COMMANDS_BIT,
+                    MAX_SEGMENT_WAIT_NS,
+                );
+                server.gl.delete_sync(fence);
+            }
+        }
+
+        self.current_segment.set(next);
+        self.cursor.set(0);
+    }
+}
+
+impl Drop for GlRingBuffer {
+    fn drop(&mut self) {
+        if let Some(server) = self.server.upgrade() {
+            unsafe {
+                for fence in self.fences.get_mut().drain(..).flatten() {
+                    server.gl.delete_sync(fence);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::RingLayout;
+
+    #[test]
+    fn test_ring_layout_alignment() {
+        assert!(RingLayout::new(0, 3, 256).is_err());
+        assert!(RingLayout::new(1024, 0, 256).is_err());
+
+        // Segments are padded to start at aligned offsets.
+        let layout = RingLayout::new(1000, 3, 256).unwrap();
+        assert_eq!(layout.segment_size, 1024);
+        assert_eq!(layout.total_size(), 3072);
+
+        // Zero alignment means no alignment.
+        let layout = RingLayout::new(1000, 2, 0).unwrap();
+        assert_eq!(layout.segment_size, 1000);
+        assert_eq!(layout.place(0, 3, 10), Some((3, 13)));
+    }
+
+    #[test]
+    fn test_ring_layout_offsets() {
+        let layout = RingLayout::new(1024, 3, 256).unwrap();
+
+        // Every write starts at an aligned offset within the current segment.
+        assert_eq!(layout.place(0, 0, 100), Some((0, 100)));
+        assert_eq!(layout.place(0, 100, 100), Some((256, 356)));
+        assert_eq!(layout.place(1, 356, 100), Some((1024 + 512, 612)));
+        assert_eq!(layout.place(2, 0, 1024), Some((2048, 1024)));
+
+        // The data must fit into the rest of the segment.
+        assert_eq!(layout.place(0, 612, 300), None);
+        assert_eq!(layout.place(0, 0, 1025), None);
+        assert_eq!(layout.place(0, 1, usize::MAX), None);
+    }
+
+    #[test]
+    fn test_ring_layout_wrap() {
+        let layout = RingLayout::new(1024, 3, 256).unwrap();
+        assert_eq!(layout.next_segment(0), 1);
+        assert_eq!(layout.next_segment(1), 2);
+        assert_eq!(layout.next_segment(2), 0);
+    }
+}
@@ -0,0 +1,122 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{
+    error::FrameworkError,
+    gl::{server::GlGraphicsServer, ToGlConstant},
+    gpu_texture::Coordinate,
+    sampler::{GpuSamplerDescriptor, GpuSamplerTrait},
+};
+use glow::HasContext;
+use std::rc::Weak;
+
+#[derive(Debug)]
+pub struct GlSampler {
+    pub(crate) id: glow::Sampler,
+    state: Weak<GlGraphicsServer>,
+    desc: GpuSamplerDescriptor,
+}
+
+impl GlSampler {
+    pub fn new(
+        server: &GlGraphicsServer,
+        desc: GpuSamplerDescriptor,
+    ) -> Result<Self, FrameworkError> {
+        unsafe {
+            let gl = &server.gl;
+            let id = gl.create_sampler()?;
+
+            gl.sampler_parameter_i32(
+                id,
+                glow::TEXTURE_MIN_FILTER,
+                desc.min_filter.into_gl() as i32,
+            );
+            gl.sampler_parameter_i32(
+                id,
+                glow::TEXTURE_MAG_FILTER,
+                desc.mag_filter.into_gl() as i32,
+            );
+            for (coordinate, wrap_mode) in [
+                (Coordinate::S, desc.s_wrap_mode),
+                (Coordinate::T, desc.t_wrap_mode),
+                (Coordinate::R, desc.r_wrap_mode),
+            ] {
+                gl.sampler_parameter_i32(id, coordinate.into_gl(), wrap_mode.into_gl() as i32);
+            }
+            gl.sampler_parameter_f32(id, glow::TEXTURE_MIN_LOD, desc.min_lod);
+            gl.sampler_parameter_f32(id, glow::TEXTURE_MAX_LOD, desc.max_lod);
+
+            if let Some(compare_func) = desc.compare_func {
+                gl.sampler_parameter_i32(
+                    id,
+                    glow::TEXTURE_COMPARE_MODE,
+                    glow::COMPARE_REF_TO_TEXTURE as i32,
+                );
+                gl.sampler_parameter_i32(
+                    id,
+                    glow::TEXTURE_COMPARE_FUNC,
+                    compare_func.into_gl() as i32,
+                );
+            } else {
+                gl.sampler_parameter_i32(id, glow::TEXTURE_COMPARE_MODE, glow::NONE as i32);
+            }
+
+            let max_anisotropy = gl.get_parameter_f32(glow::MAX_TEXTURE_MAX_ANISOTROPY_EXT);
+            gl.sampler_parameter_f32(
+                id,
+                glow::TEXTURE_MAX_ANISOTROPY_EXT,
+                desc.anisotropy.clamp(1.0, max_anisotropy),
+            );
+
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                let color = desc.border_color.as_frgba();
+                gl.sampler_parameter_f32_slice(
+                    id,
+                    glow::TEXTURE_BORDER_COLOR,
+                    &[color.x, color.y, color.z, color.w],
+                );
+            }
+
+            Ok(Self {
+                id,
+                state: server.weak(),
+                desc,
+            })
+        }
+    }
+}
+
+impl GpuSamplerTrait for GlSampler {
+    fn descriptor(&self) -> &GpuSamplerDescriptor {
+        &self.desc
+    }
+}
+
+impl Drop for GlSampler {
+    fn drop(&mut self) {
+        if let Some(state) = self.state.upgrade() {
+            state.unbind_sampler(self.id);
+            unsafe {
+                state.gl.delete_sampler(self.id);
+            }
+        }
+    }
+}
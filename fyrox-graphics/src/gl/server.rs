@@ -20,8 +20,9 @@
 
 use crate::geometry_buffer::GpuGeometryBuffer;
 use crate::gpu_program::GpuProgram;
-use crate::query::GpuQuery;
+use crate::query::{GpuQuery, QueryResult};
 use crate::read_buffer::GpuAsyncReadBuffer;
+use crate::ring_buffer::GpuRingBuffer;
 use crate::{
     buffer::GpuBuffer,
     buffer::{BufferKind, BufferUsage},
@@ -31,24 +32,36 @@ use crate::{
     framebuffer::GpuFrameBuffer,
     geometry_buffer::GeometryBufferDescriptor,
     gl::{
-        self, framebuffer::GlFrameBuffer, geometry_buffer::GlGeometryBuffer, program::GlProgram,
-        query::GlQuery, read_buffer::GlAsyncReadBuffer, texture::GlTexture, ToGlConstant,
+        self, framebuffer::GlFrameBuffer, geometry_buffer::GlGeometryBuffer,
+        pipeline::GlPipelineState, program::GlProgram, query::GlQuery,
+        read_buffer::GlAsyncReadBuffer, ring_buffer::GlRingBuffer, sampler::GlSampler,
+        texture::GlTexture, ToGlConstant,
     },
     gpu_program::ShaderResourceDefinition,
-    gpu_texture::{GpuTexture, GpuTextureDescriptor},
-    server::{GraphicsServer, ServerCapabilities, SharedGraphicsServer},
+    gpu_texture::{GpuTexture, GpuTextureDescriptor, PixelKind},
+    pipeline::GpuPipelineState,
+    sampler::{GpuSampler, GpuSamplerDescriptor},
+    server::{
+        GraphicsServer, PresentSettings, ServerCapabilities, SharedGraphicsServer, VSyncMode,
+    },
     stats::PipelineStatistics,
-    BlendEquation, BlendFactor, BlendFunc, BlendMode, ColorMask, CompareFunc, CullFace,
-    DrawParameters, PolygonFace, PolygonFillMode, ScissorBox, StencilAction, StencilFunc,
+    BlendEquation, BlendFactor, BlendFunc, BlendMode, BlendParameters, ColorMask, CompareFunc,
+    CullFace, DrawParameters, PolygonFace, PolygonFillMode, ScissorBox, StencilAction, StencilFunc,
     StencilOp,
 };
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{
+    gl::upload::{GlTextureUploader, SharedContext},
+    upload::TextureUploader,
+};
+use fxhash::FxHashMap;
 use glow::HasContext;
 #[cfg(not(target_arch = "wasm32"))]
 use glutin::{
     config::ConfigTemplateBuilder,
     context::{
         ContextApi, ContextAttributesBuilder, GlProfile, NotCurrentGlContext,
-        PossiblyCurrentContext, Version,
+        PossiblyCurrentContext, Robustness, Version,
     },
     display::{GetGlDisplay, GlDisplay},
     surface::{GlSurface, Surface, SwapInterval, WindowSurface},
@@ -57,7 +70,9 @@ use glutin::{
 use glutin_winit::{DisplayBuilder, GlWindow};
 #[cfg(not(target_arch = "wasm32"))]
 use raw_window_handle::HasRawWindowHandle;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+#[cfg(not(target_arch = "wasm32"))]
+use std::collections::VecDeque;
 use std::ops::DerefMut;
 use std::rc::{Rc, Weak};
 #[cfg(not(target_arch = "wasm32"))]
@@ -170,6 +185,20 @@ pub enum GlKind {
     OpenGLES,
 }
 
+/// The maximum amount of time (in nanoseconds) to wait for the GPU to finish a frame when limiting
+/// the amount of frames in flight. Prevents the engine from hanging forever if the driver stalls.
+#[cfg(not(target_arch = "wasm32"))]
+const MAX_FRAME_FENCE_WAIT_NS: i32 = 1_000_000_000;
+
+#[cfg(not(target_arch = "wasm32"))]
+fn swap_interval(vsync: VSyncMode) -> SwapInterval {
+    match vsync {
+        VSyncMode::Off => SwapInterval::DontWait,
+        // There's no way to request adaptive v-sync via glutin.
+        VSyncMode::On | VSyncMode::Adaptive => SwapInterval::Wait(NonZeroU32::new(1).unwrap()),
+    }
+}
+
 pub(crate) struct InnerState {
     blend: bool,
 
@@ -195,9 +224,15 @@ pub(crate) struct InnerState {
 
     blend_func: BlendFunc,
     blend_equation: BlendEquation,
+    // `true` if blending was configured per draw buffer, which means that the cached blending
+    // state above does not match the actual state of every draw buffer.
+    indexed_blend: bool,
 
     program: Option<glow::Program>,
     texture_units_storage: TextureUnitsStorage,
+    // Id of the last applied pipeline state object. Must be reset by any code that changes the
+    // pipeline state directly, so the next pipeline state object will be applied in full.
+    pipeline_state: Option<u64>,
 
     stencil_func: StencilFunc,
     stencil_op: StencilOp,
@@ -208,6 +243,11 @@ pub(crate) struct InnerState {
     gl_kind: GlKind,
 
     pub(crate) queries: Vec<glow::Query>,
+    conditional_query: Option<GpuQuery>,
+
+    present_settings: PresentSettings,
+    #[cfg(not(target_arch = "wasm32"))]
+    frame_fences: VecDeque<glow::Fence>,
 
     #[cfg(not(target_arch = "wasm32"))]
     gl_context: PossiblyCurrentContext,
@@ -218,6 +258,7 @@ pub(crate) struct InnerState {
 impl InnerState {
     fn new(
         gl_kind: GlKind,
+        present_settings: PresentSettings,
         #[cfg(not(target_arch = "wasm32"))] gl_context: PossiblyCurrentContext,
         #[cfg(not(target_arch = "wasm32"))] gl_surface: Surface<WindowSurface>,
     ) -> Self {
@@ -245,13 +286,19 @@ impl InnerState {
                 active_unit: 0,
                 units: Default::default(),
             },
+            pipeline_state: None,
             stencil_func: Default::default(),
             stencil_op: Default::default(),
             vao: Default::default(),
             frame_statistics: Default::default(),
             blend_equation: Default::default(),
+            indexed_blend: false,
             gl_kind,
             queries: Default::default(),
+            conditional_query: None,
+            present_settings,
+            #[cfg(not(target_arch = "wasm32"))]
+            frame_fences: Default::default(),
             #[cfg(not(target_arch = "wasm32"))]
             gl_context,
             #[cfg(not(target_arch = "wasm32"))]
@@ -260,10 +307,70 @@ impl InnerState {
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+type GraphicsResetStatusFn = unsafe extern "system" fn() -> u32;
+
+/// Loads `glGetGraphicsResetStatus` function (or its extension counterpart). The function is in
+/// core since OpenGL 4.5 and OpenGL ES 3.2, older versions provide it via robustness extensions.
+/// Some platforms return non-null pointers even for unsupported functions, so the version and the
+/// extensions are checked first.
+#[cfg(not(target_arch = "wasm32"))]
+fn load_graphics_reset_status_fn<F>(
+    gl: &glow::Context,
+    gl_kind: GlKind,
+    mut loader: F,
+) -> Option<GraphicsResetStatusFn>
+where
+    F: FnMut(&str) -> *const std::ffi::c_void,
+{
+    let version = gl.version();
+    let is_core = match gl_kind {
+        GlKind::OpenGL => (version.major, version.minor) >= (4, 5),
+        GlKind::OpenGLES => (version.major, version.minor) >= (3, 2),
+    };
+    let extensions = gl.supported_extensions();
+
+    [
+        ("glGetGraphicsResetStatus", is_core),
+        (
+            "glGetGraphicsResetStatusKHR",
+            extensions.contains("GL_KHR_robustness"),
+        ),
+        (
+            "glGetGraphicsResetStatusARB",
+            extensions.contains("GL_ARB_robustness"),
+        ),
+        (
+            "glGetGraphicsResetStatusEXT",
+            extensions.contains("GL_EXT_robustness"),
+        ),
+    ]
+    .into_iter()
+    .filter(|(_, is_supported)| *is_supported)
+    .find_map(|(name, _)| {
+        let ptr = loader(name);
+        // SAFETY: The pointer is not null and points to the function with the given signature.
+        (!ptr.is_null()).then(|| unsafe {
+            std::mem::transmute::<*const std::ffi::c_void, GraphicsResetStatusFn>(ptr)
+        })
+    })
+}
+
 pub struct GlGraphicsServer {
     pub gl: glow::Context,
     pub(crate) state: RefCell<InnerState>,
     this: RefCell<Option<Weak<GlGraphicsServer>>>,
+    context_lost: Cell<bool>,
+    // `glGetGraphicsResetStatus` is not provided by glow, so it is loaded manually. It is available
+    // only for robust contexts.
+    #[cfg(not(target_arch = "wasm32"))]
+    graphics_reset_status_fn: Option<GraphicsResetStatusFn>,
+    pipeline_states: RefCell<FxHashMap<(glow::Program, DrawParameters), Weak<GlPipelineState>>>,
+    next_pipeline_state_id: Cell<u64>,
+    #[cfg(not(target_arch = "wasm32"))]
+    upload_context: Cell<Option<SharedContext>>,
+    #[cfg(target_arch = "wasm32")]
+    webgl2_context: crate::core::web_sys::WebGl2RenderingContext,
 }
 
 #[derive(Copy, Clone)]
@@ -275,6 +382,7 @@ struct TextureBinding {
 #[derive(Copy, Clone)]
 struct TextureUnit {
     bindings: [TextureBinding; 4],
+    sampler: Option<glow::Sampler>,
 }
 
 impl Default for TextureUnit {
@@ -298,6 +406,7 @@ impl Default for TextureUnit {
                     texture: None,
                 },
             ],
+            sampler: None,
         }
     }
 }
@@ -318,7 +427,15 @@ impl GlGraphicsServer {
         window_builder: WindowBuilder,
     ) -> Result<(Window, SharedGraphicsServer), FrameworkError> {
         #[cfg(not(target_arch = "wasm32"))]
-        let (window, gl_context, gl_surface, mut context, gl_kind) = {
+        let (
+            window,
+            gl_context,
+            gl_surface,
+            mut context,
+            gl_kind,
+            upload_context,
+            graphics_reset_status_fn,
+        ) = {
             let mut template = ConfigTemplateBuilder::new()
                 .prefer_hardware_accelerated(Some(true))
                 .with_stencil_size(8)
@@ -346,17 +463,17 @@ impl GlGraphicsServer {
             #[cfg(not(debug_assertions))]
             let debug = true;
 
-            let gl3_3_core_context_attributes = ContextAttributesBuilder::new()
-                .with_debug(debug)
-                .with_profile(GlProfile::Core)
-                .with_context_api(ContextApi::OpenGl(Some(Version::new(3, 3))))
-                .build(Some(raw_window_handle));
+            let context_attributes = |api: ContextApi, robustness: Robustness| {
+                ContextAttributesBuilder::new()
+                    .with_debug(debug)
+                    .with_profile(GlProfile::Core)
+                    .with_robustness(robustness)
+                    .with_context_api(api)
+                    .build(Some(raw_window_handle))
+            };
 
-            let gles3_context_attributes = ContextAttributesBuilder::new()
-                .with_debug(debug)
-                .with_profile(GlProfile::Core)
-                .with_context_api(ContextApi::Gles(Some(Version::new(3, 0))))
-                .build(Some(raw_window_handle));
+            let gl3_3_core_api = ContextApi::OpenGl(Some(Version::new(3, 3)));
+            let gles3_api = ContextApi::Gles(Some(Version::new(3, 0)));
 
             unsafe {
                 let attrs = window.build_surface_attributes(Default::default());
@@ -365,40 +482,112 @@ impl GlGraphicsServer {
                     .display()
                     .create_window_surface(&gl_config, &attrs)?;
 
-                let (non_current_gl_context, gl_kind) = if let Ok(gl3_3_core_context) =
-                    gl_display.create_context(&gl_config, &gl3_3_core_context_attributes)
-                {
-                    (gl3_3_core_context, GlKind::OpenGL)
+                // A robust context reports context loss (driver reset, GPU hang, etc.) instead of
+                // crashing or silently producing garbage, so it is preferred. Not every driver
+                // supports robust contexts, a regular context is created in this case.
+                let robust_context = [
+                    (gl3_3_core_api, GlKind::OpenGL),
+                    (gles3_api, GlKind::OpenGLES),
+                ]
+                .into_iter()
+                .find_map(|(api, kind)| {
+                    gl_display
+                        .create_context(
+                            &gl_config,
+                            &context_attributes(api, Robustness::RobustLoseContextOnReset),
+                        )
+                        .ok()
+                        .map(|context| (context, kind))
+                });
+                let robustness = if robust_context.is_some() {
+                    Robustness::RobustLoseContextOnReset
                 } else {
-                    (
-                        gl_display.create_context(&gl_config, &gles3_context_attributes)?,
-                        GlKind::OpenGLES,
-                    )
+                    Log::warn(
+                        "Unable to create a robust graphics context, graphics context loss \
+                        won't be detected!",
+                    );
+                    Robustness::NotRobust
+                };
+                let (non_current_gl_context, gl_kind) = match robust_context {
+                    Some(robust_context) => robust_context,
+                    None => {
+                        if let Ok(gl3_3_core_context) = gl_display.create_context(
+                            &gl_config,
+                            &context_attributes(gl3_3_core_api, robustness),
+                        ) {
+                            (gl3_3_core_context, GlKind::OpenGL)
+                        } else {
+                            (
+                                gl_display.create_context(
+                                    &gl_config,
+                                    &context_attributes(gles3_api, robustness),
+                                )?,
+                                GlKind::OpenGLES,
+                            )
+                        }
+                    }
                 };
 
                 let gl_context = non_current_gl_context.make_current(&gl_surface)?;
 
-                if vsync {
-                    Log::verify(gl_surface.set_swap_interval(
-                        &gl_context,
-                        SwapInterval::Wait(NonZeroU32::new(1).unwrap()),
-                    ));
-                }
+                // A context that shares its objects with the main one, it is used to upload
+                // textures on a separate thread.
+                let upload_context_attributes = ContextAttributesBuilder::new()
+                    .with_sharing(&gl_context)
+                    .with_profile(GlProfile::Core)
+                    // Some platforms require shared contexts to have the same reset strategy.
+                    .with_robustness(robustness)
+                    .with_context_api(match gl_kind {
+                        GlKind::OpenGL => ContextApi::OpenGl(Some(Version::new(3, 3))),
+                        GlKind::OpenGLES => ContextApi::Gles(Some(Version::new(3, 0))),
+                    })
+                    .build(None);
+                let upload_context =
+                    match gl_display.create_context(&gl_config, &upload_context_attributes) {
+                        Ok(context) => Some(SharedContext {
+                            context,
+                            display: gl_display.clone(),
+                            config: gl_config.clone(),
+                        }),
+                        Err(err) => {
+                            Log::warn(format!(
+                            "Unable to create a shared context for texture uploads. Reason: {err:?}"
+                        ));
+                            None
+                        }
+                    };
+
+                Log::verify(gl_surface.set_swap_interval(
+                    &gl_context,
+                    swap_interval(if vsync { VSyncMode::On } else { VSyncMode::Off }),
+                ));
+
+                let gl = glow::Context::from_loader_function(|s| {
+                    gl_display.get_proc_address(&CString::new(s).unwrap())
+                });
+
+                let graphics_reset_status_fn = if robustness == Robustness::NotRobust {
+                    None
+                } else {
+                    load_graphics_reset_status_fn(&gl, gl_kind, |s| {
+                        gl_display.get_proc_address(&CString::new(s).unwrap())
+                    })
+                };
 
                 (
                     window,
                     gl_context,
                     gl_surface,
-                    glow::Context::from_loader_function(|s| {
-                        gl_display.get_proc_address(&CString::new(s).unwrap())
-                    }),
+                    gl,
                     gl_kind,
+                    upload_context,
+                    graphics_reset_status_fn,
                 )
             }
         };
 
         #[cfg(target_arch = "wasm32")]
-        let (window, mut context, gl_kind) = {
+        let (window, mut context, gl_kind, webgl2_context) = {
             use crate::core::wasm_bindgen::JsCast;
             use serde::{Deserialize, Serialize};
             use winit::{
@@ -470,8 +659,9 @@ impl GlGraphicsServer {
                 .unwrap();
             (
                 window,
-                glow::Context::from_webgl2_context(webgl2_context),
+                glow::Context::from_webgl2_context(webgl2_context.clone()),
                 GlKind::OpenGLES,
+                webgl2_context,
             )
         };
 
@@ -563,12 +753,25 @@ impl GlGraphicsServer {
             gl: context,
             state: RefCell::new(InnerState::new(
                 gl_kind,
+                PresentSettings {
+                    vsync: if vsync { VSyncMode::On } else { VSyncMode::Off },
+                    ..Default::default()
+                },
                 #[cfg(not(target_arch = "wasm32"))]
                 gl_context,
                 #[cfg(not(target_arch = "wasm32"))]
                 gl_surface,
             )),
             this: Default::default(),
+            context_lost: Cell::new(false),
+            #[cfg(not(target_arch = "wasm32"))]
+            graphics_reset_status_fn,
+            pipeline_states: Default::default(),
+            next_pipeline_state_id: Cell::new(0),
+            #[cfg(not(target_arch = "wasm32"))]
+            upload_context: Cell::new(upload_context),
+            #[cfg(target_arch = "wasm32")]
+            webgl2_context,
         };
 
         let shared = Rc::new(state);
@@ -586,6 +789,144 @@ impl GlGraphicsServer {
         self.state.borrow().gl_kind
     }
 
+    /// Checks whether the blending state could be set per each draw buffer. Indexed blending is in
+    /// core since OpenGL 4.0 and OpenGL ES 3.2.
+    pub fn supports_indexed_blending(&self) -> bool {
+        let version = self.gl.version();
+        let (major, minor) = (version.major, version.minor);
+        let core = match self.gl_kind() {
+            GlKind::OpenGL => major >= 4,
+            GlKind::OpenGLES => (major, minor) >= (3, 2),
+        };
+        let extensions = self.gl.supported_extensions();
+        core || extensions.contains("GL_EXT_draw_buffers_indexed")
+            || extensions.contains("GL_OES_draw_buffers_indexed")
+    }
+
+    /// Checks whether the server is able to source draw call parameters from GPU buffers. Indirect
+    /// drawing is in core since OpenGL 4.0 and OpenGL ES 3.1.
+    pub fn supports_indirect_draw(&self) -> bool {
+        let version = self.gl.version();
+        let (major, minor) = (version.major, version.minor);
+        let core = match self.gl_kind() {
+            GlKind::OpenGL => major >= 4,
+            GlKind::OpenGLES => (major, minor) >= (3, 1),
+        };
+        core || self
+            .gl
+            .supported_extensions()
+            .contains("GL_ARB_draw_indirect")
+    }
+
+    /// Checks whether buffers could be persistently mapped to CPU memory. Immutable buffer storage
+    /// with persistent mapping is in core since OpenGL 4.4, there's no such functionality in
+    /// OpenGL ES without extensions and in WebGL at all.
+    pub fn supports_persistent_mapping(&self) -> bool {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let version = self.gl.version();
+            let core = match self.gl_kind() {
+                GlKind::OpenGL => (version.major, version.minor) >= (4, 4),
+                GlKind::OpenGLES => false,
+            };
+            let extensions = self.gl.supported_extensions();
+            core || extensions.contains("GL_ARB_buffer_storage")
+                || extensions.contains("GL_EXT_buffer_storage")
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            false
+        }
+    }
+
+    /// Returns `true` if draw calls must be discarded, because the query of the active conditional
+    /// rendering scope reported that no samples passed.
+    pub(crate) fn is_draw_discarded(&self) -> bool {
+        let Some(query) = self.state.borrow().conditional_query.clone() else {
+            return false;
+        };
+        matches!(
+            query.try_get_result(),
+            Some(QueryResult::SamplesPassed(0)) | Some(QueryResult::AnySamplesPassed(false))
+        )
+    }
+
+    /// Checks whether the server is able to sample the stencil part of depth-stencil textures.
+    /// Stencil texturing is in core since OpenGL 4.3 and OpenGL ES 3.1.
+    pub fn supports_stencil_texturing(&self) -> bool {
+        let version = self.gl.version();
+        let (major, minor) = (version.major, version.minor);
+        let core = match self.gl_kind() {
+            GlKind::OpenGL => (major, minor) >= (4, 3),
+            GlKind::OpenGLES => (major, minor) >= (3, 1),
+        };
+        core || self
+            .gl
+            .supported_extensions()
+            .contains("GL_ARB_stencil_texturing")
+    }
+
+    /// Checks whether the channels of textures could be swizzled. Texture swizzling is in core since
+    /// OpenGL 3.3 and OpenGL ES 3.0, but it is not available in WebGL.
+    pub fn supports_texture_swizzle(&self) -> bool {
+        if cfg!(target_arch = "wasm32") {
+            return false;
+        }
+        let version = self.gl.version();
+        let (major, minor) = (version.major, version.minor);
+        let core = match self.gl_kind() {
+            GlKind::OpenGL => (major, minor) >= (3, 3),
+            GlKind::OpenGLES => major >= 3,
+        };
+        core || self
+            .gl
+            .supported_extensions()
+            .contains("GL_ARB_texture_swizzle")
+    }
+
+    /// Checks whether the time spent by GPU could be measured. Timer queries are in core since
+    /// OpenGL 3.3, OpenGL ES and WebGL require an extension.
+    pub fn supports_timer_query(&self) -> bool {
+        let version = self.gl.version();
+        let extensions = self.gl.supported_extensions();
+        match self.gl_kind() {
+            GlKind::OpenGL => {
+                (version.major, version.minor) >= (3, 3)
+                    || extensions.contains("GL_ARB_timer_query")
+            }
+            GlKind::OpenGLES => {
+                extensions.contains("GL_EXT_disjoint_timer_query")
+                    || extensions.contains("EXT_disjoint_timer_query_webgl2")
+            }
+        }
+    }
+
+    /// Checks whether the textures with 32-bit (or packed) floating point components could be used
+    /// as render targets. It is always possible on desktop OpenGL, OpenGL ES and WebGL require an
+    /// extension.
+    pub fn supports_float_render_targets(&self) -> bool {
+        match self.gl_kind() {
+            GlKind::OpenGL => true,
+            GlKind::OpenGLES => {
+                let extensions = self.gl.supported_extensions();
+                extensions.contains("GL_EXT_color_buffer_float")
+                    || extensions.contains("EXT_color_buffer_float")
+            }
+        }
+    }
+
+    /// Checks whether the textures with 16-bit floating point components could be used as render
+    /// targets. It is always possible on desktop OpenGL, OpenGL ES and WebGL require an extension.
+    pub fn supports_half_float_render_targets(&self) -> bool {
+        if self.supports_float_render_targets() {
+            return true;
+        }
+        let extensions = self.gl.supported_extensions();
+        extensions.contains("GL_EXT_color_buffer_half_float")
+            || extensions.contains("EXT_color_buffer_half_float")
+    }
+
     pub fn free_texture_unit(&self) -> Option<u32> {
         let state = self.state.borrow();
         for (index, unit) in state.texture_units_storage.units.iter().enumerate() {
@@ -803,6 +1144,59 @@ impl GlGraphicsServer {
         }
     }
 
+    // Non-indexed blending calls set the state of every draw buffer at once, so the cached state
+    // becomes valid again after re-applying it.
+    fn reset_indexed_blend_state(&self) {
+        let mut state = self.state.borrow_mut();
+        if state.indexed_blend {
+            state.indexed_blend = false;
+
+            unsafe {
+                if state.blend {
+                    self.gl.enable(glow::BLEND);
+                } else {
+                    self.gl.disable(glow::BLEND);
+                }
+                self.gl.blend_func_separate(
+                    state.blend_func.sfactor.into_gl(),
+                    state.blend_func.dfactor.into_gl(),
+                    state.blend_func.alpha_sfactor.into_gl(),
+                    state.blend_func.alpha_dfactor.into_gl(),
+                );
+                self.gl.blend_equation_separate(
+                    state.blend_equation.rgb.into_gl(),
+                    state.blend_equation.alpha.into_gl(),
+                );
+            }
+        }
+    }
+
+    fn set_attachment_blend(&self, draw_buffer: u32, blend: Option<&BlendParameters>) {
+        let mut state = self.state.borrow_mut();
+        state.indexed_blend = true;
+        state.frame_statistics.blend_state_changes += 1;
+
+        unsafe {
+            if let Some(blend) = blend {
+                self.gl.blend_func_separate_draw_buffer(
+                    draw_buffer,
+                    blend.func.sfactor.into_gl(),
+                    blend.func.dfactor.into_gl(),
+                    blend.func.alpha_sfactor.into_gl(),
+                    blend.func.alpha_dfactor.into_gl(),
+                );
+                self.gl.blend_equation_separate_draw_buffer(
+                    draw_buffer,
+                    blend.equation.rgb.into_gl(),
+                    blend.equation.alpha.into_gl(),
+                );
+                self.gl.enable_draw_buffer(glow::BLEND, draw_buffer);
+            } else {
+                self.gl.disable_draw_buffer(glow::BLEND, draw_buffer);
+            }
+        }
+    }
+
     pub(crate) fn set_depth_func(&self, depth_func: CompareFunc) {
         let mut state = self.state.borrow_mut();
         if state.depth_func != depth_func {
@@ -864,6 +1258,29 @@ impl GlGraphicsServer {
         }
     }
 
+    pub(crate) fn set_sampler(&self, unit_index: u32, sampler: Option<glow::Sampler>) {
+        let mut state = self.state.borrow_mut();
+        let unit = &mut state.texture_units_storage.units[unit_index as usize];
+        if unit.sampler != sampler {
+            unit.sampler = sampler;
+            unsafe {
+                self.gl.bind_sampler(unit_index, sampler);
+            }
+        }
+    }
+
+    pub(crate) fn unbind_sampler(&self, sampler: glow::Sampler) {
+        let mut state = self.state.borrow_mut();
+        for (unit_index, unit) in state.texture_units_storage.units.iter_mut().enumerate() {
+            if unit.sampler == Some(sampler) {
+                unit.sampler = None;
+                unsafe {
+                    self.gl.bind_sampler(unit_index as u32, None);
+                }
+            }
+        }
+    }
+
     pub(crate) fn set_stencil_func(&self, func: StencilFunc) {
         let mut state = self.state.borrow_mut();
         if state.stencil_func != func {
@@ -935,6 +1352,23 @@ impl GlGraphicsServer {
         }
     }
 
+    pub(crate) fn apply_pipeline_state(&self, pipeline_state: &GlPipelineState) {
+        if self.state.borrow().pipeline_state == Some(pipeline_state.id) {
+            return;
+        }
+
+        self.apply_draw_parameters(&pipeline_state.draw_params);
+        self.set_program(Some(pipeline_state.gl_program));
+
+        let mut state = self.state.borrow_mut();
+        state.pipeline_state = Some(pipeline_state.id);
+        state.frame_statistics.pipeline_state_changes += 1;
+    }
+
+    pub(crate) fn invalidate_pipeline_state(&self) {
+        self.state.borrow_mut().pipeline_state = None;
+    }
+
     pub(crate) fn apply_draw_parameters(&self, draw_params: &DrawParameters) {
         let DrawParameters {
             cull_face,
@@ -945,8 +1379,11 @@ impl GlGraphicsServer {
             blend,
             stencil_op,
             scissor_box,
+            attachment_blend,
         } = draw_params;
 
+        self.reset_indexed_blend_state();
+
         if let Some(ref blend_params) = blend {
             self.set_blend_func(blend_params.func);
             self.set_blend_equation(blend_params.equation);
@@ -955,6 +1392,12 @@ impl GlGraphicsServer {
             self.set_blend(false);
         }
 
+        if !attachment_blend.is_empty() && self.supports_indexed_blending() {
+            for (draw_buffer, blend) in attachment_blend.iter().enumerate() {
+                self.set_attachment_blend(draw_buffer as u32, blend.as_ref());
+            }
+        }
+
         if let Some(depth_func) = depth_test {
             self.set_depth_func(*depth_func);
             self.set_depth_test(true);
@@ -1021,6 +1464,22 @@ impl GraphicsServer for GlGraphicsServer {
         )?)))
     }
 
+    fn create_multisampled_frame_buffer(
+        &self,
+        color_pixel_kind: PixelKind,
+        width: usize,
+        height: usize,
+        sample_count: usize,
+    ) -> Result<GpuFrameBuffer, FrameworkError> {
+        Ok(GpuFrameBuffer(Rc::new(GlFrameBuffer::new_multisampled(
+            self,
+            color_pixel_kind,
+            width,
+            height,
+            sample_count,
+        )?)))
+    }
+
     fn back_buffer(&self) -> GpuFrameBuffer {
         GpuFrameBuffer(Rc::new(GlFrameBuffer::backbuffer(self)))
     }
@@ -1029,6 +1488,26 @@ impl GraphicsServer for GlGraphicsServer {
         Ok(GpuQuery(Rc::new(GlQuery::new(self)?)))
     }
 
+    fn create_ring_buffer(
+        &self,
+        kind: BufferKind,
+        segment_size: usize,
+        segment_count: usize,
+        alignment: usize,
+    ) -> Result<GpuRingBuffer, FrameworkError> {
+        Ok(GpuRingBuffer(Rc::new(GlRingBuffer::new(
+            self,
+            kind,
+            segment_size,
+            segment_count,
+            alignment,
+        )?)))
+    }
+
+    fn create_sampler(&self, desc: GpuSamplerDescriptor) -> Result<GpuSampler, FrameworkError> {
+        Ok(GpuSampler(Rc::new(GlSampler::new(self, desc)?)))
+    }
+
     fn create_program(
         &self,
         name: &str,
@@ -1045,6 +1524,34 @@ impl GraphicsServer for GlGraphicsServer {
         )?)))
     }
 
+    fn create_pipeline_state(
+        &self,
+        program: &GpuProgram,
+        draw_params: &DrawParameters,
+    ) -> Result<GpuPipelineState, FrameworkError> {
+        let gl_program = program
+            .as_any()
+            .downcast_ref::<GlProgram>()
+            .ok_or_else(|| FrameworkError::Custom("Must be OpenGL program!".to_string()))?
+            .id;
+
+        let mut pipeline_states = self.pipeline_states.borrow_mut();
+        let key = (gl_program, draw_params.clone());
+        if let Some(pipeline_state) = pipeline_states.get(&key).and_then(|state| state.upgrade()) {
+            return Ok(GpuPipelineState(pipeline_state));
+        }
+
+        // Remove the states that are no longer used by anyone.
+        pipeline_states.retain(|_, state| state.strong_count() > 0);
+
+        let id = self.next_pipeline_state_id.get();
+        self.next_pipeline_state_id.set(id + 1);
+
+        let pipeline_state = Rc::new(GlPipelineState::new(id, program, draw_params.clone()));
+        pipeline_states.insert(key, Rc::downgrade(&pipeline_state));
+        Ok(GpuPipelineState(pipeline_state))
+    }
+
     fn create_async_read_buffer(
         &self,
         pixel_size: usize,
@@ -1091,6 +1598,9 @@ impl GraphicsServer for GlGraphicsServer {
                 for binding in unit.bindings.iter() {
                     self.gl.bind_texture(binding.target, None)
                 }
+                if unit.sampler.is_some() {
+                    self.gl.bind_sampler(unit_index as u32, None);
+                }
             }
             self.gl.active_texture(glow::TEXTURE0);
         }
@@ -1107,8 +1617,29 @@ impl GraphicsServer for GlGraphicsServer {
     fn swap_buffers(&self) -> Result<(), FrameworkError> {
         #[cfg(not(target_arch = "wasm32"))]
         {
-            let state = self.state.borrow();
-            Ok(state.gl_surface.swap_buffers(&state.gl_context)?)
+            let mut state = self.state.borrow_mut();
+            state.gl_surface.swap_buffers(&state.gl_context)?;
+
+            // Limit the amount of frames queued by the driver, otherwise it could buffer a few
+            // frames ahead, which adds noticeable input latency.
+            unsafe {
+                if let Ok(fence) = self.gl.fence_sync(glow::SYNC_GPU_COMMANDS_COMPLETE, 0) {
+                    state.frame_fences.push_back(fence);
+                }
+                while state.frame_fences.len() > state.present_settings.max_frames_in_flight.max(1)
+                {
+                    if let Some(fence) = state.frame_fences.pop_front() {
+                        self.gl.client_wait_sync(
+                            fence,
+                            glow::SYNC_FLUSH_COMMANDS_BIT,
+                            MAX_FRAME_FENCE_WAIT_NS,
+                        );
+                        self.gl.delete_sync(fence);
+                    }
+                }
+            }
+
+            Ok(())
         }
 
         #[cfg(target_arch = "wasm32")]
@@ -1117,6 +1648,57 @@ impl GraphicsServer for GlGraphicsServer {
         }
     }
 
+    fn is_context_lost(&self) -> bool {
+        if !self.context_lost.get() {
+            // WebGL provides a cheap way of checking whether the context is lost, while on other
+            // platforms the reset status is reported by robust contexts only. The reset status
+            // does not affect the error state of the context, so it does not swallow real errors.
+            #[cfg(target_arch = "wasm32")]
+            let lost = self.webgl2_context.is_context_lost();
+            #[cfg(not(target_arch = "wasm32"))]
+            let lost = self
+                .graphics_reset_status_fn
+                .is_some_and(|get_graphics_reset_status| {
+                    let status = unsafe { get_graphics_reset_status() };
+                    status != glow::NO_ERROR
+                });
+
+            // Once lost, the context cannot be used anymore, so remember the state.
+            self.context_lost.set(lost);
+        }
+        self.context_lost.get()
+    }
+
+    fn set_present_settings(&self, settings: PresentSettings) -> Result<(), FrameworkError> {
+        let mut state = self.state.borrow_mut();
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if state.present_settings.vsync != settings.vsync {
+            if settings.vsync == VSyncMode::Adaptive {
+                Log::warn(
+                    "Adaptive vertical synchronization is not supported by OpenGL graphics \
+                    server, regular vertical synchronization will be used instead.",
+                );
+            }
+            state
+                .gl_surface
+                .set_swap_interval(&state.gl_context, swap_interval(settings.vsync))?;
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        if state.present_settings.vsync != settings.vsync {
+            Log::warn("Vertical synchronization is controlled by the browser on WebAssembly.");
+        }
+
+        state.present_settings = settings;
+
+        Ok(())
+    }
+
+    fn present_settings(&self) -> PresentSettings {
+        self.state.borrow().present_settings
+    }
+
     fn set_frame_size(&self, #[allow(unused_variables)] new_size: (u32, u32)) {
         #[cfg(not(target_arch = "wasm32"))]
         {
@@ -1130,6 +1712,12 @@ impl GraphicsServer for GlGraphicsServer {
         }
     }
 
+    #[cfg(not(target_arch = "wasm32"))]
+    fn create_texture_uploader(&self) -> Option<Box<dyn TextureUploader>> {
+        let shared_context = self.upload_context.take()?;
+        Some(Box::new(GlTextureUploader::new(self, shared_context)))
+    }
+
     fn capabilities(&self) -> ServerCapabilities {
         let gl = &self.gl;
         unsafe {
@@ -1139,10 +1727,40 @@ impl GraphicsServer for GlGraphicsServer {
                     .get_parameter_i32(glow::UNIFORM_BUFFER_OFFSET_ALIGNMENT)
                     as usize,
                 max_lod_bias: gl.get_parameter_f32(glow::MAX_TEXTURE_LOD_BIAS),
+                stencil_texturing: self.supports_stencil_texturing(),
+                max_color_attachments: gl.get_parameter_i32(glow::MAX_COLOR_ATTACHMENTS) as usize,
+                max_draw_buffers: gl.get_parameter_i32(glow::MAX_DRAW_BUFFERS) as usize,
+                indexed_blending: self.supports_indexed_blending(),
+                indirect_draw: self.supports_indirect_draw(),
+                persistent_mapping: self.supports_persistent_mapping(),
+                texture_swizzle: self.supports_texture_swizzle(),
+                timer_query: self.supports_timer_query(),
+                half_float_render_targets: self.supports_half_float_render_targets(),
+                float_render_targets: self.supports_float_render_targets(),
             }
         }
     }
 
+    fn begin_conditional_render(&self, query: &GpuQuery) -> Result<(), FrameworkError> {
+        if !query.is_started() {
+            return Err(FrameworkError::Custom(
+                "Conditional rendering requires a started query!".to_string(),
+            ));
+        }
+        let mut state = self.state.borrow_mut();
+        if state.conditional_query.is_some() {
+            return Err(FrameworkError::Custom(
+                "Conditional rendering scopes cannot be nested!".to_string(),
+            ));
+        }
+        state.conditional_query = Some(query.clone());
+        Ok(())
+    }
+
+    fn end_conditional_render(&self) {
+        self.state.borrow_mut().conditional_query = None;
+    }
+
     fn set_polygon_fill_mode(&self, polygon_face: PolygonFace, polygon_fill_mode: PolygonFillMode) {
         let mut state = self.state.borrow_mut();
         if state.polygon_fill_mode != polygon_fill_mode || state.polygon_face != polygon_face {
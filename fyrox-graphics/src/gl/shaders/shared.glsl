@@ -372,4 +372,30 @@ vec2 S_RotateVec2(vec2 v, float angle)
     float s = sin(angle);
     mat2 m = mat2(c, -s, s, c);
     return m * v;
-}
\ No newline at end of file
+}
+// Calculates screen-space (texture coordinates) motion vector of a fragment using its clip-space
+// positions in the current and in the previous frames. Jitter of the current frame (in NDC) is
+// removed, so static objects will have zero motion.
+vec2 S_MotionVector(vec4 clipPosition, vec4 previousClipPosition, vec2 jitter)
+{
+    vec2 ndc = clipPosition.xy / clipPosition.w - jitter;
+    vec2 previousNdc = previousClipPosition.xy / previousClipPosition.w;
+    return (ndc - previousNdc) * 0.5;
+}
+
+// Returns true if the fragment must be discarded to dissolve an instance in level-of-detail
+// transition. Positive `lodFade` keeps the given portion of the fragments, negative `lodFade` keeps
+// the fragments, that are discarded by the positive value of `1.0 + lodFade`. This way two levels
+// in transition never overlap and never leave holes.
+bool S_LodFadeDiscard(float lodFade, vec2 fragCoord)
+{
+    const float bayer[16] = float[16](
+        0.0, 8.0, 2.0, 10.0,
+        12.0, 4.0, 14.0, 6.0,
+        3.0, 11.0, 1.0, 9.0,
+        15.0, 7.0, 13.0, 5.0
+    );
+    ivec2 p = ivec2(fragCoord) % 4;
+    float threshold = (bayer[p.y * 4 + p.x] + 0.5) / 16.0;
+    return lodFade >= 0.0 ? threshold >= lodFade : threshold < 1.0 + lodFade;
+}
@@ -24,9 +24,11 @@ use crate::{
     gl::{server::GlGraphicsServer, ToGlConstant},
     gpu_texture::{
         image_1d_size_bytes, image_2d_size_bytes, image_3d_size_bytes, Coordinate, CubeMapFace,
-        GpuTextureDescriptor, GpuTextureKind, GpuTextureTrait, MagnificationFilter,
-        MinificationFilter, PixelKind, WrapMode,
+        DepthStencilTextureMode, GpuTextureDescriptor, GpuTextureKind, GpuTextureTrait,
+        MagnificationFilter, MinificationFilter, PixelKind, TextureSwizzle, WrapMode,
     },
+    pixel_conversion::SwizzleSource,
+    CompareFunc,
 };
 use glow::{HasContext, PixelPackData, PixelUnpackData, COMPRESSED_RED_RGTC1, COMPRESSED_RG_RGTC2};
 use std::cell::Cell;
@@ -119,6 +121,9 @@ pub struct GlTexture {
     min_lod: Cell<f32>,
     max_lod: Cell<f32>,
     lod_bias: Cell<f32>,
+    compare_func: Cell<Option<CompareFunc>>,
+    depth_stencil_mode: Cell<DepthStencilTextureMode>,
+    swizzle: Cell<TextureSwizzle>,
     // Force compiler to not implement Send and Sync, because OpenGL is not thread-safe.
     thread_mark: PhantomData<*const u8>,
 }
@@ -140,7 +145,7 @@ impl PixelKind {
         let (data_type, format, internal_format, swizzle_mask) = match self {
             PixelKind::R32F => (glow::FLOAT, glow::RED, glow::R32F, None),
             PixelKind::R32UI => (glow::UNSIGNED_INT, glow::RED_INTEGER, glow::R32UI, None),
-            PixelKind::R16F => (glow::FLOAT, glow::RED, glow::R16F, None),
+            PixelKind::R16F => (glow::HALF_FLOAT, glow::RED, glow::R16F, None),
             PixelKind::D32F => (
                 glow::FLOAT,
                 glow::DEPTH_COMPONENT,
@@ -188,7 +193,12 @@ impl PixelKind {
             PixelKind::RGBA32F => (glow::FLOAT, glow::RGBA, glow::RGBA32F, None),
             PixelKind::RGBA16F => (glow::HALF_FLOAT, glow::RGBA, glow::RGBA16F, None),
             PixelKind::RGB16F => (glow::HALF_FLOAT, glow::RGB, glow::RGB16F, None),
-            PixelKind::R11G11B10F => (glow::FLOAT, glow::RGB, glow::R11F_G11F_B10F, None),
+            PixelKind::R11G11B10F => (
+                glow::UNSIGNED_INT_10F_11F_11F_REV,
+                glow::RGB,
+                glow::R11F_G11F_B10F,
+                None,
+            ),
             PixelKind::L8 => (
                 glow::UNSIGNED_BYTE,
                 glow::RED,
@@ -244,6 +254,446 @@ impl PixelKind {
     }
 }
 
+impl PixelKind {
+    /// Returns the swizzle mask, that applies the given swizzle on top of the channel layout of
+    /// the pixel kind.
+    fn swizzle_mask(self, swizzle: TextureSwizzle) -> [i32; 4] {
+        let base = self.pixel_descriptor().swizzle_mask.unwrap_or([
+            glow::RED as i32,
+            glow::GREEN as i32,
+            glow::BLUE as i32,
+            glow::ALPHA as i32,
+        ]);
+        swizzle.to_array().map(|source| match source {
+            SwizzleSource::Red => base[0],
+            SwizzleSource::Green => base[1],
+            SwizzleSource::Blue => base[2],
+            SwizzleSource::Alpha => base[3],
+            SwizzleSource::Zero => glow::ZERO as i32,
+            SwizzleSource::One => glow::ONE as i32,
+        })
+    }
+}
+
+// Clamps the mip level values to sensible range to prevent weird behavior.
+fn clamp_levels(desc: &mut GpuTextureDescriptor) {
+    let actual_max_level = desc.mip_count.saturating_sub(1);
+    if desc.max_level > actual_max_level {
+        desc.max_level = actual_max_level;
+    }
+    if desc.base_level > desc.max_level {
+        desc.base_level = desc.max_level;
+    }
+    if desc.base_level > actual_max_level {
+        desc.base_level = actual_max_level;
+    }
+}
+
+fn validate_compare_func(
+    pixel_kind: PixelKind,
+    compare_func: Option<CompareFunc>,
+) -> Result<(), FrameworkError> {
+    if compare_func.is_some() && !pixel_kind.is_depth() {
+        Err(FrameworkError::Custom(format!(
+            "Depth comparison can only be used with depth textures, but the pixel kind is {pixel_kind:?}!"
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+fn validate_swizzle(
+    server: &GlGraphicsServer,
+    swizzle: TextureSwizzle,
+) -> Result<(), FrameworkError> {
+    if !swizzle.is_identity() && !server.supports_texture_swizzle() {
+        Err(FrameworkError::Custom(
+            "Texture swizzling is not supported by the graphics server!".to_string(),
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+fn validate_depth_stencil_mode(
+    server: &GlGraphicsServer,
+    pixel_kind: PixelKind,
+    mode: DepthStencilTextureMode,
+) -> Result<(), FrameworkError> {
+    if mode == DepthStencilTextureMode::Stencil {
+        if pixel_kind != PixelKind::D24S8 {
+            return Err(FrameworkError::Custom(format!(
+                "Stencil texturing can only be used with D24S8 textures, but the pixel kind is {pixel_kind:?}!"
+            )));
+        }
+        if !server.supports_stencil_texturing() {
+            return Err(FrameworkError::Custom(
+                "Stencil texturing is not supported by the graphics server!".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn validate_data_size(
+    kind: GpuTextureKind,
+    pixel_kind: PixelKind,
+    mip_count: usize,
+    data: Option<&[u8]>,
+) -> Result<(), FrameworkError> {
+    let mut desired_byte_count = 0;
+
+    'mip_loop: for mip in 0..mip_count {
+        match kind {
+            GpuTextureKind::Line { length } => {
+                if let Some(length) = length.checked_shr(mip as u32) {
+                    desired_byte_count += image_1d_size_bytes(pixel_kind, length);
+                } else {
+                    break 'mip_loop;
+                }
+            }
+            GpuTextureKind::Rectangle { width, height } => {
+                if let (Some(width), Some(height)) = (
+                    width.checked_shr(mip as u32),
+                    height.checked_shr(mip as u32),
+                ) {
+                    desired_byte_count += image_2d_size_bytes(pixel_kind, width, height);
+                } else {
+                    break 'mip_loop;
+                }
+            }
+            GpuTextureKind::Cube { width, height } => {
+                if let (Some(width), Some(height)) = (
+                    width.checked_shr(mip as u32),
+                    height.checked_shr(mip as u32),
+                ) {
+                    desired_byte_count += 6 * image_2d_size_bytes(pixel_kind, width, height);
+                } else {
+                    break 'mip_loop;
+                }
+            }
+            GpuTextureKind::Volume {
+                width,
+                height,
+                depth,
+            } => {
+                if let (Some(width), Some(height), Some(depth)) = (
+                    width.checked_shr(mip as u32),
+                    height.checked_shr(mip as u32),
+                    depth.checked_shr(mip as u32),
+                ) {
+                    desired_byte_count += image_3d_size_bytes(pixel_kind, width, height, depth);
+                } else {
+                    break 'mip_loop;
+                }
+            }
+        };
+    }
+
+    if let Some(data) = data {
+        let actual_data_size = data.len();
+        if actual_data_size != desired_byte_count {
+            return Err(FrameworkError::InvalidTextureData {
+                kind,
+                pixel_kind,
+                mip_count,
+                expected_data_size: desired_byte_count,
+                actual_data_size,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Calculates size in bytes of an image, that has the given row pitch. Tightly packed rows are
+/// assumed if the pitch is not specified.
+fn image_size_bytes(
+    pixel_kind: PixelKind,
+    row_pitch: Option<usize>,
+    width: usize,
+    height: usize,
+    depth: usize,
+) -> usize {
+    match row_pitch {
+        Some(row_pitch) => pixel_kind
+            .descriptor()
+            .padded_image_size_bytes(width, height, depth, row_pitch)
+            .unwrap_or_default(),
+        None => image_3d_size_bytes(pixel_kind, width, height, depth),
+    }
+}
+
+/// Sets unpack parameters of the context, so it reads rows of pixels that are `row_pitch` bytes
+/// apart. OpenGL measures row length in pixels, so a pitch that is not a multiple of the pixel
+/// size could only be expressed using unpack alignment of the rows.
+unsafe fn set_unpack_row_pitch(
+    gl: &glow::Context,
+    pixel_kind: PixelKind,
+    width: usize,
+    row_pitch: usize,
+) -> Result<(), FrameworkError> {
+    let bytes_per_pixel = pixel_kind.descriptor().bytes_per_block;
+    let row_size = width * bytes_per_pixel;
+
+    if row_pitch < row_size {
+        return Err(FrameworkError::Custom(format!(
+            "Row pitch {row_pitch} is less than the size of a row ({row_size} bytes)!"
+        )));
+    }
+
+    if row_pitch % bytes_per_pixel == 0 {
+        gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 1);
+        gl.pixel_store_i32(
+            glow::UNPACK_ROW_LENGTH,
+            (row_pitch / bytes_per_pixel) as i32,
+        );
+        return Ok(());
+    }
+
+    for alignment in [2, 4, 8] {
+        if row_size.next_multiple_of(alignment) == row_pitch {
+            gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, alignment as i32);
+            gl.pixel_store_i32(glow::UNPACK_ROW_LENGTH, 0);
+            return Ok(());
+        }
+    }
+
+    Err(FrameworkError::Custom(format!(
+        "Row pitch {row_pitch} cannot be used for {width} pixels wide rows of \
+        {pixel_kind:?} pixels!"
+    )))
+}
+
+/// Uploads every mip level of the texture that is currently bound to the target that corresponds
+/// to the given texture kind. It does not touch any texture binding, so it could be used on any
+/// context (including shared ones) as long as the texture is bound there. If the row pitch is
+/// specified, only the main level must be uploaded.
+pub(crate) unsafe fn upload_mips(
+    gl: &glow::Context,
+    kind: GpuTextureKind,
+    pixel_kind: PixelKind,
+    mip_count: usize,
+    row_pitch: Option<usize>,
+    data: Option<&[u8]>,
+) -> Result<(), FrameworkError> {
+    if let Some(alignment) = pixel_kind.unpack_alignment() {
+        gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, alignment);
+    }
+
+    if let Some(row_pitch) = row_pitch {
+        let width = match kind {
+            GpuTextureKind::Line { length } => length,
+            GpuTextureKind::Rectangle { width, .. }
+            | GpuTextureKind::Cube { width, .. }
+            | GpuTextureKind::Volume { width, .. } => width,
+        };
+        set_unpack_row_pitch(gl, pixel_kind, width, row_pitch)?;
+    }
+
+    let result = upload_mips_unpacked(gl, kind, pixel_kind, mip_count, row_pitch, data);
+
+    if row_pitch.is_some() {
+        gl.pixel_store_i32(glow::UNPACK_ROW_LENGTH, 0);
+    }
+
+    result
+}
+
+unsafe fn upload_mips_unpacked(
+    gl: &glow::Context,
+    kind: GpuTextureKind,
+    pixel_kind: PixelKind,
+    mip_count: usize,
+    row_pitch: Option<usize>,
+    data: Option<&[u8]>,
+) -> Result<(), FrameworkError> {
+    let PixelDescriptor {
+        data_type,
+        format,
+        internal_format,
+        ..
+    } = pixel_kind.pixel_descriptor();
+
+    let is_compressed = pixel_kind.is_compressed();
+
+    let mut mip_byte_offset = 0;
+    'mip_loop2: for mip in 0..mip_count {
+        match kind {
+            GpuTextureKind::Line { length } => {
+                if let Some(length) = length.checked_shr(mip as u32) {
+                    let size = image_size_bytes(pixel_kind, row_pitch, length, 1, 1) as i32;
+                    let pixels =
+                        data.map(|data| &data[mip_byte_offset..(mip_byte_offset + size as usize)]);
+
+                    if is_compressed {
+                        gl.compressed_tex_image_1d(
+                            glow::TEXTURE_1D,
+                            mip as i32,
+                            internal_format as i32,
+                            length as i32,
+                            0,
+                            size,
+                            pixels.ok_or(FrameworkError::EmptyTextureData)?,
+                        );
+                    } else {
+                        gl.tex_image_1d(
+                            glow::TEXTURE_1D,
+                            mip as i32,
+                            internal_format as i32,
+                            length as i32,
+                            0,
+                            format,
+                            data_type,
+                            PixelUnpackData::Slice(pixels),
+                        );
+                    }
+
+                    mip_byte_offset += size as usize;
+                } else {
+                    // No need to add degenerated mips (0x1, 0x2, 4x0, etc).
+                    break 'mip_loop2;
+                }
+            }
+            GpuTextureKind::Rectangle { width, height } => {
+                if let (Some(width), Some(height)) = (
+                    width.checked_shr(mip as u32),
+                    height.checked_shr(mip as u32),
+                ) {
+                    let size = image_size_bytes(pixel_kind, row_pitch, width, height, 1) as i32;
+                    let pixels =
+                        data.map(|data| &data[mip_byte_offset..(mip_byte_offset + size as usize)]);
+
+                    if is_compressed {
+                        gl.compressed_tex_image_2d(
+                            glow::TEXTURE_2D,
+                            mip as i32,
+                            internal_format as i32,
+                            width as i32,
+                            height as i32,
+                            0,
+                            size,
+                            pixels.ok_or(FrameworkError::EmptyTextureData)?,
+                        );
+                    } else {
+                        gl.tex_image_2d(
+                            glow::TEXTURE_2D,
+                            mip as i32,
+                            internal_format as i32,
+                            width as i32,
+                            height as i32,
+                            0,
+                            format,
+                            data_type,
+                            PixelUnpackData::Slice(pixels),
+                        );
+                    }
+
+                    mip_byte_offset += size as usize;
+                } else {
+                    // No need to add degenerated mips (0x1, 0x2, 4x0, etc).
+                    break 'mip_loop2;
+                }
+            }
+            GpuTextureKind::Cube { width, height } => {
+                if let (Some(width), Some(height)) = (
+                    width.checked_shr(mip as u32),
+                    height.checked_shr(mip as u32),
+                ) {
+                    let bytes_per_face = image_size_bytes(pixel_kind, row_pitch, width, height, 1);
+                    // Padded faces take the full pitch of their last row.
+                    let face_stride = row_pitch.map_or(bytes_per_face, |pitch| pitch * height);
+
+                    for face in 0..6 {
+                        let begin = mip_byte_offset + face * face_stride;
+                        let end = begin + bytes_per_face;
+                        let face_pixels = data.map(|data| &data[begin..end]);
+
+                        if is_compressed {
+                            gl.compressed_tex_image_2d(
+                                glow::TEXTURE_CUBE_MAP_POSITIVE_X + face as u32,
+                                mip as i32,
+                                internal_format as i32,
+                                width as i32,
+                                height as i32,
+                                0,
+                                bytes_per_face as i32,
+                                face_pixels.ok_or(FrameworkError::EmptyTextureData)?,
+                            );
+                        } else {
+                            gl.tex_image_2d(
+                                glow::TEXTURE_CUBE_MAP_POSITIVE_X + face as u32,
+                                mip as i32,
+                                internal_format as i32,
+                                width as i32,
+                                height as i32,
+                                0,
+                                format,
+                                data_type,
+                                PixelUnpackData::Slice(face_pixels),
+                            );
+                        }
+                    }
+
+                    mip_byte_offset += 6 * face_stride;
+                } else {
+                    // No need to add degenerated mips (0x1, 0x2, 4x0, etc).
+                    break 'mip_loop2;
+                }
+            }
+            GpuTextureKind::Volume {
+                width,
+                height,
+                depth,
+            } => {
+                if let (Some(width), Some(height), Some(depth)) = (
+                    width.checked_shr(mip as u32),
+                    height.checked_shr(mip as u32),
+                    depth.checked_shr(mip as u32),
+                ) {
+                    let size = image_size_bytes(pixel_kind, row_pitch, width, height, depth) as i32;
+                    let pixels =
+                        data.map(|data| &data[mip_byte_offset..(mip_byte_offset + size as usize)]);
+
+                    if is_compressed {
+                        gl.compressed_tex_image_3d(
+                            glow::TEXTURE_3D,
+                            mip as i32,
+                            internal_format as i32,
+                            width as i32,
+                            height as i32,
+                            depth as i32,
+                            0,
+                            size,
+                            pixels.ok_or(FrameworkError::EmptyTextureData)?,
+                        );
+                    } else {
+                        gl.tex_image_3d(
+                            glow::TEXTURE_3D,
+                            mip as i32,
+                            internal_format as i32,
+                            width as i32,
+                            height as i32,
+                            depth as i32,
+                            0,
+                            format,
+                            data_type,
+                            PixelUnpackData::Slice(pixels),
+                        );
+                    }
+
+                    mip_byte_offset += size as usize;
+                } else {
+                    // No need to add degenerated mips (0x1, 0x2, 4x0, etc).
+                    break 'mip_loop2;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
 struct TempBinding {
     server: Rc<GlGraphicsServer>,
     unit: u32,
@@ -347,6 +797,50 @@ impl TempBinding {
                 .tex_parameter_f32(self.target, glow::TEXTURE_LOD_BIAS, bias);
         }
     }
+
+    fn set_compare_func(&mut self, compare_func: Option<CompareFunc>) {
+        unsafe {
+            if let Some(compare_func) = compare_func {
+                self.server.gl.tex_parameter_i32(
+                    self.target,
+                    glow::TEXTURE_COMPARE_MODE,
+                    glow::COMPARE_REF_TO_TEXTURE as i32,
+                );
+                self.server.gl.tex_parameter_i32(
+                    self.target,
+                    glow::TEXTURE_COMPARE_FUNC,
+                    compare_func.into_gl() as i32,
+                );
+            } else {
+                self.server.gl.tex_parameter_i32(
+                    self.target,
+                    glow::TEXTURE_COMPARE_MODE,
+                    glow::NONE as i32,
+                );
+            }
+        }
+    }
+
+    fn set_depth_stencil_mode(&mut self, mode: DepthStencilTextureMode) {
+        unsafe {
+            self.server.gl.tex_parameter_i32(
+                self.target,
+                glow::DEPTH_STENCIL_TEXTURE_MODE,
+                match mode {
+                    DepthStencilTextureMode::Depth => glow::DEPTH_COMPONENT,
+                    DepthStencilTextureMode::Stencil => glow::STENCIL_INDEX,
+                } as i32,
+            );
+        }
+    }
+
+    fn set_swizzle_mask(&mut self, mask: [i32; 4]) {
+        unsafe {
+            self.server
+                .gl
+                .tex_parameter_i32_slice(self.target, glow::TEXTURE_SWIZZLE_RGBA, &mask);
+        }
+    }
 }
 
 impl Drop for TempBinding {
@@ -375,59 +869,128 @@ impl GlTexture {
         server: &GlGraphicsServer,
         mut desc: GpuTextureDescriptor,
     ) -> Result<Self, FrameworkError> {
-        // Clamp the mip level values to sensible range to prevent weird behavior.
-        let actual_max_level = desc.mip_count.saturating_sub(1);
-        if desc.max_level > actual_max_level {
-            desc.max_level = actual_max_level;
-        }
-        if desc.base_level > desc.max_level {
-            desc.base_level = desc.max_level;
-        }
-        if desc.base_level > actual_max_level {
-            desc.base_level = actual_max_level;
-        }
+        clamp_levels(&mut desc);
+        validate_compare_func(desc.pixel_kind, desc.compare_func)?;
+        validate_depth_stencil_mode(server, desc.pixel_kind, desc.depth_stencil_mode)?;
+        validate_swizzle(server, desc.swizzle)?;
 
         unsafe {
             let texture = server.gl.create_texture()?;
 
-            let result = Self {
-                state: server.weak(),
-                texture,
-                kind: desc.kind.into(),
-                min_filter: desc.min_filter.into(),
-                mag_filter: desc.mag_filter.into(),
-                s_wrap_mode: desc.s_wrap_mode.into(),
-                t_wrap_mode: desc.t_wrap_mode.into(),
-                r_wrap_mode: desc.r_wrap_mode.into(),
-                anisotropy: desc.anisotropy.into(),
-                pixel_kind: desc.pixel_kind.into(),
-                base_level: desc.base_level.into(),
-                max_level: desc.max_level.into(),
-                min_lod: desc.min_lod.into(),
-                max_lod: desc.max_lod.into(),
-                lod_bias: desc.lod_bias.into(),
-                thread_mark: PhantomData,
-            };
+            let result = Self::from_raw(server, texture, &desc);
 
             result.set_data(desc.kind, desc.pixel_kind, desc.mip_count, desc.data)?;
 
-            let mut binding = result.make_temp_binding();
-            binding.set_magnification_filter(desc.mag_filter);
-            binding.set_minification_filter(desc.min_filter);
-            binding.set_wrap(Coordinate::S, desc.s_wrap_mode);
-            binding.set_wrap(Coordinate::T, desc.t_wrap_mode);
-            binding.set_wrap(Coordinate::R, desc.r_wrap_mode);
-            binding.set_anisotropy(desc.anisotropy);
-            binding.set_base_level(desc.base_level);
-            binding.set_max_level(desc.max_level);
-            binding.set_min_lod(desc.min_lod);
-            binding.set_max_lod(desc.max_lod);
-            binding.set_lod_bias(desc.lod_bias);
+            result.apply_parameters(&desc);
 
             Ok(result)
         }
     }
 
+    /// Wraps a texture, that was created and filled with data using a context that shares its
+    /// objects with the context of the given server. The data of the descriptor is ignored.
+    pub(crate) fn from_shared(
+        server: &GlGraphicsServer,
+        texture: glow::Texture,
+        mut desc: GpuTextureDescriptor,
+    ) -> Self {
+        clamp_levels(&mut desc);
+        let result = Self::from_raw(server, texture, &desc);
+        result.apply_parameters(&desc);
+        result
+    }
+
+    fn from_raw(
+        server: &GlGraphicsServer,
+        texture: glow::Texture,
+        desc: &GpuTextureDescriptor,
+    ) -> Self {
+        Self {
+            state: server.weak(),
+            texture,
+            kind: desc.kind.into(),
+            min_filter: desc.min_filter.into(),
+            mag_filter: desc.mag_filter.into(),
+            s_wrap_mode: desc.s_wrap_mode.into(),
+            t_wrap_mode: desc.t_wrap_mode.into(),
+            r_wrap_mode: desc.r_wrap_mode.into(),
+            anisotropy: desc.anisotropy.into(),
+            pixel_kind: desc.pixel_kind.into(),
+            base_level: desc.base_level.into(),
+            max_level: desc.max_level.into(),
+            min_lod: desc.min_lod.into(),
+            max_lod: desc.max_lod.into(),
+            lod_bias: desc.lod_bias.into(),
+            compare_func: desc.compare_func.into(),
+            depth_stencil_mode: desc.depth_stencil_mode.into(),
+            swizzle: desc.swizzle.into(),
+            thread_mark: PhantomData,
+        }
+    }
+
+    /// Returns filters, that are actually used by GPU. Integer textures (and depth-stencil textures
+    /// in stencil mode) do not support linear filtration, such textures are incomplete and return
+    /// zeros if linear filtration is used. This method replaces linear filtration with the nearest
+    /// one for such textures.
+    fn effective_filters(&self) -> (MinificationFilter, MagnificationFilter) {
+        let (min_filter, mag_filter) = (self.min_filter.get(), self.mag_filter.get());
+        let pixel_kind = self.pixel_kind.get();
+        let stencil = pixel_kind == PixelKind::D24S8
+            && self.depth_stencil_mode.get() == DepthStencilTextureMode::Stencil;
+        if pixel_kind.is_integer() || stencil {
+            let min_filter = match min_filter {
+                MinificationFilter::Nearest | MinificationFilter::Linear => {
+                    MinificationFilter::Nearest
+                }
+                MinificationFilter::NearestMipMapNearest
+                | MinificationFilter::NearestMipMapLinear
+                | MinificationFilter::LinearMipMapNearest
+                | MinificationFilter::LinearMipMapLinear => {
+                    MinificationFilter::NearestMipMapNearest
+                }
+            };
+            (min_filter, MagnificationFilter::Nearest)
+        } else {
+            (min_filter, mag_filter)
+        }
+    }
+
+    fn apply_filters(&self, binding: &mut TempBinding) {
+        let (min_filter, mag_filter) = self.effective_filters();
+        binding.set_minification_filter(min_filter);
+        binding.set_magnification_filter(mag_filter);
+    }
+
+    /// Applies the swizzle of the texture together with the channel layout of its pixel kind (for
+    /// example, luminance textures are stored as red channel only). Does nothing if swizzling is
+    /// not supported, in this case only identity swizzle could be set.
+    fn apply_swizzle(&self, binding: &mut TempBinding) {
+        if binding.server.supports_texture_swizzle() {
+            binding.set_swizzle_mask(self.pixel_kind.get().swizzle_mask(self.swizzle.get()));
+        }
+    }
+
+    fn apply_parameters(&self, desc: &GpuTextureDescriptor) {
+        let mut binding = self.make_temp_binding();
+        self.apply_filters(&mut binding);
+        self.apply_swizzle(&mut binding);
+        binding.set_wrap(Coordinate::S, desc.s_wrap_mode);
+        binding.set_wrap(Coordinate::T, desc.t_wrap_mode);
+        binding.set_wrap(Coordinate::R, desc.r_wrap_mode);
+        binding.set_anisotropy(desc.anisotropy);
+        binding.set_base_level(desc.base_level);
+        binding.set_max_level(desc.max_level);
+        binding.set_min_lod(desc.min_lod);
+        binding.set_max_lod(desc.max_lod);
+        binding.set_lod_bias(desc.lod_bias);
+        if desc.pixel_kind.is_depth() {
+            binding.set_compare_func(desc.compare_func);
+        }
+        if desc.depth_stencil_mode == DepthStencilTextureMode::Stencil {
+            binding.set_depth_stencil_mode(desc.depth_stencil_mode);
+        }
+    }
+
     pub fn bind(&self, server: &GlGraphicsServer, sampler_index: u32) {
         server.set_texture(
             sampler_index,
@@ -467,8 +1030,8 @@ impl GpuTextureTrait for GlTexture {
     }
 
     fn set_minification_filter(&self, filter: MinificationFilter) {
-        self.make_temp_binding().set_minification_filter(filter);
         self.min_filter.set(filter);
+        self.apply_filters(&mut self.make_temp_binding());
     }
 
     fn minification_filter(&self) -> MinificationFilter {
@@ -476,8 +1039,8 @@ impl GpuTextureTrait for GlTexture {
     }
 
     fn set_magnification_filter(&self, filter: MagnificationFilter) {
-        self.make_temp_binding().set_magnification_filter(filter);
         self.mag_filter.set(filter);
+        self.apply_filters(&mut self.make_temp_binding());
     }
 
     fn magnification_filter(&self) -> MagnificationFilter {
@@ -525,278 +1088,170 @@ impl GpuTextureTrait for GlTexture {
     ) -> Result<(), FrameworkError> {
         let mip_count = mip_count.max(1);
 
-        let mut desired_byte_count = 0;
+        validate_data_size(kind, pixel_kind, mip_count, data)?;
 
-        'mip_loop: for mip in 0..mip_count {
-            match kind {
-                GpuTextureKind::Line { length } => {
-                    if let Some(length) = length.checked_shr(mip as u32) {
-                        desired_byte_count += image_1d_size_bytes(pixel_kind, length);
-                    } else {
-                        break 'mip_loop;
-                    }
-                }
-                GpuTextureKind::Rectangle { width, height } => {
-                    if let (Some(width), Some(height)) = (
-                        width.checked_shr(mip as u32),
-                        height.checked_shr(mip as u32),
-                    ) {
-                        desired_byte_count += image_2d_size_bytes(pixel_kind, width, height);
-                    } else {
-                        break 'mip_loop;
-                    }
-                }
-                GpuTextureKind::Cube { width, height } => {
-                    if let (Some(width), Some(height)) = (
-                        width.checked_shr(mip as u32),
-                        height.checked_shr(mip as u32),
-                    ) {
-                        desired_byte_count += 6 * image_2d_size_bytes(pixel_kind, width, height);
-                    } else {
-                        break 'mip_loop;
-                    }
-                }
-                GpuTextureKind::Volume {
-                    width,
-                    height,
-                    depth,
-                } => {
-                    if let (Some(width), Some(height), Some(depth)) = (
-                        width.checked_shr(mip as u32),
-                        height.checked_shr(mip as u32),
-                        depth.checked_shr(mip as u32),
-                    ) {
-                        desired_byte_count += image_3d_size_bytes(pixel_kind, width, height, depth);
-                    } else {
-                        break 'mip_loop;
-                    }
-                }
-            };
+        self.kind.set(kind);
+        self.pixel_kind.set(pixel_kind);
+
+        let mut temp_binding = self.make_temp_binding();
+        temp_binding.set_max_level(mip_count.saturating_sub(1));
+        // Pixel kind might change, so the filters and the swizzle might need to change too.
+        self.apply_filters(&mut temp_binding);
+        self.apply_swizzle(&mut temp_binding);
+
+        unsafe {
+            upload_mips(
+                &temp_binding.server.gl,
+                kind,
+                pixel_kind,
+                mip_count,
+                None,
+                data,
+            )
         }
+    }
 
-        if let Some(data) = data {
-            let actual_data_size = data.len();
-            if actual_data_size != desired_byte_count {
-                return Err(FrameworkError::InvalidTextureData {
-                    expected_data_size: desired_byte_count,
-                    actual_data_size,
-                });
-            }
+    fn set_data_with_row_pitch(
+        &self,
+        kind: GpuTextureKind,
+        pixel_kind: PixelKind,
+        row_pitch: usize,
+        data: &[u8],
+    ) -> Result<(), FrameworkError> {
+        let (width, height, depth) = match kind {
+            GpuTextureKind::Line { length } => (length, 1, 1),
+            GpuTextureKind::Rectangle { width, height } => (width, height, 1),
+            GpuTextureKind::Cube { width, height } => (width, height, 6),
+            GpuTextureKind::Volume {
+                width,
+                height,
+                depth,
+            } => (width, height, depth),
+        };
+
+        let expected_data_size = pixel_kind
+            .descriptor()
+            .padded_image_size_bytes(width, height, depth, row_pitch)
+            .ok_or_else(|| {
+                FrameworkError::Custom(format!(
+                    "Row pitch {row_pitch} cannot be used for {width} pixels wide rows of \
+                    {pixel_kind:?} pixels!"
+                ))
+            })?;
+        if data.len() < expected_data_size {
+            return Err(FrameworkError::InvalidTextureData {
+                kind,
+                pixel_kind,
+                mip_count: 1,
+                expected_data_size,
+                actual_data_size: data.len(),
+            });
         }
 
         self.kind.set(kind);
         self.pixel_kind.set(pixel_kind);
 
         let mut temp_binding = self.make_temp_binding();
-        temp_binding.set_max_level(mip_count.saturating_sub(1));
-        let target = kind.gl_texture_target();
+        temp_binding.set_max_level(0);
+        self.apply_filters(&mut temp_binding);
+        self.apply_swizzle(&mut temp_binding);
 
         unsafe {
-            let PixelDescriptor {
-                data_type,
-                format,
-                internal_format,
-                swizzle_mask,
-            } = pixel_kind.pixel_descriptor();
+            upload_mips(
+                &temp_binding.server.gl,
+                kind,
+                pixel_kind,
+                1,
+                Some(row_pitch),
+                Some(data),
+            )
+        }
+    }
 
-            let is_compressed = pixel_kind.is_compressed();
+    fn set_region_data(
+        &self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+        data: &[u8],
+    ) -> Result<(), FrameworkError> {
+        let row_pitch = image_1d_size_bytes(self.pixel_kind.get(), width);
+        self.set_region_data_with_row_pitch(x, y, width, height, row_pitch, data)
+    }
 
-            if let Some(alignment) = pixel_kind.unpack_alignment() {
-                temp_binding
-                    .server
-                    .gl
-                    .pixel_store_i32(glow::UNPACK_ALIGNMENT, alignment);
-            }
+    fn set_region_data_with_row_pitch(
+        &self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+        row_pitch: usize,
+        data: &[u8],
+    ) -> Result<(), FrameworkError> {
+        let pixel_kind = self.pixel_kind.get();
+
+        let GpuTextureKind::Rectangle {
+            width: texture_width,
+            height: texture_height,
+        } = self.kind.get()
+        else {
+            return Err(FrameworkError::Custom(
+                "Region updates are supported only for rectangle textures!".to_string(),
+            ));
+        };
 
-            if let Some(swizzle_mask) = swizzle_mask {
-                if temp_binding
-                    .server
-                    .gl
-                    .supported_extensions()
-                    .contains("GL_ARB_texture_swizzle")
-                {
-                    temp_binding.server.gl.tex_parameter_i32_slice(
-                        target,
-                        glow::TEXTURE_SWIZZLE_RGBA,
-                        &swizzle_mask,
-                    );
-                }
-            }
+        if pixel_kind.is_compressed() {
+            return Err(FrameworkError::Custom(format!(
+                "Region updates are not supported for compressed pixel format {pixel_kind:?}!"
+            )));
+        }
 
-            let mut mip_byte_offset = 0;
-            'mip_loop2: for mip in 0..mip_count {
-                match kind {
-                    GpuTextureKind::Line { length } => {
-                        if let Some(length) = length.checked_shr(mip as u32) {
-                            let size = image_1d_size_bytes(pixel_kind, length) as i32;
-                            let pixels = data.map(|data| {
-                                &data[mip_byte_offset..(mip_byte_offset + size as usize)]
-                            });
-
-                            if is_compressed {
-                                temp_binding.server.gl.compressed_tex_image_1d(
-                                    glow::TEXTURE_1D,
-                                    mip as i32,
-                                    internal_format as i32,
-                                    length as i32,
-                                    0,
-                                    size,
-                                    pixels.ok_or(FrameworkError::EmptyTextureData)?,
-                                );
-                            } else {
-                                temp_binding.server.gl.tex_image_1d(
-                                    glow::TEXTURE_1D,
-                                    mip as i32,
-                                    internal_format as i32,
-                                    length as i32,
-                                    0,
-                                    format,
-                                    data_type,
-                                    PixelUnpackData::Slice(pixels),
-                                );
-                            }
-
-                            mip_byte_offset += size as usize;
-                        } else {
-                            // No need to add degenerated mips (0x1, 0x2, 4x0, etc).
-                            break 'mip_loop2;
-                        }
-                    }
-                    GpuTextureKind::Rectangle { width, height } => {
-                        if let (Some(width), Some(height)) = (
-                            width.checked_shr(mip as u32),
-                            height.checked_shr(mip as u32),
-                        ) {
-                            let size = image_2d_size_bytes(pixel_kind, width, height) as i32;
-                            let pixels = data.map(|data| {
-                                &data[mip_byte_offset..(mip_byte_offset + size as usize)]
-                            });
-
-                            if is_compressed {
-                                temp_binding.server.gl.compressed_tex_image_2d(
-                                    glow::TEXTURE_2D,
-                                    mip as i32,
-                                    internal_format as i32,
-                                    width as i32,
-                                    height as i32,
-                                    0,
-                                    size,
-                                    pixels.ok_or(FrameworkError::EmptyTextureData)?,
-                                );
-                            } else {
-                                temp_binding.server.gl.tex_image_2d(
-                                    glow::TEXTURE_2D,
-                                    mip as i32,
-                                    internal_format as i32,
-                                    width as i32,
-                                    height as i32,
-                                    0,
-                                    format,
-                                    data_type,
-                                    PixelUnpackData::Slice(pixels),
-                                );
-                            }
-
-                            mip_byte_offset += size as usize;
-                        } else {
-                            // No need to add degenerated mips (0x1, 0x2, 4x0, etc).
-                            break 'mip_loop2;
-                        }
-                    }
-                    GpuTextureKind::Cube { width, height } => {
-                        if let (Some(width), Some(height)) = (
-                            width.checked_shr(mip as u32),
-                            height.checked_shr(mip as u32),
-                        ) {
-                            let bytes_per_face = image_2d_size_bytes(pixel_kind, width, height);
-
-                            for face in 0..6 {
-                                let begin = mip_byte_offset + face * bytes_per_face;
-                                let end = mip_byte_offset + (face + 1) * bytes_per_face;
-                                let face_pixels = data.map(|data| &data[begin..end]);
-
-                                if is_compressed {
-                                    temp_binding.server.gl.compressed_tex_image_2d(
-                                        glow::TEXTURE_CUBE_MAP_POSITIVE_X + face as u32,
-                                        mip as i32,
-                                        internal_format as i32,
-                                        width as i32,
-                                        height as i32,
-                                        0,
-                                        bytes_per_face as i32,
-                                        face_pixels.ok_or(FrameworkError::EmptyTextureData)?,
-                                    );
-                                } else {
-                                    temp_binding.server.gl.tex_image_2d(
-                                        glow::TEXTURE_CUBE_MAP_POSITIVE_X + face as u32,
-                                        mip as i32,
-                                        internal_format as i32,
-                                        width as i32,
-                                        height as i32,
-                                        0,
-                                        format,
-                                        data_type,
-                                        PixelUnpackData::Slice(face_pixels),
-                                    );
-                                }
-                            }
-
-                            mip_byte_offset += 6 * bytes_per_face;
-                        } else {
-                            // No need to add degenerated mips (0x1, 0x2, 4x0, etc).
-                            break 'mip_loop2;
-                        }
-                    }
-                    GpuTextureKind::Volume {
-                        width,
-                        height,
-                        depth,
-                    } => {
-                        if let (Some(width), Some(height), Some(depth)) = (
-                            width.checked_shr(mip as u32),
-                            height.checked_shr(mip as u32),
-                            depth.checked_shr(mip as u32),
-                        ) {
-                            let size = image_3d_size_bytes(pixel_kind, width, height, depth) as i32;
-                            let pixels = data.map(|data| {
-                                &data[mip_byte_offset..(mip_byte_offset + size as usize)]
-                            });
-
-                            if is_compressed {
-                                temp_binding.server.gl.compressed_tex_image_3d(
-                                    glow::TEXTURE_3D,
-                                    mip as i32,
-                                    internal_format as i32,
-                                    width as i32,
-                                    height as i32,
-                                    depth as i32,
-                                    0,
-                                    size,
-                                    pixels.ok_or(FrameworkError::EmptyTextureData)?,
-                                );
-                            } else {
-                                temp_binding.server.gl.tex_image_3d(
-                                    glow::TEXTURE_3D,
-                                    mip as i32,
-                                    internal_format as i32,
-                                    width as i32,
-                                    height as i32,
-                                    depth as i32,
-                                    0,
-                                    format,
-                                    data_type,
-                                    PixelUnpackData::Slice(pixels),
-                                );
-                            }
-
-                            mip_byte_offset += size as usize;
-                        } else {
-                            // No need to add degenerated mips (0x1, 0x2, 4x0, etc).
-                            break 'mip_loop2;
-                        }
-                    }
-                }
-            }
+        if x + width > texture_width || y + height > texture_height {
+            return Err(FrameworkError::Custom(format!(
+                "Region {x};{y} {width}x{height} is out of bounds of the \
+                {texture_width}x{texture_height} texture!"
+            )));
+        }
+
+        let expected_data_size = pixel_kind
+            .descriptor()
+            .padded_image_size_bytes(width, height, 1, row_pitch)
+            .ok_or_else(|| {
+                FrameworkError::Custom(format!(
+                    "Row pitch {row_pitch} is less than the size of a {width} pixels wide row!"
+                ))
+            })?;
+        if data.len() < expected_data_size {
+            return Err(FrameworkError::InvalidTextureData {
+                kind: GpuTextureKind::Rectangle { width, height },
+                pixel_kind,
+                mip_count: 1,
+                expected_data_size,
+                actual_data_size: data.len(),
+            });
+        }
+
+        let PixelDescriptor {
+            data_type, format, ..
+        } = pixel_kind.pixel_descriptor();
+
+        let temp_binding = self.make_temp_binding();
+        unsafe {
+            let gl = &temp_binding.server.gl;
+            set_unpack_row_pitch(gl, pixel_kind, width, row_pitch)?;
+            gl.tex_sub_image_2d(
+                glow::TEXTURE_2D,
+                0,
+                x as i32,
+                y as i32,
+                width as i32,
+                height as i32,
+                format,
+                data_type,
+                PixelUnpackData::Slice(Some(data)),
+            );
+            gl.pixel_store_i32(glow::UNPACK_ROW_LENGTH, 0);
         }
 
         Ok(())
@@ -806,18 +1261,23 @@ impl GpuTextureTrait for GlTexture {
         let temp_binding = self.make_temp_binding();
         unsafe {
             let desc = self.pixel_kind.get().pixel_descriptor();
+            let mip_size = |size: usize| size.checked_shr(level as u32).unwrap_or(0).max(1);
             let (kind, buffer_size) = match self.kind.get() {
                 GpuTextureKind::Line { length } => (
                     glow::TEXTURE_1D,
-                    image_1d_size_bytes(self.pixel_kind.get(), length),
+                    image_1d_size_bytes(self.pixel_kind.get(), mip_size(length)),
                 ),
                 GpuTextureKind::Rectangle { width, height } => (
                     glow::TEXTURE_2D,
-                    image_2d_size_bytes(self.pixel_kind.get(), width, height),
+                    image_2d_size_bytes(self.pixel_kind.get(), mip_size(width), mip_size(height)),
                 ),
                 GpuTextureKind::Cube { width, height } => (
                     glow::TEXTURE_CUBE_MAP,
-                    6 * image_2d_size_bytes(self.pixel_kind.get(), width, height),
+                    6 * image_2d_size_bytes(
+                        self.pixel_kind.get(),
+                        mip_size(width),
+                        mip_size(height),
+                    ),
                 ),
                 GpuTextureKind::Volume {
                     width,
@@ -825,10 +1285,21 @@ impl GpuTextureTrait for GlTexture {
                     depth,
                 } => (
                     glow::TEXTURE_3D,
-                    image_3d_size_bytes(self.pixel_kind.get(), width, height, depth),
+                    image_3d_size_bytes(
+                        self.pixel_kind.get(),
+                        mip_size(width),
+                        mip_size(height),
+                        mip_size(depth),
+                    ),
                 ),
             };
 
+            // Rows must be tightly packed, otherwise the size of the buffer will not match.
+            temp_binding
+                .server
+                .gl
+                .pixel_store_i32(glow::PACK_ALIGNMENT, 1);
+
             let mut bytes = vec![0; buffer_size];
             temp_binding.server.gl.get_tex_image(
                 kind,
@@ -847,6 +1318,10 @@ impl GpuTextureTrait for GlTexture {
             if let GpuTextureKind::Rectangle { width, height } = self.kind.get() {
                 let pixel_info = self.pixel_kind.get().pixel_descriptor();
                 let mut buffer = vec![0; image_2d_size_bytes(self.pixel_kind.get(), width, height)];
+                temp_binding
+                    .server
+                    .gl
+                    .pixel_store_i32(glow::PACK_ALIGNMENT, 1);
                 temp_binding.server.gl.read_pixels(
                     0,
                     0,
@@ -915,4 +1390,80 @@ impl GpuTextureTrait for GlTexture {
     fn lod_bias(&self) -> f32 {
         self.lod_bias.get()
     }
+
+    fn set_compare_func(&self, compare_func: Option<CompareFunc>) -> Result<(), FrameworkError> {
+        validate_compare_func(self.pixel_kind.get(), compare_func)?;
+        self.make_temp_binding().set_compare_func(compare_func);
+        self.compare_func.set(compare_func);
+        Ok(())
+    }
+
+    fn compare_func(&self) -> Option<CompareFunc> {
+        self.compare_func.get()
+    }
+
+    fn set_depth_stencil_mode(&self, mode: DepthStencilTextureMode) -> Result<(), FrameworkError> {
+        let server = self
+            .state
+            .upgrade()
+            .ok_or(FrameworkError::GraphicsServerUnavailable)?;
+        validate_depth_stencil_mode(&server, self.pixel_kind.get(), mode)?;
+        self.depth_stencil_mode.set(mode);
+        let mut binding = self.make_temp_binding();
+        binding.set_depth_stencil_mode(mode);
+        self.apply_filters(&mut binding);
+        Ok(())
+    }
+
+    fn depth_stencil_mode(&self) -> DepthStencilTextureMode {
+        self.depth_stencil_mode.get()
+    }
+
+    fn set_swizzle(&self, swizzle: TextureSwizzle) -> Result<(), FrameworkError> {
+        let server = self
+            .state
+            .upgrade()
+            .ok_or(FrameworkError::GraphicsServerUnavailable)?;
+        validate_swizzle(&server, swizzle)?;
+        self.swizzle.set(swizzle);
+        self.apply_swizzle(&mut self.make_temp_binding());
+        Ok(())
+    }
+
+    fn swizzle(&self) -> TextureSwizzle {
+        self.swizzle.get()
+    }
+
+    fn generate_mipmaps(&self) -> Result<(), FrameworkError> {
+        let pixel_kind = self.pixel_kind.get();
+        if pixel_kind.is_compressed() {
+            return Err(FrameworkError::Custom(format!(
+                "Unable to generate mip-maps for the texture with compressed pixel format {pixel_kind:?}!"
+            )));
+        }
+
+        let max_size = match self.kind.get() {
+            GpuTextureKind::Line { length } => length,
+            GpuTextureKind::Rectangle { width, height }
+            | GpuTextureKind::Cube { width, height } => width.max(height),
+            GpuTextureKind::Volume {
+                width,
+                height,
+                depth,
+            } => width.max(height).max(depth),
+        };
+        let max_level = max_size.max(1).ilog2() as usize;
+
+        let mut binding = self.make_temp_binding();
+        binding.set_base_level(0);
+        binding.set_max_level(max_level);
+        unsafe {
+            binding.server.gl.generate_mipmap(binding.target);
+        }
+
+        self.base_level.set(0);
+        self.max_level.set(max_level);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,266 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+use crate::{
+    core::log::Log,
+    error::FrameworkError,
+    gl::{
+        server::GlGraphicsServer,
+        texture::{upload_mips, validate_data_size, GlTexture},
+    },
+    gpu_texture::{GpuTexture, GpuTextureDescriptor},
+    upload::{FinishedUpload, TextureSource, TextureUploader},
+};
+use glow::HasContext;
+use glutin::{
+    config::Config,
+    context::{NotCurrentContext, NotCurrentGlContext, PossiblyCurrentContext},
+    display::{Display, GlDisplay},
+    surface::{PbufferSurface, Surface, SurfaceAttributesBuilder},
+};
+use std::{
+    ffi::CString,
+    num::NonZeroU32,
+    rc::{Rc, Weak},
+    sync::mpsc::{self, Receiver, Sender, TryRecvError},
+};
+
+/// A context, that shares its objects with the main context of the server, a display that
+/// is used to load GL functions for it on the upload thread and a config, that is used to create
+/// an off-screen surface for the context (if needed).
+pub(crate) struct SharedContext {
+    pub(crate) context: NotCurrentContext,
+    pub(crate) display: Display,
+    pub(crate) config: Config,
+}
+
+// The display is used only to load function pointers on the upload thread, which is allowed by
+// every platform that supports shared contexts.
+unsafe impl Send for SharedContext {}
+
+struct UploadRequest {
+    id: u64,
+    source: Box<dyn TextureSource>,
+}
+
+struct UploadResponse {
+    id: u64,
+    result: Result<(glow::Texture, GpuTextureDescriptor<'static>), String>,
+}
+
+pub struct GlTextureUploader {
+    server: Weak<GlGraphicsServer>,
+    request_sender: Sender<UploadRequest>,
+    response_receiver: Receiver<UploadResponse>,
+}
+
+impl GlTextureUploader {
+    pub(crate) fn new(server: &GlGraphicsServer, shared_context: SharedContext) -> Self {
+        let (request_sender, request_receiver) = mpsc::channel();
+        let (response_sender, response_receiver) = mpsc::channel();
+
+        std::thread::Builder::new()
+            .name("TextureUploader".to_string())
+            .spawn(move || run(shared_context, request_receiver, response_sender))
+            .expect("Unable to spawn texture upload thread!");
+
+        Self {
+            server: server.weak(),
+            request_sender,
+            response_receiver,
+        }
+    }
+}
+
+impl TextureUploader for GlTextureUploader {
+    fn request(&self, id: u64, source: Box<dyn TextureSource>) -> Result<(), FrameworkError> {
+        self.request_sender
+            .send(UploadRequest { id, source })
+            .map_err(|_| {
+                FrameworkError::Custom("Texture upload thread is not running!".to_string())
+            })
+    }
+
+    fn poll(&self) -> Result<Vec<FinishedUpload>, FrameworkError> {
+        let server = self
+            .server
+            .upgrade()
+            .ok_or(FrameworkError::GraphicsServerUnavailable)?;
+
+        let mut finished = Vec::new();
+        loop {
+            match self.response_receiver.try_recv() {
+                Ok(response) => finished.push(FinishedUpload {
+                    id: response.id,
+                    result: response
+                        .result
+                        .map(|(texture, desc)| {
+                            GpuTexture(Rc::new(GlTexture::from_shared(&server, texture, desc)))
+                        })
+                        .map_err(FrameworkError::Custom),
+                }),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    // Pass the finished uploads first, the error will be reported on the next
+                    // call.
+                    if finished.is_empty() {
+                        return Err(FrameworkError::Custom(
+                            "Texture upload thread is not running!".to_string(),
+                        ));
+                    }
+                    break;
+                }
+            }
+        }
+        Ok(finished)
+    }
+}
+
+/// Makes the context current on the calling thread without a window. EGL contexts could be made
+/// current without any surface, every other kind of context needs a tiny off-screen (pbuffer)
+/// surface, that must be kept alive while the context is current.
+fn make_current(
+    context: NotCurrentContext,
+    display: &Display,
+    config: &Config,
+) -> glutin::error::Result<(PossiblyCurrentContext, Option<Surface<PbufferSurface>>)> {
+    match context {
+        #[cfg(all(any(windows, unix), not(target_vendor = "apple")))]
+        NotCurrentContext::Egl(context) => context
+            .make_current_surfaceless()
+            .map(|context| (PossiblyCurrentContext::Egl(context), None)),
+        #[allow(unreachable_patterns)]
+        context => {
+            let size = NonZeroU32::new(1).unwrap();
+            let attributes = SurfaceAttributesBuilder::<PbufferSurface>::new().build(size, size);
+            let surface = unsafe { display.create_pbuffer_surface(config, &attributes)? };
+            let context = context.make_current(&surface)?;
+            Ok((context, Some(surface)))
+        }
+    }
+}
+
+fn run(
+    shared_context: SharedContext,
+    request_receiver: Receiver<UploadRequest>,
+    response_sender: Sender<UploadResponse>,
+) {
+    let SharedContext {
+        context,
+        display,
+        config,
+    } = shared_context;
+
+    // The context (and its surface) must stay current for the entire lifetime of the thread. If
+    // the thread exits, the uploader reports an error and every in-flight request is re-uploaded
+    // on the render thread.
+    let _context = match make_current(context, &display, &config) {
+        Ok(context) => context,
+        Err(err) => {
+            Log::err(format!(
+                "Unable to make texture upload context current. Textures will be uploaded on \
+                the render thread. Reason: {err:?}"
+            ));
+            return;
+        }
+    };
+
+    let gl = unsafe {
+        glow::Context::from_loader_function(|s| display.get_proc_address(&CString::new(s).unwrap()))
+    };
+
+    for request in request_receiver.iter() {
+        let mut result = None;
+        request
+            .source
+            .read(&mut |desc| result = Some(unsafe { upload(&gl, desc) }));
+
+        // Make sure that every command is executed before passing the texture to the render
+        // thread, otherwise it could see incomplete data.
+        unsafe { gl.finish() };
+
+        let result = result
+            .unwrap_or_else(|| {
+                Err(FrameworkError::Custom(
+                    "Texture data is not available!".into(),
+                ))
+            })
+            .map_err(|err| format!("{err:?}"));
+
+        if response_sender
+            .send(UploadResponse {
+                id: request.id,
+                result,
+            })
+            .is_err()
+        {
+            // The uploader was destroyed.
+            break;
+        }
+    }
+}
+
+unsafe fn upload(
+    gl: &glow::Context,
+    desc: GpuTextureDescriptor,
+) -> Result<(glow::Texture, GpuTextureDescriptor<'static>), FrameworkError> {
+    let mip_count = desc.mip_count.max(1);
+
+    validate_data_size(desc.kind, desc.pixel_kind, mip_count, desc.data)?;
+
+    let texture = gl.create_texture()?;
+    let target = desc.kind.gl_texture_target();
+    gl.bind_texture(target, Some(texture));
+    gl.tex_parameter_i32(
+        target,
+        glow::TEXTURE_MAX_LEVEL,
+        mip_count.saturating_sub(1) as i32,
+    );
+    let result = upload_mips(gl, desc.kind, desc.pixel_kind, mip_count, None, desc.data);
+    gl.bind_texture(target, None);
+
+    if let Err(err) = result {
+        gl.delete_texture(texture);
+        return Err(err);
+    }
+
+    Ok((
+        texture,
+        GpuTextureDescriptor {
+            kind: desc.kind,
+            pixel_kind: desc.pixel_kind,
+            min_filter: desc.min_filter,
+            mag_filter: desc.mag_filter,
+            mip_count: desc.mip_count,
+            s_wrap_mode: desc.s_wrap_mode,
+            t_wrap_mode: desc.t_wrap_mode,
+            r_wrap_mode: desc.r_wrap_mode,
+            anisotropy: desc.anisotropy,
+            data: None,
+            base_level: desc.base_level,
+            max_level: desc.max_level,
+            min_lod: desc.min_lod,
+            max_lod: desc.max_lod,
+            lod_bias: desc.lod_bias,
+            compare_func: desc.compare_func,
+            depth_stencil_mode: desc.depth_stencil_mode,
+            swizzle: desc.swizzle,
+        },
+    ))
+}
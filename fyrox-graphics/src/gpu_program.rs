@@ -89,6 +89,35 @@ pub enum SamplerKind {
     USampler2D,
     USampler3D,
     USamplerCube,
+    /// A 2D depth texture sampler with depth comparison. The texture must have a comparison
+    /// function set (see [`crate::gpu_texture::GpuTextureTrait::set_compare_func`]).
+    Sampler2DShadow,
+    /// A cube depth texture sampler with depth comparison. The texture must have a comparison
+    /// function set (see [`crate::gpu_texture::GpuTextureTrait::set_compare_func`]).
+    SamplerCubeShadow,
+}
+
+impl SamplerKind {
+    /// Returns `true` if the sampler fetches unsigned integer values.
+    pub fn is_integer(self) -> bool {
+        matches!(
+            self,
+            Self::USampler1D | Self::USampler2D | Self::USampler3D | Self::USamplerCube
+        )
+    }
+
+    /// Returns `true` if the sampler performs depth comparison.
+    pub fn is_shadow(self) -> bool {
+        matches!(self, Self::Sampler2DShadow | Self::SamplerCubeShadow)
+    }
+
+    /// Returns `true` if the sampler fetches data from a cube map.
+    pub fn is_cube(self) -> bool {
+        matches!(
+            self,
+            Self::SamplerCube | Self::USamplerCube | Self::SamplerCubeShadow
+        )
+    }
 }
 
 /// Shader property with default value.
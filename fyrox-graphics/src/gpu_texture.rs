@@ -27,11 +27,13 @@ use crate::{
     core::{color::Color, Downcast},
     define_shared_wrapper,
     error::FrameworkError,
+    pixel_conversion::{convert_pixels, PixelConversionOptions, SwizzleSource},
+    CompareFunc,
 };
 use bytemuck::Pod;
 
 /// A kind of GPU texture.
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug)]
 pub enum GpuTextureKind {
     /// 1D texture.
     Line {
@@ -139,6 +141,7 @@ pub enum PixelKind {
 }
 
 /// Element kind of pixel.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum PixelElementKind {
     /// Floating-point pixel.
     Float,
@@ -188,90 +191,180 @@ impl PixelKind {
 
     /// Returns `true` if the pixel kind is compressed, `false` - otherwise.
     pub fn is_compressed(self) -> bool {
-        match self {
-            Self::DXT1RGB
-            | Self::DXT1RGBA
-            | Self::DXT3RGBA
-            | Self::DXT5RGBA
-            | Self::R8RGTC
-            | Self::RG8RGTC => true,
-            // Explicit match for rest of formats instead of _ will help to not forget
-            // to add new entry here.
-            Self::RGBA16
-            | Self::RGBA16F
-            | Self::RGB16
-            | Self::RGB16F
-            | Self::RGBA8
-            | Self::SRGBA8
-            | Self::RGB8
-            | Self::SRGB8
-            | Self::BGRA8
-            | Self::BGR8
-            | Self::RG16
-            | Self::R16
-            | Self::D24S8
-            | Self::D32F
-            | Self::R32F
-            | Self::R32UI
-            | Self::RG8
-            | Self::D16
-            | Self::R16F
-            | Self::R8
-            | Self::R8UI
-            | Self::RGB32F
-            | Self::RGBA32F
-            | Self::R11G11B10F
-            | Self::RGB10A2
-            | Self::L8
-            | Self::LA8
-            | Self::L16
-            | Self::LA16 => false,
-        }
+        self.descriptor().is_compressed()
+    }
+
+    /// Returns `true` if the pixel kind stores integer values (not normalized). Such textures
+    /// must be sampled using integer samplers (`usampler2D`, etc.) and do not support filtering.
+    pub fn is_integer(self) -> bool {
+        matches!(
+            self.element_kind(),
+            PixelElementKind::Integer | PixelElementKind::UnsignedInteger
+        )
+    }
+
+    /// Returns `true` if the pixel kind stores depth values.
+    pub fn is_depth(self) -> bool {
+        self.descriptor().is_depth
     }
 
     /// Returns element kind of the pixel.
     pub fn element_kind(self) -> PixelElementKind {
+        self.descriptor().element_kind
+    }
+
+    /// Returns the descriptor of the pixel kind, that contains memory layout of the pixel kind and
+    /// its other properties. See [`PixelDescriptor`] docs for more info.
+    pub fn descriptor(self) -> PixelDescriptor {
+        use PixelElementKind::{Float, NormalizedUnsignedInteger as Unorm, UnsignedInteger};
+
         match self {
-            Self::R32F
-            | Self::R16F
-            | Self::RGB32F
-            | Self::RGBA32F
-            | Self::RGBA16F
-            | Self::RGB16F
-            | Self::D32F
-            | Self::R11G11B10F => PixelElementKind::Float,
-            Self::D16
-            | Self::D24S8
-            | Self::RGBA8
-            | Self::SRGBA8
-            | Self::RGB8
-            | Self::SRGB8
-            | Self::BGRA8
-            | Self::BGR8
-            | Self::RG8
-            | Self::RG16
-            | Self::R8
-            | Self::R16
-            | Self::RGB16
-            | Self::RGBA16
-            | Self::DXT1RGB
-            | Self::DXT1RGBA
-            | Self::DXT3RGBA
-            | Self::DXT5RGBA
-            | Self::R8RGTC
-            | Self::RG8RGTC
-            | Self::RGB10A2
-            | Self::LA8
-            | Self::L8
-            | Self::LA16
-            | Self::L16 => PixelElementKind::NormalizedUnsignedInteger,
-            Self::R8UI | Self::R32UI => PixelElementKind::UnsignedInteger,
+            Self::R32F => PixelDescriptor::uncompressed(4, 1, Float),
+            Self::R32UI => PixelDescriptor::uncompressed(4, 1, UnsignedInteger),
+            Self::R16F => PixelDescriptor::uncompressed(2, 1, Float),
+            Self::D32F => PixelDescriptor::depth(4, 1, Float),
+            Self::D16 => PixelDescriptor::depth(2, 1, Unorm),
+            Self::D24S8 => PixelDescriptor::depth(4, 2, Unorm),
+            Self::RGBA8 | Self::BGRA8 => PixelDescriptor::uncompressed(4, 4, Unorm),
+            Self::SRGBA8 => PixelDescriptor::srgb(4, 4),
+            Self::RGB8 | Self::BGR8 => PixelDescriptor::uncompressed(3, 3, Unorm),
+            Self::SRGB8 => PixelDescriptor::srgb(3, 3),
+            Self::RG8 | Self::LA8 => PixelDescriptor::uncompressed(2, 2, Unorm),
+            Self::RG16 | Self::LA16 => PixelDescriptor::uncompressed(4, 2, Unorm),
+            Self::R8 | Self::L8 => PixelDescriptor::uncompressed(1, 1, Unorm),
+            Self::R16 | Self::L16 => PixelDescriptor::uncompressed(2, 1, Unorm),
+            Self::R8UI => PixelDescriptor::uncompressed(1, 1, UnsignedInteger),
+            Self::RGB16 => PixelDescriptor::uncompressed(6, 3, Unorm),
+            Self::RGBA16 => PixelDescriptor::uncompressed(8, 4, Unorm),
+            Self::DXT1RGB => PixelDescriptor::compressed(8, 3),
+            Self::DXT1RGBA => PixelDescriptor::compressed(8, 4),
+            Self::DXT3RGBA | Self::DXT5RGBA => PixelDescriptor::compressed(16, 4),
+            Self::R8RGTC => PixelDescriptor::compressed(8, 1),
+            Self::RG8RGTC => PixelDescriptor::compressed(16, 2),
+            Self::RGB32F => PixelDescriptor::uncompressed(12, 3, Float),
+            Self::RGBA32F => PixelDescriptor::uncompressed(16, 4, Float),
+            Self::RGB16F => PixelDescriptor::uncompressed(6, 3, Float),
+            Self::RGBA16F => PixelDescriptor::uncompressed(8, 4, Float),
+            Self::R11G11B10F => PixelDescriptor::uncompressed(4, 3, Float),
+            Self::RGB10A2 => PixelDescriptor::uncompressed(4, 4, Unorm),
         }
     }
 }
 
-fn ceil_div_4(x: usize) -> usize {
-    (x + 3) / 4
+/// Memory layout and other properties of a [`PixelKind`]. Pixels are stored in blocks, for
+/// uncompressed pixel kinds a block is just a single pixel, compressed pixel kinds store 4x4
+/// pixels in a single block. Slices of volume textures are compressed separately.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PixelDescriptor {
+    /// Size of a block in bytes.
+    pub bytes_per_block: usize,
+    /// Width of a block in pixels.
+    pub block_width: usize,
+    /// Height of a block in pixels.
+    pub block_height: usize,
+    /// Depth of a block in pixels. It is used only for volume textures and it is always 1 for the
+    /// supported pixel kinds.
+    pub block_depth: usize,
+    /// Amount of channels of the pixel kind. Stencil is considered as a separate channel.
+    pub channel_count: usize,
+    /// `true` if the pixel kind stores depth values.
+    pub is_depth: bool,
+    /// `true` if the pixel kind stores colors in sRGB color space.
+    pub is_srgb: bool,
+    /// Element kind of every channel of the pixel kind.
+    pub element_kind: PixelElementKind,
+}
+
+impl PixelDescriptor {
+    const fn uncompressed(
+        bytes_per_pixel: usize,
+        channel_count: usize,
+        element_kind: PixelElementKind,
+    ) -> Self {
+        Self {
+            bytes_per_block: bytes_per_pixel,
+            block_width: 1,
+            block_height: 1,
+            block_depth: 1,
+            channel_count,
+            is_depth: false,
+            is_srgb: false,
+            element_kind,
+        }
+    }
+
+    const fn depth(
+        bytes_per_pixel: usize,
+        channel_count: usize,
+        element_kind: PixelElementKind,
+    ) -> Self {
+        Self {
+            is_depth: true,
+            ..Self::uncompressed(bytes_per_pixel, channel_count, element_kind)
+        }
+    }
+
+    const fn srgb(bytes_per_pixel: usize, channel_count: usize) -> Self {
+        Self {
+            is_srgb: true,
+            ..Self::uncompressed(
+                bytes_per_pixel,
+                channel_count,
+                PixelElementKind::NormalizedUnsignedInteger,
+            )
+        }
+    }
+
+    const fn compressed(bytes_per_block: usize, channel_count: usize) -> Self {
+        Self {
+            bytes_per_block,
+            block_width: 4,
+            block_height: 4,
+            block_depth: 1,
+            channel_count,
+            is_depth: false,
+            is_srgb: false,
+            element_kind: PixelElementKind::NormalizedUnsignedInteger,
+        }
+    }
+
+    /// Returns `true` if the pixel kind is compressed (stores more than one pixel per block),
+    /// `false` - otherwise.
+    pub fn is_compressed(&self) -> bool {
+        self.block_width > 1 || self.block_height > 1
+    }
+
+    /// Calculates size in bytes of an image of the given size. Partially filled blocks at the
+    /// edges of the image are counted as full blocks.
+    pub fn image_size_bytes(&self, width: usize, height: usize, depth: usize) -> usize {
+        width.div_ceil(self.block_width)
+            * height.div_ceil(self.block_height)
+            * depth.div_ceil(self.block_depth)
+            * self.bytes_per_block
+    }
+
+    /// Calculates size in bytes of an image of the given size, which rows are `row_pitch` bytes
+    /// apart instead of being tightly packed. The padding after the last row is not required to be
+    /// present. Returns `None` if the pixel kind is compressed or if the row pitch is less than the
+    /// size of a tightly packed row.
+    pub fn padded_image_size_bytes(
+        &self,
+        width: usize,
+        height: usize,
+        depth: usize,
+        row_pitch: usize,
+    ) -> Option<usize> {
+        let row_size = width * self.bytes_per_block;
+        if self.is_compressed() || row_pitch < row_size {
+            return None;
+        }
+        let row_count = height * depth;
+        Some(if row_count == 0 {
+            0
+        } else {
+            row_pitch * (row_count - 1) + row_size
+        })
+    }
 }
 
 /// Calculates size in bytes of a volume texture using the given size of the texture and its pixel
@@ -282,117 +375,21 @@ pub fn image_3d_size_bytes(
     height: usize,
     depth: usize,
 ) -> usize {
-    let pixel_count = width * height * depth;
-    match pixel_kind {
-        PixelKind::RGBA32F => 16 * pixel_count,
-        PixelKind::RGB32F => 12 * pixel_count,
-        PixelKind::RGBA16 | PixelKind::RGBA16F => 8 * pixel_count,
-        PixelKind::RGB16 | PixelKind::RGB16F => 6 * pixel_count,
-        PixelKind::RGBA8
-        | PixelKind::SRGBA8
-        | PixelKind::BGRA8
-        | PixelKind::RG16
-        | PixelKind::LA16
-        | PixelKind::D24S8
-        | PixelKind::D32F
-        | PixelKind::R32F
-        | PixelKind::R32UI
-        | PixelKind::R11G11B10F
-        | PixelKind::RGB10A2 => 4 * pixel_count,
-        PixelKind::RGB8 | PixelKind::SRGB8 | PixelKind::BGR8 => 3 * pixel_count,
-        PixelKind::RG8
-        | PixelKind::LA8
-        | PixelKind::R16
-        | PixelKind::L16
-        | PixelKind::D16
-        | PixelKind::R16F => 2 * pixel_count,
-        PixelKind::R8 | PixelKind::L8 | PixelKind::R8UI => pixel_count,
-        PixelKind::DXT1RGB | PixelKind::DXT1RGBA | PixelKind::R8RGTC => {
-            let block_size = 8;
-            ceil_div_4(width) * ceil_div_4(height) * ceil_div_4(depth) * block_size
-        }
-        PixelKind::DXT3RGBA | PixelKind::DXT5RGBA | PixelKind::RG8RGTC => {
-            let block_size = 16;
-            ceil_div_4(width) * ceil_div_4(height) * ceil_div_4(depth) * block_size
-        }
-    }
+    pixel_kind
+        .descriptor()
+        .image_size_bytes(width, height, depth)
 }
 
 /// Calculates size in bytes of a rectangular texture using the given size of the texture and its pixel
 /// kind.
 pub fn image_2d_size_bytes(pixel_kind: PixelKind, width: usize, height: usize) -> usize {
-    let pixel_count = width * height;
-    match pixel_kind {
-        PixelKind::RGBA32F => 16 * pixel_count,
-        PixelKind::RGB32F => 12 * pixel_count,
-        PixelKind::RGBA16 | PixelKind::RGBA16F => 8 * pixel_count,
-        PixelKind::RGB16 | PixelKind::RGB16F => 6 * pixel_count,
-        PixelKind::RGBA8
-        | PixelKind::SRGBA8
-        | PixelKind::BGRA8
-        | PixelKind::RG16
-        | PixelKind::LA16
-        | PixelKind::D24S8
-        | PixelKind::D32F
-        | PixelKind::R32F
-        | PixelKind::R32UI
-        | PixelKind::R11G11B10F
-        | PixelKind::RGB10A2 => 4 * pixel_count,
-        PixelKind::RGB8 | PixelKind::SRGB8 | PixelKind::BGR8 => 3 * pixel_count,
-        PixelKind::RG8
-        | PixelKind::LA8
-        | PixelKind::R16
-        | PixelKind::L16
-        | PixelKind::D16
-        | PixelKind::R16F => 2 * pixel_count,
-        PixelKind::R8 | PixelKind::L8 | PixelKind::R8UI => pixel_count,
-        PixelKind::DXT1RGB | PixelKind::DXT1RGBA | PixelKind::R8RGTC => {
-            let block_size = 8;
-            ceil_div_4(width) * ceil_div_4(height) * block_size
-        }
-        PixelKind::DXT3RGBA | PixelKind::DXT5RGBA | PixelKind::RG8RGTC => {
-            let block_size = 16;
-            ceil_div_4(width) * ceil_div_4(height) * block_size
-        }
-    }
+    pixel_kind.descriptor().image_size_bytes(width, height, 1)
 }
 
 /// Calculates size in bytes of a linear texture using the given size of the texture and its pixel
 /// kind.
 pub fn image_1d_size_bytes(pixel_kind: PixelKind, length: usize) -> usize {
-    match pixel_kind {
-        PixelKind::RGBA32F => 16 * length,
-        PixelKind::RGB32F => 12 * length,
-        PixelKind::RGBA16 | PixelKind::RGBA16F => 8 * length,
-        PixelKind::RGB16 | PixelKind::RGB16F => 6 * length,
-        PixelKind::RGBA8
-        | PixelKind::SRGBA8
-        | PixelKind::BGRA8
-        | PixelKind::RG16
-        | PixelKind::LA16
-        | PixelKind::D24S8
-        | PixelKind::D32F
-        | PixelKind::R32F
-        | PixelKind::R32UI
-        | PixelKind::R11G11B10F
-        | PixelKind::RGB10A2 => 4 * length,
-        PixelKind::RGB8 | PixelKind::SRGB8 | PixelKind::BGR8 => 3 * length,
-        PixelKind::RG8
-        | PixelKind::LA8
-        | PixelKind::L16
-        | PixelKind::R16
-        | PixelKind::D16
-        | PixelKind::R16F => 2 * length,
-        PixelKind::R8 | PixelKind::L8 | PixelKind::R8UI => length,
-        PixelKind::DXT1RGB | PixelKind::DXT1RGBA | PixelKind::R8RGTC => {
-            let block_size = 8;
-            ceil_div_4(length) * block_size
-        }
-        PixelKind::DXT3RGBA | PixelKind::DXT5RGBA | PixelKind::RG8RGTC => {
-            let block_size = 16;
-            ceil_div_4(length) * block_size
-        }
-    }
+    pixel_kind.descriptor().image_size_bytes(length, 1, 1)
 }
 
 /// The texture magnification function is used when the pixel being textured maps to an area
@@ -474,6 +471,102 @@ pub enum Coordinate {
     R,
 }
 
+/// Defines which component of a depth-stencil texture is read when the texture is sampled.
+#[derive(Default, Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum DepthStencilTextureMode {
+    /// Depth component is read. The texture must be sampled using a floating-point sampler
+    /// (`sampler2D`) or a shadow sampler (`sampler2DShadow`).
+    #[default]
+    Depth,
+    /// Stencil component is read. The texture must be sampled using an unsigned integer sampler
+    /// (`usampler2D`). Filtering is not supported in this mode.
+    Stencil,
+}
+
+/// Defines which channel of a texel is returned in each of the RGBA channels when a texture is
+/// sampled. Swizzling is applied on top of the channel layout of the pixel kind of the texture.
+/// For example, [`PixelKind::L8`] is sampled as `(L, L, L, 1)`, so [`SwizzleSource::Alpha`] refers
+/// to the constant one for such textures. Swizzling allows to use legacy formats and single-channel
+/// masks with standard shaders, without any extra shader variants.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct TextureSwizzle {
+    /// Source of the red channel.
+    pub red: SwizzleSource,
+    /// Source of the green channel.
+    pub green: SwizzleSource,
+    /// Source of the blue channel.
+    pub blue: SwizzleSource,
+    /// Source of the alpha channel.
+    pub alpha: SwizzleSource,
+}
+
+impl Default for TextureSwizzle {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl TextureSwizzle {
+    /// Every channel is returned as is.
+    pub const IDENTITY: Self = Self::new(
+        SwizzleSource::Red,
+        SwizzleSource::Green,
+        SwizzleSource::Blue,
+        SwizzleSource::Alpha,
+    );
+
+    /// The red channel is replicated into RGB, alpha is one (`RRR1`). It is useful to show
+    /// single-channel textures as grayscale images.
+    pub const GRAYSCALE: Self = Self::new(
+        SwizzleSource::Red,
+        SwizzleSource::Red,
+        SwizzleSource::Red,
+        SwizzleSource::One,
+    );
+
+    /// The red channel goes to alpha, RGB are ones (`111R`). It is useful for single-channel
+    /// masks (for example, glyph atlases) that are used as opacity.
+    pub const ALPHA_MASK: Self = Self::new(
+        SwizzleSource::One,
+        SwizzleSource::One,
+        SwizzleSource::One,
+        SwizzleSource::Red,
+    );
+
+    /// Red and blue channels are swapped (`BGRA`).
+    pub const SWAP_RED_BLUE: Self = Self::new(
+        SwizzleSource::Blue,
+        SwizzleSource::Green,
+        SwizzleSource::Red,
+        SwizzleSource::Alpha,
+    );
+
+    /// Creates a new swizzle from the given sources of RGBA channels.
+    pub const fn new(
+        red: SwizzleSource,
+        green: SwizzleSource,
+        blue: SwizzleSource,
+        alpha: SwizzleSource,
+    ) -> Self {
+        Self {
+            red,
+            green,
+            blue,
+            alpha,
+        }
+    }
+
+    /// Returns the sources of RGBA channels as an array.
+    pub fn to_array(self) -> [SwizzleSource; 4] {
+        [self.red, self.green, self.blue, self.alpha]
+    }
+
+    /// Returns `true` if the swizzle does not change anything.
+    pub fn is_identity(&self) -> bool {
+        *self == Self::IDENTITY
+    }
+}
+
 /// Face of a cube map.
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub enum CubeMapFace {
@@ -536,6 +629,18 @@ pub struct GpuTextureDescriptor<'a> {
     /// `−bias_max..bias_max`, where `bias_max` is the value that can be fetched from the current
     /// graphics server. The initial value is 0.0.
     pub lod_bias: f32,
+    /// Optional depth comparison function. If set, the texture must have a depth pixel kind and
+    /// it must be sampled using a shadow sampler (`sampler2DShadow`, `samplerCubeShadow`), which
+    /// returns the result of comparison of a reference value against the stored depth. Comparison
+    /// results of neighbouring texels are filtered when linear filtration is used, which gives
+    /// hardware percentage-closer filtering (PCF) of shadows.
+    pub compare_func: Option<CompareFunc>,
+    /// Defines which component of a depth-stencil texture is read when the texture is sampled.
+    /// Ignored for any other pixel kind. See [`DepthStencilTextureMode`] docs for more info.
+    pub depth_stencil_mode: DepthStencilTextureMode,
+    /// Channel swizzle of the texture. See [`TextureSwizzle`] docs for more info. Non-identity
+    /// swizzle requires [`crate::server::ServerCapabilities::texture_swizzle`] support.
+    pub swizzle: TextureSwizzle,
 }
 
 impl Default for GpuTextureDescriptor<'_> {
@@ -561,6 +666,9 @@ impl Default for GpuTextureDescriptor<'_> {
             min_lod: -1000.0,
             max_lod: 1000.0,
             lod_bias: 0.0,
+            compare_func: None,
+            depth_stencil_mode: DepthStencilTextureMode::Depth,
+            swizzle: TextureSwizzle::IDENTITY,
         }
     }
 }
@@ -651,11 +759,53 @@ pub trait GpuTextureTrait: Downcast {
         data: Option<&[u8]>,
     ) -> Result<(), FrameworkError>;
 
+    /// Same as [`Self::set_data`], but the rows of the data are `row_pitch` bytes apart instead of
+    /// being tightly packed. It allows to upload data that comes from video decoders, screen
+    /// capture, etc. without repacking it first. Only the main mip level is uploaded and compressed
+    /// pixel kinds are not supported. Faces of cube maps and slices of volume textures must follow
+    /// one after another, each taking `row_pitch * height` bytes. The padding after the very last
+    /// row is optional. See [`PixelDescriptor::padded_image_size_bytes`] for more info.
+    fn set_data_with_row_pitch(
+        &self,
+        kind: GpuTextureKind,
+        pixel_kind: PixelKind,
+        row_pitch: usize,
+        data: &[u8],
+    ) -> Result<(), FrameworkError>;
+
+    /// Replaces a rectangular region of the main mip level of a rectangle texture with the given
+    /// data. The data must contain tightly packed pixels of the region only (`width * height`
+    /// pixels). Compressed textures are not supported. This method is much faster than
+    /// [`Self::set_data`] when only a small portion of a large texture needs to be updated.
+    fn set_region_data(
+        &self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+        data: &[u8],
+    ) -> Result<(), FrameworkError>;
+
+    /// Same as [`Self::set_region_data`], but the rows of the data are `row_pitch` bytes apart
+    /// instead of being tightly packed.
+    fn set_region_data_with_row_pitch(
+        &self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+        row_pitch: usize,
+        data: &[u8],
+    ) -> Result<(), FrameworkError>;
+
     /// Reads the texture data at the given mip level. This method could block current thread until
-    /// the data comes from GPU to CPU side.
+    /// the data comes from GPU to CPU side. The data is returned as is, in the format defined by
+    /// the pixel kind of the texture; see `get_image_converted` if you need
+    /// the data in a specific format.
     fn get_image(&self, level: usize) -> Vec<u8>;
 
-    /// Reads texture pixels.
+    /// Reads texture pixels. See `read_pixels_converted` if you need the
+    /// pixels in a specific format.
     fn read_pixels(&self) -> Vec<u8>;
 
     /// Returns kind of the texture.
@@ -704,6 +854,38 @@ pub trait GpuTextureTrait: Downcast {
     /// Returns a fixed bias value that is to be added to the level-of-detail parameter for the
     /// texture before texture sampling. See [`Self::set_lod_bias`] for more info.
     fn lod_bias(&self) -> f32;
+
+    /// Generates the full mip chain of the texture on GPU using its main level (level 0). Base and
+    /// max levels of the texture will be set to cover all the generated mips. Filtering is done by
+    /// the GPU driver, it is not specified whether textures in sRGB color space are filtered in
+    /// linear space or not. Compressed textures are not supported, this method returns an error
+    /// for them.
+    fn generate_mipmaps(&self) -> Result<(), FrameworkError>;
+
+    /// Sets depth comparison function of the texture. See [`GpuTextureDescriptor::compare_func`]
+    /// docs for more info. Returns an error if the texture does not have a depth pixel kind.
+    fn set_compare_func(&self, compare_func: Option<CompareFunc>) -> Result<(), FrameworkError>;
+
+    /// Returns current depth comparison function of the texture.
+    fn compare_func(&self) -> Option<CompareFunc>;
+
+    /// Defines which component of a depth-stencil texture is read when the texture is sampled.
+    /// Filtration of the texture is switched to the nearest one when stencil component is read.
+    /// Returns an error if the texture is not a depth-stencil texture or stencil texturing is not
+    /// supported by the graphics server (see [`crate::server::ServerCapabilities::stencil_texturing`]).
+    fn set_depth_stencil_mode(&self, mode: DepthStencilTextureMode) -> Result<(), FrameworkError>;
+
+    /// Returns the component of a depth-stencil texture, that is read when the texture is sampled.
+    fn depth_stencil_mode(&self) -> DepthStencilTextureMode;
+
+    /// Sets channel swizzle of the texture. The swizzle is kept when the data of the texture is
+    /// changed, even if the pixel kind changes. Returns an error if the swizzle is not an identity
+    /// and texture swizzling is not supported by the graphics server (see
+    /// [`crate::server::ServerCapabilities::texture_swizzle`]).
+    fn set_swizzle(&self, swizzle: TextureSwizzle) -> Result<(), FrameworkError>;
+
+    /// Returns current channel swizzle of the texture.
+    fn swizzle(&self) -> TextureSwizzle;
 }
 
 impl dyn GpuTextureTrait {
@@ -724,6 +906,25 @@ impl dyn GpuTextureTrait {
         typed
     }
 
+    /// Reads the pixels at the given mip level and converts them using the given options. See
+    /// [`PixelConversionOptions`] docs for more info.
+    pub fn get_image_converted(
+        &self,
+        level: usize,
+        options: &PixelConversionOptions,
+    ) -> Result<Vec<u8>, FrameworkError> {
+        convert_pixels(self.pixel_kind(), &self.get_image(level), options)
+    }
+
+    /// Reads the pixels and converts them using the given options. See [`PixelConversionOptions`]
+    /// docs for more info.
+    pub fn read_pixels_converted(
+        &self,
+        options: &PixelConversionOptions,
+    ) -> Result<Vec<u8>, FrameworkError> {
+        convert_pixels(self.pixel_kind(), &self.read_pixels(), options)
+    }
+
     /// Reads the pixels and reinterprets them using the given type.
     pub fn read_pixels_of_type<T>(&self) -> Vec<T>
     where
@@ -743,3 +944,99 @@ impl dyn GpuTextureTrait {
 }
 
 define_shared_wrapper!(GpuTexture<dyn GpuTextureTrait>);
+
+#[cfg(test)]
+mod test {
+    use super::{image_1d_size_bytes, image_2d_size_bytes, image_3d_size_bytes, PixelKind};
+
+    // Pixel kinds with the size of a pixel (or a block of 4x4 pixels for compressed kinds) in
+    // bytes.
+    const PIXEL_KINDS: [(PixelKind, usize); 35] = [
+        (PixelKind::R32F, 4),
+        (PixelKind::R32UI, 4),
+        (PixelKind::R16F, 2),
+        (PixelKind::D32F, 4),
+        (PixelKind::D16, 2),
+        (PixelKind::D24S8, 4),
+        (PixelKind::RGBA8, 4),
+        (PixelKind::SRGBA8, 4),
+        (PixelKind::RGB8, 3),
+        (PixelKind::SRGB8, 3),
+        (PixelKind::BGRA8, 4),
+        (PixelKind::BGR8, 3),
+        (PixelKind::RG8, 2),
+        (PixelKind::LA8, 2),
+        (PixelKind::LA16, 4),
+        (PixelKind::RG16, 4),
+        (PixelKind::R8, 1),
+        (PixelKind::L8, 1),
+        (PixelKind::L16, 2),
+        (PixelKind::R8UI, 1),
+        (PixelKind::R16, 2),
+        (PixelKind::RGB16, 6),
+        (PixelKind::RGBA16, 8),
+        (PixelKind::DXT1RGB, 8),
+        (PixelKind::DXT1RGBA, 8),
+        (PixelKind::DXT3RGBA, 16),
+        (PixelKind::DXT5RGBA, 16),
+        (PixelKind::RGB32F, 12),
+        (PixelKind::RGBA32F, 16),
+        (PixelKind::RGB16F, 6),
+        (PixelKind::RGBA16F, 8),
+        (PixelKind::R8RGTC, 8),
+        (PixelKind::RG8RGTC, 16),
+        (PixelKind::R11G11B10F, 4),
+        (PixelKind::RGB10A2, 4),
+    ];
+
+    #[test]
+    fn test_image_size_bytes() {
+        for (pixel_kind, size) in PIXEL_KINDS {
+            if pixel_kind.descriptor().is_compressed() {
+                // Partially filled blocks are counted as full blocks, every slice of a volume
+                // texture is compressed separately.
+                assert_eq!(
+                    image_1d_size_bytes(pixel_kind, 5),
+                    2 * size,
+                    "{pixel_kind:?}"
+                );
+                assert_eq!(
+                    image_2d_size_bytes(pixel_kind, 8, 8),
+                    4 * size,
+                    "{pixel_kind:?}"
+                );
+                assert_eq!(
+                    image_2d_size_bytes(pixel_kind, 5, 3),
+                    2 * size,
+                    "{pixel_kind:?}"
+                );
+                assert_eq!(
+                    image_3d_size_bytes(pixel_kind, 5, 3, 2),
+                    4 * size,
+                    "{pixel_kind:?}"
+                );
+            } else {
+                assert_eq!(
+                    image_1d_size_bytes(pixel_kind, 5),
+                    5 * size,
+                    "{pixel_kind:?}"
+                );
+                assert_eq!(
+                    image_2d_size_bytes(pixel_kind, 8, 8),
+                    64 * size,
+                    "{pixel_kind:?}"
+                );
+                assert_eq!(
+                    image_2d_size_bytes(pixel_kind, 5, 3),
+                    15 * size,
+                    "{pixel_kind:?}"
+                );
+                assert_eq!(
+                    image_3d_size_bytes(pixel_kind, 5, 3, 2),
+                    30 * size,
+                    "{pixel_kind:?}"
+                );
+            }
+        }
+    }
+}
@@ -34,11 +34,16 @@ pub mod geometry_buffer;
 pub mod gl;
 pub mod gpu_program;
 pub mod gpu_texture;
+pub mod pipeline;
+pub mod pixel_conversion;
 pub mod query;
 pub mod read_buffer;
+pub mod ring_buffer;
+pub mod sampler;
 pub mod server;
 pub mod stats;
 pub mod uniform;
+pub mod upload;
 
 #[macro_export]
 macro_rules! define_shared_wrapper {
@@ -482,7 +487,7 @@ pub enum CullFace {
 }
 
 /// Blending parameters (such as blending function and its equation).
-#[derive(Serialize, Deserialize, Default, Visit, Debug, PartialEq, Clone, Eq, Reflect)]
+#[derive(Serialize, Deserialize, Default, Visit, Debug, PartialEq, Clone, Eq, Hash, Reflect)]
 pub struct BlendParameters {
     /// Blending function, see [`BlendFunc`] for more info.
     pub func: BlendFunc,
@@ -491,7 +496,9 @@ pub struct BlendParameters {
 }
 
 /// A rectangular area that defines which pixels will be rendered in a frame buffer or not.
-#[derive(Serialize, Deserialize, Default, Visit, Debug, PartialEq, Clone, Copy, Eq, Reflect)]
+#[derive(
+    Serialize, Deserialize, Default, Visit, Debug, PartialEq, Clone, Copy, Eq, Hash, Reflect,
+)]
 pub struct ScissorBox {
     /// X coordinate of the box's origin.
     pub x: i32,
@@ -505,7 +512,7 @@ pub struct ScissorBox {
 
 /// A set of drawing parameters, that are used during draw call. It defines pretty much all pipeline
 /// settings all at once.
-#[derive(Serialize, Deserialize, Visit, Debug, PartialEq, Clone, Eq, Reflect)]
+#[derive(Serialize, Deserialize, Visit, Debug, PartialEq, Clone, Eq, Hash, Reflect)]
 pub struct DrawParameters {
     /// An optional cull face. If [`None`], then the culling is disabled.
     pub cull_face: Option<CullFace>,
@@ -523,6 +530,15 @@ pub struct DrawParameters {
     pub stencil_op: StencilOp,
     /// Optional scissor box. If [`None`], then the scissor test is disabled.
     pub scissor_box: Option<ScissorBox>,
+    /// Per-attachment blending options for frame buffers with multiple color attachments. If
+    /// empty, [`Self::blend`] is used for every color attachment. Otherwise, i-th element defines
+    /// blending options of i-th color attachment ([`None`] disables blending for it) and the
+    /// attachments past the end of the list use [`Self::blend`]. Requires indexed blending
+    /// support (see [`crate::server::ServerCapabilities::indexed_blending`]), if it is not
+    /// supported, [`Self::blend`] is used for every attachment.
+    #[serde(default)]
+    #[visit(optional)]
+    pub attachment_blend: Vec<Option<BlendParameters>>,
 }
 
 impl Default for DrawParameters {
@@ -536,6 +552,7 @@ impl Default for DrawParameters {
             blend: None,
             stencil_op: Default::default(),
             scissor_box: None,
+            attachment_blend: Default::default(),
         }
     }
 }
@@ -0,0 +1,81 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Pipeline state object (PSO) is an immutable set of all the pipeline settings (blending, depth
+//! and stencil tests, culling, etc.) combined with a GPU program. See [`GpuPipelineStateTrait`]
+//! docs for more info.
+
+#![warn(missing_docs)]
+
+use crate::{core::Downcast, define_shared_wrapper, gpu_program::GpuProgram, DrawParameters};
+
+/// Pipeline state object is an immutable GPU object, that holds a GPU program and the drawing
+/// parameters that will be used together with it. Pipeline states are created by
+/// [`crate::server::GraphicsServer::create_pipeline_state`] and they're cached by the server:
+/// requesting a pipeline state for the same program and drawing parameters returns the existing
+/// object. This allows the server to skip all the pipeline state changes when consecutive draw
+/// calls use the same pipeline state, instead of re-checking every piece of the state per draw
+/// call. To get the most out of it, sort the draw calls by their pipeline states.
+///
+/// ## Examples
+///
+/// ```rust
+/// use fyrox_graphics::{
+///     error::FrameworkError,
+///     framebuffer::{GpuFrameBuffer, ResourceBindGroup},
+///     geometry_buffer::GpuGeometryBuffer,
+///     gpu_program::GpuProgram,
+///     server::GraphicsServer,
+///     core::math::Rect,
+///     DrawParameters, ElementRange,
+/// };
+///
+/// fn draw(
+///     server: &dyn GraphicsServer,
+///     framebuffer: &GpuFrameBuffer,
+///     geometry: &GpuGeometryBuffer,
+///     program: &GpuProgram,
+/// ) -> Result<(), FrameworkError> {
+///     let pipeline_state = server.create_pipeline_state(program, &DrawParameters::default())?;
+///
+///     framebuffer.draw_with_pipeline_state(
+///         1,
+///         geometry,
+///         Rect::new(0, 0, 100, 100),
+///         &pipeline_state,
+///         &[ResourceBindGroup { bindings: &[] }],
+///         ElementRange::Full,
+///     )?;
+///
+///     Ok(())
+/// }
+/// ```
+pub trait GpuPipelineStateTrait: Downcast {
+    /// Returns a unique identifier of the pipeline state. Two pipeline states with the same
+    /// identifier are guaranteed to be identical.
+    fn id(&self) -> u64;
+
+    /// Returns the GPU program of the pipeline state.
+    fn program(&self) -> &GpuProgram;
+
+    /// Returns the drawing parameters of the pipeline state.
+    fn draw_params(&self) -> &DrawParameters;
+}
+
+define_shared_wrapper!(GpuPipelineState<dyn GpuPipelineStateTrait>);
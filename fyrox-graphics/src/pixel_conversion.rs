@@ -0,0 +1,463 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Pixel conversion is a set of CPU-side routines that convert the raw pixels of a texture (for
+//! example, the ones read back from GPU) into a well-known format. It allows consumers (screenshots,
+//! previews, bakers, etc.) to not reimplement the decoding of every [`PixelKind`].
+
+#![warn(missing_docs)]
+
+use crate::{
+    error::FrameworkError,
+    gpu_texture::{PixelElementKind, PixelKind},
+};
+
+/// Color space of pixel data.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ColorSpace {
+    /// Linear color space, the values are proportional to the light intensity.
+    Linear,
+    /// sRGB color space, the values are gamma-encoded.
+    Srgb,
+}
+
+/// Tone mapping operator that is used to map high dynamic range values into `0.0..=1.0` range.
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub enum ToneMapping {
+    /// No tone mapping, the values are clamped when converted into a normalized format.
+    #[default]
+    None,
+    /// Reinhard operator (`x / (1 + x)`) with the given exposure.
+    Reinhard {
+        /// A multiplier that is applied to the values before tone mapping.
+        exposure: f32,
+    },
+    /// A fit of ACES filmic curve with the given exposure.
+    Aces {
+        /// A multiplier that is applied to the values before tone mapping.
+        exposure: f32,
+    },
+}
+
+/// A source of a channel of the converted pixel.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SwizzleSource {
+    /// Red channel of the source pixel.
+    Red,
+    /// Green channel of the source pixel.
+    Green,
+    /// Blue channel of the source pixel.
+    Blue,
+    /// Alpha channel of the source pixel.
+    Alpha,
+    /// Constant zero.
+    Zero,
+    /// Constant one.
+    One,
+}
+
+/// Pixel format of the converted data.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub enum ConvertedPixelKind {
+    /// Red, Green, Blue, Alpha; all by 8-bit (normalized).
+    #[default]
+    RGBA8,
+    /// Red, Green, Blue, Alpha; all by 32-bit floating point.
+    RGBA32F,
+}
+
+impl ConvertedPixelKind {
+    /// Returns the size of a single pixel in bytes.
+    pub fn size_bytes(self) -> usize {
+        match self {
+            Self::RGBA8 => 4,
+            Self::RGBA32F => 16,
+        }
+    }
+}
+
+/// A set of options for pixel conversion. Every source pixel is decoded into four floating-point
+/// channels (single-channel pixels are expanded the same way as GPU does: `(r, 0, 0, 1)`, luminance
+/// pixels are replicated into RGB) and then passes through the following steps:
+///
+/// 1) Tone mapping (see [`ToneMapping`]), it is done in linear color space.
+/// 2) Color space conversion (see [`Self::color_space`]).
+/// 3) Channel swizzling (see [`Self::swizzle`]).
+/// 4) Encoding into the output format (see [`ConvertedPixelKind`]).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PixelConversionOptions {
+    /// Desired color space of the output pixels. The color space of the source pixels is defined by
+    /// [`PixelKind::color_space`]. `None` means that the values will be kept in the color space
+    /// they are (the source color space or linear, if tone mapping is used).
+    pub color_space: Option<ColorSpace>,
+    /// See [`ToneMapping`] docs for more info.
+    pub tone_mapping: ToneMapping,
+    /// Defines which channel of the source pixel goes into RGBA channels of the output pixel.
+    pub swizzle: [SwizzleSource; 4],
+    /// See [`ConvertedPixelKind`] docs for more info.
+    pub output: ConvertedPixelKind,
+}
+
+impl Default for PixelConversionOptions {
+    fn default() -> Self {
+        Self {
+            color_space: None,
+            tone_mapping: ToneMapping::None,
+            swizzle: [
+                SwizzleSource::Red,
+                SwizzleSource::Green,
+                SwizzleSource::Blue,
+                SwizzleSource::Alpha,
+            ],
+            output: ConvertedPixelKind::RGBA8,
+        }
+    }
+}
+
+impl PixelConversionOptions {
+    /// Creates a set of options, that produces 8-bit sRGB pixels suitable for saving into common
+    /// image files. High dynamic range pixels are tone mapped using Reinhard operator.
+    pub fn srgb8(source: PixelKind) -> Self {
+        Self {
+            color_space: Some(ColorSpace::Srgb),
+            tone_mapping: if matches!(source.element_kind(), PixelElementKind::Float) {
+                ToneMapping::Reinhard { exposure: 1.0 }
+            } else {
+                ToneMapping::None
+            },
+            ..Default::default()
+        }
+    }
+
+    /// Sets the desired output color space.
+    pub fn with_color_space(mut self, color_space: Option<ColorSpace>) -> Self {
+        self.color_space = color_space;
+        self
+    }
+
+    /// Sets the tone mapping operator.
+    pub fn with_tone_mapping(mut self, tone_mapping: ToneMapping) -> Self {
+        self.tone_mapping = tone_mapping;
+        self
+    }
+
+    /// Sets the channel swizzle.
+    pub fn with_swizzle(mut self, swizzle: [SwizzleSource; 4]) -> Self {
+        self.swizzle = swizzle;
+        self
+    }
+
+    /// Sets the output pixel format.
+    pub fn with_output(mut self, output: ConvertedPixelKind) -> Self {
+        self.output = output;
+        self
+    }
+}
+
+impl PixelKind {
+    /// Returns color space of the pixel kind. Only explicit sRGB pixel kinds are considered to be in
+    /// sRGB color space, everything else is treated as linear.
+    pub fn color_space(self) -> ColorSpace {
+        if self.descriptor().is_srgb {
+            ColorSpace::Srgb
+        } else {
+            ColorSpace::Linear
+        }
+    }
+}
+
+/// Converts a linear value into sRGB color space.
+pub fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Converts a value in sRGB color space into linear color space.
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn f16_to_f32(bits: u16) -> f32 {
+    let sign = ((bits as u32) & 0x8000) << 16;
+    let exponent = ((bits >> 10) & 0x1F) as u32;
+    let mantissa = (bits & 0x3FF) as u32;
+    match exponent {
+        0 => {
+            let value = mantissa as f32 * (1.0 / (1 << 24) as f32);
+            if sign != 0 {
+                -value
+            } else {
+                value
+            }
+        }
+        0x1F => f32::from_bits(sign | 0x7F80_0000 | (mantissa << 13)),
+        _ => f32::from_bits(sign | ((exponent + 112) << 23) | (mantissa << 13)),
+    }
+}
+
+// Unsigned floats of packed formats (R11G11B10F) with 5-bit exponent and no sign.
+fn unsigned_small_float_to_f32(bits: u32, mantissa_bits: u32) -> f32 {
+    let exponent = (bits >> mantissa_bits) & 0x1F;
+    let mantissa = bits & ((1 << mantissa_bits) - 1);
+    let fraction = mantissa as f32 / (1 << mantissa_bits) as f32;
+    match exponent {
+        0 => fraction * 2.0f32.powi(-14),
+        0x1F => {
+            if mantissa == 0 {
+                f32::INFINITY
+            } else {
+                f32::NAN
+            }
+        }
+        _ => (1.0 + fraction) * 2.0f32.powi(exponent as i32 - 15),
+    }
+}
+
+fn read_u16(bytes: &[u8], index: usize) -> u16 {
+    u16::from_ne_bytes([bytes[2 * index], bytes[2 * index + 1]])
+}
+
+fn read_u32(bytes: &[u8], index: usize) -> u32 {
+    let i = 4 * index;
+    u32::from_ne_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]])
+}
+
+fn decode_pixel(pixel_kind: PixelKind, p: &[u8]) -> [f32; 4] {
+    let unorm8 = |i: usize| p[i] as f32 / 255.0;
+    let unorm16 = |i: usize| read_u16(p, i) as f32 / 65535.0;
+    let half = |i: usize| f16_to_f32(read_u16(p, i));
+    let float = |i: usize| f32::from_bits(read_u32(p, i));
+
+    match pixel_kind {
+        PixelKind::R32F | PixelKind::D32F => [float(0), 0.0, 0.0, 1.0],
+        PixelKind::R32UI => [read_u32(p, 0) as f32, 0.0, 0.0, 1.0],
+        PixelKind::R16F => [half(0), 0.0, 0.0, 1.0],
+        PixelKind::D16 | PixelKind::R16 => [unorm16(0), 0.0, 0.0, 1.0],
+        PixelKind::D24S8 => {
+            let packed = read_u32(p, 0);
+            [
+                (packed >> 8) as f32 / 16_777_215.0,
+                (packed & 0xFF) as f32,
+                0.0,
+                1.0,
+            ]
+        }
+        PixelKind::RGBA8 | PixelKind::SRGBA8 => [unorm8(0), unorm8(1), unorm8(2), unorm8(3)],
+        PixelKind::RGB8 | PixelKind::SRGB8 => [unorm8(0), unorm8(1), unorm8(2), 1.0],
+        PixelKind::BGRA8 => [unorm8(2), unorm8(1), unorm8(0), unorm8(3)],
+        PixelKind::BGR8 => [unorm8(2), unorm8(1), unorm8(0), 1.0],
+        PixelKind::RG8 => [unorm8(0), unorm8(1), 0.0, 1.0],
+        PixelKind::LA8 => [unorm8(0), unorm8(0), unorm8(0), unorm8(1)],
+        PixelKind::LA16 => [unorm16(0), unorm16(0), unorm16(0), unorm16(1)],
+        PixelKind::RG16 => [unorm16(0), unorm16(1), 0.0, 1.0],
+        PixelKind::R8 => [unorm8(0), 0.0, 0.0, 1.0],
+        PixelKind::L8 => [unorm8(0), unorm8(0), unorm8(0), 1.0],
+        PixelKind::L16 => [unorm16(0), unorm16(0), unorm16(0), 1.0],
+        PixelKind::R8UI => [p[0] as f32, 0.0, 0.0, 1.0],
+        PixelKind::RGB16 => [unorm16(0), unorm16(1), unorm16(2), 1.0],
+        PixelKind::RGBA16 => [unorm16(0), unorm16(1), unorm16(2), unorm16(3)],
+        PixelKind::RGB32F => [float(0), float(1), float(2), 1.0],
+        PixelKind::RGBA32F => [float(0), float(1), float(2), float(3)],
+        PixelKind::RGB16F => [half(0), half(1), half(2), 1.0],
+        PixelKind::RGBA16F => [half(0), half(1), half(2), half(3)],
+        PixelKind::R11G11B10F => {
+            let packed = read_u32(p, 0);
+            [
+                unsigned_small_float_to_f32(packed & 0x7FF, 6),
+                unsigned_small_float_to_f32((packed >> 11) & 0x7FF, 6),
+                unsigned_small_float_to_f32(packed >> 22, 5),
+                1.0,
+            ]
+        }
+        PixelKind::RGB10A2 => {
+            let packed = read_u32(p, 0);
+            [
+                (packed & 0x3FF) as f32 / 1023.0,
+                ((packed >> 10) & 0x3FF) as f32 / 1023.0,
+                ((packed >> 20) & 0x3FF) as f32 / 1023.0,
+                (packed >> 30) as f32 / 3.0,
+            ]
+        }
+        PixelKind::DXT1RGB
+        | PixelKind::DXT1RGBA
+        | PixelKind::DXT3RGBA
+        | PixelKind::DXT5RGBA
+        | PixelKind::R8RGTC
+        | PixelKind::RG8RGTC => unreachable!("compressed pixels must be rejected earlier"),
+    }
+}
+
+fn tone_map(value: f32, tone_mapping: ToneMapping) -> f32 {
+    match tone_mapping {
+        ToneMapping::None => value,
+        ToneMapping::Reinhard { exposure } => {
+            let value = value * exposure;
+            value / (1.0 + value)
+        }
+        ToneMapping::Aces { exposure } => {
+            let value = value * exposure;
+            (value * (2.51 * value + 0.03)) / (value * (2.43 * value + 0.59) + 0.14)
+        }
+    }
+}
+
+/// Converts the given raw pixels of the given kind using the given conversion options. The pixels
+/// must be tightly packed (no row padding), which is the layout returned by
+/// [`crate::gpu_texture::GpuTextureTrait::get_image`]. Compressed pixel kinds are not supported.
+pub fn convert_pixels(
+    pixel_kind: PixelKind,
+    data: &[u8],
+    options: &PixelConversionOptions,
+) -> Result<Vec<u8>, FrameworkError> {
+    if pixel_kind.is_compressed() {
+        return Err(FrameworkError::Custom(format!(
+            "Unable to convert pixels of {pixel_kind:?} kind. Compressed pixels are not supported!"
+        )));
+    }
+
+    let size = pixel_kind.descriptor().bytes_per_block;
+
+    let mut output = Vec::with_capacity(data.len() / size * options.output.size_bytes());
+    for pixel in data.chunks_exact(size) {
+        let mut color = decode_pixel(pixel_kind, pixel);
+        let mut color_space = pixel_kind.color_space();
+
+        if options.tone_mapping != ToneMapping::None {
+            for channel in color.iter_mut().take(3) {
+                if color_space == ColorSpace::Srgb {
+                    *channel = srgb_to_linear(*channel);
+                }
+                *channel = tone_map(*channel, options.tone_mapping);
+            }
+            color_space = ColorSpace::Linear;
+        }
+
+        match (color_space, options.color_space) {
+            (ColorSpace::Linear, Some(ColorSpace::Srgb)) => {
+                for channel in color.iter_mut().take(3) {
+                    *channel = linear_to_srgb(channel.max(0.0));
+                }
+            }
+            (ColorSpace::Srgb, Some(ColorSpace::Linear)) => {
+                for channel in color.iter_mut().take(3) {
+                    *channel = srgb_to_linear(*channel);
+                }
+            }
+            _ => (),
+        }
+
+        let color = options.swizzle.map(|source| match source {
+            SwizzleSource::Red => color[0],
+            SwizzleSource::Green => color[1],
+            SwizzleSource::Blue => color[2],
+            SwizzleSource::Alpha => color[3],
+            SwizzleSource::Zero => 0.0,
+            SwizzleSource::One => 1.0,
+        });
+
+        match options.output {
+            ConvertedPixelKind::RGBA8 => {
+                output.extend(color.map(|channel| (channel.clamp(0.0, 1.0) * 255.0).round() as u8));
+            }
+            ConvertedPixelKind::RGBA32F => {
+                for channel in color {
+                    output.extend_from_slice(&channel.to_ne_bytes());
+                }
+            }
+        }
+    }
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_half_float_decoding() {
+        assert_eq!(f16_to_f32(0x3C00), 1.0);
+        assert_eq!(f16_to_f32(0xC000), -2.0);
+        assert_eq!(f16_to_f32(0x0000), 0.0);
+        assert_eq!(f16_to_f32(0x7C00), f32::INFINITY);
+    }
+
+    #[test]
+    fn test_srgb_round_trip() {
+        let data = [0u8, 64, 128, 255];
+        let linear = convert_pixels(
+            PixelKind::SRGBA8,
+            &data,
+            &PixelConversionOptions::default()
+                .with_color_space(Some(ColorSpace::Linear))
+                .with_output(ConvertedPixelKind::RGBA32F),
+        )
+        .unwrap();
+        let srgb = convert_pixels(
+            PixelKind::RGBA32F,
+            &linear,
+            &PixelConversionOptions::default().with_color_space(Some(ColorSpace::Srgb)),
+        )
+        .unwrap();
+        assert_eq!(srgb, data);
+    }
+
+    #[test]
+    fn test_swizzle_and_bgra() {
+        let data = [10u8, 20, 30, 40];
+        let converted = convert_pixels(
+            PixelKind::BGRA8,
+            &data,
+            &PixelConversionOptions::default().with_swizzle([
+                SwizzleSource::Alpha,
+                SwizzleSource::Red,
+                SwizzleSource::Zero,
+                SwizzleSource::One,
+            ]),
+        )
+        .unwrap();
+        assert_eq!(converted, [40, 30, 0, 255]);
+    }
+
+    #[test]
+    fn test_tone_mapping() {
+        let data = 3.0f32
+            .to_ne_bytes()
+            .into_iter()
+            .chain(1.0f32.to_ne_bytes())
+            .chain(0.0f32.to_ne_bytes())
+            .chain(1.0f32.to_ne_bytes())
+            .collect::<Vec<_>>();
+        let converted = convert_pixels(
+            PixelKind::RGBA32F,
+            &data,
+            &PixelConversionOptions::default()
+                .with_tone_mapping(ToneMapping::Reinhard { exposure: 1.0 }),
+        )
+        .unwrap();
+        assert_eq!(converted, [191, 128, 0, 255]);
+    }
+}
@@ -5,7 +5,7 @@
 
 use crate::core::Downcast;
 use crate::define_shared_wrapper;
-use std::fmt::Debug;
+use std::{fmt::Debug, time::Duration};
 
 /// Kind of a GPU query.
 #[repr(u32)]
@@ -16,6 +16,10 @@ pub enum QueryKind {
 
     /// Queries a flag that defines whether the rendering operation produced any pixels or not.
     AnySamplesPassed = glow::ANY_SAMPLES_PASSED,
+
+    /// Queries the time spent by GPU to execute the enclosed commands. Requires timer queries
+    /// support, see [`crate::server::ServerCapabilities::timer_query`].
+    TimeElapsed = glow::TIME_ELAPSED,
 }
 
 /// Result of a query.
@@ -26,6 +30,9 @@ pub enum QueryResult {
 
     /// A flag that defines whether the rendering operation produced any pixels or not.
     AnySamplesPassed(bool),
+
+    /// Time spent by GPU to execute the enclosed commands.
+    TimeElapsed(Duration),
 }
 
 /// A query object is used to fetch some data from rendering operations asynchronously. Usually it
@@ -0,0 +1,100 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+#![warn(missing_docs)]
+
+//! Ring buffer is a GPU buffer, that is split into a number of segments that are used in
+//! round-robin fashion, one segment per frame. It is intended to be used for small portions of
+//! data that change every draw call (uniforms, per-instance data, etc.), instead of uploading the
+//! data to many small buffers. See [`GpuRingBufferTrait`] docs for more info.
+
+use crate::{buffer::GpuBuffer, core::Downcast, define_shared_wrapper};
+
+/// Ring buffer is a GPU buffer, that is split into `segment_count` segments of equal size. Only one
+/// segment is used for writing at a time, the rest of the segments could still be in use by the GPU
+/// for the previous frames. This allows the CPU to write the data of the current frame without
+/// waiting for the GPU to finish the previous frames. Usually, three segments are enough (triple
+/// buffering).
+///
+/// If the graphics server supports persistent mapping (see
+/// [`crate::server::ServerCapabilities::persistent_mapping`]), the buffer is mapped once and the
+/// data is copied directly to the GPU-visible memory without any driver calls. Otherwise, every
+/// write is a separate upload to a region of the same buffer, which is still much cheaper than
+/// juggling lots of small buffers.
+///
+/// ## Examples
+///
+/// ```rust
+/// use fyrox_graphics::{
+///     buffer::BufferKind,
+///     error::FrameworkError,
+///     framebuffer::{BufferDataUsage, ResourceBinding},
+///     server::GraphicsServer,
+/// };
+///
+/// fn ring_buffer(server: &dyn GraphicsServer) -> Result<(), FrameworkError> {
+///     let alignment = server.capabilities().uniform_buffer_offset_alignment;
+///     let ring_buffer = server.create_ring_buffer(BufferKind::Uniform, 65536, 3, alignment)?;
+///
+///     // Somewhere in the rendering loop.
+///     ring_buffer.next_segment();
+///
+///     let data = [0u8; 64];
+///     if let Some(offset) = ring_buffer.write(&data) {
+///         let _binding = ResourceBinding::Buffer {
+///             buffer: ring_buffer.buffer().clone(),
+///             binding: 0,
+///             data_usage: BufferDataUsage::UseSegment {
+///                 offset,
+///                 size: data.len(),
+///             },
+///         };
+///     }
+///
+///     Ok(())
+/// }
+/// ```
+pub trait GpuRingBufferTrait: Downcast {
+    /// Returns the underlying buffer, that should be used to bind the written data to a shader.
+    /// The buffer cannot be written directly, its [`crate::buffer::GpuBufferTrait::write_data`]
+    /// returns an error.
+    fn buffer(&self) -> &GpuBuffer;
+
+    /// Returns size of a single segment in bytes.
+    fn segment_size(&self) -> usize;
+
+    /// Returns total amount of segments.
+    fn segment_count(&self) -> usize;
+
+    /// Returns `true` if the buffer is persistently mapped to CPU memory.
+    fn is_persistently_mapped(&self) -> bool;
+
+    /// Writes the data to the current segment and returns an offset (in bytes, from the beginning
+    /// of the underlying buffer) of the written data. The offset is aligned with the alignment
+    /// specified at creation. Returns [`None`] if there's not enough space left in the current
+    /// segment.
+    fn write(&self, data: &[u8]) -> Option<usize>;
+
+    /// Finishes writing to the current segment and switches to the next one. If the next segment
+    /// is still in use by the GPU, this method blocks until the GPU finishes using it. Must be
+    /// called once per frame, before any writes.
+    fn next_segment(&self);
+}
+
+define_shared_wrapper!(GpuRingBuffer<dyn GpuRingBufferTrait>);
@@ -0,0 +1,89 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Sampler is a set of parameters that defines how a texture is sampled in a shader. Samplers are
+//! decoupled from textures, which means that the same texture could be sampled in different ways
+//! by binding different samplers with it.
+
+#![warn(missing_docs)]
+
+use crate::{
+    core::{color::Color, Downcast},
+    define_shared_wrapper,
+    gpu_texture::{MagnificationFilter, MinificationFilter, WrapMode},
+    CompareFunc,
+};
+use std::fmt::Debug;
+
+/// Sampler descriptor contains all the parameters of a sampler.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GpuSamplerDescriptor {
+    /// Minification filter of the sampler. See [`MinificationFilter`] docs for more info.
+    pub min_filter: MinificationFilter,
+    /// Magnification filter of the sampler. See [`MagnificationFilter`] docs for more info.
+    pub mag_filter: MagnificationFilter,
+    /// Wrap mode for S coordinate.
+    pub s_wrap_mode: WrapMode,
+    /// Wrap mode for T coordinate.
+    pub t_wrap_mode: WrapMode,
+    /// Wrap mode for R coordinate.
+    pub r_wrap_mode: WrapMode,
+    /// Anisotropy level of the sampler. It will be clamped to the maximum level supported by the
+    /// GPU.
+    pub anisotropy: f32,
+    /// Color, that will be used for [`WrapMode::ClampToBorder`].
+    pub border_color: Color,
+    /// Minimum level of detail that will be used for sampling.
+    pub min_lod: f32,
+    /// Maximum level of detail that will be used for sampling.
+    pub max_lod: f32,
+    /// Optional depth comparison function. When set, sampling a depth texture with this sampler
+    /// compares the reference value with the depth stored in the texture and returns the result
+    /// of the comparison (filtered, if linear filtration is used). This is what shadow samplers
+    /// (`sampler2DShadow`, etc.) expect and what makes hardware PCF work.
+    pub compare_func: Option<CompareFunc>,
+}
+
+impl Default for GpuSamplerDescriptor {
+    fn default() -> Self {
+        Self {
+            min_filter: Default::default(),
+            mag_filter: Default::default(),
+            s_wrap_mode: Default::default(),
+            t_wrap_mode: Default::default(),
+            r_wrap_mode: Default::default(),
+            anisotropy: 1.0,
+            border_color: Color::TRANSPARENT,
+            min_lod: -1000.0,
+            max_lod: 1000.0,
+            compare_func: None,
+        }
+    }
+}
+
+/// Sampler is an immutable GPU object, that holds sampling parameters. It can be bound together
+/// with a texture using [`crate::framebuffer::ResourceBinding::texture_with_sampler`], and its
+/// parameters will override the sampling parameters of the texture.
+pub trait GpuSamplerTrait: Downcast + Debug {
+    /// Returns the descriptor, that was used to create the sampler.
+    fn descriptor(&self) -> &GpuSamplerDescriptor;
+}
+
+define_shared_wrapper!(GpuSampler<dyn GpuSamplerTrait>);
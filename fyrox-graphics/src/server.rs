@@ -25,11 +25,14 @@
 
 use crate::geometry_buffer::GpuGeometryBuffer;
 use crate::gpu_program::GpuProgram;
+use crate::pipeline::GpuPipelineState;
 use crate::query::GpuQuery;
 use crate::read_buffer::GpuAsyncReadBuffer;
+use crate::ring_buffer::GpuRingBuffer;
+use crate::upload::TextureUploader;
 use crate::{
     buffer::{BufferKind, BufferUsage, GpuBuffer},
-    core::Downcast,
+    core::{reflect::prelude::*, type_traits::prelude::*, visitor::prelude::*, Downcast},
     error::FrameworkError,
     framebuffer::{Attachment, GpuFrameBuffer},
     geometry_buffer::GeometryBufferDescriptor,
@@ -38,10 +41,13 @@ use crate::{
         GpuTexture, GpuTextureDescriptor, GpuTextureKind, MagnificationFilter, MinificationFilter,
         PixelKind, WrapMode,
     },
+    sampler::{GpuSampler, GpuSamplerDescriptor},
     stats::PipelineStatistics,
-    PolygonFace, PolygonFillMode,
+    DrawParameters, PolygonFace, PolygonFillMode,
 };
+use serde::{Deserialize, Serialize};
 use std::rc::{Rc, Weak};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
 
 /// Graphics server capabilities.
 #[derive(Debug)]
@@ -53,6 +59,108 @@ pub struct ServerCapabilities {
     /// The maximum, absolute value of the texture level-of-detail bias. The value must be at least
     /// 2.0.
     pub max_lod_bias: f32,
+    /// Whether the stencil part of depth-stencil textures could be sampled in shaders (see
+    /// [`crate::gpu_texture::DepthStencilTextureMode::Stencil`]).
+    pub stencil_texturing: bool,
+    /// The maximum number of color attachments of a frame buffer.
+    pub max_color_attachments: usize,
+    /// The maximum number of color attachments that could be written in a single draw call.
+    pub max_draw_buffers: usize,
+    /// Whether the blending could be configured per each color attachment (see
+    /// [`crate::DrawParameters::attachment_blend`]).
+    pub indexed_blending: bool,
+    /// Whether the draw call parameters could be sourced from GPU buffers (see
+    /// [`crate::framebuffer::GpuFrameBufferTrait::draw_indirect`]).
+    pub indirect_draw: bool,
+    /// Whether the buffers could be persistently mapped to CPU memory (see
+    /// [`crate::ring_buffer::GpuRingBufferTrait`]).
+    pub persistent_mapping: bool,
+    /// Whether the channels of textures could be swizzled (see
+    /// [`crate::gpu_texture::TextureSwizzle`]).
+    pub texture_swizzle: bool,
+    /// Whether the time spent by GPU could be measured (see [`crate::query::QueryKind::TimeElapsed`]).
+    pub timer_query: bool,
+    /// Whether the textures with 16-bit floating point components (for example,
+    /// [`PixelKind::RGBA16F`]) could be used as render targets.
+    pub half_float_render_targets: bool,
+    /// Whether the textures with 32-bit (or packed) floating point components (for example,
+    /// [`PixelKind::R32F`] or [`PixelKind::R11G11B10F`]) could be used as render targets.
+    pub float_render_targets: bool,
+}
+
+impl ServerCapabilities {
+    /// Checks whether a texture with the given pixel kind could be used as a render target.
+    /// Compressed pixel kinds are never renderable.
+    pub fn is_render_target_supported(&self, pixel_kind: PixelKind) -> bool {
+        let descriptor = pixel_kind.descriptor();
+        if descriptor.is_compressed() {
+            return false;
+        }
+        match pixel_kind {
+            PixelKind::R16F | PixelKind::RGB16F | PixelKind::RGBA16F => {
+                self.half_float_render_targets
+            }
+            PixelKind::R32F | PixelKind::RGB32F | PixelKind::RGBA32F | PixelKind::R11G11B10F => {
+                self.float_render_targets
+            }
+            _ => true,
+        }
+    }
+}
+
+/// Defines how presented frames are synchronized with the refresh rate of the display.
+#[derive(
+    Copy,
+    Clone,
+    PartialOrd,
+    PartialEq,
+    Hash,
+    Debug,
+    Serialize,
+    Deserialize,
+    Visit,
+    Eq,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+    TypeUuidProvider,
+    Default,
+)]
+#[type_uuid(id = "0c4b6f1e-53a3-4b8e-9a55-7f3e0d9a8c21")]
+pub enum VSyncMode {
+    /// Frames are presented immediately, without waiting for the vertical blank. This mode gives
+    /// the lowest possible input latency at the cost of possible screen tearing.
+    Off,
+    /// Frames are presented on vertical blank only. There is no tearing, but the frame rate is
+    /// limited by the refresh rate of the display and the input latency is higher.
+    #[default]
+    On,
+    /// Frames are synchronized with the vertical blank when the frame rate is higher than the
+    /// refresh rate of the display, and presented immediately otherwise. Falls back to [`Self::On`]
+    /// if not supported by the graphics server.
+    Adaptive,
+}
+
+/// A set of options that controls how rendered frames are presented on screen.
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize, Visit, Reflect)]
+pub struct PresentSettings {
+    /// Vertical synchronization mode. See [`VSyncMode`] docs for more info.
+    pub vsync: VSyncMode,
+    /// The maximum amount of frames that the CPU is allowed to submit before waiting for the GPU
+    /// to finish the oldest one. Lower values reduce input latency, higher values improve
+    /// throughput. Must be at least 1.
+    #[reflect(min_value = 1.0, max_value = 8.0)]
+    pub max_frames_in_flight: usize,
+}
+
+impl Default for PresentSettings {
+    fn default() -> Self {
+        Self {
+            vsync: Default::default(),
+            max_frames_in_flight: 2,
+        }
+    }
 }
 
 /// A shared reference to a graphics server.
@@ -77,14 +185,36 @@ pub trait GraphicsServer: Downcast {
     /// Creates a new GPU texture using the given descriptor.
     fn create_texture(&self, desc: GpuTextureDescriptor) -> Result<GpuTexture, FrameworkError>;
 
+    /// Creates a new sampler using the given descriptor. Samplers are immutable, create a new one
+    /// if you need different sampling parameters.
+    fn create_sampler(&self, desc: GpuSamplerDescriptor) -> Result<GpuSampler, FrameworkError>;
+
     /// Creates a new frame buffer using the given depth and color attachments. Depth attachment
-    /// not exist, but there must be at least one color attachment of a format that supports rendering.
+    /// could not exist, but then there must be at least one color attachment of a format that
+    /// supports rendering. Color attachments could be omitted if there's a depth attachment, such
+    /// frame buffers are used for depth-only passes. The number of color attachments must not
+    /// exceed [`ServerCapabilities::max_color_attachments`] and
+    /// [`ServerCapabilities::max_draw_buffers`].
     fn create_frame_buffer(
         &self,
         depth_attachment: Option<Attachment>,
         color_attachments: Vec<Attachment>,
     ) -> Result<GpuFrameBuffer, FrameworkError>;
 
+    /// Creates a frame buffer with multisampled color (of the given pixel kind) and depth-stencil
+    /// buffers, that is used for multi-sample anti-aliasing (MSAA). Such frame buffer has no
+    /// attachments, because multisampled images cannot be sampled in shaders, its content must be
+    /// resolved into a regular frame buffer of the same size using
+    /// [`crate::framebuffer::GpuFrameBufferTrait::blit_to`]. The sample count is clamped to the
+    /// maximum supported by the graphics server.
+    fn create_multisampled_frame_buffer(
+        &self,
+        color_pixel_kind: PixelKind,
+        width: usize,
+        height: usize,
+        sample_count: usize,
+    ) -> Result<GpuFrameBuffer, FrameworkError>;
+
     /// Creates a frame buffer that "connected" to the final image that will be displayed to the
     /// screen.
     fn back_buffer(&self) -> GpuFrameBuffer;
@@ -93,6 +223,19 @@ pub trait GraphicsServer: Downcast {
     /// is used to create occlusion queries.
     fn create_query(&self) -> Result<GpuQuery, FrameworkError>;
 
+    /// Creates a new ring buffer of the given kind, that consists of `segment_count` segments of
+    /// `segment_size` bytes each. Every write to the ring buffer is aligned with the given
+    /// alignment (for example, uniform buffers must respect
+    /// [`ServerCapabilities::uniform_buffer_offset_alignment`]). See
+    /// [`crate::ring_buffer::GpuRingBufferTrait`] docs for more info.
+    fn create_ring_buffer(
+        &self,
+        kind: BufferKind,
+        segment_size: usize,
+        segment_count: usize,
+        alignment: usize,
+    ) -> Result<GpuRingBuffer, FrameworkError>;
+
     /// Creates a new named GPU program using a pair of vertex and fragment shaders. The name could
     /// be used for debugging purposes. The implementation of graphics server will generate proper
     /// resource bindings in the shader code for you.
@@ -104,6 +247,16 @@ pub trait GraphicsServer: Downcast {
         resources: &[ShaderResourceDefinition],
     ) -> Result<GpuProgram, FrameworkError>;
 
+    /// Creates a new pipeline state object for the given program and drawing parameters. Pipeline
+    /// states are cached, if there's an alive pipeline state with the same program and drawing
+    /// parameters, it will be returned instead. See [`crate::pipeline::GpuPipelineStateTrait`]
+    /// docs for more info.
+    fn create_pipeline_state(
+        &self,
+        program: &GpuProgram,
+        draw_params: &DrawParameters,
+    ) -> Result<GpuPipelineState, FrameworkError>;
+
     /// Creates a new read-back buffer, that can be used to obtain texture data from GPU. It can be
     /// used to read rendering result from GPU to CPU memory and save the result to disk.
     fn create_async_read_buffer(
@@ -142,6 +295,19 @@ pub trait GraphicsServer: Downcast {
     /// right amount, but it can't be less than two.
     fn swap_buffers(&self) -> Result<(), FrameworkError>;
 
+    /// Returns `true` if the underlying graphics context was lost (due to a driver reset, WebGL
+    /// context loss, etc.). Every GPU object of a lost context is invalid, and the only way to
+    /// recover is to create a new graphics server and re-upload everything to it.
+    fn is_context_lost(&self) -> bool;
+
+    /// Changes the way how rendered frames are presented on screen. Some graphics servers may not
+    /// support every option, in this case the closest supported option will be used. See
+    /// [`PresentSettings`] docs for more info.
+    fn set_present_settings(&self, settings: PresentSettings) -> Result<(), FrameworkError>;
+
+    /// Returns current present settings of the graphics server.
+    fn present_settings(&self) -> PresentSettings;
+
     /// Notifies the graphics server that the size of the back buffer has changed. It has very limited
     /// use and there are very few platforms (Linux with Wayland mostly) that needs this function to
     /// be called.
@@ -153,6 +319,26 @@ pub trait GraphicsServer: Downcast {
     /// Sets current polygon fill mode. See [`PolygonFace`] and [`PolygonFillMode`] docs for more info.
     fn set_polygon_fill_mode(&self, polygon_face: PolygonFace, polygon_fill_mode: PolygonFillMode);
 
+    /// Begins conditional rendering. Every draw call issued until [`Self::end_conditional_render`]
+    /// will be discarded if the given occlusion query has finished and reported that no samples
+    /// passed. If the result of the query is not yet available, the draw calls will be performed as
+    /// usual, so the rendering never stalls waiting for the GPU. The query must be started (see
+    /// [`crate::query::GpuQueryTrait::begin`]) and ended before this method is called, otherwise an
+    /// error is returned. Conditional rendering scopes cannot be nested.
+    fn begin_conditional_render(&self, query: &GpuQuery) -> Result<(), FrameworkError>;
+
+    /// Ends conditional rendering started by [`Self::begin_conditional_render`]. Does nothing if there
+    /// is no active conditional rendering scope.
+    fn end_conditional_render(&self);
+
+    /// Creates a texture uploader, that creates textures on a separate thread using a graphics
+    /// context that shares its objects with the context of the server. Returns `None` if the
+    /// server does not support shared contexts (WebGL for example) or if the uploader was already
+    /// created. See [`TextureUploader`] docs for more info.
+    fn create_texture_uploader(&self) -> Option<Box<dyn TextureUploader>> {
+        None
+    }
+
     /// A shortcut for [`Self::create_texture`], that creates a rectangular texture with the given
     /// size and pixel kind.
     fn create_2d_render_target(
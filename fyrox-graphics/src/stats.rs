@@ -36,6 +36,8 @@ pub struct PipelineStatistics {
     pub framebuffer_binding_changes: usize,
     /// Total amount of programs was used in the pipeline during the rendering.
     pub program_binding_changes: usize,
+    /// Total amount of pipeline state objects was applied to the pipeline during the rendering.
+    pub pipeline_state_changes: usize,
 }
 
 impl std::ops::AddAssign for PipelineStatistics {
@@ -46,6 +48,7 @@ impl std::ops::AddAssign for PipelineStatistics {
         self.blend_state_changes += rhs.blend_state_changes;
         self.framebuffer_binding_changes += rhs.framebuffer_binding_changes;
         self.program_binding_changes += rhs.program_binding_changes;
+        self.pipeline_state_changes += rhs.pipeline_state_changes;
     }
 }
 
@@ -61,6 +64,7 @@ impl std::ops::Sub for PipelineStatistics {
             framebuffer_binding_changes: self.framebuffer_binding_changes
                 - rhs.framebuffer_binding_changes,
             program_binding_changes: self.program_binding_changes - rhs.program_binding_changes,
+            pipeline_state_changes: self.pipeline_state_changes - rhs.pipeline_state_changes,
         }
     }
 }
@@ -75,13 +79,15 @@ impl Display for PipelineStatistics {
             \tVAO: {},\n\
             \tFBO: {},\n\
             \tShaders: {},\n\
-            \tBlend: {}",
+            \tBlend: {},\n\
+            \tPipeline States: {}",
             self.texture_binding_changes,
             self.vbo_binding_changes,
             self.vao_binding_changes,
             self.framebuffer_binding_changes,
             self.program_binding_changes,
-            self.blend_state_changes
+            self.blend_state_changes,
+            self.pipeline_state_changes
         )
     }
 }
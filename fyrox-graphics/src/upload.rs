@@ -0,0 +1,59 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Texture uploader transfers texture data to GPU memory on a separate thread, so big textures
+//! could be streamed in without stalling the thread that renders frames. See [`TextureUploader`]
+//! docs for more info.
+
+#![warn(missing_docs)]
+
+use crate::{error::FrameworkError, gpu_texture::GpuTexture, gpu_texture::GpuTextureDescriptor};
+
+/// A source of texture data. The data is read on the upload thread, which allows to avoid copying
+/// potentially huge amount of bytes on the render thread.
+pub trait TextureSource: Send {
+    /// Passes the descriptor of the texture (including its data) to the given function. The
+    /// function must not be called if the data is not available anymore.
+    fn read(&self, func: &mut dyn FnMut(GpuTextureDescriptor));
+}
+
+/// A finished upload request.
+pub struct FinishedUpload {
+    /// Unique identifier of the request, that was passed to [`TextureUploader::request`].
+    pub id: u64,
+    /// A ready-to-use GPU texture or an error, that occurred during the upload.
+    pub result: Result<GpuTexture, FrameworkError>,
+}
+
+/// Texture uploader creates GPU textures on a worker thread, that has its own graphics context,
+/// which shares its objects with the main one. It is created by
+/// [`crate::server::GraphicsServer::create_texture_uploader`] and not every graphics server is able
+/// to provide one.
+pub trait TextureUploader {
+    /// Schedules the upload of a texture from the given source. The result will be available in
+    /// [`Self::poll`] under the given id. Returns an error if the upload thread is not running
+    /// anymore, in this case the uploader should be discarded.
+    fn request(&self, id: u64, source: Box<dyn TextureSource>) -> Result<(), FrameworkError>;
+
+    /// Returns every upload request, that was finished since the last call. Returns an error if
+    /// the upload thread is not running anymore (or the graphics server was destroyed), in this
+    /// case the requests, that weren't finished, will never be finished and the uploader should
+    /// be discarded.
+    fn poll(&self) -> Result<Vec<FinishedUpload>, FrameworkError>;
+}
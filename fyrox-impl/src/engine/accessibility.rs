@@ -0,0 +1,53 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Accessibility settings of the engine. See [`AccessibilitySettings`] docs for more info.
+
+use crate::{core::reflect::prelude::*, renderer::ColorBlindnessSettings};
+use serde::{Deserialize, Serialize};
+
+/// Engine-level accessibility settings. These settings are meant to be exposed in the options menu
+/// of a game, they could be changed at any time using [`crate::engine::Engine::set_accessibility_settings`].
+/// The settings are serializable, so they could be stored alongside other game settings.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, Reflect)]
+pub struct AccessibilitySettings {
+    /// Color blindness filter settings, see [`ColorBlindnessSettings`] docs for more info.
+    #[serde(default)]
+    pub color_blindness: ColorBlindnessSettings,
+
+    /// Global scale of every user interface of the engine. Values greater than one make the user
+    /// interface larger, layouts are recalculated accordingly. See
+    /// [`crate::gui::UserInterface::set_scale`] for more info.
+    #[serde(default = "default_ui_scale")]
+    #[reflect(min_value = 0.5, max_value = 4.0)]
+    pub ui_scale: f32,
+}
+
+fn default_ui_scale() -> f32 {
+    1.0
+}
+
+impl Default for AccessibilitySettings {
+    fn default() -> Self {
+        Self {
+            color_blindness: Default::default(),
+            ui_scale: default_ui_scale(),
+        }
+    }
+}
@@ -0,0 +1,466 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Embedded play mode allows a host application (the editor, for example) to run plugins and
+//! scripts of a scene it owns in its own process and then to roll the plugins back to the state
+//! they had before the play. See [`Engine::start_embedded_play`] docs for more info.
+
+use crate::{
+    core::{log::Log, pool::Handle, visitor::prelude::*},
+    engine::{hotreload, Engine},
+    event::Event,
+    gui::UserInterface,
+    plugin::PluginContext,
+    scene::Scene,
+};
+use fxhash::FxHashSet;
+use std::{io::Cursor, path::Path, path::PathBuf};
+use winit::event_loop::EventLoopWindowTarget;
+
+pub(super) struct EmbeddedPlay {
+    scene: Handle<Scene>,
+    paused: bool,
+    plugin_states: Vec<Vec<u8>>,
+    scenes: FxHashSet<Handle<Scene>>,
+    user_interfaces: FxHashSet<Handle<UserInterface>>,
+    loading_scenes: FxHashSet<PathBuf>,
+}
+
+impl Engine {
+    /// Starts embedded play of the given scene. The method serializes the current state of every
+    /// plugin, initializes the plugins (the same way as [`crate::engine::executor::Executor`]
+    /// does with `--override-scene` argument) and registers the scene for script processing. If
+    /// a plugin requests loading of the scene at `scene_path`, the request is replaced with the
+    /// given scene, so the plugins will work with the scene owned by the host application. It is
+    /// up to the caller to make a copy of the scene before the play and to restore it after
+    /// [`Self::stop_embedded_play`] call.
+    ///
+    /// OS events are not passed to the plugins and scripts automatically, use
+    /// [`Self::handle_os_event_in_embedded_play`] to route the events that belong to the game.
+    pub fn start_embedded_play(
+        &mut self,
+        scene: Handle<Scene>,
+        scene_path: Option<&Path>,
+        window_target: Option<&EventLoopWindowTarget<()>>,
+    ) -> Result<(), String> {
+        if self.embedded_play.is_some() {
+            return Err("Embedded play is already started!".to_string());
+        }
+        if self.plugins_enabled {
+            return Err("Plugins are already enabled!".to_string());
+        }
+        if !self.scenes.is_valid_handle(scene) {
+            return Err(format!("Scene {scene} does not exist!"));
+        }
+
+        let mut plugin_states = Vec::with_capacity(self.plugins.len());
+        for plugin in self.plugins.iter_mut() {
+            let mut visitor = hotreload::make_writing_visitor();
+            plugin
+                .visit("Plugin", &mut visitor)
+                .map_err(|e| e.to_string())?;
+            let mut binary_blob = Cursor::new(Vec::<u8>::new());
+            visitor
+                .save_binary_to_memory(&mut binary_blob)
+                .map_err(|e| e.to_string())?;
+            plugin_states.push(binary_blob.into_inner());
+        }
+
+        self.embedded_play = Some(EmbeddedPlay {
+            scene,
+            paused: false,
+            plugin_states,
+            scenes: self.scenes.pair_iter().map(|(h, _)| h).collect(),
+            user_interfaces: self.user_interfaces.pair_iter().map(|(h, _)| h).collect(),
+            loading_scenes: self
+                .async_scene_loader
+                .loading_scenes
+                .keys()
+                .cloned()
+                .collect(),
+        });
+
+        if !self.has_scripted_scene(scene) {
+            self.register_scripted_scene(scene);
+        }
+
+        self.plugins_enabled = true;
+
+        let scene_path_str = scene_path.map(|path| path.to_string_lossy().to_string());
+        for plugin in self.plugins.iter_mut() {
+            plugin.init(
+                scene_path_str.as_deref(),
+                PluginContext {
+                    scenes: &mut self.scenes,
+                    resource_manager: &self.resource_manager,
+                    graphics_context: &mut self.graphics_context,
+                    dt: 0.0,
+                    lag: &mut 0.0,
+                    user_interfaces: &mut self.user_interfaces,
+                    serialization_context: &self.serialization_context,
+                    widget_constructors: &self.widget_constructors,
+                    performance_statistics: &self.performance_statistics,
+                    elapsed_time: self.elapsed_time,
+                    script_processor: &self.script_processor,
+                    async_scene_loader: &mut self.async_scene_loader,
+                    window_target,
+                    task_pool: &mut self.task_pool,
+                    simulation: &mut self.simulation,
+                    accessibility_settings: &mut self.accessibility_settings,
+                },
+            );
+        }
+
+        // Substitute the requested scene with the scene of the host. The result of the loading
+        // will be ignored, because there's no request for it anymore.
+        if let Some(scene_path) = scene_path {
+            if self
+                .async_scene_loader
+                .loading_scenes
+                .remove(scene_path)
+                .is_some()
+            {
+                let mut context = PluginContext {
+                    scenes: &mut self.scenes,
+                    resource_manager: &self.resource_manager,
+                    graphics_context: &mut self.graphics_context,
+                    dt: 0.0,
+                    lag: &mut 0.0,
+                    user_interfaces: &mut self.user_interfaces,
+                    serialization_context: &self.serialization_context,
+                    widget_constructors: &self.widget_constructors,
+                    performance_statistics: &self.performance_statistics,
+                    elapsed_time: self.elapsed_time,
+                    script_processor: &self.script_processor,
+                    async_scene_loader: &mut self.async_scene_loader,
+                    window_target,
+                    task_pool: &mut self.task_pool,
+                    simulation: &mut self.simulation,
+                    accessibility_settings: &mut self.accessibility_settings,
+                };
+
+                for plugin in self.plugins.iter_mut() {
+                    plugin.on_scene_loaded(scene_path, scene, &[], &mut context);
+                }
+            }
+        }
+
+        Log::info(format!("Embedded play of scene {scene} was started."));
+
+        Ok(())
+    }
+
+    /// Stops embedded play (if any), deinitializes the plugins and restores their state. Every
+    /// scene and user interface, that was created during the play, is removed. The scene of the
+    /// play is kept as is, see [`Self::start_embedded_play`] docs for more info. A plugin, whose
+    /// state cannot be restored, does not prevent restoring of other plugins, the method returns
+    /// an error after everything else is restored in this case.
+    pub fn stop_embedded_play(
+        &mut self,
+        window_target: Option<&EventLoopWindowTarget<()>>,
+    ) -> Result<(), String> {
+        let Some(embedded_play) = self.embedded_play.take() else {
+            return Ok(());
+        };
+
+        for plugin in self.plugins.iter_mut() {
+            plugin.on_deinit(PluginContext {
+                scenes: &mut self.scenes,
+                resource_manager: &self.resource_manager,
+                graphics_context: &mut self.graphics_context,
+                dt: 0.0,
+                lag: &mut 0.0,
+                user_interfaces: &mut self.user_interfaces,
+                serialization_context: &self.serialization_context,
+                widget_constructors: &self.widget_constructors,
+                performance_statistics: &self.performance_statistics,
+                elapsed_time: self.elapsed_time,
+                script_processor: &self.script_processor,
+                async_scene_loader: &mut self.async_scene_loader,
+                window_target,
+                task_pool: &mut self.task_pool,
+                simulation: &mut self.simulation,
+                accessibility_settings: &mut self.accessibility_settings,
+            });
+        }
+
+        self.plugins_enabled = false;
+
+        // Coroutines of the play could reference its state, that is about to be discarded.
+        self.task_pool.coroutines.retain(|coroutine| {
+            coroutine.scene_handle != embedded_play.scene
+                && embedded_play.scenes.contains(&coroutine.scene_handle)
+        });
+
+        self.script_processor
+            .scripted_scenes
+            .retain(|s| s.handle != embedded_play.scene);
+
+        let new_scenes = self
+            .scenes
+            .pair_iter()
+            .map(|(h, _)| h)
+            .filter(|h| !embedded_play.scenes.contains(h))
+            .collect::<Vec<_>>();
+        for scene in new_scenes {
+            self.scenes.remove(scene);
+        }
+
+        let new_user_interfaces = self
+            .user_interfaces
+            .pair_iter()
+            .map(|(h, _)| h)
+            .filter(|h| !embedded_play.user_interfaces.contains(h))
+            .collect::<Vec<_>>();
+        for ui in new_user_interfaces {
+            self.user_interfaces.remove(ui);
+        }
+
+        self.async_scene_loader
+            .loading_scenes
+            .retain(|path, _| embedded_play.loading_scenes.contains(path));
+
+        let mut failed_plugins = 0;
+        for (index, (plugin, state)) in self
+            .plugins
+            .iter_mut()
+            .zip(embedded_play.plugin_states.iter())
+            .enumerate()
+        {
+            let result = hotreload::make_reading_visitor(
+                state,
+                &self.serialization_context,
+                &self.resource_manager,
+                &self.widget_constructors,
+            )
+            .and_then(|mut visitor| plugin.visit("Plugin", &mut visitor));
+            if let Err(err) = result {
+                Log::err(format!(
+                    "Unable to restore the state of plugin {index} after embedded play. \
+                    Reason: {err}"
+                ));
+                failed_plugins += 1;
+            }
+        }
+
+        Log::info(format!(
+            "Embedded play of scene {} was stopped.",
+            embedded_play.scene
+        ));
+
+        if failed_plugins > 0 {
+            Err(format!(
+                "The state of {failed_plugins} plugin(s) could not be restored after embedded play."
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns `true` if the embedded play is active (paused or not), `false` - otherwise.
+    pub fn is_in_embedded_play(&self) -> bool {
+        self.embedded_play.is_some()
+    }
+
+    /// Returns `true` if the embedded play is active and paused, `false` - otherwise.
+    pub fn is_embedded_play_paused(&self) -> bool {
+        self.embedded_play.as_ref().is_some_and(|p| p.paused)
+    }
+
+    /// Pauses or resumes the embedded play. Paused play does not update plugins and scripts, it
+    /// is up to the caller to pause the scene itself (see
+    /// [`crate::scene::graph::GraphUpdateSwitches::paused`]).
+    pub fn set_embedded_play_paused(&mut self, paused: bool) {
+        if let Some(embedded_play) = self.embedded_play.as_mut() {
+            embedded_play.paused = paused;
+        }
+    }
+
+    /// Passes the given OS event to the plugins and to the scripts of the scene of the embedded
+    /// play. Does nothing if there's no embedded play or if it is paused.
+    pub fn handle_os_event_in_embedded_play(
+        &mut self,
+        event: &Event<()>,
+        dt: f32,
+        window_target: &EventLoopWindowTarget<()>,
+        lag: &mut f32,
+    ) {
+        let Some(embedded_play) = self.embedded_play.as_ref() else {
+            return;
+        };
+        if embedded_play.paused {
+            return;
+        }
+        let scene = embedded_play.scene;
+
+        self.handle_os_event_by_plugins(event, dt, window_target, lag);
+        self.handle_os_event_by_scripts(event, scene, dt);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::manager::ResourceManager,
+        core::{
+            algebra::Vector2, pool::Handle, reflect::prelude::*, task::TaskPool,
+            type_traits::prelude::*, uuid::Uuid, visitor::prelude::*,
+        },
+        engine::{Engine, EngineInitParams},
+        gui::UserInterface,
+        plugin::{Plugin, PluginContext},
+        scene::{node::Node, Scene},
+        script::{coroutine::CoroutineContext, ScriptTrait},
+    };
+    use std::sync::Arc;
+
+    #[derive(Default, Debug, Visit, Reflect)]
+    struct CounterPlugin {
+        counter: u32,
+    }
+
+    impl Plugin for CounterPlugin {
+        fn init(&mut self, _scene_path: Option<&str>, context: PluginContext) {
+            self.counter += 1;
+            context.scenes.add(Scene::new());
+            context
+                .user_interfaces
+                .add(UserInterface::new(Vector2::new(100.0, 100.0)));
+        }
+    }
+
+    // The state of the plugin cannot be restored.
+    #[derive(Default, Debug, Reflect)]
+    struct BrokenPlugin {}
+
+    impl Visit for BrokenPlugin {
+        fn visit(&mut self, _name: &str, visitor: &mut Visitor) -> VisitResult {
+            if visitor.is_reading() {
+                Err(VisitError::User("Broken".to_string()))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    impl Plugin for BrokenPlugin {}
+
+    #[derive(Debug, Clone, Default, Reflect, Visit, TypeUuidProvider, ComponentProvider)]
+    #[type_uuid(id = "b0a8e5b6-3e0e-4b7e-9a4f-5b7e4c6d1a2f")]
+    struct MyScript {}
+
+    impl ScriptTrait for MyScript {}
+
+    fn make_engine() -> Engine {
+        let task_pool = Arc::new(TaskPool::default());
+        Engine::new(EngineInitParams {
+            graphics_context_params: Default::default(),
+            serialization_context: Arc::new(Default::default()),
+            widget_constructors: Arc::new(Default::default()),
+            resource_manager: ResourceManager::new(task_pool.clone()),
+            task_pool,
+        })
+        .unwrap()
+    }
+
+    fn spawn_coroutine(engine: &mut Engine, scene: Handle<Scene>) {
+        engine.task_pool.spawn_script_coroutine(
+            scene,
+            Handle::<Node>::NONE,
+            Uuid::new_v4(),
+            |_ctx: CoroutineContext<MyScript>| async {},
+        );
+    }
+
+    fn coroutine_scenes(engine: &Engine) -> Vec<Handle<Scene>> {
+        engine
+            .task_pool
+            .coroutines
+            .iter()
+            .map(|coroutine| coroutine.scene_handle)
+            .collect()
+    }
+
+    #[test]
+    fn test_embedded_play_restores_state() {
+        let mut engine = make_engine();
+        engine.add_plugin(CounterPlugin::default());
+
+        let play_scene = engine.scenes.add(Scene::new());
+        let other_scene = engine.scenes.add(Scene::new());
+        spawn_coroutine(&mut engine, other_scene);
+
+        engine.start_embedded_play(play_scene, None, None).unwrap();
+        assert!(engine.is_in_embedded_play());
+        assert_eq!(
+            engine.plugins[0].cast::<CounterPlugin>().unwrap().counter,
+            1
+        );
+        assert_eq!(engine.scenes.pair_iter().count(), 3);
+        assert_eq!(engine.user_interfaces.pair_iter().count(), 2);
+
+        spawn_coroutine(&mut engine, play_scene);
+        let new_scene = engine
+            .scenes
+            .pair_iter()
+            .map(|(h, _)| h)
+            .find(|h| ![play_scene, other_scene].contains(h))
+            .unwrap();
+        spawn_coroutine(&mut engine, new_scene);
+
+        engine.stop_embedded_play(None).unwrap();
+        assert!(!engine.is_in_embedded_play());
+        assert_eq!(
+            engine.plugins[0].cast::<CounterPlugin>().unwrap().counter,
+            0
+        );
+        // Everything, that was created during the play, is removed.
+        assert_eq!(
+            engine
+                .scenes
+                .pair_iter()
+                .map(|(h, _)| h)
+                .collect::<Vec<_>>(),
+            [play_scene, other_scene]
+        );
+        assert_eq!(engine.user_interfaces.pair_iter().count(), 1);
+        // Coroutines of other scenes are kept.
+        assert_eq!(coroutine_scenes(&engine), [other_scene]);
+    }
+
+    #[test]
+    fn test_embedded_play_restores_remaining_plugins_on_failure() {
+        let mut engine = make_engine();
+        engine.add_plugin(BrokenPlugin {});
+        engine.add_plugin(CounterPlugin::default());
+
+        let play_scene = engine.scenes.add(Scene::new());
+
+        engine.start_embedded_play(play_scene, None, None).unwrap();
+        assert!(engine.stop_embedded_play(None).is_err());
+
+        assert!(!engine.is_in_embedded_play());
+        assert_eq!(
+            engine.plugins[1].cast::<CounterPlugin>().unwrap().counter,
+            0
+        );
+        assert_eq!(engine.scenes.pair_iter().count(), 1);
+        assert_eq!(engine.user_interfaces.pair_iter().count(), 1);
+    }
+}
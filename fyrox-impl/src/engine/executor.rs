@@ -43,6 +43,9 @@ use std::{
     sync::Arc,
 };
 
+// Maximum amount of deterministic simulation ticks per frame. Any lag above this limit is dropped.
+const MAX_DETERMINISTIC_STEPS_PER_FRAME: f32 = 5.0;
+
 #[derive(Parser, Debug, Default)]
 #[clap(author, version, about, long_about = None)]
 struct Args {
@@ -238,6 +241,26 @@ impl Executor {
             }
         }
 
+        // Exported games could have their assets packed into a single file. WebAssembly builds
+        // cannot block on loading, so they always use loose files.
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            use crate::{
+                asset::pack::{PackedResourceIo, DEFAULT_PACK_FILE_NAME},
+                core::futures::executor::block_on,
+            };
+
+            if let Ok(pack) = block_on(PackedResourceIo::load(DEFAULT_PACK_FILE_NAME)) {
+                Log::info(format!(
+                    "Using {DEFAULT_PACK_FILE_NAME} resource pack for loading resources."
+                ));
+                engine
+                    .resource_manager
+                    .state()
+                    .set_resource_io(Arc::new(pack));
+            }
+        }
+
         let args = Args::try_parse().unwrap_or_default();
 
         engine.enable_plugins(args.override_scene.as_deref(), true, Some(&event_loop));
@@ -251,20 +274,26 @@ impl Executor {
         run_executor(event_loop, move |event, window_target| {
             window_target.set_control_flow(ControlFlow::Wait);
 
-            engine.handle_os_event_by_plugins(&event, fixed_time_step, window_target, &mut lag);
+            // In deterministic mode the input reaches the game logic only via the simulation.
+            let captured = engine.simulation.push_os_event(&event);
+            engine.late_update_input.push_os_event(&event);
 
-            let scenes = engine
-                .scenes
-                .pair_iter()
-                .map(|(s, _)| s)
-                .collect::<Vec<_>>();
+            if !captured {
+                engine.handle_os_event_by_plugins(&event, fixed_time_step, window_target, &mut lag);
 
-            for &scene_handle in scenes.iter() {
-                if !engine.has_scripted_scene(scene_handle) {
-                    engine.register_scripted_scene(scene_handle);
-                }
+                let scenes = engine
+                    .scenes
+                    .pair_iter()
+                    .map(|(s, _)| s)
+                    .collect::<Vec<_>>();
+
+                for &scene_handle in scenes.iter() {
+                    if !engine.has_scripted_scene(scene_handle) {
+                        engine.register_scripted_scene(scene_handle);
+                    }
 
-                engine.handle_os_event_by_scripts(&event, scene_handle, fixed_time_step);
+                    engine.handle_os_event_by_scripts(&event, scene_handle, fixed_time_step);
+                }
             }
 
             match event {
@@ -291,14 +320,36 @@ impl Executor {
                     );
                 }
                 Event::AboutToWait => {
+                    if !headless {
+                        if let Err(err) = engine.recover_graphics_context(
+                            fixed_time_step,
+                            window_target,
+                            &mut lag,
+                        ) {
+                            Log::err(format!("Unable to recover graphics context. Reason: {err}"));
+                        }
+                    }
+
                     let elapsed = previous.elapsed();
                     previous = Instant::now();
                     lag += elapsed.as_secs_f32();
 
+                    let deterministic = engine.simulation.is_deterministic();
+                    let fixed_time_step = if deterministic {
+                        // Deterministic simulation must never be fast-forwarded, instead the game
+                        // will run slower, if it cannot keep up with real time.
+                        let time_step = engine.simulation.time_step();
+                        lag = lag.min(time_step * MAX_DETERMINISTIC_STEPS_PER_FRAME);
+                        time_step
+                    } else {
+                        fixed_time_step
+                    };
+
                     // Update rate stabilization loop.
                     while lag >= fixed_time_step {
                         let time_step;
-                        if lag >= throttle_threshold
+                        if !deterministic
+                            && lag >= throttle_threshold
                             && (frame_counter - last_throttle_frame_number
                                 >= throttle_frame_interval)
                         {
@@ -24,6 +24,7 @@ use crate::{
     core::{
         log::Log,
         pool::{Handle, PayloadContainer, Ticket},
+        reflect::Reflect,
         visitor::{Visit, VisitError, Visitor, VisitorFlags},
     },
     engine::SerializationContext,
@@ -38,12 +39,86 @@ use crate::{
     script::Script,
 };
 use std::{
+    fmt::{Display, Formatter},
     ops::Deref,
     sync::{mpsc::Sender, Arc},
 };
 
+/// Names and type names of the fields of a script, collected via reflection. It is used to detect
+/// layout changes of a script between two versions of a plugin.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct ScriptLayout {
+    fields: Vec<(String, String)>,
+}
+
+impl ScriptLayout {
+    pub fn from_script(script: &Script) -> Self {
+        let mut fields = Vec::new();
+        script.deref().fields_info(&mut |fields_info| {
+            fields.extend(
+                fields_info
+                    .iter()
+                    .map(|info| (info.name.to_string(), info.type_name.to_string())),
+            )
+        });
+        Self { fields }
+    }
+
+    /// Compares the layout with a newer version of it and returns a set of changes.
+    pub fn diff(&self, new: &ScriptLayout) -> ScriptLayoutChange {
+        let mut change = ScriptLayoutChange::default();
+
+        for (name, type_name) in self.fields.iter() {
+            match new.fields.iter().find(|(new_name, _)| new_name == name) {
+                Some((_, new_type_name)) => {
+                    if new_type_name != type_name {
+                        change.changed.push(name.clone());
+                    }
+                }
+                None => change.removed.push(name.clone()),
+            }
+        }
+
+        for (name, _) in new.fields.iter() {
+            if !self.fields.iter().any(|(old_name, _)| old_name == name) {
+                change.added.push(name.clone());
+            }
+        }
+
+        change
+    }
+}
+
+/// A set of changes between two layouts of a script.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct ScriptLayoutChange {
+    /// Fields that were added in the new version. Such fields will have default values.
+    pub added: Vec<String>,
+    /// Fields that were removed in the new version. Their values are discarded.
+    pub removed: Vec<String>,
+    /// Fields that have different types in the new version. Such fields will have default values.
+    pub changed: Vec<String>,
+}
+
+impl ScriptLayoutChange {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl Display for ScriptLayoutChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Added fields: {:?}, removed fields: {:?}, fields with changed type: {:?}",
+            self.added, self.removed, self.changed
+        )
+    }
+}
+
 pub struct ScriptState {
     index: usize,
+    layout: ScriptLayout,
     binary_blob: Vec<u8>,
 }
 
@@ -102,6 +177,7 @@ impl SceneState {
                         if is_script_belongs_to_plugin(serialization_context, script, plugin) {
                             // Take the script out of the node and serialize it. The script will be
                             // dropped and destroyed.
+                            let layout = ScriptLayout::from_script(script);
                             let mut script = record.script.take();
                             let mut visitor = make_writing_visitor();
                             visit_opt_script("Script", &mut script, &mut visitor)
@@ -111,6 +187,7 @@ impl SceneState {
 
                             node_state.scripts.push(ScriptState {
                                 index: script_index,
+                                layout,
                                 binary_blob,
                             })
                         }
@@ -199,35 +276,41 @@ impl SceneState {
             if node_state.binary_blob.is_empty() {
                 // Only scripts needs to be reloaded.
                 for script in node_state.scripts {
-                    let mut visitor = make_reading_visitor(
-                        &script.binary_blob,
+                    let opt_script = restore_script(
+                        node_state.node,
+                        &script,
                         serialization_context,
                         resource_manager,
                         widget_constructors,
-                    )
-                    .map_err(|e| e.to_string())?;
-                    let mut opt_script: Option<Script> = None;
-                    visit_opt_script("Script", &mut opt_script, &mut visitor)
-                        .map_err(|e| e.to_string())?;
+                    );
                     set_script(node_state.node, script.index, opt_script);
-
-                    Log::info(format!(
-                        "Script {} of node {} was successfully deserialized.",
-                        script.index, node_state.node
-                    ));
                 }
             } else {
-                let mut visitor = make_reading_visitor(
+                let mut container = match read_node(
                     &node_state.binary_blob,
+                    VisitorFlags::NONE,
                     serialization_context,
                     resource_manager,
                     widget_constructors,
-                )
-                .map_err(|e| e.to_string())?;
-                let mut container = NodeContainer::default();
-                container
-                    .visit("Node", &mut visitor)
-                    .map_err(|e| e.to_string())?;
+                ) {
+                    Ok(container) => container,
+                    Err(err) => {
+                        Log::warn(format!(
+                            "Unable to deserialize node {} as is, its layout was probably \
+                            changed. Trying to migrate it... Reason: {err}",
+                            node_state.node
+                        ));
+
+                        read_node(
+                            &node_state.binary_blob,
+                            VisitorFlags::IGNORE_FIELD_ERRORS,
+                            serialization_context,
+                            resource_manager,
+                            widget_constructors,
+                        )
+                        .map_err(|e| e.to_string())?
+                    }
+                };
                 if let Some(mut new_node) = container.take() {
                     new_node.on_connected_to_graph(
                         node_state.node,
@@ -269,6 +352,108 @@ pub fn make_reading_visitor(
     Ok(visitor)
 }
 
+fn read_script(
+    binary_blob: &[u8],
+    flags: VisitorFlags,
+    serialization_context: &Arc<SerializationContext>,
+    resource_manager: &ResourceManager,
+    widget_constructors: &Arc<WidgetConstructorContainer>,
+) -> Result<Option<Script>, VisitError> {
+    let mut visitor = make_reading_visitor(
+        binary_blob,
+        serialization_context,
+        resource_manager,
+        widget_constructors,
+    )?;
+    visitor.flags |= flags;
+    let mut opt_script: Option<Script> = None;
+    visit_opt_script("Script", &mut opt_script, &mut visitor)?;
+    Ok(opt_script)
+}
+
+fn read_node(
+    binary_blob: &[u8],
+    flags: VisitorFlags,
+    serialization_context: &Arc<SerializationContext>,
+    resource_manager: &ResourceManager,
+    widget_constructors: &Arc<WidgetConstructorContainer>,
+) -> Result<NodeContainer, VisitError> {
+    let mut visitor = make_reading_visitor(
+        binary_blob,
+        serialization_context,
+        resource_manager,
+        widget_constructors,
+    )?;
+    visitor.flags |= flags;
+    let mut container = NodeContainer::default();
+    container.visit("Node", &mut visitor)?;
+    Ok(container)
+}
+
+/// Tries to restore a script from its serialized state. At first, the script is deserialized as is.
+/// If it fails (the layout of the script was changed), the script state is migrated: every field,
+/// that still exists and has the same type is restored, other fields will have their default values.
+/// If the migration fails too (for example, the script was removed from the plugin), the script
+/// will be removed from the node.
+fn restore_script(
+    node: Handle<Node>,
+    script_state: &ScriptState,
+    serialization_context: &Arc<SerializationContext>,
+    resource_manager: &ResourceManager,
+    widget_constructors: &Arc<WidgetConstructorContainer>,
+) -> Option<Script> {
+    let index = script_state.index;
+
+    match read_script(
+        &script_state.binary_blob,
+        VisitorFlags::NONE,
+        serialization_context,
+        resource_manager,
+        widget_constructors,
+    ) {
+        Ok(script) => {
+            Log::info(format!(
+                "Script {index} of node {node} was successfully deserialized."
+            ));
+
+            return script;
+        }
+        Err(err) => {
+            Log::warn(format!(
+                "Unable to deserialize script {index} of node {node} as is, its layout was \
+                probably changed. Trying to migrate its state... Reason: {err}"
+            ));
+        }
+    }
+
+    match read_script(
+        &script_state.binary_blob,
+        VisitorFlags::IGNORE_FIELD_ERRORS,
+        serialization_context,
+        resource_manager,
+        widget_constructors,
+    ) {
+        Ok(script) => {
+            if let Some(script) = script.as_ref() {
+                let change = script_state.layout.diff(&ScriptLayout::from_script(script));
+                Log::warn(format!(
+                    "Script {index} of node {node} was migrated to a new layout. {change}"
+                ));
+            }
+
+            script
+        }
+        Err(err) => {
+            Log::err(format!(
+                "Unable to migrate script {index} of node {node}, the script will be removed \
+                from the node. Reason: {err}"
+            ));
+
+            None
+        }
+    }
+}
+
 fn is_script_belongs_to_plugin(
     serialization_context: &SerializationContext,
     script: &Script,
@@ -302,3 +487,38 @@ fn is_node_belongs_to_plugin(
     }
     false
 }
+
+#[cfg(test)]
+mod test {
+    use crate::engine::hotreload::{ScriptLayout, ScriptLayoutChange};
+
+    fn layout(fields: &[(&str, &str)]) -> ScriptLayout {
+        ScriptLayout {
+            fields: fields
+                .iter()
+                .map(|(name, type_name)| (name.to_string(), type_name.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_script_layout_diff() {
+        let old = layout(&[
+            ("speed", "f32"),
+            ("target", "Handle<Node>"),
+            ("timer", "f32"),
+        ]);
+
+        assert!(old.diff(&old).is_empty());
+
+        let new = layout(&[("speed", "f32"), ("timer", "f64"), ("health", "u32")]);
+        assert_eq!(
+            old.diff(&new),
+            ScriptLayoutChange {
+                added: vec!["health".to_string()],
+                removed: vec!["target".to_string()],
+                changed: vec!["timer".to_string()],
+            }
+        );
+    }
+}
@@ -0,0 +1,223 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Latency tracking for the frame pipeline. See [`LatencyTracker`] docs for more info.
+
+use crate::core::instant::Instant;
+use std::time::Duration;
+
+/// A point of the frame pipeline, which time could be recorded by [`LatencyTracker`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum LatencyMarker {
+    /// The moment when the game has read the user input that affects the current frame. This
+    /// marker is never set by the engine itself, call [`LatencyTracker::mark`] right before the
+    /// input is consumed by your game logic. If it is not set, [`Self::SimulationStart`] is used
+    /// instead.
+    InputSample,
+    /// The beginning of the first game logic update of the frame.
+    SimulationStart,
+    /// The end of the last game logic update of the frame.
+    SimulationEnd,
+    /// The moment when the renderer has started to submit draw commands.
+    RenderSubmitStart,
+    /// The moment when the renderer has submitted all the draw commands.
+    RenderSubmitEnd,
+    /// The moment right before the frame is handed to the graphics server for presentation.
+    PresentStart,
+    /// The moment when the graphics server has returned from the presentation. Depending on the
+    /// present settings, it could be either the moment when the frame was shown or the moment
+    /// when it was queued.
+    PresentEnd,
+}
+
+impl LatencyMarker {
+    const COUNT: usize = 7;
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    // "Start" markers keep the earliest time of a frame, because a frame could contain multiple
+    // updates of the game logic. Every other marker keeps the latest time.
+    fn keeps_earliest(self) -> bool {
+        matches!(
+            self,
+            Self::InputSample
+                | Self::SimulationStart
+                | Self::RenderSubmitStart
+                | Self::PresentStart
+        )
+    }
+}
+
+/// Timings of a single presented frame.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct LatencyReport {
+    /// Time spent on game logic updates.
+    pub simulation: Duration,
+    /// Time spent on submitting draw commands.
+    pub render_submit: Duration,
+    /// Time spent in the presentation (this includes waiting for the vertical blank and for the
+    /// GPU, if the amount of frames in flight is limited).
+    pub present: Duration,
+    /// Total time between input sampling and the end of presentation of the frame.
+    pub input_to_present: Duration,
+}
+
+/// Latency tracker records the time of a few key points of the frame pipeline (see
+/// [`LatencyMarker`]) and calculates the latency between the moment when the input was sampled and
+/// the moment when the frame was presented. The engine sets every marker except
+/// [`LatencyMarker::InputSample`] automatically.
+///
+/// ```rust
+/// # use fyrox_impl::engine::{latency::LatencyMarker, Engine};
+/// fn read_input(engine: &mut Engine) {
+///     engine.latency_tracker.mark(LatencyMarker::InputSample);
+///     // Read the input here.
+/// }
+///
+/// fn print_latency(engine: &Engine) {
+///     if let Some(report) = engine.latency_tracker.last_report() {
+///         println!(
+///             "Input-to-present: {:?}, average: {:?}",
+///             report.input_to_present,
+///             engine.latency_tracker.average_input_to_present()
+///         );
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct LatencyTracker {
+    markers: [Option<Instant>; LatencyMarker::COUNT],
+    last_report: Option<LatencyReport>,
+    average_input_to_present: Duration,
+}
+
+impl LatencyTracker {
+    /// Weight of the new sample in the exponential moving average.
+    const AVERAGE_WEIGHT: f64 = 0.1;
+
+    /// Records the current time for the given marker.
+    pub fn mark(&mut self, marker: LatencyMarker) {
+        self.mark_at(marker, Instant::now())
+    }
+
+    /// Records the given time for the given marker.
+    pub fn mark_at(&mut self, marker: LatencyMarker, time: Instant) {
+        let slot = &mut self.markers[marker.index()];
+        if slot.is_none() || !marker.keeps_earliest() {
+            *slot = Some(time);
+        }
+    }
+
+    /// Returns the time of the given marker in the current (not yet presented) frame.
+    pub fn marker_time(&self, marker: LatencyMarker) -> Option<Instant> {
+        self.markers[marker.index()]
+    }
+
+    /// Returns timings of the last presented frame.
+    pub fn last_report(&self) -> Option<LatencyReport> {
+        self.last_report
+    }
+
+    /// Returns smoothed input-to-present latency over the last few frames.
+    pub fn average_input_to_present(&self) -> Duration {
+        self.average_input_to_present
+    }
+
+    fn span(&self, from: LatencyMarker, to: LatencyMarker) -> Duration {
+        match (self.marker_time(from), self.marker_time(to)) {
+            (Some(from), Some(to)) => to.saturating_duration_since(from),
+            _ => Duration::default(),
+        }
+    }
+
+    /// Calculates the report for the current frame and starts a new frame. Frames that were not
+    /// presented are not reported.
+    pub(crate) fn end_frame(&mut self) {
+        if self.marker_time(LatencyMarker::PresentEnd).is_some() {
+            let input = if self.marker_time(LatencyMarker::InputSample).is_some() {
+                LatencyMarker::InputSample
+            } else {
+                LatencyMarker::SimulationStart
+            };
+
+            let report = LatencyReport {
+                simulation: self.span(LatencyMarker::SimulationStart, LatencyMarker::SimulationEnd),
+                render_submit: self.span(
+                    LatencyMarker::RenderSubmitStart,
+                    LatencyMarker::RenderSubmitEnd,
+                ),
+                present: self.span(LatencyMarker::PresentStart, LatencyMarker::PresentEnd),
+                input_to_present: self.span(input, LatencyMarker::PresentEnd),
+            };
+
+            self.average_input_to_present = if self.last_report.is_none() {
+                report.input_to_present
+            } else {
+                self.average_input_to_present
+                    .mul_f64(1.0 - Self::AVERAGE_WEIGHT)
+                    + report.input_to_present.mul_f64(Self::AVERAGE_WEIGHT)
+            };
+            self.last_report = Some(report);
+        }
+
+        self.markers = Default::default();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::instant::Instant,
+        engine::latency::{LatencyMarker, LatencyTracker},
+    };
+    use std::time::Duration;
+
+    #[test]
+    fn test_latency_tracker() {
+        let mut tracker = LatencyTracker::default();
+        let t0 = Instant::now();
+        let ms = Duration::from_millis;
+
+        tracker.mark_at(LatencyMarker::SimulationStart, t0);
+        tracker.mark_at(LatencyMarker::SimulationEnd, t0 + ms(2));
+        // Second update within the same frame.
+        tracker.mark_at(LatencyMarker::SimulationStart, t0 + ms(3));
+        tracker.mark_at(LatencyMarker::SimulationEnd, t0 + ms(5));
+        tracker.mark_at(LatencyMarker::RenderSubmitStart, t0 + ms(6));
+        tracker.mark_at(LatencyMarker::RenderSubmitEnd, t0 + ms(9));
+        tracker.mark_at(LatencyMarker::PresentStart, t0 + ms(9));
+        tracker.mark_at(LatencyMarker::PresentEnd, t0 + ms(16));
+        tracker.end_frame();
+
+        let report = tracker.last_report().unwrap();
+        assert_eq!(report.simulation, ms(5));
+        assert_eq!(report.render_submit, ms(3));
+        assert_eq!(report.present, ms(7));
+        assert_eq!(report.input_to_present, ms(16));
+        assert_eq!(tracker.average_input_to_present(), ms(16));
+        assert_eq!(tracker.marker_time(LatencyMarker::SimulationStart), None);
+
+        // A frame without presentation must not change the report.
+        tracker.mark_at(LatencyMarker::SimulationStart, t0 + ms(20));
+        tracker.end_frame();
+        assert_eq!(tracker.last_report().unwrap(), report);
+    }
+}
@@ -23,9 +23,15 @@
 
 #![warn(missing_docs)]
 
+pub mod accessibility;
+pub mod embedded;
 pub mod error;
 pub mod executor;
+pub mod latency;
+pub mod profiler_overlay;
+pub mod simulation;
 pub mod task;
+pub mod watchdog;
 
 mod hotreload;
 
@@ -48,18 +54,29 @@ use crate::{
         instant,
         log::Log,
         pool::Handle,
+        profiler,
         reflect::Reflect,
+        scope_profile,
         task::TaskPool,
         variable::try_inherit_properties,
-        visitor::VisitError,
+        visitor::{VisitError, VisitorFlags},
+    },
+    engine::{
+        accessibility::AccessibilitySettings,
+        error::EngineError,
+        latency::{LatencyMarker, LatencyTracker},
+        profiler_overlay::ProfilerOverlay,
+        simulation::Simulation,
+        task::TaskPoolHandler,
+        watchdog::FrameWatchdog,
     },
-    engine::{error::EngineError, task::TaskPoolHandler},
     event::Event,
     graph::{BaseSceneGraph, NodeMapping, SceneGraph},
     gui::{
         constructor::WidgetConstructorContainer,
         font::{loader::FontLoader, Font, BUILT_IN_FONT},
         loader::UserInterfaceLoader,
+        localization::{LocalizationDictionary, LocalizationLoader},
         style::{self, resource::StyleLoader, Style},
         UiContainer, UiUpdateSwitches, UserInterface,
     },
@@ -83,6 +100,7 @@ use crate::{
         base::NodeScriptMessage,
         camera::SkyBoxKind,
         graph::{GraphUpdateSwitches, NodePool},
+        late_update::LateUpdateInput,
         mesh::surface::{self, SurfaceData, SurfaceDataLoader},
         navmesh,
         node::{
@@ -97,18 +115,19 @@ use crate::{
         Scene, SceneContainer, SceneLoader,
     },
     script::{
-        constructor::ScriptConstructorContainer, PluginsRefMut, RoutingStrategy, Script,
-        ScriptContext, ScriptDeinitContext, ScriptMessage, ScriptMessageContext, ScriptMessageKind,
-        ScriptMessageSender, UniversalScriptContext,
+        constructor::ScriptConstructorContainer,
+        profiler::{ScriptMethod, ScriptProfiler},
+        PluginsRefMut, RoutingStrategy, Script, ScriptContext, ScriptDeinitContext, ScriptMessage,
+        ScriptMessageContext, ScriptMessageKind, ScriptMessageSender, UniversalScriptContext,
     },
     window::{Window, WindowBuilder},
 };
 use fxhash::{FxHashMap, FxHashSet};
 use fyrox_animation::AnimationTracksData;
 use fyrox_graphics::gl::server::GlGraphicsServer;
-use fyrox_graphics::server::SharedGraphicsServer;
+use fyrox_graphics::server::{PresentSettings, SharedGraphicsServer, VSyncMode};
 use fyrox_sound::{
-    buffer::{loader::SoundBufferLoader, SoundBuffer},
+    buffer::{import::DEFAULT_IMPORT_CACHE_DIR, loader::SoundBufferLoader, SoundBuffer},
     renderer::hrtf::{HrirSphereLoader, HrirSphereResourceData},
 };
 use std::rc::Rc;
@@ -485,6 +504,9 @@ pub struct Engine {
 
     plugins_enabled: bool,
 
+    // Active embedded play (if any), see `embedded` module for more info.
+    embedded_play: Option<embedded::EmbeddedPlay>,
+
     // Amount of time (in seconds) that passed from creation of the engine.
     elapsed_time: f32,
 
@@ -497,6 +519,30 @@ pub struct Engine {
 
     /// Script processor is used to run script methods in a strict order.
     pub script_processor: ScriptProcessor,
+
+    /// Frame watchdog is used to catch long frames and dump diagnostic information about them. See
+    /// [`FrameWatchdog`] docs for more info.
+    pub frame_watchdog: FrameWatchdog,
+
+    /// Simulation controls the way how the game logic is updated. It allows you to switch the engine
+    /// to deterministic mode, record and replay sessions. See [`Simulation`] docs for more info.
+    pub simulation: Simulation,
+
+    /// Latency tracker records key points of the frame pipeline and measures input-to-present
+    /// latency. See [`LatencyTracker`] docs for more info.
+    pub latency_tracker: LatencyTracker,
+
+    /// Input, that was received after the last update of the game logic. It is passed to late
+    /// update callbacks of scenes (see [`crate::scene::late_update::LateUpdateCallbacks`]).
+    pub late_update_input: LateUpdateInput,
+
+    // Present settings of the destroyed graphics context, they're restored when the context is
+    // initialized again.
+    restored_present_settings: Option<PresentSettings>,
+
+    accessibility_settings: AccessibilitySettings,
+
+    profiler_overlay: Option<ProfilerOverlay>,
 }
 
 /// Performs dispatch of script messages.
@@ -550,6 +596,8 @@ impl ScriptMessageDispatcher {
         user_interfaces: &mut UiContainer,
         graphics_context: &mut GraphicsContext,
         task_pool: &mut TaskPoolHandler,
+        simulation: &mut Simulation,
+        profiler: &mut ScriptProfiler,
     ) {
         while let Ok(message) = self.message_receiver.try_recv() {
             let receivers = self.type_groups.get(&message.payload.deref().type_id());
@@ -568,7 +616,7 @@ impl ScriptMessageDispatcher {
                     ScriptMessageKind::Targeted(target) => {
                         if receivers.contains(&target) {
                             let mut context = ScriptMessageContext {
-                                dt,
+                                dt: scene.graph.time.node_dt(target, dt),
                                 elapsed_time,
                                 plugins: PluginsRefMut(plugins),
                                 handle: target,
@@ -577,13 +625,16 @@ impl ScriptMessageDispatcher {
                                 resource_manager,
                                 message_sender,
                                 task_pool,
+                                simulation,
                                 graphics_context,
                                 user_interfaces,
                                 script_index: 0,
                             };
 
                             process_node_scripts(&mut context, &mut |s, ctx| {
-                                s.on_message(&mut *payload, ctx)
+                                let begin = profiler.begin();
+                                s.on_message(&mut *payload, ctx);
+                                profiler.end(s, ScriptMethod::Message, begin);
                             })
                         }
                     }
@@ -594,7 +645,7 @@ impl ScriptMessageDispatcher {
                                 let parent = node_ref.parent();
 
                                 let mut context = ScriptMessageContext {
-                                    dt,
+                                    dt: scene.graph.time.node_dt(node, dt),
                                     elapsed_time,
                                     plugins: PluginsRefMut(plugins),
                                     handle: node,
@@ -603,6 +654,7 @@ impl ScriptMessageDispatcher {
                                     resource_manager,
                                     message_sender,
                                     task_pool,
+                                    simulation,
                                     graphics_context,
                                     user_interfaces,
                                     script_index: 0,
@@ -610,7 +662,9 @@ impl ScriptMessageDispatcher {
 
                                 if receivers.contains(&node) {
                                     process_node_scripts(&mut context, &mut |s, ctx| {
-                                        s.on_message(&mut *payload, ctx)
+                                        let begin = profiler.begin();
+                                        s.on_message(&mut *payload, ctx);
+                                        profiler.end(s, ScriptMethod::Message, begin);
                                     });
                                 }
 
@@ -620,7 +674,7 @@ impl ScriptMessageDispatcher {
                         RoutingStrategy::Down => {
                             for node in scene.graph.traverse_handle_iter(root).collect::<Vec<_>>() {
                                 let mut context = ScriptMessageContext {
-                                    dt,
+                                    dt: scene.graph.time.node_dt(node, dt),
                                     elapsed_time,
                                     plugins: PluginsRefMut(plugins),
                                     handle: node,
@@ -629,6 +683,7 @@ impl ScriptMessageDispatcher {
                                     resource_manager,
                                     message_sender,
                                     task_pool,
+                                    simulation,
                                     graphics_context,
                                     user_interfaces,
                                     script_index: 0,
@@ -636,7 +691,9 @@ impl ScriptMessageDispatcher {
 
                                 if receivers.contains(&node) {
                                     process_node_scripts(&mut context, &mut |s, ctx| {
-                                        s.on_message(&mut *payload, ctx)
+                                        let begin = profiler.begin();
+                                        s.on_message(&mut *payload, ctx);
+                                        profiler.end(s, ScriptMethod::Message, begin);
                                     });
                                 }
                             }
@@ -645,7 +702,7 @@ impl ScriptMessageDispatcher {
                     ScriptMessageKind::Global => {
                         for &node in receivers {
                             let mut context = ScriptMessageContext {
-                                dt,
+                                dt: scene.graph.time.node_dt(node, dt),
                                 elapsed_time,
                                 plugins: PluginsRefMut(plugins),
                                 handle: node,
@@ -654,13 +711,16 @@ impl ScriptMessageDispatcher {
                                 resource_manager,
                                 message_sender,
                                 task_pool,
+                                simulation,
                                 graphics_context,
                                 user_interfaces,
                                 script_index: 0,
                             };
 
                             process_node_scripts(&mut context, &mut |s, ctx| {
-                                s.on_message(&mut *payload, ctx)
+                                let begin = profiler.begin();
+                                s.on_message(&mut *payload, ctx);
+                                profiler.end(s, ScriptMethod::Message, begin);
                             });
                         }
                     }
@@ -685,6 +745,8 @@ pub struct ScriptProcessor {
     wait_list: Vec<ResourceWaitContext>,
     /// A list of scenes.
     pub scripted_scenes: Vec<ScriptedScene>,
+    /// Per-type timings of scripts. It is disabled by default.
+    pub profiler: ScriptProfiler,
 }
 
 impl ScriptProcessor {
@@ -717,6 +779,7 @@ impl ScriptProcessor {
         plugins: &mut [PluginContainer],
         resource_manager: &ResourceManager,
         task_pool: &mut TaskPoolHandler,
+        simulation: &mut Simulation,
         graphics_context: &mut GraphicsContext,
         user_interfaces: &mut UiContainer,
         dt: f32,
@@ -732,6 +795,8 @@ impl ScriptProcessor {
         self.scripted_scenes
             .retain(|s| scenes.is_valid_handle(s.handle));
 
+        let profiler = &mut self.profiler;
+
         'scene_loop: for scripted_scene in self.scripted_scenes.iter_mut() {
             let scene = &mut scenes[scripted_scene.handle];
 
@@ -789,9 +854,11 @@ impl ScriptProcessor {
                     message_sender: &scripted_scene.message_sender,
                     message_dispatcher: &mut scripted_scene.message_dispatcher,
                     task_pool,
+                    simulation,
                     graphics_context,
                     user_interfaces,
                     script_index: 0,
+                    script_instance_id: Default::default(),
                 };
 
                 'init_loop: for init_loop_iteration in 0..max_iterations {
@@ -811,7 +878,9 @@ impl ScriptProcessor {
                                     &mut context,
                                     &mut |script, context| {
                                         if !script.initialized {
+                                            let begin = profiler.begin();
                                             script.on_init(context);
+                                            profiler.end(script, ScriptMethod::Init, begin);
                                             script.initialized = true;
                                         }
 
@@ -847,7 +916,9 @@ impl ScriptProcessor {
                                 &mut context,
                                 &mut |script, context| {
                                     if script.initialized && !script.started {
+                                        let begin = profiler.begin();
                                         script.on_start(context);
+                                        profiler.end(script, ScriptMethod::Start, begin);
                                         script.started = true;
 
                                         update_queue.push_back((handle, script_index));
@@ -874,7 +945,9 @@ impl ScriptProcessor {
                         context.script_index = script_index;
 
                         process_node_script(script_index, &mut context, &mut |script, context| {
+                            let begin = profiler.begin();
                             script.on_update(context);
+                            profiler.end(script, ScriptMethod::Update, begin);
                         });
                     }
                 }
@@ -902,6 +975,8 @@ impl ScriptProcessor {
                 user_interfaces,
                 graphics_context,
                 task_pool,
+                simulation,
+                profiler,
             );
 
             // As the last step, destroy queued scripts.
@@ -931,6 +1006,8 @@ impl ScriptProcessor {
             }
         }
 
+        profiler.end_frame();
+
         // Process scripts from destroyed scenes.
         for (handle, mut detached_scene) in scenes.destruction_list.drain(..) {
             if let Some(scripted_scene) = self.scripted_scenes.iter().find(|s| s.handle == handle) {
@@ -1078,7 +1155,9 @@ pub struct GraphicsContextParams {
     pub vsync: bool,
 
     /// Amount of samples for MSAA. Must be a power of two (1, 2, 4, 8). `None` means disabled.
-    /// MSAA works only for forward rendering and does not work for deferred rendering.
+    /// MSAA works only for forward rendering and does not work for deferred rendering. This value
+    /// is fixed for the lifetime of the graphics context, use
+    /// [`crate::renderer::QualitySettings::msaa_sample_count`] to change MSAA at runtime.
     pub msaa_sample_count: Option<u8>,
 
     /// Graphic server constructor. See [`GraphicsServerConstructor`] docs for more info.
@@ -1136,6 +1215,7 @@ where
         return false;
     };
 
+    context.set_script_instance_id(script.instance_id());
     func(&mut script, context);
 
     match context.node() {
@@ -1188,6 +1268,7 @@ pub(crate) fn process_scripts<T>(
     message_sender: &ScriptMessageSender,
     message_dispatcher: &mut ScriptMessageDispatcher,
     task_pool: &mut TaskPoolHandler,
+    simulation: &mut Simulation,
     graphics_context: &mut GraphicsContext,
     user_interfaces: &mut UiContainer,
     dt: f32,
@@ -1207,13 +1288,16 @@ pub(crate) fn process_scripts<T>(
         message_sender,
         message_dispatcher,
         task_pool,
+        simulation,
         graphics_context,
         user_interfaces,
         script_index: 0,
+        script_instance_id: Default::default(),
     };
 
     for node_index in 0..context.scene.graph.capacity() {
         context.handle = context.scene.graph.handle_from_index(node_index);
+        context.dt = context.scene.graph.time.node_dt(context.handle, dt);
 
         process_node_scripts(&mut context, &mut func);
     }
@@ -1288,6 +1372,7 @@ pub(crate) fn initialize_resource_manager_loaders(
     state.constructors_container.add::<CustomTileCollider>();
     state.constructors_container.add::<AnimationTracksData>();
     state.constructors_container.add::<Style>();
+    state.constructors_container.add::<LocalizationDictionary>();
 
     let loaders = &mut state.loaders;
     loaders.set(model_loader);
@@ -1296,6 +1381,7 @@ pub(crate) fn initialize_resource_manager_loaders(
     });
     loaders.set(SoundBufferLoader {
         default_import_options: Default::default(),
+        cache_dir: Some(PathBuf::from(DEFAULT_IMPORT_CACHE_DIR)),
     });
     loaders.set(ShaderLoader);
     loaders.set(CurveLoader);
@@ -1317,6 +1403,7 @@ pub(crate) fn initialize_resource_manager_loaders(
     state.loaders.set(StyleLoader {
         resource_manager: resource_manager.clone(),
     });
+    state.loaders.set(LocalizationLoader);
 }
 
 impl Engine {
@@ -1398,7 +1485,15 @@ impl Engine {
             serialization_context,
             widget_constructors,
             script_processor: Default::default(),
+            frame_watchdog: Default::default(),
+            latency_tracker: Default::default(),
+            late_update_input: Default::default(),
+            restored_present_settings: None,
+            accessibility_settings: Default::default(),
+            profiler_overlay: None,
+            simulation: Default::default(),
             plugins_enabled: false,
+            embedded_play: None,
             elapsed_time: 0.0,
             task_pool: TaskPoolHandler::new(task_pool),
         })
@@ -1455,7 +1550,13 @@ impl Engine {
                 params.graphics_server_constructor.0(params, window_target, window_builder)?;
             let frame_size = (window.inner_size().width, window.inner_size().height);
 
-            let renderer = Renderer::new(server, frame_size, &self.resource_manager)?;
+            let mut renderer = Renderer::new(server, frame_size, &self.resource_manager)?;
+
+            if let Some(present_settings) = self.restored_present_settings.take() {
+                Log::verify(renderer.set_present_settings(present_settings));
+            }
+
+            renderer.set_color_blindness_settings(self.accessibility_settings.color_blindness);
 
             for ui in self.user_interfaces.iter_mut() {
                 ui.set_screen_size(Vector2::new(frame_size.0 as f32, frame_size.1 as f32));
@@ -1514,9 +1615,12 @@ impl Engine {
                 .window_icon
                 .clone_from(&params.window_attributes.window_icon);
 
+            let present_settings = ctx.renderer.present_settings();
+            self.restored_present_settings = Some(present_settings);
+
             self.graphics_context = GraphicsContext::Uninitialized(GraphicsContextParams {
                 window_attributes,
-                vsync: params.vsync,
+                vsync: present_settings.vsync != VSyncMode::Off,
                 msaa_sample_count: params.msaa_sample_count,
                 graphics_server_constructor: params.graphics_server_constructor.clone(),
             });
@@ -1631,6 +1735,8 @@ impl Engine {
                             async_scene_loader: &mut self.async_scene_loader,
                             window_target: Some(window_target),
                             task_pool: &mut self.task_pool,
+                            simulation: &mut self.simulation,
+                            accessibility_settings: &mut self.accessibility_settings,
                         };
 
                         for plugin in self.plugins.iter_mut() {
@@ -1664,6 +1770,8 @@ impl Engine {
                     async_scene_loader: &mut self.async_scene_loader,
                     window_target: Some(window_target),
                     task_pool: &mut self.task_pool,
+                    simulation: &mut self.simulation,
+                    accessibility_settings: &mut self.accessibility_settings,
                 };
 
                 match loading_result.result {
@@ -1795,6 +1903,9 @@ impl Engine {
         lag: &mut f32,
         switches: FxHashMap<Handle<Scene>, GraphUpdateSwitches>,
     ) {
+        self.simulation.begin_tick();
+        self.latency_tracker.mark(LatencyMarker::SimulationStart);
+        self.late_update_input.clear();
         self.resource_manager.state().update(dt);
         self.handle_model_events();
 
@@ -1821,6 +1932,7 @@ impl Engine {
                         }
                     });
 
+            scope_profile!("Scene Update");
             scene.update(
                 frame_size,
                 dt,
@@ -1828,8 +1940,13 @@ impl Engine {
             );
         }
 
-        self.update_plugins(dt, window_target, lag);
-        self.handle_scripts(dt);
+        if !self.is_embedded_play_paused() {
+            {
+                scope_profile!("Plugins Update");
+                self.update_plugins(dt, window_target, lag);
+            }
+            self.handle_scripts(dt);
+        }
     }
 
     /// Performs post update for the engine.
@@ -1843,19 +1960,26 @@ impl Engine {
         lag: &mut f32,
         window_target: &EventLoopWindowTarget<()>,
     ) {
+        self.apply_accessibility_settings();
+
         if let GraphicsContext::Initialized(ref ctx) = self.graphics_context {
             let inner_size = ctx.window.inner_size();
             let window_size = Vector2::new(inner_size.width as f32, inner_size.height as f32);
 
             let time = instant::Instant::now();
             for ui in self.user_interfaces.iter_mut() {
+                scope_profile!("UI Update");
                 ui.update(window_size, dt, ui_update_switches);
             }
             self.performance_statistics.ui_time = instant::Instant::now() - time;
             self.elapsed_time += dt;
 
-            self.post_update_plugins(dt, window_target, lag);
+            if !self.is_embedded_play_paused() {
+                self.post_update_plugins(dt, window_target, lag);
+            }
         }
+
+        self.latency_tracker.mark(LatencyMarker::SimulationEnd);
     }
 
     /// Returns true if the scene is registered for script processing.
@@ -1870,6 +1994,8 @@ impl Engine {
     }
 
     fn handle_scripts(&mut self, dt: f32) {
+        scope_profile!("Scripts");
+
         let time = instant::Instant::now();
 
         self.script_processor.handle_scripts(
@@ -1877,15 +2003,95 @@ impl Engine {
             &mut self.plugins,
             &self.resource_manager,
             &mut self.task_pool,
+            &mut self.simulation,
             &mut self.graphics_context,
             &mut self.user_interfaces,
             dt,
             self.elapsed_time,
         );
 
+        self.handle_script_coroutines(dt);
+
         self.performance_statistics.scripts_time = instant::Instant::now() - time;
     }
 
+    fn handle_script_coroutines(&mut self, dt: f32) {
+        // Take the coroutines out of the task pool, because they could spawn new coroutines while
+        // running.
+        let mut coroutines = std::mem::take(&mut self.task_pool.coroutines);
+
+        coroutines.retain_mut(|coroutine| {
+            let Some(scripted_scene) = self
+                .script_processor
+                .scripted_scenes
+                .iter_mut()
+                .find(|e| e.handle == coroutine.scene_handle)
+            else {
+                return false;
+            };
+            let Some(scene) = self.scenes.try_get_mut(coroutine.scene_handle) else {
+                return false;
+            };
+            let Some(node) = scene.graph.try_get_mut(coroutine.node_handle) else {
+                return false;
+            };
+            // The script could be removed or replaced by another script.
+            let Some(script_index) = coroutine.script_index(node) else {
+                return false;
+            };
+            let Some(mut script) = node.scripts[script_index].script.take() else {
+                return false;
+            };
+
+            let finished = coroutine.resume(
+                script.deref_mut(),
+                &mut ScriptContext {
+                    dt: scene.graph.time.node_dt(coroutine.node_handle, dt),
+                    elapsed_time: self.elapsed_time,
+                    plugins: PluginsRefMut(&mut self.plugins),
+                    handle: coroutine.node_handle,
+                    scene,
+                    scene_handle: scripted_scene.handle,
+                    resource_manager: &self.resource_manager,
+                    message_sender: &scripted_scene.message_sender,
+                    message_dispatcher: &mut scripted_scene.message_dispatcher,
+                    task_pool: &mut self.task_pool,
+                    simulation: &mut self.simulation,
+                    graphics_context: &mut self.graphics_context,
+                    user_interfaces: &mut self.user_interfaces,
+                    script_index,
+                    script_instance_id: coroutine.script_instance_id,
+                },
+            );
+
+            if let Some(entry) = scene
+                .graph
+                .try_get_mut(coroutine.node_handle)
+                .and_then(|node| node.scripts.get_mut(script_index))
+            {
+                if entry.should_be_deleted {
+                    Log::verify(scene.graph.script_message_sender.send(
+                        NodeScriptMessage::DestroyScript {
+                            script,
+                            handle: coroutine.node_handle,
+                            script_index,
+                        },
+                    ));
+                    return false;
+                } else {
+                    entry.script = Some(script);
+                }
+            } else {
+                return false;
+            }
+
+            !finished
+        });
+
+        coroutines.append(&mut self.task_pool.coroutines);
+        self.task_pool.coroutines = coroutines;
+    }
+
     fn handle_async_tasks(
         &mut self,
         dt: f32,
@@ -1913,6 +2119,8 @@ impl Engine {
                         async_scene_loader: &mut self.async_scene_loader,
                         window_target: Some(window_target),
                         task_pool: &mut self.task_pool,
+                        simulation: &mut self.simulation,
+                        accessibility_settings: &mut self.accessibility_settings,
                     },
                 )
             } else if let Some(node_task_handler) = self.task_pool.pop_node_task_handler(result.id)
@@ -1932,6 +2140,7 @@ impl Engine {
                                 .get_mut(node_task_handler.script_index)
                                 .and_then(|e| e.script.take())
                             {
+                                let script_instance_id = script.instance_id();
                                 (node_task_handler.closure)(
                                     payload,
                                     script.deref_mut(),
@@ -1946,9 +2155,11 @@ impl Engine {
                                         message_sender: &scripted_scene.message_sender,
                                         message_dispatcher: &mut scripted_scene.message_dispatcher,
                                         task_pool: &mut self.task_pool,
+                                        simulation: &mut self.simulation,
                                         graphics_context: &mut self.graphics_context,
                                         user_interfaces: &mut self.user_interfaces,
                                         script_index: node_task_handler.script_index,
+                                        script_instance_id,
                                     },
                                 );
 
@@ -2007,6 +2218,8 @@ impl Engine {
                 async_scene_loader: &mut self.async_scene_loader,
                 window_target: Some(window_target),
                 task_pool: &mut self.task_pool,
+                simulation: &mut self.simulation,
+                accessibility_settings: &mut self.accessibility_settings,
             };
 
             for plugin in self.plugins.iter_mut() {
@@ -2040,6 +2253,8 @@ impl Engine {
                         async_scene_loader: &mut self.async_scene_loader,
                         window_target: Some(window_target),
                         task_pool: &mut self.task_pool,
+                        simulation: &mut self.simulation,
+                        accessibility_settings: &mut self.accessibility_settings,
                     };
 
                     for plugin in self.plugins.iter_mut() {
@@ -2076,6 +2291,8 @@ impl Engine {
                 async_scene_loader: &mut self.async_scene_loader,
                 window_target: Some(window_target),
                 task_pool: &mut self.task_pool,
+                simulation: &mut self.simulation,
+                accessibility_settings: &mut self.accessibility_settings,
             };
 
             for plugin in self.plugins.iter_mut() {
@@ -2112,6 +2329,8 @@ impl Engine {
                         async_scene_loader: &mut self.async_scene_loader,
                         window_target: Some(window_target),
                         task_pool: &mut self.task_pool,
+                        simulation: &mut self.simulation,
+                        accessibility_settings: &mut self.accessibility_settings,
                     },
                 );
             }
@@ -2141,6 +2360,8 @@ impl Engine {
                     async_scene_loader: &mut self.async_scene_loader,
                     window_target: Some(window_target),
                     task_pool: &mut self.task_pool,
+                    simulation: &mut self.simulation,
+                    accessibility_settings: &mut self.accessibility_settings,
                 });
             }
         }
@@ -2169,9 +2390,72 @@ impl Engine {
                     async_scene_loader: &mut self.async_scene_loader,
                     window_target: Some(window_target),
                     task_pool: &mut self.task_pool,
+                    simulation: &mut self.simulation,
+                    accessibility_settings: &mut self.accessibility_settings,
+                });
+            }
+        }
+    }
+
+    /// Returns `true` if the graphics context is initialized, but it was lost (due to a driver reset,
+    /// WebGL context loss, etc.) and must be recreated. See [`Self::recover_graphics_context`].
+    pub fn is_graphics_context_lost(&self) -> bool {
+        if let GraphicsContext::Initialized(ref ctx) = self.graphics_context {
+            ctx.renderer.server.is_context_lost()
+        } else {
+            false
+        }
+    }
+
+    /// Recreates lost graphics context. The context is destroyed and then initialized again, every
+    /// GPU object (textures, buffers, programs) is then recreated from its source resource by the
+    /// new renderer on demand. Plugins are notified about the destruction and initialization of the
+    /// context as usual, and then [`Plugin::on_graphics_context_recovered`] is called. Does nothing
+    /// if the context is not lost. The executor calls this method automatically.
+    pub fn recover_graphics_context(
+        &mut self,
+        dt: f32,
+        window_target: &EventLoopWindowTarget<()>,
+        lag: &mut f32,
+    ) -> Result<(), EngineError> {
+        if !self.is_graphics_context_lost() {
+            return Ok(());
+        }
+
+        Log::warn("Graphics context was lost, trying to recreate it...");
+
+        self.destroy_graphics_context()?;
+        self.handle_graphics_context_destroyed_by_plugins(dt, window_target, lag);
+
+        self.initialize_graphics_context(window_target)?;
+        self.handle_graphics_context_created_by_plugins(dt, window_target, lag);
+
+        if self.plugins_enabled {
+            for plugin in self.plugins.iter_mut() {
+                plugin.on_graphics_context_recovered(PluginContext {
+                    scenes: &mut self.scenes,
+                    resource_manager: &self.resource_manager,
+                    graphics_context: &mut self.graphics_context,
+                    dt,
+                    lag,
+                    user_interfaces: &mut self.user_interfaces,
+                    serialization_context: &self.serialization_context,
+                    widget_constructors: &self.widget_constructors,
+                    performance_statistics: &self.performance_statistics,
+                    elapsed_time: self.elapsed_time,
+                    script_processor: &self.script_processor,
+                    async_scene_loader: &mut self.async_scene_loader,
+                    window_target: Some(window_target),
+                    task_pool: &mut self.task_pool,
+                    simulation: &mut self.simulation,
+                    accessibility_settings: &mut self.accessibility_settings,
                 });
             }
         }
+
+        Log::info("Graphics context was successfully recovered.");
+
+        Ok(())
     }
 
     pub(crate) fn handle_before_rendering_by_plugins(
@@ -2197,6 +2481,8 @@ impl Engine {
                     async_scene_loader: &mut self.async_scene_loader,
                     window_target: Some(window_target),
                     task_pool: &mut self.task_pool,
+                    simulation: &mut self.simulation,
+                    accessibility_settings: &mut self.accessibility_settings,
                 });
             }
         }
@@ -2231,6 +2517,7 @@ impl Engine {
                     &scripted_scene.message_sender,
                     &mut scripted_scene.message_dispatcher,
                     &mut self.task_pool,
+                    &mut self.simulation,
                     &mut self.graphics_context,
                     &mut self.user_interfaces,
                     dt,
@@ -2278,25 +2565,119 @@ impl Engine {
     /// see anything.
     #[inline]
     pub fn render(&mut self) -> Result<(), FrameworkError> {
+        if let Some(overlay) = self.profiler_overlay.as_ref() {
+            let statistics = match self.graphics_context {
+                GraphicsContext::Initialized(ref ctx) => Some(ctx.renderer.get_statistics()),
+                GraphicsContext::Uninitialized(_) => None,
+            };
+            overlay.update(self.user_interfaces.first_mut(), statistics.as_ref());
+        }
+
         for ui in self.user_interfaces.iter_mut() {
             ui.set_time(self.elapsed_time);
             ui.draw();
         }
 
+        self.late_update();
+
         if let GraphicsContext::Initialized(ref mut ctx) = self.graphics_context {
-            ctx.renderer.render_and_swap_buffers(
+            // There's no point to render anything using a lost context, it will be recreated on
+            // the next update (see `Self::recover_graphics_context`).
+            if ctx.renderer.server.is_context_lost() {
+                return Ok(());
+            }
+
+            if let Err(err) = ctx.renderer.render_and_swap_buffers(
                 &self.scenes,
                 self.elapsed_time,
                 self.user_interfaces
                     .iter()
                     .map(|ui| ui.get_drawing_context()),
                 &ctx.window,
-            )?;
+                &mut self.latency_tracker,
+            ) {
+                // Rendering errors are expected when the context was lost in the middle of the
+                // frame.
+                if !ctx.renderer.server.is_context_lost() {
+                    return Err(err);
+                }
+            }
         }
 
+        self.latency_tracker.end_frame();
+
+        profiler::end_frame();
+
+        // The watchdog uses the profiler record of the frame, so it must be called after the end
+        // of the profiler frame.
+        self.frame_watchdog.end_frame(
+            self.elapsed_time,
+            &self.scenes,
+            &self.resource_manager,
+            &self.graphics_context,
+            &self.performance_statistics,
+            &self.script_processor.profiler,
+        );
+
         Ok(())
     }
 
+    /// Shows or hides the profiler overlay (see [`ProfilerOverlay`] docs). The overlay is created in
+    /// the first user interface on first use. Keep in mind, that CPU timings are collected only
+    /// when `enable_profiler` feature is enabled.
+    pub fn set_profiler_overlay_visible(&mut self, visible: bool) {
+        let ui = self.user_interfaces.first_mut();
+        let overlay = self
+            .profiler_overlay
+            .get_or_insert_with(|| ProfilerOverlay::new(&mut ui.build_ctx()));
+        overlay.set_visible(ui, visible);
+    }
+
+    /// Sets new accessibility settings of the engine. The settings are applied immediately to the
+    /// renderer and every user interface of the engine. See [`AccessibilitySettings`] docs for
+    /// more info.
+    pub fn set_accessibility_settings(&mut self, settings: AccessibilitySettings) {
+        self.accessibility_settings = settings;
+        self.apply_accessibility_settings();
+    }
+
+    /// Returns current accessibility settings of the engine.
+    pub fn accessibility_settings(&self) -> &AccessibilitySettings {
+        &self.accessibility_settings
+    }
+
+    // The settings could be changed by plugins, so they're synced every frame. It is cheap, because
+    // user interfaces recalculate their layout only if the scale has changed.
+    fn apply_accessibility_settings(&mut self) {
+        for ui in self.user_interfaces.iter_mut() {
+            ui.set_scale(self.accessibility_settings.ui_scale);
+        }
+
+        if let GraphicsContext::Initialized(ref mut ctx) = self.graphics_context {
+            ctx.renderer
+                .set_color_blindness_settings(self.accessibility_settings.color_blindness);
+        }
+    }
+
+    /// Returns `true` if the profiler overlay is visible, `false` - otherwise.
+    pub fn is_profiler_overlay_visible(&self) -> bool {
+        self.profiler_overlay
+            .as_ref()
+            .is_some_and(|overlay| overlay.is_visible())
+    }
+
+    /// Runs late update callbacks of every enabled scene. See
+    /// [`crate::scene::late_update::LateUpdateCallbacks`] docs for more info.
+    fn late_update(&mut self) {
+        for (handle, scene) in self.scenes.pair_iter_mut().filter(|(_, s)| *s.enabled) {
+            if !scene.late_update.is_empty() {
+                scene
+                    .late_update
+                    .run(handle, &mut scene.graph, &self.late_update_input);
+            }
+        }
+    }
+
     /// Enables or disables registered plugins.
     pub(crate) fn enable_plugins(
         &mut self,
@@ -2327,6 +2708,8 @@ impl Engine {
                             async_scene_loader: &mut self.async_scene_loader,
                             window_target,
                             task_pool: &mut self.task_pool,
+                            simulation: &mut self.simulation,
+                            accessibility_settings: &mut self.accessibility_settings,
                         },
                     );
                 }
@@ -2350,6 +2733,8 @@ impl Engine {
                         async_scene_loader: &mut self.async_scene_loader,
                         window_target,
                         task_pool: &mut self.task_pool,
+                        simulation: &mut self.simulation,
+                        accessibility_settings: &mut self.accessibility_settings,
                     });
                 }
             }
@@ -2567,6 +2952,14 @@ impl Engine {
                     graphics_context.renderer.remove_render_pass(render_pass);
                 }
             }
+
+            let render_graph = graphics_context.renderer.render_graph_mut();
+            let render_graph_passes = render_graph.passes().to_vec();
+            for render_pass in render_graph_passes {
+                if render_pass.borrow().source_type_id() == plugin_type_id {
+                    render_graph.remove_pass(&render_pass);
+                }
+            }
         }
 
         let mut visitor = hotreload::make_writing_visitor();
@@ -2591,6 +2984,29 @@ impl Engine {
 
         let binary_blob = binary_blob.into_inner();
 
+        // Coroutines of the scripts from the plugin contain code from the plugin, so they must be
+        // destroyed before the plugin is unloaded. Such scripts will be re-created with new
+        // instance ids anyway. Coroutines of all other scripts are kept.
+        let coroutine_count = self.task_pool.coroutines.len();
+        let scenes = &self.scenes;
+        self.task_pool.coroutines.retain(|coroutine| {
+            scenes
+                .try_get(coroutine.scene_handle)
+                .and_then(|scene| scene.graph.try_get(coroutine.node_handle))
+                .and_then(|node| {
+                    let index = coroutine.script_index(node)?;
+                    node.scripts[index].script.as_ref()
+                })
+                .is_some_and(|script| script.assembly_name() != plugin_assembly_name)
+        });
+        let destroyed_coroutine_count = coroutine_count - self.task_pool.coroutines.len();
+        if destroyed_coroutine_count > 0 {
+            Log::warn(format!(
+                "{destroyed_coroutine_count} script coroutines were destroyed, because plugin \
+                {plugin_index} is reloading."
+            ));
+        }
+
         plugin.reload(&mut |plugin| {
             // Re-register the plugin. This is needed, because it might contain new script/node/widget
             // types (or removed ones too). This is done right before deserialization, because plugin
@@ -2610,9 +3026,28 @@ impl Engine {
             )
             .map_err(|e| e.to_string())?;
 
-            plugin
-                .visit("Plugin", &mut visitor)
+            if let Err(err) = plugin.visit("Plugin", &mut visitor) {
+                // The layout of the plugin was changed, try to restore as much as possible and
+                // keep default values for everything else.
+                Log::warn(format!(
+                    "Unable to deserialize plugin {plugin_index} as is, its layout was probably \
+                    changed. Trying to migrate its state... Reason: {err}"
+                ));
+
+                let mut visitor = hotreload::make_reading_visitor(
+                    &binary_blob,
+                    &self.serialization_context,
+                    &self.resource_manager,
+                    &self.widget_constructors,
+                )
                 .map_err(|e| e.to_string())?;
+                visitor.flags |= VisitorFlags::IGNORE_FIELD_ERRORS;
+
+                plugin
+                    .visit("Plugin", &mut visitor)
+                    .map_err(|e| e.to_string())?;
+            }
+
             Ok(())
         })?;
 
@@ -2655,6 +3090,8 @@ impl Engine {
             async_scene_loader: &mut self.async_scene_loader,
             window_target: Some(window_target),
             task_pool: &mut self.task_pool,
+            simulation: &mut self.simulation,
+            accessibility_settings: &mut self.accessibility_settings,
         });
 
         Log::info(format!("Plugin {plugin_index} was successfully reloaded!"));
@@ -2929,6 +3366,7 @@ mod test {
                 &mut Vec::new(),
                 &resource_manager,
                 &mut task_pool,
+                &mut Default::default(),
                 &mut gc,
                 &mut user_interfaces,
                 0.0,
@@ -3094,6 +3532,7 @@ mod test {
                 &mut Vec::new(),
                 &resource_manager,
                 &mut task_pool,
+                &mut Default::default(),
                 &mut gc,
                 &mut user_interfaces,
                 0.0,
@@ -3357,6 +3796,7 @@ mod test {
                 &mut Vec::new(),
                 &resource_manager,
                 &mut task_pool,
+                &mut Default::default(),
                 &mut gc,
                 &mut user_interfaces,
                 0.0,
@@ -0,0 +1,219 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! In-game overlay that shows the data collected by the frame profiler. See [`ProfilerOverlay`] docs
+//! for more info.
+
+use crate::{
+    core::{algebra::Vector2, color::Color, pool::Handle, profiler, profiler::FrameRecord},
+    gui::{
+        border::BorderBuilder,
+        brush::Brush,
+        message::MessageDirection,
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        vector_image::{Primitive, VectorImage, VectorImageBuilder},
+        widget::{WidgetBuilder, WidgetMessage},
+        BuildContext, HorizontalAlignment, Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+    renderer::Statistics,
+};
+use fxhash::FxHashMap;
+use std::{fmt::Write, time::Duration};
+
+const GRAPH_WIDTH: f32 = 300.0;
+const GRAPH_HEIGHT: f32 = 60.0;
+const GRAPH_FRAMES: usize = 150;
+// Frame time that corresponds to the full height of the graph.
+const GRAPH_MAX_FRAME_TIME: f32 = 1.0 / 30.0;
+
+/// In-game overlay that shows the frame graph, CPU and GPU timings of the last frame and renderer
+/// statistics (draw calls, texture memory, etc.). Use [`crate::engine::Engine::set_profiler_overlay_visible`]
+/// to show the overlay in the first user interface of the engine.
+pub struct ProfilerOverlay {
+    root: Handle<UiNode>,
+    text: Handle<UiNode>,
+    graph: Handle<UiNode>,
+    visible: bool,
+}
+
+fn millis(duration: Duration) -> f32 {
+    duration.as_secs_f32() * 1000.0
+}
+
+impl ProfilerOverlay {
+    /// Creates the overlay widgets. The overlay is hidden by default.
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let text = TextBuilder::new(
+            WidgetBuilder::new().with_foreground(Brush::Solid(Color::WHITE).into()),
+        )
+        .build(ctx);
+        let graph = VectorImageBuilder::new(
+            WidgetBuilder::new()
+                .with_width(GRAPH_WIDTH)
+                .with_height(GRAPH_HEIGHT)
+                .with_margin(Thickness::top(2.0))
+                .with_foreground(Brush::Solid(Color::opaque(80, 200, 80)).into()),
+        )
+        .build(ctx);
+        let root = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(false)
+                .with_hit_test_visibility(false)
+                .with_horizontal_alignment(HorizontalAlignment::Left)
+                .with_vertical_alignment(VerticalAlignment::Top)
+                .with_margin(Thickness::uniform(4.0))
+                .with_background(Brush::Solid(Color::from_rgba(0, 0, 0, 180)).into())
+                .with_child(
+                    StackPanelBuilder::new(
+                        WidgetBuilder::new()
+                            .with_margin(Thickness::uniform(4.0))
+                            .with_child(text)
+                            .with_child(graph),
+                    )
+                    .build(ctx),
+                ),
+        )
+        .build(ctx);
+
+        Self {
+            root,
+            text,
+            graph,
+            visible: false,
+        }
+    }
+
+    /// Returns `true` if the overlay is visible.
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Shows or hides the overlay. The frame profiler is enabled automatically when the overlay is
+    /// shown, but it is not disabled when the overlay is hidden.
+    pub fn set_visible(&mut self, ui: &UserInterface, visible: bool) {
+        self.visible = visible;
+        if visible {
+            profiler::set_enabled(true);
+        }
+        ui.send_message(WidgetMessage::visibility(
+            self.root,
+            MessageDirection::ToWidget,
+            visible,
+        ));
+    }
+
+    /// Updates the overlay using the last frame of the profiler and the given renderer statistics.
+    pub fn update(&self, ui: &mut UserInterface, statistics: Option<&Statistics>) {
+        if !self.visible {
+            return;
+        }
+
+        let Some(last_frame) = profiler::last_frame() else {
+            return;
+        };
+
+        ui.send_message(TextMessage::text(
+            self.text,
+            MessageDirection::ToWidget,
+            Self::describe(&last_frame, statistics),
+        ));
+
+        let primitives = profiler::with_history(|history| {
+            let bar_width = GRAPH_WIDTH / GRAPH_FRAMES as f32;
+            let scale = GRAPH_HEIGHT / GRAPH_MAX_FRAME_TIME;
+            let mut primitives = history
+                .iter()
+                .rev()
+                .take(GRAPH_FRAMES)
+                .enumerate()
+                .map(|(i, frame)| {
+                    let x = GRAPH_WIDTH - (i as f32 + 0.5) * bar_width;
+                    let height = (frame.duration.as_secs_f32() * scale).min(GRAPH_HEIGHT);
+                    Primitive::Line {
+                        begin: Vector2::new(x, GRAPH_HEIGHT),
+                        end: Vector2::new(x, GRAPH_HEIGHT - height),
+                        thickness: bar_width,
+                    }
+                })
+                .collect::<Vec<_>>();
+            // Mark the frame time of 60 FPS.
+            let target = GRAPH_HEIGHT - scale / 60.0;
+            primitives.push(Primitive::Line {
+                begin: Vector2::new(0.0, target),
+                end: Vector2::new(GRAPH_WIDTH, target),
+                thickness: 1.0,
+            });
+            primitives
+        });
+
+        if let Some(image) = ui
+            .try_get_node_mut(self.graph)
+            .and_then(|node| node.cast_mut::<VectorImage>())
+        {
+            image.primitives.set_value_and_mark_modified(primitives);
+        }
+    }
+
+    fn describe(frame: &FrameRecord, statistics: Option<&Statistics>) -> String {
+        let mut text = String::new();
+
+        let _ = writeln!(text, "Frame: {:.2} ms", millis(frame.duration));
+
+        // Sum the time of the scopes with the same name and depth, keeping the order of the first
+        // appearance.
+        let mut order = Vec::new();
+        let mut totals = FxHashMap::default();
+        for scope in frame.scopes.iter() {
+            let key = (scope.depth, scope.name);
+            *totals.entry(key).or_insert_with(|| {
+                order.push(key);
+                Duration::default()
+            }) += scope.duration;
+        }
+        if !order.is_empty() {
+            let _ = writeln!(text, "CPU:");
+            for key @ (depth, name) in order {
+                let indent = "  ".repeat(depth as usize + 1);
+                let _ = writeln!(text, "{indent}{name}: {:.2} ms", millis(totals[&key]));
+            }
+        }
+
+        if !frame.gpu_scopes.is_empty() {
+            let _ = writeln!(text, "GPU:");
+            for scope in frame.gpu_scopes.iter() {
+                let _ = writeln!(text, "  {}: {:.2} ms", scope.name, millis(scope.duration));
+            }
+        }
+
+        if let Some(statistics) = statistics {
+            let _ = writeln!(
+                text,
+                "FPS: {}\nDraw Calls: {}\nTriangles: {}\nTextures: {} ({:.2} Mb)",
+                statistics.frames_per_second,
+                statistics.geometry.draw_calls,
+                statistics.geometry.triangles_rendered,
+                statistics.texture_cache_size,
+                statistics.texture_memory_usage as f32 / (1024.0 * 1024.0),
+            );
+        }
+
+        text
+    }
+}
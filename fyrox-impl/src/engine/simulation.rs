@@ -0,0 +1,538 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Deterministic simulation mode and input recording/replaying. See [`Simulation`] docs for more
+//! info.
+
+use crate::{
+    core::{
+        algebra::Vector2,
+        rand::{Error, RngCore},
+        visitor::prelude::*,
+    },
+    event::{DeviceEvent, Event, MouseScrollDelta, WindowEvent},
+    gui::message::{ButtonState, KeyCode, MouseButton},
+    keyboard::PhysicalKey,
+    utils::{translate_button, translate_key_to_ui, translate_state},
+};
+use std::path::Path;
+
+/// A small and fast pseudo-random number generator (SplitMix64), that produces exactly the same
+/// sequence of numbers for the same seed on every platform. It implements [`RngCore`], so it can
+/// be used with every distribution from the `rand` crate.
+#[derive(Visit, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SimulationRng {
+    state: u64,
+}
+
+impl SimulationRng {
+    /// Creates a new generator with the given seed.
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+}
+
+impl RngCore for SimulationRng {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// Platform-independent input event, that can be recorded and replayed.
+#[derive(Visit, Clone, Debug, PartialEq)]
+pub enum InputEvent {
+    /// A key was pressed or released.
+    Key {
+        /// Code of the key.
+        code: KeyCode,
+        /// New state of the key.
+        state: ButtonState,
+    },
+    /// A mouse button was pressed or released.
+    MouseButton {
+        /// Mouse button.
+        button: MouseButton,
+        /// New state of the button.
+        state: ButtonState,
+    },
+    /// Raw mouse motion (not bound to the cursor position).
+    MouseMotion {
+        /// Motion delta.
+        delta: Vector2<f32>,
+    },
+    /// The cursor was moved inside the window.
+    CursorMoved {
+        /// New position of the cursor (in pixels).
+        position: Vector2<f32>,
+    },
+    /// Mouse wheel was rotated.
+    MouseWheel {
+        /// Wheel delta (in lines or pixels).
+        delta: Vector2<f32>,
+    },
+    /// Game-specific input, for example a command received from a remote peer in a lockstep
+    /// multiplayer game. The meaning of the fields is defined by the game.
+    Custom {
+        /// Kind of the input.
+        kind: u32,
+        /// Arbitrary data of the input.
+        payload: Vec<u8>,
+    },
+}
+
+impl Default for InputEvent {
+    fn default() -> Self {
+        Self::Custom {
+            kind: 0,
+            payload: Default::default(),
+        }
+    }
+}
+
+impl InputEvent {
+    /// Tries to convert an OS event into an input event. Returns `None` for events, that are not
+    /// related to the input.
+    pub fn from_os_event(event: &Event<()>) -> Option<Self> {
+        match event {
+            Event::WindowEvent { event, .. } => match event {
+                WindowEvent::KeyboardInput { event, .. } => {
+                    if let PhysicalKey::Code(code) = event.physical_key {
+                        Some(Self::Key {
+                            code: translate_key_to_ui(code),
+                            state: translate_state(event.state),
+                        })
+                    } else {
+                        None
+                    }
+                }
+                WindowEvent::MouseInput { state, button, .. } => Some(Self::MouseButton {
+                    button: translate_button(*button),
+                    state: translate_state(*state),
+                }),
+                WindowEvent::CursorMoved { position, .. } => Some(Self::CursorMoved {
+                    position: Vector2::new(position.x as f32, position.y as f32),
+                }),
+                WindowEvent::MouseWheel { delta, .. } => Some(Self::MouseWheel {
+                    delta: match delta {
+                        MouseScrollDelta::LineDelta(x, y) => Vector2::new(*x, *y),
+                        MouseScrollDelta::PixelDelta(pos) => {
+                            Vector2::new(pos.x as f32, pos.y as f32)
+                        }
+                    },
+                }),
+                _ => None,
+            },
+            Event::DeviceEvent {
+                event: DeviceEvent::MouseMotion { delta },
+                ..
+            } => Some(Self::MouseMotion {
+                delta: Vector2::new(delta.0 as f32, delta.1 as f32),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Input events of a single simulation tick.
+#[derive(Visit, Clone, Debug, Default, PartialEq)]
+pub struct ReplayTick {
+    /// Index of the tick.
+    pub tick: u64,
+    /// Input events of the tick, in the order of arrival.
+    pub events: Vec<InputEvent>,
+}
+
+/// A recorded session, that can be re-simulated. The replay stores only the seed of the random
+/// number generator, the time step and the input of the ticks, that had any input, so it is quite
+/// compact.
+#[derive(Visit, Clone, Debug, Default, PartialEq)]
+pub struct Replay {
+    /// Seed of the random number generator at the beginning of the session.
+    pub seed: u64,
+    /// Time step of the session (in seconds).
+    pub time_step: f32,
+    /// Total amount of recorded ticks.
+    pub tick_count: u64,
+    /// Input of the ticks, sorted by tick index.
+    pub ticks: Vec<ReplayTick>,
+}
+
+impl Replay {
+    /// Saves the replay into a file in binary format.
+    pub fn save(&mut self, path: impl AsRef<Path>) -> VisitResult {
+        let mut visitor = Visitor::new();
+        self.visit("Replay", &mut visitor)?;
+        visitor.save_binary(path)
+    }
+
+    /// Saves the replay into a vector of bytes in binary format.
+    pub fn save_to_vec(&mut self) -> Result<Vec<u8>, VisitError> {
+        let mut visitor = Visitor::new();
+        self.visit("Replay", &mut visitor)?;
+        visitor.save_binary_to_vec()
+    }
+
+    /// Loads a replay from the given data in binary format.
+    pub fn load_from_memory(data: &[u8]) -> Result<Self, VisitError> {
+        let mut visitor = Visitor::load_from_memory(data)?;
+        let mut replay = Replay::default();
+        replay.visit("Replay", &mut visitor)?;
+        Ok(replay)
+    }
+
+    /// Loads a replay from a file in binary format.
+    pub async fn load(path: impl AsRef<Path>) -> Result<Self, VisitError> {
+        let mut visitor = Visitor::load_binary(path).await?;
+        let mut replay = Replay::default();
+        replay.visit("Replay", &mut visitor)?;
+        Ok(replay)
+    }
+}
+
+#[derive(Debug)]
+enum SimulationState {
+    Free,
+    Deterministic,
+    Recording(Replay),
+    Replaying { replay: Replay, position: usize },
+}
+
+/// Simulation controls the way how the game logic (physics, scripts, plugins) is updated. By default,
+/// the simulation is in "free" mode, which means that the executor is allowed to change the time step
+/// to catch up with real time if the game runs too slow, input events are delivered to scripts and
+/// plugins as soon as they arrive and the random number generator is seeded arbitrarily.
+///
+/// In deterministic mode, the simulation always uses fixed time step, input events are buffered and
+/// delivered at the beginning of the next tick in the order of arrival (see [`Self::input`]) and
+/// the random number generator (see [`Self::rng`]) is seeded with a known value. This mode is meant
+/// to be used for lockstep multiplayer and for reproducing bugs. Game logic must use only these
+/// sources of input and randomness for the simulation to be reproducible.
+///
+/// Deterministic simulation can be recorded into a compact [`Replay`], which then can be used to
+/// re-simulate the session. Recording and replaying should be started from the same initial state
+/// of the game (for example, right after loading a level).
+///
+/// ```rust
+/// # use fyrox_impl::{engine::simulation::Simulation, core::rand::Rng};
+/// fn record(simulation: &mut Simulation) {
+///     simulation.start_recording(123, 1.0 / 60.0);
+///
+///     // Use the deterministic random number generator in the game logic.
+///     let _damage = simulation.rng().gen_range(10..20);
+///
+///     // ...
+///
+///     if let Some(mut replay) = simulation.stop_recording() {
+///         replay.save("session.replay").unwrap();
+///     }
+/// }
+/// ```
+///
+/// Keep in mind, that physics is deterministic across different platforms only if the physics
+/// engine is compiled with cross-platform determinism support.
+#[derive(Debug)]
+pub struct Simulation {
+    state: SimulationState,
+    time_step: f32,
+    tick: u64,
+    rng: SimulationRng,
+    pending_input: Vec<InputEvent>,
+    input: Vec<InputEvent>,
+}
+
+impl Default for Simulation {
+    fn default() -> Self {
+        Self {
+            state: SimulationState::Free,
+            time_step: 1.0 / 60.0,
+            tick: 0,
+            rng: SimulationRng::new(0),
+            pending_input: Default::default(),
+            input: Default::default(),
+        }
+    }
+}
+
+impl Simulation {
+    fn reset(&mut self, seed: u64, time_step: f32) {
+        self.time_step = time_step;
+        self.tick = 0;
+        self.rng = SimulationRng::new(seed);
+        self.pending_input.clear();
+        self.input.clear();
+    }
+
+    /// Switches the simulation to the deterministic mode with the given seed of the random number
+    /// generator and the fixed time step (in seconds). Stops recording or replaying (if any).
+    pub fn enable_deterministic_mode(&mut self, seed: u64, time_step: f32) {
+        self.reset(seed, time_step);
+        self.state = SimulationState::Deterministic;
+    }
+
+    /// Switches the simulation back to the free mode. Stops recording or replaying (if any).
+    pub fn disable_deterministic_mode(&mut self) {
+        self.state = SimulationState::Free;
+        self.pending_input.clear();
+        self.input.clear();
+    }
+
+    /// Returns `true` if the simulation is deterministic (it is also the case when a session is
+    /// being recorded or replayed).
+    pub fn is_deterministic(&self) -> bool {
+        !matches!(self.state, SimulationState::Free)
+    }
+
+    /// Switches the simulation to the deterministic mode and starts recording of the input.
+    pub fn start_recording(&mut self, seed: u64, time_step: f32) {
+        self.reset(seed, time_step);
+        self.state = SimulationState::Recording(Replay {
+            seed,
+            time_step,
+            tick_count: 0,
+            ticks: Default::default(),
+        });
+    }
+
+    /// Returns `true` if the simulation is being recorded.
+    pub fn is_recording(&self) -> bool {
+        matches!(self.state, SimulationState::Recording(_))
+    }
+
+    /// Stops recording and returns recorded replay. The simulation stays in deterministic mode.
+    /// Returns `None` if there was no recording.
+    pub fn stop_recording(&mut self) -> Option<Replay> {
+        if let SimulationState::Recording(replay) =
+            std::mem::replace(&mut self.state, SimulationState::Deterministic)
+        {
+            Some(replay)
+        } else {
+            None
+        }
+    }
+
+    /// Switches the simulation to the deterministic mode and starts replaying of the given replay.
+    /// Input events from the OS will be ignored until the replay is finished.
+    pub fn start_replay(&mut self, replay: Replay) {
+        self.reset(replay.seed, replay.time_step);
+        self.state = SimulationState::Replaying {
+            replay,
+            position: 0,
+        };
+    }
+
+    /// Returns `true` if the simulation is being replayed.
+    pub fn is_replaying(&self) -> bool {
+        matches!(self.state, SimulationState::Replaying { .. })
+    }
+
+    /// Returns `true` if the simulation is being replayed and all the recorded ticks were simulated.
+    pub fn is_replay_finished(&self) -> bool {
+        match self.state {
+            SimulationState::Replaying { ref replay, .. } => self.tick >= replay.tick_count,
+            _ => false,
+        }
+    }
+
+    /// Returns fixed time step of the deterministic mode (in seconds).
+    pub fn time_step(&self) -> f32 {
+        self.time_step
+    }
+
+    /// Returns index of the current tick (the amount of ticks since the simulation was switched to
+    /// deterministic mode).
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Returns a reference to the deterministic random number generator.
+    pub fn rng(&mut self) -> &mut SimulationRng {
+        &mut self.rng
+    }
+
+    /// Returns input events of the current tick, in the order of arrival. It is always empty in
+    /// the free mode.
+    pub fn input(&self) -> &[InputEvent] {
+        &self.input
+    }
+
+    /// Adds a new input event, that will be delivered at the beginning of the next tick. Could be
+    /// used to add game-specific input (see [`InputEvent::Custom`]). Does nothing in free mode and
+    /// when replaying.
+    pub fn push_input(&mut self, event: InputEvent) {
+        if matches!(
+            self.state,
+            SimulationState::Deterministic | SimulationState::Recording(_)
+        ) {
+            self.pending_input.push(event);
+        }
+    }
+
+    /// Buffers an input event from the OS. Returns `true` if the event was captured by the
+    /// simulation (or must be ignored because of replaying) and must not be delivered to scripts
+    /// and plugins directly, otherwise the input would bypass the tick order and the replay.
+    pub(crate) fn push_os_event(&mut self, event: &Event<()>) -> bool {
+        if !self.is_deterministic() {
+            return false;
+        }
+
+        match InputEvent::from_os_event(event) {
+            Some(event) => {
+                self.push_input(event);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Starts a new tick of the deterministic simulation, makes the input of the tick available
+    /// via [`Self::input`].
+    pub(crate) fn begin_tick(&mut self) {
+        self.input.clear();
+
+        match self.state {
+            SimulationState::Free => return,
+            SimulationState::Deterministic => {
+                std::mem::swap(&mut self.input, &mut self.pending_input);
+            }
+            SimulationState::Recording(ref mut replay) => {
+                std::mem::swap(&mut self.input, &mut self.pending_input);
+                if !self.input.is_empty() {
+                    replay.ticks.push(ReplayTick {
+                        tick: self.tick,
+                        events: self.input.clone(),
+                    });
+                }
+                replay.tick_count = self.tick + 1;
+            }
+            SimulationState::Replaying {
+                ref replay,
+                ref mut position,
+            } => {
+                if let Some(tick) = replay.ticks.get(*position) {
+                    if tick.tick == self.tick {
+                        self.input.extend_from_slice(&tick.events);
+                        *position += 1;
+                    }
+                }
+            }
+        }
+
+        self.tick += 1;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Vector2, rand::Rng},
+        engine::simulation::{InputEvent, Replay, Simulation, SimulationRng},
+        event::{DeviceEvent, DeviceId, Event},
+    };
+
+    #[test]
+    fn test_simulation_rng_is_reproducible() {
+        let mut a = SimulationRng::new(42);
+        let mut b = SimulationRng::new(42);
+        for _ in 0..100 {
+            assert_eq!(a.gen_range(0..1000), b.gen_range(0..1000));
+        }
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        let mut simulation = Simulation::default();
+        simulation.start_recording(7, 1.0 / 30.0);
+
+        let mut recorded_numbers = Vec::new();
+        let mut recorded_input = Vec::new();
+        for i in 0..10 {
+            if i % 3 == 0 {
+                simulation.push_input(InputEvent::MouseMotion {
+                    delta: Vector2::new(i as f32, 0.0),
+                });
+            }
+            simulation.begin_tick();
+            recorded_input.push(simulation.input().to_vec());
+            recorded_numbers.push(simulation.rng().gen::<u32>());
+        }
+
+        let mut replay = simulation.stop_recording().unwrap();
+        assert_eq!(replay.tick_count, 10);
+        assert_eq!(replay.ticks.len(), 4);
+
+        let data = replay.save_to_vec().unwrap();
+        let replay = Replay::load_from_memory(&data).unwrap();
+
+        simulation.start_replay(replay);
+        for i in 0..10 {
+            // Live input must be ignored while replaying.
+            simulation.push_input(InputEvent::default());
+            simulation.begin_tick();
+            assert_eq!(simulation.input(), recorded_input[i].as_slice());
+            assert_eq!(simulation.rng().gen::<u32>(), recorded_numbers[i]);
+        }
+        assert!(simulation.is_replay_finished());
+    }
+
+    #[test]
+    fn test_os_input_is_captured_only_in_deterministic_mode() {
+        let event = Event::DeviceEvent {
+            // SAFETY: The id is used only for comparison.
+            device_id: unsafe { DeviceId::dummy() },
+            event: DeviceEvent::MouseMotion { delta: (1.0, 2.0) },
+        };
+        let non_input = Event::AboutToWait;
+
+        let mut simulation = Simulation::default();
+        assert!(!simulation.push_os_event(&event));
+        assert!(!simulation.push_os_event(&non_input));
+
+        simulation.enable_deterministic_mode(0, 1.0 / 60.0);
+        assert!(simulation.push_os_event(&event));
+        assert!(!simulation.push_os_event(&non_input));
+        simulation.begin_tick();
+        assert_eq!(simulation.input().len(), 1);
+
+        // Live input must not reach the game logic while replaying.
+        simulation.start_replay(Replay::default());
+        assert!(simulation.push_os_event(&event));
+        simulation.begin_tick();
+        assert!(simulation.input().is_empty());
+    }
+}
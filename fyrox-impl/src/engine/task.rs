@@ -29,10 +29,13 @@ use crate::{
     },
     plugin::{Plugin, PluginContext},
     scene::{node::Node, Scene},
-    script::{ScriptContext, ScriptTrait},
+    script::{
+        coroutine::{CoroutineContext, ScriptCoroutine},
+        ScriptContext, ScriptTrait,
+    },
 };
 use fxhash::FxHashMap;
-use std::sync::Arc;
+use std::{future::Future, sync::Arc};
 
 pub(crate) type NodeTaskHandlerClosure = Box<
     dyn for<'a, 'b, 'c> Fn(
@@ -80,6 +83,7 @@ pub struct TaskPoolHandler {
     task_pool: Arc<TaskPool>,
     plugin_task_handlers: FxHashMap<Uuid, PluginTaskHandler>,
     node_task_handlers: FxHashMap<Uuid, NodeTaskHandler>,
+    pub(crate) coroutines: Vec<ScriptCoroutine>,
 }
 
 impl TaskPoolHandler {
@@ -88,6 +92,7 @@ impl TaskPoolHandler {
             task_pool,
             plugin_task_handlers: Default::default(),
             node_task_handlers: Default::default(),
+            coroutines: Default::default(),
         }
     }
 
@@ -239,6 +244,35 @@ impl TaskPoolHandler {
         );
     }
 
+    /// Spawns a coroutine for a script of a scene node. Unlike tasks, coroutines are executed
+    /// cooperatively on the main thread, just after the scripts were updated. A coroutine can wait
+    /// for timers, next frames, arbitrary conditions and any other futures (such as resources). It
+    /// also has full access to the script and its context using [`CoroutineContext::with`]. See
+    /// [`CoroutineContext`] docs for more info and usage examples.
+    ///
+    /// The coroutine is bound to the script instance with the given id (see
+    /// [`ScriptContext::script_instance_id`]). It is destroyed when it finishes, when its script is
+    /// removed or replaced by another one, or when its node is destroyed.
+    #[inline]
+    pub fn spawn_script_coroutine<S, F, Fut>(
+        &mut self,
+        scene_handle: Handle<Scene>,
+        node_handle: Handle<Node>,
+        script_instance_id: Uuid,
+        func: F,
+    ) where
+        S: ScriptTrait,
+        F: FnOnce(CoroutineContext<S>) -> Fut,
+        Fut: Future<Output = ()> + 'static,
+    {
+        self.coroutines.push(ScriptCoroutine::new(
+            scene_handle,
+            node_handle,
+            script_instance_id,
+            func,
+        ));
+    }
+
     /// Returns a reference to the underlying, low level task pool, that could be used to for special
     /// cases.
     #[inline]
@@ -0,0 +1,449 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Frame watchdog is used to catch long frames (hitches) and dump diagnostic information about
+//! them. See [`FrameWatchdog`] docs for more info.
+
+use crate::{
+    asset::manager::ResourceManager,
+    core::{
+        instant::Instant,
+        log::Log,
+        profiler::{self, FrameRecord},
+    },
+    engine::{GraphicsContext, PerformanceStatistics},
+    scene::SceneContainer,
+    script::profiler::ScriptProfiler,
+};
+use std::{
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// Diagnostic information about a scene at the moment of a long frame.
+#[derive(Clone, Debug, Default)]
+pub struct SceneDiagnostics {
+    /// Total amount of nodes in the scene.
+    pub node_count: u32,
+    /// Scene update statistics for the last frame.
+    pub statistics: String,
+}
+
+/// A snapshot of the engine state, that was captured right after a long frame.
+#[derive(Clone, Debug, Default)]
+pub struct FrameDiagnostics {
+    /// Duration of the long frame.
+    pub frame_time: Duration,
+    /// Threshold of the watchdog at the moment of capturing.
+    pub threshold: Duration,
+    /// Amount of time (in seconds) that passed from creation of the engine.
+    pub elapsed_time: f32,
+    /// Engine statistics (UI, scripts, plugins timings) for the last update.
+    pub engine_statistics: String,
+    /// Per-scene diagnostics.
+    pub scenes: Vec<SceneDiagnostics>,
+    /// Change in the total amount of scene nodes since the previous frame. Large positive values
+    /// usually mean that there was a spike of allocations (mass instantiation, for example).
+    pub node_count_delta: i64,
+    /// Renderer statistics for the last frame, if there's a graphics context.
+    pub renderer_statistics: Option<String>,
+    /// Script profiler report, if the profiler is enabled.
+    pub script_timings: Option<String>,
+    /// Total time of the top-level scopes of the frame profiler (see [`crate::core::profiler`]),
+    /// sorted from the longest to the shortest. Empty if the frame profiler is disabled.
+    pub profiler_scopes: Vec<(&'static str, Duration)>,
+    /// Paths of the resources that are still loading.
+    pub pending_resources: Vec<String>,
+    /// Total amount of resources registered in the resource manager.
+    pub registered_resources: usize,
+    /// Change in the amount of registered resources since the previous frame. Negative values mean
+    /// that the resource manager has destroyed some unused resources during the frame.
+    pub registered_resources_delta: i64,
+    /// Amount of unused resources, that were destroyed by the resource manager during the frame.
+    pub destroyed_resources: u64,
+}
+
+impl Display for FrameDiagnostics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Long frame detected at {:.3}s: {:?} (threshold is {:?})",
+            self.elapsed_time, self.frame_time, self.threshold
+        )?;
+        writeln!(f, "{}", self.engine_statistics)?;
+        for (i, scene) in self.scenes.iter().enumerate() {
+            writeln!(
+                f,
+                "Scene #{i} ({} nodes):\n{}",
+                scene.node_count, scene.statistics
+            )?;
+        }
+        writeln!(f, "Node Count Delta: {:+}", self.node_count_delta)?;
+        if let Some(renderer_statistics) = self.renderer_statistics.as_ref() {
+            writeln!(f, "{renderer_statistics}")?;
+        }
+        if let Some(script_timings) = self.script_timings.as_ref() {
+            writeln!(f, "{script_timings}")?;
+        }
+        if !self.profiler_scopes.is_empty() {
+            writeln!(f, "Profiler Scopes:")?;
+            for (name, duration) in self.profiler_scopes.iter() {
+                writeln!(f, "\t{name}: {duration:?}")?;
+            }
+        }
+        writeln!(
+            f,
+            "Resources: {} registered ({:+} since previous frame), {} destroyed, {} loading",
+            self.registered_resources,
+            self.registered_resources_delta,
+            self.destroyed_resources,
+            self.pending_resources.len()
+        )?;
+        for path in self.pending_resources.iter() {
+            writeln!(f, "\tLoading: {path}")?;
+        }
+        Ok(())
+    }
+}
+
+fn top_level_scopes(frame: &FrameRecord) -> Vec<(&'static str, Duration)> {
+    let mut scopes = Vec::<(&'static str, Duration)>::new();
+    for scope in frame.scopes.iter().filter(|scope| scope.depth == 0) {
+        match scopes.iter_mut().find(|(name, _)| *name == scope.name) {
+            Some((_, duration)) => *duration += scope.duration,
+            None => scopes.push((scope.name, scope.duration)),
+        }
+    }
+    scopes.sort_by(|(_, a), (_, b)| b.cmp(a));
+    scopes
+}
+
+/// Frame watchdog measures the time between two consecutive rendered frames and, when it exceeds
+/// the threshold, captures a [`FrameDiagnostics`] snapshot and writes it to the log (and optionally
+/// to a file). It helps to diagnose intermittent hitches in shipped games using player reports.
+/// The watchdog is disabled by default.
+///
+/// ```rust
+/// # use fyrox_impl::engine::Engine;
+/// # use std::time::Duration;
+/// fn enable_watchdog(engine: &mut Engine) {
+///     let watchdog = &mut engine.frame_watchdog;
+///     watchdog.set_enabled(true);
+///     watchdog.set_threshold(Duration::from_millis(50));
+///     watchdog.set_dump_path(Some("hitches.log".into()));
+/// }
+/// ```
+///
+/// Captured snapshots contain only the information that the engine tracks anyway, so the script
+/// timings will be included only if the script profiler is enabled.
+#[derive(Debug)]
+pub struct FrameWatchdog {
+    enabled: bool,
+    threshold: Duration,
+    cooldown: Duration,
+    dump_path: Option<PathBuf>,
+    frame_start: Option<Instant>,
+    last_dump: Option<Instant>,
+    last_node_count: u64,
+    last_resource_count: usize,
+    last_destroyed_resource_count: u64,
+    dump_count: usize,
+    last_diagnostics: Option<FrameDiagnostics>,
+}
+
+impl Default for FrameWatchdog {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold: Duration::from_millis(100),
+            cooldown: Duration::from_secs(5),
+            dump_path: None,
+            frame_start: None,
+            last_dump: None,
+            last_node_count: 0,
+            last_resource_count: 0,
+            last_destroyed_resource_count: 0,
+            dump_count: 0,
+            last_diagnostics: None,
+        }
+    }
+}
+
+impl FrameWatchdog {
+    /// Enables or disables the watchdog.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        self.frame_start = None;
+    }
+
+    /// Returns `true` if the watchdog is enabled, `false` - otherwise.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Sets the maximum duration of a frame. Every frame that takes longer will be reported.
+    pub fn set_threshold(&mut self, threshold: Duration) {
+        self.threshold = threshold;
+    }
+
+    /// Returns current threshold of the watchdog.
+    pub fn threshold(&self) -> Duration {
+        self.threshold
+    }
+
+    /// Sets minimal amount of time between two consecutive dumps. It prevents the log from being
+    /// flooded when the game is constantly running slow.
+    pub fn set_cooldown(&mut self, cooldown: Duration) {
+        self.cooldown = cooldown;
+    }
+
+    /// Returns current cooldown of the watchdog.
+    pub fn cooldown(&self) -> Duration {
+        self.cooldown
+    }
+
+    /// Sets a path to a file, to which every captured snapshot will be appended. Does nothing on
+    /// WebAssembly, snapshots are written only to the log there.
+    pub fn set_dump_path(&mut self, path: Option<PathBuf>) {
+        self.dump_path = path;
+    }
+
+    /// Returns current dump path of the watchdog.
+    pub fn dump_path(&self) -> Option<&Path> {
+        self.dump_path.as_deref()
+    }
+
+    /// Returns total amount of captured snapshots.
+    pub fn dump_count(&self) -> usize {
+        self.dump_count
+    }
+
+    /// Returns the last captured snapshot (if any).
+    pub fn last_diagnostics(&self) -> Option<&FrameDiagnostics> {
+        self.last_diagnostics.as_ref()
+    }
+
+    /// Marks the end of a frame at the given moment. Returns the duration of the frame if it is
+    /// longer than the threshold and the cooldown has passed.
+    fn check_frame(&mut self, now: Instant) -> Option<Duration> {
+        let frame_start = self.frame_start.replace(now)?;
+        let frame_time = now.saturating_duration_since(frame_start);
+        if frame_time <= self.threshold {
+            return None;
+        }
+        if let Some(last_dump) = self.last_dump {
+            if now.saturating_duration_since(last_dump) < self.cooldown {
+                return None;
+            }
+        }
+        self.last_dump = Some(now);
+        Some(frame_time)
+    }
+
+    pub(crate) fn end_frame(
+        &mut self,
+        elapsed_time: f32,
+        scenes: &SceneContainer,
+        resource_manager: &ResourceManager,
+        graphics_context: &GraphicsContext,
+        performance_statistics: &PerformanceStatistics,
+        script_profiler: &ScriptProfiler,
+    ) {
+        if !self.enabled {
+            return;
+        }
+
+        let node_count = scenes
+            .iter()
+            .map(|scene| scene.graph.node_count() as u64)
+            .sum::<u64>();
+        let node_count_delta = node_count as i64 - self.last_node_count as i64;
+        self.last_node_count = node_count;
+
+        let (registered_resources, destroyed_resource_count) = {
+            let state = resource_manager.state();
+            (state.len(), state.destroyed_resource_count())
+        };
+        let registered_resources_delta =
+            registered_resources as i64 - self.last_resource_count as i64;
+        self.last_resource_count = registered_resources;
+        let destroyed_resources =
+            destroyed_resource_count.saturating_sub(self.last_destroyed_resource_count);
+        self.last_destroyed_resource_count = destroyed_resource_count;
+
+        // Only the counters above are tracked on every frame, everything else is gathered when a
+        // long frame is detected.
+        let Some(frame_time) = self.check_frame(Instant::now()) else {
+            return;
+        };
+
+        let pending_resources = resource_manager
+            .state()
+            .iter()
+            .filter(|resource| resource.is_loading())
+            .map(|resource| resource.kind().to_string())
+            .collect::<Vec<_>>();
+
+        // The engine ends the profiler frame before the watchdog, so the last frame record is the
+        // long frame.
+        let profiler_scopes = if profiler::is_enabled() {
+            profiler::last_frame()
+                .map(|frame| top_level_scopes(&frame))
+                .unwrap_or_default()
+        } else {
+            Default::default()
+        };
+
+        let diagnostics = FrameDiagnostics {
+            frame_time,
+            threshold: self.threshold,
+            elapsed_time,
+            engine_statistics: performance_statistics.to_string(),
+            scenes: scenes
+                .iter()
+                .map(|scene| SceneDiagnostics {
+                    node_count: scene.graph.node_count(),
+                    statistics: scene.performance_statistics.to_string(),
+                })
+                .collect(),
+            node_count_delta,
+            renderer_statistics: if let GraphicsContext::Initialized(ctx) = graphics_context {
+                Some(ctx.renderer.get_statistics().to_string())
+            } else {
+                None
+            },
+            script_timings: script_profiler
+                .is_enabled()
+                .then(|| script_profiler.report().to_string()),
+            profiler_scopes,
+            pending_resources,
+            registered_resources,
+            registered_resources_delta,
+            destroyed_resources,
+        };
+
+        let text = diagnostics.to_string();
+
+        Log::warn(text.as_str());
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(dump_path) = self.dump_path.as_ref() {
+            use std::io::Write;
+
+            let result = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(dump_path)
+                .and_then(|mut file| writeln!(file, "{text}"));
+            if let Err(err) = result {
+                Log::err(format!(
+                    "Unable to write frame diagnostics to {}. Reason: {err}",
+                    dump_path.display()
+                ));
+            }
+        }
+
+        self.dump_count += 1;
+        self.last_diagnostics = Some(diagnostics);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            instant::Instant,
+            profiler::{FrameRecord, ScopeRecord},
+        },
+        engine::watchdog::{top_level_scopes, FrameDiagnostics, FrameWatchdog},
+    };
+    use std::time::Duration;
+
+    fn scope(name: &'static str, depth: u32, duration_ms: u64) -> ScopeRecord {
+        ScopeRecord {
+            name,
+            thread: 0,
+            depth,
+            start: Default::default(),
+            duration: Duration::from_millis(duration_ms),
+        }
+    }
+
+    #[test]
+    fn test_top_level_scopes() {
+        let frame = FrameRecord {
+            scopes: vec![
+                scope("Update", 0, 10),
+                scope("Physics", 1, 8),
+                scope("Render", 0, 30),
+                scope("Update", 0, 5),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            top_level_scopes(&frame),
+            [
+                ("Render", Duration::from_millis(30)),
+                ("Update", Duration::from_millis(15))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diagnostics_report() {
+        let diagnostics = FrameDiagnostics {
+            profiler_scopes: vec![("Render", Duration::from_millis(30))],
+            registered_resources: 10,
+            registered_resources_delta: -2,
+            destroyed_resources: 3,
+            pending_resources: vec!["foo.png".to_string()],
+            ..Default::default()
+        };
+        let text = diagnostics.to_string();
+        assert!(text.contains("\tRender: 30ms"));
+        assert!(text.contains("10 registered (-2 since previous frame), 3 destroyed, 1 loading"));
+        assert!(text.contains("\tLoading: foo.png"));
+    }
+
+    #[test]
+    fn test_long_frame_detection() {
+        let mut watchdog = FrameWatchdog::default();
+        watchdog.set_threshold(Duration::from_millis(100));
+        watchdog.set_cooldown(Duration::from_secs(1));
+
+        let start = Instant::now();
+
+        // The first frame has no start.
+        assert_eq!(watchdog.check_frame(start), None);
+
+        let t = start + Duration::from_millis(50);
+        assert_eq!(watchdog.check_frame(t), None);
+
+        let t = t + Duration::from_millis(200);
+        assert_eq!(watchdog.check_frame(t), Some(Duration::from_millis(200)));
+
+        // Cooldown.
+        let t = t + Duration::from_millis(300);
+        assert_eq!(watchdog.check_frame(t), None);
+
+        let t = t + Duration::from_millis(800);
+        assert_eq!(watchdog.check_frame(t), Some(Duration::from_millis(800)));
+    }
+}
@@ -35,9 +35,11 @@
 
 pub mod engine;
 pub mod material;
+pub mod net;
 pub mod plugin;
 pub mod renderer;
 pub mod resource;
+pub mod save;
 pub mod scene;
 pub mod script;
 pub mod utils;
@@ -44,7 +44,7 @@ use crate::{
         TypeUuidProvider,
     },
     material::shader::{SamplerFallback, ShaderResource, ShaderResourceExtension},
-    resource::texture::TextureResource,
+    resource::texture::{TextureResource, TextureSampler},
 };
 use fxhash::FxHashMap;
 use fyrox_core::Downcast;
@@ -67,6 +67,26 @@ pub struct MaterialTextureBinding {
     /// Actual value of the texture binding. Could be [`None`], in this case fallback value of the
     /// shader will be used.
     pub value: Option<TextureResource>,
+    /// Optional sampler of the texture binding. If set, it overrides the sampling parameters of
+    /// the texture for this binding only, the texture resource itself stays unchanged.
+    #[visit(optional)]
+    pub sampler: Option<TextureSampler>,
+}
+
+impl MaterialTextureBinding {
+    /// Creates a new texture binding without a sampler.
+    pub fn new(value: Option<TextureResource>) -> Self {
+        Self {
+            value,
+            sampler: None,
+        }
+    }
+
+    /// Sets a sampler of the texture binding.
+    pub fn with_sampler(mut self, sampler: TextureSampler) -> Self {
+        self.sampler = Some(sampler);
+        self
+    }
 }
 
 /// A value of a resource binding that will be used for rendering.
@@ -410,13 +430,13 @@ impl_from_ref!(Color => Color);
 
 impl From<Option<TextureResource>> for MaterialResourceBinding {
     fn from(value: Option<TextureResource>) -> Self {
-        Self::Texture(MaterialTextureBinding { value })
+        Self::Texture(MaterialTextureBinding::new(value))
     }
 }
 
 impl From<TextureResource> for MaterialResourceBinding {
     fn from(value: TextureResource) -> Self {
-        Self::Texture(MaterialTextureBinding { value: Some(value) })
+        Self::Texture(MaterialTextureBinding::new(Some(value)))
     }
 }
 
@@ -693,6 +713,7 @@ impl Visit for Material {
                             name.clone(),
                             MaterialResourceBinding::Texture(MaterialTextureBinding {
                                 value: value.clone(),
+                                sampler: None,
                             }),
                         )
                     }
@@ -1012,6 +1033,22 @@ impl Material {
         }
     }
 
+    /// Sets a sampler of the texture binding with the given name. The sampler overrides the
+    /// sampling parameters of the texture for this material only. Returns `false` if there's no
+    /// texture binding with the given name.
+    pub fn set_texture_sampler(
+        &mut self,
+        name: impl Into<ImmutableString>,
+        sampler: Option<TextureSampler>,
+    ) -> bool {
+        if let Some(binding) = self.texture_mut(name) {
+            binding.sampler = sampler;
+            true
+        } else {
+            false
+        }
+    }
+
     /// Searches for a property group binding with the given name and returns immutable reference to it
     /// (if any).
     ///
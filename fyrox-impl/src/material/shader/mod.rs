@@ -204,7 +204,9 @@
 //!
 //! - `GBuffer` - A pass that fills a set of textures (render targets) with various data about each
 //! rendered object (depth, normal, albedo, etc.). These textures then are used for physically-based
-//! lighting. Use this pass when you want the standard lighting to work with your objects.
+//! lighting. Use this pass when you want the standard lighting to work with your objects. The pass
+//! should also write screen-space motion vectors to the sixth render target, see the standard shader
+//! for an example. Objects without motion vectors are reprojected using camera motion only.
 //!
 //! - `Forward` - A pass that draws an object directly in a render target. It could be used to render
 //! translucent objects.
@@ -260,10 +262,29 @@
 //! |----------------------|------------|---------------------------------------------|
 //! | worldMatrix          | `mat4`     | Local-to-world transformation.              |
 //! | worldViewProjection  | `mat4`     | Local-to-clip-space transform.              |
+//! | previousWorldViewProjection | `mat4` | Local-to-clip-space transform of the previous frame. |
+//! | customData           | `vec4`     | Custom data of the instance (see [`crate::scene::mesh::Mesh::instance_custom_data`]). |
+//! | lodFade              | `float`    | Visibility of the instance in LOD transition (see [`crate::scene::base::LodGroup::visibility`]), use `S_LodFadeDiscard` to apply it. |
 //! | blendShapesCount     | `int`      | Total amount of blend shapes.               |
 //! | useSkeletalAnimation | `bool`     | Whether skinned meshes is rendering or not. |
 //! | blendShapesWeights   | `vec4[32]` | Blend shape weights.                        |
 //!
+//! ### `fyrox_instances`
+//!
+//! Property group. Contains data of every instance drawn by an instanced draw call, use
+//! `gl_InstanceID` to index the arrays. If a shader defines this group, the renderer groups the
+//! instances of the same surface with the same material and draws up to 64 instances in a single
+//! draw call. Instances with skeletal animation or blend shapes are always drawn one-by-one, so
+//! `fyrox_boneMatrices` and blend shape data of `fyrox_instanceData` remain valid for them. In an
+//! instanced draw call, `fyrox_instanceData` contains the data of the first instance.
+//!
+//! | Name                         | Type       | Description                                         |
+//! |------------------------------|------------|-----------------------------------------------------|
+//! | worldMatrices                | `mat4[64]` | Local-to-world transformation.                      |
+//! | worldViewProjections         | `mat4[64]` | Local-to-clip-space transform.                      |
+//! | previousWorldViewProjections | `mat4[64]` | Local-to-clip-space transform of the previous frame.|
+//! | customData                   | `vec4[64]` | Custom data of the instance.                        |
+//!
 //! ### `fyrox_boneMatrices`
 //!
 //! Property group. Provided for each rendered surface, that has skeletal animation.
@@ -272,6 +293,16 @@
 //! |----------|-------------|---------------|
 //! | matrices | `mat4[256]` | Bone matrices |
 //!
+//! ### `fyrox_previousBoneMatrices`
+//!
+//! Property group. The same as `fyrox_boneMatrices`, but contains bone matrices of the previous
+//! frame. Available only in `GBuffer` pass, it is used to calculate motion vectors of skinned
+//! meshes.
+//!
+//! | Name     | Type        | Description   |
+//! |----------|-------------|---------------|
+//! | matrices | `mat4[256]` | Bone matrices |
+//!
 //!
 //! ### `fyrox_cameraData`
 //!
@@ -288,6 +319,7 @@
 //! | zNear                | `float`    | Near clipping plane location.                    |
 //! | zFar                 | `float`    | Far clipping plane location.                     |
 //! | zRange               | `float`    | `zFar - zNear`                                   |
+//! | jitter               | `vec2`     | Sub-pixel projection offset in NDC (see TAA).    |
 //!
 //! ### `fyrox_lightData`
 //!
@@ -599,6 +631,9 @@ impl ShaderDefinition {
     /// Maximum amount of blend shape weight groups (packed weights of blend shapes into vec4).
     pub const MAX_BLEND_SHAPE_WEIGHT_GROUPS: usize = 32;
 
+    /// Maximum amount of instances that could be drawn in a single instanced draw call.
+    pub const MAX_INSTANCES: usize = 64;
+
     fn from_buf(buf: Vec<u8>) -> Result<Self, ShaderError> {
         let mut definition: ShaderDefinition = ron::de::from_reader(Cursor::new(buf))?;
         definition.generate_built_in_resources();
@@ -649,6 +684,12 @@ impl ShaderDefinition {
                         ShaderProperty::new("zNear", Float { value: 0.0 }),
                         ShaderProperty::new("zFar", Float { value: 0.0 }),
                         ShaderProperty::new("zRange", Float { value: 0.0 }),
+                        ShaderProperty::new(
+                            "jitter",
+                            Vector2 {
+                                value: Default::default(),
+                            },
+                        ),
                     ]);
                 }
                 "fyrox_lightData" => {
@@ -721,6 +762,19 @@ impl ShaderDefinition {
                                 value: algebra::Matrix4::identity(),
                             },
                         ),
+                        ShaderProperty::new(
+                            "previousWorldViewProjection",
+                            Matrix4 {
+                                value: algebra::Matrix4::identity(),
+                            },
+                        ),
+                        ShaderProperty::new(
+                            "customData",
+                            Vector4 {
+                                value: Default::default(),
+                            },
+                        ),
+                        ShaderProperty::new("lodFade", Float { value: 1.0 }),
                         ShaderProperty::new("blendShapesCount", Int { value: 0 }),
                         ShaderProperty::new("useSkeletalAnimation", Bool { value: false }),
                         ShaderProperty::new(
@@ -732,7 +786,40 @@ impl ShaderDefinition {
                         ),
                     ]);
                 }
-                "fyrox_boneMatrices" => {
+                "fyrox_instances" => {
+                    properties.clear();
+                    properties.extend([
+                        ShaderProperty::new(
+                            "worldMatrices",
+                            Matrix4Array {
+                                value: Default::default(),
+                                max_len: Self::MAX_INSTANCES,
+                            },
+                        ),
+                        ShaderProperty::new(
+                            "worldViewProjections",
+                            Matrix4Array {
+                                value: Default::default(),
+                                max_len: Self::MAX_INSTANCES,
+                            },
+                        ),
+                        ShaderProperty::new(
+                            "previousWorldViewProjections",
+                            Matrix4Array {
+                                value: Default::default(),
+                                max_len: Self::MAX_INSTANCES,
+                            },
+                        ),
+                        ShaderProperty::new(
+                            "customData",
+                            Vector4Array {
+                                value: Default::default(),
+                                max_len: Self::MAX_INSTANCES,
+                            },
+                        ),
+                    ]);
+                }
+                "fyrox_boneMatrices" | "fyrox_previousBoneMatrices" => {
                     properties.clear();
                     properties.extend([ShaderProperty::new(
                         "matrices",
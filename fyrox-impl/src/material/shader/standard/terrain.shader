@@ -169,6 +169,8 @@
                 out vec3 tangent;
                 out vec3 binormal;
                 out vec2 secondTexCoord;
+                out vec4 clipPosition;
+                out vec4 previousClipPosition;
 
                 void main()
                 {
@@ -197,6 +199,8 @@
                     position = vec3(fyrox_instanceData.worldMatrix * finalVertexPosition);
                     secondTexCoord = vertexSecondTexCoord;
                     gl_Position = fyrox_instanceData.worldViewProjection * finalVertexPosition;
+                    clipPosition = gl_Position;
+                    previousClipPosition = fyrox_instanceData.previousWorldViewProjection * finalVertexPosition;
                 }
                 "#,
             fragment_shader:
@@ -206,6 +210,7 @@
                 layout(location = 2) out vec4 outAmbient;
                 layout(location = 3) out vec4 outMaterial;
                 layout(location = 4) out uint outDecalMask;
+                layout(location = 5) out vec4 outMotion;
 
                 in vec3 position;
                 in vec3 normal;
@@ -213,6 +218,8 @@
                 in vec3 tangent;
                 in vec3 binormal;
                 in vec2 secondTexCoord;
+                in vec4 clipPosition;
+                in vec4 previousClipPosition;
 
                 void main()
                 {
@@ -250,6 +257,9 @@
 
                     outDecalMask = properties.layerIndex;
 
+                    // Every layer has the same geometry, so motion vector is simply overwritten.
+                    outMotion = vec4(S_MotionVector(clipPosition, previousClipPosition, fyrox_cameraData.jitter), 1.0, 1.0);
+
                     float mask = texture(maskTexture, texCoord).r;
 
                     outColor.a = mask;
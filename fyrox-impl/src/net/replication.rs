@@ -0,0 +1,763 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Server-authoritative replication of scene nodes. See [`ReplicationServer`] and
+//! [`ReplicationClient`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{UnitQuaternion, Vector2, Vector3},
+        instant::Instant,
+        log::Log,
+        pool::Handle,
+        reflect::prelude::*,
+    },
+    fxhash::{FxHashMap, FxHashSet},
+    graph::BaseSceneGraph,
+    net::transport::{Delivery, Endpoint, TransportEvent, MAX_UNRELIABLE_MESSAGE_SIZE},
+    scene::{node::Node, Scene},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    io,
+    net::{SocketAddr, ToSocketAddrs},
+    time::Duration,
+};
+
+/// A tag, that marks a script field as replicated. Every replicated field of a script of a
+/// replicated node is sent to clients when it changes:
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::{reflect::prelude::*, visitor::prelude::*, variable::InheritableVariable},
+/// # };
+/// #[derive(Visit, Reflect, Default, Debug, Clone)]
+/// struct Player {
+///     #[reflect(tag = "Replicated")]
+///     health: InheritableVariable<f32>,
+///
+///     // Not replicated.
+///     fire_cooldown: f32,
+/// }
+/// ```
+///
+/// Only the types supported by [`ReplicatedValue`] can be replicated, fields of other types are
+/// ignored.
+pub const REPLICATED_TAG: &str = "Replicated";
+
+/// A value of a replicated script field.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum ReplicatedValue {
+    /// Boolean value.
+    Bool(bool),
+    /// Signed 32-bit integer.
+    I32(i32),
+    /// Unsigned 32-bit integer.
+    U32(u32),
+    /// Signed 64-bit integer.
+    I64(i64),
+    /// Unsigned 64-bit integer.
+    U64(u64),
+    /// 32-bit floating point number.
+    F32(f32),
+    /// 64-bit floating point number.
+    F64(f64),
+    /// A string.
+    String(String),
+    /// 2D vector.
+    Vector2(Vector2<f32>),
+    /// 3D vector.
+    Vector3(Vector3<f32>),
+    /// Rotation.
+    Quaternion(UnitQuaternion<f32>),
+}
+
+macro_rules! define_replicated_value_conversions {
+    ($($variant:ident => $ty:ty),*) => {
+        impl ReplicatedValue {
+            /// Tries to convert a reflected value into replicated value. Returns `None` if the type
+            /// of the value is not supported.
+            pub fn from_reflect(value: &dyn Reflect) -> Option<Self> {
+                let mut result = None;
+                value.as_any(&mut |any| {
+                    $(
+                        if let Some(value) = any.downcast_ref::<$ty>() {
+                            result = Some(Self::$variant(value.clone()));
+                        }
+                    )*
+                });
+                result
+            }
+
+            /// Converts the value into a boxed reflected value, that can be used to set a field.
+            pub fn into_reflect(self) -> Box<dyn Reflect> {
+                match self {
+                    $(Self::$variant(value) => Box::new(value),)*
+                }
+            }
+        }
+    };
+}
+
+define_replicated_value_conversions!(
+    Bool => bool,
+    I32 => i32,
+    U32 => u32,
+    I64 => i64,
+    U64 => u64,
+    F32 => f32,
+    F64 => f64,
+    String => String,
+    Vector2 => Vector2<f32>,
+    Vector3 => Vector3<f32>,
+    Quaternion => UnitQuaternion<f32>
+);
+
+/// Network id of a node. Server and clients must have the same scene loaded, so handles of the nodes
+/// are the same on both sides.
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, Hash)]
+struct NetNodeId {
+    index: u32,
+    generation: u32,
+}
+
+impl From<Handle<Node>> for NetNodeId {
+    fn from(handle: Handle<Node>) -> Self {
+        Self {
+            index: handle.index(),
+            generation: handle.generation(),
+        }
+    }
+}
+
+impl From<NetNodeId> for Handle<Node> {
+    fn from(id: NetNodeId) -> Self {
+        Handle::new(id.index, id.generation)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+struct FieldState {
+    script: u16,
+    name: String,
+    value: ReplicatedValue,
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq)]
+struct TransformState {
+    position: Vector3<f32>,
+    rotation: UnitQuaternion<f32>,
+    scale: Vector3<f32>,
+}
+
+impl TransformState {
+    fn capture(node: &Node) -> Self {
+        let transform = node.local_transform();
+        Self {
+            position: **transform.position(),
+            rotation: **transform.rotation(),
+            scale: **transform.scale(),
+        }
+    }
+
+    fn apply(&self, node: &mut Node) {
+        node.local_transform_mut()
+            .set_position(self.position)
+            .set_rotation(self.rotation)
+            .set_scale(self.scale);
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct NodeSnapshot {
+    transform: TransformState,
+    fields: Vec<FieldState>,
+}
+
+impl NodeSnapshot {
+    fn capture(node: &Node) -> Self {
+        let mut fields = Vec::new();
+        for script_index in 0..node.script_count() {
+            if let Some(script) = node.script(script_index) {
+                script.fields_info(&mut |infos| {
+                    for info in infos.iter().filter(|info| info.tag == REPLICATED_TAG) {
+                        if let Some(value) = ReplicatedValue::from_reflect(info.reflect_value) {
+                            fields.push(FieldState {
+                                script: script_index as u16,
+                                name: info.name.to_string(),
+                                value,
+                            });
+                        }
+                    }
+                });
+            }
+        }
+
+        Self {
+            transform: TransformState::capture(node),
+            fields,
+        }
+    }
+
+    // Returns the fields, that differ from the baseline (the last state that was sent to a client).
+    fn changed_fields(&self, baseline: &[FieldState]) -> Vec<FieldState> {
+        self.fields
+            .iter()
+            .filter(|field| !baseline.contains(field))
+            .cloned()
+            .collect()
+    }
+}
+
+fn apply_fields(node: &mut Node, fields: Vec<FieldState>) {
+    for field in fields {
+        let Some(script) = node.script_mut(field.script as usize) else {
+            continue;
+        };
+        let mut value = Some(field.value.into_reflect());
+        script.field_mut(&field.name, &mut |reflect| {
+            if let (Some(reflect), Some(value)) = (reflect, value.take()) {
+                if reflect.set(value).is_err() {
+                    Log::warn(format!(
+                        "Unable to set replicated field {}: type mismatch!",
+                        field.name
+                    ));
+                }
+            }
+        });
+    }
+}
+
+// Checks whether the sequence number `a` is newer than `b`, taking wrapping into account.
+fn is_newer(a: u32, b: u32) -> bool {
+    a != b && a.wrapping_sub(b) < u32::MAX / 2
+}
+
+// Transforms of a node and its id take 48 bytes, the rest of the message takes 16 bytes. It gives
+// 24 transforms per message to fit into the maximum unreliable message size.
+const TRANSFORMS_PER_MESSAGE: usize = (MAX_UNRELIABLE_MESSAGE_SIZE - 16) / 48;
+
+// Maximum amount of sequence numbers in a single acknowledgement message.
+const ACKS_PER_MESSAGE: usize = 256;
+
+// Maximum amount of transform messages, that are waiting for acknowledgement from a client. If a
+// message is forgotten, the transforms from it are simply re-sent.
+const MAX_IN_FLIGHT_MESSAGES: usize = 64;
+
+#[derive(Serialize, Deserialize, Debug)]
+enum ServerMessage {
+    // Transforms of the nodes, that have changed, sent over the unreliable channel.
+    Transforms {
+        sequence: u32,
+        nodes: Vec<(NetNodeId, TransformState)>,
+    },
+    // Changed replicated fields of a node, sent over the reliable channel.
+    Fields {
+        node: NetNodeId,
+        fields: Vec<FieldState>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+enum ClientMessage {
+    // Sequence numbers of the received transform messages.
+    Ack(Vec<u32>),
+}
+
+/// An event produced by the replication server.
+#[derive(Debug, PartialEq)]
+pub enum ReplicationEvent {
+    /// A new client has connected.
+    ClientConnected(SocketAddr),
+    /// A client has disconnected (or timed out).
+    ClientDisconnected(SocketAddr),
+}
+
+// Replication state of a node for a particular client.
+#[derive(Default)]
+struct NodeReplication {
+    // The last sent transform, the sequence number of the message it was sent in and the time of
+    // sending.
+    sent: Option<(u32, TransformState, Instant)>,
+    // The transform, that is known to be applied on the client.
+    acknowledged: Option<TransformState>,
+    // The last replicated fields, that were sent to the client.
+    fields: Vec<FieldState>,
+}
+
+impl NodeReplication {
+    fn should_send_transform(
+        &self,
+        transform: &TransformState,
+        now: Instant,
+        resend_interval: Duration,
+    ) -> bool {
+        if self.acknowledged.as_ref() == Some(transform) {
+            return false;
+        }
+
+        // The same transform is already on its way, wait for the acknowledgement for a while.
+        !matches!(self.sent, Some((_, sent, time)) if sent == *transform
+            && now.saturating_duration_since(time) < resend_interval)
+    }
+
+    fn acknowledge(&mut self, sequence: u32) {
+        // Only the latest sent transform could be acknowledged, because a client does not apply
+        // outdated transforms and the older ones could be already overwritten.
+        if let Some((sent_sequence, transform, _)) = self.sent {
+            if sent_sequence == sequence {
+                self.acknowledged = Some(transform);
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct ClientState {
+    observer: Handle<Node>,
+    nodes: FxHashMap<Handle<Node>, NodeReplication>,
+    next_sequence: u32,
+    // Sequence numbers of the transform messages, that were not acknowledged yet, and the nodes
+    // they contain.
+    in_flight: VecDeque<(u32, Vec<Handle<Node>>)>,
+}
+
+impl ClientState {
+    fn acknowledge(&mut self, sequence: u32) {
+        let Some(index) = self.in_flight.iter().position(|(s, _)| *s == sequence) else {
+            return;
+        };
+        if let Some((_, nodes)) = self.in_flight.remove(index) {
+            for node in nodes {
+                if let Some(state) = self.nodes.get_mut(&node) {
+                    state.acknowledge(sequence);
+                }
+            }
+        }
+    }
+}
+
+/// Replication server sends the state of the replicated nodes (local transform and replicated script
+/// fields, see [`REPLICATED_TAG`]) to every connected client. The server is the only authority: clients
+/// must not modify the replicated state, because it will be overwritten.
+///
+/// Only changes are sent (delta compression). Transforms change frequently, so they're sent over
+/// the unreliable channel with sequence numbers: clients drop outdated transforms and acknowledge
+/// the received ones, the server re-sends a transform if it was not acknowledged during
+/// [`Self::resend_interval`]. Replicated script fields are sent over the reliable channel. This way
+/// the state on the clients always converges to the state on the server, while lost or slow
+/// clients don't cause unbounded growth of the send queues.
+///
+/// Every client could have an observer node (usually a player character), in this case the client
+/// receives the state of the nodes that are closer than [`Self::interest_radius`] to the observer
+/// (interest management). When a node comes back into the area of interest, its full state is sent.
+///
+/// The server and the clients must have the same scene loaded, because nodes are matched by their
+/// handles. Spawning and destruction of nodes is not replicated.
+///
+/// ```rust,no_run
+/// # use fyrox_impl::{
+/// #     core::pool::Handle,
+/// #     net::replication::{ReplicationEvent, ReplicationServer},
+/// #     scene::{node::Node, Scene},
+/// # };
+/// fn update_server(server: &mut ReplicationServer, scene: &Scene, player: Handle<Node>) {
+///     for event in server.update(scene) {
+///         if let ReplicationEvent::ClientConnected(client) = event {
+///             server.set_observer(client, player);
+///         }
+///     }
+/// }
+/// ```
+pub struct ReplicationServer {
+    endpoint: Endpoint,
+    clients: FxHashMap<SocketAddr, ClientState>,
+    replicated: FxHashSet<Handle<Node>>,
+    /// Maximum distance between an observer and a node at which the state of the node is sent to
+    /// the client of the observer.
+    pub interest_radius: f32,
+    /// A transform, that was not acknowledged by a client during this time, is sent again.
+    pub resend_interval: Duration,
+}
+
+impl ReplicationServer {
+    /// Creates a new server, that listens to the given address.
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        Ok(Self {
+            endpoint: Endpoint::bind(addr)?,
+            clients: Default::default(),
+            replicated: Default::default(),
+            interest_radius: 100.0,
+            resend_interval: Duration::from_millis(100),
+        })
+    }
+
+    /// Returns local address of the server.
+    pub fn local_address(&self) -> io::Result<SocketAddr> {
+        self.endpoint.local_address()
+    }
+
+    /// Marks the node as replicated.
+    pub fn replicate(&mut self, node: Handle<Node>) {
+        self.replicated.insert(node);
+    }
+
+    /// Stops replication of the node.
+    pub fn stop_replicating(&mut self, node: Handle<Node>) {
+        self.replicated.remove(&node);
+        for client in self.clients.values_mut() {
+            client.nodes.remove(&node);
+        }
+    }
+
+    /// Returns `true` if the node is replicated, `false` - otherwise.
+    pub fn is_replicated(&self, node: Handle<Node>) -> bool {
+        self.replicated.contains(&node)
+    }
+
+    /// Returns an iterator over addresses of the connected clients.
+    pub fn clients(&self) -> impl Iterator<Item = &SocketAddr> {
+        self.clients.keys()
+    }
+
+    /// Sets an observer node of the client. [`Handle::NONE`] means that the client receives the state
+    /// of every replicated node.
+    pub fn set_observer(&mut self, client: SocketAddr, observer: Handle<Node>) {
+        if let Some(client) = self.clients.get_mut(&client) {
+            client.observer = observer;
+        }
+    }
+
+    /// Processes incoming packets and sends the changes of the replicated nodes to the clients. Should
+    /// be called once per frame (or at a fixed network tick rate).
+    pub fn update(&mut self, scene: &Scene) -> Vec<ReplicationEvent> {
+        let mut events = Vec::new();
+
+        for event in self.endpoint.poll() {
+            match event {
+                TransportEvent::Connected(addr) => {
+                    self.clients.insert(addr, Default::default());
+                    events.push(ReplicationEvent::ClientConnected(addr));
+                }
+                TransportEvent::Disconnected(addr) => {
+                    if self.clients.remove(&addr).is_some() {
+                        events.push(ReplicationEvent::ClientDisconnected(addr));
+                    }
+                }
+                TransportEvent::Message { peer, data } => {
+                    match bincode::deserialize::<ClientMessage>(&data) {
+                        Ok(ClientMessage::Ack(sequences)) => {
+                            if let Some(client) = self.clients.get_mut(&peer) {
+                                for sequence in sequences {
+                                    client.acknowledge(sequence);
+                                }
+                            }
+                        }
+                        Err(err) => Log::warn(format!("Malformed message from {peer}: {err}")),
+                    }
+                }
+            }
+        }
+
+        self.replicated
+            .retain(|handle| scene.graph.is_valid_handle(*handle));
+
+        let snapshots = self
+            .replicated
+            .iter()
+            .map(|handle| {
+                let node = &scene.graph[*handle];
+                (*handle, node.global_position(), NodeSnapshot::capture(node))
+            })
+            .collect::<Vec<_>>();
+
+        let now = Instant::now();
+        for (addr, client) in self.clients.iter_mut() {
+            let observer_position = scene
+                .graph
+                .try_get(client.observer)
+                .map(|observer| observer.global_position());
+
+            let mut transforms = Vec::new();
+            for (handle, position, snapshot) in snapshots.iter() {
+                let is_relevant = observer_position.map_or(true, |observer_position| {
+                    observer_position.metric_distance(position) <= self.interest_radius
+                });
+
+                if !is_relevant {
+                    // Forget the state, so the full state will be sent when the node becomes
+                    // relevant again.
+                    client.nodes.remove(handle);
+                    continue;
+                }
+
+                let state = client.nodes.entry(*handle).or_default();
+
+                if state.should_send_transform(&snapshot.transform, now, self.resend_interval) {
+                    transforms.push((*handle, snapshot.transform));
+                }
+
+                let fields = snapshot.changed_fields(&state.fields);
+                if !fields.is_empty() {
+                    let message = ServerMessage::Fields {
+                        node: (*handle).into(),
+                        fields,
+                    };
+                    match bincode::serialize(&message) {
+                        Ok(data) => match self.endpoint.send(*addr, data, Delivery::Reliable) {
+                            Ok(()) => state.fields.clone_from(&snapshot.fields),
+                            Err(err) => {
+                                Log::err(format!("Unable to send node fields to {addr}: {err:?}"))
+                            }
+                        },
+                        Err(err) => Log::err(format!("Unable to serialize node fields: {err}")),
+                    }
+                }
+            }
+
+            for chunk in transforms.chunks(TRANSFORMS_PER_MESSAGE) {
+                let sequence = client.next_sequence;
+                client.next_sequence = client.next_sequence.wrapping_add(1);
+
+                let message = ServerMessage::Transforms {
+                    sequence,
+                    nodes: chunk
+                        .iter()
+                        .map(|(handle, transform)| ((*handle).into(), *transform))
+                        .collect(),
+                };
+                let data = match bincode::serialize(&message) {
+                    Ok(data) => data,
+                    Err(err) => {
+                        Log::err(format!("Unable to serialize node transforms: {err}"));
+                        continue;
+                    }
+                };
+                if let Err(err) = self.endpoint.send(*addr, data, Delivery::Unreliable) {
+                    Log::err(format!("Unable to send node transforms to {addr}: {err:?}"));
+                    continue;
+                }
+
+                for (handle, transform) in chunk {
+                    if let Some(state) = client.nodes.get_mut(handle) {
+                        state.sent = Some((sequence, *transform, now));
+                    }
+                }
+
+                if client.in_flight.len() >= MAX_IN_FLIGHT_MESSAGES {
+                    client.in_flight.pop_front();
+                }
+                client
+                    .in_flight
+                    .push_back((sequence, chunk.iter().map(|(handle, _)| *handle).collect()));
+            }
+        }
+
+        self.endpoint.flush();
+
+        events
+    }
+}
+
+/// Replication client receives the state of the replicated nodes from a [`ReplicationServer`] and
+/// applies it to the local copy of the scene.
+pub struct ReplicationClient {
+    endpoint: Endpoint,
+    server: SocketAddr,
+    // Sequence number of the last applied transform of each node, outdated transforms are dropped.
+    last_sequences: FxHashMap<Handle<Node>, u32>,
+}
+
+impl ReplicationClient {
+    /// Connects to a server with the given address.
+    pub fn connect<A: ToSocketAddrs>(server: A) -> io::Result<Self> {
+        let mut endpoint = Endpoint::bind("0.0.0.0:0")?;
+        let server = endpoint.connect(server)?;
+        Ok(Self {
+            endpoint,
+            server,
+            last_sequences: Default::default(),
+        })
+    }
+
+    /// Returns address of the server.
+    pub fn server_address(&self) -> SocketAddr {
+        self.server
+    }
+
+    /// Returns `true` if the client is still connected to the server.
+    pub fn is_connected(&self) -> bool {
+        self.endpoint.is_connected(self.server)
+    }
+
+    /// Receives the changes from the server and applies them to the scene. Should be called once per
+    /// frame.
+    pub fn update(&mut self, scene: &mut Scene) {
+        let mut received = Vec::new();
+
+        for event in self.endpoint.poll() {
+            let TransportEvent::Message { peer, data } = event else {
+                continue;
+            };
+            if peer != self.server {
+                continue;
+            }
+            match bincode::deserialize::<ServerMessage>(&data) {
+                Ok(ServerMessage::Transforms { sequence, nodes }) => {
+                    received.push(sequence);
+                    for (id, transform) in nodes {
+                        let handle = Handle::<Node>::from(id);
+                        let is_outdated = self
+                            .last_sequences
+                            .get(&handle)
+                            .is_some_and(|last| !is_newer(sequence, *last));
+                        if is_outdated {
+                            continue;
+                        }
+                        if let Some(node) = scene.graph.try_get_mut(handle) {
+                            transform.apply(node);
+                            self.last_sequences.insert(handle, sequence);
+                        }
+                    }
+                }
+                Ok(ServerMessage::Fields { node, fields }) => {
+                    if let Some(node) = scene.graph.try_get_mut(node.into()) {
+                        apply_fields(node, fields);
+                    }
+                }
+                Err(err) => Log::warn(format!("Malformed message from the server: {err}")),
+            }
+        }
+
+        for chunk in received.chunks(ACKS_PER_MESSAGE) {
+            match bincode::serialize(&ClientMessage::Ack(chunk.to_vec())) {
+                Ok(data) => {
+                    if let Err(err) = self.endpoint.send(self.server, data, Delivery::Unreliable) {
+                        Log::err(format!("Unable to acknowledge node transforms: {err:?}"));
+                    }
+                }
+                Err(err) => Log::err(format!("Unable to serialize acknowledgements: {err}")),
+            }
+        }
+
+        self.endpoint.flush();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{UnitQuaternion, Vector3},
+            instant::Instant,
+        },
+        net::replication::{
+            is_newer, ClientState, NodeReplication, NodeSnapshot, ReplicatedValue, TransformState,
+        },
+        scene::{base::BaseBuilder, pivot::PivotBuilder, transform::TransformBuilder, Scene},
+    };
+    use std::time::Duration;
+
+    #[test]
+    fn test_replicated_value_conversion() {
+        let value = ReplicatedValue::from_reflect(&123.0f32).unwrap();
+        assert_eq!(value, ReplicatedValue::F32(123.0));
+        assert!(ReplicatedValue::from_reflect(&Vec::<u8>::new()).is_none());
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let mut scene = Scene::new();
+        let node = PivotBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(1.0, 2.0, 3.0))
+                    .build(),
+            ),
+        )
+        .build(&mut scene.graph);
+
+        let snapshot = NodeSnapshot::capture(&scene.graph[node]);
+        assert_eq!(snapshot.transform.position, Vector3::new(1.0, 2.0, 3.0));
+        assert!(snapshot.changed_fields(&[]).is_empty());
+    }
+
+    fn transform(x: f32) -> TransformState {
+        TransformState {
+            position: Vector3::new(x, 0.0, 0.0),
+            rotation: UnitQuaternion::identity(),
+            scale: Vector3::repeat(1.0),
+        }
+    }
+
+    #[test]
+    fn test_transform_is_resent_until_acknowledged() {
+        let interval = Duration::from_millis(100);
+        let now = Instant::now();
+        let mut state = NodeReplication::default();
+
+        // Full state is sent when there's no baseline.
+        assert!(state.should_send_transform(&transform(1.0), now, interval));
+
+        state.sent = Some((0, transform(1.0), now));
+        // Waiting for the acknowledgement.
+        assert!(!state.should_send_transform(&transform(1.0), now, interval));
+        // The transform has changed.
+        assert!(state.should_send_transform(&transform(2.0), now, interval));
+        // The acknowledgement is late (or the message is lost).
+        assert!(state.should_send_transform(&transform(1.0), now + interval, interval));
+
+        state.acknowledge(0);
+        assert_eq!(state.acknowledged, Some(transform(1.0)));
+        assert!(!state.should_send_transform(&transform(1.0), now + interval, interval));
+    }
+
+    #[test]
+    fn test_outdated_acknowledgements_are_ignored() {
+        let mut client = ClientState::default();
+        let node = Default::default();
+        client.in_flight.push_back((0, vec![node]));
+        client.in_flight.push_back((1, vec![node]));
+        client.nodes.insert(
+            node,
+            NodeReplication {
+                sent: Some((1, transform(2.0), Instant::now())),
+                ..Default::default()
+            },
+        );
+
+        // The client could have a newer transform, that was sent later.
+        client.acknowledge(0);
+        assert_eq!(client.nodes[&node].acknowledged, None);
+        assert_eq!(client.in_flight.len(), 1);
+
+        client.acknowledge(1);
+        assert_eq!(client.nodes[&node].acknowledged, Some(transform(2.0)));
+        assert!(client.in_flight.is_empty());
+    }
+
+    #[test]
+    fn test_sequence_order() {
+        assert!(is_newer(1, 0));
+        assert!(!is_newer(0, 1));
+        assert!(!is_newer(5, 5));
+        assert!(is_newer(0, u32::MAX));
+    }
+}
@@ -0,0 +1,759 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! UDP transport with reliable (ordered) and unreliable channels. See [`Endpoint`] docs for more
+//! info.
+
+use crate::{
+    core::{instant::Instant, log::Log},
+    fxhash::FxHashMap,
+    rand,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    io::{self, ErrorKind},
+    net::{SocketAddr, ToSocketAddrs, UdpSocket},
+    time::Duration,
+};
+
+/// Maximum size of a datagram, that will be received by an endpoint.
+pub const MAX_DATAGRAM_SIZE: usize = 65507;
+
+// Messages are packed into a single packet until its size exceeds this limit, the rest is sent in
+// the next packets. The value is chosen to fit into the typical MTU without fragmentation.
+const PAYLOAD_BUDGET: usize = 1200;
+
+/// Maximum size of a reliable message. Bigger messages are rejected by [`Endpoint::send`] and
+/// ignored when received.
+pub const MAX_RELIABLE_MESSAGE_SIZE: usize = 16 * 1024;
+
+/// Maximum size of an unreliable message. Unreliable messages are never split into multiple
+/// packets, so the limit is chosen to fit a message into the typical MTU without fragmentation.
+pub const MAX_UNRELIABLE_MESSAGE_SIZE: usize = PAYLOAD_BUDGET;
+
+// Reliable messages, that are too far ahead of the next expected message, are ignored (and will be
+// re-sent by the peer later). It limits the amount of memory that a peer could occupy by sending
+// messages out of order.
+const RECEIVE_WINDOW: u32 = 256;
+
+// Maximum amount of acknowledgements per packet, the rest is sent in the next packets.
+const MAX_ACKS_PER_PACKET: usize = 256;
+
+/// Delivery guarantees of a message.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Delivery {
+    /// The message is guaranteed to be delivered, and all reliable messages are delivered in the
+    /// same order as they were sent. Lost messages are re-sent until the peer acknowledges them.
+    Reliable,
+    /// The message could be lost or delivered out of order. Use it for frequently changing data,
+    /// that quickly becomes outdated.
+    Unreliable,
+}
+
+/// An error, that could occur when sending a message.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SendError {
+    /// There's no peer with the given address (or the handshake with the peer has failed).
+    NoSuchPeer,
+    /// The message exceeds [`MAX_RELIABLE_MESSAGE_SIZE`] or [`MAX_UNRELIABLE_MESSAGE_SIZE`]
+    /// (depending on the delivery).
+    MessageTooBig,
+    /// The peer did not acknowledge [`Endpoint::max_unacknowledged`] reliable messages. The
+    /// delivery of reliable messages cannot be guaranteed anymore, so the peer is disconnected.
+    QueueOverflow,
+}
+
+/// An event produced by an endpoint.
+#[derive(Debug, PartialEq)]
+pub enum TransportEvent {
+    /// A handshake with a new peer was completed.
+    Connected(SocketAddr),
+    /// A message was received from the peer.
+    Message {
+        /// Address of the sender.
+        peer: SocketAddr,
+        /// Contents of the message.
+        data: Vec<u8>,
+    },
+    /// A peer has not sent anything for too long (or overflowed its reliable message queue) and
+    /// was removed. It is also produced (followed by [`TransportEvent::Connected`]) when a peer
+    /// starts a new connection from the same address, for example after a restart.
+    Disconnected(SocketAddr),
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+enum Packet {
+    // Sent by a connecting endpoint until the peer accepts the connection. The token allows the
+    // connecting endpoint to ignore stale responses.
+    Connect { token: u64 },
+    // Sent in response to every connection request.
+    Accept { token: u64 },
+    // Data packets are accepted only from the peers, that have completed the handshake.
+    Data(DataPacket),
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+struct DataPacket {
+    // Ids of reliable messages, that were received by the sender of the packet.
+    acks: Vec<u32>,
+    reliable: Vec<(u32, Vec<u8>)>,
+    unreliable: Vec<Vec<u8>>,
+}
+
+struct PendingMessage {
+    data: Vec<u8>,
+    last_sent: Option<Instant>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum PeerState {
+    Connecting {
+        token: u64,
+        last_attempt: Option<Instant>,
+    },
+    Connected,
+}
+
+struct Peer {
+    state: PeerState,
+    // A token of the connection request, that must be answered.
+    pending_accept: Option<u64>,
+    // A token of the connection request, that was accepted. A request with a different token
+    // means that the peer has restarted and connects again from the same address.
+    accepted_token: Option<u64>,
+    next_reliable_id: u32,
+    // Sent, but not yet acknowledged, reliable messages.
+    unacknowledged: BTreeMap<u32, PendingMessage>,
+    unreliable: Vec<Vec<u8>>,
+    acks: Vec<u32>,
+    // Id of the next reliable message, that should be delivered to the user.
+    next_expected_id: u32,
+    // Reliable messages, that arrived out of order.
+    out_of_order: BTreeMap<u32, Vec<u8>>,
+    last_received: Instant,
+    last_sent: Instant,
+}
+
+impl Peer {
+    fn new(state: PeerState, now: Instant) -> Self {
+        Self {
+            state,
+            pending_accept: None,
+            accepted_token: None,
+            next_reliable_id: 0,
+            unacknowledged: Default::default(),
+            unreliable: Default::default(),
+            acks: Default::default(),
+            next_expected_id: 0,
+            out_of_order: Default::default(),
+            last_received: now,
+            last_sent: now,
+        }
+    }
+
+    fn is_connected(&self) -> bool {
+        self.state == PeerState::Connected
+    }
+}
+
+fn send_packet(socket: &UdpSocket, addr: &SocketAddr, packet: &Packet) {
+    match bincode::serialize(packet) {
+        Ok(data) => {
+            if let Err(err) = socket.send_to(&data, addr) {
+                if err.kind() != ErrorKind::WouldBlock {
+                    Log::err(format!("Unable to send a packet to {addr}: {err}"));
+                }
+            }
+        }
+        Err(err) => Log::err(format!("Unable to serialize a packet: {err}")),
+    }
+}
+
+/// Endpoint is a non-blocking UDP socket, that maintains a set of peers and provides reliable and
+/// unreliable channels for each of them. It is used for both servers and clients: a server simply
+/// accepts connections from many peers, while a client connects to a single server peer.
+///
+/// A peer must complete a handshake before any of its messages are accepted: the connecting side
+/// sends connection requests (see [`Self::connect`]) until the other side accepts the connection.
+/// Every resource, that a peer could occupy, is bounded: the amount of peers is limited by
+/// [`Self::max_peers`], reliable messages are received only within a small window ahead of the
+/// next expected message and the reliable messages of a peer, that does not acknowledge them for
+/// too long, are limited by [`Self::max_unacknowledged`].
+///
+/// Endpoints are polled manually (usually once per frame), see [`Self::poll`] and [`Self::flush`].
+pub struct Endpoint {
+    socket: UdpSocket,
+    peers: FxHashMap<SocketAddr, Peer>,
+    receive_buffer: Vec<u8>,
+    // Events, that will be returned on the next poll.
+    events: Vec<TransportEvent>,
+    /// Interval between re-sending of unacknowledged reliable messages and connection requests.
+    pub resend_interval: Duration,
+    /// A peer will be disconnected if it does not send anything during this time.
+    pub timeout: Duration,
+    /// An empty packet will be sent to a peer if nothing was sent to it during this time, it prevents
+    /// peers from disconnecting due to the timeout.
+    pub keep_alive_interval: Duration,
+    /// Maximum amount of peers. Connection requests are ignored when the limit is reached.
+    pub max_peers: usize,
+    /// Maximum amount of reliable messages, that were sent to a peer, but were not acknowledged
+    /// by it yet. A peer that exceeds the limit is disconnected.
+    pub max_unacknowledged: usize,
+}
+
+impl Endpoint {
+    /// Binds an endpoint to the given address. Use `0.0.0.0:0` for clients to pick any free port.
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        let socket = UdpSocket::bind(addr)?;
+        socket.set_nonblocking(true)?;
+        Ok(Self {
+            socket,
+            peers: Default::default(),
+            receive_buffer: vec![0; MAX_DATAGRAM_SIZE],
+            events: Default::default(),
+            resend_interval: Duration::from_millis(100),
+            timeout: Duration::from_secs(5),
+            keep_alive_interval: Duration::from_millis(250),
+            max_peers: 64,
+            max_unacknowledged: 4096,
+        })
+    }
+
+    /// Returns local address of the endpoint.
+    pub fn local_address(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Starts a handshake with the given peer. [`TransportEvent::Connected`] is produced when the
+    /// peer accepts the connection, [`TransportEvent::Disconnected`] - if it does not respond during
+    /// [`Self::timeout`]. Messages could be sent right away, they're delivered when the handshake
+    /// is completed.
+    pub fn connect<A: ToSocketAddrs>(&mut self, addr: A) -> io::Result<SocketAddr> {
+        let addr = addr
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "No address to connect to"))?;
+        self.peers.entry(addr).or_insert_with(|| {
+            Peer::new(
+                PeerState::Connecting {
+                    token: rand::random(),
+                    last_attempt: None,
+                },
+                Instant::now(),
+            )
+        });
+        Ok(addr)
+    }
+
+    /// Removes the peer. Pending messages of the peer will be discarded.
+    pub fn disconnect(&mut self, addr: SocketAddr) {
+        self.peers.remove(&addr);
+    }
+
+    /// Returns an iterator over addresses of all connected peers.
+    pub fn peers(&self) -> impl Iterator<Item = &SocketAddr> {
+        self.peers
+            .iter()
+            .filter_map(|(addr, peer)| peer.is_connected().then_some(addr))
+    }
+
+    /// Returns `true` if the given peer is connected (the handshake was completed), `false` -
+    /// otherwise.
+    pub fn is_connected(&self, addr: SocketAddr) -> bool {
+        self.peers.get(&addr).is_some_and(Peer::is_connected)
+    }
+
+    /// Returns total amount of reliable messages that were sent to the given peer, but were not
+    /// acknowledged yet.
+    pub fn unacknowledged_count(&self, addr: SocketAddr) -> usize {
+        self.peers
+            .get(&addr)
+            .map_or(0, |peer| peer.unacknowledged.len())
+    }
+
+    /// Queues a message for sending to the given peer. Messages are actually sent on [`Self::flush`].
+    /// See [`SendError`] docs for possible errors.
+    pub fn send(
+        &mut self,
+        addr: SocketAddr,
+        data: Vec<u8>,
+        delivery: Delivery,
+    ) -> Result<(), SendError> {
+        let Some(peer) = self.peers.get_mut(&addr) else {
+            return Err(SendError::NoSuchPeer);
+        };
+
+        match delivery {
+            Delivery::Reliable => {
+                if data.len() > MAX_RELIABLE_MESSAGE_SIZE {
+                    return Err(SendError::MessageTooBig);
+                }
+
+                if peer.unacknowledged.len() >= self.max_unacknowledged {
+                    Log::warn(format!(
+                        "Peer {addr} does not acknowledge reliable messages, disconnecting."
+                    ));
+                    self.peers.remove(&addr);
+                    self.events.push(TransportEvent::Disconnected(addr));
+                    return Err(SendError::QueueOverflow);
+                }
+
+                let id = peer.next_reliable_id;
+                peer.next_reliable_id = peer.next_reliable_id.wrapping_add(1);
+                peer.unacknowledged.insert(
+                    id,
+                    PendingMessage {
+                        data,
+                        last_sent: None,
+                    },
+                );
+            }
+            Delivery::Unreliable => {
+                if data.len() > MAX_UNRELIABLE_MESSAGE_SIZE {
+                    return Err(SendError::MessageTooBig);
+                }
+
+                peer.unreliable.push(data)
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Receives all incoming packets and returns a list of events in the order of arrival. Reliable
+    /// messages are reordered (if needed) and delivered only once.
+    pub fn poll(&mut self) -> Vec<TransportEvent> {
+        let now = Instant::now();
+        let mut events = std::mem::take(&mut self.events);
+
+        loop {
+            match self.socket.recv_from(&mut self.receive_buffer) {
+                Ok((size, addr)) => {
+                    let packet = match bincode::deserialize::<Packet>(&self.receive_buffer[..size])
+                    {
+                        Ok(packet) => packet,
+                        Err(err) => {
+                            Log::warn(format!("Malformed packet from {addr}: {err}"));
+                            continue;
+                        }
+                    };
+
+                    match packet {
+                        Packet::Connect { token } => {
+                            let is_new = !self.peers.contains_key(&addr);
+                            if is_new && self.peers.len() >= self.max_peers {
+                                Log::warn(format!(
+                                    "Connection request from {addr} was ignored, the maximum \
+                                    amount of peers is reached."
+                                ));
+                                continue;
+                            }
+
+                            let peer = self
+                                .peers
+                                .entry(addr)
+                                .or_insert_with(|| Peer::new(PeerState::Connected, now));
+                            if is_new || !peer.is_connected() {
+                                // Both sides could connect to each other at the same time.
+                                peer.state = PeerState::Connected;
+                                events.push(TransportEvent::Connected(addr));
+                            } else if peer
+                                .accepted_token
+                                .is_some_and(|accepted| accepted != token)
+                            {
+                                // The peer has restarted, messages of the previous connection
+                                // must not be mixed with the new ones.
+                                *peer = Peer::new(PeerState::Connected, now);
+                                events.push(TransportEvent::Disconnected(addr));
+                                events.push(TransportEvent::Connected(addr));
+                            }
+                            peer.accepted_token = Some(token);
+                            // Repeated requests are answered too, because the previous response
+                            // could be lost.
+                            peer.pending_accept = Some(token);
+                            peer.last_received = now;
+                        }
+                        Packet::Accept { token } => {
+                            if let Some(peer) = self.peers.get_mut(&addr) {
+                                if let PeerState::Connecting {
+                                    token: expected, ..
+                                } = peer.state
+                                {
+                                    if token == expected {
+                                        peer.state = PeerState::Connected;
+                                        peer.last_received = now;
+                                        events.push(TransportEvent::Connected(addr));
+                                    }
+                                }
+                            }
+                        }
+                        Packet::Data(packet) => {
+                            let Some(peer) =
+                                self.peers.get_mut(&addr).filter(|peer| peer.is_connected())
+                            else {
+                                continue;
+                            };
+
+                            peer.last_received = now;
+
+                            for id in packet.acks {
+                                peer.unacknowledged.remove(&id);
+                            }
+
+                            for (id, data) in packet.reliable {
+                                let offset = id.wrapping_sub(peer.next_expected_id);
+                                if offset >= u32::MAX / 2 {
+                                    // The message was delivered already, but the acknowledgement
+                                    // was lost.
+                                    peer.acks.push(id);
+                                } else if offset < RECEIVE_WINDOW
+                                    && data.len() <= MAX_RELIABLE_MESSAGE_SIZE
+                                {
+                                    peer.acks.push(id);
+                                    peer.out_of_order.insert(id, data);
+                                }
+                                // Everything else is ignored without an acknowledgement, the
+                                // message will be re-sent later.
+                            }
+
+                            while let Some(data) = peer.out_of_order.remove(&peer.next_expected_id)
+                            {
+                                events.push(TransportEvent::Message { peer: addr, data });
+                                peer.next_expected_id = peer.next_expected_id.wrapping_add(1);
+                            }
+
+                            for data in packet.unreliable {
+                                if data.len() <= MAX_UNRELIABLE_MESSAGE_SIZE {
+                                    events.push(TransportEvent::Message { peer: addr, data });
+                                }
+                            }
+                        }
+                    }
+                }
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                // Some platforms report ICMP "port unreachable" messages as errors of the next
+                // receive call, these are not fatal.
+                Err(err) if err.kind() == ErrorKind::ConnectionReset => continue,
+                Err(err) => {
+                    Log::err(format!("Unable to receive a packet: {err}"));
+                    break;
+                }
+            }
+        }
+
+        let timeout = self.timeout;
+        self.peers.retain(|addr, peer| {
+            let alive = now.saturating_duration_since(peer.last_received) < timeout;
+            if !alive {
+                events.push(TransportEvent::Disconnected(*addr));
+            }
+            alive
+        });
+
+        events
+    }
+
+    /// Sends all queued messages, acknowledgements and re-sends lost reliable messages and
+    /// connection requests.
+    pub fn flush(&mut self) {
+        let now = Instant::now();
+
+        for (addr, peer) in self.peers.iter_mut() {
+            if let PeerState::Connecting {
+                token,
+                ref mut last_attempt,
+            } = peer.state
+            {
+                let should_send = last_attempt.map_or(true, |last_attempt| {
+                    now.saturating_duration_since(last_attempt) >= self.resend_interval
+                });
+                if should_send {
+                    *last_attempt = Some(now);
+                    send_packet(&self.socket, addr, &Packet::Connect { token });
+                }
+                // Messages are sent only when the handshake is completed.
+                continue;
+            }
+
+            if let Some(token) = peer.pending_accept.take() {
+                send_packet(&self.socket, addr, &Packet::Accept { token });
+            }
+
+            peer.acks.sort_unstable();
+            peer.acks.dedup();
+            let acks_count = peer.acks.len().min(MAX_ACKS_PER_PACKET);
+            let mut packet = DataPacket {
+                acks: peer.acks.drain(..acks_count).collect(),
+                reliable: Default::default(),
+                unreliable: Default::default(),
+            };
+
+            let mut payload_size = 0;
+            for (id, message) in peer.unacknowledged.iter_mut() {
+                if payload_size >= PAYLOAD_BUDGET {
+                    break;
+                }
+                let should_send = message.last_sent.map_or(true, |last_sent| {
+                    now.saturating_duration_since(last_sent) >= self.resend_interval
+                });
+                if should_send {
+                    message.last_sent = Some(now);
+                    payload_size += message.data.len();
+                    packet.reliable.push((*id, message.data.clone()));
+                }
+            }
+
+            let mut unreliable = std::mem::take(&mut peer.unreliable).into_iter().peekable();
+            loop {
+                while let Some(data) = unreliable.next_if(|data| {
+                    payload_size == 0 || payload_size + data.len() <= PAYLOAD_BUDGET
+                }) {
+                    payload_size += data.len();
+                    packet.unreliable.push(data);
+                }
+
+                if packet.acks.is_empty()
+                    && packet.reliable.is_empty()
+                    && packet.unreliable.is_empty()
+                    && now.saturating_duration_since(peer.last_sent) < self.keep_alive_interval
+                {
+                    break;
+                }
+
+                peer.last_sent = now;
+                send_packet(
+                    &self.socket,
+                    addr,
+                    &Packet::Data(std::mem::take(&mut packet)),
+                );
+                payload_size = 0;
+
+                if unreliable.peek().is_none() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::net::transport::{
+        DataPacket, Delivery, Endpoint, Packet, SendError, TransportEvent,
+    };
+    use std::{net::UdpSocket, time::Duration};
+
+    fn exchange(a: &mut Endpoint, b: &mut Endpoint) -> (Vec<TransportEvent>, Vec<TransportEvent>) {
+        let mut a_events = Vec::new();
+        let mut b_events = Vec::new();
+        for _ in 0..20 {
+            a.flush();
+            b.flush();
+            std::thread::sleep(Duration::from_millis(5));
+            a_events.extend(a.poll());
+            b_events.extend(b.poll());
+        }
+        (a_events, b_events)
+    }
+
+    fn send_raw(socket: &UdpSocket, endpoint: &Endpoint, packet: &Packet) {
+        socket
+            .send_to(
+                &bincode::serialize(packet).unwrap(),
+                endpoint.local_address().unwrap(),
+            )
+            .unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+    }
+
+    #[test]
+    fn test_reliable_messages_are_ordered() {
+        let mut server = Endpoint::bind("127.0.0.1:0").unwrap();
+        let mut client = Endpoint::bind("127.0.0.1:0").unwrap();
+
+        let server_addr = client.connect(server.local_address().unwrap()).unwrap();
+        for i in 0..10u8 {
+            assert_eq!(
+                client.send(server_addr, vec![i], Delivery::Reliable),
+                Ok(())
+            );
+        }
+
+        let (server_events, client_events) = exchange(&mut server, &mut client);
+
+        let client_addr = client.local_address().unwrap();
+        assert_eq!(server_events[0], TransportEvent::Connected(client_addr));
+        assert_eq!(client_events[0], TransportEvent::Connected(server_addr));
+        let messages = server_events
+            .into_iter()
+            .filter_map(|e| match e {
+                TransportEvent::Message { data, .. } => Some(data[0]),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(messages, (0..10).collect::<Vec<_>>());
+
+        // Everything must be acknowledged.
+        assert_eq!(client.unacknowledged_count(server_addr), 0);
+    }
+
+    #[test]
+    fn test_data_without_handshake_is_ignored() {
+        let mut server = Endpoint::bind("127.0.0.1:0").unwrap();
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+
+        send_raw(
+            &socket,
+            &server,
+            &Packet::Data(DataPacket {
+                unreliable: vec![vec![1, 2, 3]],
+                ..Default::default()
+            }),
+        );
+
+        assert!(server.poll().is_empty());
+        assert_eq!(server.peers().count(), 0);
+    }
+
+    #[test]
+    fn test_receive_window() {
+        let mut server = Endpoint::bind("127.0.0.1:0").unwrap();
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let client_addr = socket.local_addr().unwrap();
+
+        send_raw(&socket, &server, &Packet::Connect { token: 123 });
+        assert_eq!(server.poll(), vec![TransportEvent::Connected(client_addr)]);
+
+        send_raw(
+            &socket,
+            &server,
+            &Packet::Data(DataPacket {
+                reliable: vec![(1, vec![1]), (u32::MAX / 4, vec![2])],
+                ..Default::default()
+            }),
+        );
+
+        assert!(server.poll().is_empty());
+        let peer = &server.peers[&client_addr];
+        // Only the message within the window is stored and acknowledged.
+        assert_eq!(peer.out_of_order.keys().copied().collect::<Vec<_>>(), [1]);
+        assert_eq!(peer.acks, [1]);
+    }
+
+    #[test]
+    fn test_reconnect_from_same_address() {
+        let mut server = Endpoint::bind("127.0.0.1:0").unwrap();
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let client_addr = socket.local_addr().unwrap();
+
+        send_raw(&socket, &server, &Packet::Connect { token: 1 });
+        assert_eq!(server.poll(), vec![TransportEvent::Connected(client_addr)]);
+
+        send_raw(
+            &socket,
+            &server,
+            &Packet::Data(DataPacket {
+                reliable: vec![(0, vec![1])],
+                ..Default::default()
+            }),
+        );
+        assert_eq!(
+            server.poll(),
+            vec![TransportEvent::Message {
+                peer: client_addr,
+                data: vec![1]
+            }]
+        );
+        assert_eq!(
+            server.send(client_addr, vec![2], Delivery::Reliable),
+            Ok(())
+        );
+
+        // Repeated requests of the same connection do not change anything.
+        send_raw(&socket, &server, &Packet::Connect { token: 1 });
+        assert!(server.poll().is_empty());
+        assert_eq!(server.unacknowledged_count(client_addr), 1);
+
+        // The client has restarted and starts a new connection.
+        send_raw(&socket, &server, &Packet::Connect { token: 2 });
+        assert_eq!(
+            server.poll(),
+            vec![
+                TransportEvent::Disconnected(client_addr),
+                TransportEvent::Connected(client_addr)
+            ]
+        );
+        let peer = &server.peers[&client_addr];
+        assert_eq!(peer.next_reliable_id, 0);
+        assert_eq!(peer.next_expected_id, 0);
+        assert_eq!(server.unacknowledged_count(client_addr), 0);
+
+        // The first message of the new connection is delivered.
+        send_raw(
+            &socket,
+            &server,
+            &Packet::Data(DataPacket {
+                reliable: vec![(0, vec![3])],
+                ..Default::default()
+            }),
+        );
+        assert_eq!(
+            server.poll(),
+            vec![TransportEvent::Message {
+                peer: client_addr,
+                data: vec![3]
+            }]
+        );
+    }
+
+    #[test]
+    fn test_message_limits() {
+        let mut server = Endpoint::bind("127.0.0.1:0").unwrap();
+        let mut client = Endpoint::bind("127.0.0.1:0").unwrap();
+        client.max_unacknowledged = 2;
+
+        let server_addr = client.connect(server.local_address().unwrap()).unwrap();
+
+        assert_eq!(
+            client.send(server_addr, vec![0; 2000], Delivery::Unreliable),
+            Err(SendError::MessageTooBig)
+        );
+
+        assert_eq!(
+            client.send(server_addr, vec![0], Delivery::Reliable),
+            Ok(())
+        );
+        assert_eq!(
+            client.send(server_addr, vec![1], Delivery::Reliable),
+            Ok(())
+        );
+        assert_eq!(
+            client.send(server_addr, vec![2], Delivery::Reliable),
+            Err(SendError::QueueOverflow)
+        );
+        assert_eq!(
+            client.poll(),
+            vec![TransportEvent::Disconnected(server_addr)]
+        );
+
+        let (server_events, _) = exchange(&mut server, &mut client);
+        assert!(server_events.is_empty());
+    }
+}
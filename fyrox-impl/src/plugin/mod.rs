@@ -33,8 +33,9 @@ use crate::{
         Downcast,
     },
     engine::{
-        task::TaskPoolHandler, AsyncSceneLoader, GraphicsContext, PerformanceStatistics,
-        ScriptProcessor, SerializationContext,
+        accessibility::AccessibilitySettings, simulation::Simulation, task::TaskPoolHandler,
+        AsyncSceneLoader, GraphicsContext, PerformanceStatistics, ScriptProcessor,
+        SerializationContext,
     },
     event::Event,
     gui::{
@@ -185,6 +186,16 @@ pub struct PluginContext<'a, 'b> {
 
     /// Task pool for asynchronous task management.
     pub task_pool: &'a mut TaskPoolHandler,
+
+    /// Simulation controls the way how the game logic is updated. It could be used to switch the
+    /// engine to deterministic mode, record and replay sessions. See [`Simulation`] docs for more
+    /// info.
+    pub simulation: &'a mut Simulation,
+
+    /// Accessibility settings of the engine (color blindness filter, user interface scale). Changes
+    /// are applied by the engine on the next frame. See [`AccessibilitySettings`] docs for more
+    /// info.
+    pub accessibility_settings: &'a mut AccessibilitySettings,
 }
 
 impl dyn Plugin {
@@ -307,6 +318,13 @@ pub trait Plugin: Downcast + Visit + Reflect {
     fn on_graphics_context_destroyed(&mut self, #[allow(unused_variables)] context: PluginContext) {
     }
 
+    /// The method is called when the graphics context was lost and then successfully recreated by
+    /// the engine. At this moment, [`Self::on_graphics_context_destroyed`] and
+    /// [`Self::on_graphics_context_initialized`] were already called. It could be useful to recreate
+    /// GPU objects, that were created by the plugin manually.
+    fn on_graphics_context_recovered(&mut self, #[allow(unused_variables)] context: PluginContext) {
+    }
+
     /// The method will be called when there is any message from main user interface instance
     /// of the engine.
     fn on_ui_message(
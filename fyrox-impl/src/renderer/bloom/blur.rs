@@ -27,7 +27,7 @@ use crate::{
         },
         framework::{
             error::FrameworkError,
-            framebuffer::{Attachment, AttachmentKind, GpuFrameBuffer},
+            framebuffer::{Attachment, GpuFrameBuffer},
             geometry_buffer::GpuGeometryBuffer,
             gpu_texture::{GpuTexture, PixelKind},
             server::GraphicsServer,
@@ -52,13 +52,7 @@ fn create_framebuffer(
 ) -> Result<GpuFrameBuffer, FrameworkError> {
     let frame = server.create_2d_render_target(pixel_kind, width, height)?;
 
-    server.create_frame_buffer(
-        None,
-        vec![Attachment {
-            kind: AttachmentKind::Color,
-            texture: frame,
-        }],
-    )
+    server.create_frame_buffer(None, vec![Attachment::color(frame)])
 }
 
 impl GaussianBlur {
@@ -80,7 +74,7 @@ impl GaussianBlur {
         })
     }
 
-    fn h_blurred(&self) -> &GpuTexture {
+    pub(crate) fn h_blurred(&self) -> &GpuTexture {
         &self.h_framebuffer.color_attachments()[0].texture
     }
 
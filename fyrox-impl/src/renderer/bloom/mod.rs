@@ -28,11 +28,15 @@ use crate::{
         },
         framework::{
             error::FrameworkError,
-            framebuffer::{Attachment, AttachmentKind, GpuFrameBuffer},
+            framebuffer::{Attachment, GpuFrameBuffer},
             geometry_buffer::GpuGeometryBuffer,
             gpu_texture::{GpuTexture, PixelKind},
             server::GraphicsServer,
         },
+        inspection::{
+            FrameTarget, FrameTargetGroup, BLOOM_GLOW_TARGET, BLOOM_HORIZONTAL_BLUR_TARGET,
+            BLOOM_RESULT_TARGET,
+        },
         make_viewport_matrix, RenderPassStatistics,
     },
 };
@@ -52,16 +56,16 @@ impl BloomRenderer {
         server: &dyn GraphicsServer,
         width: usize,
         height: usize,
+        pixel_kind: PixelKind,
     ) -> Result<Self, FrameworkError> {
         Ok(Self {
             shader: RenderPassContainer::from_str(server, include_str!("../shaders/bloom.shader"))?,
-            blur: GaussianBlur::new(server, width, height, PixelKind::RGBA16F)?,
+            blur: GaussianBlur::new(server, width, height, pixel_kind)?,
             framebuffer: server.create_frame_buffer(
                 None,
-                vec![Attachment {
-                    kind: AttachmentKind::Color,
-                    texture: server.create_2d_render_target(PixelKind::RGBA16F, width, height)?,
-                }],
+                vec![Attachment::color(
+                    server.create_2d_render_target(pixel_kind, width, height)?,
+                )],
             )?,
             width,
             height,
@@ -76,6 +80,26 @@ impl BloomRenderer {
         self.blur.result()
     }
 
+    pub(crate) fn collect_frame_targets(&self, targets: &mut Vec<FrameTarget>) {
+        targets.extend([
+            FrameTarget::new(
+                BLOOM_GLOW_TARGET,
+                FrameTargetGroup::Bloom,
+                self.glow_texture().clone(),
+            ),
+            FrameTarget::new(
+                BLOOM_HORIZONTAL_BLUR_TARGET,
+                FrameTargetGroup::Bloom,
+                self.blur.h_blurred().clone(),
+            ),
+            FrameTarget::new(
+                BLOOM_RESULT_TARGET,
+                FrameTargetGroup::Bloom,
+                self.result().clone(),
+            ),
+        ]);
+    }
+
     pub(crate) fn render(
         &self,
         quad: &GpuGeometryBuffer,
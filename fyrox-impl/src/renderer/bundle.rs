@@ -25,13 +25,13 @@
 use crate::{
     asset::untyped::ResourceKind,
     core::{
-        algebra::{Matrix4, Vector3, Vector4},
+        algebra::{Matrix4, Vector2, Vector3, Vector4},
         arrayvec::ArrayVec,
         color,
         color::Color,
         err_once,
         log::Log,
-        math::{frustum::Frustum, Matrix4Ext, Rect},
+        math::{aabb::AxisAlignedBoundingBox, frustum::Frustum, Matrix4Ext, Rect},
         pool::Handle,
         sstorage::ImmutableString,
     },
@@ -61,6 +61,7 @@ use crate::{
     },
     resource::texture::TextureResource,
     scene::{
+        base::LodMetric,
         graph::Graph,
         light::{
             directional::{CsmOptions, DirectionalLight},
@@ -121,6 +122,11 @@ pub struct RenderContext<'a> {
     pub graph: &'a Graph,
     /// A name of the render pass for which the context was created for.
     pub render_pass_name: &'a ImmutableString,
+    /// Visibility of the current node defined by a LOD group (see
+    /// [`crate::scene::base::LodGroup::visibility`]). `1.0` for the nodes that are not a part of
+    /// a level of detail that is in transition. Should be passed to
+    /// [`SurfaceInstanceData::lod_fade`].
+    pub lod_fade: f32,
 }
 
 impl RenderContext<'_> {
@@ -149,12 +155,79 @@ pub struct BundleRenderContext<'a> {
 
     // Built-in uniforms.
     pub use_pom: bool,
+    /// Whether identical surfaces could be merged into instanced draw calls or not.
+    pub use_instancing: bool,
     pub light_position: &'a Vector3<f32>,
     pub ambient_light: Color,
     // TODO: Add depth pre-pass to remove Option here. Current architecture allows only forward
     // renderer to have access to depth buffer that is available from G-Buffer.
     pub scene_depth: Option<&'a GpuTexture>,
     pub fallback_resources: &'a FallbackResources,
+    /// Previous frame data for motion vectors. [`None`] for every pass, except the G-Buffer pass.
+    pub motion: Option<MotionVectorContext<'a>>,
+}
+
+/// A set of data of the previous frame, that is used to calculate per-pixel motion vectors.
+pub struct MotionVectorContext<'a> {
+    /// View-projection matrix of the observer in the previous frame (without jitter).
+    pub previous_view_projection: Matrix4<f32>,
+    /// Sub-pixel offset of the projection matrix of the current frame in normalized device
+    /// coordinates. It is used to remove the jitter from motion vectors.
+    pub jitter: Vector2<f32>,
+    /// State of the surface instances in the previous frame.
+    pub instance_history: &'a InstanceHistory,
+}
+
+#[derive(Clone)]
+struct PreviousInstanceState {
+    world_transform: Matrix4<f32>,
+    bone_matrices: Vec<Matrix4<f32>>,
+}
+
+/// Stores world transforms and bone matrices of surface instances of the previous frame. Instances
+/// are identified by a pair of a scene node handle and a surface data, so it works for skinned
+/// meshes and for multiple instances of the same surface.
+#[derive(Default)]
+pub struct InstanceHistory {
+    previous: FxHashMap<(Handle<Node>, u64), PreviousInstanceState>,
+}
+
+impl InstanceHistory {
+    /// Remembers the state of every instance of the bundles that pass the given filter. Must be
+    /// called once per frame, after the rendering.
+    pub fn update<F>(&mut self, bundle_storage: &RenderDataBundleStorage, mut bundle_filter: F)
+    where
+        F: FnMut(&RenderDataBundle) -> bool,
+    {
+        self.previous.clear();
+        for bundle in bundle_storage.bundles.iter() {
+            if !bundle_filter(bundle) {
+                continue;
+            }
+            let data_key = bundle.data.key();
+            for instance in bundle.instances.iter() {
+                if instance.node_handle.is_none() {
+                    continue;
+                }
+                self.previous.insert(
+                    (instance.node_handle, data_key),
+                    PreviousInstanceState {
+                        world_transform: instance.world_transform,
+                        bone_matrices: instance.bone_matrices.clone(),
+                    },
+                );
+            }
+        }
+    }
+
+    /// Forgets the state of all instances.
+    pub fn clear(&mut self) {
+        self.previous.clear();
+    }
+
+    fn get(&self, node_handle: Handle<Node>, data_key: u64) -> Option<&PreviousInstanceState> {
+        self.previous.get(&(node_handle, data_key))
+    }
 }
 
 /// A set of data of a surface for rendering.
@@ -170,6 +243,12 @@ pub struct SurfaceInstanceData {
     pub element_range: ElementRange,
     /// A handle of a node that emitted this surface data. Could be none, if there's no info about scene node.
     pub node_handle: Handle<Node>,
+    /// Custom data of the instance, that is available in shaders as `customData` field of the
+    /// `fyrox_instanceData` and `fyrox_instances` built-in property groups.
+    pub custom_data: Vector4<f32>,
+    /// Visibility of the instance in a LOD transition, see [`RenderContext::lod_fade`]. Instances
+    /// that are in transition are never merged into instanced draw calls.
+    pub lod_fade: f32,
 }
 
 /// A set of surface instances that share the same vertex/index data and a material.
@@ -186,6 +265,16 @@ pub struct RenderDataBundle {
     /// A render path of the bundle.
     pub render_path: RenderPath,
     sort_index: u64,
+    // Bundles with the same shader use the same pipeline states, so drawing them one after another
+    // minimizes the amount of pipeline state changes.
+    shader_key: u64,
+}
+
+fn shader_key(material: &MaterialResource) -> u64 {
+    let mut material_state = material.state();
+    material_state
+        .data()
+        .map_or(0, |material| material.shader().key())
 }
 
 impl Debug for RenderDataBundle {
@@ -206,6 +295,9 @@ pub struct InstanceUniformData {
     pub instance_block: UniformBlockLocation,
     /// Bone matrices block location. Could be [`None`], if there's no bone matrices.
     pub bone_matrices_block: Option<UniformBlockLocation>,
+    /// Bone matrices of the previous frame block location. Could be [`None`], if there's no
+    /// bone matrices or no motion vectors are needed.
+    pub previous_bone_matrices_block: Option<UniformBlockLocation>,
 }
 
 /// Describes where to the actual uniform data is located in the memory backed by the uniform
@@ -217,6 +309,18 @@ pub struct BundleUniformData {
     pub light_data_block: UniformBlockLocation,
     /// Block locations for each instance in a bundle.
     pub instance_blocks: Vec<InstanceUniformData>,
+    /// A set of instance groups, each group is drawn in a single draw call. Contains only the
+    /// instances that passed the instance filter.
+    pub instance_batches: Vec<InstanceBatch>,
+}
+
+/// A group of instances of a bundle that is drawn in a single (possibly instanced) draw call.
+pub struct InstanceBatch {
+    /// Indices of the instances of the bundle. Each batch has at least one instance.
+    pub instances: ArrayVec<usize, { ShaderDefinition::MAX_INSTANCES }>,
+    /// Location of the block with per-instance data of the whole batch. Could be [`None`], if the
+    /// shader of the bundle does not use `fyrox_instances` property group.
+    pub instances_block: Option<UniformBlockLocation>,
 }
 
 pub struct GlobalUniformData {
@@ -342,11 +446,15 @@ pub fn write_shader_values<T: ByteStorage>(
 
 impl RenderDataBundle {
     /// Writes all the required uniform data of the bundle to uniform memory allocator.
-    pub fn write_uniforms(
+    pub fn write_uniforms<F>(
         &self,
         view_projection_matrix: &Matrix4<f32>,
+        instance_filter: &mut F,
         render_context: &mut BundleRenderContext,
-    ) -> Option<BundleUniformData> {
+    ) -> Option<BundleUniformData>
+    where
+        F: FnMut(&SurfaceInstanceData) -> bool,
+    {
         let mut material_state = self.material.state();
 
         let material = material_state.data()?;
@@ -395,9 +503,31 @@ impl RenderDataBundle {
             .with(&render_context.ambient_light.as_frgba());
         let light_data_block = render_context.uniform_memory_allocator.allocate(light_data);
 
+        const INIT: Matrix4<f32> = Matrix4::new(
+            0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+        );
+        const SIZE: usize = ShaderDefinition::MAX_BONE_MATRICES * size_of::<Matrix4<f32>>();
+
         // Upload instance uniforms.
+        let data_key = self.data.key();
         let mut instance_blocks = Vec::with_capacity(self.instances.len());
+        let mut previous_world_view_projections = Vec::with_capacity(self.instances.len());
         for instance in self.instances.iter() {
+            let previous_state = render_context.motion.as_ref().and_then(|motion| {
+                motion
+                    .instance_history
+                    .get(instance.node_handle, data_key)
+                    .map(|state| (motion, state))
+            });
+            let previous_world_view_projection = match (&render_context.motion, previous_state) {
+                (_, Some((motion, state))) => {
+                    motion.previous_view_projection * state.world_transform
+                }
+                // The instance has just appeared, so it has no own motion.
+                (Some(motion), None) => motion.previous_view_projection * instance.world_transform,
+                (None, None) => view_projection_matrix * instance.world_transform,
+            };
+
             let mut packed_blend_shape_weights =
                 [Vector4::<f32>::default(); ShaderDefinition::MAX_BLEND_SHAPE_WEIGHT_GROUPS];
 
@@ -410,6 +540,9 @@ impl RenderDataBundle {
             let instance_buffer = StaticUniformBuffer::<1024>::new()
                 .with(&instance.world_transform)
                 .with(&(view_projection_matrix * instance.world_transform))
+                .with(&previous_world_view_projection)
+                .with(&instance.custom_data)
+                .with(&instance.lod_fade)
                 .with(&(instance.blend_shapes_weights.len() as i32))
                 .with(&(!instance.bone_matrices.is_empty()))
                 .with_slice_with_max_size(
@@ -422,46 +555,136 @@ impl RenderDataBundle {
                     .uniform_memory_allocator
                     .allocate(instance_buffer),
                 bone_matrices_block: None,
+                previous_bone_matrices_block: None,
             };
 
             if !instance.bone_matrices.is_empty() {
-                const INIT: Matrix4<f32> = Matrix4::new(
-                    0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0,
-                );
                 let mut matrices = [INIT; ShaderDefinition::MAX_BONE_MATRICES];
-                const SIZE: usize = ShaderDefinition::MAX_BONE_MATRICES * size_of::<Matrix4<f32>>();
                 matrices[0..instance.bone_matrices.len()].copy_from_slice(&instance.bone_matrices);
 
                 let bone_matrices_block = render_context
                     .uniform_memory_allocator
                     .allocate(StaticUniformBuffer::<SIZE>::new().with(&matrices));
                 instance_uniform_data.bone_matrices_block = Some(bone_matrices_block);
+
+                if let Some((_, state)) = previous_state {
+                    if state.bone_matrices.len() == instance.bone_matrices.len() {
+                        matrices[0..state.bone_matrices.len()]
+                            .copy_from_slice(&state.bone_matrices);
+
+                        let previous_bone_matrices_block = render_context
+                            .uniform_memory_allocator
+                            .allocate(StaticUniformBuffer::<SIZE>::new().with(&matrices));
+                        instance_uniform_data.previous_bone_matrices_block =
+                            Some(previous_bone_matrices_block);
+                    }
+                }
             }
 
             instance_blocks.push(instance_uniform_data);
+            previous_world_view_projections.push(previous_world_view_projection);
+        }
+
+        // Group the instances into batches. Skinned instances, instances with blend shapes and
+        // instances in LOD transition have their own uniform data that cannot be shared, so
+        // they're always drawn one-by-one.
+        fn can_be_batched(instance: &SurfaceInstanceData) -> bool {
+            instance.bone_matrices.is_empty()
+                && instance.blend_shapes_weights.is_empty()
+                && instance.lod_fade == 1.0
+        }
+
+        let supports_instancing = shader
+            .definition
+            .resources
+            .iter()
+            .any(|resource| resource.name.as_str() == "fyrox_instances");
+        let max_batch_size = if supports_instancing && render_context.use_instancing {
+            ShaderDefinition::MAX_INSTANCES
+        } else {
+            1
+        };
+        let mut instance_batches = Vec::<InstanceBatch>::new();
+        for (index, instance) in self.instances.iter().enumerate() {
+            if !instance_filter(instance) {
+                continue;
+            }
+
+            if can_be_batched(instance) && max_batch_size > 1 {
+                if let Some(batch) = instance_batches.iter_mut().find(|batch| {
+                    let first = &self.instances[batch.instances[0]];
+                    batch.instances.len() < max_batch_size
+                        && can_be_batched(first)
+                        && first.element_range == instance.element_range
+                }) {
+                    batch.instances.push(index);
+                    continue;
+                }
+            }
+
+            let mut instances = ArrayVec::new();
+            instances.push(index);
+            instance_batches.push(InstanceBatch {
+                instances,
+                instances_block: None,
+            });
+        }
+
+        if supports_instancing {
+            for batch in instance_batches.iter_mut() {
+                let mut world_matrices =
+                    ArrayVec::<Matrix4<f32>, { ShaderDefinition::MAX_INSTANCES }>::new();
+                let mut world_view_projections = world_matrices.clone();
+                let mut previous_world_view_projections_of_batch = world_matrices.clone();
+                let mut custom_data =
+                    ArrayVec::<Vector4<f32>, { ShaderDefinition::MAX_INSTANCES }>::new();
+                for &index in batch.instances.iter() {
+                    let instance = &self.instances[index];
+                    world_matrices.push(instance.world_transform);
+                    world_view_projections.push(view_projection_matrix * instance.world_transform);
+                    previous_world_view_projections_of_batch
+                        .push(previous_world_view_projections[index]);
+                    custom_data.push(instance.custom_data);
+                }
+
+                let instances_buffer = StaticUniformBuffer::<16384>::new()
+                    .with_slice_with_max_size(&world_matrices, ShaderDefinition::MAX_INSTANCES)
+                    .with_slice_with_max_size(
+                        &world_view_projections,
+                        ShaderDefinition::MAX_INSTANCES,
+                    )
+                    .with_slice_with_max_size(
+                        &previous_world_view_projections_of_batch,
+                        ShaderDefinition::MAX_INSTANCES,
+                    )
+                    .with_slice_with_max_size(&custom_data, ShaderDefinition::MAX_INSTANCES);
+
+                batch.instances_block = Some(
+                    render_context
+                        .uniform_memory_allocator
+                        .allocate(instances_buffer),
+                );
+            }
         }
 
         Some(BundleUniformData {
             material_property_group_blocks,
             light_data_block,
             instance_blocks,
+            instance_batches,
         })
     }
 
     /// Draws the entire bundle to the specified frame buffer with the specified rendering environment.
-    pub fn render_to_frame_buffer<F>(
+    pub fn render_to_frame_buffer(
         &self,
         server: &dyn GraphicsServer,
         geometry_cache: &mut GeometryCache,
         shader_cache: &mut ShaderCache,
-        instance_filter: &mut F,
         render_context: &mut BundleRenderContext,
         bundle_uniform_data: BundleUniformData,
         global_uniform_data: &GlobalUniformData,
-    ) -> Result<RenderPassStatistics, FrameworkError>
-    where
-        F: FnMut(&SurfaceInstanceData) -> bool,
-    {
+    ) -> Result<RenderPassStatistics, FrameworkError> {
         let mut stats = RenderPassStatistics::default();
 
         let mut material_state = self.material.state();
@@ -477,7 +700,9 @@ impl RenderDataBundle {
         };
 
         let geometry = match geometry_cache.get(server, &self.data, self.time_to_live) {
-            Ok(geometry) => geometry,
+            Ok(Some(geometry)) => geometry,
+            // Waiting for upload.
+            Ok(None) => return Ok(stats),
             Err(err) => {
                 err_once!(
                     self.data.key() as usize,
@@ -570,18 +795,25 @@ impl RenderDataBundle {
                     );
                 }
                 _ => match resource_definition.kind {
-                    ShaderResourceKind::Texture { fallback, .. } => {
-                        let fallback = render_context.fallback_resources.sampler_fallback(fallback);
+                    ShaderResourceKind::Texture { kind, fallback } => {
+                        let fallback = render_context
+                            .fallback_resources
+                            .sampler_fallback_of_kind(kind, fallback);
 
-                        let texture = if let Some(binding) =
+                        let (texture, sampler) = if let Some(binding) =
                             material.binding_ref(resource_definition.name.clone())
                         {
                             if let material::MaterialResourceBinding::Texture(binding) = binding {
-                                binding
+                                let texture = binding
                                     .value
                                     .as_ref()
                                     .and_then(|t| render_context.texture_cache.get(server, t))
                                     .unwrap_or(fallback)
+                                    .clone();
+                                let sampler = binding.sampler.as_ref().and_then(|s| {
+                                    render_context.texture_cache.get_sampler(server, s).cloned()
+                                });
+                                (texture, sampler)
                             } else {
                                 Log::err(format!(
                                     "Unable to use texture binding {}, types mismatch! Expected \
@@ -589,16 +821,21 @@ impl RenderDataBundle {
                                     resource_definition.name, resource_definition.kind, binding
                                 ));
 
-                                fallback
+                                (fallback.clone(), None)
                             }
                         } else {
-                            fallback
+                            (fallback.clone(), None)
                         };
 
-                        material_bindings.push(ResourceBinding::texture(
-                            texture,
-                            resource_definition.binding,
-                        ));
+                        material_bindings.push(if let Some(sampler) = sampler {
+                            ResourceBinding::texture_with_sampler(
+                                &texture,
+                                &sampler,
+                                resource_definition.binding,
+                            )
+                        } else {
+                            ResourceBinding::texture(&texture, resource_definition.binding)
+                        });
                     }
                     ShaderResourceKind::PropertyGroup(_) => {
                         // No validation here, it is done in uniform variables collection step.
@@ -618,14 +855,10 @@ impl RenderDataBundle {
             }
         }
 
-        for (instance, uniform_data) in self
-            .instances
-            .iter()
-            .zip(bundle_uniform_data.instance_blocks)
-        {
-            if !instance_filter(instance) {
-                continue;
-            }
+        for batch in bundle_uniform_data.instance_batches.iter() {
+            let first_instance = batch.instances[0];
+            let element_range = self.instances[first_instance].element_range;
+            let uniform_data = &bundle_uniform_data.instance_blocks[first_instance];
             let mut instance_bindings = ArrayVec::<ResourceBinding, 32>::new();
 
             for resource_definition in shader.definition.resources.iter() {
@@ -639,6 +872,15 @@ impl RenderDataBundle {
                             ),
                         );
                     }
+                    "fyrox_instances" => {
+                        if let Some(block) = batch.instances_block {
+                            instance_bindings.push(
+                                render_context
+                                    .uniform_memory_allocator
+                                    .block_to_binding(block, resource_definition.binding),
+                            );
+                        }
+                    }
                     "fyrox_boneMatrices" => {
                         match uniform_data.bone_matrices_block {
                             Some(block) => {
@@ -662,24 +904,51 @@ impl RenderDataBundle {
                             }
                         }
                     }
+                    "fyrox_previousBoneMatrices" => {
+                        // Fallback to the current bone matrices, if there's no previous state.
+                        match uniform_data
+                            .previous_bone_matrices_block
+                            .or(uniform_data.bone_matrices_block)
+                        {
+                            Some(block) => {
+                                instance_bindings.push(
+                                    render_context
+                                        .uniform_memory_allocator
+                                        .block_to_binding(block, resource_definition.binding),
+                                );
+                            }
+                            None => {
+                                instance_bindings.push(ResourceBinding::Buffer {
+                                    buffer: render_context
+                                        .fallback_resources
+                                        .bone_matrices_stub_uniform_buffer
+                                        .clone(),
+                                    binding: resource_definition.binding,
+                                    data_usage: Default::default(),
+                                });
+                            }
+                        }
+                    }
                     _ => (),
                 };
             }
 
-            stats += render_context.frame_buffer.draw(
+            let resources = [
+                ResourceBindGroup {
+                    bindings: &material_bindings,
+                },
+                ResourceBindGroup {
+                    bindings: &instance_bindings,
+                },
+            ];
+
+            stats += render_context.frame_buffer.draw_with_pipeline_state(
+                batch.instances.len(),
                 geometry,
                 render_context.viewport,
-                &render_pass.program,
-                &render_pass.draw_params,
-                &[
-                    ResourceBindGroup {
-                        bindings: &material_bindings,
-                    },
-                    ResourceBindGroup {
-                        bindings: &instance_bindings,
-                    },
-                ],
-                instance.element_range,
+                &render_pass.pipeline_state,
+                &resources,
+                element_range,
             )?;
         }
 
@@ -767,6 +1036,27 @@ pub struct LightSource {
     pub scatter: Vector3<f32>,
 }
 
+// Calculates a portion of the screen height, that is covered by the bounding sphere of the given
+// bounding box.
+fn screen_coverage(observer_info: &ObserverInfo, bounding_box: &AxisAlignedBoundingBox) -> f32 {
+    let radius = bounding_box.half_extents().norm();
+    let projection = &observer_info.projection_matrix;
+    let coverage = if projection[(3, 3)] == 1.0 {
+        // Orthographic projection, the size does not depend on the distance.
+        radius * projection[(1, 1)]
+    } else {
+        let distance = observer_info
+            .observer_position
+            .metric_distance(&bounding_box.center());
+        if distance <= radius {
+            1.0
+        } else {
+            radius * projection[(1, 1)] / distance
+        }
+    };
+    coverage.clamp(0.0, 1.0)
+}
+
 /// Bundle storage handles bundle generation for a scene before rendering. It is used to optimize
 /// rendering by reducing amount of state changes of OpenGL context.
 pub struct RenderDataBundleStorage {
@@ -823,20 +1113,28 @@ impl RenderDataBundleStorage {
         )
         .unwrap_or_default();
 
-        let mut lod_filter = vec![true; graph.capacity() as usize];
+        let mut lod_fades = vec![1.0; graph.capacity() as usize];
         for (node_handle, node) in graph.pair_iter() {
             if let Some(lod_group) = node.lod_group() {
                 for level in lod_group.levels.iter() {
                     for &object in level.objects.iter() {
                         if let Some(object_ref) = graph.try_get(object) {
-                            let distance = observer_info
-                                .observer_position
-                                .metric_distance(&object_ref.global_position());
-                            let z_range = observer_info.z_far - observer_info.z_near;
-                            let normalized_distance = (distance - observer_info.z_near) / z_range;
-                            let visible = normalized_distance >= level.begin()
-                                && normalized_distance <= level.end();
-                            lod_filter[object.index() as usize] = visible;
+                            let value = match lod_group.metric {
+                                LodMetric::Distance => {
+                                    let distance = observer_info
+                                        .observer_position
+                                        .metric_distance(&object_ref.global_position());
+                                    let z_range = observer_info.z_far - observer_info.z_near;
+                                    (distance - observer_info.z_near) / z_range
+                                }
+                                LodMetric::ScreenCoverage => {
+                                    1.0 - screen_coverage(
+                                        &observer_info,
+                                        &object_ref.world_bounding_box(),
+                                    )
+                                }
+                            };
+                            lod_fades[object.index() as usize] = lod_group.visibility(level, value);
                         }
                     }
                 }
@@ -898,35 +1196,47 @@ impl RenderDataBundleStorage {
             storage: &mut storage,
             graph,
             render_pass_name: &render_pass_name,
+            lod_fade: 1.0,
         };
 
         #[inline(always)]
         fn iterate_recursive(
             node_handle: Handle<Node>,
             graph: &Graph,
-            lod_filter: &[bool],
+            lod_fades: &[f32],
+            parent_lod_fade: f32,
             ctx: &mut RenderContext,
         ) {
-            if lod_filter[node_handle.index() as usize] {
+            let lod_fade = lod_fades[node_handle.index() as usize];
+            if lod_fade != 0.0 {
+                // Descendants of a level in transition fade together with it.
+                let lod_fade = if lod_fade != 1.0 {
+                    lod_fade
+                } else {
+                    parent_lod_fade
+                };
                 let node = graph.node(node_handle);
+                ctx.lod_fade = lod_fade;
                 if let RdcControlFlow::Continue = node.collect_render_data(ctx) {
                     for child in node.children() {
-                        iterate_recursive(*child, graph, lod_filter, ctx);
+                        iterate_recursive(*child, graph, lod_fades, lod_fade, ctx);
                     }
                 }
             }
         }
 
-        iterate_recursive(graph.root(), graph, &lod_filter, &mut ctx);
+        iterate_recursive(graph.root(), graph, &lod_fades, 1.0, &mut ctx);
 
         storage.sort();
 
         storage
     }
 
-    /// Sorts the bundles by their respective sort index.
+    /// Sorts the bundles by their respective sort index. Bundles with the same sort index are
+    /// grouped by their shaders and materials to minimize the amount of pipeline state changes.
     pub fn sort(&mut self) {
-        self.bundles.sort_unstable_by_key(|b| b.sort_index);
+        self.bundles
+            .sort_unstable_by_key(|b| (b.sort_index, b.shader_key, b.material.key()));
     }
 
     pub fn write_global_uniform_blocks(
@@ -1000,7 +1310,13 @@ impl RenderDataBundleStorage {
             .with(&camera_side)
             .with(&self.observer_info.z_near)
             .with(&self.observer_info.z_far)
-            .with(&(self.observer_info.z_far - self.observer_info.z_near));
+            .with(&(self.observer_info.z_far - self.observer_info.z_near))
+            .with(
+                &render_context
+                    .motion
+                    .as_ref()
+                    .map_or_else(Vector2::default, |motion| motion.jitter),
+            );
         let camera_block = render_context
             .uniform_memory_allocator
             .allocate(camera_uniforms);
@@ -1039,8 +1355,11 @@ impl RenderDataBundleStorage {
             if !bundle_filter(bundle) {
                 continue;
             }
-            bundle_uniform_data_set
-                .push(bundle.write_uniforms(&view_projection, &mut render_context));
+            bundle_uniform_data_set.push(bundle.write_uniforms(
+                &view_projection,
+                &mut instance_filter,
+                &mut render_context,
+            ));
         }
         render_context.uniform_memory_allocator.upload(server)?;
 
@@ -1056,7 +1375,6 @@ impl RenderDataBundleStorage {
                     server,
                     geometry_cache,
                     shader_cache,
-                    &mut instance_filter,
                     &mut render_context,
                     bundle_uniform_data,
                     &global_uniforms,
@@ -1129,6 +1447,7 @@ impl RenderDataBundleStorageTrait for RenderDataBundleStorage {
             self.bundles.push(RenderDataBundle {
                 data,
                 sort_index,
+                shader_key: shader_key(material),
                 instances: vec![
                     // Each bundle must have at least one instance to be rendered.
                     SurfaceInstanceData {
@@ -1137,6 +1456,8 @@ impl RenderDataBundleStorageTrait for RenderDataBundleStorage {
                         blend_shapes_weights: Default::default(),
                         element_range: Default::default(),
                         node_handle,
+                        custom_data: Default::default(),
+                        lod_fade: 1.0,
                     },
                 ],
                 material: material.clone(),
@@ -1180,6 +1501,7 @@ impl RenderDataBundleStorageTrait for RenderDataBundleStorage {
             self.bundles.push(RenderDataBundle {
                 data: data.clone(),
                 sort_index,
+                shader_key: shader_key(material),
                 instances: Default::default(),
                 material: material.clone(),
                 render_path,
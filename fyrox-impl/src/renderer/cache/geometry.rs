@@ -21,7 +21,7 @@
 use crate::renderer::framework::GeometryBufferExt;
 use crate::{
     renderer::{
-        cache::{TemporaryCache, TimeToLive},
+        cache::{texture::UploadSettings, TemporaryCache, TimeToLive},
         framework::{error::FrameworkError, server::GraphicsServer},
     },
     scene::mesh::surface::{SurfaceData, SurfaceResource},
@@ -39,6 +39,14 @@ struct SurfaceRenderData {
 #[derive(Default)]
 pub struct GeometryCache {
     buffer: TemporaryCache<SurfaceRenderData>,
+    settings: UploadSettings,
+    // Amount of bytes of the new buffers, that were uploaded in the current frame.
+    uploaded_bytes: usize,
+}
+
+fn surface_data_size(data: &SurfaceData) -> usize {
+    data.vertex_buffer.raw_data().len()
+        + std::mem::size_of_val(data.geometry_buffer.triangles_ref())
 }
 
 fn create_geometry_buffer(
@@ -57,14 +65,28 @@ fn create_geometry_buffer(
 }
 
 impl GeometryCache {
+    /// Returns a geometry buffer for the given surface data, creating it if needed. New buffers,
+    /// that are bigger than the immediate upload threshold, are created only within the per-frame
+    /// budget (see [`UploadSettings`]), `None` is returned if the budget is exhausted - the buffer
+    /// will be created on one of the next frames.
     pub fn get<'a>(
         &'a mut self,
         server: &dyn GraphicsServer,
         data: &SurfaceResource,
         time_to_live: TimeToLive,
-    ) -> Result<&'a GpuGeometryBuffer, FrameworkError> {
+    ) -> Result<Option<&'a GpuGeometryBuffer>, FrameworkError> {
         let data = data.data_ref();
 
+        if self.buffer.get_mut(&data.cache_index).is_none() {
+            let size = surface_data_size(&data);
+            if size >= self.settings.immediate_upload_threshold {
+                if !self.settings.fits_budget(self.uploaded_bytes, size) {
+                    return Ok(None);
+                }
+                self.uploaded_bytes += size;
+            }
+        }
+
         match self
             .buffer
             .get_entry_mut_or_insert_with(&data.cache_index, time_to_live, || {
@@ -96,7 +118,7 @@ impl GeometryCache {
                             data.geometry_buffer.modifications_count();
                     }
                 }
-                Ok(&entry.buffer)
+                Ok(Some(&entry.buffer))
             }
             Err(err) => Err(err),
         }
@@ -104,6 +126,15 @@ impl GeometryCache {
 
     pub fn update(&mut self, dt: f32) {
         self.buffer.update(dt);
+        self.uploaded_bytes = 0;
+    }
+
+    pub fn upload_settings(&self) -> UploadSettings {
+        self.settings
+    }
+
+    pub fn set_upload_settings(&mut self, settings: UploadSettings) {
+        self.settings = settings;
     }
 
     pub fn clear(&mut self) {
@@ -19,7 +19,7 @@
 // SOFTWARE.
 
 use crate::{
-    core::{arrayvec::ArrayVec, log::Log, math::Rect, sstorage::ImmutableString},
+    core::{arrayvec::ArrayVec, math::Rect, sstorage::ImmutableString},
     material::{
         shader::{Shader, ShaderResource},
         MaterialPropertyRef,
@@ -27,12 +27,14 @@ use crate::{
     renderer::{
         bundle,
         cache::{uniform::UniformBufferCache, TemporaryCache},
+        diagnostics::{RenderErrorBroadcaster, RenderErrorSource},
         framework::{
             error::FrameworkError,
             framebuffer::{DrawCallStatistics, GpuFrameBuffer, ResourceBindGroup, ResourceBinding},
             geometry_buffer::GpuGeometryBuffer,
             gpu_program::{GpuProgram, ShaderResourceDefinition, ShaderResourceKind},
             gpu_texture::GpuTexture,
+            pipeline::GpuPipelineState,
             server::GraphicsServer,
             DrawParameters, ElementRange,
         },
@@ -190,6 +192,7 @@ impl<'a, 'b, const N: usize> From<[NamedValue<GpuResourceBinding<'a, 'b>>; N]>
 pub struct RenderPassData {
     pub program: GpuProgram,
     pub draw_params: DrawParameters,
+    pub pipeline_state: GpuPipelineState,
 }
 
 pub struct RenderPassContainer {
@@ -215,18 +218,19 @@ impl RenderPassContainer {
                 &shader.definition.resources,
             ) {
                 Ok(gpu_program) => {
+                    let pipeline_state =
+                        server.create_pipeline_state(&gpu_program, &render_pass.draw_parameters)?;
                     render_passes.insert(
                         ImmutableString::new(&render_pass.name),
                         RenderPassData {
                             program: gpu_program,
                             draw_params: render_pass.draw_parameters.clone(),
+                            pipeline_state,
                         },
                     );
                 }
                 Err(e) => {
-                    return Err(FrameworkError::Custom(format!(
-                        "Failed to create {program_name} shader' GPU program. Reason: {e:?}"
-                    )));
+                    return Err(e.context(format!("Failed to create {program_name} GPU program")));
                 }
             };
         }
@@ -323,11 +327,8 @@ impl RenderPassContainer {
                         bundle::write_shader_values(shader_property_group, &mut buf)
                     }
 
-                    resource_bindings.push(ResourceBinding::buffer(
-                        &uniform_buffer_cache.write(buf)?,
-                        resource.binding,
-                        Default::default(),
-                    ));
+                    resource_bindings
+                        .push(uniform_buffer_cache.write_binding(buf, resource.binding)?);
                 }
             }
         }
@@ -336,22 +337,22 @@ impl RenderPassContainer {
             bindings: &resource_bindings,
         }];
 
-        if instance_count == 1 {
-            framebuffer.draw(
+        if let Some(override_params) = override_params {
+            framebuffer.draw_instances(
+                instance_count,
                 geometry,
                 viewport,
                 &render_pass.program,
-                override_params.unwrap_or(&render_pass.draw_params),
+                override_params,
                 &resources,
                 element_range,
             )
         } else {
-            framebuffer.draw_instances(
+            framebuffer.draw_with_pipeline_state(
                 instance_count,
                 geometry,
                 viewport,
-                &render_pass.program,
-                override_params.unwrap_or(&render_pass.draw_params),
+                &render_pass.pipeline_state,
                 &resources,
                 element_range,
             )
@@ -362,6 +363,7 @@ impl RenderPassContainer {
 #[derive(Default)]
 pub struct ShaderCache {
     pub(super) cache: TemporaryCache<RenderPassContainer>,
+    pub(crate) errors: RenderErrorBroadcaster,
 }
 
 impl ShaderCache {
@@ -387,7 +389,10 @@ impl ShaderCache {
             ) {
                 Ok(shader_set) => Some(shader_set),
                 Err(error) => {
-                    Log::err(format!("{error}"));
+                    self.errors.report(
+                        RenderErrorSource::Shader,
+                        error.context(format!("Failed to create {} shader", shader.kind())),
+                    );
                     None
                 }
             }
@@ -19,9 +19,11 @@
 // SOFTWARE.
 
 use crate::{
-    core::log::{Log, MessageKind},
+    core::log::Log,
+    fxhash::{FxHashMap, FxHashSet},
     renderer::{
         cache::{TemporaryCache, TimeToLive},
+        diagnostics::{RenderErrorBroadcaster, RenderErrorSource},
         framework::{
             error::FrameworkError,
             gpu_texture::{Coordinate, PixelKind},
@@ -30,23 +32,124 @@ use crate::{
     },
     resource::texture::{Texture, TextureResource},
 };
-use fyrox_graphics::gpu_texture::{
-    GpuTexture, GpuTextureDescriptor, GpuTextureKind, MagnificationFilter, MinificationFilter,
-    WrapMode,
+use fyrox_graphics::{
+    gpu_texture::{
+        image_1d_size_bytes, image_2d_size_bytes, image_3d_size_bytes, GpuTexture,
+        GpuTextureDescriptor, GpuTextureKind, MagnificationFilter, MinificationFilter, WrapMode,
+    },
+    sampler::{GpuSampler, GpuSamplerDescriptor},
+    upload::{TextureSource, TextureUploader},
 };
 use fyrox_texture::{
     TextureKind, TextureMagnificationFilter, TextureMinificationFilter, TexturePixelKind,
-    TextureWrapMode,
+    TextureSampler, TextureWrapMode,
 };
+use std::collections::VecDeque;
 
 pub(crate) struct TextureRenderData {
     pub gpu_texture: GpuTexture,
     pub modifications_counter: u64,
 }
 
+/// Limits of the upload queues. Textures and geometry buffers are uploaded to GPU memory
+/// gradually, to prevent hitches when there are lots of (or just huge) resources loaded at once.
+/// Textures and buffers have separate budgets, each of them is limited by the settings.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct UploadSettings {
+    /// The maximum amount of bytes, that could be uploaded to GPU memory per frame. At least one
+    /// texture (or buffer) is uploaded per frame regardless of its size, so huge resources won't
+    /// stay in the queue forever.
+    pub bytes_per_frame: usize,
+    /// Resources, that are smaller than this amount of bytes, are uploaded right when they are
+    /// needed for rendering, bypassing the queue.
+    pub immediate_upload_threshold: usize,
+    /// Whether to upload textures on a separate thread (if supported by the graphics server).
+    /// When enabled, the render thread only wraps the textures that were uploaded by the worker
+    /// thread, which is almost free.
+    pub use_upload_thread: bool,
+}
+
+impl Default for UploadSettings {
+    fn default() -> Self {
+        Self {
+            bytes_per_frame: 8 * 1024 * 1024,
+            immediate_upload_threshold: 64 * 1024,
+            use_upload_thread: true,
+        }
+    }
+}
+
+impl UploadSettings {
+    /// Checks whether a resource of the given size could be uploaded, when the given amount of
+    /// bytes was already uploaded in the current frame.
+    pub(crate) fn fits_budget(&self, uploaded_bytes: usize, size: usize) -> bool {
+        uploaded_bytes == 0 || uploaded_bytes + size <= self.bytes_per_frame
+    }
+}
+
+/// Reads texture data on the upload thread directly from the texture resource.
+struct TextureResourceSource(TextureResource);
+
+impl TextureSource for TextureResourceSource {
+    fn read(&self, func: &mut dyn FnMut(GpuTextureDescriptor)) {
+        let mut state = self.0.state();
+        if let Some(texture) = state.data() {
+            func(make_descriptor(texture))
+        }
+    }
+}
+
+struct InFlightUpload {
+    texture: TextureResource,
+    modifications_count: u64,
+}
+
+#[derive(Default)]
+struct UploadQueue {
+    settings: UploadSettings,
+    pending: VecDeque<TextureResource>,
+    // Keys of the textures that are either pending or in flight.
+    queued: FxHashSet<u64>,
+    in_flight: FxHashMap<u64, InFlightUpload>,
+    uploader: Option<Box<dyn TextureUploader>>,
+    uploader_requested: bool,
+    next_request_id: u64,
+}
+
+impl UploadQueue {
+    fn push(&mut self, texture: TextureResource) {
+        if self.queued.insert(texture.key()) {
+            self.pending.push_back(texture);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.pending.clear();
+        self.queued.clear();
+        self.in_flight.clear();
+    }
+
+    /// Discards the uploader and puts every in-flight texture back in the pending queue (in the
+    /// order of requests), so the textures will be uploaded on the render thread. Responses for the
+    /// in-flight requests will never arrive after this, because the uploader is destroyed.
+    fn discard_uploader(&mut self) {
+        self.uploader = None;
+
+        let mut in_flight = self.in_flight.drain().collect::<Vec<_>>();
+        in_flight.sort_by_key(|(id, _)| *id);
+        for (_, upload) in in_flight.into_iter().rev() {
+            self.pending.push_front(upload.texture);
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct TextureCache {
     cache: TemporaryCache<TextureRenderData>,
+    queue: UploadQueue,
+    // Usually there's just a handful of unique samplers, so linear search is fine here.
+    samplers: Vec<(TextureSampler, GpuSampler)>,
+    pub(crate) errors: RenderErrorBroadcaster,
 }
 
 fn convert_texture_kind(v: TextureKind) -> GpuTextureKind {
@@ -132,31 +235,111 @@ fn convert_wrap_mode(v: TextureWrapMode) -> WrapMode {
     }
 }
 
+fn make_sampler_descriptor(sampler: &TextureSampler) -> GpuSamplerDescriptor {
+    GpuSamplerDescriptor {
+        min_filter: convert_minification_filter(sampler.minification_filter),
+        mag_filter: convert_magnification_filter(sampler.magnification_filter),
+        s_wrap_mode: convert_wrap_mode(sampler.s_wrap_mode),
+        t_wrap_mode: convert_wrap_mode(sampler.t_wrap_mode),
+        r_wrap_mode: convert_wrap_mode(sampler.r_wrap_mode),
+        anisotropy: sampler.anisotropy,
+        border_color: sampler.border_color,
+        ..Default::default()
+    }
+}
+
+fn make_descriptor(texture: &Texture) -> GpuTextureDescriptor {
+    GpuTextureDescriptor {
+        kind: convert_texture_kind(texture.kind()),
+        pixel_kind: convert_pixel_kind(texture.pixel_kind()),
+        mag_filter: convert_magnification_filter(texture.magnification_filter()),
+        min_filter: convert_minification_filter(texture.minification_filter()),
+        mip_count: texture.mip_count() as usize,
+        s_wrap_mode: convert_wrap_mode(texture.s_wrap_mode()),
+        t_wrap_mode: convert_wrap_mode(texture.t_wrap_mode()),
+        r_wrap_mode: convert_wrap_mode(texture.r_wrap_mode()),
+        anisotropy: texture.anisotropy_level(),
+        data: Some(texture.data()),
+        base_level: texture.base_level(),
+        max_level: texture.max_level(),
+        min_lod: texture.min_lod(),
+        max_lod: texture.max_lod(),
+        lod_bias: texture.lod_bias(),
+        ..Default::default()
+    }
+}
+
+/// Generates mips of the GPU texture, if the texture requires it, and restores the mip levels
+/// range defined by the texture.
+fn generate_mips_if_needed(gpu_texture: &GpuTexture, texture: &Texture) {
+    if !texture.generate_mips_on_gpu() || texture.mip_count() > 1 {
+        return;
+    }
+
+    match gpu_texture.generate_mipmaps() {
+        Ok(()) => {
+            let max_level = texture.max_level().min(gpu_texture.max_level());
+            gpu_texture.set_max_level(max_level);
+            gpu_texture.set_base_level(texture.base_level().min(max_level));
+        }
+        Err(e) => Log::err(format!("Unable to generate mips on GPU. Reason: {e:?}")),
+    }
+}
+
+/// Uploads modified texture data to GPU. Uploads only the modified region of the texture (if
+/// any and if it is possible), otherwise uploads the entire texture.
+fn upload_modified_data(
+    gpu_texture: &GpuTexture,
+    texture: &mut Texture,
+    uploaded_modifications_count: u64,
+) -> Result<(), FrameworkError> {
+    if let Some(dirty_region) = texture.take_dirty_region() {
+        // The region is valid only if the GPU texture has the same content as the texture had
+        // before the first modification of the region.
+        if dirty_region.modifications_count == uploaded_modifications_count
+            && texture.mip_count() == 1
+        {
+            if let (TextureKind::Rectangle { width, .. }, Some(pixel_size)) =
+                (texture.kind(), texture.pixel_kind().size_in_bytes())
+            {
+                let rect = dirty_region.rect;
+                let row_size = rect.w() as usize * pixel_size;
+                let mut region_data = Vec::with_capacity(row_size * rect.h() as usize);
+                for y in rect.y()..(rect.y() + rect.h()) {
+                    let begin = (y as usize * width as usize + rect.x() as usize) * pixel_size;
+                    region_data.extend_from_slice(&texture.data()[begin..(begin + row_size)]);
+                }
+                return gpu_texture.set_region_data(
+                    rect.x() as usize,
+                    rect.y() as usize,
+                    rect.w() as usize,
+                    rect.h() as usize,
+                    &region_data,
+                );
+            }
+        }
+    }
+
+    gpu_texture.set_data(
+        convert_texture_kind(texture.kind()),
+        convert_pixel_kind(texture.pixel_kind()),
+        texture.mip_count() as usize,
+        Some(texture.data()),
+    )
+}
+
 fn create_gpu_texture(
     server: &dyn GraphicsServer,
     texture: &Texture,
 ) -> Result<TextureRenderData, FrameworkError> {
     server
-        .create_texture(GpuTextureDescriptor {
-            kind: convert_texture_kind(texture.kind()),
-            pixel_kind: convert_pixel_kind(texture.pixel_kind()),
-            mag_filter: convert_magnification_filter(texture.magnification_filter()),
-            min_filter: convert_minification_filter(texture.minification_filter()),
-            mip_count: texture.mip_count() as usize,
-            s_wrap_mode: convert_wrap_mode(texture.s_wrap_mode()),
-            t_wrap_mode: convert_wrap_mode(texture.t_wrap_mode()),
-            r_wrap_mode: convert_wrap_mode(texture.r_wrap_mode()),
-            anisotropy: texture.anisotropy_level(),
-            data: Some(texture.data()),
-            base_level: texture.base_level(),
-            max_level: texture.max_level(),
-            min_lod: texture.min_lod(),
-            max_lod: texture.max_lod(),
-            lod_bias: texture.lod_bias(),
-        })
-        .map(|gpu_texture| TextureRenderData {
-            gpu_texture,
-            modifications_counter: texture.modifications_count(),
+        .create_texture(make_descriptor(texture))
+        .map(|gpu_texture| {
+            generate_mips_if_needed(&gpu_texture, texture);
+            TextureRenderData {
+                gpu_texture,
+                modifications_counter: texture.modifications_count(),
+            }
         })
 }
 
@@ -188,9 +371,23 @@ impl TextureCache {
         server: &dyn GraphicsServer,
         texture_resource: &TextureResource,
     ) -> Option<&GpuTexture> {
+        // Do not lock the texture that is waiting for upload, it could be locked by the upload
+        // thread.
+        if self.queue.queued.contains(&texture_resource.key()) {
+            return None;
+        }
+
         let mut texture_data_guard = texture_resource.state();
 
         if let Some(texture) = texture_data_guard.data() {
+            if self.cache.get_mut(&texture.cache_index).is_none()
+                && texture.data().len() > self.queue.settings.immediate_upload_threshold
+            {
+                drop(texture_data_guard);
+                self.queue.push(texture_resource.clone());
+                return None;
+            }
+
             match self.cache.get_mut_or_insert_with(
                 &texture.cache_index,
                 Default::default(),
@@ -202,17 +399,20 @@ impl TextureCache {
                     // Data might change from last frame, so we have to check it and upload new if so.
                     let modifications_count = texture.modifications_count();
                     if entry.modifications_counter != modifications_count {
-                        if let Err(e) = entry.gpu_texture.set_data(
-                            convert_texture_kind(texture.kind()),
-                            convert_pixel_kind(texture.pixel_kind()),
-                            texture.mip_count() as usize,
-                            Some(texture.data()),
+                        if let Err(e) = upload_modified_data(
+                            &entry.gpu_texture,
+                            texture,
+                            entry.modifications_counter,
                         ) {
-                            Log::writeln(
-                                MessageKind::Error,
-                                format!("Unable to upload new texture data to GPU. Reason: {e:?}"),
-                            )
+                            self.errors.report(
+                                RenderErrorSource::Texture,
+                                e.context(format!(
+                                    "Unable to upload new data of {} texture to GPU",
+                                    texture_resource.kind()
+                                )),
+                            );
                         } else {
+                            generate_mips_if_needed(&entry.gpu_texture, texture);
                             entry.modifications_counter = modifications_count;
                         }
                     }
@@ -253,13 +453,12 @@ impl TextureCache {
                 }
                 Err(e) => {
                     drop(texture_data_guard);
-                    Log::writeln(
-                        MessageKind::Error,
-                        format!(
-                            "Failed to create GPU texture from {} texture. Reason: {:?}",
-                            texture_resource.kind(),
-                            e
-                        ),
+                    self.errors.report(
+                        RenderErrorSource::Texture,
+                        e.context(format!(
+                            "Failed to create GPU texture from {} texture",
+                            texture_resource.kind()
+                        )),
                     );
                 }
             }
@@ -271,11 +470,195 @@ impl TextureCache {
         self.cache.update(dt)
     }
 
+    /// Puts the texture in the upload queue. The texture will be uploaded to GPU memory on one
+    /// of the next calls of [`Self::process_uploads`].
+    pub fn enqueue(&mut self, texture: TextureResource) {
+        self.queue.push(texture);
+    }
+
+    /// Uploads the textures from the upload queue to GPU memory, until the per-frame budget is
+    /// exhausted (see [`UploadSettings`] docs). The actual upload could be done on a separate
+    /// thread, in this case the textures will be available on one of the next frames.
+    pub fn process_uploads(&mut self, server: &dyn GraphicsServer) {
+        if self.queue.settings.use_upload_thread && !self.queue.uploader_requested {
+            self.queue.uploader_requested = true;
+            self.queue.uploader = server.create_texture_uploader();
+        }
+
+        let finished_uploads = match self.queue.uploader.as_ref().map(|u| u.poll()) {
+            Some(Ok(finished_uploads)) => finished_uploads,
+            Some(Err(e)) => {
+                Log::warn(format!(
+                    "Texture upload thread has failed, textures will be uploaded on \
+                    the render thread. Reason: {e:?}"
+                ));
+                self.queue.discard_uploader();
+                Default::default()
+            }
+            None => Default::default(),
+        };
+
+        for finished in finished_uploads {
+            let Some(in_flight) = self.queue.in_flight.remove(&finished.id) else {
+                continue;
+            };
+
+            self.queue.queued.remove(&in_flight.texture.key());
+
+            match finished.result {
+                Ok(gpu_texture) => {
+                    if let Some(texture) = in_flight.texture.state().data() {
+                        if self.cache.get_mut(&texture.cache_index).is_none() {
+                            generate_mips_if_needed(&gpu_texture, texture);
+                            self.cache.spawn(
+                                TextureRenderData {
+                                    gpu_texture,
+                                    modifications_counter: in_flight.modifications_count,
+                                },
+                                texture.cache_index.clone(),
+                                TimeToLive::default(),
+                            );
+                        }
+                    }
+                }
+                Err(e) => self.errors.report(
+                    RenderErrorSource::Texture,
+                    e.context(format!(
+                        "Failed to upload {} texture to GPU",
+                        in_flight.texture.kind()
+                    )),
+                ),
+            }
+        }
+
+        let mut uploaded_bytes = 0;
+        while let Some(resource) = self.queue.pending.pop_front() {
+            let key = resource.key();
+            let mut state = resource.state();
+
+            let Some(texture) = state.data() else {
+                // Failed to load, there's nothing to upload.
+                drop(state);
+                self.queue.queued.remove(&key);
+                continue;
+            };
+
+            if self.cache.get_mut(&texture.cache_index).is_some() {
+                drop(state);
+                self.queue.queued.remove(&key);
+                continue;
+            }
+
+            let size = texture.data().len();
+            if !self.queue.settings.fits_budget(uploaded_bytes, size) {
+                drop(state);
+                self.queue.pending.push_front(resource);
+                break;
+            }
+            uploaded_bytes += size;
+
+            if let Some(uploader) = self
+                .queue
+                .uploader
+                .as_ref()
+                .filter(|_| self.queue.settings.use_upload_thread)
+            {
+                let id = self.queue.next_request_id;
+                self.queue.next_request_id += 1;
+                let modifications_count = texture.modifications_count();
+                drop(state);
+
+                match uploader.request(id, Box::new(TextureResourceSource(resource.clone()))) {
+                    Ok(()) => {
+                        self.queue.in_flight.insert(
+                            id,
+                            InFlightUpload {
+                                texture: resource,
+                                modifications_count,
+                            },
+                        );
+                    }
+                    Err(e) => {
+                        Log::warn(format!(
+                            "Texture upload thread has failed, textures will be uploaded on \
+                            the render thread. Reason: {e:?}"
+                        ));
+                        uploaded_bytes -= size;
+                        self.queue.pending.push_front(resource);
+                        self.queue.discard_uploader();
+                    }
+                }
+
+                continue;
+            }
+
+            if let Err(e) = self.cache.get_entry_mut_or_insert_with(
+                &texture.cache_index,
+                Default::default(),
+                || create_gpu_texture(server, texture),
+            ) {
+                self.errors.report(
+                    RenderErrorSource::Texture,
+                    e.context(format!(
+                        "Failed to upload {} texture to GPU",
+                        resource.kind()
+                    )),
+                );
+            }
+
+            drop(state);
+            self.queue.queued.remove(&key);
+        }
+    }
+
+    /// Returns the amount of textures, that are waiting for upload or being uploaded.
+    pub fn pending_uploads_count(&self) -> usize {
+        self.queue.queued.len()
+    }
+
+    pub fn upload_settings(&self) -> UploadSettings {
+        self.queue.settings
+    }
+
+    pub fn set_upload_settings(&mut self, settings: UploadSettings) {
+        self.queue.settings = settings;
+    }
+
+    /// Returns a GPU sampler, that matches the given texture sampler. GPU samplers are created on
+    /// demand and shared between all the texture bindings that use the same sampling parameters.
+    pub fn get_sampler(
+        &mut self,
+        server: &dyn GraphicsServer,
+        sampler: &TextureSampler,
+    ) -> Option<&GpuSampler> {
+        let index = match self.samplers.iter().position(|(s, _)| s == sampler) {
+            Some(index) => index,
+            None => match server.create_sampler(make_sampler_descriptor(sampler)) {
+                Ok(gpu_sampler) => {
+                    self.samplers.push((*sampler, gpu_sampler));
+                    self.samplers.len() - 1
+                }
+                Err(e) => {
+                    Log::err(format!("Unable to create a sampler. Reason: {e:?}"));
+                    return None;
+                }
+            },
+        };
+        self.samplers.get(index).map(|(_, gpu_sampler)| gpu_sampler)
+    }
+
     pub fn clear(&mut self) {
         self.cache.clear();
+        self.queue.clear();
+        self.samplers.clear();
     }
 
     pub fn unload(&mut self, texture: TextureResource) {
+        let key = texture.key();
+        if self.queue.queued.remove(&key) {
+            self.queue.pending.retain(|pending| pending.key() != key);
+        }
+
         if let Some(texture) = texture.state().data() {
             self.cache.remove(&texture.cache_index);
         }
@@ -285,6 +668,37 @@ impl TextureCache {
         self.cache.alive_count()
     }
 
+    /// Returns approximate amount of GPU memory (in bytes) occupied by the textures in the cache.
+    /// Mip levels are assumed to take an extra third of the size of the base level.
+    pub fn memory_usage(&self) -> usize {
+        self.cache
+            .buffer
+            .iter()
+            .map(|entry| {
+                let texture = &entry.value.gpu_texture;
+                let pixel_kind = texture.pixel_kind();
+                let base_level_size = match texture.kind() {
+                    GpuTextureKind::Line { length } => image_1d_size_bytes(pixel_kind, length),
+                    GpuTextureKind::Rectangle { width, height } => {
+                        image_2d_size_bytes(pixel_kind, width, height)
+                    }
+                    GpuTextureKind::Cube { width, height } => {
+                        6 * image_2d_size_bytes(pixel_kind, width, height)
+                    }
+                    GpuTextureKind::Volume {
+                        width,
+                        height,
+                        depth,
+                    } => image_3d_size_bytes(pixel_kind, width, height, depth),
+                };
+                match texture.minification_filter() {
+                    MinificationFilter::Nearest | MinificationFilter::Linear => base_level_size,
+                    _ => base_level_size + base_level_size / 3,
+                }
+            })
+            .sum()
+    }
+
     /// Tries to bind existing GPU texture with a texture resource. If there's no such binding, then
     /// a new binding is created, otherwise - only the TTL is updated to keep the GPU texture alive
     /// for a certain time period (see [`TimeToLive`]).
@@ -304,3 +718,66 @@ impl TextureCache {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{InFlightUpload, UploadQueue, UploadSettings};
+    use crate::{
+        asset::untyped::ResourceKind,
+        resource::texture::{Texture, TextureResource},
+    };
+
+    fn texture() -> TextureResource {
+        TextureResource::new_ok(ResourceKind::Embedded, Texture::default())
+    }
+
+    #[test]
+    fn test_upload_budget() {
+        let settings = UploadSettings {
+            bytes_per_frame: 100,
+            immediate_upload_threshold: 10,
+            use_upload_thread: false,
+        };
+
+        // At least one resource is uploaded per frame.
+        assert!(settings.fits_budget(0, 1000));
+        assert!(settings.fits_budget(50, 50));
+        assert!(!settings.fits_budget(50, 51));
+    }
+
+    #[test]
+    fn test_discard_uploader_requeues_in_flight_textures() {
+        let mut queue = UploadQueue::default();
+
+        let textures = [texture(), texture(), texture()];
+        for texture in textures.iter() {
+            queue.push(texture.clone());
+        }
+
+        // Simulate that the first two textures were sent to the upload thread.
+        for id in 0..2 {
+            let texture = queue.pending.pop_front().unwrap();
+            queue.in_flight.insert(
+                id,
+                InFlightUpload {
+                    texture,
+                    modifications_count: 0,
+                },
+            );
+        }
+
+        queue.discard_uploader();
+
+        assert!(queue.in_flight.is_empty());
+        assert_eq!(
+            queue
+                .pending
+                .iter()
+                .map(|texture| texture.key())
+                .collect::<Vec<_>>(),
+            textures.iter().map(|t| t.key()).collect::<Vec<_>>()
+        );
+        // The textures are still considered queued, so they won't be requested twice.
+        assert_eq!(queue.queued.len(), 3);
+    }
+}
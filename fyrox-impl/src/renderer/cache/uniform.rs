@@ -30,6 +30,7 @@ use crate::renderer::framework::{
 };
 use fxhash::FxHashMap;
 use fyrox_graphics::buffer::GpuBuffer;
+use fyrox_graphics::ring_buffer::GpuRingBuffer;
 use fyrox_graphics::server::SharedGraphicsServer;
 use std::cell::RefCell;
 
@@ -68,16 +69,51 @@ impl UniformBufferSet {
 /// (guaranteed to be at least 16kb and on vast majority of GPUs the upper limit is 65kb) and they
 /// are intended to be used as a storage for relatively small set of data that can fit into L1 cache
 /// of a GPU for very fast access.
+///
+/// Per-draw uniform data could be written to a ring buffer (see [`Self::with_ring_buffer`]), which
+/// avoids lots of small buffer uploads per frame. The pool of buffers is used as a fallback when
+/// there's no ring buffer or when the current segment of the ring buffer is full.
 pub struct UniformBufferCache {
     server: SharedGraphicsServer,
     cache: RefCell<FxHashMap<usize, UniformBufferSet>>,
+    ring_buffer: Option<GpuRingBuffer>,
 }
 
 impl UniformBufferCache {
+    /// Default size of a single segment of the ring buffer. It should be enough to fit per-draw
+    /// uniforms of a few thousands of draw calls.
+    pub const DEFAULT_RING_SEGMENT_SIZE: usize = 2 * 1024 * 1024;
+
+    /// Amount of frames that could be processed by the GPU while the next frame is being recorded.
+    const RING_SEGMENT_COUNT: usize = 3;
+
+    /// Creates a new cache, that uses pooled buffers only.
     pub fn new(server: SharedGraphicsServer) -> Self {
         Self {
             server,
             cache: Default::default(),
+            ring_buffer: None,
+        }
+    }
+
+    /// Creates a new cache, that writes per-draw uniforms to a ring buffer with segments of the
+    /// given size (three segments in total). The ring buffer occupies the memory for the whole
+    /// lifetime of the cache, so it should be used only by the renderers with lots of draw calls
+    /// per frame. If the ring buffer cannot be created, the cache uses pooled buffers only.
+    pub fn with_ring_buffer(server: SharedGraphicsServer, segment_size: usize) -> Self {
+        let ring_buffer = server
+            .create_ring_buffer(
+                BufferKind::Uniform,
+                segment_size,
+                Self::RING_SEGMENT_COUNT,
+                server.capabilities().uniform_buffer_offset_alignment,
+            )
+            .ok();
+
+        Self {
+            server,
+            cache: Default::default(),
+            ring_buffer,
         }
     }
 
@@ -101,12 +137,50 @@ impl UniformBufferCache {
         Ok(buffer)
     }
 
-    /// Marks all reserved buffers as unused. Must be called at least once per frame to prevent
-    /// uncontrollable growth of the cache.
+    /// Writes the given CPU uniform buffer to the GPU memory and returns a resource binding that
+    /// points to the written data. The data is written to the ring buffer if possible, otherwise
+    /// it is written to a pooled buffer (see [`Self::write`]).
+    pub fn write_binding<T>(
+        &self,
+        uniform_buffer: UniformBuffer<T>,
+        binding: usize,
+    ) -> Result<ResourceBinding, FrameworkError>
+    where
+        T: ByteStorage,
+    {
+        let data = uniform_buffer.finish();
+
+        if let Some(ring_buffer) = self.ring_buffer.as_ref() {
+            if let Some(offset) = ring_buffer.write(data.bytes()) {
+                return Ok(ResourceBinding::buffer(
+                    ring_buffer.buffer(),
+                    binding,
+                    BufferDataUsage::UseSegment {
+                        offset,
+                        size: data.bytes_count(),
+                    },
+                ));
+            }
+        }
+
+        let buffer = self.get_or_create(data.bytes_count())?;
+        buffer.write_data(data.bytes())?;
+        Ok(ResourceBinding::buffer(
+            &buffer,
+            binding,
+            Default::default(),
+        ))
+    }
+
+    /// Marks all reserved buffers as unused and switches the ring buffer to its next segment. Must
+    /// be called once per frame to prevent uncontrollable growth of the cache.
     pub fn mark_all_unused(&mut self) {
         for set in self.cache.borrow_mut().values_mut() {
             set.mark_unused();
         }
+        if let Some(ring_buffer) = self.ring_buffer.as_ref() {
+            ring_buffer.next_segment();
+        }
     }
 
     /// Returns the total amount of allocated uniforms buffers.
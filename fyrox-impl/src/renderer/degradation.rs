@@ -0,0 +1,385 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Graceful degradation of the renderer on hardware that lacks some capabilities. Instead of
+//! failing to initialize, the renderer picks documented fallbacks for the missing capabilities and
+//! records what was degraded. See [`DegradationReport`] docs for more info.
+
+use crate::renderer::{
+    framework::{gpu_texture::PixelKind, server::ServerCapabilities},
+    QualitySettings,
+};
+use std::fmt::{Display, Formatter};
+
+/// Pixel kinds of the render targets used by the renderer. Default values are the best ones, they
+/// are replaced with fallbacks when the graphics server is unable to render into them.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RenderTargetFormats {
+    /// Pixel kind of high dynamic range color render targets (HDR frame, ambient lighting buffer,
+    /// bloom).
+    pub hdr_color: PixelKind,
+    /// Pixel kind of the motion vectors buffer.
+    pub motion_vectors: PixelKind,
+    /// Pixel kind of single channel render targets (scene luminance, ambient occlusion).
+    pub scalar: PixelKind,
+    /// Pixel kind of point light shadow maps, that store the distance from the light source.
+    pub point_shadow_distance: PixelKind,
+}
+
+impl Default for RenderTargetFormats {
+    fn default() -> Self {
+        Self {
+            hdr_color: PixelKind::RGBA16F,
+            motion_vectors: PixelKind::RGBA16F,
+            scalar: PixelKind::R32F,
+            point_shadow_distance: PixelKind::R16F,
+        }
+    }
+}
+
+/// A part of the renderer that could be degraded.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum DegradedFeature {
+    /// Pixel kind of high dynamic range color render targets, see
+    /// [`RenderTargetFormats::hdr_color`].
+    HdrColorFormat,
+    /// Pixel kind of the motion vectors buffer, see [`RenderTargetFormats::motion_vectors`].
+    MotionVectorsFormat,
+    /// Pixel kind of single channel render targets, see [`RenderTargetFormats::scalar`].
+    ScalarFormat,
+    /// Point light shadows, see [`QualitySettings::point_shadows_enabled`].
+    PointShadows,
+    /// Screen-space ambient occlusion, see [`QualitySettings::use_ssao`].
+    Ssao,
+    /// Screen-space global illumination, see [`QualitySettings::ssgi_settings`].
+    Ssgi,
+    /// Temporal anti-aliasing, see [`QualitySettings::taa_settings`].
+    Taa,
+}
+
+/// A record of a single degraded feature.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Degradation {
+    /// The degraded feature.
+    pub feature: DegradedFeature,
+    /// Description of the missing capability.
+    pub reason: String,
+    /// Description of the fallback that is used instead.
+    pub fallback: String,
+}
+
+impl Display for Degradation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:?}: {}. Fallback: {}",
+            self.feature, self.reason, self.fallback
+        )
+    }
+}
+
+/// Result of matching the capabilities of a graphics server against the requirements of the
+/// renderer. The matrix of the requirements and fallbacks is the following:
+///
+/// | Feature                | Requirement                   | Fallbacks                             |
+/// |------------------------|-------------------------------|---------------------------------------|
+/// | HDR color format       | RGBA16F render targets        | R11G11B10F, then RGBA8 (no HDR range) |
+/// | Motion vectors format  | RGBA16F render targets        | RGBA8, temporal effects are disabled  |
+/// | Scalar format          | R32F render targets           | R16F, then R8 (clamped to `[0; 1]`)   |
+/// | Point shadows          | R16F or R32F render targets   | Disabled                              |
+/// | SSAO                   | Float render targets          | Disabled                              |
+/// | SSGI, TAA              | Valid motion vectors, RGBA16F | Disabled                              |
+///
+/// Hardware without float render targets is usually too slow for screen-space ambient occlusion
+/// anyway, that's why it is disabled instead of using low-precision buffers.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DegradationReport {
+    /// Pixel kinds of the render targets, that should be used by the renderer.
+    pub formats: RenderTargetFormats,
+    /// A list of degraded features. Empty if the renderer works at full quality.
+    pub degradations: Vec<Degradation>,
+}
+
+fn pick_format(caps: &ServerCapabilities, candidates: &[PixelKind]) -> Option<PixelKind> {
+    candidates
+        .iter()
+        .copied()
+        .find(|pixel_kind| caps.is_render_target_supported(*pixel_kind))
+}
+
+impl DegradationReport {
+    /// Matches the given capabilities against the requirements of the renderer.
+    pub fn evaluate(caps: &ServerCapabilities) -> Self {
+        let mut report = Self::default();
+        let best = RenderTargetFormats::default();
+
+        let hdr_color = pick_format(caps, &[PixelKind::RGBA16F, PixelKind::R11G11B10F])
+            .unwrap_or(PixelKind::RGBA8);
+        if hdr_color != best.hdr_color {
+            report.degrade(
+                DegradedFeature::HdrColorFormat,
+                "RGBA16F render targets are not supported",
+                if hdr_color == PixelKind::R11G11B10F {
+                    "R11G11B10F, HDR render targets have no alpha channel"
+                } else {
+                    "RGBA8, bright areas are clipped"
+                },
+            );
+        }
+        report.formats.hdr_color = hdr_color;
+
+        if !caps.is_render_target_supported(best.motion_vectors) {
+            report.formats.motion_vectors = PixelKind::RGBA8;
+            report.degrade(
+                DegradedFeature::MotionVectorsFormat,
+                "RGBA16F render targets are not supported",
+                "RGBA8, motion vectors are not available",
+            );
+            for feature in [DegradedFeature::Ssgi, DegradedFeature::Taa] {
+                report.degrade(feature, "motion vectors are not available", "disabled");
+            }
+        }
+
+        let scalar =
+            pick_format(caps, &[PixelKind::R32F, PixelKind::R16F]).unwrap_or(PixelKind::R8);
+        if scalar != best.scalar {
+            report.degrade(
+                DegradedFeature::ScalarFormat,
+                "R32F render targets are not supported",
+                if scalar == PixelKind::R16F {
+                    "R16F, reduced precision"
+                } else {
+                    "R8, values are clamped to [0; 1] range"
+                },
+            );
+        }
+        report.formats.scalar = scalar;
+
+        match pick_format(caps, &[PixelKind::R16F, PixelKind::R32F]) {
+            Some(pixel_kind) => report.formats.point_shadow_distance = pixel_kind,
+            None => {
+                report.formats.point_shadow_distance = PixelKind::R8;
+                report.degrade(
+                    DegradedFeature::PointShadows,
+                    "float render targets are not supported",
+                    "disabled",
+                );
+            }
+        }
+
+        if scalar == PixelKind::R8 {
+            report.degrade(
+                DegradedFeature::Ssao,
+                "float render targets are not supported",
+                "disabled",
+            );
+        }
+
+        report
+    }
+
+    fn degrade(&mut self, feature: DegradedFeature, reason: &str, fallback: &str) {
+        self.degradations.push(Degradation {
+            feature,
+            reason: reason.to_string(),
+            fallback: fallback.to_string(),
+        });
+    }
+
+    /// Returns `true` if the given feature was degraded.
+    pub fn is_degraded(&self, feature: DegradedFeature) -> bool {
+        self.degradations
+            .iter()
+            .any(|degradation| degradation.feature == feature)
+    }
+
+    /// Returns `true` if nothing was degraded.
+    pub fn is_full_quality(&self) -> bool {
+        self.degradations.is_empty()
+    }
+
+    /// Disables the features, that are not supported, in the given quality settings. Returns `true`
+    /// if the settings were changed.
+    pub fn restrict(&self, settings: &mut QualitySettings) -> bool {
+        let original = *settings;
+        if self.is_degraded(DegradedFeature::PointShadows) {
+            settings.point_shadows_enabled = false;
+        }
+        if self.is_degraded(DegradedFeature::Ssao) {
+            settings.use_ssao = false;
+        }
+        if self.is_degraded(DegradedFeature::Ssgi) {
+            settings.ssgi_settings.enabled = false;
+        }
+        if self.is_degraded(DegradedFeature::Taa) {
+            settings.taa_settings.enabled = false;
+        }
+        original != *settings
+    }
+}
+
+impl Display for DegradationReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_full_quality() {
+            return writeln!(f, "Renderer works at full quality.");
+        }
+        writeln!(f, "Renderer features degraded due to missing capabilities:")?;
+        for degradation in self.degradations.iter() {
+            writeln!(f, "\t{degradation}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::renderer::{
+        degradation::{DegradationReport, DegradedFeature, RenderTargetFormats},
+        framework::{gpu_texture::PixelKind, server::ServerCapabilities},
+        QualitySettings,
+    };
+
+    fn capabilities(half_float: bool, float: bool) -> ServerCapabilities {
+        ServerCapabilities {
+            max_uniform_block_size: 16384,
+            uniform_buffer_offset_alignment: 256,
+            max_lod_bias: 2.0,
+            stencil_texturing: true,
+            max_color_attachments: 8,
+            max_draw_buffers: 8,
+            indexed_blending: true,
+            indirect_draw: true,
+            persistent_mapping: true,
+            texture_swizzle: true,
+            timer_query: true,
+            half_float_render_targets: half_float,
+            float_render_targets: float,
+        }
+    }
+
+    fn degraded_features(report: &DegradationReport) -> Vec<DegradedFeature> {
+        report.degradations.iter().map(|d| d.feature).collect()
+    }
+
+    fn all_enabled() -> QualitySettings {
+        let mut settings = QualitySettings::ultra();
+        settings.point_shadows_enabled = true;
+        settings.use_ssao = true;
+        settings.ssgi_settings.enabled = true;
+        settings.taa_settings.enabled = true;
+        settings
+    }
+
+    #[test]
+    fn test_full_quality() {
+        let report = DegradationReport::evaluate(&capabilities(true, true));
+        assert!(report.is_full_quality());
+        assert_eq!(report.formats, RenderTargetFormats::default());
+
+        let mut settings = all_enabled();
+        assert!(!report.restrict(&mut settings));
+        assert_eq!(settings, all_enabled());
+    }
+
+    #[test]
+    fn test_no_half_float_render_targets() {
+        let report = DegradationReport::evaluate(&capabilities(false, true));
+        assert_eq!(
+            report.formats,
+            RenderTargetFormats {
+                hdr_color: PixelKind::R11G11B10F,
+                motion_vectors: PixelKind::RGBA8,
+                scalar: PixelKind::R32F,
+                point_shadow_distance: PixelKind::R32F,
+            }
+        );
+        assert_eq!(
+            degraded_features(&report),
+            [
+                DegradedFeature::HdrColorFormat,
+                DegradedFeature::MotionVectorsFormat,
+                DegradedFeature::Ssgi,
+                DegradedFeature::Taa,
+            ]
+        );
+
+        let mut settings = all_enabled();
+        assert!(report.restrict(&mut settings));
+        assert!(settings.point_shadows_enabled);
+        assert!(settings.use_ssao);
+        assert!(!settings.ssgi_settings.enabled);
+        assert!(!settings.taa_settings.enabled);
+    }
+
+    #[test]
+    fn test_no_full_float_render_targets() {
+        let report = DegradationReport::evaluate(&capabilities(true, false));
+        assert_eq!(
+            report.formats,
+            RenderTargetFormats {
+                hdr_color: PixelKind::RGBA16F,
+                motion_vectors: PixelKind::RGBA16F,
+                scalar: PixelKind::R16F,
+                point_shadow_distance: PixelKind::R16F,
+            }
+        );
+        assert_eq!(degraded_features(&report), [DegradedFeature::ScalarFormat]);
+
+        let mut settings = all_enabled();
+        assert!(!report.restrict(&mut settings));
+    }
+
+    #[test]
+    fn test_gles2_class_device() {
+        // No float render targets at all.
+        let report = DegradationReport::evaluate(&capabilities(false, false));
+        assert_eq!(
+            report.formats,
+            RenderTargetFormats {
+                hdr_color: PixelKind::RGBA8,
+                motion_vectors: PixelKind::RGBA8,
+                scalar: PixelKind::R8,
+                point_shadow_distance: PixelKind::R8,
+            }
+        );
+        assert_eq!(
+            degraded_features(&report),
+            [
+                DegradedFeature::HdrColorFormat,
+                DegradedFeature::MotionVectorsFormat,
+                DegradedFeature::Ssgi,
+                DegradedFeature::Taa,
+                DegradedFeature::ScalarFormat,
+                DegradedFeature::PointShadows,
+                DegradedFeature::Ssao,
+            ]
+        );
+        assert!(report.degradations[0].fallback.starts_with("RGBA8"));
+
+        let mut settings = all_enabled();
+        assert!(report.restrict(&mut settings));
+        assert!(!settings.point_shadows_enabled);
+        assert!(!settings.use_ssao);
+        assert!(!settings.ssgi_settings.enabled);
+        assert!(!settings.taa_settings.enabled);
+
+        // Restricted settings are not changed again.
+        assert!(!report.restrict(&mut settings));
+    }
+}
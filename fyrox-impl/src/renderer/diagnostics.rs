@@ -0,0 +1,106 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Rendering error reporting. The renderer does not stop on errors, instead it skips the things
+//! that cannot be rendered and reports the errors via [`RenderErrorBroadcaster`], so the user could
+//! show them somewhere (for example, in a dedicated UI panel).
+
+use crate::{
+    core::{
+        log::Log,
+        parking_lot::Mutex,
+        pool::{Handle, Pool},
+    },
+    renderer::framework::error::FrameworkError,
+};
+use fxhash::FxHashSet;
+use std::sync::{mpsc::Sender, Arc};
+
+/// A part of the renderer that produced an error.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RenderErrorSource {
+    /// The error occurred when creating GPU programs of a shader.
+    Shader,
+    /// The error occurred when creating a GPU texture or uploading its data.
+    Texture,
+    /// Any other error.
+    Other,
+}
+
+/// An error that occurred during rendering.
+#[derive(Clone, Debug)]
+pub struct RenderErrorEvent {
+    /// A part of the renderer that produced the error.
+    pub source: RenderErrorSource,
+    /// The actual error. Use [`FrameworkError::root_cause`] to get the error without the context.
+    pub error: FrameworkError,
+}
+
+/// Type alias for render error event sender.
+pub type RenderErrorSender = Sender<RenderErrorEvent>;
+
+#[derive(Default)]
+struct BroadcasterState {
+    senders: Pool<RenderErrorSender>,
+    reported: FxHashSet<String>,
+}
+
+/// Render error broadcaster is responsible for delivering render errors to "subscribers". The same
+/// error usually repeats every frame (a shader that fails to compile will fail again on every
+/// attempt), so every unique error is logged and delivered only once until [`Self::reset`] is
+/// called.
+#[derive(Clone, Default)]
+pub struct RenderErrorBroadcaster {
+    state: Arc<Mutex<BroadcasterState>>,
+}
+
+impl RenderErrorBroadcaster {
+    /// Adds an event sender to the broadcaster and returns its handle.
+    pub fn add(&self, sender: RenderErrorSender) -> Handle<RenderErrorSender> {
+        self.state.lock().senders.spawn(sender)
+    }
+
+    /// Removes an event sender by its handle.
+    pub fn remove(&self, handle: Handle<RenderErrorSender>) -> RenderErrorSender {
+        self.state.lock().senders.free(handle)
+    }
+
+    /// Writes the error to the log and sends it to all "subscribers", if the same error wasn't
+    /// reported before.
+    pub fn report(&self, source: RenderErrorSource, error: FrameworkError) {
+        let mut state = self.state.lock();
+        let message = error.to_string();
+        if !state.reported.insert(message.clone()) {
+            return;
+        }
+
+        Log::err(message);
+
+        let event = RenderErrorEvent { source, error };
+        for sender in state.senders.iter() {
+            let _ = sender.send(event.clone());
+        }
+    }
+
+    /// Forgets all reported errors, so they will be reported again if they occur. Useful when the
+    /// reason of the errors may have been fixed, for example, after a resource was reloaded.
+    pub fn reset(&self) {
+        self.state.lock().reported.clear();
+    }
+}
@@ -93,10 +93,12 @@ impl ForwardRenderer {
                 viewport,
                 uniform_memory_allocator,
                 use_pom: quality_settings.use_parallax_mapping,
+                use_instancing: quality_settings.use_instancing,
                 light_position: &Default::default(),
                 fallback_resources,
                 ambient_light,
                 scene_depth: Some(scene_depth),
+                motion: None,
             },
         )?;
 
@@ -22,9 +22,12 @@
 //!
 //! RT0: sRGBA8 - Diffuse color (xyz)
 //! RT1: RGBA8 - Normal (xyz)
-//! RT2: RGBA16F - Ambient light + emission (both in xyz)
+//! RT2: RGBA16F - Ambient light + emission (both in xyz). See [`RenderTargetFormats::hdr_color`]
+//! for fallbacks.
 //! RT3: RGBA8 - Metallic (x) + Roughness (y) + Ambient Occlusion (z)
 //! RT4: R8UI - Decal mask (x)
+//! RT5: RGBA16F - Screen-space motion vector (xy) + motion vector presence flag (z). See
+//! [`RenderTargetFormats::motion_vectors`] for fallbacks.
 //!
 //! Every alpha channel is used for layer blending for terrains. This is inefficient, but for
 //! now I don't know better solution.
@@ -37,7 +40,10 @@ use crate::{
         sstorage::ImmutableString,
     },
     renderer::{
-        bundle::{BundleRenderContext, RenderDataBundleStorage, SurfaceInstanceData},
+        bundle::{
+            BundleRenderContext, InstanceHistory, MotionVectorContext, RenderDataBundleStorage,
+            SurfaceInstanceData,
+        },
         cache::{
             shader::{
                 binding, property, PropertyGroup, RenderMaterial, RenderPassContainer, ShaderCache,
@@ -45,16 +51,18 @@ use crate::{
             uniform::{UniformBufferCache, UniformMemoryAllocator},
         },
         debug_renderer::DebugRenderer,
+        degradation::RenderTargetFormats,
         framework::{
             buffer::BufferUsage,
             error::FrameworkError,
-            framebuffer::{Attachment, AttachmentKind, GpuFrameBuffer},
+            framebuffer::{Attachment, GpuFrameBuffer},
             geometry_buffer::GpuGeometryBuffer,
             gpu_texture::{GpuTexture, PixelKind},
             server::GraphicsServer,
             GeometryBufferExt,
         },
         occlusion::OcclusionTester,
+        temporal::TemporalFrame,
         FallbackResources, GeometryCache, QualitySettings, RenderPassStatistics, TextureCache,
     },
     scene::{
@@ -92,6 +100,9 @@ pub(crate) struct GBufferRenderContext<'a, 'b> {
     #[allow(dead_code)]
     pub screen_space_debug_renderer: &'a mut DebugRenderer,
     pub unit_quad: &'a GpuGeometryBuffer,
+    pub temporal_frame: TemporalFrame,
+    pub jitter: Vector2<f32>,
+    pub instance_history: &'a mut InstanceHistory,
 }
 
 impl GBuffer {
@@ -99,49 +110,45 @@ impl GBuffer {
         server: &dyn GraphicsServer,
         width: usize,
         height: usize,
+        formats: &RenderTargetFormats,
     ) -> Result<Self, FrameworkError> {
         let diffuse_texture = server.create_2d_render_target(PixelKind::RGBA8, width, height)?;
         let normal_texture = server.create_2d_render_target(PixelKind::RGBA8, width, height)?;
+        let material_texture = server.create_2d_render_target(PixelKind::RGBA8, width, height)?;
         let framebuffer = server.create_frame_buffer(
-            Some(Attachment {
-                kind: AttachmentKind::DepthStencil,
-                texture: server.create_2d_render_target(PixelKind::D24S8, width, height)?,
-            }),
+            Some(Attachment::depth_stencil(server.create_2d_render_target(
+                PixelKind::D24S8,
+                width,
+                height,
+            )?)),
             vec![
-                Attachment {
-                    kind: AttachmentKind::Color,
-                    texture: diffuse_texture.clone(),
-                },
-                Attachment {
-                    kind: AttachmentKind::Color,
-                    texture: normal_texture.clone(),
-                },
-                Attachment {
-                    kind: AttachmentKind::Color,
-                    texture: server.create_2d_render_target(PixelKind::RGBA16F, width, height)?,
-                },
-                Attachment {
-                    kind: AttachmentKind::Color,
-                    texture: server.create_2d_render_target(PixelKind::RGBA8, width, height)?,
-                },
-                Attachment {
-                    kind: AttachmentKind::Color,
-                    texture: server.create_2d_render_target(PixelKind::R8UI, width, height)?,
-                },
+                Attachment::color(diffuse_texture.clone()),
+                Attachment::color(normal_texture.clone()),
+                Attachment::color(server.create_2d_render_target(
+                    formats.hdr_color,
+                    width,
+                    height,
+                )?),
+                Attachment::color(material_texture.clone()),
+                Attachment::color(server.create_2d_render_target(
+                    PixelKind::R8UI,
+                    width,
+                    height,
+                )?),
+                Attachment::color(server.create_2d_render_target(
+                    formats.motion_vectors,
+                    width,
+                    height,
+                )?),
             ],
         )?;
 
         let decal_framebuffer = server.create_frame_buffer(
             None,
             vec![
-                Attachment {
-                    kind: AttachmentKind::Color,
-                    texture: diffuse_texture,
-                },
-                Attachment {
-                    kind: AttachmentKind::Color,
-                    texture: normal_texture,
-                },
+                Attachment::color(diffuse_texture),
+                Attachment::color(normal_texture),
+                Attachment::color(material_texture),
             ],
         )?;
 
@@ -192,6 +199,10 @@ impl GBuffer {
         &self.framebuffer.color_attachments()[4].texture
     }
 
+    pub fn motion_texture(&self) -> &GpuTexture {
+        &self.framebuffer.color_attachments()[5].texture
+    }
+
     pub(crate) fn fill(
         &mut self,
         args: GBufferRenderContext,
@@ -211,6 +222,9 @@ impl GBuffer {
             uniform_buffer_cache,
             unit_quad,
             uniform_memory_allocator,
+            temporal_frame,
+            jitter,
+            instance_history,
             ..
         } = args;
 
@@ -251,13 +265,23 @@ impl GBuffer {
                 viewport,
                 uniform_memory_allocator,
                 use_pom: quality_settings.use_parallax_mapping,
+                use_instancing: quality_settings.use_instancing,
                 light_position: &Default::default(),
                 fallback_resources,
                 ambient_light: Color::WHITE, // TODO
                 scene_depth: None,           // TODO. Add z-pre-pass.
+                motion: Some(MotionVectorContext {
+                    previous_view_projection: temporal_frame.previous_view_projection_matrix,
+                    jitter,
+                    instance_history,
+                }),
             },
         )?;
 
+        instance_history.update(bundle_storage, |bundle| {
+            bundle.render_path == RenderPath::Deferred
+        });
+
         if quality_settings.use_occlusion_culling {
             let mut objects = FxHashSet::default();
             for bundle in bundle_storage.bundles.iter() {
@@ -285,9 +309,14 @@ impl GBuffer {
 
         // Render decals after because we need to modify diffuse texture of G-Buffer and use depth texture
         // for rendering. We'll render in the G-Buffer, but depth will be used from final frame, since
-        // decals do not modify depth (only diffuse and normal maps).
+        // decals do not modify depth (only diffuse, normal and material maps).
         let unit_cube = &self.cube;
         for decal in graph.linear_iter().filter_map(|n| n.cast::<Decal>()) {
+            let fade_factor = decal.fade_factor();
+            if fade_factor <= 0.0 {
+                continue;
+            }
+
             let world_view_proj = view_projection * decal.global_transform();
 
             let diffuse_texture = decal
@@ -299,8 +328,17 @@ impl GBuffer {
             let normal_texture = decal
                 .normal_texture()
                 .and_then(|t| texture_cache.get(server, t))
-                .unwrap_or(&fallback_resources.normal_dummy)
-                .clone();
+                .cloned();
+            let use_normal_texture = normal_texture.is_some();
+            let normal_texture =
+                normal_texture.unwrap_or_else(|| fallback_resources.normal_dummy.clone());
+
+            let orm_texture = decal
+                .orm_texture()
+                .and_then(|t| texture_cache.get(server, t))
+                .cloned();
+            let use_orm_texture = orm_texture.is_some();
+            let orm_texture = orm_texture.unwrap_or_else(|| fallback_resources.white_dummy.clone());
 
             let inv_world_decal = decal.global_transform().try_inverse().unwrap_or_default();
             let color = decal.color().srgb_to_linear_f32();
@@ -312,11 +350,15 @@ impl GBuffer {
                 property("resolution", &resolution),
                 property("color", &color),
                 property("layerIndex", &layer_index),
+                property("fadeFactor", &fade_factor),
+                property("useNormalTexture", &use_normal_texture),
+                property("useOrmTexture", &use_orm_texture),
             ]);
             let material = RenderMaterial::from([
                 binding("sceneDepth", depth),
                 binding("diffuseTexture", &diffuse_texture),
                 binding("normalTexture", &normal_texture),
+                binding("ormTexture", &orm_texture),
                 binding("decalMask", decal_mask),
                 binding("properties", &properties),
             ]);
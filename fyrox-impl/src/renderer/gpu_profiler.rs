@@ -0,0 +1,96 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Measures GPU time of the renderer passes using timer queries and passes the results to the
+//! frame profiler (see [`crate::core::profiler`]).
+
+use crate::{
+    core::profiler,
+    renderer::framework::{
+        query::{GpuQuery, QueryKind, QueryResult},
+        server::GraphicsServer,
+    },
+};
+use std::collections::VecDeque;
+
+// Results of timer queries are usually available in 2-3 frames, this limit prevents unbounded
+// growth of the queue when the results are never available (lost context, broken drivers, etc.).
+const MAX_IN_FLIGHT_QUERIES: usize = 256;
+
+/// Timer queries cannot be nested, so there could be only one active GPU scope at a time. Starting
+/// a new scope ends the previous one.
+#[derive(Default)]
+pub(crate) struct GpuProfiler {
+    supported: bool,
+    free: Vec<GpuQuery>,
+    in_flight: VecDeque<(&'static str, GpuQuery)>,
+    active: Option<(&'static str, GpuQuery)>,
+}
+
+impl GpuProfiler {
+    pub fn new(server: &dyn GraphicsServer) -> Self {
+        Self {
+            supported: server.capabilities().timer_query,
+            ..Default::default()
+        }
+    }
+
+    pub fn begin(&mut self, server: &dyn GraphicsServer, name: &'static str) {
+        self.end();
+
+        if !self.supported || !profiler::is_enabled() {
+            return;
+        }
+
+        let query = match self.free.pop() {
+            Some(query) => query,
+            None => match server.create_query() {
+                Ok(query) => query,
+                Err(_) => return,
+            },
+        };
+        query.begin(QueryKind::TimeElapsed);
+        self.active = Some((name, query));
+    }
+
+    pub fn end(&mut self) {
+        if let Some((name, query)) = self.active.take() {
+            query.end();
+            self.in_flight.push_back((name, query));
+            if self.in_flight.len() > MAX_IN_FLIGHT_QUERIES {
+                self.in_flight.pop_front();
+            }
+        }
+    }
+
+    /// Passes the results of finished queries to the frame profiler. The queries finish in the
+    /// same order as they were issued, so the polling stops at the first unfinished query.
+    pub fn collect(&mut self) {
+        while let Some((_, query)) = self.in_flight.front() {
+            let Some(result) = query.try_get_result() else {
+                break;
+            };
+            let (name, query) = self.in_flight.pop_front().unwrap();
+            if let QueryResult::TimeElapsed(time) = result {
+                profiler::record_gpu_scope(name, time);
+            }
+            self.free.push(query);
+        }
+    }
+}
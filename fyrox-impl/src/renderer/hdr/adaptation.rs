@@ -19,7 +19,11 @@
 // SOFTWARE.
 
 use crate::renderer::{
-    framework::{error::FrameworkError, gpu_texture::GpuTexture, server::GraphicsServer},
+    framework::{
+        error::FrameworkError,
+        gpu_texture::{GpuTexture, PixelKind},
+        server::GraphicsServer,
+    },
     hdr::LumBuffer,
 };
 use std::cell::Cell;
@@ -35,9 +39,12 @@ pub struct AdaptationContext<'a> {
 }
 
 impl AdaptationChain {
-    pub fn new(server: &dyn GraphicsServer) -> Result<Self, FrameworkError> {
+    pub fn new(server: &dyn GraphicsServer, pixel_kind: PixelKind) -> Result<Self, FrameworkError> {
         Ok(Self {
-            lum_framebuffers: [LumBuffer::new(server, 1)?, LumBuffer::new(server, 1)?],
+            lum_framebuffers: [
+                LumBuffer::new(server, 1, pixel_kind)?,
+                LumBuffer::new(server, 1, pixel_kind)?,
+            ],
             swap: Cell::new(false),
         })
     }
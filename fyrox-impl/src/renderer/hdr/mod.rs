@@ -33,7 +33,7 @@ use crate::{
         },
         framework::{
             error::FrameworkError,
-            framebuffer::{Attachment, AttachmentKind, DrawCallStatistics, GpuFrameBuffer},
+            framebuffer::{Attachment, DrawCallStatistics, GpuFrameBuffer},
             geometry_buffer::GpuGeometryBuffer,
             gpu_texture::{GpuTexture, GpuTextureDescriptor, GpuTextureKind, PixelKind},
             server::GraphicsServer,
@@ -59,16 +59,14 @@ pub struct LumBuffer {
 }
 
 impl LumBuffer {
-    fn new(server: &dyn GraphicsServer, size: usize) -> Result<Self, FrameworkError> {
-        let texture = server.create_2d_render_target(PixelKind::R32F, size, size)?;
+    fn new(
+        server: &dyn GraphicsServer,
+        size: usize,
+        pixel_kind: PixelKind,
+    ) -> Result<Self, FrameworkError> {
+        let texture = server.create_2d_render_target(pixel_kind, size, size)?;
         Ok(Self {
-            framebuffer: server.create_frame_buffer(
-                None,
-                vec![Attachment {
-                    kind: AttachmentKind::Color,
-                    texture,
-                }],
-            )?,
+            framebuffer: server.create_frame_buffer(None, vec![Attachment::color(texture)])?,
             size,
         })
     }
@@ -104,18 +102,23 @@ pub struct HighDynamicRangeRenderer {
 }
 
 impl HighDynamicRangeRenderer {
-    pub fn new(server: &dyn GraphicsServer) -> Result<Self, FrameworkError> {
+    /// Creates new HDR renderer. `luminance_pixel_kind` defines the pixel kind of luminance
+    /// buffers, it should be a single-channel float format to avoid clamping of the luminance.
+    pub fn new(
+        server: &dyn GraphicsServer,
+        luminance_pixel_kind: PixelKind,
+    ) -> Result<Self, FrameworkError> {
         Ok(Self {
-            frame_luminance: LumBuffer::new(server, 64)?,
+            frame_luminance: LumBuffer::new(server, 64, luminance_pixel_kind)?,
             downscale_chain: [
-                LumBuffer::new(server, 32)?,
-                LumBuffer::new(server, 16)?,
-                LumBuffer::new(server, 8)?,
-                LumBuffer::new(server, 4)?,
-                LumBuffer::new(server, 2)?,
-                LumBuffer::new(server, 1)?,
+                LumBuffer::new(server, 32, luminance_pixel_kind)?,
+                LumBuffer::new(server, 16, luminance_pixel_kind)?,
+                LumBuffer::new(server, 8, luminance_pixel_kind)?,
+                LumBuffer::new(server, 4, luminance_pixel_kind)?,
+                LumBuffer::new(server, 2, luminance_pixel_kind)?,
+                LumBuffer::new(server, 1, luminance_pixel_kind)?,
             ],
-            adaptation_chain: AdaptationChain::new(server)?,
+            adaptation_chain: AdaptationChain::new(server, luminance_pixel_kind)?,
             adaptation_shader: RenderPassContainer::from_str(
                 server,
                 include_str!("../shaders/hdr_adaptation.shader"),
@@ -0,0 +1,138 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Inspection of the intermediate render targets of the renderer (G-Buffer layers, shadow maps,
+//! bloom chain, etc.). It is mostly useful to debug custom shaders, for example to check what a
+//! shader writes to the G-Buffer. See [`crate::renderer::Renderer::frame_targets`] and
+//! [`crate::renderer::Renderer::set_debug_target`] docs for more info.
+
+use crate::renderer::framework::gpu_texture::{GpuTexture, GpuTextureKind, PixelKind};
+use std::fmt::{Display, Formatter};
+
+/// Name of the render target with the bright parts of the frame, that are used for bloom.
+pub const BLOOM_GLOW_TARGET: &str = "BloomGlow";
+/// Name of the render target with the horizontally blurred glow.
+pub const BLOOM_HORIZONTAL_BLUR_TARGET: &str = "BloomHorizontalBlur";
+/// Name of the render target with the final (blurred) glow.
+pub const BLOOM_RESULT_TARGET: &str = "BloomResult";
+/// Name of the render target with raw (not blurred) screen-space ambient occlusion.
+pub const SSAO_RAW_TARGET: &str = "SsaoRaw";
+/// Name of the render target with the final screen-space ambient occlusion.
+pub const SSAO_RESULT_TARGET: &str = "SsaoResult";
+
+/// Returns a name of the shadow map of the given cascade of directional lights.
+pub fn csm_cascade_target(cascade: usize) -> String {
+    format!("CsmCascade{cascade}")
+}
+
+/// Returns a name of the shadow map of the given cascade of spot lights.
+pub fn spot_shadow_cascade_target(cascade: usize) -> String {
+    format!("SpotShadowCascade{cascade}")
+}
+
+/// A group of intermediate render targets.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum FrameTargetGroup {
+    /// High and low dynamic range frames of a scene.
+    SceneFrame,
+    /// Layers of the G-Buffer.
+    GBuffer,
+    /// Screen-space ambient occlusion.
+    AmbientOcclusion,
+    /// Bloom chain.
+    Bloom,
+    /// Shadow maps. Shadow maps are shared by all lights of the same kind, so they contain the
+    /// shadows of the last rendered light.
+    ShadowMap,
+    /// Render targets created by render graph passes.
+    RenderGraph,
+}
+
+impl Display for FrameTargetGroup {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::SceneFrame => "Scene Frame",
+            Self::GBuffer => "G-Buffer",
+            Self::AmbientOcclusion => "Ambient Occlusion",
+            Self::Bloom => "Bloom",
+            Self::ShadowMap => "Shadow Map",
+            Self::RenderGraph => "Render Graph",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Description of an intermediate render target.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FrameTargetInfo {
+    /// Unique name of the render target. It could be used to select the render target for debug
+    /// visualization.
+    pub name: String,
+    /// A group of the render target.
+    pub group: FrameTargetGroup,
+    /// Width of the render target in pixels.
+    pub width: usize,
+    /// Height of the render target in pixels.
+    pub height: usize,
+    /// Pixel kind of the render target.
+    pub pixel_kind: PixelKind,
+}
+
+impl Display for FrameTargetInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({}, {}x{}, {:?})",
+            self.name, self.group, self.width, self.height, self.pixel_kind
+        )
+    }
+}
+
+/// An intermediate render target of a scene.
+pub(crate) struct FrameTarget {
+    pub name: String,
+    pub group: FrameTargetGroup,
+    pub texture: GpuTexture,
+}
+
+impl FrameTarget {
+    pub fn new(name: impl Into<String>, group: FrameTargetGroup, texture: GpuTexture) -> Self {
+        Self {
+            name: name.into(),
+            group,
+            texture,
+        }
+    }
+
+    /// Returns a description of the render target. Only rectangle textures could be shown on
+    /// screen, so other kinds of textures (cube maps of point light shadows) are ignored.
+    pub fn info(&self) -> Option<FrameTargetInfo> {
+        if let GpuTextureKind::Rectangle { width, height } = self.texture.kind() {
+            Some(FrameTargetInfo {
+                name: self.name.clone(),
+                group: self.group,
+                width,
+                height,
+                pixel_kind: self.texture.pixel_kind(),
+            })
+        } else {
+            None
+        }
+    }
+}
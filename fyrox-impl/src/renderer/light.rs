@@ -33,6 +33,7 @@ use crate::{
             },
             uniform::{UniformBufferCache, UniformMemoryAllocator},
         },
+        degradation::RenderTargetFormats,
         framework::{
             buffer::BufferUsage, error::FrameworkError, framebuffer::GpuFrameBuffer,
             geometry_buffer::GpuGeometryBuffer, server::GraphicsServer, ColorMask, CompareFunc,
@@ -40,6 +41,10 @@ use crate::{
             StencilOp,
         },
         gbuffer::GBuffer,
+        inspection::{
+            csm_cascade_target, spot_shadow_cascade_target, FrameTarget, FrameTargetGroup,
+            SSAO_RAW_TARGET, SSAO_RESULT_TARGET,
+        },
         light_volume::LightVolumeRenderer,
         make_viewport_matrix,
         shadow::{
@@ -48,6 +53,8 @@ use crate::{
             spot::SpotShadowMapRenderer,
         },
         ssao::ScreenSpaceAmbientOcclusionRenderer,
+        ssgi::{ScreenSpaceGlobalIlluminationRenderer, SsgiRenderContext},
+        temporal::TemporalFrame,
         visibility::ObserverVisibilityCache,
         FallbackResources, GeometryCache, LightingStatistics, QualitySettings,
         RenderPassStatistics, TextureCache,
@@ -64,7 +71,8 @@ use crate::{
 };
 
 pub struct DeferredLightRenderer {
-    pub ssao_renderer: ScreenSpaceAmbientOcclusionRenderer,
+    /// SSAO renderer exists only while SSAO is enabled in the quality settings.
+    pub ssao_renderer: Option<ScreenSpaceAmbientOcclusionRenderer>,
     spot_light_shader: RenderPassContainer,
     point_light_shader: RenderPassContainer,
     directional_light_shader: RenderPassContainer,
@@ -80,6 +88,8 @@ pub struct DeferredLightRenderer {
     light_volume: LightVolumeRenderer,
     volume_marker: RenderPassContainer,
     pixel_counter: RenderPassContainer,
+    formats: RenderTargetFormats,
+    frame_size: (u32, u32),
 }
 
 pub(crate) struct DeferredRendererContext<'a> {
@@ -99,6 +109,24 @@ pub(crate) struct DeferredRendererContext<'a> {
     pub uniform_buffer_cache: &'a mut UniformBufferCache,
     pub visibility_cache: &'a mut ObserverVisibilityCache,
     pub uniform_memory_allocator: &'a mut UniformMemoryAllocator,
+    pub ssgi_renderer: Option<&'a mut ScreenSpaceGlobalIlluminationRenderer>,
+    pub temporal_frame: TemporalFrame,
+}
+
+fn make_ssao_renderer(
+    server: &dyn GraphicsServer,
+    frame_size: (u32, u32),
+    formats: RenderTargetFormats,
+    radius: f32,
+) -> Result<ScreenSpaceAmbientOcclusionRenderer, FrameworkError> {
+    let mut renderer = ScreenSpaceAmbientOcclusionRenderer::new(
+        server,
+        frame_size.0 as usize,
+        frame_size.1 as usize,
+        formats.scalar,
+    )?;
+    renderer.set_radius(radius);
+    Ok(renderer)
 }
 
 impl DeferredLightRenderer {
@@ -106,6 +134,7 @@ impl DeferredLightRenderer {
         server: &dyn GraphicsServer,
         frame_size: (u32, u32),
         settings: &QualitySettings,
+        formats: RenderTargetFormats,
     ) -> Result<Self, FrameworkError> {
         let vertices = vec![
             // Front
@@ -140,14 +169,17 @@ impl DeferredLightRenderer {
             SimpleVertex::new(-0.5, -0.5, -0.5),
         ];
 
-        let quality_defaults = QualitySettings::default();
-
         Ok(Self {
-            ssao_renderer: ScreenSpaceAmbientOcclusionRenderer::new(
-                server,
-                frame_size.0 as usize,
-                frame_size.1 as usize,
-            )?,
+            ssao_renderer: if settings.use_ssao {
+                Some(make_ssao_renderer(
+                    server,
+                    frame_size,
+                    formats,
+                    settings.ssao_radius,
+                )?)
+            } else {
+                None
+            },
             spot_light_shader: RenderPassContainer::from_str(
                 server,
                 include_str!("shaders/deferred_spot_light.shader"),
@@ -212,18 +244,19 @@ impl DeferredLightRenderer {
             spot_shadow_map_renderer: SpotShadowMapRenderer::new(
                 server,
                 settings.spot_shadow_map_size,
-                quality_defaults.spot_shadow_map_precision,
+                settings.spot_shadow_map_precision,
             )?,
             point_shadow_map_renderer: PointShadowMapRenderer::new(
                 server,
                 settings.point_shadow_map_size,
-                quality_defaults.point_shadow_map_precision,
+                settings.point_shadow_map_precision,
+                formats.point_shadow_distance,
             )?,
             light_volume: LightVolumeRenderer::new(server)?,
             csm_renderer: CsmRenderer::new(
                 server,
-                quality_defaults.csm_settings.size,
-                quality_defaults.csm_settings.precision,
+                settings.csm_settings.size,
+                settings.csm_settings.precision,
             )?,
             volume_marker: RenderPassContainer::from_str(
                 server,
@@ -233,6 +266,8 @@ impl DeferredLightRenderer {
                 server,
                 include_str!("shaders/pixel_counter.shader"),
             )?,
+            formats,
+            frame_size,
         })
     }
 
@@ -241,34 +276,72 @@ impl DeferredLightRenderer {
         server: &dyn GraphicsServer,
         settings: &QualitySettings,
     ) -> Result<(), FrameworkError> {
-        if settings.spot_shadow_map_size != self.spot_shadow_map_renderer.base_size()
+        // Create all the new resources first and only then replace the old ones, this way the
+        // renderer stays in its previous state if any of the resources cannot be created.
+        let spot_shadow_map_renderer = if settings.spot_shadow_map_size
+            != self.spot_shadow_map_renderer.base_size()
             || settings.spot_shadow_map_precision != self.spot_shadow_map_renderer.precision()
         {
-            self.spot_shadow_map_renderer = SpotShadowMapRenderer::new(
+            Some(SpotShadowMapRenderer::new(
                 server,
                 settings.spot_shadow_map_size,
                 settings.spot_shadow_map_precision,
-            )?;
-        }
-        if settings.point_shadow_map_size != self.point_shadow_map_renderer.base_size()
+            )?)
+        } else {
+            None
+        };
+        let point_shadow_map_renderer = if settings.point_shadow_map_size
+            != self.point_shadow_map_renderer.base_size()
             || settings.point_shadow_map_precision != self.point_shadow_map_renderer.precision()
         {
-            self.point_shadow_map_renderer = PointShadowMapRenderer::new(
+            Some(PointShadowMapRenderer::new(
                 server,
                 settings.point_shadow_map_size,
                 settings.point_shadow_map_precision,
-            )?;
-        }
-        if settings.csm_settings.precision != self.csm_renderer.precision()
+                self.formats.point_shadow_distance,
+            )?)
+        } else {
+            None
+        };
+        let csm_renderer = if settings.csm_settings.precision != self.csm_renderer.precision()
             || settings.csm_settings.size != self.csm_renderer.size()
         {
-            self.csm_renderer = CsmRenderer::new(
+            Some(CsmRenderer::new(
                 server,
                 settings.csm_settings.size,
                 settings.csm_settings.precision,
-            )?;
+            )?)
+        } else {
+            None
+        };
+        let ssao_renderer = if settings.use_ssao && self.ssao_renderer.is_none() {
+            Some(make_ssao_renderer(
+                server,
+                self.frame_size,
+                self.formats,
+                settings.ssao_radius,
+            )?)
+        } else {
+            None
+        };
+
+        if let Some(spot_shadow_map_renderer) = spot_shadow_map_renderer {
+            self.spot_shadow_map_renderer = spot_shadow_map_renderer;
+        }
+        if let Some(point_shadow_map_renderer) = point_shadow_map_renderer {
+            self.point_shadow_map_renderer = point_shadow_map_renderer;
+        }
+        if let Some(csm_renderer) = csm_renderer {
+            self.csm_renderer = csm_renderer;
+        }
+        if !settings.use_ssao {
+            self.ssao_renderer = None;
+        } else if ssao_renderer.is_some() {
+            self.ssao_renderer = ssao_renderer;
+        }
+        if let Some(ssao_renderer) = self.ssao_renderer.as_mut() {
+            ssao_renderer.set_radius(settings.ssao_radius);
         }
-        self.ssao_renderer.set_radius(settings.ssao_radius);
         Ok(())
     }
 
@@ -277,14 +350,43 @@ impl DeferredLightRenderer {
         server: &dyn GraphicsServer,
         frame_size: (u32, u32),
     ) -> Result<(), FrameworkError> {
-        self.ssao_renderer = ScreenSpaceAmbientOcclusionRenderer::new(
-            server,
-            frame_size.0 as usize,
-            frame_size.1 as usize,
-        )?;
+        self.frame_size = frame_size;
+        if let Some(ssao_renderer) = self.ssao_renderer.as_mut() {
+            *ssao_renderer =
+                make_ssao_renderer(server, frame_size, self.formats, ssao_renderer.radius())?;
+        }
         Ok(())
     }
 
+    pub(crate) fn collect_frame_targets(&self, targets: &mut Vec<FrameTarget>) {
+        if let Some(ssao_renderer) = self.ssao_renderer.as_ref() {
+            targets.push(FrameTarget::new(
+                SSAO_RAW_TARGET,
+                FrameTargetGroup::AmbientOcclusion,
+                ssao_renderer.raw_ao_map(),
+            ));
+            targets.push(FrameTarget::new(
+                SSAO_RESULT_TARGET,
+                FrameTargetGroup::AmbientOcclusion,
+                ssao_renderer.ao_map(),
+            ));
+        }
+        for (i, cascade) in self.csm_renderer.cascades().iter().enumerate() {
+            targets.push(FrameTarget::new(
+                csm_cascade_target(i),
+                FrameTargetGroup::ShadowMap,
+                cascade.texture().clone(),
+            ));
+        }
+        for i in 0..3 {
+            targets.push(FrameTarget::new(
+                spot_shadow_cascade_target(i),
+                FrameTargetGroup::ShadowMap,
+                self.spot_shadow_map_renderer.cascade_texture(i).clone(),
+            ));
+        }
+    }
+
     pub(crate) fn render(
         &mut self,
         args: DeferredRendererContext,
@@ -309,6 +411,8 @@ impl DeferredLightRenderer {
             uniform_buffer_cache,
             visibility_cache,
             uniform_memory_allocator,
+            ssgi_renderer,
+            temporal_frame,
         } = args;
 
         let viewport = Rect::new(0, 0, gbuffer.width, gbuffer.height);
@@ -324,8 +428,8 @@ impl DeferredLightRenderer {
         let camera_global_position = camera.global_position();
 
         // Fill SSAO map.
-        if settings.use_ssao {
-            pass_stats += self.ssao_renderer.render(
+        if let Some(ssao_renderer) = self.ssao_renderer.as_mut() {
+            pass_stats += ssao_renderer.render(
                 gbuffer,
                 projection_matrix,
                 camera.view_matrix().basis(),
@@ -372,7 +476,10 @@ impl DeferredLightRenderer {
         let gbuffer_normal_map = gbuffer.normal_texture();
         let gbuffer_material_map = gbuffer.material_texture();
         let gbuffer_ambient_map = gbuffer.ambient_texture();
-        let ao_map = self.ssao_renderer.ao_map();
+        let ao_map = self
+            .ssao_renderer
+            .as_ref()
+            .map_or_else(|| fallback_resources.white_dummy.clone(), |r| r.ao_map());
 
         let ambient_color = ambient_color.srgb_to_linear_f32();
         let properties = PropertyGroup::from([
@@ -381,14 +488,7 @@ impl DeferredLightRenderer {
         ]);
         let material = RenderMaterial::from([
             binding("diffuseTexture", gbuffer_diffuse_map),
-            binding(
-                "aoSampler",
-                if settings.use_ssao {
-                    &ao_map
-                } else {
-                    &fallback_resources.white_dummy
-                },
-            ),
+            binding("aoSampler", &ao_map),
             binding("ambientTexture", gbuffer_ambient_map),
             binding("properties", &properties),
         ]);
@@ -530,6 +630,7 @@ impl DeferredLightRenderer {
                     depth_test: Some(CompareFunc::Less),
                     blend: None,
                     scissor_box: None,
+                    attachment_blend: Default::default(),
                 };
                 let properties =
                     PropertyGroup::from([property("worldViewProjection", &shape_wvp_matrix)]);
@@ -621,6 +722,7 @@ impl DeferredLightRenderer {
                             textures,
                             fallback_resources,
                             uniform_memory_allocator,
+                            settings.use_instancing,
                         )?;
 
                         light_stats.spot_shadow_maps_rendered += 1;
@@ -640,6 +742,7 @@ impl DeferredLightRenderer {
                                     texture_cache: textures,
                                     fallback_resources,
                                     uniform_memory_allocator,
+                                    use_instancing: settings.use_instancing,
                                 })?;
 
                         light_stats.point_shadow_maps_rendered += 1;
@@ -657,6 +760,7 @@ impl DeferredLightRenderer {
                             texture_cache: textures,
                             fallback_resources,
                             uniform_memory_allocator,
+                            use_instancing: settings.use_instancing,
                         })?;
 
                         light_stats.csm_rendered += 1;
@@ -857,6 +961,22 @@ impl DeferredLightRenderer {
             }
         }
 
+        // Add bounce lighting, it must be done after all the direct lighting is rendered.
+        if let Some(ssgi_renderer) = ssgi_renderer {
+            pass_stats += ssgi_renderer.render(SsgiRenderContext {
+                gbuffer,
+                frame_buffer,
+                ao_map: &ao_map,
+                quad: &self.quad,
+                projection_matrix,
+                view_matrix: camera.view_matrix(),
+                view_projection_matrix: view_projection,
+                temporal_frame,
+                settings: &settings.ssgi_settings,
+                uniform_buffer_cache,
+            })?;
+        }
+
         Ok((pass_stats, light_stats))
     }
 }
@@ -0,0 +1,455 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! GPU lightmap baker. See [`GpuLightmapBaker`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Point3, Vector2, Vector3},
+        color::Color,
+        math::{aabb::AxisAlignedBoundingBox, Rect},
+        sstorage::ImmutableString,
+    },
+    renderer::{
+        cache::{
+            shader::{binding, property, PropertyGroup, RenderMaterial, RenderPassContainer},
+            uniform::UniformBufferCache,
+        },
+        framework::{
+            buffer::BufferUsage,
+            error::FrameworkError,
+            framebuffer::{Attachment, GpuFrameBuffer},
+            geometry_buffer::GpuGeometryBuffer,
+            gpu_texture::{GpuTexture, PixelKind},
+            server::SharedGraphicsServer,
+            GeometryBufferExt,
+        },
+    },
+    scene::mesh::{buffer::VertexAttributeUsage, buffer::VertexReadTrait, surface::SurfaceData},
+};
+use lightmap::light::LightDefinition;
+
+/// A set of parameters of [`GpuLightmapBaker`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct GpuLightmapBakerSettings {
+    /// Size (in texels) of the shadow maps that are used to calculate shadows from every light
+    /// source. Larger values give sharper shadows at the cost of video memory.
+    pub shadow_map_size: usize,
+    /// Depth bias that is used to prevent self-shadowing artifacts.
+    pub shadow_bias: f32,
+    /// Distance (in world units) along the surface normal, that is used to offset the sample
+    /// position before shadow map lookup. Prevents shadow acne on surfaces that are almost
+    /// parallel to the light direction.
+    pub normal_offset: f32,
+}
+
+impl Default for GpuLightmapBakerSettings {
+    fn default() -> Self {
+        Self {
+            shadow_map_size: 2048,
+            shadow_bias: 0.0005,
+            normal_offset: 0.01,
+        }
+    }
+}
+
+struct LightView {
+    view_projection: Matrix4<f32>,
+    face_axis: Vector3<f32>,
+    shadow_map: GpuFrameBuffer,
+}
+
+struct PreparedLight {
+    kind: i32,
+    position: Vector3<f32>,
+    direction: Vector3<f32>,
+    color: Vector3<f32>,
+    intensity: f32,
+    radius: f32,
+    cone_angle_cos: Vector2<f32>,
+    views: Vec<LightView>,
+}
+
+/// A scene, that was uploaded to GPU and prepared for baking. It contains GPU geometry of every mesh
+/// and shadow maps of every light source. See [`GpuLightmapBaker::prepare_scene`] for more info.
+pub struct GpuLightmapScene {
+    geometries: Vec<GpuGeometryBuffer>,
+    lights: Vec<PreparedLight>,
+}
+
+impl GpuLightmapScene {
+    /// Returns the total amount of meshes in the scene.
+    pub fn mesh_count(&self) -> usize {
+        self.geometries.len()
+    }
+}
+
+/// Raw lighting data of a single mesh, calculated by [`GpuLightmapBaker::bake`].
+#[derive(Clone, Debug, Default)]
+pub struct GpuLightmapTexels {
+    /// Width of the lightmap.
+    pub width: usize,
+    /// Height of the lightmap.
+    pub height: usize,
+    /// Linear lighting of every texel. Rows are stored from the bottom of the texture space to
+    /// the top, the same way as lightmap textures.
+    pub pixels: Vec<Vector3<f32>>,
+    /// A mask of texels, that are covered by at least one triangle of the mesh. Every other texel
+    /// lies in the gaps between UV islands and contains no valid data.
+    pub coverage: Vec<bool>,
+}
+
+/// GPU lightmap baker calculates direct lighting by rasterizing meshes in their lightmap texture
+/// space (second texture coordinates). Shadows are calculated using shadow maps, that are rendered
+/// once per light source for the whole scene: a single orthographic map for directional lights,
+/// a single perspective map for spot lights and six perspective maps for point lights.
+///
+/// The baker is orders of magnitude faster than the CPU ray tracer, but it requires a graphics
+/// context and must be used from the thread that owns the graphics server.
+pub struct GpuLightmapBaker {
+    server: SharedGraphicsServer,
+    uniform_buffer_cache: UniformBufferCache,
+    shadow_shader: RenderPassContainer,
+    light_shader: RenderPassContainer,
+    dummy_shadow_map: GpuTexture,
+    settings: GpuLightmapBakerSettings,
+}
+
+fn make_light_view(
+    server: &SharedGraphicsServer,
+    size: usize,
+    view_projection: Matrix4<f32>,
+    face_axis: Vector3<f32>,
+) -> Result<LightView, FrameworkError> {
+    let depth = server.create_2d_render_target(PixelKind::D32F, size, size)?;
+    let shadow_map = server.create_frame_buffer(Some(Attachment::depth(depth)), vec![])?;
+    Ok(LightView {
+        view_projection,
+        face_axis,
+        shadow_map,
+    })
+}
+
+impl GpuLightmapBaker {
+    /// Creates a new GPU lightmap baker.
+    pub fn new(
+        server: SharedGraphicsServer,
+        settings: GpuLightmapBakerSettings,
+    ) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            uniform_buffer_cache: UniformBufferCache::new(server.clone()),
+            shadow_shader: RenderPassContainer::from_str(
+                &*server,
+                include_str!("shaders/lightmap_shadow.shader"),
+            )?,
+            light_shader: RenderPassContainer::from_str(
+                &*server,
+                include_str!("shaders/lightmap_light.shader"),
+            )?,
+            dummy_shadow_map: server.create_2d_render_target(PixelKind::R32F, 1, 1)?,
+            server,
+            settings,
+        })
+    }
+
+    /// Returns current settings of the baker.
+    pub fn settings(&self) -> &GpuLightmapBakerSettings {
+        &self.settings
+    }
+
+    /// Uploads the given meshes to GPU and renders shadow maps for every light source. Every mesh
+    /// must be in world space and must have second texture coordinates (lightmap UVs).
+    pub fn prepare_scene(
+        &mut self,
+        meshes: &[SurfaceData],
+        lights: &[LightDefinition],
+    ) -> Result<GpuLightmapScene, FrameworkError> {
+        let mut geometries = Vec::with_capacity(meshes.len());
+        let mut scene_bounds = AxisAlignedBoundingBox::default();
+        for mesh in meshes {
+            for view in mesh.vertex_buffer.iter() {
+                if let Ok(position) = view.read_3_f32(VertexAttributeUsage::Position) {
+                    scene_bounds.add_point(position);
+                }
+            }
+            geometries.push(GpuGeometryBuffer::from_surface_data(
+                mesh,
+                BufferUsage::StaticDraw,
+                &*self.server,
+            )?);
+        }
+
+        if !scene_bounds.is_valid() {
+            scene_bounds = AxisAlignedBoundingBox::collapsed();
+        }
+
+        let size = self.settings.shadow_map_size;
+        let scene_center = scene_bounds.center();
+        let scene_radius = scene_bounds.half_extents().norm().max(0.01);
+
+        let mut prepared_lights = Vec::with_capacity(lights.len());
+        for light in lights {
+            let prepared = match light {
+                LightDefinition::Directional(directional) => {
+                    let direction = directional
+                        .direction
+                        .try_normalize(f32::EPSILON)
+                        .unwrap_or_else(Vector3::y);
+                    let up = if direction.y.abs() > 0.99 {
+                        Vector3::z()
+                    } else {
+                        Vector3::y()
+                    };
+                    let view = Matrix4::look_at_rh(
+                        &Point3::from(scene_center + direction * scene_radius),
+                        &Point3::from(scene_center),
+                        &up,
+                    );
+                    let projection = Matrix4::new_orthographic(
+                        -scene_radius,
+                        scene_radius,
+                        -scene_radius,
+                        scene_radius,
+                        0.0,
+                        2.0 * scene_radius,
+                    );
+                    PreparedLight {
+                        kind: 0,
+                        position: Default::default(),
+                        direction,
+                        color: directional.color,
+                        intensity: directional.intensity,
+                        radius: 0.0,
+                        cone_angle_cos: Default::default(),
+                        views: vec![make_light_view(
+                            &self.server,
+                            size,
+                            projection * view,
+                            Default::default(),
+                        )?],
+                    }
+                }
+                LightDefinition::Spot(spot) => {
+                    let direction = spot
+                        .direction
+                        .try_normalize(f32::EPSILON)
+                        .unwrap_or_else(Vector3::y);
+                    let up = if direction.y.abs() > 0.99 {
+                        Vector3::z()
+                    } else {
+                        Vector3::y()
+                    };
+                    let full_cone_angle = (2.0 * spot.edge0.clamp(-1.0, 1.0).acos())
+                        .clamp(0.01, std::f32::consts::PI * 0.99);
+                    let view = Matrix4::look_at_rh(
+                        &Point3::from(spot.position),
+                        &Point3::from(spot.position - direction),
+                        &up,
+                    );
+                    let projection =
+                        Matrix4::new_perspective(1.0, full_cone_angle, 0.01, spot.distance);
+                    PreparedLight {
+                        kind: 1,
+                        position: spot.position,
+                        direction,
+                        color: spot.color,
+                        intensity: spot.intensity,
+                        radius: spot.distance,
+                        cone_angle_cos: Vector2::new(spot.edge0, spot.edge1),
+                        views: vec![make_light_view(
+                            &self.server,
+                            size,
+                            projection * view,
+                            Default::default(),
+                        )?],
+                    }
+                }
+                LightDefinition::Point(point) => {
+                    let projection = Matrix4::new_perspective(
+                        1.0,
+                        std::f32::consts::FRAC_PI_2,
+                        0.01,
+                        point.radius,
+                    );
+                    let faces = [
+                        (Vector3::x(), -Vector3::y()),
+                        (-Vector3::x(), -Vector3::y()),
+                        (Vector3::y(), Vector3::z()),
+                        (-Vector3::y(), -Vector3::z()),
+                        (Vector3::z(), -Vector3::y()),
+                        (-Vector3::z(), -Vector3::y()),
+                    ];
+                    let mut views = Vec::with_capacity(faces.len());
+                    for (look, up) in faces {
+                        let view = Matrix4::look_at_rh(
+                            &Point3::from(point.position),
+                            &Point3::from(point.position + look),
+                            &up,
+                        );
+                        views.push(make_light_view(
+                            &self.server,
+                            size,
+                            projection * view,
+                            look,
+                        )?);
+                    }
+                    PreparedLight {
+                        kind: 2,
+                        position: point.position,
+                        direction: Default::default(),
+                        color: point.color,
+                        intensity: point.intensity,
+                        radius: point.radius,
+                        cone_angle_cos: Default::default(),
+                        views,
+                    }
+                }
+            };
+
+            let viewport = Rect::new(0, 0, size as i32, size as i32);
+            for view in prepared.views.iter() {
+                view.shadow_map.clear(viewport, None, Some(1.0), None);
+
+                let properties =
+                    PropertyGroup::from([property("worldViewProjection", &view.view_projection)]);
+                let material = RenderMaterial::from([binding("properties", &properties)]);
+
+                for geometry in geometries.iter() {
+                    self.shadow_shader.run_pass(
+                        1,
+                        &ImmutableString::new("Primary"),
+                        &view.shadow_map,
+                        geometry,
+                        viewport,
+                        &material,
+                        &mut self.uniform_buffer_cache,
+                        Default::default(),
+                        None,
+                    )?;
+                }
+            }
+
+            prepared_lights.push(prepared);
+        }
+
+        Ok(GpuLightmapScene {
+            geometries,
+            lights: prepared_lights,
+        })
+    }
+
+    /// Calculates lighting for a mesh with the given index in the prepared scene. The result is
+    /// a set of raw linear texels, that could be post-processed (dilated, denoised) and then
+    /// converted to a texture.
+    pub fn bake(
+        &mut self,
+        scene: &GpuLightmapScene,
+        mesh_index: usize,
+        width: usize,
+        height: usize,
+    ) -> Result<GpuLightmapTexels, FrameworkError> {
+        let geometry = scene.geometries.get(mesh_index).ok_or_else(|| {
+            FrameworkError::Custom(format!("There's no mesh with {mesh_index} index!"))
+        })?;
+
+        let target = self
+            .server
+            .create_2d_render_target(PixelKind::RGBA32F, width, height)?;
+        let framebuffer = self
+            .server
+            .create_frame_buffer(None, vec![Attachment::color(target.clone())])?;
+
+        let viewport = Rect::new(0, 0, width as i32, height as i32);
+        framebuffer.clear(viewport, Some(Color::TRANSPARENT), None, None);
+
+        let shadow_map_inv_size = 1.0 / self.settings.shadow_map_size as f32;
+
+        let properties = PropertyGroup::from([property("lightKind", &0i32)]);
+        let material = RenderMaterial::from([
+            binding("shadowMap", &self.dummy_shadow_map),
+            binding("properties", &properties),
+        ]);
+        self.light_shader.run_pass(
+            1,
+            &ImmutableString::new("Coverage"),
+            &framebuffer,
+            geometry,
+            viewport,
+            &material,
+            &mut self.uniform_buffer_cache,
+            Default::default(),
+            None,
+        )?;
+
+        for light in scene.lights.iter() {
+            for view in light.views.iter() {
+                let properties = PropertyGroup::from([
+                    property("lightViewProjMatrix", &view.view_projection),
+                    property("lightPosition", &light.position),
+                    property("lightDirection", &light.direction),
+                    property("lightColor", &light.color),
+                    property("faceAxis", &view.face_axis),
+                    property("coneAngleCos", &light.cone_angle_cos),
+                    property("lightIntensity", &light.intensity),
+                    property("lightRadius", &light.radius),
+                    property("shadowBias", &self.settings.shadow_bias),
+                    property("normalOffset", &self.settings.normal_offset),
+                    property("shadowMapInvSize", &shadow_map_inv_size),
+                    property("lightKind", &light.kind),
+                ]);
+                let material = RenderMaterial::from([
+                    binding(
+                        "shadowMap",
+                        &view.shadow_map.depth_attachment().unwrap().texture,
+                    ),
+                    binding("properties", &properties),
+                ]);
+                self.light_shader.run_pass(
+                    1,
+                    &ImmutableString::new("Light"),
+                    &framebuffer,
+                    geometry,
+                    viewport,
+                    &material,
+                    &mut self.uniform_buffer_cache,
+                    Default::default(),
+                    None,
+                )?;
+            }
+        }
+
+        let raw = target.get_image_of_type::<f32>(0);
+
+        // Readback synchronizes with the GPU, so every uniform buffer is free at this point.
+        self.uniform_buffer_cache.mark_all_unused();
+
+        let mut texels = GpuLightmapTexels {
+            width,
+            height,
+            pixels: Vec::with_capacity(width * height),
+            coverage: Vec::with_capacity(width * height),
+        };
+        for rgba in raw.chunks_exact(4) {
+            texels.pixels.push(Vector3::new(rgba[0], rgba[1], rgba[2]));
+            texels.coverage.push(rgba[3] > 0.0);
+        }
+        Ok(texels)
+    }
+}
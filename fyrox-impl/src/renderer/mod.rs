@@ -33,21 +33,31 @@ pub mod framework;
 pub mod bundle;
 pub mod cache;
 pub mod debug_renderer;
+pub mod degradation;
+pub mod diagnostics;
+pub mod inspection;
+pub mod lightmap_baker;
+pub mod render_graph;
 pub mod storage;
 pub mod ui_renderer;
 pub mod visibility;
 
 mod bloom;
+mod color_blindness;
 mod forward_renderer;
 mod fxaa;
 mod gbuffer;
+mod gpu_profiler;
 mod hdr;
 mod light;
 mod light_volume;
 mod occlusion;
 mod shadow;
 mod ssao;
+mod ssgi;
 mod stats;
+mod taa;
+mod temporal;
 
 use crate::{
     asset::{event::ResourceEvent, manager::ResourceManager},
@@ -56,44 +66,65 @@ use crate::{
         array_as_u8_slice,
         color::Color,
         instant,
-        log::{Log, MessageKind},
+        log::Log,
         math::Rect,
         pool::Handle,
+        profiler,
         reflect::prelude::*,
+        scope_profile,
         sstorage::ImmutableString,
         uuid_provider,
     },
-    engine::error::EngineError,
+    engine::{
+        error::EngineError,
+        latency::{LatencyMarker, LatencyTracker},
+    },
     graph::SceneGraph,
     gui::draw::DrawingContext,
     material::shader::{Shader, ShaderDefinition},
     renderer::{
         bloom::BloomRenderer,
-        bundle::{ObserverInfo, RenderDataBundleStorage, RenderDataBundleStorageOptions},
+        bundle::{
+            InstanceHistory, ObserverInfo, RenderDataBundleStorage, RenderDataBundleStorageOptions,
+        },
         cache::{
             geometry::GeometryCache,
             shader::{
                 binding, property, PropertyGroup, RenderMaterial, RenderPassContainer, ShaderCache,
             },
-            texture::TextureCache,
+            texture::{TextureCache, UploadSettings},
             uniform::{UniformBufferCache, UniformMemoryAllocator},
         },
+        color_blindness::ColorBlindnessRenderer,
         debug_renderer::DebugRenderer,
+        degradation::{DegradationReport, RenderTargetFormats},
+        diagnostics::{RenderErrorBroadcaster, RenderErrorSender},
         forward_renderer::{ForwardRenderContext, ForwardRenderer},
         framework::{
             buffer::{BufferKind, BufferUsage, GpuBuffer},
             error::FrameworkError,
-            framebuffer::{Attachment, AttachmentKind, DrawCallStatistics, GpuFrameBuffer},
+            framebuffer::{Attachment, DrawCallStatistics, GpuFrameBuffer},
             geometry_buffer::GpuGeometryBuffer,
-            gpu_program::SamplerFallback,
-            gpu_texture::{GpuTexture, GpuTextureDescriptor, GpuTextureKind, PixelKind},
-            server::{GraphicsServer, SharedGraphicsServer},
-            GeometryBufferExt, PolygonFace, PolygonFillMode,
+            gpu_program::{SamplerFallback, SamplerKind},
+            gpu_texture::{
+                GpuTexture, GpuTextureDescriptor, GpuTextureKind, MagnificationFilter,
+                MinificationFilter, PixelKind,
+            },
+            server::{GraphicsServer, PresentSettings, SharedGraphicsServer},
+            CompareFunc, GeometryBufferExt, PolygonFace, PolygonFillMode,
         },
         fxaa::FxaaRenderer,
         gbuffer::{GBuffer, GBufferRenderContext},
+        gpu_profiler::GpuProfiler,
         hdr::HighDynamicRangeRenderer,
+        inspection::{FrameTarget, FrameTargetGroup, FrameTargetInfo},
         light::{DeferredLightRenderer, DeferredRendererContext},
+        render_graph::{
+            RenderGraph, RenderGraphExecutionContext, RenderGraphStage, RenderGraphTargets,
+        },
+        ssgi::ScreenSpaceGlobalIlluminationRenderer,
+        taa::{TaaRenderContext, TemporalAntiAliasingRenderer},
+        temporal::TemporalReprojection,
         ui_renderer::{UiRenderContext, UiRenderer},
         visibility::VisibilityCache,
     },
@@ -179,6 +210,230 @@ impl Default for CsmSettings {
     }
 }
 
+/// Quality tier of screen-space global illumination. Higher tiers trace more rays with more steps
+/// in higher resolution, which gives less noisy and more precise bounce lighting.
+#[derive(
+    Copy,
+    Clone,
+    Hash,
+    PartialOrd,
+    PartialEq,
+    Eq,
+    Ord,
+    Debug,
+    Default,
+    Serialize,
+    Deserialize,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+)]
+pub enum SsgiQuality {
+    /// Quarter resolution, one ray with 8 steps per pixel.
+    Low,
+    /// Half resolution, two rays with 12 steps per pixel.
+    #[default]
+    Medium,
+    /// Half resolution, four rays with 16 steps per pixel.
+    High,
+}
+
+uuid_provider!(SsgiQuality = "ea86882b-6201-4842-ad3b-fd186b40d4b7");
+
+impl SsgiQuality {
+    fn resolution_divisor(self) -> usize {
+        match self {
+            SsgiQuality::Low => 4,
+            SsgiQuality::Medium | SsgiQuality::High => 2,
+        }
+    }
+
+    fn ray_count(self) -> i32 {
+        match self {
+            SsgiQuality::Low => 1,
+            SsgiQuality::Medium => 2,
+            SsgiQuality::High => 4,
+        }
+    }
+
+    fn step_count(self) -> i32 {
+        match self {
+            SsgiQuality::Low => 8,
+            SsgiQuality::Medium => 12,
+            SsgiQuality::High => 16,
+        }
+    }
+}
+
+/// Screen-space global illumination (SSGI) settings. SSGI gives approximate single-bounce
+/// indirect lighting for dynamic scenes without baking, by tracing rays against the depth buffer
+/// and fetching the lit color of the surfaces that were hit. It can only "see" surfaces visible on
+/// screen, so the bounce lighting fades when the surfaces that emit it go off-screen.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, Reflect)]
+pub struct SsgiSettings {
+    /// Whether the screen-space global illumination is enabled or not.
+    pub enabled: bool,
+
+    /// Quality tier, see [`SsgiQuality`] docs for more info.
+    pub quality: SsgiQuality,
+
+    /// Multiplier of the bounce lighting.
+    #[reflect(min_value = 0.0)]
+    pub intensity: f32,
+
+    /// Maximum length of each ray in world units.
+    #[reflect(min_value = 0.0)]
+    pub max_distance: f32,
+
+    /// Assumed thickness of the surfaces in the depth buffer in world units. Rays pass behind the
+    /// surfaces that are thinner than the value.
+    #[reflect(min_value = 0.0)]
+    pub thickness: f32,
+
+    /// How much of the accumulated lighting of the previous frames is kept every frame. Must be
+    /// in `[0; 1)` range. Higher values give less noise, but more ghosting on moving objects.
+    #[reflect(min_value = 0.0, max_value = 0.99)]
+    pub temporal_blend: f32,
+}
+
+impl Default for SsgiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            quality: SsgiQuality::Medium,
+            intensity: 1.0,
+            max_distance: 2.0,
+            thickness: 0.5,
+            temporal_blend: 0.9,
+        }
+    }
+}
+
+/// Temporal anti-aliasing (TAA) settings. TAA slightly shifts the projection of the camera every
+/// frame by a sub-pixel offset and accumulates the results of multiple frames, using per-pixel
+/// motion vectors to find where each pixel was in the previous frames. Unlike FXAA, it is stable
+/// in motion and handles thin geometry (foliage, wires, etc.) well, but it could cause slight
+/// ghosting on fast moving objects.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, Reflect)]
+pub struct TaaSettings {
+    /// Whether the temporal anti-aliasing is enabled or not.
+    pub enabled: bool,
+
+    /// How much of the accumulated frames is kept every frame. Must be in `[0; 1)` range. Higher
+    /// values give smoother edges, but more ghosting.
+    #[reflect(min_value = 0.0, max_value = 0.98)]
+    pub history_weight: f32,
+
+    /// Strength of sharpening, that is applied after the accumulation to compensate blurriness.
+    /// Zero disables the sharpening.
+    #[reflect(min_value = 0.0, max_value = 1.0)]
+    pub sharpness: f32,
+
+    /// Scale of sub-pixel offsets of the camera projection. One means that the offsets are within
+    /// a single pixel.
+    #[reflect(min_value = 0.0, max_value = 1.0)]
+    pub jitter_scale: f32,
+}
+
+impl Default for TaaSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            history_weight: 0.9,
+            sharpness: 0.25,
+            jitter_scale: 1.0,
+        }
+    }
+}
+
+/// A kind of color vision deficiency (color blindness).
+#[derive(
+    Copy,
+    Clone,
+    Hash,
+    PartialEq,
+    Eq,
+    Debug,
+    Default,
+    Serialize,
+    Deserialize,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+)]
+pub enum ColorVisionDeficiency {
+    /// Normal color vision, no filtering is performed.
+    #[default]
+    None,
+    /// Absence of red cones (red-green color blindness).
+    Protanopia,
+    /// Absence of green cones (the most common red-green color blindness).
+    Deuteranopia,
+    /// Absence of blue cones (blue-yellow color blindness).
+    Tritanopia,
+}
+
+/// Defines what the color blindness filter does with the frame.
+#[derive(
+    Copy,
+    Clone,
+    Hash,
+    PartialEq,
+    Eq,
+    Debug,
+    Default,
+    Serialize,
+    Deserialize,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+)]
+pub enum ColorBlindnessFilterMode {
+    /// Shifts the colors, that are indistinguishable for people with the deficiency, to the
+    /// colors they can distinguish (daltonization).
+    #[default]
+    Correction,
+    /// Shows how the frame is seen by people with the deficiency. It is useful for developers to
+    /// check whether the game is playable with a color vision deficiency.
+    Simulation,
+}
+
+/// Color blindness filter settings. The filter is a post-processing pass, that is applied to the
+/// final (tone mapped) frames of scenes, the user interface is not affected.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, Reflect)]
+pub struct ColorBlindnessSettings {
+    /// A kind of color vision deficiency to correct or simulate. [`ColorVisionDeficiency::None`]
+    /// disables the filter.
+    pub deficiency: ColorVisionDeficiency,
+
+    /// Defines whether the filter corrects or simulates the deficiency.
+    pub mode: ColorBlindnessFilterMode,
+
+    /// Strength of the filter, where zero means no effect and one means the full effect.
+    #[reflect(min_value = 0.0, max_value = 1.0)]
+    pub strength: f32,
+}
+
+impl Default for ColorBlindnessSettings {
+    fn default() -> Self {
+        Self {
+            deficiency: ColorVisionDeficiency::None,
+            mode: ColorBlindnessFilterMode::Correction,
+            strength: 1.0,
+        }
+    }
+}
+
+impl ColorBlindnessSettings {
+    /// Returns `true` if the filter has any effect.
+    pub fn is_enabled(&self) -> bool {
+        self.deficiency != ColorVisionDeficiency::None && self.strength > 0.0
+    }
+}
+
 /// Quality settings allows you to find optimal balance between performance and
 /// graphics quality.
 #[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, Reflect)]
@@ -225,6 +480,10 @@ pub struct QualitySettings {
     /// occlusion will be in your scene.
     pub ssao_radius: f32,
 
+    /// Screen-space global illumination settings.
+    #[serde(default)]
+    pub ssgi_settings: SsgiSettings,
+
     /// Global switch to enable or disable light scattering. Each light can have
     /// its own scatter switch, but this one is able to globally disable scatter.
     pub light_scatter_enabled: bool,
@@ -232,6 +491,19 @@ pub struct QualitySettings {
     /// Whether to use Fast Approximate AntiAliasing or not.
     pub fxaa: bool,
 
+    /// Amount of samples of multi-sample anti-aliasing (MSAA) of the final frame. Must be a power
+    /// of two (2, 4, 8), `None` disables MSAA. MSAA smooths the edges of everything, that is
+    /// rendered directly into the final frame (user interface, screen-space debug geometry), scenes
+    /// are rendered using deferred shading and should use [`Self::fxaa`] or [`Self::taa_settings`]
+    /// instead. Unlike [`crate::engine::GraphicsContextParams::msaa_sample_count`], it can be
+    /// changed at any time.
+    #[serde(default)]
+    pub msaa_sample_count: Option<u8>,
+
+    /// Temporal anti-aliasing settings.
+    #[serde(default)]
+    pub taa_settings: TaaSettings,
+
     /// Whether to use Parallax Mapping or not.
     pub use_parallax_mapping: bool,
 
@@ -247,6 +519,16 @@ pub struct QualitySettings {
     /// feature that may have bugs and unstable behavior. Disabled by default.
     #[serde(default)]
     pub use_light_occlusion_culling: bool,
+
+    /// Whether to merge draw calls of identical surfaces (same geometry, material and render
+    /// states) into instanced draw calls or not. Significantly reduces the amount of draw calls
+    /// for scenes with lots of repeating objects (foliage, rocks, props, etc.). Enabled by default.
+    #[serde(default = "default_use_instancing")]
+    pub use_instancing: bool,
+}
+
+fn default_use_instancing() -> bool {
+    true
 }
 
 impl Default for QualitySettings {
@@ -256,6 +538,13 @@ impl Default for QualitySettings {
 }
 
 impl QualitySettings {
+    /// Returns the amount of MSAA samples, that is actually used. `None` means that MSAA is
+    /// disabled.
+    pub fn effective_msaa_sample_count(&self) -> Option<u8> {
+        self.msaa_sample_count
+            .filter(|sample_count| *sample_count > 1)
+    }
+
     /// Highest possible graphics quality. Requires very powerful GPU.
     pub fn ultra() -> Self {
         Self {
@@ -274,12 +563,24 @@ impl QualitySettings {
             use_ssao: true,
             ssao_radius: 0.5,
 
+            ssgi_settings: SsgiSettings {
+                enabled: true,
+                quality: SsgiQuality::High,
+                ..Default::default()
+            },
+
             light_scatter_enabled: true,
 
             point_shadow_map_precision: ShadowMapPrecision::Full,
             spot_shadow_map_precision: ShadowMapPrecision::Full,
 
-            fxaa: true,
+            fxaa: false,
+            msaa_sample_count: None,
+
+            taa_settings: TaaSettings {
+                enabled: true,
+                ..Default::default()
+            },
 
             use_bloom: true,
 
@@ -289,6 +590,8 @@ impl QualitySettings {
 
             use_occlusion_culling: false,
             use_light_occlusion_culling: false,
+
+            use_instancing: true,
         }
     }
 
@@ -310,12 +613,17 @@ impl QualitySettings {
             use_ssao: true,
             ssao_radius: 0.5,
 
+            ssgi_settings: Default::default(),
+
             light_scatter_enabled: true,
 
             point_shadow_map_precision: ShadowMapPrecision::Full,
             spot_shadow_map_precision: ShadowMapPrecision::Full,
 
             fxaa: true,
+            msaa_sample_count: None,
+
+            taa_settings: Default::default(),
 
             use_bloom: true,
 
@@ -330,6 +638,8 @@ impl QualitySettings {
 
             use_occlusion_culling: false,
             use_light_occlusion_culling: false,
+
+            use_instancing: true,
         }
     }
 
@@ -351,12 +661,20 @@ impl QualitySettings {
             use_ssao: true,
             ssao_radius: 0.5,
 
+            ssgi_settings: SsgiSettings {
+                quality: SsgiQuality::Low,
+                ..Default::default()
+            },
+
             light_scatter_enabled: false,
 
             point_shadow_map_precision: ShadowMapPrecision::Half,
             spot_shadow_map_precision: ShadowMapPrecision::Half,
 
             fxaa: true,
+            msaa_sample_count: None,
+
+            taa_settings: Default::default(),
 
             use_bloom: true,
 
@@ -371,6 +689,8 @@ impl QualitySettings {
 
             use_occlusion_culling: false,
             use_light_occlusion_culling: false,
+
+            use_instancing: true,
         }
     }
 
@@ -392,12 +712,17 @@ impl QualitySettings {
             use_ssao: false,
             ssao_radius: 0.5,
 
+            ssgi_settings: Default::default(),
+
             light_scatter_enabled: false,
 
             point_shadow_map_precision: ShadowMapPrecision::Half,
             spot_shadow_map_precision: ShadowMapPrecision::Half,
 
             fxaa: false,
+            msaa_sample_count: None,
+
+            taa_settings: Default::default(),
 
             use_bloom: false,
 
@@ -412,6 +737,8 @@ impl QualitySettings {
 
             use_occlusion_culling: false,
             use_light_occlusion_culling: false,
+
+            use_instancing: true,
         }
     }
 }
@@ -462,6 +789,8 @@ impl Default for Statistics {
             capped_frame_time: 0.0,
             frames_per_second: 0,
             texture_cache_size: 0,
+            texture_memory_usage: 0,
+            pending_texture_uploads: 0,
             geometry_cache_size: 0,
             shader_cache_size: 0,
             uniform_buffer_cache_size: 0,
@@ -472,6 +801,28 @@ impl Default for Statistics {
     }
 }
 
+/// Defines what happens with a resource of the renderer, when quality settings are changed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum ResourceTransition {
+    /// The resource stays intact.
+    Keep,
+    /// A new resource must be created, the old one (if any) is replaced.
+    Recreate,
+    /// The resource is no longer needed.
+    Destroy,
+}
+
+fn msaa_transition(current: &QualitySettings, new: &QualitySettings) -> ResourceTransition {
+    match (
+        current.effective_msaa_sample_count(),
+        new.effective_msaa_sample_count(),
+    ) {
+        (current, new) if current == new => ResourceTransition::Keep,
+        (_, Some(_)) => ResourceTransition::Recreate,
+        (_, None) => ResourceTransition::Destroy,
+    }
+}
+
 /// A set of frame buffers, renderers, that contains scene-specific data.
 pub struct AssociatedSceneData {
     /// G-Buffer of the scene.
@@ -496,29 +847,42 @@ pub struct AssociatedSceneData {
 
     /// Rendering statistics for a scene.
     pub statistics: SceneStatistics,
+
+    /// Camera state of the previous frame, that is used for temporal reprojection.
+    temporal_reprojection: TemporalReprojection,
+
+    /// Screen-space global illumination renderer. It has to be created per scene, because it
+    /// contains accumulated lighting of the previous frames. Exists only if SSGI is enabled.
+    ssgi_renderer: Option<ScreenSpaceGlobalIlluminationRenderer>,
+
+    /// Temporal anti-aliasing renderer. It has to be created per scene, because it contains
+    /// accumulated frames. Exists only if TAA is enabled.
+    taa_renderer: Option<TemporalAntiAliasingRenderer>,
+
+    /// World transforms and bone matrices of the rendered instances of the previous frame, that
+    /// are used to calculate motion vectors.
+    instance_history: InstanceHistory,
+
+    /// Render targets and frame buffers of render graph passes.
+    render_graph_targets: RenderGraphTargets,
 }
 
 impl AssociatedSceneData {
-    /// Creates new scene data.
+    /// Creates new scene data. `formats` defines pixel kinds of the intermediate render targets,
+    /// see [`DegradationReport`] docs for more info.
     pub fn new(
         server: &dyn GraphicsServer,
         width: usize,
         height: usize,
+        formats: &RenderTargetFormats,
     ) -> Result<Self, FrameworkError> {
         let depth_stencil = server.create_2d_render_target(PixelKind::D24S8, width, height)?;
         // Intermediate scene frame will be rendered in HDR render target.
-        let hdr_frame_texture =
-            server.create_2d_render_target(PixelKind::RGBA16F, width, height)?;
+        let hdr_frame_texture = server.create_2d_render_target(formats.hdr_color, width, height)?;
 
         let hdr_scene_framebuffer = server.create_frame_buffer(
-            Some(Attachment {
-                kind: AttachmentKind::DepthStencil,
-                texture: depth_stencil.clone(),
-            }),
-            vec![Attachment {
-                kind: AttachmentKind::Color,
-                texture: hdr_frame_texture,
-            }],
+            Some(Attachment::depth_stencil(depth_stencil.clone())),
+            vec![Attachment::color(hdr_frame_texture)],
         )?;
 
         let ldr_frame_texture = server.create_texture(GpuTextureDescriptor {
@@ -529,14 +893,8 @@ impl AssociatedSceneData {
         })?;
 
         let ldr_scene_framebuffer = server.create_frame_buffer(
-            Some(Attachment {
-                kind: AttachmentKind::DepthStencil,
-                texture: depth_stencil.clone(),
-            }),
-            vec![Attachment {
-                kind: AttachmentKind::Color,
-                texture: ldr_frame_texture,
-            }],
+            Some(Attachment::depth_stencil(depth_stencil.clone())),
+            vec![Attachment::color(ldr_frame_texture)],
         )?;
 
         let ldr_temp_texture = server.create_texture(GpuTextureDescriptor {
@@ -547,27 +905,67 @@ impl AssociatedSceneData {
         })?;
 
         let ldr_temp_framebuffer = server.create_frame_buffer(
-            Some(Attachment {
-                kind: AttachmentKind::DepthStencil,
-                texture: depth_stencil,
-            }),
-            vec![Attachment {
-                kind: AttachmentKind::Color,
-                texture: ldr_temp_texture,
-            }],
+            Some(Attachment::depth_stencil(depth_stencil)),
+            vec![Attachment::color(ldr_temp_texture)],
         )?;
 
         Ok(Self {
-            gbuffer: GBuffer::new(server, width, height)?,
-            hdr_renderer: HighDynamicRangeRenderer::new(server)?,
-            bloom_renderer: BloomRenderer::new(server, width, height)?,
+            gbuffer: GBuffer::new(server, width, height, formats)?,
+            hdr_renderer: HighDynamicRangeRenderer::new(server, formats.scalar)?,
+            bloom_renderer: BloomRenderer::new(server, width, height, formats.hdr_color)?,
             hdr_scene_framebuffer,
             ldr_scene_framebuffer,
             ldr_temp_framebuffer,
             statistics: Default::default(),
+            temporal_reprojection: Default::default(),
+            ssgi_renderer: None,
+            taa_renderer: None,
+            instance_history: Default::default(),
+            render_graph_targets: Default::default(),
         })
     }
 
+    fn sync_taa_renderer(
+        &mut self,
+        server: &dyn GraphicsServer,
+        settings: &TaaSettings,
+    ) -> Result<(), FrameworkError> {
+        if !settings.enabled {
+            self.taa_renderer = None;
+        } else if self.taa_renderer.is_none() {
+            self.taa_renderer = Some(TemporalAntiAliasingRenderer::new(
+                server,
+                self.gbuffer.width as usize,
+                self.gbuffer.height as usize,
+            )?);
+        }
+        Ok(())
+    }
+
+    fn sync_ssgi_renderer(
+        &mut self,
+        server: &dyn GraphicsServer,
+        settings: &SsgiSettings,
+    ) -> Result<(), FrameworkError> {
+        if !settings.enabled {
+            self.ssgi_renderer = None;
+        } else if self
+            .ssgi_renderer
+            .as_ref()
+            .map_or(true, |renderer| renderer.quality() != settings.quality)
+        {
+            self.ssgi_renderer = Some(ScreenSpaceGlobalIlluminationRenderer::new(
+                server,
+                self.gbuffer.width as usize,
+                self.gbuffer.height as usize,
+                settings.quality,
+            )?);
+            // New renderer has no history.
+            self.temporal_reprojection.reset();
+        }
+        Ok(())
+    }
+
     fn copy_depth_stencil_to_scene_framebuffer(&mut self) {
         self.gbuffer.framebuffer().blit_to(
             &self.hdr_scene_framebuffer,
@@ -599,6 +997,90 @@ impl AssociatedSceneData {
     pub fn ldr_temp_frame_texture(&self) -> &GpuTexture {
         &self.ldr_temp_framebuffer.color_attachments()[0].texture
     }
+
+    /// Returns a texture of the render target with the given name, that was created by a render
+    /// graph pass (see [`render_graph::RenderPassBuilder::create`]).
+    pub fn render_graph_target(&self, name: &str) -> Option<&GpuTexture> {
+        self.render_graph_targets.get(name)
+    }
+
+    fn render_graph_built_in_targets(&self) -> Vec<(&'static str, GpuTexture)> {
+        let depth_stencil = self
+            .hdr_scene_framebuffer
+            .depth_attachment()
+            .map(|attachment| attachment.texture.clone());
+        [
+            (
+                render_graph::SCENE_HDR_TARGET,
+                Some(self.hdr_scene_frame_texture().clone()),
+            ),
+            (
+                render_graph::SCENE_LDR_TARGET,
+                Some(self.ldr_scene_frame_texture().clone()),
+            ),
+            (render_graph::SCENE_DEPTH_STENCIL_TARGET, depth_stencil),
+            (
+                render_graph::GBUFFER_DEPTH_TARGET,
+                Some(self.gbuffer.depth().clone()),
+            ),
+            (
+                render_graph::GBUFFER_DIFFUSE_TARGET,
+                Some(self.gbuffer.diffuse_texture().clone()),
+            ),
+            (
+                render_graph::GBUFFER_NORMAL_TARGET,
+                Some(self.gbuffer.normal_texture().clone()),
+            ),
+            (
+                render_graph::GBUFFER_AMBIENT_TARGET,
+                Some(self.gbuffer.ambient_texture().clone()),
+            ),
+            (
+                render_graph::GBUFFER_MATERIAL_TARGET,
+                Some(self.gbuffer.material_texture().clone()),
+            ),
+            (
+                render_graph::GBUFFER_MOTION_TARGET,
+                Some(self.gbuffer.motion_texture().clone()),
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(name, texture)| texture.map(|texture| (name, texture)))
+        .collect()
+    }
+
+    fn collect_frame_targets(&self, targets: &mut Vec<FrameTarget>) {
+        for (name, texture) in self.render_graph_built_in_targets() {
+            let group = match name {
+                render_graph::SCENE_HDR_TARGET | render_graph::SCENE_LDR_TARGET => {
+                    FrameTargetGroup::SceneFrame
+                }
+                // The depth-stencil of the scene frame contains a copy of the G-Buffer depth, it
+                // also cannot be sampled while the scene frame buffer is bound.
+                render_graph::SCENE_DEPTH_STENCIL_TARGET => continue,
+                _ => FrameTargetGroup::GBuffer,
+            };
+            targets.push(FrameTarget::new(name, group, texture));
+        }
+        self.bloom_renderer.collect_frame_targets(targets);
+        for (name, texture) in self.render_graph_targets.iter() {
+            targets.push(FrameTarget::new(
+                name,
+                FrameTargetGroup::RenderGraph,
+                texture.clone(),
+            ));
+        }
+    }
+}
+
+fn collect_frame_targets(
+    scene_data: &AssociatedSceneData,
+    deferred_light_renderer: &DeferredLightRenderer,
+) -> Vec<FrameTarget> {
+    let mut targets = Vec::new();
+    scene_data.collect_frame_targets(&mut targets);
+    deferred_light_renderer.collect_frame_targets(&mut targets);
+    targets
 }
 
 /// Creates a view-projection matrix that projects unit quad a screen with the specified viewport.
@@ -635,6 +1117,16 @@ pub struct FallbackResources {
     pub metallic_dummy: GpuTexture,
     /// One pixel volume texture.
     pub volume_dummy: GpuTexture,
+    /// One pixel unsigned integer texture with zero value. Used as a stub for integer samplers.
+    pub uint_dummy: GpuTexture,
+    /// One pixel unsigned integer cube map with zero values. Used as a stub for integer cube
+    /// samplers.
+    pub uint_cube_dummy: GpuTexture,
+    /// One pixel depth texture with depth comparison enabled and maximum depth. Used as a stub for
+    /// shadow samplers, every comparison with it passes (i.e. "no shadow").
+    pub shadow_dummy: GpuTexture,
+    /// Cube map version of [`Self::shadow_dummy`].
+    pub shadow_cube_dummy: GpuTexture,
     /// A stub uniform buffer for situation when there's no actual bone matrices.
     pub bone_matrices_stub_uniform_buffer: GpuBuffer,
 }
@@ -649,12 +1141,33 @@ impl FallbackResources {
             SamplerFallback::Volume => &self.volume_dummy,
         }
     }
+
+    /// Picks a texture that could be bound to a sampler of the given kind. Integer and shadow
+    /// samplers require textures of specific pixel kind, so the fallback value is ignored for
+    /// them.
+    pub fn sampler_fallback_of_kind(
+        &self,
+        kind: SamplerKind,
+        sampler_fallback: SamplerFallback,
+    ) -> &GpuTexture {
+        match (kind.is_integer(), kind.is_shadow(), kind.is_cube()) {
+            (true, _, false) => &self.uint_dummy,
+            (true, _, true) => &self.uint_cube_dummy,
+            (_, true, false) => &self.shadow_dummy,
+            (_, true, true) => &self.shadow_cube_dummy,
+            _ => self.sampler_fallback(sampler_fallback),
+        }
+    }
 }
 
 /// See module docs.
 pub struct Renderer {
     backbuffer: GpuFrameBuffer,
+    // Exists only if MSAA is enabled. The final frame is rendered into this frame buffer and then
+    // resolved into the back buffer.
+    msaa_frame_buffer: Option<GpuFrameBuffer>,
     scene_render_passes: Vec<Rc<RefCell<dyn SceneRenderPass>>>,
+    render_graph: RenderGraph,
     deferred_light_renderer: DeferredLightRenderer,
     blit_shader: RenderPassContainer,
     /// A set of textures of certain kinds that could be used as a stub in cases when you don't have
@@ -682,6 +1195,8 @@ pub struct Renderer {
     geometry_cache: GeometryCache,
     forward_renderer: ForwardRenderer,
     fxaa_renderer: FxaaRenderer,
+    color_blindness_renderer: ColorBlindnessRenderer,
+    color_blindness_settings: ColorBlindnessSettings,
     texture_event_receiver: Receiver<ResourceEvent>,
     shader_event_receiver: Receiver<ResourceEvent>,
     // TextureId -> FrameBuffer mapping. This mapping is used for temporal frame buffers
@@ -692,6 +1207,23 @@ pub struct Renderer {
     pub visibility_cache: VisibilityCache,
     /// Graphics server.
     pub server: SharedGraphicsServer,
+    error_broadcaster: RenderErrorBroadcaster,
+    gpu_profiler: GpuProfiler,
+    degradation: DegradationReport,
+    debug_target: Option<String>,
+}
+
+fn make_msaa_frame_buffer(
+    server: &dyn GraphicsServer,
+    frame_size: (u32, u32),
+    sample_count: u8,
+) -> Result<GpuFrameBuffer, FrameworkError> {
+    server.create_multisampled_frame_buffer(
+        PixelKind::RGBA8,
+        frame_size.0.max(1) as usize,
+        frame_size.1.max(1) as usize,
+        sample_count as usize,
+    )
 }
 
 fn make_ui_frame_buffer(
@@ -715,14 +1247,8 @@ fn make_ui_frame_buffer(
     )?;
 
     server.create_frame_buffer(
-        Some(Attachment {
-            kind: AttachmentKind::DepthStencil,
-            texture: depth_stencil,
-        }),
-        vec![Attachment {
-            kind: AttachmentKind::Color,
-            texture: color_texture,
-        }],
+        Some(Attachment::depth_stencil(depth_stencil)),
+        vec![Attachment::color(color_texture)],
     )
 }
 
@@ -891,7 +1417,7 @@ impl Renderer {
         frame_size: (u32, u32),
         resource_manager: &ResourceManager,
     ) -> Result<Self, EngineError> {
-        let settings = QualitySettings::default();
+        let mut settings = QualitySettings::default();
 
         let (texture_event_sender, texture_event_receiver) = std::sync::mpsc::channel();
 
@@ -910,7 +1436,22 @@ impl Renderer {
         let caps = server.capabilities();
         Log::info(format!("Graphics Server Capabilities\n{caps:?}",));
 
-        let shader_cache = ShaderCache::default();
+        let degradation = DegradationReport::evaluate(&caps);
+        for entry in degradation.degradations.iter() {
+            Log::warn(format!("Renderer feature degraded. {entry}"));
+        }
+        degradation.restrict(&mut settings);
+
+        let error_broadcaster = RenderErrorBroadcaster::default();
+
+        let shader_cache = ShaderCache {
+            errors: error_broadcaster.clone(),
+            ..Default::default()
+        };
+        let texture_cache = TextureCache {
+            errors: error_broadcaster.clone(),
+            ..Default::default()
+        };
 
         let one_megabyte = 1024 * 1024;
         let uniform_memory_allocator = UniformMemoryAllocator::new(
@@ -984,6 +1525,52 @@ impl Renderer {
                 data: Some(&[0u8, 0u8, 0u8, 0u8]),
                 ..Default::default()
             })?,
+            uint_dummy: server.create_texture(GpuTextureDescriptor {
+                kind: GpuTextureKind::Rectangle {
+                    width: 1,
+                    height: 1,
+                },
+                pixel_kind: PixelKind::R32UI,
+                min_filter: MinificationFilter::Nearest,
+                mag_filter: MagnificationFilter::Nearest,
+                data: Some(&[0u8; 4]),
+                ..Default::default()
+            })?,
+            uint_cube_dummy: server.create_texture(GpuTextureDescriptor {
+                kind: GpuTextureKind::Cube {
+                    width: 1,
+                    height: 1,
+                },
+                pixel_kind: PixelKind::R32UI,
+                min_filter: MinificationFilter::Nearest,
+                mag_filter: MagnificationFilter::Nearest,
+                data: Some(&[0u8; 6 * 4]),
+                ..Default::default()
+            })?,
+            shadow_dummy: server.create_texture(GpuTextureDescriptor {
+                kind: GpuTextureKind::Rectangle {
+                    width: 1,
+                    height: 1,
+                },
+                pixel_kind: PixelKind::D32F,
+                min_filter: MinificationFilter::Nearest,
+                mag_filter: MagnificationFilter::Nearest,
+                data: Some(array_as_u8_slice(&[1.0f32])),
+                compare_func: Some(CompareFunc::LessOrEqual),
+                ..Default::default()
+            })?,
+            shadow_cube_dummy: server.create_texture(GpuTextureDescriptor {
+                kind: GpuTextureKind::Cube {
+                    width: 1,
+                    height: 1,
+                },
+                pixel_kind: PixelKind::D32F,
+                min_filter: MinificationFilter::Nearest,
+                mag_filter: MagnificationFilter::Nearest,
+                data: Some(array_as_u8_slice(&[1.0f32; 6])),
+                compare_func: Some(CompareFunc::LessOrEqual),
+                ..Default::default()
+            })?,
             bone_matrices_stub_uniform_buffer: {
                 let buffer = server.create_buffer(
                     ShaderDefinition::MAX_BONE_MATRICES * size_of::<Matrix4<f32>>(),
@@ -999,8 +1586,17 @@ impl Renderer {
 
         Ok(Self {
             backbuffer: server.back_buffer(),
+            msaa_frame_buffer: settings
+                .effective_msaa_sample_count()
+                .map(|sample_count| make_msaa_frame_buffer(&*server, frame_size, sample_count))
+                .transpose()?,
             frame_size,
-            deferred_light_renderer: DeferredLightRenderer::new(&*server, frame_size, &settings)?,
+            deferred_light_renderer: DeferredLightRenderer::new(
+                &*server,
+                frame_size,
+                &settings,
+                degradation.formats,
+            )?,
             blit_shader: RenderPassContainer::from_str(
                 &*server,
                 include_str!("shaders/blit.shader"),
@@ -1017,20 +1613,30 @@ impl Renderer {
             screen_space_debug_renderer: DebugRenderer::new(&*server)?,
             scene_data_map: Default::default(),
             backbuffer_clear_color: Color::BLACK,
-            texture_cache: Default::default(),
+            texture_cache,
             geometry_cache: Default::default(),
             forward_renderer: ForwardRenderer::new(),
             ui_frame_buffers: Default::default(),
             fxaa_renderer: FxaaRenderer::new(&*server)?,
+            color_blindness_renderer: ColorBlindnessRenderer::new(&*server)?,
+            color_blindness_settings: Default::default(),
             statistics: Statistics::default(),
             shader_event_receiver,
             texture_event_receiver,
             shader_cache,
             scene_render_passes: Default::default(),
-            uniform_buffer_cache: UniformBufferCache::new(server.clone()),
+            render_graph: Default::default(),
+            uniform_buffer_cache: UniformBufferCache::with_ring_buffer(
+                server.clone(),
+                UniformBufferCache::DEFAULT_RING_SEGMENT_SIZE,
+            ),
+            gpu_profiler: GpuProfiler::new(&*server),
             server,
             visibility_cache: Default::default(),
             uniform_memory_allocator,
+            error_broadcaster,
+            degradation,
+            debug_target: None,
         })
     }
 
@@ -1060,6 +1666,86 @@ impl Renderer {
         self.scene_render_passes.clear()
     }
 
+    /// Returns a reference to the render graph, that contains user-defined render passes with
+    /// declared render targets. See [`RenderGraph`] docs for more info.
+    pub fn render_graph(&self) -> &RenderGraph {
+        &self.render_graph
+    }
+
+    /// Returns a reference to the render graph, that contains user-defined render passes with
+    /// declared render targets. See [`RenderGraph`] docs for more info.
+    pub fn render_graph_mut(&mut self) -> &mut RenderGraph {
+        &mut self.render_graph
+    }
+
+    /// Adds a listener of rendering errors (failed shader compilation, texture upload, etc.) and
+    /// returns its handle. Every unique error is delivered only once, see [`RenderErrorBroadcaster`]
+    /// docs for more info.
+    pub fn add_error_listener(&self, sender: RenderErrorSender) -> Handle<RenderErrorSender> {
+        self.error_broadcaster.add(sender)
+    }
+
+    /// Removes a listener of rendering errors by its handle.
+    pub fn remove_error_listener(&self, handle: Handle<RenderErrorSender>) -> RenderErrorSender {
+        self.error_broadcaster.remove(handle)
+    }
+
+    /// Returns a reference to the broadcaster of rendering errors.
+    pub fn error_broadcaster(&self) -> &RenderErrorBroadcaster {
+        &self.error_broadcaster
+    }
+
+    /// Returns a report about the features, that were degraded because of missing capabilities of
+    /// the graphics server. See [`DegradationReport`] docs for more info.
+    pub fn degradation_report(&self) -> &DegradationReport {
+        &self.degradation
+    }
+
+    /// Returns descriptions of the intermediate render targets (G-Buffer layers, shadow maps, bloom
+    /// chain, render graph targets, etc.) that were used to render the given scene. Returns an
+    /// empty list if the scene wasn't rendered yet. Names of the render targets could be used in
+    /// [`Self::set_debug_target`] and [`Self::frame_target_texture`].
+    pub fn frame_targets(&self, scene_handle: Handle<Scene>) -> Vec<FrameTargetInfo> {
+        self.scene_data_map
+            .get(&scene_handle)
+            .map(|scene_data| {
+                collect_frame_targets(scene_data, &self.deferred_light_renderer)
+                    .iter()
+                    .filter_map(|target| target.info())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns a texture of the intermediate render target with the given name, that was used to
+    /// render the given scene. See [`Self::frame_targets`] for more info.
+    pub fn frame_target_texture(
+        &self,
+        scene_handle: Handle<Scene>,
+        name: &str,
+    ) -> Option<GpuTexture> {
+        let scene_data = self.scene_data_map.get(&scene_handle)?;
+        collect_frame_targets(scene_data, &self.deferred_light_renderer)
+            .into_iter()
+            .find(|target| target.name == name && target.info().is_some())
+            .map(|target| target.texture)
+    }
+
+    /// Sets an intermediate render target, that will be shown instead of the final frame of every
+    /// scene (see [`Self::frame_targets`] for the list of available targets). It is useful to debug
+    /// custom shaders, for example to see what a shader writes to the G-Buffer. The render target
+    /// is shown as is, without tone mapping or any other conversion. [`None`] disables the debug
+    /// visualization. Unknown names are ignored, the final frame is shown in this case.
+    pub fn set_debug_target(&mut self, name: Option<String>) {
+        self.debug_target = name;
+    }
+
+    /// Returns the name of the intermediate render target, that is shown instead of the final frame.
+    /// See [`Self::set_debug_target`] for more info.
+    pub fn debug_target(&self) -> Option<&str> {
+        self.debug_target.as_deref()
+    }
+
     /// Returns statistics for last frame.
     pub fn get_statistics(&self) -> Statistics {
         self.statistics
@@ -1095,11 +1781,28 @@ impl Renderer {
         self.deferred_light_renderer
             .set_frame_size(&*self.server, new_size)?;
 
+        if let Some(sample_count) = self.quality_settings.effective_msaa_sample_count() {
+            self.msaa_frame_buffer = Some(make_msaa_frame_buffer(
+                &*self.server,
+                self.frame_size,
+                sample_count,
+            )?);
+        }
+
         self.graphics_server().set_frame_size(new_size);
 
         Ok(())
     }
 
+    // Returns a frame buffer, that the final frame is rendered to. It is either the back buffer or
+    // a multisampled frame buffer, that is resolved into the back buffer at the end of the frame.
+    fn frame_buffer(&self) -> GpuFrameBuffer {
+        self.msaa_frame_buffer
+            .as_ref()
+            .unwrap_or(&self.backbuffer)
+            .clone()
+    }
+
     /// Returns current (width, height) pair of back buffer size.
     pub fn get_frame_size(&self) -> (u32, u32) {
         self.frame_size
@@ -1110,16 +1813,46 @@ impl Renderer {
         Vector2::new(self.frame_size.0 as f32, self.frame_size.1 as f32)
     }
 
-    /// Sets new quality settings for renderer. Never call this method in a loop, otherwise
-    /// you may get **significant** lags. Always check if current quality setting differs
-    /// from new!
+    /// Sets new quality settings for renderer. The settings can be changed at any time (for
+    /// example, from in-game graphics settings menu), the renderer recreates only the resources
+    /// that are affected by the changed settings (shadow maps, SSAO buffers, etc.) and keeps
+    /// everything else, including per-scene render targets with their temporal history. Per-scene
+    /// resources (SSGI, TAA) are synced lazily on the next frame. If any of the new resources
+    /// cannot be created, the renderer keeps its previous settings and the error is returned.
+    ///
+    /// Still, recreation of shadow maps isn't free, so do not call this method in a loop. Features,
+    /// that are not supported by the graphics server, will be disabled regardless of the given
+    /// settings, see [`Self::degradation_report`]. MSAA of the final frame is changed by
+    /// recreating the multisampled frame buffer, see [`QualitySettings::msaa_sample_count`].
     pub fn set_quality_settings(
         &mut self,
         settings: &QualitySettings,
     ) -> Result<(), FrameworkError> {
-        self.quality_settings = *settings;
+        let mut settings = *settings;
+        self.degradation.restrict(&mut settings);
+        if settings == self.quality_settings {
+            return Ok(());
+        }
+
+        let msaa_transition = msaa_transition(&self.quality_settings, &settings);
+        let msaa_frame_buffer = match settings.effective_msaa_sample_count() {
+            Some(sample_count) if msaa_transition == ResourceTransition::Recreate => Some(
+                make_msaa_frame_buffer(&*self.server, self.frame_size, sample_count)?,
+            ),
+            _ => None,
+        };
+
         self.deferred_light_renderer
-            .set_quality_settings(&*self.server, settings)
+            .set_quality_settings(&*self.server, &settings)?;
+
+        match msaa_transition {
+            ResourceTransition::Keep => (),
+            ResourceTransition::Recreate | ResourceTransition::Destroy => {
+                self.msaa_frame_buffer = msaa_frame_buffer
+            }
+        }
+        self.quality_settings = settings;
+        Ok(())
     }
 
     /// Returns current quality settings.
@@ -1127,6 +1860,44 @@ impl Renderer {
         self.quality_settings
     }
 
+    /// Sets new present settings (vertical synchronization mode, amount of frames in flight). Unlike
+    /// quality settings, these settings are cheap to change and could be changed at any time. See
+    /// [`PresentSettings`] docs for more info.
+    pub fn set_present_settings(
+        &mut self,
+        settings: PresentSettings,
+    ) -> Result<(), FrameworkError> {
+        self.server.set_present_settings(settings)
+    }
+
+    /// Returns current present settings.
+    pub fn present_settings(&self) -> PresentSettings {
+        self.server.present_settings()
+    }
+
+    /// Sets new limits of the texture and geometry buffer upload queues. See [`UploadSettings`]
+    /// docs for more info.
+    pub fn set_upload_settings(&mut self, settings: UploadSettings) {
+        self.texture_cache.set_upload_settings(settings);
+        self.geometry_cache.set_upload_settings(settings);
+    }
+
+    /// Returns current limits of the upload queues.
+    pub fn upload_settings(&self) -> UploadSettings {
+        self.texture_cache.upload_settings()
+    }
+
+    /// Sets new color blindness filter settings. The settings are cheap to change and could be
+    /// changed at any time. See [`ColorBlindnessSettings`] docs for more info.
+    pub fn set_color_blindness_settings(&mut self, settings: ColorBlindnessSettings) {
+        self.color_blindness_settings = settings;
+    }
+
+    /// Returns current color blindness filter settings.
+    pub fn color_blindness_settings(&self) -> ColorBlindnessSettings {
+        self.color_blindness_settings
+    }
+
     /// Removes all cached GPU data, forces renderer to re-upload data to GPU.
     /// Do not call this method until you absolutely need! It may cause **significant**
     /// performance lag!
@@ -1206,33 +1977,18 @@ impl Renderer {
     }
 
     fn update_texture_cache(&mut self, dt: f32) {
-        // Maximum amount of textures uploaded to GPU per frame. This defines throughput **only** for
-        // requests from resource manager. This is needed to prevent huge lag when there are tons of
-        // requests, so this is some kind of work load balancer.
-        const THROUGHPUT: usize = 5;
-
-        let mut uploaded = 0;
+        // Loaded textures are not uploaded right away, instead they're put in the upload queue that
+        // limits the amount of data transferred to GPU per frame. This is needed to prevent huge
+        // lag when there are tons of requests (or just huge textures).
         while let Ok(event) = self.texture_event_receiver.try_recv() {
             if let ResourceEvent::Loaded(resource) | ResourceEvent::Reloaded(resource) = event {
                 if let Some(texture) = resource.try_cast::<Texture>() {
-                    match self.texture_cache.upload(&*self.server, &texture) {
-                        Ok(_) => {
-                            uploaded += 1;
-                            if uploaded >= THROUGHPUT {
-                                break;
-                            }
-                        }
-                        Err(e) => {
-                            Log::writeln(
-                                MessageKind::Error,
-                                format!("Failed to upload texture to GPU. Reason: {e:?}"),
-                            );
-                        }
-                    }
+                    self.texture_cache.enqueue(texture);
                 }
             }
         }
 
+        self.texture_cache.process_uploads(&*self.server);
         self.texture_cache.update(dt);
     }
 
@@ -1240,6 +1996,8 @@ impl Renderer {
         while let Ok(event) = self.shader_event_receiver.try_recv() {
             if let ResourceEvent::Loaded(resource) | ResourceEvent::Reloaded(resource) = event {
                 if let Some(shader) = resource.try_cast::<Shader>() {
+                    // The shader could be fixed, so its errors must be reported once again.
+                    self.error_broadcaster.reset();
                     // Remove and immediately "touch" the shader cache to force upload shader.
                     self.shader_cache.remove(&shader);
                     let _ = self.shader_cache.get(&*self.server, &shader);
@@ -1269,6 +2027,8 @@ impl Renderer {
         elapsed_time: f32,
         dt: f32,
     ) -> Result<&AssociatedSceneData, FrameworkError> {
+        scope_profile!("Render Scene");
+
         let graph = &scene.graph;
 
         let backbuffer_width = self.frame_size.0 as f32;
@@ -1295,7 +2055,9 @@ impl Renderer {
             // Clamp to [1.0; infinity] range.
             .sup(&Vector2::new(1.0, 1.0));
 
+        let frame_buffer = self.frame_buffer();
         let server = &*self.server;
+        let formats = self.degradation.formats;
 
         let scene_associated_data = self
             .scene_data_map
@@ -1313,7 +2075,7 @@ impl Renderer {
                         data.gbuffer.width,data.gbuffer.height,width,height
                     ));
 
-                    *data = AssociatedSceneData::new(server, width, height).unwrap();
+                    *data = AssociatedSceneData::new(server, width, height, &formats).unwrap();
                 }
             })
             .or_insert_with(|| {
@@ -1324,7 +2086,7 @@ impl Renderer {
                     "A new associated scene rendering data was created for scene {scene_handle}!"
                 ));
 
-                AssociatedSceneData::new(server, width, height).unwrap()
+                AssociatedSceneData::new(server, width, height, &formats).unwrap()
             });
 
         let pipeline_stats = server.pipeline_statistics();
@@ -1338,6 +2100,15 @@ impl Renderer {
                 .try_register(&rt, scene_associated_data.ldr_scene_frame_texture().clone());
         }
 
+        let render_graph_schedule = self.render_graph.compile();
+        scene_associated_data.render_graph_targets.prepare(
+            server,
+            &render_graph_schedule,
+            frame_size.x as usize,
+            frame_size.y as usize,
+        )?;
+        let render_graph_targets = scene_associated_data.render_graph_built_in_targets();
+
         for (camera_handle, camera) in graph.pair_iter().filter_map(|(handle, node)| {
             if node.is_globally_enabled() {
                 if let Some(camera) = node.cast::<Camera>() {
@@ -1352,6 +2123,32 @@ impl Renderer {
 
             let viewport = camera.viewport_pixels(frame_size);
 
+            scene_associated_data
+                .sync_ssgi_renderer(server, &self.quality_settings.ssgi_settings)?;
+            scene_associated_data.sync_taa_renderer(server, &self.quality_settings.taa_settings)?;
+
+            let temporal_frame = scene_associated_data.temporal_reprojection.advance(
+                camera_handle,
+                viewport,
+                camera.view_matrix(),
+                camera.view_projection_matrix(),
+            );
+
+            // TAA requires the projection to be shifted by a sub-pixel offset every frame.
+            let taa_settings = &self.quality_settings.taa_settings;
+            let jitter = if taa_settings.enabled {
+                taa::jitter_offset(
+                    temporal_frame.frame_index,
+                    viewport,
+                    taa_settings.jitter_scale,
+                )
+            } else {
+                Vector2::default()
+            };
+            let projection_matrix =
+                Matrix4::new_translation(&Vector3::new(jitter.x, jitter.y, 0.0))
+                    * camera.projection_matrix();
+
             let bundle_storage = RenderDataBundleStorage::from_graph(
                 graph,
                 elapsed_time,
@@ -1360,7 +2157,7 @@ impl Renderer {
                     z_near: camera.projection().z_near(),
                     z_far: camera.projection().z_far(),
                     view_matrix: camera.view_matrix(),
-                    projection_matrix: camera.projection_matrix(),
+                    projection_matrix,
                 },
                 GBUFFER_PASS_NAME.clone(),
                 RenderDataBundleStorageOptions {
@@ -1373,6 +2170,7 @@ impl Renderer {
                 scene.rendering_options.polygon_rasterization_mode,
             );
 
+            self.gpu_profiler.begin(server, "GBuffer");
             scene_associated_data.statistics +=
                 scene_associated_data.gbuffer.fill(GBufferRenderContext {
                     server,
@@ -1388,6 +2186,9 @@ impl Renderer {
                     uniform_memory_allocator: &mut self.uniform_memory_allocator,
                     screen_space_debug_renderer: &mut self.screen_space_debug_renderer,
                     unit_quad: &self.quad,
+                    temporal_frame,
+                    jitter,
+                    instance_history: &mut scene_associated_data.instance_history,
                 })?;
 
             server.set_polygon_fill_mode(PolygonFace::FrontAndBack, PolygonFillMode::Fill);
@@ -1406,6 +2207,7 @@ impl Renderer {
                 Some(0),
             );
 
+            self.gpu_profiler.begin(server, "Lighting");
             let (pass_stats, light_stats) =
                 self.deferred_light_renderer
                     .render(DeferredRendererContext {
@@ -1425,6 +2227,8 @@ impl Renderer {
                         uniform_buffer_cache: &mut self.uniform_buffer_cache,
                         visibility_cache,
                         uniform_memory_allocator: &mut self.uniform_memory_allocator,
+                        ssgi_renderer: scene_associated_data.ssgi_renderer.as_mut(),
+                        temporal_frame,
                     })?;
 
             scene_associated_data.statistics += light_stats;
@@ -1432,6 +2236,7 @@ impl Renderer {
 
             let depth = scene_associated_data.gbuffer.depth();
 
+            self.gpu_profiler.begin(server, "Forward");
             scene_associated_data.statistics +=
                 self.forward_renderer.render(ForwardRenderContext {
                     state: server,
@@ -1448,6 +2253,7 @@ impl Renderer {
                     uniform_memory_allocator: &mut self.uniform_memory_allocator,
                 })?;
 
+            self.gpu_profiler.begin(server, "Custom HDR Passes");
             for render_pass in self.scene_render_passes.iter() {
                 scene_associated_data.statistics +=
                     render_pass
@@ -1475,8 +2281,32 @@ impl Renderer {
                         })?;
             }
 
+            scene_associated_data.statistics +=
+                scene_associated_data.render_graph_targets.execute(
+                    &render_graph_schedule,
+                    RenderGraphStage::Hdr,
+                    RenderGraphExecutionContext {
+                        elapsed_time,
+                        server,
+                        texture_cache: &mut self.texture_cache,
+                        geometry_cache: &mut self.geometry_cache,
+                        shader_cache: &mut self.shader_cache,
+                        bundle_storage: &bundle_storage,
+                        quality_settings: &self.quality_settings,
+                        scene,
+                        camera,
+                        viewport,
+                        scene_handle,
+                        fallback_resources: &self.fallback_resources,
+                        uniform_buffer_cache: &mut self.uniform_buffer_cache,
+                        uniform_memory_allocator: &mut self.uniform_memory_allocator,
+                        built_in_targets: &render_graph_targets,
+                    },
+                )?;
+
             let quad = &self.quad;
 
+            self.gpu_profiler.begin(server, "Post Processing");
             // Prepare glow map.
             scene_associated_data.statistics += scene_associated_data.bloom_renderer.render(
                 quad,
@@ -1500,6 +2330,21 @@ impl Renderer {
                 &mut self.uniform_buffer_cache,
             )?;
 
+            // Apply TAA if needed.
+            if let Some(taa_renderer) = scene_associated_data.taa_renderer.as_mut() {
+                scene_associated_data.statistics += taa_renderer.render(TaaRenderContext {
+                    gbuffer: &scene_associated_data.gbuffer,
+                    frame_buffer: &scene_associated_data.ldr_scene_framebuffer,
+                    viewport,
+                    quad,
+                    view_projection_matrix: projection_matrix * camera.view_matrix(),
+                    jitter,
+                    temporal_frame,
+                    settings: &self.quality_settings.taa_settings,
+                    uniform_buffer_cache: &mut self.uniform_buffer_cache,
+                })?;
+            }
+
             // Apply FXAA if needed.
             if self.quality_settings.fxaa {
                 scene_associated_data.statistics += self.fxaa_renderer.render(
@@ -1530,6 +2375,7 @@ impl Renderer {
                 camera.view_projection_matrix(),
             )?;
 
+            self.gpu_profiler.begin(server, "Custom LDR Passes");
             for render_pass in self.scene_render_passes.iter() {
                 scene_associated_data.statistics +=
                     render_pass
@@ -1556,15 +2402,96 @@ impl Renderer {
                             uniform_memory_allocator: &mut self.uniform_memory_allocator,
                         })?;
             }
+
+            scene_associated_data.statistics +=
+                scene_associated_data.render_graph_targets.execute(
+                    &render_graph_schedule,
+                    RenderGraphStage::Ldr,
+                    RenderGraphExecutionContext {
+                        elapsed_time,
+                        server,
+                        texture_cache: &mut self.texture_cache,
+                        geometry_cache: &mut self.geometry_cache,
+                        shader_cache: &mut self.shader_cache,
+                        bundle_storage: &bundle_storage,
+                        quality_settings: &self.quality_settings,
+                        scene,
+                        camera,
+                        viewport,
+                        scene_handle,
+                        fallback_resources: &self.fallback_resources,
+                        uniform_buffer_cache: &mut self.uniform_buffer_cache,
+                        uniform_memory_allocator: &mut self.uniform_memory_allocator,
+                        built_in_targets: &render_graph_targets,
+                    },
+                )?;
+
+            // Apply color blindness filter if needed.
+            if self.color_blindness_settings.is_enabled() {
+                scene_associated_data.statistics += self.color_blindness_renderer.render(
+                    viewport,
+                    scene_associated_data.ldr_scene_frame_texture(),
+                    &scene_associated_data.ldr_temp_framebuffer,
+                    &self.color_blindness_settings,
+                    &mut self.uniform_buffer_cache,
+                )?;
+
+                let quad = &self.quad;
+                let temp_frame_texture = scene_associated_data.ldr_temp_frame_texture();
+                scene_associated_data.statistics += blit_pixels(
+                    &mut self.uniform_buffer_cache,
+                    &scene_associated_data.ldr_scene_framebuffer,
+                    temp_frame_texture,
+                    &self.blit_shader,
+                    viewport,
+                    quad,
+                )?;
+            }
+
+            if let Some(debug_target) = self
+                .debug_target
+                .as_deref()
+                .filter(|name| *name != render_graph::SCENE_LDR_TARGET)
+            {
+                let texture =
+                    collect_frame_targets(scene_associated_data, &self.deferred_light_renderer)
+                        .into_iter()
+                        .find(|target| target.name == debug_target && target.info().is_some())
+                        .map(|target| target.texture);
+                if let Some(texture) = texture {
+                    scene_associated_data.statistics += blit_pixels(
+                        &mut self.uniform_buffer_cache,
+                        &scene_associated_data.ldr_scene_framebuffer,
+                        &texture,
+                        &self.blit_shader,
+                        viewport,
+                        &self.quad,
+                    )?;
+                }
+            }
+
+            self.gpu_profiler.end();
         }
 
         self.visibility_cache.update(graph);
 
         // Optionally render everything into back buffer.
-        if scene.rendering_options.render_target.is_none() {
+        if let Some(render_target) = scene.rendering_options.render_target.as_ref() {
+            // Render targets could be sampled with mip-mapping (for example, when a scene is
+            // rendered to a texture of a distant screen), so update the mips.
+            let generate_mips = render_target.state().data().is_some_and(|texture| {
+                texture.generate_mips_on_gpu()
+                    && texture.minification_filter().is_using_mip_mapping()
+            });
+            if generate_mips {
+                scene_associated_data
+                    .ldr_scene_frame_texture()
+                    .generate_mipmaps()?;
+            }
+        } else {
             scene_associated_data.statistics += blit_pixels(
                 &mut self.uniform_buffer_cache,
-                &self.backbuffer,
+                &frame_buffer,
                 scene_associated_data.ldr_scene_frame_texture(),
                 &self.blit_shader,
                 window_viewport,
@@ -1588,6 +2515,10 @@ impl Renderer {
             return Ok(());
         }
 
+        scope_profile!("Render Frame");
+
+        self.gpu_profiler.collect();
+
         self.uniform_buffer_cache.mark_all_unused();
         self.uniform_memory_allocator.clear();
 
@@ -1604,7 +2535,8 @@ impl Renderer {
         self.statistics.begin_frame();
 
         let window_viewport = Rect::new(0, 0, self.frame_size.0 as i32, self.frame_size.1 as i32);
-        self.backbuffer.clear(
+        let frame_buffer = self.frame_buffer();
+        frame_buffer.clear(
             window_viewport,
             Some(self.backbuffer_clear_color),
             Some(1.0),
@@ -1622,11 +2554,12 @@ impl Renderer {
             .set_polygon_fill_mode(PolygonFace::FrontAndBack, PolygonFillMode::Fill);
 
         // Render UI on top of everything without gamma correction.
+        self.gpu_profiler.begin(&*self.server, "UI");
         for drawing_context in drawing_contexts {
             self.statistics += self.ui_renderer.render(UiRenderContext {
                 server: &*self.server,
                 viewport: window_viewport,
-                frame_buffer: &self.backbuffer,
+                frame_buffer: &frame_buffer,
                 frame_width: backbuffer_width,
                 frame_height: backbuffer_height,
                 drawing_context,
@@ -1641,15 +2574,52 @@ impl Renderer {
         self.screen_space_debug_renderer.render(
             &mut self.uniform_buffer_cache,
             window_viewport,
-            &self.backbuffer,
+            &frame_buffer,
             screen_matrix,
         )?;
 
+        // Resolve the multisampled frame.
+        if let Some(msaa_frame_buffer) = self.msaa_frame_buffer.as_ref() {
+            let (width, height) = (self.frame_size.0 as i32, self.frame_size.1 as i32);
+            msaa_frame_buffer.blit_to(
+                &self.backbuffer,
+                0,
+                0,
+                width,
+                height,
+                0,
+                0,
+                width,
+                height,
+                true,
+                false,
+                false,
+            );
+        }
+        self.gpu_profiler.end();
+
         self.statistics.geometry_cache_size = self.geometry_cache.alive_count();
         self.statistics.texture_cache_size = self.texture_cache.alive_count();
+        self.statistics.texture_memory_usage = self.texture_cache.memory_usage();
+        self.statistics.pending_texture_uploads = self.texture_cache.pending_uploads_count();
         self.statistics.shader_cache_size = self.shader_cache.alive_count();
         self.statistics.uniform_buffer_cache_size = self.uniform_buffer_cache.alive_count();
 
+        if profiler::is_enabled() {
+            let statistics = &self.statistics;
+            profiler::set_counter("Draw Calls", statistics.geometry.draw_calls as f64);
+            profiler::set_counter("Triangles", statistics.geometry.triangles_rendered as f64);
+            profiler::set_counter("Textures", statistics.texture_cache_size as f64);
+            profiler::set_counter(
+                "Texture Memory (Mb)",
+                statistics.texture_memory_usage as f64 / (1024.0 * 1024.0),
+            );
+            profiler::set_counter(
+                "Pending Texture Uploads",
+                statistics.pending_texture_uploads as f64,
+            );
+        }
+
         Ok(())
     }
 
@@ -1659,13 +2629,73 @@ impl Renderer {
         elapsed_time: f32,
         drawing_contexts: impl Iterator<Item = &'a DrawingContext>,
         window: &Window,
+        latency_tracker: &mut LatencyTracker,
     ) -> Result<(), FrameworkError> {
+        latency_tracker.mark(LatencyMarker::RenderSubmitStart);
         self.render_frame(scenes, elapsed_time, drawing_contexts)?;
+        latency_tracker.mark(LatencyMarker::RenderSubmitEnd);
         self.statistics.end_frame();
         window.pre_present_notify();
+        latency_tracker.mark(LatencyMarker::PresentStart);
         self.graphics_server().swap_buffers()?;
+        latency_tracker.mark(LatencyMarker::PresentEnd);
         self.statistics.finalize();
         self.statistics.pipeline = self.server.pipeline_statistics();
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::renderer::{msaa_transition, QualitySettings, ResourceTransition};
+
+    fn settings(msaa_sample_count: Option<u8>) -> QualitySettings {
+        QualitySettings {
+            msaa_sample_count,
+            ..QualitySettings::high()
+        }
+    }
+
+    #[test]
+    fn test_msaa_transitions() {
+        // Enable.
+        assert_eq!(
+            msaa_transition(&settings(None), &settings(Some(4))),
+            ResourceTransition::Recreate
+        );
+        // Change sample count.
+        assert_eq!(
+            msaa_transition(&settings(Some(4)), &settings(Some(8))),
+            ResourceTransition::Recreate
+        );
+        // Same sample count.
+        assert_eq!(
+            msaa_transition(&settings(Some(4)), &settings(Some(4))),
+            ResourceTransition::Keep
+        );
+        assert_eq!(
+            msaa_transition(&settings(None), &settings(None)),
+            ResourceTransition::Keep
+        );
+        // Disable.
+        assert_eq!(
+            msaa_transition(&settings(Some(4)), &settings(None)),
+            ResourceTransition::Destroy
+        );
+    }
+
+    #[test]
+    fn test_single_sample_msaa_is_disabled() {
+        assert_eq!(settings(Some(1)).effective_msaa_sample_count(), None);
+        assert_eq!(settings(Some(0)).effective_msaa_sample_count(), None);
+        assert_eq!(settings(Some(2)).effective_msaa_sample_count(), Some(2));
+        assert_eq!(
+            msaa_transition(&settings(Some(4)), &settings(Some(1))),
+            ResourceTransition::Destroy
+        );
+        assert_eq!(
+            msaa_transition(&settings(None), &settings(Some(1))),
+            ResourceTransition::Keep
+        );
+    }
+}
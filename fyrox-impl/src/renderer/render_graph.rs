@@ -0,0 +1,1067 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Render graph allows you to add custom render passes to the renderer without patching it. Every
+//! pass declares render targets it reads from and writes to, the renderer then schedules the passes
+//! according to their dependencies, allocates intermediate render targets and creates frame buffers
+//! for them. See [`RenderGraphPass`] docs for more info.
+
+use crate::{
+    core::{log::Log, math::Rect, pool::Handle, sstorage::ImmutableString},
+    renderer::{
+        bundle::{BundleRenderContext, RenderDataBundleStorage},
+        cache::{
+            shader::ShaderCache,
+            texture::TextureCache,
+            uniform::{UniformBufferCache, UniformMemoryAllocator},
+        },
+        framework::{
+            error::FrameworkError,
+            framebuffer::{Attachment, GpuFrameBuffer},
+            gpu_texture::{GpuTexture, GpuTextureKind, PixelKind},
+            server::GraphicsServer,
+        },
+        FallbackResources, GeometryCache, QualitySettings, RenderPassStatistics,
+    },
+    scene::{camera::Camera, node::Node, Scene},
+};
+use fxhash::FxHashMap;
+use std::{any::TypeId, cell::RefCell, rc::Rc};
+
+/// Name of the built-in render target with the high dynamic range frame of a scene (RGBA16F by
+/// default, see [`crate::renderer::degradation::RenderTargetFormats::hdr_color`]). It could be
+/// written by the passes of [`RenderGraphStage::Hdr`] stage.
+pub const SCENE_HDR_TARGET: &str = "SceneHdr";
+/// Name of the built-in render target with the final (tone mapped and gamma corrected) frame of a
+/// scene (RGBA8). It could be written by the passes of [`RenderGraphStage::Ldr`] stage.
+pub const SCENE_LDR_TARGET: &str = "SceneLdr";
+/// Name of the built-in depth-stencil render target (D24S8), that is shared by both HDR and LDR
+/// frames of a scene.
+pub const SCENE_DEPTH_STENCIL_TARGET: &str = "SceneDepthStencil";
+/// Name of the built-in render target with depth values from G-Buffer. Read-only.
+pub const GBUFFER_DEPTH_TARGET: &str = "GBufferDepth";
+/// Name of the built-in render target with diffuse colors from G-Buffer. Read-only.
+pub const GBUFFER_DIFFUSE_TARGET: &str = "GBufferDiffuse";
+/// Name of the built-in render target with world-space normals from G-Buffer. Read-only.
+pub const GBUFFER_NORMAL_TARGET: &str = "GBufferNormal";
+/// Name of the built-in render target with ambient lighting from G-Buffer. Read-only.
+pub const GBUFFER_AMBIENT_TARGET: &str = "GBufferAmbient";
+/// Name of the built-in render target with material properties from G-Buffer. Read-only.
+pub const GBUFFER_MATERIAL_TARGET: &str = "GBufferMaterial";
+/// Name of the built-in render target with per-pixel motion vectors from G-Buffer. Read-only.
+pub const GBUFFER_MOTION_TARGET: &str = "GBufferMotion";
+
+const READ_ONLY_TARGETS: [&str; 6] = [
+    GBUFFER_DEPTH_TARGET,
+    GBUFFER_DIFFUSE_TARGET,
+    GBUFFER_NORMAL_TARGET,
+    GBUFFER_AMBIENT_TARGET,
+    GBUFFER_MATERIAL_TARGET,
+    GBUFFER_MOTION_TARGET,
+];
+
+fn is_built_in_target(name: &str) -> bool {
+    READ_ONLY_TARGETS.contains(&name)
+        || [
+            SCENE_HDR_TARGET,
+            SCENE_LDR_TARGET,
+            SCENE_DEPTH_STENCIL_TARGET,
+        ]
+        .contains(&name)
+}
+
+/// A stage of the frame at which a render graph pass is executed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RenderGraphStage {
+    /// The pass is executed after the lighting and the forward passes, but before bloom and tone
+    /// mapping. Use this stage for additional geometry passes and HDR post effects.
+    Hdr = 0,
+    /// The pass is executed after tone mapping and anti-aliasing, right before the final frame is
+    /// shown on screen. Use this stage for LDR post effects and overlays.
+    Ldr = 1,
+}
+
+/// Size of a render target, that is created by a render graph pass.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RenderTargetSize {
+    /// The render target has the same size as the frame of the scene.
+    Frame,
+    /// The render target has the size of the frame of the scene multiplied by the given scale. It
+    /// is useful for effects that could be rendered in lower resolution (blur, for example).
+    ScaledFrame(f32),
+    /// The render target has a fixed size.
+    Fixed {
+        /// Width of the render target.
+        width: usize,
+        /// Height of the render target.
+        height: usize,
+    },
+}
+
+impl RenderTargetSize {
+    fn resolve(self, frame_width: usize, frame_height: usize) -> (usize, usize) {
+        match self {
+            RenderTargetSize::Frame => (frame_width, frame_height),
+            RenderTargetSize::ScaledFrame(scale) => (
+                ((frame_width as f32 * scale) as usize).max(1),
+                ((frame_height as f32 * scale) as usize).max(1),
+            ),
+            RenderTargetSize::Fixed { width, height } => (width.max(1), height.max(1)),
+        }
+    }
+}
+
+/// Describes a render target, that is created by a render graph pass.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderTargetDescriptor {
+    /// Size of the render target.
+    pub size: RenderTargetSize,
+    /// Pixel kind of the render target. Depth pixel kinds could be used only for depth targets
+    /// (see [`RenderPassBuilder::write_depth`]).
+    pub pixel_kind: PixelKind,
+}
+
+/// A declaration of render targets used by a render graph pass. See [`RenderGraphPass::setup`].
+#[derive(Default, Clone, Debug)]
+pub struct RenderPassBuilder {
+    created: Vec<(ImmutableString, RenderTargetDescriptor)>,
+    reads: Vec<ImmutableString>,
+    writes: Vec<ImmutableString>,
+    depth: Option<ImmutableString>,
+}
+
+impl RenderPassBuilder {
+    /// Declares a new render target with the given name. The target is allocated and managed by the
+    /// renderer, it could be used by any other pass. Keep in mind, that this method only declares
+    /// the target, use [`Self::write`] or [`Self::write_depth`] to write to it.
+    pub fn create(&mut self, name: &str, descriptor: RenderTargetDescriptor) -> &mut Self {
+        self.created.push((ImmutableString::new(name), descriptor));
+        self
+    }
+
+    /// Declares that the pass reads the render target with the given name. The texture of the
+    /// target could be fetched using [`RenderGraphPassContext::texture`].
+    pub fn read(&mut self, name: &str) -> &mut Self {
+        self.reads.push(ImmutableString::new(name));
+        self
+    }
+
+    /// Declares that the pass writes to the render target with the given name. The target will be
+    /// attached to the frame buffer of the pass as a color attachment, in the order of declaration.
+    /// If the pass reads the same target, it receives a copy of the target made before the pass.
+    pub fn write(&mut self, name: &str) -> &mut Self {
+        self.writes.push(ImmutableString::new(name));
+        self
+    }
+
+    /// Declares that the pass writes to the depth render target with the given name. The target
+    /// will be attached to the frame buffer of the pass as a depth (or depth-stencil) attachment.
+    pub fn write_depth(&mut self, name: &str) -> &mut Self {
+        self.depth = Some(ImmutableString::new(name));
+        self
+    }
+
+    fn creates(&self, name: &ImmutableString) -> bool {
+        self.created.iter().any(|(created, _)| created == name)
+    }
+
+    fn reads(&self, name: &ImmutableString) -> bool {
+        self.reads.contains(name)
+    }
+
+    fn writes(&self, name: &ImmutableString) -> bool {
+        self.writes.contains(name) || self.depth.as_ref() == Some(name)
+    }
+
+    fn written_targets(&self) -> impl Iterator<Item = &ImmutableString> {
+        self.writes.iter().chain(self.depth.iter())
+    }
+
+    fn used_targets(&self) -> impl Iterator<Item = &ImmutableString> {
+        self.reads.iter().chain(self.written_targets())
+    }
+
+    fn depends_on(&self, other: &Self, self_index: usize, other_index: usize) -> bool {
+        self.used_targets().any(|target| {
+            if other.creates(target) {
+                // The creator of a target is executed before every other user of the target.
+                !self.creates(target)
+            } else if self.creates(target) || !other.writes(target) {
+                false
+            } else if self.writes(target) {
+                // Passes that write the same target are executed in the order of registration.
+                other_index < self_index
+            } else {
+                // Passes that only read a target are executed after every writer of the target.
+                self.reads(target)
+            }
+        })
+    }
+}
+
+/// A context for render graph passes.
+pub struct RenderGraphPassContext<'a, 'b> {
+    /// Amount of time (in seconds) that passed from creation of the engine.
+    pub elapsed_time: f32,
+    /// A graphics server that is used as a wrapper to underlying graphics API.
+    pub server: &'a dyn GraphicsServer,
+    /// A texture cache that uploads engine's `Texture` as internal `GpuTexture` to GPU.
+    pub texture_cache: &'a mut TextureCache,
+    /// A geometry cache that uploads engine's `SurfaceData` as internal `GeometryBuffer` to GPU.
+    pub geometry_cache: &'a mut GeometryCache,
+    /// A cache that stores all native shaders associated with a shader resource.
+    pub shader_cache: &'a mut ShaderCache,
+    /// A storage that contains "pre-compiled" groups of render data (batches).
+    pub bundle_storage: &'a RenderDataBundleStorage,
+    /// Current quality settings of the renderer.
+    pub quality_settings: &'a QualitySettings,
+    /// A frame buffer with every render target written by the pass attached to it. Color targets
+    /// are attached in the order of declaration.
+    pub framebuffer: &'a GpuFrameBuffer,
+    /// A scene being rendered.
+    pub scene: &'b Scene,
+    /// A camera from the scene that is used as "eyes".
+    pub camera: &'b Camera,
+    /// A viewport of the camera.
+    pub viewport: Rect<i32>,
+    /// A handle of the scene being rendered.
+    pub scene_handle: Handle<Scene>,
+    /// A set of textures of certain kinds that could be used as a stub in cases when you don't have
+    /// your own texture of this kind.
+    pub fallback_resources: &'a FallbackResources,
+    /// A cache of uniform buffers.
+    pub uniform_buffer_cache: &'a mut UniformBufferCache,
+    /// Memory allocator for uniform buffers that tries to pack uniforms densely into large uniform
+    /// buffers, giving you offsets to the data.
+    pub uniform_memory_allocator: &'a mut UniformMemoryAllocator,
+    scene_depth: &'a GpuTexture,
+    textures: &'a [(ImmutableString, GpuTexture)],
+}
+
+impl RenderGraphPassContext<'_, '_> {
+    /// Returns a texture of the render target with the given name. Only the targets that were
+    /// declared as read by the pass (see [`RenderPassBuilder::read`]) are available.
+    pub fn texture(&self, name: &str) -> Option<&GpuTexture> {
+        self.textures
+            .iter()
+            .find_map(|(target, texture)| (target.as_str() == name).then_some(texture))
+    }
+
+    /// Renders every surface instance, which scene node has the given render tag (see
+    /// [`crate::scene::base::Base::render_tags`]), using the render pass with the given name from
+    /// shaders of the materials of the surfaces.
+    pub fn render_tagged(
+        &mut self,
+        tag: &str,
+        render_pass_name: &ImmutableString,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let scene = self.scene;
+        let graph = &scene.graph;
+        let is_tagged = |handle: Handle<Node>| {
+            graph
+                .try_get(handle)
+                .is_some_and(|node| node.has_render_tag(tag))
+        };
+
+        self.bundle_storage.render_to_frame_buffer(
+            self.server,
+            self.geometry_cache,
+            self.shader_cache,
+            |bundle| {
+                bundle
+                    .instances
+                    .iter()
+                    .any(|instance| is_tagged(instance.node_handle))
+            },
+            |instance| is_tagged(instance.node_handle),
+            BundleRenderContext {
+                texture_cache: self.texture_cache,
+                render_pass_name,
+                frame_buffer: self.framebuffer,
+                viewport: self.viewport,
+                uniform_memory_allocator: self.uniform_memory_allocator,
+                use_pom: self.quality_settings.use_parallax_mapping,
+                use_instancing: self.quality_settings.use_instancing,
+                light_position: &Default::default(),
+                ambient_light: scene.rendering_options.ambient_lighting_color,
+                scene_depth: Some(self.scene_depth),
+                fallback_resources: self.fallback_resources,
+                motion: None,
+            },
+        )
+    }
+}
+
+/// A render pass of a render graph. Unlike [`super::SceneRenderPass`], render graph passes declare
+/// the render targets they use, and the renderer takes care of the execution order, allocation of
+/// the targets and frame buffers.
+///
+/// ## Scheduling
+///
+/// Passes of the same [`RenderGraphStage`] are sorted according to their dependencies:
+///
+/// - A pass that creates a render target is executed before every other pass that uses the target.
+/// - Passes that write to the same render target are executed in the order of registration.
+/// - Passes that only read a render target are executed after every pass that writes to it.
+///
+/// Circular dependencies are reported as errors, in this case no render graph passes will be
+/// executed.
+///
+/// ## Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::sstorage::ImmutableString,
+/// #     renderer::{
+/// #         framework::{error::FrameworkError, gpu_texture::PixelKind},
+/// #         render_graph::{
+/// #             RenderGraphPass, RenderGraphPassContext, RenderGraphStage, RenderPassBuilder,
+/// #             RenderTargetDescriptor, RenderTargetSize, SCENE_DEPTH_STENCIL_TARGET,
+/// #         },
+/// #         RenderPassStatistics,
+/// #     },
+/// # };
+/// # use std::any::TypeId;
+/// struct OutlinePass {
+///     render_pass_name: ImmutableString,
+/// }
+///
+/// impl RenderGraphPass for OutlinePass {
+///     fn name(&self) -> &str {
+///         "Outline"
+///     }
+///
+///     fn stage(&self) -> RenderGraphStage {
+///         RenderGraphStage::Hdr
+///     }
+///
+///     fn setup(&self, builder: &mut RenderPassBuilder) {
+///         builder
+///             .create(
+///                 "OutlineMask",
+///                 RenderTargetDescriptor {
+///                     size: RenderTargetSize::Frame,
+///                     pixel_kind: PixelKind::R8,
+///                 },
+///             )
+///             .write("OutlineMask")
+///             .read(SCENE_DEPTH_STENCIL_TARGET);
+///     }
+///
+///     fn execute(
+///         &mut self,
+///         mut ctx: RenderGraphPassContext,
+///     ) -> Result<RenderPassStatistics, FrameworkError> {
+///         ctx.framebuffer.clear(ctx.viewport, Some(Default::default()), None, None);
+///         // Render every scene node tagged with "Outlined" tag.
+///         ctx.render_tagged("Outlined", &self.render_pass_name)
+///     }
+///
+///     fn source_type_id(&self) -> TypeId {
+///         TypeId::of::<()>()
+///     }
+/// }
+/// ```
+pub trait RenderGraphPass {
+    /// Returns a name of the pass. It is used for error reporting and must be unique.
+    fn name(&self) -> &str;
+
+    /// Returns a stage of the frame at which the pass should be executed.
+    fn stage(&self) -> RenderGraphStage;
+
+    /// Declares render targets used by the pass. It is called once per frame, so the declaration
+    /// could change over time.
+    fn setup(&self, builder: &mut RenderPassBuilder);
+
+    /// Executes the pass. It will be called for **each** camera of **each** scene registered in the
+    /// engine, but you are able to filter out scene by its handle.
+    fn execute(
+        &mut self,
+        ctx: RenderGraphPassContext,
+    ) -> Result<RenderPassStatistics, FrameworkError>;
+
+    /// Should return type id of a plugin, that holds this render pass. **WARNING:** Setting incorrect
+    /// (anything else, than a real plugin's type id) value here will result in hard crash with happy
+    /// debugging times.
+    fn source_type_id(&self) -> TypeId;
+}
+
+struct ScheduledPass {
+    pass: Rc<RefCell<dyn RenderGraphPass>>,
+    name: String,
+    declaration: RenderPassBuilder,
+}
+
+/// Execution order of render graph passes along with the render targets they create.
+#[derive(Default)]
+pub(crate) struct RenderGraphSchedule {
+    stages: [Vec<ScheduledPass>; 2],
+    targets: FxHashMap<ImmutableString, RenderTargetDescriptor>,
+}
+
+/// A set of user-defined render passes. See [`RenderGraphPass`] docs for more info.
+#[derive(Default)]
+pub struct RenderGraph {
+    passes: Vec<Rc<RefCell<dyn RenderGraphPass>>>,
+    last_error: RefCell<Option<String>>,
+}
+
+impl RenderGraph {
+    /// Adds a new pass to the graph.
+    pub fn add_pass(&mut self, pass: Rc<RefCell<dyn RenderGraphPass>>) {
+        self.passes.push(pass);
+    }
+
+    /// Removes the specified pass from the graph.
+    pub fn remove_pass(&mut self, pass: &Rc<RefCell<dyn RenderGraphPass>>) {
+        self.passes.retain(|p| !Rc::ptr_eq(p, pass));
+    }
+
+    /// Returns a slice with every registered pass.
+    pub fn passes(&self) -> &[Rc<RefCell<dyn RenderGraphPass>>] {
+        &self.passes
+    }
+
+    /// Removes all passes from the graph.
+    pub fn clear(&mut self) {
+        self.passes.clear();
+    }
+
+    /// Sorts the passes according to their dependencies. An error is written to the log only once
+    /// (until it changes), an empty schedule is returned in this case.
+    pub(crate) fn compile(&self) -> RenderGraphSchedule {
+        match self.try_compile() {
+            Ok(schedule) => {
+                self.last_error.borrow_mut().take();
+                schedule
+            }
+            Err(err) => {
+                let mut last_error = self.last_error.borrow_mut();
+                if last_error.as_ref() != Some(&err) {
+                    Log::err(format!("Unable to compile render graph: {err}"));
+                    *last_error = Some(err);
+                }
+                Default::default()
+            }
+        }
+    }
+
+    fn try_compile(&self) -> Result<RenderGraphSchedule, String> {
+        let mut targets =
+            FxHashMap::<ImmutableString, (RenderTargetDescriptor, RenderGraphStage)>::default();
+        let mut declared = Vec::with_capacity(self.passes.len());
+        for pass in self.passes.iter() {
+            let pass_ref = pass.borrow();
+            let mut declaration = RenderPassBuilder::default();
+            pass_ref.setup(&mut declaration);
+            let name = pass_ref.name().to_string();
+            let stage = pass_ref.stage();
+            drop(pass_ref);
+
+            for (target, descriptor) in declaration.created.iter() {
+                if is_built_in_target(target)
+                    || targets
+                        .insert(target.clone(), (descriptor.clone(), stage))
+                        .is_some()
+                {
+                    return Err(format!(
+                        "render target {target} created by {name} pass is already defined!"
+                    ));
+                }
+            }
+
+            declared.push((pass.clone(), name, stage, declaration));
+        }
+
+        for (_, name, stage, declaration) in declared.iter() {
+            if declaration.written_targets().next().is_none() {
+                return Err(format!("{name} pass does not write to any render target!"));
+            }
+
+            for target in declaration.used_targets() {
+                if is_built_in_target(target) {
+                    continue;
+                }
+                match targets.get(target) {
+                    None => {
+                        return Err(format!("{name} pass uses unknown render target {target}!"))
+                    }
+                    Some((_, created_at)) if created_at > stage => {
+                        return Err(format!(
+                            "{name} pass uses render target {target} before it is created!"
+                        ))
+                    }
+                    _ => (),
+                }
+            }
+
+            for target in declaration.written_targets() {
+                if READ_ONLY_TARGETS.contains(&target.as_str()) {
+                    return Err(format!(
+                        "{name} pass writes to read-only render target {target}!"
+                    ));
+                }
+            }
+
+            for target in declaration.writes.iter() {
+                let pixel_kind = targets.get(target).map(|(desc, _)| desc.pixel_kind);
+                if target.as_str() == SCENE_DEPTH_STENCIL_TARGET
+                    || pixel_kind.is_some_and(|kind| kind.is_depth())
+                {
+                    return Err(format!(
+                        "{name} pass writes to depth render target {target} as color target!"
+                    ));
+                }
+            }
+
+            if let Some(depth) = declaration.depth.as_ref() {
+                if declaration.reads(depth) {
+                    return Err(format!(
+                        "{name} pass cannot read and write depth render target {depth} at once!"
+                    ));
+                }
+            }
+        }
+
+        let mut schedule = RenderGraphSchedule::default();
+        for stage in [RenderGraphStage::Hdr, RenderGraphStage::Ldr] {
+            let stage_passes = declared
+                .iter()
+                .filter(|(_, _, pass_stage, _)| *pass_stage == stage)
+                .collect::<Vec<_>>();
+
+            let count = stage_passes.len();
+            let dependencies = (0..count)
+                .map(|i| {
+                    (0..count)
+                        .filter(|&j| {
+                            i != j && stage_passes[i].3.depends_on(&stage_passes[j].3, i, j)
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+
+            // Topological sorting, that prefers the order of registration.
+            let mut executed = vec![false; count];
+            for _ in 0..count {
+                let Some(next) = (0..count)
+                    .find(|&i| !executed[i] && dependencies[i].iter().all(|&j| executed[j]))
+                else {
+                    let names = (0..count)
+                        .filter(|&i| !executed[i])
+                        .map(|i| stage_passes[i].1.as_str())
+                        .collect::<Vec<_>>();
+                    return Err(format!(
+                        "circular dependency between passes: {}!",
+                        names.join(", ")
+                    ));
+                };
+                executed[next] = true;
+
+                let (pass, name, _, declaration) = stage_passes[next];
+                schedule.stages[stage as usize].push(ScheduledPass {
+                    pass: pass.clone(),
+                    name: name.clone(),
+                    declaration: declaration.clone(),
+                });
+            }
+        }
+
+        schedule.targets = targets
+            .into_iter()
+            .map(|(name, (descriptor, _))| (name, descriptor))
+            .collect();
+
+        Ok(schedule)
+    }
+}
+
+struct TargetCopy {
+    source: GpuTexture,
+    source_framebuffer: GpuFrameBuffer,
+    copy: GpuTexture,
+    copy_framebuffer: GpuFrameBuffer,
+}
+
+struct PassFrameBuffer {
+    color: Vec<GpuTexture>,
+    depth: Option<GpuTexture>,
+    framebuffer: GpuFrameBuffer,
+}
+
+fn rectangle_size(texture: &GpuTexture) -> (usize, usize) {
+    match texture.kind() {
+        GpuTextureKind::Rectangle { width, height } => (width, height),
+        _ => (1, 1),
+    }
+}
+
+fn same_textures<'a>(
+    a: impl IntoIterator<Item = &'a GpuTexture>,
+    b: impl IntoIterator<Item = &'a GpuTexture>,
+) -> bool {
+    let mut b = b.into_iter();
+    a.into_iter()
+        .all(|a| b.next().is_some_and(|b| Rc::ptr_eq(&a.0, &b.0)))
+        && b.next().is_none()
+}
+
+/// Copies the content of the target, so it could be read and written by the same pass.
+fn copy_target(
+    copies: &mut FxHashMap<ImmutableString, TargetCopy>,
+    server: &dyn GraphicsServer,
+    name: &ImmutableString,
+    source: &GpuTexture,
+) -> Result<GpuTexture, FrameworkError> {
+    let is_valid = copies
+        .get(name)
+        .is_some_and(|copy| Rc::ptr_eq(&copy.source.0, &source.0));
+    if !is_valid {
+        let (width, height) = rectangle_size(source);
+        let copy = server.create_2d_render_target(source.pixel_kind(), width, height)?;
+        copies.insert(
+            name.clone(),
+            TargetCopy {
+                source: source.clone(),
+                source_framebuffer: server
+                    .create_frame_buffer(None, vec![Attachment::color(source.clone())])?,
+                copy_framebuffer: server
+                    .create_frame_buffer(None, vec![Attachment::color(copy.clone())])?,
+                copy,
+            },
+        );
+    }
+
+    let copy = &copies[name];
+    let (width, height) = rectangle_size(source);
+    let (width, height) = (width as i32, height as i32);
+    copy.source_framebuffer.blit_to(
+        &copy.copy_framebuffer,
+        0,
+        0,
+        width,
+        height,
+        0,
+        0,
+        width,
+        height,
+        true,
+        false,
+        false,
+    );
+
+    Ok(copy.copy.clone())
+}
+
+/// Render targets and frame buffers of render graph passes of a scene.
+#[derive(Default)]
+pub(crate) struct RenderGraphTargets {
+    targets: FxHashMap<ImmutableString, (RenderTargetDescriptor, GpuTexture)>,
+    copies: FxHashMap<ImmutableString, TargetCopy>,
+    framebuffers: FxHashMap<String, PassFrameBuffer>,
+}
+
+/// A context for execution of a stage of render graph.
+pub(crate) struct RenderGraphExecutionContext<'a, 'b> {
+    pub elapsed_time: f32,
+    pub server: &'a dyn GraphicsServer,
+    pub texture_cache: &'a mut TextureCache,
+    pub geometry_cache: &'a mut GeometryCache,
+    pub shader_cache: &'a mut ShaderCache,
+    pub bundle_storage: &'a RenderDataBundleStorage,
+    pub quality_settings: &'a QualitySettings,
+    pub scene: &'b Scene,
+    pub camera: &'b Camera,
+    pub viewport: Rect<i32>,
+    pub scene_handle: Handle<Scene>,
+    pub fallback_resources: &'a FallbackResources,
+    pub uniform_buffer_cache: &'a mut UniformBufferCache,
+    pub uniform_memory_allocator: &'a mut UniformMemoryAllocator,
+    pub built_in_targets: &'a [(&'static str, GpuTexture)],
+}
+
+impl RenderGraphTargets {
+    /// Returns a texture of a render target created by a render graph pass.
+    pub(crate) fn get(&self, name: &str) -> Option<&GpuTexture> {
+        self.targets
+            .iter()
+            .find_map(|(target, (_, texture))| (target.as_str() == name).then_some(texture))
+    }
+
+    /// Returns an iterator over the names and textures of every render target created by render
+    /// graph passes.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&str, &GpuTexture)> {
+        self.targets
+            .iter()
+            .map(|(name, (_, texture))| (name.as_str(), texture))
+    }
+
+    /// Allocates render targets declared in the schedule and frees the ones that are not used
+    /// anymore.
+    pub(crate) fn prepare(
+        &mut self,
+        server: &dyn GraphicsServer,
+        schedule: &RenderGraphSchedule,
+        frame_width: usize,
+        frame_height: usize,
+    ) -> Result<(), FrameworkError> {
+        self.targets
+            .retain(|name, (descriptor, _)| schedule.targets.get(name) == Some(&*descriptor));
+        for (name, descriptor) in schedule.targets.iter() {
+            if !self.targets.contains_key(name) {
+                let (width, height) = descriptor.size.resolve(frame_width, frame_height);
+                let texture =
+                    server.create_2d_render_target(descriptor.pixel_kind, width, height)?;
+                self.targets
+                    .insert(name.clone(), (descriptor.clone(), texture));
+            }
+        }
+        self.copies
+            .retain(|name, _| is_built_in_target(name) || schedule.targets.contains_key(name));
+        self.framebuffers.retain(|name, _| {
+            schedule
+                .stages
+                .iter()
+                .flatten()
+                .any(|pass| pass.name == *name)
+        });
+        Ok(())
+    }
+
+    fn find(
+        &self,
+        name: &ImmutableString,
+        built_in_targets: &[(&str, GpuTexture)],
+    ) -> Option<GpuTexture> {
+        built_in_targets
+            .iter()
+            .find_map(|(target, texture)| (*target == name.as_str()).then(|| texture.clone()))
+            .or_else(|| self.targets.get(name).map(|(_, texture)| texture.clone()))
+    }
+
+    /// Executes every pass of the given stage in the scheduled order.
+    pub(crate) fn execute(
+        &mut self,
+        schedule: &RenderGraphSchedule,
+        stage: RenderGraphStage,
+        ctx: RenderGraphExecutionContext,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let RenderGraphExecutionContext {
+            elapsed_time,
+            server,
+            texture_cache,
+            geometry_cache,
+            shader_cache,
+            bundle_storage,
+            quality_settings,
+            scene,
+            camera,
+            viewport,
+            scene_handle,
+            fallback_resources,
+            uniform_buffer_cache,
+            uniform_memory_allocator,
+            built_in_targets,
+        } = ctx;
+
+        let Some(scene_depth) = self.find(
+            &ImmutableString::new(GBUFFER_DEPTH_TARGET),
+            built_in_targets,
+        ) else {
+            return Ok(Default::default());
+        };
+
+        let mut stats = RenderPassStatistics::default();
+        for scheduled in schedule.stages[stage as usize].iter() {
+            let declaration = &scheduled.declaration;
+
+            let mut textures = Vec::with_capacity(declaration.reads.len());
+            for name in declaration.reads.iter() {
+                let Some(texture) = self.find(name, built_in_targets) else {
+                    continue;
+                };
+                let texture = if declaration.writes.contains(name) {
+                    copy_target(&mut self.copies, server, name, &texture)?
+                } else {
+                    texture
+                };
+                textures.push((name.clone(), texture));
+            }
+
+            let color = declaration
+                .writes
+                .iter()
+                .filter_map(|name| self.find(name, built_in_targets))
+                .collect::<Vec<_>>();
+            let depth = declaration
+                .depth
+                .as_ref()
+                .and_then(|name| self.find(name, built_in_targets));
+
+            let framebuffer = match self.framebuffers.get(&scheduled.name) {
+                Some(cached)
+                    if same_textures(&cached.color, &color)
+                        && same_textures(&cached.depth, &depth) =>
+                {
+                    cached.framebuffer.clone()
+                }
+                _ => {
+                    let depth_attachment = depth.clone().map(|texture| {
+                        if texture.pixel_kind() == PixelKind::D24S8 {
+                            Attachment::depth_stencil(texture)
+                        } else {
+                            Attachment::depth(texture)
+                        }
+                    });
+                    let framebuffer = server.create_frame_buffer(
+                        depth_attachment,
+                        color.iter().cloned().map(Attachment::color).collect(),
+                    )?;
+                    self.framebuffers.insert(
+                        scheduled.name.clone(),
+                        PassFrameBuffer {
+                            color,
+                            depth,
+                            framebuffer: framebuffer.clone(),
+                        },
+                    );
+                    framebuffer
+                }
+            };
+
+            stats += scheduled
+                .pass
+                .borrow_mut()
+                .execute(RenderGraphPassContext {
+                    elapsed_time,
+                    server,
+                    texture_cache: &mut *texture_cache,
+                    geometry_cache: &mut *geometry_cache,
+                    shader_cache: &mut *shader_cache,
+                    bundle_storage,
+                    quality_settings,
+                    framebuffer: &framebuffer,
+                    scene,
+                    camera,
+                    viewport,
+                    scene_handle,
+                    fallback_resources,
+                    uniform_buffer_cache: &mut *uniform_buffer_cache,
+                    uniform_memory_allocator: &mut *uniform_memory_allocator,
+                    scene_depth: &scene_depth,
+                    textures: &textures,
+                })?;
+        }
+
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::renderer::{
+        framework::{error::FrameworkError, gpu_texture::PixelKind},
+        render_graph::{
+            RenderGraph, RenderGraphPass, RenderGraphPassContext, RenderGraphStage,
+            RenderPassBuilder, RenderTargetDescriptor, RenderTargetSize, SCENE_HDR_TARGET,
+            SCENE_LDR_TARGET,
+        },
+        RenderPassStatistics,
+    };
+    use std::{any::TypeId, cell::RefCell, rc::Rc};
+
+    struct TestPass {
+        name: &'static str,
+        stage: RenderGraphStage,
+        setup: fn(&mut RenderPassBuilder),
+    }
+
+    impl RenderGraphPass for TestPass {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn stage(&self) -> RenderGraphStage {
+            self.stage
+        }
+
+        fn setup(&self, builder: &mut RenderPassBuilder) {
+            (self.setup)(builder)
+        }
+
+        fn execute(
+            &mut self,
+            _ctx: RenderGraphPassContext,
+        ) -> Result<RenderPassStatistics, FrameworkError> {
+            Ok(Default::default())
+        }
+
+        fn source_type_id(&self) -> TypeId {
+            TypeId::of::<()>()
+        }
+    }
+
+    fn descriptor() -> RenderTargetDescriptor {
+        RenderTargetDescriptor {
+            size: RenderTargetSize::Frame,
+            pixel_kind: PixelKind::RGBA8,
+        }
+    }
+
+    fn make_graph(
+        passes: &[(&'static str, RenderGraphStage, fn(&mut RenderPassBuilder))],
+    ) -> RenderGraph {
+        let mut graph = RenderGraph::default();
+        for &(name, stage, setup) in passes {
+            graph.add_pass(Rc::new(RefCell::new(TestPass { name, stage, setup })));
+        }
+        graph
+    }
+
+    fn order(graph: &RenderGraph, stage: RenderGraphStage) -> Vec<String> {
+        graph.try_compile().unwrap().stages[stage as usize]
+            .iter()
+            .map(|pass| pass.name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_depends_on() {
+        let mut creator = RenderPassBuilder::default();
+        creator.create("A", descriptor()).write("A");
+        let mut reader = RenderPassBuilder::default();
+        reader.read("A").write(SCENE_HDR_TARGET);
+        let mut writer = RenderPassBuilder::default();
+        writer.write("A");
+
+        // The creator goes first, regardless of registration order.
+        assert!(reader.depends_on(&creator, 0, 1));
+        assert!(!creator.depends_on(&reader, 1, 0));
+        assert!(writer.depends_on(&creator, 0, 1));
+        assert!(!creator.depends_on(&writer, 1, 0));
+
+        // Readers go after writers.
+        assert!(reader.depends_on(&writer, 0, 1));
+        assert!(!writer.depends_on(&reader, 1, 0));
+
+        // Writers of the same target keep the order of registration.
+        let mut other_writer = RenderPassBuilder::default();
+        other_writer.write("A");
+        assert!(other_writer.depends_on(&writer, 1, 0));
+        assert!(!other_writer.depends_on(&writer, 0, 1));
+
+        // Unrelated passes.
+        let mut unrelated = RenderPassBuilder::default();
+        unrelated.write(SCENE_HDR_TARGET);
+        assert!(!unrelated.depends_on(&writer, 1, 0));
+        assert!(!writer.depends_on(&unrelated, 1, 0));
+    }
+
+    #[test]
+    fn test_circular_dependency() {
+        let graph = make_graph(&[
+            ("First", RenderGraphStage::Hdr, |builder| {
+                builder.create("A", descriptor()).write("A").read("B");
+            }),
+            ("Second", RenderGraphStage::Hdr, |builder| {
+                builder.create("B", descriptor()).write("B").read("A");
+            }),
+            ("Independent", RenderGraphStage::Hdr, |builder| {
+                builder.write(SCENE_HDR_TARGET);
+            }),
+        ]);
+
+        let err = graph.try_compile().err().unwrap();
+        assert!(err.contains("circular dependency"));
+        assert!(err.contains("First") && err.contains("Second"));
+        assert!(!err.contains("Independent"));
+
+        // The error is not fatal, an empty schedule is used instead.
+        let schedule = graph.compile();
+        assert!(schedule.stages.iter().all(|stage| stage.is_empty()));
+    }
+
+    #[test]
+    fn test_read_before_write() {
+        // The reader is registered before the pass that creates and writes the target.
+        let graph = make_graph(&[
+            ("Reader", RenderGraphStage::Hdr, |builder| {
+                builder.read("A").write(SCENE_HDR_TARGET);
+            }),
+            ("Writer", RenderGraphStage::Hdr, |builder| {
+                builder.create("A", descriptor()).write("A");
+            }),
+        ]);
+        assert_eq!(order(&graph, RenderGraphStage::Hdr), ["Writer", "Reader"]);
+
+        // Nobody creates the target.
+        let graph = make_graph(&[("Reader", RenderGraphStage::Hdr, |builder| {
+            builder.read("A").write(SCENE_HDR_TARGET);
+        })]);
+        assert!(graph.try_compile().err().unwrap().contains("unknown"));
+
+        // The target is created at a later stage.
+        let graph = make_graph(&[
+            ("Reader", RenderGraphStage::Hdr, |builder| {
+                builder.read("A").write(SCENE_HDR_TARGET);
+            }),
+            ("Writer", RenderGraphStage::Ldr, |builder| {
+                builder.create("A", descriptor()).write("A");
+            }),
+        ]);
+        assert!(graph
+            .try_compile()
+            .err()
+            .unwrap()
+            .contains("before it is created"));
+    }
+
+    #[test]
+    fn test_stable_ordering() {
+        let graph = make_graph(&[
+            ("Reader", RenderGraphStage::Hdr, |builder| {
+                builder.read("A").write(SCENE_HDR_TARGET);
+            }),
+            ("Independent1", RenderGraphStage::Hdr, |builder| {
+                builder.create("B", descriptor()).write("B");
+            }),
+            ("Writer1", RenderGraphStage::Hdr, |builder| {
+                builder.write("A");
+            }),
+            ("Creator", RenderGraphStage::Hdr, |builder| {
+                builder.create("A", descriptor()).write("A");
+            }),
+            ("Writer2", RenderGraphStage::Hdr, |builder| {
+                builder.write("A");
+            }),
+            ("Overlay", RenderGraphStage::Ldr, |builder| {
+                builder.read("A").write(SCENE_LDR_TARGET);
+            }),
+        ]);
+
+        let expected = ["Independent1", "Creator", "Writer1", "Writer2", "Reader"];
+        assert_eq!(order(&graph, RenderGraphStage::Hdr), expected);
+        assert_eq!(order(&graph, RenderGraphStage::Ldr), ["Overlay"]);
+
+        // Compilation is deterministic.
+        for _ in 0..10 {
+            assert_eq!(order(&graph, RenderGraphStage::Hdr), expected);
+        }
+    }
+}
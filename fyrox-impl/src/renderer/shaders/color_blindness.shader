@@ -0,0 +1,120 @@
+(
+    name: "ColorBlindness",
+    resources: [
+        (
+            name: "screenTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 0
+        ),
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (name: "worldViewProjection", kind: Matrix4()),
+                (name: "deficiency", kind: Int()),
+                (name: "correction", kind: Bool()),
+                (name: "strength", kind: Float()),
+            ]),
+            binding: 0
+        ),
+    ],
+    passes: [
+        (
+            name: "Primary",
+
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+
+            vertex_shader:
+                r#"
+                    layout (location = 0) in vec3 vertexPosition;
+                    layout (location = 1) in vec2 vertexTexCoord;
+
+                    out vec2 texCoord;
+
+                    void main()
+                    {
+                        texCoord = vertexTexCoord;
+                        gl_Position = properties.worldViewProjection * vec4(vertexPosition, 1.0);
+                    }
+                "#,
+
+            fragment_shader:
+                r#"
+                    // Color vision deficiency simulation is based on "A Physiologically-based Model
+                    // for Simulation of Color Vision Deficiency" by Machado, Oliveira and
+                    // Fernandes (2009), correction uses the daltonization algorithm by Fidaner,
+                    // Lin and Ozguven.
+
+                    in vec2 texCoord;
+                    out vec4 fragColor;
+
+                    vec3 srgbToLinear(vec3 color) {
+                        return mix(color / 12.92, pow((color + 0.055) / 1.055, vec3(2.4)), step(0.04045, color));
+                    }
+
+                    vec3 linearToSrgb(vec3 color) {
+                        return mix(color * 12.92, 1.055 * pow(color, vec3(1.0 / 2.4)) - 0.055, step(0.0031308, color));
+                    }
+
+                    vec3 simulate(vec3 color, int deficiency) {
+                        // Rows of the simulation matrices with full severity.
+                        if (deficiency == 1) {
+                            return vec3(
+                                dot(vec3(0.152286, 1.052583, -0.204868), color),
+                                dot(vec3(0.114503, 0.786281, 0.099216), color),
+                                dot(vec3(-0.003882, -0.048116, 1.051998), color));
+                        } else if (deficiency == 2) {
+                            return vec3(
+                                dot(vec3(0.367322, 0.860646, -0.227968), color),
+                                dot(vec3(0.280085, 0.672501, 0.047413), color),
+                                dot(vec3(-0.011820, 0.042940, 0.968881), color));
+                        } else if (deficiency == 3) {
+                            return vec3(
+                                dot(vec3(1.255528, -0.076749, -0.178779), color),
+                                dot(vec3(-0.078411, 0.930809, 0.147602), color),
+                                dot(vec3(0.004733, 0.691367, 0.303900), color));
+                        }
+                        return color;
+                    }
+
+                    void main() {
+                        vec4 frame = texture(screenTexture, texCoord);
+                        vec3 color = srgbToLinear(clamp(frame.rgb, 0.0, 1.0));
+                        vec3 simulated = simulate(color, properties.deficiency);
+
+                        vec3 result;
+                        if (properties.correction) {
+                            // Shift the information, that is lost for the deficient eye, to the
+                            // channels it can perceive.
+                            vec3 error = color - simulated;
+                            vec3 shift = vec3(0.0, 0.7 * error.r + error.g, 0.7 * error.r + error.b);
+                            result = color + shift;
+                        } else {
+                            result = simulated;
+                        }
+
+                        result = linearToSrgb(clamp(result, 0.0, 1.0));
+                        fragColor = vec4(mix(frame.rgb, result, properties.strength), frame.a);
+                    }
+                "#,
+        )
+    ]
+)
@@ -21,6 +21,11 @@
             kind: Texture(kind: USampler2D, fallback: White),
             binding: 3
         ),
+        (
+            name: "ormTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 4
+        ),
         (
             name: "properties",
             kind: PropertyGroup([
@@ -30,6 +35,9 @@
                 (name: "resolution", kind: Vector2()),
                 (name: "color", kind: Vector4()),
                 (name: "layerIndex", kind: UInt()),
+                (name: "fadeFactor", kind: Float()),
+                (name: "useNormalTexture", kind: Bool()),
+                (name: "useOrmTexture", kind: Bool()),
             ]),
             binding: 0
         ),
@@ -87,6 +95,7 @@
                 r#"
                     layout (location = 0) out vec4 outDiffuseMap;
                     layout (location = 1) out vec4 outNormalMap;
+                    layout (location = 2) out vec4 outMaterialMap;
 
                     in vec4 clipSpacePosition;
 
@@ -121,6 +130,7 @@
                         vec2 decalTexCoord = decalSpacePosition.xz + 0.5;
 
                         outDiffuseMap = properties.color * texture(diffuseTexture, decalTexCoord);
+                        outDiffuseMap.a *= properties.fadeFactor;
 
                         vec3 fragmentTangent = dFdx(sceneWorldPosition);
                         vec3 fragmentBinormal = dFdy(sceneWorldPosition);
@@ -133,7 +143,15 @@
 
                         vec3 rawNormal = (texture(normalTexture, decalTexCoord) * 2.0 - 1.0).xyz;
                         vec3 worldSpaceNormal = tangentToWorld * rawNormal;
-                        outNormalMap = vec4(worldSpaceNormal * 0.5 + 0.5, outDiffuseMap.a);
+                        // Zero alpha leaves the respective G-Buffer data untouched.
+                        float normalAlpha = properties.useNormalTexture ? outDiffuseMap.a : 0.0;
+                        outNormalMap = vec4(worldSpaceNormal * 0.5 + 0.5, normalAlpha);
+
+                        // ORM texture layout: occlusion (R), roughness (G), metallic (B). G-Buffer layout:
+                        // metallic (R), roughness (G), occlusion (B).
+                        vec3 orm = texture(ormTexture, decalTexCoord).rgb;
+                        float materialAlpha = properties.useOrmTexture ? outDiffuseMap.a : 0.0;
+                        outMaterialMap = vec4(orm.b, orm.g, orm.r, materialAlpha);
                     }
                 "#,
         )
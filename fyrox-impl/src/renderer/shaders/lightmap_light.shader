@@ -0,0 +1,206 @@
+(
+    name: "LightmapLight",
+    resources: [
+        (
+            name: "shadowMap",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 0
+        ),
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (name: "lightViewProjMatrix", kind: Matrix4()),
+                (name: "lightPosition", kind: Vector3()),
+                (name: "lightDirection", kind: Vector3()),
+                (name: "lightColor", kind: Vector3()),
+                (name: "faceAxis", kind: Vector3()),
+                (name: "coneAngleCos", kind: Vector2()),
+                (name: "lightIntensity", kind: Float()),
+                (name: "lightRadius", kind: Float()),
+                (name: "shadowBias", kind: Float()),
+                (name: "normalOffset", kind: Float()),
+                (name: "shadowMapInvSize", kind: Float()),
+                (name: "lightKind", kind: Int()),
+            ]),
+            binding: 0
+        ),
+    ],
+    passes: [
+        (
+            name: "Coverage",
+
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                blend: Some(BlendParameters(
+                    func: BlendFunc(
+                        sfactor: One,
+                        dfactor: One,
+                        alpha_sfactor: One,
+                        alpha_dfactor: One,
+                    ),
+                    equation: BlendEquation(
+                        rgb: Add,
+                        alpha: Add
+                    )
+                )),
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+
+            vertex_shader:
+                r#"
+                    layout (location = 0) in vec3 vertexPosition;
+                    layout (location = 1) in vec2 vertexSecondTexCoord;
+                    layout (location = 2) in vec3 vertexNormal;
+
+                    out vec3 worldPosition;
+                    out vec3 worldNormal;
+
+                    void main()
+                    {
+                        // Geometry is already in world space, it is rasterized in the lightmap
+                        // texture space instead of the screen space.
+                        worldPosition = vertexPosition;
+                        worldNormal = vertexNormal;
+                        gl_Position = vec4(vertexSecondTexCoord * 2.0 - 1.0, 0.0, 1.0);
+                    }
+                "#,
+
+            fragment_shader:
+                r#"
+                    out vec4 FragColor;
+
+                    void main()
+                    {
+                        // Alpha channel is used to mark texels that are covered by the geometry.
+                        FragColor = vec4(0.0, 0.0, 0.0, 1.0);
+                    }
+                "#,
+        ),
+        (
+            name: "Light",
+
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                blend: Some(BlendParameters(
+                    func: BlendFunc(
+                        sfactor: One,
+                        dfactor: One,
+                        alpha_sfactor: One,
+                        alpha_dfactor: One,
+                    ),
+                    equation: BlendEquation(
+                        rgb: Add,
+                        alpha: Add
+                    )
+                )),
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+
+            vertex_shader:
+                r#"
+                    layout (location = 0) in vec3 vertexPosition;
+                    layout (location = 1) in vec2 vertexSecondTexCoord;
+                    layout (location = 2) in vec3 vertexNormal;
+
+                    out vec3 worldPosition;
+                    out vec3 worldNormal;
+
+                    void main()
+                    {
+                        // Geometry is already in world space, it is rasterized in the lightmap
+                        // texture space instead of the screen space.
+                        worldPosition = vertexPosition;
+                        worldNormal = vertexNormal;
+                        gl_Position = vec4(vertexSecondTexCoord * 2.0 - 1.0, 0.0, 1.0);
+                    }
+                "#,
+
+            fragment_shader:
+                r#"
+                    in vec3 worldPosition;
+                    in vec3 worldNormal;
+
+                    out vec4 FragColor;
+
+                    void main()
+                    {
+                        vec3 normal = normalize(worldNormal);
+
+                        vec3 fragmentToLight;
+                        float attenuation = 1.0;
+
+                        // 0 - directional, 1 - spot, 2 - single face of a point light.
+                        if (properties.lightKind == 0)
+                        {
+                            fragmentToLight = properties.lightDirection;
+                        }
+                        else
+                        {
+                            vec3 lightVector = properties.lightPosition - worldPosition;
+                            float distance = length(lightVector);
+                            fragmentToLight = lightVector / max(distance, 0.00001);
+                            attenuation = S_LightDistanceAttenuation(distance, properties.lightRadius);
+
+                            if (properties.lightKind == 1)
+                            {
+                                float spotAngleCos = dot(properties.lightDirection, fragmentToLight);
+                                attenuation *= smoothstep(properties.coneAngleCos.x, properties.coneAngleCos.y, spotAngleCos);
+                            }
+                            else
+                            {
+                                // Point lights are rendered face-by-face, every face lights only the
+                                // fragments whose major axis matches the face.
+                                vec3 lightToFragment = -lightVector;
+                                vec3 absolute = abs(lightToFragment);
+                                float majorAxis = max(absolute.x, max(absolute.y, absolute.z));
+                                if (dot(lightToFragment, properties.faceAxis) < majorAxis)
+                                {
+                                    discard;
+                                }
+                            }
+                        }
+
+                        float lambert = max(dot(normal, fragmentToLight), 0.0);
+
+                        vec3 offsetPosition = worldPosition + normal * properties.normalOffset;
+                        float shadow = S_SpotShadowFactor(true, true, properties.shadowBias, offsetPosition,
+                            properties.lightViewProjMatrix, properties.shadowMapInvSize, shadowMap);
+
+                        vec3 light = properties.lightColor * properties.lightIntensity * lambert * attenuation * shadow;
+
+                        FragColor = vec4(light, 0.0);
+                    }
+                "#,
+        ),
+    ]
+)
@@ -0,0 +1,102 @@
+(
+    name: "SSGIComposite",
+    resources: [
+        (
+            name: "giSampler",
+            kind: Texture(kind: Sampler2D, fallback: Black),
+            binding: 0
+        ),
+        (
+            name: "diffuseTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 1
+        ),
+        (
+            name: "materialTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 2
+        ),
+        (
+            name: "aoSampler",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 3
+        ),
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (name: "worldViewProjection", kind: Matrix4()),
+                (name: "intensity", kind: Float()),
+            ]),
+            binding: 0
+        ),
+    ],
+    passes: [
+        (
+            name: "Primary",
+
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: false,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                blend: Some(BlendParameters(
+                    func: BlendFunc(
+                        sfactor: One,
+                        dfactor: One,
+                        alpha_sfactor: One,
+                        alpha_dfactor: One,
+                    ),
+                    equation: BlendEquation(
+                        rgb: Add,
+                        alpha: Add
+                    )
+                )),
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+
+            vertex_shader:
+                r#"
+                    layout (location = 0) in vec3 vertexPosition;
+                    layout (location = 1) in vec2 vertexTexCoord;
+
+                    out vec2 texCoord;
+
+                    void main()
+                    {
+                        texCoord = vertexTexCoord;
+                        gl_Position = properties.worldViewProjection * vec4(vertexPosition, 1.0);
+                    }
+                "#,
+
+            fragment_shader:
+                r#"
+                    out vec4 FragColor;
+
+                    in vec2 texCoord;
+
+                    void main() {
+                        vec3 albedo = S_SRGBToLinear(texture(diffuseTexture, texCoord)).rgb;
+                        vec3 material = texture(materialTexture, texCoord).rgb;
+                        float metallic = material.x;
+                        float occlusion = material.z * texture(aoSampler, texCoord).r;
+                        vec3 indirect = texture(giSampler, texCoord).rgb;
+
+                        // Metals do not have diffuse reflection.
+                        FragColor = vec4(albedo * (1.0 - metallic) * occlusion * indirect * properties.intensity, 0.0);
+                    }
+                "#,
+        )
+    ]
+)
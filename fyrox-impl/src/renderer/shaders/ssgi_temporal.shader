@@ -0,0 +1,110 @@
+(
+    name: "SSGITemporal",
+    resources: [
+        (
+            name: "currentSampler",
+            kind: Texture(kind: Sampler2D, fallback: Black),
+            binding: 0
+        ),
+        (
+            name: "historySampler",
+            kind: Texture(kind: Sampler2D, fallback: Black),
+            binding: 1
+        ),
+        (
+            name: "depthSampler",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 2
+        ),
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (name: "worldViewProjection", kind: Matrix4()),
+                (name: "inverseViewProjectionMatrix", kind: Matrix4()),
+                (name: "viewMatrix", kind: Matrix4()),
+                (name: "previousViewMatrix", kind: Matrix4()),
+                (name: "previousViewProjectionMatrix", kind: Matrix4()),
+                (name: "historyWeight", kind: Float()),
+            ]),
+            binding: 0
+        ),
+    ],
+    passes: [
+        (
+            name: "Primary",
+
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+
+            vertex_shader:
+                r#"
+                    layout (location = 0) in vec3 vertexPosition;
+                    layout (location = 1) in vec2 vertexTexCoord;
+
+                    out vec2 texCoord;
+
+                    void main()
+                    {
+                        texCoord = vertexTexCoord;
+                        gl_Position = properties.worldViewProjection * vec4(vertexPosition, 1.0);
+                    }
+                "#,
+
+            fragment_shader:
+                r#"
+                    // Alpha channel contains the distance from the camera to the pixel, it is used
+                    // to reject the history in disoccluded areas.
+                    out vec4 FragColor;
+
+                    in vec2 texCoord;
+
+                    void main() {
+                        vec3 current = texture(currentSampler, texCoord).rgb;
+
+                        float depth = texture(depthSampler, texCoord).r;
+                        vec3 worldPosition = S_UnProject(vec3(texCoord, depth), properties.inverseViewProjectionMatrix);
+                        float currentDistance = length((properties.viewMatrix * vec4(worldPosition, 1.0)).xyz);
+
+                        float weight = properties.historyWeight;
+
+                        vec4 previousClipPosition = properties.previousViewProjectionMatrix * vec4(worldPosition, 1.0);
+                        vec2 previousCoord = (previousClipPosition.xy / previousClipPosition.w) * 0.5 + 0.5;
+                        if (previousClipPosition.w <= 0.0
+                            || any(lessThan(previousCoord, vec2(0.0)))
+                            || any(greaterThan(previousCoord, vec2(1.0)))) {
+                            weight = 0.0;
+                        }
+
+                        vec4 history = texture(historySampler, previousCoord);
+
+                        // The pixel was occluded in the previous frame, if the distance stored in
+                        // the history does not match the expected one.
+                        float previousDistance = length((properties.previousViewMatrix * vec4(worldPosition, 1.0)).xyz);
+                        if (abs(history.a - previousDistance) > 0.1 * previousDistance) {
+                            weight = 0.0;
+                        }
+
+                        FragColor = vec4(mix(current, history.rgb, weight), currentDistance);
+                    }
+                "#,
+        )
+    ]
+)
@@ -0,0 +1,153 @@
+(
+    name: "SSGITrace",
+    resources: [
+        (
+            name: "depthSampler",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 0
+        ),
+        (
+            name: "normalSampler",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 1
+        ),
+        (
+            name: "frameSampler",
+            kind: Texture(kind: Sampler2D, fallback: Black),
+            binding: 2
+        ),
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (name: "worldViewProjection", kind: Matrix4()),
+                (name: "inverseProjectionMatrix", kind: Matrix4()),
+                (name: "projectionMatrix", kind: Matrix4()),
+                (name: "viewMatrix", kind: Matrix3()),
+                (name: "maxDistance", kind: Float()),
+                (name: "thickness", kind: Float()),
+                (name: "frameIndex", kind: Float()),
+                (name: "rayCount", kind: Int()),
+                (name: "stepCount", kind: Int()),
+            ]),
+            binding: 0
+        ),
+    ],
+    passes: [
+        (
+            name: "Primary",
+
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+
+            vertex_shader:
+                r#"
+                    layout (location = 0) in vec3 vertexPosition;
+                    layout (location = 1) in vec2 vertexTexCoord;
+
+                    out vec2 texCoord;
+
+                    void main()
+                    {
+                        texCoord = vertexTexCoord;
+                        gl_Position = properties.worldViewProjection * vec4(vertexPosition, 1.0);
+                    }
+                "#,
+
+            fragment_shader:
+                r#"
+                    out vec4 FragColor;
+
+                    in vec2 texCoord;
+
+                    const float PI = 3.14159265359;
+
+                    vec3 GetViewSpacePosition(vec2 screenCoord) {
+                        return S_UnProject(vec3(screenCoord, texture(depthSampler, screenCoord).r), properties.inverseProjectionMatrix);
+                    }
+
+                    // Interleaved gradient noise, that changes every frame to let temporal accumulation
+                    // converge to a noise-free result.
+                    float Noise(vec2 pixel, float offset) {
+                        pixel += offset * 5.588238;
+                        return fract(52.9829189 * fract(dot(pixel, vec2(0.06711056, 0.00583715))));
+                    }
+
+                    vec3 CosineSampleHemisphere(vec3 n, vec2 u) {
+                        float phi = 2.0 * PI * u.x;
+                        float r = sqrt(u.y);
+                        vec3 tangent = normalize(abs(n.z) < 0.999 ? cross(n, vec3(0.0, 0.0, 1.0)) : cross(n, vec3(1.0, 0.0, 0.0)));
+                        vec3 bitangent = cross(n, tangent);
+                        return normalize(tangent * (r * cos(phi)) + bitangent * (r * sin(phi)) + n * sqrt(max(0.0, 1.0 - u.y)));
+                    }
+
+                    void main() {
+                        float depth = texture(depthSampler, texCoord).r;
+                        if (depth >= 1.0) {
+                            // Nothing to illuminate (sky).
+                            FragColor = vec4(0.0);
+                            return;
+                        }
+
+                        vec3 fragPos = GetViewSpacePosition(texCoord);
+                        vec3 worldSpaceNormal = texture(normalSampler, texCoord).xyz * 2.0 - 1.0;
+                        vec3 viewSpaceNormal = normalize(properties.viewMatrix * worldSpaceNormal);
+
+                        // Offset the origin a bit to prevent self-intersections.
+                        vec3 origin = fragPos + viewSpaceNormal * 0.01;
+
+                        vec3 radiance = vec3(0.0);
+                        for (int ray = 0; ray < properties.rayCount; ++ray) {
+                            float rayOffset = properties.frameIndex + float(ray) * 7.0;
+                            vec2 u = vec2(Noise(gl_FragCoord.xy, rayOffset), Noise(gl_FragCoord.yx, rayOffset + 3.0));
+                            vec3 direction = CosineSampleHemisphere(viewSpaceNormal, u);
+                            float jitter = Noise(gl_FragCoord.xy, rayOffset + 11.0);
+
+                            for (int step = 0; step < properties.stepCount; ++step) {
+                                float t = properties.maxDistance * (float(step) + jitter) / float(properties.stepCount);
+                                vec3 samplePoint = origin + direction * t;
+
+                                vec4 offset = properties.projectionMatrix * vec4(samplePoint, 1.0);
+                                if (offset.w <= 0.0) {
+                                    break;
+                                }
+                                vec2 sampleCoord = (offset.xy / offset.w) * 0.5 + 0.5;
+                                if (any(lessThan(sampleCoord, vec2(0.0))) || any(greaterThan(sampleCoord, vec2(1.0)))) {
+                                    break;
+                                }
+
+                                vec3 scenePosition = GetViewSpacePosition(sampleCoord);
+                                float difference = scenePosition.z - samplePoint.z;
+                                if (difference > 0.0 && difference < properties.thickness) {
+                                    // Since the directions are cosine-distributed, the average of the
+                                    // incoming radiance gives irradiance divided by PI, which is exactly
+                                    // what Lambertian surfaces reflect.
+                                    radiance += texture(frameSampler, sampleCoord).rgb;
+                                    break;
+                                }
+                            }
+                        }
+
+                        FragColor = vec4(radiance / float(max(properties.rayCount, 1)), 1.0);
+                    }
+                "#,
+        )
+    ]
+)
@@ -0,0 +1,156 @@
+(
+    name: "TAAResolve",
+    resources: [
+        (
+            name: "currentSampler",
+            kind: Texture(kind: Sampler2D, fallback: Black),
+            binding: 0
+        ),
+        (
+            name: "historySampler",
+            kind: Texture(kind: Sampler2D, fallback: Black),
+            binding: 1
+        ),
+        (
+            name: "motionSampler",
+            kind: Texture(kind: Sampler2D, fallback: Black),
+            binding: 2
+        ),
+        (
+            name: "depthSampler",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 3
+        ),
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (name: "worldViewProjection", kind: Matrix4()),
+                (name: "inverseViewProjectionMatrix", kind: Matrix4()),
+                (name: "previousViewProjectionMatrix", kind: Matrix4()),
+                (name: "jitter", kind: Vector2()),
+                (name: "inverseScreenSize", kind: Vector2()),
+                (name: "historyWeight", kind: Float()),
+            ]),
+            binding: 0
+        ),
+    ],
+    passes: [
+        (
+            name: "Primary",
+
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+
+            vertex_shader:
+                r#"
+                    layout (location = 0) in vec3 vertexPosition;
+                    layout (location = 1) in vec2 vertexTexCoord;
+
+                    out vec2 texCoord;
+
+                    void main()
+                    {
+                        texCoord = vertexTexCoord;
+                        gl_Position = properties.worldViewProjection * vec4(vertexPosition, 1.0);
+                    }
+                "#,
+
+            fragment_shader:
+                r#"
+                    out vec4 FragColor;
+
+                    in vec2 texCoord;
+
+                    vec3 RGBToYCoCg(vec3 c) {
+                        return vec3(
+                            0.25 * c.r + 0.5 * c.g + 0.25 * c.b,
+                            0.5 * c.r - 0.5 * c.b,
+                            -0.25 * c.r + 0.5 * c.g - 0.25 * c.b
+                        );
+                    }
+
+                    vec3 YCoCgToRGB(vec3 c) {
+                        return vec3(c.x + c.y - c.z, c.x + c.z, c.x - c.y - c.z);
+                    }
+
+                    void main() {
+                        vec2 texelSize = properties.inverseScreenSize;
+
+                        // Gather the neighborhood of the pixel and find the closest to the camera
+                        // pixel. Its motion is used to keep the edges of moving objects sharp.
+                        vec3 current = vec3(0.0);
+                        vec3 minColor = vec3(1.0e10);
+                        vec3 maxColor = vec3(-1.0e10);
+                        float closestDepth = 1.0;
+                        vec2 closestCoord = texCoord;
+                        for (int y = -1; y <= 1; ++y) {
+                            for (int x = -1; x <= 1; ++x) {
+                                vec2 coord = texCoord + vec2(float(x), float(y)) * texelSize;
+                                vec3 color = RGBToYCoCg(texture(currentSampler, coord).rgb);
+                                if (x == 0 && y == 0) {
+                                    current = color;
+                                }
+                                minColor = min(minColor, color);
+                                maxColor = max(maxColor, color);
+
+                                float depth = texture(depthSampler, coord).r;
+                                if (depth < closestDepth) {
+                                    closestDepth = depth;
+                                    closestCoord = coord;
+                                }
+                            }
+                        }
+
+                        vec2 velocity;
+                        vec4 motion = texture(motionSampler, closestCoord);
+                        if (motion.z > 0.5) {
+                            velocity = motion.xy;
+                        } else {
+                            // There's no motion vector for the pixel (background or an object
+                            // without motion vectors), reproject it using camera motion only.
+                            vec3 worldPosition = S_UnProject(vec3(closestCoord, closestDepth), properties.inverseViewProjectionMatrix);
+                            vec4 previousClipPosition = properties.previousViewProjectionMatrix * vec4(worldPosition, 1.0);
+                            vec2 previousCoord = (previousClipPosition.xy / previousClipPosition.w) * 0.5 + 0.5;
+                            velocity = closestCoord - 0.5 * properties.jitter - previousCoord;
+                        }
+
+                        vec2 historyCoord = texCoord - velocity;
+
+                        float weight = properties.historyWeight;
+                        if (any(lessThan(historyCoord, vec2(0.0))) || any(greaterThan(historyCoord, vec2(1.0)))) {
+                            weight = 0.0;
+                        }
+
+                        // Clamp the history to the color range of the neighborhood, this removes
+                        // most of the ghosting on disocclusions.
+                        vec3 history = RGBToYCoCg(texture(historySampler, historyCoord).rgb);
+                        history = clamp(history, minColor, maxColor);
+
+                        // Fast moving pixels have less reliable history.
+                        float speed = length(velocity / texelSize);
+                        weight *= clamp(1.0 - speed * 0.02, 0.5, 1.0);
+
+                        FragColor = vec4(YCoCgToRGB(mix(current, history, weight)), 1.0);
+                    }
+                "#,
+        )
+    ]
+)
@@ -0,0 +1,83 @@
+(
+    name: "TAASharpen",
+    resources: [
+        (
+            name: "frameSampler",
+            kind: Texture(kind: Sampler2D, fallback: Black),
+            binding: 0
+        ),
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (name: "worldViewProjection", kind: Matrix4()),
+                (name: "inverseScreenSize", kind: Vector2()),
+                (name: "sharpness", kind: Float()),
+            ]),
+            binding: 0
+        ),
+    ],
+    passes: [
+        (
+            name: "Primary",
+
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+
+            vertex_shader:
+                r#"
+                    layout (location = 0) in vec3 vertexPosition;
+                    layout (location = 1) in vec2 vertexTexCoord;
+
+                    out vec2 texCoord;
+
+                    void main()
+                    {
+                        texCoord = vertexTexCoord;
+                        gl_Position = properties.worldViewProjection * vec4(vertexPosition, 1.0);
+                    }
+                "#,
+
+            fragment_shader:
+                r#"
+                    // Unsharp mask, that compensates the blurriness caused by the history
+                    // resampling.
+                    out vec4 FragColor;
+
+                    in vec2 texCoord;
+
+                    void main() {
+                        vec2 texelSize = properties.inverseScreenSize;
+
+                        vec3 center = texture(frameSampler, texCoord).rgb;
+                        vec3 neighbors =
+                            texture(frameSampler, texCoord + vec2(texelSize.x, 0.0)).rgb +
+                            texture(frameSampler, texCoord - vec2(texelSize.x, 0.0)).rgb +
+                            texture(frameSampler, texCoord + vec2(0.0, texelSize.y)).rgb +
+                            texture(frameSampler, texCoord - vec2(0.0, texelSize.y)).rgb;
+
+                        vec3 sharpened = center + (center - 0.25 * neighbors) * properties.sharpness;
+
+                        FragColor = vec4(clamp(sharpened, 0.0, 1.0), 1.0);
+                    }
+                "#,
+        )
+    ]
+)
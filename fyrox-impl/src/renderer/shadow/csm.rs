@@ -34,9 +34,7 @@ use crate::{
             uniform::UniformMemoryAllocator,
         },
         framework::{
-            error::FrameworkError,
-            framebuffer::{Attachment, AttachmentKind},
-            gpu_texture::PixelKind,
+            error::FrameworkError, framebuffer::Attachment, gpu_texture::PixelKind,
             server::GraphicsServer,
         },
         FallbackResources, RenderPassStatistics, ShadowMapPrecision, DIRECTIONAL_SHADOW_PASS_NAME,
@@ -72,13 +70,8 @@ impl Cascade {
         )?;
 
         Ok(Self {
-            frame_buffer: server.create_frame_buffer(
-                Some(Attachment {
-                    kind: AttachmentKind::Depth,
-                    texture: depth,
-                }),
-                Default::default(),
-            )?,
+            frame_buffer: server
+                .create_frame_buffer(Some(Attachment::depth(depth)), Default::default())?,
             view_proj_matrix: Default::default(),
             z_far: 0.0,
         })
@@ -107,6 +100,7 @@ pub(crate) struct CsmRenderContext<'a, 'c> {
     pub texture_cache: &'a mut TextureCache,
     pub fallback_resources: &'a FallbackResources,
     pub uniform_memory_allocator: &'a mut UniformMemoryAllocator,
+    pub use_instancing: bool,
 }
 
 impl CsmRenderer {
@@ -156,6 +150,7 @@ impl CsmRenderer {
             texture_cache,
             fallback_resources,
             uniform_memory_allocator,
+            use_instancing,
         } = ctx;
 
         let LightSourceKind::Directional { ref csm_options } = light.kind else {
@@ -276,10 +271,12 @@ impl CsmRenderer {
                     viewport,
                     uniform_memory_allocator,
                     use_pom: false,
+                    use_instancing,
                     light_position: &Default::default(),
                     fallback_resources,
                     ambient_light: Color::WHITE, // TODO
                     scene_depth: None,
+                    motion: None,
                 },
             )?;
         }
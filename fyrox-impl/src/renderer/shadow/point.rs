@@ -32,7 +32,7 @@ use crate::{
         cache::{shader::ShaderCache, texture::TextureCache, uniform::UniformMemoryAllocator},
         framework::{
             error::FrameworkError,
-            framebuffer::{Attachment, AttachmentKind},
+            framebuffer::Attachment,
             gpu_texture::{
                 CubeMapFace, GpuTextureDescriptor, GpuTextureKind, MagnificationFilter,
                 MinificationFilter, PixelKind, WrapMode,
@@ -73,6 +73,7 @@ pub(crate) struct PointShadowMapRenderContext<'a> {
     pub texture_cache: &'a mut TextureCache,
     pub fallback_resources: &'a FallbackResources,
     pub uniform_memory_allocator: &'a mut UniformMemoryAllocator,
+    pub use_instancing: bool,
 }
 
 impl PointShadowMapRenderer {
@@ -80,11 +81,13 @@ impl PointShadowMapRenderer {
         server: &dyn GraphicsServer,
         size: usize,
         precision: ShadowMapPrecision,
+        distance_pixel_kind: PixelKind,
     ) -> Result<Self, FrameworkError> {
         fn make_cascade(
             server: &dyn GraphicsServer,
             size: usize,
             precision: ShadowMapPrecision,
+            distance_pixel_kind: PixelKind,
         ) -> Result<GpuFrameBuffer, FrameworkError> {
             let depth = server.create_2d_render_target(
                 match precision {
@@ -100,7 +103,7 @@ impl PointShadowMapRenderer {
                     width: size,
                     height: size,
                 },
-                pixel_kind: PixelKind::R16F,
+                pixel_kind: distance_pixel_kind,
                 min_filter: MinificationFilter::Nearest,
                 mag_filter: MagnificationFilter::Nearest,
                 s_wrap_mode: WrapMode::ClampToEdge,
@@ -110,23 +113,32 @@ impl PointShadowMapRenderer {
             })?;
 
             server.create_frame_buffer(
-                Some(Attachment {
-                    kind: AttachmentKind::Depth,
-                    texture: depth,
-                }),
-                vec![Attachment {
-                    kind: AttachmentKind::Color,
-                    texture: cube_map,
-                }],
+                Some(Attachment::depth(depth)),
+                vec![Attachment::color(cube_map)],
             )
         }
 
         Ok(Self {
             precision,
             cascades: [
-                make_cascade(server, cascade_size(size, 0), precision)?,
-                make_cascade(server, cascade_size(size, 1), precision)?,
-                make_cascade(server, cascade_size(size, 2), precision)?,
+                make_cascade(
+                    server,
+                    cascade_size(size, 0),
+                    precision,
+                    distance_pixel_kind,
+                )?,
+                make_cascade(
+                    server,
+                    cascade_size(size, 1),
+                    precision,
+                    distance_pixel_kind,
+                )?,
+                make_cascade(
+                    server,
+                    cascade_size(size, 2),
+                    precision,
+                    distance_pixel_kind,
+                )?,
             ],
             size,
             faces: [
@@ -194,6 +206,7 @@ impl PointShadowMapRenderer {
             texture_cache,
             fallback_resources,
             uniform_memory_allocator,
+            use_instancing,
         } = args;
 
         let framebuffer = &self.cascades[cascade];
@@ -246,10 +259,12 @@ impl PointShadowMapRenderer {
                     viewport,
                     uniform_memory_allocator,
                     use_pom: false,
+                    use_instancing,
                     light_position: &light_pos,
                     fallback_resources,
                     ambient_light: Color::WHITE, // TODO
                     scene_depth: None,
+                    motion: None,
                 },
             )?;
         }
@@ -31,9 +31,7 @@ use crate::{
         },
         cache::{shader::ShaderCache, texture::TextureCache, uniform::UniformMemoryAllocator},
         framework::{
-            error::FrameworkError,
-            framebuffer::{Attachment, AttachmentKind},
-            gpu_texture::PixelKind,
+            error::FrameworkError, framebuffer::Attachment, gpu_texture::PixelKind,
             server::GraphicsServer,
         },
         shadow::cascade_size,
@@ -75,13 +73,7 @@ impl SpotShadowMapRenderer {
                 size,
             )?;
 
-            server.create_frame_buffer(
-                Some(Attachment {
-                    kind: AttachmentKind::Depth,
-                    texture: depth,
-                }),
-                vec![],
-            )
+            server.create_frame_buffer(Some(Attachment::depth(depth)), vec![])
         }
 
         Ok(Self {
@@ -128,6 +120,7 @@ impl SpotShadowMapRenderer {
         texture_cache: &mut TextureCache,
         fallback_resources: &FallbackResources,
         uniform_memory_allocator: &mut UniformMemoryAllocator,
+        use_instancing: bool,
     ) -> Result<RenderPassStatistics, FrameworkError> {
         let mut statistics = RenderPassStatistics::default();
 
@@ -167,10 +160,12 @@ impl SpotShadowMapRenderer {
                 viewport,
                 uniform_memory_allocator,
                 use_pom: false,
+                use_instancing,
                 light_position: &Default::default(),
                 fallback_resources,
                 ambient_light: Color::WHITE, // TODO
                 scene_depth: None,
+                motion: None,
             },
         )?;
 
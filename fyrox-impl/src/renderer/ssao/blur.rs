@@ -52,8 +52,9 @@ impl Blur {
         server: &dyn GraphicsServer,
         width: usize,
         height: usize,
+        pixel_kind: PixelKind,
     ) -> Result<Self, FrameworkError> {
-        let frame = server.create_2d_render_target(PixelKind::R32F, width, height)?;
+        let frame = server.create_2d_render_target(pixel_kind, width, height)?;
         let program =
             RenderPassContainer::from_str(server, include_str!("../shaders/blur.shader"))?;
         Ok(Self {
@@ -34,7 +34,7 @@ use crate::{
         framework::{
             buffer::BufferUsage,
             error::FrameworkError,
-            framebuffer::{Attachment, AttachmentKind, GpuFrameBuffer},
+            framebuffer::{Attachment, GpuFrameBuffer},
             geometry_buffer::GpuGeometryBuffer,
             gpu_texture::{
                 GpuTexture, GpuTextureDescriptor, GpuTextureKind, MagnificationFilter,
@@ -76,26 +76,21 @@ impl ScreenSpaceAmbientOcclusionRenderer {
         server: &dyn GraphicsServer,
         frame_width: usize,
         frame_height: usize,
+        pixel_kind: PixelKind,
     ) -> Result<Self, FrameworkError> {
         // It is good balance between quality and performance, no need to do SSAO in full resolution.
         // This SSAO map size reduction was taken from DOOM (2016).
         let width = (frame_width / 2).max(1);
         let height = (frame_height / 2).max(1);
 
-        let occlusion = server.create_2d_render_target(PixelKind::R32F, width, height)?;
+        let occlusion = server.create_2d_render_target(pixel_kind, width, height)?;
 
         let mut rng = crate::rand::thread_rng();
 
         Ok(Self {
-            blur: Blur::new(server, width, height)?,
+            blur: Blur::new(server, width, height, pixel_kind)?,
             program: RenderPassContainer::from_str(server, include_str!("../shaders/ssao.shader"))?,
-            framebuffer: server.create_frame_buffer(
-                None,
-                vec![Attachment {
-                    kind: AttachmentKind::Color,
-                    texture: occlusion,
-                }],
-            )?,
+            framebuffer: server.create_frame_buffer(None, vec![Attachment::color(occlusion)])?,
             quad: GpuGeometryBuffer::from_surface_data(
                 &SurfaceData::make_unit_xy_quad(),
                 BufferUsage::StaticDraw,
@@ -149,7 +144,11 @@ impl ScreenSpaceAmbientOcclusionRenderer {
         self.radius = radius.abs();
     }
 
-    fn raw_ao_map(&self) -> GpuTexture {
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    pub(crate) fn raw_ao_map(&self) -> GpuTexture {
         self.framebuffer.color_attachments()[0].texture.clone()
     }
 
@@ -0,0 +1,278 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Screen-space global illumination (SSGI) renderer. See [`super::SsgiSettings`] docs for more
+//! info.
+//!
+//! The renderer works in three steps:
+//!
+//! 1) Trace - traces a few rays per pixel in reduced resolution against the depth buffer and
+//! fetches the lit color of the hit surfaces from the frame with direct lighting.
+//! 2) Temporal accumulation - reprojects the accumulated result of the previous frames and blends
+//! it with the noisy result of the current frame.
+//! 3) Composite - modulates the accumulated bounce lighting with the albedo of the surfaces and
+//! adds it to the frame.
+
+use crate::{
+    core::{
+        algebra::Matrix4,
+        color::Color,
+        math::{Matrix4Ext, Rect},
+        sstorage::ImmutableString,
+    },
+    renderer::{
+        cache::{
+            shader::{binding, property, PropertyGroup, RenderMaterial, RenderPassContainer},
+            uniform::UniformBufferCache,
+        },
+        framework::{
+            error::FrameworkError,
+            framebuffer::{Attachment, GpuFrameBuffer},
+            geometry_buffer::GpuGeometryBuffer,
+            gpu_texture::{GpuTexture, MagnificationFilter, MinificationFilter, PixelKind},
+            server::GraphicsServer,
+        },
+        gbuffer::GBuffer,
+        make_viewport_matrix,
+        temporal::TemporalFrame,
+        RenderPassStatistics, SsgiQuality, SsgiSettings,
+    },
+};
+
+pub(crate) struct SsgiRenderContext<'a> {
+    pub gbuffer: &'a GBuffer,
+    /// Frame buffer with direct lighting, the bounce lighting is added to it.
+    pub frame_buffer: &'a GpuFrameBuffer,
+    pub ao_map: &'a GpuTexture,
+    pub quad: &'a GpuGeometryBuffer,
+    pub projection_matrix: Matrix4<f32>,
+    pub view_matrix: Matrix4<f32>,
+    pub view_projection_matrix: Matrix4<f32>,
+    pub temporal_frame: TemporalFrame,
+    pub settings: &'a SsgiSettings,
+    pub uniform_buffer_cache: &'a mut UniformBufferCache,
+}
+
+pub struct ScreenSpaceGlobalIlluminationRenderer {
+    trace_shader: RenderPassContainer,
+    temporal_shader: RenderPassContainer,
+    composite_shader: RenderPassContainer,
+    trace_framebuffer: GpuFrameBuffer,
+    // Ping-pong pair of frame buffers with accumulated lighting. Alpha channel stores the distance
+    // from the camera to the pixel, that is used to detect disocclusions.
+    history: [GpuFrameBuffer; 2],
+    current_history: usize,
+    quality: SsgiQuality,
+    width: i32,
+    height: i32,
+}
+
+fn make_target(
+    server: &dyn GraphicsServer,
+    width: usize,
+    height: usize,
+) -> Result<GpuFrameBuffer, FrameworkError> {
+    let texture = server.create_2d_render_target(PixelKind::RGBA16F, width, height)?;
+    // Results are upsampled to the full resolution, bilinear filtration hides blockiness.
+    texture.set_minification_filter(MinificationFilter::Linear);
+    texture.set_magnification_filter(MagnificationFilter::Linear);
+    server.create_frame_buffer(None, vec![Attachment::color(texture)])
+}
+
+impl ScreenSpaceGlobalIlluminationRenderer {
+    pub fn new(
+        server: &dyn GraphicsServer,
+        frame_width: usize,
+        frame_height: usize,
+        quality: SsgiQuality,
+    ) -> Result<Self, FrameworkError> {
+        let divisor = quality.resolution_divisor();
+        let width = (frame_width / divisor).max(1);
+        let height = (frame_height / divisor).max(1);
+
+        let history = [
+            make_target(server, width, height)?,
+            make_target(server, width, height)?,
+        ];
+        let viewport = Rect::new(0, 0, width as i32, height as i32);
+        for framebuffer in history.iter() {
+            framebuffer.clear(viewport, Some(Color::TRANSPARENT), None, None);
+        }
+
+        Ok(Self {
+            trace_shader: RenderPassContainer::from_str(
+                server,
+                include_str!("shaders/ssgi_trace.shader"),
+            )?,
+            temporal_shader: RenderPassContainer::from_str(
+                server,
+                include_str!("shaders/ssgi_temporal.shader"),
+            )?,
+            composite_shader: RenderPassContainer::from_str(
+                server,
+                include_str!("shaders/ssgi_composite.shader"),
+            )?,
+            trace_framebuffer: make_target(server, width, height)?,
+            history,
+            current_history: 0,
+            quality,
+            width: width as i32,
+            height: height as i32,
+        })
+    }
+
+    pub fn quality(&self) -> SsgiQuality {
+        self.quality
+    }
+
+    /// Returns a texture with accumulated bounce lighting.
+    pub fn result(&self) -> &GpuTexture {
+        &self.history[self.current_history].color_attachments()[0].texture
+    }
+
+    pub(crate) fn render(
+        &mut self,
+        ctx: SsgiRenderContext,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let SsgiRenderContext {
+            gbuffer,
+            frame_buffer,
+            ao_map,
+            quad,
+            projection_matrix,
+            view_matrix,
+            view_projection_matrix,
+            temporal_frame,
+            settings,
+            uniform_buffer_cache,
+        } = ctx;
+
+        let mut stats = RenderPassStatistics::default();
+
+        let viewport = Rect::new(0, 0, self.width, self.height);
+        let frame_matrix = make_viewport_matrix(viewport);
+
+        // Trace.
+        let frame_texture = &frame_buffer.color_attachments()[0].texture;
+        let inverse_projection = projection_matrix.try_inverse().unwrap_or_default();
+        let view_basis = view_matrix.basis();
+        let frame_index = (temporal_frame.frame_index % 1024) as f32;
+        let properties = PropertyGroup::from([
+            property("worldViewProjection", &frame_matrix),
+            property("inverseProjectionMatrix", &inverse_projection),
+            property("projectionMatrix", &projection_matrix),
+            property("viewMatrix", &view_basis),
+            property("maxDistance", &settings.max_distance),
+            property("thickness", &settings.thickness),
+            property("frameIndex", &frame_index),
+            property("rayCount", &self.quality.ray_count()),
+            property("stepCount", &self.quality.step_count()),
+        ]);
+        let material = RenderMaterial::from([
+            binding("depthSampler", gbuffer.depth()),
+            binding("normalSampler", gbuffer.normal_texture()),
+            binding("frameSampler", frame_texture),
+            binding("properties", &properties),
+        ]);
+        stats += self.trace_shader.run_pass(
+            1,
+            &ImmutableString::new("Primary"),
+            &self.trace_framebuffer,
+            quad,
+            viewport,
+            &material,
+            uniform_buffer_cache,
+            Default::default(),
+            None,
+        )?;
+
+        // Temporal accumulation.
+        let previous_history = self.current_history;
+        self.current_history = (self.current_history + 1) % self.history.len();
+        let history_weight = if temporal_frame.history_valid {
+            settings.temporal_blend.clamp(0.0, 0.99)
+        } else {
+            0.0
+        };
+        let inverse_view_projection = view_projection_matrix.try_inverse().unwrap_or_default();
+        let properties = PropertyGroup::from([
+            property("worldViewProjection", &frame_matrix),
+            property("inverseViewProjectionMatrix", &inverse_view_projection),
+            property("viewMatrix", &view_matrix),
+            property("previousViewMatrix", &temporal_frame.previous_view_matrix),
+            property(
+                "previousViewProjectionMatrix",
+                &temporal_frame.previous_view_projection_matrix,
+            ),
+            property("historyWeight", &history_weight),
+        ]);
+        let material = RenderMaterial::from([
+            binding(
+                "currentSampler",
+                &self.trace_framebuffer.color_attachments()[0].texture,
+            ),
+            binding(
+                "historySampler",
+                &self.history[previous_history].color_attachments()[0].texture,
+            ),
+            binding("depthSampler", gbuffer.depth()),
+            binding("properties", &properties),
+        ]);
+        stats += self.temporal_shader.run_pass(
+            1,
+            &ImmutableString::new("Primary"),
+            &self.history[self.current_history],
+            quad,
+            viewport,
+            &material,
+            uniform_buffer_cache,
+            Default::default(),
+            None,
+        )?;
+
+        // Composite.
+        let frame_viewport = Rect::new(0, 0, gbuffer.width, gbuffer.height);
+        let frame_matrix = make_viewport_matrix(frame_viewport);
+        let properties = PropertyGroup::from([
+            property("worldViewProjection", &frame_matrix),
+            property("intensity", &settings.intensity),
+        ]);
+        let material = RenderMaterial::from([
+            binding("giSampler", self.result()),
+            binding("diffuseTexture", gbuffer.diffuse_texture()),
+            binding("materialTexture", gbuffer.material_texture()),
+            binding("aoSampler", ao_map),
+            binding("properties", &properties),
+        ]);
+        stats += self.composite_shader.run_pass(
+            1,
+            &ImmutableString::new("Primary"),
+            frame_buffer,
+            quad,
+            frame_viewport,
+            &material,
+            uniform_buffer_cache,
+            Default::default(),
+            None,
+        )?;
+
+        Ok(stats)
+    }
+}
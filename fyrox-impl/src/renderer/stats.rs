@@ -140,6 +140,10 @@ pub struct Statistics {
     pub frames_per_second: usize,
     /// Total amount of textures in the textures cache.
     pub texture_cache_size: usize,
+    /// Approximate amount of GPU memory (in bytes) occupied by the textures in the textures cache.
+    pub texture_memory_usage: usize,
+    /// Total amount of textures, that are waiting for upload to GPU memory or being uploaded.
+    pub pending_texture_uploads: usize,
     /// Total amount of vertex+index buffers pairs in the geometry cache.
     pub geometry_cache_size: usize,
     /// Total amount of shaders in the shaders cache.
@@ -168,6 +172,8 @@ impl Display for Statistics {
         let lighting_stats = &self.lighting;
         let pipeline_stats = &self.pipeline;
         let texture_cache_size = self.texture_cache_size;
+        let texture_memory_usage = self.texture_memory_usage as f32 / (1024.0 * 1024.0);
+        let pending_texture_uploads = self.pending_texture_uploads;
         let geometry_cache_size = self.geometry_cache_size;
         let shader_cache_size = self.shader_cache_size;
         let uniform_buffer_cache_size = self.uniform_buffer_cache_size;
@@ -180,6 +186,8 @@ impl Display for Statistics {
             {lighting_stats}\n\
             {pipeline_stats}\n\
             Texture Cache Size: {texture_cache_size}\n\
+            Texture Memory Usage: {texture_memory_usage:.2} Mb\n\
+            Pending Texture Uploads: {pending_texture_uploads}\n\
             Geometry Cache Size: {geometry_cache_size}\n\
             Shader Cache Size: {shader_cache_size}\n
             Uniform Buffer Cache Size: {uniform_buffer_cache_size}\n",
@@ -0,0 +1,247 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Temporal anti-aliasing (TAA) renderer. See [`super::TaaSettings`] docs for more info.
+//!
+//! The renderer works in two steps:
+//!
+//! 1) Resolve - reprojects the accumulated frame of the previous frames using per-pixel motion
+//! vectors, clamps it to the color range of the neighborhood of each pixel and blends it with the
+//! current (jittered) frame.
+//! 2) Sharpen - applies unsharp mask to the accumulated frame and writes the result back to the
+//! frame.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Vector2},
+        color::Color,
+        math::Rect,
+        sstorage::ImmutableString,
+    },
+    renderer::{
+        cache::{
+            shader::{binding, property, PropertyGroup, RenderMaterial, RenderPassContainer},
+            uniform::UniformBufferCache,
+        },
+        framework::{
+            error::FrameworkError,
+            framebuffer::{Attachment, GpuFrameBuffer},
+            geometry_buffer::GpuGeometryBuffer,
+            gpu_texture::{GpuTexture, MagnificationFilter, MinificationFilter, PixelKind},
+            server::GraphicsServer,
+        },
+        gbuffer::GBuffer,
+        make_viewport_matrix,
+        temporal::TemporalFrame,
+        RenderPassStatistics, TaaSettings,
+    },
+};
+
+/// Amount of unique sub-pixel offsets of the projection matrix.
+const JITTER_SEQUENCE_LENGTH: u64 = 8;
+
+fn halton(mut index: u64, base: u64) -> f32 {
+    let mut fraction = 1.0;
+    let mut result = 0.0;
+    while index > 0 {
+        fraction /= base as f32;
+        result += fraction * (index % base) as f32;
+        index /= base;
+    }
+    result
+}
+
+/// Calculates sub-pixel offset of the projection matrix for the given frame in normalized device
+/// coordinates. Offsets are taken from Halton (2, 3) sequence, which gives evenly distributed
+/// samples inside a pixel.
+pub fn jitter_offset(frame_index: u64, viewport: Rect<i32>, scale: f32) -> Vector2<f32> {
+    // Skip the first element, which is always zero.
+    let index = frame_index % JITTER_SEQUENCE_LENGTH + 1;
+    let x = halton(index, 2) - 0.5;
+    let y = halton(index, 3) - 0.5;
+    Vector2::new(
+        2.0 * scale * x / viewport.w().max(1) as f32,
+        2.0 * scale * y / viewport.h().max(1) as f32,
+    )
+}
+
+pub(crate) struct TaaRenderContext<'a> {
+    pub gbuffer: &'a GBuffer,
+    /// Frame with aliased image, the anti-aliased image is written back to it.
+    pub frame_buffer: &'a GpuFrameBuffer,
+    pub viewport: Rect<i32>,
+    pub quad: &'a GpuGeometryBuffer,
+    /// View-projection matrix of the camera with jitter.
+    pub view_projection_matrix: Matrix4<f32>,
+    pub jitter: Vector2<f32>,
+    pub temporal_frame: TemporalFrame,
+    pub settings: &'a TaaSettings,
+    pub uniform_buffer_cache: &'a mut UniformBufferCache,
+}
+
+pub struct TemporalAntiAliasingRenderer {
+    resolve_shader: RenderPassContainer,
+    sharpen_shader: RenderPassContainer,
+    // Ping-pong pair of frame buffers with accumulated frames.
+    history: [GpuFrameBuffer; 2],
+    current_history: usize,
+    has_history: bool,
+}
+
+fn make_target(
+    server: &dyn GraphicsServer,
+    width: usize,
+    height: usize,
+) -> Result<GpuFrameBuffer, FrameworkError> {
+    let texture = server.create_2d_render_target(PixelKind::RGBA16F, width, height)?;
+    // History is fetched at sub-pixel positions.
+    texture.set_minification_filter(MinificationFilter::Linear);
+    texture.set_magnification_filter(MagnificationFilter::Linear);
+    server.create_frame_buffer(None, vec![Attachment::color(texture)])
+}
+
+impl TemporalAntiAliasingRenderer {
+    pub fn new(
+        server: &dyn GraphicsServer,
+        width: usize,
+        height: usize,
+    ) -> Result<Self, FrameworkError> {
+        let history = [
+            make_target(server, width, height)?,
+            make_target(server, width, height)?,
+        ];
+        let viewport = Rect::new(0, 0, width as i32, height as i32);
+        for framebuffer in history.iter() {
+            framebuffer.clear(viewport, Some(Color::TRANSPARENT), None, None);
+        }
+
+        Ok(Self {
+            resolve_shader: RenderPassContainer::from_str(
+                server,
+                include_str!("shaders/taa_resolve.shader"),
+            )?,
+            sharpen_shader: RenderPassContainer::from_str(
+                server,
+                include_str!("shaders/taa_sharpen.shader"),
+            )?,
+            history,
+            current_history: 0,
+            has_history: false,
+        })
+    }
+
+    /// Returns a texture with accumulated (anti-aliased, but not sharpened) frame.
+    pub fn result(&self) -> &GpuTexture {
+        &self.history[self.current_history].color_attachments()[0].texture
+    }
+
+    pub(crate) fn render(
+        &mut self,
+        ctx: TaaRenderContext,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let TaaRenderContext {
+            gbuffer,
+            frame_buffer,
+            viewport,
+            quad,
+            view_projection_matrix,
+            jitter,
+            temporal_frame,
+            settings,
+            uniform_buffer_cache,
+        } = ctx;
+
+        let mut stats = RenderPassStatistics::default();
+
+        let frame_matrix = make_viewport_matrix(viewport);
+        let inverse_screen_size =
+            Vector2::new(1.0 / viewport.w() as f32, 1.0 / viewport.h() as f32);
+
+        // Resolve.
+        let previous_history = self.current_history;
+        self.current_history = (self.current_history + 1) % self.history.len();
+        let history_weight = if temporal_frame.history_valid && self.has_history {
+            settings.history_weight.clamp(0.0, 0.98)
+        } else {
+            0.0
+        };
+        let inverse_view_projection = view_projection_matrix.try_inverse().unwrap_or_default();
+        let properties = PropertyGroup::from([
+            property("worldViewProjection", &frame_matrix),
+            property("inverseViewProjectionMatrix", &inverse_view_projection),
+            property(
+                "previousViewProjectionMatrix",
+                &temporal_frame.previous_view_projection_matrix,
+            ),
+            property("jitter", &jitter),
+            property("inverseScreenSize", &inverse_screen_size),
+            property("historyWeight", &history_weight),
+        ]);
+        let material = RenderMaterial::from([
+            binding(
+                "currentSampler",
+                &frame_buffer.color_attachments()[0].texture,
+            ),
+            binding(
+                "historySampler",
+                &self.history[previous_history].color_attachments()[0].texture,
+            ),
+            binding("motionSampler", gbuffer.motion_texture()),
+            binding("depthSampler", gbuffer.depth()),
+            binding("properties", &properties),
+        ]);
+        stats += self.resolve_shader.run_pass(
+            1,
+            &ImmutableString::new("Primary"),
+            &self.history[self.current_history],
+            quad,
+            viewport,
+            &material,
+            uniform_buffer_cache,
+            Default::default(),
+            None,
+        )?;
+        self.has_history = true;
+
+        // Sharpen.
+        let sharpness = settings.sharpness.max(0.0);
+        let properties = PropertyGroup::from([
+            property("worldViewProjection", &frame_matrix),
+            property("inverseScreenSize", &inverse_screen_size),
+            property("sharpness", &sharpness),
+        ]);
+        let material = RenderMaterial::from([
+            binding("frameSampler", self.result()),
+            binding("properties", &properties),
+        ]);
+        stats += self.sharpen_shader.run_pass(
+            1,
+            &ImmutableString::new("Primary"),
+            frame_buffer,
+            quad,
+            viewport,
+            &material,
+            uniform_buffer_cache,
+            Default::default(),
+            None,
+        )?;
+
+        Ok(stats)
+    }
+}
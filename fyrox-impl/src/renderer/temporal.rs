@@ -0,0 +1,99 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Temporal reprojection allows render passes to reuse the results of previous frames (history).
+//! It keeps the camera matrices of the previous frame, that could be used to find where a pixel
+//! of the current frame was located on screen in the previous frame.
+
+use crate::{
+    core::{algebra::Matrix4, math::Rect, pool::Handle},
+    scene::node::Node,
+};
+
+/// Camera state of the previous frame, that is used for temporal reprojection.
+#[derive(Copy, Clone, Debug)]
+pub struct TemporalFrame {
+    /// View matrix of the camera in the previous frame.
+    pub previous_view_matrix: Matrix4<f32>,
+    /// View-projection matrix of the camera in the previous frame.
+    pub previous_view_projection_matrix: Matrix4<f32>,
+    /// `true` if the history of previous frame is valid and could be used. History is invalid on
+    /// the first frame, when the camera has changed or when the viewport has changed.
+    pub history_valid: bool,
+    /// Index of the current frame. Could be used to vary sampling patterns from frame to frame.
+    pub frame_index: u64,
+}
+
+struct PreviousFrame {
+    camera: Handle<Node>,
+    viewport: Rect<i32>,
+    view_matrix: Matrix4<f32>,
+    view_projection_matrix: Matrix4<f32>,
+}
+
+/// Tracks camera state between frames. See module docs for more info.
+#[derive(Default)]
+pub struct TemporalReprojection {
+    previous: Option<PreviousFrame>,
+    frame_index: u64,
+}
+
+impl TemporalReprojection {
+    /// Remembers the state of the given camera and returns the state of the camera from the
+    /// previous frame. Must be called once per frame.
+    pub fn advance(
+        &mut self,
+        camera: Handle<Node>,
+        viewport: Rect<i32>,
+        view_matrix: Matrix4<f32>,
+        view_projection_matrix: Matrix4<f32>,
+    ) -> TemporalFrame {
+        let frame = match self.previous.as_ref() {
+            Some(previous) => TemporalFrame {
+                previous_view_matrix: previous.view_matrix,
+                previous_view_projection_matrix: previous.view_projection_matrix,
+                history_valid: previous.camera == camera && previous.viewport == viewport,
+                frame_index: self.frame_index,
+            },
+            None => TemporalFrame {
+                previous_view_matrix: view_matrix,
+                previous_view_projection_matrix: view_projection_matrix,
+                history_valid: false,
+                frame_index: self.frame_index,
+            },
+        };
+
+        self.previous = Some(PreviousFrame {
+            camera,
+            viewport,
+            view_matrix,
+            view_projection_matrix,
+        });
+        self.frame_index = self.frame_index.wrapping_add(1);
+
+        frame
+    }
+
+    /// Discards the state of the previous frame, which makes the history invalid for the next
+    /// frame.
+    pub fn reset(&mut self) {
+        self.previous = None;
+    }
+}
@@ -301,6 +301,7 @@ impl UiRenderer {
                 }),
                 stencil_op: Default::default(),
                 scissor_box,
+                attachment_blend: Default::default(),
             };
 
             let solid_color = match cmd.brush {
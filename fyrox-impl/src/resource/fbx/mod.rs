@@ -432,6 +432,7 @@ async fn create_surfaces(
                                 property_name,
                                 MaterialResourceBinding::Texture(MaterialTextureBinding {
                                     value: Some(texture),
+                                    sampler: None,
                                 }),
                             );
                         }
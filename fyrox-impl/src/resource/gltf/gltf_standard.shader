@@ -86,6 +86,13 @@
             ]),
             binding: 2
         ),
+        (
+            name: "fyrox_previousBoneMatrices",
+            kind: PropertyGroup([
+                // Autogenerated
+            ]),
+            binding: 6
+        ),
         (
             name: "fyrox_graphicsSettings",
             kind: PropertyGroup([
@@ -107,6 +114,13 @@
             ]),
             binding: 5
         ),
+        (
+            name: "fyrox_instances",
+            kind: PropertyGroup([
+                // Autogenerated
+            ]),
+            binding: 7
+        ),
     ],
 
     passes: [
@@ -148,10 +162,13 @@
                 out vec3 tangent;
                 out vec3 binormal;
                 out vec2 secondTexCoord;
+                out vec4 clipPosition;
+                out vec4 previousClipPosition;
 
                 void main()
                 {
                     vec4 localPosition = vec4(0);
+                    vec4 previousLocalPosition = vec4(0);
                     vec3 localNormal = vec3(0);
                     vec3 localTangent = vec3(0);
 
@@ -184,6 +201,16 @@
                         localPosition += m2 * inputPosition * boneWeights.z;
                         localPosition += m3 * inputPosition * boneWeights.w;
 
+                        mat4 p0 = fyrox_previousBoneMatrices.matrices[i0];
+                        mat4 p1 = fyrox_previousBoneMatrices.matrices[i1];
+                        mat4 p2 = fyrox_previousBoneMatrices.matrices[i2];
+                        mat4 p3 = fyrox_previousBoneMatrices.matrices[i3];
+
+                        previousLocalPosition += p0 * inputPosition * boneWeights.x;
+                        previousLocalPosition += p1 * inputPosition * boneWeights.y;
+                        previousLocalPosition += p2 * inputPosition * boneWeights.z;
+                        previousLocalPosition += p3 * inputPosition * boneWeights.w;
+
                         localNormal += mat3(m0) * inputNormal * boneWeights.x;
                         localNormal += mat3(m1) * inputNormal * boneWeights.y;
                         localNormal += mat3(m2) * inputNormal * boneWeights.z;
@@ -197,19 +224,22 @@
                     else
                     {
                         localPosition = inputPosition;
+                        previousLocalPosition = inputPosition;
                         localNormal = inputNormal;
                         localTangent = inputTangent;
                     }
 
-                    mat3 nm = mat3(fyrox_instanceData.worldMatrix);
+                    mat3 nm = mat3(fyrox_instances.worldMatrices[gl_InstanceID]);
                     normal = normalize(nm * localNormal);
                     tangent = normalize(nm * localTangent);
                     binormal = normalize(vertexTangent.w * cross(normal, tangent));
                     texCoord = vertexTexCoord;
-                    position = vec3(fyrox_instanceData.worldMatrix * localPosition);
+                    position = vec3(fyrox_instances.worldMatrices[gl_InstanceID] * localPosition);
                     secondTexCoord = vertexSecondTexCoord;
 
-                    gl_Position = fyrox_instanceData.worldViewProjection * localPosition;
+                    gl_Position = fyrox_instances.worldViewProjections[gl_InstanceID] * localPosition;
+                    clipPosition = gl_Position;
+                    previousClipPosition = fyrox_instances.previousWorldViewProjections[gl_InstanceID] * previousLocalPosition;
                 }
                 "#,
             fragment_shader:
@@ -219,6 +249,7 @@
                 layout(location = 2) out vec4 outAmbient;
                 layout(location = 3) out vec4 outMaterial;
                 layout(location = 4) out uint outDecalMask;
+                layout(location = 5) out vec4 outMotion;
 
                 in vec3 position;
                 in vec3 normal;
@@ -226,9 +257,13 @@
                 in vec3 tangent;
                 in vec3 binormal;
                 in vec2 secondTexCoord;
+                in vec4 clipPosition;
+                in vec4 previousClipPosition;
 
                 void main()
                 {
+                    if (S_LodFadeDiscard(fyrox_instanceData.lodFade, gl_FragCoord.xy)) discard;
+
                     mat3 tangentSpace = mat3(tangent, binormal, normal);
                     vec3 toFragment = normalize(position - fyrox_cameraData.position);
 
@@ -266,6 +301,8 @@
                     outAmbient.a = 1.0;
 
                     outDecalMask = properties.layerIndex;
+
+                    outMotion = vec4(S_MotionVector(clipPosition, previousClipPosition, fyrox_cameraData.jitter), 1.0, 1.0);
                 }
                 "#,
         ),
@@ -345,7 +382,7 @@
                     {
                         localPosition = inputPosition;
                     }
-                    gl_Position = fyrox_instanceData.worldViewProjection * localPosition;
+                    gl_Position = fyrox_instances.worldViewProjections[gl_InstanceID] * localPosition;
                     texCoord = vertexTexCoord;
                 }
                "#,
@@ -358,6 +395,8 @@
 
                 void main()
                 {
+                    if (S_LodFadeDiscard(fyrox_instanceData.lodFade, gl_FragCoord.xy)) discard;
+
                     FragColor = properties.diffuseColor * texture(diffuseTexture, texCoord);
                 }
                "#,
@@ -426,7 +465,7 @@
                         localPosition = inputPosition;
                     }
 
-                    gl_Position = fyrox_instanceData.worldViewProjection * localPosition;
+                    gl_Position = fyrox_instances.worldViewProjections[gl_InstanceID] * localPosition;
                     texCoord = vertexTexCoord;
                 }
                 "#,
@@ -437,6 +476,8 @@
 
                 void main()
                 {
+                    if (S_LodFadeDiscard(fyrox_instanceData.lodFade, gl_FragCoord.xy)) discard;
+
                     if (texture(diffuseTexture, texCoord).a < 0.2) discard;
                 }
                 "#,
@@ -505,7 +546,7 @@
                         localPosition = inputPosition;
                     }
 
-                    gl_Position = fyrox_instanceData.worldViewProjection * localPosition;
+                    gl_Position = fyrox_instances.worldViewProjections[gl_InstanceID] * localPosition;
                     texCoord = vertexTexCoord;
                 }
                 "#,
@@ -516,6 +557,8 @@
 
                 void main()
                 {
+                    if (S_LodFadeDiscard(fyrox_instanceData.lodFade, gl_FragCoord.xy)) discard;
+
                     if (texture(diffuseTexture, texCoord).a < 0.2) discard;
                 }
                 "#,
@@ -585,8 +628,8 @@
                         localPosition = inputPosition;
                     }
 
-                    gl_Position = fyrox_instanceData.worldViewProjection * localPosition;
-                    worldPosition = (fyrox_instanceData.worldMatrix * localPosition).xyz;
+                    gl_Position = fyrox_instances.worldViewProjections[gl_InstanceID] * localPosition;
+                    worldPosition = (fyrox_instances.worldMatrices[gl_InstanceID] * localPosition).xyz;
                     texCoord = vertexTexCoord;
                 }
                 "#,
@@ -600,6 +643,8 @@
 
                 void main()
                 {
+                    if (S_LodFadeDiscard(fyrox_instanceData.lodFade, gl_FragCoord.xy)) discard;
+
                     if (texture(diffuseTexture, texCoord).a < 0.2) discard;
                     depth = length(fyrox_lightData.lightPosition - worldPosition);
                 }
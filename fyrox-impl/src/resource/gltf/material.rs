@@ -248,7 +248,7 @@ fn set_texture(
         .clone();
     material.bind(
         name,
-        MaterialResourceBinding::Texture(MaterialTextureBinding { value: Some(tex) }),
+        MaterialResourceBinding::Texture(MaterialTextureBinding::new(Some(tex))),
     );
     Ok(())
 }
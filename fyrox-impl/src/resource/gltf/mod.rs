@@ -331,6 +331,9 @@ async fn load(
     scene.graph[root].set_name(root_name.clone());
     import_from_path(&mut scene.graph, &context).await?;
     node_names::resolve_name_conflicts(context.model_path.as_path(), &mut scene.graph);
+    if let Some(lod_generation) = options.lod_generation.as_ref() {
+        lod_generation.apply(&mut scene.graph);
+    }
     Ok(Model::new(NodeMapping::UseNames, scene))
 }
 
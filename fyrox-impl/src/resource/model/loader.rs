@@ -24,7 +24,8 @@ use crate::{
     asset::{
         io::ResourceIo,
         loader::{
-            BoxedImportOptionsLoaderFuture, BoxedLoaderFuture, LoaderPayload, ResourceLoader,
+            BoxedImportOptionsLoaderFuture, BoxedLoaderFuture, ContentSignature, LoaderPayload,
+            ResourceLoader,
         },
         manager::ResourceManager,
         options::{try_get_import_settings, try_get_import_settings_opaque, BaseImportOptions},
@@ -36,6 +37,12 @@ use crate::{
 use fyrox_resource::state::LoadError;
 use std::{path::PathBuf, sync::Arc};
 
+/// Signatures of binary and ASCII FBX files.
+pub(crate) const FBX_SIGNATURES: [ContentSignature; 2] = [
+    ContentSignature::new(b"Kaydara FBX Binary"),
+    ContentSignature::new(b"; FBX"),
+];
+
 /// Default implementation for model loading.
 pub struct ModelLoader {
     /// Resource manager to allow complex model loading.
@@ -52,6 +59,12 @@ impl ResourceLoader for ModelLoader {
         &["rgs", "fbx"]
     }
 
+    fn content_signatures(&self) -> &[ContentSignature] {
+        // Native scenes share their signature with every other resource that is stored using
+        // `Visitor`, so they are recognized only by their extension.
+        &FBX_SIGNATURES
+    }
+
     fn data_type_uuid(&self) -> Uuid {
         Model::type_uuid()
     }
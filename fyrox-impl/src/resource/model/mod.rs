@@ -47,7 +47,7 @@ use crate::{
         algebra::{UnitQuaternion, Vector3},
         log::{Log, MessageKind},
         pool::Handle,
-        reflect::prelude::*,
+        reflect::{prelude::*, SetFieldByPathError},
         uuid::Uuid,
         uuid_provider,
         variable::InheritableVariable,
@@ -59,7 +59,15 @@ use crate::{
     graph::{BaseSceneGraph, NodeHandleMap, NodeMapping, PrefabData, SceneGraph, SceneGraphNode},
     resource::fbx::{self, error::FbxError},
     scene::{
-        animation::Animation, base::SceneNodeId, graph::Graph, node::Node, transform::Transform,
+        animation::Animation,
+        base::{BaseBuilder, LevelOfDetail, LodGroup, LodMetric, SceneNodeId},
+        graph::Graph,
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceResource},
+            Mesh, MeshBuilder,
+        },
+        node::Node,
+        transform::Transform,
         Scene, SceneLoader,
     },
 };
@@ -107,12 +115,32 @@ impl TypeUuidProvider for Model {
     }
 }
 
+/// A property override, that will be applied to a node of a prefab instance.
+#[derive(Debug)]
+pub struct PropertyOverride {
+    /// A path to a node relative to the root of the instance. It consists of node names separated
+    /// by `/`, empty path means the root node of the instance. The root of the instance is a copy
+    /// of the root of the prefab scene, so the path starts with the name of a top-level node of
+    /// the prefab, for example `Player/Body/Weapon`. Nodes of nested prefabs are addressed the same
+    /// way, since they're part of the hierarchy.
+    pub node_path: String,
+    /// A path to a property of the node, for example `name` or `local_transform.position`.
+    pub property_path: String,
+    /// A new value of the property.
+    pub value: Box<dyn Reflect>,
+}
+
+/// An alias for [`InstantiationContext`], that emphasizes its usage as a builder of prefab
+/// instances with property overrides.
+pub type PrefabInstanceBuilder<'a> = InstantiationContext<'a>;
+
 /// Instantiation context holds additional data that could be useful for a prefab instantiation.
 pub struct InstantiationContext<'a> {
     model: &'a ModelResource,
     dest_scene: &'a mut Scene,
     local_transform: Option<Transform>,
     ids: Option<&'a FxHashMap<Handle<Node>, SceneNodeId>>,
+    overrides: Vec<PropertyOverride>,
 }
 
 impl<'a> InstantiationContext<'a> {
@@ -159,6 +187,50 @@ impl<'a> InstantiationContext<'a> {
         self
     }
 
+    /// Adds a property override, that will be applied to a node of the instance. See
+    /// [`PropertyOverride`] docs for more info about the paths. Overridden properties are marked
+    /// as modified, which means that they will be preserved when the instance is synced with its
+    /// prefab (for example, when the prefab was changed). This is the same as modifying a
+    /// property of an instance in the editor.
+    ///
+    /// ```rust ,no_run
+    /// # use fyrox_impl::{
+    /// #     core::algebra::Vector3,
+    /// #     resource::model::{ModelResource, ModelResourceExtension},
+    /// #     scene::Scene,
+    /// # };
+    /// fn instantiate(prefab: &ModelResource, scene: &mut Scene) {
+    ///     prefab
+    ///         .begin_instantiation(scene)
+    ///         .with_property_override("Player", "tag", "RedTeam".to_string())
+    ///         .with_property_override("Player/Body/Weapon", "visibility", false)
+    ///         .with_position(Vector3::new(1.0, 0.0, 2.0))
+    ///         .finish();
+    /// }
+    /// ```
+    pub fn with_property_override<T: Reflect>(
+        mut self,
+        node_path: impl Into<String>,
+        property_path: impl Into<String>,
+        value: T,
+    ) -> Self {
+        self.overrides.push(PropertyOverride {
+            node_path: node_path.into(),
+            property_path: property_path.into(),
+            value: Box::new(value),
+        });
+        self
+    }
+
+    /// Adds a set of property overrides. See [`Self::with_property_override`] for more info.
+    pub fn with_property_overrides(
+        mut self,
+        overrides: impl IntoIterator<Item = PropertyOverride>,
+    ) -> Self {
+        self.overrides.extend(overrides);
+        self
+    }
+
     /// Finishes instantiation.
     pub fn finish(self) -> Handle<Node> {
         let model = self.model.clone();
@@ -192,10 +264,66 @@ impl<'a> InstantiationContext<'a> {
         // Explicitly mark as root node.
         self.dest_scene.graph[root].is_resource_instance_root = true;
 
+        for property_override in self.overrides {
+            apply_property_override(&mut self.dest_scene.graph, root, property_override);
+        }
+
         root
     }
 }
 
+fn find_node_by_path(graph: &Graph, root: Handle<Node>, path: &str) -> Handle<Node> {
+    let mut handle = root;
+    for name in path.split('/').filter(|name| !name.is_empty()) {
+        let Some(child) = graph[handle]
+            .children()
+            .iter()
+            .find(|child| graph[**child].name() == name)
+        else {
+            return Handle::NONE;
+        };
+        handle = *child;
+    }
+    handle
+}
+
+fn apply_property_override(
+    graph: &mut Graph,
+    root: Handle<Node>,
+    property_override: PropertyOverride,
+) {
+    let PropertyOverride {
+        node_path,
+        property_path,
+        value,
+    } = property_override;
+
+    let handle = find_node_by_path(graph, root, &node_path);
+    let Some(node) = graph.try_get_mut(handle) else {
+        Log::err(format!(
+            "Unable to override property {property_path}: there's no node at {node_path} path!"
+        ));
+        return;
+    };
+
+    (node as &mut dyn Reflect).set_field_by_path(
+        &property_path,
+        value,
+        &mut |result| match result {
+            Ok(_) => (),
+            Err(SetFieldByPathError::InvalidPath { reason, .. }) => Log::err(format!(
+                "Unable to override property {property_path} of node {node_path}! \
+            Invalid path {reason:?}!"
+            )),
+            Err(SetFieldByPathError::InvalidValue(value)) => Log::err(format!(
+                "Unable to override property {property_path} of node {node_path}! \
+            Incompatible types {}!",
+                value.type_name()
+            )),
+        },
+    );
+}
+
 /// Common trait that has animation retargetting methods.
 pub trait AnimationSource {
     /// Prefab type.
@@ -495,6 +623,7 @@ impl ModelResourceExtension for ModelResource {
             dest_scene,
             local_transform: None,
             ids: None,
+            overrides: Default::default(),
         }
     }
 
@@ -712,11 +841,143 @@ impl MaterialSearchOptions {
 /// ```
 ///
 /// Check documentation of the field of the structure for more info about each parameter.
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default, Reflect, Eq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default, Reflect)]
 pub struct ModelImportOptions {
     /// See [`MaterialSearchOptions`] docs for more info.
     #[serde(default)]
     pub material_search_options: MaterialSearchOptions,
+    /// Optional settings for automatic generation of levels of detail. See [`LodGenerationOptions`]
+    /// docs for more info.
+    #[serde(default)]
+    pub lod_generation: Option<LodGenerationOptions>,
+}
+
+/// A single level of detail, that will be generated at import stage.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default, Reflect)]
+pub struct GeneratedLodLevel {
+    /// Portion of the triangles of the source mesh, that should be left in the level. Must be in
+    /// `0.0..=1.0` range.
+    #[reflect(min_value = 0.0, max_value = 1.0)]
+    pub triangle_ratio: f32,
+    /// Beginning of the range of the level (in normalized units, see [`LevelOfDetail`] docs for
+    /// more info). The range of the level ends where the next level begins.
+    #[reflect(min_value = 0.0, max_value = 1.0)]
+    pub begin: f32,
+}
+
+/// A set of options for automatic generation of levels of detail for every mesh of a model. The
+/// generation is done by simplification of the surfaces of the source mesh. Every mesh gets a
+/// [`LodGroup`] with the full-detail level (`LOD0` child node) and a child node for every generated
+/// level (`LOD1`, `LOD2`, etc.). Skinned meshes and meshes with blend shapes are not processed.
+///
+/// ```text
+/// (
+///     material_search_options: RecursiveUp,
+///     lod_generation: Some((
+///         levels: [
+///             (triangle_ratio: 0.5, begin: 0.25),
+///             (triangle_ratio: 0.2, begin: 0.5),
+///         ],
+///         metric: Distance,
+///         crossfade: 0.05,
+///     )),
+/// )
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default, Reflect)]
+pub struct LodGenerationOptions {
+    /// Levels that will be generated in addition to the full-detail level. Levels must be sorted
+    /// by [`GeneratedLodLevel::begin`].
+    pub levels: Vec<GeneratedLodLevel>,
+    /// See [`LodMetric`] docs for more info.
+    #[serde(default)]
+    pub metric: LodMetric,
+    /// See [`LodGroup::crossfade`] docs for more info.
+    #[serde(default)]
+    #[reflect(min_value = 0.0, max_value = 1.0)]
+    pub crossfade: f32,
+}
+
+uuid_provider!(GeneratedLodLevel = "5a0b7c52-3c5e-4d8f-9a4b-0b1f6f5e2d71");
+uuid_provider!(LodGenerationOptions = "e6b2a1de-7c1f-4a59-8d3e-2f3c9b0a4e18");
+
+impl LodGenerationOptions {
+    pub(crate) fn apply(&self, graph: &mut Graph) {
+        if self.levels.is_empty() {
+            return;
+        }
+
+        let meshes = graph
+            .pair_iter()
+            .filter_map(|(handle, node)| {
+                let mesh = node.cast::<Mesh>()?;
+                (!mesh.surfaces().is_empty()
+                    && mesh.blend_shapes().is_empty()
+                    && mesh.surfaces().iter().all(|s| s.bones().is_empty()))
+                .then_some(handle)
+            })
+            .collect::<Vec<_>>();
+
+        for handle in meshes {
+            let Some(mesh) = graph[handle].cast_mut::<Mesh>() else {
+                continue;
+            };
+            let name = mesh.name_owned();
+            let render_path = mesh.render_path();
+            let cast_shadows = mesh.cast_shadows();
+            let surfaces = mesh.set_surfaces(Vec::new());
+
+            let mut levels = Vec::with_capacity(self.levels.len() + 1);
+            let mut lod_surfaces = vec![surfaces.clone()];
+            for level in self.levels.iter() {
+                let mut simplified = Vec::with_capacity(surfaces.len());
+                for surface in surfaces.iter() {
+                    let data = surface.data();
+                    let data = data.data_ref();
+                    match data.simplify(level.triangle_ratio) {
+                        Ok(data) => simplified.push(
+                            SurfaceBuilder::new(SurfaceResource::new_ok(
+                                ResourceKind::Embedded,
+                                data,
+                            ))
+                            .with_material(surface.material().clone())
+                            .build(),
+                        ),
+                        Err(err) => Log::err(format!(
+                            "Unable to generate a level of detail for {name} mesh. Reason: {err:?}"
+                        )),
+                    }
+                }
+                lod_surfaces.push(simplified);
+            }
+
+            for (i, surfaces) in lod_surfaces.into_iter().enumerate() {
+                let begin = if i == 0 {
+                    0.0
+                } else {
+                    self.levels[i - 1].begin
+                };
+                let end = self.levels.get(i).map_or(1.0, |level| level.begin);
+
+                let lod = MeshBuilder::new(
+                    BaseBuilder::new()
+                        .with_name(format!("{name}_LOD{i}"))
+                        .with_cast_shadows(cast_shadows),
+                )
+                .with_surfaces(surfaces)
+                .with_render_path(render_path)
+                .build(graph);
+                graph.link_nodes(lod, handle);
+
+                levels.push(LevelOfDetail::new(begin, end, vec![lod]));
+            }
+
+            graph[handle].set_lod_group(Some(LodGroup {
+                levels,
+                metric: self.metric,
+                crossfade: self.crossfade,
+            }));
+        }
+    }
 }
 
 impl ImportOptions for ModelImportOptions {}
@@ -759,6 +1020,27 @@ impl From<VisitError> for ModelLoadError {
     }
 }
 
+/// Detects the format of a model file by its first bytes and returns the usual extension of
+/// the format.
+async fn detect_format(path: &Path, io: &dyn ResourceIo) -> Option<&'static str> {
+    use crate::asset::loader::CONTENT_HEADER_SIZE;
+    use std::io::Read;
+
+    let reader = io.file_reader(path).await.ok()?;
+    let mut header = Vec::with_capacity(CONTENT_HEADER_SIZE);
+    reader
+        .take(CONTENT_HEADER_SIZE as u64)
+        .read_to_end(&mut header)
+        .ok()?;
+    if loader::FBX_SIGNATURES.iter().any(|s| s.matches(&header)) {
+        Some("fbx")
+    } else if header.starts_with(Visitor::MAGIC.as_bytes()) {
+        Some("rgs")
+    } else {
+        None
+    }
+}
+
 impl Model {
     /// Creates a new Model instance using the given node mapping and the given scene. It could be
     /// used to create your own Model resources.
@@ -773,13 +1055,20 @@ impl Model {
         resource_manager: ResourceManager,
         model_import_options: ModelImportOptions,
     ) -> Result<Self, ModelLoadError> {
-        let extension = path
+        let mut extension = path
             .as_ref()
             .extension()
             .unwrap_or_default()
             .to_string_lossy()
             .as_ref()
             .to_lowercase();
+        if extension != "fbx" && extension != "rgs" {
+            // The loader could be picked by the content of the file, use the content to find
+            // out the actual format.
+            if let Some(detected) = detect_format(path.as_ref(), io).await {
+                extension = detected.to_string();
+            }
+        }
         let (scene, mapping) = match extension.as_ref() {
             "fbx" => {
                 let mut scene = Scene::new();
@@ -795,6 +1084,9 @@ impl Model {
                     &model_import_options,
                 )
                 .await?;
+                if let Some(lod_generation) = model_import_options.lod_generation.as_ref() {
+                    lod_generation.apply(&mut scene.graph);
+                }
                 // Set NodeMapping::UseNames as mapping here because FBX does not have
                 // any persistent unique ids, and we have to use names.
                 (scene, NodeMapping::UseNames)
@@ -842,3 +1134,56 @@ impl Model {
         &mut self.scene
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::untyped::ResourceKind,
+        core::algebra::Vector3,
+        graph::NodeMapping,
+        resource::model::{Model, ModelResource, ModelResourceExtension},
+        scene::{base::BaseBuilder, pivot::PivotBuilder, Scene},
+    };
+
+    #[test]
+    fn test_property_overrides() {
+        let mut prefab_scene = Scene::new();
+        let child =
+            PivotBuilder::new(BaseBuilder::new().with_name("Child")).build(&mut prefab_scene.graph);
+        PivotBuilder::new(BaseBuilder::new().with_name("Root").with_children(&[child]))
+            .build(&mut prefab_scene.graph);
+        let prefab = ModelResource::new_ok(
+            ResourceKind::Embedded,
+            Model::new(NodeMapping::UseNames, prefab_scene),
+        );
+
+        let mut scene = Scene::new();
+        let instance = prefab
+            .begin_instantiation(&mut scene)
+            .with_property_override("Root/Child", "tag", "Overridden".to_string())
+            .with_property_override("Root", "visibility", false)
+            .with_property_override("Root/Missing", "tag", "Ignored".to_string())
+            .with_position(Vector3::new(1.0, 2.0, 3.0))
+            .finish();
+
+        let root = scene.graph[instance].children()[0];
+        assert!(!scene.graph[root].visibility());
+        let child = scene.graph[root].children()[0];
+        assert_eq!(scene.graph[child].tag(), "Overridden");
+
+        // Change the prefab and sync the instance with it, the overrides must be preserved.
+        {
+            let mut data = prefab.data_ref();
+            let graph = &mut data.get_scene_mut().graph;
+            for (name, tag) in [("Root", "FromPrefab"), ("Child", "FromPrefab")] {
+                let handle = graph.find_by_name_from_root(name).unwrap().0;
+                graph[handle].set_tag(tag.to_string());
+            }
+        }
+        scene.graph.resolve();
+
+        assert!(!scene.graph[root].visibility());
+        assert_eq!(scene.graph[root].tag(), "FromPrefab");
+        assert_eq!(scene.graph[child].tag(), "Overridden");
+    }
+}
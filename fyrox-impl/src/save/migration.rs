@@ -0,0 +1,213 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Migrations of save games between different versions of a game. See [`SaveMigrations`] docs for
+//! more info.
+
+use crate::save::{SaveGame, SaveGameError};
+
+type MigrationFn = dyn Fn(&mut SaveGame) -> Result<(), String> + Send + Sync;
+
+struct Migration {
+    version: u32,
+    func: Box<MigrationFn>,
+}
+
+/// A set of migrations, that convert save games created by older versions of a game to the
+/// current version. Every migration has a target version and converts the save game from the
+/// previous version to it. When a save game is migrated, every migration with the target version
+/// in `(saved version; current version]` range is applied in ascending order of the versions.
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::{uuid::{uuid, Uuid}, visitor::prelude::*},
+/// #     save::SaveMigrations,
+/// # };
+/// #[derive(Visit, Default)]
+/// struct PlayerV1 {
+///     health: f32,
+/// }
+///
+/// #[derive(Visit, Default)]
+/// struct PlayerV2 {
+///     hit_points: u32,
+/// }
+///
+/// const PLAYER_UUID: Uuid = uuid!("c5671d19-9f1a-4286-8486-add4ebaadaec");
+///
+/// fn migrations() -> SaveMigrations {
+///     SaveMigrations::new().with_migration(2, |save_game| {
+///         for script in save_game.scripts_mut(PLAYER_UUID) {
+///             script
+///                 .convert(|old: PlayerV1| PlayerV2 {
+///                     hit_points: old.health as u32,
+///                 })
+///                 .map_err(|err| err.to_string())?;
+///         }
+///         Ok(())
+///     })
+/// }
+/// ```
+#[derive(Default)]
+pub struct SaveMigrations {
+    migrations: Vec<Migration>,
+}
+
+impl SaveMigrations {
+    /// Creates an empty set of migrations.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a migration, that converts save games to the given version.
+    pub fn add<F>(&mut self, version: u32, func: F)
+    where
+        F: Fn(&mut SaveGame) -> Result<(), String> + Send + Sync + 'static,
+    {
+        let position = self
+            .migrations
+            .partition_point(|migration| migration.version <= version);
+        self.migrations.insert(
+            position,
+            Migration {
+                version,
+                func: Box::new(func),
+            },
+        );
+    }
+
+    /// Adds a migration, that converts save games to the given version.
+    pub fn with_migration<F>(mut self, version: u32, func: F) -> Self
+    where
+        F: Fn(&mut SaveGame) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.add(version, func);
+        self
+    }
+
+    /// Converts the given save game to the given version. Fails if the save game was created by a
+    /// newer version of the game, or if a migration has failed. The save game is left in the
+    /// state of the last successful migration in case of failure.
+    pub fn migrate(&self, save_game: &mut SaveGame, current: u32) -> Result<(), SaveGameError> {
+        if save_game.version > current {
+            return Err(SaveGameError::UnsupportedVersion {
+                saved: save_game.version,
+                current,
+            });
+        }
+
+        for migration in self.migrations.iter() {
+            if migration.version > save_game.version && migration.version <= current {
+                (migration.func)(save_game).map_err(|reason| SaveGameError::Migration {
+                    version: migration.version,
+                    reason,
+                })?;
+                save_game.version = migration.version;
+            }
+        }
+
+        save_game.version = current;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{uuid::Uuid, visitor::prelude::*},
+        save::{NodeState, SaveGame, SaveGameError, SaveMigrations, ScriptState},
+    };
+
+    #[derive(Visit, Default, Debug, PartialEq)]
+    struct OldLayout {
+        health: f32,
+    }
+
+    #[derive(Visit, Default, Debug, PartialEq)]
+    struct NewLayout {
+        hit_points: u32,
+        armor: u32,
+    }
+
+    fn save_game(version: u32) -> SaveGame {
+        let type_uuid = Uuid::new_v4();
+        let mut script = ScriptState {
+            type_uuid,
+            data: Default::default(),
+        };
+        script.write(OldLayout { health: 42.0 }).unwrap();
+        SaveGame {
+            version,
+            destroyed: Default::default(),
+            spawned: Default::default(),
+            nodes: vec![NodeState {
+                id: Uuid::new_v4(),
+                visibility: true,
+                scripts: vec![script],
+                ..Default::default()
+            }],
+        }
+    }
+
+    #[test]
+    fn test_migrations() {
+        let mut save_game = save_game(1);
+        let type_uuid = save_game.nodes[0].scripts[0].type_uuid;
+
+        let migrations = SaveMigrations::new()
+            .with_migration(3, |save_game| {
+                for script in save_game.scripts_mut(Uuid::nil()) {
+                    script.data.clear();
+                }
+                assert_eq!(save_game.version, 2);
+                Ok(())
+            })
+            .with_migration(2, move |save_game| {
+                for script in save_game.scripts_mut(type_uuid) {
+                    script
+                        .convert(|old: OldLayout| NewLayout {
+                            hit_points: old.health as u32,
+                            armor: 0,
+                        })
+                        .map_err(|err| err.to_string())?;
+                }
+                Ok(())
+            })
+            .with_migration(4, |_| Err("must not be applied".to_string()));
+
+        migrations.migrate(&mut save_game, 3).unwrap();
+        assert_eq!(save_game.version, 3);
+        assert_eq!(
+            save_game.nodes[0].scripts[0].read::<NewLayout>().unwrap(),
+            NewLayout {
+                hit_points: 42,
+                armor: 0
+            }
+        );
+
+        assert!(matches!(
+            migrations.migrate(&mut save_game, 2),
+            Err(SaveGameError::UnsupportedVersion {
+                saved: 3,
+                current: 2
+            })
+        ));
+    }
+}
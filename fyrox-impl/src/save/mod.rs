@@ -0,0 +1,815 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Save game system built on top of [`Visit`] trait. A save game stores the state of persistent
+//! scene nodes (see [`Base::is_persistent`](crate::scene::base::Base::is_persistent)) and the
+//! difference between the current scene and its initial state (destroyed and spawned nodes).
+//!
+//! ## Capturing and applying
+//!
+//! A save game is always captured relative to a [`SceneBaseline`], which is a set of nodes of a
+//! freshly loaded scene. The following data is stored:
+//!
+//! - Local transform, visibility, enabled flag and the data of every script of every persistent
+//! node. Scripts are serialized using their [`Visit`] implementations, so the fields marked with
+//! `#[visit(skip)]` are not stored. Handles of persistent nodes (including the nodes of spawned
+//! instances), that are stored in scripts, are remapped to the new handles of the nodes when the
+//! save game is applied.
+//! - Ids of the nodes from the baseline, that do not exist anymore.
+//! - Persistent prefab instances, that were spawned at runtime. Only a path to the prefab and the
+//! ids of the instance nodes are stored, the instance is re-created from the prefab when the save
+//! game is applied. Persistent nodes, that were created from code, cannot be restored and are
+//! skipped.
+//!
+//! To load a save game, load the same scene from its file, capture the baseline and call
+//! [`SaveGame::apply`]. Keep the baseline alive for the next saves: it should always describe
+//! the initial state of the scene, not the state after applying a save game.
+//!
+//! ```rust,no_run
+//! # use fyrox_impl::{
+//! #     asset::manager::ResourceManager,
+//! #     save::{SaveGame, SaveGameError, SaveMigrations, SceneBaseline},
+//! #     scene::Scene,
+//! # };
+//! const SAVE_VERSION: u32 = 2;
+//!
+//! fn save(scene: &mut Scene, baseline: &SceneBaseline) -> Result<(), SaveGameError> {
+//!     SaveGame::capture(scene, baseline, SAVE_VERSION)?.save("save.bin")
+//! }
+//!
+//! async fn load(
+//!     scene: &mut Scene,
+//!     resource_manager: &ResourceManager,
+//!     migrations: &SaveMigrations,
+//! ) -> Result<SceneBaseline, SaveGameError> {
+//!     // The scene must be freshly loaded at this point.
+//!     let baseline = SceneBaseline::new(scene);
+//!     let mut save_game = SaveGame::load("save.bin").await?;
+//!     migrations.migrate(&mut save_game, SAVE_VERSION)?;
+//!     save_game.apply(scene, resource_manager).await?;
+//!     Ok(baseline)
+//! }
+//! ```
+//!
+//! ## Versioning
+//!
+//! Every save game has a version, that is defined by the game. When the layout of a script changes
+//! after a game update, increase the version and register a migration, that converts the old data
+//! to the new layout. See [`SaveMigrations`] docs for more info. Fields, that were added to a script
+//! and have no data in a save game, keep the values from the scene.
+
+mod migration;
+
+pub use migration::SaveMigrations;
+
+use crate::{
+    asset::{manager::ResourceManager, untyped::UntypedResource},
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        log::Log,
+        pool::Handle,
+        reflect::Reflect,
+        uuid::Uuid,
+        visitor::{prelude::*, PodVecView, VisitorFlags},
+    },
+    fxhash::{FxHashMap, FxHashSet},
+    graph::{BaseSceneGraph, NodeHandleMap, SceneGraph},
+    resource::model::{Model, ModelResourceExtension},
+    scene::{base::SceneNodeId, node::Node, Scene},
+};
+use std::{
+    any::TypeId,
+    error::Error,
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Version of the layout of save game files. It is not related to [`SaveGame::version`], which is
+/// defined by a game.
+const FORMAT_VERSION: u32 = 1;
+
+/// An error that may occur when capturing, applying or migrating a save game.
+#[derive(Debug)]
+pub enum SaveGameError {
+    /// Serialization or deserialization error.
+    Visit(VisitError),
+    /// The save game has the version, that is newer than the version of the game.
+    UnsupportedVersion {
+        /// Version of the save game.
+        saved: u32,
+        /// Current version of the game.
+        current: u32,
+    },
+    /// A prefab of a spawned instance cannot be loaded.
+    PrefabLoad {
+        /// Path of the prefab.
+        path: PathBuf,
+        /// Description of the error.
+        reason: String,
+    },
+    /// A migration has failed.
+    Migration {
+        /// Target version of the migration.
+        version: u32,
+        /// Description of the error.
+        reason: String,
+    },
+}
+
+impl Display for SaveGameError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Visit(err) => write!(f, "Save game serialization error: {err}"),
+            Self::UnsupportedVersion { saved, current } => write!(
+                f,
+                "Save game version {saved} is newer than the current version {current}"
+            ),
+            Self::PrefabLoad { path, reason } => write!(
+                f,
+                "Unable to load prefab {} of a spawned instance: {reason}",
+                path.display()
+            ),
+            Self::Migration { version, reason } => {
+                write!(
+                    f,
+                    "Save game migration to version {version} failed: {reason}"
+                )
+            }
+        }
+    }
+}
+
+impl Error for SaveGameError {}
+
+impl From<VisitError> for SaveGameError {
+    fn from(err: VisitError) -> Self {
+        Self::Visit(err)
+    }
+}
+
+/// A set of nodes of a freshly loaded scene. It is used to find nodes, that were destroyed or
+/// spawned at runtime.
+#[derive(Default, Debug, Clone)]
+pub struct SceneBaseline {
+    ids: FxHashSet<SceneNodeId>,
+}
+
+impl SceneBaseline {
+    /// Captures the baseline of the given scene. The scene must be in its initial state (as it was
+    /// loaded from its file), otherwise spawned nodes won't be stored in save games.
+    pub fn new(scene: &Scene) -> Self {
+        Self {
+            ids: scene
+                .graph
+                .pair_iter()
+                .map(|(_, node)| node.instance_id())
+                .collect(),
+        }
+    }
+
+    /// Returns `true` if the node with the given id is a part of the initial state of the scene.
+    pub fn contains(&self, id: SceneNodeId) -> bool {
+        self.ids.contains(&id)
+    }
+}
+
+/// Serialized data of a script.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct ScriptState {
+    /// Type uuid of the script (see [`crate::script::BaseScript::id`]).
+    pub type_uuid: Uuid,
+    /// Serialized data of the script.
+    pub data: Vec<u8>,
+}
+
+impl Visit for ScriptState {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut region = visitor.enter_region(name)?;
+        self.type_uuid.visit("TypeUuid", &mut region)?;
+        PodVecView::from_pod_vec(&mut self.data).visit("Data", &mut region)?;
+        Ok(())
+    }
+}
+
+impl ScriptState {
+    fn capture<T: Visit + ?Sized>(script: &mut T, type_uuid: Uuid) -> Result<Self, VisitError> {
+        let mut visitor = Visitor::new();
+        visitor.flags = VisitorFlags::SERIALIZE_EVERYTHING;
+        script.visit("Data", &mut visitor)?;
+        Ok(Self {
+            type_uuid,
+            data: visitor.save_binary_to_vec()?,
+        })
+    }
+
+    fn restore<T: Visit + ?Sized>(
+        &self,
+        script: &mut T,
+        resource_manager: &ResourceManager,
+    ) -> Result<(), VisitError> {
+        let mut visitor = Visitor::load_from_memory(&self.data)?;
+        visitor.flags = VisitorFlags::IGNORE_FIELD_ERRORS;
+        visitor
+            .blackboard
+            .register(Arc::new(resource_manager.clone()));
+        script.visit("Data", &mut visitor)
+    }
+
+    /// Deserializes the data of the script into a value of the given type. It is useful in
+    /// migrations, to read the data using an old layout of a script.
+    pub fn read<T: Visit + Default>(&self) -> Result<T, VisitError> {
+        let mut visitor = Visitor::load_from_memory(&self.data)?;
+        let mut value = T::default();
+        value.visit("Data", &mut visitor)?;
+        Ok(value)
+    }
+
+    /// Replaces the data of the script with the serialized data of the given value.
+    pub fn write<T: Visit>(&mut self, mut value: T) -> Result<(), VisitError> {
+        self.data = Self::capture(&mut value, self.type_uuid)?.data;
+        Ok(())
+    }
+
+    /// Converts the data of the script from one layout to another.
+    ///
+    /// ```rust
+    /// # use fyrox_impl::{core::visitor::prelude::*, save::ScriptState};
+    /// #[derive(Visit, Default)]
+    /// struct PlayerV1 {
+    ///     health: f32,
+    /// }
+    ///
+    /// #[derive(Visit, Default)]
+    /// struct PlayerV2 {
+    ///     health: f32,
+    ///     max_health: f32,
+    /// }
+    ///
+    /// fn migrate(state: &mut ScriptState) -> Result<(), VisitError> {
+    ///     state.convert(|old: PlayerV1| PlayerV2 {
+    ///         health: old.health,
+    ///         max_health: 100.0,
+    ///     })
+    /// }
+    /// ```
+    pub fn convert<Old, New, F>(&mut self, func: F) -> Result<(), VisitError>
+    where
+        Old: Visit + Default,
+        New: Visit,
+        F: FnOnce(Old) -> New,
+    {
+        let old = self.read::<Old>()?;
+        self.write(func(old))
+    }
+}
+
+/// Saved state of a persistent node.
+#[derive(Default, Debug, Clone, PartialEq, Visit)]
+pub struct NodeState {
+    /// Instance id of the node.
+    pub id: Uuid,
+    /// Local position of the node.
+    pub position: Vector3<f32>,
+    /// Local rotation of the node.
+    pub rotation: UnitQuaternion<f32>,
+    /// Local scale of the node.
+    pub scale: Vector3<f32>,
+    /// Visibility of the node.
+    pub visibility: bool,
+    /// Enabled flag of the node.
+    pub enabled: bool,
+    /// Serialized scripts of the node in the same order as on the node.
+    pub scripts: Vec<ScriptState>,
+    /// Handle of the node at the moment of capturing. It is used to remap the handles stored in
+    /// scripts.
+    #[visit(optional)]
+    pub handle: Handle<Node>,
+}
+
+impl NodeState {
+    fn capture(node: &mut Node, handle: Handle<Node>) -> Result<Self, VisitError> {
+        let transform = node.local_transform();
+        let mut state = Self {
+            handle,
+            id: node.instance_id().0,
+            position: **transform.position(),
+            rotation: **transform.rotation(),
+            scale: **transform.scale(),
+            visibility: node.visibility(),
+            enabled: node.is_enabled(),
+            scripts: Default::default(),
+        };
+        for index in 0..node.script_count() {
+            if let Some(script) = node.script_mut(index) {
+                let type_uuid = script.id();
+                state
+                    .scripts
+                    .push(ScriptState::capture(&mut **script, type_uuid)?);
+            }
+        }
+        Ok(state)
+    }
+
+    fn apply(
+        &self,
+        node: &mut Node,
+        old_new_map: &NodeHandleMap<Node>,
+        resource_manager: &ResourceManager,
+    ) {
+        let name = node.name_owned();
+        let transform = node.local_transform_mut();
+        transform.set_position(self.position);
+        transform.set_rotation(self.rotation);
+        transform.set_scale(self.scale);
+        node.set_visibility(self.visibility);
+        node.set_enabled(self.enabled);
+
+        for (index, state) in self.scripts.iter().enumerate() {
+            let Some(script) = node.script_mut(index) else {
+                Log::warn(format!(
+                    "Unable to restore script {index} of node {}: there's no such script!",
+                    self.id
+                ));
+                continue;
+            };
+            if script.id() != state.type_uuid {
+                Log::warn(format!(
+                    "Unable to restore script {index} of node {}: type mismatch!",
+                    self.id
+                ));
+                continue;
+            }
+            if let Err(err) = state.restore(&mut **script, resource_manager) {
+                Log::err(format!(
+                    "Unable to restore script {index} of node {}: {err}",
+                    self.id
+                ));
+                continue;
+            }
+            script.as_reflect_mut(&mut |script| {
+                old_new_map.remap_entity_handles(script, &name, &[TypeId::of::<UntypedResource>()])
+            });
+        }
+    }
+}
+
+/// A pair of a node handle in a prefab and the instance id of its copy.
+#[derive(Default, Debug, Clone, PartialEq, Visit)]
+pub struct SpawnedNodeId {
+    /// Handle of the node in the prefab.
+    pub original: Handle<Node>,
+    /// Instance id of the copy of the node.
+    pub id: Uuid,
+    /// Handle of the copy of the node at the moment of capturing. It is used to remap the handles
+    /// stored in scripts.
+    #[visit(optional)]
+    pub handle: Handle<Node>,
+}
+
+/// A prefab instance, that was spawned at runtime.
+#[derive(Default, Debug, Clone, PartialEq, Visit)]
+pub struct SpawnedInstance {
+    /// Path of the prefab.
+    pub prefab: PathBuf,
+    /// Instance id of the parent node. Nil uuid means the root of the scene.
+    pub parent: Uuid,
+    /// Instance ids of every node of the instance.
+    pub ids: Vec<SpawnedNodeId>,
+}
+
+/// Saved state of a scene. See the [module docs](self) for more info.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct SaveGame {
+    /// Version of the save game defined by the game. See [`SaveMigrations`] for more info.
+    pub version: u32,
+    /// Instance ids of the baseline nodes, that were destroyed.
+    pub destroyed: Vec<Uuid>,
+    /// Prefab instances, that were spawned at runtime, parents go before their children.
+    pub spawned: Vec<SpawnedInstance>,
+    /// The state of every persistent node.
+    pub nodes: Vec<NodeState>,
+}
+
+impl Visit for SaveGame {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut region = visitor.enter_region(name)?;
+
+        let mut format_version = FORMAT_VERSION;
+        format_version.visit("FormatVersion", &mut region)?;
+        if format_version > FORMAT_VERSION {
+            return Err(VisitError::User(format!(
+                "Unsupported save game format version {format_version}!"
+            )));
+        }
+
+        self.version.visit("Version", &mut region)?;
+        self.destroyed.visit("Destroyed", &mut region)?;
+        self.spawned.visit("Spawned", &mut region)?;
+        self.nodes.visit("Nodes", &mut region)?;
+
+        Ok(())
+    }
+}
+
+impl SaveGame {
+    /// Captures the state of the given scene relative to its initial state. `version` is the
+    /// current version of the save data of the game.
+    pub fn capture(
+        scene: &mut Scene,
+        baseline: &SceneBaseline,
+        version: u32,
+    ) -> Result<Self, SaveGameError> {
+        let graph = &mut scene.graph;
+
+        let mut save_game = Self {
+            version,
+            ..Default::default()
+        };
+
+        let alive = graph
+            .pair_iter()
+            .map(|(_, node)| node.instance_id())
+            .collect::<FxHashSet<_>>();
+        save_game.destroyed = baseline
+            .ids
+            .iter()
+            .filter(|id| !alive.contains(id))
+            .map(|id| id.0)
+            .collect();
+
+        let root = graph.get_root();
+        let persistent = graph
+            .traverse_iter(root)
+            .filter(|(handle, node)| *handle != root && node.is_persistent())
+            .map(|(handle, _)| handle)
+            .collect::<Vec<_>>();
+
+        // Nodes, that are parts of the captured spawned instances.
+        let mut covered = FxHashSet::default();
+        for &handle in persistent.iter() {
+            let node = &graph[handle];
+            let id = node.instance_id();
+            if baseline.contains(id) || covered.contains(&id) {
+                continue;
+            }
+
+            let prefab = node
+                .resource()
+                .filter(|_| node.is_resource_instance_root())
+                .and_then(|resource| resource.kind().into_path());
+            let Some(prefab) = prefab else {
+                Log::warn(format!(
+                    "Node {} was spawned from code and cannot be stored in a save game!",
+                    node.name()
+                ));
+                continue;
+            };
+
+            let resource = node.resource();
+            let mut ids = Vec::new();
+            for (descendant_handle, descendant) in graph.traverse_iter(handle) {
+                if descendant.resource() == resource {
+                    covered.insert(descendant.instance_id());
+                    ids.push(SpawnedNodeId {
+                        original: descendant.original_handle_in_resource(),
+                        id: descendant.instance_id().0,
+                        handle: descendant_handle,
+                    });
+                }
+            }
+
+            let parent = node.parent();
+            save_game.spawned.push(SpawnedInstance {
+                prefab,
+                parent: if parent == root {
+                    Uuid::nil()
+                } else {
+                    graph[parent].instance_id().0
+                },
+                ids,
+            });
+        }
+
+        for handle in persistent {
+            let node = &mut graph[handle];
+            if !baseline.contains(node.instance_id()) && !covered.contains(&node.instance_id()) {
+                continue;
+            }
+            save_game.nodes.push(NodeState::capture(node, handle)?);
+        }
+
+        Ok(save_game)
+    }
+
+    /// Applies the save game to the given scene. The scene must be in its initial state (as it was
+    /// loaded from its file). The prefabs of spawned instances are loaded using the given resource
+    /// manager.
+    pub async fn apply(
+        &self,
+        scene: &mut Scene,
+        resource_manager: &ResourceManager,
+    ) -> Result<(), SaveGameError> {
+        let mut prefabs = Vec::with_capacity(self.spawned.len());
+        for spawned in self.spawned.iter() {
+            let prefab = resource_manager
+                .request::<Model>(&spawned.prefab)
+                .await
+                .map_err(|err| SaveGameError::PrefabLoad {
+                    path: spawned.prefab.clone(),
+                    reason: format!("{err:?}"),
+                })?;
+            prefabs.push(prefab);
+        }
+
+        let mut handles = scene
+            .graph
+            .pair_iter()
+            .map(|(handle, node)| (node.instance_id().0, handle))
+            .collect::<FxHashMap<_, _>>();
+
+        for id in self.destroyed.iter() {
+            if let Some(handle) = handles.remove(id) {
+                if scene.graph.is_valid_handle(handle) {
+                    scene.graph.remove_node(handle);
+                }
+            }
+        }
+
+        for (spawned, prefab) in self.spawned.iter().zip(prefabs) {
+            let parent = if spawned.parent.is_nil() {
+                scene.graph.get_root()
+            } else if let Some(parent) = handles.get(&spawned.parent) {
+                *parent
+            } else {
+                Log::warn(format!(
+                    "Unable to restore an instance of {}: parent {} does not exist!",
+                    spawned.prefab.display(),
+                    spawned.parent
+                ));
+                continue;
+            };
+
+            let ids = spawned
+                .ids
+                .iter()
+                .map(|id| (id.original, SceneNodeId(id.id)))
+                .collect::<FxHashMap<_, _>>();
+            let instance = prefab.begin_instantiation(scene).with_ids(&ids).finish();
+            scene.graph.link_nodes(instance, parent);
+
+            for handle in scene
+                .graph
+                .traverse_handle_iter(instance)
+                .collect::<Vec<_>>()
+            {
+                handles.insert(scene.graph[handle].instance_id().0, handle);
+            }
+        }
+
+        // Scripts store the handles of the scene, in which the save game was captured. The nodes of
+        // spawned instances have new handles (as well as the nodes of the scene, if its layout was
+        // changed), so the handles are remapped using instance ids. Other handles stay the same.
+        let mut old_new_map = NodeHandleMap::default();
+        for &handle in handles.values() {
+            old_new_map.insert(handle, handle);
+        }
+        let old_handles = self
+            .spawned
+            .iter()
+            .flat_map(|spawned| spawned.ids.iter().map(|id| (id.handle, id.id)))
+            .chain(self.nodes.iter().map(|state| (state.handle, state.id)));
+        for (old_handle, id) in old_handles {
+            if let Some(new_handle) = handles.get(&id).filter(|_| old_handle.is_some()) {
+                old_new_map.insert(old_handle, *new_handle);
+            }
+        }
+
+        for state in self.nodes.iter() {
+            match handles
+                .get(&state.id)
+                .and_then(|handle| scene.graph.try_get_mut(*handle))
+            {
+                Some(node) => state.apply(node, &old_new_map, resource_manager),
+                None => Log::warn(format!(
+                    "Unable to restore the state of node {}: there's no such node!",
+                    state.id
+                )),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns an iterator over the scripts of the given type. It is useful in migrations.
+    pub fn scripts_mut(&mut self, type_uuid: Uuid) -> impl Iterator<Item = &mut ScriptState> {
+        self.nodes
+            .iter_mut()
+            .flat_map(|node| node.scripts.iter_mut())
+            .filter(move |script| script.type_uuid == type_uuid)
+    }
+
+    /// Serializes the save game into a vector of bytes.
+    pub fn save_to_vec(&mut self) -> Result<Vec<u8>, SaveGameError> {
+        let mut visitor = Visitor::new();
+        self.visit("SaveGame", &mut visitor)?;
+        Ok(visitor.save_binary_to_vec()?)
+    }
+
+    /// Saves the save game to the given file.
+    pub fn save(&mut self, path: impl AsRef<Path>) -> Result<(), SaveGameError> {
+        let mut visitor = Visitor::new();
+        self.visit("SaveGame", &mut visitor)?;
+        Ok(visitor.save_binary(path)?)
+    }
+
+    /// Deserializes a save game from the given bytes.
+    pub fn load_from_memory(data: &[u8]) -> Result<Self, SaveGameError> {
+        let mut visitor = Visitor::load_from_memory(data)?;
+        let mut save_game = Self::default();
+        save_game.visit("SaveGame", &mut visitor)?;
+        Ok(save_game)
+    }
+
+    /// Loads a save game from the given file.
+    pub async fn load(path: impl AsRef<Path>) -> Result<Self, SaveGameError> {
+        let mut visitor = Visitor::load_binary(path).await?;
+        let mut save_game = Self::default();
+        save_game.visit("SaveGame", &mut visitor)?;
+        Ok(save_game)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::manager::ResourceManager,
+        core::{
+            algebra::Vector3, futures::executor::block_on, pool::Handle, reflect::prelude::*,
+            type_traits::prelude::*, uuid::Uuid, visitor::prelude::*,
+        },
+        graph::{BaseSceneGraph, SceneGraph},
+        save::{SaveGame, SceneBaseline, SpawnedInstance},
+        scene::{
+            base::{BaseBuilder, SceneNodeId},
+            node::Node,
+            pivot::PivotBuilder,
+            Scene,
+        },
+        script::ScriptTrait,
+    };
+    use std::sync::Arc;
+
+    #[derive(Clone, Debug, Default, Reflect, Visit, TypeUuidProvider, ComponentProvider)]
+    #[type_uuid(id = "5b0d7a37-8a0e-4a43-8d4c-0c1f7f3e5a21")]
+    struct Follower {
+        target: Handle<Node>,
+    }
+
+    impl ScriptTrait for Follower {}
+
+    // Emulates loading of the same scene from a file, the nodes have the same ids every time.
+    fn make_scene(ids: &[Uuid; 2]) -> Scene {
+        let mut scene = Scene::new();
+        PivotBuilder::new(
+            BaseBuilder::new()
+                .with_name("Player")
+                .with_persistent(true)
+                .with_instance_id(SceneNodeId(ids[0])),
+        )
+        .build(&mut scene.graph);
+        PivotBuilder::new(
+            BaseBuilder::new()
+                .with_name("Enemy")
+                .with_instance_id(SceneNodeId(ids[1])),
+        )
+        .build(&mut scene.graph);
+        scene
+    }
+
+    #[test]
+    fn test_capture_and_apply() {
+        let ids = [Uuid::new_v4(), Uuid::new_v4()];
+
+        let mut scene = make_scene(&ids);
+        let baseline = SceneBaseline::new(&scene);
+
+        let (player, _) = scene.graph.find_by_name_from_root("Player").unwrap();
+        scene.graph[player]
+            .local_transform_mut()
+            .set_position(Vector3::new(1.0, 2.0, 3.0));
+        scene.graph[player].set_visibility(false);
+        let (enemy, _) = scene.graph.find_by_name_from_root("Enemy").unwrap();
+        scene.graph.remove_node(enemy);
+
+        let mut save_game = SaveGame::capture(&mut scene, &baseline, 1).unwrap();
+        assert_eq!(save_game.destroyed, vec![ids[1]]);
+        assert!(save_game.spawned.is_empty());
+        assert_eq!(save_game.nodes.len(), 1);
+        assert_eq!(save_game.nodes[0].id, ids[0]);
+
+        let data = save_game.save_to_vec().unwrap();
+        let loaded = SaveGame::load_from_memory(&data).unwrap();
+        assert_eq!(loaded, save_game);
+
+        let mut fresh_scene = make_scene(&ids);
+        let resource_manager = ResourceManager::new(Arc::new(Default::default()));
+        block_on(loaded.apply(&mut fresh_scene, &resource_manager)).unwrap();
+
+        assert!(fresh_scene.graph.find_by_name_from_root("Enemy").is_none());
+        let (_, player) = fresh_scene.graph.find_by_name_from_root("Player").unwrap();
+        assert_eq!(
+            **player.local_transform().position(),
+            Vector3::new(1.0, 2.0, 3.0)
+        );
+        assert!(!player.visibility());
+    }
+
+    // Emulates loading of the scene, whose layout has changed. `padding` nodes shift the handles of
+    // the other nodes.
+    fn make_follower_scene(ids: &[Uuid; 2], padding: usize) -> Scene {
+        let mut scene = Scene::new();
+        for _ in 0..padding {
+            PivotBuilder::new(BaseBuilder::new()).build(&mut scene.graph);
+        }
+        PivotBuilder::new(
+            BaseBuilder::new()
+                .with_name("Leader")
+                .with_persistent(true)
+                .with_instance_id(SceneNodeId(ids[0])),
+        )
+        .build(&mut scene.graph);
+        PivotBuilder::new(
+            BaseBuilder::new()
+                .with_name("Follower")
+                .with_persistent(true)
+                .with_instance_id(SceneNodeId(ids[1]))
+                .with_script(Follower::default()),
+        )
+        .build(&mut scene.graph);
+        scene
+    }
+
+    #[test]
+    fn test_script_handles_are_remapped() {
+        let ids = [Uuid::new_v4(), Uuid::new_v4()];
+
+        let mut scene = make_follower_scene(&ids, 0);
+        let baseline = SceneBaseline::new(&scene);
+        let (leader, _) = scene.graph.find_by_name_from_root("Leader").unwrap();
+        let (follower, _) = scene.graph.find_by_name_from_root("Follower").unwrap();
+        scene.graph[follower]
+            .try_get_script_mut::<Follower>()
+            .unwrap()
+            .target = leader;
+
+        let save_game = SaveGame::capture(&mut scene, &baseline, 1).unwrap();
+
+        let mut fresh_scene = make_follower_scene(&ids, 2);
+        let resource_manager = ResourceManager::new(Arc::new(Default::default()));
+        block_on(save_game.apply(&mut fresh_scene, &resource_manager)).unwrap();
+
+        let (new_leader, _) = fresh_scene.graph.find_by_name_from_root("Leader").unwrap();
+        assert_ne!(new_leader, leader);
+        let (_, new_follower) = fresh_scene
+            .graph
+            .find_by_name_from_root("Follower")
+            .unwrap();
+        assert_eq!(
+            new_follower.try_get_script::<Follower>().unwrap().target,
+            new_leader
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut save_game = SaveGame {
+            version: 3,
+            destroyed: vec![Uuid::new_v4()],
+            spawned: vec![SpawnedInstance {
+                prefab: "data/crate.rgs".into(),
+                parent: Uuid::nil(),
+                ids: Default::default(),
+            }],
+            nodes: Default::default(),
+        };
+        let data = save_game.save_to_vec().unwrap();
+        assert_eq!(SaveGame::load_from_memory(&data).unwrap(), save_game);
+    }
+}
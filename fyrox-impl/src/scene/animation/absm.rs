@@ -33,9 +33,12 @@ use crate::{
         visitor::prelude::*,
     },
     scene::{
-        animation::prelude::*,
+        animation::{
+            lod::{animation_targets, pose_source_animations, AnimationLod, AnimationLodAction},
+            prelude::*,
+        },
         base::{Base, BaseBuilder},
-        graph::Graph,
+        graph::{Graph, NodePool},
         node::{Node, NodeTrait, UpdateContext},
         Scene,
     },
@@ -224,6 +227,8 @@ pub struct AnimationBlendingStateMachine {
     machine: InheritableVariable<Machine>,
     #[component(include)]
     animation_player: InheritableVariable<Handle<Node>>,
+    #[visit(optional)]
+    lod: InheritableVariable<AnimationLod>,
 }
 
 impl AnimationBlendingStateMachine {
@@ -253,6 +258,16 @@ impl AnimationBlendingStateMachine {
     pub fn animation_player(&self) -> Handle<Node> {
         *self.animation_player
     }
+
+    /// Sets new level of details settings of the node. See [`AnimationLod`] docs for more info.
+    pub fn set_lod(&mut self, lod: AnimationLod) {
+        self.lod.set_value_and_mark_modified(lod);
+    }
+
+    /// Returns a reference to the level of details settings of the node.
+    pub fn lod(&self) -> &InheritableVariable<AnimationLod> {
+        &self.lod
+    }
 }
 
 impl TypeUuidProvider for AnimationBlendingStateMachine {
@@ -311,6 +326,32 @@ impl NodeTrait for AnimationBlendingStateMachine {
     }
 
     fn update(&mut self, context: &mut UpdateContext) {
+        let animation_player = *self.animation_player;
+        let targets = move |nodes: &NodePool| {
+            pose_source_animations(animation_player, |h| nodes.try_borrow(h))
+                .map(animation_targets)
+                .unwrap_or_default()
+        };
+        let lod = self.lod.get_value_mut_silent();
+        lod.capture_bind_pose(targets, context.nodes);
+
+        let dt = match lod.begin_update(
+            self.base.global_position(),
+            context.observer_position,
+            context.dt,
+        ) {
+            AnimationLodAction::Skip => None,
+            AnimationLodAction::Evaluate(dt) => Some(dt),
+            AnimationLodAction::BindPose => {
+                lod.apply_bind_pose(context.nodes);
+                None
+            }
+            AnimationLodAction::Share(source) => {
+                lod.share_pose(source, targets, context.nodes);
+                None
+            }
+        };
+
         if let Some(animation_player) = context
             .nodes
             .try_borrow_mut(*self.animation_player)
@@ -320,12 +361,15 @@ impl NodeTrait for AnimationBlendingStateMachine {
             // do than instead.
             animation_player.set_auto_apply(false);
 
-            let pose = self.machine.get_value_mut_silent().evaluate_pose(
-                animation_player.animations.get_value_mut_silent(),
-                context.dt,
-            );
+            if let Some(dt) = dt {
+                let pose = self.machine.get_value_mut_silent().evaluate_pose_substeps(
+                    animation_player.animations.get_value_mut_silent(),
+                    dt,
+                    context.sub_stepping.substep_count(dt),
+                );
 
-            pose.apply_internal(context.nodes);
+                pose.apply_internal(context.nodes);
+            }
         }
     }
 
@@ -352,6 +396,7 @@ pub struct AnimationBlendingStateMachineBuilder {
     base_builder: BaseBuilder,
     machine: Machine,
     animation_player: Handle<Node>,
+    lod: AnimationLod,
 }
 
 impl AnimationBlendingStateMachineBuilder {
@@ -361,6 +406,7 @@ impl AnimationBlendingStateMachineBuilder {
             base_builder,
             machine: Default::default(),
             animation_player: Default::default(),
+            lod: Default::default(),
         }
     }
 
@@ -376,12 +422,19 @@ impl AnimationBlendingStateMachineBuilder {
         self
     }
 
+    /// Sets the desired level of details settings. See [`AnimationLod`] docs for more info.
+    pub fn with_lod(mut self, lod: AnimationLod) -> Self {
+        self.lod = lod;
+        self
+    }
+
     /// Creates new node.
     pub fn build_node(self) -> Node {
         Node::new(AnimationBlendingStateMachine {
             base: self.base_builder.build_base(),
             machine: self.machine.into(),
             animation_player: self.animation_player.into(),
+            lod: self.lod.into(),
         })
     }
 
@@ -0,0 +1,401 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Animation level of details (LOD) for crowds. See [`AnimationLod`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        pool::Handle,
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    scene::{
+        animation::{absm::AnimationBlendingStateMachine, AnimationContainer, AnimationPlayer},
+        graph::NodePool,
+        node::Node,
+    },
+};
+use fxhash::FxHashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// A single level of animation LOD.
+#[derive(Clone, Debug, Visit, Reflect, PartialEq)]
+pub struct AnimationLodLevel {
+    /// Minimal distance from the observer at which the level is used.
+    pub distance: f32,
+    /// Poses are evaluated once per the given amount of frames. `1` means every frame, `2` - every
+    /// second frame and so on. Time of skipped frames is accumulated, so animations keep their
+    /// speed.
+    pub update_interval: u32,
+}
+
+impl Default for AnimationLodLevel {
+    fn default() -> Self {
+        Self {
+            distance: 0.0,
+            update_interval: 1,
+        }
+    }
+}
+
+/// What should be done with the animation on the current frame.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum AnimationLodAction {
+    /// Pose evaluation is skipped on this frame, the previous pose is kept.
+    Skip,
+    /// The pose must be evaluated with the given time step (it includes the time of skipped
+    /// frames).
+    Evaluate(f32),
+    /// The pose must be reset to the bind pose. The bind pose is applied only once, when the
+    /// animation goes beyond the bind pose distance.
+    BindPose,
+    /// The pose must be copied from the given pose source.
+    Share(Handle<Node>),
+}
+
+#[derive(Clone, Debug)]
+struct LocalTransform {
+    node: Handle<Node>,
+    position: Vector3<f32>,
+    rotation: UnitQuaternion<f32>,
+    scale: Vector3<f32>,
+}
+
+impl LocalTransform {
+    fn read(node: Handle<Node>, nodes: &NodePool) -> Option<Self> {
+        let transform = nodes.try_borrow(node)?.local_transform();
+        Some(Self {
+            node,
+            position: **transform.position(),
+            rotation: **transform.rotation(),
+            scale: **transform.scale(),
+        })
+    }
+
+    fn write(&self, node: Handle<Node>, nodes: &mut NodePool) {
+        if let Some(node) = nodes.try_borrow_mut(node) {
+            node.local_transform_mut()
+                .set_position(self.position)
+                .set_rotation(self.rotation)
+                .set_scale(self.scale);
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct SharedBones {
+    source: Handle<Node>,
+    // Pairs of (own bone, bone of the pose source).
+    bones: Vec<(Handle<Node>, Handle<Node>)>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct LodState {
+    phase: Option<u32>,
+    frame: u32,
+    accumulated_dt: f32,
+    bind_pose: Option<Vec<LocalTransform>>,
+    in_bind_pose: bool,
+    shared_bones: Option<SharedBones>,
+}
+
+// Every animation gets its own phase, so animations with the same update interval are evaluated
+// on different frames and the load is spread evenly.
+static NEXT_PHASE: AtomicU32 = AtomicU32::new(0);
+
+/// Animation level of details (LOD) allows to reduce CPU load caused by animations of distant
+/// characters, which is essential for crowds with hundreds of skinned characters. There are three
+/// mechanisms (each one is optional):
+///
+/// - **Reduced update rate** - distant animations are evaluated every N frames (see
+///   [`AnimationLodLevel`]) instead of every frame. Animations of different characters are evaluated
+///   on different frames, so the load is spread evenly.
+/// - **Bind pose fallback** - animations beyond the bind pose distance are not evaluated at all,
+///   animated nodes are reset to their initial (bind) pose instead.
+/// - **Pose sharing** - identical agents could share a single evaluated pose. A pose source is
+///   another animation player or animation blending state machine, which pose is copied to the
+///   nodes of this animation by names. The copying is much cheaper than evaluation of a state
+///   machine. The pose source should be updated before (created earlier than) the agents that
+///   use it, otherwise the pose is one frame late.
+///
+/// Distance is measured from the animation player (or the state machine) node to the observer,
+/// which is the first enabled camera in the scene. There is no LOD if there's no camera.
+#[derive(Clone, Debug, Visit, Reflect, Default)]
+pub struct AnimationLod {
+    /// Enables or disables the LOD.
+    pub enabled: bool,
+    /// A set of levels, the level with the largest distance which is still less than the distance
+    /// to the observer is used.
+    pub levels: Vec<AnimationLodLevel>,
+    /// A distance at which animations are switched to the bind pose. `None` means that the bind
+    /// pose is never used.
+    pub bind_pose_distance: Option<f32>,
+    /// A handle of an animation player or animation blending state machine, which pose is shared
+    /// with this animation. `Handle::NONE` means that the pose is evaluated.
+    pub pose_source: Handle<Node>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    state: LodState,
+}
+
+impl AnimationLod {
+    /// Returns the update interval (in frames) for the given distance to the observer.
+    pub fn update_interval(&self, distance: f32) -> u32 {
+        self.levels
+            .iter()
+            .filter(|level| distance >= level.distance)
+            .max_by(|a, b| a.distance.total_cmp(&b.distance))
+            .map_or(1, |level| level.update_interval.max(1))
+    }
+
+    /// Decides what should be done with the animation at the given position on the current frame.
+    pub fn begin_update(
+        &mut self,
+        position: Vector3<f32>,
+        observer_position: Option<Vector3<f32>>,
+        dt: f32,
+    ) -> AnimationLodAction {
+        let distance = match observer_position {
+            Some(observer_position) if self.enabled => observer_position.metric_distance(&position),
+            _ => return self.evaluate(dt),
+        };
+
+        if self
+            .bind_pose_distance
+            .is_some_and(|bind_pose_distance| distance >= bind_pose_distance)
+        {
+            self.state.accumulated_dt = 0.0;
+            return if self.state.in_bind_pose {
+                AnimationLodAction::Skip
+            } else {
+                AnimationLodAction::BindPose
+            };
+        }
+
+        if self.pose_source.is_some() {
+            self.state.in_bind_pose = false;
+            return AnimationLodAction::Share(self.pose_source);
+        }
+
+        let interval = self.update_interval(distance);
+        let phase = *self
+            .state
+            .phase
+            .get_or_insert_with(|| NEXT_PHASE.fetch_add(1, Ordering::Relaxed));
+        self.state.frame = self.state.frame.wrapping_add(1);
+        if interval <= 1 || self.state.frame.wrapping_add(phase) % interval == 0 {
+            let dt = dt + std::mem::take(&mut self.state.accumulated_dt);
+            self.evaluate(dt)
+        } else {
+            self.state.accumulated_dt += dt;
+            AnimationLodAction::Skip
+        }
+    }
+
+    fn evaluate(&mut self, dt: f32) -> AnimationLodAction {
+        self.state.in_bind_pose = false;
+        AnimationLodAction::Evaluate(dt)
+    }
+
+    /// Remembers local transforms of the animated nodes as the bind pose. It is done once, before
+    /// the first evaluation of the animation, and only if the bind pose fallback is used.
+    pub(crate) fn capture_bind_pose<F>(&mut self, targets: F, nodes: &NodePool)
+    where
+        F: FnOnce(&NodePool) -> Vec<Handle<Node>>,
+    {
+        if !self.enabled || self.bind_pose_distance.is_none() || self.state.bind_pose.is_some() {
+            return;
+        }
+
+        self.state.bind_pose = Some(
+            targets(nodes)
+                .into_iter()
+                .filter_map(|node| LocalTransform::read(node, nodes))
+                .collect(),
+        );
+    }
+
+    /// Resets animated nodes to the bind pose.
+    pub(crate) fn apply_bind_pose(&mut self, nodes: &mut NodePool) {
+        if let Some(bind_pose) = self.state.bind_pose.as_ref() {
+            for transform in bind_pose {
+                transform.write(transform.node, nodes);
+            }
+        }
+        self.state.in_bind_pose = true;
+    }
+
+    /// Copies local transforms of the bones of the pose source to the bones of this animation with
+    /// the same names.
+    pub(crate) fn share_pose<F>(&mut self, source: Handle<Node>, targets: F, nodes: &mut NodePool)
+    where
+        F: FnOnce(&NodePool) -> Vec<Handle<Node>>,
+    {
+        if self
+            .state
+            .shared_bones
+            .as_ref()
+            .map_or(true, |shared| shared.source != source)
+        {
+            let source_bones = pose_source_animations(source, |h| nodes.try_borrow(h))
+                .map(animation_targets)
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|bone| Some((nodes.try_borrow(bone)?.name_owned(), bone)))
+                .collect::<FxHashMap<_, _>>();
+            let bones = targets(nodes)
+                .into_iter()
+                .filter_map(|bone| {
+                    let name = nodes.try_borrow(bone)?.name();
+                    Some((bone, *source_bones.get(name)?))
+                })
+                .collect();
+            self.state.shared_bones = Some(SharedBones { source, bones });
+        }
+
+        if let Some(shared) = self.state.shared_bones.as_ref() {
+            for &(bone, source_bone) in shared.bones.iter() {
+                if let Some(transform) = LocalTransform::read(source_bone, nodes) {
+                    transform.write(bone, nodes);
+                }
+            }
+        }
+    }
+}
+
+/// Returns a list of nodes animated by the animations of the given container.
+pub fn animation_targets(animations: &AnimationContainer) -> Vec<Handle<Node>> {
+    let mut targets = animations
+        .iter()
+        .flat_map(|animation| {
+            animation
+                .track_bindings()
+                .values()
+                .map(|binding| binding.target)
+        })
+        .collect::<Vec<_>>();
+    targets.sort();
+    targets.dedup();
+    targets
+}
+
+/// Returns the animations of the given animation player node or the animations of the player used
+/// by the given animation blending state machine node.
+pub(crate) fn pose_source_animations<'a, F>(
+    source: Handle<Node>,
+    borrow: F,
+) -> Option<&'a AnimationContainer>
+where
+    F: Fn(Handle<Node>) -> Option<&'a Node>,
+{
+    let node = borrow(source)?;
+    let player = match node.cast::<AnimationBlendingStateMachine>() {
+        Some(absm) => borrow(absm.animation_player())?,
+        None => node,
+    };
+    player
+        .component_ref::<AnimationPlayer>()
+        .map(|player| &**player.animations())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn lod() -> AnimationLod {
+        AnimationLod {
+            enabled: true,
+            levels: vec![
+                AnimationLodLevel {
+                    distance: 10.0,
+                    update_interval: 2,
+                },
+                AnimationLodLevel {
+                    distance: 20.0,
+                    update_interval: 4,
+                },
+            ],
+            bind_pose_distance: Some(50.0),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_update_interval() {
+        let lod = lod();
+        assert_eq!(lod.update_interval(5.0), 1);
+        assert_eq!(lod.update_interval(15.0), 2);
+        assert_eq!(lod.update_interval(30.0), 4);
+    }
+
+    #[test]
+    fn test_reduced_update_rate_accumulates_time() {
+        let mut lod = lod();
+        let observer = Some(Vector3::new(30.0, 0.0, 0.0));
+
+        let mut evaluated = Vec::new();
+        for _ in 0..8 {
+            if let AnimationLodAction::Evaluate(dt) =
+                lod.begin_update(Vector3::default(), observer, 0.1)
+            {
+                evaluated.push(dt);
+            }
+        }
+
+        assert_eq!(evaluated.len(), 2);
+        // Every evaluation except the first one gets the time of all skipped frames.
+        assert!((evaluated[1] - 0.4).abs() < 1.0e-5);
+    }
+
+    #[test]
+    fn test_bind_pose_fallback() {
+        let mut lod = lod();
+        let far = Some(Vector3::new(100.0, 0.0, 0.0));
+
+        assert_eq!(
+            lod.begin_update(Vector3::default(), far, 0.1),
+            AnimationLodAction::BindPose
+        );
+        let mut nodes = NodePool::new();
+        lod.apply_bind_pose(&mut nodes);
+        assert_eq!(
+            lod.begin_update(Vector3::default(), far, 0.1),
+            AnimationLodAction::Skip
+        );
+
+        // Close animations are evaluated every frame.
+        assert_eq!(
+            lod.begin_update(Vector3::default(), Some(Vector3::default()), 0.1),
+            AnimationLodAction::Evaluate(0.1)
+        );
+    }
+
+    #[test]
+    fn test_disabled_lod() {
+        let mut lod = AnimationLod {
+            enabled: false,
+            ..lod()
+        };
+        assert_eq!(
+            lod.begin_update(Vector3::default(), Some(Vector3::new(100.0, 0.0, 0.0)), 0.1),
+            AnimationLodAction::Evaluate(0.1)
+        );
+    }
+}
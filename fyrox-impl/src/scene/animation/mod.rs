@@ -35,6 +35,7 @@ use crate::{
     },
     generic_animation::value::{BoundValueCollection, TrackValue, ValueBinding},
     scene::{
+        animation::lod::{animation_targets, AnimationLod, AnimationLodAction},
         base::{Base, BaseBuilder},
         graph::{Graph, NodePool},
         node::{Node, NodeTrait, UpdateContext},
@@ -45,6 +46,8 @@ use fyrox_graph::BaseSceneGraph;
 use std::ops::{Deref, DerefMut};
 
 pub mod absm;
+pub mod lod;
+pub mod snapshot;
 pub mod spritesheet;
 
 /// Scene specific animation.
@@ -78,12 +81,21 @@ pub trait AnimationContainerExt {
     /// Updates all animations in the container and applies their poses to respective nodes. This method is intended to
     /// be used only by the internals of the engine!
     fn update_animations(&mut self, nodes: &mut NodePool, dt: f32);
+
+    /// Same as [`Self::update_animations`], but splits the time step into the given amount of
+    /// sub-steps (see [`Animation::tick_substeps`]). This method is intended to be used only by the
+    /// internals of the engine!
+    fn update_animations_substeps(&mut self, nodes: &mut NodePool, dt: f32, substeps: u32);
 }
 
 impl AnimationContainerExt for AnimationContainer {
     fn update_animations(&mut self, nodes: &mut NodePool, dt: f32) {
+        self.update_animations_substeps(nodes, dt, 1)
+    }
+
+    fn update_animations_substeps(&mut self, nodes: &mut NodePool, dt: f32, substeps: u32) {
         for animation in self.iter_mut().filter(|anim| anim.is_enabled()) {
-            animation.tick(dt);
+            animation.tick_substeps(dt, substeps);
             animation.pose().apply_internal(nodes);
         }
     }
@@ -250,6 +262,8 @@ pub struct AnimationPlayer {
     animations: InheritableVariable<AnimationContainer>,
     #[component(include)]
     auto_apply: bool,
+    #[visit(optional)]
+    lod: InheritableVariable<AnimationLod>,
 }
 
 impl Default for AnimationPlayer {
@@ -258,6 +272,7 @@ impl Default for AnimationPlayer {
             base: Default::default(),
             animations: Default::default(),
             auto_apply: true,
+            lod: Default::default(),
         }
     }
 }
@@ -297,6 +312,17 @@ impl AnimationPlayer {
     pub fn set_animations(&mut self, animations: AnimationContainer) {
         self.animations.set_value_and_mark_modified(animations);
     }
+
+    /// Sets new level of details settings of the animation player. The settings are used only if
+    /// auto applying is enabled. See [`AnimationLod`] docs for more info.
+    pub fn set_lod(&mut self, lod: AnimationLod) {
+        self.lod.set_value_and_mark_modified(lod);
+    }
+
+    /// Returns a reference to the level of details settings of the animation player.
+    pub fn lod(&self) -> &InheritableVariable<AnimationLod> {
+        &self.lod
+    }
 }
 
 impl TypeUuidProvider for AnimationPlayer {
@@ -346,9 +372,30 @@ impl NodeTrait for AnimationPlayer {
 
     fn update(&mut self, context: &mut UpdateContext) {
         if self.auto_apply {
-            self.animations
-                .get_value_mut_silent()
-                .update_animations(context.nodes, context.dt);
+            let animations = &self.animations;
+            let lod = self.lod.get_value_mut_silent();
+            lod.capture_bind_pose(|_| animation_targets(animations), context.nodes);
+
+            match lod.begin_update(
+                self.base.global_position(),
+                context.observer_position,
+                context.dt,
+            ) {
+                AnimationLodAction::Skip => {}
+                AnimationLodAction::Evaluate(dt) => {
+                    self.animations
+                        .get_value_mut_silent()
+                        .update_animations_substeps(
+                            context.nodes,
+                            dt,
+                            context.sub_stepping.substep_count(dt),
+                        );
+                }
+                AnimationLodAction::BindPose => lod.apply_bind_pose(context.nodes),
+                AnimationLodAction::Share(source) => {
+                    lod.share_pose(source, |_| animation_targets(animations), context.nodes)
+                }
+            }
         }
     }
 }
@@ -358,6 +405,7 @@ pub struct AnimationPlayerBuilder {
     base_builder: BaseBuilder,
     animations: AnimationContainer,
     auto_apply: bool,
+    lod: AnimationLod,
 }
 
 impl AnimationPlayerBuilder {
@@ -367,6 +415,7 @@ impl AnimationPlayerBuilder {
             base_builder,
             animations: AnimationContainer::new(),
             auto_apply: true,
+            lod: Default::default(),
         }
     }
 
@@ -382,12 +431,19 @@ impl AnimationPlayerBuilder {
         self
     }
 
+    /// Sets the desired level of details settings. See [`AnimationLod`] docs for more info.
+    pub fn with_lod(mut self, lod: AnimationLod) -> Self {
+        self.lod = lod;
+        self
+    }
+
     /// Creates an instance of [`AnimationPlayer`] node.
     pub fn build_node(self) -> Node {
         Node::new(AnimationPlayer {
             base: self.base_builder.build_base(),
             animations: self.animations.into(),
             auto_apply: self.auto_apply,
+            lod: self.lod.into(),
         })
     }
 
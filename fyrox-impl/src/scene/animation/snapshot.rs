@@ -0,0 +1,511 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Pose snapshots allow to modify evaluated poses of animations programmatically, right after the
+//! animations were evaluated and before the scene is rendered. See [`PoseModifiers`] docs for more
+//! info.
+
+use crate::{
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        pool::Handle,
+    },
+    graph::BaseSceneGraph,
+    scene::{
+        animation::lod::{animation_targets, pose_source_animations},
+        graph::Graph,
+        node::Node,
+    },
+};
+use fxhash::FxHashMap;
+use std::fmt::{Debug, Formatter};
+
+/// Local transform of a single bone.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BonePose {
+    /// Local position of the bone.
+    pub position: Vector3<f32>,
+    /// Local rotation of the bone.
+    pub rotation: UnitQuaternion<f32>,
+    /// Local scale of the bone.
+    pub scale: Vector3<f32>,
+}
+
+impl BonePose {
+    /// Applies the given rotation on top of the current rotation of the bone. The rotation is
+    /// defined in the local space of the bone.
+    pub fn rotate(&mut self, rotation: UnitQuaternion<f32>) {
+        self.rotation *= rotation;
+    }
+
+    /// Moves the bone by the given offset, defined in the space of the parent of the bone.
+    pub fn translate(&mut self, offset: Vector3<f32>) {
+        self.position += offset;
+    }
+
+    /// Blends the pose with the other one using the given weight. `0.0` keeps the current pose,
+    /// `1.0` - replaces it with the other pose.
+    pub fn blend(&mut self, other: &BonePose, weight: f32) {
+        self.position = self.position.lerp(&other.position, weight);
+        self.rotation = self.rotation.nlerp(&other.rotation, weight);
+        self.scale = self.scale.lerp(&other.scale, weight);
+    }
+
+    fn read(node: &Node) -> Self {
+        let transform = node.local_transform();
+        Self {
+            position: **transform.position(),
+            rotation: **transform.rotation(),
+            scale: **transform.scale(),
+        }
+    }
+}
+
+/// A mutable snapshot of local transforms of the bones animated by an animation player or an
+/// animation blending state machine.
+#[derive(Clone, Debug, Default)]
+pub struct PoseSnapshot {
+    animation: Handle<Node>,
+    bones: Vec<(Handle<Node>, BonePose)>,
+    indices: FxHashMap<Handle<Node>, usize>,
+    names: FxHashMap<String, usize>,
+}
+
+impl PoseSnapshot {
+    /// Captures current local transforms of the bones animated by the given animation player or
+    /// animation blending state machine.
+    pub fn capture(graph: &Graph, animation: Handle<Node>) -> Self {
+        let bones = pose_source_animations(animation, |h| graph.try_get(h))
+            .map(animation_targets)
+            .unwrap_or_default();
+        let mut snapshot = Self::from_bones(graph, bones);
+        snapshot.animation = animation;
+        snapshot
+    }
+
+    /// Captures current local transforms of the given bones.
+    pub fn from_bones<I>(graph: &Graph, bones: I) -> Self
+    where
+        I: IntoIterator<Item = Handle<Node>>,
+    {
+        let mut snapshot = Self::default();
+        for bone in bones {
+            if let Some(node) = graph.try_get(bone) {
+                if snapshot.indices.contains_key(&bone) {
+                    continue;
+                }
+                let index = snapshot.bones.len();
+                snapshot.indices.insert(bone, index);
+                snapshot.names.entry(node.name_owned()).or_insert(index);
+                snapshot.bones.push((bone, BonePose::read(node)));
+            }
+        }
+        snapshot
+    }
+
+    /// Returns a handle of the animation player or animation blending state machine, which pose
+    /// was captured.
+    pub fn animation(&self) -> Handle<Node> {
+        self.animation
+    }
+
+    /// Returns an iterator over the bones of the snapshot.
+    pub fn bones(&self) -> impl Iterator<Item = (Handle<Node>, &BonePose)> {
+        self.bones.iter().map(|(handle, pose)| (*handle, pose))
+    }
+
+    /// Returns a reference to the pose of the given bone.
+    pub fn bone(&self, handle: Handle<Node>) -> Option<&BonePose> {
+        self.indices.get(&handle).map(|i| &self.bones[*i].1)
+    }
+
+    /// Returns a reference to the pose of the given bone.
+    pub fn bone_mut(&mut self, handle: Handle<Node>) -> Option<&mut BonePose> {
+        self.indices.get(&handle).map(|i| &mut self.bones[*i].1)
+    }
+
+    /// Searches for a bone with the given name. If there are multiple bones with the same name,
+    /// the first one is returned.
+    pub fn find_bone(&self, name: &str) -> Option<Handle<Node>> {
+        self.names.get(name).map(|i| self.bones[*i].0)
+    }
+
+    /// Returns a reference to the pose of a bone with the given name.
+    pub fn bone_by_name_mut(&mut self, name: &str) -> Option<&mut BonePose> {
+        self.names.get(name).map(|i| &mut self.bones[*i].1)
+    }
+
+    /// Writes local transforms of the bones to the graph and recalculates global transforms of
+    /// the bones and their descendants.
+    pub fn apply(&self, graph: &mut Graph) {
+        for (handle, pose) in self.bones.iter() {
+            if let Some(node) = graph.try_get_mut(*handle) {
+                node.local_transform_mut()
+                    .set_position(pose.position)
+                    .set_rotation(pose.rotation)
+                    .set_scale(pose.scale);
+            }
+        }
+
+        // Only the topmost bones are updated, the rest of the bones are their descendants.
+        for (handle, _) in self.bones.iter() {
+            let parent = graph.try_get(*handle).map(|node| node.parent());
+            if parent.is_some_and(|parent| !self.indices.contains_key(&parent)) {
+                graph.update_hierarchical_data_for_descendants(*handle);
+            }
+        }
+    }
+
+    // Animations could skip pose evaluation (for example, because of animation LOD) and thus
+    // leave the pose written by pose modifiers on the previous frame in the graph. Such values
+    // are replaced with the values before the modification, otherwise additive modifiers would
+    // accumulate their changes.
+    fn restore_unchanged(&mut self, previous: &AppliedPose) {
+        for (handle, pose) in self.bones.iter_mut() {
+            let (Some(base), Some(applied)) =
+                (previous.base.bone(*handle), previous.applied.bone(*handle))
+            else {
+                continue;
+            };
+            if pose.position == applied.position {
+                pose.position = base.position;
+            }
+            if pose.rotation == applied.rotation {
+                pose.rotation = base.rotation;
+            }
+            if pose.scale == applied.scale {
+                pose.scale = base.scale;
+            }
+        }
+    }
+}
+
+/// Defines when a pose modifier is executed relative to inverse kinematics.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PoseStage {
+    /// The modifier is executed before inverse kinematics. It is the right place for additive
+    /// layers, such as procedural leaning or weapon recoil, so IK could fix hands and feet
+    /// afterwards.
+    #[default]
+    BeforeIk,
+    /// A stage for inverse kinematics solvers.
+    Ik,
+    /// The modifier is executed after inverse kinematics. It is the right place for corrections
+    /// that must not be overridden by IK, such as look-at of the head.
+    AfterIk,
+}
+
+/// A context of a pose modifier.
+pub struct PoseModifierContext<'a> {
+    /// A graph of the scene. Global transforms of the bones correspond to the pose at the
+    /// beginning of the current stage, changes made by modifiers of the same stage are visible
+    /// only in the snapshot.
+    pub graph: &'a Graph,
+    /// A handle of the node, that owns the modifier.
+    pub owner: Handle<Node>,
+    /// A stage, that is being executed.
+    pub stage: PoseStage,
+    /// A time that have passed since last update call.
+    pub dt: f32,
+    /// A mutable snapshot of the evaluated pose.
+    pub pose: &'a mut PoseSnapshot,
+}
+
+/// A callback, that modifies evaluated pose of an animation.
+pub type PoseModifier = dyn FnMut(&mut PoseModifierContext) + 'static;
+
+/// A handle of a registered pose modifier.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub struct PoseModifierId(u64);
+
+struct Entry {
+    id: PoseModifierId,
+    owner: Handle<Node>,
+    animation: Handle<Node>,
+    stage: PoseStage,
+    callback: Box<PoseModifier>,
+}
+
+struct AppliedPose {
+    base: PoseSnapshot,
+    applied: PoseSnapshot,
+}
+
+/// A set of callbacks, that modify evaluated poses of animation players and animation blending
+/// state machines. Modifiers are executed after the scene graph was updated (so every animation
+/// has its pose evaluated and applied) and before scripts and rendering. Every modifier gets a
+/// mutable [`PoseSnapshot`] of the animation it was registered for, the snapshot is written back
+/// to the graph after every [`PoseStage`]. Modifiers of the same animation are executed in the
+/// order of stages and then in the order of registration.
+///
+/// Every modifier is owned by a scene node and is removed automatically when its owner or its
+/// animation is deleted.
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::{algebra::{UnitQuaternion, Vector3}, pool::Handle, reflect::prelude::*,
+/// #            visitor::prelude::*, type_traits::prelude::*},
+/// #     scene::{animation::snapshot::PoseStage, node::Node},
+/// #     script::{ScriptContext, ScriptTrait},
+/// # };
+/// #[derive(Visit, Reflect, Default, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+/// #[type_uuid(id = "6f2c0a1e-5f33-4a43-9f38-4cb8a5b1c3d2")]
+/// struct Lean {
+///     animation: Handle<Node>,
+///     angle: f32,
+/// }
+///
+/// impl ScriptTrait for Lean {
+///     fn on_start(&mut self, ctx: &mut ScriptContext) {
+///         ctx.scene.pose_modifiers.register(
+///             ctx.handle,
+///             self.animation,
+///             PoseStage::BeforeIk,
+///             |ctx| {
+///                 let angle = ctx.graph[ctx.owner]
+///                     .try_get_script::<Lean>()
+///                     .map_or(0.0, |lean| lean.angle);
+///                 if let Some(spine) = ctx.pose.bone_by_name_mut("Spine") {
+///                     spine.rotate(UnitQuaternion::from_axis_angle(&Vector3::z_axis(), angle));
+///                 }
+///             },
+///         );
+///     }
+/// }
+/// ```
+#[derive(Default)]
+pub struct PoseModifiers {
+    entries: Vec<Entry>,
+    applied: FxHashMap<Handle<Node>, AppliedPose>,
+    id_counter: u64,
+}
+
+impl Debug for PoseModifiers {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PoseModifiers - {} modifiers", self.entries.len())
+    }
+}
+
+impl PoseModifiers {
+    /// Registers a new modifier of the pose of the given animation player or animation blending
+    /// state machine. The modifier is owned by the given node. Returns an id of the modifier, that
+    /// could be used to unregister it.
+    pub fn register<F>(
+        &mut self,
+        owner: Handle<Node>,
+        animation: Handle<Node>,
+        stage: PoseStage,
+        callback: F,
+    ) -> PoseModifierId
+    where
+        F: FnMut(&mut PoseModifierContext) + 'static,
+    {
+        self.id_counter += 1;
+        let id = PoseModifierId(self.id_counter);
+        // Keep the entries sorted by stages, preserving the order of registration.
+        let index = self.entries.partition_point(|e| e.stage <= stage);
+        self.entries.insert(
+            index,
+            Entry {
+                id,
+                owner,
+                animation,
+                stage,
+                callback: Box::new(callback),
+            },
+        );
+        id
+    }
+
+    /// Unregisters a modifier with the given id. Returns `true` if the modifier was registered.
+    pub fn unregister(&mut self, id: PoseModifierId) -> bool {
+        let count = self.entries.len();
+        self.entries.retain(|e| e.id != id);
+        self.entries.len() != count
+    }
+
+    /// Unregisters every modifier of the given node.
+    pub fn unregister_all(&mut self, owner: Handle<Node>) {
+        self.entries.retain(|e| e.owner != owner);
+    }
+
+    /// Returns `true` if there's no registered modifiers.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns total amount of registered modifiers.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub(crate) fn run(&mut self, graph: &mut Graph, dt: f32) {
+        self.entries.retain(|entry| {
+            graph.is_valid_handle(entry.owner) && graph.is_valid_handle(entry.animation)
+        });
+
+        let mut animations = Vec::new();
+        for entry in self.entries.iter() {
+            if !animations.contains(&entry.animation) {
+                animations.push(entry.animation);
+            }
+        }
+        self.applied
+            .retain(|animation, _| animations.contains(animation));
+
+        for animation in animations {
+            let mut pose = PoseSnapshot::capture(graph, animation);
+            if let Some(previous) = self.applied.get(&animation) {
+                pose.restore_unchanged(previous);
+            }
+            // Refresh global transforms, so modifiers could use them.
+            pose.apply(graph);
+            let base = pose.clone();
+
+            let mut current_stage = None;
+            for entry in self.entries.iter_mut().filter(|e| e.animation == animation) {
+                if current_stage.is_some_and(|stage| stage != entry.stage) {
+                    pose.apply(graph);
+                }
+                current_stage = Some(entry.stage);
+
+                (entry.callback)(&mut PoseModifierContext {
+                    graph,
+                    owner: entry.owner,
+                    stage: entry.stage,
+                    dt,
+                    pose: &mut pose,
+                });
+            }
+
+            pose.apply(graph);
+            self.applied.insert(
+                animation,
+                AppliedPose {
+                    base,
+                    applied: pose,
+                },
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Vector3, pool::Handle},
+        graph::BaseSceneGraph,
+        scene::{
+            animation::{
+                prelude::*,
+                snapshot::{PoseModifiers, PoseStage},
+            },
+            base::BaseBuilder,
+            graph::Graph,
+            node::Node,
+            pivot::PivotBuilder,
+        },
+    };
+    use fyrox_animation::track::TrackBinding;
+    use std::{cell::RefCell, rc::Rc};
+
+    fn make_skeleton(graph: &mut Graph) -> (Handle<Node>, Handle<Node>, Handle<Node>) {
+        let hand = PivotBuilder::new(BaseBuilder::new().with_name("Hand")).build(graph);
+        let arm = PivotBuilder::new(BaseBuilder::new().with_name("Arm").with_children(&[hand]))
+            .build(graph);
+
+        let mut animation = Animation::default();
+        for bone in [arm, hand] {
+            animation.add_track_with_binding(
+                TrackBinding::new(bone),
+                Track::new(
+                    TrackDataContainer::new(TrackValueKind::UnitQuaternion),
+                    ValueBinding::Rotation,
+                ),
+            );
+        }
+        let mut animations = AnimationContainer::new();
+        animations.add(animation);
+        let player = AnimationPlayerBuilder::new(BaseBuilder::new())
+            .with_animations(animations)
+            .build(graph);
+        graph.update_hierarchical_data();
+
+        (player, arm, hand)
+    }
+
+    #[test]
+    fn test_pose_modifier_is_not_accumulated() {
+        let mut graph = Graph::new();
+        let (player, arm, hand) = make_skeleton(&mut graph);
+
+        let mut modifiers = PoseModifiers::default();
+        modifiers.register(player, player, PoseStage::BeforeIk, |ctx| {
+            ctx.pose
+                .bone_by_name_mut("Arm")
+                .unwrap()
+                .translate(Vector3::new(1.0, 0.0, 0.0));
+        });
+
+        // The animation does not rewrite the position, so the modification must not accumulate.
+        for _ in 0..2 {
+            modifiers.run(&mut graph, 0.1);
+        }
+
+        assert_eq!(**graph[arm].local_transform().position(), Vector3::x());
+        assert_eq!(graph[hand].global_position(), Vector3::x());
+    }
+
+    #[test]
+    fn test_pose_modifier_order() {
+        let mut graph = Graph::new();
+        let (player, _, hand) = make_skeleton(&mut graph);
+
+        let order = Rc::new(RefCell::new(Vec::new()));
+        let mut modifiers = PoseModifiers::default();
+        for stage in [PoseStage::AfterIk, PoseStage::Ik, PoseStage::BeforeIk] {
+            let order = order.clone();
+            modifiers.register(player, player, stage, move |ctx| {
+                // Global transforms must include changes of the previous stages.
+                order
+                    .borrow_mut()
+                    .push((ctx.stage, ctx.graph[hand].global_position().y));
+                ctx.pose
+                    .bone_by_name_mut("Hand")
+                    .unwrap()
+                    .translate(Vector3::y());
+            });
+        }
+
+        modifiers.run(&mut graph, 0.1);
+
+        assert_eq!(
+            *order.borrow(),
+            vec![
+                (PoseStage::BeforeIk, 0.0),
+                (PoseStage::Ik, 1.0),
+                (PoseStage::AfterIk, 2.0)
+            ]
+        );
+
+        graph.remove_node(player);
+        modifiers.run(&mut graph, 0.1);
+        assert!(modifiers.is_empty());
+    }
+}
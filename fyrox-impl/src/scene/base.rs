@@ -37,7 +37,7 @@ use crate::{
     engine::SerializationContext,
     graph::BaseSceneGraph,
     resource::model::ModelResource,
-    scene::{node::Node, transform::Transform},
+    scene::{node::Node, socket::Socket, transform::Transform},
     script::{Script, ScriptTrait},
 };
 use fyrox_core::algebra::UnitQuaternion;
@@ -118,6 +118,36 @@ impl LevelOfDetail {
     }
 }
 
+/// Defines how the value, that is compared with the ranges of the levels of detail, is calculated.
+#[derive(
+    Default,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Debug,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+    TypeUuidProvider,
+    Serialize,
+    Deserialize,
+)]
+#[type_uuid(id = "d3c0a6b4-1a4e-4c55-9c4f-7e4d1d2b5c61")]
+pub enum LodMetric {
+    /// Distance from the observer to an object, normalized to the (z_near; z_far) range of the
+    /// observer.
+    #[default]
+    Distance,
+    /// Portion of the screen height covered by the bounding sphere of an object. The value is
+    /// inverted (`1.0 - coverage`) to keep the ranges of the levels in the same order as for the
+    /// distance metric: 0.0 - the object covers the entire screen, 1.0 - the object is infinitely
+    /// small.
+    ScreenCoverage,
+}
+
 /// LOD (Level-Of-Detail) group is a set of cascades (levels), where each cascade takes specific
 /// distance range. Each cascade contains list of objects that should or shouldn't be rendered
 /// if distance satisfy cascade range. LOD may significantly improve performance if your scene
@@ -128,11 +158,60 @@ impl LevelOfDetail {
 /// Lod group must contain non-overlapping cascades, each cascade with its own set of objects
 /// that belongs to level of detail. Engine does not care if you create overlapping cascades,
 /// it is your responsibility to create non-overlapping cascades.
+///
+/// ## Crossfade
+///
+/// Instant switching between the levels could be noticeable, especially if the levels are very
+/// different. Set [`Self::crossfade`] to a non-zero value to smoothly dissolve a level into the
+/// next one. The dissolve is done by dithering, which is supported by the standard shaders (see
+/// `lodFade` property of `fyrox_instanceData`).
 #[derive(Debug, Default, Clone, Visit, Reflect, PartialEq, TypeUuidProvider)]
 #[type_uuid(id = "8e7b18b1-c1e0-47d7-b952-4394c1d049e5")]
 pub struct LodGroup {
     /// Set of cascades.
     pub levels: Vec<LevelOfDetail>,
+    /// Defines how the value, that is compared with the ranges of the levels, is calculated.
+    #[visit(optional)]
+    pub metric: LodMetric,
+    /// Width (in normalized units) of the transition zone past the end of each level. In this zone
+    /// a level dissolves into the next one. Zero means instant switching.
+    #[visit(optional)]
+    #[reflect(min_value = 0.0, max_value = 1.0)]
+    pub crossfade: f32,
+}
+
+impl LodGroup {
+    /// Calculates the visibility of the given level of the group for the given value of the
+    /// metric (see [`LodMetric`]). The result is:
+    ///
+    /// - `0.0` - the level is invisible.
+    /// - `1.0` - the level is fully visible.
+    /// - `(0.0; 1.0)` - the level is fading out, the value defines the portion of visible pixels.
+    /// - `(-1.0; 0.0)` - the level is fading in, the absolute value defines the portion of
+    ///   visible pixels. The visible pixels are exactly the ones, that are hidden by the previous
+    ///   level that is fading out at the same time.
+    pub fn visibility(&self, level: &LevelOfDetail, value: f32) -> f32 {
+        let crossfade = self.crossfade.max(0.0);
+
+        if value < level.begin() || value > level.end() + crossfade {
+            return 0.0;
+        }
+
+        if value > level.end() {
+            return 1.0 - (value - level.end()) / crossfade;
+        }
+
+        if value < level.begin() + crossfade
+            && self
+                .levels
+                .iter()
+                .any(|other| (other.end() - level.begin()).abs() <= f32::EPSILON)
+        {
+            return -(value - level.begin()) / crossfade;
+        }
+
+        1.0
+    }
 }
 
 /// Mobility defines a group for scene node which has direct impact on performance
@@ -479,6 +558,30 @@ pub struct Base {
     #[reflect(setter = "set_cast_shadows")]
     cast_shadows: InheritableVariable<bool>,
 
+    #[reflect(
+        setter = "set_render_tags",
+        description = "A set of tags that is used to include the node in custom render graph passes."
+    )]
+    render_tags: InheritableVariable<Vec<String>>,
+
+    #[reflect(
+        setter = "set_persistent",
+        description = "Whether the state of the node and its scripts should be stored in save games or not."
+    )]
+    persistent: InheritableVariable<bool>,
+
+    #[reflect(
+        setter = "set_ignore_time_scale",
+        description = "Whether the node and its descendants should ignore time scale and pause of the scene."
+    )]
+    ignore_time_scale: InheritableVariable<bool>,
+
+    #[reflect(
+        setter = "set_sockets",
+        description = "A set of named attachment points of the node."
+    )]
+    sockets: InheritableVariable<Vec<Socket>>,
+
     /// A set of custom properties that can hold almost any data. It can be used to set additional
     /// properties to scene nodes.
     #[reflect(setter = "set_properties")]
@@ -864,6 +967,72 @@ impl Base {
         self.cast_shadows.set_value_and_mark_modified(cast_shadows)
     }
 
+    /// Returns a set of render tags of the node. Render tags are used by custom render graph passes
+    /// to select which nodes should be rendered by them. See
+    /// [`crate::renderer::render_graph::RenderGraphPassContext::render_tagged`] for more info.
+    #[inline]
+    pub fn render_tags(&self) -> &[String] {
+        &self.render_tags
+    }
+
+    /// Returns `true` if the node has the given render tag.
+    #[inline]
+    pub fn has_render_tag(&self, tag: &str) -> bool {
+        self.render_tags.iter().any(|t| t == tag)
+    }
+
+    /// Sets a new set of render tags of the node and returns the old one.
+    #[inline]
+    pub fn set_render_tags(&mut self, render_tags: Vec<String>) -> Vec<String> {
+        self.render_tags.set_value_and_mark_modified(render_tags)
+    }
+
+    /// Returns `true` if the state of the node (local transform, visibility, enabled flag and
+    /// scripts) should be stored in save games. See [`crate::save`] docs for more info.
+    #[inline]
+    pub fn is_persistent(&self) -> bool {
+        *self.persistent
+    }
+
+    /// Sets whether the state of the node should be stored in save games or not. Returns the old
+    /// value.
+    #[inline]
+    pub fn set_persistent(&mut self, persistent: bool) -> bool {
+        self.persistent.set_value_and_mark_modified(persistent)
+    }
+
+    /// Returns `true` if the node (and all its descendants) ignores time scale and pause of the
+    /// scene. See [`crate::scene::graph::time::SceneTime`] docs for more info.
+    #[inline]
+    pub fn is_ignoring_time_scale(&self) -> bool {
+        *self.ignore_time_scale
+    }
+
+    /// Sets whether the node (and all its descendants) should ignore time scale and pause of the
+    /// scene. It is useful for UI, pause menus, etc. Returns the old value.
+    #[inline]
+    pub fn set_ignore_time_scale(&mut self, ignore: bool) -> bool {
+        self.ignore_time_scale.set_value_and_mark_modified(ignore)
+    }
+
+    /// Returns a slice of sockets of the node. See [`Socket`] docs for more info.
+    #[inline]
+    pub fn sockets(&self) -> &[Socket] {
+        &self.sockets
+    }
+
+    /// Searches for a socket with the given name.
+    #[inline]
+    pub fn socket(&self, name: &str) -> Option<&Socket> {
+        self.sockets.iter().find(|socket| socket.name == name)
+    }
+
+    /// Sets new sockets of the node. Returns the old sockets.
+    #[inline]
+    pub fn set_sockets(&mut self, sockets: Vec<Socket>) -> Vec<Socket> {
+        self.sockets.set_value_and_mark_modified(sockets)
+    }
+
     /// Returns current instance id.
     pub fn instance_id(&self) -> SceneNodeId {
         self.instance_id
@@ -1204,8 +1373,12 @@ impl Visit for Base {
         let _ = self.properties.visit("Properties", &mut region);
         let _ = self.frustum_culling.visit("FrustumCulling", &mut region);
         let _ = self.cast_shadows.visit("CastShadows", &mut region);
+        let _ = self.render_tags.visit("RenderTags", &mut region);
         let _ = self.instance_id.visit("InstanceId", &mut region);
         let _ = self.enabled.visit("Enabled", &mut region);
+        let _ = self.persistent.visit("Persistent", &mut region);
+        let _ = self.ignore_time_scale.visit("IgnoreTimeScale", &mut region);
+        let _ = self.sockets.visit("Sockets", &mut region);
 
         // Script visiting may fail for various reasons:
         //
@@ -1244,9 +1417,13 @@ pub struct BaseBuilder {
     tag: String,
     frustum_culling: bool,
     cast_shadows: bool,
+    render_tags: Vec<String>,
     scripts: Vec<ScriptRecord>,
     instance_id: SceneNodeId,
     enabled: bool,
+    persistent: bool,
+    ignore_time_scale: bool,
+    sockets: Vec<Socket>,
 }
 
 impl Default for BaseBuilder {
@@ -1271,9 +1448,13 @@ impl BaseBuilder {
             tag: Default::default(),
             frustum_culling: true,
             cast_shadows: true,
+            render_tags: Default::default(),
             scripts: vec![],
             instance_id: SceneNodeId(Uuid::new_v4()),
             enabled: true,
+            persistent: false,
+            ignore_time_scale: false,
+            sockets: Default::default(),
         }
     }
 
@@ -1367,6 +1548,36 @@ impl BaseBuilder {
         self
     }
 
+    /// Sets a set of render tags of the node. See [`Base::render_tags`] for more info.
+    #[inline]
+    pub fn with_render_tags(mut self, render_tags: Vec<String>) -> Self {
+        self.render_tags = render_tags;
+        self
+    }
+
+    /// Sets whether the state of the node should be stored in save games or not. See
+    /// [`Base::is_persistent`] for more info.
+    #[inline]
+    pub fn with_persistent(mut self, persistent: bool) -> Self {
+        self.persistent = persistent;
+        self
+    }
+
+    /// Sets whether the node should ignore time scale of the scene or not. See
+    /// [`Base::is_ignoring_time_scale`] for more info.
+    #[inline]
+    pub fn with_ignore_time_scale(mut self, ignore: bool) -> Self {
+        self.ignore_time_scale = ignore;
+        self
+    }
+
+    /// Sets the desired sockets of the node. See [`Socket`] docs for more info.
+    #[inline]
+    pub fn with_sockets(mut self, sockets: Vec<Socket>) -> Self {
+        self.sockets = sockets;
+        self
+    }
+
     /// Sets script of the node.
     #[inline]
     pub fn with_script<T>(mut self, script: T) -> Self
@@ -1418,6 +1629,10 @@ impl BaseBuilder {
             properties: Default::default(),
             frustum_culling: self.frustum_culling.into(),
             cast_shadows: self.cast_shadows.into(),
+            render_tags: self.render_tags.into(),
+            persistent: self.persistent.into(),
+            ignore_time_scale: self.ignore_time_scale.into(),
+            sockets: self.sockets.into(),
             scripts: self.scripts,
             instance_id: SceneNodeId(Uuid::new_v4()),
 
@@ -444,12 +444,19 @@ impl Camera {
     /// this method, it will be called automatically when new frame starts.
     #[inline]
     pub fn calculate_matrices(&mut self, frame_size: Vector2<f32>) {
+        self.update_view_matrix();
+        self.projection_matrix = self.projection.matrix(frame_size);
+    }
+
+    /// Recalculates view matrix only, using current global transform of the camera. Could be used
+    /// to apply late changes of the camera transform (see [`crate::scene::late_update`]).
+    #[inline]
+    pub fn update_view_matrix(&mut self) {
         let pos = self.base.global_position();
         let look = self.base.look_vector();
         let up = self.base.up_vector();
 
         self.view_matrix = Matrix4::look_at_rh(&Point3::from(pos), &Point3::from(pos + look), &up);
-        self.projection_matrix = self.projection.matrix(frame_size);
     }
 
     /// Sets new viewport in resolution-independent format. In other words
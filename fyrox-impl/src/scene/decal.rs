@@ -38,7 +38,8 @@ use crate::{
     scene::{
         base::{Base, BaseBuilder},
         graph::Graph,
-        node::{Node, NodeTrait},
+        mesh::Mesh,
+        node::{Node, NodeTrait, UpdateContext},
     },
 };
 use fyrox_graph::constructor::ConstructorProvider;
@@ -64,8 +65,21 @@ use std::ops::{Deref, DerefMut};
 ///
 /// # Supported maps
 ///
-/// Currently, only diffuse and normal maps are supported. Diffuse and normal maps will be automatically projected
-/// on the data stored in G-Buffer.
+/// Diffuse, normal and ORM (occlusion (R), roughness (G), metallic (B)) maps are supported. All of them will be
+/// automatically projected on the data stored in G-Buffer. Normal and ORM maps are optional, if a map is not
+/// set, the respective data in G-Buffer stays untouched.
+///
+/// # Fading and lifetime
+///
+/// Decals could be faded in and out smoothly, see [`Decal::set_fade_in_time`] and [`Decal::set_fade_out_time`].
+/// Fade out starts when the remaining lifetime of the decal (see [`Base::set_lifetime`]) is less than fade out
+/// time, which is useful for temporary decals like bullet holes.
+///
+/// # Skinned meshes
+///
+/// Decals are projected in screen space, which means that a decal will "slide" over an animated skinned mesh
+/// unless it moves together with the mesh. Use [`Decal::attach_to_skinned_mesh`] to link a decal to the closest
+/// bone of a skinned mesh, so it will follow the animation (bullet wounds on characters, etc.).
 ///
 /// # Limitations
 ///
@@ -116,12 +130,28 @@ pub struct Decal {
     #[reflect(setter = "set_normal_texture")]
     normal_texture: InheritableVariable<Option<TextureResource>>,
 
+    #[reflect(setter = "set_orm_texture")]
+    #[visit(optional)]
+    orm_texture: InheritableVariable<Option<TextureResource>>,
+
     #[reflect(setter = "set_color")]
     color: InheritableVariable<Color>,
 
     #[reflect(min_value = 0.0)]
     #[reflect(setter = "set_layer")]
     layer: InheritableVariable<u8>,
+
+    #[reflect(min_value = 0.0, setter = "set_fade_in_time")]
+    #[visit(optional)]
+    fade_in_time: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0, setter = "set_fade_out_time")]
+    #[visit(optional)]
+    fade_out_time: InheritableVariable<f32>,
+
+    #[reflect(hidden)]
+    #[visit(optional)]
+    age: f32,
 }
 
 impl Deref for Decal {
@@ -187,6 +217,28 @@ impl Decal {
         (*self.normal_texture).clone()
     }
 
+    /// Sets new ORM texture. ORM texture contains ambient occlusion (R), roughness (G) and metallic (B)
+    /// values, that will replace the respective values in G-Buffer.
+    pub fn set_orm_texture(
+        &mut self,
+        orm_texture: Option<TextureResource>,
+    ) -> Option<TextureResource> {
+        std::mem::replace(
+            self.orm_texture.get_value_mut_and_mark_modified(),
+            orm_texture,
+        )
+    }
+
+    /// Returns current ORM texture.
+    pub fn orm_texture(&self) -> Option<&TextureResource> {
+        self.orm_texture.as_ref()
+    }
+
+    /// Returns current ORM texture.
+    pub fn orm_texture_value(&self) -> Option<TextureResource> {
+        (*self.orm_texture).clone()
+    }
+
     /// Sets new color for the decal.
     pub fn set_color(&mut self, color: Color) -> Color {
         self.color.set_value_and_mark_modified(color)
@@ -210,6 +262,82 @@ impl Decal {
     pub fn layer(&self) -> u8 {
         *self.layer
     }
+
+    /// Sets the time (in seconds) during which the decal will fade in after its creation.
+    pub fn set_fade_in_time(&mut self, time: f32) -> f32 {
+        self.fade_in_time.set_value_and_mark_modified(time.max(0.0))
+    }
+
+    /// Returns current fade in time.
+    pub fn fade_in_time(&self) -> f32 {
+        *self.fade_in_time
+    }
+
+    /// Sets the time (in seconds) during which the decal will fade out before the end of its lifetime.
+    /// Has no effect if the decal has unlimited lifetime.
+    pub fn set_fade_out_time(&mut self, time: f32) -> f32 {
+        self.fade_out_time
+            .set_value_and_mark_modified(time.max(0.0))
+    }
+
+    /// Returns current fade out time.
+    pub fn fade_out_time(&self) -> f32 {
+        *self.fade_out_time
+    }
+
+    /// Returns current opacity factor of the decal in `[0; 1]` range, that is defined by fade in and
+    /// fade out times.
+    pub fn fade_factor(&self) -> f32 {
+        let fade_in = if *self.fade_in_time > 0.0 {
+            self.age / *self.fade_in_time
+        } else {
+            1.0
+        };
+
+        let fade_out = match self.lifetime() {
+            Some(lifetime) if *self.fade_out_time > 0.0 => lifetime / *self.fade_out_time,
+            _ => 1.0,
+        };
+
+        fade_in.min(fade_out).clamp(0.0, 1.0)
+    }
+
+    /// Links the given decal to the closest (to the decal) bone of the given skinned mesh, so the decal will
+    /// follow the animation of the mesh. Global position and rotation of the decal are preserved. Returns a
+    /// handle of the bone the decal was attached to, or [`Handle::NONE`] if the given node is not a skinned
+    /// mesh. In this case the decal is left untouched.
+    pub fn attach_to_skinned_mesh(
+        graph: &mut Graph,
+        decal: Handle<Node>,
+        mesh: Handle<Node>,
+    ) -> Handle<Node> {
+        let Some(decal_position) = graph.try_get(decal).map(|decal| decal.global_position()) else {
+            return Handle::NONE;
+        };
+
+        let Some(mesh) = graph.try_get(mesh).and_then(|n| n.cast::<Mesh>()) else {
+            return Handle::NONE;
+        };
+
+        let mut closest = None;
+        for &bone in mesh.surfaces().iter().flat_map(|surface| surface.bones()) {
+            if let Some(bone_node) = graph.try_get(bone) {
+                let sqr_distance = (bone_node.global_position() - decal_position).norm_squared();
+                if closest.map_or(true, |(_, closest_sqr_distance)| {
+                    sqr_distance < closest_sqr_distance
+                }) {
+                    closest = Some((bone, sqr_distance));
+                }
+            }
+        }
+
+        if let Some((bone, _)) = closest {
+            graph.link_nodes_keep_global_position_rotation(decal, bone);
+            bone
+        } else {
+            Handle::NONE
+        }
+    }
 }
 
 impl ConstructorProvider<Node, Graph> for Decal {
@@ -238,6 +366,10 @@ impl NodeTrait for Decal {
     fn id(&self) -> Uuid {
         Self::type_uuid()
     }
+
+    fn update(&mut self, context: &mut UpdateContext) {
+        self.age += context.dt;
+    }
 }
 
 /// Allows you to create a Decal in a declarative manner.
@@ -245,8 +377,11 @@ pub struct DecalBuilder {
     base_builder: BaseBuilder,
     diffuse_texture: Option<TextureResource>,
     normal_texture: Option<TextureResource>,
+    orm_texture: Option<TextureResource>,
     color: Color,
     layer: u8,
+    fade_in_time: f32,
+    fade_out_time: f32,
 }
 
 impl DecalBuilder {
@@ -256,8 +391,11 @@ impl DecalBuilder {
             base_builder,
             diffuse_texture: None,
             normal_texture: None,
+            orm_texture: None,
             color: Color::opaque(255, 255, 255),
             layer: 0,
+            fade_in_time: 0.0,
+            fade_out_time: 0.0,
         }
     }
 
@@ -273,6 +411,12 @@ impl DecalBuilder {
         self
     }
 
+    /// Sets desired ORM texture.
+    pub fn with_orm_texture(mut self, orm_texture: TextureResource) -> Self {
+        self.orm_texture = Some(orm_texture);
+        self
+    }
+
     /// Sets desired decal color.
     pub fn with_color(mut self, color: Color) -> Self {
         self.color = color;
@@ -285,14 +429,30 @@ impl DecalBuilder {
         self
     }
 
+    /// Sets desired fade in time.
+    pub fn with_fade_in_time(mut self, time: f32) -> Self {
+        self.fade_in_time = time;
+        self
+    }
+
+    /// Sets desired fade out time.
+    pub fn with_fade_out_time(mut self, time: f32) -> Self {
+        self.fade_out_time = time;
+        self
+    }
+
     /// Creates new Decal node.
     pub fn build_decal(self) -> Decal {
         Decal {
             base: self.base_builder.build_base(),
             diffuse_texture: self.diffuse_texture.into(),
             normal_texture: self.normal_texture.into(),
+            orm_texture: self.orm_texture.into(),
             color: self.color.into(),
             layer: self.layer.into(),
+            fade_in_time: self.fade_in_time.max(0.0).into(),
+            fade_out_time: self.fade_out_time.max(0.0).into(),
+            age: 0.0,
         }
     }
 
@@ -306,3 +466,29 @@ impl DecalBuilder {
         graph.add_node(self.build_node())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::scene::{base::BaseBuilder, decal::DecalBuilder};
+
+    #[test]
+    fn test_decal_fade_factor() {
+        let mut decal = DecalBuilder::new(BaseBuilder::new().with_lifetime(10.0))
+            .with_fade_in_time(2.0)
+            .with_fade_out_time(4.0)
+            .build_decal();
+        assert_eq!(decal.fade_factor(), 0.0);
+
+        decal.age = 1.0;
+        assert_eq!(decal.fade_factor(), 0.5);
+
+        decal.age = 3.0;
+        assert_eq!(decal.fade_factor(), 1.0);
+
+        decal.set_lifetime(Some(1.0));
+        assert_eq!(decal.fade_factor(), 0.25);
+
+        decal.set_lifetime(None);
+        assert_eq!(decal.fade_factor(), 1.0);
+    }
+}
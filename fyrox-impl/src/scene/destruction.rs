@@ -0,0 +1,950 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Destruction subsystem, that allows to break meshes into pieces on impacts. See [`Destructible`]
+//! docs for more info.
+
+use crate::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{Matrix4, Point3, UnitQuaternion, Vector2, Vector3, Vector4},
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        uuid::{uuid, Uuid},
+        uuid_provider,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    material::MaterialResource,
+    rand::{rngs::StdRng, Rng, SeedableRng},
+    scene::{
+        base::{Base, BaseBuilder},
+        collider::{
+            Collider, ColliderBuilder, ColliderShape, ConvexPolyhedronShape, GeometrySource,
+        },
+        graph::Graph,
+        mesh::{
+            buffer::{
+                TriangleBuffer, TriangleDefinition, VertexAttributeUsage, VertexBuffer,
+                VertexReadTrait,
+            },
+            surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+            vertex::StaticVertex,
+            Mesh, MeshBuilder,
+        },
+        node::{constructor::NodeConstructor, Node, NodeTrait, UpdateContext},
+        rigidbody::{RigidBody, RigidBodyBuilder},
+        transform::TransformBuilder,
+        Scene,
+    },
+};
+use fyrox_graph::{constructor::ConstructorProvider, BaseSceneGraph, SceneGraphNode};
+use std::ops::{Deref, DerefMut};
+
+/// Distance (in meters) at which a point is considered to lie on a cutting plane.
+const PLANE_EPSILON: f32 = 1.0e-5;
+
+/// Material index of polygons, that were created by cutting planes.
+const CAP: usize = usize::MAX;
+
+#[derive(Clone, Debug)]
+struct Polygon {
+    vertices: Vec<StaticVertex>,
+    surface: usize,
+}
+
+fn lerp_vertex(a: &StaticVertex, b: &StaticVertex, t: f32) -> StaticVertex {
+    let tangent = a.tangent.xyz().lerp(&b.tangent.xyz(), t);
+    StaticVertex {
+        position: a.position.lerp(&b.position, t),
+        tex_coord: a.tex_coord.lerp(&b.tex_coord, t),
+        normal: a
+            .normal
+            .lerp(&b.normal, t)
+            .try_normalize(f32::EPSILON)
+            .unwrap_or(a.normal),
+        tangent: Vector4::new(tangent.x, tangent.y, tangent.z, a.tangent.w),
+    }
+}
+
+fn read_polygons(data: &SurfaceData, transform: &Matrix4<f32>, surface: usize) -> Vec<Polygon> {
+    let normal_matrix = transform.try_inverse().unwrap_or_default().transpose();
+
+    let read_vertex = |index: u32| {
+        let view = data.vertex_buffer.get(index as usize)?;
+        let position = view.read_3_f32(VertexAttributeUsage::Position).ok()?;
+        let normal = view
+            .read_3_f32(VertexAttributeUsage::Normal)
+            .unwrap_or_default();
+        let tangent = view
+            .read_4_f32(VertexAttributeUsage::Tangent)
+            .unwrap_or_default();
+        let new_tangent = normal_matrix.transform_vector(&tangent.xyz());
+        Some(StaticVertex {
+            position: transform.transform_point(&Point3::from(position)).coords,
+            tex_coord: view
+                .read_2_f32(VertexAttributeUsage::TexCoord0)
+                .unwrap_or_default(),
+            normal: normal_matrix
+                .transform_vector(&normal)
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_default(),
+            tangent: Vector4::new(new_tangent.x, new_tangent.y, new_tangent.z, tangent.w),
+        })
+    };
+
+    data.geometry_buffer
+        .iter()
+        .filter_map(|triangle| {
+            Some(Polygon {
+                vertices: vec![
+                    read_vertex(triangle[0])?,
+                    read_vertex(triangle[1])?,
+                    read_vertex(triangle[2])?,
+                ],
+                surface,
+            })
+        })
+        .collect()
+}
+
+/// Builds convex hull of a set of points lying on a plane with the given normal. The hull is
+/// wound counterclockwise around the normal.
+fn plane_convex_hull(points: &[Vector3<f32>], normal: Vector3<f32>) -> Vec<Vector3<f32>> {
+    let (u, v) = plane_basis(normal);
+
+    let mut projected = points
+        .iter()
+        .map(|p| (Vector2::new(u.dot(p), v.dot(p)), *p))
+        .collect::<Vec<_>>();
+    projected.sort_by(|a, b| {
+        a.0.x
+            .total_cmp(&b.0.x)
+            .then_with(|| a.0.y.total_cmp(&b.0.y))
+    });
+    projected.dedup_by(|a, b| (a.0 - b.0).norm() <= PLANE_EPSILON);
+
+    if projected.len() < 3 {
+        return Vec::new();
+    }
+
+    let cross = |o: Vector2<f32>, a: Vector2<f32>, b: Vector2<f32>| {
+        (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x)
+    };
+
+    // Andrew's monotone chain.
+    let mut hull: Vec<(Vector2<f32>, Vector3<f32>)> = Vec::with_capacity(projected.len() * 2);
+    for reverse in [false, true] {
+        let start = hull.len();
+        let points = if reverse {
+            projected.iter().rev().collect::<Vec<_>>()
+        } else {
+            projected.iter().collect::<Vec<_>>()
+        };
+        for point in points {
+            while hull.len() >= start + 2
+                && cross(hull[hull.len() - 2].0, hull[hull.len() - 1].0, point.0) <= 0.0
+            {
+                hull.pop();
+            }
+            hull.push(*point);
+        }
+        hull.pop();
+    }
+
+    hull.into_iter().map(|(_, p)| p).collect()
+}
+
+fn plane_basis(normal: Vector3<f32>) -> (Vector3<f32>, Vector3<f32>) {
+    let reference = if normal.x.abs() < 0.9 {
+        Vector3::x()
+    } else {
+        Vector3::y()
+    };
+    let u = normal.cross(&reference).normalize();
+    let v = normal.cross(&u);
+    (u, v)
+}
+
+/// Clips the polygons by the plane and keeps the part, that lies behind it. The hole made by the
+/// plane is closed by a cap polygon.
+fn clip_by_plane(polygons: Vec<Polygon>, normal: Vector3<f32>, d: f32) -> Vec<Polygon> {
+    let mut result = Vec::with_capacity(polygons.len());
+    let mut cut_points = Vec::new();
+    let mut clipped = false;
+
+    for polygon in polygons {
+        let distances = polygon
+            .vertices
+            .iter()
+            .map(|v| normal.dot(&v.position) - d)
+            .collect::<Vec<_>>();
+
+        if distances.iter().all(|d| *d <= PLANE_EPSILON) {
+            for (vertex, distance) in polygon.vertices.iter().zip(distances.iter()) {
+                if distance.abs() <= PLANE_EPSILON {
+                    cut_points.push(vertex.position);
+                }
+            }
+            result.push(polygon);
+            continue;
+        }
+
+        clipped = true;
+
+        let count = polygon.vertices.len();
+        let mut vertices = Vec::with_capacity(count + 1);
+        for i in 0..count {
+            let (a, da) = (&polygon.vertices[i], distances[i]);
+            let (b, db) = (
+                &polygon.vertices[(i + 1) % count],
+                distances[(i + 1) % count],
+            );
+
+            let a_inside = da <= PLANE_EPSILON;
+            let b_inside = db <= PLANE_EPSILON;
+
+            if a_inside {
+                vertices.push(*a);
+                if da.abs() <= PLANE_EPSILON {
+                    cut_points.push(a.position);
+                }
+            }
+
+            if a_inside != b_inside && da.abs() > PLANE_EPSILON && db.abs() > PLANE_EPSILON {
+                let vertex = lerp_vertex(a, b, da / (da - db));
+                cut_points.push(vertex.position);
+                vertices.push(vertex);
+            }
+        }
+
+        if vertices.len() >= 3 {
+            result.push(Polygon {
+                vertices,
+                surface: polygon.surface,
+            });
+        }
+    }
+
+    if clipped && !result.is_empty() {
+        let hull = plane_convex_hull(&cut_points, normal);
+        if hull.len() >= 3 {
+            let (u, v) = plane_basis(normal);
+            result.push(Polygon {
+                vertices: hull
+                    .into_iter()
+                    .map(|position| StaticVertex {
+                        position,
+                        tex_coord: Vector2::new(u.dot(&position), v.dot(&position)),
+                        normal,
+                        tangent: Vector4::new(u.x, u.y, u.z, 1.0),
+                    })
+                    .collect(),
+                surface: CAP,
+            });
+        }
+    }
+
+    result
+}
+
+/// Returns a part of the polygons, that lies inside the Voronoi cell of the site with the given
+/// index.
+fn voronoi_cell(polygons: &[Polygon], sites: &[Vector3<f32>], index: usize) -> Vec<Polygon> {
+    let site = sites[index];
+    let mut cell = polygons.to_vec();
+    for (other_index, other) in sites.iter().enumerate() {
+        if other_index == index {
+            continue;
+        }
+        let Some(normal) = (other - site).try_normalize(f32::EPSILON) else {
+            continue;
+        };
+        let d = normal.dot(&((site + other).scale(0.5)));
+        cell = clip_by_plane(cell, normal, d);
+        if cell.is_empty() {
+            break;
+        }
+    }
+    cell
+}
+
+/// Calculates volume and center of mass of a closed polygonal mesh. Falls back to the average
+/// of vertices if the mesh is not closed or degenerate.
+fn volume_and_center(polygons: &[Polygon]) -> (f32, Vector3<f32>) {
+    let Some(origin) = polygons
+        .first()
+        .and_then(|p| p.vertices.first())
+        .map(|v| v.position)
+    else {
+        return (0.0, Vector3::default());
+    };
+
+    let mut volume = 0.0;
+    let mut weighted_center = Vector3::default();
+    let mut average = Vector3::default();
+    let mut count = 0;
+    for polygon in polygons {
+        let a = polygon.vertices[0].position - origin;
+        for pair in polygon.vertices[1..].windows(2) {
+            let b = pair[0].position - origin;
+            let c = pair[1].position - origin;
+            let tetra_volume = a.dot(&b.cross(&c)) / 6.0;
+            volume += tetra_volume;
+            weighted_center += (a + b + c).scale(tetra_volume / 4.0);
+        }
+        for vertex in polygon.vertices.iter() {
+            average += vertex.position;
+            count += 1;
+        }
+    }
+
+    if volume.abs() > f32::EPSILON {
+        (volume.abs(), origin + weighted_center.unscale(volume))
+    } else {
+        (volume.abs(), average.unscale(count as f32))
+    }
+}
+
+fn make_surface_data<'a>(
+    polygons: impl Iterator<Item = &'a Polygon>,
+    offset: Vector3<f32>,
+) -> Option<SurfaceData> {
+    let mut vertices = Vec::new();
+    let mut triangles = Vec::new();
+    for polygon in polygons {
+        let first = vertices.len() as u32;
+        vertices.extend(polygon.vertices.iter().map(|v| StaticVertex {
+            position: v.position - offset,
+            ..*v
+        }));
+        for i in 1..polygon.vertices.len() as u32 - 1 {
+            triangles.push(TriangleDefinition([first, first + i, first + i + 1]));
+        }
+    }
+
+    if triangles.is_empty() {
+        None
+    } else {
+        Some(SurfaceData::new(
+            VertexBuffer::new(vertices.len(), vertices).unwrap(),
+            TriangleBuffer::new(triangles),
+        ))
+    }
+}
+
+/// Generates a set of random points inside the given bounds, that could be used as Voronoi sites
+/// for [`fracture`]. The same seed always produces the same set of points.
+pub fn voronoi_sites(
+    bounds: &AxisAlignedBoundingBox,
+    count: usize,
+    seed: u64,
+) -> Vec<Vector3<f32>> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..count)
+        .map(|_| {
+            Vector3::new(
+                rng.gen_range(0.0..=1.0),
+                rng.gen_range(0.0..=1.0),
+                rng.gen_range(0.0..=1.0),
+            )
+        })
+        .map(|t| bounds.min + (bounds.max - bounds.min).component_mul(&t))
+        .collect()
+}
+
+/// A piece of a fractured surface.
+#[derive(Debug)]
+pub struct FractureChunk {
+    /// Geometry of the chunk. Vertex positions are relative to the [`Self::center`].
+    pub data: SurfaceData,
+    /// Center of mass of the chunk in the coordinates of the source surface.
+    pub center: Vector3<f32>,
+    /// Volume of the chunk.
+    pub volume: f32,
+}
+
+/// Splits the surface into a set of pieces using Voronoi diagram built from the given set of
+/// sites (see [`voronoi_sites`]). Every piece is closed with caps along the cutting planes. The
+/// result is exact for convex surfaces, concave surfaces produce pieces with approximate caps.
+/// Sites, that lie outside the surface, produce no pieces.
+pub fn fracture(data: &SurfaceData, sites: &[Vector3<f32>]) -> Vec<FractureChunk> {
+    let polygons = read_polygons(data, &Matrix4::identity(), 0);
+    (0..sites.len())
+        .filter_map(|index| {
+            let cell = voronoi_cell(&polygons, sites, index);
+            let (volume, center) = volume_and_center(&cell);
+            Some(FractureChunk {
+                data: make_surface_data(cell.iter(), center)?,
+                center,
+                volume,
+            })
+        })
+        .collect()
+}
+
+/// Options of [`prefracture`].
+#[derive(Clone, Debug)]
+pub struct FractureOptions {
+    /// Desired amount of chunks. Actual amount could be less, because some Voronoi cells could
+    /// be outside of the mesh.
+    pub chunk_count: usize,
+    /// Seed for the random generator of Voronoi sites.
+    pub seed: u64,
+    /// Material, that will be used for the inner faces of chunks. If not set, the material of
+    /// the first surface will be used.
+    pub interior_material: Option<MaterialResource>,
+}
+
+impl Default for FractureOptions {
+    fn default() -> Self {
+        Self {
+            chunk_count: 16,
+            seed: 0,
+            interior_material: None,
+        }
+    }
+}
+
+/// Pre-fractures the intact body of the destructible node. Every mesh, that is a direct child of
+/// the intact body, is split into a set of convex chunks. Every chunk is represented by a
+/// disabled rigid body with a mesh and a convex polyhedron collider, attached to the
+/// destructible node. Previously created chunks are removed. The mass of the intact body is
+/// distributed across the chunks proportionally to their volume. Returns handles of the new chunk
+/// rigid bodies.
+///
+/// This function could be used either at runtime or at the edit/import time, chunks are
+/// serialized with the scene as regular nodes.
+pub fn prefracture(
+    graph: &mut Graph,
+    destructible: Handle<Node>,
+    options: &FractureOptions,
+) -> Vec<Handle<Node>> {
+    let Some(destructible_ref) = graph
+        .try_get(destructible)
+        .and_then(|n| n.component_ref::<Destructible>())
+    else {
+        return Vec::new();
+    };
+    let intact = *destructible_ref.intact;
+    let old_chunks = destructible_ref
+        .chunks
+        .iter()
+        .map(|c| c.body)
+        .collect::<Vec<_>>();
+    for old_chunk in old_chunks {
+        if graph.is_valid_handle(old_chunk) {
+            graph.remove_node(old_chunk);
+        }
+    }
+
+    let Some(intact_ref) = graph.try_get(intact) else {
+        return Vec::new();
+    };
+    let Some(intact_body) = intact_ref.component_ref::<RigidBody>() else {
+        return Vec::new();
+    };
+    let total_mass = intact_body.mass();
+    let intact_transform = intact_ref.local_transform().clone();
+
+    let mut polygons = Vec::new();
+    let mut materials = Vec::new();
+    for child in intact_ref.children() {
+        let Some(mesh) = graph[*child].cast::<Mesh>() else {
+            continue;
+        };
+        let transform = mesh.local_transform().matrix();
+        for surface in mesh.surfaces() {
+            let data = surface.data();
+            let data = data.data_ref();
+            polygons.extend(read_polygons(&data, &transform, materials.len()));
+            materials.push(surface.material().clone());
+        }
+    }
+
+    if polygons.is_empty() {
+        return Vec::new();
+    }
+    let bounds = AxisAlignedBoundingBox::from_points(
+        &polygons
+            .iter()
+            .flat_map(|p| p.vertices.iter().map(|v| v.position))
+            .collect::<Vec<_>>(),
+    );
+
+    let interior_material = options
+        .interior_material
+        .clone()
+        .or_else(|| materials.first().cloned())
+        .unwrap_or_default();
+
+    let sites = voronoi_sites(&bounds, options.chunk_count, options.seed);
+    let cells = (0..sites.len())
+        .map(|index| {
+            let cell = voronoi_cell(&polygons, &sites, index);
+            let (volume, center) = volume_and_center(&cell);
+            (cell, volume, center)
+        })
+        .filter(|(cell, _, _)| !cell.is_empty())
+        .collect::<Vec<_>>();
+    let total_volume = cells.iter().map(|(_, volume, _)| *volume).sum::<f32>();
+
+    let mut chunks = Vec::with_capacity(cells.len());
+    for (index, (cell, volume, center)) in cells.into_iter().enumerate() {
+        let surfaces = materials
+            .iter()
+            .enumerate()
+            .map(|(surface_index, material)| (surface_index, material.clone()))
+            .chain(std::iter::once((CAP, interior_material.clone())))
+            .filter_map(|(surface_index, material)| {
+                let data =
+                    make_surface_data(cell.iter().filter(|p| p.surface == surface_index), center)?;
+                Some(
+                    SurfaceBuilder::new(SurfaceResource::new_ok(ResourceKind::Embedded, data))
+                        .with_material(material)
+                        .build(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let mesh = MeshBuilder::new(BaseBuilder::new().with_name("ChunkMesh"))
+            .with_surfaces(surfaces)
+            .build(graph);
+        let collider = ColliderBuilder::new(BaseBuilder::new().with_name("ChunkCollider"))
+            .with_shape(ColliderShape::Polyhedron(ConvexPolyhedronShape {
+                geometry_source: GeometrySource(mesh),
+            }))
+            .build(graph);
+
+        let rotation = **intact_transform.rotation();
+        let mass = if total_volume > f32::EPSILON {
+            total_mass * volume / total_volume
+        } else {
+            total_mass
+        };
+        let body = RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_name(format!("Chunk{index}"))
+                .with_enabled(false)
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(**intact_transform.position() + rotation * center)
+                        .with_local_rotation(rotation)
+                        .build(),
+                )
+                .with_children(&[mesh, collider]),
+        )
+        .with_mass(mass.max(f32::EPSILON))
+        .build(graph);
+        graph.link_nodes(body, destructible);
+
+        chunks.push(DestructibleChunk {
+            body,
+            offset: center,
+            volume,
+        });
+    }
+
+    let handles = chunks.iter().map(|c| c.body).collect();
+    if let Some(destructible) = graph[destructible].component_mut::<Destructible>() {
+        destructible.chunks.set_value_and_mark_modified(chunks);
+        destructible.fractured = false;
+        destructible.active_chunks.clear();
+    }
+    handles
+}
+
+/// A pre-fractured piece of a [`Destructible`].
+#[derive(Clone, Debug, Default, PartialEq, Reflect, Visit)]
+pub struct DestructibleChunk {
+    /// A handle of the rigid body of the chunk.
+    pub body: Handle<Node>,
+    /// Position of the center of mass of the chunk in the local coordinates of the intact body.
+    pub offset: Vector3<f32>,
+    /// Volume of the chunk.
+    #[reflect(min_value = 0.0)]
+    pub volume: f32,
+}
+
+uuid_provider!(DestructibleChunk = "6c0d1f67-1b5e-4a3e-8f7c-2a9e0c4d7b15");
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct ActiveChunk {
+    body: Handle<Node>,
+    age: f32,
+}
+
+/// Destructible is a node, that replaces an intact rigid body with a set of pre-fractured chunks,
+/// when the body receives an impact that is stronger than the force threshold.
+///
+/// ## Hierarchy
+///
+/// The intact rigid body (with its colliders and meshes) must be a direct child of the
+/// destructible node. Chunks are created by [`prefracture`] function and are attached to the
+/// destructible node as well. Chunks are disabled until the fracture happens, at this moment the
+/// intact body is disabled and the chunks are enabled at the current position of the intact body
+/// with its velocity.
+///
+/// ## Pooling
+///
+/// Chunks are created only once, the destructible node just enables and disables them. Only
+/// [`Self::max_active_chunks`] largest chunks are activated on fracture, small debris stays in the
+/// pool. Active chunks are disabled again once their age exceeds [`Self::chunk_lifetime`]. Use
+/// [`Self::reset`] to restore the intact body and return every chunk back to the pool.
+#[derive(Clone, Reflect, Visit, Debug, ComponentProvider)]
+#[visit(optional)]
+pub struct Destructible {
+    base: Base,
+    /// A handle of the intact rigid body.
+    pub intact: InheritableVariable<Handle<Node>>,
+    /// A set of pre-fractured chunks. See [`prefracture`].
+    pub chunks: InheritableVariable<Vec<DestructibleChunk>>,
+    /// Minimal contact force (in Newtons), that breaks the intact body.
+    #[reflect(min_value = 0.0)]
+    pub force_threshold: InheritableVariable<f32>,
+    /// Time (in seconds) after which a chunk is returned back to the pool. `None` means that chunks
+    /// will live forever.
+    pub chunk_lifetime: InheritableVariable<Option<f32>>,
+    /// Maximum amount of chunks, that can be active at the same time.
+    pub max_active_chunks: InheritableVariable<usize>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    fractured: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    fracture_requested: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    reset_requested: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    active_chunks: Vec<ActiveChunk>,
+}
+
+impl Default for Destructible {
+    fn default() -> Self {
+        DestructibleBuilder::new(BaseBuilder::new()).build_destructible()
+    }
+}
+
+impl Deref for Destructible {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for Destructible {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for Destructible {
+    fn type_uuid() -> Uuid {
+        uuid!("b7e4f5a2-93c1-4d0e-a6b8-1f2c3d4e5a6b")
+    }
+}
+
+impl ConstructorProvider<Node, Graph> for Destructible {
+    fn constructor() -> NodeConstructor {
+        NodeConstructor::new::<Self>()
+            .with_variant("Destructible", |_| {
+                DestructibleBuilder::new(BaseBuilder::new().with_name("Destructible"))
+                    .build_node()
+                    .into()
+            })
+            .with_group("Physics")
+    }
+}
+
+impl Destructible {
+    /// Returns `true` if the intact body was replaced with chunks.
+    pub fn is_fractured(&self) -> bool {
+        self.fractured
+    }
+
+    /// Requests the fracture of the intact body regardless of the impact force. The fracture will
+    /// happen on the next update.
+    pub fn fracture(&mut self) {
+        self.fracture_requested = true;
+    }
+
+    /// Requests the reset of the destructible. On the next update every chunk will be returned to
+    /// the pool and the intact body will be enabled again.
+    pub fn reset(&mut self) {
+        self.reset_requested = true;
+    }
+
+    /// Returns handles of the chunks, that are currently active.
+    pub fn active_chunks(&self) -> impl Iterator<Item = Handle<Node>> + '_ {
+        self.active_chunks.iter().map(|c| c.body)
+    }
+
+    fn max_contact_force(&self, ctx: &UpdateContext) -> f32 {
+        let Some(intact) = ctx.nodes.try_borrow(*self.intact) else {
+            return 0.0;
+        };
+
+        let mut max_impulse = 0.0f32;
+        for child in intact.children() {
+            let Some(collider) = ctx
+                .nodes
+                .try_borrow(*child)
+                .and_then(|n| n.component_ref::<Collider>())
+            else {
+                continue;
+            };
+            for pair in collider.contacts(&*ctx.physics) {
+                let impulse = pair
+                    .manifolds
+                    .iter()
+                    .flat_map(|m| m.points.iter())
+                    .map(|p| p.impulse.abs())
+                    .sum::<f32>();
+                max_impulse = max_impulse.max(impulse);
+            }
+        }
+
+        max_impulse / ctx.dt
+    }
+
+    fn activate_chunks(&mut self, ctx: &mut UpdateContext) {
+        let Some(intact) = ctx.nodes.try_borrow_mut(*self.intact) else {
+            return;
+        };
+        let position = **intact.local_transform().position();
+        let rotation: UnitQuaternion<f32> = **intact.local_transform().rotation();
+        let global_transform = intact.global_transform();
+        let (lin_vel, ang_vel) = intact
+            .component_ref::<RigidBody>()
+            .map(|b| (b.lin_vel(), b.ang_vel()))
+            .unwrap_or_default();
+        intact.set_enabled(false);
+
+        let mut order = (0..self.chunks.len()).collect::<Vec<_>>();
+        order.sort_by(|a, b| self.chunks[*b].volume.total_cmp(&self.chunks[*a].volume));
+        order.truncate(*self.max_active_chunks);
+
+        for index in order {
+            let chunk = &self.chunks[index];
+            let Some(node) = ctx.nodes.try_borrow_mut(chunk.body) else {
+                continue;
+            };
+            node.local_transform_mut()
+                .set_position(position + rotation * chunk.offset)
+                .set_rotation(rotation);
+            node.set_enabled(true);
+            if let Some(body) = node.component_mut::<RigidBody>() {
+                let world_offset = global_transform.transform_vector(&chunk.offset);
+                body.set_lin_vel(lin_vel + ang_vel.cross(&world_offset));
+                body.set_ang_vel(ang_vel);
+            }
+            self.active_chunks.push(ActiveChunk {
+                body: chunk.body,
+                age: 0.0,
+            });
+        }
+
+        self.fractured = true;
+    }
+
+    fn restore(&mut self, ctx: &mut UpdateContext) {
+        for chunk in self.active_chunks.drain(..) {
+            if let Some(node) = ctx.nodes.try_borrow_mut(chunk.body) {
+                node.set_enabled(false);
+            }
+        }
+        if let Some(intact) = ctx.nodes.try_borrow_mut(*self.intact) {
+            intact.set_enabled(true);
+        }
+        self.fractured = false;
+    }
+}
+
+impl NodeTrait for Destructible {
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.local_bounding_box()
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.world_bounding_box()
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn update(&mut self, ctx: &mut UpdateContext) {
+        if std::mem::take(&mut self.reset_requested) {
+            self.restore(ctx);
+        }
+
+        if self.fractured {
+            if let Some(lifetime) = *self.chunk_lifetime {
+                let dt = ctx.dt;
+                self.active_chunks.retain_mut(|chunk| {
+                    chunk.age += dt;
+                    if chunk.age < lifetime {
+                        return true;
+                    }
+                    if let Some(node) = ctx.nodes.try_borrow_mut(chunk.body) {
+                        node.set_enabled(false);
+                    }
+                    false
+                });
+            }
+            return;
+        }
+
+        if ctx.dt <= 0.0 || self.chunks.is_empty() {
+            return;
+        }
+
+        if std::mem::take(&mut self.fracture_requested)
+            || self.max_contact_force(ctx) >= *self.force_threshold
+        {
+            self.activate_chunks(ctx);
+        }
+    }
+
+    fn validate(&self, scene: &Scene) -> Result<(), String> {
+        let Some(intact) = scene.graph.try_get(*self.intact) else {
+            return Err("The intact body is not set!".to_string());
+        };
+        if intact.component_ref::<RigidBody>().is_none() {
+            Err("The intact body must be a rigid body!".to_string())
+        } else if intact.parent() != self.handle() {
+            Err("The intact body must be a direct child of the destructible!".to_string())
+        } else if self.chunks.is_empty() {
+            Err(
+                "The destructible has no chunks, use prefracture function to create them!"
+                    .to_string(),
+            )
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Destructible builder allows you to create [`Destructible`] nodes in declarative manner.
+pub struct DestructibleBuilder {
+    base_builder: BaseBuilder,
+    intact: Handle<Node>,
+    force_threshold: f32,
+    chunk_lifetime: Option<f32>,
+    max_active_chunks: usize,
+}
+
+impl DestructibleBuilder {
+    /// Creates a new destructible builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            intact: Default::default(),
+            force_threshold: 5000.0,
+            chunk_lifetime: Some(10.0),
+            max_active_chunks: 32,
+        }
+    }
+
+    /// Sets the desired intact rigid body.
+    pub fn with_intact(mut self, intact: Handle<Node>) -> Self {
+        self.intact = intact;
+        self
+    }
+
+    /// Sets the desired force threshold.
+    pub fn with_force_threshold(mut self, force_threshold: f32) -> Self {
+        self.force_threshold = force_threshold;
+        self
+    }
+
+    /// Sets the desired lifetime of chunks.
+    pub fn with_chunk_lifetime(mut self, chunk_lifetime: Option<f32>) -> Self {
+        self.chunk_lifetime = chunk_lifetime;
+        self
+    }
+
+    /// Sets the desired maximum amount of active chunks.
+    pub fn with_max_active_chunks(mut self, max_active_chunks: usize) -> Self {
+        self.max_active_chunks = max_active_chunks;
+        self
+    }
+
+    /// Creates the destructible, but does not add it to a graph.
+    pub fn build_destructible(self) -> Destructible {
+        Destructible {
+            base: self.base_builder.build_base(),
+            intact: self.intact.into(),
+            chunks: Default::default(),
+            force_threshold: self.force_threshold.into(),
+            chunk_lifetime: self.chunk_lifetime.into(),
+            max_active_chunks: self.max_active_chunks.into(),
+            fractured: false,
+            fracture_requested: false,
+            reset_requested: false,
+            active_chunks: Default::default(),
+        }
+    }
+
+    /// Creates destructible node, but does not add it to a graph.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_destructible())
+    }
+
+    /// Creates destructible node and adds it to the graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Matrix4, Vector3},
+            math::aabb::AxisAlignedBoundingBox,
+        },
+        scene::{
+            destruction::{fracture, voronoi_sites},
+            mesh::surface::SurfaceData,
+        },
+    };
+
+    #[test]
+    fn test_fracture_preserves_volume() {
+        let cube = SurfaceData::make_cube(Matrix4::identity());
+        let sites = voronoi_sites(
+            &AxisAlignedBoundingBox::from_min_max(Vector3::repeat(-0.5), Vector3::repeat(0.5)),
+            8,
+            42,
+        );
+        let chunks = fracture(&cube, &sites);
+        assert_eq!(chunks.len(), 8);
+        let total_volume = chunks.iter().map(|c| c.volume).sum::<f32>();
+        assert!((total_volume - 1.0).abs() < 1.0e-3, "{total_volume}");
+        for chunk in chunks {
+            assert!(chunk.center.iter().all(|c| c.abs() <= 0.5));
+        }
+    }
+}
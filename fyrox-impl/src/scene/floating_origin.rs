@@ -0,0 +1,209 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Floating origin keeps the observer of a scene close to the origin of the world, which is needed
+//! to preserve precision of 32-bit floats in large worlds. See [`FloatingOrigin`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::Vector3, pool::Handle, reflect::prelude::*, type_traits::prelude::*,
+        visitor::prelude::*,
+    },
+    graph::BaseSceneGraph,
+    scene::{graph::Graph, node::Node},
+};
+
+/// Floating origin periodically shifts the world, so the observer (usually the main camera) stays
+/// close to the origin. 32-bit floats have about 7 significant decimal digits, which means that a
+/// few kilometers away from the origin the precision of positions drops to millimeters, which
+/// causes jittering of meshes, shadows, physics, etc.
+///
+/// When enabled, the scene checks the distance between the observer and the origin on every update
+/// and once it exceeds the threshold, the world is rebased around the observer (see
+/// [`Graph::shift_origin`]). Accumulated offset is stored in double precision, use
+/// [`Self::to_absolute`] and [`Self::to_relative`] to convert positions between "true" world
+/// coordinates and current scene coordinates.
+///
+/// Any world-space position that is stored outside the scene graph (for example in a script) must
+/// be shifted as well. Subscribe to [`crate::scene::graph::event::GraphEvent::OriginShifted`]
+/// events to do so.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect, TypeUuidProvider)]
+#[type_uuid(id = "7f8a1c40-2b7b-4c55-8d0e-2b3a4b3f2c11")]
+#[visit(optional)]
+pub struct FloatingOrigin {
+    /// Whether the floating origin is enabled or not. Disabled by default.
+    pub enabled: bool,
+    /// Maximum distance between the observer and the origin, after which the world is rebased.
+    #[reflect(min_value = 1.0)]
+    pub threshold: f32,
+    /// A node, around which the world is rebased. If not set, the first enabled camera is used.
+    pub observer: Handle<Node>,
+    /// Size of the grid to which the shift offset is snapped. Snapping keeps positions of static
+    /// objects on the same "grid", which reduces floating point error accumulation. Zero disables
+    /// snapping.
+    #[reflect(min_value = 0.0)]
+    pub snap: f32,
+    #[reflect(read_only)]
+    offset: Vector3<f64>,
+}
+
+impl Default for FloatingOrigin {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold: 1000.0,
+            observer: Default::default(),
+            snap: 1.0,
+            offset: Default::default(),
+        }
+    }
+}
+
+impl FloatingOrigin {
+    /// Returns total offset of the origin, which is basically the position of the current origin
+    /// in "true" world coordinates.
+    pub fn offset(&self) -> Vector3<f64> {
+        self.offset
+    }
+
+    /// Converts a position in current scene coordinates to the "true" world coordinates.
+    pub fn to_absolute(&self, position: Vector3<f32>) -> Vector3<f64> {
+        self.offset + position.cast::<f64>()
+    }
+
+    /// Converts a position in "true" world coordinates to current scene coordinates.
+    pub fn to_relative(&self, position: Vector3<f64>) -> Vector3<f32> {
+        (position - self.offset).cast::<f32>()
+    }
+
+    /// Shifts the origin of the graph by the given offset and tracks the offset.
+    pub fn shift(&mut self, graph: &mut Graph, offset: Vector3<f32>) {
+        graph.shift_origin(offset);
+        self.offset += offset.cast::<f64>();
+    }
+
+    fn observer_position(&self, graph: &Graph) -> Option<Vector3<f32>> {
+        if let Some(observer) = graph.try_get(self.observer) {
+            return Some(observer.global_position());
+        }
+
+        graph
+            .linear_iter()
+            .find(|n| n.is_globally_enabled() && n.is_camera())
+            .map(|n| n.global_position())
+    }
+
+    /// Checks the distance between the observer and the origin, and shifts the origin if needed.
+    /// Returns the offset of the shift, if any.
+    pub(crate) fn update(&mut self, graph: &mut Graph) -> Option<Vector3<f32>> {
+        if !self.enabled {
+            return None;
+        }
+
+        let position = self.observer_position(graph)?;
+        if position.norm() <= self.threshold {
+            return None;
+        }
+
+        let offset = if self.snap > 0.0 {
+            position.map(|c| (c / self.snap).round() * self.snap)
+        } else {
+            position
+        };
+
+        self.shift(graph, offset);
+
+        Some(offset)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{Vector2, Vector3},
+        scene::{
+            base::BaseBuilder,
+            camera::CameraBuilder,
+            floating_origin::FloatingOrigin,
+            graph::{event::GraphEvent, Graph},
+            pivot::PivotBuilder,
+            transform::TransformBuilder,
+        },
+    };
+    use std::sync::mpsc::channel;
+
+    fn build_at(position: Vector3<f32>) -> BaseBuilder {
+        BaseBuilder::new().with_local_transform(
+            TransformBuilder::new()
+                .with_local_position(position)
+                .build(),
+        )
+    }
+
+    #[test]
+    fn test_floating_origin() {
+        let mut graph = Graph::new();
+        let camera = CameraBuilder::new(build_at(Vector3::new(1500.2, 0.0, 0.0))).build(&mut graph);
+        let child = PivotBuilder::new(build_at(Vector3::new(1.0, 0.0, 0.0))).build(&mut graph);
+        let parent =
+            PivotBuilder::new(build_at(Vector3::new(1000.0, 0.0, 10.0)).with_children(&[child]))
+                .build(&mut graph);
+        graph.update_hierarchical_data();
+
+        let (tx, rx) = channel();
+        graph.event_broadcaster.subscribe(tx);
+
+        let mut origin = FloatingOrigin {
+            enabled: true,
+            ..Default::default()
+        };
+
+        let offset = origin.update(&mut graph).unwrap();
+        assert_eq!(offset, Vector3::new(1500.0, 0.0, 0.0));
+        assert_eq!(rx.try_recv(), Ok(GraphEvent::OriginShifted(offset)));
+
+        // Relative placement of the nodes must be preserved.
+        assert!((graph[camera].global_position() - Vector3::new(0.2, 0.0, 0.0)).norm() < 1e-4);
+        assert_eq!(
+            graph[parent].global_position(),
+            Vector3::new(-500.0, 0.0, 10.0)
+        );
+        assert_eq!(
+            graph[child].global_position(),
+            Vector3::new(-499.0, 0.0, 10.0)
+        );
+        assert_eq!(
+            **graph[child].local_transform().position(),
+            Vector3::new(1.0, 0.0, 0.0)
+        );
+
+        assert_eq!(
+            origin.to_absolute(graph[parent].global_position()),
+            Vector3::new(1000.0, 0.0, 10.0)
+        );
+        assert_eq!(
+            origin.to_relative(Vector3::new(1000.0, 0.0, 10.0)),
+            Vector3::new(-500.0, 0.0, 10.0)
+        );
+
+        // The observer is close to the origin now.
+        graph.update(Vector2::new(1.0, 1.0), 0.0, Default::default());
+        assert_eq!(origin.update(&mut graph), None);
+    }
+}
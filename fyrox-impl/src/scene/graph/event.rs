@@ -21,19 +21,26 @@
 //! Graph event broadcaster allows you to receive graph events such as node deletion or addition.
 //! Check [GraphEventBroadcaster::subscribe] for examples.
 
-use crate::{core::pool::Handle, scene::node::Node};
+use crate::{
+    core::{algebra::Vector3, pool::Handle},
+    scene::node::Node,
+};
 use std::{
     fmt::{Debug, Formatter},
     sync::mpsc::Sender,
 };
 
 /// An event that happened in a graph.
-#[derive(Clone, PartialEq, Debug, Eq)]
+#[derive(Clone, PartialEq, Debug)]
 pub enum GraphEvent {
     /// A node was added.
     Added(Handle<Node>),
     /// A node was removed.
     Removed(Handle<Node>),
+    /// The origin of the world was shifted by the given offset, every position in world
+    /// coordinates, that is stored outside the graph must be shifted by negated offset. See
+    /// [`super::Graph::shift_origin`] for more info.
+    OriginShifted(Vector3<f32>),
 }
 
 /// Graph event broadcaster allows you to receive graph events such as node deletion or addition.
@@ -0,0 +1,148 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Ray and shape casts against rendered geometry. Unlike physics queries, these casts do not
+//! require colliders and test exact triangles of meshes. See [`Graph::raycast_geometry`] and
+//! [`Graph::spherecast_geometry`] for more info.
+
+use crate::{
+    core::{
+        algebra::{Point3, Vector2, Vector3},
+        math::ray::Ray,
+        pool::Handle,
+    },
+    graph::SceneGraph,
+    scene::{
+        graph::Graph,
+        mesh::{
+            bvh::{cast_mesh, GeometryCast},
+            Mesh,
+        },
+        node::Node,
+    },
+};
+
+/// A set of options for ray and shape casts against rendered geometry.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeometryCastOptions {
+    /// A ray origin.
+    pub ray_origin: Point3<f32>,
+
+    /// A ray direction. Can be non-normalized.
+    pub ray_direction: Vector3<f32>,
+
+    /// Maximum distance of cast.
+    pub max_len: f32,
+
+    /// Whether to test skinned meshes in their current pose (defined by their bones) or in their
+    /// bind pose. Testing the current pose is slower, because vertices must be skinned on CPU.
+    pub respect_skinning: bool,
+
+    /// Whether to ignore back faces (the faces whose normal points in the same direction as the
+    /// ray).
+    pub ignore_back_faces: bool,
+
+    /// Whether to sort intersections from closest to farthest.
+    pub sort_results: bool,
+}
+
+impl Default for GeometryCastOptions {
+    fn default() -> Self {
+        Self {
+            ray_origin: Point3::origin(),
+            ray_direction: Vector3::z(),
+            max_len: 1000.0,
+            respect_skinning: false,
+            ignore_back_faces: false,
+            sort_results: true,
+        }
+    }
+}
+
+/// An intersection with rendered geometry. Every mesh reports its closest intersection only.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeometryIntersection {
+    /// A handle of the mesh with which intersection was detected.
+    pub node: Handle<Node>,
+
+    /// Index of the surface of the mesh.
+    pub surface_index: usize,
+
+    /// Index of the triangle in the geometry buffer of the surface.
+    pub triangle_index: usize,
+
+    /// A position of the intersection in world coordinates.
+    pub position: Point3<f32>,
+
+    /// A normal at the intersection position in world coordinates. For ray casts it is the
+    /// normal of the triangle, for sphere casts it points from the contact point to the center
+    /// of the sphere.
+    pub normal: Vector3<f32>,
+
+    /// Texture coordinates (first set) at the intersection position.
+    pub uv: Vector2<f32>,
+
+    /// Distance from the ray origin.
+    pub toi: f32,
+}
+
+pub(super) fn cast_geometry(
+    graph: &Graph,
+    opts: GeometryCastOptions,
+    radius: f32,
+    query_buffer: &mut Vec<GeometryIntersection>,
+) {
+    query_buffer.clear();
+
+    let Some(direction) = opts.ray_direction.try_normalize(f32::EPSILON) else {
+        return;
+    };
+    let cast = GeometryCast {
+        ray: Ray::new(opts.ray_origin.coords, direction.scale(opts.max_len)),
+        radius,
+        respect_skinning: opts.respect_skinning,
+        ignore_back_faces: opts.ignore_back_faces,
+    };
+
+    for (handle, node) in graph.pair_iter() {
+        if !node.global_visibility() {
+            continue;
+        }
+
+        let Some(mesh) = node.component_ref::<Mesh>() else {
+            continue;
+        };
+
+        // Do coarse, but fast, intersection test with bounding box first.
+        let mut aabb = mesh.world_bounding_box();
+        aabb.inflate(Vector3::repeat(2.0 * radius));
+        if cast.ray.aabb_intersection(&aabb).is_none() {
+            continue;
+        }
+
+        if let Some(intersection) = cast_mesh(handle, mesh, graph, &cast) {
+            query_buffer.push(intersection);
+        }
+    }
+
+    if opts.sort_results {
+        query_buffer.sort_by(|a, b| a.toi.total_cmp(&b.toi));
+    }
+}
@@ -51,6 +51,7 @@ use crate::{
         math::{aabb::AxisAlignedBoundingBox, Matrix4Ext},
         pool::{ErasedHandle, Handle, MultiBorrowContext, Pool, Ticket},
         reflect::prelude::*,
+        scope_profile,
         visitor::{Visit, VisitResult, Visitor},
     },
     graph::{AbstractSceneGraph, AbstractSceneNode, BaseSceneGraph, NodeHandleMap, SceneGraph},
@@ -62,13 +63,15 @@ use crate::{
         dim2::{self},
         graph::{
             event::{GraphEvent, GraphEventBroadcaster},
+            geometry::{GeometryCastOptions, GeometryIntersection},
             physics::{PhysicsPerformanceStatistics, PhysicsWorld},
+            time::SceneTime,
         },
         mesh::Mesh,
         navmesh,
         node::{container::NodeContainer, Node, NodeTrait, SyncContext, UpdateContext},
         pivot::Pivot,
-        sound::context::SoundContext,
+        sound::{context::SoundContext, reverb_zone::ReverbZone, Sound},
         transform::TransformBuilder,
     },
     script::ScriptTrait,
@@ -86,7 +89,11 @@ use std::{
 };
 
 pub mod event;
+pub mod geometry;
 pub mod physics;
+#[cfg(feature = "f64_transform")]
+pub mod precise;
+pub mod time;
 
 /// Graph performance statistics. Allows you to find out "hot" parts of the scene graph, which
 /// parts takes the most time to update.
@@ -156,6 +163,9 @@ pub struct Graph {
     /// Current lightmap.
     lightmap: Option<Lightmap>,
 
+    /// Time scale and pause controls of the graph. See [`SceneTime`] docs for more info.
+    pub time: SceneTime,
+
     #[reflect(hidden)]
     pub(crate) script_message_sender: Sender<NodeScriptMessage>,
     #[reflect(hidden)]
@@ -167,6 +177,11 @@ pub struct Graph {
     pub(crate) message_receiver: Receiver<NodeMessage>,
 
     instance_id_map: FxHashMap<SceneNodeId, Handle<Node>>,
+
+    /// Double-precision positions of top-level nodes.
+    #[cfg(feature = "f64_transform")]
+    #[reflect(hidden)]
+    pub precise_positions: precise::PrecisePositions,
 }
 
 impl Default for Graph {
@@ -187,8 +202,11 @@ impl Default for Graph {
             message_sender,
             script_message_sender,
             lightmap: None,
+            time: Default::default(),
             instance_id_map: Default::default(),
             message_receiver,
+            #[cfg(feature = "f64_transform")]
+            precise_positions: Default::default(),
         }
     }
 }
@@ -318,8 +336,11 @@ impl Graph {
             message_sender,
             script_message_sender,
             lightmap: None,
+            time: Default::default(),
             instance_id_map,
             message_receiver,
+            #[cfg(feature = "f64_transform")]
+            precise_positions: Default::default(),
         }
     }
 
@@ -781,6 +802,7 @@ impl Graph {
                             "lightmapTexture",
                             MaterialResourceBinding::Texture(MaterialTextureBinding {
                                 value: Some(texture),
+                                sampler: None,
                             }),
                         );
                     }
@@ -836,6 +858,7 @@ impl Graph {
                                 "lightmapTexture",
                                 MaterialResourceBinding::Texture(MaterialTextureBinding {
                                     value: entry.texture.clone(),
+                                    sampler: None,
                                 }),
                             );
                         }
@@ -888,6 +911,29 @@ impl Graph {
         aabb_of_descendants_recursive(self, root, &mut filter)
     }
 
+    /// Casts a ray against the triangles of every visible mesh in the graph, no colliders are
+    /// needed. Every mesh reports its closest intersection only. Each surface of a mesh builds an
+    /// acceleration structure on first use and keeps it until the surface data is changed, so
+    /// the first cast could be slower than the subsequent ones.
+    pub fn raycast_geometry(
+        &self,
+        opts: GeometryCastOptions,
+        query_buffer: &mut Vec<GeometryIntersection>,
+    ) {
+        geometry::cast_geometry(self, opts, 0.0, query_buffer)
+    }
+
+    /// Casts a sphere with the given radius against the triangles of every visible mesh in the
+    /// graph. See [`Self::raycast_geometry`] for more info.
+    pub fn spherecast_geometry(
+        &self,
+        opts: GeometryCastOptions,
+        radius: f32,
+        query_buffer: &mut Vec<GeometryIntersection>,
+    ) {
+        geometry::cast_geometry(self, opts, radius.max(0.0), query_buffer)
+    }
+
     pub(crate) fn update_enabled_flag_recursively(nodes: &NodePool, node_handle: Handle<Node>) {
         let Some(node) = nodes.try_borrow(node_handle) else {
             return;
@@ -993,6 +1039,172 @@ impl Graph {
         self.update_hierarchical_data_for_descendants(self.root);
     }
 
+    /// Shifts the origin of the world by the given offset, so a point with `offset` coordinates
+    /// will become the new origin. It moves every node of the graph (including physical entities
+    /// and sound sources), and notifies every node about the shift (see
+    /// [`NodeTrait::on_origin_shifted`]). Subscribers of [`Self::event_broadcaster`] will receive
+    /// [`GraphEvent::OriginShifted`] event, so they can shift their own world-space data.
+    ///
+    /// This method is used to keep the precision of 32-bit floats in large worlds, usually it is
+    /// called by the [`crate::scene::floating_origin::FloatingOrigin`] automatically.
+    ///
+    /// # Important Notes
+    ///
+    /// This method recalculates global transforms of the entire graph, so it is relatively slow
+    /// and should be called only when the observer went too far from the origin.
+    pub fn shift_origin(&mut self, offset: Vector3<f32>) {
+        if offset == Vector3::zeros() {
+            return;
+        }
+
+        // Pick up the latest changes of top-level nodes before moving the origin.
+        #[cfg(feature = "f64_transform")]
+        {
+            self.precise_positions.sync(&self.pool, self.root);
+            self.precise_positions.shift(offset);
+        }
+
+        // Moving top-level nodes is enough, every other node will be moved by its ancestors.
+        #[cfg(feature = "f64_transform")]
+        let root_transform = self.pool[self.root].global_transform();
+        let top_level_nodes = self.pool[self.root].children().to_vec();
+        for handle in top_level_nodes {
+            let position = **self.pool[handle].local_transform().position() - offset;
+            // Nodes with double-precision positions are placed exactly, without error accumulation.
+            #[cfg(feature = "f64_transform")]
+            let position = self
+                .precise_positions
+                .place(handle, &root_transform)
+                .unwrap_or(position);
+            self.pool[handle]
+                .local_transform_mut()
+                .set_position(position);
+        }
+
+        // Rigid bodies and sound sources will be synced with their nodes while updating transforms.
+        self.update_hierarchical_data();
+
+        // Nested nodes with double-precision positions could be placed only when their ancestors
+        // are moved.
+        #[cfg(feature = "f64_transform")]
+        {
+            let nested = self
+                .precise_positions
+                .iter()
+                .map(|(handle, _)| handle)
+                .filter(|handle| self.pool[*handle].parent() != self.root)
+                .collect::<Vec<_>>();
+            if !nested.is_empty() {
+                for handle in nested {
+                    let parent_transform = self.pool[self.pool[handle].parent()].global_transform();
+                    if let Some(position) = self.precise_positions.place(handle, &parent_transform)
+                    {
+                        self.pool[handle]
+                            .local_transform_mut()
+                            .set_position(position);
+                    }
+                }
+                self.update_hierarchical_data();
+            }
+        }
+
+        for node in self.pool.iter_mut() {
+            node.on_origin_shifted(offset);
+        }
+
+        self.event_broadcaster
+            .broadcast(GraphEvent::OriginShifted(offset));
+    }
+
+    /// Sets double-precision world position of a node. The node will be placed relative to the
+    /// current origin of the render space and will keep its exact position when the origin moves.
+    /// The node must not be the root or a descendant of another node with precise position. See
+    /// [`precise::PrecisePositions`] docs for more info and limitations.
+    #[cfg(feature = "f64_transform")]
+    pub fn set_precise_position(&mut self, handle: Handle<Node>, position: Vector3<f64>) {
+        let Some(node) = self.pool.try_borrow(handle) else {
+            return;
+        };
+
+        let mut ancestor = node.parent();
+        while let Some(ancestor_ref) = self.pool.try_borrow(ancestor) {
+            if self.precise_positions.get(ancestor).is_some() {
+                Log::warn(format!(
+                    "Unable to set precise position of {} node, because its ancestor {} \
+                    already has precise position!",
+                    node.name(),
+                    ancestor_ref.name()
+                ));
+                return;
+            }
+            ancestor = ancestor_ref.parent();
+        }
+
+        let Some(parent_transform) = self
+            .pool
+            .try_borrow(node.parent())
+            .map(|parent| parent.global_transform())
+        else {
+            Log::warn("Unable to set precise position of the root node!");
+            return;
+        };
+
+        self.precise_positions.insert(handle, position);
+        if let Some(local_position) = self.precise_positions.place(handle, &parent_transform) {
+            self.pool[handle]
+                .local_transform_mut()
+                .set_position(local_position);
+        }
+    }
+
+    /// Removes double-precision position of a node. The node stays at its current position in the
+    /// render space.
+    #[cfg(feature = "f64_transform")]
+    pub fn clear_precise_position(&mut self, handle: Handle<Node>) -> Option<Vector3<f64>> {
+        self.precise_positions.remove(handle)
+    }
+
+    /// Returns world position of a node in double precision. The position is calculated using the
+    /// double-precision position of the node or its closest precise ancestor (if any), so only
+    /// the offset of the node relative to that ancestor is limited by 32-bit precision.
+    #[cfg(feature = "f64_transform")]
+    pub fn precise_global_position(&self, handle: Handle<Node>) -> Vector3<f64> {
+        let Some(node) = self.pool.try_borrow(handle) else {
+            return Default::default();
+        };
+
+        let mut ancestor = handle;
+        while let Some(ancestor_ref) = self.pool.try_borrow(ancestor) {
+            if let Some(position) = self.precise_positions.get(ancestor) {
+                return position
+                    + (node.global_position() - ancestor_ref.global_position()).cast::<f64>();
+            }
+            ancestor = ancestor_ref.parent();
+        }
+
+        self.precise_positions.to_absolute(node.global_position())
+    }
+
+    /// Moves the origin of the render space to the first enabled camera, if the camera is too far
+    /// from it. This is an automatic origin shift (see [`Self::shift_origin`]), double-precision
+    /// positions only make it lossless for the nodes that have them. See
+    /// [`precise::PrecisePositions`] docs for more info.
+    #[cfg(feature = "f64_transform")]
+    pub(crate) fn update_precise_origin(&mut self) {
+        let Some(position) = self
+            .pool
+            .iter()
+            .find(|n| n.is_globally_enabled() && n.is_camera())
+            .map(|n| n.global_position())
+        else {
+            return;
+        };
+
+        if position.norm() > self.precise_positions.rebase_distance {
+            self.shift_origin(position.map(|c| c.round()));
+        }
+    }
+
     pub(crate) fn update_hierarchical_data_recursively(
         nodes: &NodePool,
         sound_context: &mut SoundContext,
@@ -1137,19 +1349,25 @@ impl Graph {
         handle: Handle<Node>,
         frame_size: Vector2<f32>,
         dt: f32,
+        observer_position: Option<Vector3<f32>>,
         delete_dead_nodes: bool,
     ) {
         if let Some((ticket, mut node)) = self.pool.try_take_reserve(handle) {
             let mut is_alive = node.is_alive();
+            let time_scale = self.time.node_time_scale(handle);
+            let dt = dt * time_scale;
 
             if node.is_globally_enabled() {
                 node.update(&mut UpdateContext {
                     frame_size,
                     dt,
+                    time_scale,
+                    sub_stepping: self.time.sub_stepping(),
                     nodes: &mut self.pool,
                     physics: &mut self.physics,
                     physics2d: &mut self.physics2d,
                     sound_context: &mut self.sound_context,
+                    observer_position,
                 });
 
                 if delete_dead_nodes {
@@ -1188,28 +1406,63 @@ impl Graph {
         self.performance_statistics.hierarchical_properties_time =
             instant::Instant::now() - last_time;
 
+        self.time.update(&self.pool, self.root);
+
         let last_time = instant::Instant::now();
         self.sync_native(&switches);
         self.performance_statistics.sync_time = instant::Instant::now() - last_time;
 
-        if switches.physics {
+        self.sound_context.apply_time_scale(
+            &self.time,
+            self.pool.pair_iter().filter_map(|(handle, node)| {
+                node.cast::<Sound>()
+                    .map(|sound| (sound, self.time.node_time_scale(handle)))
+            }),
+        );
+
+        self.sound_context.update_reverb_zones(
+            self.pool
+                .iter()
+                .filter(|n| n.is_globally_enabled())
+                .filter_map(|n| n.cast::<ReverbZone>()),
+            dt,
+        );
+
+        // Physics is simulated for the entire scene at once, so it can't ignore time scale.
+        let scaled_dt = self.time.scale_dt(dt);
+
+        if switches.physics && scaled_dt > 0.0 {
+            scope_profile!("Physics");
             self.physics.performance_statistics.reset();
-            self.physics.update(dt);
+            self.physics.update(scaled_dt);
             self.performance_statistics.physics = self.physics.performance_statistics.clone();
         }
 
-        if switches.physics2d {
+        if switches.physics2d && scaled_dt > 0.0 {
+            scope_profile!("Physics 2D");
             self.physics2d.performance_statistics.reset();
-            self.physics2d.update(dt);
+            self.physics2d.update(scaled_dt);
             self.performance_statistics.physics2d = self.physics2d.performance_statistics.clone();
         }
 
         self.performance_statistics.sound_update_time =
             self.sound_context.state().full_render_duration();
 
+        let observer_position = self
+            .pool
+            .iter()
+            .find(|n| n.is_globally_enabled() && n.is_camera())
+            .map(|camera| camera.global_position());
+
         if let Some(overrides) = switches.node_overrides.as_ref() {
             for handle in overrides {
-                self.update_node(*handle, frame_size, dt, switches.delete_dead_nodes);
+                self.update_node(
+                    *handle,
+                    frame_size,
+                    dt,
+                    observer_position,
+                    switches.delete_dead_nodes,
+                );
             }
         } else {
             for i in 0..self.pool.get_capacity() {
@@ -1217,10 +1470,14 @@ impl Graph {
                     self.pool.handle_from_index(i),
                     frame_size,
                     dt,
+                    observer_position,
                     switches.delete_dead_nodes,
                 );
             }
         }
+
+        #[cfg(feature = "f64_transform")]
+        self.precise_positions.sync(&self.pool, self.root);
     }
 
     /// Returns capacity of internal pool. Can be used to iterate over all **potentially**
@@ -1406,6 +1663,7 @@ impl Graph {
             sound_context: self.sound_context.deep_clone(),
             physics: self.physics.clone(),
             physics2d: self.physics2d.clone(),
+            time: self.time.clone(),
             ..Default::default()
         };
 
@@ -1433,9 +1691,22 @@ impl Graph {
                 }
             }
             lightmap.map = map;
+
+            let mut nodes = FxHashMap::default();
+            for (mut handle, fingerprint) in std::mem::take(&mut lightmap.fingerprint.nodes) {
+                if old_new_map.try_map(&mut handle) {
+                    nodes.insert(handle, fingerprint);
+                }
+            }
+            lightmap.fingerprint.nodes = nodes;
         }
         copy.lightmap = lightmap;
 
+        #[cfg(feature = "f64_transform")]
+        {
+            copy.precise_positions = self.precise_positions.remap(&old_new_map);
+        }
+
         (copy, old_new_map)
     }
 
@@ -1699,6 +1970,11 @@ impl Visit for Graph {
         self.physics.visit("PhysicsWorld", &mut region)?;
         self.physics2d.visit("PhysicsWorld2D", &mut region)?;
         let _ = self.lightmap.visit("Lightmap", &mut region);
+        let _ = self.time.visit("Time", &mut region);
+        #[cfg(feature = "f64_transform")]
+        let _ = self
+            .precise_positions
+            .visit("PrecisePositions", &mut region);
 
         Ok(())
     }
@@ -1869,7 +2145,7 @@ mod test {
     use crate::{
         asset::{io::FsResourceIo, manager::ResourceManager},
         core::{
-            algebra::{Matrix4, Vector3},
+            algebra::{Matrix4, Point3, Vector3},
             futures::executor::block_on,
             pool::Handle,
             reflect::prelude::*,
@@ -1881,7 +2157,7 @@ mod test {
         resource::model::{Model, ModelResourceExtension},
         scene::{
             base::BaseBuilder,
-            graph::Graph,
+            graph::{geometry::GeometryCastOptions, Graph},
             mesh::{
                 surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
                 MeshBuilder,
@@ -2305,4 +2581,72 @@ mod test {
         assert!(!graph[c].is_globally_enabled());
         assert!(!graph[d].is_globally_enabled());
     }
+
+    #[test]
+    fn test_geometry_casts() {
+        let mut graph = Graph::new();
+
+        let mut make_cube = |position: Vector3<f32>| {
+            MeshBuilder::new(
+                BaseBuilder::new().with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(position)
+                        .with_local_scale(Vector3::repeat(2.0))
+                        .build(),
+                ),
+            )
+            .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+                ResourceKind::Embedded,
+                SurfaceData::make_cube(Matrix4::identity()),
+            ))
+            .build()])
+            .build(&mut graph)
+        };
+        let near = make_cube(Vector3::new(0.0, 0.0, 5.0));
+        let far = make_cube(Vector3::new(0.0, 0.0, 10.0));
+
+        graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+
+        let mut results = Vec::new();
+        graph.raycast_geometry(
+            GeometryCastOptions {
+                ray_origin: Point3::new(0.3, 0.1, 0.0),
+                ray_direction: Vector3::z(),
+                ..Default::default()
+            },
+            &mut results,
+        );
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].node, near);
+        assert_eq!(results[1].node, far);
+        assert!((results[0].toi - 4.0).abs() < 1.0e-4);
+        assert!((results[0].position.coords - Vector3::new(0.3, 0.1, 4.0)).norm() < 1.0e-4);
+        assert!((results[0].normal.z.abs() - 1.0).abs() < 1.0e-4);
+        assert!((results[1].toi - 9.0).abs() < 1.0e-4);
+
+        // The ray is too short to reach the far cube.
+        graph.raycast_geometry(
+            GeometryCastOptions {
+                ray_origin: Point3::new(0.3, 0.1, 0.0),
+                ray_direction: Vector3::z(),
+                max_len: 6.0,
+                ..Default::default()
+            },
+            &mut results,
+        );
+        assert_eq!(results.len(), 1);
+
+        // The ray passes by the cubes, but the sphere touches them.
+        let opts = GeometryCastOptions {
+            ray_origin: Point3::new(1.25, 0.0, 0.0),
+            ray_direction: Vector3::z(),
+            ..Default::default()
+        };
+        graph.raycast_geometry(opts.clone(), &mut results);
+        assert!(results.is_empty());
+        graph.spherecast_geometry(opts, 0.5, &mut results);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].node, near);
+        assert!((results[0].position.x - 1.0).abs() < 1.0e-4);
+    }
 }
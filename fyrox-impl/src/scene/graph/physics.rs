@@ -389,6 +389,17 @@ impl ContactPair {
     }
 }
 
+/// A result of point projection on colliders.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PointProjection {
+    /// A handle of the collider, that is the closest to the point.
+    pub collider: Handle<Node>,
+    /// The projection of the point on the collider in world coordinates.
+    pub point: Vector3<f32>,
+    /// `true` if the point is inside the collider.
+    pub is_inside: bool,
+}
+
 /// Intersection info for pair of colliders.
 #[derive(Debug, Clone, PartialEq)]
 pub struct IntersectionPair {
@@ -1235,6 +1246,13 @@ impl PhysicsWorld {
         }
     }
 
+    /// Returns world-space center of mass of a rigid body with the given handle.
+    pub(crate) fn body_center_of_mass(&self, handle: RigidBodyHandle) -> Option<Vector3<f32>> {
+        self.bodies
+            .get(handle)
+            .map(|body| body.center_of_mass().coords)
+    }
+
     /// Draws physics world. Very useful for debugging, it allows you to see where are
     /// rigid bodies, which colliders they have and so on.
     pub fn draw(&self, context: &mut SceneDrawingContext) {
@@ -1301,6 +1319,35 @@ impl PhysicsWorld {
         );
     }
 
+    /// Finds the collider, that is the closest to the given point, and projects the point on it.
+    /// If `solid` is `true`, a point inside a collider is projected on itself, otherwise it is
+    /// projected on the boundary of the collider. Only colliders, that match the given groups, are
+    /// taken into account.
+    pub fn project_point(
+        &self,
+        point: Vector3<f32>,
+        solid: bool,
+        groups: collider::InteractionGroups,
+    ) -> Option<PointProjection> {
+        let query = self.query.borrow();
+        query
+            .project_point(
+                &self.bodies,
+                &self.colliders,
+                &Point3::from(point),
+                solid,
+                rapier3d::pipeline::QueryFilter::new().groups(InteractionGroups::new(
+                    u32_to_group(groups.memberships.0),
+                    u32_to_group(groups.filter.0),
+                )),
+            )
+            .map(|(handle, projection)| PointProjection {
+                collider: Handle::decode_from_u128(self.colliders.get(handle).unwrap().user_data),
+                point: projection.point.coords,
+                is_inside: projection.is_inside,
+            })
+    }
+
     /// Casts a shape at a constant linear velocity and retrieve the first collider it hits.
     ///
     /// This is similar to ray-casting except that we are casting a whole shape instead of just a
@@ -0,0 +1,295 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Double-precision positions of scene nodes. Available only with `f64_transform` feature. See
+//! [`PrecisePositions`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Point3, Vector3},
+        pool::Handle,
+        visitor::prelude::*,
+    },
+    graph::NodeHandleMap,
+    scene::{graph::NodePool, node::Node},
+};
+use fxhash::FxHashMap;
+
+#[derive(Clone, Debug, Default, PartialEq, Visit)]
+struct PreciseEntry {
+    position: Vector3<f64>,
+    // Last known position of the node in the render space. Used to detect changes made by
+    // physics, scripts, etc.
+    relative: Vector3<f32>,
+}
+
+/// Stores double-precision world positions of nodes of a graph.
+///
+/// Scene nodes store their transforms in 32-bit floats, this is what the renderer, physics and
+/// sound engines work with. With `f64_transform` feature, nodes could also have a "true" position
+/// in 64-bit floats (see [`super::Graph::set_precise_position`]). 32-bit positions of such nodes
+/// are always calculated relative to the origin of the render space, which is kept near the active
+/// camera, so everything that is close to the camera has full precision. The origin is moved
+/// automatically once the camera goes further than [`Self::rebase_distance`] from it, 64-bit
+/// positions stay intact while the origin moves.
+///
+/// Changes of 32-bit positions of the nodes (made by physics, scripts, etc.) are written back to
+/// their 64-bit positions on every update of the graph.
+///
+/// # Limitations
+///
+/// This is not a full double-precision transform pipeline - the render space is still a floating
+/// origin, only the nodes with precise positions (and their descendants) keep exact world
+/// positions while the origin moves. Every other top-level node is simply shifted with the origin
+/// in 32-bit floats and accumulates the rounding error of every shift.
+///
+/// A precise node could be nested (for example, it could be a part of a prefab instance), but not
+/// inside another precise node - such entries are dropped on the next update. The 32-bit local
+/// position of a nested node is calculated relative to its parent, so its render space position is
+/// only as precise as the distance between the node and its parent allows. Keep the ancestors of
+/// nested precise nodes close to them, or make the ancestors precise instead. Pivot and offsets of
+/// the transform of a precise node are ignored.
+#[derive(Clone, Debug, Visit)]
+#[visit(optional)]
+pub struct PrecisePositions {
+    /// Maximum distance between the active camera and the origin of the render space, after
+    /// which the origin is moved to the camera.
+    pub rebase_distance: f32,
+    origin: Vector3<f64>,
+    entries: FxHashMap<Handle<Node>, PreciseEntry>,
+}
+
+impl Default for PrecisePositions {
+    fn default() -> Self {
+        Self {
+            rebase_distance: 1024.0,
+            origin: Default::default(),
+            entries: Default::default(),
+        }
+    }
+}
+
+impl PrecisePositions {
+    /// Returns the position of the origin of the render space in world coordinates.
+    pub fn origin(&self) -> Vector3<f64> {
+        self.origin
+    }
+
+    /// Returns double-precision position of the given node, if any.
+    pub fn get(&self, handle: Handle<Node>) -> Option<Vector3<f64>> {
+        self.entries.get(&handle).map(|e| e.position)
+    }
+
+    /// Converts a world position to the render space.
+    pub fn to_relative(&self, position: Vector3<f64>) -> Vector3<f32> {
+        (position - self.origin).cast::<f32>()
+    }
+
+    /// Converts a position in the render space to world coordinates.
+    pub fn to_absolute(&self, position: Vector3<f32>) -> Vector3<f64> {
+        self.origin + position.cast::<f64>()
+    }
+
+    /// Returns an iterator over the nodes with double-precision positions.
+    pub fn iter(&self) -> impl Iterator<Item = (Handle<Node>, Vector3<f64>)> + '_ {
+        self.entries.iter().map(|(h, e)| (*h, e.position))
+    }
+
+    /// Sets the position of the node.
+    pub(crate) fn insert(&mut self, handle: Handle<Node>, position: Vector3<f64>) {
+        let relative = self.to_relative(position);
+        self.entries
+            .insert(handle, PreciseEntry { position, relative });
+    }
+
+    pub(crate) fn remove(&mut self, handle: Handle<Node>) -> Option<Vector3<f64>> {
+        self.entries.remove(&handle).map(|e| e.position)
+    }
+
+    /// Moves the origin by the given offset and recalculates render space positions of the nodes.
+    pub(crate) fn shift(&mut self, offset: Vector3<f32>) {
+        self.origin += offset.cast::<f64>();
+        let origin = self.origin;
+        for entry in self.entries.values_mut() {
+            entry.relative = (entry.position - origin).cast::<f32>();
+        }
+    }
+
+    /// Returns local position of the node, that places the node at its double-precision position.
+    /// `parent_transform` is the global transform of the parent of the node. The render space
+    /// position of the node is updated to the one, that the returned local position produces, so
+    /// the rounding error is not treated as a movement of the node.
+    pub(crate) fn place(
+        &mut self,
+        handle: Handle<Node>,
+        parent_transform: &Matrix4<f32>,
+    ) -> Option<Vector3<f32>> {
+        let entry = self.entries.get_mut(&handle)?;
+        let local = parent_transform
+            .try_inverse()
+            .unwrap_or_else(Matrix4::identity)
+            .transform_point(&Point3::from(entry.relative));
+        entry.relative = parent_transform.transform_point(&local).coords;
+        Some(local.coords)
+    }
+
+    fn has_precise_ancestor(&self, pool: &NodePool, handle: Handle<Node>) -> bool {
+        let mut ancestor = pool[handle].parent();
+        while let Some(node) = pool.try_borrow(ancestor) {
+            if self.entries.contains_key(&ancestor) {
+                return true;
+            }
+            ancestor = node.parent();
+        }
+        false
+    }
+
+    /// Writes changes of render space positions back to double-precision positions and removes
+    /// entries of deleted nodes, the root and nodes that are nested inside other precise nodes.
+    pub(crate) fn sync(&mut self, pool: &NodePool, root: Handle<Node>) {
+        let handles = self.entries.keys().cloned().collect::<Vec<_>>();
+        for handle in handles {
+            if handle == root
+                || !pool.is_valid_handle(handle)
+                || self.has_precise_ancestor(pool, handle)
+            {
+                self.entries.remove(&handle);
+                continue;
+            }
+
+            let node = &pool[handle];
+            let local = Point3::from(**node.local_transform().position());
+            // Global transform of the parent could be one frame old here, but this is fine since
+            // it only affects how fast the changes are picked up.
+            let relative = pool
+                .try_borrow(node.parent())
+                .map_or(local, |parent| {
+                    parent.global_transform().transform_point(&local)
+                })
+                .coords;
+
+            let origin = self.origin;
+            let entry = self.entries.get_mut(&handle).unwrap();
+            if relative != entry.relative {
+                entry.position = origin + relative.cast::<f64>();
+                entry.relative = relative;
+            }
+        }
+    }
+
+    pub(crate) fn remap(&self, old_new_map: &NodeHandleMap<Node>) -> Self {
+        Self {
+            rebase_distance: self.rebase_distance,
+            origin: self.origin,
+            entries: self
+                .entries
+                .iter()
+                .filter_map(|(handle, entry)| {
+                    let mut handle = *handle;
+                    old_new_map
+                        .try_map(&mut handle)
+                        .then(|| (handle, entry.clone()))
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{Vector2, Vector3},
+        graph::BaseSceneGraph,
+        scene::{
+            base::BaseBuilder, camera::CameraBuilder, graph::Graph, pivot::PivotBuilder,
+            transform::TransformBuilder,
+        },
+    };
+
+    #[test]
+    fn test_precise_positions() {
+        let mut graph = Graph::new();
+        let far = Vector3::new(1.0e7 + 0.125, 0.0, -3.0e6 + 0.5);
+
+        let camera = CameraBuilder::new(BaseBuilder::new()).build(&mut graph);
+        let child = PivotBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(1.0, 0.0, 0.0))
+                    .build(),
+            ),
+        )
+        .build(&mut graph);
+        let pivot = PivotBuilder::new(BaseBuilder::new().with_children(&[child])).build(&mut graph);
+
+        graph.set_precise_position(pivot, far);
+        graph.set_precise_position(camera, far - Vector3::new(0.0, 0.0, 5.0));
+        graph.update(Vector2::new(100.0, 100.0), 1.0 / 60.0, Default::default());
+        graph.update_precise_origin();
+
+        // The camera must be close to the origin now and the pivot must keep its exact position.
+        assert!(graph[camera].global_position().norm() < 10.0);
+        assert_eq!(graph.precise_positions.get(pivot), Some(far));
+        assert_eq!(
+            graph.precise_global_position(child),
+            far + Vector3::new(1.0, 0.0, 0.0)
+        );
+        assert_eq!(
+            graph[pivot].global_position(),
+            graph.precise_positions.to_relative(far)
+        );
+    }
+
+    #[test]
+    fn test_nested_precise_positions() {
+        let mut graph = Graph::new();
+        let far = Vector3::new(5000.125, 0.0, -3000.5);
+
+        let camera = CameraBuilder::new(BaseBuilder::new()).build(&mut graph);
+        let ship = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+        let nested_ship = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+        let container =
+            PivotBuilder::new(BaseBuilder::new().with_children(&[ship])).build(&mut graph);
+        graph.update(Vector2::new(100.0, 100.0), 1.0 / 60.0, Default::default());
+
+        graph.set_precise_position(ship, far);
+        graph.set_precise_position(camera, far - Vector3::new(0.0, 0.0, 5.0));
+        graph.update(Vector2::new(100.0, 100.0), 1.0 / 60.0, Default::default());
+        graph.update_precise_origin();
+
+        assert!(graph[camera].global_position().norm() < 10.0);
+        assert_eq!(graph.precise_positions.get(ship), Some(far));
+        assert!(graph[ship].global_position().norm() < 10.0);
+        assert_eq!(graph.precise_global_position(ship), far);
+
+        // Precise nodes can't be nested inside each other.
+        graph.link_nodes(nested_ship, ship);
+        graph.set_precise_position(nested_ship, far);
+        assert_eq!(graph.precise_positions.get(nested_ship), None);
+
+        // Moving the container inside the render space moves the ship in the world.
+        graph[container]
+            .local_transform_mut()
+            .offset(Vector3::new(1.0, 0.0, 0.0));
+        graph.update(Vector2::new(100.0, 100.0), 1.0 / 60.0, Default::default());
+        graph.update(Vector2::new(100.0, 100.0), 1.0 / 60.0, Default::default());
+        let position = graph.precise_positions.get(ship).unwrap();
+        assert!((position - far - Vector3::new(1.0, 0.0, 0.0)).norm() < 1.0e-2);
+    }
+}
@@ -0,0 +1,366 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Scene time allows you to slow down, speed up or pause a scene. See [`SceneTime`] docs for more
+//! info.
+
+use crate::{
+    core::{pool::Handle, reflect::prelude::*, visitor::prelude::*},
+    scene::{graph::NodePool, node::Node},
+};
+
+/// Sub-stepping splits large time steps into a few smaller ones, so time-dependent simulations
+/// (particle systems, animations) remain stable when the time scale is high or when the frame rate
+/// is low. It is disabled by default, so existing scenes are updated exactly as before.
+#[derive(Copy, Clone, Debug, PartialEq, Reflect, Visit)]
+#[visit(optional)]
+pub struct SubStepping {
+    /// Whether the sub-stepping is enabled or not.
+    pub enabled: bool,
+    /// Maximum duration (in seconds) of a single step. Larger time steps will be split into
+    /// multiple sub-steps.
+    #[reflect(min_value = 0.001, step = 0.001)]
+    pub max_step: f32,
+    /// Maximum amount of sub-steps per update. It prevents the simulation from taking too much
+    /// time when the time step is very large (for example, when the game was frozen for a while).
+    #[reflect(min_value = 1.0)]
+    pub max_substeps: u32,
+}
+
+impl Default for SubStepping {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_step: 1.0 / 30.0,
+            max_substeps: 8,
+        }
+    }
+}
+
+impl SubStepping {
+    /// Returns the amount of sub-steps for the given time step. It is always at least one.
+    pub fn substep_count(&self, dt: f32) -> u32 {
+        if !self.enabled || self.max_step <= 0.0 || dt <= self.max_step {
+            1
+        } else {
+            ((dt / self.max_step).ceil() as u32).clamp(1, self.max_substeps.max(1))
+        }
+    }
+
+    /// Splits the given time step into equal sub-steps and calls the given closure for each of
+    /// them. The closure receives the duration of the sub-step and its index.
+    pub fn for_each_substep<F>(&self, dt: f32, mut func: F)
+    where
+        F: FnMut(f32, u32),
+    {
+        let count = self.substep_count(dt);
+        let step = dt / count as f32;
+        for i in 0..count {
+            func(step, i)
+        }
+    }
+}
+
+/// Scene time defines how fast the time flows in a scene. It consistently affects every time-dependent
+/// part of the scene: node updates (animations, particle systems, etc.), physics, pitch of sounds and
+/// delta time of scripts. It could be used to create slow motion effects, or to pause the game while
+/// some menu is opened.
+///
+/// ## Opt-out
+///
+/// Some nodes must not be affected by time scale (for example, a pause menu with animated widgets
+/// should work while the game is paused). Such nodes (and all their descendants) could be marked
+/// with [`crate::scene::base::Base::set_ignore_time_scale`], in this case they will receive unscaled
+/// delta time. Physics is simulated for the entire scene at once, so it is always scaled.
+///
+/// ## Sub-stepping
+///
+/// High time scale produces large time steps, which could make particle systems and animations
+/// unstable. To prevent this, such time steps could be split into a few smaller ones. Sub-stepping
+/// is disabled by default and could be enabled with [`SceneTime::set_sub_stepping`]. See
+/// [`SubStepping`] docs for more info.
+///
+/// ## Example
+///
+/// ```rust
+/// # use fyrox_impl::scene::Scene;
+/// fn enter_slow_motion(scene: &mut Scene) {
+///     scene.graph.time.set_time_scale(0.25);
+/// }
+///
+/// fn toggle_pause(scene: &mut Scene) {
+///     let paused = scene.graph.time.is_paused();
+///     scene.graph.time.set_paused(!paused);
+/// }
+/// ```
+///
+/// This is different from [`crate::scene::graph::GraphUpdateSwitches::paused`], which stops the
+/// update of the entire graph, while the paused scene time still allows to update the nodes that
+/// ignore time scale.
+#[derive(Clone, Debug, PartialEq, Reflect, Visit)]
+#[visit(optional)]
+pub struct SceneTime {
+    #[reflect(
+        setter = "set_time_scale",
+        min_value = 0.0,
+        step = 0.05,
+        description = "Speed of time in the scene. 1.0 - normal speed, 0.5 - two times slower, etc."
+    )]
+    time_scale: f32,
+
+    #[reflect(
+        setter = "set_paused",
+        description = "Whether the time in the scene is stopped or not."
+    )]
+    paused: bool,
+
+    #[reflect(description = "Sub-stepping settings for particle systems and animations.")]
+    sub_stepping: SubStepping,
+
+    // Indexed by node index, `true` if a node ignores time scale. Empty if the time is not scaled.
+    #[reflect(hidden)]
+    #[visit(skip)]
+    ignoring_nodes: Vec<bool>,
+
+    // State of the previous update, it is used to detect changes of time scale of the nodes.
+    #[reflect(hidden)]
+    #[visit(skip)]
+    previous_ignoring_nodes: Vec<bool>,
+    #[reflect(hidden)]
+    #[visit(skip)]
+    previous_time_scale: Option<f32>,
+    #[reflect(hidden)]
+    #[visit(skip)]
+    changed: bool,
+}
+
+impl Default for SceneTime {
+    fn default() -> Self {
+        Self {
+            time_scale: 1.0,
+            paused: false,
+            sub_stepping: Default::default(),
+            ignoring_nodes: Default::default(),
+            previous_ignoring_nodes: Default::default(),
+            previous_time_scale: None,
+            changed: false,
+        }
+    }
+}
+
+impl SceneTime {
+    /// Sets new time scale and returns the old one. Negative values are clamped to zero.
+    pub fn set_time_scale(&mut self, time_scale: f32) -> f32 {
+        std::mem::replace(&mut self.time_scale, time_scale.max(0.0))
+    }
+
+    /// Returns current time scale.
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
+    /// Pauses or resumes the time in the scene. Returns the old value. Pause does not change the
+    /// time scale, so the previous speed of time will be restored on resume.
+    pub fn set_paused(&mut self, paused: bool) -> bool {
+        std::mem::replace(&mut self.paused, paused)
+    }
+
+    /// Returns `true` if the time in the scene is stopped.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Sets new sub-stepping settings and returns the old ones.
+    pub fn set_sub_stepping(&mut self, sub_stepping: SubStepping) -> SubStepping {
+        std::mem::replace(&mut self.sub_stepping, sub_stepping)
+    }
+
+    /// Returns current sub-stepping settings.
+    pub fn sub_stepping(&self) -> SubStepping {
+        self.sub_stepping
+    }
+
+    /// Returns the actual time scale, that takes pause into account.
+    pub fn effective_time_scale(&self) -> f32 {
+        if self.paused {
+            0.0
+        } else {
+            self.time_scale
+        }
+    }
+
+    /// Returns `true` if the time in the scene flows at different speed than the real time.
+    pub fn is_scaled(&self) -> bool {
+        self.effective_time_scale() != 1.0
+    }
+
+    /// Scales the given delta time.
+    pub fn scale_dt(&self, dt: f32) -> f32 {
+        dt * self.effective_time_scale()
+    }
+
+    /// Returns `true` if the given node ignores time scale of the scene. The result is valid
+    /// only after the graph update (see [`crate::scene::base::Base::is_ignoring_time_scale`]).
+    pub fn is_node_ignoring_time_scale(&self, node: Handle<Node>) -> bool {
+        self.ignoring_nodes
+            .get(node.index() as usize)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Returns the time scale for the given node.
+    pub fn node_time_scale(&self, node: Handle<Node>) -> f32 {
+        if self.is_node_ignoring_time_scale(node) {
+            1.0
+        } else {
+            self.effective_time_scale()
+        }
+    }
+
+    /// Returns delta time for the given node.
+    pub fn node_dt(&self, node: Handle<Node>, dt: f32) -> f32 {
+        dt * self.node_time_scale(node)
+    }
+
+    /// Returns `true` if the time scale of any node has changed during the last graph update.
+    pub fn is_changed(&self) -> bool {
+        self.changed
+    }
+
+    pub(crate) fn update(&mut self, nodes: &NodePool, root: Handle<Node>) {
+        std::mem::swap(&mut self.ignoring_nodes, &mut self.previous_ignoring_nodes);
+        self.ignoring_nodes.clear();
+
+        let time_scale = self.effective_time_scale();
+        self.changed = self.previous_time_scale.replace(time_scale) != Some(time_scale);
+
+        if self.is_scaled() {
+            self.ignoring_nodes
+                .resize(nodes.get_capacity() as usize, false);
+
+            let mut stack = vec![(root, false)];
+            while let Some((handle, parent_ignoring)) = stack.pop() {
+                if let Some(node) = nodes.try_borrow(handle) {
+                    let ignoring = parent_ignoring || node.is_ignoring_time_scale();
+                    self.ignoring_nodes[handle.index() as usize] = ignoring;
+                    stack.extend(node.children().iter().map(|child| (*child, ignoring)));
+                }
+            }
+        }
+
+        self.changed |= self.ignoring_nodes != self.previous_ignoring_nodes;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::scene::{
+        base::BaseBuilder,
+        graph::{time::SubStepping, Graph},
+        pivot::PivotBuilder,
+    };
+
+    #[test]
+    fn test_scene_time() {
+        let mut graph = Graph::new();
+        let child = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+        let menu = PivotBuilder::new(
+            BaseBuilder::new()
+                .with_ignore_time_scale(true)
+                .with_children(&[child]),
+        )
+        .build(&mut graph);
+        let other = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+
+        graph.time.set_time_scale(0.5);
+        graph.time.update(&graph.pool, graph.get_root());
+
+        assert_eq!(graph.time.node_dt(other, 1.0), 0.5);
+        assert_eq!(graph.time.node_dt(menu, 1.0), 1.0);
+        assert_eq!(graph.time.node_dt(child, 1.0), 1.0);
+
+        graph.time.set_paused(true);
+        graph.time.update(&graph.pool, graph.get_root());
+        assert_eq!(graph.time.node_dt(other, 1.0), 0.0);
+        assert_eq!(graph.time.node_dt(child, 1.0), 1.0);
+
+        graph.time.set_paused(false);
+        assert_eq!(graph.time.time_scale(), 0.5);
+    }
+
+    #[test]
+    fn test_time_scale_change_detection() {
+        let mut graph = Graph::new();
+        let node = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+
+        // The first update is always a change.
+        graph.time.update(&graph.pool, graph.get_root());
+        assert!(graph.time.is_changed());
+        graph.time.update(&graph.pool, graph.get_root());
+        assert!(!graph.time.is_changed());
+
+        graph.time.set_time_scale(0.5);
+        graph.time.update(&graph.pool, graph.get_root());
+        assert!(graph.time.is_changed());
+        graph.time.update(&graph.pool, graph.get_root());
+        assert!(!graph.time.is_changed());
+
+        graph[node].set_ignore_time_scale(true);
+        graph.time.update(&graph.pool, graph.get_root());
+        assert!(graph.time.is_changed());
+        graph.time.update(&graph.pool, graph.get_root());
+        assert!(!graph.time.is_changed());
+
+        graph.time.set_paused(true);
+        graph.time.update(&graph.pool, graph.get_root());
+        assert!(graph.time.is_changed());
+    }
+
+    #[test]
+    fn test_sub_stepping() {
+        let sub_stepping = SubStepping {
+            enabled: true,
+            max_step: 0.1,
+            max_substeps: 4,
+        };
+
+        assert_eq!(sub_stepping.substep_count(0.05), 1);
+        assert_eq!(sub_stepping.substep_count(0.25), 3);
+        assert_eq!(sub_stepping.substep_count(10.0), 4);
+
+        let mut total = 0.0;
+        let mut count = 0;
+        sub_stepping.for_each_substep(0.25, |step, _| {
+            total += step;
+            count += 1;
+        });
+        assert_eq!(count, 3);
+        assert!((total - 0.25f32).abs() < 1.0e-6);
+
+        let disabled = SubStepping {
+            enabled: false,
+            ..sub_stepping
+        };
+        assert_eq!(disabled.substep_count(10.0), 1);
+
+        assert!(!SubStepping::default().enabled);
+        assert_eq!(SubStepping::default().substep_count(10.0), 1);
+    }
+}
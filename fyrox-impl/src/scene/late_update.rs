@@ -0,0 +1,250 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Late update stage allows to modify scene nodes (usually cameras) right before the scene is
+//! rendered, using the most recent input. See [`LateUpdateCallbacks`] docs for more info.
+
+use crate::{
+    core::{algebra::Vector2, pool::Handle},
+    engine::simulation::InputEvent,
+    event::Event,
+    graph::BaseSceneGraph,
+    scene::{camera::Camera, graph::Graph, node::Node, Scene},
+};
+use std::fmt::{Debug, Formatter};
+
+/// Input, that was received after the last update of the game logic.
+#[derive(Clone, Debug, Default)]
+pub struct LateUpdateInput {
+    events: Vec<InputEvent>,
+    mouse_delta: Vector2<f32>,
+}
+
+impl LateUpdateInput {
+    /// Returns input events in the order of arrival.
+    pub fn events(&self) -> &[InputEvent] {
+        &self.events
+    }
+
+    /// Returns accumulated raw mouse motion.
+    pub fn mouse_delta(&self) -> Vector2<f32> {
+        self.mouse_delta
+    }
+
+    pub(crate) fn push_os_event(&mut self, event: &Event<()>) {
+        if let Some(event) = InputEvent::from_os_event(event) {
+            if let InputEvent::MouseMotion { delta } = event {
+                self.mouse_delta += delta;
+            }
+            self.events.push(event);
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.events.clear();
+        self.mouse_delta = Default::default();
+    }
+}
+
+/// A context of a late update callback.
+pub struct LateUpdateContext<'a> {
+    /// A handle of the scene, that is being updated.
+    pub scene: Handle<Scene>,
+    /// A graph of the scene.
+    pub graph: &'a mut Graph,
+    /// A handle of the node, that owns the callback.
+    pub owner: Handle<Node>,
+    /// Input, that was received after the last update of the game logic. The same input will be
+    /// delivered to the game logic on the next update, so the changes made by late update
+    /// callbacks should be derived from the state calculated in the last update.
+    pub input: &'a LateUpdateInput,
+}
+
+/// A callback, that will be called right before rendering.
+pub type LateUpdateCallback = dyn FnMut(&mut LateUpdateContext) + 'static;
+
+/// A handle of a registered late update callback.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub struct LateUpdateId(u64);
+
+struct Entry {
+    id: LateUpdateId,
+    owner: Handle<Node>,
+    callback: Box<LateUpdateCallback>,
+}
+
+/// A set of callbacks, that are called after the scene was updated, right before the view matrices
+/// of cameras are uploaded to the GPU. It is the right place to apply input with minimal latency,
+/// for example to rotate a first-person camera using the mouse motion received after the last
+/// update of the game logic.
+///
+/// Every callback is owned by a scene node. A callback must modify only its owner and descendants
+/// of the owner, because only their global transforms (and view matrices of cameras) are
+/// recalculated after the callback. A callback is removed automatically when its owner is deleted.
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::{algebra::{UnitQuaternion, Vector3}, reflect::prelude::*, visitor::prelude::*,
+/// #            type_traits::prelude::*},
+/// #     script::{ScriptContext, ScriptTrait},
+/// # };
+/// #[derive(Visit, Reflect, Default, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+/// #[type_uuid(id = "a6a3f4d5-5a4b-4f53-bb6b-7b0dd1e3b0e1")]
+/// struct MouseLook;
+///
+/// impl ScriptTrait for MouseLook {
+///     fn on_start(&mut self, ctx: &mut ScriptContext) {
+///         ctx.scene.late_update.register(ctx.handle, |ctx| {
+///             let yaw = -ctx.input.mouse_delta().x * 0.005;
+///             let node = &mut ctx.graph[ctx.owner];
+///             let rotation = **node.local_transform().rotation();
+///             node.local_transform_mut().set_rotation(
+///                 UnitQuaternion::from_axis_angle(&Vector3::y_axis(), yaw) * rotation,
+///             );
+///         });
+///     }
+/// }
+/// ```
+#[derive(Default)]
+pub struct LateUpdateCallbacks {
+    entries: Vec<Entry>,
+    id_counter: u64,
+}
+
+impl Debug for LateUpdateCallbacks {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "LateUpdateCallbacks - {} callbacks", self.entries.len())
+    }
+}
+
+impl LateUpdateCallbacks {
+    /// Registers a new callback owned by the given node. Returns an id of the callback, that
+    /// could be used to unregister it.
+    pub fn register<F>(&mut self, owner: Handle<Node>, callback: F) -> LateUpdateId
+    where
+        F: FnMut(&mut LateUpdateContext) + 'static,
+    {
+        self.id_counter += 1;
+        let id = LateUpdateId(self.id_counter);
+        self.entries.push(Entry {
+            id,
+            owner,
+            callback: Box::new(callback),
+        });
+        id
+    }
+
+    /// Unregisters a callback with the given id. Returns `true` if the callback was registered.
+    pub fn unregister(&mut self, id: LateUpdateId) -> bool {
+        let count = self.entries.len();
+        self.entries.retain(|e| e.id != id);
+        self.entries.len() != count
+    }
+
+    /// Unregisters every callback of the given node.
+    pub fn unregister_all(&mut self, owner: Handle<Node>) {
+        self.entries.retain(|e| e.owner != owner);
+    }
+
+    /// Returns `true` if there's no registered callbacks.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns total amount of registered callbacks.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub(crate) fn run(&mut self, scene: Handle<Scene>, graph: &mut Graph, input: &LateUpdateInput) {
+        self.entries
+            .retain(|entry| graph.is_valid_handle(entry.owner));
+
+        for entry in self.entries.iter_mut() {
+            (entry.callback)(&mut LateUpdateContext {
+                scene,
+                graph,
+                owner: entry.owner,
+                input,
+            });
+
+            // The callback could remove its owner.
+            if graph.is_valid_handle(entry.owner) {
+                graph.update_hierarchical_data_for_descendants(entry.owner);
+
+                let mut stack = vec![entry.owner];
+                while let Some(handle) = stack.pop() {
+                    let node = &mut graph[handle];
+                    if let Some(camera) = node.cast_mut::<Camera>() {
+                        camera.update_view_matrix();
+                    }
+                    stack.extend_from_slice(node.children());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Vector3, pool::Handle},
+        graph::BaseSceneGraph,
+        scene::{
+            base::BaseBuilder,
+            camera::CameraBuilder,
+            graph::Graph,
+            late_update::{LateUpdateCallbacks, LateUpdateInput},
+            pivot::PivotBuilder,
+        },
+    };
+
+    #[test]
+    fn test_late_update() {
+        let mut graph = Graph::new();
+        let camera = CameraBuilder::new(BaseBuilder::new()).build(&mut graph);
+        let pivot =
+            PivotBuilder::new(BaseBuilder::new().with_children(&[camera])).build(&mut graph);
+        graph.update_hierarchical_data();
+
+        let mut callbacks = LateUpdateCallbacks::default();
+        callbacks.register(pivot, |ctx| {
+            ctx.graph[ctx.owner]
+                .local_transform_mut()
+                .set_position(Vector3::new(1.0, 2.0, 3.0));
+        });
+        let id = callbacks.register(pivot, |_| {});
+        assert_eq!(callbacks.len(), 2);
+        assert!(callbacks.unregister(id));
+
+        callbacks.run(Handle::NONE, &mut graph, &LateUpdateInput::default());
+
+        assert_eq!(graph[camera].global_position(), Vector3::new(1.0, 2.0, 3.0));
+        let view = graph[camera].as_camera().view_matrix();
+        assert_eq!(
+            view.transform_point(&Vector3::new(1.0, 2.0, 3.0).into()),
+            Vector3::zeros().into()
+        );
+
+        // Callbacks of deleted nodes must be removed.
+        graph.remove_node(pivot);
+        callbacks.run(Handle::NONE, &mut graph, &LateUpdateInput::default());
+        assert!(callbacks.is_empty());
+    }
+}
@@ -0,0 +1,711 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Bounding volume hierarchy for triangles of mesh surfaces. It is used to accelerate ray and
+//! shape casts against rendered geometry, see [`crate::scene::graph::Graph::raycast_geometry`]
+//! for more info.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Point3, Vector2, Vector3},
+        math::{
+            aabb::AxisAlignedBoundingBox,
+            get_barycentric_coords, is_point_inside_triangle,
+            ray::{CylinderKind, IntersectionResult, Ray},
+            Matrix4Ext, TriangleDefinition,
+        },
+        parking_lot::Mutex,
+        pool::Handle,
+    },
+    graph::BaseSceneGraph,
+    scene::{
+        graph::{geometry::GeometryIntersection, Graph},
+        mesh::{
+            buffer::{VertexAttributeUsage, VertexReadTrait},
+            surface::{Surface, SurfaceData},
+            Mesh,
+        },
+        node::Node,
+    },
+};
+
+/// Maximum amount of triangles in a leaf of the hierarchy.
+const LEAF_SIZE: usize = 4;
+
+#[derive(Clone, Debug)]
+struct BvhNode {
+    bounds: AxisAlignedBoundingBox,
+    // Index of the first triangle for leaves and index of the right child for branches. Left
+    // child of a branch is always placed right after the branch.
+    first: u32,
+    // Zero for branches.
+    count: u32,
+}
+
+/// Bounding volume hierarchy of a set of triangles. The hierarchy does not store the triangles
+/// themselves, instead it works with the triangles and vertex positions passed to its methods.
+/// The same triangles must be used all the times, vertex positions however could change - in
+/// this case [`Self::refit`] must be called to update the bounds of the hierarchy.
+#[derive(Clone, Debug, Default)]
+pub struct TriangleBvh {
+    nodes: Vec<BvhNode>,
+    indices: Vec<u32>,
+}
+
+fn triangle_vertices(
+    positions: &[Vector3<f32>],
+    triangle: &TriangleDefinition,
+) -> Option<[Vector3<f32>; 3]> {
+    Some([
+        *positions.get(triangle[0] as usize)?,
+        *positions.get(triangle[1] as usize)?,
+        *positions.get(triangle[2] as usize)?,
+    ])
+}
+
+impl TriangleBvh {
+    /// Builds a new hierarchy for the given triangles. Triangles that reference missing vertices
+    /// are ignored.
+    pub fn new(positions: &[Vector3<f32>], triangles: &[TriangleDefinition]) -> Self {
+        let mut bvh = Self::default();
+
+        let mut centroids = vec![Vector3::default(); triangles.len()];
+        for (index, triangle) in triangles.iter().enumerate() {
+            if let Some([a, b, c]) = triangle_vertices(positions, triangle) {
+                centroids[index] = (a + b + c).scale(1.0 / 3.0);
+                bvh.indices.push(index as u32);
+            }
+        }
+
+        if !bvh.indices.is_empty() {
+            bvh.build_recursive(0, bvh.indices.len(), &centroids, positions, triangles);
+        }
+
+        bvh
+    }
+
+    fn build_recursive(
+        &mut self,
+        start: usize,
+        end: usize,
+        centroids: &[Vector3<f32>],
+        positions: &[Vector3<f32>],
+        triangles: &[TriangleDefinition],
+    ) -> usize {
+        let mut bounds = AxisAlignedBoundingBox::default();
+        let mut centroid_bounds = AxisAlignedBoundingBox::default();
+        for &index in &self.indices[start..end] {
+            if let Some(vertices) = triangle_vertices(positions, &triangles[index as usize]) {
+                bounds.add_box(AxisAlignedBoundingBox::from_points(&vertices));
+            }
+            centroid_bounds.add_point(centroids[index as usize]);
+        }
+
+        let node_index = self.nodes.len();
+        self.nodes.push(BvhNode {
+            bounds,
+            first: start as u32,
+            count: (end - start) as u32,
+        });
+
+        if end - start > LEAF_SIZE {
+            let size = centroid_bounds.max - centroid_bounds.min;
+            let axis = if size.x >= size.y && size.x >= size.z {
+                0
+            } else if size.y >= size.z {
+                1
+            } else {
+                2
+            };
+
+            // All the centroids could be at the same spot, there is no way to split such set.
+            if size[axis] > 0.0 {
+                let middle = (start + end) / 2;
+                self.indices[start..end].select_nth_unstable_by(middle - start, |a, b| {
+                    centroids[*a as usize][axis].total_cmp(&centroids[*b as usize][axis])
+                });
+
+                self.build_recursive(start, middle, centroids, positions, triangles);
+                let right = self.build_recursive(middle, end, centroids, positions, triangles);
+
+                let node = &mut self.nodes[node_index];
+                node.first = right as u32;
+                node.count = 0;
+            }
+        }
+
+        node_index
+    }
+
+    /// Updates bounds of the hierarchy using new vertex positions. It is much faster than
+    /// building a new hierarchy, but the quality of the hierarchy degrades if the vertices
+    /// have moved significantly.
+    pub fn refit(&mut self, positions: &[Vector3<f32>], triangles: &[TriangleDefinition]) {
+        // Children are always placed after their parents, so reverse order guarantees that the
+        // children are updated first.
+        for node_index in (0..self.nodes.len()).rev() {
+            let node = &self.nodes[node_index];
+            let bounds = if node.count > 0 {
+                let mut bounds = AxisAlignedBoundingBox::default();
+                let range = node.first as usize..(node.first + node.count) as usize;
+                for &index in &self.indices[range] {
+                    if let Some(vertices) = triangle_vertices(positions, &triangles[index as usize])
+                    {
+                        bounds.add_box(AxisAlignedBoundingBox::from_points(&vertices));
+                    }
+                }
+                bounds
+            } else {
+                let mut bounds = self.nodes[node_index + 1].bounds;
+                bounds.add_box(self.nodes[node.first as usize].bounds);
+                bounds
+            };
+            self.nodes[node_index].bounds = bounds;
+        }
+    }
+
+    /// Returns bounds of the entire hierarchy.
+    pub fn bounds(&self) -> AxisAlignedBoundingBox {
+        self.nodes.first().map(|n| n.bounds).unwrap_or_default()
+    }
+
+    /// Finds the closest triangle along the given ray (`t` parameter in `[0; 1]` range). Bounds of
+    /// the hierarchy are inflated by the given amount (on each side), which is useful for shape
+    /// casts. `test` closure is called for every triangle that could be hit, it should return
+    /// the `t` parameter of the hit, if any. Returns the index of the closest triangle and its `t`
+    /// parameter.
+    pub fn cast(
+        &self,
+        ray: &Ray,
+        inflation: f32,
+        mut test: impl FnMut(u32) -> Option<f32>,
+    ) -> Option<(u32, f32)> {
+        let mut closest: Option<(u32, f32)> = None;
+        let mut stack = Vec::new();
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+
+            let mut bounds = node.bounds;
+            bounds.inflate(Vector3::repeat(2.0 * inflation));
+            let Some(range) = ray.aabb_intersection(&bounds) else {
+                continue;
+            };
+            let max_t = closest.map_or(1.0, |(_, t)| t);
+            if range.max < 0.0 || range.min > max_t {
+                continue;
+            }
+
+            if node.count > 0 {
+                for &index in &self.indices[node.first as usize..(node.first + node.count) as usize]
+                {
+                    if let Some(t) = test(index) {
+                        if (0.0..=closest.map_or(1.0, |(_, t)| t)).contains(&t) {
+                            closest = Some((index, t));
+                        }
+                    }
+                }
+            } else {
+                stack.push(node.first as usize);
+                stack.push(node_index + 1);
+            }
+        }
+
+        closest
+    }
+}
+
+/// Returns the first non-negative root of the intersection. Negative minimal root with positive
+/// maximal root means that the ray starts inside the shape, zero is returned in this case.
+fn first_root(result: Option<IntersectionResult>) -> Option<f32> {
+    let result = result?;
+    if result.min >= 0.0 {
+        Some(result.min)
+    } else if result.max >= 0.0 {
+        Some(0.0)
+    } else {
+        None
+    }
+}
+
+fn closest_point_on_segment(point: Vector3<f32>, a: Vector3<f32>, b: Vector3<f32>) -> Vector3<f32> {
+    let ab = b - a;
+    let t = ((point - a).dot(&ab) / ab.norm_squared().max(f32::EPSILON)).clamp(0.0, 1.0);
+    a + ab.scale(t)
+}
+
+/// Result of a ray or a sphere cast against a single triangle.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TriangleHit {
+    /// `t` parameter of the ray at the moment of contact.
+    pub t: f32,
+    /// A point of the triangle, that was hit.
+    pub contact: Vector3<f32>,
+    /// Normal at the contact point. For sphere casts the normal points from the contact point to
+    /// the center of the sphere.
+    pub normal: Vector3<f32>,
+}
+
+/// Casts a ray (`t` parameter in `[0; 1]` range) against the given triangle. Back faces (the
+/// faces whose normal points in the same direction as the ray) are ignored if `ignore_back_faces`
+/// is set.
+pub fn ray_triangle_cast(
+    ray: &Ray,
+    vertices: &[Vector3<f32>; 3],
+    ignore_back_faces: bool,
+) -> Option<TriangleHit> {
+    let normal = (vertices[1] - vertices[0])
+        .cross(&(vertices[2] - vertices[0]))
+        .try_normalize(f32::EPSILON)?;
+    if ignore_back_faces && normal.dot(&ray.dir) >= 0.0 {
+        return None;
+    }
+    let (t, contact) = ray.triangle_intersection(vertices)?;
+    Some(TriangleHit { t, contact, normal })
+}
+
+/// Casts a sphere with the given radius along the ray (`t` parameter in `[0; 1]` range) against
+/// the given triangle. Back faces (the faces whose normal points in the same direction as the
+/// ray) are ignored if `ignore_back_faces` is set.
+pub fn sphere_triangle_cast(
+    ray: &Ray,
+    radius: f32,
+    vertices: &[Vector3<f32>; 3],
+    ignore_back_faces: bool,
+) -> Option<TriangleHit> {
+    let mut normal = (vertices[1] - vertices[0])
+        .cross(&(vertices[2] - vertices[0]))
+        .try_normalize(f32::EPSILON)?;
+    if ignore_back_faces && normal.dot(&ray.dir) >= 0.0 {
+        return None;
+    }
+
+    // Make the normal face the origin of the ray, so the sphere hits the front side.
+    if normal.dot(&(ray.origin - vertices[0])) < 0.0 {
+        normal = -normal;
+    }
+
+    let mut closest: Option<TriangleHit> = None;
+    let mut check = |hit: TriangleHit| {
+        if (0.0..=1.0).contains(&hit.t) && closest.map_or(true, |closest| hit.t < closest.t) {
+            closest = Some(hit);
+        }
+    };
+
+    // Face. The sphere touches the face when its center reaches the plane shifted by the radius.
+    let distance = normal.dot(&(ray.origin - vertices[0]));
+    let speed = normal.dot(&ray.dir);
+    let face_t = if distance <= radius {
+        Some(0.0)
+    } else if speed < 0.0 {
+        Some((radius - distance) / speed)
+    } else {
+        None
+    };
+    if let Some(t) = face_t {
+        let contact =
+            ray.get_point(t) - normal.scale(normal.dot(&(ray.get_point(t) - vertices[0])));
+        if is_point_inside_triangle(&contact, vertices) {
+            check(TriangleHit { t, contact, normal });
+        }
+    }
+
+    // Edges and vertices.
+    for (a, b) in [
+        (vertices[0], vertices[1]),
+        (vertices[1], vertices[2]),
+        (vertices[2], vertices[0]),
+    ] {
+        let edge_t = first_root(ray.cylinder_intersection(&a, &b, radius, CylinderKind::Finite));
+        let vertex_t = first_root(ray.sphere_intersection(&a, radius));
+        for t in [edge_t, vertex_t].into_iter().flatten() {
+            let center = ray.get_point(t);
+            let contact = closest_point_on_segment(center, a, b);
+            check(TriangleHit {
+                t,
+                contact,
+                normal: (center - contact)
+                    .try_normalize(f32::EPSILON)
+                    .unwrap_or(normal),
+            });
+        }
+    }
+
+    closest
+}
+
+/// World-space shape cast against rendered geometry.
+pub(crate) struct GeometryCast {
+    /// Ray segment in world space.
+    pub ray: Ray,
+    /// Radius of a sphere that is being cast. Zero means that this is a ray cast.
+    pub radius: f32,
+    pub respect_skinning: bool,
+    pub ignore_back_faces: bool,
+}
+
+impl GeometryCast {
+    fn cast_triangle(&self, ray: &Ray, vertices: &[Vector3<f32>; 3]) -> Option<TriangleHit> {
+        if self.radius > 0.0 {
+            sphere_triangle_cast(ray, self.radius, vertices, self.ignore_back_faces)
+        } else {
+            ray_triangle_cast(ray, vertices, self.ignore_back_faces)
+        }
+    }
+}
+
+#[derive(Debug)]
+struct SurfaceGeometry {
+    data_key: u64,
+    vertex_modifications: u64,
+    geometry_modifications: u64,
+    positions: Vec<Vector3<f32>>,
+    tex_coords: Vec<Vector2<f32>>,
+    triangles: Vec<TriangleDefinition>,
+    bvh: TriangleBvh,
+    // Refitted copy of the hierarchy and skinned world-space positions, used for casts against
+    // skinned poses.
+    skinned_positions: Vec<Vector3<f32>>,
+    skinned_bvh: Option<TriangleBvh>,
+}
+
+impl SurfaceGeometry {
+    fn new(data_key: u64, data: &SurfaceData) -> Self {
+        let positions = data
+            .vertex_buffer
+            .iter()
+            .map(|view| {
+                view.read_3_f32(VertexAttributeUsage::Position)
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>();
+        let tex_coords = data
+            .vertex_buffer
+            .iter()
+            .map(|view| {
+                view.read_2_f32(VertexAttributeUsage::TexCoord0)
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>();
+        let triangles = data.geometry_buffer.triangles_ref().to_vec();
+        let bvh = TriangleBvh::new(&positions, &triangles);
+
+        Self {
+            data_key,
+            vertex_modifications: data.vertex_buffer.modifications_count(),
+            geometry_modifications: data.geometry_buffer.modifications_count(),
+            positions,
+            tex_coords,
+            triangles,
+            bvh,
+            skinned_positions: Default::default(),
+            skinned_bvh: None,
+        }
+    }
+
+    fn is_valid_for(&self, data_key: u64, data: &SurfaceData) -> bool {
+        self.data_key == data_key
+            && self.vertex_modifications == data.vertex_buffer.modifications_count()
+            && self.geometry_modifications == data.geometry_buffer.modifications_count()
+    }
+
+    fn update_skinned_positions(&mut self, surface: &Surface, data: &SurfaceData, graph: &Graph) {
+        let bone_matrices = surface
+            .bones()
+            .iter()
+            .map(|&bone| {
+                graph
+                    .try_get(bone)
+                    .map(|bone| bone.global_transform() * bone.inv_bind_pose_transform())
+                    .unwrap_or_else(Matrix4::identity)
+            })
+            .collect::<Vec<_>>();
+
+        self.skinned_positions.clear();
+        for (view, position) in data.vertex_buffer.iter().zip(self.positions.iter()) {
+            let (Ok(bone_indices), Ok(bone_weights)) = (
+                view.read_4_u8(VertexAttributeUsage::BoneIndices),
+                view.read_4_f32(VertexAttributeUsage::BoneWeight),
+            ) else {
+                self.skinned_positions.push(*position);
+                continue;
+            };
+
+            let mut skinned = Vector3::default();
+            for (&bone_index, &weight) in bone_indices.iter().zip(bone_weights.iter()) {
+                if let Some(matrix) = bone_matrices.get(bone_index as usize) {
+                    skinned += matrix
+                        .transform_point(&Point3::from(*position))
+                        .coords
+                        .scale(weight);
+                }
+            }
+            self.skinned_positions.push(skinned);
+        }
+
+        let bvh = self.skinned_bvh.get_or_insert_with(|| self.bvh.clone());
+        bvh.refit(&self.skinned_positions, &self.triangles);
+    }
+
+    fn tex_coord(
+        &self,
+        triangle: &TriangleDefinition,
+        barycentric: (f32, f32, f32),
+    ) -> Vector2<f32> {
+        let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|index| {
+            self.tex_coords
+                .get(index as usize)
+                .cloned()
+                .unwrap_or_default()
+        });
+        a.scale(barycentric.0) + b.scale(barycentric.1) + c.scale(barycentric.2)
+    }
+}
+
+/// Lazily built acceleration structures for every surface of a mesh.
+#[derive(Debug, Default)]
+pub(crate) struct MeshGeometryCache(Mutex<Vec<Option<SurfaceGeometry>>>);
+
+impl Clone for MeshGeometryCache {
+    fn clone(&self) -> Self {
+        // The cache will be rebuilt on demand.
+        Self::default()
+    }
+}
+
+/// Casts the shape against every surface of the mesh and returns the closest intersection.
+pub(crate) fn cast_mesh(
+    handle: Handle<Node>,
+    mesh: &Mesh,
+    graph: &Graph,
+    cast: &GeometryCast,
+) -> Option<GeometryIntersection> {
+    let mut cache = mesh.geometry_cache.0.lock();
+    cache.resize_with(mesh.surfaces().len(), || None);
+
+    let global_transform = mesh.global_transform();
+    let inv_global_transform = global_transform.try_inverse()?;
+    // Radius of the sphere in local space of the mesh. Local space could be non-uniformly scaled,
+    // so the radius is taken conservatively - it is used only to cull the nodes of the hierarchy,
+    // exact tests are performed in world space.
+    let local_radius = cast.radius * inv_global_transform.basis().norm();
+    let local_ray = cast.ray.transform(inv_global_transform);
+
+    let mut closest: Option<GeometryIntersection> = None;
+    for (surface_index, (surface, geometry)) in
+        mesh.surfaces().iter().zip(cache.iter_mut()).enumerate()
+    {
+        let data_resource = surface.data();
+        let data_key = data_resource.key();
+        let data_guard = data_resource.data_ref();
+        let Some(data) = data_guard.data_ref() else {
+            continue;
+        };
+
+        if geometry
+            .as_ref()
+            .map_or(true, |geometry| !geometry.is_valid_for(data_key, data))
+        {
+            *geometry = Some(SurfaceGeometry::new(data_key, data));
+        }
+        let geometry = geometry.as_mut().unwrap();
+
+        // The hierarchy reports only the index of the closest triangle, so the details of the
+        // closest hit are tracked separately.
+        let mut hit: Option<(u32, TriangleHit, [Vector3<f32>; 3])> = None;
+        let mut test = |index: u32, vertices: [Vector3<f32>; 3]| {
+            let result = cast.cast_triangle(&cast.ray, &vertices)?;
+            if hit.map_or(true, |(_, closest, _)| result.t < closest.t) {
+                hit = Some((index, result, vertices));
+            }
+            Some(result.t)
+        };
+
+        if cast.respect_skinning && !surface.bones().is_empty() {
+            // Skinned positions are in world space already.
+            geometry.update_skinned_positions(surface, data, graph);
+            let positions = &geometry.skinned_positions;
+            let triangles = &geometry.triangles;
+            if let Some(bvh) = geometry.skinned_bvh.as_ref() {
+                bvh.cast(&cast.ray, cast.radius, |index| {
+                    test(
+                        index,
+                        triangle_vertices(positions, &triangles[index as usize])?,
+                    )
+                });
+            }
+        } else {
+            let positions = &geometry.positions;
+            let triangles = &geometry.triangles;
+            geometry.bvh.cast(&local_ray, local_radius, |index| {
+                let vertices = triangle_vertices(positions, &triangles[index as usize])?;
+                test(
+                    index,
+                    vertices.map(|vertex| {
+                        global_transform
+                            .transform_point(&Point3::from(vertex))
+                            .coords
+                    }),
+                )
+            });
+        }
+
+        let Some((triangle_index, hit, vertices)) = hit else {
+            continue;
+        };
+
+        let toi = hit.t * cast.ray.dir.norm();
+        if closest.as_ref().map_or(true, |closest| toi < closest.toi) {
+            let barycentric =
+                get_barycentric_coords(&hit.contact, &vertices[0], &vertices[1], &vertices[2]);
+            closest = Some(GeometryIntersection {
+                node: handle,
+                surface_index,
+                triangle_index: triangle_index as usize,
+                position: Point3::from(hit.contact),
+                normal: hit.normal,
+                uv: geometry.tex_coord(&geometry.triangles[triangle_index as usize], barycentric),
+                toi,
+            });
+        }
+    }
+
+    closest
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn make_grid(size: usize) -> (Vec<Vector3<f32>>, Vec<TriangleDefinition>) {
+        let mut positions = Vec::new();
+        let mut triangles = Vec::new();
+        for z in 0..=size {
+            for x in 0..=size {
+                positions.push(Vector3::new(x as f32, 0.0, z as f32));
+            }
+        }
+        let row = size as u32 + 1;
+        for z in 0..size as u32 {
+            for x in 0..size as u32 {
+                let i = z * row + x;
+                triangles.push(TriangleDefinition([i, i + row, i + 1]));
+                triangles.push(TriangleDefinition([i + 1, i + row, i + row + 1]));
+            }
+        }
+        (positions, triangles)
+    }
+
+    fn brute_force(
+        ray: &Ray,
+        positions: &[Vector3<f32>],
+        triangles: &[TriangleDefinition],
+    ) -> Option<(u32, f32)> {
+        let mut closest: Option<(u32, f32)> = None;
+        for (index, triangle) in triangles.iter().enumerate() {
+            let vertices = triangle_vertices(positions, triangle).unwrap();
+            if let Some(hit) = ray_triangle_cast(ray, &vertices, false) {
+                if closest.map_or(true, |(_, t)| hit.t < t) {
+                    closest = Some((index as u32, hit.t));
+                }
+            }
+        }
+        closest
+    }
+
+    fn bvh_cast(
+        bvh: &TriangleBvh,
+        ray: &Ray,
+        positions: &[Vector3<f32>],
+        triangles: &[TriangleDefinition],
+    ) -> Option<(u32, f32)> {
+        bvh.cast(ray, 0.0, |index| {
+            let vertices = triangle_vertices(positions, &triangles[index as usize])?;
+            ray_triangle_cast(ray, &vertices, false).map(|hit| hit.t)
+        })
+    }
+
+    #[test]
+    fn triangle_bvh_matches_brute_force() {
+        let (positions, triangles) = make_grid(16);
+        let bvh = TriangleBvh::new(&positions, &triangles);
+
+        for (x, z) in [
+            (0.25, 0.25),
+            (3.7, 8.1),
+            (15.9, 15.9),
+            (7.5, 0.1),
+            (20.0, 3.0),
+        ] {
+            let ray = Ray::from_two_points(Vector3::new(x, 5.0, z), Vector3::new(x, -5.0, z));
+            let expected = brute_force(&ray, &positions, &triangles);
+            let actual = bvh_cast(&bvh, &ray, &positions, &triangles);
+            assert_eq!(actual.map(|(_, t)| t), expected.map(|(_, t)| t));
+            if let (Some((a, _)), Some((b, _))) = (actual, expected) {
+                assert_eq!(a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn triangle_bvh_refit() {
+        let (mut positions, triangles) = make_grid(8);
+        let mut bvh = TriangleBvh::new(&positions, &triangles);
+
+        for position in positions.iter_mut() {
+            position.y += 10.0;
+        }
+        let ray = Ray::from_two_points(Vector3::new(2.5, 20.0, 2.5), Vector3::new(2.5, 5.0, 2.5));
+        assert!(bvh_cast(&bvh, &ray, &positions, &triangles).is_none());
+
+        bvh.refit(&positions, &triangles);
+        let (_, t) = bvh_cast(&bvh, &ray, &positions, &triangles).unwrap();
+        assert!((t - 10.0 / 15.0).abs() < 1.0e-5);
+        assert_eq!(bvh.bounds().min.y, 10.0);
+    }
+
+    #[test]
+    fn sphere_triangle_cast_face_and_edge() {
+        let vertices = [
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, 1.0),
+            Vector3::new(1.0, 0.0, 0.0),
+        ];
+
+        // Face hit - the sphere stops at the radius above the plane.
+        let ray = Ray::from_two_points(Vector3::new(0.2, 2.0, 0.2), Vector3::new(0.2, -2.0, 0.2));
+        let hit = sphere_triangle_cast(&ray, 0.5, &vertices, false).unwrap();
+        assert!((ray.get_point(hit.t).y - 0.5).abs() < 1.0e-5);
+        assert!((hit.contact - Vector3::new(0.2, 0.0, 0.2)).norm() < 1.0e-5);
+        assert!((hit.normal - Vector3::y()).norm() < 1.0e-5);
+
+        // Edge hit - the sphere passes next to the triangle and touches its edge.
+        let ray = Ray::from_two_points(Vector3::new(-0.3, 2.0, 0.5), Vector3::new(-0.3, -2.0, 0.5));
+        let hit = sphere_triangle_cast(&ray, 0.5, &vertices, false).unwrap();
+        assert!((hit.contact - Vector3::new(0.0, 0.0, 0.5)).norm() < 1.0e-5);
+
+        // Miss.
+        let ray = Ray::from_two_points(Vector3::new(-2.0, 2.0, 0.5), Vector3::new(-2.0, -2.0, 0.5));
+        assert!(sphere_triangle_cast(&ray, 0.5, &vertices, false).is_none());
+    }
+}
@@ -56,6 +56,7 @@ use crate::{
                 VertexAttributeUsage, VertexBuffer, VertexBufferRefMut, VertexReadTrait,
                 VertexViewMut, VertexWriteTrait,
             },
+            bvh::MeshGeometryCache,
             surface::{BlendShape, Surface, SurfaceData, SurfaceResource},
         },
         node::{Node, NodeTrait, RdcControlFlow, SyncContext},
@@ -72,6 +73,8 @@ use std::{
 use strum_macros::{AsRefStr, EnumString, VariantNames};
 
 pub mod buffer;
+pub mod bvh;
+mod simplify;
 pub mod surface;
 pub mod vertex;
 
@@ -183,6 +186,7 @@ impl BatchContainer {
                 storage: self,
                 graph: ctx.graph,
                 render_pass_name: ctx.render_pass_name,
+                lod_fade: 1.0,
             });
         }
     }
@@ -343,6 +347,14 @@ pub struct Mesh {
     #[visit(optional)]
     blend_shapes: InheritableVariable<Vec<BlendShape>>,
 
+    #[visit(optional)]
+    #[reflect(
+        setter = "set_instance_custom_data",
+        description = "Custom data of the mesh, that is passed to shaders per each instance. It \
+    could be used to vary the look of instances that share the same material (tint, for example)."
+    )]
+    instance_custom_data: InheritableVariable<Vector4<f32>>,
+
     #[reflect(hidden)]
     #[visit(skip)]
     local_bounding_box: Cell<AxisAlignedBoundingBox>,
@@ -358,6 +370,10 @@ pub struct Mesh {
     #[reflect(hidden)]
     #[visit(skip)]
     batch_container: BatchContainerWrapper,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    pub(crate) geometry_cache: MeshGeometryCache,
 }
 
 impl Default for Mesh {
@@ -372,7 +388,9 @@ impl Default for Mesh {
             batching_mode: Default::default(),
             blend_shapes_property_name: Mesh::DEFAULT_BLEND_SHAPES_PROPERTY_NAME.to_string(),
             blend_shapes: Default::default(),
+            instance_custom_data: Default::default(),
             batch_container: Default::default(),
+            geometry_cache: Default::default(),
         }
     }
 }
@@ -531,6 +549,19 @@ impl Mesh {
     pub fn batching_mode(&self) -> BatchingMode {
         *self.batching_mode
     }
+
+    /// Sets custom data of the mesh, that is passed to shaders per each instance (`customData`
+    /// field of `fyrox_instanceData` and `fyrox_instances` built-in property groups). It allows you
+    /// to vary the look of meshes that share the same material, without breaking instanced
+    /// rendering. Returns the old value.
+    pub fn set_instance_custom_data(&mut self, data: Vector4<f32>) -> Vector4<f32> {
+        self.instance_custom_data.set_value_and_mark_modified(data)
+    }
+
+    /// Returns custom data of the mesh, that is passed to shaders per each instance.
+    pub fn instance_custom_data(&self) -> Vector4<f32> {
+        *self.instance_custom_data
+    }
 }
 
 fn extend_aabb_from_vertex_buffer(
@@ -693,6 +724,8 @@ impl NodeTrait for Mesh {
                         blend_shapes_weights: Default::default(),
                         element_range: ElementRange::Full,
                         node_handle: self.handle(),
+                        custom_data: self.instance_custom_data(),
+                        lod_fade: ctx.lod_fade,
                     },
                 );
             }
@@ -738,6 +771,7 @@ impl NodeTrait for Mesh {
                                     &self.blend_shapes_property_name,
                                     MaterialResourceBinding::Texture(MaterialTextureBinding {
                                         value: Some(texture.clone()),
+                                        sampler: None,
                                     }),
                                 );
                                 material_copy
@@ -769,6 +803,8 @@ impl NodeTrait for Mesh {
                                     .collect(),
                                 element_range: ElementRange::Full,
                                 node_handle: self.handle(),
+                                custom_data: self.instance_custom_data(),
+                                lod_fade: ctx.lod_fade,
                             },
                         );
                     }
@@ -872,6 +908,7 @@ pub struct MeshBuilder {
     blend_shapes: Vec<BlendShape>,
     batching_mode: BatchingMode,
     blend_shapes_property_name: String,
+    instance_custom_data: Vector4<f32>,
 }
 
 impl MeshBuilder {
@@ -884,6 +921,7 @@ impl MeshBuilder {
             blend_shapes: Default::default(),
             batching_mode: BatchingMode::None,
             blend_shapes_property_name: Mesh::DEFAULT_BLEND_SHAPES_PROPERTY_NAME.to_string(),
+            instance_custom_data: Default::default(),
         }
     }
 
@@ -919,6 +957,13 @@ impl MeshBuilder {
         self
     }
 
+    /// Sets custom data of the mesh, that is passed to shaders per each instance. See
+    /// [`Mesh::set_instance_custom_data`] for more info.
+    pub fn with_instance_custom_data(mut self, data: Vector4<f32>) -> Self {
+        self.instance_custom_data = data;
+        self
+    }
+
     /// Creates new mesh.
     pub fn build_node(self) -> Node {
         Node::new(Mesh {
@@ -931,7 +976,9 @@ impl MeshBuilder {
             world_bounding_box: Default::default(),
             batching_mode: self.batching_mode.into(),
             batch_container: Default::default(),
+            geometry_cache: Default::default(),
             blend_shapes_property_name: self.blend_shapes_property_name,
+            instance_custom_data: self.instance_custom_data.into(),
         })
     }
 
@@ -0,0 +1,450 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Mesh simplification based on quadric error metrics. See [`simplify_triangles`] docs for more
+//! info.
+
+use crate::core::{algebra::Vector3, math::TriangleDefinition};
+use fxhash::FxHashMap;
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+// Edges on the open borders of a mesh are penalized much more than interior edges, otherwise the
+// borders (and the silhouette of the mesh) will be eaten first.
+const BOUNDARY_WEIGHT: f64 = 100.0;
+
+/// Symmetric 4x4 matrix of a quadric error, only the upper triangle is stored.
+#[derive(Copy, Clone, Default)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+    fn from_plane(normal: Vector3<f64>, d: f64, weight: f64) -> Self {
+        let (a, b, c) = (normal.x, normal.y, normal.z);
+        Self(
+            [
+                a * a,
+                a * b,
+                a * c,
+                a * d,
+                b * b,
+                b * c,
+                b * d,
+                c * c,
+                c * d,
+                d * d,
+            ]
+            .map(|v| v * weight),
+        )
+    }
+
+    fn add(&mut self, other: &Quadric) {
+        for (a, b) in self.0.iter_mut().zip(other.0.iter()) {
+            *a += *b;
+        }
+    }
+
+    fn error(&self, p: &Vector3<f64>) -> f64 {
+        let q = &self.0;
+        let (x, y, z) = (p.x, p.y, p.z);
+        q[0] * x * x
+            + 2.0 * q[1] * x * y
+            + 2.0 * q[2] * x * z
+            + 2.0 * q[3] * x
+            + q[4] * y * y
+            + 2.0 * q[5] * y * z
+            + 2.0 * q[6] * y
+            + q[7] * z * z
+            + 2.0 * q[8] * z
+            + q[9]
+    }
+}
+
+struct Candidate {
+    cost: f64,
+    from: u32,
+    to: u32,
+    from_version: u32,
+    to_version: u32,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cost == other.cost
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed, the heap must pop the cheapest collapse first.
+        other
+            .cost
+            .partial_cmp(&self.cost)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+struct Simplifier {
+    // Vertices with the same position are welded into a single cluster, so the seams (UV, normals)
+    // of the mesh do not prevent collapses and do not crack.
+    vertex_cluster: Vec<u32>,
+    cluster_positions: Vec<Vector3<f64>>,
+    cluster_representatives: Vec<u32>,
+    cluster_triangles: Vec<Vec<usize>>,
+    cluster_quadrics: Vec<Quadric>,
+    cluster_versions: Vec<u32>,
+    cluster_removed: Vec<bool>,
+    triangles: Vec<[u32; 3]>,
+    triangle_alive: Vec<bool>,
+    alive_count: usize,
+    heap: BinaryHeap<Candidate>,
+}
+
+impl Simplifier {
+    fn new(positions: &[Vector3<f32>], triangles: &[TriangleDefinition]) -> Self {
+        let mut vertex_cluster = Vec::with_capacity(positions.len());
+        let mut cluster_positions = Vec::new();
+        let mut cluster_representatives = Vec::new();
+        let mut clusters = FxHashMap::default();
+        for (index, position) in positions.iter().enumerate() {
+            let key = [
+                position.x.to_bits(),
+                position.y.to_bits(),
+                position.z.to_bits(),
+            ];
+            let cluster = *clusters.entry(key).or_insert_with(|| {
+                cluster_positions.push(position.cast::<f64>());
+                cluster_representatives.push(index as u32);
+                cluster_positions.len() as u32 - 1
+            });
+            vertex_cluster.push(cluster);
+        }
+
+        let cluster_count = cluster_positions.len();
+        let mut simplifier = Self {
+            vertex_cluster,
+            cluster_positions,
+            cluster_representatives,
+            cluster_triangles: vec![Vec::new(); cluster_count],
+            cluster_quadrics: vec![Quadric::default(); cluster_count],
+            cluster_versions: vec![0; cluster_count],
+            cluster_removed: vec![false; cluster_count],
+            triangles: Vec::with_capacity(triangles.len()),
+            triangle_alive: Vec::with_capacity(triangles.len()),
+            alive_count: 0,
+            heap: Default::default(),
+        };
+
+        for triangle in triangles {
+            let clusters = triangle.0.map(|i| simplifier.vertex_cluster[i as usize]);
+            let alive = clusters[0] != clusters[1]
+                && clusters[1] != clusters[2]
+                && clusters[0] != clusters[2];
+            let index = simplifier.triangles.len();
+            simplifier.triangles.push(triangle.0);
+            simplifier.triangle_alive.push(alive);
+            if alive {
+                simplifier.alive_count += 1;
+                for cluster in clusters {
+                    simplifier.cluster_triangles[cluster as usize].push(index);
+                }
+            }
+        }
+
+        simplifier.compute_quadrics();
+
+        for cluster in 0..cluster_count as u32 {
+            simplifier.push_candidates(cluster);
+        }
+
+        simplifier
+    }
+
+    fn triangle_clusters(&self, triangle: usize) -> [u32; 3] {
+        self.triangles[triangle].map(|i| self.vertex_cluster[i as usize])
+    }
+
+    fn triangle_normal(&self, clusters: [u32; 3]) -> Vector3<f64> {
+        let [a, b, c] = clusters.map(|c| self.cluster_positions[c as usize]);
+        (b - a).cross(&(c - a))
+    }
+
+    fn compute_quadrics(&mut self) {
+        let mut edge_usage = FxHashMap::<(u32, u32), usize>::default();
+
+        for triangle in 0..self.triangles.len() {
+            if !self.triangle_alive[triangle] {
+                continue;
+            }
+
+            let clusters = self.triangle_clusters(triangle);
+            let normal = self.triangle_normal(clusters);
+            let double_area = normal.norm();
+            if let Some(normal) = normal.try_normalize(f64::EPSILON) {
+                let d = -normal.dot(&self.cluster_positions[clusters[0] as usize]);
+                let quadric = Quadric::from_plane(normal, d, double_area * 0.5);
+                for cluster in clusters {
+                    self.cluster_quadrics[cluster as usize].add(&quadric);
+                }
+            }
+
+            for (a, b) in [(0, 1), (1, 2), (2, 0)] {
+                let (a, b) = (clusters[a], clusters[b]);
+                *edge_usage.entry((a.min(b), a.max(b))).or_default() += 1;
+            }
+        }
+
+        for triangle in 0..self.triangles.len() {
+            if !self.triangle_alive[triangle] {
+                continue;
+            }
+
+            let clusters = self.triangle_clusters(triangle);
+            let Some(normal) = self.triangle_normal(clusters).try_normalize(f64::EPSILON) else {
+                continue;
+            };
+
+            for (a, b) in [(0, 1), (1, 2), (2, 0)] {
+                let (a, b) = (clusters[a], clusters[b]);
+                if edge_usage.get(&(a.min(b), a.max(b))) != Some(&1) {
+                    continue;
+                }
+
+                let pa = self.cluster_positions[a as usize];
+                let edge = self.cluster_positions[b as usize] - pa;
+                if let Some(plane_normal) = edge.cross(&normal).try_normalize(f64::EPSILON) {
+                    let d = -plane_normal.dot(&pa);
+                    let quadric =
+                        Quadric::from_plane(plane_normal, d, edge.norm_squared() * BOUNDARY_WEIGHT);
+                    self.cluster_quadrics[a as usize].add(&quadric);
+                    self.cluster_quadrics[b as usize].add(&quadric);
+                }
+            }
+        }
+    }
+
+    fn push_candidates(&mut self, cluster: u32) {
+        let mut neighbours = Vec::new();
+        for &triangle in self.cluster_triangles[cluster as usize].iter() {
+            if !self.triangle_alive[triangle] {
+                continue;
+            }
+            for other in self.triangle_clusters(triangle) {
+                if other != cluster && !neighbours.contains(&other) {
+                    neighbours.push(other);
+                }
+            }
+        }
+
+        for other in neighbours {
+            let mut quadric = self.cluster_quadrics[cluster as usize];
+            quadric.add(&self.cluster_quadrics[other as usize]);
+
+            // Only the existing vertices are used as collapse targets, so the attributes of the
+            // vertices (texture coordinates, normals, etc.) remain valid.
+            let to_other = quadric.error(&self.cluster_positions[other as usize]);
+            let to_cluster = quadric.error(&self.cluster_positions[cluster as usize]);
+            let (from, to, cost) = if to_other <= to_cluster {
+                (cluster, other, to_other)
+            } else {
+                (other, cluster, to_cluster)
+            };
+
+            self.heap.push(Candidate {
+                cost,
+                from,
+                to,
+                from_version: self.cluster_versions[from as usize],
+                to_version: self.cluster_versions[to as usize],
+            });
+        }
+    }
+
+    fn is_collapse_valid(&self, from: u32, to: u32) -> bool {
+        let to_position = self.cluster_positions[to as usize];
+        for &triangle in self.cluster_triangles[from as usize].iter() {
+            if !self.triangle_alive[triangle] {
+                continue;
+            }
+
+            let clusters = self.triangle_clusters(triangle);
+            if clusters.contains(&to) {
+                continue;
+            }
+
+            let old_normal = self.triangle_normal(clusters);
+            let positions = clusters.map(|c| {
+                if c == from {
+                    to_position
+                } else {
+                    self.cluster_positions[c as usize]
+                }
+            });
+            let new_normal = (positions[1] - positions[0]).cross(&(positions[2] - positions[0]));
+
+            // Prevent flipped and degenerated triangles.
+            if old_normal.dot(&new_normal) <= 0.0 {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn collapse(&mut self, from: u32, to: u32) {
+        // Triangles sharing the collapsed edge disappear, their corners define which vertex of the
+        // target cluster replaces each vertex of the source cluster.
+        let mut replacements = FxHashMap::default();
+        let triangles = std::mem::take(&mut self.cluster_triangles[from as usize]);
+        for &triangle in triangles.iter() {
+            if !self.triangle_alive[triangle] {
+                continue;
+            }
+
+            let clusters = self.triangle_clusters(triangle);
+            if let Some(to_corner) = clusters.iter().position(|c| *c == to) {
+                let from_corner = clusters.iter().position(|c| *c == from).unwrap();
+                replacements.insert(
+                    self.triangles[triangle][from_corner],
+                    self.triangles[triangle][to_corner],
+                );
+                self.triangle_alive[triangle] = false;
+                self.alive_count -= 1;
+            }
+        }
+
+        let representative = self.cluster_representatives[to as usize];
+        for &triangle in triangles.iter() {
+            if !self.triangle_alive[triangle] {
+                continue;
+            }
+
+            for vertex in self.triangles[triangle].iter_mut() {
+                if self.vertex_cluster[*vertex as usize] == from {
+                    *vertex = replacements.get(vertex).cloned().unwrap_or(representative);
+                }
+            }
+            self.cluster_triangles[to as usize].push(triangle);
+        }
+
+        let quadric = self.cluster_quadrics[from as usize];
+        self.cluster_quadrics[to as usize].add(&quadric);
+        self.cluster_removed[from as usize] = true;
+        self.cluster_versions[to as usize] += 1;
+        self.cluster_triangles[to as usize].retain(|t| self.triangle_alive[*t]);
+
+        self.push_candidates(to);
+    }
+
+    fn run(&mut self, target_triangle_count: usize) {
+        while self.alive_count > target_triangle_count {
+            let Some(candidate) = self.heap.pop() else {
+                break;
+            };
+
+            let (from, to) = (candidate.from as usize, candidate.to as usize);
+            if self.cluster_removed[from]
+                || self.cluster_removed[to]
+                || self.cluster_versions[from] != candidate.from_version
+                || self.cluster_versions[to] != candidate.to_version
+            {
+                continue;
+            }
+
+            if self.is_collapse_valid(candidate.from, candidate.to) {
+                self.collapse(candidate.from, candidate.to);
+            }
+        }
+    }
+}
+
+/// Reduces the amount of triangles in the given triangle list down to `target_triangle_count` (or
+/// less, if it is impossible to reach the target without breaking the mesh) using iterative edge
+/// collapses, guided by quadric error metrics (Garland and Heckbert, "Surface Simplification Using
+/// Quadric Error Metrics"). Edges are collapsed into one of their existing vertices, so the
+/// returned triangles reference the same vertices as the source triangles and the vertex buffer
+/// could be reused as is.
+pub fn simplify_triangles(
+    positions: &[Vector3<f32>],
+    triangles: &[TriangleDefinition],
+    target_triangle_count: usize,
+) -> Vec<TriangleDefinition> {
+    let mut simplifier = Simplifier::new(positions, triangles);
+    simplifier.run(target_triangle_count);
+    simplifier
+        .triangles
+        .iter()
+        .zip(simplifier.triangle_alive.iter())
+        .filter_map(|(triangle, alive)| alive.then_some(TriangleDefinition(*triangle)))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::simplify_triangles;
+    use crate::core::{algebra::Vector3, math::TriangleDefinition};
+
+    fn grid(size: u32) -> (Vec<Vector3<f32>>, Vec<TriangleDefinition>) {
+        let mut positions = Vec::new();
+        for y in 0..=size {
+            for x in 0..=size {
+                positions.push(Vector3::new(x as f32, y as f32, 0.0));
+            }
+        }
+        let mut triangles = Vec::new();
+        for y in 0..size {
+            for x in 0..size {
+                let i = y * (size + 1) + x;
+                triangles.push(TriangleDefinition([i, i + 1, i + size + 2]));
+                triangles.push(TriangleDefinition([i, i + size + 2, i + size + 1]));
+            }
+        }
+        (positions, triangles)
+    }
+
+    #[test]
+    fn test_simplify_flat_grid() {
+        let (positions, triangles) = grid(8);
+        let simplified = simplify_triangles(&positions, &triangles, triangles.len() / 4);
+        assert!(simplified.len() <= triangles.len() / 4);
+        assert!(!simplified.is_empty());
+
+        // Flat grid must stay flat and must cover the same area.
+        let area = simplified
+            .iter()
+            .map(|t| {
+                let [a, b, c] = t.0.map(|i| positions[i as usize]);
+                (b - a).cross(&(c - a)).z * 0.5
+            })
+            .sum::<f32>();
+        assert!((area - 64.0).abs() < 1.0e-3);
+    }
+
+    #[test]
+    fn test_simplify_keeps_target() {
+        let (positions, triangles) = grid(4);
+        let simplified = simplify_triangles(&positions, &triangles, triangles.len());
+        assert_eq!(simplified, triangles);
+    }
+}
@@ -51,6 +51,7 @@ use crate::{
                 TriangleBuffer, VertexAttributeUsage, VertexBuffer, VertexFetchError,
                 VertexReadTrait, VertexTrait, VertexWriteTrait,
             },
+            simplify::simplify_triangles,
             vertex::StaticVertex,
         },
         node::Node,
@@ -1048,6 +1049,31 @@ impl SurfaceData {
         )
     }
 
+    /// Creates a simplified copy of the surface data, that has approximately `triangle_ratio` (in
+    /// `0.0..=1.0` range) of the triangles of the source data. The vertex buffer is shared with the
+    /// source data as is, only the index buffer is reduced. This method is meant to be used to
+    /// generate levels of detail for meshes, see [`crate::scene::base::LodGroup`] for more info.
+    pub fn simplify(&self, triangle_ratio: f32) -> Result<Self, VertexFetchError> {
+        let positions = self
+            .vertex_buffer
+            .iter()
+            .map(|view| view.read_3_f32(VertexAttributeUsage::Position))
+            .collect::<Result<Vec<_>, _>>()?;
+        let triangles = self.geometry_buffer.triangles_ref();
+        let target_triangle_count =
+            (triangles.len() as f32 * triangle_ratio.clamp(0.0, 1.0)).round() as usize;
+        let mut data = Self::new(
+            self.vertex_buffer.clone(),
+            TriangleBuffer::new(simplify_triangles(
+                &positions,
+                triangles,
+                target_triangle_count,
+            )),
+        );
+        data.blend_shapes_container = self.blend_shapes_container.clone();
+        Ok(data)
+    }
+
     /// Clears both vertex and index buffers.
     pub fn clear(&mut self) {
         self.geometry_buffer.modify().clear();
@@ -1361,6 +1387,12 @@ impl Surface {
         &self.data
     }
 
+    /// Sets new data for the surface.
+    #[inline]
+    pub fn set_data(&mut self, data: SurfaceResource) {
+        self.data.set_value_and_mark_modified(data);
+    }
+
     /// Returns current material of the surface.
     pub fn material(&self) -> &MaterialResource {
         &self.material
@@ -31,9 +31,12 @@ pub mod camera;
 pub mod collider;
 pub mod debug;
 pub mod decal;
+pub mod destruction;
 pub mod dim2;
+pub mod floating_origin;
 pub mod graph;
 pub mod joint;
+pub mod late_update;
 pub mod light;
 pub mod mesh;
 pub mod navmesh;
@@ -42,11 +45,15 @@ pub mod particle_system;
 pub mod pivot;
 pub mod ragdoll;
 pub mod rigidbody;
+pub mod socket;
+pub mod soft_body;
 pub mod sound;
 pub mod sprite;
 pub mod terrain;
 pub mod tilemap;
 pub mod transform;
+pub mod vehicle;
+pub mod wind_zone;
 
 use crate::renderer::framework::PolygonFillMode;
 use crate::{
@@ -64,12 +71,16 @@ use crate::{
     graph::NodeHandleMap,
     resource::texture::TextureResource,
     scene::{
+        animation::snapshot::PoseModifiers,
         base::BaseBuilder,
         camera::Camera,
         debug::SceneDrawingContext,
+        floating_origin::FloatingOrigin,
         graph::{Graph, GraphPerformanceStatistics, GraphUpdateSwitches},
+        late_update::LateUpdateCallbacks,
         navmesh::NavigationalMeshBuilder,
         node::Node,
+        socket::SocketAttachments,
         sound::SoundEngine,
     },
     utils::navmesh::Navmesh,
@@ -233,6 +244,23 @@ pub struct Scene {
     /// to false for menu's scene and when you need to open a menu - set it to true and
     /// set `enabled` flag to false for level's scene.
     pub enabled: InheritableVariable<bool>,
+
+    /// Floating origin settings of the scene. See [`FloatingOrigin`] docs for more info.
+    pub floating_origin: FloatingOrigin,
+
+    /// A set of callbacks, that will be called right before rendering of the scene. See
+    /// [`LateUpdateCallbacks`] docs for more info.
+    #[reflect(hidden)]
+    pub late_update: LateUpdateCallbacks,
+
+    /// A set of callbacks, that modify evaluated poses of animations. See [`PoseModifiers`] docs
+    /// for more info.
+    #[reflect(hidden)]
+    pub pose_modifiers: PoseModifiers,
+
+    /// A set of nodes attached to sockets. See [`SocketAttachments`] docs for more info.
+    #[reflect(hidden)]
+    pub sockets: SocketAttachments,
 }
 
 impl Default for Scene {
@@ -243,6 +271,10 @@ impl Default for Scene {
             drawing_context: Default::default(),
             performance_statistics: Default::default(),
             enabled: true.into(),
+            floating_origin: Default::default(),
+            late_update: Default::default(),
+            pose_modifiers: Default::default(),
+            sockets: Default::default(),
         }
     }
 }
@@ -402,6 +434,10 @@ impl Scene {
             drawing_context: Default::default(),
             performance_statistics: Default::default(),
             enabled: true.into(),
+            floating_origin: Default::default(),
+            late_update: Default::default(),
+            pose_modifiers: Default::default(),
+            sockets: Default::default(),
         }
     }
 
@@ -426,7 +462,19 @@ impl Scene {
     /// it updates physics, animations, and each graph node. In most cases there is
     /// no need to call it directly, engine automatically updates all available scenes.
     pub fn update(&mut self, frame_size: Vector2<f32>, dt: f32, switches: GraphUpdateSwitches) {
+        // Rebase the world before the update, so every world-space data (camera matrices, etc.)
+        // will be calculated using new origin.
+        self.floating_origin.update(&mut self.graph);
+        #[cfg(feature = "f64_transform")]
+        self.graph.update_precise_origin();
+        let paused = switches.paused;
         self.graph.update(frame_size, dt, switches);
+        if !paused && !self.pose_modifiers.is_empty() {
+            self.pose_modifiers.run(&mut self.graph, dt);
+        }
+        if !paused && !self.sockets.is_empty() {
+            self.sockets.sync(&mut self.graph);
+        }
         self.performance_statistics.graph = self.graph.performance_statistics.clone();
     }
 
@@ -455,6 +503,10 @@ impl Scene {
                 drawing_context: self.drawing_context.clone(),
                 performance_statistics: Default::default(),
                 enabled: self.enabled.clone(),
+                floating_origin: self.floating_origin.clone(),
+                late_update: Default::default(),
+                pose_modifiers: Default::default(),
+                sockets: self.sockets.remap(&old_new_map),
             },
             old_new_map,
         )
@@ -479,6 +531,8 @@ impl Scene {
         let _ = self
             .rendering_options
             .visit("RenderingOptions", &mut region);
+        let _ = self.floating_origin.visit("FloatingOrigin", &mut region);
+        let _ = self.sockets.visit("Sockets", &mut region);
 
         // Backward compatibility.
         let mut navmeshes = NavMeshContainer::default();
@@ -619,6 +673,17 @@ impl SceneContainer {
         self.destruction_list.push((handle, self.pool.free(handle)));
     }
 
+    /// Replaces the scene at the given handle with the new one and returns the old scene. Unlike
+    /// removal and addition, the handle of the scene stays the same, so every place, that
+    /// references the scene, remains valid. It could be used to implement snapshots of scenes.
+    pub fn replace(&mut self, handle: Handle<Scene>, scene: Scene) -> Scene {
+        let mut sound_engine = self.sound_engine.state();
+        sound_engine.remove_context(self.pool[handle].graph.sound_context.native.clone());
+        sound_engine.add_context(scene.graph.sound_context.native.clone());
+        drop(sound_engine);
+        std::mem::replace(&mut self.pool[handle], scene)
+    }
+
     /// Takes scene from the container and transfers ownership to caller. You must either
     /// put scene back using ticket or call `forget_ticket` to make memory used by scene
     /// vacant again.
@@ -26,6 +26,7 @@ use crate::scene::{
     animation::{absm::AnimationBlendingStateMachine, AnimationPlayer},
     camera::Camera,
     decal::Decal,
+    destruction::Destructible,
     dim2::{self, rectangle::Rectangle},
     light::{directional::DirectionalLight, point::PointLight, spot::SpotLight},
     mesh::Mesh,
@@ -34,10 +35,13 @@ use crate::scene::{
     particle_system::ParticleSystem,
     pivot::Pivot,
     ragdoll::Ragdoll,
-    sound::{listener::Listener, Sound},
+    soft_body::{cloth::Cloth, SoftBody},
+    sound::{listener::Listener, reverb_zone::ReverbZone, Sound},
     sprite::Sprite,
     terrain::Terrain,
     tilemap::TileMap,
+    vehicle::RayCastVehicle,
+    wind_zone::WindZone,
 };
 use fyrox_graph::constructor::{GraphNodeConstructor, GraphNodeConstructorContainer};
 
@@ -75,6 +79,12 @@ pub fn new_node_constructor_container() -> NodeConstructorContainer {
     container.add::<NavigationalMesh>();
     container.add::<Ragdoll>();
     container.add::<TileMap>();
+    container.add::<RayCastVehicle>();
+    container.add::<Destructible>();
+    container.add::<SoftBody>();
+    container.add::<Cloth>();
+    container.add::<WindZone>();
+    container.add::<ReverbZone>();
 
     container
 }
@@ -28,7 +28,7 @@ use crate::resource::model::Model;
 use crate::{
     asset::untyped::UntypedResource,
     core::{
-        algebra::{Matrix4, Vector2},
+        algebra::{Matrix4, Vector2, Vector3},
         math::aabb::AxisAlignedBoundingBox,
         pool::Handle,
         reflect::prelude::*,
@@ -122,8 +122,14 @@ pub struct SyncContext<'a, 'b> {
 pub struct UpdateContext<'a> {
     /// Size of client area of the window.
     pub frame_size: Vector2<f32>,
-    /// A time that have passed since last update call.
+    /// A time that have passed since last update call. It is already scaled by the time scale of
+    /// the scene (see [`graph::time::SceneTime`]).
     pub dt: f32,
+    /// Time scale, that was used to calculate `dt`. It could be used to scale values that do not
+    /// depend on time directly.
+    pub time_scale: f32,
+    /// Sub-stepping settings of the scene. See [`graph::time::SubStepping`] docs for more info.
+    pub sub_stepping: graph::time::SubStepping,
     /// A reference to a pool with nodes from a scene graph.
     pub nodes: &'a mut NodePool,
     /// A mutable reference to 3D physics world.
@@ -132,6 +138,9 @@ pub struct UpdateContext<'a> {
     pub physics2d: &'a mut dim2::physics::PhysicsWorld,
     /// A mutable reference to sound context.
     pub sound_context: &'a mut SoundContext,
+    /// Global position of the first enabled camera in the scene, if any. It could be used to
+    /// implement distance-based level of details.
+    pub observer_position: Option<Vector3<f32>>,
 }
 
 /// An enumeration, that contains all possible render data collection strategies.
@@ -199,6 +208,11 @@ pub trait NodeTrait: BaseNodeTrait + Reflect + Visit + ComponentProvider {
     /// Updates internal state of the node.
     fn update(&mut self, #[allow(unused_variables)] context: &mut UpdateContext) {}
 
+    /// Called when the origin of the world was shifted by the given offset (see
+    /// [`Graph::shift_origin`]). Transforms of every node are shifted automatically, this method
+    /// should be used to shift internal data of the node, that is stored in world coordinates.
+    fn on_origin_shifted(&mut self, #[allow(unused_variables)] offset: Vector3<f32>) {}
+
     /// Allows the node to emit a set of render data. This is a high-level rendering method which can only
     /// do culling and provide render data. Render data is just a surface (vertex + index buffers) and a
     /// material.
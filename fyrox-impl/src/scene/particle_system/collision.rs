@@ -0,0 +1,58 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Particle collisions with physics colliders. See [`ParticleCollision`] docs for more info.
+
+use crate::{
+    core::{reflect::prelude::*, type_traits::prelude::*, visitor::prelude::*},
+    scene::collider::InteractionGroups,
+};
+
+/// Defines how particles collide with physics colliders. Every particle casts a ray along its
+/// path on every update, so collisions should be used with care on particle systems with lots
+/// of particles.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect, TypeUuidProvider)]
+#[type_uuid(id = "931160ba-5682-4a03-be2d-7cc0b1420049")]
+pub struct ParticleCollision {
+    /// Whether the collisions are enabled or not.
+    pub enabled: bool,
+    /// Defines how much of the velocity is kept along the normal of a surface after a collision.
+    /// 0.0 - the particle sticks to the surface, 1.0 - perfectly elastic bounce.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    pub restitution: f32,
+    /// A fraction of the velocity, that is lost on every collision.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    pub friction: f32,
+    /// Whether to kill a particle on collision or not.
+    pub kill_on_collision: bool,
+    /// Collision groups, that are used to filter out colliders.
+    pub groups: InteractionGroups,
+}
+
+impl Default for ParticleCollision {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            restitution: 0.5,
+            friction: 0.1,
+            kill_on_collision: false,
+            groups: Default::default(),
+        }
+    }
+}
@@ -25,7 +25,7 @@ use crate::{
         algebra::Vector3, color::Color, numeric_range::RangeExt, reflect::prelude::*,
         visitor::prelude::*,
     },
-    scene::particle_system::{Particle, ParticleSystemRng},
+    scene::particle_system::{emitter::sub::SubEmitter, Particle, ParticleSystemRng},
 };
 use std::ops::Range;
 
@@ -69,6 +69,9 @@ pub struct BaseEmitter {
     resurrect_particles: bool,
     #[reflect(hidden)]
     pub(crate) spawned_particles: u64,
+    /// A set of sub-emitters, that spawn particles when particles of this emitter die or collide.
+    #[visit(optional)]
+    sub_emitters: Vec<SubEmitter>,
 }
 
 /// Emitter builder allows you to construct emitter in declarative manner.
@@ -86,6 +89,7 @@ pub struct BaseEmitterBuilder {
     rotation_speed: Range<f32>,
     rotation: Range<f32>,
     resurrect_particles: bool,
+    sub_emitters: Vec<SubEmitter>,
 }
 
 impl Default for BaseEmitterBuilder {
@@ -110,6 +114,7 @@ impl BaseEmitterBuilder {
             rotation_speed: -0.02..0.02,
             rotation: -std::f32::consts::PI..std::f32::consts::PI,
             resurrect_particles: true,
+            sub_emitters: Default::default(),
         }
     }
 
@@ -185,6 +190,12 @@ impl BaseEmitterBuilder {
         self
     }
 
+    /// Sets desired sub-emitters. See [`SubEmitter`] docs for more info.
+    pub fn with_sub_emitters(mut self, sub_emitters: Vec<SubEmitter>) -> Self {
+        self.sub_emitters = sub_emitters;
+        self
+    }
+
     /// Creates new instance of emitter.
     pub fn build(self) -> BaseEmitter {
         BaseEmitter {
@@ -204,6 +215,7 @@ impl BaseEmitterBuilder {
             particles_to_spawn: 0,
             resurrect_particles: self.resurrect_particles,
             spawned_particles: 0,
+            sub_emitters: self.sub_emitters,
         }
     }
 }
@@ -397,6 +409,17 @@ impl BaseEmitter {
     pub fn spawned_particles(&self) -> u64 {
         self.spawned_particles
     }
+
+    /// Sets new set of sub-emitters. See [`SubEmitter`] docs for more info.
+    pub fn set_sub_emitters(&mut self, sub_emitters: Vec<SubEmitter>) -> &mut Self {
+        self.sub_emitters = sub_emitters;
+        self
+    }
+
+    /// Returns current set of sub-emitters.
+    pub fn sub_emitters(&self) -> &[SubEmitter] {
+        &self.sub_emitters
+    }
 }
 
 impl Clone for BaseEmitter {
@@ -418,6 +441,7 @@ impl Clone for BaseEmitter {
             particles_to_spawn: 0,
             resurrect_particles: self.resurrect_particles,
             spawned_particles: self.spawned_particles,
+            sub_emitters: self.sub_emitters.clone(),
         }
     }
 }
@@ -441,6 +465,7 @@ impl Default for BaseEmitter {
             particles_to_spawn: 0,
             resurrect_particles: true,
             spawned_particles: 0,
+            sub_emitters: Default::default(),
         }
     }
 }
@@ -39,6 +39,7 @@ pub mod base;
 pub mod cuboid;
 pub mod cylinder;
 pub mod sphere;
+pub mod sub;
 
 /// Emit trait must be implemented for any particle system emitter.
 pub trait Emit {
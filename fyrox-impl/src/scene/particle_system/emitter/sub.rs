@@ -0,0 +1,84 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Sub-emitters spawn particles when particles of their parent emitter die or collide with
+//! something. See [`SubEmitter`] docs for more info.
+
+use crate::core::{reflect::prelude::*, type_traits::prelude::*, visitor::prelude::*};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// An event that triggers a sub-emitter.
+#[derive(
+    Default,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    Debug,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+    TypeUuidProvider,
+)]
+#[type_uuid(id = "1e387b3f-9924-4b5c-b86b-a846eaa07815")]
+pub enum SubEmitterTrigger {
+    /// A particle reached the end of its lifetime (or was killed by a collision).
+    #[default]
+    Death,
+    /// A particle collided with a physics collider. Works only if particle collisions are enabled
+    /// in the particle system.
+    Collision,
+}
+
+/// Sub-emitter spawns a burst of particles at the position of a particle of its parent emitter,
+/// when the particle dies or collides with something. New particles are generated by another
+/// emitter of the same particle system, which is used as a template. Such emitters usually have
+/// zero spawn rate, so they do not spawn particles on their own. It could be used to create
+/// fireworks, sparks on impact, splashes, etc.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect, TypeUuidProvider)]
+#[type_uuid(id = "df319435-e054-4c83-90af-93383c12c73f")]
+pub struct SubEmitter {
+    /// An event that triggers this sub-emitter.
+    pub trigger: SubEmitterTrigger,
+    /// An index of the emitter (in the same particle system) that will be used to generate new
+    /// particles. The position of the particles produced by this emitter is treated as an offset
+    /// from the parent particle.
+    pub emitter: u32,
+    /// Amount of particles spawned per event.
+    #[reflect(min_value = 0.0)]
+    pub count: u32,
+    /// A fraction of the velocity of the parent particle, that will be added to the velocity of
+    /// new particles.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    pub inherit_velocity: f32,
+}
+
+impl Default for SubEmitter {
+    fn default() -> Self {
+        Self {
+            trigger: Default::default(),
+            emitter: 0,
+            count: 10,
+            inherit_velocity: 0.0,
+        }
+    }
+}
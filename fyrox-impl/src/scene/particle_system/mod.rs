@@ -26,10 +26,10 @@ use crate::scene::particle_system::emitter::base::BaseEmitterBuilder;
 use crate::scene::particle_system::emitter::sphere::SphereEmitterBuilder;
 use crate::{
     core::{
-        algebra::{Point3, Vector2, Vector3},
+        algebra::{Matrix4, Point3, Vector2, Vector3},
         color::Color,
         color_gradient::ColorGradient,
-        math::{aabb::AxisAlignedBoundingBox, TriangleDefinition},
+        math::{aabb::AxisAlignedBoundingBox, curve::Curve, TriangleDefinition},
         pool::Handle,
         reflect::prelude::*,
         type_traits::prelude::*,
@@ -43,13 +43,18 @@ use crate::{
     renderer::{self, bundle::RenderContext},
     scene::{
         base::{Base, BaseBuilder},
-        graph::Graph,
+        graph::{
+            physics::{PhysicsWorld, RayCastOptions},
+            Graph,
+        },
         mesh::{buffer::VertexTrait, RenderPath},
         node::{Node, NodeTrait, RdcControlFlow, UpdateContext},
         particle_system::{
+            collision::ParticleCollision,
             draw::Vertex,
-            emitter::{Emit, Emitter},
+            emitter::{sub::SubEmitterTrigger, Emit, Emitter},
             particle::Particle,
+            trail::ParticleTrail,
         },
     },
 };
@@ -62,9 +67,11 @@ use std::{
 };
 use strum_macros::{AsRefStr, EnumString, VariantNames};
 
+pub mod collision;
 pub(crate) mod draw;
 pub mod emitter;
 pub mod particle;
+pub mod trail;
 
 /// Pseudo-random numbers generator for particle systems.
 #[derive(Debug, Clone, Reflect)]
@@ -147,6 +154,19 @@ impl Visit for ParticleSystemRng {
 /// Particle system can contain multiple particle emitters, each emitter has its own
 /// set of properties and it defines law of change of particle parameters over time.
 ///
+/// # Curves
+///
+/// Size and velocity of particles could be changed over their lifetime using curves, color is
+/// defined by a color gradient. See [`ParticleSystem::set_size_over_lifetime_curve`],
+/// [`ParticleSystem::set_velocity_over_lifetime_curve`] and
+/// [`ParticleSystem::set_color_over_lifetime_gradient`].
+///
+/// # Sub-emitters, trails and collisions
+///
+/// Emitters could have sub-emitters that spawn particles when a particle dies or collides with
+/// a physics collider (see [`emitter::sub::SubEmitter`]). Particles could leave trails (see
+/// [`ParticleTrail`]) and collide with physics colliders (see [`ParticleCollision`]).
+///
 /// # Performance
 ///
 /// In general particle system can be considered as heavy visual effect, but total impact
@@ -237,6 +257,26 @@ pub struct ParticleSystem {
     #[reflect(setter = "set_color_over_lifetime_gradient")]
     color_over_lifetime: InheritableVariable<ColorGradient>,
 
+    #[reflect(
+        setter = "set_size_over_lifetime_curve",
+        description = "Size multiplier over the lifetime of a particle. Empty curve means \
+        constant size."
+    )]
+    size_over_lifetime: InheritableVariable<Curve>,
+
+    #[reflect(
+        setter = "set_velocity_over_lifetime_curve",
+        description = "Velocity multiplier over the lifetime of a particle. Empty curve means \
+        constant velocity."
+    )]
+    velocity_over_lifetime: InheritableVariable<Curve>,
+
+    #[reflect(setter = "set_trail")]
+    trail: InheritableVariable<ParticleTrail>,
+
+    #[reflect(setter = "set_collision")]
+    collision: InheritableVariable<ParticleCollision>,
+
     #[reflect(setter = "play")]
     is_playing: InheritableVariable<bool>,
 
@@ -316,6 +356,14 @@ impl Visit for ParticleSystem {
         let _ = self
             .coordinate_system
             .visit("CoordinateSystem", &mut region);
+        let _ = self
+            .size_over_lifetime
+            .visit("SizeOverLifetime", &mut region);
+        let _ = self
+            .velocity_over_lifetime
+            .visit("VelocityOverLifetime", &mut region);
+        let _ = self.trail.visit("Trail", &mut region);
+        let _ = self.collision.visit("Collision", &mut region);
 
         // Backward compatibility.
         if region.is_reading() {
@@ -386,6 +434,54 @@ impl ParticleSystem {
             .set_value_and_mark_modified(gradient)
     }
 
+    /// Sets new size multiplier curve, that will be evaluated over the lifetime of every particle.
+    /// Empty curve means constant size.
+    pub fn set_size_over_lifetime_curve(&mut self, curve: Curve) -> Curve {
+        self.size_over_lifetime.set_value_and_mark_modified(curve)
+    }
+
+    /// Returns current size multiplier curve.
+    pub fn size_over_lifetime_curve(&self) -> &Curve {
+        &self.size_over_lifetime
+    }
+
+    /// Sets new velocity multiplier curve, that will be evaluated over the lifetime of every
+    /// particle. Empty curve means constant velocity.
+    pub fn set_velocity_over_lifetime_curve(&mut self, curve: Curve) -> Curve {
+        self.velocity_over_lifetime
+            .set_value_and_mark_modified(curve)
+    }
+
+    /// Returns current velocity multiplier curve.
+    pub fn velocity_over_lifetime_curve(&self) -> &Curve {
+        &self.velocity_over_lifetime
+    }
+
+    /// Sets new trail settings. See [`ParticleTrail`] docs for more info.
+    pub fn set_trail(&mut self, trail: ParticleTrail) -> ParticleTrail {
+        if !trail.enabled {
+            for particle in self.particles.iter_mut() {
+                particle.trail.clear();
+            }
+        }
+        self.trail.set_value_and_mark_modified(trail)
+    }
+
+    /// Returns current trail settings.
+    pub fn trail(&self) -> &ParticleTrail {
+        &self.trail
+    }
+
+    /// Sets new collision settings. See [`ParticleCollision`] docs for more info.
+    pub fn set_collision(&mut self, collision: ParticleCollision) -> ParticleCollision {
+        self.collision.set_value_and_mark_modified(collision)
+    }
+
+    /// Returns current collision settings.
+    pub fn collision(&self) -> &ParticleCollision {
+        &self.collision
+    }
+
     /// Plays or pauses the particle system. Paused particle system remains in "frozen" state
     /// until played again again. You can manually reset state of the system by calling [`Self::clear_particles`].
     pub fn play(&mut self, is_playing: bool) -> bool {
@@ -434,12 +530,17 @@ impl ParticleSystem {
         &self.material
     }
 
-    fn tick(&mut self, dt: f32) {
+    // Velocity of particles is defined in units per tick, where tick is a "nominal" time step of the
+    // simulation (usually a frame). `dt` could be smaller than the nominal step when time is
+    // scaled down or when the step is split into sub-steps, in this case the displacement of
+    // particles is scaled accordingly.
+    fn tick(&mut self, dt: f32, nominal_dt: f32, physics: Option<&PhysicsWorld>) {
         for emitter in self.emitters.get_value_mut_silent().iter_mut() {
             emitter.tick(dt);
         }
 
         let global_transform = self.global_transform();
+        let is_world_space = *self.coordinate_system == CoordinateSystem::World;
 
         for (i, emitter) in self.emitters.get_value_mut_silent().iter_mut().enumerate() {
             for _ in 0..emitter.particles_to_spawn {
@@ -449,25 +550,106 @@ impl ParticleSystem {
                 };
                 emitter.alive_particles += 1;
                 emitter.emit(&mut particle, &mut self.rng);
-                if *self.coordinate_system == CoordinateSystem::World {
+                if is_world_space {
                     particle.position = global_transform
                         .transform_point(&particle.position.into())
                         .coords;
                 }
-                if let Some(free_index) = self.free_particles.pop() {
-                    self.particles[free_index as usize] = particle;
-                } else {
-                    self.particles.push(particle);
-                }
+                add_particle(&mut self.particles, &mut self.free_particles, particle);
             }
         }
 
-        let acceleration_offset = self.acceleration.scale(dt * dt);
+        let acceleration_offset = self.acceleration.scale(dt * nominal_dt);
+        let displacement_scale = dt / nominal_dt;
+        let inv_global_transform = global_transform
+            .try_inverse()
+            .unwrap_or_else(Matrix4::identity);
+        let collision = &*self.collision;
+        let trail = &*self.trail;
+        let mut intersections = Vec::new();
+        let mut events = Vec::new();
 
         for (i, particle) in self.particles.iter_mut().enumerate() {
             if particle.alive {
                 particle.lifetime += dt;
-                if particle.lifetime >= particle.initial_lifetime {
+                let mut is_dead = particle.lifetime >= particle.initial_lifetime;
+
+                if !is_dead {
+                    particle.velocity += acceleration_offset;
+
+                    let k = particle.normalized_lifetime();
+                    let displacement = particle.velocity
+                        * curve_factor(&self.velocity_over_lifetime, k)
+                        * displacement_scale;
+
+                    let hit = physics.filter(|_| collision.enabled).and_then(|physics| {
+                        let (origin, direction) = if is_world_space {
+                            (Point3::from(particle.position), displacement)
+                        } else {
+                            (
+                                global_transform.transform_point(&Point3::from(particle.position)),
+                                global_transform.transform_vector(&displacement),
+                            )
+                        };
+
+                        let max_len = direction.norm();
+                        if max_len <= f32::EPSILON {
+                            return None;
+                        }
+
+                        physics.cast_ray(
+                            RayCastOptions {
+                                ray_origin: origin,
+                                ray_direction: direction,
+                                max_len,
+                                groups: collision.groups,
+                                sort_results: true,
+                            },
+                            &mut intersections,
+                        );
+
+                        intersections.first().map(|hit| {
+                            if is_world_space {
+                                (hit.position.coords, hit.normal)
+                            } else {
+                                (
+                                    inv_global_transform.transform_point(&hit.position).coords,
+                                    inv_global_transform
+                                        .transform_vector(&hit.normal)
+                                        .try_normalize(f32::EPSILON)
+                                        .unwrap_or(hit.normal),
+                                )
+                            }
+                        })
+                    });
+
+                    if let Some((position, normal)) = hit {
+                        // Keep the particle slightly above the surface to prevent it from falling
+                        // through on the next update.
+                        particle.position = position + normal.scale(0.001);
+                        let normal_velocity = particle.velocity.dot(&normal);
+                        if normal_velocity < 0.0 {
+                            particle.velocity -=
+                                normal.scale((1.0 + collision.restitution) * normal_velocity);
+                        }
+                        particle.velocity = particle.velocity.scale(1.0 - collision.friction);
+
+                        if has_sub_emitters(
+                            &self.emitters,
+                            particle.emitter_index,
+                            SubEmitterTrigger::Collision,
+                        ) {
+                            events
+                                .push(SubEmitterEvent::new(particle, SubEmitterTrigger::Collision));
+                        }
+
+                        is_dead = collision.kill_on_collision;
+                    } else {
+                        particle.position += displacement;
+                    }
+                }
+
+                if is_dead {
                     self.free_particles.push(i as u32);
                     if let Some(emitter) = self
                         .emitters
@@ -478,17 +660,84 @@ impl ParticleSystem {
                     }
                     particle.alive = false;
                     particle.lifetime = particle.initial_lifetime;
+                    particle.trail.clear();
+
+                    if has_sub_emitters(
+                        &self.emitters,
+                        particle.emitter_index,
+                        SubEmitterTrigger::Death,
+                    ) {
+                        events.push(SubEmitterEvent::new(particle, SubEmitterTrigger::Death));
+                    }
                 } else {
-                    particle.velocity += acceleration_offset;
-                    particle.position += particle.velocity;
                     particle.size += particle.size_modifier * dt;
                     if particle.size < 0.0 {
                         particle.size = 0.0;
                     }
                     particle.rotation += particle.rotation_speed * dt;
 
-                    let k = particle.lifetime / particle.initial_lifetime;
+                    let k = particle.normalized_lifetime();
                     particle.color = self.color_over_lifetime.get_color(k);
+
+                    if trail.enabled
+                        && particle.trail.front().map_or(true, |last| {
+                            last.metric_distance(&particle.position) >= trail.min_vertex_distance
+                        })
+                    {
+                        particle.trail.push_front(particle.position);
+                        particle.trail.truncate(trail.max_points as usize);
+                    }
+                }
+            }
+        }
+
+        for event in events {
+            let sub_emitters = self
+                .emitters
+                .get(event.emitter_index as usize)
+                .map(|source| {
+                    source
+                        .sub_emitters()
+                        .iter()
+                        .filter(|sub_emitter| sub_emitter.trigger == event.trigger)
+                        .cloned()
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+
+            for sub_emitter in sub_emitters {
+                let Some(template) = self
+                    .emitters
+                    .get_value_mut_silent()
+                    .get_mut(sub_emitter.emitter as usize)
+                else {
+                    continue;
+                };
+
+                for _ in 0..sub_emitter.count {
+                    if template
+                        .max_particles()
+                        .is_some_and(|max_particles| template.alive_particles >= max_particles)
+                    {
+                        break;
+                    }
+
+                    let mut particle = Particle {
+                        emitter_index: sub_emitter.emitter,
+                        ..Particle::default()
+                    };
+                    template.alive_particles += 1;
+                    template.emit(&mut particle, &mut self.rng);
+
+                    let offset = if is_world_space {
+                        global_transform.transform_vector(&particle.position)
+                    } else {
+                        particle.position
+                    };
+                    particle.position = event.position + offset;
+                    particle.velocity += event.velocity.scale(sub_emitter.inherit_velocity);
+
+                    add_particle(&mut self.particles, &mut self.free_particles, particle);
                 }
             }
         }
@@ -503,7 +752,7 @@ impl ParticleSystem {
 
         let mut t = 0.0;
         while t < time {
-            self.tick(dt);
+            self.tick(dt, dt, None);
             t += dt;
         }
     }
@@ -540,6 +789,50 @@ impl ParticleSystem {
     }
 }
 
+/// Evaluates the given curve at the given position, empty curve is treated as a constant `1.0`.
+pub(crate) fn curve_factor(curve: &Curve, t: f32) -> f32 {
+    if curve.is_empty() {
+        1.0
+    } else {
+        curve.value_at(t)
+    }
+}
+
+fn add_particle(particles: &mut Vec<Particle>, free_particles: &mut Vec<u32>, particle: Particle) {
+    if let Some(free_index) = free_particles.pop() {
+        particles[free_index as usize] = particle;
+    } else {
+        particles.push(particle);
+    }
+}
+
+fn has_sub_emitters(emitters: &[Emitter], emitter_index: u32, trigger: SubEmitterTrigger) -> bool {
+    emitters.get(emitter_index as usize).is_some_and(|emitter| {
+        emitter
+            .sub_emitters()
+            .iter()
+            .any(|sub_emitter| sub_emitter.trigger == trigger)
+    })
+}
+
+struct SubEmitterEvent {
+    emitter_index: u32,
+    trigger: SubEmitterTrigger,
+    position: Vector3<f32>,
+    velocity: Vector3<f32>,
+}
+
+impl SubEmitterEvent {
+    fn new(particle: &Particle, trigger: SubEmitterTrigger) -> Self {
+        Self {
+            emitter_index: particle.emitter_index,
+            trigger,
+            position: particle.position,
+            velocity: particle.velocity,
+        }
+    }
+}
+
 impl Default for ParticleSystem {
     fn default() -> Self {
         ParticleSystemBuilder::new(BaseBuilder::new()).build_particle_system()
@@ -580,8 +873,25 @@ impl NodeTrait for ParticleSystem {
     fn update(&mut self, context: &mut UpdateContext) {
         let dt = context.dt;
 
-        if *self.is_playing {
-            self.tick(dt);
+        if *self.is_playing && dt > 0.0 && context.time_scale > 0.0 {
+            // Real (unscaled) time step defines the nominal step of the simulation, so slowed
+            // down time produces proportionally slower particles.
+            let nominal_dt = dt / context.time_scale;
+            context.sub_stepping.for_each_substep(dt, |step, _| {
+                self.tick(step, nominal_dt, Some(&*context.physics));
+            });
+        }
+    }
+
+    fn on_origin_shifted(&mut self, offset: Vector3<f32>) {
+        // Particles in local coordinates are moved together with the particle system.
+        if *self.coordinate_system == CoordinateSystem::World {
+            for particle in self.particles.iter_mut() {
+                particle.position -= offset;
+                for point in particle.trail.iter_mut() {
+                    *point -= offset;
+                }
+            }
         }
     }
 
@@ -636,6 +946,7 @@ impl NodeTrait for ParticleSystem {
 
         let global_transform = self.global_transform();
         let sort_index = ctx.calculate_sorting_index(self.global_position());
+        let observer_position = ctx.observer_info.observer_position;
 
         ctx.storage.push_triangles(
             Vertex::layout(),
@@ -663,32 +974,35 @@ impl NodeTrait for ParticleSystem {
                         alpha,
                     );
 
+                    let size = particle.size
+                        * curve_factor(&self.size_over_lifetime, particle.normalized_lifetime());
+
                     [
                         Vertex {
                             position,
                             tex_coord: Vector2::default(),
-                            size: particle.size,
+                            size,
                             rotation: particle.rotation,
                             color,
                         },
                         Vertex {
                             position,
                             tex_coord: Vector2::new(1.0, 0.0),
-                            size: particle.size,
+                            size,
                             rotation: particle.rotation,
                             color,
                         },
                         Vertex {
                             position,
                             tex_coord: Vector2::new(1.0, 1.0),
-                            size: particle.size,
+                            size,
                             rotation: particle.rotation,
                             color,
                         },
                         Vertex {
                             position,
                             tex_coord: Vector2::new(0.0, 1.0),
-                            size: particle.size,
+                            size,
                             rotation: particle.rotation,
                             color,
                         },
@@ -712,7 +1026,60 @@ impl NodeTrait for ParticleSystem {
                         .unwrap();
                 }
 
-                triangle_buffer.push_triangles_iter_with_offset(start_vertex_index, triangles)
+                triangle_buffer.push_triangles_iter_with_offset(start_vertex_index, triangles);
+
+                if self.trail.enabled {
+                    let mut trail_vertices = Vec::new();
+                    let mut trail_triangles = Vec::new();
+                    let mut points = Vec::new();
+
+                    for particle_index in sorted_particles.iter() {
+                        let particle = self.particles.get(*particle_index as usize).unwrap();
+
+                        points.clear();
+                        points.extend(
+                            std::iter::once(&particle.position)
+                                .chain(particle.trail.iter())
+                                .map(|point| {
+                                    if *self.coordinate_system == CoordinateSystem::Local {
+                                        global_transform
+                                            .transform_point(&Point3::from(*point))
+                                            .coords
+                                    } else {
+                                        *point
+                                    }
+                                }),
+                        );
+
+                        let color = Color::from_rgba(
+                            particle.color.r,
+                            particle.color.g,
+                            particle.color.b,
+                            (particle.color.a as f32 * particle_alpha_factor) as u8,
+                        );
+
+                        self.trail.generate(
+                            &points,
+                            color,
+                            observer_position,
+                            &mut trail_vertices,
+                            &mut trail_triangles,
+                        );
+                    }
+
+                    let start_vertex_index = vertex_buffer.vertex_count();
+
+                    for vertex in trail_vertices.iter() {
+                        vertex_buffer
+                            .push_vertex_raw(value_as_u8_slice(vertex))
+                            .unwrap();
+                    }
+
+                    triangle_buffer.push_triangles_iter_with_offset(
+                        start_vertex_index,
+                        trail_triangles.into_iter(),
+                    );
+                }
             },
         );
 
@@ -733,6 +1100,10 @@ pub struct ParticleSystemBuilder {
     rng: ParticleSystemRng,
     visible_distance: f32,
     coordinate_system: CoordinateSystem,
+    size_over_lifetime: Curve,
+    velocity_over_lifetime: Curve,
+    trail: ParticleTrail,
+    collision: ParticleCollision,
 }
 
 impl ParticleSystemBuilder {
@@ -752,6 +1123,10 @@ impl ParticleSystemBuilder {
             rng: ParticleSystemRng::default(),
             visible_distance: 30.0,
             coordinate_system: Default::default(),
+            size_over_lifetime: Default::default(),
+            velocity_over_lifetime: Default::default(),
+            trail: Default::default(),
+            collision: Default::default(),
         }
     }
 
@@ -811,6 +1186,30 @@ impl ParticleSystemBuilder {
         self
     }
 
+    /// Sets size multiplier curve over lifetime for particle system.
+    pub fn with_size_over_lifetime_curve(mut self, curve: Curve) -> Self {
+        self.size_over_lifetime = curve;
+        self
+    }
+
+    /// Sets velocity multiplier curve over lifetime for particle system.
+    pub fn with_velocity_over_lifetime_curve(mut self, curve: Curve) -> Self {
+        self.velocity_over_lifetime = curve;
+        self
+    }
+
+    /// Sets the desired trail settings for particles.
+    pub fn with_trail(mut self, trail: ParticleTrail) -> Self {
+        self.trail = trail;
+        self
+    }
+
+    /// Sets the desired collision settings for particles.
+    pub fn with_collision(mut self, collision: ParticleCollision) -> Self {
+        self.collision = collision;
+        self
+    }
+
     fn build_particle_system(self) -> ParticleSystem {
         ParticleSystem {
             base: self.base_builder.build_base(),
@@ -824,6 +1223,10 @@ impl ParticleSystemBuilder {
             rng: self.rng,
             visible_distance: self.visible_distance.into(),
             coordinate_system: self.coordinate_system.into(),
+            size_over_lifetime: self.size_over_lifetime.into(),
+            velocity_over_lifetime: self.velocity_over_lifetime.into(),
+            trail: self.trail.into(),
+            collision: self.collision.into(),
         }
     }
 
@@ -837,3 +1240,55 @@ impl ParticleSystemBuilder {
         graph.add_node(self.build_node())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::scene::{
+        base::BaseBuilder,
+        particle_system::{
+            emitter::{
+                base::BaseEmitterBuilder,
+                sphere::SphereEmitterBuilder,
+                sub::{SubEmitter, SubEmitterTrigger},
+            },
+            ParticleSystemBuilder,
+        },
+    };
+
+    #[test]
+    fn test_death_sub_emitter() {
+        let mut particle_system = ParticleSystemBuilder::new(BaseBuilder::new())
+            .with_emitters(vec![
+                SphereEmitterBuilder::new(
+                    BaseEmitterBuilder::new()
+                        .with_spawn_rate(10)
+                        .with_max_particles(1)
+                        .with_lifetime_range(0.5..0.6)
+                        .with_sub_emitters(vec![SubEmitter {
+                            trigger: SubEmitterTrigger::Death,
+                            emitter: 1,
+                            count: 5,
+                            inherit_velocity: 0.0,
+                        }]),
+                )
+                .build(),
+                SphereEmitterBuilder::new(
+                    BaseEmitterBuilder::new()
+                        .with_spawn_rate(0)
+                        .with_lifetime_range(10.0..11.0),
+                )
+                .build(),
+            ])
+            .build_particle_system();
+
+        particle_system.rewind(1.0 / 60.0, 1.0);
+
+        let alive = particle_system
+            .particles()
+            .iter()
+            .filter(|particle| particle.alive)
+            .count();
+        assert_eq!(alive, 5);
+        assert_eq!(particle_system.emitters[1].alive_particles, 5);
+    }
+}
@@ -0,0 +1,129 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Trails (ribbons) are camera-facing strips, that follow particles and could be used to create
+//! effects like sparks, magic missiles, etc. See [`ParticleTrail`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Vector2, Vector3},
+        color::Color,
+        math::{curve::Curve, TriangleDefinition},
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        visitor::prelude::*,
+    },
+    scene::particle_system::{curve_factor, draw::Vertex},
+};
+
+/// Defines how trails of particles look like. Every particle of a particle system with enabled
+/// trails leaves a trail of its previous positions, which is rendered as a strip facing the
+/// camera. The trail is fading out towards its tail.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect, TypeUuidProvider)]
+#[type_uuid(id = "2fd33212-3a65-474c-839e-209ed208d5d0")]
+pub struct ParticleTrail {
+    /// Whether the trails are enabled or not.
+    pub enabled: bool,
+    /// The maximum amount of points in a trail. The larger the value, the longer the trail.
+    #[reflect(min_value = 2.0)]
+    pub max_points: u32,
+    /// The minimum distance between two adjacent points of a trail. A new point is added to the
+    /// trail only when a particle travels at least this distance.
+    #[reflect(min_value = 0.0, step = 0.01)]
+    pub min_vertex_distance: f32,
+    /// Width of the trail.
+    #[reflect(min_value = 0.0, step = 0.01)]
+    pub width: f32,
+    /// Width multiplier along the trail, where 0.0 is the head of the trail and 1.0 is its tail.
+    /// Empty curve means constant width.
+    pub width_over_trail: Curve,
+}
+
+impl Default for ParticleTrail {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_points: 16,
+            min_vertex_distance: 0.05,
+            width: 0.1,
+            width_over_trail: Default::default(),
+        }
+    }
+}
+
+impl ParticleTrail {
+    /// Generates a camera-facing strip using the given set of points (in world space, starting
+    /// from the head of the trail).
+    pub(super) fn generate(
+        &self,
+        points: &[Vector3<f32>],
+        color: Color,
+        observer_position: Vector3<f32>,
+        vertices: &mut Vec<Vertex>,
+        triangles: &mut Vec<TriangleDefinition>,
+    ) {
+        let count = points.len();
+        if count < 2 {
+            return;
+        }
+
+        let base_index = vertices.len() as u32;
+
+        for (i, point) in points.iter().enumerate() {
+            let prev = points[i.saturating_sub(1)];
+            let next = points[(i + 1).min(count - 1)];
+            let t = i as f32 / (count - 1) as f32;
+
+            let half_width = 0.5 * self.width * curve_factor(&self.width_over_trail, t);
+            let side = (next - prev)
+                .cross(&(observer_position - point))
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_default()
+                .scale(half_width);
+
+            let color = Color::from_rgba(
+                color.r,
+                color.g,
+                color.b,
+                (color.a as f32 * (1.0 - t)) as u8,
+            );
+
+            vertices.push(Vertex {
+                position: point - side,
+                tex_coord: Vector2::new(t, 0.0),
+                size: 0.0,
+                rotation: 0.0,
+                color,
+            });
+            vertices.push(Vertex {
+                position: point + side,
+                tex_coord: Vector2::new(t, 1.0),
+                size: 0.0,
+                rotation: 0.0,
+                color,
+            });
+        }
+
+        for i in 0..(count - 1) as u32 {
+            let a = base_index + i * 2;
+            triangles.push(TriangleDefinition([a, a + 1, a + 3]));
+            triangles.push(TriangleDefinition([a, a + 3, a + 2]));
+        }
+    }
+}
@@ -0,0 +1,403 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Sockets are named attachment points on bones (or any other scene nodes). See [`Socket`] and
+//! [`SocketAttachments`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, UnitQuaternion, Vector3},
+        math::{Matrix3Ext, Matrix4Ext},
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        visitor::prelude::*,
+    },
+    graph::{BaseSceneGraph, NodeHandleMap, SceneGraph},
+    scene::{graph::Graph, node::Node},
+};
+use std::fmt::{Display, Formatter};
+
+/// A named attachment point of a scene node (usually a bone of a skeleton), for example `hand_r_weapon`.
+/// The socket is defined by an offset relative to its node, so attachments could be positioned
+/// precisely without adding helper nodes to the skeleton. Sockets are usually defined in the
+/// editor, in the `Sockets` property of a node. See [`SocketAttachments`] docs for more info.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect, TypeUuidProvider)]
+#[type_uuid(id = "1d4d1c9a-e8e3-4b8b-8e0a-5c7a8b5f1f0e")]
+pub struct Socket {
+    /// Name of the socket. It must be unique within a skeleton.
+    pub name: String,
+    /// Position of the socket in the local space of its node.
+    pub position: Vector3<f32>,
+    /// Rotation of the socket in the local space of its node.
+    pub rotation: UnitQuaternion<f32>,
+}
+
+impl Socket {
+    /// Creates a new socket with the given name and no offset.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Sets the desired position of the socket.
+    pub fn with_position(mut self, position: Vector3<f32>) -> Self {
+        self.position = position;
+        self
+    }
+
+    /// Sets the desired rotation of the socket.
+    pub fn with_rotation(mut self, rotation: UnitQuaternion<f32>) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Returns a transformation matrix of the socket in the local space of its node.
+    pub fn local_matrix(&self) -> Matrix4<f32> {
+        Matrix4::new_translation(&self.position) * self.rotation.to_homogeneous()
+    }
+}
+
+/// An error, that may occur when attaching a node to a socket.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SocketError {
+    /// There's no socket with the given name in the hierarchy.
+    NoSuchSocket(String),
+    /// The node that is being attached is invalid.
+    InvalidNode(Handle<Node>),
+}
+
+impl Display for SocketError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SocketError::NoSuchSocket(name) => write!(f, "There's no {name} socket!"),
+            SocketError::InvalidNode(handle) => write!(f, "{handle} node is invalid!"),
+        }
+    }
+}
+
+impl std::error::Error for SocketError {}
+
+/// Searches for a socket with the given name in the hierarchy starting from the `root` node.
+/// Returns a handle of the node that has the socket and the socket itself.
+pub fn find_socket<'a>(
+    graph: &'a Graph,
+    root: Handle<Node>,
+    name: &str,
+) -> Option<(Handle<Node>, &'a Socket)> {
+    graph
+        .find(root, &mut |node| node.socket(name).is_some())
+        .and_then(|(handle, node)| Some((handle, node.socket(name)?)))
+}
+
+/// A node attached to a socket.
+#[derive(Clone, Debug, Default, PartialEq, Visit)]
+pub struct SocketAttachment {
+    /// A handle of the attached node.
+    pub node: Handle<Node>,
+    /// A handle of the node that owns the socket.
+    pub socket_owner: Handle<Node>,
+    /// Name of the socket.
+    pub socket: String,
+    /// A root of the hierarchy (usually a character), that contains the socket.
+    pub root: Handle<Node>,
+}
+
+/// A set of nodes attached to sockets. Attached nodes are moved and rotated every frame, so they
+/// match the sockets exactly. Attachments are synced after animations and pose modifiers (see
+/// [`crate::scene::animation::snapshot::PoseModifiers`]) of the scene, so they follow the final
+/// pose of a skeleton. Global transforms of the hierarchy that contains a socket are recalculated
+/// before the sync, so attachments are not one frame late.
+///
+/// Attached nodes are not re-parented, which means that they could be a part of any hierarchy (for
+/// example a weapon with a rigid body) and could be detached at any time without any changes to
+/// the graph. Only local position and rotation of attached nodes are changed, their scale is
+/// preserved (scale of the socket owner and its ancestors is not applied to attachments).
+///
+/// Attachments are saved with the scene and are copied when the scene is cloned.
+///
+/// ```rust
+/// # use fyrox_impl::{core::pool::Handle, scene::{node::Node, Scene}};
+/// fn equip_weapon(scene: &mut Scene, character: Handle<Node>, weapon: Handle<Node>) {
+///     if let Err(err) =
+///         scene
+///             .sockets
+///             .attach_to_socket(&scene.graph, character, weapon, "hand_r_weapon")
+///     {
+///         println!("Unable to equip the weapon: {err}");
+///     }
+/// }
+/// ```
+#[derive(Default, Debug, Clone, Visit)]
+pub struct SocketAttachments {
+    attachments: Vec<SocketAttachment>,
+}
+
+impl SocketAttachments {
+    /// Attaches the given node to a socket with the given name. The socket is searched in the
+    /// hierarchy starting from the `root` node, `Handle::NONE` means that the entire graph will be
+    /// searched. If the node was already attached to some socket, it is re-attached.
+    pub fn attach_to_socket(
+        &mut self,
+        graph: &Graph,
+        root: Handle<Node>,
+        node: Handle<Node>,
+        socket: &str,
+    ) -> Result<(), SocketError> {
+        if !graph.is_valid_handle(node) {
+            return Err(SocketError::InvalidNode(node));
+        }
+
+        let root = if root.is_some() {
+            root
+        } else {
+            graph.get_root()
+        };
+
+        let (socket_owner, _) = find_socket(graph, root, socket)
+            .ok_or_else(|| SocketError::NoSuchSocket(socket.to_string()))?;
+
+        self.detach(node);
+        self.attachments.push(SocketAttachment {
+            node,
+            socket_owner,
+            socket: socket.to_string(),
+            root,
+        });
+
+        Ok(())
+    }
+
+    /// Detaches the given node from its socket. Returns `true` if the node was attached.
+    pub fn detach(&mut self, node: Handle<Node>) -> bool {
+        let count = self.attachments.len();
+        self.attachments.retain(|a| a.node != node);
+        self.attachments.len() != count
+    }
+
+    /// Returns an attachment of the given node, if any.
+    pub fn attachment(&self, node: Handle<Node>) -> Option<&SocketAttachment> {
+        self.attachments.iter().find(|a| a.node == node)
+    }
+
+    /// Returns an iterator over every attachment.
+    pub fn iter(&self) -> impl Iterator<Item = &SocketAttachment> {
+        self.attachments.iter()
+    }
+
+    /// Returns `true` if there's no attachments.
+    pub fn is_empty(&self) -> bool {
+        self.attachments.is_empty()
+    }
+
+    /// Returns total amount of attachments.
+    pub fn len(&self) -> usize {
+        self.attachments.len()
+    }
+
+    /// Creates a copy of the attachments for a copy of the graph, attachments of the nodes that
+    /// were not copied are dropped.
+    pub(crate) fn remap(&self, old_new_map: &NodeHandleMap<Node>) -> Self {
+        Self {
+            attachments: self
+                .attachments
+                .iter()
+                .filter_map(|attachment| {
+                    let mut attachment = attachment.clone();
+                    (old_new_map.try_map(&mut attachment.node)
+                        && old_new_map.try_map(&mut attachment.socket_owner)
+                        && old_new_map.try_map(&mut attachment.root))
+                    .then_some(attachment)
+                })
+                .collect(),
+        }
+    }
+
+    pub(crate) fn sync(&mut self, graph: &mut Graph) {
+        self.attachments.retain(|a| {
+            graph.is_valid_handle(a.node)
+                && graph.is_valid_handle(a.socket_owner)
+                && graph.is_valid_handle(a.root)
+        });
+
+        let mut updated_roots = Vec::new();
+        for attachment in self.attachments.iter() {
+            if !updated_roots.contains(&attachment.root) {
+                graph.update_hierarchical_data_for_descendants(attachment.root);
+                updated_roots.push(attachment.root);
+            }
+
+            let owner = &graph[attachment.socket_owner];
+            let Some(socket) = owner.socket(&attachment.socket) else {
+                continue;
+            };
+            let socket_transform = owner.global_transform() * socket.local_matrix();
+
+            let parent_transform = graph
+                .try_get(graph[attachment.node].parent())
+                .map_or(Matrix4::identity(), |parent| parent.global_transform());
+            let Some(inv_parent_transform) = parent_transform.try_inverse() else {
+                continue;
+            };
+            let local_transform = inv_parent_transform * socket_transform;
+
+            // The basis could contain scale (and even shear) of the ancestors, build pure rotation
+            // from its axes.
+            let basis = local_transform.basis();
+            let (look, up) = (basis.look(), basis.up());
+            if look.norm_squared() <= f32::EPSILON || up.norm_squared() <= f32::EPSILON {
+                continue;
+            }
+
+            graph[attachment.node]
+                .local_transform_mut()
+                .set_position(local_transform.position())
+                .set_rotation(UnitQuaternion::face_towards(&look, &up));
+            graph.update_hierarchical_data_for_descendants(attachment.node);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{UnitQuaternion, Vector3},
+        graph::BaseSceneGraph,
+        scene::{
+            base::BaseBuilder,
+            graph::Graph,
+            pivot::PivotBuilder,
+            socket::{Socket, SocketAttachments, SocketError},
+            transform::TransformBuilder,
+        },
+    };
+
+    #[test]
+    fn test_socket_attachment() {
+        let mut graph = Graph::new();
+        let hand = PivotBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, 1.0, 0.0))
+                        .with_local_rotation(UnitQuaternion::from_axis_angle(
+                            &Vector3::y_axis(),
+                            std::f32::consts::FRAC_PI_2,
+                        ))
+                        .build(),
+                )
+                .with_sockets(vec![
+                    Socket::new("hand_r_weapon").with_position(Vector3::new(1.0, 0.0, 0.0))
+                ]),
+        )
+        .build(&mut graph);
+        let character =
+            PivotBuilder::new(BaseBuilder::new().with_children(&[hand])).build(&mut graph);
+        let weapon = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+
+        let mut sockets = SocketAttachments::default();
+        assert_eq!(
+            sockets.attach_to_socket(&graph, character, weapon, "foo"),
+            Err(SocketError::NoSuchSocket("foo".to_string()))
+        );
+        assert!(sockets
+            .attach_to_socket(&graph, character, weapon, "hand_r_weapon")
+            .is_ok());
+
+        // Move the character without updating the hierarchy, the sync must take care of it.
+        graph[character]
+            .local_transform_mut()
+            .set_position(Vector3::new(10.0, 0.0, 0.0));
+        sockets.sync(&mut graph);
+
+        // The socket offset is rotated by the hand.
+        let position = graph[weapon].global_position();
+        assert!((position - Vector3::new(10.0, 1.0, -1.0)).norm() < 1.0e-5);
+
+        assert!(sockets.detach(weapon));
+        assert!(sockets.is_empty());
+    }
+
+    #[test]
+    fn test_socket_attachment_ignores_scale() {
+        let mut graph = Graph::new();
+        let rotation = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 0.7);
+        let hand = PivotBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_rotation(rotation)
+                        .with_local_scale(Vector3::new(2.0, 3.0, 4.0))
+                        .build(),
+                )
+                .with_sockets(vec![Socket::new("hand_r_weapon")]),
+        )
+        .build(&mut graph);
+        let weapon = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+
+        let mut sockets = SocketAttachments::default();
+        sockets
+            .attach_to_socket(&graph, hand, weapon, "hand_r_weapon")
+            .unwrap();
+        sockets.sync(&mut graph);
+
+        let transform = graph[weapon].local_transform();
+        assert!(transform.rotation().angle_to(&rotation) < 1.0e-5);
+        assert_eq!(**transform.scale(), Vector3::repeat(1.0));
+    }
+
+    #[test]
+    fn test_socket_attachments_remap() {
+        let mut graph = Graph::new();
+        let hand =
+            PivotBuilder::new(BaseBuilder::new().with_sockets(vec![Socket::new("hand_r_weapon")]))
+                .build(&mut graph);
+        let weapon = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+
+        let mut sockets = SocketAttachments::default();
+        sockets
+            .attach_to_socket(&graph, hand, weapon, "hand_r_weapon")
+            .unwrap();
+
+        let (copy, old_new_map) = graph.clone(
+            graph.get_root(),
+            &mut |_, _| true,
+            &mut |_, _| {},
+            &mut |_, _, _| {},
+        );
+        let copy_sockets = sockets.remap(&old_new_map);
+        let attachment = copy_sockets.iter().next().unwrap();
+        let (mut weapon_copy, mut hand_copy) = (weapon, hand);
+        old_new_map.map(&mut weapon_copy).map(&mut hand_copy);
+        assert_eq!(attachment.node, weapon_copy);
+        assert_eq!(attachment.socket_owner, hand_copy);
+        assert!(copy.is_valid_handle(attachment.node));
+
+        // Attachments of the nodes, that were not copied, are dropped.
+        let (_, old_new_map) = graph.clone(
+            graph.get_root(),
+            &mut |handle, _| handle != weapon,
+            &mut |_, _| {},
+            &mut |_, _, _| {},
+        );
+        assert!(sockets.remap(&old_new_map).is_empty());
+    }
+}
@@ -0,0 +1,456 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Cloth is a thin deformable surface, such as cape, flag or curtain. See [`Cloth`] docs for more
+//! info.
+
+use crate::{
+    core::{
+        algebra::Vector3,
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        uuid::{uuid, Uuid},
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    scene::{
+        base::{Base, BaseBuilder},
+        collider::InteractionGroups,
+        graph::Graph,
+        node::{constructor::NodeConstructor, Node, NodeTrait, UpdateContext},
+        soft_body::{
+            simulate,
+            solver::{CollisionParams, SimulationParams, XpbdBody},
+            validate_mesh, Pin,
+        },
+        Scene,
+    },
+};
+use fyrox_graph::constructor::ConstructorProvider;
+use std::ops::{Deref, DerefMut};
+
+/// Cloth simulates a mesh as a thin sheet of fabric. Vertices of the mesh are simulated as
+/// particles connected along the edges of triangles, additional bending constraints between
+/// adjacent triangles define how easily the cloth folds. Vertices with the same position are
+/// welded, so the seams of the mesh stay closed.
+///
+/// Usually a cloth is attached to something using [`Pin`]s: a flag is pinned to its pole, a cape
+/// is pinned to the bones of a character, a curtain is pinned to its rail. The simulated mesh is
+/// set by [`Self::mesh`] field (the parent node is used, if it is not set) and must not be skinned,
+/// its vertex buffers are modified every frame. Since both sides of a cloth are visible, it
+/// should use a material without back face culling.
+///
+/// Cloth collides with colliders of the scene and is moved by the wind: [`Self::wind`] defines
+/// a constant wind for this cloth only, while [`crate::scene::wind_zone::WindZone`]s affect
+/// every cloth in the scene.
+#[derive(Clone, Reflect, Visit, Debug, ComponentProvider)]
+#[visit(optional)]
+pub struct Cloth {
+    base: Base,
+    /// A handle of a mesh, that will be simulated. If not set, the parent node is used.
+    pub mesh: InheritableVariable<Handle<Node>>,
+    /// Total mass of the cloth.
+    #[reflect(min_value = 0.001)]
+    pub mass: InheritableVariable<f32>,
+    /// Compliance (inverse stiffness) of the edges. Zero means that the cloth can't be
+    /// stretched.
+    #[reflect(min_value = 0.0)]
+    pub stretch_compliance: InheritableVariable<f32>,
+    /// Compliance (inverse stiffness) of bending. Larger values make the cloth softer.
+    #[reflect(min_value = 0.0)]
+    pub bend_compliance: InheritableVariable<f32>,
+    /// Damping of velocities of particles (per second).
+    #[reflect(min_value = 0.0)]
+    pub damping: InheritableVariable<f32>,
+    /// Amount of sub-steps per frame. More sub-steps make the cloth stiffer and more stable.
+    #[reflect(min_value = 1.0)]
+    pub substeps: InheritableVariable<u32>,
+    /// A set of pins, that attach the cloth to other nodes.
+    pub pins: InheritableVariable<Vec<Pin>>,
+    /// Defines whether the cloth collides with colliders of the scene or not.
+    pub collide: InheritableVariable<bool>,
+    /// Collision groups, that are used for collision queries.
+    pub collision_groups: InheritableVariable<InteractionGroups>,
+    /// Distance, that particles keep from surfaces of colliders.
+    #[reflect(min_value = 0.0)]
+    pub thickness: InheritableVariable<f32>,
+    /// Friction between the cloth and colliders in `[0; 1]` range.
+    #[reflect(min_value = 0.0, max_value = 1.0)]
+    pub friction: InheritableVariable<f32>,
+    /// Aerodynamic drag coefficient. Zero disables the effect of wind.
+    #[reflect(min_value = 0.0)]
+    pub drag: InheritableVariable<f32>,
+    /// Velocity of the wind (in world coordinates), that affects only this cloth.
+    pub wind: InheritableVariable<Vector3<f32>>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    body: Option<XpbdBody>,
+}
+
+impl Default for Cloth {
+    fn default() -> Self {
+        ClothBuilder::new(BaseBuilder::new()).build_cloth()
+    }
+}
+
+impl Deref for Cloth {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for Cloth {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for Cloth {
+    fn type_uuid() -> Uuid {
+        uuid!("a3c5e7f9-2b4d-4f6a-8c0e-1d3f5a7b9c2e")
+    }
+}
+
+impl ConstructorProvider<Node, Graph> for Cloth {
+    fn constructor() -> NodeConstructor {
+        NodeConstructor::new::<Self>()
+            .with_variant("Cloth", |_| {
+                ClothBuilder::new(BaseBuilder::new().with_name("Cloth"))
+                    .build_node()
+                    .into()
+            })
+            .with_group("Physics")
+    }
+}
+
+impl Cloth {
+    fn mesh_handle(&self) -> Handle<Node> {
+        if self.mesh.is_some() {
+            *self.mesh
+        } else {
+            self.parent()
+        }
+    }
+
+    /// Restarts the simulation from the initial shape of the mesh on the next update. Pins are
+    /// re-attached as well.
+    pub fn restart(&mut self) {
+        self.body = None;
+    }
+
+    /// Returns world-space positions of simulated particles. The iterator is empty, if the
+    /// simulation is not started yet.
+    pub fn particle_positions(&self) -> impl Iterator<Item = Vector3<f32>> + '_ {
+        self.body.iter().flat_map(|b| b.positions())
+    }
+}
+
+impl NodeTrait for Cloth {
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.local_bounding_box()
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.world_bounding_box()
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn update(&mut self, ctx: &mut UpdateContext) {
+        let params = SimulationParams {
+            substeps: *self.substeps,
+            gravity: *ctx.physics.gravity,
+            damping: *self.damping,
+            stretch_compliance: *self.stretch_compliance,
+            bend_compliance: *self.bend_compliance,
+            pressure: None,
+            drag: *self.drag,
+            wind: *self.wind,
+            collision: self.collide.then(|| CollisionParams {
+                groups: *self.collision_groups,
+                thickness: *self.thickness,
+                friction: *self.friction,
+            }),
+        };
+        let mesh = self.mesh_handle();
+        simulate(&mut self.body, mesh, *self.mass, &self.pins, &params, ctx);
+    }
+
+    fn validate(&self, scene: &Scene) -> Result<(), String> {
+        validate_mesh(scene, self.mesh_handle())
+    }
+}
+
+/// Cloth builder allows you to create [`Cloth`] nodes in declarative manner.
+pub struct ClothBuilder {
+    base_builder: BaseBuilder,
+    mesh: Handle<Node>,
+    mass: f32,
+    stretch_compliance: f32,
+    bend_compliance: f32,
+    damping: f32,
+    substeps: u32,
+    pins: Vec<Pin>,
+    collide: bool,
+    collision_groups: InteractionGroups,
+    thickness: f32,
+    friction: f32,
+    drag: f32,
+    wind: Vector3<f32>,
+}
+
+impl ClothBuilder {
+    /// Creates a new cloth builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            mesh: Default::default(),
+            mass: 1.0,
+            stretch_compliance: 0.0,
+            bend_compliance: 0.01,
+            damping: 0.2,
+            substeps: 10,
+            pins: Default::default(),
+            collide: true,
+            collision_groups: Default::default(),
+            thickness: 0.02,
+            friction: 0.3,
+            drag: 0.5,
+            wind: Default::default(),
+        }
+    }
+
+    /// Sets the desired simulated mesh.
+    pub fn with_mesh(mut self, mesh: Handle<Node>) -> Self {
+        self.mesh = mesh;
+        self
+    }
+
+    /// Sets the desired total mass.
+    pub fn with_mass(mut self, mass: f32) -> Self {
+        self.mass = mass;
+        self
+    }
+
+    /// Sets the desired compliance of the edges.
+    pub fn with_stretch_compliance(mut self, compliance: f32) -> Self {
+        self.stretch_compliance = compliance;
+        self
+    }
+
+    /// Sets the desired compliance of bending.
+    pub fn with_bend_compliance(mut self, compliance: f32) -> Self {
+        self.bend_compliance = compliance;
+        self
+    }
+
+    /// Sets the desired damping.
+    pub fn with_damping(mut self, damping: f32) -> Self {
+        self.damping = damping;
+        self
+    }
+
+    /// Sets the desired amount of sub-steps.
+    pub fn with_substeps(mut self, substeps: u32) -> Self {
+        self.substeps = substeps;
+        self
+    }
+
+    /// Sets the desired set of pins.
+    pub fn with_pins(mut self, pins: Vec<Pin>) -> Self {
+        self.pins = pins;
+        self
+    }
+
+    /// Sets whether the cloth should collide with colliders or not.
+    pub fn with_collide(mut self, collide: bool) -> Self {
+        self.collide = collide;
+        self
+    }
+
+    /// Sets the desired collision groups.
+    pub fn with_collision_groups(mut self, groups: InteractionGroups) -> Self {
+        self.collision_groups = groups;
+        self
+    }
+
+    /// Sets the desired collision thickness.
+    pub fn with_thickness(mut self, thickness: f32) -> Self {
+        self.thickness = thickness;
+        self
+    }
+
+    /// Sets the desired friction.
+    pub fn with_friction(mut self, friction: f32) -> Self {
+        self.friction = friction;
+        self
+    }
+
+    /// Sets the desired aerodynamic drag coefficient.
+    pub fn with_drag(mut self, drag: f32) -> Self {
+        self.drag = drag;
+        self
+    }
+
+    /// Sets the desired wind velocity.
+    pub fn with_wind(mut self, wind: Vector3<f32>) -> Self {
+        self.wind = wind;
+        self
+    }
+
+    /// Creates the cloth, but does not add it to a graph.
+    pub fn build_cloth(self) -> Cloth {
+        Cloth {
+            base: self.base_builder.build_base(),
+            mesh: self.mesh.into(),
+            mass: self.mass.into(),
+            stretch_compliance: self.stretch_compliance.into(),
+            bend_compliance: self.bend_compliance.into(),
+            damping: self.damping.into(),
+            substeps: self.substeps.into(),
+            pins: self.pins.into(),
+            collide: self.collide.into(),
+            collision_groups: self.collision_groups.into(),
+            thickness: self.thickness.into(),
+            friction: self.friction.into(),
+            drag: self.drag.into(),
+            wind: self.wind.into(),
+            body: None,
+        }
+    }
+
+    /// Creates cloth node, but does not add it to a graph.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_cloth())
+    }
+
+    /// Creates cloth node and adds it to the graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::untyped::ResourceKind,
+        core::algebra::{Vector2, Vector3, Vector4},
+        graph::SceneGraphNode,
+        scene::{
+            base::BaseBuilder,
+            graph::Graph,
+            mesh::{
+                buffer::{TriangleBuffer, TriangleDefinition, VertexBuffer},
+                surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+                vertex::StaticVertex,
+                MeshBuilder,
+            },
+            pivot::PivotBuilder,
+            soft_body::{
+                cloth::{Cloth, ClothBuilder},
+                Pin,
+            },
+            transform::TransformBuilder,
+        },
+    };
+
+    // A vertical sheet of 1x1 meters in XY plane, with its top edge at Y = 0.
+    fn make_sheet(resolution: u32) -> SurfaceData {
+        let step = 1.0 / resolution as f32;
+        let mut vertices = Vec::new();
+        for y in 0..=resolution {
+            for x in 0..=resolution {
+                vertices.push(StaticVertex {
+                    position: Vector3::new(x as f32 * step - 0.5, -(y as f32) * step, 0.0),
+                    tex_coord: Vector2::new(x as f32 * step, y as f32 * step),
+                    normal: Vector3::z(),
+                    tangent: Vector4::new(1.0, 0.0, 0.0, 1.0),
+                });
+            }
+        }
+        let mut triangles = Vec::new();
+        let row = resolution + 1;
+        for y in 0..resolution {
+            for x in 0..resolution {
+                let i = y * row + x;
+                triangles.push(TriangleDefinition([i, i + row, i + 1]));
+                triangles.push(TriangleDefinition([i + 1, i + row, i + row + 1]));
+            }
+        }
+        SurfaceData::new(
+            VertexBuffer::new(vertices.len(), vertices).unwrap(),
+            TriangleBuffer::new(triangles),
+        )
+    }
+
+    #[test]
+    fn test_pinned_cloth() {
+        let mut graph = Graph::new();
+
+        let pole = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+        let cloth = ClothBuilder::new(BaseBuilder::new())
+            .with_collide(false)
+            .with_damping(5.0)
+            .with_wind(Vector3::new(0.0, 0.0, 5.0))
+            .with_pins(vec![Pin {
+                node: pole,
+                radius: 0.51,
+            }])
+            .build(&mut graph);
+        MeshBuilder::new(BaseBuilder::new().with_children(&[cloth]))
+            .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+                ResourceKind::Embedded,
+                make_sheet(8),
+            ))
+            .build()])
+            .build(&mut graph);
+
+        for _ in 0..60 {
+            graph.update(Vector2::new(100.0, 100.0), 1.0 / 60.0, Default::default());
+        }
+
+        let positions = graph[cloth]
+            .component_ref::<Cloth>()
+            .unwrap()
+            .particle_positions()
+            .collect::<Vec<_>>();
+        assert_eq!(positions.len(), 81);
+        // Edges can't be stretched, so nothing can fall lower than the length of the sheet.
+        assert!(positions.iter().all(|p| p.y >= -1.05));
+        // The wind blows the free part of the cloth away.
+        assert!(positions.iter().filter(|p| p.y < -0.6).all(|p| p.z > 0.05));
+
+        // Move the pole, pinned particles must follow it.
+        graph[pole]
+            .local_transform_mut()
+            .set_position(Vector3::new(0.0, 0.0, 1.0));
+        graph.update(Vector2::new(100.0, 100.0), 1.0 / 60.0, Default::default());
+
+        let cloth_ref = graph[cloth].component_ref::<Cloth>().unwrap();
+        assert!(cloth_ref
+            .particle_positions()
+            .any(|p| (p - Vector3::new(0.0, 0.0, 1.0)).norm() < 1.0e-3));
+    }
+}
@@ -0,0 +1,494 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Soft bodies and cloth, simulated using extended position based dynamics (XPBD). See
+//! [`SoftBody`] and [`cloth::Cloth`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::Vector3,
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        uuid::{uuid, Uuid},
+        uuid_provider,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    scene::{
+        base::{Base, BaseBuilder},
+        collider::InteractionGroups,
+        graph::Graph,
+        mesh::Mesh,
+        node::{constructor::NodeConstructor, Node, NodeTrait, UpdateContext},
+        soft_body::solver::{CollisionParams, SimulationParams, XpbdBody},
+        Scene,
+    },
+};
+use fyrox_graph::{constructor::ConstructorProvider, BaseSceneGraph};
+use std::ops::{Deref, DerefMut};
+
+pub mod cloth;
+pub(crate) mod solver;
+
+/// Pins every vertex of a simulated mesh, that is closer than [`Self::radius`] to the node
+/// at the moment when the simulation starts. Pinned vertices are not simulated, instead they
+/// follow the node. Pins could be attached to any node, including bones of skinned meshes.
+#[derive(Clone, Debug, Default, PartialEq, Reflect, Visit)]
+pub struct Pin {
+    /// A node to which vertices are pinned.
+    pub node: Handle<Node>,
+    /// Radius of the area around the node, in which vertices are pinned.
+    #[reflect(min_value = 0.0)]
+    pub radius: f32,
+}
+
+uuid_provider!(Pin = "0d7b3f5e-8c1a-4b2e-9f6d-3a5c7e9b1d2f");
+
+/// Soft body is a deformable volumetric body, such as rubber ball or a jelly cube. The body
+/// takes a closed mesh and simulates its vertices as particles connected along the edges of
+/// triangles, while a volume constraint tries to keep the volume of the mesh. Vertices with the
+/// same position are welded, so the seams of the mesh stay closed.
+///
+/// The simulated mesh is set by [`Self::mesh`] field (the parent node is used, if it is not set).
+/// The mesh must not be skinned, its vertex buffers are modified every frame. Soft body collides
+/// with colliders of the scene (but does not push rigid bodies) and is affected by
+/// [`crate::scene::wind_zone::WindZone`]s, when [`Self::drag`] is non-zero.
+#[derive(Clone, Reflect, Visit, Debug, ComponentProvider)]
+#[visit(optional)]
+pub struct SoftBody {
+    base: Base,
+    /// A handle of a mesh, that will be simulated. If not set, the parent node is used.
+    pub mesh: InheritableVariable<Handle<Node>>,
+    /// Total mass of the body.
+    #[reflect(min_value = 0.001)]
+    pub mass: InheritableVariable<f32>,
+    /// Compliance (inverse stiffness) of the edges. Zero means that edges can't be stretched.
+    #[reflect(min_value = 0.0)]
+    pub stretch_compliance: InheritableVariable<f32>,
+    /// Compliance (inverse stiffness) of bending. Larger values make the surface softer.
+    #[reflect(min_value = 0.0)]
+    pub bend_compliance: InheritableVariable<f32>,
+    /// Target volume of the body relative to its initial volume. Values above 1.0 inflate the
+    /// body.
+    #[reflect(min_value = 0.0)]
+    pub pressure: InheritableVariable<f32>,
+    /// Compliance (inverse stiffness) of the volume constraint.
+    #[reflect(min_value = 0.0)]
+    pub volume_compliance: InheritableVariable<f32>,
+    /// Damping of velocities of particles (per second).
+    #[reflect(min_value = 0.0)]
+    pub damping: InheritableVariable<f32>,
+    /// Amount of sub-steps per frame. More sub-steps make the body stiffer and more stable.
+    #[reflect(min_value = 1.0)]
+    pub substeps: InheritableVariable<u32>,
+    /// A set of pins, that attach the body to other nodes.
+    pub pins: InheritableVariable<Vec<Pin>>,
+    /// Defines whether the body collides with colliders of the scene or not.
+    pub collide: InheritableVariable<bool>,
+    /// Collision groups, that are used for collision queries.
+    pub collision_groups: InheritableVariable<InteractionGroups>,
+    /// Distance, that particles keep from surfaces of colliders.
+    #[reflect(min_value = 0.0)]
+    pub thickness: InheritableVariable<f32>,
+    /// Friction between particles and colliders in `[0; 1]` range.
+    #[reflect(min_value = 0.0, max_value = 1.0)]
+    pub friction: InheritableVariable<f32>,
+    /// Aerodynamic drag coefficient. Zero disables the effect of wind.
+    #[reflect(min_value = 0.0)]
+    pub drag: InheritableVariable<f32>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    body: Option<XpbdBody>,
+}
+
+impl Default for SoftBody {
+    fn default() -> Self {
+        SoftBodyBuilder::new(BaseBuilder::new()).build_soft_body()
+    }
+}
+
+impl Deref for SoftBody {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for SoftBody {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for SoftBody {
+    fn type_uuid() -> Uuid {
+        uuid!("4f9a2c7d-1e3b-4d5f-8a6c-9b0e2d4f6a8c")
+    }
+}
+
+impl ConstructorProvider<Node, Graph> for SoftBody {
+    fn constructor() -> NodeConstructor {
+        NodeConstructor::new::<Self>()
+            .with_variant("Soft Body", |_| {
+                SoftBodyBuilder::new(BaseBuilder::new().with_name("Soft Body"))
+                    .build_node()
+                    .into()
+            })
+            .with_group("Physics")
+    }
+}
+
+impl SoftBody {
+    fn mesh_handle(&self) -> Handle<Node> {
+        if self.mesh.is_some() {
+            *self.mesh
+        } else {
+            self.parent()
+        }
+    }
+
+    /// Restarts the simulation from the initial shape of the mesh on the next update. Pins are
+    /// re-attached as well.
+    pub fn restart(&mut self) {
+        self.body = None;
+    }
+
+    /// Returns current volume of the body, or `None` if the simulation is not started yet.
+    pub fn volume(&self) -> Option<f32> {
+        self.body.as_ref().map(|b| b.volume())
+    }
+}
+
+pub(crate) fn simulate(
+    body: &mut Option<XpbdBody>,
+    mesh: Handle<Node>,
+    mass: f32,
+    pins: &[Pin],
+    params: &SimulationParams,
+    ctx: &mut UpdateContext,
+) {
+    if ctx.dt <= 0.0 {
+        return;
+    }
+
+    if body.is_none() {
+        *body = XpbdBody::from_mesh(ctx.nodes, mesh, mass, pins);
+    }
+
+    if let Some(body) = body.as_mut() {
+        body.step(ctx.dt, params, ctx.nodes, ctx.physics);
+        body.write_to_mesh(ctx.nodes, mesh);
+    }
+}
+
+pub(crate) fn validate_mesh(scene: &Scene, mesh: Handle<Node>) -> Result<(), String> {
+    match scene.graph.try_get(mesh) {
+        Some(node) => match node.cast::<Mesh>() {
+            Some(mesh) => {
+                if mesh.surfaces().iter().any(|s| !s.bones().is_empty()) {
+                    Err(
+                        "Simulated mesh must not be skinned, use pins to attach it to bones!"
+                            .to_string(),
+                    )
+                } else {
+                    Ok(())
+                }
+            }
+            None => Err("Simulated node must be a mesh!".to_string()),
+        },
+        None => Err("A mesh must be set or the node must be attached to a mesh!".to_string()),
+    }
+}
+
+impl NodeTrait for SoftBody {
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.local_bounding_box()
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.world_bounding_box()
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn update(&mut self, ctx: &mut UpdateContext) {
+        let params = SimulationParams {
+            substeps: *self.substeps,
+            gravity: *ctx.physics.gravity,
+            damping: *self.damping,
+            stretch_compliance: *self.stretch_compliance,
+            bend_compliance: *self.bend_compliance,
+            pressure: Some((*self.pressure, *self.volume_compliance)),
+            drag: *self.drag,
+            wind: Vector3::zeros(),
+            collision: self.collide.then(|| CollisionParams {
+                groups: *self.collision_groups,
+                thickness: *self.thickness,
+                friction: *self.friction,
+            }),
+        };
+        let mesh = self.mesh_handle();
+        simulate(&mut self.body, mesh, *self.mass, &self.pins, &params, ctx);
+    }
+
+    fn validate(&self, scene: &Scene) -> Result<(), String> {
+        validate_mesh(scene, self.mesh_handle())
+    }
+}
+
+/// Soft body builder allows you to create [`SoftBody`] nodes in declarative manner.
+pub struct SoftBodyBuilder {
+    base_builder: BaseBuilder,
+    mesh: Handle<Node>,
+    mass: f32,
+    stretch_compliance: f32,
+    bend_compliance: f32,
+    pressure: f32,
+    volume_compliance: f32,
+    damping: f32,
+    substeps: u32,
+    pins: Vec<Pin>,
+    collide: bool,
+    collision_groups: InteractionGroups,
+    thickness: f32,
+    friction: f32,
+    drag: f32,
+}
+
+impl SoftBodyBuilder {
+    /// Creates a new soft body builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            mesh: Default::default(),
+            mass: 1.0,
+            stretch_compliance: 0.0001,
+            bend_compliance: 0.0001,
+            pressure: 1.0,
+            volume_compliance: 0.0,
+            damping: 0.5,
+            substeps: 10,
+            pins: Default::default(),
+            collide: true,
+            collision_groups: Default::default(),
+            thickness: 0.02,
+            friction: 0.3,
+            drag: 0.0,
+        }
+    }
+
+    /// Sets the desired simulated mesh.
+    pub fn with_mesh(mut self, mesh: Handle<Node>) -> Self {
+        self.mesh = mesh;
+        self
+    }
+
+    /// Sets the desired total mass.
+    pub fn with_mass(mut self, mass: f32) -> Self {
+        self.mass = mass;
+        self
+    }
+
+    /// Sets the desired compliance of the edges.
+    pub fn with_stretch_compliance(mut self, compliance: f32) -> Self {
+        self.stretch_compliance = compliance;
+        self
+    }
+
+    /// Sets the desired compliance of bending.
+    pub fn with_bend_compliance(mut self, compliance: f32) -> Self {
+        self.bend_compliance = compliance;
+        self
+    }
+
+    /// Sets the desired pressure.
+    pub fn with_pressure(mut self, pressure: f32) -> Self {
+        self.pressure = pressure;
+        self
+    }
+
+    /// Sets the desired compliance of the volume constraint.
+    pub fn with_volume_compliance(mut self, compliance: f32) -> Self {
+        self.volume_compliance = compliance;
+        self
+    }
+
+    /// Sets the desired damping.
+    pub fn with_damping(mut self, damping: f32) -> Self {
+        self.damping = damping;
+        self
+    }
+
+    /// Sets the desired amount of sub-steps.
+    pub fn with_substeps(mut self, substeps: u32) -> Self {
+        self.substeps = substeps;
+        self
+    }
+
+    /// Sets the desired set of pins.
+    pub fn with_pins(mut self, pins: Vec<Pin>) -> Self {
+        self.pins = pins;
+        self
+    }
+
+    /// Sets whether the body should collide with colliders or not.
+    pub fn with_collide(mut self, collide: bool) -> Self {
+        self.collide = collide;
+        self
+    }
+
+    /// Sets the desired collision groups.
+    pub fn with_collision_groups(mut self, groups: InteractionGroups) -> Self {
+        self.collision_groups = groups;
+        self
+    }
+
+    /// Sets the desired collision thickness.
+    pub fn with_thickness(mut self, thickness: f32) -> Self {
+        self.thickness = thickness;
+        self
+    }
+
+    /// Sets the desired friction.
+    pub fn with_friction(mut self, friction: f32) -> Self {
+        self.friction = friction;
+        self
+    }
+
+    /// Sets the desired aerodynamic drag coefficient.
+    pub fn with_drag(mut self, drag: f32) -> Self {
+        self.drag = drag;
+        self
+    }
+
+    /// Creates the soft body, but does not add it to a graph.
+    pub fn build_soft_body(self) -> SoftBody {
+        SoftBody {
+            base: self.base_builder.build_base(),
+            mesh: self.mesh.into(),
+            mass: self.mass.into(),
+            stretch_compliance: self.stretch_compliance.into(),
+            bend_compliance: self.bend_compliance.into(),
+            pressure: self.pressure.into(),
+            volume_compliance: self.volume_compliance.into(),
+            damping: self.damping.into(),
+            substeps: self.substeps.into(),
+            pins: self.pins.into(),
+            collide: self.collide.into(),
+            collision_groups: self.collision_groups.into(),
+            thickness: self.thickness.into(),
+            friction: self.friction.into(),
+            drag: self.drag.into(),
+            body: None,
+        }
+    }
+
+    /// Creates soft body node, but does not add it to a graph.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_soft_body())
+    }
+
+    /// Creates soft body node and adds it to the graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::untyped::ResourceKind,
+        core::algebra::{Matrix4, Vector2, Vector3},
+        graph::SceneGraphNode,
+        scene::{
+            base::BaseBuilder,
+            collider::{ColliderBuilder, ColliderShape},
+            graph::Graph,
+            mesh::{
+                surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+                MeshBuilder,
+            },
+            rigidbody::{RigidBodyBuilder, RigidBodyType},
+            soft_body::{SoftBody, SoftBodyBuilder},
+            transform::TransformBuilder,
+        },
+    };
+
+    #[test]
+    fn test_soft_body_keeps_volume() {
+        let mut graph = Graph::new();
+
+        let ground_collider = ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(ColliderShape::cuboid(10.0, 0.5, 10.0))
+            .build(&mut graph);
+        RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, -0.5, 0.0))
+                        .build(),
+                )
+                .with_children(&[ground_collider]),
+        )
+        .with_body_type(RigidBodyType::Static)
+        .build(&mut graph);
+
+        let soft_body = SoftBodyBuilder::new(BaseBuilder::new()).build(&mut graph);
+        MeshBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, 1.0, 0.0))
+                        .build(),
+                )
+                .with_children(&[soft_body]),
+        )
+        .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+            ResourceKind::Embedded,
+            SurfaceData::make_cube(Matrix4::identity()),
+        ))
+        .build()])
+        .build(&mut graph);
+
+        graph.update(Vector2::new(100.0, 100.0), 1.0 / 60.0, Default::default());
+        let initial_volume = graph[soft_body]
+            .component_ref::<SoftBody>()
+            .unwrap()
+            .volume()
+            .unwrap();
+        assert!((initial_volume - 1.0).abs() < 0.01, "{initial_volume}");
+
+        for _ in 0..120 {
+            graph.update(Vector2::new(100.0, 100.0), 1.0 / 60.0, Default::default());
+        }
+
+        // The body must land on the ground and keep its volume.
+        let volume = graph[soft_body]
+            .component_ref::<SoftBody>()
+            .unwrap()
+            .volume()
+            .unwrap();
+        assert!((volume - 1.0).abs() < 0.1, "{volume}");
+    }
+}
@@ -0,0 +1,491 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Extended position based dynamics (XPBD) solver, that is used by [`super::SoftBody`] and
+//! [`super::cloth::Cloth`] nodes. The solver uses "small steps" approach: a frame is split into
+//! a number of sub-steps and every constraint is solved once per sub-step.
+
+use crate::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{Matrix4, Point3, Vector3},
+        pool::Handle,
+    },
+    scene::{
+        collider::InteractionGroups,
+        graph::{physics::PhysicsWorld, NodePool},
+        mesh::{
+            buffer::{VertexAttributeUsage, VertexReadTrait, VertexWriteTrait},
+            surface::{SurfaceData, SurfaceResource},
+            Mesh,
+        },
+        node::Node,
+        soft_body::Pin,
+        wind_zone::WindZone,
+    },
+};
+use fxhash::FxHashMap;
+
+#[derive(Clone, Debug, Default)]
+struct Particle {
+    position: Vector3<f32>,
+    prev_position: Vector3<f32>,
+    velocity: Vector3<f32>,
+    inv_mass: f32,
+}
+
+#[derive(Clone, Debug)]
+struct DistanceConstraint {
+    a: usize,
+    b: usize,
+    rest_length: f32,
+}
+
+#[derive(Clone, Debug)]
+struct PinnedParticle {
+    particle: usize,
+    node: Handle<Node>,
+    // Position of the particle in the local coordinates of the node.
+    offset: Vector3<f32>,
+}
+
+/// Parameters of a simulation step.
+pub(crate) struct SimulationParams {
+    pub substeps: u32,
+    pub gravity: Vector3<f32>,
+    pub damping: f32,
+    pub stretch_compliance: f32,
+    pub bend_compliance: f32,
+    /// Target volume (relative to the rest volume) and compliance of the volume constraint.
+    pub pressure: Option<(f32, f32)>,
+    pub drag: f32,
+    pub wind: Vector3<f32>,
+    pub collision: Option<CollisionParams>,
+}
+
+pub(crate) struct CollisionParams {
+    pub groups: InteractionGroups,
+    pub thickness: f32,
+    pub friction: f32,
+}
+
+/// A set of particles built from vertices of a mesh, connected with distance constraints along
+/// the edges of triangles.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct XpbdBody {
+    particles: Vec<Particle>,
+    stretch_constraints: Vec<DistanceConstraint>,
+    bend_constraints: Vec<DistanceConstraint>,
+    triangles: Vec<[usize; 3]>,
+    // Maps vertices of every surface of the mesh to particles.
+    vertex_maps: Vec<Vec<usize>>,
+    rest_volume: f32,
+    pins: Vec<PinnedParticle>,
+}
+
+impl XpbdBody {
+    /// Creates a body from every surface of the given mesh. Vertices with the same position are
+    /// welded into a single particle, so UV seams do not tear the body apart. Surface data of the
+    /// mesh is replaced with unique copies, so the vertex buffers could be modified without
+    /// affecting other meshes, that share the same data.
+    pub fn from_mesh(
+        nodes: &mut NodePool,
+        mesh: Handle<Node>,
+        mass: f32,
+        pins: &[Pin],
+    ) -> Option<Self> {
+        let mesh_node = nodes.try_borrow_mut(mesh)?;
+        let global_transform = mesh_node.global_transform();
+        let mesh = mesh_node.cast_mut::<Mesh>()?;
+
+        let mut body = Self::default();
+        let mut welded = FxHashMap::default();
+        for surface in mesh.surfaces_mut() {
+            let data = surface.data();
+            let unique = {
+                let data = data.data_ref();
+                SurfaceData::new(data.vertex_buffer.clone(), data.geometry_buffer.clone())
+            };
+
+            let mut vertex_map = Vec::with_capacity(unique.vertex_buffer.vertex_count() as usize);
+            for view in unique.vertex_buffer.iter() {
+                let position = view
+                    .read_3_f32(VertexAttributeUsage::Position)
+                    .unwrap_or_default();
+                let key = position.map(|c| c.to_bits());
+                let index = *welded.entry((key.x, key.y, key.z)).or_insert_with(|| {
+                    let position = global_transform
+                        .transform_point(&Point3::from(position))
+                        .coords;
+                    body.particles.push(Particle {
+                        position,
+                        prev_position: position,
+                        velocity: Default::default(),
+                        inv_mass: 0.0,
+                    });
+                    body.particles.len() - 1
+                });
+                vertex_map.push(index);
+            }
+
+            for triangle in unique.geometry_buffer.iter() {
+                let [a, b, c] = triangle.0.map(|i| vertex_map[i as usize]);
+                if a != b && b != c && a != c {
+                    body.triangles.push([a, b, c]);
+                }
+            }
+
+            body.vertex_maps.push(vertex_map);
+            surface.set_data(SurfaceResource::new_ok(ResourceKind::Embedded, unique));
+        }
+
+        if body.particles.is_empty() {
+            return None;
+        }
+
+        let inv_mass = body.particles.len() as f32 / mass.max(f32::EPSILON);
+        for particle in body.particles.iter_mut() {
+            particle.inv_mass = inv_mass;
+        }
+
+        body.build_constraints();
+        body.rest_volume = body.volume();
+        body.pin(nodes, pins);
+
+        Some(body)
+    }
+
+    fn build_constraints(&mut self) {
+        // Maps an edge to the vertices, that are opposite to it in adjacent triangles.
+        let mut edges = FxHashMap::<(usize, usize), Vec<usize>>::default();
+        for &[a, b, c] in self.triangles.iter() {
+            for (i, j, opposite) in [(a, b, c), (b, c, a), (c, a, b)] {
+                edges
+                    .entry((i.min(j), i.max(j)))
+                    .or_default()
+                    .push(opposite);
+            }
+        }
+
+        for (&(a, b), opposites) in edges.iter() {
+            self.stretch_constraints.push(DistanceConstraint {
+                a,
+                b,
+                rest_length: (self.particles[a].position - self.particles[b].position).norm(),
+            });
+
+            // Bending is approximated by a distance constraint between the vertices, that are
+            // opposite to the shared edge of two adjacent triangles.
+            if let [c, d] = opposites.as_slice() {
+                if c != d && !edges.contains_key(&((*c).min(*d), (*c).max(*d))) {
+                    self.bend_constraints.push(DistanceConstraint {
+                        a: *c,
+                        b: *d,
+                        rest_length: (self.particles[*c].position - self.particles[*d].position)
+                            .norm(),
+                    });
+                }
+            }
+        }
+    }
+
+    fn pin(&mut self, nodes: &NodePool, pins: &[Pin]) {
+        for pin in pins {
+            let Some(node) = nodes.try_borrow(pin.node) else {
+                continue;
+            };
+            let transform = node.global_transform();
+            let inv_transform = transform.try_inverse().unwrap_or_default();
+            let center = node.global_position();
+            for (index, particle) in self.particles.iter_mut().enumerate() {
+                if (particle.position - center).norm() <= pin.radius {
+                    particle.inv_mass = 0.0;
+                    self.pins.push(PinnedParticle {
+                        particle: index,
+                        node: pin.node,
+                        offset: inv_transform
+                            .transform_point(&Point3::from(particle.position))
+                            .coords,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Returns world-space positions of the particles.
+    pub fn positions(&self) -> impl Iterator<Item = Vector3<f32>> + '_ {
+        self.particles.iter().map(|p| p.position)
+    }
+
+    /// Calculates the volume enclosed by the triangles of the body. The result is meaningful
+    /// only for closed meshes.
+    pub fn volume(&self) -> f32 {
+        self.triangles
+            .iter()
+            .map(|&[a, b, c]| {
+                self.particles[a].position.dot(
+                    &self.particles[b]
+                        .position
+                        .cross(&self.particles[c].position),
+                )
+            })
+            .sum::<f32>()
+            / 6.0
+    }
+
+    /// Performs a simulation step.
+    pub fn step(
+        &mut self,
+        dt: f32,
+        params: &SimulationParams,
+        nodes: &NodePool,
+        physics: &PhysicsWorld,
+    ) {
+        let substeps = params.substeps.max(1);
+        let h = dt / substeps as f32;
+
+        let pin_targets = self
+            .pins
+            .iter()
+            .map(|pin| {
+                let target = nodes
+                    .try_borrow(pin.node)
+                    .map(|n| {
+                        n.global_transform()
+                            .transform_point(&Point3::from(pin.offset))
+                            .coords
+                    })
+                    .unwrap_or(self.particles[pin.particle].position);
+                (self.particles[pin.particle].position, target)
+            })
+            .collect::<Vec<_>>();
+
+        // Aerodynamic forces change slowly, so they're calculated once per step.
+        let accelerations = self.aerodynamic_accelerations(params, nodes);
+
+        for substep in 0..substeps {
+            for (particle, acceleration) in self.particles.iter_mut().zip(accelerations.iter()) {
+                particle.prev_position = particle.position;
+                if particle.inv_mass > 0.0 {
+                    particle.velocity += (params.gravity + acceleration).scale(h);
+                    particle.position += particle.velocity.scale(h);
+                }
+            }
+
+            // Pinned particles are moved smoothly towards their targets.
+            let t = (substep + 1) as f32 / substeps as f32;
+            for (pin, (start, target)) in self.pins.iter().zip(pin_targets.iter()) {
+                self.particles[pin.particle].position = start.lerp(target, t);
+            }
+
+            Self::solve_distance_constraints(
+                &mut self.particles,
+                &self.stretch_constraints,
+                params.stretch_compliance / (h * h),
+            );
+            Self::solve_distance_constraints(
+                &mut self.particles,
+                &self.bend_constraints,
+                params.bend_compliance / (h * h),
+            );
+
+            if let Some((pressure, compliance)) = params.pressure {
+                self.solve_volume_constraint(pressure, compliance / (h * h));
+            }
+
+            if let Some(collision) = params.collision.as_ref() {
+                self.solve_collisions(collision, physics);
+            }
+
+            let damping = (1.0 - params.damping * h).max(0.0);
+            for particle in self.particles.iter_mut() {
+                particle.velocity = (particle.position - particle.prev_position).scale(damping / h);
+            }
+        }
+    }
+
+    fn solve_distance_constraints(
+        particles: &mut [Particle],
+        constraints: &[DistanceConstraint],
+        alpha: f32,
+    ) {
+        for constraint in constraints {
+            let (pa, pb) = (&particles[constraint.a], &particles[constraint.b]);
+            let w = pa.inv_mass + pb.inv_mass;
+            if w == 0.0 {
+                continue;
+            }
+            let delta = pa.position - pb.position;
+            let length = delta.norm();
+            if length <= f32::EPSILON {
+                continue;
+            }
+            let normal = delta.unscale(length);
+            let lambda = -(length - constraint.rest_length) / (w + alpha);
+            let (wa, wb) = (pa.inv_mass, pb.inv_mass);
+            particles[constraint.a].position += normal.scale(lambda * wa);
+            particles[constraint.b].position -= normal.scale(lambda * wb);
+        }
+    }
+
+    fn solve_volume_constraint(&mut self, pressure: f32, alpha: f32) {
+        let mut gradients = vec![Vector3::<f32>::zeros(); self.particles.len()];
+        for &[a, b, c] in self.triangles.iter() {
+            let (pa, pb, pc) = (
+                self.particles[a].position,
+                self.particles[b].position,
+                self.particles[c].position,
+            );
+            gradients[a] += pb.cross(&pc).unscale(6.0);
+            gradients[b] += pc.cross(&pa).unscale(6.0);
+            gradients[c] += pa.cross(&pb).unscale(6.0);
+        }
+
+        let w = self
+            .particles
+            .iter()
+            .zip(gradients.iter())
+            .map(|(p, g)| p.inv_mass * g.norm_squared())
+            .sum::<f32>();
+        if w <= f32::EPSILON {
+            return;
+        }
+
+        let lambda = -(self.volume() - pressure * self.rest_volume) / (w + alpha);
+        for (particle, gradient) in self.particles.iter_mut().zip(gradients.iter()) {
+            particle.position += gradient.scale(lambda * particle.inv_mass);
+        }
+    }
+
+    fn solve_collisions(&mut self, params: &CollisionParams, physics: &PhysicsWorld) {
+        for particle in self.particles.iter_mut() {
+            if particle.inv_mass == 0.0 {
+                continue;
+            }
+            let Some(projection) = physics.project_point(particle.position, false, params.groups)
+            else {
+                continue;
+            };
+            let delta = particle.position - projection.point;
+            let distance = delta.norm();
+            if distance <= f32::EPSILON || (!projection.is_inside && distance >= params.thickness) {
+                continue;
+            }
+            let normal = if projection.is_inside {
+                -delta.unscale(distance)
+            } else {
+                delta.unscale(distance)
+            };
+            particle.position = projection.point + normal.scale(params.thickness);
+
+            // Simple static friction: remove a portion of the tangential motion.
+            let displacement = particle.position - particle.prev_position;
+            let tangential = displacement - normal.scale(displacement.dot(&normal));
+            particle.position -= tangential.scale(params.friction.clamp(0.0, 1.0));
+        }
+    }
+
+    fn aerodynamic_accelerations(
+        &self,
+        params: &SimulationParams,
+        nodes: &NodePool,
+    ) -> Vec<Vector3<f32>> {
+        let mut accelerations = vec![Vector3::zeros(); self.particles.len()];
+        if params.drag <= 0.0 {
+            return accelerations;
+        }
+
+        let wind_zones = nodes
+            .iter()
+            .filter(|n| n.is_globally_enabled())
+            .filter_map(|n| n.cast::<WindZone>())
+            .collect::<Vec<_>>();
+
+        for &[a, b, c] in self.triangles.iter() {
+            let (pa, pb, pc) = (&self.particles[a], &self.particles[b], &self.particles[c]);
+            let cross = (pb.position - pa.position).cross(&(pc.position - pa.position));
+            let double_area = cross.norm();
+            if double_area <= f32::EPSILON {
+                continue;
+            }
+            let normal = cross.unscale(double_area);
+            let center = (pa.position + pb.position + pc.position).unscale(3.0);
+            let wind = wind_zones
+                .iter()
+                .fold(params.wind, |wind, zone| wind + zone.velocity_at(center));
+            let relative_velocity = wind - (pa.velocity + pb.velocity + pc.velocity).unscale(3.0);
+            let normal_velocity = relative_velocity.dot(&normal);
+            let force = normal.scale(
+                params.drag * 0.5 * double_area * normal_velocity * normal_velocity.abs() / 3.0,
+            );
+            for index in [a, b, c] {
+                accelerations[index] += force.scale(self.particles[index].inv_mass);
+            }
+        }
+
+        accelerations
+    }
+
+    /// Writes positions and normals of particles back to the surfaces of the mesh. Only position
+    /// and normal attributes are modified, the layout of vertex buffers stays the same, so the
+    /// renderer just re-uploads the contents of the buffers.
+    pub fn write_to_mesh(&self, nodes: &mut NodePool, mesh: Handle<Node>) {
+        let Some(mesh_node) = nodes.try_borrow_mut(mesh) else {
+            return;
+        };
+        let global_transform = mesh_node.global_transform();
+        let inv_transform = global_transform.try_inverse().unwrap_or_default();
+        // Normals are transformed using inverse-transpose of the world-to-local transform.
+        let normal_matrix: Matrix4<f32> = global_transform.transpose();
+        let Some(mesh) = mesh_node.cast_mut::<Mesh>() else {
+            return;
+        };
+
+        let mut normals = vec![Vector3::<f32>::zeros(); self.particles.len()];
+        for &[a, b, c] in self.triangles.iter() {
+            let normal = (self.particles[b].position - self.particles[a].position)
+                .cross(&(self.particles[c].position - self.particles[a].position));
+            normals[a] += normal;
+            normals[b] += normal;
+            normals[c] += normal;
+        }
+
+        for (surface, vertex_map) in mesh.surfaces_mut().iter_mut().zip(self.vertex_maps.iter()) {
+            let data = surface.data();
+            let mut data = data.data_ref();
+            let has_normals = data
+                .vertex_buffer
+                .has_attribute(VertexAttributeUsage::Normal);
+            let mut vertex_buffer = data.vertex_buffer.modify();
+            for (mut view, &index) in vertex_buffer.iter_mut().zip(vertex_map.iter()) {
+                let position = inv_transform
+                    .transform_point(&Point3::from(self.particles[index].position))
+                    .coords;
+                let _ = view.write_3_f32(VertexAttributeUsage::Position, position);
+                if has_normals {
+                    let normal = normal_matrix
+                        .transform_vector(&normals[index])
+                        .try_normalize(f32::EPSILON)
+                        .unwrap_or_else(Vector3::y);
+                    let _ = view.write_3_f32(VertexAttributeUsage::Normal, normal);
+                }
+            }
+        }
+    }
+}
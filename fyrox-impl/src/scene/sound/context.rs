@@ -26,13 +26,22 @@ use crate::{
         pool::Handle,
         visitor::prelude::*,
     },
-    scene::{node::Node, sound::Sound},
+    scene::{
+        graph::time::SceneTime,
+        node::Node,
+        sound::{
+            reverb_zone::{blend_reverb_zones, ReverbParameters, ReverbZone},
+            Sound,
+        },
+    },
 };
-use fxhash::FxHashSet;
+use fxhash::{FxHashMap, FxHashSet};
 use fyrox_sound::{
     bus::AudioBusGraph,
     context::DistanceModel,
+    effects::{reverb::Reverb, Effect},
     renderer::Renderer,
+    snapshot::SnapshotMixer,
     source::{SoundSource, SoundSourceBuilder, Status},
 };
 use std::{sync::MutexGuard, time::Duration};
@@ -42,6 +51,14 @@ use std::{sync::MutexGuard, time::Duration};
 pub struct SoundContext {
     #[visit(optional)]
     pub(crate) native: fyrox_sound::context::SoundContext,
+    #[visit(optional)]
+    reverb_transition_time: f32,
+    #[visit(skip)]
+    reverb_blend: FxHashMap<String, ReverbParameters>,
+    // `true` if native sources were created or their pitch or status were synced since the last
+    // application of the time scale, such sources are not scaled yet.
+    #[visit(skip)]
+    sources_changed: bool,
 }
 
 /// Proxy for guarded access to the sound context.
@@ -60,6 +77,18 @@ impl SoundContextGuard<'_> {
         self.guard.bus_graph_mut()
     }
 
+    /// Returns a reference to the snapshot mixer, that could be used to smoothly apply listener-level
+    /// states (underwater, pause, slow motion, etc.) to audio buses.
+    pub fn snapshot_mixer_ref(&self) -> &SnapshotMixer {
+        self.guard.snapshot_mixer_ref()
+    }
+
+    /// Returns a reference to the snapshot mixer, that could be used to smoothly apply listener-level
+    /// states (underwater, pause, slow motion, etc.) to audio buses.
+    pub fn snapshot_mixer_mut(&mut self) -> &mut SnapshotMixer {
+        self.guard.snapshot_mixer_mut()
+    }
+
     /// Pause/unpause the sound context. Paused context won't play any sounds.
     pub fn pause(&mut self, pause: bool) {
         self.guard.pause(pause);
@@ -124,7 +153,12 @@ impl Default for SoundContext {
         // There's no need to serialize native sources, because they'll be re-created automatically.
         state.serialization_options.skip_sources = true;
         drop(state);
-        Self { native }
+        Self {
+            native,
+            reverb_transition_time: 0.5,
+            reverb_blend: Default::default(),
+            sources_changed: false,
+        }
     }
 }
 
@@ -137,6 +171,128 @@ impl SoundContext {
     pub fn deep_clone(&self) -> Self {
         Self {
             native: self.native.deep_clone(),
+            reverb_transition_time: self.reverb_transition_time,
+            reverb_blend: self.reverb_blend.clone(),
+            sources_changed: self.sources_changed,
+        }
+    }
+
+    /// Sets the time (in seconds) over which reverb parameters of audio buses smoothly change
+    /// to the parameters defined by [`ReverbZone`]s around the listener. Zero means instant
+    /// change. Default is 0.5 seconds.
+    pub fn set_reverb_transition_time(&mut self, time: f32) {
+        self.reverb_transition_time = time.max(0.0);
+    }
+
+    /// Returns the time (in seconds) over which reverb parameters of audio buses change. See
+    /// [`Self::set_reverb_transition_time`] for more info.
+    pub fn reverb_transition_time(&self) -> f32 {
+        self.reverb_transition_time
+    }
+
+    pub(crate) fn apply_time_scale<'a>(
+        &mut self,
+        time: &SceneTime,
+        sounds: impl Iterator<Item = (&'a Sound, f32)>,
+    ) {
+        // Sources keep their scaled pitch and status, so there's nothing to do unless the time
+        // scale has changed or some sources were synced with unscaled values.
+        let sources_changed = std::mem::take(&mut self.sources_changed);
+        if !time.is_changed() && !(sources_changed && time.is_scaled()) {
+            return;
+        }
+
+        let mut state = self.native.state();
+        for (sound, time_scale) in sounds {
+            let Some(source) = state.try_get_source_mut(sound.native.get()) else {
+                continue;
+            };
+
+            if time_scale > 0.0 {
+                let pitch = sound.pitch() * time_scale as f64;
+                if source.pitch() != pitch {
+                    source.set_pitch(pitch);
+                }
+
+                if sound.paused_by_time.replace(false) && source.status() == Status::Paused {
+                    source.play();
+                }
+            } else if source.status() == Status::Playing {
+                source.pause();
+                sound.paused_by_time.set(true);
+            }
+        }
+    }
+
+    pub(crate) fn update_reverb_zones<'a>(
+        &mut self,
+        zones: impl Iterator<Item = &'a ReverbZone>,
+        dt: f32,
+    ) {
+        let mut state = self.native.state();
+        let listener_position = state.listener().position();
+
+        let mut contributions = FxHashMap::<&str, Vec<_>>::default();
+        for zone in zones {
+            contributions
+                .entry(zone.audio_bus.as_str())
+                .or_default()
+                .push(zone.contribution_at(listener_position));
+        }
+
+        let mut targets = FxHashMap::default();
+        for (bus, contributions) in contributions {
+            if let Some(parameters) = blend_reverb_zones(contributions) {
+                targets.insert(bus.to_string(), parameters);
+            }
+        }
+        // Buses that are not affected by any zone anymore should fade out smoothly.
+        for (bus, current) in self.reverb_blend.iter() {
+            if !targets.contains_key(bus) {
+                targets.insert(bus.clone(), current.silent());
+            }
+        }
+
+        let t = if self.reverb_transition_time > 0.0 {
+            1.0 - (-dt / self.reverb_transition_time).exp()
+        } else {
+            1.0
+        };
+
+        for (bus_name, target) in targets {
+            let current = match self.reverb_blend.get(&bus_name) {
+                Some(current) => current.lerp(&target, t),
+                // The zone fades in from silence, but other parameters are applied immediately.
+                None => target.silent().lerp(&target, t),
+            };
+            let faded_out = target.level == 0.0 && current.level <= 1.0e-4;
+            let current = if faded_out { target } else { current };
+
+            if let Some(bus) = state
+                .bus_graph_mut()
+                .buses_iter_mut()
+                .find(|bus| bus.name() == bus_name)
+            {
+                let reverb = match bus
+                    .effects_mut()
+                    .position(|effect| matches!(effect, Effect::Reverb(_)))
+                {
+                    Some(index) => index,
+                    None => {
+                        bus.add_effect(Effect::Reverb(Reverb::new()));
+                        bus.effects().count() - 1
+                    }
+                };
+                if let Some(Effect::Reverb(reverb)) = bus.effect_mut(reverb) {
+                    current.apply(reverb);
+                }
+            }
+
+            if faded_out {
+                self.reverb_blend.remove(&bus_name);
+            } else {
+                self.reverb_blend.insert(bus_name, current);
+            }
         }
     }
 
@@ -164,8 +320,11 @@ impl SoundContext {
 
     pub(crate) fn sync_with_sound(&self, sound: &mut Sound) {
         if let Some(source) = self.native.state().try_get_source_mut(sound.native.get()) {
-            // Sync back.
-            sound.status.set_value_silent(source.status());
+            // Sync back. The status of a source, that was paused by the scene time, must remain
+            // the same, so it could be resumed later.
+            if !sound.paused_by_time.get() {
+                sound.status.set_value_silent(source.status());
+            }
             sound
                 .playback_time
                 .set_value_silent(source.playback_time().as_secs_f32());
@@ -206,6 +365,7 @@ impl SoundContext {
             });
             sound.pitch.try_sync_model(|v| {
                 source.set_pitch(v);
+                self.sources_changed = true;
             });
             sound.looping.try_sync_model(|v| {
                 source.set_looping(v);
@@ -225,6 +385,7 @@ impl SoundContext {
                 }
                 Status::Playing => {
                     source.play();
+                    self.sources_changed = true;
                 }
                 Status::Paused => {
                     source.pause();
@@ -251,6 +412,7 @@ impl SoundContext {
             {
                 Ok(source) => {
                     sound.native.set(self.native.state().add_source(source));
+                    self.sources_changed = true;
 
                     Log::writeln(
                         MessageKind::Information,
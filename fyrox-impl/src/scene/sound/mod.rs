@@ -43,7 +43,8 @@ use crate::{
 pub use fyrox_sound::{
     buffer::{
         generic::Samples,
-        loader::{SoundBufferImportOptions, SoundBufferLoader},
+        import::DEFAULT_IMPORT_CACHE_DIR,
+        loader::{ChannelConversion, SoundBufferImportOptions, SoundBufferLoader},
         DataSource, SoundBuffer, SoundBufferResource, SoundBufferResourceLoadError,
     },
     bus::*,
@@ -54,6 +55,7 @@ pub use fyrox_sound::{
     error::SoundError,
     hrtf::HrirSphere,
     renderer::{hrtf::*, Renderer},
+    snapshot::*,
     source::Status,
 };
 
@@ -71,6 +73,7 @@ use std::{
 
 pub mod context;
 pub mod listener;
+pub mod reverb_zone;
 
 /// Sound source.
 #[derive(Visit, Reflect, Debug, ComponentProvider)]
@@ -129,6 +132,11 @@ pub struct Sound {
     #[reflect(hidden)]
     #[visit(skip)]
     pub(crate) native: Cell<Handle<SoundSource>>,
+
+    // `true` if the native source was paused because the scene time is stopped.
+    #[reflect(hidden)]
+    #[visit(skip)]
+    pub(crate) paused_by_time: Cell<bool>,
 }
 
 impl Deref for Sound {
@@ -163,6 +171,7 @@ impl Default for Sound {
             spatial_blend: InheritableVariable::new_modified(1.0),
             audio_bus: InheritableVariable::new_modified(AudioBusGraph::PRIMARY_BUS.to_string()),
             native: Default::default(),
+            paused_by_time: Default::default(),
         }
     }
 }
@@ -186,6 +195,7 @@ impl Clone for Sound {
             audio_bus: self.audio_bus.clone(),
             // Do not copy. The copy will have its own native representation.
             native: Default::default(),
+            paused_by_time: Default::default(),
         }
     }
 }
@@ -596,6 +606,7 @@ impl SoundBuilder {
             spatial_blend: self.spatial_blend.into(),
             audio_bus: self.audio_bus.into(),
             native: Default::default(),
+            paused_by_time: Default::default(),
         }
     }
 
@@ -0,0 +1,442 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Reverb zone is a volume that defines acoustics of some area of a scene. See [`ReverbZone`]
+//! docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Point3, Vector3},
+        math::{aabb::AxisAlignedBoundingBox, lerpf},
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        uuid::{uuid, Uuid},
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    scene::{
+        base::{Base, BaseBuilder},
+        graph::Graph,
+        node::{constructor::NodeConstructor, Node, NodeTrait},
+        sound::{reverb::Reverb, AudioBusGraph},
+    },
+};
+use fyrox_graph::constructor::ConstructorProvider;
+use std::ops::{Deref, DerefMut};
+
+/// A set of parameters of the reverb effect, that is applied to an audio bus by reverb zones.
+#[derive(Copy, Clone, Debug, PartialEq, Reflect, Visit)]
+#[visit(optional)]
+pub struct ReverbParameters {
+    /// Loudness of reverberation.
+    #[reflect(min_value = 0.0)]
+    pub level: f32,
+    /// How much of the input signal is passed to output without any processing.
+    #[reflect(min_value = 0.0, max_value = 1.0)]
+    pub dry: f32,
+    /// Stereo mixing of reverberation. See [`Reverb::set_wet`] for more info.
+    #[reflect(min_value = 0.0, max_value = 1.0)]
+    pub wet: f32,
+    /// Duration of reverberation (in seconds). The larger an environment, the longer the duration.
+    #[reflect(min_value = 0.0)]
+    pub decay_time: f32,
+    /// Normalized cutoff frequency of reflections. Lower values produce muffled reflections. See
+    /// [`Reverb::set_fc`] for more info.
+    #[reflect(min_value = 0.0, max_value = 1.0)]
+    pub cutoff_frequency: f32,
+}
+
+impl Default for ReverbParameters {
+    fn default() -> Self {
+        Self::ROOM
+    }
+}
+
+impl ReverbParameters {
+    /// Small room with short reflections.
+    pub const ROOM: Self = Self {
+        level: 0.6,
+        dry: 1.0,
+        wet: 1.0,
+        decay_time: 0.8,
+        cutoff_frequency: 0.25615,
+    };
+
+    /// Large hall with long, bright reflections.
+    pub const HALL: Self = Self {
+        level: 0.8,
+        dry: 1.0,
+        wet: 1.0,
+        decay_time: 3.0,
+        cutoff_frequency: 0.3,
+    };
+
+    /// Cave with very long, muffled reflections.
+    pub const CAVE: Self = Self {
+        level: 1.0,
+        dry: 0.8,
+        wet: 1.0,
+        decay_time: 5.0,
+        cutoff_frequency: 0.1,
+    };
+
+    /// Open field with almost no reflections.
+    pub const OPEN_FIELD: Self = Self {
+        level: 0.1,
+        dry: 1.0,
+        wet: 1.0,
+        decay_time: 0.3,
+        cutoff_frequency: 0.2,
+    };
+
+    /// Returns the same parameters, but with zero level (no reverberation).
+    pub fn silent(self) -> Self {
+        Self { level: 0.0, ..self }
+    }
+
+    /// Linearly interpolates the parameters.
+    pub fn lerp(&self, other: &Self, t: f32) -> Self {
+        Self {
+            level: lerpf(self.level, other.level, t),
+            dry: lerpf(self.dry, other.dry, t),
+            wet: lerpf(self.wet, other.wet, t),
+            decay_time: lerpf(self.decay_time, other.decay_time, t),
+            cutoff_frequency: lerpf(self.cutoff_frequency, other.cutoff_frequency, t),
+        }
+    }
+
+    /// Applies the parameters to the given reverb effect.
+    pub fn apply(&self, reverb: &mut Reverb) {
+        reverb.set_level(self.level);
+        reverb.set_dry(self.dry);
+        reverb.set_wet(self.wet);
+        // Changing these parameters recalculates the filters of the reverb, so do it only if
+        // needed.
+        if (reverb.decay_time() - self.decay_time).abs() > 1.0e-4 {
+            reverb.set_decay_time(self.decay_time);
+        }
+        if (reverb.fc() - self.cutoff_frequency).abs() > 1.0e-5 {
+            reverb.set_fc(self.cutoff_frequency);
+        }
+    }
+}
+
+/// Contribution of a reverb zone to the acoustics at some point.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ReverbZoneContribution {
+    /// Priority of the zone.
+    pub priority: i32,
+    /// Weight of the zone in `[0; 1]` range.
+    pub weight: f32,
+    /// Parameters of the zone.
+    pub parameters: ReverbParameters,
+}
+
+/// Blends contributions of reverb zones. Zones with higher priority are layered on top of the
+/// zones with lower priority, proportionally to their weight. This way a zone fully overrides all
+/// the zones with lower priority, when the point is deep enough inside it, and smoothly fades into
+/// them near its border. Parameters of the zones that have the same priority are blended in the
+/// order of the contributions. Returns `None` if there are no contributing zones.
+pub fn blend_reverb_zones(
+    mut contributions: Vec<ReverbZoneContribution>,
+) -> Option<ReverbParameters> {
+    // Stable sort keeps the order of the zones with the same priority.
+    contributions.sort_by_key(|contribution| contribution.priority);
+
+    let mut result: Option<ReverbParameters> = None;
+    for contribution in contributions {
+        if contribution.weight <= 0.0 {
+            continue;
+        }
+
+        let base = result.unwrap_or_else(|| contribution.parameters.silent());
+        result = Some(base.lerp(&contribution.parameters, contribution.weight.min(1.0)));
+    }
+    result
+}
+
+/// Reverb zone is a box-shaped volume, that defines acoustics of some area of a scene (caves,
+/// halls, open fields, etc.). When the listener is inside the zone, the parameters of the zone
+/// are applied to the reverb effect of the audio bus of the zone (the effect is added to the bus
+/// automatically, if there's none). This removes the need to change effect parameters manually
+/// in scripts.
+///
+/// ## Blending
+///
+/// The influence of a zone fades out towards its borders (see [`ReverbZone::fade_distance`]), so
+/// the acoustics change smoothly when the listener moves between zones. Overlapping zones are
+/// blended according to their priorities - a zone with higher priority is layered on top of the
+/// zones with lower priority (see [`blend_reverb_zones`]). For example, a small room inside a
+/// large cave should have higher priority than the cave. Additionally, the resulting parameters
+/// change smoothly over time (see [`crate::scene::sound::context::SoundContext::set_reverb_transition_time`]),
+/// to hide abrupt changes when the listener teleports or the zones are enabled/disabled.
+///
+/// A zone with zero size is global - it affects the entire scene. Such zones are useful to define
+/// the "default" acoustics (for example, an open field) with the lowest priority.
+///
+/// The size of the zone is defined in the local coordinates of the node, so the zone can be
+/// rotated and scaled as any other node.
+#[derive(Clone, Reflect, Visit, Debug, ComponentProvider)]
+#[visit(optional)]
+pub struct ReverbZone {
+    base: Base,
+    /// Name of the audio bus, which reverb effect will be controlled by the zone.
+    pub audio_bus: InheritableVariable<String>,
+    /// Half size of the zone box along each axis. Zero size means that the zone is global.
+    pub half_extents: InheritableVariable<Vector3<f32>>,
+    /// Distance from the border of the zone to the inside, over which the influence of the zone
+    /// fades in. Zero distance means that there is no fading.
+    #[reflect(min_value = 0.0)]
+    pub fade_distance: InheritableVariable<f32>,
+    /// Priority of the zone. Zones with higher priority override zones with lower priority.
+    pub priority: InheritableVariable<i32>,
+    /// Parameters of the reverb effect inside the zone.
+    pub parameters: InheritableVariable<ReverbParameters>,
+}
+
+impl Default for ReverbZone {
+    fn default() -> Self {
+        ReverbZoneBuilder::new(BaseBuilder::new()).build_reverb_zone()
+    }
+}
+
+impl Deref for ReverbZone {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for ReverbZone {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for ReverbZone {
+    fn type_uuid() -> Uuid {
+        uuid!("3f1e7a52-8c4d-4b6e-a9d0-5b2c7e8f9a13")
+    }
+}
+
+impl ConstructorProvider<Node, Graph> for ReverbZone {
+    fn constructor() -> NodeConstructor {
+        NodeConstructor::new::<Self>()
+            .with_variant("Reverb Zone", |_| {
+                ReverbZoneBuilder::new(BaseBuilder::new().with_name("Reverb Zone"))
+                    .build_node()
+                    .into()
+            })
+            .with_group("Sound")
+    }
+}
+
+impl ReverbZone {
+    /// Returns `true` if the zone affects the entire scene.
+    pub fn is_global(&self) -> bool {
+        *self.half_extents == Vector3::zeros()
+    }
+
+    /// Returns weight (in `[0; 1]` range) of the zone at the given point in world coordinates.
+    pub fn weight_at(&self, position: Vector3<f32>) -> f32 {
+        if self.is_global() {
+            return 1.0;
+        }
+
+        let Some(inv_transform) = self.global_transform().try_inverse() else {
+            return 0.0;
+        };
+        let local = inv_transform
+            .transform_point(&Point3::from(position))
+            .coords;
+
+        // Distance to the closest face of the box, positive inside the box.
+        let depth = (0..3)
+            .map(|i| self.half_extents[i] - local[i].abs())
+            .fold(f32::MAX, f32::min);
+        if depth <= 0.0 {
+            0.0
+        } else if *self.fade_distance <= 0.0 {
+            1.0
+        } else {
+            (depth / *self.fade_distance).min(1.0)
+        }
+    }
+
+    /// Returns contribution of the zone at the given point in world coordinates.
+    pub fn contribution_at(&self, position: Vector3<f32>) -> ReverbZoneContribution {
+        ReverbZoneContribution {
+            priority: *self.priority,
+            weight: self.weight_at(position),
+            parameters: *self.parameters,
+        }
+    }
+}
+
+impl NodeTrait for ReverbZone {
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        if self.is_global() {
+            self.base.local_bounding_box()
+        } else {
+            AxisAlignedBoundingBox::from_min_max(-*self.half_extents, *self.half_extents)
+        }
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.local_bounding_box()
+            .transform(&self.global_transform())
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
+
+/// Reverb zone builder allows you to create [`ReverbZone`] nodes in declarative manner.
+pub struct ReverbZoneBuilder {
+    base_builder: BaseBuilder,
+    audio_bus: String,
+    half_extents: Vector3<f32>,
+    fade_distance: f32,
+    priority: i32,
+    parameters: ReverbParameters,
+}
+
+impl ReverbZoneBuilder {
+    /// Creates a new reverb zone builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            audio_bus: AudioBusGraph::PRIMARY_BUS.to_string(),
+            half_extents: Vector3::repeat(5.0),
+            fade_distance: 1.0,
+            priority: 0,
+            parameters: Default::default(),
+        }
+    }
+
+    /// Sets the desired audio bus.
+    pub fn with_audio_bus(mut self, audio_bus: String) -> Self {
+        self.audio_bus = audio_bus;
+        self
+    }
+
+    /// Sets the desired half size of the zone. Zero size makes the zone global.
+    pub fn with_half_extents(mut self, half_extents: Vector3<f32>) -> Self {
+        self.half_extents = half_extents;
+        self
+    }
+
+    /// Sets the desired fade distance.
+    pub fn with_fade_distance(mut self, fade_distance: f32) -> Self {
+        self.fade_distance = fade_distance;
+        self
+    }
+
+    /// Sets the desired priority.
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Sets the desired reverb parameters.
+    pub fn with_parameters(mut self, parameters: ReverbParameters) -> Self {
+        self.parameters = parameters;
+        self
+    }
+
+    /// Creates the reverb zone, but does not add it to a graph.
+    pub fn build_reverb_zone(self) -> ReverbZone {
+        ReverbZone {
+            base: self.base_builder.build_base(),
+            audio_bus: self.audio_bus.into(),
+            half_extents: self.half_extents.into(),
+            fade_distance: self.fade_distance.into(),
+            priority: self.priority.into(),
+            parameters: self.parameters.into(),
+        }
+    }
+
+    /// Creates reverb zone node, but does not add it to a graph.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_reverb_zone())
+    }
+
+    /// Creates reverb zone node and adds it to the graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn contribution(priority: i32, weight: f32, level: f32) -> ReverbZoneContribution {
+        ReverbZoneContribution {
+            priority,
+            weight,
+            parameters: ReverbParameters {
+                level,
+                ..ReverbParameters::HALL
+            },
+        }
+    }
+
+    #[test]
+    fn reverb_zone_weight() {
+        let zone = ReverbZoneBuilder::new(BaseBuilder::new())
+            .with_half_extents(Vector3::new(4.0, 2.0, 4.0))
+            .with_fade_distance(1.0)
+            .build_reverb_zone();
+
+        assert_eq!(zone.weight_at(Vector3::zeros()), 1.0);
+        assert_eq!(zone.weight_at(Vector3::new(3.5, 0.0, 0.0)), 0.5);
+        assert_eq!(zone.weight_at(Vector3::new(0.0, 3.0, 0.0)), 0.0);
+
+        let global = ReverbZoneBuilder::new(BaseBuilder::new())
+            .with_half_extents(Vector3::zeros())
+            .build_reverb_zone();
+        assert!(global.is_global());
+        assert_eq!(global.weight_at(Vector3::new(100.0, 0.0, 0.0)), 1.0);
+    }
+
+    #[test]
+    fn reverb_zone_blending() {
+        assert_eq!(blend_reverb_zones(vec![]), None);
+        assert_eq!(blend_reverb_zones(vec![contribution(0, 0.0, 1.0)]), None);
+
+        // A single zone fades in from silence.
+        let result = blend_reverb_zones(vec![contribution(0, 0.5, 1.0)]).unwrap();
+        assert_eq!(result.level, 0.5);
+
+        // Higher priority overrides lower priority regardless of the order.
+        let result =
+            blend_reverb_zones(vec![contribution(1, 1.0, 0.2), contribution(0, 1.0, 1.0)]).unwrap();
+        assert_eq!(result.level, 0.2);
+
+        // Partially faded high priority zone is blended with the low priority one.
+        let result =
+            blend_reverb_zones(vec![contribution(1, 0.5, 0.2), contribution(0, 1.0, 1.0)]).unwrap();
+        assert!((result.level - 0.6).abs() < 1.0e-6);
+    }
+}
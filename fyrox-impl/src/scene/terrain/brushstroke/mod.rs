@@ -38,7 +38,7 @@
 //! values are actually written to the textures of the terrain.
 //! [StrokeChunks] is used to keep track of which pixels are waiting to be written
 //! to which terrain chunks.
-use super::Chunk;
+use super::{Chunk, Terrain};
 use crate::asset::ResourceDataRef;
 use crate::core::{
     algebra::{Matrix2, Vector2},
@@ -236,6 +236,58 @@ pub struct UndoData {
     pub target: BrushTarget,
 }
 
+/// A set of changes that was made to a terrain by a brushstroke. It stores the data of the chunks
+/// as they were before the stroke, and it can be used to undo and redo the stroke by calling
+/// [TerrainChangeSet::swap].
+#[derive(Debug)]
+pub struct TerrainChangeSet {
+    /// The handle of the terrain that was edited.
+    pub node: Handle<Node>,
+    /// The kind of data within the terrain that was edited.
+    pub target: BrushTarget,
+    /// The data of the modified chunks. Initially this is the data before the stroke,
+    /// but after each call of [TerrainChangeSet::swap] this is replaced with the data that
+    /// was in the terrain.
+    pub chunks: Vec<ChunkData>,
+}
+
+impl From<UndoData> for TerrainChangeSet {
+    fn from(data: UndoData) -> Self {
+        Self {
+            node: data.node,
+            target: data.target,
+            chunks: data.chunks,
+        }
+    }
+}
+
+impl TerrainChangeSet {
+    /// Exchanges the data stored in this change set with the corresponding data in the
+    /// given terrain. The first call undoes the changes, the second call redoes them, and so on.
+    pub fn swap(&mut self, terrain: &mut Terrain) {
+        let current_chunks = terrain.chunks_mut();
+        match self.target {
+            BrushTarget::HeightMap => {
+                for c in self.chunks.iter_mut() {
+                    c.swap_height_from_list(current_chunks);
+                }
+                terrain.update_quad_trees();
+            }
+            BrushTarget::HoleMask => {
+                for c in self.chunks.iter_mut() {
+                    c.swap_holes_from_list(current_chunks);
+                }
+                terrain.update_quad_trees();
+            }
+            BrushTarget::LayerMask { layer } => {
+                for c in self.chunks.iter_mut() {
+                    c.swap_layer_mask_from_list(current_chunks, layer);
+                }
+            }
+        }
+    }
+}
+
 #[derive(Default)]
 /// Data for an in-progress terrain painting operation
 pub struct BrushStroke {
@@ -293,6 +345,11 @@ impl BrushStroke {
             ..Default::default()
         }
     }
+    /// Replace the handler for saving undo data for chunks. `None` disables the saving
+    /// of the original data of chunks, which makes strokes slightly cheaper.
+    pub fn set_chunk_handler(&mut self, undo_chunk_handler: Option<Box<UndoChunkHandler>>) {
+        self.undo_chunk_handler = undo_chunk_handler;
+    }
     /// The brush that this stroke is using. This is immutable access only, because
     /// the brush's target may only be changed through [BrushStroke::start_stroke] or
     /// [BrushStroke::accept_messages].
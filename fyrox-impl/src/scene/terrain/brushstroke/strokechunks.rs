@@ -23,10 +23,30 @@
 //! the changes were written to the terrain's textures.
 use super::{ChunkData, StrokeData, TerrainTextureKind};
 use crate::core::algebra::Vector2;
+use crate::core::math::Rect;
 use crate::fxhash::{FxHashMap, FxHashSet};
 use crate::resource::texture::TextureResource;
 use crate::scene::terrain::pixel_position_to_grid_position;
 
+/// Calculates the smallest rectangle that contains all of the given pixels,
+/// or `None` if there are no pixels.
+fn bounding_rect(pixels: &FxHashSet<Vector2<u32>>) -> Option<Rect<u32>> {
+    let mut iter = pixels.iter();
+    let first = iter.next()?;
+    let (min, max) = iter.fold((*first, *first), |(min, max), p| {
+        (
+            Vector2::new(min.x.min(p.x), min.y.min(p.y)),
+            Vector2::new(max.x.max(p.x), max.y.max(p.y)),
+        )
+    });
+    Some(Rect::new(
+        min.x,
+        min.y,
+        max.x - min.x + 1,
+        max.y - min.y + 1,
+    ))
+}
+
 /// The list of modified pixels in each chunk.
 #[derive(Debug, Default)]
 pub struct StrokeChunks {
@@ -114,8 +134,12 @@ impl StrokeChunks {
             let Some(texture) = textures.get(c) else {
                 continue;
             };
+            let Some(region) = bounding_rect(pxs) else {
+                continue;
+            };
             let mut texture_data = texture.data_ref();
-            let mut modify = texture_data.modify();
+            // Modify only the written region, so the renderer could upload just this region.
+            let mut modify = texture_data.modify_region(region);
             let Some(data) = modify.data_mut_of_type::<V>() else {
                 continue;
             };
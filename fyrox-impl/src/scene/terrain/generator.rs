@@ -0,0 +1,276 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Procedural generation of terrain height maps. See [`HeightMapProvider`] docs for more info.
+
+use crate::{
+    core::{algebra::Vector2, log::Log},
+    scene::terrain::Terrain,
+};
+use std::sync::{
+    mpsc::{self, Receiver, TryRecvError},
+    Arc,
+};
+
+/// Describes a height map of a single chunk that should be generated by a [`HeightMapProvider`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct HeightMapRequest {
+    /// The grid position of the chunk.
+    pub grid_position: Vector2<i32>,
+    /// The size of the height map in pixels. It includes the margins of the height map, that
+    /// overlap with the neighbouring chunks.
+    pub size: Vector2<u32>,
+    /// The position of the first pixel (top-left) of the height map, measured in height pixel
+    /// coordinates of the terrain. Use it to make the neighbouring chunks seamless.
+    pub origin: Vector2<i32>,
+    /// The distance between two adjacent pixels of the height map in local coordinates of the
+    /// terrain.
+    pub cell_size: Vector2<f32>,
+}
+
+impl HeightMapRequest {
+    /// Calculates the position of the pixel with the given index in the local 2D coordinates of
+    /// the terrain. It could be used to sample a noise function, for example.
+    pub fn local_position(&self, index: usize) -> Vector2<f32> {
+        let x = self.origin.x + (index % self.size.x as usize) as i32;
+        let y = self.origin.y + (index / self.size.x as usize) as i32;
+        Vector2::new(x as f32 * self.cell_size.x, y as f32 * self.cell_size.y)
+    }
+}
+
+/// Height map provider is a source of procedurally generated height maps for terrain chunks.
+/// It is used by [`Terrain::generate_height_maps`], which runs the provider on a separate thread
+/// (on platforms that support threads), so the provider could do heavy computations without
+/// stalling the game loop.
+///
+/// ## Example
+///
+/// ```rust
+/// use fyrox_impl::scene::terrain::{HeightMapProvider, HeightMapRequest};
+///
+/// struct Waves;
+///
+/// impl HeightMapProvider for Waves {
+///     fn generate(&self, request: &HeightMapRequest, heights: &mut [f32]) {
+///         for (index, height) in heights.iter_mut().enumerate() {
+///             let position = request.local_position(index);
+///             *height = position.x.sin() * position.y.cos();
+///         }
+///     }
+/// }
+/// ```
+pub trait HeightMapProvider: Send + Sync {
+    /// Fills the given height map of a chunk. `heights` is a row-major array of
+    /// `request.size.x * request.size.y` pixels, that contains the current heights of the chunk.
+    fn generate(&self, request: &HeightMapRequest, heights: &mut [f32]);
+}
+
+struct GeneratedHeightMap {
+    request: HeightMapRequest,
+    heights: Vec<f32>,
+}
+
+/// A handle to the height map generation, started by [`Terrain::generate_height_maps`]. It must be
+/// polled periodically (for example, once per frame) to write the generated height maps to the
+/// terrain.
+pub struct HeightMapGenerationTask {
+    receiver: Receiver<GeneratedHeightMap>,
+    remaining: usize,
+}
+
+impl HeightMapGenerationTask {
+    /// Writes all the height maps, that were generated since the last call, to the given terrain.
+    /// The height maps are written only to the chunks that have the same grid position and the
+    /// same height map size as they had when the generation started. Returns the number of updated
+    /// chunks.
+    pub fn poll(&mut self, terrain: &mut Terrain) -> usize {
+        let mut count = 0;
+        while self.remaining > 0 {
+            match self.receiver.try_recv() {
+                Ok(height_map) => {
+                    self.remaining -= 1;
+                    if write_height_map(terrain, height_map) {
+                        count += 1;
+                    }
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    Log::err("Height map generation was terminated unexpectedly!");
+                    self.remaining = 0;
+                }
+            }
+        }
+        count
+    }
+
+    /// Blocks current thread until all the height maps are generated and writes them to the given
+    /// terrain. Returns the number of updated chunks.
+    pub fn wait(mut self, terrain: &mut Terrain) -> usize {
+        let mut count = 0;
+        while self.remaining > 0 {
+            let Ok(height_map) = self.receiver.recv() else {
+                Log::err("Height map generation was terminated unexpectedly!");
+                break;
+            };
+            self.remaining -= 1;
+            if write_height_map(terrain, height_map) {
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Returns `true` if all the height maps were generated and written to the terrain.
+    pub fn is_finished(&self) -> bool {
+        self.remaining == 0
+    }
+}
+
+fn write_height_map(terrain: &mut Terrain, height_map: GeneratedHeightMap) -> bool {
+    let GeneratedHeightMap { request, heights } = height_map;
+    let Some(chunk) = terrain
+        .chunks_mut()
+        .iter_mut()
+        .find(|c| c.grid_position() == request.grid_position)
+    else {
+        return false;
+    };
+    if chunk.height_map_size() != request.size {
+        return false;
+    }
+    let mut heightmap = chunk.heightmap().data_ref();
+    let mut modify = heightmap.modify();
+    let Some(data) = modify.data_mut_of_type::<f32>() else {
+        return false;
+    };
+    data.copy_from_slice(&heights);
+    drop(modify);
+    drop(heightmap);
+    chunk.update_quad_tree();
+    true
+}
+
+impl Terrain {
+    /// Starts procedural generation of the height maps of the chunks with the given grid positions,
+    /// using the given provider. The generation is performed on a separate thread (except on
+    /// WebAssembly, where it is performed immediately) and the results must be written to the
+    /// terrain using [`HeightMapGenerationTask::poll`]. Use
+    /// `terrain.chunks_ref().iter().map(|c| c.grid_position())` to regenerate the entire terrain.
+    pub fn generate_height_maps(
+        &self,
+        provider: Arc<dyn HeightMapProvider>,
+        grid_positions: impl IntoIterator<Item = Vector2<i32>>,
+    ) -> HeightMapGenerationTask {
+        let cell_size = self.height_grid_scale();
+        let requests = grid_positions
+            .into_iter()
+            .filter_map(|grid_position| {
+                let chunk = self.find_chunk(grid_position)?;
+                let size = chunk.height_map_size();
+                let heights = chunk.heightmap().data_ref().data_of_type::<f32>()?.to_vec();
+                // Height maps have a one pixel margin around the area, that is used to calculate
+                // normals, and the distance between chunk origins excludes all the margins.
+                let spacing = size.map(|v| v as i32 - 3);
+                let origin = Vector2::new(
+                    grid_position.x * spacing.x - 1,
+                    grid_position.y * spacing.y - 1,
+                );
+                Some(GeneratedHeightMap {
+                    request: HeightMapRequest {
+                        grid_position,
+                        size,
+                        origin,
+                        cell_size,
+                    },
+                    heights,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let (sender, receiver) = mpsc::channel();
+        let remaining = requests.len();
+
+        let generate = move || {
+            for mut height_map in requests {
+                provider.generate(&height_map.request, &mut height_map.heights);
+                if sender.send(height_map).is_err() {
+                    // The task was dropped, there's no need to continue.
+                    break;
+                }
+            }
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            std::thread::spawn(generate);
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            generate();
+        }
+
+        HeightMapGenerationTask {
+            receiver,
+            remaining,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::scene::{base::BaseBuilder, terrain::TerrainBuilder};
+
+    struct Slope;
+
+    impl HeightMapProvider for Slope {
+        fn generate(&self, request: &HeightMapRequest, heights: &mut [f32]) {
+            for (index, height) in heights.iter_mut().enumerate() {
+                *height = (request.origin.x + (index % request.size.x as usize) as i32) as f32;
+            }
+        }
+    }
+
+    #[test]
+    fn test_height_map_generation() {
+        let mut node = TerrainBuilder::new(BaseBuilder::new())
+            .with_width_chunks(0..2)
+            .with_length_chunks(0..1)
+            .with_height_map_size(Vector2::new(7, 7))
+            .build_node();
+        let terrain = node.as_terrain_mut();
+
+        let grid_positions = terrain
+            .chunks_ref()
+            .iter()
+            .map(|c| c.grid_position())
+            .collect::<Vec<_>>();
+        let task = terrain.generate_height_maps(Arc::new(Slope), grid_positions);
+        assert_eq!(task.wait(terrain), 2);
+
+        // The overlapping pixels of neighbouring chunks must have the same heights.
+        let first = terrain.chunks_ref()[0].heightmap_owned();
+        let second = terrain.chunks_ref()[1].heightmap_owned();
+        assert_eq!(first[0], -1.0);
+        assert_eq!(first[5], second[1]);
+        assert_eq!(second[0], 3.0);
+    }
+}
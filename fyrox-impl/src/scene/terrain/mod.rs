@@ -70,15 +70,18 @@ use std::{
     cmp::Ordering,
     collections::HashMap,
     ops::{Deref, DerefMut, Range},
+    sync::Arc,
 };
 
 pub mod brushstroke;
+mod generator;
 mod geometry;
 mod quadtree;
 
 use crate::scene::node::constructor::NodeConstructor;
 pub use brushstroke::*;
 use fyrox_graph::constructor::ConstructorProvider;
+pub use generator::*;
 
 /// Current implementation version marker.
 pub const VERSION: u8 = 2;
@@ -853,9 +856,54 @@ pub struct BrushContext {
     pub value: Option<f32>,
     /// The pixel and brush data of the in-progress stroke.
     pub stroke: BrushStroke,
+    /// The changes made by finished strokes, if change recording is enabled.
+    changes: Option<Arc<Mutex<Vec<UndoData>>>>,
 }
 
 impl BrushContext {
+    /// Creates a new BrushContext that records the changes made by each stroke.
+    /// The changes can be taken using [BrushContext::take_changes] and then used
+    /// to undo or redo strokes.
+    pub fn with_change_recording() -> Self {
+        let mut context = Self::default();
+        context.set_change_recording(true);
+        context
+    }
+    /// Enables or disables recording of the changes made by each stroke. Disabling
+    /// the recording discards all the changes that were not taken yet.
+    pub fn set_change_recording(&mut self, enabled: bool) {
+        if enabled == self.changes.is_some() {
+            return;
+        }
+        if enabled {
+            let changes = Arc::new(Mutex::new(Vec::new()));
+            let sender = changes.clone();
+            self.stroke
+                .set_chunk_handler(Some(Box::new(move |data| sender.lock().push(data))));
+            self.changes = Some(changes);
+        } else {
+            self.stroke.set_chunk_handler(None);
+            self.changes = None;
+        }
+    }
+    /// Returns `true` if the changes of each stroke are being recorded.
+    pub fn is_recording_changes(&self) -> bool {
+        self.changes.is_some()
+    }
+    /// Takes the changes that were made by all the strokes finished since the last call
+    /// of this method. The changes are stored in the order of the strokes. Returns an empty
+    /// list if change recording is disabled.
+    pub fn take_changes(&mut self) -> Vec<TerrainChangeSet> {
+        self.changes
+            .as_ref()
+            .map(|changes| {
+                std::mem::take(&mut *changes.lock())
+                    .into_iter()
+                    .map(TerrainChangeSet::from)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
     /// The current brush. This is immutable access only, because
     /// the brush's target may only be changed through [BrushContext::start_stroke].
     ///
@@ -2683,6 +2731,8 @@ impl NodeTrait for Terrain {
                                 blend_shapes_weights: Default::default(),
                                 element_range: ElementRange::Full,
                                 node_handle: self.handle(),
+                                custom_data: Default::default(),
+                                lod_fade: ctx.lod_fade,
                             },
                         );
                     } else {
@@ -2699,6 +2749,8 @@ impl NodeTrait for Terrain {
                                         blend_shapes_weights: Default::default(),
                                         element_range: self.geometry.quadrants[i],
                                         node_handle: self.handle(),
+                                        custom_data: Default::default(),
+                                        lod_fade: ctx.lod_fade,
                                     },
                                 );
                             }
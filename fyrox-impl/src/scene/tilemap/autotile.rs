@@ -0,0 +1,403 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Auto-tiling chooses tiles automatically based on their neighbours, so that the
+//! edges and corners of an area of terrain are drawn using the matching tiles without
+//! picking each tile by hand. An [`AutoTileRule`] lists the tiles of some terrain together
+//! with the arrangement of neighbours that each tile is meant for.
+//!
+//! The arrangement of neighbours is stored as a bit mask with one bit for each of the
+//! 8 neighbours of a cell, see [`AutoTileRule::NORTH`] and the other direction constants.
+//! A bit is set when the neighbour belongs to the same terrain. The positive y-axis points north.
+//!
+//! Rules are applied using [`TilesUpdate::auto_tile`], [`TilesUpdate::auto_erase`] and
+//! [`TilesUpdate::apply_auto_tile_rules`], which add the modified neighbours to the update so that
+//! the whole change can be applied to the tiles at once.
+
+use crate::core::{algebra::Vector2, reflect::prelude::*, visitor::prelude::*};
+use fxhash::FxHashSet;
+
+use super::*;
+
+/// The neighbours that an [`AutoTileRule`] takes into account.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Visit, Reflect)]
+pub enum AutoTileKind {
+    /// All 8 neighbours are taken into account, but a corner neighbour only matters when both
+    /// of the edge neighbours next to it belong to the terrain. This leaves 47 distinct masks,
+    /// which is the common "blob" tile set layout.
+    #[default]
+    Blob47,
+    /// Only the 4 edge neighbours are taken into account, which leaves 16 distinct masks.
+    Wang16,
+}
+
+impl AutoTileKind {
+    /// Remove the bits of the given neighbour mask that do not matter for this kind of rule.
+    pub fn reduce_mask(self, mask: u8) -> u8 {
+        let edges = mask & AutoTileRule::EDGES;
+        match self {
+            AutoTileKind::Wang16 => edges,
+            AutoTileKind::Blob47 => {
+                let mut result = edges;
+                for (corner, first, second) in [
+                    (
+                        AutoTileRule::NORTH_EAST,
+                        AutoTileRule::NORTH,
+                        AutoTileRule::EAST,
+                    ),
+                    (
+                        AutoTileRule::SOUTH_EAST,
+                        AutoTileRule::SOUTH,
+                        AutoTileRule::EAST,
+                    ),
+                    (
+                        AutoTileRule::SOUTH_WEST,
+                        AutoTileRule::SOUTH,
+                        AutoTileRule::WEST,
+                    ),
+                    (
+                        AutoTileRule::NORTH_WEST,
+                        AutoTileRule::NORTH,
+                        AutoTileRule::WEST,
+                    ),
+                ] {
+                    if mask & corner != 0 && mask & first != 0 && mask & second != 0 {
+                        result |= corner;
+                    }
+                }
+                result
+            }
+        }
+    }
+    /// All the distinct masks that a rule of this kind may need a tile for, in ascending order.
+    /// There are 47 masks for [`AutoTileKind::Blob47`] and 16 masks for [`AutoTileKind::Wang16`].
+    pub fn all_masks(self) -> Vec<u8> {
+        let mut masks = (0..=u8::MAX)
+            .map(|mask| self.reduce_mask(mask))
+            .collect::<Vec<_>>();
+        masks.sort_unstable();
+        masks.dedup();
+        masks
+    }
+}
+
+/// A tile of an [`AutoTileRule`] along with the neighbour mask it should be used for.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct AutoTileVariant {
+    /// The neighbour mask, see [`AutoTileRule::NORTH`] and the other direction constants.
+    pub mask: u8,
+    /// The tile to use for cells with the given mask.
+    pub tile: TileDefinitionHandle,
+}
+
+/// A terrain that chooses its tiles based on which of the neighbouring cells belong to the same terrain.
+/// A cell belongs to the terrain if its tile is any of the tiles of the rule.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct AutoTileRule {
+    /// The name of the terrain.
+    pub name: String,
+    /// The neighbours that are taken into account.
+    pub kind: AutoTileKind,
+    /// The tiles of the terrain along with their masks.
+    pub variants: Vec<AutoTileVariant>,
+    /// The tile to use when there is no variant for the mask of a cell.
+    pub fallback: Option<TileDefinitionHandle>,
+}
+
+impl AutoTileRule {
+    /// The bit of the neighbour with the offset (0, 1).
+    pub const NORTH: u8 = 1;
+    /// The bit of the neighbour with the offset (1, 1).
+    pub const NORTH_EAST: u8 = 2;
+    /// The bit of the neighbour with the offset (1, 0).
+    pub const EAST: u8 = 4;
+    /// The bit of the neighbour with the offset (1, -1).
+    pub const SOUTH_EAST: u8 = 8;
+    /// The bit of the neighbour with the offset (0, -1).
+    pub const SOUTH: u8 = 16;
+    /// The bit of the neighbour with the offset (-1, -1).
+    pub const SOUTH_WEST: u8 = 32;
+    /// The bit of the neighbour with the offset (-1, 0).
+    pub const WEST: u8 = 64;
+    /// The bit of the neighbour with the offset (-1, 1).
+    pub const NORTH_WEST: u8 = 128;
+    /// The bits of the 4 edge neighbours.
+    pub const EDGES: u8 = Self::NORTH | Self::EAST | Self::SOUTH | Self::WEST;
+    /// The offsets of the neighbours, in the order of their bits.
+    pub const NEIGHBOURS: [Vector2<i32>; 8] = [
+        Vector2::new(0, 1),
+        Vector2::new(1, 1),
+        Vector2::new(1, 0),
+        Vector2::new(1, -1),
+        Vector2::new(0, -1),
+        Vector2::new(-1, -1),
+        Vector2::new(-1, 0),
+        Vector2::new(-1, 1),
+    ];
+
+    /// Creates a new rule without tiles.
+    pub fn new<S: Into<String>>(name: S, kind: AutoTileKind) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+            variants: Vec::new(),
+            fallback: None,
+        }
+    }
+    /// Adds a tile for the given neighbour mask.
+    pub fn with_variant(mut self, mask: u8, tile: TileDefinitionHandle) -> Self {
+        self.variants.push(AutoTileVariant { mask, tile });
+        self
+    }
+    /// Sets the tile to use when there is no variant for the mask of a cell.
+    pub fn with_fallback(mut self, tile: TileDefinitionHandle) -> Self {
+        self.fallback = Some(tile);
+        self
+    }
+    /// True if the given tile is one of the tiles of this rule.
+    pub fn contains(&self, tile: TileDefinitionHandle) -> bool {
+        self.fallback == Some(tile) || self.variants.iter().any(|v| v.tile == tile)
+    }
+    /// Any tile of this rule, or None if the rule has no tiles.
+    pub fn any_tile(&self) -> Option<TileDefinitionHandle> {
+        self.variants.first().map(|v| v.tile).or(self.fallback)
+    }
+    /// Calculates the neighbour mask of the cell at the given position, using the given
+    /// function to check whether a cell belongs to the terrain.
+    pub fn neighbour_mask<F>(&self, position: Vector2<i32>, mut is_terrain: F) -> u8
+    where
+        F: FnMut(Vector2<i32>) -> bool,
+    {
+        let mut mask = 0;
+        for (i, offset) in Self::NEIGHBOURS.iter().enumerate() {
+            if is_terrain(position + offset) {
+                mask |= 1 << i;
+            }
+        }
+        self.kind.reduce_mask(mask)
+    }
+    /// The tile that should be used for a cell with the given neighbour mask. If there is no variant
+    /// for the exact mask, the variant that matches only the edge neighbours is used, and if there is
+    /// no such variant either, then the fallback tile is used.
+    pub fn tile_for_mask(&self, mask: u8) -> Option<TileDefinitionHandle> {
+        let mask = self.kind.reduce_mask(mask);
+        let find = |mask: u8| {
+            self.variants
+                .iter()
+                .find(|v| v.mask == mask)
+                .map(|v| v.tile)
+        };
+        find(mask)
+            .or_else(|| find(mask & Self::EDGES))
+            .or(self.fallback)
+    }
+}
+
+impl TilesUpdate {
+    /// Paints the given positions with the terrain of the given rule, choosing the tiles
+    /// of the painted cells and of their neighbours that belong to the same terrain.
+    /// The current tiles are taken from the given source, and tiles that are already in
+    /// this update take priority over them.
+    pub fn auto_tile<S, I>(&mut self, tiles: &S, rule: &AutoTileRule, positions: I)
+    where
+        S: TileSource,
+        I: IntoIterator<Item = Vector2<i32>>,
+    {
+        let Some(tile) = rule.any_tile() else {
+            return;
+        };
+        let mut affected = FxHashSet::default();
+        for position in positions {
+            self.insert(position, Some(tile));
+            add_with_neighbours(&mut affected, position);
+        }
+        self.refresh_auto_tiles(tiles, rule, affected);
+    }
+    /// Erases the given positions and updates the tiles of the neighbours that belong
+    /// to the terrain of the given rule.
+    pub fn auto_erase<S, I>(&mut self, tiles: &S, rule: &AutoTileRule, positions: I)
+    where
+        S: TileSource,
+        I: IntoIterator<Item = Vector2<i32>>,
+    {
+        let mut affected = FxHashSet::default();
+        for position in positions {
+            self.insert(position, None);
+            add_with_neighbours(&mut affected, position);
+        }
+        self.refresh_auto_tiles(tiles, rule, affected);
+    }
+    /// Finds the cells of this update where a tile of one of the given rules is drawn or erased,
+    /// and chooses the tiles of those cells and of their neighbours according to the rules.
+    /// This allows auto-tiling to work with any other way of drawing tiles.
+    pub fn apply_auto_tile_rules<S: TileSource>(&mut self, tiles: &S, rules: &[AutoTileRule]) {
+        for rule in rules {
+            let mut affected = FxHashSet::default();
+            for (position, value) in self.iter() {
+                let is_drawn = value.is_some_and(|h| rule.contains(h));
+                let is_erased =
+                    !is_drawn && tiles.get_at(*position).is_some_and(|h| rule.contains(h));
+                if is_drawn || is_erased {
+                    add_with_neighbours(&mut affected, *position);
+                }
+            }
+            if !affected.is_empty() {
+                self.refresh_auto_tiles(tiles, rule, affected);
+            }
+        }
+    }
+    fn refresh_auto_tiles<S: TileSource>(
+        &mut self,
+        tiles: &S,
+        rule: &AutoTileRule,
+        positions: FxHashSet<Vector2<i32>>,
+    ) {
+        let update: &TilesUpdate = self;
+        let is_terrain = |position: Vector2<i32>| {
+            let tile = match update.get(&position) {
+                Some(value) => *value,
+                None => tiles.get_at(position),
+            };
+            tile.is_some_and(|h| rule.contains(h))
+        };
+        // Choosing a tile for a cell never changes whether the cell belongs to the terrain,
+        // so all the tiles can be chosen before any of them is written.
+        let chosen = positions
+            .into_iter()
+            .filter(|p| is_terrain(*p))
+            .filter_map(|p| {
+                let mask = rule.neighbour_mask(p, is_terrain);
+                rule.tile_for_mask(mask).map(|tile| (p, tile))
+            })
+            .collect::<Vec<_>>();
+        for (position, tile) in chosen {
+            self.insert(position, Some(tile));
+        }
+    }
+}
+
+fn add_with_neighbours(positions: &mut FxHashSet<Vector2<i32>>, position: Vector2<i32>) {
+    positions.insert(position);
+    for offset in AutoTileRule::NEIGHBOURS.iter() {
+        positions.insert(position + offset);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tile(mask: u8) -> TileDefinitionHandle {
+        TileDefinitionHandle::new(0, 0, mask as i16, 0)
+    }
+
+    fn full_rule(kind: AutoTileKind) -> AutoTileRule {
+        kind.all_masks()
+            .into_iter()
+            .fold(AutoTileRule::new("Terrain", kind), |rule, mask| {
+                rule.with_variant(mask, tile(mask))
+            })
+    }
+
+    fn result(update: &TilesUpdate, x: i32, y: i32) -> Option<TileDefinitionHandle> {
+        update.get(&Vector2::new(x, y)).copied().flatten()
+    }
+
+    #[test]
+    fn mask_counts() {
+        assert_eq!(AutoTileKind::Blob47.all_masks().len(), 47);
+        assert_eq!(AutoTileKind::Wang16.all_masks().len(), 16);
+    }
+
+    #[test]
+    fn corners_need_edges() {
+        let kind = AutoTileKind::Blob47;
+        assert_eq!(kind.reduce_mask(AutoTileRule::NORTH_EAST), 0);
+        assert_eq!(
+            kind.reduce_mask(AutoTileRule::NORTH | AutoTileRule::NORTH_EAST),
+            AutoTileRule::NORTH
+        );
+        let mask = AutoTileRule::NORTH | AutoTileRule::EAST | AutoTileRule::NORTH_EAST;
+        assert_eq!(kind.reduce_mask(mask), mask);
+    }
+
+    #[test]
+    fn tile_for_mask_fallback() {
+        let rule = AutoTileRule::new("Terrain", AutoTileKind::Blob47)
+            .with_variant(AutoTileRule::EDGES, tile(1))
+            .with_fallback(tile(2));
+        assert_eq!(rule.tile_for_mask(u8::MAX), Some(tile(1)));
+        assert_eq!(rule.tile_for_mask(AutoTileRule::NORTH), Some(tile(2)));
+    }
+
+    #[test]
+    fn paint_square() {
+        let rule = full_rule(AutoTileKind::Blob47);
+        let map = TileMapData::default();
+        let mut update = TilesUpdate::default();
+        let positions = (-1..=1).flat_map(|x| (-1..=1).map(move |y| Vector2::new(x, y)));
+        update.auto_tile(&map, &rule, positions);
+        assert_eq!(result(&update, 0, 0), Some(tile(u8::MAX)));
+        assert_eq!(
+            result(&update, 0, 1),
+            Some(tile(
+                AutoTileRule::EAST
+                    | AutoTileRule::SOUTH_EAST
+                    | AutoTileRule::SOUTH
+                    | AutoTileRule::SOUTH_WEST
+                    | AutoTileRule::WEST
+            ))
+        );
+        assert_eq!(
+            result(&update, -1, -1),
+            Some(tile(
+                AutoTileRule::NORTH | AutoTileRule::NORTH_EAST | AutoTileRule::EAST
+            ))
+        );
+        assert_eq!(result(&update, 2, 0), None);
+    }
+
+    #[test]
+    fn erase_updates_neighbours() {
+        let rule = full_rule(AutoTileKind::Wang16);
+        let mut map = TileMapData::default();
+        let mut update = TilesUpdate::default();
+        update.auto_tile(&map, &rule, [Vector2::new(0, 0), Vector2::new(1, 0)]);
+        map.swap_tiles(&mut update);
+        assert_eq!(map.get(Vector2::new(0, 0)), Some(tile(AutoTileRule::EAST)));
+        let mut update = TilesUpdate::default();
+        update.auto_erase(&map, &rule, [Vector2::new(1, 0)]);
+        map.swap_tiles(&mut update);
+        assert_eq!(map.get(Vector2::new(0, 0)), Some(tile(0)));
+        assert_eq!(map.get(Vector2::new(1, 0)), None);
+    }
+
+    #[test]
+    fn apply_rules_to_drawn_tiles() {
+        let rule = full_rule(AutoTileKind::Wang16);
+        let map = TileMapData::default();
+        let mut update = TilesUpdate::default();
+        update.insert(Vector2::new(0, 0), Some(tile(0)));
+        update.insert(Vector2::new(0, 1), Some(tile(0)));
+        update.apply_auto_tile_rules(&map, &[rule]);
+        assert_eq!(result(&update, 0, 0), Some(tile(AutoTileRule::NORTH)));
+        assert_eq!(result(&update, 0, 1), Some(tile(AutoTileRule::SOUTH)));
+    }
+}
@@ -178,6 +178,10 @@ pub struct TileMapBrush {
     /// users to customize the organization of pages.
     #[reflect(hidden)]
     pub pages: TileGridMap<TileMapBrushPage>,
+    /// Terrains that choose their tiles automatically. When the brush draws a tile of one of these
+    /// rules, the tile and its neighbours are replaced by the tiles that match their surroundings.
+    #[visit(optional)]
+    pub auto_tile_rules: Vec<AutoTileRule>,
     /// A count of changes since last save. New changes add +1. Reverting to previous
     /// states add -1. Reverting to a state before the last save can result in negative
     /// values. Saving is unnecessary whenever this value is 0.
@@ -30,8 +30,8 @@ use crate::core::{
 
 use super::*;
 
-const CHUNK_WIDTH: usize = 16;
-const CHUNK_HEIGHT: usize = 16;
+pub(super) const CHUNK_WIDTH: usize = 16;
+pub(super) const CHUNK_HEIGHT: usize = 16;
 const WIDTH_BITS: i32 = (CHUNK_WIDTH - 1) as i32;
 const HEIGHT_BITS: i32 = (CHUNK_HEIGHT - 1) as i32;
 
@@ -41,7 +41,9 @@ pub type TileMapDataResource = Resource<TileMapData>;
 /// Given a tile position, calculate the position of the chunk containing that tile
 /// and the position of the tile within that chunk, and return them as a pair:
 /// (chunk position, tile position within chunk)
-fn tile_position_to_chunk_position(position: Vector2<i32>) -> (Vector2<i32>, Vector2<i32>) {
+pub(super) fn tile_position_to_chunk_position(
+    position: Vector2<i32>,
+) -> (Vector2<i32>, Vector2<i32>) {
     let x = position.x;
     let y = position.y;
     let x_chunk = x & !WIDTH_BITS;
@@ -181,6 +183,13 @@ impl<'a, P: FnMut(Vector2<i32>) -> bool> TileMapDataIterator<'a, P> {
 #[type_uuid(id = "a8e4b6b4-c1bd-4ed9-a753-0d5a3dfe1729")]
 pub struct TileMapData {
     content: FxHashMap<Vector2<i32>, Chunk>,
+    /// A counter that is incremented on every change of the tiles.
+    #[reflect(hidden)]
+    revision: u64,
+    /// The value of `revision` at the time of the last change of each chunk. It is used to find
+    /// chunks that need to be re-built for rendering.
+    #[reflect(hidden)]
+    chunk_revisions: FxHashMap<Vector2<i32>, u64>,
 }
 
 impl Visit for TileMapData {
@@ -188,7 +197,15 @@ impl Visit for TileMapData {
         if !visitor.is_reading() {
             self.shrink_to_fit();
         }
-        self.content.visit(name, visitor)
+        self.content.visit(name, visitor)?;
+        if visitor.is_reading() {
+            self.chunk_revisions.clear();
+            let positions = self.content.keys().copied().collect::<Vec<_>>();
+            for position in positions {
+                self.mark_chunk_modified(position);
+            }
+        }
+        Ok(())
     }
 }
 
@@ -258,6 +275,33 @@ impl TileMapData {
             chunk_iter: None,
         }
     }
+    /// Iterate over the positions of the chunks of this data. A chunk is a 16x16 block of tiles,
+    /// the position of a chunk is the position of its tile with the smallest coordinates.
+    pub fn chunk_positions(&self) -> impl Iterator<Item = Vector2<i32>> + '_ {
+        self.content.keys().copied()
+    }
+    /// Iterate over all pairs of (position, handle) in the chunk at the given position.
+    pub fn chunk_iter(
+        &self,
+        chunk_position: Vector2<i32>,
+    ) -> impl Iterator<Item = (Vector2<i32>, TileDefinitionHandle)> + '_ {
+        self.content
+            .get(&chunk_position)
+            .into_iter()
+            .flat_map(move |chunk| chunk.iter(chunk_position))
+    }
+    /// A number that changes whenever any tile of the chunk at the given position is changed.
+    /// It could be used to cache some data that is derived from the tiles of a chunk.
+    pub fn chunk_revision(&self, chunk_position: Vector2<i32>) -> u64 {
+        self.chunk_revisions
+            .get(&chunk_position)
+            .copied()
+            .unwrap_or_default()
+    }
+    fn mark_chunk_modified(&mut self, chunk_position: Vector2<i32>) {
+        self.revision += 1;
+        self.chunk_revisions.insert(chunk_position, self.revision);
+    }
     /// Apply the updates specified in the given `TileUpdate` and modify it so that it
     /// contains the tiles require to undo the change. Calling `swap_tiles` twice with the same
     /// `TileUpdate` object will do the changes and then undo them, leaving the tiles unchanged in the end.
@@ -285,6 +329,7 @@ impl TileMapData {
         value: Option<TileDefinitionHandle>,
     ) -> Option<TileDefinitionHandle> {
         let (chunk, pos) = tile_position_to_chunk_position(position);
+        self.mark_chunk_modified(chunk);
         if let Some(chunk) = self.content.get_mut(&chunk) {
             let handle = &mut chunk[pos];
             let result = *handle;
@@ -305,12 +350,14 @@ impl TileMapData {
     /// Set a new handle for the tile at the given position.
     pub fn set(&mut self, position: Vector2<i32>, value: TileDefinitionHandle) {
         let (chunk, pos) = tile_position_to_chunk_position(position);
+        self.mark_chunk_modified(chunk);
         let chunk = self.content.entry(chunk).or_default();
         chunk[pos] = value;
     }
     /// Remove the tile at the given position.
     pub fn remove(&mut self, position: Vector2<i32>) {
         let (chunk, pos) = tile_position_to_chunk_position(position);
+        self.mark_chunk_modified(chunk);
         if let Some(chunk) = self.content.get_mut(&chunk) {
             chunk[pos] = TileDefinitionHandle::EMPTY;
         }
@@ -419,4 +466,22 @@ mod tests {
         coords.sort_by(|(a, _), (b, _)| v_ord(a, b));
         assert_eq!(result, coords);
     }
+    #[test]
+    fn chunk_revisions() {
+        let mut data = TileMapData::default();
+        data.set(v(0, 0), h(1, 2, 3, 4));
+        data.set(v(16, 0), h(1, 2, 3, 4));
+        let first = data.chunk_revision(v(0, 0));
+        let second = data.chunk_revision(v(16, 0));
+        assert_ne!(first, 0);
+        assert_ne!(first, second);
+        data.remove(v(1, 1));
+        assert_ne!(data.chunk_revision(v(0, 0)), first);
+        assert_eq!(data.chunk_revision(v(16, 0)), second);
+        assert_eq!(data.chunk_revision(v(-16, 0)), 0);
+        assert_eq!(
+            data.chunk_iter(v(16, 0)).collect::<Vec<_>>(),
+            vec![(v(16, 0), h(1, 2, 3, 4))]
+        );
+    }
 }
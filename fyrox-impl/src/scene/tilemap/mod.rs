@@ -21,10 +21,12 @@
 //! Tile map is a 2D "image", made out of a small blocks called tiles. Tile maps used in 2D games to
 //! build game worlds quickly and easily. See [`TileMap`] docs for more info and usage examples.
 
+mod autotile;
 pub mod brush;
 mod data;
 mod effect;
 mod property;
+mod render_cache;
 mod tile_collider;
 mod tile_rect;
 mod tile_source;
@@ -32,6 +34,7 @@ pub mod tileset;
 mod transform;
 mod update;
 
+pub use autotile::*;
 use brush::*;
 pub use data::*;
 pub use effect::*;
@@ -41,6 +44,7 @@ use fyrox_core::{
     parking_lot::Mutex,
 };
 use fyrox_resource::Resource;
+use render_cache::*;
 pub use tile_collider::*;
 pub use tile_rect::*;
 pub use tile_source::*;
@@ -185,12 +189,8 @@ impl TileMapRenderContext<'_, '_> {
     }
 
     fn push_color_tile(&mut self, position: Vector2<i32>, color: Color) {
-        let position = position.cast::<f32>();
-        let vertices = [(0.0, 1.0), (1.0, 1.0), (1.0, 0.0), (0.0, 0.0)]
-            .map(|(x, y)| Vector2::new(x, y))
-            .map(|p| make_rect_vertex(&self.transform, position + p, color));
-
-        let triangles = [[0, 1, 2], [2, 3, 0]].map(TriangleDefinition);
+        let vertices = make_color_tile_vertices(&self.transform, position, color);
+        let triangles = tile_triangles();
 
         let sort_index = self.context.calculate_sorting_index(self.position());
 
@@ -218,23 +218,8 @@ impl TileMapRenderContext<'_, '_> {
         bounds: &TileBounds,
         color: Color,
     ) {
-        let position = position.cast::<f32>();
-        let uvs = [
-            bounds.right_top_corner,
-            bounds.left_top_corner,
-            bounds.left_bottom_corner,
-            bounds.right_bottom_corner,
-        ];
-        let vertices = [
-            (1.0, 1.0, uvs[0]),
-            (0.0, 1.0, uvs[1]),
-            (0.0, 0.0, uvs[2]),
-            (1.0, 0.0, uvs[3]),
-        ]
-        .map(|(x, y, uv)| (Vector2::new(x, y), uv))
-        .map(|(p, uv)| make_tile_vertex(&self.transform, position + p, uv, color));
-
-        let triangles = [[0, 1, 2], [2, 3, 0]].map(TriangleDefinition);
+        let vertices = make_material_tile_vertices(&self.transform, position, bounds, color);
+        let triangles = tile_triangles();
 
         let sort_index = self.context.calculate_sorting_index(self.position());
 
@@ -256,6 +241,46 @@ impl TileMapRenderContext<'_, '_> {
     }
 }
 
+/// The two triangles of a tile quad, for vertices that are created by [`make_color_tile_vertices`]
+/// or [`make_material_tile_vertices`].
+fn tile_triangles() -> [TriangleDefinition; 2] {
+    [[0, 1, 2], [2, 3, 0]].map(TriangleDefinition)
+}
+
+fn make_color_tile_vertices(
+    transform: &Matrix4<f32>,
+    position: Vector2<i32>,
+    color: Color,
+) -> [RectangleVertex; 4] {
+    let position = position.cast::<f32>();
+    [(0.0, 1.0), (1.0, 1.0), (1.0, 0.0), (0.0, 0.0)]
+        .map(|(x, y)| Vector2::new(x, y))
+        .map(|p| make_rect_vertex(transform, position + p, color))
+}
+
+fn make_material_tile_vertices(
+    transform: &Matrix4<f32>,
+    position: Vector2<i32>,
+    bounds: &TileBounds,
+    color: Color,
+) -> [TileVertex; 4] {
+    let position = position.cast::<f32>();
+    let uvs = [
+        bounds.right_top_corner,
+        bounds.left_top_corner,
+        bounds.left_bottom_corner,
+        bounds.right_bottom_corner,
+    ];
+    [
+        (1.0, 1.0, uvs[0]),
+        (0.0, 1.0, uvs[1]),
+        (0.0, 0.0, uvs[2]),
+        (1.0, 0.0, uvs[3]),
+    ]
+    .map(|(x, y, uv)| (Vector2::new(x, y), uv))
+    .map(|(p, uv)| make_tile_vertex(transform, position + p, uv, color))
+}
+
 fn make_rect_vertex(
     transform: &Matrix4<f32>,
     position: Vector2<f32>,
@@ -286,24 +311,50 @@ fn make_tile_vertex(
 }
 
 /// A record whether a change has happened since the most recent save.
-#[derive(Default, Debug, Copy, Clone)]
-pub struct ChangeFlag(bool);
+/// It also counts changes, so the owner of the flag could be checked for modifications
+/// since some moment by comparing [`ChangeFlag::revision`] values.
+#[derive(Debug, Copy, Clone)]
+pub struct ChangeFlag {
+    needs_save: bool,
+    revision: u64,
+}
+
+fn next_change_revision() -> u64 {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    static REVISION: AtomicU64 = AtomicU64::new(1);
+    REVISION.fetch_add(1, Ordering::Relaxed)
+}
+
+impl Default for ChangeFlag {
+    fn default() -> Self {
+        Self {
+            needs_save: false,
+            revision: next_change_revision(),
+        }
+    }
+}
 
 impl ChangeFlag {
     /// True if there are changes.
     #[inline]
     pub fn needs_save(&self) -> bool {
-        self.0
+        self.needs_save
     }
     /// Reset the flag to indicate that there are no unsaved changes.
     #[inline]
     pub fn reset(&mut self) {
-        self.0 = false;
+        self.needs_save = false;
     }
     /// Set the flat to indicate that there could be unsaved changes.
     #[inline]
     pub fn set(&mut self) {
-        self.0 = true;
+        self.needs_save = true;
+        self.revision = next_change_revision();
+    }
+    /// A number that is unique among all flags and is changed each time [`ChangeFlag::set`] is called.
+    #[inline]
+    pub fn revision(&self) -> u64 {
+        self.revision
     }
 }
 
@@ -898,6 +949,9 @@ pub struct TileMap {
     /// This is part of how [`TileMapEffect`] can prevent a tile from being rendered.
     #[reflect(hidden)]
     hidden_tiles: Mutex<FxHashSet<Vector2<i32>>>,
+    /// Prepared render data of the chunks of the tile map, so only modified chunks need to be re-built.
+    #[reflect(hidden)]
+    render_cache: Mutex<TileMapRenderCache>,
     /// Special rendering effects that may change how the tile map renders.
     /// These effects are processed in order before the tile map performs the
     /// normal rendering of tiles, and they can prevent some times from being
@@ -1099,6 +1153,13 @@ impl TileMap {
             Ok(property.prop_type.default_value())
         }
     }
+    /// Drops prepared render data of the tile map, forcing every chunk of tiles to be re-built on the
+    /// next frame. Render data is re-built automatically when tiles are changed or the tile set
+    /// is marked as changed using its [`ChangeFlag`], so this method is only needed when the tile
+    /// set was modified without updating its change flag.
+    pub fn invalidate_render_cache(&self) {
+        self.render_cache.lock().clear();
+    }
     /// The global transform of the tile map with initial x-axis flip applied, so the positive x-axis points left instead of right.
     pub fn tile_map_transform(&self) -> Matrix4<f32> {
         self.global_transform()
@@ -1269,6 +1330,7 @@ impl Default for TileMap {
             tile_scale: Vector2::repeat(1.0).into(),
             active_brush: Default::default(),
             hidden_tiles: Mutex::default(),
+            render_cache: Mutex::default(),
             before_effects: Vec::default(),
             after_effects: Vec::default(),
         }
@@ -1284,6 +1346,7 @@ impl Clone for TileMap {
             tile_scale: self.tile_scale.clone(),
             active_brush: self.active_brush.clone(),
             hidden_tiles: Mutex::default(),
+            render_cache: Mutex::default(),
             before_effects: self.before_effects.clone(),
             after_effects: self.after_effects.clone(),
         }
@@ -1378,25 +1441,53 @@ impl NodeTrait for TileMap {
             effect.lock().render_special_tiles(&mut tile_render_context);
         }
         let bounds = tile_render_context.visible_bounds();
-        let Some(tiles) = self.tiles.as_ref().map(|r| r.data_ref()) else {
+        let Some(tiles_resource) = self.tiles.as_ref() else {
             return RdcControlFlow::Continue;
         };
+        let tiles = tiles_resource.data_ref();
         let Some(tiles) = tiles.as_loaded_ref() else {
             return RdcControlFlow::Continue;
         };
-        if bounds.is_some() {
-            for (position, handle) in tiles.bounded_iter(bounds) {
-                if bounds.contains(position) && tile_render_context.is_tile_visible(position) {
-                    let handle = tile_render_context.get_animated_version(handle);
-                    tile_render_context.draw_tile(position, handle);
-                }
+        let tile_set_revision = tile_render_context
+            .tile_set
+            .as_ref()
+            .map(|t| t.change_count.revision())
+            .unwrap_or_default();
+        let mut render_cache = self.render_cache.lock();
+        render_cache.sync(
+            tiles_resource.key(),
+            tile_set_resource.key(),
+            tile_set_revision,
+        );
+        // Chunks with tiles that were hidden by effects cannot use the prepared render data.
+        let modified_chunks = tile_render_context
+            .hidden_tiles
+            .iter()
+            .map(|p| tile_position_to_chunk_position(*p).0)
+            .collect::<FxHashSet<_>>();
+        for chunk_position in tiles.chunk_positions() {
+            let chunk_rect = TileRect::new(
+                chunk_position.x,
+                chunk_position.y,
+                CHUNK_WIDTH as i32,
+                CHUNK_HEIGHT as i32,
+            );
+            if bounds.is_some() && !bounds.intersects(chunk_rect) {
+                continue;
             }
-        } else {
-            for (position, handle) in tiles.iter() {
-                if tile_render_context.is_tile_visible(position) {
-                    let handle = tile_render_context.get_animated_version(handle);
-                    tile_render_context.draw_tile(position, handle);
+            if modified_chunks.contains(&chunk_position) {
+                for (position, handle) in tiles.chunk_iter(chunk_position) {
+                    if (bounds.is_none() || bounds.contains(position))
+                        && tile_render_context.is_tile_visible(position)
+                    {
+                        let handle = tile_render_context.get_animated_version(handle);
+                        tile_render_context.draw_tile(position, handle);
+                    }
                 }
+            } else {
+                render_cache
+                    .chunk(chunk_position, tiles, &tile_render_context.tile_set)
+                    .draw(&mut tile_render_context);
             }
         }
         for effect in self.after_effects.iter() {
@@ -1481,6 +1572,7 @@ impl TileMapBuilder {
             tile_scale: self.tile_scale.into(),
             active_brush: Default::default(),
             hidden_tiles: Mutex::default(),
+            render_cache: Mutex::default(),
             before_effects: self.before_effects,
             after_effects: self.after_effects,
         })
@@ -0,0 +1,221 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Tiles of a tile map are stored in chunks of 16x16 tiles, and rendering a tile map
+//! one tile at a time would require re-creating the geometry of every visible tile on every frame.
+//! Instead, [`TileMapRenderCache`] stores prepared surfaces for each chunk and re-builds them only
+//! when the chunk is changed. The surfaces are submitted to the renderer as surface instances with
+//! the transform of the tile map, so the geometry of unchanged chunks stays on the GPU between frames.
+//!
+//! Animated tiles change on every frame, so they are never baked into chunk surfaces and instead are
+//! rendered one at a time as usual.
+
+use crate::{
+    renderer::{bundle::SurfaceInstanceData, framework::ElementRange},
+    scene::mesh::{
+        buffer::{TriangleBuffer, VertexBuffer},
+        surface::{SurfaceData, SurfaceResource},
+    },
+};
+use fxhash::FxHashMap;
+
+use super::*;
+
+/// Prepared render data of a single chunk of a tile map.
+#[derive(Debug, Clone)]
+pub(super) struct ChunkRenderData {
+    /// The revision of the chunk at the moment when this data was built.
+    /// See [`TileMapData::chunk_revision`].
+    revision: u64,
+    /// Geometry of the static tiles of the chunk, one surface per material.
+    surfaces: Vec<(MaterialResource, SurfaceResource)>,
+    /// The tiles of the chunk that are part of an animation.
+    animated_tiles: Vec<(Vector2<i32>, TileDefinitionHandle)>,
+}
+
+/// A collection of prepared chunk surfaces for a tile map. The whole cache is dropped whenever
+/// it is used with a different tile data resource or tile set resource, or when the tile set
+/// has been modified.
+#[derive(Debug, Default, Clone)]
+pub(super) struct TileMapRenderCache {
+    tiles_key: u64,
+    tile_set_key: u64,
+    tile_set_revision: u64,
+    chunks: FxHashMap<Vector2<i32>, ChunkRenderData>,
+}
+
+impl TileMapRenderCache {
+    /// Remove all cached chunks, forcing them to be re-built on the next frame.
+    pub fn clear(&mut self) {
+        self.chunks.clear();
+    }
+
+    /// Make sure that the cache corresponds to the given resources, otherwise drop all the cached chunks.
+    pub fn sync(&mut self, tiles_key: u64, tile_set_key: u64, tile_set_revision: u64) {
+        if self.tiles_key != tiles_key
+            || self.tile_set_key != tile_set_key
+            || self.tile_set_revision != tile_set_revision
+        {
+            self.tiles_key = tiles_key;
+            self.tile_set_key = tile_set_key;
+            self.tile_set_revision = tile_set_revision;
+            self.chunks.clear();
+        }
+    }
+
+    /// Render data of the chunk at the given position. It is re-built if the chunk was modified
+    /// since the data was built the last time.
+    pub fn chunk(
+        &mut self,
+        position: Vector2<i32>,
+        tiles: &TileMapData,
+        tile_set: &OptionTileSet,
+    ) -> &ChunkRenderData {
+        let revision = tiles.chunk_revision(position);
+        let is_valid = self
+            .chunks
+            .get(&position)
+            .is_some_and(|chunk| chunk.revision == revision);
+        if !is_valid {
+            let chunk = ChunkRenderData::build(position, revision, tiles, tile_set);
+            self.chunks.insert(position, chunk);
+        }
+        &self.chunks[&position]
+    }
+}
+
+impl ChunkRenderData {
+    fn build(
+        position: Vector2<i32>,
+        revision: u64,
+        tiles: &TileMapData,
+        tile_set: &OptionTileSet,
+    ) -> Self {
+        // The geometry is built in the space of the tile map, the transformation of the tile map
+        // is applied by the renderer.
+        let transform = Matrix4::identity();
+        let mut color_vertices = Vec::new();
+        let mut color_triangles = Vec::new();
+        let mut material_geometry = FxHashMap::<u64, (MaterialResource, Vec<_>, Vec<_>)>::default();
+        let mut animated_tiles = Vec::new();
+        for (tile_position, handle) in tiles.chunk_iter(position) {
+            if tile_set.get_animated_version(0.0, handle).is_some() {
+                animated_tiles.push((tile_position, handle));
+                continue;
+            }
+            let Some(data) = tile_set.get_tile_render_data(handle.into()) else {
+                continue;
+            };
+            if let Some(material_bounds) = data.material_bounds.as_ref() {
+                let material = &material_bounds.material;
+                let (_, vertices, triangles) = material_geometry
+                    .entry(material.key())
+                    .or_insert_with(|| (material.clone(), Vec::new(), Vec::new()));
+                push_quad(
+                    vertices,
+                    triangles,
+                    make_material_tile_vertices(
+                        &transform,
+                        tile_position,
+                        &material_bounds.bounds,
+                        data.color,
+                    ),
+                );
+            } else {
+                push_quad(
+                    &mut color_vertices,
+                    &mut color_triangles,
+                    make_color_tile_vertices(&transform, tile_position, data.color),
+                );
+            }
+        }
+        let mut surfaces = Vec::new();
+        if let Some(surface) = make_surface(color_vertices, color_triangles) {
+            surfaces.push((STANDARD_2D.resource.clone(), surface));
+        }
+        for (material, vertices, triangles) in material_geometry.into_values() {
+            if let Some(surface) = make_surface(vertices, triangles) {
+                surfaces.push((material, surface));
+            }
+        }
+        Self {
+            revision,
+            surfaces,
+            animated_tiles,
+        }
+    }
+
+    /// Submit the chunk to the renderer.
+    pub fn draw(&self, ctx: &mut TileMapRenderContext) {
+        let sort_index = ctx.context.calculate_sorting_index(ctx.position());
+        for (material, surface) in self.surfaces.iter() {
+            ctx.context.storage.push(
+                surface,
+                material,
+                RenderPath::Forward,
+                sort_index,
+                SurfaceInstanceData {
+                    world_transform: ctx.transform,
+                    bone_matrices: Default::default(),
+                    blend_shapes_weights: Default::default(),
+                    element_range: ElementRange::Full,
+                    node_handle: ctx.tile_map_handle,
+                    custom_data: Default::default(),
+                    lod_fade: ctx.context.lod_fade,
+                },
+            );
+        }
+        let bounds = ctx.visible_bounds();
+        for &(position, handle) in self.animated_tiles.iter() {
+            if bounds.is_none() || bounds.contains(position) {
+                let handle = ctx.get_animated_version(handle);
+                ctx.draw_tile(position, handle);
+            }
+        }
+    }
+}
+
+fn push_quad<T: Copy>(
+    vertices: &mut Vec<T>,
+    triangles: &mut Vec<TriangleDefinition>,
+    quad: [T; 4],
+) {
+    let offset = vertices.len() as u32;
+    vertices.extend_from_slice(&quad);
+    triangles.extend(
+        tile_triangles()
+            .into_iter()
+            .map(|TriangleDefinition(indices)| TriangleDefinition(indices.map(|i| i + offset))),
+    );
+}
+
+fn make_surface<T: VertexTrait>(
+    vertices: Vec<T>,
+    triangles: Vec<TriangleDefinition>,
+) -> Option<SurfaceResource> {
+    if vertices.is_empty() {
+        return None;
+    }
+    let vertex_buffer = VertexBuffer::new(vertices.len(), vertices).ok()?;
+    Some(SurfaceResource::new_ok(
+        ResourceKind::Embedded,
+        SurfaceData::new(vertex_buffer, TriangleBuffer::new(triangles)),
+    ))
+}
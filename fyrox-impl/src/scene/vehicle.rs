@@ -0,0 +1,786 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Ray cast vehicle is a simple vehicle controller, that uses ray casts to simulate suspension
+//! of wheels. See [`RayCastVehicle`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Point3, UnitQuaternion, Vector3},
+        color::Color,
+        math::{
+            aabb::AxisAlignedBoundingBox,
+            curve::{Curve, CurveKey, CurveKeyKind},
+            Matrix4Ext,
+        },
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        uuid::{uuid, Uuid},
+        uuid_provider,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    scene::{
+        base::{Base, BaseBuilder},
+        collider::InteractionGroups,
+        debug::{Line, SceneDrawingContext},
+        graph::{
+            physics::{Intersection, RayCastOptions},
+            Graph,
+        },
+        node::{constructor::NodeConstructor, Node, NodeTrait, UpdateContext},
+        rigidbody::RigidBody,
+        Scene,
+    },
+};
+use fyrox_graph::{constructor::ConstructorProvider, BaseSceneGraph, SceneGraphNode};
+use std::ops::{Deref, DerefMut};
+
+/// Longitudinal speed (in m/s) below which the slip calculations use this value instead of the
+/// actual speed. Prevents division by zero and jittering of a vehicle at rest.
+const MIN_SLIP_SPEED: f32 = 1.0;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct WheelState {
+    in_contact: bool,
+    hard_point: Vector3<f32>,
+    center: Vector3<f32>,
+    contact_point: Vector3<f32>,
+    contact_normal: Vector3<f32>,
+    suspension_length: f32,
+    suspension_force: f32,
+    steering_angle: f32,
+    angular_velocity: f32,
+    rotation_angle: f32,
+    slip_ratio: f32,
+    slip_angle: f32,
+}
+
+/// A wheel of a [`RayCastVehicle`]. The wheel does not have a physical body, instead it casts a
+/// ray along the suspension axis and applies suspension and tire forces to the chassis at the
+/// contact point.
+#[derive(Clone, Debug, PartialEq, Reflect, Visit)]
+#[visit(optional)]
+pub struct Wheel {
+    /// A handle of a scene node, that is used as visual representation of the wheel. Its transform
+    /// is set by the vehicle every frame.
+    pub node: Handle<Node>,
+    /// A point (in local coordinates of the vehicle), where the suspension is attached to the
+    /// chassis.
+    pub connection_point: Vector3<f32>,
+    /// Radius of the wheel.
+    #[reflect(min_value = 0.0)]
+    pub radius: f32,
+    /// Length of the suspension when it is not loaded.
+    #[reflect(min_value = 0.0)]
+    pub suspension_rest_length: f32,
+    /// Stiffness of the suspension spring (in N/m).
+    #[reflect(min_value = 0.0)]
+    pub suspension_stiffness: f32,
+    /// Damping of the suspension (in N*s/m).
+    #[reflect(min_value = 0.0)]
+    pub suspension_damping: f32,
+    /// Maximum force, that the suspension could apply to the chassis.
+    #[reflect(min_value = 0.0)]
+    pub max_suspension_force: f32,
+    /// Defines how much the wheel is steered. Usually it is 1.0 for front wheels and 0.0 for rear
+    /// wheels. Negative values steer the wheel in the opposite direction.
+    pub steering_factor: f32,
+    /// Portion of the engine torque, that is applied to the wheel. Zero means that the wheel is not
+    /// driven.
+    #[reflect(min_value = 0.0)]
+    pub drive_factor: f32,
+    /// Portion of the brake torque, that is applied to the wheel.
+    #[reflect(min_value = 0.0)]
+    pub brake_factor: f32,
+    /// Friction coefficient of the tire. Defines the maximum tire force relative to the load of the
+    /// wheel.
+    #[reflect(min_value = 0.0)]
+    pub friction: f32,
+    /// Defines how fast the longitudinal tire force grows with the slip ratio.
+    #[reflect(min_value = 0.0)]
+    pub longitudinal_stiffness: f32,
+    /// Defines how fast the lateral tire force grows with the slip angle (in radians).
+    #[reflect(min_value = 0.0)]
+    pub lateral_stiffness: f32,
+    /// Moment of inertia of the wheel around its axle.
+    #[reflect(min_value = 0.001)]
+    pub inertia: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    state: WheelState,
+}
+
+uuid_provider!(Wheel = "8a8fe7d4-6a3b-4a92-9df8-5b83e4ad5c6e");
+
+impl Default for Wheel {
+    fn default() -> Self {
+        Self {
+            node: Default::default(),
+            connection_point: Default::default(),
+            radius: 0.35,
+            suspension_rest_length: 0.3,
+            suspension_stiffness: 30000.0,
+            suspension_damping: 3000.0,
+            max_suspension_force: 25000.0,
+            steering_factor: 0.0,
+            drive_factor: 1.0,
+            brake_factor: 1.0,
+            friction: 1.0,
+            longitudinal_stiffness: 10.0,
+            lateral_stiffness: 8.0,
+            inertia: 1.5,
+            state: Default::default(),
+        }
+    }
+}
+
+impl Wheel {
+    /// Returns `true` if the wheel touches the ground.
+    pub fn is_in_contact(&self) -> bool {
+        self.state.in_contact
+    }
+
+    /// Returns world-space contact point of the wheel. The value is meaningful only if the wheel is
+    /// in contact.
+    pub fn contact_point(&self) -> Vector3<f32> {
+        self.state.contact_point
+    }
+
+    /// Returns world-space normal of the surface at the contact point.
+    pub fn contact_normal(&self) -> Vector3<f32> {
+        self.state.contact_normal
+    }
+
+    /// Returns current length of the suspension.
+    pub fn suspension_length(&self) -> f32 {
+        self.state.suspension_length
+    }
+
+    /// Returns the force, that is applied by the suspension to the chassis.
+    pub fn suspension_force(&self) -> f32 {
+        self.state.suspension_force
+    }
+
+    /// Returns current steering angle of the wheel (in radians).
+    pub fn steering_angle(&self) -> f32 {
+        self.state.steering_angle
+    }
+
+    /// Returns angular velocity of the wheel around its axle (in rad/s).
+    pub fn angular_velocity(&self) -> f32 {
+        self.state.angular_velocity
+    }
+
+    /// Returns current slip ratio of the tire. Positive values mean that the wheel spins faster
+    /// than the ground moves (acceleration), negative - slower (braking).
+    pub fn slip_ratio(&self) -> f32 {
+        self.state.slip_ratio
+    }
+
+    /// Returns current slip angle of the tire (in radians).
+    pub fn slip_angle(&self) -> f32 {
+        self.state.slip_angle
+    }
+}
+
+/// Ray cast vehicle is a vehicle controller, that simulates wheels using ray casts. Each wheel
+/// casts a ray along the suspension axis (-Y axis of the vehicle node), and if the ray hits
+/// something, the wheel applies suspension and tire forces to the chassis rigid body.
+///
+/// ## Setup
+///
+/// The vehicle node must be a child of a dynamic rigid body (chassis), or the chassis must be set
+/// explicitly using [`RayCastVehicle::chassis`] field. Colliders of the chassis are ignored by
+/// wheel ray casts. Connection points of the wheels are defined in local coordinates of the vehicle
+/// node, +Z axis of the node is the forward direction of the vehicle.
+///
+/// ## Control
+///
+/// Use [`RayCastVehicle::set_throttle`], [`RayCastVehicle::set_brake`] and
+/// [`RayCastVehicle::set_steering`] to control the vehicle. The torque applied to driven wheels is
+/// defined by [`RayCastVehicle::engine_torque`] curve, that maps the forward speed of the vehicle
+/// (in m/s) to the torque (in N*m). The same is applicable for brakes.
+///
+/// ## Tire model
+///
+/// Tire forces are calculated using a simple slip-based model: the longitudinal force depends on
+/// the slip ratio, the lateral force depends on the slip angle. The total force is limited by the
+/// friction circle, which is defined by the load of the wheel and the friction coefficient.
+#[derive(Clone, Reflect, Visit, Debug, ComponentProvider)]
+#[visit(optional)]
+pub struct RayCastVehicle {
+    base: Base,
+    /// A handle of the chassis rigid body. If not set, the parent node of the vehicle is used.
+    pub chassis: InheritableVariable<Handle<Node>>,
+    /// A set of wheels of the vehicle.
+    pub wheels: InheritableVariable<Vec<Wheel>>,
+    /// Maps the forward speed of the vehicle (in m/s) to the maximum engine torque (in N*m).
+    pub engine_torque: InheritableVariable<Curve>,
+    /// Maps the forward speed of the vehicle (in m/s) to the maximum brake torque (in N*m).
+    pub brake_torque: InheritableVariable<Curve>,
+    /// Maximum steering angle of wheels (in radians).
+    #[reflect(min_value = 0.0, max_value = 1.57)]
+    pub max_steering_angle: InheritableVariable<f32>,
+    /// Collision groups, that are used for wheel ray casts.
+    pub groups: InheritableVariable<InteractionGroups>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    throttle: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    brake: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    steering: f32,
+}
+
+impl Default for RayCastVehicle {
+    fn default() -> Self {
+        RayCastVehicleBuilder::new(BaseBuilder::new()).build_ray_cast_vehicle()
+    }
+}
+
+impl Deref for RayCastVehicle {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for RayCastVehicle {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for RayCastVehicle {
+    fn type_uuid() -> Uuid {
+        uuid!("2c1b0a6e-4f2e-4d8e-9c53-0fd0e7b6a0b4")
+    }
+}
+
+impl ConstructorProvider<Node, Graph> for RayCastVehicle {
+    fn constructor() -> NodeConstructor {
+        NodeConstructor::new::<Self>()
+            .with_variant("Ray Cast Vehicle", |_| {
+                RayCastVehicleBuilder::new(BaseBuilder::new().with_name("Ray Cast Vehicle"))
+                    .build_node()
+                    .into()
+            })
+            .with_group("Physics")
+    }
+}
+
+impl RayCastVehicle {
+    /// Sets throttle of the vehicle in `[-1; 1]` range. Negative values are used to drive
+    /// backwards.
+    pub fn set_throttle(&mut self, throttle: f32) {
+        self.throttle = throttle.clamp(-1.0, 1.0);
+    }
+
+    /// Returns current throttle of the vehicle.
+    pub fn throttle(&self) -> f32 {
+        self.throttle
+    }
+
+    /// Sets brake input of the vehicle in `[0; 1]` range.
+    pub fn set_brake(&mut self, brake: f32) {
+        self.brake = brake.clamp(0.0, 1.0);
+    }
+
+    /// Returns current brake input of the vehicle.
+    pub fn brake(&self) -> f32 {
+        self.brake
+    }
+
+    /// Sets steering input of the vehicle in `[-1; 1]` range. Positive values steer to the left
+    /// (counterclockwise rotation around the up axis).
+    pub fn set_steering(&mut self, steering: f32) {
+        self.steering = steering.clamp(-1.0, 1.0);
+    }
+
+    /// Returns current steering input of the vehicle.
+    pub fn steering(&self) -> f32 {
+        self.steering
+    }
+
+    fn chassis_handle(&self) -> Handle<Node> {
+        if self.chassis.is_some() {
+            *self.chassis
+        } else {
+            self.parent()
+        }
+    }
+}
+
+impl NodeTrait for RayCastVehicle {
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.local_bounding_box()
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.world_bounding_box()
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn update(&mut self, ctx: &mut UpdateContext) {
+        let dt = ctx.dt;
+        if dt <= 0.0 {
+            return;
+        }
+
+        let chassis_handle = self.chassis_handle();
+        let Some(chassis) = ctx
+            .nodes
+            .try_borrow(chassis_handle)
+            .and_then(|n| n.component_ref::<RigidBody>())
+        else {
+            return;
+        };
+        let lin_vel = chassis.lin_vel();
+        let ang_vel = chassis.ang_vel();
+        let center_of_mass = ctx
+            .physics
+            .body_center_of_mass(chassis.native.get())
+            .unwrap_or_else(|| chassis.global_position());
+
+        let transform = self.global_transform();
+        let rotation = UnitQuaternion::from_matrix_eps(
+            &transform.basis(),
+            f32::EPSILON,
+            16,
+            Default::default(),
+        );
+        let up_axis = rotation * Vector3::y_axis();
+        let up = up_axis.into_inner();
+        let look = rotation * Vector3::z();
+        let forward_speed = lin_vel.dot(&look);
+
+        let steering_angle = self.steering * *self.max_steering_angle;
+        let engine_torque = self.throttle * self.engine_torque.value_at(forward_speed.abs());
+        let brake_torque = self.brake * self.brake_torque.value_at(forward_speed.abs());
+
+        let mut intersections = Vec::<Intersection>::new();
+        let mut forces = Vec::with_capacity(self.wheels.len());
+
+        for wheel in self.wheels.get_value_mut_silent().iter_mut() {
+            let state = &mut wheel.state;
+
+            state.hard_point = transform
+                .transform_point(&Point3::from(wheel.connection_point))
+                .coords;
+            state.steering_angle = steering_angle * wheel.steering_factor;
+
+            let max_len = wheel.suspension_rest_length + wheel.radius;
+            intersections.clear();
+            ctx.physics.cast_ray(
+                RayCastOptions {
+                    ray_origin: Point3::from(state.hard_point),
+                    ray_direction: -up,
+                    max_len,
+                    groups: *self.groups,
+                    sort_results: true,
+                },
+                &mut intersections,
+            );
+
+            // Skip colliders of the chassis.
+            let hit = intersections.iter().find(|i| {
+                ctx.nodes
+                    .try_borrow(i.collider)
+                    .map_or(true, |c| c.parent() != chassis_handle)
+            });
+
+            let drive_torque = engine_torque * wheel.drive_factor;
+            let mut tire_torque = 0.0;
+
+            if let Some(hit) = hit {
+                state.in_contact = true;
+                state.contact_point = hit.position.coords;
+                state.contact_normal = hit.normal;
+                state.suspension_length = (hit.toi - wheel.radius).max(0.0);
+
+                let point_velocity =
+                    lin_vel + ang_vel.cross(&(state.contact_point - center_of_mass));
+
+                // Suspension.
+                let compression = wheel.suspension_rest_length - state.suspension_length;
+                let compression_speed = -point_velocity.dot(&up);
+                state.suspension_force = (wheel.suspension_stiffness * compression
+                    + wheel.suspension_damping * compression_speed)
+                    .clamp(0.0, wheel.max_suspension_force);
+                let load = state.suspension_force;
+
+                // Tire frame.
+                let steering = UnitQuaternion::from_axis_angle(&up_axis, state.steering_angle);
+                let normal = state.contact_normal;
+                let forward = steering * look;
+                let forward = (forward - normal.scale(forward.dot(&normal)))
+                    .try_normalize(f32::EPSILON)
+                    .unwrap_or(forward);
+                let side = normal.cross(&forward);
+
+                let longitudinal_speed = point_velocity.dot(&forward);
+                let lateral_speed = point_velocity.dot(&side);
+                let reference_speed = longitudinal_speed.abs().max(MIN_SLIP_SPEED);
+                let free_rolling_velocity = longitudinal_speed / wheel.radius;
+
+                state.slip_ratio =
+                    (state.angular_velocity * wheel.radius - longitudinal_speed) / reference_speed;
+                state.slip_angle = lateral_speed.atan2(reference_speed);
+
+                let mut longitudinal_force = wheel.longitudinal_stiffness * state.slip_ratio * load;
+                let mut lateral_force = -wheel.lateral_stiffness * state.slip_angle * load;
+
+                // Friction circle.
+                let max_force = wheel.friction * load;
+                let magnitude = longitudinal_force.hypot(lateral_force);
+                if magnitude > max_force && magnitude > 0.0 {
+                    let k = max_force / magnitude;
+                    longitudinal_force *= k;
+                    lateral_force *= k;
+                }
+
+                // The tire can't transfer more momentum than needed to bring the wheel to the free
+                // rolling state in one step, otherwise the simulation becomes unstable.
+                let max_longitudinal_force = (state.angular_velocity - free_rolling_velocity).abs()
+                    * wheel.inertia
+                    / (wheel.radius * dt);
+                longitudinal_force =
+                    longitudinal_force.clamp(-max_longitudinal_force, max_longitudinal_force);
+
+                tire_torque = longitudinal_force * wheel.radius;
+
+                forces.push((
+                    up.scale(state.suspension_force)
+                        + forward.scale(longitudinal_force)
+                        + side.scale(lateral_force),
+                    state.contact_point,
+                ));
+            } else {
+                state.in_contact = false;
+                state.suspension_length = wheel.suspension_rest_length;
+                state.suspension_force = 0.0;
+                state.slip_ratio = 0.0;
+                state.slip_angle = 0.0;
+            }
+
+            // Wheel spin.
+            state.angular_velocity += (drive_torque - tire_torque) / wheel.inertia * dt;
+            let brake_velocity = brake_torque * wheel.brake_factor / wheel.inertia * dt;
+            if state.angular_velocity.abs() <= brake_velocity {
+                state.angular_velocity = 0.0;
+            } else {
+                state.angular_velocity -= state.angular_velocity.signum() * brake_velocity;
+            }
+            state.rotation_angle =
+                (state.rotation_angle + state.angular_velocity * dt) % std::f32::consts::TAU;
+
+            state.center = state.hard_point - up.scale(state.suspension_length);
+        }
+
+        if let Some(chassis) = ctx
+            .nodes
+            .try_borrow_mut(chassis_handle)
+            .and_then(|n| n.component_mut::<RigidBody>())
+        {
+            if !forces.is_empty() || self.throttle != 0.0 {
+                chassis.wake_up();
+            }
+            for (force, point) in forces {
+                chassis.apply_force_at_point(force, point);
+            }
+        }
+
+        // Sync visual wheels.
+        for wheel in self.wheels.iter() {
+            let Some(parent_transform) = ctx
+                .nodes
+                .try_borrow(wheel.node)
+                .and_then(|n| ctx.nodes.try_borrow(n.parent()))
+                .map(|p| p.global_transform())
+            else {
+                continue;
+            };
+            let parent_inv_transform = parent_transform.try_inverse().unwrap_or_default();
+            let parent_rotation = UnitQuaternion::from_matrix_eps(
+                &parent_transform.basis(),
+                f32::EPSILON,
+                16,
+                Default::default(),
+            );
+
+            let world_rotation = rotation
+                * UnitQuaternion::from_axis_angle(&Vector3::y_axis(), wheel.state.steering_angle)
+                * UnitQuaternion::from_axis_angle(&Vector3::x_axis(), wheel.state.rotation_angle);
+
+            if let Some(node) = ctx.nodes.try_borrow_mut(wheel.node) {
+                node.local_transform_mut()
+                    .set_position(
+                        parent_inv_transform
+                            .transform_point(&Point3::from(wheel.state.center))
+                            .coords,
+                    )
+                    .set_rotation(parent_rotation.inverse() * world_rotation);
+            }
+
+            Graph::update_hierarchical_data_recursively(
+                ctx.nodes,
+                ctx.sound_context,
+                ctx.physics,
+                ctx.physics2d,
+                wheel.node,
+            );
+        }
+    }
+
+    fn debug_draw(&self, ctx: &mut SceneDrawingContext) {
+        let transform = self.global_transform();
+        let rotation = UnitQuaternion::from_matrix_eps(
+            &transform.basis(),
+            f32::EPSILON,
+            16,
+            Default::default(),
+        );
+
+        for wheel in self.wheels.iter() {
+            let state = &wheel.state;
+            let color = if state.in_contact {
+                Color::GREEN
+            } else {
+                Color::RED
+            };
+
+            ctx.add_line(Line {
+                begin: state.hard_point,
+                end: state.center,
+                color,
+            });
+
+            // The circle is drawn in XY plane, rotate it so it will lie in the plane of the wheel.
+            let wheel_rotation = rotation
+                * UnitQuaternion::from_axis_angle(&Vector3::y_axis(), state.steering_angle)
+                * UnitQuaternion::from_axis_angle(&Vector3::y_axis(), std::f32::consts::FRAC_PI_2);
+            ctx.draw_circle(
+                Default::default(),
+                wheel.radius,
+                16,
+                Matrix4::new_translation(&state.center) * wheel_rotation.to_homogeneous(),
+                color,
+            );
+
+            if state.in_contact {
+                ctx.add_line(Line {
+                    begin: state.contact_point,
+                    end: state.contact_point
+                        + state.contact_normal.scale(
+                            state.suspension_force / wheel.max_suspension_force.max(f32::EPSILON),
+                        ),
+                    color: Color::opaque(255, 255, 0),
+                });
+            }
+        }
+    }
+
+    fn validate(&self, scene: &Scene) -> Result<(), String> {
+        if scene
+            .graph
+            .try_get(self.chassis_handle())
+            .and_then(|n| n.component_ref::<RigidBody>())
+            .is_none()
+        {
+            Err(
+                "The vehicle must be attached to a rigid body or must have a handle to \
+            a rigid body in the Chassis field!"
+                    .to_string(),
+            )
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Ray cast vehicle builder allows you to create [`RayCastVehicle`] nodes in declarative manner.
+pub struct RayCastVehicleBuilder {
+    base_builder: BaseBuilder,
+    chassis: Handle<Node>,
+    wheels: Vec<Wheel>,
+    engine_torque: Curve,
+    brake_torque: Curve,
+    max_steering_angle: f32,
+    groups: InteractionGroups,
+}
+
+impl RayCastVehicleBuilder {
+    /// Creates a new ray cast vehicle builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            chassis: Default::default(),
+            wheels: Default::default(),
+            engine_torque: Curve::from(vec![
+                CurveKey::new(0.0, 600.0, CurveKeyKind::Linear),
+                CurveKey::new(40.0, 600.0, CurveKeyKind::Linear),
+                CurveKey::new(60.0, 0.0, CurveKeyKind::Linear),
+            ]),
+            brake_torque: Curve::from(vec![CurveKey::new(0.0, 2000.0, CurveKeyKind::Constant)]),
+            max_steering_angle: 35.0f32.to_radians(),
+            groups: Default::default(),
+        }
+    }
+
+    /// Sets the desired chassis rigid body.
+    pub fn with_chassis(mut self, chassis: Handle<Node>) -> Self {
+        self.chassis = chassis;
+        self
+    }
+
+    /// Sets the desired set of wheels.
+    pub fn with_wheels(mut self, wheels: Vec<Wheel>) -> Self {
+        self.wheels = wheels;
+        self
+    }
+
+    /// Sets the desired engine torque curve.
+    pub fn with_engine_torque(mut self, engine_torque: Curve) -> Self {
+        self.engine_torque = engine_torque;
+        self
+    }
+
+    /// Sets the desired brake torque curve.
+    pub fn with_brake_torque(mut self, brake_torque: Curve) -> Self {
+        self.brake_torque = brake_torque;
+        self
+    }
+
+    /// Sets the desired maximum steering angle (in radians).
+    pub fn with_max_steering_angle(mut self, max_steering_angle: f32) -> Self {
+        self.max_steering_angle = max_steering_angle;
+        self
+    }
+
+    /// Sets the desired collision groups for wheel ray casts.
+    pub fn with_groups(mut self, groups: InteractionGroups) -> Self {
+        self.groups = groups;
+        self
+    }
+
+    /// Creates the ray cast vehicle, but does not add it to a graph.
+    pub fn build_ray_cast_vehicle(self) -> RayCastVehicle {
+        RayCastVehicle {
+            base: self.base_builder.build_base(),
+            chassis: self.chassis.into(),
+            wheels: self.wheels.into(),
+            engine_torque: self.engine_torque.into(),
+            brake_torque: self.brake_torque.into(),
+            max_steering_angle: self.max_steering_angle.into(),
+            groups: self.groups.into(),
+            throttle: 0.0,
+            brake: 0.0,
+            steering: 0.0,
+        }
+    }
+
+    /// Creates ray cast vehicle node, but does not add it to a graph.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_ray_cast_vehicle())
+    }
+
+    /// Creates ray cast vehicle node and adds it to the graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{Vector2, Vector3},
+        graph::SceneGraphNode,
+        scene::{
+            base::BaseBuilder,
+            collider::{ColliderBuilder, ColliderShape},
+            graph::Graph,
+            rigidbody::{RigidBodyBuilder, RigidBodyType},
+            transform::TransformBuilder,
+            vehicle::{RayCastVehicle, RayCastVehicleBuilder, Wheel},
+        },
+    };
+
+    #[test]
+    fn test_vehicle_rests_on_suspension() {
+        let mut graph = Graph::new();
+
+        let ground_collider = ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(ColliderShape::cuboid(50.0, 0.5, 50.0))
+            .build(&mut graph);
+        RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, -0.5, 0.0))
+                        .build(),
+                )
+                .with_children(&[ground_collider]),
+        )
+        .with_body_type(RigidBodyType::Static)
+        .build(&mut graph);
+
+        let wheels = [(-0.8, 1.2), (0.8, 1.2), (-0.8, -1.2), (0.8, -1.2)]
+            .into_iter()
+            .map(|(x, z)| Wheel {
+                connection_point: Vector3::new(x, 0.0, z),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        let vehicle = RayCastVehicleBuilder::new(BaseBuilder::new())
+            .with_wheels(wheels)
+            .build(&mut graph);
+        let chassis_collider = ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(ColliderShape::cuboid(1.0, 0.25, 2.0))
+            .build(&mut graph);
+        let chassis = RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, 0.8, 0.0))
+                        .build(),
+                )
+                .with_children(&[vehicle, chassis_collider]),
+        )
+        .with_mass(1000.0)
+        .build(&mut graph);
+
+        for _ in 0..300 {
+            graph.update(Vector2::new(800.0, 600.0), 1.0 / 60.0, Default::default());
+        }
+
+        let vehicle_ref = graph[vehicle].component_ref::<RayCastVehicle>().unwrap();
+        assert!(vehicle_ref.wheels.iter().all(|w| w.is_in_contact()));
+        // The chassis must be held above the ground by the suspension.
+        let height = graph[chassis].global_position().y;
+        assert!(height > 0.3 && height < 0.8, "{height}");
+    }
+}
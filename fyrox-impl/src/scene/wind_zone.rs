@@ -0,0 +1,226 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Wind zone is a force field, that affects aerodynamic bodies (cloth, soft bodies). See
+//! [`WindZone`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::Vector3,
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        uuid::{uuid, Uuid},
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    scene::{
+        base::{Base, BaseBuilder},
+        graph::Graph,
+        node::{constructor::NodeConstructor, Node, NodeTrait, UpdateContext},
+    },
+};
+use fyrox_graph::constructor::ConstructorProvider;
+use std::ops::{Deref, DerefMut};
+
+/// Wind zone defines velocity of air in some area of a scene. The wind blows along the look
+/// vector of the node. A zone with zero radius is global and affects the entire scene,
+/// otherwise the wind speed fades out linearly towards the border of the zone sphere.
+///
+/// Turbulence adds periodic gusts to the wind, that vary in space and time, so large surfaces
+/// (flags, curtains) do not move as a single piece.
+#[derive(Clone, Reflect, Visit, Debug, ComponentProvider)]
+#[visit(optional)]
+pub struct WindZone {
+    base: Base,
+    /// Speed of the wind (in m/s).
+    #[reflect(min_value = 0.0)]
+    pub strength: InheritableVariable<f32>,
+    /// Radius of the zone. Zero means that the zone is global.
+    #[reflect(min_value = 0.0)]
+    pub radius: InheritableVariable<f32>,
+    /// Amplitude of gusts relative to the strength of the wind.
+    #[reflect(min_value = 0.0)]
+    pub turbulence: InheritableVariable<f32>,
+    /// Frequency of gusts (in Hz).
+    #[reflect(min_value = 0.0)]
+    pub frequency: InheritableVariable<f32>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    time: f32,
+}
+
+impl Default for WindZone {
+    fn default() -> Self {
+        WindZoneBuilder::new(BaseBuilder::new()).build_wind_zone()
+    }
+}
+
+impl Deref for WindZone {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for WindZone {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for WindZone {
+    fn type_uuid() -> Uuid {
+        uuid!("e1d8c3b4-5a6f-4e70-9b81-2c3d4e5f6a7b")
+    }
+}
+
+impl ConstructorProvider<Node, Graph> for WindZone {
+    fn constructor() -> NodeConstructor {
+        NodeConstructor::new::<Self>()
+            .with_variant("Wind Zone", |_| {
+                WindZoneBuilder::new(BaseBuilder::new().with_name("Wind Zone"))
+                    .build_node()
+                    .into()
+            })
+            .with_group("Physics")
+    }
+}
+
+impl WindZone {
+    /// Returns velocity of the wind at the given point in world coordinates.
+    pub fn velocity_at(&self, position: Vector3<f32>) -> Vector3<f32> {
+        let Some(direction) = self.look_vector().try_normalize(f32::EPSILON) else {
+            return Vector3::zeros();
+        };
+
+        let attenuation = if *self.radius > 0.0 {
+            (1.0 - (position - self.global_position()).norm() / *self.radius).max(0.0)
+        } else {
+            1.0
+        };
+        if attenuation == 0.0 {
+            return Vector3::zeros();
+        }
+
+        // Gusts are travelling along the wind direction.
+        let phase =
+            std::f32::consts::TAU * *self.frequency * self.time - position.dot(&direction) * 0.25;
+        let gust = *self.turbulence
+            * (phase.sin()
+                + 0.5 * (2.3 * phase + position.x * 0.7).sin()
+                + 0.5 * (1.7 * phase + position.z * 0.9).cos())
+            / 2.0;
+
+        direction.scale(*self.strength * attenuation * (1.0 + gust))
+    }
+}
+
+impl NodeTrait for WindZone {
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        if *self.radius > 0.0 {
+            AxisAlignedBoundingBox::from_radius(*self.radius)
+        } else {
+            self.base.local_bounding_box()
+        }
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.local_bounding_box()
+            .transform(&self.global_transform())
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn update(&mut self, ctx: &mut UpdateContext) {
+        self.time += ctx.dt;
+    }
+}
+
+/// Wind zone builder allows you to create [`WindZone`] nodes in declarative manner.
+pub struct WindZoneBuilder {
+    base_builder: BaseBuilder,
+    strength: f32,
+    radius: f32,
+    turbulence: f32,
+    frequency: f32,
+}
+
+impl WindZoneBuilder {
+    /// Creates a new wind zone builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            strength: 5.0,
+            radius: 0.0,
+            turbulence: 0.5,
+            frequency: 0.5,
+        }
+    }
+
+    /// Sets the desired wind speed.
+    pub fn with_strength(mut self, strength: f32) -> Self {
+        self.strength = strength;
+        self
+    }
+
+    /// Sets the desired radius of the zone.
+    pub fn with_radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    /// Sets the desired turbulence.
+    pub fn with_turbulence(mut self, turbulence: f32) -> Self {
+        self.turbulence = turbulence;
+        self
+    }
+
+    /// Sets the desired frequency of gusts.
+    pub fn with_frequency(mut self, frequency: f32) -> Self {
+        self.frequency = frequency;
+        self
+    }
+
+    /// Creates the wind zone, but does not add it to a graph.
+    pub fn build_wind_zone(self) -> WindZone {
+        WindZone {
+            base: self.base_builder.build_base(),
+            strength: self.strength.into(),
+            radius: self.radius.into(),
+            turbulence: self.turbulence.into(),
+            frequency: self.frequency.into(),
+            time: 0.0,
+        }
+    }
+
+    /// Creates wind zone node, but does not add it to a graph.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_wind_zone())
+    }
+
+    /// Creates wind zone node and adds it to the graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
@@ -0,0 +1,423 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Coroutines for scripts. See [`CoroutineContext`] docs for more info and usage examples.
+
+use crate::{
+    core::{futures::task::noop_waker_ref, pool::Handle, uuid::Uuid},
+    scene::{node::Node, Scene},
+    script::{ScriptContext, ScriptTrait},
+};
+use std::{
+    any::Any,
+    cell::RefCell,
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+type CoroutineAction = Box<
+    dyn for<'a, 'b, 'c> FnOnce(
+        &mut dyn ScriptTrait,
+        &mut ScriptContext<'a, 'b, 'c>,
+    ) -> Box<dyn Any>,
+>;
+
+#[derive(Default)]
+struct CoroutineState {
+    frame: u64,
+    elapsed_time: f32,
+    scene_time: f32,
+    action: Option<CoroutineAction>,
+    action_result: Option<Box<dyn Any>>,
+}
+
+/// Coroutine context is a handle, that is given to a coroutine and allows it to wait for various
+/// events (timers, next frame, arbitrary conditions) and to access the script and its context. Every
+/// coroutine is executed cooperatively on the main thread, just after the scripts were updated. It
+/// allows you to write sequential game logic without a nest of flags in `on_update`.
+///
+/// ## Example
+///
+/// ```rust ,no_run
+/// # use fyrox_impl::{
+/// #     core::{pool::Handle, reflect::prelude::*, type_traits::prelude::*, visitor::prelude::*},
+/// #     scene::node::Node,
+/// #     script::{ScriptContext, ScriptTrait},
+/// # };
+/// #
+/// #[derive(Visit, Reflect, Default, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+/// #[type_uuid(id = "2b5ad6b1-9a8a-4d3c-8a51-6f3b1e9d8c42")]
+/// struct Door {
+///     player: Handle<Node>,
+///     open: bool,
+/// }
+///
+/// impl ScriptTrait for Door {
+///     fn on_start(&mut self, ctx: &mut ScriptContext) {
+///         ctx.task_pool.spawn_script_coroutine(
+///             ctx.scene_handle,
+///             ctx.handle,
+///             ctx.script_instance_id(),
+///             |co| async move {
+///                 // Wait until the player is close enough.
+///                 co.wait_until(|door: &mut Door, ctx| {
+///                     let door_position = ctx.scene.graph[ctx.handle].global_position();
+///                     ctx.scene
+///                         .graph
+///                         .try_get(door.player)
+///                         .map_or(false, |p| p.global_position().metric_distance(&door_position) < 2.0)
+///                 })
+///                 .await;
+///
+///                 co.wait(2.0).await;
+///
+///                 co.with(|door: &mut Door, _ctx| door.open = true).await;
+///             },
+///         );
+///     }
+/// }
+/// ```
+///
+/// Any other future can be awaited inside a coroutine as well, for example a resource:
+/// `resource_manager.request::<Model>("path/to/model.fbx").await`. Keep in mind, that coroutines
+/// are polled once per frame, so the awaited future will be checked at most once per frame.
+///
+/// A coroutine is bound to the instance of its script (see [`crate::script::Script::instance_id`]).
+/// It is destroyed when the script is removed or replaced by another one, or when the node of the
+/// script is destroyed.
+pub struct CoroutineContext<S> {
+    state: Rc<RefCell<CoroutineState>>,
+    phantom: PhantomData<fn() -> S>,
+}
+
+impl<S> Clone for CoroutineContext<S> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<S: ScriptTrait> CoroutineContext<S> {
+    /// Returns amount of time (in seconds) that passed from creation of the engine. The value is
+    /// updated once per frame, before the coroutine is resumed.
+    pub fn elapsed_time(&self) -> f32 {
+        self.state.borrow().elapsed_time
+    }
+
+    /// Returns a future, that will be resolved on the next frame.
+    pub fn next_frame(&self) -> NextFrame {
+        NextFrame {
+            state: self.state.clone(),
+            frame: self.state.borrow().frame,
+        }
+    }
+
+    /// Returns a future, that will be resolved after the given amount of seconds. The time is
+    /// measured in the time of the scene of the coroutine, which means that it respects the time
+    /// scale and the pause of the scene (see [`crate::scene::graph::time::SceneTime`] docs for more
+    /// info), unless the node of the script ignores time scale.
+    pub fn wait(&self, seconds: f32) -> Wait {
+        Wait {
+            state: self.state.clone(),
+            until: self.state.borrow().scene_time + seconds,
+        }
+    }
+
+    /// Executes the given closure with mutable access to the script and its context and returns
+    /// the result of the closure. The closure is executed on the same frame.
+    pub async fn with<F, R>(&self, func: F) -> R
+    where
+        F: for<'a, 'b, 'c> FnOnce(&mut S, &mut ScriptContext<'a, 'b, 'c>) -> R + 'static,
+        R: 'static,
+    {
+        {
+            let mut state = self.state.borrow_mut();
+            state.action_result = None;
+            state.action = Some(Box::new(
+                move |script: &mut dyn ScriptTrait, ctx: &mut ScriptContext| {
+                    let script = script
+                        .as_any_ref_mut()
+                        .downcast_mut::<S>()
+                        .expect("Types must match!");
+                    Box::new(func(script, ctx))
+                },
+            ));
+        }
+
+        let result = ActionResult {
+            state: self.state.clone(),
+        }
+        .await;
+
+        *result.downcast::<R>().expect("Types must match!")
+    }
+
+    /// Waits until the given predicate returns `true`. The predicate is checked once per frame and
+    /// has full access to the script and its context, so it could be used to wait for physics
+    /// events (contacts, intersections), animation signals, etc.
+    pub async fn wait_until<F>(&self, predicate: F)
+    where
+        F: for<'a, 'b, 'c> FnMut(&mut S, &mut ScriptContext<'a, 'b, 'c>) -> bool + 'static,
+    {
+        let predicate = Rc::new(RefCell::new(predicate));
+
+        loop {
+            let predicate = predicate.clone();
+            if self
+                .with(move |script, ctx| (&mut *predicate.borrow_mut())(script, ctx))
+                .await
+            {
+                break;
+            }
+
+            self.next_frame().await;
+        }
+    }
+}
+
+/// A future, that will be resolved on the next frame. See [`CoroutineContext::next_frame`].
+pub struct NextFrame {
+    state: Rc<RefCell<CoroutineState>>,
+    frame: u64,
+}
+
+impl Future for NextFrame {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.state.borrow().frame > self.frame {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// A future, that will be resolved after some time. See [`CoroutineContext::wait`].
+pub struct Wait {
+    state: Rc<RefCell<CoroutineState>>,
+    until: f32,
+}
+
+impl Future for Wait {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.state.borrow().scene_time >= self.until {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+struct ActionResult {
+    state: Rc<RefCell<CoroutineState>>,
+}
+
+impl Future for ActionResult {
+    type Output = Box<dyn Any>;
+
+    fn poll(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.state.borrow_mut().action_result.take() {
+            Some(result) => Poll::Ready(result),
+            None => Poll::Pending,
+        }
+    }
+}
+
+pub(crate) struct ScriptCoroutine {
+    pub(crate) scene_handle: Handle<Scene>,
+    pub(crate) node_handle: Handle<Node>,
+    pub(crate) script_instance_id: Uuid,
+    state: Rc<RefCell<CoroutineState>>,
+    future: Pin<Box<dyn Future<Output = ()>>>,
+}
+
+impl ScriptCoroutine {
+    pub(crate) fn new<S, F, Fut>(
+        scene_handle: Handle<Scene>,
+        node_handle: Handle<Node>,
+        script_instance_id: Uuid,
+        func: F,
+    ) -> Self
+    where
+        S: ScriptTrait,
+        F: FnOnce(CoroutineContext<S>) -> Fut,
+        Fut: Future<Output = ()> + 'static,
+    {
+        let state = Rc::new(RefCell::new(CoroutineState::default()));
+        let future = func(CoroutineContext {
+            state: state.clone(),
+            phantom: PhantomData,
+        });
+        Self {
+            scene_handle,
+            node_handle,
+            script_instance_id,
+            state,
+            future: Box::pin(future),
+        }
+    }
+
+    /// Returns current index of the script of the coroutine on the given node or `None` if the
+    /// script does not exist anymore.
+    pub(crate) fn script_index(&self, node: &Node) -> Option<usize> {
+        node.scripts.iter().position(|record| {
+            record
+                .as_ref()
+                .is_some_and(|script| script.instance_id() == self.script_instance_id)
+        })
+    }
+
+    /// Resumes the coroutine and runs it until it either finishes or waits for something. Returns
+    /// `true` if the coroutine has finished.
+    pub(crate) fn resume(
+        &mut self,
+        script: &mut dyn ScriptTrait,
+        context: &mut ScriptContext,
+    ) -> bool {
+        {
+            let mut state = self.state.borrow_mut();
+            state.frame += 1;
+            state.elapsed_time = context.elapsed_time;
+            // Delta time of the context is already scaled by the time scale of the scene.
+            state.scene_time += context.dt;
+        }
+
+        let mut cx = Context::from_waker(noop_waker_ref());
+
+        loop {
+            let action = self.state.borrow_mut().action.take();
+            if let Some(action) = action {
+                let result = action(script, context);
+                self.state.borrow_mut().action_result = Some(result);
+            }
+
+            if self.future.as_mut().poll(&mut cx).is_ready() {
+                return true;
+            }
+
+            // Keep running the coroutine on the same frame, if it wants to access the script again.
+            if self.state.borrow().action.is_none() {
+                return false;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            futures::task::noop_waker_ref, impl_component_provider, pool::Handle,
+            reflect::prelude::*, uuid_provider, visitor::prelude::*,
+        },
+        scene::{base::BaseBuilder, pivot::PivotBuilder},
+        script::{
+            coroutine::{CoroutineContext, CoroutineState, NextFrame, ScriptCoroutine, Wait},
+            Script, ScriptTrait,
+        },
+    };
+    use std::{
+        cell::RefCell,
+        future::Future,
+        pin::pin,
+        rc::Rc,
+        task::{Context, Poll},
+    };
+
+    #[test]
+    fn test_coroutine_timers() {
+        let state = Rc::new(RefCell::new(CoroutineState::default()));
+        let mut cx = Context::from_waker(noop_waker_ref());
+
+        let mut next_frame = pin!(NextFrame {
+            state: state.clone(),
+            frame: 0,
+        });
+        let mut wait = pin!(Wait {
+            state: state.clone(),
+            until: 1.0,
+        });
+
+        assert_eq!(next_frame.as_mut().poll(&mut cx), Poll::Pending);
+        assert_eq!(wait.as_mut().poll(&mut cx), Poll::Pending);
+
+        {
+            let mut state = state.borrow_mut();
+            state.frame += 1;
+            state.scene_time = 0.5;
+        }
+
+        assert_eq!(next_frame.as_mut().poll(&mut cx), Poll::Ready(()));
+        assert_eq!(wait.as_mut().poll(&mut cx), Poll::Pending);
+
+        // Engine time must not affect timers, only the scaled time of the scene does.
+        state.borrow_mut().elapsed_time = 10.0;
+        assert_eq!(wait.as_mut().poll(&mut cx), Poll::Pending);
+
+        state.borrow_mut().scene_time = 1.0;
+
+        assert_eq!(wait.as_mut().poll(&mut cx), Poll::Ready(()));
+    }
+
+    #[derive(Reflect, Visit, Debug, Clone, Default)]
+    struct MyScript;
+
+    impl_component_provider!(MyScript);
+    uuid_provider!(MyScript = "0f6d5d0e-4c63-4c8b-9a53-2f4f0b4d1e27");
+
+    impl ScriptTrait for MyScript {}
+
+    #[test]
+    fn test_coroutine_script_lookup() {
+        let mut node = PivotBuilder::new(
+            BaseBuilder::new()
+                .with_script(MyScript)
+                .with_script(MyScript),
+        )
+        .build_node();
+
+        let instance_id = node.script(1).unwrap().instance_id();
+        assert_ne!(node.script(0).unwrap().instance_id(), instance_id);
+
+        let coroutine = ScriptCoroutine::new(
+            Handle::NONE,
+            Handle::NONE,
+            instance_id,
+            |_: CoroutineContext<MyScript>| async {},
+        );
+        assert_eq!(coroutine.script_index(&node), Some(1));
+
+        // The coroutine must not be resumed for a script, that took the slot of its script.
+        node.replace_script(1, Some(Script::new(MyScript)));
+        assert_eq!(coroutine.script_index(&node), None);
+
+        // Copies of the script have their own ids.
+        let copy = node.script(0).unwrap().clone();
+        assert_ne!(copy.instance_id(), node.script(0).unwrap().instance_id());
+    }
+}
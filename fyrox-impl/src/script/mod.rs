@@ -33,7 +33,9 @@ use crate::{
         visitor::{Visit, VisitResult, Visitor},
         TypeUuidProvider,
     },
-    engine::{task::TaskPoolHandler, GraphicsContext, ScriptMessageDispatcher},
+    engine::{
+        simulation::Simulation, task::TaskPoolHandler, GraphicsContext, ScriptMessageDispatcher,
+    },
     event::Event,
     gui::UiContainer,
     plugin::{Plugin, PluginContainer},
@@ -48,11 +50,14 @@ use std::{
 };
 
 pub mod constructor;
+pub mod coroutine;
+pub mod profiler;
 
 pub(crate) trait UniversalScriptContext {
     fn node(&mut self) -> Option<&mut Node>;
     fn destroy_script_deferred(&self, script: Script, index: usize);
     fn set_script_index(&mut self, index: usize);
+    fn set_script_instance_id(&mut self, _instance_id: Uuid) {}
 }
 
 /// A script message's payload.
@@ -315,6 +320,8 @@ impl PluginsRefMut<'_> {
 /// A set of data, that provides contextual information for script methods.
 pub struct ScriptContext<'a, 'b, 'c> {
     /// Amount of time that passed from last call. It has valid values only when called from `on_update`.
+    /// The value is scaled by the time scale of the scene, unless the node ignores it (see
+    /// [`crate::scene::graph::time::SceneTime`] docs for more info).
     pub dt: f32,
 
     /// Amount of time (in seconds) that passed from creation of the engine. Keep in mind, that
@@ -396,8 +403,22 @@ pub struct ScriptContext<'a, 'b, 'c> {
     /// get a reference to it.
     pub user_interfaces: &'a mut UiContainer,
 
+    /// Simulation controls the way how the game logic is updated. Use it to get input and random
+    /// numbers in deterministic mode. See [`Simulation`] docs for more info.
+    pub simulation: &'a mut Simulation,
+
     /// Index of the script. Never save this index, it is only valid while this context exists!
     pub script_index: usize,
+
+    pub(crate) script_instance_id: Uuid,
+}
+
+impl ScriptContext<'_, '_, '_> {
+    /// Returns unique id of the script instance (see [`Script::instance_id`]). Unlike
+    /// [`Self::script_index`], it stays valid while the script exists.
+    pub fn script_instance_id(&self) -> Uuid {
+        self.script_instance_id
+    }
 }
 
 impl UniversalScriptContext for ScriptContext<'_, '_, '_> {
@@ -421,11 +442,17 @@ impl UniversalScriptContext for ScriptContext<'_, '_, '_> {
     fn set_script_index(&mut self, index: usize) {
         self.script_index = index;
     }
+
+    fn set_script_instance_id(&mut self, instance_id: Uuid) {
+        self.script_instance_id = instance_id;
+    }
 }
 
 /// A set of data, that provides contextual information for script methods.
 pub struct ScriptMessageContext<'a, 'b, 'c> {
     /// Amount of time that passed from last call. It has valid values only when called from `on_update`.
+    /// The value is scaled by the time scale of the scene, unless the node ignores it (see
+    /// [`crate::scene::graph::time::SceneTime`] docs for more info).
     pub dt: f32,
 
     /// Amount of time (in seconds) that passed from creation of the engine. Keep in mind, that
@@ -472,6 +499,10 @@ pub struct ScriptMessageContext<'a, 'b, 'c> {
     /// get a reference to it.
     pub user_interfaces: &'a mut UiContainer,
 
+    /// Simulation controls the way how the game logic is updated. Use it to get input and random
+    /// numbers in deterministic mode. See [`Simulation`] docs for more info.
+    pub simulation: &'a mut Simulation,
+
     /// Index of the script. Never save this index, it is only valid while this context exists!
     pub script_index: usize,
 }
@@ -656,6 +687,7 @@ pub trait ScriptTrait: BaseScript + ComponentProvider {
 #[derive(Debug)]
 pub struct Script {
     instance: Box<dyn ScriptTrait>,
+    instance_id: Uuid,
     pub(crate) initialized: bool,
     pub(crate) started: bool,
 }
@@ -796,6 +828,7 @@ impl Clone for Script {
     fn clone(&self) -> Self {
         Self {
             instance: self.instance.clone_box(),
+            instance_id: Uuid::new_v4(),
             initialized: false,
             started: false,
         }
@@ -808,11 +841,20 @@ impl Script {
     pub fn new<T: ScriptTrait>(script_object: T) -> Self {
         Self {
             instance: Box::new(script_object),
+            instance_id: Uuid::new_v4(),
             initialized: false,
             started: false,
         }
     }
 
+    /// Returns a unique id of the script instance. Unlike the index of the script on its node, it
+    /// cannot be reused by another script. The id is not serialized and every copy of the script
+    /// has its own id.
+    #[inline]
+    pub fn instance_id(&self) -> Uuid {
+        self.instance_id
+    }
+
     /// Performs downcasting to a particular type.
     #[inline]
     pub fn cast<T: ScriptTrait>(&self) -> Option<&T> {
@@ -0,0 +1,326 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Per-type timing collection for scripts. See [`ScriptProfiler`] docs for more info.
+
+use crate::{
+    core::{instant, reflect::Reflect, uuid::Uuid},
+    script::Script,
+};
+use fxhash::FxHashMap;
+use std::{
+    fmt::{Display, Formatter},
+    ops::Deref,
+    time::Duration,
+};
+
+/// A script method, that is being measured.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ScriptMethod {
+    /// [`crate::script::ScriptTrait::on_init`]
+    Init,
+    /// [`crate::script::ScriptTrait::on_start`]
+    Start,
+    /// [`crate::script::ScriptTrait::on_update`]
+    Update,
+    /// [`crate::script::ScriptTrait::on_message`]
+    Message,
+}
+
+/// Timings of a single script method.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct MethodTimings {
+    /// Total amount of calls of the method.
+    pub calls: usize,
+    /// Total amount of time spent in the method.
+    pub total: Duration,
+    /// Maximum amount of time spent in a single call of the method.
+    pub max: Duration,
+}
+
+impl MethodTimings {
+    fn add(&mut self, time: Duration) {
+        self.calls += 1;
+        self.total += time;
+        self.max = self.max.max(time);
+    }
+
+    fn merge(&mut self, other: &MethodTimings) {
+        self.calls += other.calls;
+        self.total += other.total;
+        self.max = self.max.max(other.max);
+    }
+
+    /// Returns average time of a single call of the method.
+    pub fn average(&self) -> Duration {
+        if self.calls == 0 {
+            Duration::default()
+        } else {
+            self.total / self.calls as u32
+        }
+    }
+}
+
+/// Timings of every measured method of a script type.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScriptTypeTimings {
+    /// Unique id of the script type.
+    pub type_uuid: Uuid,
+    /// Name of the script type.
+    pub type_name: &'static str,
+    /// Timings of [`crate::script::ScriptTrait::on_init`].
+    pub init: MethodTimings,
+    /// Timings of [`crate::script::ScriptTrait::on_start`].
+    pub start: MethodTimings,
+    /// Timings of [`crate::script::ScriptTrait::on_update`].
+    pub update: MethodTimings,
+    /// Timings of [`crate::script::ScriptTrait::on_message`].
+    pub message: MethodTimings,
+}
+
+impl ScriptTypeTimings {
+    fn new(type_uuid: Uuid, type_name: &'static str) -> Self {
+        Self {
+            type_uuid,
+            type_name,
+            init: Default::default(),
+            start: Default::default(),
+            update: Default::default(),
+            message: Default::default(),
+        }
+    }
+
+    fn method_mut(&mut self, method: ScriptMethod) -> &mut MethodTimings {
+        match method {
+            ScriptMethod::Init => &mut self.init,
+            ScriptMethod::Start => &mut self.start,
+            ScriptMethod::Update => &mut self.update,
+            ScriptMethod::Message => &mut self.message,
+        }
+    }
+
+    fn merge(&mut self, other: &ScriptTypeTimings) {
+        self.init.merge(&other.init);
+        self.start.merge(&other.start);
+        self.update.merge(&other.update);
+        self.message.merge(&other.message);
+    }
+
+    /// Returns total amount of time spent in every measured method.
+    pub fn total(&self) -> Duration {
+        self.init.total + self.start.total + self.update.total + self.message.total
+    }
+}
+
+/// A report of the script profiler. Script types are sorted by total time in descending order,
+/// which means that the most expensive script types go first.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ScriptProfilerReport {
+    /// Timings for the last frame.
+    pub last_frame: Vec<ScriptTypeTimings>,
+    /// Timings accumulated from the moment when the profiler was enabled (or reset).
+    pub accumulated: Vec<ScriptTypeTimings>,
+    /// Amount of frames in the accumulated timings.
+    pub frames: usize,
+}
+
+impl Display for ScriptProfilerReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Script Timings (Last Frame):")?;
+        for timings in self.last_frame.iter() {
+            writeln!(
+                f,
+                "\t{}: {:?} (update: {:?} [{}], message: {:?} [{}], init: {:?}, start: {:?})",
+                timings.type_name,
+                timings.total(),
+                timings.update.total,
+                timings.update.calls,
+                timings.message.total,
+                timings.message.calls,
+                timings.init.total,
+                timings.start.total
+            )?;
+        }
+        writeln!(f, "Script Timings (Average of {} Frames):", self.frames)?;
+        let frames = self.frames.max(1) as u32;
+        for timings in self.accumulated.iter() {
+            writeln!(
+                f,
+                "\t{}: {:?} (update: {:?}, message: {:?}, max update: {:?})",
+                timings.type_name,
+                timings.total() / frames,
+                timings.update.total / frames,
+                timings.message.total / frames,
+                timings.update.max,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Script profiler collects per-type timings of script methods (initialization, starting, updating,
+/// message handling). It allows you to find which script types are the most expensive without any
+/// external profilers. The profiler is disabled by default, because time measurement has its own
+/// cost.
+///
+/// ```rust
+/// # use fyrox_impl::engine::Engine;
+/// fn print_script_timings(engine: &mut Engine) {
+///     engine.script_processor.profiler.set_enabled(true);
+///
+///     // ...
+///
+///     println!("{}", engine.script_processor.profiler.report());
+/// }
+/// ```
+#[derive(Default, Debug)]
+pub struct ScriptProfiler {
+    enabled: bool,
+    current_frame: FxHashMap<Uuid, ScriptTypeTimings>,
+    last_frame: FxHashMap<Uuid, ScriptTypeTimings>,
+    accumulated: FxHashMap<Uuid, ScriptTypeTimings>,
+    frames: usize,
+}
+
+impl ScriptProfiler {
+    /// Enables or disables the profiler. Disabling the profiler does not reset collected timings.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Returns `true` if the profiler is enabled, `false` - otherwise.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Resets every collected timing.
+    pub fn reset(&mut self) {
+        self.current_frame.clear();
+        self.last_frame.clear();
+        self.accumulated.clear();
+        self.frames = 0;
+    }
+
+    /// Begins measurement of a script method. Returns `None` if the profiler is disabled.
+    #[inline]
+    pub fn begin(&self) -> Option<instant::Instant> {
+        if self.enabled {
+            Some(instant::Instant::now())
+        } else {
+            None
+        }
+    }
+
+    /// Ends measurement of a script method, that was started with [`Self::begin`].
+    #[inline]
+    pub fn end(&mut self, script: &Script, method: ScriptMethod, begin: Option<instant::Instant>) {
+        if let Some(begin) = begin {
+            self.add(script, method, instant::Instant::now() - begin);
+        }
+    }
+
+    /// Adds a timing of a script method.
+    pub fn add(&mut self, script: &Script, method: ScriptMethod, time: Duration) {
+        let script = script.deref();
+        self.current_frame
+            .entry(script.id())
+            .or_insert_with(|| ScriptTypeTimings::new(script.id(), Reflect::type_name(script)))
+            .method_mut(method)
+            .add(time);
+    }
+
+    /// Finishes current frame. It is called automatically by the engine at the end of script
+    /// processing.
+    pub fn end_frame(&mut self) {
+        if !self.enabled {
+            return;
+        }
+
+        for (type_uuid, timings) in self.current_frame.iter() {
+            self.accumulated
+                .entry(*type_uuid)
+                .or_insert_with(|| ScriptTypeTimings::new(timings.type_uuid, timings.type_name))
+                .merge(timings);
+        }
+        self.last_frame = std::mem::take(&mut self.current_frame);
+        self.frames += 1;
+    }
+
+    /// Creates a new report from the collected timings.
+    pub fn report(&self) -> ScriptProfilerReport {
+        fn sorted(timings: &FxHashMap<Uuid, ScriptTypeTimings>) -> Vec<ScriptTypeTimings> {
+            let mut timings = timings.values().cloned().collect::<Vec<_>>();
+            timings.sort_by(|a, b| b.total().cmp(&a.total()));
+            timings
+        }
+
+        ScriptProfilerReport {
+            last_frame: sorted(&self.last_frame),
+            accumulated: sorted(&self.accumulated),
+            frames: self.frames,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::uuid::Uuid,
+        script::profiler::{ScriptMethod, ScriptProfiler, ScriptTypeTimings},
+    };
+    use std::time::Duration;
+
+    #[test]
+    fn test_script_profiler_report() {
+        let mut profiler = ScriptProfiler::default();
+        profiler.set_enabled(true);
+
+        let cheap = Uuid::new_v4();
+        let expensive = Uuid::new_v4();
+        for (uuid, name, time) in [(cheap, "Cheap", 1), (expensive, "Expensive", 10)] {
+            let timings = profiler
+                .current_frame
+                .entry(uuid)
+                .or_insert_with(|| ScriptTypeTimings::new(uuid, name));
+            timings
+                .method_mut(ScriptMethod::Update)
+                .add(Duration::from_millis(time));
+            timings
+                .method_mut(ScriptMethod::Update)
+                .add(Duration::from_millis(time));
+        }
+        profiler.end_frame();
+
+        let report = profiler.report();
+        assert_eq!(report.frames, 1);
+        assert_eq!(report.last_frame.len(), 2);
+        assert_eq!(report.last_frame[0].type_name, "Expensive");
+        assert_eq!(report.last_frame[0].update.calls, 2);
+        assert_eq!(report.last_frame[0].update.total, Duration::from_millis(20));
+        assert_eq!(
+            report.last_frame[0].update.average(),
+            Duration::from_millis(10)
+        );
+        assert_eq!(report.accumulated[1].type_name, "Cheap");
+
+        profiler.reset();
+        assert_eq!(profiler.report(), Default::default());
+    }
+}
@@ -0,0 +1,564 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Runtime level editing toolkit. It is meant to be used in games with built-in level editors or
+//! user-generated content. See [`LevelEditor`] docs for more info.
+
+use crate::{
+    asset::io::{ResourceIo, ResourceIoFuture},
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        io::FileLoadError,
+        log::Log,
+        pool::Handle,
+        visitor::{VisitError, Visitor},
+    },
+    graph::BaseSceneGraph,
+    resource::model::{ModelResource, ModelResourceExtension},
+    scene::{graph::SubGraph, node::Node, Scene},
+};
+use std::{
+    fmt::{Display, Formatter},
+    future::ready,
+    path::Path,
+};
+
+/// An error, that may occur during saving of the edited content.
+#[derive(Debug)]
+pub enum LevelEditorError {
+    /// Unable to serialize the content.
+    Visit(VisitError),
+    /// Unable to write the content using the resource IO.
+    Io(FileLoadError),
+    /// The node does not exist.
+    InvalidHandle(Handle<Node>),
+}
+
+impl Display for LevelEditorError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LevelEditorError::Visit(v) => Display::fmt(v, f),
+            LevelEditorError::Io(v) => write!(f, "Io error: {v:?}"),
+            LevelEditorError::InvalidHandle(v) => write!(f, "Invalid node handle {v}"),
+        }
+    }
+}
+
+impl From<VisitError> for LevelEditorError {
+    fn from(e: VisitError) -> Self {
+        Self::Visit(e)
+    }
+}
+
+impl From<FileLoadError> for LevelEditorError {
+    fn from(e: FileLoadError) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// Grid snapping settings. When enabled, every position passed to the level editor will be
+/// snapped to the closest point of the grid.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GridSnapping {
+    /// A flag, that defines whether the snapping is enabled or not.
+    pub enabled: bool,
+    /// Size of a grid cell along each axis. Zero (or negative) value disables snapping on the
+    /// respective axis.
+    pub step: Vector3<f32>,
+}
+
+impl Default for GridSnapping {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            step: Vector3::repeat(1.0),
+        }
+    }
+}
+
+impl GridSnapping {
+    /// Snaps the given position to the grid. Returns the position as is, if the snapping is
+    /// disabled.
+    pub fn snap(&self, position: Vector3<f32>) -> Vector3<f32> {
+        if !self.enabled {
+            return position;
+        }
+
+        position.zip_map(&self.step, |p, step| {
+            if step > 0.0 {
+                (p / step).round() * step
+            } else {
+                p
+            }
+        })
+    }
+}
+
+/// A named prefab in a palette.
+#[derive(Clone, Debug)]
+pub struct PaletteEntry {
+    /// A name of the entry, that could be shown in the UI.
+    pub name: String,
+    /// A prefab, that will be instantiated.
+    pub prefab: ModelResource,
+}
+
+/// A set of prefabs, that could be placed in a scene by a user.
+#[derive(Clone, Debug, Default)]
+pub struct PrefabPalette {
+    entries: Vec<PaletteEntry>,
+    selected: Option<usize>,
+}
+
+impl PrefabPalette {
+    /// Adds a new prefab to the palette and returns its index.
+    pub fn add(&mut self, name: impl Into<String>, prefab: ModelResource) -> usize {
+        self.entries.push(PaletteEntry {
+            name: name.into(),
+            prefab,
+        });
+        self.entries.len() - 1
+    }
+
+    /// Removes a prefab at the given index. Resets selection if the selected prefab was removed.
+    pub fn remove(&mut self, index: usize) -> Option<PaletteEntry> {
+        if index >= self.entries.len() {
+            return None;
+        }
+
+        match self.selected {
+            Some(selected) if selected == index => self.selected = None,
+            Some(selected) if selected > index => self.selected = Some(selected - 1),
+            _ => (),
+        }
+
+        Some(self.entries.remove(index))
+    }
+
+    /// Returns a slice of all the prefabs in the palette.
+    pub fn entries(&self) -> &[PaletteEntry] {
+        &self.entries
+    }
+
+    /// Selects a prefab at the given index. `None` clears selection.
+    pub fn select(&mut self, index: Option<usize>) {
+        self.selected = index.filter(|i| *i < self.entries.len());
+    }
+
+    /// Returns currently selected prefab (if any).
+    pub fn selected(&self) -> Option<&PaletteEntry> {
+        self.selected.and_then(|i| self.entries.get(i))
+    }
+}
+
+#[derive(Debug)]
+enum LevelEditCommand {
+    Spawn {
+        handle: Handle<Node>,
+        // Some when the command is reverted.
+        sub_graph: Option<SubGraph>,
+    },
+    Delete {
+        handle: Handle<Node>,
+        // Some when the command is executed.
+        sub_graph: Option<SubGraph>,
+    },
+    Move {
+        handle: Handle<Node>,
+        old_position: Vector3<f32>,
+        new_position: Vector3<f32>,
+    },
+}
+
+impl LevelEditCommand {
+    fn revert(&mut self, scene: &mut Scene) {
+        match self {
+            LevelEditCommand::Spawn { handle, sub_graph } => {
+                *sub_graph = Some(scene.graph.take_reserve_sub_graph(*handle));
+            }
+            LevelEditCommand::Delete { sub_graph, .. } => {
+                if let Some(sub_graph) = sub_graph.take() {
+                    scene.graph.put_sub_graph_back(sub_graph);
+                }
+            }
+            LevelEditCommand::Move {
+                handle,
+                old_position,
+                ..
+            } => {
+                if let Some(node) = scene.graph.try_get_mut(*handle) {
+                    node.local_transform_mut().set_position(*old_position);
+                }
+            }
+        }
+    }
+
+    fn execute(&mut self, scene: &mut Scene) {
+        match self {
+            LevelEditCommand::Spawn { sub_graph, .. } => {
+                if let Some(sub_graph) = sub_graph.take() {
+                    scene.graph.put_sub_graph_back(sub_graph);
+                }
+            }
+            LevelEditCommand::Delete { handle, sub_graph } => {
+                *sub_graph = Some(scene.graph.take_reserve_sub_graph(*handle));
+            }
+            LevelEditCommand::Move {
+                handle,
+                new_position,
+                ..
+            } => {
+                if let Some(node) = scene.graph.try_get_mut(*handle) {
+                    node.local_transform_mut().set_position(*new_position);
+                }
+            }
+        }
+    }
+
+    fn finalize(self, scene: &mut Scene) {
+        match self {
+            LevelEditCommand::Spawn { sub_graph, .. }
+            | LevelEditCommand::Delete { sub_graph, .. } => {
+                if let Some(sub_graph) = sub_graph {
+                    scene.graph.forget_sub_graph(sub_graph);
+                }
+            }
+            LevelEditCommand::Move { .. } => {}
+        }
+    }
+}
+
+/// Level editor is a runtime toolkit for scene editing. It allows you to spawn prefabs from a
+/// palette, move and delete nodes with unlimited (or limited) undo/redo, snap positions to a grid
+/// and save the result as a scene or a prefab using a resource IO.
+///
+/// ## Important
+///
+/// Deleted nodes (and spawned nodes, that were reverted) are kept detached from the graph, so they
+/// could be restored later on. This means that [`LevelEditor::clear_history`] must be called with
+/// the edited scene before the level editor is dropped, otherwise the detached nodes will never be
+/// released.
+///
+/// ## Example
+///
+/// ```rust ,no_run
+/// # use fyrox_impl::{
+/// #     asset::{io::FsResourceIo, manager::ResourceManager},
+/// #     core::algebra::Vector3,
+/// #     resource::model::Model,
+/// #     scene::Scene,
+/// #     utils::level_editor::LevelEditor,
+/// # };
+/// # use std::path::Path;
+/// async fn edit(scene: &mut Scene, resource_manager: &ResourceManager) {
+///     let mut editor = LevelEditor::new();
+///     editor
+///         .palette
+///         .add("Crate", resource_manager.request::<Model>("data/crate.rgs"));
+///     editor.palette.select(Some(0));
+///
+///     let handle = editor
+///         .spawn_selected(scene, Vector3::new(1.2, 0.0, 2.7))
+///         .unwrap();
+///     editor.move_node(scene, handle, Vector3::new(3.4, 0.0, 2.0));
+///     editor.undo(scene);
+///
+///     LevelEditor::save_scene(scene, Path::new("data/my_level.rgs"), &FsResourceIo)
+///         .await
+///         .unwrap();
+///
+///     editor.clear_history(scene);
+/// }
+/// ```
+pub struct LevelEditor {
+    /// Grid snapping settings.
+    pub snapping: GridSnapping,
+    /// A set of prefabs, that could be placed in a scene.
+    pub palette: PrefabPalette,
+    commands: Vec<LevelEditCommand>,
+    top: usize,
+    max_history: Option<usize>,
+}
+
+impl Default for LevelEditor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LevelEditor {
+    /// Creates a new level editor with unlimited history.
+    pub fn new() -> Self {
+        Self {
+            snapping: Default::default(),
+            palette: Default::default(),
+            commands: Default::default(),
+            top: 0,
+            max_history: None,
+        }
+    }
+
+    /// Sets maximum amount of commands in the history. `None` - unlimited history.
+    pub fn with_max_history(mut self, max_history: Option<usize>) -> Self {
+        self.max_history = max_history;
+        self
+    }
+
+    fn push(&mut self, scene: &mut Scene, command: LevelEditCommand) {
+        // Drop reverted commands, they cannot be re-done anymore.
+        for command in self.commands.drain(self.top..) {
+            command.finalize(scene);
+        }
+
+        self.commands.push(command);
+
+        if let Some(max_history) = self.max_history {
+            while self.commands.len() > max_history {
+                self.commands.remove(0).finalize(scene);
+            }
+        }
+
+        self.top = self.commands.len();
+    }
+
+    /// Instantiates the given prefab at the given position (it will be snapped to the grid) with
+    /// the given rotation. Returns a handle of the root node of the instance.
+    pub fn spawn(
+        &mut self,
+        scene: &mut Scene,
+        prefab: &ModelResource,
+        position: Vector3<f32>,
+        rotation: UnitQuaternion<f32>,
+    ) -> Handle<Node> {
+        let handle = prefab.instantiate_at(scene, self.snapping.snap(position), rotation);
+        self.push(
+            scene,
+            LevelEditCommand::Spawn {
+                handle,
+                sub_graph: None,
+            },
+        );
+        handle
+    }
+
+    /// Instantiates currently selected prefab of the palette at the given position (it will be
+    /// snapped to the grid). Returns `None` if there's no selected prefab.
+    pub fn spawn_selected(
+        &mut self,
+        scene: &mut Scene,
+        position: Vector3<f32>,
+    ) -> Option<Handle<Node>> {
+        let prefab = self.palette.selected()?.prefab.clone();
+        Some(self.spawn(scene, &prefab, position, UnitQuaternion::identity()))
+    }
+
+    /// Moves a node to the given local position (it will be snapped to the grid).
+    pub fn move_node(&mut self, scene: &mut Scene, handle: Handle<Node>, position: Vector3<f32>) {
+        let Some(node) = scene.graph.try_get_mut(handle) else {
+            Log::warn(format!("Unable to move node {handle}, it does not exist!"));
+            return;
+        };
+
+        let old_position = **node.local_transform().position();
+        let new_position = self.snapping.snap(position);
+        if old_position == new_position {
+            return;
+        }
+
+        let mut command = LevelEditCommand::Move {
+            handle,
+            old_position,
+            new_position,
+        };
+        command.execute(scene);
+        self.push(scene, command);
+    }
+
+    /// Deletes a node with all its descendants. The root node of the scene cannot be deleted.
+    pub fn delete_node(&mut self, scene: &mut Scene, handle: Handle<Node>) {
+        if !scene.graph.is_valid_handle(handle) || handle == scene.graph.get_root() {
+            Log::warn(format!("Unable to delete node {handle}!"));
+            return;
+        }
+
+        let mut command = LevelEditCommand::Delete {
+            handle,
+            sub_graph: None,
+        };
+        command.execute(scene);
+        self.push(scene, command);
+    }
+
+    /// Returns `true` if there's a command, that could be reverted.
+    pub fn can_undo(&self) -> bool {
+        self.top > 0
+    }
+
+    /// Returns `true` if there's a reverted command, that could be executed again.
+    pub fn can_redo(&self) -> bool {
+        self.top < self.commands.len()
+    }
+
+    /// Reverts the last command. Returns `false` if there's nothing to revert.
+    pub fn undo(&mut self, scene: &mut Scene) -> bool {
+        if !self.can_undo() {
+            return false;
+        }
+        self.top -= 1;
+        self.commands[self.top].revert(scene);
+        true
+    }
+
+    /// Executes the last reverted command again. Returns `false` if there's nothing to execute.
+    pub fn redo(&mut self, scene: &mut Scene) -> bool {
+        if !self.can_redo() {
+            return false;
+        }
+        self.commands[self.top].execute(scene);
+        self.top += 1;
+        true
+    }
+
+    /// Clears the history and destroys every detached node, that was kept for undo/redo.
+    pub fn clear_history(&mut self, scene: &mut Scene) {
+        for command in self.commands.drain(..) {
+            command.finalize(scene);
+        }
+        self.top = 0;
+    }
+
+    /// Serializes the entire scene and writes it to the given path using the given resource IO.
+    pub fn save_scene<'a>(
+        scene: &mut Scene,
+        path: &'a Path,
+        io: &'a dyn ResourceIo,
+    ) -> ResourceIoFuture<'a, Result<(), LevelEditorError>> {
+        let mut visitor = Visitor::new();
+        let data = scene
+            .save("Scene", &mut visitor)
+            .and_then(|_| visitor.save_binary_to_vec());
+        write_data(data, path, io)
+    }
+
+    /// Copies a node with all its descendants into a new scene and writes it to the given path
+    /// using the given resource IO. The saved scene could then be used as a prefab.
+    pub fn save_prefab<'a>(
+        scene: &Scene,
+        root: Handle<Node>,
+        path: &'a Path,
+        io: &'a dyn ResourceIo,
+    ) -> ResourceIoFuture<'a, Result<(), LevelEditorError>> {
+        if !scene.graph.is_valid_handle(root) {
+            return Box::pin(ready(Err(LevelEditorError::InvalidHandle(root))));
+        }
+
+        let mut prefab = Scene::new();
+        let (copy, _) = scene.graph.copy_node(
+            root,
+            &mut prefab.graph,
+            &mut |_, _| true,
+            &mut |_, _| {},
+            &mut |_, _, _| {},
+        );
+        let prefab_root = prefab.graph.get_root();
+        prefab.graph.link_nodes(copy, prefab_root);
+
+        let mut visitor = Visitor::new();
+        let data = prefab
+            .save("Scene", &mut visitor)
+            .and_then(|_| visitor.save_binary_to_vec());
+        write_data(data, path, io)
+    }
+}
+
+fn write_data<'a>(
+    data: Result<Vec<u8>, VisitError>,
+    path: &'a Path,
+    io: &'a dyn ResourceIo,
+) -> ResourceIoFuture<'a, Result<(), LevelEditorError>> {
+    match data {
+        Ok(data) => Box::pin(async move {
+            io.write_file(path, data)
+                .await
+                .map_err(LevelEditorError::Io)
+        }),
+        Err(err) => Box::pin(ready(Err(err.into()))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Vector3, pool::Handle},
+        graph::BaseSceneGraph,
+        scene::{base::BaseBuilder, pivot::PivotBuilder, Scene},
+        utils::level_editor::{GridSnapping, LevelEditor},
+    };
+
+    #[test]
+    fn test_grid_snapping() {
+        let snapping = GridSnapping {
+            enabled: true,
+            step: Vector3::new(1.0, 0.0, 0.5),
+        };
+        assert_eq!(
+            snapping.snap(Vector3::new(1.4, 2.3, 0.8)),
+            Vector3::new(1.0, 2.3, 1.0)
+        );
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut scene = Scene::new();
+        let node = PivotBuilder::new(BaseBuilder::new()).build(&mut scene.graph);
+
+        let mut editor = LevelEditor::new();
+        editor.move_node(&mut scene, node, Vector3::new(1.2, 0.0, 0.0));
+        assert_eq!(
+            **scene.graph[node].local_transform().position(),
+            Vector3::new(1.0, 0.0, 0.0)
+        );
+
+        editor.delete_node(&mut scene, node);
+        assert!(!scene.graph.is_valid_handle(node));
+
+        assert!(editor.undo(&mut scene));
+        assert!(scene.graph.is_valid_handle(node));
+
+        assert!(editor.undo(&mut scene));
+        assert_eq!(
+            **scene.graph[node].local_transform().position(),
+            Vector3::default()
+        );
+        assert!(!editor.undo(&mut scene));
+
+        assert!(editor.redo(&mut scene));
+        assert!(editor.redo(&mut scene));
+        assert!(!scene.graph.is_valid_handle(node));
+
+        // Root node must not be deleted.
+        let root = scene.graph.get_root();
+        editor.delete_node(&mut scene, root);
+        assert_ne!(scene.graph.get_root(), Handle::NONE);
+
+        editor.clear_history(&mut scene);
+        assert!(!editor.can_undo());
+    }
+}
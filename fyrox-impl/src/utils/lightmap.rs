@@ -22,7 +22,15 @@
 //!
 //! # Performance
 //!
-//! This is CPU lightmapper, its performance is linear with core count of your CPU.
+//! There are two lightmap bakers: CPU ray tracer and GPU rasterizer (see [`LightmapBaker`]). CPU
+//! baker's performance is linear with core count of your CPU, it does not need a graphics context
+//! and can run in a background thread. GPU baker is orders of magnitude faster on large scenes, but
+//! it must run on the thread that owns the graphics context.
+//!
+//! # Incremental baking
+//!
+//! Every lightmap stores fingerprints of the baked objects and lights, which allows re-baking only
+//! the objects that were changed since the last bake, see [`Lightmap::rebake`].
 
 #![forbid(unsafe_code)]
 
@@ -30,22 +38,29 @@ use crate::material::MaterialResourceBinding;
 use crate::{
     asset::manager::{ResourceManager, ResourceRegistrationError},
     core::{
-        algebra::{Matrix3, Matrix4, Point3, Vector2, Vector3},
+        algebra::{Matrix3, Matrix4, Point3, Vector2, Vector3, Vector4},
+        log::Log,
         math::{Matrix4Ext, TriangleDefinition},
         pool::Handle,
         reflect::prelude::*,
         visitor::{prelude::*, BinaryBlob},
     },
     graph::SceneGraph,
+    renderer::{
+        framework::error::FrameworkError,
+        lightmap_baker::{GpuLightmapBaker, GpuLightmapTexels},
+    },
     resource::texture::{Texture, TextureKind, TexturePixelKind, TextureResource},
     scene::{
         light::{directional::DirectionalLight, point::PointLight, spot::SpotLight},
         mesh::{
             buffer::{
-                VertexAttributeDataType, VertexAttributeDescriptor, VertexAttributeUsage,
-                VertexFetchError, VertexReadTrait, VertexWriteTrait,
+                TriangleBuffer, VertexAttributeDataType, VertexAttributeDescriptor,
+                VertexAttributeUsage, VertexBuffer, VertexFetchError, VertexReadTrait,
+                VertexWriteTrait,
             },
             surface::{SurfaceData, SurfaceResource},
+            vertex::StaticVertex,
             Mesh,
         },
         node::Node,
@@ -53,13 +68,14 @@ use crate::{
     },
     utils::{uvgen, uvgen::SurfaceDataPatch},
 };
-use fxhash::FxHashMap;
+use fxhash::{FxHashMap, FxHashSet, FxHasher};
 use lightmap::light::{
     DirectionalLightDefinition, LightDefinition, PointLightDefinition, SpotLightDefinition,
 };
 use rayon::prelude::*;
 use std::{
     fmt::{Display, Formatter},
+    hash::{Hash, Hasher},
     ops::Deref,
     path::Path,
     sync::{
@@ -68,6 +84,16 @@ use std::{
     },
 };
 
+/// Minimal size of a lightmap produced by the GPU baker.
+const MIN_GPU_LIGHTMAP_SIZE: usize = 4;
+/// Maximal size of a lightmap produced by the GPU baker.
+const MAX_GPU_LIGHTMAP_SIZE: usize = 4096;
+/// Amount of dilation iterations, that is used to fill gaps between UV islands. It prevents dark
+/// seams from appearing, when lightmaps are sampled with bilinear filtration.
+const DILATION_ITERATIONS: u32 = 4;
+/// Resolution of the grid, that is used to check lightmap UVs for overlaps.
+const UV_VALIDATION_RESOLUTION: u32 = 512;
+
 /// Applies surface data patch to a surface data.
 pub fn apply_surface_data_patch(data: &mut SurfaceData, patch: &SurfaceDataPatch) {
     if !data
@@ -169,13 +195,100 @@ pub struct Lightmap {
     // We don't need to inspect patches, because they contain no useful data.
     #[reflect(hidden)]
     pub patches: FxHashMap<u64, SurfaceDataPatchWrapper>,
+
+    /// Fingerprints of the baked objects and lights. They're used to find objects, that must be
+    /// re-baked after changes in the scene.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    pub fingerprint: LightmapFingerprint,
+}
+
+/// Fingerprints of the baked objects and lights. See [`LightmapInputData::changed_nodes`] for more
+/// info.
+#[derive(Default, Clone, Debug, Visit)]
+pub struct LightmapFingerprint {
+    /// Node handle to the hash of its transform and surfaces.
+    pub nodes: FxHashMap<Handle<Node>, u64>,
+    /// Content hashes of the surface data that already have lightmap texture coordinates.
+    pub surfaces: FxHashSet<u64>,
+    /// Combined hash of all light sources.
+    pub lights: u64,
 }
 
 struct Instance {
     owner: Handle<Node>,
     source_data: SurfaceResource,
-    data: Option<lightmap::input::Mesh>,
     transform: Matrix4<f32>,
+    // Calculated on geometry caching stage.
+    world_vertices: Vec<lightmap::input::WorldVertex>,
+    triangles: Vec<[u32; 3]>,
+}
+
+fn node_fingerprints(instances: &[Instance]) -> FxHashMap<Handle<Node>, u64> {
+    let mut hashers = FxHashMap::<Handle<Node>, FxHasher>::default();
+    for instance in instances {
+        let hasher = hashers.entry(instance.owner).or_default();
+        for element in instance.transform.iter() {
+            element.to_bits().hash(hasher);
+        }
+        instance.source_data.data_ref().content_hash().hash(hasher);
+    }
+    hashers
+        .into_iter()
+        .map(|(handle, hasher)| (handle, hasher.finish()))
+        .collect()
+}
+
+fn lights_fingerprint(lights: &FxHashMap<Handle<Node>, LightDefinition>) -> u64 {
+    let mut handles = lights.keys().cloned().collect::<Vec<_>>();
+    handles.sort_by_key(|h| (h.index(), h.generation()));
+
+    let mut hasher = FxHasher::default();
+    for handle in handles {
+        let values = match &lights[&handle] {
+            LightDefinition::Directional(directional) => vec![
+                0.0,
+                directional.intensity,
+                directional.direction.x,
+                directional.direction.y,
+                directional.direction.z,
+                directional.color.x,
+                directional.color.y,
+                directional.color.z,
+            ],
+            LightDefinition::Spot(spot) => vec![
+                1.0,
+                spot.intensity,
+                spot.edge0,
+                spot.edge1,
+                spot.color.x,
+                spot.color.y,
+                spot.color.z,
+                spot.direction.x,
+                spot.direction.y,
+                spot.direction.z,
+                spot.position.x,
+                spot.position.y,
+                spot.position.z,
+                spot.distance,
+            ],
+            LightDefinition::Point(point) => vec![
+                2.0,
+                point.intensity,
+                point.position.x,
+                point.position.y,
+                point.position.z,
+                point.color.x,
+                point.color.y,
+                point.color.z,
+                point.radius,
+            ],
+        };
+        for value in values {
+            value.to_bits().hash(&mut hasher);
+        }
+    }
+    hasher.finish()
 }
 
 /// Small helper that allows you stop lightmap generation in any time.
@@ -305,6 +418,8 @@ pub enum LightmapGenerationError {
     InvalidIndex,
     /// Vertex buffer of a mesh lacks required data.
     InvalidData(VertexFetchError),
+    /// GPU baker failed to render a lightmap.
+    Graphics(FrameworkError),
 }
 
 impl Display for LightmapGenerationError {
@@ -319,6 +434,9 @@ impl Display for LightmapGenerationError {
             LightmapGenerationError::InvalidData(v) => {
                 write!(f, "Vertex buffer of a mesh lacks required data {v}.")
             }
+            LightmapGenerationError::Graphics(v) => {
+                write!(f, "GPU baker failed to render a lightmap: {v:?}.")
+            }
         }
     }
 }
@@ -329,6 +447,317 @@ impl From<VertexFetchError> for LightmapGenerationError {
     }
 }
 
+impl From<FrameworkError> for LightmapGenerationError {
+    fn from(e: FrameworkError) -> Self {
+        Self::Graphics(e)
+    }
+}
+
+/// A problem with lightmap texture coordinates of a surface. See [`validate_lightmap_uvs`] for more
+/// info.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LightmapUvIssue {
+    /// Surface has no lightmap texture coordinates at all.
+    MissingUvs,
+    /// A triangle has texture coordinates outside of `[0; 1]` range.
+    OutOfBounds {
+        /// Index of the triangle.
+        triangle: usize,
+    },
+    /// A triangle has zero area in texture space and won't receive any lighting.
+    Degenerate {
+        /// Index of the triangle.
+        triangle: usize,
+    },
+    /// Two triangles occupy the same texels of a lightmap, which leads to incorrect lighting on
+    /// both of them.
+    Overlap {
+        /// Index of the first triangle.
+        first: usize,
+        /// Index of the second triangle.
+        second: usize,
+    },
+}
+
+impl Display for LightmapUvIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LightmapUvIssue::MissingUvs => {
+                write!(f, "The surface has no lightmap texture coordinates.")
+            }
+            LightmapUvIssue::OutOfBounds { triangle } => {
+                write!(
+                    f,
+                    "Triangle {triangle} has texture coordinates outside of [0; 1] range."
+                )
+            }
+            LightmapUvIssue::Degenerate { triangle } => {
+                write!(f, "Triangle {triangle} has zero area in texture space.")
+            }
+            LightmapUvIssue::Overlap { first, second } => {
+                write!(
+                    f,
+                    "Triangles {first} and {second} overlap in texture space."
+                )
+            }
+        }
+    }
+}
+
+/// Checks lightmap texture coordinates (second texture coordinates) of the given surface data for
+/// common problems: coordinates outside of `[0; 1]` range, degenerate and overlapping triangles.
+/// Overlaps are checked by rasterizing triangles into a grid with the given `resolution`, only the
+/// overlaps that cover at least one cell center are reported, each pair of triangles is reported
+/// once.
+pub fn validate_lightmap_uvs(data: &SurfaceData, resolution: u32) -> Vec<LightmapUvIssue> {
+    if !data
+        .vertex_buffer
+        .has_attribute(VertexAttributeUsage::TexCoord1)
+    {
+        return vec![LightmapUvIssue::MissingUvs];
+    }
+
+    let uvs = data
+        .vertex_buffer
+        .iter()
+        .map(|v| {
+            v.read_2_f32(VertexAttributeUsage::TexCoord1)
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>();
+
+    let mut issues = Vec::new();
+    let resolution = resolution.max(1) as usize;
+    // Index of the last triangle, that was rasterized to a cell.
+    let mut grid = vec![u32::MAX; resolution * resolution];
+    let mut overlaps = FxHashSet::default();
+
+    for (index, triangle) in data.geometry_buffer.iter().enumerate() {
+        let (Some(a), Some(b), Some(c)) = (
+            uvs.get(triangle[0] as usize),
+            uvs.get(triangle[1] as usize),
+            uvs.get(triangle[2] as usize),
+        ) else {
+            continue;
+        };
+
+        if [a, b, c]
+            .iter()
+            .any(|uv| uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0)
+        {
+            issues.push(LightmapUvIssue::OutOfBounds { triangle: index });
+        }
+
+        let double_area = (b - a).perp(&(c - a));
+        if double_area.abs() <= f32::EPSILON * f32::EPSILON {
+            issues.push(LightmapUvIssue::Degenerate { triangle: index });
+            continue;
+        }
+
+        let scale = resolution as f32;
+        let min = a.inf(b).inf(c) * scale;
+        let max = a.sup(b).sup(c) * scale;
+        let x_range =
+            (min.x.floor().max(0.0) as usize)..(max.x.ceil().max(0.0) as usize).min(resolution);
+        let y_range =
+            (min.y.floor().max(0.0) as usize)..(max.y.ceil().max(0.0) as usize).min(resolution);
+        for y in y_range {
+            for x in x_range.clone() {
+                let p = Vector2::new((x as f32 + 0.5) / scale, (y as f32 + 0.5) / scale);
+                // Barycentric coordinates, cells that lie exactly on the edges are not counted,
+                // because adjacent triangles always share them.
+                let w0 = (c - b).perp(&(p - b)) / double_area;
+                let w1 = (a - c).perp(&(p - c)) / double_area;
+                let w2 = 1.0 - w0 - w1;
+                if w0 > 0.0 && w1 > 0.0 && w2 > 0.0 {
+                    let cell = &mut grid[y * resolution + x];
+                    if *cell != u32::MAX && overlaps.insert((*cell, index as u32)) {
+                        issues.push(LightmapUvIssue::Overlap {
+                            first: *cell as usize,
+                            second: index,
+                        });
+                    }
+                    *cell = index as u32;
+                }
+            }
+        }
+    }
+
+    issues
+}
+
+/// A set of parameters of the lightmap denoiser. See [`denoise_lightmap`] for more info.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LightmapDenoiseSettings {
+    /// Radius (in texels) of the filter kernel.
+    pub radius: u32,
+    /// Standard deviation of the spatial weight (in texels). The larger the value, the more
+    /// distant texels contribute to the result.
+    pub spatial_sigma: f32,
+    /// Standard deviation of the color weight. It defines how much the color of a neighbour texel
+    /// could differ from the color of the center texel to contribute to the result. Lower values
+    /// preserve shadow edges better, but remove less noise.
+    pub range_sigma: f32,
+}
+
+impl Default for LightmapDenoiseSettings {
+    fn default() -> Self {
+        Self {
+            radius: 2,
+            spatial_sigma: 1.5,
+            range_sigma: 0.15,
+        }
+    }
+}
+
+/// Removes high-frequency noise from a lightmap using an edge-preserving (bilateral) filter. Only
+/// the texels marked in the `coverage` mask are filtered and only they are used as neighbours, so
+/// empty space between UV islands won't bleed into the lighting.
+pub fn denoise_lightmap(
+    width: usize,
+    height: usize,
+    pixels: &mut [Vector3<f32>],
+    coverage: &[bool],
+    settings: &LightmapDenoiseSettings,
+) {
+    assert_eq!(pixels.len(), width * height);
+    assert_eq!(coverage.len(), width * height);
+
+    let radius = settings.radius as isize;
+    let spatial_factor = -0.5 / settings.spatial_sigma.max(f32::EPSILON).powi(2);
+    let range_factor = -0.5 / settings.range_sigma.max(f32::EPSILON).powi(2);
+
+    let source = pixels.to_vec();
+    for y in 0..height {
+        for x in 0..width {
+            let center_index = y * width + x;
+            if !coverage[center_index] {
+                continue;
+            }
+            let center = source[center_index];
+
+            let mut sum = Vector3::default();
+            let mut weight_sum = 0.0;
+            for dy in -radius..=radius {
+                for dx in -radius..=radius {
+                    let nx = x as isize + dx;
+                    let ny = y as isize + dy;
+                    if nx < 0 || ny < 0 || nx >= width as isize || ny >= height as isize {
+                        continue;
+                    }
+                    let index = ny as usize * width + nx as usize;
+                    if !coverage[index] {
+                        continue;
+                    }
+                    let neighbour = source[index];
+                    let spatial = (dx * dx + dy * dy) as f32 * spatial_factor;
+                    let range = (neighbour - center).norm_squared() * range_factor;
+                    let weight = (spatial + range).exp();
+                    sum += neighbour * weight;
+                    weight_sum += weight;
+                }
+            }
+
+            if weight_sum > 0.0 {
+                pixels[center_index] = sum / weight_sum;
+            }
+        }
+    }
+}
+
+/// Extends lighting of the covered texels to the adjacent empty ones, repeating the process the
+/// given amount of times. It prevents dark seams from appearing on the edges of UV islands, when
+/// a lightmap is sampled with bilinear filtration. Filled texels are marked in the coverage mask.
+pub fn dilate_lightmap(
+    width: usize,
+    height: usize,
+    pixels: &mut [Vector3<f32>],
+    coverage: &mut [bool],
+    iterations: u32,
+) {
+    assert_eq!(pixels.len(), width * height);
+    assert_eq!(coverage.len(), width * height);
+
+    for _ in 0..iterations {
+        let mut filled = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                if coverage[y * width + x] {
+                    continue;
+                }
+
+                let mut sum = Vector3::default();
+                let mut count = 0;
+                for (dx, dy) in [
+                    (-1, 0),
+                    (1, 0),
+                    (0, -1),
+                    (0, 1),
+                    (-1, -1),
+                    (1, -1),
+                    (-1, 1),
+                    (1, 1),
+                ] {
+                    let nx = x as isize + dx;
+                    let ny = y as isize + dy;
+                    if nx < 0 || ny < 0 || nx >= width as isize || ny >= height as isize {
+                        continue;
+                    }
+                    let index = ny as usize * width + nx as usize;
+                    if coverage[index] {
+                        sum += pixels[index];
+                        count += 1;
+                    }
+                }
+
+                if count > 0 {
+                    filled.push((y * width + x, sum / count as f32));
+                }
+            }
+        }
+
+        if filled.is_empty() {
+            break;
+        }
+
+        for (index, value) in filled {
+            pixels[index] = value;
+            coverage[index] = true;
+        }
+    }
+}
+
+/// Lightmap baker defines how lighting is calculated for each texel of a lightmap.
+pub enum LightmapBaker {
+    /// CPU ray tracer. It does not need a graphics context and can run in any thread, but it is
+    /// quite slow on large scenes.
+    Cpu,
+    /// GPU baker, see [`GpuLightmapBaker`] docs for more info.
+    Gpu(GpuLightmapBaker),
+}
+
+/// A set of parameters for lightmap baking.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LightmapBakeSettings {
+    /// Defines resolution of lightmaps. The higher value is, the more quality lightmaps will be
+    /// generated, but also it will be slower to generate them.
+    pub texels_per_unit: u32,
+    /// Relative spacing between UV elements generated by the built-in UV mapper.
+    pub uv_spacing: f32,
+    /// Optional denoising, that is applied to every lightmap after baking.
+    pub denoise: Option<LightmapDenoiseSettings>,
+}
+
+impl Default for LightmapBakeSettings {
+    fn default() -> Self {
+        Self {
+            texels_per_unit: 64,
+            uv_spacing: 0.005,
+            denoise: None,
+        }
+    }
+}
+
 /// Data set required to generate a lightmap. It could be produced from a scene using [`LightmapInputData::from_scene`] method.
 /// It is used to split preparation step from the actual lightmap generation; to be able to put heavy generation in a separate
 /// thread.
@@ -336,6 +765,7 @@ pub struct LightmapInputData {
     data_set: FxHashMap<u64, SurfaceResource>,
     instances: Vec<Instance>,
     lights: FxHashMap<Handle<Node>, LightDefinition>,
+    lights_fingerprint: u64,
 }
 
 impl LightmapInputData {
@@ -468,8 +898,9 @@ impl LightmapInputData {
                         owner: handle,
                         source_data: data.clone(),
                         transform: global_transform,
-                        // Calculated down below.
-                        data: None,
+                        // Calculated on geometry caching stage.
+                        world_vertices: Default::default(),
+                        triangles: Default::default(),
                     });
                 }
             }
@@ -478,9 +909,30 @@ impl LightmapInputData {
         Ok(Self {
             data_set,
             instances,
+            lights_fingerprint: lights_fingerprint(&lights),
             lights,
         })
     }
+
+    /// Returns a set of nodes, that were changed (moved, have different surfaces, etc.) since the
+    /// given lightmap was baked. If any light source was changed, then every node is considered
+    /// changed, because lighting of every object might be affected.
+    ///
+    /// Keep in mind, that objects that receive shadows from the changed ones are not included in
+    /// the set.
+    pub fn changed_nodes(&self, lightmap: &Lightmap) -> FxHashSet<Handle<Node>> {
+        let lights_changed = self.lights_fingerprint != lightmap.fingerprint.lights;
+        node_fingerprints(&self.instances)
+            .into_iter()
+            .filter_map(|(handle, fingerprint)| {
+                if lights_changed || lightmap.fingerprint.nodes.get(&handle) != Some(&fingerprint) {
+                    Some(handle)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
 }
 
 impl Lightmap {
@@ -519,17 +971,114 @@ impl Lightmap {
         uv_spacing: f32,
         cancellation_token: CancellationToken,
         progress_indicator: ProgressIndicator,
+    ) -> Result<Self, LightmapGenerationError> {
+        Self::bake(
+            data,
+            &LightmapBakeSettings {
+                texels_per_unit,
+                uv_spacing,
+                denoise: None,
+            },
+            &mut LightmapBaker::Cpu,
+            cancellation_token,
+            progress_indicator,
+        )
+    }
+
+    /// Generates lightmap for given scene using the given baker. This method **automatically**
+    /// generates secondary texture coordinates! See [`Self::new`] docs for more info.
+    pub fn bake(
+        data: LightmapInputData,
+        settings: &LightmapBakeSettings,
+        baker: &mut LightmapBaker,
+        cancellation_token: CancellationToken,
+        progress_indicator: ProgressIndicator,
+    ) -> Result<Self, LightmapGenerationError> {
+        Self::bake_internal(
+            data,
+            settings,
+            baker,
+            None,
+            cancellation_token,
+            progress_indicator,
+        )
+    }
+
+    /// Re-bakes only the nodes, that were changed since this lightmap was generated (see
+    /// [`LightmapInputData::changed_nodes`]). Lightmaps of the nodes that were removed from
+    /// the scene are removed as well. Secondary texture coordinates are generated only for
+    /// the surfaces that do not have them yet. Returns a set of re-baked nodes.
+    ///
+    /// Re-baked lightmap must be set to the scene again via [`crate::scene::graph::Graph::set_lightmap`].
+    pub fn rebake(
+        &mut self,
+        data: LightmapInputData,
+        settings: &LightmapBakeSettings,
+        baker: &mut LightmapBaker,
+        cancellation_token: CancellationToken,
+        progress_indicator: ProgressIndicator,
+    ) -> Result<FxHashSet<Handle<Node>>, LightmapGenerationError> {
+        let changed = data.changed_nodes(self);
+        let owners = data
+            .instances
+            .iter()
+            .map(|i| i.owner)
+            .collect::<FxHashSet<_>>();
+
+        let mut lightmap = Self::bake_internal(
+            data,
+            settings,
+            baker,
+            Some((&changed, &self.fingerprint.surfaces)),
+            cancellation_token,
+            progress_indicator,
+        )?;
+
+        self.map.retain(|handle, _| owners.contains(handle));
+        self.map.extend(lightmap.map.drain());
+        self.patches.extend(lightmap.patches.drain());
+        self.fingerprint = lightmap.fingerprint;
+
+        Ok(changed)
+    }
+
+    fn bake_internal(
+        data: LightmapInputData,
+        settings: &LightmapBakeSettings,
+        baker: &mut LightmapBaker,
+        // A set of nodes to bake and a set of content hashes of the surfaces that already have
+        // lightmap texture coordinates. Everything will be baked if `None`.
+        incremental: Option<(&FxHashSet<Handle<Node>>, &FxHashSet<u64>)>,
+        cancellation_token: CancellationToken,
+        progress_indicator: ProgressIndicator,
     ) -> Result<Self, LightmapGenerationError> {
         let LightmapInputData {
-            data_set,
+            mut data_set,
             mut instances,
             lights,
+            lights_fingerprint,
         } = data;
 
+        let is_target =
+            |owner: Handle<Node>| incremental.map_or(true, |(targets, _)| targets.contains(&owner));
+
+        if let Some((targets, baked_surfaces)) = incremental {
+            let target_data = instances
+                .iter()
+                .filter(|i| targets.contains(&i.owner))
+                .map(|i| &*i.source_data.data_ref() as *const _ as u64)
+                .collect::<FxHashSet<_>>();
+            data_set.retain(|key, data| {
+                target_data.contains(key)
+                    && !baked_surfaces.contains(&data.data_ref().content_hash())
+            });
+        }
+
         progress_indicator.set_stage(ProgressStage::UvGeneration, data_set.len() as u32);
 
+        let uv_spacing = settings.uv_spacing;
         let patches = data_set
-            .into_par_iter()
+            .par_iter()
             .map(|(_, data)| {
                 if cancellation_token.is_cancelled() {
                     Err(LightmapGenerationError::Cancelled)
@@ -555,6 +1104,22 @@ impl Lightmap {
             })
             .collect::<Result<FxHashMap<_, _>, LightmapGenerationError>>()?;
 
+        for (key, data) in data_set.iter() {
+            let issues = validate_lightmap_uvs(&data.data_ref(), UV_VALIDATION_RESOLUTION);
+            if let Some(first_issue) = issues.first() {
+                let owners = instances
+                    .iter()
+                    .filter(|i| &*i.source_data.data_ref() as *const _ as u64 == *key)
+                    .map(|i| i.owner)
+                    .collect::<FxHashSet<_>>();
+                Log::warn(format!(
+                    "Lightmap texture coordinates of a surface of {owners:?} nodes have {} issue(s). \
+                    First one: {first_issue}",
+                    issues.len()
+                ));
+            }
+        }
+
         progress_indicator.set_stage(ProgressStage::GeometryCaching, instances.len() as u32);
 
         instances
@@ -572,7 +1137,7 @@ impl Lightmap {
                         .map(|m| m.transpose())
                         .unwrap_or_else(Matrix3::identity);
 
-                    let world_vertices = data
+                    instance.world_vertices = data
                         .vertex_buffer
                         .iter()
                         .map(|view| {
@@ -596,17 +1161,12 @@ impl Lightmap {
                         })
                         .collect::<Vec<_>>();
 
-                    instance.data = Some(
-                        lightmap::input::Mesh::new(
-                            world_vertices,
-                            data.geometry_buffer
-                                .triangles_ref()
-                                .iter()
-                                .map(|t| t.0)
-                                .collect(),
-                        )
-                        .unwrap(),
-                    );
+                    instance.triangles = data
+                        .geometry_buffer
+                        .triangles_ref()
+                        .iter()
+                        .map(|t| t.0)
+                        .collect();
 
                     progress_indicator.advance_progress();
 
@@ -615,29 +1175,97 @@ impl Lightmap {
             })
             .collect::<Result<(), LightmapGenerationError>>()?;
 
-        progress_indicator.set_stage(ProgressStage::CalculatingLight, instances.len() as u32);
+        let fingerprint = LightmapFingerprint {
+            nodes: node_fingerprints(&instances),
+            surfaces: instances
+                .iter()
+                .map(|i| i.source_data.data_ref().content_hash())
+                .collect(),
+            lights: lights_fingerprint,
+        };
+
+        let target_count = instances.iter().filter(|i| is_target(i.owner)).count();
+        progress_indicator.set_stage(ProgressStage::CalculatingLight, target_count as u32);
 
         let mut map: FxHashMap<Handle<Node>, Vec<LightmapEntry>> = FxHashMap::default();
-        let meshes = instances
-            .iter_mut()
-            .filter_map(|i| i.data.take())
-            .collect::<Vec<_>>();
         let light_definitions = lights.values().cloned().collect::<Vec<_>>();
-        for (mesh, instance) in meshes.iter().zip(instances.iter()) {
-            if cancellation_token.is_cancelled() {
-                return Err(LightmapGenerationError::Cancelled);
+        let light_handles = lights.keys().cloned().collect::<Vec<_>>();
+        let mut push_entry = |owner: Handle<Node>, texture: Texture| {
+            map.entry(owner).or_default().push(LightmapEntry {
+                texture: Some(TextureResource::new_ok(Default::default(), texture)),
+                lights: light_handles.clone(),
+            });
+        };
+
+        match baker {
+            LightmapBaker::Cpu => {
+                let meshes = instances
+                    .iter_mut()
+                    .map(|i| {
+                        lightmap::input::Mesh::new(
+                            std::mem::take(&mut i.world_vertices),
+                            i.triangles.clone(),
+                        )
+                        .unwrap()
+                    })
+                    .collect::<Vec<_>>();
+                for (mesh, instance) in meshes.iter().zip(instances.iter()) {
+                    if !is_target(instance.owner) {
+                        continue;
+                    }
+
+                    if cancellation_token.is_cancelled() {
+                        return Err(LightmapGenerationError::Cancelled);
+                    }
+
+                    let lightmap = generate_lightmap(
+                        mesh,
+                        &meshes,
+                        &light_definitions,
+                        settings.texels_per_unit,
+                        settings.denoise.as_ref(),
+                    );
+                    push_entry(instance.owner, lightmap);
+
+                    progress_indicator.advance_progress();
+                }
             }
+            LightmapBaker::Gpu(gpu_baker) => {
+                let meshes = instances
+                    .iter()
+                    .map(|i| make_world_space_surface(&i.world_vertices, &i.triangles))
+                    .collect::<Vec<_>>();
+                let scene = gpu_baker.prepare_scene(&meshes, &light_definitions)?;
+                for (index, instance) in instances.iter().enumerate() {
+                    if !is_target(instance.owner) {
+                        continue;
+                    }
 
-            let lightmap = generate_lightmap(mesh, &meshes, &light_definitions, texels_per_unit);
-            map.entry(instance.owner).or_default().push(LightmapEntry {
-                texture: Some(TextureResource::new_ok(Default::default(), lightmap)),
-                lights: lights.keys().cloned().collect(),
-            });
+                    if cancellation_token.is_cancelled() {
+                        return Err(LightmapGenerationError::Cancelled);
+                    }
+
+                    let size = gpu_lightmap_size(
+                        &instance.world_vertices,
+                        &instance.triangles,
+                        settings.texels_per_unit,
+                    );
+                    let texels = gpu_baker.bake(&scene, index, size, size)?;
+                    push_entry(
+                        instance.owner,
+                        finalize_gpu_lightmap(texels, settings.denoise.as_ref()),
+                    );
 
-            progress_indicator.advance_progress();
+                    progress_indicator.advance_progress();
+                }
+            }
         }
 
-        Ok(Self { map, patches })
+        Ok(Self {
+            map,
+            patches,
+            fingerprint,
+        })
     }
 
     /// Saves lightmap textures into specified folder.
@@ -679,25 +1307,110 @@ fn generate_lightmap(
     other_meshes: &[lightmap::input::Mesh],
     lights: &[LightDefinition],
     texels_per_unit: u32,
+    denoise: Option<&LightmapDenoiseSettings>,
 ) -> Texture {
-    let map = lightmap::LightMap::new(mesh, other_meshes, lights, texels_per_unit as usize);
+    let mut map = lightmap::LightMap::new(mesh, other_meshes, lights, texels_per_unit as usize);
+
+    if let Some(denoise) = denoise {
+        let mut pixels = map
+            .pixels
+            .chunks_exact(3)
+            .map(|rgb| Vector3::new(rgb[0], rgb[1], rgb[2]).cast::<f32>() / 255.0)
+            .collect::<Vec<_>>();
+        // CPU baker does not provide coverage info, so every texel is filtered.
+        let coverage = vec![true; pixels.len()];
+        denoise_lightmap(map.width, map.height, &mut pixels, &coverage, denoise);
+        map.pixels = pixels_to_rgb8(&pixels);
+    }
+
+    make_lightmap_texture(map.width, map.height, map.pixels)
+}
 
+fn make_lightmap_texture(width: usize, height: usize, pixels: Vec<u8>) -> Texture {
     Texture::from_bytes(
         TextureKind::Rectangle {
-            width: map.width as u32,
-            height: map.height as u32,
+            width: width as u32,
+            height: height as u32,
         },
         TexturePixelKind::RGB8,
-        map.pixels,
+        pixels,
     )
     .unwrap()
 }
 
+fn pixels_to_rgb8(pixels: &[Vector3<f32>]) -> Vec<u8> {
+    pixels
+        .iter()
+        .flat_map(|p| [p.x, p.y, p.z])
+        .map(|c| (c.clamp(0.0, 1.0) * 255.0) as u8)
+        .collect()
+}
+
+fn finalize_gpu_lightmap(
+    texels: GpuLightmapTexels,
+    denoise: Option<&LightmapDenoiseSettings>,
+) -> Texture {
+    let GpuLightmapTexels {
+        width,
+        height,
+        mut pixels,
+        mut coverage,
+    } = texels;
+    if let Some(denoise) = denoise {
+        denoise_lightmap(width, height, &mut pixels, &coverage, denoise);
+    }
+    dilate_lightmap(
+        width,
+        height,
+        &mut pixels,
+        &mut coverage,
+        DILATION_ITERATIONS,
+    );
+    make_lightmap_texture(width, height, pixels_to_rgb8(&pixels))
+}
+
+fn gpu_lightmap_size(
+    vertices: &[lightmap::input::WorldVertex],
+    triangles: &[[u32; 3]],
+    texels_per_unit: u32,
+) -> usize {
+    let area = triangles
+        .iter()
+        .filter_map(|t| {
+            let a = vertices.get(t[0] as usize)?.world_position;
+            let b = vertices.get(t[1] as usize)?.world_position;
+            let c = vertices.get(t[2] as usize)?.world_position;
+            Some((b - a).cross(&(c - a)).norm() * 0.5)
+        })
+        .sum::<f32>();
+    ((area.sqrt() * texels_per_unit as f32).ceil() as usize)
+        .clamp(MIN_GPU_LIGHTMAP_SIZE, MAX_GPU_LIGHTMAP_SIZE)
+}
+
+fn make_world_space_surface(
+    vertices: &[lightmap::input::WorldVertex],
+    triangles: &[[u32; 3]],
+) -> SurfaceData {
+    let vertices = vertices
+        .iter()
+        .map(|v| StaticVertex {
+            position: v.world_position,
+            tex_coord: v.second_tex_coord,
+            normal: v.world_normal,
+            tangent: Vector4::new(1.0, 0.0, 0.0, 1.0),
+        })
+        .collect::<Vec<_>>();
+    SurfaceData::new(
+        VertexBuffer::new(vertices.len(), vertices).unwrap(),
+        TriangleBuffer::new(triangles.iter().map(|t| TriangleDefinition(*t)).collect()),
+    )
+}
+
 #[cfg(test)]
 mod test {
     use crate::{
         asset::ResourceData,
-        core::algebra::{Matrix4, Vector3},
+        core::algebra::{Matrix4, Vector2, Vector3},
         scene::{
             base::BaseBuilder,
             light::{point::PointLightBuilder, BaseLightBuilder},
@@ -709,11 +1422,105 @@ mod test {
             transform::TransformBuilder,
             Scene,
         },
-        utils::lightmap::{Lightmap, LightmapInputData},
+        utils::{
+            lightmap::{
+                apply_surface_data_patch, denoise_lightmap, dilate_lightmap, validate_lightmap_uvs,
+                Lightmap, LightmapDenoiseSettings, LightmapInputData, LightmapUvIssue,
+            },
+            uvgen::SurfaceDataPatch,
+        },
     };
     use fyrox_resource::untyped::ResourceKind;
     use std::path::Path;
 
+    #[test]
+    fn test_validate_lightmap_uvs() {
+        let mut data = SurfaceData::make_quad(&Matrix4::identity());
+        assert_eq!(
+            validate_lightmap_uvs(&data, 64),
+            vec![LightmapUvIssue::MissingUvs]
+        );
+
+        apply_surface_data_patch(
+            &mut data,
+            &SurfaceDataPatch {
+                // The first two triangles share the same texels, the last one is degenerate and
+                // lies outside of the texture.
+                triangles: vec![[0, 1, 2], [0, 1, 2], [3, 3, 3]],
+                second_tex_coords: vec![
+                    Vector2::new(0.0, 0.0),
+                    Vector2::new(1.0, 0.0),
+                    Vector2::new(0.0, 1.0),
+                    Vector2::new(2.0, 2.0),
+                ],
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(
+            validate_lightmap_uvs(&data, 64),
+            vec![
+                LightmapUvIssue::Overlap {
+                    first: 0,
+                    second: 1
+                },
+                LightmapUvIssue::OutOfBounds { triangle: 2 },
+                LightmapUvIssue::Degenerate { triangle: 2 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_denoise_lightmap() {
+        let (width, height) = (8, 8);
+        let mut pixels = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                let base = if x < width / 2 { 0.2 } else { 0.8 };
+                let noise = if (x + y) % 2 == 0 { 0.05 } else { -0.05 };
+                pixels.push(Vector3::repeat(base + noise));
+            }
+        }
+        let mut coverage = vec![true; pixels.len()];
+        coverage[0] = false;
+
+        let source = pixels.clone();
+        denoise_lightmap(
+            width,
+            height,
+            &mut pixels,
+            &coverage,
+            &LightmapDenoiseSettings {
+                radius: 2,
+                spatial_sigma: 1.5,
+                range_sigma: 0.1,
+            },
+        );
+
+        // Uncovered texels must stay untouched.
+        assert_eq!(pixels[0], source[0]);
+
+        for y in 1..height - 1 {
+            for x in 1..width - 1 {
+                let index = y * width + x;
+                let base = if x < width / 2 { 0.2 } else { 0.8 };
+                // Noise is reduced, but the edge between the halves is preserved.
+                assert!((pixels[index].x - base).abs() < (source[index].x - base).abs());
+            }
+        }
+    }
+
+    #[test]
+    fn test_dilate_lightmap() {
+        let mut pixels = vec![Vector3::repeat(1.0), Vector3::default(), Vector3::default()];
+        let mut coverage = vec![true, false, false];
+
+        dilate_lightmap(3, 1, &mut pixels, &mut coverage, 1);
+
+        assert_eq!(pixels[1], Vector3::repeat(1.0));
+        assert_eq!(coverage, vec![true, true, false]);
+    }
+
     #[test]
     fn test_generate_lightmap() {
         let mut scene = Scene::new();
@@ -24,6 +24,7 @@
 
 pub mod astar;
 pub mod behavior;
+pub mod level_editor;
 pub mod lightmap;
 pub mod navmesh;
 pub mod raw_mesh;
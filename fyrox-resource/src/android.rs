@@ -0,0 +1,249 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Resource IO for Android, that works directly with the asset storage of an application package.
+//! See [`AndroidAssetsResourceIo`] docs for more info.
+
+use crate::{
+    core::{
+        android_activity::ndk::asset::{Asset, AssetManager},
+        io::{FileLoadError, ANDROID_APP},
+    },
+    io::{FileReader, PathIter, ResourceIo, ResourceIoFuture},
+    manifest::{AssetManifest, ASSET_MANIFEST_FILE_NAME},
+    pack::{normalize_path, path_to_entry_name},
+};
+use fxhash::FxHashSet;
+use std::{
+    ffi::CString,
+    future::ready,
+    io::{self, ErrorKind, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+fn asset_manager() -> Result<AssetManager, FileLoadError> {
+    ANDROID_APP
+        .get()
+        .map(|app| app.asset_manager())
+        .ok_or_else(|| FileLoadError::Custom("ANDROID_APP is not set".to_string()))
+}
+
+fn asset_path(path: &Path) -> Result<CString, FileLoadError> {
+    CString::new(path_to_entry_name(path)).map_err(|err| FileLoadError::Custom(err.to_string()))
+}
+
+fn open_asset(path: &Path) -> Result<Asset, FileLoadError> {
+    asset_manager()?.open(&asset_path(path)?).ok_or_else(|| {
+        FileLoadError::Io(io::Error::new(
+            ErrorKind::NotFound,
+            format!("{} does not exist in the asset storage!", path.display()),
+        ))
+    })
+}
+
+/// File reader, that streams the content of an asset directly from the asset storage, without
+/// loading the entire file into memory.
+#[derive(Debug)]
+pub struct AndroidAssetReader {
+    asset: Asset,
+}
+
+// SAFETY: An asset is not bound to the thread it was opened on, and the reader gives access to it
+// only via mutable references (`Read` and `Seek` methods), so it is never used concurrently.
+unsafe impl Send for AndroidAssetReader {}
+unsafe impl Sync for AndroidAssetReader {}
+
+impl Read for AndroidAssetReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.asset.read(buf)
+    }
+}
+
+impl Seek for AndroidAssetReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.asset.seek(pos)
+    }
+}
+
+impl FileReader for AndroidAssetReader {
+    fn byte_len(&self) -> Option<u64> {
+        Some(self.asset.length() as u64)
+    }
+}
+
+/// Read-only resource IO, that loads resources from the asset storage of an application package
+/// using Android asset manager.
+///
+/// ## Directories
+///
+/// Android asset manager can list only files of a directory, but not its subdirectories. To
+/// support nested directories, the resource IO uses an asset manifest ([`AssetManifest`]) - a
+/// list of every file in the storage, that is generated by the editor when exporting a game and
+/// placed at the root of the storage ([`ASSET_MANIFEST_FILE_NAME`]). If there is no manifest,
+/// only the files of the requested directory are listed and [`ResourceIo::walk_directory`] does
+/// not descend into subdirectories.
+#[derive(Default)]
+pub struct AndroidAssetsResourceIo {
+    manifest: OnceLock<Option<AssetManifest>>,
+}
+
+impl AndroidAssetsResourceIo {
+    /// Creates a new resource IO. The asset manifest is loaded from the storage on first use.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new resource IO with the given asset manifest.
+    pub fn with_manifest(manifest: AssetManifest) -> Self {
+        Self {
+            manifest: OnceLock::from(Some(manifest)),
+        }
+    }
+
+    /// Returns the asset manifest, if any.
+    pub fn manifest(&self) -> Option<&AssetManifest> {
+        self.manifest
+            .get_or_init(|| {
+                let mut asset = open_asset(Path::new(ASSET_MANIFEST_FILE_NAME)).ok()?;
+                let mut text = String::new();
+                asset.read_to_string(&mut text).ok()?;
+                Some(AssetManifest::parse(&text))
+            })
+            .as_ref()
+    }
+
+    fn list_files(&self, path: &Path) -> Vec<PathBuf> {
+        let (Ok(manager), Ok(dir_path)) = (asset_manager(), asset_path(path)) else {
+            return Default::default();
+        };
+        let path = normalize_path(path);
+        manager
+            .open_dir(&dir_path)
+            .map(|dir| {
+                dir.map(|name| path.join(name.to_string_lossy().as_ref()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn children(&self, path: &Path) -> Vec<PathBuf> {
+        let mut entries = self.list_files(path);
+        if let Some(manifest) = self.manifest() {
+            entries.extend(manifest.children(path));
+        }
+        dedup(entries)
+    }
+
+    fn descendants(&self, path: &Path) -> Vec<PathBuf> {
+        let mut entries = Vec::new();
+        let mut stack = vec![normalize_path(path)];
+        while let Some(directory) = stack.pop() {
+            entries.extend(self.list_files(&directory));
+            if let Some(manifest) = self.manifest() {
+                for subdirectory in manifest.subdirectories(&directory) {
+                    entries.push(subdirectory.clone());
+                    stack.push(subdirectory);
+                }
+            }
+        }
+        dedup(entries)
+    }
+}
+
+fn dedup(entries: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut unique = FxHashSet::default();
+    entries
+        .into_iter()
+        .filter(|entry| unique.insert(entry.clone()))
+        .collect()
+}
+
+impl ResourceIo for AndroidAssetsResourceIo {
+    fn load_file<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<Vec<u8>, FileLoadError>> {
+        let result = open_asset(path).and_then(|mut asset| {
+            let mut bytes = Vec::with_capacity(asset.length());
+            asset.read_to_end(&mut bytes)?;
+            Ok(bytes)
+        });
+        Box::pin(ready(result))
+    }
+
+    fn move_file<'a>(
+        &'a self,
+        source: &'a Path,
+        _dest: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<(), FileLoadError>> {
+        Box::pin(ready(Err(FileLoadError::Custom(format!(
+            "Unable to move {}. The asset storage is read-only!",
+            source.display()
+        )))))
+    }
+
+    fn read_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<PathIter, FileLoadError>> {
+        let iter: PathIter = Box::new(self.children(path).into_iter());
+        Box::pin(ready(Ok(iter)))
+    }
+
+    fn walk_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<PathIter, FileLoadError>> {
+        let iter: PathIter = Box::new(self.descendants(path).into_iter());
+        Box::pin(ready(Ok(iter)))
+    }
+
+    fn file_reader<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<Box<dyn FileReader>, FileLoadError>> {
+        let result = open_asset(path)
+            .map(|asset| Box::new(AndroidAssetReader { asset }) as Box<dyn FileReader>);
+        Box::pin(ready(result))
+    }
+
+    fn exists<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool> {
+        Box::pin(async move { self.is_file(path).await || self.is_dir(path).await })
+    }
+
+    fn is_file<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool> {
+        let is_file = self
+            .manifest()
+            .map_or(false, |manifest| manifest.is_file(path))
+            || open_asset(path).is_ok();
+        Box::pin(ready(is_file))
+    }
+
+    fn is_dir<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool> {
+        // Asset manager "opens" any directory, even a missing one, so the directory is considered
+        // existing only if it has at least one file.
+        let is_dir = self
+            .manifest()
+            .map_or(false, |manifest| manifest.is_dir(path))
+            || !self.list_files(path).is_empty();
+        Box::pin(ready(is_dir))
+    }
+}
@@ -0,0 +1,243 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Content-based duplicate asset detection. Large projects tend to accumulate copies of the same
+//! asset under different paths (the same texture imported twice, a model copied to another
+//! folder, etc.). Every copy is loaded as a separate resource and occupies its own memory (and
+//! video memory, in case of textures), so such copies should be found and references to them
+//! merged into a single asset. See [`find_duplicates`] for more info.
+
+use crate::{core::append_extension, io::ResourceIo, options::OPTIONS_EXTENSION};
+use fxhash::{FxHashMap, FxHasher};
+use std::{
+    hash::Hasher,
+    path::{Path, PathBuf},
+};
+
+/// A set of assets with identical content and import options.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DuplicateGroup {
+    /// Size of a single asset of the group in bytes.
+    pub size: u64,
+    /// Sorted paths of the assets. There are always at least two paths in a group.
+    pub paths: Vec<PathBuf>,
+}
+
+impl DuplicateGroup {
+    /// Returns the path of the asset that is suggested to be kept, every other asset of the group
+    /// could be replaced with it. This is the shortest path (or the first in lexicographical order
+    /// among the paths of the same length).
+    pub fn original(&self) -> &Path {
+        self.paths
+            .iter()
+            .min_by_key(|path| path.as_os_str().len())
+            .map(|path| path.as_path())
+            .unwrap_or_else(|| Path::new(""))
+    }
+
+    /// Returns an iterator over the paths of the assets, that could be replaced with the
+    /// [`Self::original`] one.
+    pub fn duplicates(&self) -> impl Iterator<Item = &Path> {
+        let original = self.original();
+        self.paths
+            .iter()
+            .map(|path| path.as_path())
+            .filter(move |path| *path != original)
+    }
+
+    /// Returns the amount of bytes that are occupied by the duplicates.
+    pub fn wasted_bytes(&self) -> u64 {
+        self.size * self.paths.len().saturating_sub(1) as u64
+    }
+}
+
+/// Result of [`find_duplicates`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DuplicateReport {
+    /// Groups of duplicated assets, sorted by the amount of wasted bytes (largest first).
+    pub groups: Vec<DuplicateGroup>,
+}
+
+impl DuplicateReport {
+    /// Returns `true` if no duplicates were found.
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    /// Returns the total amount of bytes that are occupied by the duplicates.
+    pub fn wasted_bytes(&self) -> u64 {
+        self.groups.iter().map(|group| group.wasted_bytes()).sum()
+    }
+}
+
+struct AssetContent {
+    data: Vec<u8>,
+    options: Vec<u8>,
+}
+
+async fn load_content(io: &dyn ResourceIo, path: &Path) -> Option<AssetContent> {
+    let data = io.load_file(path).await.ok()?;
+    // Import options define how an asset is loaded (texture compression, mip maps, etc.), so the
+    // assets with the same content, but different options, produce different resources.
+    let options = io
+        .load_file(&append_extension(path, OPTIONS_EXTENSION))
+        .await
+        .unwrap_or_default();
+    Some(AssetContent { data, options })
+}
+
+fn content_hash(content: &AssetContent) -> (u64, u64) {
+    let mut hasher = FxHasher::default();
+    hasher.write(&content.data);
+    let data_hash = hasher.finish();
+    let mut hasher = FxHasher::default();
+    hasher.write(&content.options);
+    (data_hash, hasher.finish())
+}
+
+/// Searches for the assets with identical content and import options among the given paths.
+/// Import options files are never treated as assets on their own. The files are hashed first and
+/// then the files with the same hash are compared byte-by-byte, so hash collisions cannot produce
+/// false positives. Files that cannot be read are ignored.
+pub async fn find_duplicates(
+    io: &dyn ResourceIo,
+    paths: impl IntoIterator<Item = PathBuf>,
+) -> DuplicateReport {
+    let mut candidates = FxHashMap::<(u64, u64, u64), Vec<PathBuf>>::default();
+    for path in paths {
+        if path.extension().is_some_and(|ext| ext == OPTIONS_EXTENSION) {
+            continue;
+        }
+        if let Some(content) = load_content(io, &path).await {
+            let (data_hash, options_hash) = content_hash(&content);
+            candidates
+                .entry((content.data.len() as u64, data_hash, options_hash))
+                .or_default()
+                .push(path);
+        }
+    }
+
+    let mut groups = Vec::new();
+    for ((size, _, _), mut paths) in candidates {
+        if paths.len() < 2 {
+            continue;
+        }
+        paths.sort();
+
+        // Split the candidates into the sets of assets that are equal byte-by-byte.
+        let mut classes = Vec::<(AssetContent, Vec<PathBuf>)>::new();
+        for path in paths {
+            let Some(content) = load_content(io, &path).await else {
+                continue;
+            };
+            if let Some((_, class_paths)) = classes.iter_mut().find(|(class_content, _)| {
+                class_content.data == content.data && class_content.options == content.options
+            }) {
+                class_paths.push(path);
+            } else {
+                classes.push((content, vec![path]));
+            }
+        }
+
+        groups.extend(
+            classes
+                .into_iter()
+                .filter(|(_, paths)| paths.len() > 1)
+                .map(|(_, paths)| DuplicateGroup { size, paths }),
+        );
+    }
+
+    groups.sort_by(|a, b| {
+        b.wasted_bytes()
+            .cmp(&a.wasted_bytes())
+            .then_with(|| a.paths.cmp(&b.paths))
+    });
+
+    DuplicateReport { groups }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::pack::{PackedResourceIo, ResourcePackBuilder};
+    use fyrox_core::futures::executor::block_on;
+
+    fn find(files: &[(&str, &[u8])]) -> DuplicateReport {
+        let mut builder = ResourcePackBuilder::new();
+        for (path, data) in files {
+            builder.add_file(path, data.to_vec());
+        }
+        let io = PackedResourceIo::from_bytes(builder.build()).unwrap();
+        block_on(find_duplicates(
+            &io,
+            files.iter().map(|(path, _)| PathBuf::from(path)),
+        ))
+    }
+
+    #[test]
+    fn duplicates_are_grouped() {
+        let report = find(&[
+            ("data/textures/grass.png", &[1, 2, 3, 4]),
+            ("data/grass_copy.png", &[1, 2, 3, 4]),
+            ("data/rock.png", &[5, 6]),
+            ("data/old/rock.png", &[5, 6]),
+            ("data/unique.png", &[7]),
+        ]);
+
+        assert_eq!(report.groups.len(), 2);
+        assert_eq!(report.wasted_bytes(), 6);
+
+        let group = &report.groups[0];
+        assert_eq!(group.size, 4);
+        assert_eq!(group.original(), Path::new("data/grass_copy.png"));
+        assert_eq!(
+            group.duplicates().collect::<Vec<_>>(),
+            vec![Path::new("data/textures/grass.png")]
+        );
+
+        let group = &report.groups[1];
+        assert_eq!(group.original(), Path::new("data/rock.png"));
+        assert_eq!(group.wasted_bytes(), 2);
+    }
+
+    #[test]
+    fn different_import_options_are_not_duplicates() {
+        let report = find(&[
+            ("a.png", &[1, 2, 3]),
+            ("a.png.options", &[10]),
+            ("b.png", &[1, 2, 3]),
+            ("b.png.options", &[20]),
+            ("c.png", &[1, 2, 3]),
+            ("c.png.options", &[10]),
+        ]);
+
+        assert_eq!(report.groups.len(), 1);
+        assert_eq!(
+            report.groups[0].paths,
+            vec![PathBuf::from("a.png"), PathBuf::from("c.png")]
+        );
+    }
+
+    #[test]
+    fn no_duplicates() {
+        let report = find(&[("a.png", &[1]), ("b.png", &[2])]);
+        assert!(report.is_empty());
+        assert_eq!(report.wasted_bytes(), 0);
+    }
+}
@@ -73,6 +73,49 @@ pub trait ResourceIo: Send + Sync + 'static {
         path: &'a Path,
     ) -> ResourceIoFuture<'a, Result<Vec<u8>, FileLoadError>>;
 
+    /// Attempts to load a batch of files at once. Results are returned in the same order as the
+    /// given paths. Backends that can coalesce reads (for example, archives that store many files
+    /// in a single blob) should override this method.
+    ///
+    /// Default implementation loads the files one by one using [`Self::load_file`].
+    fn load_files<'a>(
+        &'a self,
+        paths: &'a [PathBuf],
+    ) -> ResourceIoFuture<'a, Vec<Result<Vec<u8>, FileLoadError>>> {
+        Box::pin(async move {
+            let mut results = Vec::with_capacity(paths.len());
+            for path in paths {
+                results.push(self.load_file(path).await);
+            }
+            results
+        })
+    }
+
+    /// Returns the maximum number of reads that could be performed concurrently without degrading
+    /// the throughput of the backend (for example, a spinning disk or a limited amount of HTTP
+    /// connections). The resource manager uses this value to limit the amount of simultaneous
+    /// reads, unless it is overridden by the user. `None` means that there is no limit.
+    ///
+    /// Default implementation returns `None`.
+    fn max_concurrent_reads(&self) -> Option<usize> {
+        None
+    }
+
+    /// Attempts to write the given data to a file at the provided path. The file will be created
+    /// if it does not exist, or overwritten otherwise.
+    ///
+    /// Default implementation returns an error, which means that the IO is read-only.
+    fn write_file<'a>(
+        &'a self,
+        path: &'a Path,
+        #[allow(unused)] data: Vec<u8>,
+    ) -> ResourceIoFuture<'a, Result<(), FileLoadError>> {
+        Box::pin(ready(Err(FileLoadError::Custom(format!(
+            "Unable to write {}. The resource IO is read-only!",
+            path.display()
+        )))))
+    }
+
     /// Attempts to move a file at the given `source` path to the given `dest` path.
     fn move_file<'a>(
         &'a self,
@@ -167,6 +210,34 @@ impl ResourceIo for FsResourceIo {
         Box::pin(fyrox_core::io::load_file(path))
     }
 
+    /// On wasm every read is an HTTP request and browsers limit the amount of simultaneous
+    /// connections, so it is better to keep the amount of requests low.
+    fn max_concurrent_reads(&self) -> Option<usize> {
+        if cfg!(target_arch = "wasm32") {
+            Some(4)
+        } else {
+            Some(16)
+        }
+    }
+
+    /// wasm should fallback to the default impl, because there's no file system to write to.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn write_file<'a>(
+        &'a self,
+        path: &'a Path,
+        data: Vec<u8>,
+    ) -> ResourceIoFuture<'a, Result<(), FileLoadError>> {
+        Box::pin(async move {
+            if let Some(parent) = path.parent() {
+                if !parent.as_os_str().is_empty() {
+                    std::fs::create_dir_all(parent)?;
+                }
+            }
+            std::fs::write(path, data)?;
+            Ok(())
+        })
+    }
+
     fn move_file<'a>(
         &'a self,
         source: &'a Path,
@@ -188,8 +259,7 @@ impl ResourceIo for FsResourceIo {
     /// wasm should fallback to the default no-op impl as im not sure if they
     /// can directly read a directory
     ///
-    /// Note: Android directory reading should be possible just I have not created
-    /// an implementation for this yet
+    /// Note: Android directories are listed by `AndroidAssetsResourceIo`
     #[cfg(all(not(target_os = "android"), not(target_arch = "wasm32")))]
     fn read_directory<'a>(
         &'a self,
@@ -225,8 +295,7 @@ impl ResourceIo for FsResourceIo {
 
     /// Only use file reader when not targetting android or wasm
     ///
-    /// Note: Might be possible to use the Android Asset struct for reading as
-    /// long as its Send + Sync + 'static (It already implements Debug + Read + Seek)
+    /// Note: Android assets are streamed by `AndroidAssetsResourceIo`
     #[cfg(all(not(target_os = "android"), not(target_arch = "wasm32")))]
     fn file_reader<'a>(
         &'a self,
@@ -55,14 +55,22 @@ pub use fyrox_core as core;
 use fyrox_core::log::Log;
 use fyrox_core::{combine_uuids, Downcast};
 
+#[cfg(target_os = "android")]
+pub mod android;
 pub mod constructor;
+pub mod duplicates;
 pub mod entry;
 pub mod event;
 pub mod graph;
 pub mod io;
 pub mod loader;
 pub mod manager;
+pub mod manifest;
+pub mod normalize;
 pub mod options;
+pub mod pack;
+pub mod scheduler;
+pub mod scope;
 pub mod state;
 pub mod untyped;
 
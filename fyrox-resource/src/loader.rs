@@ -24,7 +24,13 @@ use crate::{
     core::uuid::Uuid, io::ResourceIo, options::BaseImportOptions, state::LoadError, ResourceData,
 };
 use fyrox_core::Downcast;
-use std::{future::Future, path::PathBuf, pin::Pin, sync::Arc};
+use std::{
+    future::Future,
+    io::Read,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+};
 
 #[cfg(target_arch = "wasm32")]
 #[doc(hidden)]
@@ -36,6 +42,37 @@ pub trait BaseResourceLoader: Send + Downcast {}
 
 impl<T> BaseResourceLoader for T where T: ResourceLoader {}
 
+/// Amount of bytes from the beginning of a file that are used to detect the format of the file.
+/// See [`ResourceLoader::supports_content`].
+pub const CONTENT_HEADER_SIZE: usize = 64;
+
+/// A sequence of bytes at a fixed offset from the beginning of a file, that identifies the format
+/// of the file (so called "magic").
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ContentSignature {
+    /// Offset of the bytes from the beginning of the file.
+    pub offset: usize,
+    /// The bytes that must be present at the offset.
+    pub bytes: &'static [u8],
+}
+
+impl ContentSignature {
+    /// Creates a signature that is located at the beginning of a file.
+    pub const fn new(bytes: &'static [u8]) -> Self {
+        Self { offset: 0, bytes }
+    }
+
+    /// Creates a signature that is located at the given offset from the beginning of a file.
+    pub const fn with_offset(offset: usize, bytes: &'static [u8]) -> Self {
+        Self { offset, bytes }
+    }
+
+    /// Checks if the given first bytes of a file contain the signature.
+    pub fn matches(&self, header: &[u8]) -> bool {
+        header.get(self.offset..self.offset + self.bytes.len()) == Some(self.bytes)
+    }
+}
+
 /// Trait for resource loading.
 pub trait ResourceLoader: BaseResourceLoader {
     /// Returns a list of file extensions supported by the loader. Resource manager will use this list
@@ -49,6 +86,23 @@ pub trait ResourceLoader: BaseResourceLoader {
             .any(|e| fyrox_core::cmp_strings_case_insensitive(e, ext))
     }
 
+    /// Returns a list of signatures of the file formats supported by the loader. Signatures are
+    /// not required, but they allow the resource manager to pick the correct loader for files
+    /// with a wrong or missing extension, and for extensions that are supported by multiple loaders.
+    /// Signatures must fit in the first [`CONTENT_HEADER_SIZE`] bytes of a file.
+    fn content_signatures(&self) -> &[ContentSignature] {
+        &[]
+    }
+
+    /// Checks if the given first bytes of a file (up to [`CONTENT_HEADER_SIZE`]) contain data that
+    /// is supported by this loader. Default implementation checks [`Self::content_signatures`],
+    /// formats that cannot be described by signatures could override this method instead.
+    fn supports_content(&self, header: &[u8]) -> bool {
+        self.content_signatures()
+            .iter()
+            .any(|signature| signature.matches(header))
+    }
+
     /// Must return a type uuid of the resource data type.
     fn data_type_uuid(&self) -> Uuid;
 
@@ -178,12 +232,72 @@ impl ResourceLoadersContainer {
         self.loaders.iter().map(|boxed| &**boxed)
     }
 
+    /// Returns every loader, that supports the given extension and loads resources of the given
+    /// type (of any type, if the type is not specified). When there is exactly one such loader, it
+    /// could be used right away, otherwise the loader must be picked by the content of the file
+    /// using [`Self::find_by_content`].
+    pub fn find_by_extension(
+        &self,
+        extension: Option<&str>,
+        type_uuid: Option<Uuid>,
+    ) -> Vec<&dyn ResourceLoader> {
+        extension
+            .map(|ext| {
+                self.iter()
+                    .filter(|loader| {
+                        loader.supports_extension(ext) && is_of_type(*loader, type_uuid)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Picks a loader for a file with the given extension by the first bytes of the file (see
+    /// [`CONTENT_HEADER_SIZE`]). The first loader that supports the content is used, preferring
+    /// the loaders that support the extension. If the content is not recognized by any loader,
+    /// the first loader for the extension is used (if any). Only the loaders of the given type
+    /// are considered, if the type is specified.
+    pub fn find_by_content(
+        &self,
+        extension: Option<&str>,
+        type_uuid: Option<Uuid>,
+        header: &[u8],
+    ) -> Option<&dyn ResourceLoader> {
+        let candidates = self.find_by_extension(extension, type_uuid);
+        candidates
+            .iter()
+            .copied()
+            .find(|loader| loader.supports_content(header))
+            .or_else(|| {
+                self.iter().find(|loader| {
+                    is_of_type(*loader, type_uuid) && loader.supports_content(header)
+                })
+            })
+            .or_else(|| candidates.first().copied())
+    }
+
     /// Returns an iterator yielding mutable references to "untyped" resource loaders.
     pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut dyn ResourceLoader> {
         self.loaders.iter_mut().map(|boxed| &mut **boxed)
     }
 }
 
+fn is_of_type(loader: &dyn ResourceLoader, type_uuid: Option<Uuid>) -> bool {
+    type_uuid.map_or(true, |type_uuid| loader.data_type_uuid() == type_uuid)
+}
+
+/// Reads the first bytes of a file (up to [`CONTENT_HEADER_SIZE`]), that could be used to detect
+/// the format of the file.
+pub async fn read_content_header(io: &dyn ResourceIo, path: &Path) -> Option<Vec<u8>> {
+    let reader = io.file_reader(path).await.ok()?;
+    let mut header = Vec::with_capacity(CONTENT_HEADER_SIZE);
+    reader
+        .take(CONTENT_HEADER_SIZE as u64)
+        .read_to_end(&mut header)
+        .ok()?;
+    Some(header)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -262,4 +376,118 @@ mod test {
         assert!(!container.is_empty());
         assert_eq!(container.len(), 1);
     }
+
+    struct SignatureLoader {
+        extensions: &'static [&'static str],
+        signatures: &'static [ContentSignature],
+        uuid: Uuid,
+    }
+
+    impl ResourceLoader for SignatureLoader {
+        fn extensions(&self) -> &[&str] {
+            self.extensions
+        }
+
+        fn content_signatures(&self) -> &[ContentSignature] {
+            self.signatures
+        }
+
+        fn data_type_uuid(&self) -> Uuid {
+            self.uuid
+        }
+
+        fn load(&self, _path: PathBuf, _io: Arc<dyn ResourceIo>) -> BoxedLoaderFuture {
+            todo!()
+        }
+    }
+
+    struct OtherSignatureLoader(SignatureLoader);
+
+    impl ResourceLoader for OtherSignatureLoader {
+        fn extensions(&self) -> &[&str] {
+            self.0.extensions()
+        }
+
+        fn content_signatures(&self) -> &[ContentSignature] {
+            self.0.content_signatures()
+        }
+
+        fn data_type_uuid(&self) -> Uuid {
+            self.0.data_type_uuid()
+        }
+
+        fn load(&self, path: PathBuf, io: Arc<dyn ResourceIo>) -> BoxedLoaderFuture {
+            self.0.load(path, io)
+        }
+    }
+
+    const FOO_SIGNATURES: [ContentSignature; 1] = [ContentSignature::new(b"FOO")];
+    const BAR_SIGNATURES: [ContentSignature; 1] = [ContentSignature::with_offset(4, b"BAR")];
+
+    fn sniffing_container() -> ResourceLoadersContainer {
+        let mut container = ResourceLoadersContainer::new();
+        container.set(SignatureLoader {
+            extensions: &["foo", "bin"],
+            signatures: &FOO_SIGNATURES,
+            uuid: Uuid::from_u128(1),
+        });
+        container.set(OtherSignatureLoader(SignatureLoader {
+            extensions: &["bar", "bin"],
+            signatures: &BAR_SIGNATURES,
+            uuid: Uuid::from_u128(2),
+        }));
+        container
+    }
+
+    fn find_uuid(
+        container: &ResourceLoadersContainer,
+        extension: Option<&str>,
+        header: &[u8],
+    ) -> Option<Uuid> {
+        container
+            .find_by_content(extension, None, header)
+            .map(|loader| loader.data_type_uuid())
+    }
+
+    #[test]
+    fn content_signature_matches() {
+        assert!(ContentSignature::new(b"FOO").matches(b"FOOBAR"));
+        assert!(!ContentSignature::new(b"FOO").matches(b"FO"));
+        assert!(ContentSignature::with_offset(3, b"BAR").matches(b"FOOBAR"));
+        assert!(!ContentSignature::with_offset(4, b"BAR").matches(b"FOOBAR"));
+    }
+
+    #[test]
+    fn resource_loader_container_find_by_content() {
+        let container = sniffing_container();
+        let foo = Some(Uuid::from_u128(1));
+        let bar = Some(Uuid::from_u128(2));
+
+        // A unique extension does not require the content.
+        let loaders = container.find_by_extension(Some("bar"), None);
+        assert_eq!(loaders.len(), 1);
+        assert_eq!(Some(loaders[0].data_type_uuid()), bar);
+        assert_eq!(container.find_by_extension(Some("bin"), None).len(), 2);
+        assert_eq!(container.find_by_extension(Some("bin"), bar).len(), 1);
+
+        // An ambiguous extension is resolved by the content.
+        assert_eq!(find_uuid(&container, Some("bin"), b"0000BAR"), bar);
+        assert_eq!(find_uuid(&container, Some("bin"), b"FOO"), foo);
+        assert_eq!(find_uuid(&container, Some("bin"), b"????"), foo);
+
+        // Unknown and missing extensions are resolved by the content.
+        assert_eq!(find_uuid(&container, Some("download"), b"0000BAR"), bar);
+        assert_eq!(find_uuid(&container, None, b"FOO"), foo);
+        assert_eq!(find_uuid(&container, None, b"????"), None);
+
+        // The type of the resource limits the loaders, that could be picked.
+        let find_typed = |extension, header: &[u8]| {
+            container
+                .find_by_content(extension, foo, header)
+                .map(|loader| loader.data_type_uuid())
+        };
+        assert_eq!(find_typed(Some("bin"), b"0000BAR"), foo);
+        assert_eq!(find_typed(None, b"0000BAR"), None);
+        assert_eq!(find_typed(None, b"FOO"), foo);
+    }
 }
@@ -26,20 +26,25 @@ use crate::{
     constructor::ResourceConstructorContainer,
     core::{
         append_extension,
-        futures::future::join_all,
+        futures::future::{join_all, AbortHandle, Abortable},
         io::FileLoadError,
         log::Log,
         make_relative_path, notify,
         parking_lot::{Mutex, MutexGuard},
+        profiler,
         task::TaskPool,
+        uuid::Uuid,
         watcher::FileSystemWatcher,
         TypeUuidProvider,
     },
+    duplicates::{find_duplicates, DuplicateReport},
     entry::{TimedEntry, DEFAULT_RESOURCE_LIFETIME},
     event::{ResourceEvent, ResourceEventBroadcaster},
-    io::{FsResourceIo, ResourceIo},
-    loader::{ResourceLoader, ResourceLoadersContainer},
+    io::ResourceIo,
+    loader::{read_content_header, BoxedLoaderFuture, ResourceLoader, ResourceLoadersContainer},
     options::OPTIONS_EXTENSION,
+    scheduler::{IoLimiter, LimitedResourceIo, LoadOrder},
+    scope::{LoadInterest, ResourceScope, ScopedLoad},
     state::{LoadError, ResourceState},
     untyped::ResourceKind,
     Resource, ResourceData, TypedResourceData, UntypedResource,
@@ -52,7 +57,7 @@ use std::{
     fmt::{Debug, Display, Formatter},
     marker::PhantomData,
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{Arc, Weak},
 };
 
 /// A set of resources that can be waited for.
@@ -215,6 +220,27 @@ pub struct ResourceManagerState {
     resources: Vec<TimedEntry<UntypedResource>>,
     task_pool: Arc<TaskPool>,
     watcher: Option<FileSystemWatcher>,
+    io_limiter: IoLimiter,
+    max_concurrent_reads: Option<usize>,
+    pending_loads: FxHashMap<PathBuf, Arc<LoadInterest>>,
+    destroyed_resources: u64,
+    // A weak reference to the shared state itself, it is used by the loading tasks that pick
+    // loaders by the content of files.
+    self_ref: Weak<Mutex<ResourceManagerState>>,
+}
+
+/// A way to pick a loader for a resource, that was decided before the resource is loaded.
+enum LoaderChoice<'a> {
+    /// The extension of the file is enough to pick the loader.
+    Loader(&'a dyn ResourceLoader),
+    /// The loader will be picked by the content of the file in the loading task. Contains the
+    /// type of the resource (if it is known) and the type that is used until the loader is picked.
+    ByContent {
+        type_uuid: Option<Uuid>,
+        provisional_type_uuid: Uuid,
+    },
+    /// There's no loader, that could load the file.
+    None,
 }
 
 /// Resource manager controls loading and lifetime of resource in the engine. Resource manager can hold
@@ -273,7 +299,11 @@ impl ResourceManager {
     /// Creates a resource manager with default settings and loaders.
     pub fn new(task_pool: Arc<TaskPool>) -> Self {
         Self {
-            state: Arc::new(Mutex::new(ResourceManagerState::new(task_pool))),
+            state: Arc::new_cyclic(|self_ref| {
+                let mut state = ResourceManagerState::new(task_pool);
+                state.self_ref = self_ref.clone();
+                Mutex::new(state)
+            }),
         }
     }
 
@@ -329,7 +359,10 @@ impl ResourceManager {
     where
         T: TypedResourceData,
     {
-        let untyped = self.state().request(path);
+        let type_uuid = <T as TypeUuidProvider>::type_uuid();
+        let (untyped, _) =
+            self.state()
+                .request_with_interest(path.as_ref(), Some(type_uuid), false);
         let actual_type_uuid = untyped.type_uuid();
         assert_eq!(actual_type_uuid, <T as TypeUuidProvider>::type_uuid());
         Resource {
@@ -348,7 +381,10 @@ impl ResourceManager {
     where
         T: TypedResourceData,
     {
-        let untyped = self.state().request(path);
+        let type_uuid = <T as TypeUuidProvider>::type_uuid();
+        let (untyped, _) =
+            self.state()
+                .request_with_interest(path.as_ref(), Some(type_uuid), false);
         let actual_type_uuid = untyped.type_uuid();
         if actual_type_uuid == <T as TypeUuidProvider>::type_uuid() {
             Some(Resource {
@@ -368,6 +404,12 @@ impl ResourceManager {
         self.state().request(path)
     }
 
+    /// Creates a new scope of resource requests. Pending loads of the resources requested through
+    /// the scope are abandoned when the scope is dropped. See [`ResourceScope`] docs for more info.
+    pub fn new_scope(&self) -> ResourceScope {
+        ResourceScope::new(self.clone())
+    }
+
     /// Saves given resources in the specified path and registers it in resource manager, so
     /// it will be accessible through it later.
     pub fn register<P, F>(
@@ -509,6 +551,35 @@ impl ResourceManager {
         Ok(())
     }
 
+    /// Searches for the resources with identical content and import options in the given directory
+    /// and its subdirectories. Only the files that could be loaded by the registered loaders and
+    /// pass the given filter are checked. See [`crate::duplicates`] module docs for more info.
+    pub async fn find_duplicate_resources(
+        &self,
+        root: impl AsRef<Path>,
+        mut filter: impl FnMut(&Path) -> bool,
+    ) -> Result<DuplicateReport, FileLoadError> {
+        let io = self.state().resource_io.clone();
+        let paths = io.walk_directory(root.as_ref()).await?.collect::<Vec<_>>();
+        let paths = {
+            let state = self.state();
+            paths
+                .into_iter()
+                .filter(|path| {
+                    filter(path)
+                        && path.extension().is_some_and(|ext| {
+                            let ext = ext.to_string_lossy();
+                            state
+                                .loaders
+                                .iter()
+                                .any(|loader| loader.supports_extension(&ext))
+                        })
+                })
+                .collect::<Vec<_>>()
+        };
+        Ok(find_duplicates(&*io, paths).await)
+    }
+
     /// Reloads all loaded resources. Normally it should never be called, because it is **very** heavy
     /// method! This method is asynchronous, it uses all available CPU power to reload resources as
     /// fast as possible.
@@ -518,6 +589,20 @@ impl ResourceManager {
     }
 }
 
+/// Use the file system resource io by default, except Android where the assets are stored in the
+/// application package.
+fn default_resource_io() -> Arc<dyn ResourceIo> {
+    #[cfg(target_os = "android")]
+    {
+        Arc::new(crate::android::AndroidAssetsResourceIo::new())
+    }
+
+    #[cfg(not(target_os = "android"))]
+    {
+        Arc::new(crate::io::FsResourceIo)
+    }
+}
+
 impl ResourceManagerState {
     pub(crate) fn new(task_pool: Arc<TaskPool>) -> Self {
         Self {
@@ -528,8 +613,12 @@ impl ResourceManagerState {
             constructors_container: Default::default(),
             watcher: None,
             built_in_resources: Default::default(),
-            // Use the file system resource io by default
-            resource_io: Arc::new(FsResourceIo),
+            resource_io: default_resource_io(),
+            io_limiter: Default::default(),
+            max_concurrent_reads: None,
+            pending_loads: Default::default(),
+            destroyed_resources: 0,
+            self_ref: Default::default(),
         }
     }
 
@@ -544,6 +633,39 @@ impl ResourceManagerState {
         self.resource_io = resource_io;
     }
 
+    /// Overrides the maximum amount of concurrent reads performed by resource loaders. `None`
+    /// means that the limit is defined by the current resource IO (see
+    /// [`ResourceIo::max_concurrent_reads`]). Lowering the limit does not interrupt active reads.
+    pub fn set_max_concurrent_reads(&mut self, max_concurrent_reads: Option<usize>) {
+        self.max_concurrent_reads = max_concurrent_reads;
+        self.io_limiter
+            .set_max_concurrency(self.max_concurrent_reads());
+    }
+
+    /// Returns the maximum amount of concurrent reads performed by resource loaders. `None` means
+    /// that there is no limit.
+    pub fn max_concurrent_reads(&self) -> Option<usize> {
+        self.max_concurrent_reads
+            .or_else(|| self.resource_io.max_concurrent_reads())
+    }
+
+    /// Sets the order in which pending reads are served when the limit of concurrent reads is
+    /// reached. See [`LoadOrder`] docs for more info.
+    pub fn set_load_order(&mut self, order: LoadOrder) {
+        self.io_limiter.set_order(order);
+    }
+
+    /// Returns the order in which pending reads are served.
+    pub fn load_order(&self) -> LoadOrder {
+        self.io_limiter.order()
+    }
+
+    /// Returns the limiter of concurrent reads. It could be used to check how many reads are
+    /// active or waiting at the moment.
+    pub fn io_limiter(&self) -> &IoLimiter {
+        &self.io_limiter
+    }
+
     /// Sets resource watcher which will track any modifications in file system and forcing
     /// the manager to reload changed resources. By default there is no watcher, since it
     /// may be an undesired effect to reload resources at runtime. This is very useful thing
@@ -578,6 +700,10 @@ impl ResourceManagerState {
     /// Normally, this is called from `Engine::update()`.
     /// You should only call this manually if you don't use that method.
     pub fn update(&mut self, dt: f32) {
+        self.pending_loads
+            .retain(|_, interest| !interest.is_finished());
+
+        let count = self.resources.len();
         self.resources.retain_mut(|resource| {
             // One usage means that the resource has single owner, and that owner
             // is this container. Such resources have limited life time, if the time
@@ -608,6 +734,7 @@ impl ResourceManagerState {
                 true
             }
         });
+        self.destroyed_resources += (count - self.resources.len()) as u64;
 
         if let Some(watcher) = self.watcher.as_ref() {
             if let Some(evt) = watcher.try_get_event() {
@@ -673,8 +800,16 @@ impl ResourceManagerState {
 
     /// Immediately destroys all resources in the manager that are not used anywhere else.
     pub fn destroy_unused_resources(&mut self) {
+        let count = self.resources.len();
         self.resources
             .retain(|resource| resource.value.use_count() > 1);
+        self.destroyed_resources += (count - self.resources.len()) as u64;
+    }
+
+    /// Returns total amount of unused resources, that were destroyed by the manager since its
+    /// creation (see [`Self::update`] and [`Self::destroy_unused_resources`]).
+    pub fn destroyed_resource_count(&self) -> u64 {
+        self.destroyed_resources
     }
 
     /// Returns total amount of resources that still loading.
@@ -709,78 +844,267 @@ impl ResourceManagerState {
     where
         P: AsRef<Path>,
     {
-        if let Some(built_in_resource) = self.built_in_resources.get(path.as_ref()) {
-            return built_in_resource.resource.clone();
+        self.request_with_interest(path.as_ref(), None, false).0
+    }
+
+    /// Requests a resource and registers the interest of the caller in its pending load. The
+    /// interest is returned only for scoped requests of resources that are still loading. The
+    /// type of the resource (if known) is used to pick a loader, when the extension of the file is
+    /// not enough.
+    pub(crate) fn request_with_interest(
+        &mut self,
+        path: &Path,
+        type_uuid: Option<Uuid>,
+        scoped: bool,
+    ) -> (UntypedResource, Option<Arc<LoadInterest>>) {
+        if let Some(built_in_resource) = self.built_in_resources.get(path) {
+            return (built_in_resource.resource.clone(), None);
         }
 
-        match self.find(path.as_ref()) {
-            Some(existing) => existing.clone(),
+        match self.find(path) {
+            Some(existing) => {
+                let existing = existing.clone();
+                let interest = if existing.is_loading() {
+                    self.pending_loads
+                        .get(path)
+                        .filter(|interest| !interest.is_finished())
+                        .cloned()
+                } else {
+                    None
+                };
+                if let Some(interest) = interest.as_ref() {
+                    interest.add_interest(scoped);
+                }
+                (existing, interest.filter(|_| scoped))
+            }
             None => {
-                let path = path.as_ref().to_owned();
+                let path = path.to_owned();
                 let kind = ResourceKind::External(path.clone());
 
-                if let Some(loader) = self.find_loader(path.as_ref()) {
-                    let resource = UntypedResource::new_pending(kind, loader.data_type_uuid());
-                    self.spawn_loading_task(path, resource.clone(), loader, false);
+                let resource_type_uuid = match self.choose_loader(&path, type_uuid) {
+                    LoaderChoice::Loader(loader) => Some(loader.data_type_uuid()),
+                    LoaderChoice::ByContent {
+                        provisional_type_uuid,
+                        ..
+                    } => Some(provisional_type_uuid),
+                    LoaderChoice::None => None,
+                };
+                if let Some(resource_type_uuid) = resource_type_uuid {
+                    let resource = UntypedResource::new_pending(kind, resource_type_uuid);
+                    let interest = self.spawn_loading_task(
+                        path.clone(),
+                        resource.clone(),
+                        type_uuid,
+                        false,
+                        scoped,
+                    );
+                    self.pending_loads.insert(path, interest.clone());
                     self.push(resource.clone());
-                    resource
+                    (resource, scoped.then_some(interest))
                 } else {
                     let err =
                         LoadError::new(format!("There's no resource loader for {kind} resource!",));
-                    UntypedResource::new_load_error(kind, err, Default::default())
+                    (
+                        UntypedResource::new_load_error(kind, err, Default::default()),
+                        None,
+                    )
                 }
             }
         }
     }
 
-    fn find_loader(&self, path: &Path) -> Option<&dyn ResourceLoader> {
-        path.extension().and_then(|extension| {
-            self.loaders
+    /// Releases the interest of a dropped scope in a pending load. If nobody else is interested
+    /// in the load, it is abandoned and the resource is removed from the manager.
+    pub(crate) fn release_scoped_load(&mut self, load: ScopedLoad) {
+        let Some(path) = load.resource.kind().path_owned() else {
+            return;
+        };
+
+        // The resource could be reloaded after the scope requested it, the new load is not bound
+        // to the scope.
+        if !self
+            .pending_loads
+            .get(&path)
+            .is_some_and(|interest| Arc::ptr_eq(interest, &load.interest))
+        {
+            return;
+        }
+
+        // The header is locked while the load is cancelled, so the loading task cannot commit its
+        // result in the meantime.
+        let mut header = load.resource.0.lock();
+        if header.state.is_loading() && load.interest.release_scope() {
+            header.state.commit_error(format!(
+                "Loading of {} resource was cancelled.",
+                path.display()
+            ));
+            drop(header);
+
+            Log::info(format!(
+                "Loading of {} resource was cancelled, because it is not needed anymore.",
+                path.display()
+            ));
+
+            self.pending_loads.remove(&path);
+            if let Some(position) = self
+                .resources
                 .iter()
-                .find(|loader| loader.supports_extension(&extension.to_string_lossy()))
-        })
+                .position(|entry| entry.value == load.resource)
+            {
+                self.resources.remove(position);
+            }
+            self.event_broadcaster
+                .broadcast(ResourceEvent::Removed(path));
+        }
+    }
+
+    /// Picks a loader by the extension of the file. Files are never read here, if the extension is
+    /// not enough, the loader is picked by the content of the file in the loading task.
+    fn choose_loader(&self, path: &Path, type_uuid: Option<Uuid>) -> LoaderChoice<'_> {
+        let extension = path.extension().map(|ext| ext.to_string_lossy());
+        let candidates = self.loaders.find_by_extension(extension.as_deref(), None);
+        if let [loader] = candidates.as_slice() {
+            return LoaderChoice::Loader(*loader);
+        }
+
+        if !self
+            .loaders
+            .iter()
+            .any(|loader| type_uuid.map_or(true, |uuid| loader.data_type_uuid() == uuid))
+        {
+            return LoaderChoice::None;
+        }
+
+        LoaderChoice::ByContent {
+            type_uuid,
+            provisional_type_uuid: type_uuid
+                .or_else(|| candidates.first().map(|loader| loader.data_type_uuid()))
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Creates a future, that loads the resource using the chosen loader.
+    fn make_loader_future(
+        &self,
+        path: PathBuf,
+        resource: UntypedResource,
+        type_uuid: Option<Uuid>,
+        io: Arc<dyn ResourceIo>,
+    ) -> BoxedLoaderFuture {
+        match self.choose_loader(&path, type_uuid) {
+            LoaderChoice::Loader(loader) => loader.load(path, io),
+            LoaderChoice::ByContent { type_uuid, .. } => {
+                let self_ref = self.self_ref.clone();
+                Box::pin(async move {
+                    // The header is read without locking the state, the lock is needed only to
+                    // pick the loader.
+                    let header = read_content_header(&*io, &path).await.unwrap_or_default();
+                    let loader_future = {
+                        let state = self_ref
+                            .upgrade()
+                            .ok_or_else(|| LoadError::new("The resource manager was destroyed!"))?;
+                        let state = state.lock();
+                        let extension = path.extension().map(|ext| ext.to_string_lossy());
+                        let loader = state
+                            .loaders
+                            .find_by_content(extension.as_deref(), type_uuid, &header)
+                            .ok_or_else(|| {
+                                LoadError::new(format!(
+                                    "There's no resource loader for {} resource!",
+                                    path.display()
+                                ))
+                            })?;
+                        resource.0.lock().type_uuid = loader.data_type_uuid();
+                        loader.load(path.clone(), io.clone())
+                    };
+                    loader_future.await
+                })
+            }
+            LoaderChoice::None => {
+                let error = LoadError::new(format!(
+                    "There's no resource loader for {} resource!",
+                    path.display()
+                ));
+                Box::pin(async move { Err(error) })
+            }
+        }
     }
 
     fn spawn_loading_task(
         &self,
         path: PathBuf,
         resource: UntypedResource,
-        loader: &dyn ResourceLoader,
+        type_uuid: Option<Uuid>,
         reload: bool,
-    ) {
+        scoped: bool,
+    ) -> Arc<LoadInterest> {
         let event_broadcaster = self.event_broadcaster.clone();
-        let loader_future = loader.load(path.clone(), self.resource_io.clone());
+        // The resource IO is a public field and could be replaced at any time, so the limit is
+        // synced right before it is used.
+        self.io_limiter
+            .set_max_concurrency(self.max_concurrent_reads());
+        let io = Arc::new(LimitedResourceIo::new(
+            self.resource_io.clone(),
+            self.io_limiter.clone(),
+        ));
+        // Aborting drops the loader future at its next suspension point, which also gives back
+        // the IO slot it waits for (if any).
+        let (abort_handle, abort_registration) = AbortHandle::new_pair();
+        let interest = Arc::new(LoadInterest::new(abort_handle, scoped));
+        let loader_future = Abortable::new(
+            profiler::profile_future(
+                "Load Resource",
+                self.make_loader_future(path.clone(), resource.clone(), type_uuid, io),
+            ),
+            abort_registration,
+        );
+        let task_interest = interest.clone();
         self.task_pool.spawn_task(async move {
             match loader_future.await {
-                Ok(data) => {
+                Ok(Ok(data)) => {
                     let data = data.0;
 
-                    Log::info(format!(
-                        "Resource {} was loaded successfully!",
-                        path.display()
-                    ));
-
                     // Separate scope to keep mutex locking time at minimum.
-                    {
+                    let committed = {
                         let mut mutex_guard = resource.0.lock();
-                        assert_eq!(mutex_guard.type_uuid, data.type_uuid());
-                        assert!(mutex_guard.kind.is_external());
-                        mutex_guard.state.commit(ResourceState::Ok(data));
-                    }
+                        if task_interest.is_cancelled() {
+                            false
+                        } else {
+                            assert_eq!(mutex_guard.type_uuid, data.type_uuid());
+                            assert!(mutex_guard.kind.is_external());
+                            mutex_guard.state.commit(ResourceState::Ok(data));
+                            true
+                        }
+                    };
+
+                    if committed {
+                        Log::info(format!(
+                            "Resource {} was loaded successfully!",
+                            path.display()
+                        ));
 
-                    event_broadcaster.broadcast_loaded_or_reloaded(resource, reload);
+                        event_broadcaster.broadcast_loaded_or_reloaded(resource, reload);
+                    }
                 }
-                Err(error) => {
+                Ok(Err(error)) => {
                     Log::info(format!(
                         "Resource {} failed to load. Reason: {:?}",
                         path.display(),
                         error
                     ));
 
-                    resource.commit_error(error);
+                    let mut mutex_guard = resource.0.lock();
+                    if !task_interest.is_cancelled() {
+                        mutex_guard.state.commit_error(error);
+                    }
                 }
+                // The resource was already switched to the error state by the scope.
+                Err(_) => (),
             }
+
+            task_interest.finish();
         });
+        interest
     }
 
     /// Reloads a single resource.
@@ -789,11 +1113,13 @@ impl ResourceManagerState {
 
         if !header.state.is_loading() {
             if let Some(path) = header.kind.path_owned() {
-                if let Some(loader) = self.find_loader(&path) {
+                if !matches!(self.choose_loader(&path, None), LoaderChoice::None) {
                     header.state.switch_to_pending_state();
                     drop(header);
 
-                    self.spawn_loading_task(path, resource, loader, true);
+                    let interest =
+                        self.spawn_loading_task(path.clone(), resource, None, true, false);
+                    self.pending_loads.insert(path, interest);
                 } else {
                     let msg = format!(
                         "There's no resource loader for {} resource!",
@@ -855,6 +1181,7 @@ impl ResourceManagerState {
         {
             self.resources.remove(position);
         }
+        self.pending_loads.remove(path);
     }
 }
 
@@ -863,7 +1190,8 @@ mod test {
     use std::error::Error;
     use std::{fs::File, time::Duration};
 
-    use crate::loader::{BoxedLoaderFuture, LoaderPayload, ResourceLoader};
+    use crate::loader::{BoxedLoaderFuture, ContentSignature, LoaderPayload, ResourceLoader};
+    use fyrox_core::futures::executor::block_on;
 
     use super::*;
 
@@ -902,6 +1230,11 @@ mod test {
             &["txt"]
         }
 
+        fn content_signatures(&self) -> &[ContentSignature] {
+            const SIGNATURES: [ContentSignature; 1] = [ContentSignature::new(b"STUB")];
+            &SIGNATURES
+        }
+
         fn data_type_uuid(&self) -> Uuid {
             <Stub as TypeUuidProvider>::type_uuid()
         }
@@ -948,11 +1281,13 @@ mod test {
         let mut state = new_resource_manager();
         assert!(state.watcher.is_none());
 
-        let path = PathBuf::from("test.txt");
+        let path = std::env::temp_dir().join("fyrox_resource_manager_set_watcher.txt");
         if File::create(path.clone()).is_ok() {
             let watcher = FileSystemWatcher::new(path.clone(), Duration::from_secs(1));
             state.set_watcher(watcher.ok());
             assert!(state.watcher.is_some());
+            drop(state);
+            let _ = std::fs::remove_file(path);
         }
     }
 
@@ -1046,6 +1381,27 @@ mod test {
 
         state.destroy_unused_resources();
         assert_eq!(state.len(), 0);
+        assert_eq!(state.destroyed_resource_count(), 1);
+    }
+
+    #[test]
+    fn resource_manager_state_update_destroys_unused_resources() {
+        let mut state = new_resource_manager();
+
+        let used = UntypedResource::new_pending(PathBuf::from("used.txt").into(), Uuid::default());
+        state.push(used.clone());
+        state.push(UntypedResource::new_pending(
+            PathBuf::from("unused.txt").into(),
+            Uuid::default(),
+        ));
+
+        state.update(DEFAULT_RESOURCE_LIFETIME * 0.5);
+        assert_eq!(state.len(), 2);
+        assert_eq!(state.destroyed_resource_count(), 0);
+
+        state.update(DEFAULT_RESOURCE_LIFETIME);
+        assert_eq!(state.resources(), vec![used]);
+        assert_eq!(state.destroyed_resource_count(), 1);
     }
 
     #[test]
@@ -1138,6 +1494,28 @@ mod test {
         );
     }
 
+    #[test]
+    fn resource_manager_picks_loader_by_content() {
+        let manager = ResourceManager::new(Arc::new(Default::default()));
+        manager.state().loaders.set(Stub {});
+
+        let dir = std::env::temp_dir();
+        let stub_path = dir.join("fyrox_resource_manager_stub_content");
+        let unknown_path = dir.join("fyrox_resource_manager_unknown_content");
+        std::fs::write(&stub_path, b"STUB data").unwrap();
+        std::fs::write(&unknown_path, b"????").unwrap();
+
+        // The files have no extension, so they stay pending until the loading task reads them.
+        let resource: Resource<Stub> = manager.request(&stub_path);
+        assert!(block_on(resource).is_ok());
+
+        let resource = manager.request_untyped(&unknown_path);
+        assert!(block_on(resource).is_err());
+
+        std::fs::remove_file(stub_path).unwrap();
+        std::fs::remove_file(unknown_path).unwrap();
+    }
+
     #[test]
     fn resource_manager_request_untyped() {
         let manager = ResourceManager::new(Arc::new(Default::default()));
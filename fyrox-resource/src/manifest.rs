@@ -0,0 +1,196 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Asset manifest is a plain list of files of an asset storage. It is used by the resource IO
+//! implementations that cannot enumerate directories of their storage on their own (for example,
+//! Android asset manager can list only files of a directory, but not its subdirectories). The
+//! manifest is generated by the editor when exporting a game.
+
+use crate::pack::{normalize_path, path_to_entry_name};
+use fxhash::FxHashSet;
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+/// Name of the manifest file, that is placed at the root of an asset storage.
+pub const ASSET_MANIFEST_FILE_NAME: &str = "fyrox_asset_manifest.txt";
+
+/// A list of files of an asset storage. Every line of the serialized manifest is a path of a file
+/// relative to the root of the storage, with `/` as separator. Directories are derived from the
+/// paths of the files.
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct AssetManifest {
+    files: FxHashSet<PathBuf>,
+    directories: FxHashSet<PathBuf>,
+}
+
+impl AssetManifest {
+    /// Parses the given manifest. Empty lines are ignored.
+    pub fn parse(text: &str) -> Self {
+        let mut manifest = Self::default();
+        for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            manifest.add_file(line);
+        }
+        manifest
+    }
+
+    /// Collects all files in the given directory (recursively). Paths of the files are stored
+    /// relative to the directory.
+    pub fn generate(root: impl AsRef<Path>) -> io::Result<Self> {
+        let root = root.as_ref();
+        let mut manifest = Self::default();
+        for entry in walkdir::WalkDir::new(root) {
+            let entry = entry?;
+            if entry.file_type().is_file() {
+                if let Ok(path) = entry.path().strip_prefix(root) {
+                    if path != Path::new(ASSET_MANIFEST_FILE_NAME) {
+                        manifest.add_file(path);
+                    }
+                }
+            }
+        }
+        Ok(manifest)
+    }
+
+    /// Generates a manifest for the given directory and writes it to the root of the directory.
+    pub fn generate_and_write(root: impl AsRef<Path>) -> io::Result<Self> {
+        let root = root.as_ref();
+        let manifest = Self::generate(root)?;
+        std::fs::write(root.join(ASSET_MANIFEST_FILE_NAME), manifest.serialize())?;
+        Ok(manifest)
+    }
+
+    /// Adds a file to the manifest.
+    pub fn add_file(&mut self, path: impl AsRef<Path>) {
+        let path = normalize_path(path.as_ref());
+        self.directories
+            .extend(path.ancestors().skip(1).map(Path::to_path_buf));
+        self.files.insert(path);
+    }
+
+    /// Serializes the manifest into a string. Paths are sorted to make the output stable.
+    pub fn serialize(&self) -> String {
+        let mut lines = self
+            .files
+            .iter()
+            .map(|path| path_to_entry_name(path))
+            .collect::<Vec<_>>();
+        lines.sort();
+        let mut text = lines.join("\n");
+        text.push('\n');
+        text
+    }
+
+    /// Returns an iterator over the paths of every file in the manifest.
+    pub fn files(&self) -> impl Iterator<Item = &Path> {
+        self.files.iter().map(|path| path.as_path())
+    }
+
+    /// Returns `true` if the manifest contains a file with the given path.
+    pub fn is_file(&self, path: &Path) -> bool {
+        self.files.contains(&normalize_path(path))
+    }
+
+    /// Returns `true` if the manifest contains at least one file in the given directory.
+    pub fn is_dir(&self, path: &Path) -> bool {
+        self.directories.contains(&normalize_path(path))
+    }
+
+    /// Returns the paths of the files and directories immediately within the given directory.
+    pub fn children(&self, path: &Path) -> Vec<PathBuf> {
+        let path = normalize_path(path);
+        self.files
+            .iter()
+            .chain(self.directories.iter())
+            .filter(|entry| entry.parent() == Some(path.as_path()))
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the paths of the directories immediately within the given directory.
+    pub fn subdirectories(&self, path: &Path) -> Vec<PathBuf> {
+        let path = normalize_path(path);
+        self.directories
+            .iter()
+            .filter(|entry| entry.parent() == Some(path.as_path()))
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the paths of all the files and directories within the given directory
+    /// (recursively).
+    pub fn descendants(&self, path: &Path) -> Vec<PathBuf> {
+        let path = normalize_path(path);
+        self.files
+            .iter()
+            .chain(self.directories.iter())
+            .filter(|entry| *entry != &path && entry.starts_with(&path))
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sorted(mut paths: Vec<PathBuf>) -> Vec<PathBuf> {
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn asset_manifest_parse_and_query() {
+        let manifest = AssetManifest::parse(
+            "data/textures/foo.png\n./data/scene.rgs\n\ndata/textures/ui/button.png\n",
+        );
+
+        assert!(manifest.is_file(Path::new("data/scene.rgs")));
+        assert!(manifest.is_file(Path::new("./data/textures/foo.png")));
+        assert!(!manifest.is_file(Path::new("data/textures")));
+        assert!(manifest.is_dir(Path::new("data/textures")));
+        assert!(manifest.is_dir(Path::new("data")));
+
+        assert_eq!(
+            sorted(manifest.children(Path::new("data"))),
+            vec![
+                PathBuf::from("data/scene.rgs"),
+                PathBuf::from("data/textures")
+            ]
+        );
+        assert_eq!(
+            manifest.subdirectories(Path::new("data/textures")),
+            vec![PathBuf::from("data/textures/ui")]
+        );
+        assert_eq!(manifest.descendants(Path::new("data")).len(), 5);
+    }
+
+    #[test]
+    fn asset_manifest_serialize() {
+        let mut manifest = AssetManifest::default();
+        manifest.add_file("data/b.png");
+        manifest.add_file("./data/a.png");
+
+        let text = manifest.serialize();
+        assert_eq!(text, "data/a.png\ndata/b.png\n");
+        assert_eq!(AssetManifest::parse(&text), manifest);
+    }
+}
@@ -0,0 +1,363 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Path normalization layer for resource IO. Projects are often authored on Windows, where paths
+//! may contain `\` separators and file systems are case-insensitive, and then shipped on Linux,
+//! Android or web, where the same paths fail to load. [`NormalizedResourceIo`] wraps any other
+//! resource IO and fixes such paths before passing them further.
+
+use crate::{
+    core::{cmp_strings_case_insensitive, io::FileLoadError, log::Log, parking_lot::Mutex},
+    io::{FileReader, PathIter, ResourceIo, ResourceIoFuture},
+};
+use fxhash::FxHashMap;
+use std::{
+    ffi::OsString,
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+};
+
+/// Lexically normalizes the given path: replaces `\` separators with `/`, removes `.` segments
+/// and resolves `..` segments where possible. Leading `..` segments of relative paths are kept.
+/// The file system is not accessed.
+pub fn normalize_resource_path(path: &Path) -> PathBuf {
+    let text = path.to_string_lossy();
+    let path = if text.contains('\\') {
+        PathBuf::from(text.replace('\\', "/"))
+    } else {
+        path.to_path_buf()
+    };
+
+    let mut result = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => (),
+            Component::ParentDir => match result.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    result.pop();
+                }
+                // `..` cannot go above the root.
+                Some(Component::RootDir) | Some(Component::Prefix(_)) => (),
+                _ => result.push(".."),
+            },
+            component => result.push(component),
+        }
+    }
+
+    if result.as_os_str().is_empty() && !path.as_os_str().is_empty() {
+        result.push(".");
+    }
+
+    result
+}
+
+/// Defines what [`NormalizedResourceIo`] does with the paths, that differ from the actual paths
+/// in the storage only by case.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum CaseCheck {
+    /// Case of the paths isn't checked.
+    #[default]
+    Disabled,
+    /// A warning is written to the log and the path is used as is. It is still loaded on
+    /// case-insensitive file systems, so the issue could be fixed before shipping the game.
+    Warn,
+    /// A warning is written to the log and the actual path is used instead, so the file could be
+    /// loaded on any file system.
+    Correct,
+}
+
+/// Resource IO wrapper, that normalizes every path (see [`normalize_resource_path`]) before
+/// passing it to the inner resource IO and optionally checks the case of the paths of the files
+/// that are read (see [`CaseCheck`]). The case check requires listing the directories of the
+/// storage, so its results are cached. Only relative paths are checked.
+pub struct NormalizedResourceIo {
+    io: Arc<dyn ResourceIo>,
+    case_check: CaseCheck,
+    resolved_paths: Mutex<FxHashMap<PathBuf, PathBuf>>,
+}
+
+impl NormalizedResourceIo {
+    /// Creates a new wrapper over the given resource IO.
+    pub fn new(io: Arc<dyn ResourceIo>, case_check: CaseCheck) -> Self {
+        Self {
+            io,
+            case_check,
+            resolved_paths: Default::default(),
+        }
+    }
+
+    /// Returns a reference to the inner resource IO.
+    pub fn inner(&self) -> &Arc<dyn ResourceIo> {
+        &self.io
+    }
+
+    /// Returns current case check mode.
+    pub fn case_check(&self) -> CaseCheck {
+        self.case_check
+    }
+
+    /// Normalizes the path of a file that is about to be read and checks its case.
+    async fn resolve(&self, path: &Path) -> PathBuf {
+        let path = normalize_resource_path(path);
+
+        if self.case_check == CaseCheck::Disabled || !path.is_relative() {
+            return path;
+        }
+
+        let cached = self.resolved_paths.lock().get(&path).cloned();
+        if let Some(resolved) = cached {
+            return resolved;
+        }
+
+        let mut actual = PathBuf::new();
+        let mut mismatch = false;
+        for component in path.components() {
+            let Component::Normal(name) = component else {
+                actual.push(component);
+                continue;
+            };
+
+            let directory = if actual.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                actual.as_path()
+            };
+            let Ok(entries) = self.io.read_directory(directory).await else {
+                return path;
+            };
+            let names = entries
+                .filter_map(|entry| entry.file_name().map(|name| name.to_os_string()))
+                .collect::<Vec<OsString>>();
+
+            if names.iter().any(|entry| entry == name) {
+                actual.push(name);
+            } else if let Some(entry) = names.iter().find(|entry| {
+                cmp_strings_case_insensitive(entry.to_string_lossy(), name.to_string_lossy())
+            }) {
+                mismatch = true;
+                actual.push(entry);
+            } else {
+                // The file does not exist at all, there's nothing to check. Such paths aren't
+                // cached, the file could be created later.
+                return path;
+            }
+        }
+
+        let resolved = if mismatch {
+            Log::warn(format!(
+                "Path {} differs from the actual path {} only by case. It won't be loaded \
+                on case-sensitive file systems (Linux, Android, Web).",
+                path.display(),
+                actual.display()
+            ));
+            if self.case_check == CaseCheck::Correct {
+                actual
+            } else {
+                path.clone()
+            }
+        } else {
+            path.clone()
+        };
+
+        self.resolved_paths.lock().insert(path, resolved.clone());
+
+        resolved
+    }
+}
+
+impl ResourceIo for NormalizedResourceIo {
+    fn load_file<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<Vec<u8>, FileLoadError>> {
+        Box::pin(async move {
+            let path = self.resolve(path).await;
+            self.io.load_file(&path).await
+        })
+    }
+
+    fn load_files<'a>(
+        &'a self,
+        paths: &'a [PathBuf],
+    ) -> ResourceIoFuture<'a, Vec<Result<Vec<u8>, FileLoadError>>> {
+        Box::pin(async move {
+            let mut resolved = Vec::with_capacity(paths.len());
+            for path in paths {
+                resolved.push(self.resolve(path).await);
+            }
+            self.io.load_files(&resolved).await
+        })
+    }
+
+    fn max_concurrent_reads(&self) -> Option<usize> {
+        self.io.max_concurrent_reads()
+    }
+
+    fn write_file<'a>(
+        &'a self,
+        path: &'a Path,
+        data: Vec<u8>,
+    ) -> ResourceIoFuture<'a, Result<(), FileLoadError>> {
+        Box::pin(async move {
+            let path = normalize_resource_path(path);
+            self.io.write_file(&path, data).await
+        })
+    }
+
+    fn move_file<'a>(
+        &'a self,
+        source: &'a Path,
+        dest: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<(), FileLoadError>> {
+        Box::pin(async move {
+            let source = self.resolve(source).await;
+            let dest = normalize_resource_path(dest);
+            self.io.move_file(&source, &dest).await
+        })
+    }
+
+    fn canonicalize_path<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<PathBuf, FileLoadError>> {
+        Box::pin(async move {
+            let path = self.resolve(path).await;
+            self.io.canonicalize_path(&path).await
+        })
+    }
+
+    fn read_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<PathIter, FileLoadError>> {
+        Box::pin(async move {
+            let path = self.resolve(path).await;
+            self.io.read_directory(&path).await
+        })
+    }
+
+    fn walk_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<PathIter, FileLoadError>> {
+        Box::pin(async move {
+            let path = self.resolve(path).await;
+            self.io.walk_directory(&path).await
+        })
+    }
+
+    fn file_reader<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<Box<dyn FileReader>, FileLoadError>> {
+        Box::pin(async move {
+            let path = self.resolve(path).await;
+            self.io.file_reader(&path).await
+        })
+    }
+
+    fn exists<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool> {
+        Box::pin(async move {
+            let path = self.resolve(path).await;
+            self.io.exists(&path).await
+        })
+    }
+
+    fn is_file<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool> {
+        Box::pin(async move {
+            let path = self.resolve(path).await;
+            self.io.is_file(&path).await
+        })
+    }
+
+    fn is_dir<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool> {
+        Box::pin(async move {
+            let path = self.resolve(path).await;
+            self.io.is_dir(&path).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::pack::{PackedResourceIo, ResourcePackBuilder};
+    use fyrox_core::futures::executor::block_on;
+
+    fn make_io(case_check: CaseCheck) -> NormalizedResourceIo {
+        let mut builder = ResourcePackBuilder::new();
+        builder
+            .add_file("data/Textures/Grass.png", vec![1, 2, 3])
+            .add_file("data/scene.rgs", vec![4, 5]);
+        let pack = PackedResourceIo::from_bytes(builder.build()).unwrap();
+        NormalizedResourceIo::new(Arc::new(pack), case_check)
+    }
+
+    #[test]
+    fn path_normalization() {
+        assert_eq!(
+            normalize_resource_path(Path::new(r"data\textures\grass.png")),
+            PathBuf::from("data/textures/grass.png")
+        );
+        assert_eq!(
+            normalize_resource_path(Path::new("./data/./models/../textures/grass.png")),
+            PathBuf::from("data/textures/grass.png")
+        );
+        assert_eq!(
+            normalize_resource_path(Path::new("../shared/../../grass.png")),
+            PathBuf::from("../../grass.png")
+        );
+        assert_eq!(
+            normalize_resource_path(Path::new("/data/../../grass.png")),
+            PathBuf::from("/grass.png")
+        );
+        assert_eq!(
+            normalize_resource_path(Path::new("data/..")),
+            PathBuf::from(".")
+        );
+    }
+
+    #[test]
+    fn normalized_paths_are_loaded() {
+        let io = make_io(CaseCheck::Disabled);
+        assert_eq!(
+            block_on(io.load_file(Path::new(r".\data\Textures\..\scene.rgs"))).unwrap(),
+            vec![4, 5]
+        );
+        assert!(block_on(io.load_file(Path::new("data/textures/grass.png"))).is_err());
+    }
+
+    #[test]
+    fn case_mismatch_is_corrected() {
+        let io = make_io(CaseCheck::Correct);
+        assert_eq!(
+            block_on(io.load_file(Path::new(r"data\textures\GRASS.png"))).unwrap(),
+            vec![1, 2, 3]
+        );
+        assert!(block_on(io.is_dir(Path::new("DATA/textures"))));
+        assert!(block_on(io.load_file(Path::new("data/missing.png"))).is_err());
+    }
+
+    #[test]
+    fn case_mismatch_is_only_reported() {
+        let io = make_io(CaseCheck::Warn);
+        assert!(block_on(io.load_file(Path::new("data/textures/grass.png"))).is_err());
+        assert!(block_on(io.load_file(Path::new("data/Textures/Grass.png"))).is_ok());
+    }
+}
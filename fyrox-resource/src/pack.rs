@@ -0,0 +1,410 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Resource packs are simple archives that store a set of files in a single blob. They are used
+//! to ship assets of a game as one file instead of a directory tree. See [`ResourcePackBuilder`]
+//! to create a pack and [`PackedResourceIo`] to load resources from it.
+
+use crate::io::{ResourceIo, ResourceIoFuture};
+use fxhash::{FxHashMap, FxHashSet};
+use fyrox_core::io::FileLoadError;
+use std::{
+    future::ready,
+    io::{self, ErrorKind},
+    ops::Range,
+    path::{Component, Path, PathBuf},
+};
+
+/// Standard extension of resource pack files.
+pub const PACK_EXTENSION: &str = "fpak";
+
+/// Name of the resource pack, that is produced by the editor when exporting a game. The executor
+/// loads resources from this pack (if it exists in the working directory) instead of loose files.
+pub const DEFAULT_PACK_FILE_NAME: &str = "assets.fpak";
+
+const MAGIC: [u8; 4] = *b"FPAK";
+const VERSION: u32 = 1;
+
+pub(crate) fn normalize_path(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|component| !matches!(component, Component::CurDir))
+        .collect()
+}
+
+pub(crate) fn path_to_entry_name(path: &Path) -> String {
+    normalize_path(path)
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Collects files and writes them into a resource pack. Paths of the files are stored exactly as
+/// they're passed to the builder (excluding `./` prefixes), so they must match the paths that will
+/// be used to request resources at runtime.
+#[derive(Default)]
+pub struct ResourcePackBuilder {
+    entries: Vec<(String, Vec<u8>)>,
+}
+
+impl ResourcePackBuilder {
+    /// Creates an empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a file with the given path and content to the pack. A file with the same path will be
+    /// replaced.
+    pub fn add_file(&mut self, path: impl AsRef<Path>, data: Vec<u8>) -> &mut Self {
+        let name = path_to_entry_name(path.as_ref());
+        if let Some(entry) = self.entries.iter_mut().find(|(n, _)| *n == name) {
+            entry.1 = data;
+        } else {
+            self.entries.push((name, data));
+        }
+        self
+    }
+
+    /// Returns total amount of files in the pack.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the pack has no files.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Serializes the pack into a blob of bytes.
+    pub fn build(&self) -> Vec<u8> {
+        let header_size = MAGIC.len()
+            + 2 * size_of::<u32>()
+            + self
+                .entries
+                .iter()
+                .map(|(name, _)| size_of::<u32>() + name.len() + 2 * size_of::<u64>())
+                .sum::<usize>();
+        let data_size = self
+            .entries
+            .iter()
+            .map(|(_, data)| data.len())
+            .sum::<usize>();
+
+        let mut bytes = Vec::with_capacity(header_size + data_size);
+        bytes.extend_from_slice(&MAGIC);
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bytes.extend_from_slice(&(self.entries.len() as u32).to_le_bytes());
+
+        let mut offset = header_size as u64;
+        for (name, data) in self.entries.iter() {
+            bytes.extend_from_slice(&(name.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(name.as_bytes());
+            bytes.extend_from_slice(&offset.to_le_bytes());
+            bytes.extend_from_slice(&(data.len() as u64).to_le_bytes());
+            offset += data.len() as u64;
+        }
+
+        for (_, data) in self.entries.iter() {
+            bytes.extend_from_slice(data);
+        }
+
+        bytes
+    }
+
+    /// Serializes the pack and writes it to a file at the given path.
+    pub fn write(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, self.build())
+    }
+}
+
+struct PackReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl PackReader<'_> {
+    fn read_bytes(&mut self, count: usize) -> Result<&[u8], FileLoadError> {
+        let bytes = self
+            .data
+            .get(self.position..self.position + count)
+            .ok_or_else(|| FileLoadError::Custom("Unexpected end of resource pack!".to_string()))?;
+        self.position += count;
+        Ok(bytes)
+    }
+
+    fn read_u32(&mut self) -> Result<u32, FileLoadError> {
+        Ok(u32::from_le_bytes(self.read_bytes(4)?.try_into().unwrap()))
+    }
+
+    fn read_u64(&mut self) -> Result<u64, FileLoadError> {
+        Ok(u64::from_le_bytes(self.read_bytes(8)?.try_into().unwrap()))
+    }
+}
+
+/// Read-only resource IO, that loads files from a resource pack. The entire pack is kept in
+/// memory, which makes it suitable for platforms without a file system (such as WebAssembly).
+pub struct PackedResourceIo {
+    data: Vec<u8>,
+    files: FxHashMap<PathBuf, Range<usize>>,
+    directories: FxHashSet<PathBuf>,
+}
+
+impl PackedResourceIo {
+    /// Parses the given resource pack.
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, FileLoadError> {
+        let mut reader = PackReader {
+            data: &data,
+            position: 0,
+        };
+
+        if reader.read_bytes(MAGIC.len())? != MAGIC {
+            return Err(FileLoadError::Custom(
+                "The data is not a resource pack!".to_string(),
+            ));
+        }
+
+        let version = reader.read_u32()?;
+        if version != VERSION {
+            return Err(FileLoadError::Custom(format!(
+                "Unsupported resource pack version {version}!"
+            )));
+        }
+
+        let mut files = FxHashMap::default();
+        let mut directories = FxHashSet::default();
+        for _ in 0..reader.read_u32()? {
+            let name_len = reader.read_u32()? as usize;
+            let name = String::from_utf8(reader.read_bytes(name_len)?.to_vec())
+                .map_err(|err| FileLoadError::Custom(err.to_string()))?;
+            let offset = reader.read_u64()? as usize;
+            let size = reader.read_u64()? as usize;
+
+            let range = offset..offset + size;
+            if range.end > data.len() {
+                return Err(FileLoadError::Custom(format!(
+                    "Resource pack entry {name} is out of bounds!"
+                )));
+            }
+
+            let path = PathBuf::from(name);
+            directories.extend(path.ancestors().skip(1).map(Path::to_path_buf));
+            files.insert(path, range);
+        }
+
+        Ok(Self {
+            data,
+            files,
+            directories,
+        })
+    }
+
+    /// Loads a resource pack from the given path. The pack is loaded using standard file system
+    /// of the current platform.
+    pub async fn load(path: impl AsRef<Path>) -> Result<Self, FileLoadError> {
+        Self::from_bytes(fyrox_core::io::load_file(path).await?)
+    }
+
+    /// Returns an iterator over the paths of every file in the pack.
+    pub fn files(&self) -> impl Iterator<Item = &Path> {
+        self.files.keys().map(|path| path.as_path())
+    }
+
+    fn children(&self, path: &Path) -> Vec<PathBuf> {
+        let path = normalize_path(path);
+        self.files
+            .keys()
+            .chain(self.directories.iter())
+            .filter(|entry| entry.parent() == Some(path.as_path()))
+            .cloned()
+            .collect()
+    }
+
+    fn descendants(&self, path: &Path) -> Vec<PathBuf> {
+        let path = normalize_path(path);
+        self.files
+            .keys()
+            .chain(self.directories.iter())
+            .filter(|entry| *entry != &path && entry.starts_with(&path))
+            .cloned()
+            .collect()
+    }
+
+    fn read_entry(&self, path: &Path) -> Result<Vec<u8>, FileLoadError> {
+        match self.files.get(&normalize_path(path)) {
+            Some(range) => Ok(self.data[range.clone()].to_vec()),
+            None => Err(FileLoadError::Io(io::Error::new(
+                ErrorKind::NotFound,
+                format!("{} does not exist in the resource pack!", path.display()),
+            ))),
+        }
+    }
+}
+
+impl ResourceIo for PackedResourceIo {
+    fn load_file<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<Vec<u8>, FileLoadError>> {
+        Box::pin(ready(self.read_entry(path)))
+    }
+
+    /// The entire pack is kept in memory, so the whole batch is read at once without waiting.
+    fn load_files<'a>(
+        &'a self,
+        paths: &'a [PathBuf],
+    ) -> ResourceIoFuture<'a, Vec<Result<Vec<u8>, FileLoadError>>> {
+        Box::pin(ready(
+            paths.iter().map(|path| self.read_entry(path)).collect(),
+        ))
+    }
+
+    fn move_file<'a>(
+        &'a self,
+        source: &'a Path,
+        _dest: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<(), FileLoadError>> {
+        Box::pin(ready(Err(FileLoadError::Custom(format!(
+            "Unable to move {}. The resource pack is read-only!",
+            source.display()
+        )))))
+    }
+
+    fn read_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<Box<dyn Iterator<Item = PathBuf> + Send>, FileLoadError>> {
+        let iter: Box<dyn Iterator<Item = PathBuf> + Send> =
+            Box::new(self.children(path).into_iter());
+        Box::pin(ready(Ok(iter)))
+    }
+
+    fn walk_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<Box<dyn Iterator<Item = PathBuf> + Send>, FileLoadError>> {
+        let iter: Box<dyn Iterator<Item = PathBuf> + Send> =
+            Box::new(self.descendants(path).into_iter());
+        Box::pin(ready(Ok(iter)))
+    }
+
+    fn exists<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool> {
+        let path = normalize_path(path);
+        Box::pin(ready(
+            self.files.contains_key(&path) || self.directories.contains(&path),
+        ))
+    }
+
+    fn is_file<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool> {
+        Box::pin(ready(self.files.contains_key(&normalize_path(path))))
+    }
+
+    fn is_dir<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool> {
+        Box::pin(ready(self.directories.contains(&normalize_path(path))))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use fyrox_core::futures::executor::block_on;
+
+    fn make_pack() -> PackedResourceIo {
+        let mut builder = ResourcePackBuilder::new();
+        builder
+            .add_file("./data/textures/foo.png", vec![1, 2, 3])
+            .add_file("data/scene.rgs", vec![4, 5])
+            .add_file("data/empty.txt", vec![]);
+        PackedResourceIo::from_bytes(builder.build()).unwrap()
+    }
+
+    #[test]
+    fn resource_pack_load_file() {
+        let pack = make_pack();
+
+        assert_eq!(
+            block_on(pack.load_file(Path::new("data/textures/foo.png"))).unwrap(),
+            vec![1, 2, 3]
+        );
+        assert_eq!(
+            block_on(pack.load_file(Path::new("./data/scene.rgs"))).unwrap(),
+            vec![4, 5]
+        );
+        assert!(block_on(pack.load_file(Path::new("data/empty.txt")))
+            .unwrap()
+            .is_empty());
+        assert!(block_on(pack.load_file(Path::new("data/missing.png"))).is_err());
+    }
+
+    #[test]
+    fn resource_pack_load_files() {
+        let pack = make_pack();
+
+        let paths = [
+            PathBuf::from("data/scene.rgs"),
+            PathBuf::from("data/missing.png"),
+            PathBuf::from("./data/textures/foo.png"),
+        ];
+        let results = block_on(pack.load_files(&paths));
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap(), &vec![4, 5]);
+        assert!(results[1].is_err());
+        assert_eq!(results[2].as_ref().unwrap(), &vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn resource_pack_directories() {
+        let pack = make_pack();
+
+        assert!(block_on(pack.is_dir(Path::new("data"))));
+        assert!(block_on(pack.is_dir(Path::new("data/textures"))));
+        assert!(!block_on(pack.is_file(Path::new("data/textures"))));
+        assert!(block_on(pack.is_file(Path::new("data/scene.rgs"))));
+        assert!(block_on(pack.exists(Path::new("./data/textures/foo.png"))));
+
+        let mut children = block_on(pack.read_directory(Path::new("data")))
+            .unwrap()
+            .collect::<Vec<_>>();
+        children.sort();
+        assert_eq!(
+            children,
+            vec![
+                PathBuf::from("data/empty.txt"),
+                PathBuf::from("data/scene.rgs"),
+                PathBuf::from("data/textures"),
+            ]
+        );
+
+        assert_eq!(
+            block_on(pack.walk_directory(Path::new("data")))
+                .unwrap()
+                .count(),
+            4
+        );
+    }
+
+    #[test]
+    fn resource_pack_invalid_data() {
+        assert!(PackedResourceIo::from_bytes(b"not a pack".to_vec()).is_err());
+
+        let mut bytes = make_pack().data;
+        bytes.truncate(bytes.len() - 1);
+        assert!(PackedResourceIo::from_bytes(bytes).is_err());
+    }
+}
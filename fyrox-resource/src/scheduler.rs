@@ -0,0 +1,416 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! IO scheduling for resource loading. It limits the amount of concurrent reads performed by
+//! resource loaders and defines the order in which pending reads are served. See [`IoLimiter`]
+//! and [`LimitedResourceIo`] docs for more info.
+
+use crate::{
+    core::{futures::channel::oneshot, io::FileLoadError, parking_lot::Mutex},
+    io::{FileReader, PathIter, ResourceIo, ResourceIoFuture},
+};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Defines the order in which pending reads are served when the limit of concurrent reads is
+/// reached.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash)]
+pub enum LoadOrder {
+    /// Reads are served in the order they were requested.
+    #[default]
+    Fifo,
+    /// The most recently requested read is served first. It could be useful to show the most
+    /// recent content (for example, the content around the player) as fast as possible.
+    Lifo,
+    /// Reads are served in the lexicographical order of their paths. Files in the same folder
+    /// are usually placed close to each other on a disk, which reduces the amount of seeks on
+    /// HDDs.
+    Path,
+}
+
+struct Waiter {
+    path: PathBuf,
+    sender: oneshot::Sender<IoPermit>,
+}
+
+#[derive(Default)]
+struct LimiterState {
+    max_concurrency: Option<usize>,
+    order: LoadOrder,
+    active: usize,
+    waiting: Vec<Waiter>,
+}
+
+impl LimiterState {
+    fn has_free_slot(&self) -> bool {
+        self.max_concurrency.map_or(true, |max| self.active < max)
+    }
+
+    fn take_next_waiter(&mut self) -> Option<Waiter> {
+        if self.waiting.is_empty() {
+            return None;
+        }
+
+        let index = match self.order {
+            LoadOrder::Fifo => 0,
+            LoadOrder::Lifo => self.waiting.len() - 1,
+            // `min_by` returns the first element among the equal ones, so the waiters with the
+            // same path are still served in FIFO order.
+            LoadOrder::Path => self
+                .waiting
+                .iter()
+                .enumerate()
+                .min_by(|(_, a), (_, b)| a.path.cmp(&b.path))
+                .map(|(index, _)| index)?,
+        };
+
+        Some(self.waiting.remove(index))
+    }
+}
+
+/// Asynchronous limiter of concurrent IO operations. Every operation must acquire a permit
+/// ([`IoPermit`]) first, the permit gives back its slot to the limiter when dropped. If there
+/// are no free slots, the operation waits until one of the active operations is finished. Pending
+/// operations are served in the order defined by [`LoadOrder`].
+///
+/// The limiter is cheap to clone, all clones share the same state.
+#[derive(Clone, Default)]
+pub struct IoLimiter {
+    state: Arc<Mutex<LimiterState>>,
+}
+
+impl IoLimiter {
+    /// Creates a new limiter with the given amount of concurrent operations and the order of
+    /// pending operations. `None` means that there is no limit.
+    pub fn new(max_concurrency: Option<usize>, order: LoadOrder) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(LimiterState {
+                max_concurrency: max_concurrency.map(|max| max.max(1)),
+                order,
+                ..Default::default()
+            })),
+        }
+    }
+
+    /// Returns current maximum amount of concurrent operations. `None` means that there is no
+    /// limit.
+    pub fn max_concurrency(&self) -> Option<usize> {
+        self.state.lock().max_concurrency
+    }
+
+    /// Sets new maximum amount of concurrent operations. `None` means that there is no limit. The
+    /// limit cannot be less than one. Lowering the limit does not interrupt active operations.
+    pub fn set_max_concurrency(&self, max_concurrency: Option<usize>) {
+        self.state.lock().max_concurrency = max_concurrency.map(|max| max.max(1));
+        Self::grant_free_slots(&self.state);
+    }
+
+    /// Returns current order of pending operations.
+    pub fn order(&self) -> LoadOrder {
+        self.state.lock().order
+    }
+
+    /// Sets new order of pending operations. It affects pending operations as well.
+    pub fn set_order(&self, order: LoadOrder) {
+        self.state.lock().order = order;
+    }
+
+    /// Returns the amount of operations that are currently holding a permit.
+    pub fn active_count(&self) -> usize {
+        self.state.lock().active
+    }
+
+    /// Returns the amount of operations that are waiting for a permit.
+    pub fn pending_count(&self) -> usize {
+        self.state.lock().waiting.len()
+    }
+
+    /// Waits until there is a free slot for an operation with the given path and returns a
+    /// permit that holds the slot until dropped.
+    pub async fn acquire(&self, path: &Path) -> IoPermit {
+        let receiver = {
+            let mut state = self.state.lock();
+            if state.has_free_slot() {
+                state.active += 1;
+                return IoPermit {
+                    state: Some(self.state.clone()),
+                };
+            }
+
+            let (sender, receiver) = oneshot::channel();
+            state.waiting.push(Waiter {
+                path: path.to_path_buf(),
+                sender,
+            });
+            receiver
+        };
+
+        // The sender could only be dropped without sending when the state is dropped, there is
+        // nothing to limit in this case.
+        receiver.await.unwrap_or(IoPermit { state: None })
+    }
+
+    fn grant_free_slots(state: &Arc<Mutex<LimiterState>>) {
+        loop {
+            let waiter = {
+                let mut guard = state.lock();
+                if !guard.has_free_slot() {
+                    return;
+                }
+                let Some(waiter) = guard.take_next_waiter() else {
+                    return;
+                };
+                guard.active += 1;
+                waiter
+            };
+
+            if let Err(mut permit) = waiter.sender.send(IoPermit {
+                state: Some(state.clone()),
+            }) {
+                // The waiting operation was cancelled, take the slot back and try the next one.
+                permit.state = None;
+                state.lock().active -= 1;
+            }
+        }
+    }
+}
+
+/// A permit that holds a slot of an [`IoLimiter`]. The slot is given back to the limiter when the
+/// permit is dropped.
+#[must_use]
+pub struct IoPermit {
+    state: Option<Arc<Mutex<LimiterState>>>,
+}
+
+impl Drop for IoPermit {
+    fn drop(&mut self) {
+        if let Some(state) = self.state.take() {
+            state.lock().active -= 1;
+            IoLimiter::grant_free_slots(&state);
+        }
+    }
+}
+
+/// Resource IO wrapper, that limits the amount of concurrent reads of the inner resource IO using
+/// the given [`IoLimiter`]. Batched reads ([`ResourceIo::load_files`]) occupy a single slot.
+/// Every other operation is passed to the inner resource IO as is.
+pub struct LimitedResourceIo {
+    io: Arc<dyn ResourceIo>,
+    limiter: IoLimiter,
+}
+
+impl LimitedResourceIo {
+    /// Creates a new wrapper over the given resource IO.
+    pub fn new(io: Arc<dyn ResourceIo>, limiter: IoLimiter) -> Self {
+        Self { io, limiter }
+    }
+
+    /// Returns a reference to the inner resource IO.
+    pub fn inner(&self) -> &Arc<dyn ResourceIo> {
+        &self.io
+    }
+
+    /// Returns a reference to the limiter.
+    pub fn limiter(&self) -> &IoLimiter {
+        &self.limiter
+    }
+}
+
+impl ResourceIo for LimitedResourceIo {
+    fn load_file<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<Vec<u8>, FileLoadError>> {
+        Box::pin(async move {
+            let _permit = self.limiter.acquire(path).await;
+            self.io.load_file(path).await
+        })
+    }
+
+    fn load_files<'a>(
+        &'a self,
+        paths: &'a [PathBuf],
+    ) -> ResourceIoFuture<'a, Vec<Result<Vec<u8>, FileLoadError>>> {
+        Box::pin(async move {
+            let first = paths.first().map(|path| path.as_path());
+            let _permit = self.limiter.acquire(first.unwrap_or(Path::new(""))).await;
+            self.io.load_files(paths).await
+        })
+    }
+
+    fn max_concurrent_reads(&self) -> Option<usize> {
+        self.limiter.max_concurrency()
+    }
+
+    fn write_file<'a>(
+        &'a self,
+        path: &'a Path,
+        data: Vec<u8>,
+    ) -> ResourceIoFuture<'a, Result<(), FileLoadError>> {
+        self.io.write_file(path, data)
+    }
+
+    fn move_file<'a>(
+        &'a self,
+        source: &'a Path,
+        dest: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<(), FileLoadError>> {
+        self.io.move_file(source, dest)
+    }
+
+    fn canonicalize_path<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<PathBuf, FileLoadError>> {
+        self.io.canonicalize_path(path)
+    }
+
+    fn read_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<PathIter, FileLoadError>> {
+        self.io.read_directory(path)
+    }
+
+    fn walk_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<PathIter, FileLoadError>> {
+        self.io.walk_directory(path)
+    }
+
+    /// The slot is held only while the reader is being opened, the actual reading is performed
+    /// by the caller afterwards.
+    fn file_reader<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<Box<dyn FileReader>, FileLoadError>> {
+        Box::pin(async move {
+            let _permit = self.limiter.acquire(path).await;
+            self.io.file_reader(path).await
+        })
+    }
+
+    fn exists<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool> {
+        self.io.exists(path)
+    }
+
+    fn is_file<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool> {
+        self.io.is_file(path)
+    }
+
+    fn is_dir<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool> {
+        self.io.is_dir(path)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::futures::{executor::block_on, task::noop_waker, FutureExt};
+    use std::{
+        future::Future,
+        task::{Context, Poll},
+    };
+
+    fn poll<F: Future + Unpin>(future: &mut F) -> Poll<F::Output> {
+        let waker = noop_waker();
+        future.poll_unpin(&mut Context::from_waker(&waker))
+    }
+
+    #[test]
+    fn io_limiter_respects_limit() {
+        let limiter = IoLimiter::new(Some(2), LoadOrder::Fifo);
+
+        let a = block_on(limiter.acquire(Path::new("a")));
+        let b = block_on(limiter.acquire(Path::new("b")));
+        assert_eq!(limiter.active_count(), 2);
+
+        let mut c = Box::pin(limiter.acquire(Path::new("c")));
+        assert!(poll(&mut c).is_pending());
+        assert_eq!(limiter.pending_count(), 1);
+
+        drop(a);
+        let c = match poll(&mut c) {
+            Poll::Ready(permit) => permit,
+            Poll::Pending => panic!("The slot must be handed over to the pending read!"),
+        };
+        assert_eq!(limiter.active_count(), 2);
+        assert_eq!(limiter.pending_count(), 0);
+
+        drop(b);
+        drop(c);
+        assert_eq!(limiter.active_count(), 0);
+    }
+
+    #[test]
+    fn io_limiter_order() {
+        for (order, expected) in [
+            (LoadOrder::Fifo, ["c", "a", "b"]),
+            (LoadOrder::Lifo, ["b", "a", "c"]),
+            (LoadOrder::Path, ["a", "b", "c"]),
+        ] {
+            let limiter = IoLimiter::new(Some(1), order);
+            let permit = block_on(limiter.acquire(Path::new("z")));
+
+            let mut pending = ["c", "a", "b"]
+                .into_iter()
+                .map(|name| (name, Box::pin(limiter.acquire(Path::new(name)))))
+                .collect::<Vec<_>>();
+            for (_, future) in pending.iter_mut() {
+                assert!(poll(future).is_pending());
+            }
+
+            let mut served = Vec::new();
+            drop(permit);
+            while !pending.is_empty() {
+                let (index, permit) = pending
+                    .iter_mut()
+                    .enumerate()
+                    .find_map(|(index, (_, future))| match poll(future) {
+                        Poll::Ready(permit) => Some((index, permit)),
+                        Poll::Pending => None,
+                    })
+                    .unwrap();
+                served.push(pending.remove(index).0);
+                // Only one read is served at a time, the next one gets the slot after this one.
+                drop(permit);
+            }
+
+            assert_eq!(served, expected, "{order:?}");
+        }
+    }
+
+    #[test]
+    fn io_limiter_cancelled_read_releases_slot() {
+        let limiter = IoLimiter::new(Some(1), LoadOrder::Fifo);
+
+        let a = block_on(limiter.acquire(Path::new("a")));
+        let mut b = Box::pin(limiter.acquire(Path::new("b")));
+        assert!(poll(&mut b).is_pending());
+        drop(b);
+
+        drop(a);
+        assert_eq!(limiter.active_count(), 0);
+        assert_eq!(limiter.pending_count(), 0);
+    }
+}
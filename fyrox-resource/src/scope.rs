@@ -0,0 +1,404 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Scoped resource requests. A [`ResourceScope`] tracks the resources requested through it and
+//! abandons their pending loads when it is dropped. It is useful for loading screens and streamed
+//! regions of a world - when the player leaves a region (or closes a loading screen) before its
+//! content is loaded, there is no point to finish the loading. Abandoned loads stop at their next
+//! suspension point (usually before or after a read), give back their IO slots (see
+//! [`crate::scheduler::IoLimiter`]) and free the memory occupied by partially loaded data.
+
+use crate::{
+    core::{futures::future::AbortHandle, parking_lot::Mutex, uuid::Uuid, TypeUuidProvider},
+    manager::ResourceManager,
+    Resource, TypedResourceData, UntypedResource,
+};
+use std::{marker::PhantomData, path::Path, sync::Arc};
+
+#[derive(Default)]
+struct InterestState {
+    pinned: bool,
+    scopes: usize,
+    cancelled: bool,
+    finished: bool,
+}
+
+/// Tracks who is interested in a single pending load. A load is abandoned only when every scope
+/// that requested it is dropped and it was never requested outside of a scope (such requests
+/// "pin" the load).
+pub(crate) struct LoadInterest {
+    abort_handle: AbortHandle,
+    state: Mutex<InterestState>,
+}
+
+impl LoadInterest {
+    pub(crate) fn new(abort_handle: AbortHandle, scoped: bool) -> Self {
+        Self {
+            abort_handle,
+            state: Mutex::new(InterestState {
+                pinned: !scoped,
+                scopes: usize::from(scoped),
+                ..Default::default()
+            }),
+        }
+    }
+
+    pub(crate) fn add_interest(&self, scoped: bool) {
+        let mut state = self.state.lock();
+        if scoped {
+            state.scopes += 1;
+        } else {
+            state.pinned = true;
+        }
+    }
+
+    /// Releases the interest of a scope and cancels the load if nobody else is interested in it.
+    /// Returns `true` if the load was cancelled by this call.
+    pub(crate) fn release_scope(&self) -> bool {
+        let mut state = self.state.lock();
+        state.scopes = state.scopes.saturating_sub(1);
+        if state.scopes == 0 && !state.pinned && !state.cancelled && !state.finished {
+            state.cancelled = true;
+            self.abort_handle.abort();
+            true
+        } else {
+            false
+        }
+    }
+
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.state.lock().cancelled
+    }
+
+    pub(crate) fn finish(&self) {
+        self.state.lock().finished = true;
+    }
+
+    pub(crate) fn is_finished(&self) -> bool {
+        self.state.lock().finished
+    }
+}
+
+pub(crate) struct ScopedLoad {
+    pub(crate) resource: UntypedResource,
+    pub(crate) interest: Arc<LoadInterest>,
+}
+
+/// A set of resource requests, whose pending loads are abandoned when the scope is dropped.
+///
+/// Resources requested through a scope are shared with the rest of the resource manager as usual:
+/// if a resource was already requested (or loaded) elsewhere, the scope gets the existing instance.
+/// A pending load is abandoned only if nobody else is interested in it - that is, every scope that
+/// requested it is dropped and it was never requested directly via [`ResourceManager`]. Abandoned
+/// resources are switched to [`crate::state::ResourceState::LoadError`] state (so anyone waiting
+/// for them is woken up) and removed from the resource manager, the next request of the same path
+/// starts a new load.
+///
+/// The scope keeps every requested resource alive while it exists, completely loaded resources are
+/// not affected by dropping the scope other than losing this reference.
+///
+/// ## Example
+///
+/// ```rust
+/// use fyrox_resource::{manager::ResourceManager, scope::ResourceScope};
+///
+/// fn enter_region(resource_manager: &ResourceManager) -> ResourceScope {
+///     let mut scope = resource_manager.new_scope();
+///     scope.request_untyped("data/region_1/terrain.rgs");
+///     scope.request_untyped("data/region_1/props.rgs");
+///     // Keep the scope while the player is in the region. Dropping it abandons the loads that
+///     // are not yet finished.
+///     scope
+/// }
+/// ```
+pub struct ResourceScope {
+    resource_manager: ResourceManager,
+    resources: Vec<UntypedResource>,
+    loads: Vec<ScopedLoad>,
+}
+
+impl ResourceScope {
+    /// Creates a new empty scope for the given resource manager.
+    pub fn new(resource_manager: ResourceManager) -> Self {
+        Self {
+            resource_manager,
+            resources: Default::default(),
+            loads: Default::default(),
+        }
+    }
+
+    /// Returns the resource manager of the scope.
+    pub fn resource_manager(&self) -> &ResourceManager {
+        &self.resource_manager
+    }
+
+    /// Requests a resource of the given type located at the given path. It works the same as
+    /// [`ResourceManager::request`], except that the load is bound to the scope.
+    ///
+    /// ## Panic
+    ///
+    /// This method will panic, if type UUID of `T` does not match the actual type UUID of the
+    /// resource. If this is undesirable, use [`Self::try_request`] instead.
+    pub fn request<T>(&mut self, path: impl AsRef<Path>) -> Resource<T>
+    where
+        T: TypedResourceData,
+    {
+        let type_uuid = <T as TypeUuidProvider>::type_uuid();
+        let untyped = self.request_with_type(path.as_ref(), Some(type_uuid));
+        assert_eq!(untyped.type_uuid(), type_uuid);
+        Resource {
+            untyped,
+            phantom: PhantomData::<T>,
+        }
+    }
+
+    /// The same as [`Self::request`], but returns [`None`] if type UUID of `T` does not match the
+    /// actual type UUID of the resource.
+    pub fn try_request<T>(&mut self, path: impl AsRef<Path>) -> Option<Resource<T>>
+    where
+        T: TypedResourceData,
+    {
+        let type_uuid = <T as TypeUuidProvider>::type_uuid();
+        let untyped = self.request_with_type(path.as_ref(), Some(type_uuid));
+        if untyped.type_uuid() == type_uuid {
+            Some(Resource {
+                untyped,
+                phantom: PhantomData::<T>,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Same as [`Self::request`], but returns untyped resource.
+    pub fn request_untyped(&mut self, path: impl AsRef<Path>) -> UntypedResource {
+        self.request_with_type(path.as_ref(), None)
+    }
+
+    fn request_with_type(&mut self, path: &Path, type_uuid: Option<Uuid>) -> UntypedResource {
+        let (resource, interest) = self
+            .resource_manager
+            .state()
+            .request_with_interest(path, type_uuid, true);
+        if let Some(interest) = interest {
+            self.loads.push(ScopedLoad {
+                resource: resource.clone(),
+                interest,
+            });
+        }
+        self.resources.push(resource.clone());
+        resource
+    }
+
+    /// Returns a slice of every resource requested through the scope.
+    pub fn resources(&self) -> &[UntypedResource] {
+        &self.resources
+    }
+
+    /// Returns `true` if every resource requested through the scope is either loaded or failed to
+    /// load.
+    pub fn is_all_loaded(&self) -> bool {
+        self.resources.iter().all(|resource| !resource.is_loading())
+    }
+
+    /// Returns percentage of loading progress of the resources requested through the scope. It
+    /// could be used to show the progress of a loading screen.
+    pub fn loading_progress(&self) -> usize {
+        if self.resources.is_empty() {
+            100
+        } else {
+            let loaded = self
+                .resources
+                .iter()
+                .filter(|resource| !resource.is_loading())
+                .count();
+            loaded * 100 / self.resources.len()
+        }
+    }
+}
+
+impl Drop for ResourceScope {
+    fn drop(&mut self) {
+        if self.loads.is_empty() {
+            return;
+        }
+
+        let mut state = self.resource_manager.state();
+        for load in self.loads.drain(..) {
+            state.release_scoped_load(load);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        core::{
+            futures::executor::block_on,
+            reflect::prelude::*,
+            uuid::{uuid, Uuid},
+            visitor::prelude::*,
+        },
+        io::ResourceIo,
+        loader::{BoxedLoaderFuture, LoaderPayload, ResourceLoader},
+        state::ResourceState,
+        ResourceData,
+    };
+    use std::{error::Error, path::PathBuf};
+
+    #[derive(Debug, Default, Reflect, Visit)]
+    struct Stub {}
+
+    impl TypeUuidProvider for Stub {
+        fn type_uuid() -> Uuid {
+            uuid!("4d0b3a61-1f53-4c1e-9a0c-6f3a2fbd9e57")
+        }
+    }
+
+    impl ResourceData for Stub {
+        fn type_uuid(&self) -> Uuid {
+            <Self as TypeUuidProvider>::type_uuid()
+        }
+
+        fn save(&mut self, _path: &Path) -> Result<(), Box<dyn Error>> {
+            Err("Saving is not supported!".to_string().into())
+        }
+
+        fn can_be_saved(&self) -> bool {
+            false
+        }
+    }
+
+    // Never finishes loading, so the loads stay pending until they are abandoned.
+    struct EndlessLoader;
+
+    impl ResourceLoader for EndlessLoader {
+        fn extensions(&self) -> &[&str] {
+            &["endless"]
+        }
+
+        fn data_type_uuid(&self) -> Uuid {
+            <Stub as TypeUuidProvider>::type_uuid()
+        }
+
+        fn load(&self, _path: PathBuf, _io: Arc<dyn ResourceIo>) -> BoxedLoaderFuture {
+            Box::pin(std::future::pending())
+        }
+    }
+
+    struct InstantLoader;
+
+    impl ResourceLoader for InstantLoader {
+        fn extensions(&self) -> &[&str] {
+            &["instant"]
+        }
+
+        fn data_type_uuid(&self) -> Uuid {
+            <Stub as TypeUuidProvider>::type_uuid()
+        }
+
+        fn load(&self, _path: PathBuf, _io: Arc<dyn ResourceIo>) -> BoxedLoaderFuture {
+            Box::pin(async move { Ok(LoaderPayload::new(Stub::default())) })
+        }
+    }
+
+    fn new_resource_manager() -> ResourceManager {
+        let resource_manager = ResourceManager::new(Arc::new(Default::default()));
+        {
+            let mut state = resource_manager.state();
+            state.loaders.set(EndlessLoader);
+            state.loaders.set(InstantLoader);
+        }
+        resource_manager
+    }
+
+    fn is_failed(resource: &UntypedResource) -> bool {
+        matches!(resource.0.lock().state, ResourceState::LoadError { .. })
+    }
+
+    fn is_loaded(resource: &UntypedResource) -> bool {
+        matches!(resource.0.lock().state, ResourceState::Ok(_))
+    }
+
+    #[test]
+    fn dropping_scope_abandons_pending_loads() {
+        let resource_manager = new_resource_manager();
+        let mut scope = ResourceScope::new(resource_manager.clone());
+
+        let resource = scope.request_untyped("a.endless");
+        assert!(resource.is_loading());
+        assert!(!scope.is_all_loaded());
+        assert_eq!(resource_manager.state().len(), 1);
+
+        drop(scope);
+        assert!(is_failed(&resource));
+        assert!(resource_manager.state().find("a.endless").is_none());
+
+        // The next request starts a new load.
+        let new_resource = resource_manager.request_untyped("a.endless");
+        assert!(new_resource.is_loading());
+        assert_ne!(new_resource, resource);
+    }
+
+    #[test]
+    fn direct_request_keeps_scoped_load_alive() {
+        let resource_manager = new_resource_manager();
+        let mut scope = ResourceScope::new(resource_manager.clone());
+
+        let resource = scope.request_untyped("a.endless");
+        let direct = resource_manager.request_untyped("a.endless");
+        assert_eq!(resource, direct);
+
+        drop(scope);
+        assert!(resource.is_loading());
+        assert!(resource_manager.state().find("a.endless").is_some());
+    }
+
+    #[test]
+    fn load_is_abandoned_when_last_scope_is_dropped() {
+        let resource_manager = new_resource_manager();
+        let mut first = ResourceScope::new(resource_manager.clone());
+        let mut second = ResourceScope::new(resource_manager.clone());
+
+        let resource = first.request_untyped("a.endless");
+        second.request_untyped("a.endless");
+
+        drop(first);
+        assert!(resource.is_loading());
+
+        drop(second);
+        assert!(is_failed(&resource));
+    }
+
+    #[test]
+    fn dropping_scope_keeps_loaded_resources() {
+        let resource_manager = new_resource_manager();
+        let mut scope = ResourceScope::new(resource_manager.clone());
+
+        let resource = scope.request_untyped("a.instant");
+        assert!(block_on(resource.clone()).is_ok());
+        assert!(scope.is_all_loaded());
+        assert_eq!(scope.loading_progress(), 100);
+
+        drop(scope);
+        assert!(is_loaded(&resource));
+        assert!(resource_manager.state().find("a.instant").is_some());
+    }
+}
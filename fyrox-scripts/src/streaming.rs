@@ -0,0 +1,425 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! World streaming script is used to load and unload parts of large (open) worlds around an observer
+//! (usually the player or the main camera). See [`WorldStreamer`] docs for more info.
+
+use fyrox::{
+    asset::manager::ResourceManager,
+    core::{
+        algebra::{Vector2, Vector3},
+        impl_component_provider,
+        log::Log,
+        pool::Handle,
+        reflect::prelude::*,
+        uuid_provider,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    graph::{BaseSceneGraph, SceneGraph},
+    resource::model::{Model, ModelResource, ModelResourceExtension},
+    scene::{camera::Camera, node::Node, Scene},
+    script::{ScriptContext, ScriptDeinitContext, ScriptTrait},
+};
+use std::path::PathBuf;
+
+/// Runtime state of a streaming cell. It is transient, it is neither saved nor cloned, because it
+/// references an instance on a particular scene.
+#[derive(Default, Debug)]
+enum CellState {
+    #[default]
+    Unloaded,
+    Loading(ModelResource),
+    Loaded {
+        // Keep the resource alive while its instance is on the scene, so it won't be collected by
+        // the resource manager.
+        _resource: ModelResource,
+        root: Handle<Node>,
+    },
+    // The sub-scene failed to load. The cell stays in this state until the observer leaves it,
+    // so the loading is attempted again only when the observer comes back.
+    Failed,
+}
+
+/// A single cell of the streaming grid. Every cell references a sub-scene, that will be instantiated
+/// when an observer is close enough to the cell.
+#[derive(Visit, Reflect, Default, Debug)]
+pub struct StreamingCell {
+    #[reflect(description = "Position of the cell on the grid (X and Z axes of the world).")]
+    pub coords: Vector2<i32>,
+
+    #[reflect(description = "A path to a sub-scene (or a prefab) of the cell.")]
+    pub scene: PathBuf,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    state: CellState,
+}
+
+impl Clone for StreamingCell {
+    fn clone(&self) -> Self {
+        // The copy must load its own instance of the sub-scene, the instance of this cell belongs
+        // to (possibly) another scene.
+        Self::new(self.coords, self.scene.clone())
+    }
+}
+
+impl StreamingCell {
+    /// Creates a new cell at the given grid coordinates, that will use the given sub-scene.
+    pub fn new(coords: Vector2<i32>, scene: impl Into<PathBuf>) -> Self {
+        Self {
+            coords,
+            scene: scene.into(),
+            state: Default::default(),
+        }
+    }
+
+    /// Returns `true` if the sub-scene of the cell is currently loading.
+    pub fn is_loading(&self) -> bool {
+        matches!(self.state, CellState::Loading(_))
+    }
+
+    /// Returns `true` if the sub-scene of the cell has failed to load. The loading will be attempted
+    /// again when an observer leaves the cell and then comes back.
+    pub fn is_failed(&self) -> bool {
+        matches!(self.state, CellState::Failed)
+    }
+
+    /// Returns a handle of the instance of the sub-scene of the cell, or [`Handle::NONE`] if the cell
+    /// is not loaded.
+    pub fn instance(&self) -> Handle<Node> {
+        match self.state {
+            CellState::Loaded { root, .. } => root,
+            _ => Handle::NONE,
+        }
+    }
+}
+
+/// World streamer is a script, that splits a world into a grid of cells on XZ plane, where each cell
+/// references a sub-scene. The script should be assigned to a node of a "master" scene, which is
+/// usually a lightweight scene that contains only persistent content (player, sky, global lights, etc.).
+///
+/// When an observer comes closer than [`Self::load_distance`] to a cell, the sub-scene of the cell is
+/// requested from the resource manager. Loading is performed in the background, and when it is done,
+/// the sub-scene is instantiated and attached to the node with the streamer, at the origin of the
+/// cell. When the observer goes farther than [`Self::unload_distance`] from the cell, its instance
+/// is removed from the scene and the reference to the sub-scene resource is dropped, so the resource
+/// manager could release it. Unload distance should be larger than the load distance, otherwise the
+/// cells at the boundary will be loaded and unloaded over and over again.
+///
+/// If the observer is not set, the first enabled camera of the scene is used.
+#[derive(Visit, Reflect, Debug, Clone)]
+pub struct WorldStreamer {
+    #[reflect(
+        description = "A node, around which the cells will be loaded. If not set, the first \
+    enabled camera of the scene will be used."
+    )]
+    #[visit(optional)]
+    pub observer: InheritableVariable<Handle<Node>>,
+
+    #[reflect(
+        description = "Size of a single cell of the grid (in meters).",
+        min_value = 0.001
+    )]
+    #[visit(optional)]
+    pub cell_size: InheritableVariable<f32>,
+
+    #[reflect(
+        description = "Cells whose centers are closer than this distance to the observer will be loaded.",
+        min_value = 0.0
+    )]
+    #[visit(optional)]
+    pub load_distance: InheritableVariable<f32>,
+
+    #[reflect(
+        description = "Cells whose centers are farther than this distance from the observer will be unloaded.",
+        min_value = 0.0
+    )]
+    #[visit(optional)]
+    pub unload_distance: InheritableVariable<f32>,
+
+    #[reflect(description = "A grid of cells of the world.")]
+    #[visit(optional)]
+    pub cells: InheritableVariable<Vec<StreamingCell>>,
+}
+
+impl Default for WorldStreamer {
+    fn default() -> Self {
+        Self {
+            observer: Default::default(),
+            cell_size: 100.0.into(),
+            load_distance: 150.0.into(),
+            unload_distance: 200.0.into(),
+            cells: Default::default(),
+        }
+    }
+}
+
+impl_component_provider!(WorldStreamer);
+uuid_provider!(WorldStreamer = "1bd9e5f4-52d6-4ac6-9a41-a2ff5a2a5b5e");
+
+impl WorldStreamer {
+    /// Returns local position of the origin of the cell with the given coordinates.
+    pub fn cell_origin(&self, coords: Vector2<i32>) -> Vector3<f32> {
+        Vector3::new(
+            coords.x as f32 * *self.cell_size,
+            0.0,
+            coords.y as f32 * *self.cell_size,
+        )
+    }
+
+    /// Returns local position of the center of the cell with the given coordinates.
+    pub fn cell_center(&self, coords: Vector2<i32>) -> Vector3<f32> {
+        let half_size = *self.cell_size * 0.5;
+        self.cell_origin(coords) + Vector3::new(half_size, 0.0, half_size)
+    }
+
+    /// Returns coordinates of a cell, that contains the given local position.
+    pub fn cell_at(&self, position: Vector3<f32>) -> Vector2<i32> {
+        Vector2::new(
+            (position.x / *self.cell_size).floor() as i32,
+            (position.z / *self.cell_size).floor() as i32,
+        )
+    }
+
+    /// Returns `true` if the cell with the given coordinates has its sub-scene instantiated.
+    pub fn is_cell_loaded(&self, coords: Vector2<i32>) -> bool {
+        self.cells
+            .iter()
+            .any(|cell| cell.coords == coords && cell.instance().is_some())
+    }
+
+    fn observer_position(&self, scene: &Scene, this: Handle<Node>) -> Option<Vector3<f32>> {
+        let observer = if scene.graph.is_valid_handle(*self.observer) {
+            *self.observer
+        } else {
+            scene
+                .graph
+                .pair_iter()
+                .find(|(_, node)| node.is_globally_enabled() && node.cast::<Camera>().is_some())
+                .map(|(handle, _)| handle)?
+        };
+
+        // Distances are measured in the local space of the streamer, so the whole world could be
+        // moved by moving the streamer node.
+        let inv_transform = scene.graph[this]
+            .global_transform()
+            .try_inverse()
+            .unwrap_or_default();
+        Some(
+            inv_transform
+                .transform_point(&scene.graph[observer].global_position().into())
+                .coords,
+        )
+    }
+
+    fn unload_cell(cell: &mut StreamingCell, scene: &mut Scene) {
+        match std::mem::take(&mut cell.state) {
+            CellState::Loaded { root, .. } => {
+                if scene.graph.is_valid_handle(root) {
+                    scene.graph.remove_node(root);
+                }
+            }
+            // Dropping a pending resource is enough, the resource manager will destroy it when
+            // it is loaded and no one else is using it.
+            CellState::Loading(_) | CellState::Unloaded | CellState::Failed => {}
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn update_cell(
+        cell: &mut StreamingCell,
+        origin: Vector3<f32>,
+        distance: f32,
+        load_distance: f32,
+        unload_distance: f32,
+        scene: &mut Scene,
+        this: Handle<Node>,
+        rm: &ResourceManager,
+    ) {
+        match cell.state {
+            CellState::Unloaded => {
+                if distance <= load_distance && !cell.scene.as_os_str().is_empty() {
+                    cell.state = CellState::Loading(rm.request::<Model>(&cell.scene));
+                }
+            }
+            CellState::Loading(ref resource) => {
+                if distance > unload_distance {
+                    Self::unload_cell(cell, scene);
+                } else if resource.is_ok() {
+                    let resource = resource.clone();
+                    let root = resource
+                        .begin_instantiation(scene)
+                        .with_position(origin)
+                        .finish();
+                    scene.graph.link_nodes(root, this);
+                    cell.state = CellState::Loaded {
+                        _resource: resource,
+                        root,
+                    };
+                } else if resource.is_failed_to_load() {
+                    Log::err(format!(
+                        "Unable to load {} sub-scene for {:?} streaming cell!",
+                        cell.scene.display(),
+                        cell.coords
+                    ));
+                    cell.state = CellState::Failed;
+                }
+            }
+            CellState::Loaded { .. } | CellState::Failed => {
+                if distance > unload_distance {
+                    Self::unload_cell(cell, scene);
+                }
+            }
+        }
+    }
+
+    fn update_cells(&mut self, scene: &mut Scene, this: Handle<Node>, rm: &ResourceManager) {
+        let Some(observer_position) = self.observer_position(scene, this) else {
+            return;
+        };
+
+        let load_distance = *self.load_distance;
+        let unload_distance = self.unload_distance.max(load_distance);
+
+        let half_size = *self.cell_size * 0.5;
+        let origins = self
+            .cells
+            .iter()
+            .map(|cell| self.cell_origin(cell.coords))
+            .collect::<Vec<_>>();
+
+        for (cell, origin) in self.cells.get_value_mut_silent().iter_mut().zip(origins) {
+            let center = origin + Vector3::new(half_size, 0.0, half_size);
+            let distance = Vector2::new(
+                center.x - observer_position.x,
+                center.z - observer_position.z,
+            )
+            .norm();
+
+            Self::update_cell(
+                cell,
+                origin,
+                distance,
+                load_distance,
+                unload_distance,
+                scene,
+                this,
+                rm,
+            );
+        }
+    }
+}
+
+impl ScriptTrait for WorldStreamer {
+    fn on_update(&mut self, context: &mut ScriptContext) {
+        self.update_cells(context.scene, context.handle, context.resource_manager);
+    }
+
+    fn on_deinit(&mut self, context: &mut ScriptDeinitContext) {
+        for cell in self.cells.get_value_mut_silent().iter_mut() {
+            Self::unload_cell(cell, context.scene);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::streaming::{CellState, StreamingCell, WorldStreamer};
+    use fyrox::{
+        asset::{manager::ResourceManager, state::LoadError, untyped::ResourceKind},
+        core::{algebra::Vector2, algebra::Vector3, pool::Handle},
+        engine::SerializationContext,
+        resource::model::{loader::ModelLoader, ModelResource},
+        scene::Scene,
+    };
+    use std::sync::Arc;
+
+    fn failed_resource() -> ModelResource {
+        ModelResource::new_load_error(ResourceKind::Embedded, LoadError::new("error"))
+    }
+
+    #[test]
+    fn test_clone_does_not_copy_runtime_state() {
+        let mut cell = StreamingCell::new(Vector2::new(1, 2), "cell.rgs");
+        cell.state = CellState::Loaded {
+            _resource: failed_resource(),
+            root: Handle::new(1, 1),
+        };
+
+        let copy = cell.clone();
+        assert_eq!(copy.coords, cell.coords);
+        assert_eq!(copy.scene, cell.scene);
+        assert!(matches!(copy.state, CellState::Unloaded));
+        assert_eq!(copy.instance(), Handle::NONE);
+
+        let streamer = WorldStreamer {
+            cells: vec![cell].into(),
+            ..Default::default()
+        };
+        let copy = streamer.clone();
+        assert!(!copy.is_cell_loaded(Vector2::new(1, 2)));
+    }
+
+    #[test]
+    fn test_failed_cell_is_retried_after_leaving() {
+        let rm = ResourceManager::new(Arc::new(Default::default()));
+        rm.state().loaders.set(ModelLoader {
+            resource_manager: rm.clone(),
+            serialization_context: Arc::new(SerializationContext::new()),
+            default_import_options: Default::default(),
+        });
+        let mut scene = Scene::new();
+        let this = scene.graph.get_root();
+
+        let mut cell = StreamingCell::new(Vector2::new(0, 0), "does_not_exist.rgs");
+        cell.state = CellState::Loading(failed_resource());
+
+        let mut update = |cell: &mut StreamingCell, distance: f32| {
+            WorldStreamer::update_cell(
+                cell,
+                Vector3::default(),
+                distance,
+                10.0,
+                20.0,
+                &mut scene,
+                this,
+                &rm,
+            )
+        };
+
+        update(&mut cell, 0.0);
+        assert!(cell.is_failed());
+        assert_eq!(cell.instance(), Handle::NONE);
+
+        // No new attempts while the observer is inside the cell.
+        update(&mut cell, 0.0);
+        assert!(cell.is_failed());
+        update(&mut cell, 15.0);
+        assert!(cell.is_failed());
+
+        // The observer leaves the cell.
+        update(&mut cell, 25.0);
+        assert!(matches!(cell.state, CellState::Unloaded));
+
+        // The observer comes back, the sub-scene is requested again.
+        update(&mut cell, 5.0);
+        assert!(cell.is_loading());
+    }
+}
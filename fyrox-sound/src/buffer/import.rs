@@ -0,0 +1,454 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Import-time processing of sound buffers. It is used by [`super::loader::SoundBufferLoader`] to
+//! bring sounds to a consistent loudness and format (see [`SoundBufferImportOptions`]). Processing
+//! is done once - processed sounds are stored in a cache as WAV files and every subsequent load
+//! just reads the cached file.
+//!
+//! ## Loudness
+//!
+//! Loudness is measured as integrated loudness according to ITU-R BS.1770 (K-weighted, gated
+//! loudness in LUFS). It matches the way loudness is measured by most audio tools, so target values
+//! from common guidelines (for example, -16 LUFS for dialogs and music, or -23 LUFS for broadcast)
+//! could be used as is.
+
+use crate::buffer::loader::{ChannelConversion, SoundBufferImportOptions};
+use fxhash::FxHasher64;
+use std::{
+    f64::consts::PI,
+    hash::Hasher,
+    io::Cursor,
+    path::{Path, PathBuf},
+};
+
+/// Default folder of processed sounds cache.
+pub const DEFAULT_IMPORT_CACHE_DIR: &str = ".cache/sound";
+
+/// Decoded sound in interleaved format.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ImportedSound {
+    /// Interleaved samples (mono sounds: L..., stereo sounds: LR...).
+    pub samples: Vec<f32>,
+    /// Total amount of channels.
+    pub channel_count: usize,
+    /// Sample rate of the sound.
+    pub sample_rate: usize,
+}
+
+impl ImportedSound {
+    /// Returns the amount of samples per channel.
+    pub fn channel_duration_in_samples(&self) -> usize {
+        self.samples.len() / self.channel_count.max(1)
+    }
+
+    /// Encodes the sound as a WAV file with 32-bit float samples.
+    pub fn to_wav(&self) -> Result<Vec<u8>, hound::Error> {
+        let spec = hound::WavSpec {
+            channels: self.channel_count as u16,
+            sample_rate: self.sample_rate as u32,
+            bits_per_sample: 32,
+            sample_format: hound::SampleFormat::Float,
+        };
+        let mut cursor = Cursor::new(Vec::new());
+        let mut writer = hound::WavWriter::new(&mut cursor, spec)?;
+        for &sample in self.samples.iter() {
+            writer.write_sample(sample)?;
+        }
+        writer.finalize()?;
+        Ok(cursor.into_inner())
+    }
+}
+
+/// Biquad filter in the direct form I. Coefficients are normalized (`a0 == 1`).
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    x: [f64; 2],
+    y: [f64; 2],
+}
+
+impl Biquad {
+    fn new(b: [f64; 3], a: [f64; 2]) -> Self {
+        Self {
+            b,
+            a,
+            x: [0.0; 2],
+            y: [0.0; 2],
+        }
+    }
+
+    fn feed(&mut self, input: f64) -> f64 {
+        let output = self.b[0] * input + self.b[1] * self.x[0] + self.b[2] * self.x[1]
+            - self.a[0] * self.y[0]
+            - self.a[1] * self.y[1];
+        self.x = [input, self.x[0]];
+        self.y = [output, self.y[0]];
+        output
+    }
+}
+
+/// Creates K-weighting filter stages (a high shelf and a high pass) for the given sample rate as
+/// defined by ITU-R BS.1770. The coefficients are derived for an arbitrary sample rate, for 48 kHz
+/// they match the ones from the specification.
+fn k_weighting(sample_rate: usize) -> [Biquad; 2] {
+    let sample_rate = sample_rate as f64;
+
+    let shelf = {
+        let f0 = 1681.974450955533;
+        let gain = 3.999843853973347;
+        let q = 0.7071752369554196;
+        let k = (PI * f0 / sample_rate).tan();
+        let vh = 10.0f64.powf(gain / 20.0);
+        let vb = vh.powf(0.4996667741545416);
+        let a0 = 1.0 + k / q + k * k;
+        Biquad::new(
+            [
+                (vh + vb * k / q + k * k) / a0,
+                2.0 * (k * k - vh) / a0,
+                (vh - vb * k / q + k * k) / a0,
+            ],
+            [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        )
+    };
+
+    let high_pass = {
+        let f0 = 38.13547087602444;
+        let q = 0.5003270373238773;
+        let k = (PI * f0 / sample_rate).tan();
+        let a0 = 1.0 + k / q + k * k;
+        Biquad::new(
+            [1.0, -2.0, 1.0],
+            [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        )
+    };
+
+    [shelf, high_pass]
+}
+
+fn power_to_lufs(power: f64) -> f64 {
+    -0.691 + 10.0 * power.log10()
+}
+
+/// Measures integrated loudness (in LUFS) of the given interleaved samples according to ITU-R
+/// BS.1770. Returns [`None`] if the sound is silent (or too quiet to be measured).
+pub fn integrated_loudness(
+    samples: &[f32],
+    channel_count: usize,
+    sample_rate: usize,
+) -> Option<f32> {
+    if channel_count == 0 || sample_rate == 0 {
+        return None;
+    }
+
+    // Squared K-weighted samples, summed over the channels (every channel has the weight of 1 for
+    // mono and stereo sounds).
+    let mut filters = (0..channel_count)
+        .map(|_| k_weighting(sample_rate))
+        .collect::<Vec<_>>();
+    let weighted = samples
+        .chunks_exact(channel_count)
+        .map(|frame| {
+            frame
+                .iter()
+                .zip(filters.iter_mut())
+                .map(|(&sample, [shelf, high_pass])| {
+                    let filtered = high_pass.feed(shelf.feed(sample as f64));
+                    filtered * filtered
+                })
+                .sum::<f64>()
+        })
+        .collect::<Vec<_>>();
+    if weighted.is_empty() {
+        return None;
+    }
+
+    // 400 ms blocks with 75% overlap. Sounds shorter than a single block are measured as a whole.
+    let block_len = (sample_rate * 4 / 10).max(1);
+    let step = (block_len / 4).max(1);
+    let blocks = if weighted.len() <= block_len {
+        vec![weighted.iter().sum::<f64>() / weighted.len() as f64]
+    } else {
+        (0..=(weighted.len() - block_len) / step)
+            .map(|i| {
+                let block = &weighted[i * step..i * step + block_len];
+                block.iter().sum::<f64>() / block_len as f64
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let gated_mean = |threshold: f64| {
+        let (sum, count) = blocks
+            .iter()
+            .filter(|&&power| power > 0.0 && power_to_lufs(power) > threshold)
+            .fold((0.0, 0usize), |(sum, count), power| {
+                (sum + power, count + 1)
+            });
+        (count > 0).then(|| sum / count as f64)
+    };
+
+    // Absolute gate at -70 LUFS, then relative gate 10 LU below the absolute-gated loudness.
+    let absolute = gated_mean(-70.0)?;
+    let relative = gated_mean(power_to_lufs(absolute) - 10.0)?;
+    Some(power_to_lufs(relative) as f32)
+}
+
+/// Converts the given interleaved samples to the given amount of channels. Only mono and stereo
+/// layouts are supported, multichannel sounds are averaged when downmixed to mono.
+pub fn convert_channels(
+    samples: &[f32],
+    channel_count: usize,
+    new_channel_count: usize,
+) -> Vec<f32> {
+    if channel_count == new_channel_count || channel_count == 0 {
+        return samples.to_vec();
+    }
+
+    match new_channel_count {
+        1 => samples
+            .chunks_exact(channel_count)
+            .map(|frame| frame.iter().sum::<f32>() / channel_count as f32)
+            .collect(),
+        _ => samples
+            .chunks_exact(channel_count)
+            .flat_map(|frame| {
+                let mono = frame.iter().sum::<f32>() / channel_count as f32;
+                std::iter::repeat(mono).take(new_channel_count)
+            })
+            .collect(),
+    }
+}
+
+/// Resamples the given interleaved samples to the new sample rate using linear interpolation.
+pub fn resample(
+    samples: &[f32],
+    channel_count: usize,
+    sample_rate: usize,
+    new_sample_rate: usize,
+) -> Vec<f32> {
+    if sample_rate == new_sample_rate || sample_rate == 0 || new_sample_rate == 0 {
+        return samples.to_vec();
+    }
+
+    let channel_count = channel_count.max(1);
+    let frame_count = samples.len() / channel_count;
+    if frame_count == 0 {
+        return Vec::new();
+    }
+
+    let new_frame_count =
+        ((frame_count as u64 * new_sample_rate as u64).div_ceil(sample_rate as u64)) as usize;
+    let ratio = sample_rate as f64 / new_sample_rate as f64;
+    let mut output = Vec::with_capacity(new_frame_count * channel_count);
+    for i in 0..new_frame_count {
+        let position = i as f64 * ratio;
+        let left = (position as usize).min(frame_count - 1);
+        let right = (left + 1).min(frame_count - 1);
+        let t = (position - left as f64) as f32;
+        for channel in 0..channel_count {
+            let a = samples[left * channel_count + channel];
+            let b = samples[right * channel_count + channel];
+            output.push(a + (b - a) * t);
+        }
+    }
+    output
+}
+
+/// Applies the import options to the sound. The order of processing is: channel conversion,
+/// resampling and loudness normalization. Normalization never amplifies the sound above full
+/// scale - if the gain needed to reach the target loudness would clip the peaks, it is reduced
+/// to the maximum gain without clipping.
+pub fn process(mut sound: ImportedSound, options: &SoundBufferImportOptions) -> ImportedSound {
+    let new_channel_count = match options.channels {
+        ChannelConversion::Keep => sound.channel_count,
+        ChannelConversion::Mono => 1,
+        ChannelConversion::Stereo => 2,
+    };
+    if new_channel_count != sound.channel_count {
+        sound.samples = convert_channels(&sound.samples, sound.channel_count, new_channel_count);
+        sound.channel_count = new_channel_count;
+    }
+
+    if let Some(sample_rate) = options.sample_rate {
+        let sample_rate = sample_rate as usize;
+        if sample_rate != sound.sample_rate {
+            sound.samples = resample(
+                &sound.samples,
+                sound.channel_count,
+                sound.sample_rate,
+                sample_rate,
+            );
+            sound.sample_rate = sample_rate;
+        }
+    }
+
+    if let Some(target_loudness) = options.target_loudness {
+        if let Some(loudness) =
+            integrated_loudness(&sound.samples, sound.channel_count, sound.sample_rate)
+        {
+            let peak = sound
+                .samples
+                .iter()
+                .fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+            let mut gain = 10.0f32.powf((target_loudness - loudness) / 20.0);
+            if peak > 0.0 {
+                gain = gain.min(1.0 / peak);
+            }
+            for sample in sound.samples.iter_mut() {
+                *sample *= gain;
+            }
+        }
+    }
+
+    sound
+}
+
+/// Returns a path of the processed sound in the cache folder. The name of the file is a hash of
+/// the source data and the import options, so changing either of them leads to processing the
+/// sound again. The hash does not depend on the platform or the compiler version, so the cache
+/// could be shared between machines. Stale files are not removed automatically, the cache folder
+/// could be safely deleted at any time.
+pub fn cache_path(
+    cache_dir: &Path,
+    source_path: &Path,
+    source_data: &[u8],
+    options: &SoundBufferImportOptions,
+) -> PathBuf {
+    let mut hasher = FxHasher64::default();
+    hasher.write_u64(source_data.len() as u64);
+    hasher.write(source_data);
+    hasher.write_u64(
+        options
+            .target_loudness
+            .map_or(u64::MAX, |loudness| loudness.to_bits() as u64),
+    );
+    hasher.write_u8(match options.channels {
+        ChannelConversion::Keep => 0,
+        ChannelConversion::Mono => 1,
+        ChannelConversion::Stereo => 2,
+    });
+    hasher.write_u64(options.sample_rate.map_or(u64::MAX, u64::from));
+
+    let stem = source_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    cache_dir.join(format!("{}_{:016x}.wav", stem, hasher.finish()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sine(amplitude: f32, frequency: f32, sample_rate: usize, seconds: f32) -> Vec<f32> {
+        (0..(sample_rate as f32 * seconds) as usize)
+            .map(|i| {
+                amplitude
+                    * (2.0 * std::f32::consts::PI * frequency * i as f32 / sample_rate as f32).sin()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_integrated_loudness_of_sine() {
+        // Full scale 1 kHz sine in a single channel is -3.01 LUFS by the definition.
+        let samples = sine(1.0, 1000.0, 48000, 2.0);
+        let loudness = integrated_loudness(&samples, 1, 48000).unwrap();
+        assert!((loudness + 3.01).abs() < 0.1, "{loudness}");
+
+        // -20 dB gives -20 LU less.
+        let samples = sine(0.1, 1000.0, 44100, 2.0);
+        let loudness = integrated_loudness(&samples, 1, 44100).unwrap();
+        assert!((loudness + 23.01).abs() < 0.1, "{loudness}");
+
+        assert_eq!(integrated_loudness(&vec![0.0; 48000], 1, 48000), None);
+    }
+
+    #[test]
+    fn test_cache_path_is_stable() {
+        let options = SoundBufferImportOptions {
+            target_loudness: Some(-16.0),
+            channels: ChannelConversion::Mono,
+            sample_rate: Some(44100),
+            ..Default::default()
+        };
+        let path = cache_path(
+            Path::new("cache"),
+            Path::new("data/sound.ogg"),
+            b"sound data",
+            &options,
+        );
+        // The name must never change, otherwise every cached sound will be processed again.
+        assert_eq!(path, Path::new("cache").join("sound_14be5cf696e4649b.wav"));
+
+        let other = cache_path(
+            Path::new("cache"),
+            Path::new("data/sound.ogg"),
+            b"sound data",
+            &Default::default(),
+        );
+        assert_ne!(path, other);
+    }
+
+    #[test]
+    fn test_convert_channels() {
+        assert_eq!(convert_channels(&[1.0, 0.0, 0.5, 0.5], 2, 1), [0.5, 0.5]);
+        assert_eq!(convert_channels(&[0.5, 0.25], 1, 2), [0.5, 0.5, 0.25, 0.25]);
+    }
+
+    #[test]
+    fn test_resample() {
+        let resampled = resample(&[0.0, 1.0, 0.0, 1.0], 2, 1, 2);
+        assert_eq!(resampled, [0.0, 1.0, 0.0, 1.0, 0.0, 1.0, 0.0, 1.0]);
+
+        let resampled = resample(&[0.0, 1.0, 2.0, 3.0], 1, 2, 4);
+        assert_eq!(resampled, [0.0, 0.5, 1.0, 1.5, 2.0, 2.5, 3.0, 3.0]);
+    }
+
+    #[test]
+    fn test_process_normalizes_loudness() {
+        let sound = ImportedSound {
+            samples: sine(0.05, 1000.0, 48000, 2.0),
+            channel_count: 1,
+            sample_rate: 48000,
+        };
+        let options = SoundBufferImportOptions {
+            target_loudness: Some(-16.0),
+            ..Default::default()
+        };
+        let processed = process(sound, &options);
+        let loudness =
+            integrated_loudness(&processed.samples, processed.channel_count, 48000).unwrap();
+        assert!((loudness + 16.0).abs() < 0.1, "{loudness}");
+
+        // Peaks are never pushed above full scale.
+        let sound = ImportedSound {
+            samples: sine(0.5, 1000.0, 48000, 2.0),
+            channel_count: 1,
+            sample_rate: 48000,
+        };
+        let options = SoundBufferImportOptions {
+            target_loudness: Some(0.0),
+            ..Default::default()
+        };
+        let processed = process(sound, &options);
+        let peak = processed.samples.iter().fold(0.0f32, |p, s| p.max(s.abs()));
+        assert!(peak <= 1.0 + 1.0e-6);
+    }
+}
@@ -20,32 +20,102 @@
 
 //! Sound buffer loader.
 
-use crate::buffer::{DataSource, SoundBuffer};
-use fyrox_core::{reflect::prelude::*, uuid::Uuid, TypeUuidProvider};
+use crate::buffer::{
+    generic::GenericBuffer,
+    import::{self, ImportedSound},
+    DataSource, SoundBuffer,
+};
+use fyrox_core::{log::Log, reflect::prelude::*, uuid::Uuid, TypeUuidProvider};
 use fyrox_resource::{
     io::ResourceIo,
-    loader::{BoxedImportOptionsLoaderFuture, BoxedLoaderFuture, LoaderPayload, ResourceLoader},
+    loader::{
+        BoxedImportOptionsLoaderFuture, BoxedLoaderFuture, ContentSignature, LoaderPayload,
+        ResourceLoader,
+    },
     options::{
         try_get_import_settings, try_get_import_settings_opaque, BaseImportOptions, ImportOptions,
     },
     state::LoadError,
 };
 use serde::{Deserialize, Serialize};
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Defines how channels of a sound are converted on import.
+#[derive(
+    Copy,
+    Clone,
+    Default,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    Deserialize,
+    Serialize,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+)]
+pub enum ChannelConversion {
+    /// Channels are kept as is.
+    #[default]
+    Keep,
+    /// Channels are mixed down to a single channel. Mono sounds are cheaper to store and they're
+    /// required for sounds with spatial (3D) positioning anyway.
+    Mono,
+    /// Mono sounds are duplicated to both channels, stereo sounds are kept as is.
+    Stereo,
+}
 
 /// Defines sound buffer resource import options.
 #[derive(Clone, Deserialize, Serialize, Default, Debug, Reflect)]
 pub struct SoundBufferImportOptions {
-    /// Whether the buffer is streaming or not.
+    /// Whether the buffer is streaming or not. Streaming buffers are decoded in small portions
+    /// during playback, it is suitable for long sounds (such as music).
     pub stream: bool,
+    /// Target integrated loudness of the sound in LUFS (for example, -16.0). The sound is
+    /// amplified or attenuated on import to match the target loudness. `None` keeps the
+    /// loudness as is. See [`import`] module docs for more info.
+    #[serde(default)]
+    pub target_loudness: Option<f32>,
+    /// Channel conversion performed on import.
+    #[serde(default)]
+    pub channels: ChannelConversion,
+    /// Sample rate the sound is resampled to on import. `None` keeps the sample rate as is. It
+    /// is better to match the sample rate of the sound context (see
+    /// [`crate::context::SAMPLE_RATE`]), so the sound won't be resampled during playback.
+    #[serde(default)]
+    pub sample_rate: Option<u32>,
 }
 
 impl ImportOptions for SoundBufferImportOptions {}
 
+impl SoundBufferImportOptions {
+    /// Returns `true` if the options require the sound to be processed on import.
+    pub fn needs_processing(&self) -> bool {
+        self.target_loudness.is_some()
+            || self.channels != ChannelConversion::Keep
+            || self.sample_rate.is_some()
+    }
+}
+
+/// Signatures of WAV and Ogg files.
+const SIGNATURES: [ContentSignature; 2] = [
+    ContentSignature::with_offset(8, b"WAVE"),
+    ContentSignature::new(b"OggS"),
+];
+
 /// Default implementation for sound buffer loading.
 pub struct SoundBufferLoader {
     /// Default import options for sound buffer resources.
     pub default_import_options: SoundBufferImportOptions,
+    /// A folder where processed sounds are cached (see [`import`] module docs). `None` means that
+    /// sounds that require processing are processed on every load.
+    pub cache_dir: Option<PathBuf>,
 }
 
 impl ResourceLoader for SoundBufferLoader {
@@ -53,12 +123,17 @@ impl ResourceLoader for SoundBufferLoader {
         &["wav", "ogg"]
     }
 
+    fn content_signatures(&self) -> &[ContentSignature] {
+        &SIGNATURES
+    }
+
     fn data_type_uuid(&self) -> Uuid {
         SoundBuffer::type_uuid()
     }
 
     fn load(&self, path: PathBuf, io: Arc<dyn ResourceIo>) -> BoxedLoaderFuture {
         let default_import_options = self.default_import_options.clone();
+        let cache_dir = self.cache_dir.clone();
 
         Box::pin(async move {
             let io = io.as_ref();
@@ -67,9 +142,13 @@ impl ResourceLoader for SoundBufferLoader {
                 .await
                 .unwrap_or(default_import_options);
 
-            let source = DataSource::from_file(&path, io)
-                .await
-                .map_err(LoadError::new)?;
+            let source = if import_options.needs_processing() {
+                import_processed(&path, io, &import_options, cache_dir).await?
+            } else {
+                DataSource::from_file(&path, io)
+                    .await
+                    .map_err(LoadError::new)?
+            };
 
             let result = if import_options.stream {
                 SoundBuffer::raw_streaming(source)
@@ -98,3 +177,51 @@ impl ResourceLoader for SoundBufferLoader {
         Some(Box::<SoundBufferImportOptions>::default())
     }
 }
+
+/// Returns a data source with the processed sound. The processed sound is taken from the cache, if
+/// it is there, otherwise the source file is decoded, processed and written to the cache.
+async fn import_processed(
+    path: &Path,
+    io: &dyn ResourceIo,
+    import_options: &SoundBufferImportOptions,
+    cache_dir: Option<PathBuf>,
+) -> Result<DataSource, LoadError> {
+    let source_data = io.load_file(path).await.map_err(LoadError::new)?;
+
+    let cache_path = cache_dir
+        .map(|cache_dir| import::cache_path(&cache_dir, path, &source_data, import_options));
+    if let Some(cache_path) = cache_path.as_ref() {
+        if io.exists(cache_path).await {
+            return DataSource::from_file(cache_path, io)
+                .await
+                .map_err(LoadError::new);
+        }
+    }
+
+    let buffer = GenericBuffer::new(DataSource::from_memory(source_data))
+        .map_err(|_| LoadError::new("Invalid data source."))?;
+    let sound = import::process(
+        ImportedSound {
+            samples: buffer.samples.0,
+            channel_count: buffer.channel_count,
+            sample_rate: buffer.sample_rate,
+        },
+        import_options,
+    );
+    let wav = sound
+        .to_wav()
+        .map_err(|err| LoadError::new(err.to_string()))?;
+
+    if let Some(cache_path) = cache_path {
+        if let Err(err) = io.write_file(&cache_path, wav.clone()).await {
+            Log::warn(format!(
+                "Unable to cache processed sound {} at {}. Reason: {:?}",
+                path.display(),
+                cache_path.display(),
+                err
+            ));
+        }
+    }
+
+    Ok(DataSource::from_memory(wav))
+}
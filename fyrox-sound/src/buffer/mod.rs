@@ -50,6 +50,7 @@ use std::{
 use symphonia::core::io::MediaSource;
 
 pub mod generic;
+pub mod import;
 pub mod loader;
 pub mod streaming;
 
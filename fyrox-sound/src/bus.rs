@@ -21,7 +21,10 @@
 //! Everything related to audio buses and audio bus graphs. See docs of [`AudioBus`] and [`AudioBusGraph`]
 //! for more info and examples
 
-use crate::effects::{Effect, EffectRenderTrait};
+use crate::{
+    effects::{filter::LowPassFilterEffect, Effect, EffectRenderTrait},
+    snapshot::{BusParameters, SnapshotMixer},
+};
 use fyrox_core::{
     pool::{Handle, Pool, Ticket},
     reflect::prelude::*,
@@ -115,6 +118,15 @@ pub struct AudioBus {
     #[reflect(hidden)]
     #[visit(skip)]
     ping_pong_buffer: PingPongBuffer,
+
+    // Parameters set by the snapshot mixer of the sound context.
+    #[reflect(hidden)]
+    #[visit(skip)]
+    snapshot_parameters: BusParameters,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    snapshot_filter: LowPassFilterEffect,
 }
 
 impl Default for AudioBus {
@@ -126,6 +138,8 @@ impl Default for AudioBus {
             gain: 1.0,
             ping_pong_buffer: Default::default(),
             parent_bus: Default::default(),
+            snapshot_parameters: Default::default(),
+            snapshot_filter: Default::default(),
         }
     }
 }
@@ -172,6 +186,22 @@ impl AudioBus {
         self.gain
     }
 
+    /// Returns bus-wide parameters, that are currently applied to the bus by the snapshot mixer of
+    /// the sound context. See [`crate::snapshot::SnapshotMixer`] docs for more info.
+    pub fn snapshot_parameters(&self) -> BusParameters {
+        self.snapshot_parameters
+    }
+
+    pub(crate) fn set_snapshot_parameters(&mut self, parameters: BusParameters) {
+        if parameters.is_lowpass_enabled()
+            && parameters.lowpass_cutoff_hz != self.snapshot_filter.cutoff_frequency_hz()
+        {
+            self.snapshot_filter
+                .set_cutoff_frequency_hz(parameters.lowpass_cutoff_hz);
+        }
+        self.snapshot_parameters = parameters;
+    }
+
     pub(crate) fn input_buffer(&mut self) -> &mut [(f32, f32)] {
         self.ping_pong_buffer.input_mut()
     }
@@ -191,6 +221,12 @@ impl AudioBus {
             effect.render(input, output);
             self.ping_pong_buffer.swap();
         }
+
+        if self.snapshot_parameters.is_lowpass_enabled() {
+            let (input, output) = self.ping_pong_buffer.input_output_buffers();
+            self.snapshot_filter.render(input, output);
+            self.ping_pong_buffer.swap();
+        }
     }
 
     /// Adds new effect to the effects chain.
@@ -381,6 +417,32 @@ impl AudioBusGraph {
         })
     }
 
+    /// Returns the total pitch multiplier for sound sources that output their samples to the bus
+    /// with the given name. It is the product of snapshot pitches of the bus and all its ancestors.
+    pub(crate) fn bus_pitch(&self, name: &str) -> f64 {
+        let Some(mut handle) = self
+            .buses
+            .pair_iter()
+            .find_map(|(handle, bus)| (bus.name == name).then_some(handle))
+        else {
+            return 1.0;
+        };
+
+        let mut pitch = 1.0;
+        while let Some(bus) = self.buses.try_borrow(handle) {
+            pitch *= bus.snapshot_parameters.pitch as f64;
+            handle = bus.parent_bus;
+        }
+        pitch
+    }
+
+    pub(crate) fn apply_snapshots(&mut self, mixer: &SnapshotMixer) {
+        for bus in self.buses.iter_mut() {
+            let parameters = mixer.bus_parameters(&bus.name);
+            bus.set_snapshot_parameters(parameters);
+        }
+    }
+
     /// Removes an audio bus at the given handle.
     pub fn remove_bus(&mut self, handle: Handle<AudioBus>) -> AudioBus {
         assert_ne!(handle, self.root);
@@ -500,7 +562,7 @@ impl AudioBusGraph {
                 let leaf_ref = ctx.try_get_mut(leaf).expect("Malformed bus graph!");
 
                 let input_buffer = leaf_ref.ping_pong_buffer.input_ref();
-                let leaf_gain = leaf_ref.gain;
+                let leaf_gain = leaf_ref.gain * leaf_ref.snapshot_parameters.gain;
                 let mut parent_buffer = ctx.try_get_mut(leaf_ref.parent_bus);
                 let output_buffer = parent_buffer
                     .as_mut()
@@ -525,6 +587,7 @@ mod test {
     use crate::{
         bus::{AudioBus, AudioBusGraph},
         effects::{Attenuate, Effect},
+        snapshot::{AudioSnapshot, BusParameters, SnapshotMixer},
     };
 
     #[test]
@@ -606,4 +669,51 @@ mod test {
 
         assert_eq!(output_buffer[0], (0.75, 0.75));
     }
+
+    #[test]
+    fn test_snapshot_parameters() {
+        let mut output_buffer = [(0.0f32, 0.0f32)];
+
+        let mut graph = AudioBusGraph::new();
+
+        let bus1 = graph.add_bus(AudioBus::new("Bus1".to_string()), graph.root);
+
+        let mut mixer = SnapshotMixer::default();
+        mixer.add_snapshot(
+            AudioSnapshot::new("Test")
+                .with_bus(
+                    "Bus1",
+                    BusParameters {
+                        gain: 0.5,
+                        pitch: 0.5,
+                        ..Default::default()
+                    },
+                )
+                .with_bus(
+                    AudioBusGraph::PRIMARY_BUS,
+                    BusParameters {
+                        pitch: 0.5,
+                        ..Default::default()
+                    },
+                ),
+        );
+        mixer.set_weight("Test", 1.0);
+        graph.apply_snapshots(&mixer);
+
+        // Pitch is inherited from the ancestor buses.
+        assert_eq!(graph.bus_pitch("Bus1"), 0.25);
+        assert_eq!(graph.bus_pitch(AudioBusGraph::PRIMARY_BUS), 0.5);
+        assert_eq!(graph.bus_pitch("Unknown"), 1.0);
+
+        graph.begin_render(output_buffer.len());
+
+        for (left, right) in graph.buses[bus1].input_buffer() {
+            *left = 1.0;
+            *right = 1.0;
+        }
+
+        graph.end_render(&mut output_buffer);
+
+        assert_eq!(output_buffer[0], (0.5, 0.5));
+    }
 }
@@ -35,6 +35,7 @@ use crate::{
     listener::Listener,
     pool::Ticket,
     renderer::{render_source_default, Renderer},
+    snapshot::SnapshotMixer,
     source::{SoundSource, Status},
 };
 use fyrox_core::{
@@ -138,6 +139,7 @@ pub struct State {
     bus_graph: AudioBusGraph,
     distance_model: DistanceModel,
     paused: bool,
+    snapshot_mixer: SnapshotMixer,
     /// A set of flags, that can be used to define what should be skipped during the
     /// serialization of a sound context.
     #[reflect(hidden)]
@@ -274,6 +276,18 @@ impl State {
         &mut self.bus_graph
     }
 
+    /// Returns a reference to the snapshot mixer, that can be used to smoothly apply listener-level
+    /// states (underwater, pause, slow motion, etc.) to audio buses. See [`SnapshotMixer`] docs for
+    /// more info.
+    pub fn snapshot_mixer_ref(&self) -> &SnapshotMixer {
+        &self.snapshot_mixer
+    }
+
+    /// Returns a reference to the snapshot mixer. See [`SnapshotMixer`] docs for more info.
+    pub fn snapshot_mixer_mut(&mut self) -> &mut SnapshotMixer {
+        &mut self.snapshot_mixer
+    }
+
     pub(crate) fn render(&mut self, output_device_buffer: &mut [(f32, f32)]) {
         let last_time = fyrox_core::instant::Instant::now();
 
@@ -285,12 +299,18 @@ impl State {
 
             self.bus_graph.begin_render(output_device_buffer.len());
 
+            self.snapshot_mixer
+                .update(output_device_buffer.len() as f32 / SAMPLE_RATE as f32);
+            self.bus_graph.apply_snapshots(&self.snapshot_mixer);
+
             // Render sounds to respective audio buses.
             for source in self
                 .sources
                 .iter_mut()
                 .filter(|s| s.status() == Status::Playing)
             {
+                source.bus_pitch = self.bus_graph.bus_pitch(&source.bus);
+
                 if let Some(bus_input_buffer) = self.bus_graph.try_get_bus_input_buffer(&source.bus)
                 {
                     source.render(output_device_buffer.len());
@@ -347,6 +367,7 @@ impl SoundContext {
                 bus_graph: AudioBusGraph::new(),
                 distance_model: DistanceModel::InverseDistance,
                 paused: false,
+                snapshot_mixer: Default::default(),
                 serialization_options: Default::default(),
             }))),
         }
@@ -405,6 +426,7 @@ impl Visit for State {
         self.renderer.visit("Renderer", &mut region)?;
         self.paused.visit("Paused", &mut region)?;
         self.distance_model.visit("DistanceModel", &mut region)?;
+        let _ = self.snapshot_mixer.visit("SnapshotMixer", &mut region);
 
         Ok(())
     }
@@ -167,6 +167,8 @@ impl ChannelReverb {
 pub struct Reverb {
     dry: f32,
     wet: f32,
+    #[reflect(setter = "set_level", min_value = 0.0)]
+    level: f32,
     #[reflect(setter = "set_decay_time", min_value = 0.0)]
     decay_time: f32,
     #[reflect(setter = "set_fc", min_value = 0.0, max_value = 1.0)]
@@ -183,6 +185,8 @@ impl Visit for Reverb {
 
         self.dry.visit("Dry", &mut region)?;
         self.wet.visit("Wet", &mut region)?;
+        // Optional, for backward compatibility.
+        let _ = self.level.visit("Level", &mut region);
         self.decay_time.visit("DecayTime", &mut region)?;
         self.fc.visit("Fc", &mut region)?;
 
@@ -214,6 +218,7 @@ impl Reverb {
         Self {
             dry: 1.0,
             wet: 1.0,
+            level: 1.0,
             decay_time: 2.0,
             fc,
             left: ChannelReverb::new(0, fc, Reverb::FEEDBACK, decay_time),
@@ -246,6 +251,18 @@ impl Reverb {
         self.wet
     }
 
+    /// Sets loudness of reverberation (processed signal). It does not affect the input signal, that
+    /// is passed to output (see [`Self::set_dry`]). Zero level effectively disables reverberation.
+    /// Default value is 1.0.
+    pub fn set_level(&mut self, level: f32) {
+        self.level = level.max(0.0);
+    }
+
+    /// Returns loudness of reverberation.
+    pub fn level(&self) -> f32 {
+        self.level
+    }
+
     /// Sets actual sample rate of effect. It was designed to 44100 Hz sampling rate.
     /// TODO: This shouldn't be in public API.
     pub fn set_sample_rate(&mut self, sample_rate: usize) {
@@ -291,8 +308,8 @@ impl Reverb {
 
 impl EffectRenderTrait for Reverb {
     fn render(&mut self, input: &[(f32, f32)], mix_buf: &mut [(f32, f32)]) {
-        let wet = self.wet;
-        let dry = 1.0 - self.wet;
+        let wet = self.wet * self.level;
+        let dry = (1.0 - self.wet) * self.level;
 
         for ((out_left, out_right), &(left, right)) in mix_buf.iter_mut().zip(input.iter()) {
             let mid = (left + right) * 0.5;
@@ -97,6 +97,7 @@ pub mod engine;
 pub mod error;
 pub mod listener;
 pub mod renderer;
+pub mod snapshot;
 pub mod source;
 
 // Reexport some modules because there some types of them in public API.
@@ -0,0 +1,420 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Audio snapshots are named sets of bus-wide parameters (gain, low-pass cutoff, pitch), that can be
+//! smoothly blended in and out over time. They're used to implement listener-level states, such as
+//! "underwater", "paused" or "slow motion". See [`SnapshotMixer`] docs for more info.
+
+use fyrox_core::{math::lerpf, reflect::prelude::*, visitor::prelude::*};
+
+/// A set of parameters that affect an entire audio bus.
+#[derive(Copy, Clone, Debug, PartialEq, Reflect, Visit)]
+#[visit(optional)]
+pub struct BusParameters {
+    /// Gain multiplier of the bus.
+    #[reflect(min_value = 0.0, step = 0.05)]
+    pub gain: f32,
+    /// Cutoff frequency (in Hertz) of the low-pass filter, that is applied to the output of the bus.
+    /// Values larger or equal to [`Self::BYPASS_CUTOFF_HZ`] disable the filter.
+    #[reflect(min_value = 0.0)]
+    pub lowpass_cutoff_hz: f32,
+    /// Pitch multiplier of every sound source that outputs its samples to the bus (or any of its
+    /// descendant buses).
+    #[reflect(min_value = 0.0, step = 0.05)]
+    pub pitch: f32,
+}
+
+impl Default for BusParameters {
+    fn default() -> Self {
+        Self::NEUTRAL
+    }
+}
+
+impl BusParameters {
+    /// Cutoff frequency at which the low-pass filter is disabled.
+    pub const BYPASS_CUTOFF_HZ: f32 = 20000.0;
+
+    /// Parameters that do not change the bus output.
+    pub const NEUTRAL: Self = Self {
+        gain: 1.0,
+        lowpass_cutoff_hz: Self::BYPASS_CUTOFF_HZ,
+        pitch: 1.0,
+    };
+
+    /// Returns `true` if the low-pass filter should be applied.
+    pub fn is_lowpass_enabled(&self) -> bool {
+        self.lowpass_cutoff_hz < Self::BYPASS_CUTOFF_HZ
+    }
+
+    /// Linearly interpolates the parameters. Cutoff frequency is interpolated in logarithmic
+    /// scale, so the change of frequency sounds uniform.
+    pub fn lerp(&self, other: &Self, t: f32) -> Self {
+        let lowpass_cutoff_hz = if self.lowpass_cutoff_hz == other.lowpass_cutoff_hz {
+            self.lowpass_cutoff_hz
+        } else {
+            lerpf(
+                self.lowpass_cutoff_hz.max(1.0).ln(),
+                other.lowpass_cutoff_hz.max(1.0).ln(),
+                t,
+            )
+            .exp()
+        };
+        Self {
+            gain: lerpf(self.gain, other.gain, t),
+            lowpass_cutoff_hz,
+            pitch: lerpf(self.pitch, other.pitch, t),
+        }
+    }
+
+    /// Combines two sets of parameters: gain and pitch are multiplied and the lowest cutoff
+    /// frequency is taken.
+    pub fn combine(&self, other: &Self) -> Self {
+        Self {
+            gain: self.gain * other.gain,
+            lowpass_cutoff_hz: self.lowpass_cutoff_hz.min(other.lowpass_cutoff_hz),
+            pitch: self.pitch * other.pitch,
+        }
+    }
+}
+
+/// Parameters of a single audio bus in a snapshot.
+#[derive(Clone, Debug, Default, PartialEq, Reflect, Visit)]
+pub struct SnapshotBus {
+    /// Name of the audio bus.
+    pub bus: String,
+    /// Parameters of the audio bus.
+    pub parameters: BusParameters,
+}
+
+/// Audio snapshot is a named set of parameters of audio buses.
+///
+/// ## Examples
+///
+/// ```rust
+/// # use fyrox_sound::{bus::AudioBusGraph, snapshot::{AudioSnapshot, BusParameters}};
+/// let underwater = AudioSnapshot::new("Underwater").with_bus(
+///     AudioBusGraph::PRIMARY_BUS,
+///     BusParameters {
+///         lowpass_cutoff_hz: 600.0,
+///         ..Default::default()
+///     },
+/// );
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Reflect, Visit)]
+pub struct AudioSnapshot {
+    name: String,
+    buses: Vec<SnapshotBus>,
+}
+
+impl AudioSnapshot {
+    /// Name of the built-in underwater snapshot. See [`Self::underwater`].
+    pub const UNDERWATER: &'static str = "Underwater";
+    /// Name of the built-in pause snapshot. See [`Self::paused`].
+    pub const PAUSED: &'static str = "Paused";
+    /// Name of the built-in slow motion snapshot. See [`Self::slow_motion`].
+    pub const SLOW_MOTION: &'static str = "SlowMotion";
+
+    /// Creates a new empty snapshot with the given name.
+    pub fn new<S: AsRef<str>>(name: S) -> Self {
+        Self {
+            name: name.as_ref().to_string(),
+            buses: Default::default(),
+        }
+    }
+
+    /// Creates an "underwater" snapshot, that heavily muffles the given bus.
+    pub fn underwater<S: AsRef<str>>(bus: S) -> Self {
+        Self::new(Self::UNDERWATER).with_bus(
+            bus,
+            BusParameters {
+                gain: 0.8,
+                lowpass_cutoff_hz: 600.0,
+                pitch: 1.0,
+            },
+        )
+    }
+
+    /// Creates a "paused" snapshot, that ducks and muffles the given bus. Useful for pause menus.
+    pub fn paused<S: AsRef<str>>(bus: S) -> Self {
+        Self::new(Self::PAUSED).with_bus(
+            bus,
+            BusParameters {
+                gain: 0.35,
+                lowpass_cutoff_hz: 1200.0,
+                pitch: 1.0,
+            },
+        )
+    }
+
+    /// Creates a "slow motion" snapshot, that lowers the pitch of all the sounds of the given bus.
+    pub fn slow_motion<S: AsRef<str>>(bus: S) -> Self {
+        Self::new(Self::SLOW_MOTION).with_bus(
+            bus,
+            BusParameters {
+                gain: 1.0,
+                lowpass_cutoff_hz: 5000.0,
+                pitch: 0.6,
+            },
+        )
+    }
+
+    /// Sets parameters of the given bus and returns self.
+    pub fn with_bus<S: AsRef<str>>(mut self, bus: S, parameters: BusParameters) -> Self {
+        self.set_bus(bus, parameters);
+        self
+    }
+
+    /// Returns name of the snapshot.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Sets parameters of the given bus.
+    pub fn set_bus<S: AsRef<str>>(&mut self, bus: S, parameters: BusParameters) {
+        let bus = bus.as_ref();
+        if let Some(entry) = self.buses.iter_mut().find(|entry| entry.bus == bus) {
+            entry.parameters = parameters;
+        } else {
+            self.buses.push(SnapshotBus {
+                bus: bus.to_string(),
+                parameters,
+            });
+        }
+    }
+
+    /// Removes parameters of the given bus.
+    pub fn remove_bus(&mut self, bus: &str) -> Option<BusParameters> {
+        let index = self.buses.iter().position(|entry| entry.bus == bus)?;
+        Some(self.buses.remove(index).parameters)
+    }
+
+    /// Returns parameters of the given bus, if any.
+    pub fn bus(&self, bus: &str) -> Option<&BusParameters> {
+        self.buses
+            .iter()
+            .find(|entry| entry.bus == bus)
+            .map(|entry| &entry.parameters)
+    }
+
+    /// Returns an iterator over all the buses of the snapshot.
+    pub fn buses(&self) -> impl Iterator<Item = &SnapshotBus> {
+        self.buses.iter()
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Reflect)]
+struct SnapshotBlend {
+    name: String,
+    weight: f32,
+    target: f32,
+    // Change of weight per second.
+    speed: f32,
+}
+
+/// Snapshot mixer holds a set of named [`AudioSnapshot`]s and blends them over time. Every
+/// snapshot has a weight in `[0; 1]` range, zero weight means that the snapshot has no effect,
+/// one - the snapshot is fully applied. Multiple snapshots can be active at the same time, their
+/// parameters are combined (see [`BusParameters::combine`]). Blending is done by the audio engine
+/// itself, so it continues even if the game logic is paused.
+///
+/// ## Examples
+///
+/// ```rust
+/// # use fyrox_sound::{bus::AudioBusGraph, context::SoundContext, snapshot::AudioSnapshot};
+/// let context = SoundContext::new();
+/// let mut state = context.state();
+/// let mixer = state.snapshot_mixer_mut();
+/// mixer.add_snapshot(AudioSnapshot::underwater(AudioBusGraph::PRIMARY_BUS));
+///
+/// // The listener dived under water, muffle all the sounds in a half of a second.
+/// mixer.blend_to(AudioSnapshot::UNDERWATER, 1.0, 0.5);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Reflect, Visit)]
+pub struct SnapshotMixer {
+    snapshots: Vec<AudioSnapshot>,
+    #[reflect(hidden)]
+    #[visit(skip)]
+    blends: Vec<SnapshotBlend>,
+}
+
+impl SnapshotMixer {
+    /// Adds a new snapshot to the mixer. If there's a snapshot with the same name, it will be
+    /// replaced and returned.
+    pub fn add_snapshot(&mut self, snapshot: AudioSnapshot) -> Option<AudioSnapshot> {
+        if let Some(existing) = self
+            .snapshots
+            .iter_mut()
+            .find(|existing| existing.name == snapshot.name)
+        {
+            Some(std::mem::replace(existing, snapshot))
+        } else {
+            self.snapshots.push(snapshot);
+            None
+        }
+    }
+
+    /// Removes a snapshot with the given name. Its effect is removed immediately.
+    pub fn remove_snapshot(&mut self, name: &str) -> Option<AudioSnapshot> {
+        self.blends.retain(|blend| blend.name != name);
+        let index = self.snapshots.iter().position(|s| s.name == name)?;
+        Some(self.snapshots.remove(index))
+    }
+
+    /// Returns a reference to a snapshot with the given name.
+    pub fn snapshot(&self, name: &str) -> Option<&AudioSnapshot> {
+        self.snapshots.iter().find(|s| s.name == name)
+    }
+
+    /// Returns a reference to a snapshot with the given name.
+    pub fn snapshot_mut(&mut self, name: &str) -> Option<&mut AudioSnapshot> {
+        self.snapshots.iter_mut().find(|s| s.name == name)
+    }
+
+    /// Returns an iterator over all the snapshots of the mixer.
+    pub fn snapshots(&self) -> impl Iterator<Item = &AudioSnapshot> {
+        self.snapshots.iter()
+    }
+
+    /// Starts blending of the snapshot with the given name to the given weight over the given
+    /// time (in seconds). Zero time means instant change. Blending starts from the current weight
+    /// of the snapshot, so it is safe to call this method while another blending is in progress.
+    pub fn blend_to(&mut self, name: &str, weight: f32, time: f32) {
+        let target = weight.clamp(0.0, 1.0);
+        let current = self.weight(name);
+        let speed = if time > 0.0 {
+            (target - current).abs() / time
+        } else {
+            f32::INFINITY
+        };
+
+        if let Some(blend) = self.blends.iter_mut().find(|blend| blend.name == name) {
+            blend.target = target;
+            blend.speed = speed;
+        } else {
+            self.blends.push(SnapshotBlend {
+                name: name.to_string(),
+                weight: current,
+                target,
+                speed,
+            });
+        }
+    }
+
+    /// Immediately sets the weight of the snapshot with the given name.
+    pub fn set_weight(&mut self, name: &str, weight: f32) {
+        self.blend_to(name, weight, 0.0);
+        self.update(0.0);
+    }
+
+    /// Returns the current weight of the snapshot with the given name.
+    pub fn weight(&self, name: &str) -> f32 {
+        self.blends
+            .iter()
+            .find(|blend| blend.name == name)
+            .map_or(0.0, |blend| blend.weight)
+    }
+
+    /// Returns `true` if the snapshot with the given name is still blending to its target weight.
+    pub fn is_blending(&self, name: &str) -> bool {
+        self.blends
+            .iter()
+            .any(|blend| blend.name == name && blend.weight != blend.target)
+    }
+
+    /// Advances blending by the given amount of time (in seconds). This method is called by the
+    /// sound context automatically.
+    pub fn update(&mut self, dt: f32) {
+        for blend in self.blends.iter_mut() {
+            let max_step = blend.speed * dt;
+            let delta = blend.target - blend.weight;
+            if blend.speed.is_infinite() || delta.abs() <= max_step {
+                blend.weight = blend.target;
+            } else {
+                blend.weight += max_step.copysign(delta);
+            }
+        }
+        self.blends
+            .retain(|blend| !(blend.weight == 0.0 && blend.target == 0.0));
+    }
+
+    /// Returns blended parameters of the given bus.
+    pub fn bus_parameters(&self, bus: &str) -> BusParameters {
+        let mut result = BusParameters::NEUTRAL;
+        for blend in self.blends.iter() {
+            if let Some(parameters) = self.snapshot(&blend.name).and_then(|s| s.bus(bus)) {
+                result = result.combine(&BusParameters::NEUTRAL.lerp(parameters, blend.weight));
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::snapshot::{AudioSnapshot, BusParameters, SnapshotMixer};
+
+    #[test]
+    fn test_snapshot_blending() {
+        let mut mixer = SnapshotMixer::default();
+        mixer.add_snapshot(AudioSnapshot::new("Duck").with_bus(
+            "Bus",
+            BusParameters {
+                gain: 0.0,
+                ..Default::default()
+            },
+        ));
+
+        assert_eq!(mixer.bus_parameters("Bus"), BusParameters::NEUTRAL);
+
+        mixer.blend_to("Duck", 1.0, 1.0);
+        mixer.update(0.5);
+        assert_eq!(mixer.weight("Duck"), 0.5);
+        assert_eq!(mixer.bus_parameters("Bus").gain, 0.5);
+        assert_eq!(mixer.bus_parameters("Other"), BusParameters::NEUTRAL);
+
+        mixer.update(1.0);
+        assert_eq!(mixer.weight("Duck"), 1.0);
+        assert!(!mixer.is_blending("Duck"));
+        assert_eq!(mixer.bus_parameters("Bus").gain, 0.0);
+
+        // Blending out starts from the current weight.
+        mixer.blend_to("Duck", 0.0, 2.0);
+        mixer.update(1.0);
+        assert_eq!(mixer.weight("Duck"), 0.5);
+        mixer.update(1.0);
+        assert_eq!(mixer.weight("Duck"), 0.0);
+        assert_eq!(mixer.bus_parameters("Bus"), BusParameters::NEUTRAL);
+    }
+
+    #[test]
+    fn test_snapshot_combination() {
+        let mut mixer = SnapshotMixer::default();
+        mixer.add_snapshot(AudioSnapshot::underwater("Bus"));
+        mixer.add_snapshot(AudioSnapshot::slow_motion("Bus"));
+
+        mixer.set_weight(AudioSnapshot::UNDERWATER, 1.0);
+        mixer.set_weight(AudioSnapshot::SLOW_MOTION, 1.0);
+
+        let parameters = mixer.bus_parameters("Bus");
+        assert_eq!(parameters.lowpass_cutoff_hz.round(), 600.0);
+        assert!((parameters.pitch - 0.6).abs() < 1.0e-6);
+        assert!((parameters.gain - 0.8).abs() < 1.0e-6);
+    }
+}
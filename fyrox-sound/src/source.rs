@@ -159,6 +159,10 @@ pub struct SoundSource {
     #[reflect(hidden)]
     #[visit(skip)]
     pub(crate) prev_distance_gain: Option<f32>,
+    // Pitch multiplier of the audio bus of the source, defined by audio snapshots.
+    #[reflect(hidden)]
+    #[visit(skip)]
+    pub(crate) bus_pitch: f64,
 }
 
 impl Default for SoundSource {
@@ -189,6 +193,7 @@ impl Default for SoundSource {
             prev_right_samples: Default::default(),
             prev_sampling_vector: Vector3::new(0.0, 0.0, 1.0),
             prev_distance_gain: None,
+            bus_pitch: 1.0,
         }
     }
 }
@@ -571,7 +576,7 @@ impl SoundSource {
     // Renders until the end of the block or until amount samples is written and returns
     // the number of written samples.
     fn render_until_block_end(&mut self, buffer: &mut SoundBuffer, mut amount: usize) -> usize {
-        let step = self.pitch * self.resampling_multiplier;
+        let step = self.pitch * self.bus_pitch * self.resampling_multiplier;
         if step == 1.0 {
             if self.buf_read_pos < 0.0 {
                 // This can theoretically happen if we change pitch on the fly.
@@ -46,8 +46,10 @@ use fast_image_resize::ResizeOptions;
 use fxhash::FxHasher;
 use fyrox_core::{
     algebra::{Vector2, Vector3},
+    color::Color,
     futures::io::Error,
     io::FileLoadError,
+    math::Rect,
     num_traits::Bounded,
     reflect::prelude::*,
     sparse::AtomicIndex,
@@ -281,6 +283,9 @@ pub struct Texture {
     anisotropy: f32,
     modifications_counter: u64,
     is_render_target: bool,
+    generate_mips_on_gpu: bool,
+    #[reflect(hidden)]
+    dirty_region: Option<TextureDirtyRegion>,
     #[doc(hidden)]
     #[reflect(hidden)]
     pub cache_index: Arc<AtomicIndex>,
@@ -371,6 +376,9 @@ impl Visit for Texture {
         let _ = self.min_lod.visit("MinLod", &mut region);
         let _ = self.max_lod.visit("MaxLod", &mut region);
         let _ = self.lod_bias.visit("LodBias", &mut region);
+        let _ = self
+            .generate_mips_on_gpu
+            .visit("GenerateMipsOnGpu", &mut region);
 
         Ok(())
     }
@@ -402,11 +410,42 @@ impl Default for Texture {
             anisotropy: 16.0,
             modifications_counter: 0,
             is_render_target: false,
+            generate_mips_on_gpu: false,
+            dirty_region: None,
             cache_index: Default::default(),
         }
     }
 }
 
+/// Defines where mip-maps of a texture will be generated.
+#[derive(
+    Default,
+    Copy,
+    Clone,
+    Deserialize,
+    Serialize,
+    PartialEq,
+    Eq,
+    Debug,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+)]
+pub enum MipGeneration {
+    /// Mip-maps are generated on CPU at load time using [`MipFilter`]. It is slow, but provides the
+    /// best quality and works with compressed textures.
+    #[default]
+    Cpu,
+    /// Only the main level is loaded, mip-maps are generated on GPU when the texture is uploaded
+    /// to GPU memory. It is much faster, but the quality depends on the GPU driver (usually it is
+    /// a box filter) and [`MipFilter`] is ignored. Compressed textures always use [`Self::Cpu`]
+    /// mode.
+    Gpu,
+}
+
+uuid_provider!(MipGeneration = "fc3df0ae-2bc8-4d32-981e-909a36b8c2fb");
+
 /// A filter for mip-map generation.
 #[derive(
     Default, Copy, Clone, Deserialize, Serialize, Debug, Reflect, AsRefStr, EnumString, VariantNames,
@@ -479,6 +518,8 @@ pub struct TextureImportOptions {
     #[serde(default)]
     pub(crate) mip_filter: MipFilter,
     #[serde(default)]
+    pub(crate) mip_generation: MipGeneration,
+    #[serde(default)]
     pub(crate) flip_green_channel: bool,
     #[serde(default)]
     pub(crate) base_level: usize,
@@ -515,6 +556,7 @@ impl Default for TextureImportOptions {
             anisotropy: 16.0,
             compression: CompressionOptions::default(),
             mip_filter: Default::default(),
+            mip_generation: Default::default(),
             flip_green_channel: false,
             base_level: 0,
             max_level: default_max_level(),
@@ -610,6 +652,17 @@ impl TextureImportOptions {
         self.compression = compression;
     }
 
+    /// Sets desired mip-map generation mode. See [`MipGeneration`] docs for more info.
+    pub fn with_mip_generation(mut self, mip_generation: MipGeneration) -> Self {
+        self.mip_generation = mip_generation;
+        self
+    }
+
+    /// Sets desired mip-map generation mode. See [`MipGeneration`] docs for more info.
+    pub fn set_mip_generation(&mut self, mip_generation: MipGeneration) {
+        self.mip_generation = mip_generation;
+    }
+
     /// Same effect as [`Texture::set_base_level`].
     pub fn with_base_level(mut self, base_level: usize) -> Self {
         self.base_level = base_level;
@@ -749,6 +802,8 @@ impl TextureResourceExtension for TextureResource {
                 anisotropy: 1.0,
                 modifications_counter: 0,
                 is_render_target: true,
+                generate_mips_on_gpu: false,
+                dirty_region: None,
                 cache_index: Default::default(),
             },
         )
@@ -943,6 +998,93 @@ impl Default for TextureWrapMode {
     }
 }
 
+/// Texture sampler defines how a texture is sampled in a shader. It could be assigned to a texture
+/// binding of a material, and it will override sampling parameters of the texture for this
+/// binding only. This way the same texture could be sampled differently by different materials,
+/// without modifying the shared texture resource or creating copies of it.
+#[derive(Copy, Clone, Debug, PartialEq, Reflect, Visit)]
+pub struct TextureSampler {
+    /// Minification filter of the sampler. See [`TextureMinificationFilter`] docs for more info.
+    pub minification_filter: TextureMinificationFilter,
+    /// Magnification filter of the sampler. See [`TextureMagnificationFilter`] docs for more info.
+    pub magnification_filter: TextureMagnificationFilter,
+    /// Wrap mode for S coordinate.
+    pub s_wrap_mode: TextureWrapMode,
+    /// Wrap mode for T coordinate.
+    pub t_wrap_mode: TextureWrapMode,
+    /// Wrap mode for R coordinate.
+    pub r_wrap_mode: TextureWrapMode,
+    /// Anisotropy level of the sampler. It will be clamped to the maximum level supported by the
+    /// GPU.
+    #[reflect(min_value = 1.0, max_value = 16.0)]
+    pub anisotropy: f32,
+    /// Color, that will be used for [`TextureWrapMode::ClampToBorder`].
+    pub border_color: Color,
+}
+
+uuid_provider!(TextureSampler = "2ca522f6-630d-45bc-8bca-6e2986e7a7e7");
+
+impl Default for TextureSampler {
+    fn default() -> Self {
+        Self {
+            minification_filter: TextureMinificationFilter::LinearMipMapLinear,
+            magnification_filter: TextureMagnificationFilter::Linear,
+            s_wrap_mode: TextureWrapMode::Repeat,
+            t_wrap_mode: TextureWrapMode::Repeat,
+            r_wrap_mode: TextureWrapMode::Repeat,
+            anisotropy: 16.0,
+            border_color: Color::TRANSPARENT,
+        }
+    }
+}
+
+impl TextureSampler {
+    /// Creates a sampler with the same sampling parameters as the given texture has.
+    pub fn from_texture(texture: &Texture) -> Self {
+        Self {
+            minification_filter: texture.minification_filter,
+            magnification_filter: texture.magnification_filter,
+            s_wrap_mode: texture.s_wrap_mode,
+            t_wrap_mode: texture.t_wrap_mode,
+            r_wrap_mode: texture.r_wrap_mode,
+            anisotropy: texture.anisotropy,
+            border_color: Color::TRANSPARENT,
+        }
+    }
+
+    /// Sets new minification filter of the sampler.
+    pub fn with_minification_filter(mut self, filter: TextureMinificationFilter) -> Self {
+        self.minification_filter = filter;
+        self
+    }
+
+    /// Sets new magnification filter of the sampler.
+    pub fn with_magnification_filter(mut self, filter: TextureMagnificationFilter) -> Self {
+        self.magnification_filter = filter;
+        self
+    }
+
+    /// Sets the same wrap mode for all the coordinates.
+    pub fn with_wrap_mode(mut self, wrap_mode: TextureWrapMode) -> Self {
+        self.s_wrap_mode = wrap_mode;
+        self.t_wrap_mode = wrap_mode;
+        self.r_wrap_mode = wrap_mode;
+        self
+    }
+
+    /// Sets new anisotropy level of the sampler.
+    pub fn with_anisotropy(mut self, anisotropy: f32) -> Self {
+        self.anisotropy = anisotropy;
+        self
+    }
+
+    /// Sets new border color of the sampler.
+    pub fn with_border_color(mut self, color: Color) -> Self {
+        self.border_color = color;
+        self
+    }
+}
+
 /// Texture kind defines pixel format of texture.
 #[derive(Copy, Clone, PartialEq, Eq, Debug, Reflect)]
 #[repr(u32)]
@@ -1517,6 +1659,8 @@ impl Texture {
                     }
                 },
                 is_render_target: false,
+                generate_mips_on_gpu: false,
+                dirty_region: None,
                 cache_index: Default::default(),
                 lod_bias: import_options.lod_bias,
             })
@@ -1563,7 +1707,12 @@ impl Texture {
                 width as usize * height as usize * src_pixel_kind.size_in_bytes().unwrap_or(4),
             );
 
-            if import_options.minification_filter.is_using_mip_mapping() {
+            // Compressed textures cannot be rendered to, so their mips must be generated on CPU.
+            let generate_mips_on_gpu = import_options.minification_filter.is_using_mip_mapping()
+                && import_options.mip_generation == MipGeneration::Gpu
+                && import_options.compression == CompressionOptions::NoCompression;
+
+            if import_options.minification_filter.is_using_mip_mapping() && !generate_mips_on_gpu {
                 let src_pixel_type = convert_pixel_type_enum(src_pixel_kind);
                 let mut level_width = width;
                 let mut level_height = height;
@@ -1655,6 +1804,8 @@ impl Texture {
                 max_lod: import_options.max_lod,
                 anisotropy: import_options.anisotropy,
                 is_render_target: false,
+                generate_mips_on_gpu,
+                dirty_region: None,
                 cache_index: Default::default(),
                 lod_bias: import_options.lod_bias,
             })
@@ -1676,6 +1827,39 @@ impl Texture {
         Self::load_from_memory(&data, import_options)
     }
 
+    /// Encodes the texture into DDS container, including all its mip levels. Only rectangle
+    /// textures with block-compressed pixel formats are supported. Such containers are loaded
+    /// as-is, without any further processing, which makes them a good choice for shipping
+    /// textures that were compressed ahead of time.
+    pub fn encode_dds(&self) -> Result<Vec<u8>, TextureError> {
+        let format = match self.pixel_kind {
+            TexturePixelKind::DXT1RGB | TexturePixelKind::DXT1RGBA => D3DFormat::DXT1,
+            TexturePixelKind::DXT3RGBA => D3DFormat::DXT3,
+            TexturePixelKind::DXT5RGBA => D3DFormat::DXT5,
+            _ => return Err(TextureError::UnsupportedFormat),
+        };
+
+        let TextureKind::Rectangle { width, height } = self.kind else {
+            return Err(TextureError::UnsupportedFormat);
+        };
+
+        let mut dds = ddsfile::Dds::new_d3d(ddsfile::NewD3dParams {
+            height,
+            width,
+            depth: None,
+            format,
+            mipmap_levels: Some(self.mip_count),
+            caps2: None,
+        })
+        .map_err(|_| TextureError::UnsupportedFormat)?;
+        dds.data = self.bytes.to_vec();
+
+        let mut bytes = Vec::new();
+        dds.write(&mut bytes)
+            .map_err(|_| TextureError::UnsupportedFormat)?;
+        Ok(bytes)
+    }
+
     /// Creates new texture instance from given parameters.
     ///
     /// # Limitations
@@ -1923,6 +2107,21 @@ impl Texture {
         self.is_render_target
     }
 
+    /// Returns true if the mip-maps of the texture must be generated on GPU, when the texture is
+    /// uploaded to GPU memory. See [`MipGeneration`] docs for more info.
+    #[inline]
+    pub fn generate_mips_on_gpu(&self) -> bool {
+        self.generate_mips_on_gpu
+    }
+
+    /// Enables or disables mip-map generation on GPU. The texture must have only one mip level
+    /// and must not be compressed, otherwise the flag is ignored. Render targets with mip-mapping
+    /// minification filter will regenerate their mips after each render, if this flag is set.
+    #[inline]
+    pub fn set_generate_mips_on_gpu(&mut self, generate: bool) {
+        self.generate_mips_on_gpu = generate;
+    }
+
     /// Max samples for anisotropic filtering. Default value is 16.0 (max).
     /// However real value passed to GPU will be clamped to maximum supported
     /// by current GPU. To disable anisotropic filtering set this to 1.0.
@@ -1942,19 +2141,67 @@ impl Texture {
     /// texture and automatically calculates hash of the data in its destructor.
     #[inline]
     pub fn modify(&mut self) -> TextureDataRefMut<'_> {
-        TextureDataRefMut { texture: self }
+        TextureDataRefMut {
+            texture: self,
+            region: None,
+        }
+    }
+
+    /// Same as [`Self::modify`], but tells the engine that only the given region (in pixels) of
+    /// the main mip level will be modified. It allows the renderer to upload only the modified
+    /// region to GPU, which is much faster for large textures. Modifications of the data outside
+    /// the region could be ignored by the renderer.
+    #[inline]
+    pub fn modify_region(&mut self, region: Rect<u32>) -> TextureDataRefMut<'_> {
+        TextureDataRefMut {
+            texture: self,
+            region: Some(region),
+        }
+    }
+
+    /// Takes the region of the texture, that was modified via [`Self::modify_region`] since the
+    /// last call of this method. This method is used by the renderer to perform partial
+    /// uploads of texture data and usually there's no need to call it manually.
+    #[inline]
+    pub fn take_dirty_region(&mut self) -> Option<TextureDirtyRegion> {
+        self.dirty_region.take()
     }
 }
 
+/// A region of the main mip level of a texture, that was modified by
+/// [`Texture::modify_region`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TextureDirtyRegion {
+    /// The value of the modifications counter of the texture before the first modification
+    /// of the region. The region is valid only for the users, that have seen the texture in this
+    /// state, any other users must use the whole texture.
+    pub modifications_count: u64,
+    /// The modified region in pixels.
+    pub rect: Rect<u32>,
+}
+
 /// A special reference holder that provides mutable access to content of the
 /// texture and automatically calculates hash of the data in its destructor.
 pub struct TextureDataRefMut<'a> {
     texture: &'a mut Texture,
+    region: Option<Rect<u32>>,
 }
 
 impl Drop for TextureDataRefMut<'_> {
     fn drop(&mut self) {
-        self.texture.modifications_counter += 1;
+        let texture = &mut *self.texture;
+        match (self.region, texture.dirty_region.as_mut()) {
+            (Some(region), Some(dirty_region)) => dirty_region.rect.extend_to_contain(region),
+            (Some(region), None) => {
+                texture.dirty_region = Some(TextureDirtyRegion {
+                    modifications_count: texture.modifications_counter,
+                    rect: region,
+                })
+            }
+            // Whole texture was modified, there's no point to track the region anymore.
+            (None, _) => texture.dirty_region = None,
+        }
+        texture.modifications_counter += 1;
     }
 }
 
@@ -1993,3 +2240,68 @@ impl TextureDataRefMut<'_> {
         None
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use image::{ImageFormat, RgbaImage};
+
+    fn png_data(width: u32, height: u32) -> Vec<u8> {
+        let mut data = Vec::new();
+        RgbaImage::from_pixel(width, height, image::Rgba([255, 128, 64, 255]))
+            .write_to(&mut Cursor::new(&mut data), ImageFormat::Png)
+            .unwrap();
+        data
+    }
+
+    #[test]
+    fn test_mip_generation_import_options() {
+        let data = png_data(4, 4);
+
+        let cpu = Texture::load_from_memory(
+            &data,
+            TextureImportOptions::default()
+                .with_compression(CompressionOptions::NoCompression)
+                .with_mip_generation(MipGeneration::Cpu),
+        )
+        .unwrap();
+        assert!(!cpu.generate_mips_on_gpu());
+        assert_eq!(cpu.mip_count(), 3);
+        assert_eq!(cpu.data().len(), (16 + 4 + 1) * 4);
+
+        // Only the main level is loaded, the rest is generated on GPU.
+        let gpu = Texture::load_from_memory(
+            &data,
+            TextureImportOptions::default()
+                .with_compression(CompressionOptions::NoCompression)
+                .with_mip_generation(MipGeneration::Gpu),
+        )
+        .unwrap();
+        assert!(gpu.generate_mips_on_gpu());
+        assert_eq!(gpu.mip_count(), 1);
+        assert_eq!(gpu.data().len(), 16 * 4);
+
+        // Compressed textures cannot be rendered to, so the mips are generated on CPU.
+        let compressed = Texture::load_from_memory(
+            &data,
+            TextureImportOptions::default()
+                .with_compression(CompressionOptions::Quality)
+                .with_mip_generation(MipGeneration::Gpu),
+        )
+        .unwrap();
+        assert!(!compressed.generate_mips_on_gpu());
+        assert_eq!(compressed.mip_count(), 3);
+
+        // Nothing to generate, if the texture does not use mip-mapping.
+        let no_mips = Texture::load_from_memory(
+            &data,
+            TextureImportOptions::default()
+                .with_compression(CompressionOptions::NoCompression)
+                .with_minification_filter(TextureMinificationFilter::Linear)
+                .with_mip_generation(MipGeneration::Gpu),
+        )
+        .unwrap();
+        assert!(!no_mips.generate_mips_on_gpu());
+        assert_eq!(no_mips.mip_count(), 1);
+    }
+}
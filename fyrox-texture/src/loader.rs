@@ -24,11 +24,25 @@ use crate::{Texture, TextureImportOptions};
 use fyrox_core::{uuid::Uuid, TypeUuidProvider};
 use fyrox_resource::{
     io::ResourceIo, loader::BoxedImportOptionsLoaderFuture, loader::BoxedLoaderFuture,
-    loader::LoaderPayload, loader::ResourceLoader, options::try_get_import_settings,
-    options::try_get_import_settings_opaque, options::BaseImportOptions, state::LoadError,
+    loader::ContentSignature, loader::LoaderPayload, loader::ResourceLoader,
+    options::try_get_import_settings, options::try_get_import_settings_opaque,
+    options::BaseImportOptions, state::LoadError,
 };
 use std::{path::PathBuf, sync::Arc};
 
+/// Signatures of the supported image formats. TGA has no signature, so it can only be detected
+/// by its extension.
+const SIGNATURES: [ContentSignature; 8] = [
+    ContentSignature::new(b"\x89PNG\r\n\x1a\n"),
+    ContentSignature::new(b"\xFF\xD8\xFF"),
+    ContentSignature::new(b"GIF87a"),
+    ContentSignature::new(b"GIF89a"),
+    ContentSignature::new(b"BM"),
+    ContentSignature::new(b"II*\0"),
+    ContentSignature::new(b"MM\0*"),
+    ContentSignature::new(b"DDS "),
+];
+
 /// Default implementation for texture loading.
 pub struct TextureLoader {
     /// Default import options for textures.
@@ -42,6 +56,10 @@ impl ResourceLoader for TextureLoader {
         ]
     }
 
+    fn content_signatures(&self) -> &[ContentSignature] {
+        &SIGNATURES
+    }
+
     fn data_type_uuid(&self) -> Uuid {
         Texture::type_uuid()
     }
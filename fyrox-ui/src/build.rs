@@ -23,8 +23,8 @@
 
 use crate::style::resource::StyleResource;
 use crate::{
-    core::pool::Handle, font::FontResource, message::UiMessage, RestrictionEntry, UiNode,
-    UserInterface,
+    core::pool::Handle, font::FontResource, localization::Localization, message::UiMessage,
+    RestrictionEntry, UiNode, UserInterface,
 };
 use fyrox_graph::BaseSceneGraph;
 use std::{
@@ -120,6 +120,11 @@ impl BuildContext<'_> {
         self.ui.default_font.clone()
     }
 
+    /// Returns localization of the UI, that is used by localized widgets.
+    pub fn localization(&self) -> &Localization {
+        &self.ui.localization
+    }
+
     /// Returns current message sender of the UI, that is used for message passing mechanism. You can
     /// send messages for your widgets inside your builders, however this has limited use and should
     /// be avoided in the favor of explicit state modification to not overload message pipeline.
@@ -280,6 +280,18 @@ pub enum ButtonContent {
         /// Font size of the text. Default is 14.0 (defined by default style of the crate).
         size: Option<StyledProperty<f32>>,
     },
+    /// A shortcut to create a localized [crate::text::Text] widget as the button content. The text is taken from the
+    /// [`crate::localization::Localization`] of the user interface and updated automatically when the locale is
+    /// changed.
+    LocalizedText {
+        /// Localization key of the text.
+        key: String,
+        /// Optional font of the button. If [`None`], the default font will be used. The font of the current locale
+        /// has priority over this font.
+        font: Option<FontResource>,
+        /// Font size of the text. Default is 14.0 (defined by default style of the crate).
+        size: Option<StyledProperty<f32>>,
+    },
     /// Arbitrary widget handle. It could be any widget handle, for example a handle of [`crate::image::Image`]
     /// widget.
     Node(Handle<UiNode>),
@@ -317,6 +329,15 @@ impl ButtonContent {
         }
     }
 
+    /// Creates [`ButtonContent::LocalizedText`] with default font.
+    pub fn localized_text<S: AsRef<str>>(key: S) -> Self {
+        Self::LocalizedText {
+            key: key.as_ref().to_owned(),
+            font: None,
+            size: None,
+        }
+    }
+
     /// Creates [`ButtonContent::Node`].
     pub fn node(node: Handle<UiNode>) -> Self {
         Self::Node(node)
@@ -334,6 +355,16 @@ impl ButtonContent {
                         .unwrap_or_else(|| ctx.style.property(Style::FONT_SIZE)),
                 )
                 .build(ctx),
+            Self::LocalizedText { key, font, size } => TextBuilder::new(WidgetBuilder::new())
+                .with_localization_key(key)
+                .with_horizontal_text_alignment(HorizontalAlignment::Center)
+                .with_vertical_text_alignment(VerticalAlignment::Center)
+                .with_font(font.clone().unwrap_or_else(|| ctx.default_font()))
+                .with_font_size(
+                    size.clone()
+                        .unwrap_or_else(|| ctx.style.property(Style::FONT_SIZE)),
+                )
+                .build(ctx),
             Self::Node(node) => *node,
         }
     }
@@ -383,6 +414,12 @@ impl ButtonBuilder {
         self
     }
 
+    /// Sets the content of the button to be [`ButtonContent::LocalizedText`] (localized text with the default font).
+    pub fn with_localized_text(mut self, key: &str) -> Self {
+        self.content = Some(ButtonContent::localized_text(key));
+        self
+    }
+
     /// Sets the content of the button to be [`ButtonContent::Node`] (arbitrary widget handle).
     pub fn with_content(mut self, node: Handle<UiNode>) -> Self {
         self.content = Some(ButtonContent::Node(node));
@@ -26,7 +26,7 @@ use crate::{
 };
 use fyrox_resource::{
     io::ResourceIo,
-    loader::{BoxedLoaderFuture, LoaderPayload, ResourceLoader},
+    loader::{BoxedLoaderFuture, ContentSignature, LoaderPayload, ResourceLoader},
     options::{try_get_import_settings, ImportOptions},
     state::LoadError,
 };
@@ -53,6 +53,13 @@ impl Default for FontImportOptions {
 
 impl ImportOptions for FontImportOptions {}
 
+/// Signatures of TrueType and OpenType fonts.
+const SIGNATURES: [ContentSignature; 3] = [
+    ContentSignature::new(b"\0\x01\0\0"),
+    ContentSignature::new(b"OTTO"),
+    ContentSignature::new(b"true"),
+];
+
 /// Default implementation for font loading.
 #[derive(Default)]
 pub struct FontLoader {
@@ -64,6 +71,10 @@ impl ResourceLoader for FontLoader {
         &["ttf", "otf"]
     }
 
+    fn content_signatures(&self) -> &[ContentSignature] {
+        &SIGNATURES
+    }
+
     fn data_type_uuid(&self) -> Uuid {
         Font::type_uuid()
     }
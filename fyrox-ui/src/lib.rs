@@ -233,6 +233,7 @@ pub mod inspector;
 pub mod key;
 pub mod list_view;
 pub mod loader;
+pub mod localization;
 pub mod log;
 pub mod matrix;
 pub mod menu;
@@ -288,11 +289,13 @@ use crate::{
     draw::{CommandTexture, Draw, DrawingContext},
     font::FontResource,
     font::BUILT_IN_FONT,
+    localization::Localization,
     message::{
         ButtonState, CursorIcon, KeyboardModifiers, MessageDirection, MouseButton, OsEvent,
         UiMessage,
     },
     popup::{Placement, PopupMessage},
+    text::{Text, TextMessage},
     widget::{Widget, WidgetBuilder, WidgetMessage},
 };
 use copypasta::ClipboardContext;
@@ -653,6 +656,7 @@ pub struct UiUpdateSwitches {
 #[derive(Reflect, Debug)]
 pub struct UserInterface {
     screen_size: Vector2<f32>,
+    scale: f32,
     nodes: Pool<UiNode, WidgetContainer>,
     #[reflect(hidden)]
     drawing_context: DrawingContext,
@@ -695,6 +699,10 @@ pub struct UserInterface {
     double_click_entries: FxHashMap<MouseButton, DoubleClickEntry>,
     pub double_click_time_slice: f32,
     pub tooltip_appear_delay: f32,
+    #[reflect(hidden)]
+    localization: Localization,
+    #[reflect(hidden)]
+    need_update_localization: bool,
 }
 
 impl Visit for UserInterface {
@@ -752,6 +760,7 @@ impl Clone for UserInterface {
 
         Self {
             screen_size: self.screen_size,
+            scale: self.scale,
             nodes,
             drawing_context: self.drawing_context.clone(),
             visual_debug: self.visual_debug,
@@ -782,6 +791,8 @@ impl Clone for UserInterface {
             double_click_entries: self.double_click_entries.clone(),
             double_click_time_slice: self.double_click_time_slice,
             tooltip_appear_delay: self.tooltip_appear_delay,
+            localization: self.localization.clone(),
+            need_update_localization: self.need_update_localization,
         }
     }
 }
@@ -1052,6 +1063,7 @@ impl UserInterface {
         let style = StyleResource::new_ok(ResourceKind::Embedded, Style::dark_style());
         let mut ui = UserInterface {
             screen_size,
+            scale: 1.0,
             sender,
             receiver,
             visual_debug: false,
@@ -1082,6 +1094,8 @@ impl UserInterface {
             double_click_entries: Default::default(),
             double_click_time_slice: 0.5, // 500 ms is standard in most operating systems.
             tooltip_appear_delay: 0.55,
+            localization: Default::default(),
+            need_update_localization: false,
         };
         let root_node = UiNode::new(Canvas {
             widget: WidgetBuilder::new().build(&ui.build_ctx()),
@@ -1172,6 +1186,7 @@ impl UserInterface {
     }
 
     fn update_visual_transform(&mut self, from: Handle<UiNode>) {
+        let scale = self.scale;
         self.stack.clear();
         self.stack.push(from);
         while let Some(node_handle) = self.stack.pop() {
@@ -1192,7 +1207,9 @@ impl UserInterface {
                 let visual_transform = if let Some(parent) = parent {
                     parent.visual_transform * layout_transform * widget.render_transform
                 } else {
-                    layout_transform * widget.render_transform
+                    // The global scale is applied to the root of the hierarchy only, every other
+                    // widget inherits it from its parent.
+                    Matrix3::new_scaling(scale) * layout_transform * widget.render_transform
                 };
 
                 widget.visual_transform = visual_transform;
@@ -1209,6 +1226,36 @@ impl UserInterface {
         self.screen_size = screen_size;
     }
 
+    /// Returns current global scale of the user interface.
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// Sets new global scale of the user interface. Every widget is scaled uniformly, layout is
+    /// performed in a root canvas, which size is equal to the screen size divided by the scale.
+    /// It could be used to make the user interface more readable on high-DPI screens or for
+    /// accessibility purposes.
+    pub fn set_scale(&mut self, scale: f32) {
+        let scale = scale.max(0.1);
+        if self.scale != scale {
+            self.scale = scale;
+            self.invalidate_layout();
+            self.need_update_global_transform = true;
+        }
+    }
+
+    /// Returns the size of the given widget in the layout space. The global scale is a part of the
+    /// visual transform of every widget, but not of the layout, so it is compensated here.
+    fn layout_size(&self, node: &UiNode) -> Vector2<f32> {
+        node.actual_initial_size() * self.scale
+    }
+
+    /// Returns the size of the root canvas, which is the screen size divided by the global scale
+    /// of the user interface. All layout calculations of top-level widgets use this size.
+    pub fn root_canvas_size(&self) -> Vector2<f32> {
+        self.screen_size / self.scale
+    }
+
     fn handle_layout_events(&mut self) {
         fn invalidate_recursive_up(
             nodes: &Pool<UiNode, WidgetContainer>,
@@ -1272,10 +1319,11 @@ impl UserInterface {
 
         self.handle_layout_events();
 
-        self.measure_node(self.root_canvas, screen_size);
+        let root_canvas_size = self.root_canvas_size();
+        self.measure_node(self.root_canvas, root_canvas_size);
         let arrangement_changed = self.arrange_node(
             self.root_canvas,
-            &Rect::new(0.0, 0.0, screen_size.x, screen_size.y),
+            &Rect::new(0.0, 0.0, root_canvas_size.x, root_canvas_size.y),
         );
 
         if self.need_update_global_transform {
@@ -1296,6 +1344,11 @@ impl UserInterface {
             entry.timer -= dt;
         }
 
+        if self.need_update_localization && !self.localization.is_loading() {
+            self.need_update_localization = false;
+            self.update_localization();
+        }
+
         self.update_layout(screen_size);
 
         if let Some(node_overrides) = switches.node_overrides.as_ref() {
@@ -1356,6 +1409,44 @@ impl UserInterface {
         notify_depth_first(self.root_canvas, self);
     }
 
+    /// Returns localization of the UI. See [`Localization`] docs for more info.
+    pub fn localization(&self) -> &Localization {
+        &self.localization
+    }
+
+    /// Sets new localization of the UI. Every localized widget will be updated once the
+    /// dictionaries of the current locale are loaded.
+    pub fn set_localization(&mut self, localization: Localization) {
+        self.localization = localization;
+        self.need_update_localization = true;
+    }
+
+    /// Changes current locale of the UI. Every localized widget will be updated once the
+    /// dictionaries of the locale are loaded. Returns `false` if there's no such locale.
+    pub fn set_locale(&mut self, name: &str) -> bool {
+        if self.localization.current_locale() == Some(name) {
+            return true;
+        }
+        let changed = self.localization.set_current_locale(name);
+        self.need_update_localization |= changed;
+        changed
+    }
+
+    fn update_localization(&self) {
+        for (handle, node) in self.nodes.pair_iter() {
+            if let Some(key) = node
+                .cast::<Text>()
+                .and_then(|text| text.localization_key.as_ref())
+            {
+                self.send_message(TextMessage::localization_key(
+                    handle,
+                    MessageDirection::ToWidget,
+                    Some(key.clone()),
+                ));
+            }
+        }
+    }
+
     pub fn cursor(&self) -> CursorIcon {
         self.cursor_icon
     }
@@ -1927,12 +2018,12 @@ impl UserInterface {
                         WidgetMessage::Center => {
                             if self.nodes.is_valid_handle(message.destination()) {
                                 let node = self.node(message.destination());
-                                let size = node.actual_initial_size();
+                                let size = self.layout_size(node);
                                 let parent = node.parent();
                                 let parent_size = if parent.is_some() {
-                                    self.node(parent).actual_initial_size()
+                                    self.layout_size(self.node(parent))
                                 } else {
-                                    self.screen_size
+                                    self.root_canvas_size()
                                 };
 
                                 self.send_message(WidgetMessage::desired_position(
@@ -1951,12 +2042,12 @@ impl UserInterface {
                             if self.nodes.is_valid_handle(message.destination()) {
                                 let node = self.node(message.destination());
                                 let mut position = node.actual_local_position();
-                                let size = node.actual_initial_size();
+                                let size = self.layout_size(node);
                                 let parent = node.parent();
                                 let parent_size = if parent.is_some() {
-                                    self.node(parent).actual_initial_size()
+                                    self.layout_size(self.node(parent))
                                 } else {
-                                    self.screen_size
+                                    self.root_canvas_size()
                                 };
 
                                 if position.x < 0.0 {
@@ -3548,6 +3639,40 @@ mod test_inner {
         assert_eq!(actual_position, expected_position);
     }
 
+    #[test]
+    fn test_scale() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let widget_size = Vector2::new(100.0, 100.0);
+        let mut ui = UserInterface::new(screen_size);
+        let widget = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_width(widget_size.x)
+                .with_height(widget_size.y),
+        )
+        .build(&mut ui.build_ctx());
+        ui.set_scale(2.0);
+        ui.update(screen_size, 0.0, &Default::default());
+        assert_eq!(ui.root_canvas_size(), Vector2::new(500.0, 500.0));
+
+        ui.send_message(WidgetMessage::center(widget, MessageDirection::ToWidget));
+        while ui.poll_message().is_some() {}
+        ui.update(screen_size, 0.0, &Default::default());
+
+        // Layout is done in the root canvas space, but the widget is scaled on the screen.
+        assert_eq!(
+            ui.node(widget).actual_local_position(),
+            Vector2::new(200.0, 200.0)
+        );
+        let screen_bounds = ui.node(widget).screen_bounds();
+        assert_eq!(screen_bounds.position, Vector2::new(400.0, 400.0));
+        assert_eq!(screen_bounds.size, Vector2::new(200.0, 200.0));
+
+        // Picking uses the drawing commands, so the widget must be drawn first.
+        ui.draw();
+        assert_eq!(ui.hit_test(Vector2::new(500.0, 500.0)), widget);
+        assert!(ui.hit_test(Vector2::new(350.0, 350.0)).is_none());
+    }
+
     #[test]
     fn test_keyboard_focus() {
         let screen_size = Vector2::new(1000.0, 1000.0);
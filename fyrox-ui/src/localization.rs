@@ -0,0 +1,623 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Localization of user interface. See [`Localization`] docs for more info.
+
+#![warn(missing_docs)]
+
+use crate::{
+    core::{io::FileLoadError, reflect::prelude::*, type_traits::prelude::*, visitor::prelude::*},
+    font::FontResource,
+};
+use fxhash::FxHashMap;
+use fyrox_resource::{
+    io::ResourceIo,
+    loader::{BoxedLoaderFuture, LoaderPayload, ResourceLoader},
+    state::LoadError,
+    Resource, ResourceData,
+};
+use std::{
+    error::Error,
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Maximum depth of message references, it prevents infinite recursion on cyclic references.
+const MAX_REFERENCE_DEPTH: usize = 8;
+
+/// An error that may occur during localization dictionary loading.
+#[derive(Debug)]
+pub enum LocalizationError {
+    /// An i/o error has occurred.
+    Io(FileLoadError),
+    /// The dictionary is not a valid UTF-8 text.
+    Utf8,
+    /// Syntax error.
+    Syntax {
+        /// Line number (starting from one).
+        line: usize,
+        /// Description of the error.
+        reason: String,
+    },
+}
+
+impl Display for LocalizationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(v) => write!(f, "A file load error has occurred {v:?}"),
+            Self::Utf8 => write!(f, "The dictionary is not a valid UTF-8 text"),
+            Self::Syntax { line, reason } => write!(f, "Syntax error at line {line}: {reason}"),
+        }
+    }
+}
+
+impl Error for LocalizationError {}
+
+impl From<FileLoadError> for LocalizationError {
+    fn from(e: FileLoadError) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// A set of translated messages of a single locale. Dictionaries use a subset of
+/// [Fluent](https://projectfluent.org) syntax:
+///
+/// ```text
+/// # Comments start with `#`.
+/// -game-name = Space Rangers
+/// main-menu-title = Welcome to { -game-name }!
+/// greeting = Hello, { $name }!
+/// # Indented lines continue the previous message.
+/// tutorial =
+///     Use arrow keys to move.
+///     Press space to jump.
+/// # Attributes are available as `message.attribute`.
+/// start-button = Start
+///     .tooltip = Start a new game
+/// ```
+///
+/// Placeables could be variables (`{ $name }`), references to other messages and terms
+/// (`{ message }`, `{ -term }`) and string literals (`{ "{" }`). Selectors and functions are not
+/// supported. Plain key-value files (`key = value` on each line) are valid dictionaries as well.
+#[derive(Clone, Default, Debug, Visit, Reflect, TypeUuidProvider)]
+#[type_uuid(id = "67fc0203-2cd0-4b49-967b-65a82a91e597")]
+pub struct LocalizationDictionary {
+    entries: FxHashMap<String, String>,
+}
+
+impl ResourceData for LocalizationDictionary {
+    fn type_uuid(&self) -> Uuid {
+        <Self as TypeUuidProvider>::type_uuid()
+    }
+
+    fn save(&mut self, _path: &Path) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    fn can_be_saved(&self) -> bool {
+        false
+    }
+}
+
+fn is_identifier(key: &str) -> bool {
+    let key = key.strip_prefix('-').unwrap_or(key);
+    key.starts_with(|c: char| c.is_alphabetic())
+        && key
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+}
+
+impl LocalizationDictionary {
+    /// Parses a dictionary from the given text. See the type docs for the syntax.
+    pub fn parse(text: &str) -> Result<Self, LocalizationError> {
+        let mut entries = FxHashMap::<String, String>::default();
+        let mut message: Option<String> = None;
+        let mut current: Option<String> = None;
+
+        for (index, line) in text.lines().enumerate() {
+            let syntax_error = |reason: &str| LocalizationError::Syntax {
+                line: index + 1,
+                reason: reason.to_string(),
+            };
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            if line.starts_with('#') {
+                message = None;
+                current = None;
+                continue;
+            }
+
+            if line.starts_with(char::is_whitespace) {
+                let line = line.trim();
+                let Some(message) = message.as_ref() else {
+                    return Err(syntax_error("indented line does not belong to any message"));
+                };
+                if let Some(attribute) = line.strip_prefix('.') {
+                    let Some((name, value)) = attribute.split_once('=') else {
+                        return Err(syntax_error("expected `=` after attribute name"));
+                    };
+                    let key = format!("{message}.{}", name.trim());
+                    entries.insert(key.clone(), value.trim().to_string());
+                    current = Some(key);
+                } else if let Some(value) = current.as_ref().and_then(|key| entries.get_mut(key)) {
+                    if !value.is_empty() {
+                        value.push('\n');
+                    }
+                    value.push_str(line);
+                }
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(syntax_error("expected `key = value`"));
+            };
+            let key = key.trim();
+            if !is_identifier(key) {
+                return Err(syntax_error(&format!("invalid identifier `{key}`")));
+            }
+            entries.insert(key.to_string(), value.trim().to_string());
+            message = Some(key.to_string());
+            current = message.clone();
+        }
+
+        Ok(Self { entries })
+    }
+
+    /// Loads a dictionary from the given file.
+    pub async fn from_file(path: &Path, io: &dyn ResourceIo) -> Result<Self, LocalizationError> {
+        let bytes = io.load_file(path).await?;
+        let text = String::from_utf8(bytes).map_err(|_| LocalizationError::Utf8)?;
+        Self::parse(&text)
+    }
+
+    /// Adds a new message to the dictionary, or replaces an existing one.
+    pub fn insert(&mut self, key: impl Into<String>, pattern: impl Into<String>) {
+        self.entries.insert(key.into(), pattern.into());
+    }
+
+    /// Returns a raw (unformatted) pattern of the message with the given key.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries.get(key).map(|pattern| pattern.as_str())
+    }
+
+    /// Returns an iterator over the keys of every message in the dictionary.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(|key| key.as_str())
+    }
+
+    /// Formats the message with the given key, using the given arguments as values of variables.
+    pub fn format(&self, key: &str, args: &[(&str, &str)]) -> Option<String> {
+        let pattern = self.get(key)?;
+        Some(format_pattern(
+            pattern,
+            args,
+            &mut |key| self.get(key).map(|pattern| pattern.to_string()),
+            0,
+        ))
+    }
+}
+
+fn format_pattern(
+    pattern: &str,
+    args: &[(&str, &str)],
+    lookup: &mut dyn FnMut(&str) -> Option<String>,
+    depth: usize,
+) -> String {
+    let mut result = String::with_capacity(pattern.len());
+    let mut rest = pattern;
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        let Some(end) = find_placeable_end(&rest[start + 1..]) else {
+            rest = &rest[start..];
+            break;
+        };
+        let placeable = rest[start + 1..start + 1 + end].trim();
+        rest = &rest[start + 1 + end + 1..];
+
+        if let Some(name) = placeable.strip_prefix('$') {
+            match args.iter().find(|(arg, _)| *arg == name) {
+                Some((_, value)) => result.push_str(value),
+                None => {
+                    result.push('{');
+                    result.push_str(placeable);
+                    result.push('}');
+                }
+            }
+        } else if let Some(literal) = placeable
+            .strip_prefix('"')
+            .and_then(|literal| literal.strip_suffix('"'))
+        {
+            unescape_literal(literal, &mut result);
+        } else {
+            match lookup(placeable).filter(|_| depth < MAX_REFERENCE_DEPTH) {
+                Some(referenced) => {
+                    result.push_str(&format_pattern(&referenced, args, lookup, depth + 1))
+                }
+                None => {
+                    result.push('{');
+                    result.push_str(placeable);
+                    result.push('}');
+                }
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Returns the position of the `}` that closes a placeable, skipping string literals, that could
+/// contain braces too. The given string must start right after the opening `{`.
+fn find_placeable_end(placeable: &str) -> Option<usize> {
+    let mut in_literal = false;
+    let mut escaped = false;
+    for (i, c) in placeable.char_indices() {
+        if in_literal {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_literal = false;
+            }
+        } else if c == '"' {
+            in_literal = true;
+        } else if c == '}' {
+            return Some(i);
+        }
+    }
+    None
+}
+
+/// Writes the content of a string literal with `\"` and `\\` escape sequences resolved.
+fn unescape_literal(literal: &str, result: &mut String) {
+    let mut chars = literal.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            if let Some(escaped) = chars.next() {
+                result.push(escaped);
+            }
+        } else {
+            result.push(c);
+        }
+    }
+}
+
+/// Localization dictionary resource.
+pub type LocalizationDictionaryResource = Resource<LocalizationDictionary>;
+
+/// A loader for localization dictionaries. Supports `.ftl` (Fluent) and `.lang` (key-value) files.
+pub struct LocalizationLoader;
+
+impl ResourceLoader for LocalizationLoader {
+    fn extensions(&self) -> &[&str] {
+        &["ftl", "lang"]
+    }
+
+    fn data_type_uuid(&self) -> Uuid {
+        <LocalizationDictionary as TypeUuidProvider>::type_uuid()
+    }
+
+    fn load(&self, path: PathBuf, io: Arc<dyn ResourceIo>) -> BoxedLoaderFuture {
+        Box::pin(async move {
+            let dictionary = LocalizationDictionary::from_file(&path, io.as_ref())
+                .await
+                .map_err(LoadError::new)?;
+            Ok(LoaderPayload::new(dictionary))
+        })
+    }
+}
+
+/// A set of dictionaries and an optional font of a single language.
+#[derive(Clone, Debug, Default)]
+pub struct Locale {
+    /// Name of the locale, for example `en-US`.
+    pub name: String,
+    /// Dictionaries of the locale. When a message is present in several dictionaries, the first
+    /// one wins.
+    pub dictionaries: Vec<LocalizationDictionaryResource>,
+    /// A font, that should be used by localized texts in this locale. It is useful for languages,
+    /// that need glyphs which are missing in the default font (for example, CJK languages). If
+    /// [`None`], every localized text uses its own font.
+    pub font: Option<FontResource>,
+}
+
+impl Locale {
+    /// Creates a new locale without dictionaries.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            dictionaries: Default::default(),
+            font: None,
+        }
+    }
+
+    /// Adds a dictionary to the locale.
+    pub fn with_dictionary(mut self, dictionary: LocalizationDictionaryResource) -> Self {
+        self.dictionaries.push(dictionary);
+        self
+    }
+
+    /// Sets the font of the locale.
+    pub fn with_font(mut self, font: FontResource) -> Self {
+        self.font = Some(font);
+        self
+    }
+
+    fn pattern(&self, key: &str) -> Option<String> {
+        self.dictionaries.iter().find_map(|dictionary| {
+            dictionary
+                .state()
+                .data_ref()
+                .and_then(|data| data.get(key).map(|pattern| pattern.to_string()))
+        })
+    }
+}
+
+/// Localization service of the user interface. It contains a set of locales and allows to switch
+/// the current one at runtime. The instance used by widgets is stored in
+/// [`crate::UserInterface`], use [`crate::UserInterface::set_localization`] and
+/// [`crate::UserInterface::set_locale`] to change it.
+///
+/// Messages that are missing in the current locale are taken from the fallback locale (if any).
+/// Localized widgets (see [`crate::text::TextBuilder::with_localization_key`] and
+/// [`crate::button::ButtonBuilder::with_localized_text`]) are updated automatically, when the
+/// current locale is changed or when its dictionaries are loaded.
+///
+/// ```rust,no_run
+/// # use fyrox_resource::manager::ResourceManager;
+/// # use fyrox_ui::{
+/// #     localization::{Locale, Localization, LocalizationDictionary},
+/// #     text::TextBuilder,
+/// #     widget::WidgetBuilder,
+/// #     UserInterface,
+/// # };
+/// fn setup(ui: &mut UserInterface, resource_manager: &ResourceManager) {
+///     ui.set_localization(
+///         Localization::new()
+///             .with_locale(Locale::new("en").with_dictionary(
+///                 resource_manager.request::<LocalizationDictionary>("data/locales/en.ftl"),
+///             ))
+///             .with_locale(Locale::new("de").with_dictionary(
+///                 resource_manager.request::<LocalizationDictionary>("data/locales/de.ftl"),
+///             ))
+///             .with_fallback_locale("en"),
+///     );
+///     ui.set_locale("de");
+///
+///     TextBuilder::new(WidgetBuilder::new())
+///         .with_localization_key("main-menu-title")
+///         .build(&mut ui.build_ctx());
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Localization {
+    locales: Vec<Locale>,
+    current: Option<String>,
+    fallback: Option<String>,
+}
+
+impl Localization {
+    /// Creates a new localization without locales.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a new locale. The first added locale becomes the current one.
+    pub fn add_locale(&mut self, locale: Locale) {
+        if self.current.is_none() {
+            self.current = Some(locale.name.clone());
+        }
+        self.locales.retain(|existing| existing.name != locale.name);
+        self.locales.push(locale);
+    }
+
+    /// Adds a new locale. The first added locale becomes the current one.
+    pub fn with_locale(mut self, locale: Locale) -> Self {
+        self.add_locale(locale);
+        self
+    }
+
+    /// Sets a locale, that will be used for messages that are missing in the current locale.
+    pub fn set_fallback_locale(&mut self, name: impl Into<String>) {
+        self.fallback = Some(name.into());
+    }
+
+    /// Sets a locale, that will be used for messages that are missing in the current locale.
+    pub fn with_fallback_locale(mut self, name: impl Into<String>) -> Self {
+        self.set_fallback_locale(name);
+        self
+    }
+
+    /// Returns a locale with the given name.
+    pub fn locale(&self, name: &str) -> Option<&Locale> {
+        self.locales.iter().find(|locale| locale.name == name)
+    }
+
+    /// Returns an iterator over the names of every locale.
+    pub fn locale_names(&self) -> impl Iterator<Item = &str> {
+        self.locales.iter().map(|locale| locale.name.as_str())
+    }
+
+    /// Returns the name of the current locale.
+    pub fn current_locale(&self) -> Option<&str> {
+        self.current.as_deref()
+    }
+
+    /// Sets the current locale. Returns `false` if there's no locale with the given name.
+    pub fn set_current_locale(&mut self, name: &str) -> bool {
+        if self.locale(name).is_some() {
+            self.current = Some(name.to_string());
+            true
+        } else {
+            false
+        }
+    }
+
+    fn active_locales(&self) -> impl Iterator<Item = &Locale> {
+        let current = self.current.as_deref().and_then(|name| self.locale(name));
+        let fallback = self
+            .fallback
+            .as_deref()
+            .filter(|fallback| Some(*fallback) != self.current.as_deref())
+            .and_then(|name| self.locale(name));
+        current.into_iter().chain(fallback)
+    }
+
+    /// Returns `true` if some dictionaries of the current or the fallback locale are still loading.
+    pub fn is_loading(&self) -> bool {
+        self.active_locales().any(|locale| {
+            locale
+                .dictionaries
+                .iter()
+                .any(|dictionary| dictionary.is_loading())
+        })
+    }
+
+    /// Returns the font of the current locale.
+    pub fn font(&self) -> Option<FontResource> {
+        self.current
+            .as_deref()
+            .and_then(|name| self.locale(name))
+            .and_then(|locale| locale.font.clone())
+    }
+
+    fn pattern(&self, key: &str) -> Option<String> {
+        self.active_locales().find_map(|locale| locale.pattern(key))
+    }
+
+    /// Formats the message with the given key using the current locale, the arguments are used as
+    /// values of variables. Returns [`None`] if there's no such message.
+    pub fn format(&self, key: &str, args: &[(&str, &str)]) -> Option<String> {
+        let pattern = self.pattern(key)?;
+        Some(format_pattern(
+            &pattern,
+            args,
+            &mut |key| self.pattern(key),
+            0,
+        ))
+    }
+
+    /// Returns the translation of the message with the given key in the current locale, or
+    /// [`None`] if there's no such message.
+    pub fn translate(&self, key: &str) -> Option<String> {
+        self.format(key, &[])
+    }
+
+    /// Same as [`Self::translate`], but returns the key itself if there's no such message.
+    pub fn translate_or_key(&self, key: &str) -> String {
+        self.translate(key).unwrap_or_else(|| key.to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::localization::{Locale, Localization, LocalizationDictionary};
+    use fyrox_resource::{untyped::ResourceKind, Resource};
+
+    const EN: &str = r#"
+# Comment.
+-game = Fyrox Game
+title = Welcome to { -game }!
+greeting = Hello, { $name }!
+multiline =
+    First line
+    Second line
+start = Start
+    .tooltip = Start a { "{new}" } game
+quotes = Say { "\"hi\"" } and { "}" }
+only-en = English only
+"#;
+
+    #[test]
+    fn test_parse_and_format() {
+        let dictionary = LocalizationDictionary::parse(EN).unwrap();
+        assert_eq!(
+            dictionary.format("title", &[]).unwrap(),
+            "Welcome to Fyrox Game!"
+        );
+        assert_eq!(
+            dictionary.format("greeting", &[("name", "Bob")]).unwrap(),
+            "Hello, Bob!"
+        );
+        assert_eq!(
+            dictionary.format("greeting", &[]).unwrap(),
+            "Hello, {$name}!"
+        );
+        assert_eq!(
+            dictionary.format("multiline", &[]).unwrap(),
+            "First line\nSecond line"
+        );
+        assert_eq!(
+            dictionary.format("start.tooltip", &[]).unwrap(),
+            "Start a {new} game"
+        );
+        assert_eq!(
+            dictionary.format("quotes", &[]).unwrap(),
+            "Say \"hi\" and }"
+        );
+        assert!(dictionary.format("missing", &[]).is_none());
+
+        assert!(LocalizationDictionary::parse("no equals sign").is_err());
+        assert!(LocalizationDictionary::parse("    indented = value").is_err());
+    }
+
+    #[test]
+    fn test_cyclic_references() {
+        let dictionary = LocalizationDictionary::parse("a = { b }\nb = { a }").unwrap();
+        assert!(dictionary.format("a", &[]).is_some());
+    }
+
+    #[test]
+    fn test_locale_switching() {
+        let en = LocalizationDictionary::parse(EN).unwrap();
+        let de =
+            LocalizationDictionary::parse("-game = Fyrox Spiel\ntitle = Willkommen bei { -game }!")
+                .unwrap();
+
+        let mut localization = Localization::new()
+            .with_locale(
+                Locale::new("en").with_dictionary(Resource::new_ok(ResourceKind::Embedded, en)),
+            )
+            .with_locale(
+                Locale::new("de").with_dictionary(Resource::new_ok(ResourceKind::Embedded, de)),
+            )
+            .with_fallback_locale("en");
+
+        assert_eq!(localization.current_locale(), Some("en"));
+        assert_eq!(
+            localization.translate("title").unwrap(),
+            "Welcome to Fyrox Game!"
+        );
+
+        assert!(localization.set_current_locale("de"));
+        assert_eq!(
+            localization.translate("title").unwrap(),
+            "Willkommen bei Fyrox Spiel!"
+        );
+        assert_eq!(localization.translate("only-en").unwrap(), "English only");
+        assert_eq!(localization.translate_or_key("missing"), "missing");
+
+        assert!(!localization.set_current_locale("fr"));
+        assert_eq!(localization.current_locale(), Some("de"));
+    }
+}
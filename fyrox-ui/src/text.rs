@@ -34,6 +34,7 @@ use crate::{
     draw::DrawingContext,
     font::FontResource,
     formatted_text::{FormattedText, FormattedTextBuilder, WrapMode},
+    localization::Localization,
     message::{MessageDirection, UiMessage},
     style::{resource::StyleResourceExt, Style},
     widget::{Widget, WidgetBuilder},
@@ -71,6 +72,9 @@ pub enum TextMessage {
     ShadowOffset(Vector2<f32>),
     /// Used to set font height of the widget.
     FontSize(StyledProperty<f32>),
+    /// Used to set new localization key of the widget. See [Text](Text#localization) for usage
+    /// examples.
+    LocalizationKey(Option<String>),
 }
 
 impl TextMessage {
@@ -123,6 +127,11 @@ impl TextMessage {
         /// Creates new [`TextMessage::FontSize`] message.
         TextMessage:FontSize => fn font_size(StyledProperty<f32>), layout: false
     );
+
+    define_constructor!(
+        /// Creates new [`TextMessage::LocalizationKey`] message.
+        TextMessage:LocalizationKey => fn localization_key(Option<String>), layout: false
+    );
 }
 
 /// Text is a simple widget that allows you to print text on screen. It has various options like word wrapping, text
@@ -304,6 +313,7 @@ impl TextMessage {
 /// - [`TextMessage::ShadowDilation`] - sets "thickness" of the shadows under the tex.
 /// - [`TextMessage::ShadowBrush`] - sets shadow brush (allows you to change color and even make shadow with color gradients).
 /// - [`TextMessage::ShadowOffset`] - sets offset of the shadows.
+/// - [`TextMessage::LocalizationKey`] - sets new [localization key](Text#localization).
 ///
 /// An example of changing text at runtime could be something like this:
 ///
@@ -325,12 +335,38 @@ impl TextMessage {
 ///
 /// Please keep in mind, that like any other situation when you "changing" something via messages, you should remember
 /// that the change is **not** immediate.
+///
+/// ## Localization
+///
+/// Instead of a fixed text, the widget could show a message from the [`Localization`] of the user interface. In this
+/// case the text is taken from the current locale and updated automatically when the locale is changed. If the current
+/// locale has its own font, the widget uses it instead of its own font.
+///
+/// ```rust
+/// # use fyrox_ui::{
+/// #     core::pool::Handle,
+/// #     text::TextBuilder, widget::WidgetBuilder, UiNode, UserInterface
+/// # };
+/// fn create_localized_text(ui: &mut UserInterface) -> Handle<UiNode> {
+///     TextBuilder::new(WidgetBuilder::new())
+///         .with_localization_key("main-menu-title")
+///         .build(&mut ui.build_ctx())
+/// }
+/// ```
 #[derive(Default, Clone, Visit, Reflect, Debug, ComponentProvider)]
 pub struct Text {
     /// Base widget of the Text widget.
     pub widget: Widget,
     /// [`FormattedText`] instance that is used to layout text and generate drawing commands.
     pub formatted_text: RefCell<FormattedText>,
+    /// A key of the message from the [`Localization`] of the user interface. If set, the text of
+    /// the widget is replaced with the translation of the message.
+    #[visit(optional)]
+    pub localization_key: Option<String>,
+    /// Own font of the widget, that is stored while the font of the current locale is used.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    unlocalized_font: Option<FontResource>,
 }
 
 impl ConstructorProvider<UiNode, UserInterface> for Text {
@@ -398,7 +434,11 @@ impl Control for Text {
                         }
                     }
                     TextMessage::Font(font) => {
-                        if &text_ref.get_font() != font {
+                        if let Some(unlocalized_font) = self.unlocalized_font.as_mut() {
+                            // The font of the locale has priority, the new one will be used when
+                            // the locale has no font.
+                            *unlocalized_font = font.clone();
+                        } else if &text_ref.get_font() != font {
                             text_ref.set_font(font.clone());
                             drop(text_ref);
                             self.invalidate_layout();
@@ -453,6 +493,11 @@ impl Control for Text {
                             self.invalidate_layout();
                         }
                     }
+                    TextMessage::LocalizationKey(key) => {
+                        drop(text_ref);
+                        self.localization_key.clone_from(key);
+                        self.localize(ui.localization());
+                    }
                 }
             }
         }
@@ -484,6 +529,39 @@ impl Text {
     pub fn horizontal_alignment(&self) -> HorizontalAlignment {
         self.formatted_text.borrow().horizontal_alignment()
     }
+
+    /// Returns current localization key of the widget.
+    pub fn localization_key(&self) -> Option<&str> {
+        self.localization_key.as_deref()
+    }
+
+    fn localize(&mut self, localization: &Localization) {
+        let Some(key) = self.localization_key.as_ref() else {
+            if let Some(font) = self.unlocalized_font.take() {
+                self.formatted_text.borrow_mut().set_font(font);
+                self.invalidate_layout();
+            }
+            return;
+        };
+
+        let mut text_ref = self.formatted_text.borrow_mut();
+        text_ref.set_text(localization.translate_or_key(key));
+        match localization.font() {
+            Some(font) => {
+                if self.unlocalized_font.is_none() {
+                    self.unlocalized_font = Some(text_ref.get_font());
+                }
+                text_ref.set_font(font);
+            }
+            None => {
+                if let Some(font) = self.unlocalized_font.take() {
+                    text_ref.set_font(font);
+                }
+            }
+        }
+        drop(text_ref);
+        self.invalidate_layout();
+    }
 }
 
 /// TextBuilder is used to create instances of [`Text`] widget and register them in the user interface.
@@ -499,6 +577,7 @@ pub struct TextBuilder {
     shadow_dilation: f32,
     shadow_offset: Vector2<f32>,
     font_size: Option<StyledProperty<f32>>,
+    localization_key: Option<String>,
 }
 
 impl TextBuilder {
@@ -516,6 +595,7 @@ impl TextBuilder {
             shadow_dilation: 1.0,
             shadow_offset: Vector2::new(1.0, 1.0),
             font_size: None,
+            localization_key: None,
         }
     }
 
@@ -525,6 +605,13 @@ impl TextBuilder {
         self
     }
 
+    /// Sets the desired localization key of the widget. The text of the widget will be taken from
+    /// the current locale of the user interface, see [Text](Text#localization) for more info.
+    pub fn with_localization_key<P: AsRef<str>>(mut self, key: P) -> Self {
+        self.localization_key = Some(key.as_ref().to_owned());
+        self
+    }
+
     /// Sets the desired font of the widget.
     pub fn with_font(mut self, font: FontResource) -> Self {
         self.font = Some(font);
@@ -588,7 +675,7 @@ impl TextBuilder {
 
     /// Finishes text widget creation and registers it in the user interface, returning its handle to you.
     pub fn build(mut self, ctx: &mut BuildContext) -> Handle<UiNode> {
-        let font = if let Some(font) = self.font {
+        let mut font = if let Some(font) = self.font {
             font
         } else {
             ctx.default_font()
@@ -598,11 +685,22 @@ impl TextBuilder {
             self.widget_builder.foreground = Some(ctx.style.property(Style::BRUSH_TEXT));
         }
 
+        let mut unlocalized_font = None;
+        let text = if let Some(key) = self.localization_key.as_ref() {
+            let localization = ctx.localization();
+            if let Some(locale_font) = localization.font() {
+                unlocalized_font = Some(std::mem::replace(&mut font, locale_font));
+            }
+            localization.translate_or_key(key)
+        } else {
+            self.text.unwrap_or_default()
+        };
+
         let text = Text {
             widget: self.widget_builder.build(ctx),
             formatted_text: RefCell::new(
                 FormattedTextBuilder::new(font)
-                    .with_text(self.text.unwrap_or_default())
+                    .with_text(text)
                     .with_vertical_alignment(self.vertical_text_alignment)
                     .with_horizontal_alignment(self.horizontal_text_alignment)
                     .with_wrap(self.wrap)
@@ -616,6 +714,8 @@ impl TextBuilder {
                     )
                     .build(),
             ),
+            localization_key: self.localization_key,
+            unlocalized_font,
         };
         ctx.add_node(UiNode::new(text))
     }
@@ -1432,6 +1432,8 @@ impl Control for TextBox {
                                 ui.send_message(message.reverse());
                             }
                         }
+                        // Text of the text box is edited by users, so it cannot be localized.
+                        TextMessage::LocalizationKey(_) => (),
                     }
                 }
             } else if let Some(msg) = message.data::<TextBoxMessage>() {
@@ -852,11 +852,11 @@ impl Control for Window {
                             // application window, thus leaving an opportunity to drag window to some other place.
                             new_pos.x = new_pos.x.clamp(
                                 -(self.actual_local_size().x - safe_border.x).abs(),
-                                (ui.screen_size().x - safe_border.x).abs(),
+                                (ui.root_canvas_size().x - safe_border.x).abs(),
                             );
                             new_pos.y = new_pos
                                 .y
-                                .clamp(0.0, (ui.screen_size().y - safe_border.y).abs());
+                                .clamp(0.0, (ui.root_canvas_size().y - safe_border.y).abs());
                         }
 
                         if self.is_dragging && self.desired_local_position() != new_pos {